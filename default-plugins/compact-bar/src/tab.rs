@@ -98,6 +98,18 @@ pub fn tab_style(
     } else if tab.is_sync_panes_active {
         tabname.push_str(" (SYNC)");
     }
+    if tab.has_input_locked_pane {
+        tabname.push_str(" (INPUT LOCKED)");
+    }
+    if tab.has_bell_alert {
+        tabname.push_str(" (BELL)");
+    } else if tab.has_command_exit_alert {
+        tabname.push_str(" (EXITED)");
+    } else if tab.has_activity_alert {
+        tabname.push_str(" (ACTIVITY)");
+    } else if tab.has_silence_alert {
+        tabname.push_str(" (SILENCE)");
+    }
     // we only color alternate tabs differently if we can't use the arrow fonts to separate them
     if !capabilities.arrow_fonts {
         is_alternate_tab = false;
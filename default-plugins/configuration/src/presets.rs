@@ -103,6 +103,7 @@ keybinds clear-defaults=true {{
         bind "c" {{ SearchToggleOption "CaseSensitivity"; }}
         bind "w" {{ SearchToggleOption "Wrap"; }}
         bind "o" {{ SearchToggleOption "WholeWord"; }}
+        bind "r" {{ SearchToggleOption "Regex"; }}
     }}
     entersearch {{
         bind "Ctrl c" "Esc" {{ SwitchToMode "Scroll"; }}
@@ -308,6 +309,7 @@ keybinds clear-defaults=true {{
         bind "c" {{ SearchToggleOption "CaseSensitivity"; }}
         bind "w" {{ SearchToggleOption "Wrap"; }}
         bind "o" {{ SearchToggleOption "WholeWord"; }}
+        bind "r" {{ SearchToggleOption "Regex"; }}
     }}
     entersearch {{
         bind "Ctrl c" "Esc" {{ SwitchToMode "Scroll"; }}
@@ -516,6 +518,7 @@ keybinds clear-defaults=true {{
         bind "c" {{ SearchToggleOption "CaseSensitivity"; }}
         bind "w" {{ SearchToggleOption "Wrap"; }}
         bind "o" {{ SearchToggleOption "WholeWord"; }}
+        bind "r" {{ SearchToggleOption "Regex"; }}
     }}
     entersearch {{
         bind "Ctrl c" "Esc" {{ SwitchToMode "Scroll"; }}
@@ -701,6 +704,7 @@ keybinds clear-defaults=true {{
         bind "c" {{ SearchToggleOption "CaseSensitivity"; }}
         bind "w" {{ SearchToggleOption "Wrap"; }}
         bind "o" {{ SearchToggleOption "WholeWord"; }}
+        bind "r" {{ SearchToggleOption "Regex"; }}
     }}
     entersearch {{
         bind "Ctrl c" "Esc" {{ SwitchToMode "Scroll"; }}
@@ -892,6 +896,7 @@ keybinds clear-defaults=true {{
         bind "c" {{ SearchToggleOption "CaseSensitivity"; }}
         bind "w" {{ SearchToggleOption "Wrap"; }}
         bind "o" {{ SearchToggleOption "WholeWord"; }}
+        bind "r" {{ SearchToggleOption "Regex"; }}
     }}
     entersearch {{
         bind "Ctrl c" "Esc" {{ SwitchToMode "Scroll"; }}
@@ -1074,6 +1079,7 @@ keybinds clear-defaults=true {{
         bind "c" {{ SearchToggleOption "CaseSensitivity"; }}
         bind "w" {{ SearchToggleOption "Wrap"; }}
         bind "o" {{ SearchToggleOption "WholeWord"; }}
+        bind "r" {{ SearchToggleOption "Regex"; }}
     }}
     entersearch {{
         bind "Ctrl c" "Esc" {{ SwitchToMode "Scroll"; }}
@@ -13,6 +13,10 @@ struct State {
     received_payload: Option<String>,
     configuration: BTreeMap<String, String>,
     message_to_plugin_payload: Option<String>,
+    request_id_from_plugin: Option<String>,
+    reply_from_plugin: Option<(Option<String>, Option<String>)>, // in reply to, payload
+    named_timers_fired: Vec<String>,
+    watch_paths_results: Vec<Result<(), String>>,
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -535,6 +539,9 @@ impl ZellijPlugin for State {
                     self.received_payload = Some(payload.clone());
                 }
             },
+            Event::NamedTimer(name, _seconds) => {
+                self.named_timers_fired.push(name.clone());
+            },
             Event::SystemClipboardFailure => {
                 // this is just to trigger the worker message
                 post_message_to(PluginMessage {
@@ -550,6 +557,18 @@ impl ZellijPlugin for State {
         should_render
     }
     fn pipe(&mut self, pipe_message: PipeMessage) -> bool {
+        if pipe_message.name == "request_to_plugin" {
+            if pipe_message.in_reply_to.is_some() {
+                self.reply_from_plugin = Some((
+                    pipe_message.in_reply_to.clone(),
+                    pipe_message.payload.clone(),
+                ));
+            } else {
+                self.request_id_from_plugin = pipe_message.request_id.clone();
+                reply_to_plugin_message(&pipe_message, "my_cool_reply");
+            }
+            return true;
+        }
         let input_pipe_id = match pipe_message.source {
             PipeSource::Cli(id) => id.clone(),
             PipeSource::Plugin(id) => format!("{}", id),
@@ -569,6 +588,24 @@ impl ZellijPlugin for State {
             );
         } else if name == "message_to_plugin" {
             self.message_to_plugin_payload = payload.clone();
+        } else if name == "set_timers" {
+            subscribe(&[EventType::NamedTimer]);
+            // replaced by a timer that fires sooner
+            set_timer("replaced", 10.0, false);
+            set_timer("replaced", 0.05, false);
+            set_timer("cancelled", 0.1, false);
+            cancel_timer("cancelled");
+            set_timer("repeating", 0.05, true);
+        } else if name == "watch_paths" {
+            let path = payload.unwrap_or_default();
+            self.watch_paths_results.push(watch_paths(&[path]));
+        } else if name == "request_from_plugin" {
+            pipe_message_to_plugin(
+                MessageToPlugin::new("request_to_plugin")
+                    .with_destination_plugin_id(get_plugin_ids().plugin_id)
+                    .with_request_id("my_request_id")
+                    .with_payload("my_cool_request"),
+            );
         }
         let should_render = true;
         should_render
@@ -579,6 +616,15 @@ impl ZellijPlugin for State {
             println!("Payload from worker: {:?}", payload);
         } else if let Some(payload) = self.message_to_plugin_payload.take() {
             println!("Payload from self: {:?}", payload);
+        } else if !self.named_timers_fired.is_empty() {
+            println!("Named timers fired: {:?}", self.named_timers_fired);
+        } else if !self.watch_paths_results.is_empty() {
+            println!("Watch paths results: {:?}", self.watch_paths_results);
+        } else if let Some((in_reply_to, payload)) = self.reply_from_plugin.as_ref() {
+            println!(
+                "Request id received: {:?}, reply in reply to: {:?}, reply payload: {:?}",
+                self.request_id_from_plugin, in_reply_to, payload
+            );
         } else {
             println!(
                 "Rows: {:?}, Cols: {:?}, Received events: {:?}",
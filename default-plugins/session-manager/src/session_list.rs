@@ -29,13 +29,33 @@ impl SessionList {
         self.session_ui_infos = session_ui_infos;
     }
     pub fn update_search_term(&mut self, search_term: &str, colors: &Colors) {
+        // "tag:<name>" words filter the sessions by tag, the rest of the term is fuzzy matched
+        let (tag_filters, search_words): (Vec<&str>, Vec<&str>) = search_term
+            .split_whitespace()
+            .partition(|word| word.starts_with("tag:"));
+        let tag_filters: Vec<&str> = tag_filters
+            .iter()
+            .map(|word| word.trim_start_matches("tag:"))
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        let fuzzy_search_term = search_words.join(" ");
         let mut flattened_assets = self.flatten_assets(colors);
         let mut matches = vec![];
         let matcher = SkimMatcherV2::default().use_cache(true);
         for (list_item, session_name, tab_position, pane_id, is_current_session) in
             flattened_assets.drain(..)
         {
-            if let Some((score, indices)) = matcher.fuzzy_indices(&list_item.name, &search_term) {
+            if !tag_filters.is_empty() {
+                let is_session_item = tab_position.is_none() && pane_id.is_none();
+                if !self.session_has_tags(&session_name, &tag_filters)
+                    || (fuzzy_search_term.is_empty() && !is_session_item)
+                {
+                    continue;
+                }
+            }
+            if let Some((score, indices)) =
+                matcher.fuzzy_indices(&list_item.name, &fuzzy_search_term)
+            {
                 matches.push(SearchResult::new(
                     score,
                     indices,
@@ -52,6 +72,13 @@ impl SessionList {
         self.is_searching = !search_term.is_empty();
         self.selected_search_index = Some(0);
     }
+    fn session_has_tags(&self, session_name: &str, tags: &[&str]) -> bool {
+        self.session_ui_infos
+            .iter()
+            .find(|s| s.name == session_name)
+            .map(|s| tags.iter().all(|tag| s.tags.iter().any(|t| t == tag)))
+            .unwrap_or(false)
+    }
     fn flatten_assets(
         &self,
         colors: &Colors,
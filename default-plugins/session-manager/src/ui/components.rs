@@ -422,6 +422,24 @@ pub fn build_session_ui_line(session_ui_info: &SessionUiInfo, colors: Colors) ->
     ui_spans.push(session_name_span);
     ui_spans.push(tab_and_pane_count);
     ui_spans.push(connected_users_count);
+    if !session_ui_info.tags.is_empty() {
+        let tags = session_ui_info
+            .tags
+            .iter()
+            .map(|t| format!("#{}", t))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let tags_styled = colors.session_tags(&tags);
+        ui_spans.push(UiSpan::UiSpanTelescope(UiSpanTelescope::new(vec![
+            StringAndLength::new(format!(" {tags_styled}"), 1 + tags.width()),
+        ])));
+    }
+    if let Some(note) = &session_ui_info.note {
+        let note_styled = colors.session_note(note);
+        ui_spans.push(UiSpan::UiSpanTelescope(UiSpanTelescope::new(vec![
+            StringAndLength::new(format!(" - {note_styled}"), 3 + note.width()),
+        ])));
+    }
     if session_ui_info.is_current_session {
         let current_session_indication = UiSpan::UiSpanTelescope(UiSpanTelescope::new(vec![
             StringAndLength::new(
@@ -1028,6 +1046,14 @@ impl Colors {
         self.color(&self.palette.exit_code_error.base, text)
     }
 
+    pub fn session_tags(&self, text: &str) -> String {
+        self.color(&self.palette.text_unselected.emphasis_3, text)
+    }
+
+    pub fn session_note(&self, text: &str) -> String {
+        self.color(&self.palette.text_unselected.emphasis_2, text)
+    }
+
     pub fn tab_count(&self, text: &str) -> String {
         self.color(&self.palette.text_unselected.emphasis_1, text)
     }
@@ -227,6 +227,8 @@ pub struct SessionUiInfo {
     pub tabs: Vec<TabUiInfo>,
     pub connected_users: usize,
     pub is_current_session: bool,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
 }
 
 impl SessionUiInfo {
@@ -240,6 +242,8 @@ impl SessionUiInfo {
                 .collect(),
             connected_users: session_info.connected_clients,
             is_current_session: session_info.is_current_session,
+            tags: session_info.tags.clone(),
+            note: session_info.note.clone(),
         }
     }
     pub fn line_count(&self, selected_index: &SelectedIndex) -> usize {
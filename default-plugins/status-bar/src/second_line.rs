@@ -242,6 +242,8 @@ fn get_keys_and_hints(mi: &ModeInfo) -> Vec<(String, String, Vec<KeyWithModifier
             action_key(&km, &[A::SearchToggleOption(SOpt::Wrap)])),
         (s("Whole words"), s("Whole"),
             action_key(&km, &[A::SearchToggleOption(SOpt::WholeWord)])),
+        (s("Regex"), s("Regex"),
+            action_key(&km, &[A::SearchToggleOption(SOpt::Regex)])),
     ]} else if mi.mode == IM::Session { vec![
         (s("Detach"), s("Detach"), action_key(&km, &[Action::Detach])),
         (s("Session Manager"), s("Manager"), action_key(&km, &[A::LaunchOrFocusPlugin(Default::default(), true, true, false, false), TO_NORMAL])), // not entirely accurate
@@ -372,6 +374,24 @@ pub fn system_clipboard_error(palette: &Styling) -> LinePart {
     }
 }
 
+pub fn available_version_hint(mode_info: &ModeInfo) -> Option<LinePart> {
+    let hint = if let Some(installed_version) = &mode_info.installed_version {
+        format!(
+            " Zellij {} was installed, restart this session to use it.",
+            installed_version
+        )
+    } else if let Some(latest_release) = &mode_info.latest_release {
+        format!(" Zellij {} is available.", latest_release)
+    } else {
+        return None;
+    };
+    let orange_color = palette_match!(mode_info.style.colors.text_unselected.emphasis_0);
+    Some(LinePart {
+        len: hint.chars().count(),
+        part: Style::new().fg(orange_color).bold().paint(hint).to_string(),
+    })
+}
+
 pub fn fullscreen_panes_to_hide(palette: &Styling, panes_to_hide: usize) -> LinePart {
     let text_color = palette_match!(palette.text_unselected.base);
     let green_color = palette_match!(palette.text_unselected.emphasis_2);
@@ -801,4 +821,27 @@ mod tests {
 
         assert_eq!(ret, " <BACKSPACE> New / Ctrl + <a|ENTER|1|SPACE> Change Focus / <ESC> Close / <END> Toggle Fullscreen");
     }
+
+    #[test]
+    fn available_version_hint_prefers_installed_version() {
+        let mode_info = ModeInfo {
+            installed_version: Some("0.99.0".to_owned()),
+            latest_release: Some("1.0.0".to_owned()),
+            ..ModeInfo::default()
+        };
+        let ret = unstyle(available_version_hint(&mode_info).unwrap());
+        assert_eq!(
+            ret,
+            " Zellij 0.99.0 was installed, restart this session to use it."
+        );
+
+        let mode_info = ModeInfo {
+            latest_release: Some("1.0.0".to_owned()),
+            ..ModeInfo::default()
+        };
+        let ret = unstyle(available_version_hint(&mode_info).unwrap());
+        assert_eq!(ret, " Zellij 1.0.0 is available.");
+
+        assert!(available_version_hint(&ModeInfo::default()).is_none());
+    }
 }
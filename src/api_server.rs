@@ -0,0 +1,485 @@
+//! `zellij api-server` exposes the running sessions over JSON-RPC 2.0, so that tools which are not
+//! written in Rust do not have to parse the output of the CLI.
+//!
+//! Requests and responses are newline delimited JSON objects on a unix socket, eg.
+//! `{"jsonrpc": "2.0", "id": 1, "method": "run_action", "params": {"session": "main", "action": {"GoToTab": 2}}}`
+//!
+//! Methods:
+//! - `list_sessions`: the running sessions
+//! - `list_tabs`, `list_panes`, `list_clients` (params: `session`): the session's tabs, panes and
+//! connected clients
+//! - `dump_session` (params: `session`): a snapshot of the whole session, as in
+//! `zellij action dump-session`
+//! - `run_action` (params: `session`, `action`): an `Action`, serialized as it is in the IPC
+//! messages between the client and the server
+//! - `run_cli_action` (params: `session`, `args`): the arguments of `zellij action`, eg.
+//! `["new-tab", "--name", "logs"]`
+//! - `subscribe_events` (params: `session`, optionally `filter`): the events of the session (as in
+//! `zellij events --format json`) are then sent on the connection as `event` notifications, eg.
+//! `{"jsonrpc": "2.0", "method": "event", "params": {"session": "main", "event": {"event": "tab-created", ..}}}`,
+//! followed by an `event_stream_ended` notification when the session ends. Closing the connection
+//! ends the subscription.
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::{fs, thread};
+
+use crate::exit_codes::{exit_with_error, ExitCode};
+use crate::sessions::get_sessions;
+use zellij_client::{
+    cli_client::{receive_session_events, send_actions_and_collect_output},
+    os_input_output::get_cli_client_os_input,
+};
+use zellij_utils::{
+    clap::Parser,
+    cli::{CliArgs, Command, Sessions},
+    input::{actions::Action, config::Config},
+    serde_json::{self, json, Value},
+    session_events::{event_name, SESSION_EVENTS},
+    shared::set_permissions,
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// the range from -32000 to -32099 is reserved for implementation defined errors
+const SESSION_NOT_FOUND: i64 = -32001;
+const ACTION_FAILED: i64 = -32002;
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// The events of a session that a connection subscribed to
+#[derive(Debug, PartialEq)]
+struct Subscription {
+    session_name: String,
+    filter: Vec<String>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+pub(crate) fn start_api_server(listen: &str, config: Option<Config>) {
+    let socket_path = parse_listen_address(listen)
+        .unwrap_or_else(|e| exit_with_error(ExitCode::InvalidInput, &e));
+    let listener = bind(&socket_path).unwrap_or_else(|e| exit_with_error(ExitCode::Failure, &e));
+    log::info!("Api server listening on {}", listen);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let config = config.clone();
+                let _ = thread::Builder::new()
+                    .name("api_server_connection".to_string())
+                    .spawn(move || handle_connection(stream, config));
+            },
+            Err(e) => log::error!("Failed to accept api server connection: {}", e),
+        }
+    }
+}
+
+fn parse_listen_address(listen: &str) -> Result<PathBuf, String> {
+    match listen.strip_prefix("unix:") {
+        Some(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+        _ => Err(format!(
+            "Cannot listen on '{}', only unix sockets are supported (eg. unix:/run/zellij-api.sock)",
+            listen
+        )),
+    }
+}
+
+fn bind(socket_path: &PathBuf) -> Result<UnixListener, String> {
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).is_ok() {
+            return Err(format!(
+                "Another server is already listening on {}",
+                socket_path.display()
+            ));
+        }
+        // left behind by a server that did not shut down cleanly
+        let _ = fs::remove_file(socket_path);
+    }
+    let listener = UnixListener::bind(socket_path)
+        .map_err(|e| format!("Failed to listen on {}: {}", socket_path.display(), e))?;
+    // the api can do anything the user can do in their sessions
+    set_permissions(socket_path, 0o600).map_err(|e| {
+        format!(
+            "Failed to restrict access to {}: {}",
+            socket_path.display(),
+            e
+        )
+    })?;
+    Ok(listener)
+}
+
+fn handle_connection(stream: UnixStream, config: Option<Config>) {
+    // shared with the threads sending the events of the subscribed sessions
+    let writer = match stream.try_clone() {
+        Ok(writer) => Arc::new(Mutex::new(writer)),
+        Err(e) => {
+            log::error!("Failed to handle api server connection: {}", e);
+            return;
+        },
+    };
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (response, subscription) = handle_line(&line, &config);
+        if let Some(response) = response {
+            if send(&writer, &response).is_err() {
+                break;
+            }
+        }
+        // only once the subscription was confirmed, so that no event precedes the response
+        if let Some(subscription) = subscription {
+            let writer = writer.clone();
+            let _ = thread::Builder::new()
+                .name("api_server_events".to_string())
+                .spawn(move || stream_events(subscription, writer));
+        }
+    }
+}
+
+fn send(writer: &Arc<Mutex<UnixStream>>, message: &Value) -> std::io::Result<()> {
+    writeln!(writer.lock().unwrap(), "{}", message)
+}
+
+/// The response to a line received on a connection (none for notifications), and the events the
+/// connection subscribed to if it did
+fn handle_line(line: &str, config: &Option<Config>) -> (Option<Value>, Option<Subscription>) {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(request) => request,
+        Err(e) => {
+            let error = RpcError::new(PARSE_ERROR, e.to_string());
+            return (Some(response(Value::Null, Err(error))), None);
+        },
+    };
+    let mut subscription = None;
+    let result = if request.get("method").and_then(Value::as_str) == Some("subscribe_events") {
+        subscribe_events(&request.get("params").cloned().unwrap_or(Value::Null)).map(
+            |new_subscription| {
+                subscription = Some(new_subscription);
+                json!({ "subscribed": true })
+            },
+        )
+    } else {
+        handle_request(&request, config)
+    };
+    // a notification, which is not answered
+    let response = request.get("id").map(|id| response(id.clone(), result));
+    (response, subscription)
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+fn handle_request(request: &Value, config: &Option<Config>) -> Result<Value, RpcError> {
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_REQUEST, "Requests must have a method"))?;
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    match method {
+        "list_sessions" => {
+            let sessions = get_sessions().map_err(|e| {
+                RpcError::new(ACTION_FAILED, format!("Failed to list sessions: {:?}", e))
+            })?;
+            Ok(Value::Array(
+                sessions
+                    .into_iter()
+                    .map(|(name, age)| json!({ "name": name, "age_secs": age.as_secs() }))
+                    .collect(),
+            ))
+        },
+        "list_tabs" => {
+            let tab_names = run_actions(&session_param(&params)?, vec![Action::QueryTabNames])?;
+            Ok(json!(tab_names))
+        },
+        "list_panes" => {
+            let panes = run_actions(
+                &session_param(&params)?,
+                vec![Action::ListPanes { json: true }],
+            )?;
+            output_as_json(panes)
+        },
+        "list_clients" => {
+            let clients = run_actions(
+                &session_param(&params)?,
+                vec![Action::ListClients { json: true }],
+            )?;
+            output_as_json(clients)
+        },
+        "dump_session" => {
+            let session_dump = run_actions(&session_param(&params)?, vec![Action::DumpSession])?;
+            output_as_json(session_dump)
+        },
+        "run_action" => {
+            let session_name = session_param(&params)?;
+            let action = params
+                .get("action")
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing the 'action' parameter"))?;
+            let action: Action = serde_json::from_value(action.clone())
+                .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid action: {}", e)))?;
+            let output = run_actions(&session_name, vec![action])?;
+            Ok(json!({ "output": output }))
+        },
+        "run_cli_action" => {
+            let session_name = session_param(&params)?;
+            let args: Vec<String> = params
+                .get("args")
+                .and_then(|args| serde_json::from_value(args.clone()).ok())
+                .ok_or_else(|| {
+                    RpcError::new(
+                        INVALID_PARAMS,
+                        "The 'args' parameter must be a list of strings",
+                    )
+                })?;
+            let cli_args = CliArgs::try_parse_from(
+                ["zellij", "action"]
+                    .into_iter()
+                    .map(String::from)
+                    .chain(args),
+            )
+            .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+            let cli_action = match cli_args.command {
+                Some(Command::Sessions(Sessions::Action(cli_action))) => cli_action,
+                _ => return Err(RpcError::new(INVALID_PARAMS, "Not an action")),
+            };
+            let get_current_dir = || std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            let actions =
+                Action::actions_from_cli(cli_action, Box::new(get_current_dir), config.clone())
+                    .map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+            let output = run_actions(&session_name, actions)?;
+            Ok(json!({ "output": output }))
+        },
+        method => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{}'", method),
+        )),
+    }
+}
+
+fn subscribe_events(params: &Value) -> Result<Subscription, RpcError> {
+    let session_name = session_param(params)?;
+    let filter: Vec<String> = match params.get("filter") {
+        Some(filter) => serde_json::from_value(filter.clone()).map_err(|_| {
+            RpcError::new(
+                INVALID_PARAMS,
+                "The 'filter' parameter must be a list of event names",
+            )
+        })?,
+        None => vec![],
+    };
+    if let Some(unknown_event) = filter
+        .iter()
+        .find(|f| !SESSION_EVENTS.contains(&f.as_str()))
+    {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            format!(
+                "Unknown event '{}', the events are: {}",
+                unknown_event,
+                SESSION_EVENTS.join(", ")
+            ),
+        ));
+    }
+    ensure_session_exists(&session_name)?;
+    Ok(Subscription {
+        session_name,
+        filter,
+    })
+}
+
+/// Sends the events of the session as notifications until the session ends or the connection is
+/// closed
+fn stream_events(subscription: Subscription, writer: Arc<Mutex<UnixStream>>) {
+    let Subscription {
+        session_name,
+        filter,
+    } = subscription;
+    let notification = |method: &str, params: Value| json!({ "jsonrpc": "2.0", "method": method, "params": params });
+    let result = get_cli_client_os_input()
+        .map_err(|e| format!("Failed to connect: {}", e))
+        .and_then(|os_input| {
+            receive_session_events(Box::new(os_input), &session_name, |_state, events| {
+                events
+                    .iter()
+                    .filter(|r| filter.is_empty() || filter.iter().any(|f| f == event_name(r)))
+                    .all(|record| {
+                        let params = json!({ "session": session_name, "event": record });
+                        send(&writer, &notification("event", params)).is_ok()
+                    })
+            })
+        });
+    let params = match result {
+        Ok(()) => json!({ "session": session_name }),
+        Err(e) => json!({ "session": session_name, "error": e }),
+    };
+    let _ = send(&writer, &notification("event_stream_ended", params));
+}
+
+fn session_param(params: &Value) -> Result<String, RpcError> {
+    params
+        .get("session")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing the 'session' parameter"))
+}
+
+fn ensure_session_exists(session_name: &str) -> Result<(), RpcError> {
+    // connecting to a session that does not exist would wait for it indefinitely
+    let session_exists = get_sessions()
+        .unwrap_or_default()
+        .iter()
+        .any(|(name, _)| name == session_name);
+    if session_exists {
+        Ok(())
+    } else {
+        Err(RpcError::new(
+            SESSION_NOT_FOUND,
+            format!("Session '{}' not found", session_name),
+        ))
+    }
+}
+
+fn run_actions(session_name: &str, actions: Vec<Action>) -> Result<Vec<String>, RpcError> {
+    ensure_session_exists(session_name)?;
+    let os_input = get_cli_client_os_input()
+        .map_err(|e| RpcError::new(ACTION_FAILED, format!("Failed to connect: {}", e)))?;
+    send_actions_and_collect_output(Box::new(os_input), session_name, actions)
+        .map_err(|e| RpcError::new(ACTION_FAILED, e))
+}
+
+fn output_as_json(output: Vec<String>) -> Result<Value, RpcError> {
+    serde_json::from_str(&output.join("\n")).map_err(|e| {
+        RpcError::new(
+            ACTION_FAILED,
+            format!("Session responded with invalid json: {}", e),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn respond_to(line: &str) -> Value {
+        let (response, subscription) = handle_line(line, &None);
+        assert!(subscription.is_none());
+        response.expect("request was answered")
+    }
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response
+            .get("error")
+            .and_then(|error| error.get("code"))
+            .and_then(Value::as_i64)
+    }
+
+    #[test]
+    fn only_unix_sockets_can_be_listened_on() {
+        assert_eq!(
+            parse_listen_address("unix:/run/zellij-api.sock"),
+            Ok(PathBuf::from("/run/zellij-api.sock"))
+        );
+        assert!(parse_listen_address("unix:").is_err());
+        assert!(parse_listen_address("tcp://127.0.0.1:8080").is_err());
+        assert!(parse_listen_address("/run/zellij-api.sock").is_err());
+    }
+
+    #[test]
+    fn responses_echo_the_request_id() {
+        assert_eq!(
+            response(json!("abc"), Ok(json!(["main"]))),
+            json!({ "jsonrpc": "2.0", "id": "abc", "result": ["main"] })
+        );
+        assert_eq!(
+            response(
+                json!(7),
+                Err(RpcError::new(METHOD_NOT_FOUND, "Unknown method 'foo'"))
+            ),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "error": { "code": METHOD_NOT_FOUND, "message": "Unknown method 'foo'" },
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_json_is_a_parse_error() {
+        let response = respond_to("{\"jsonrpc\": \"2.0\", \"id\": 1,");
+        assert_eq!(error_code(&response), Some(PARSE_ERROR));
+        assert_eq!(response.get("id"), Some(&Value::Null), "id is unknown");
+    }
+
+    #[test]
+    fn requests_must_have_a_method() {
+        let response = respond_to(r#"{"jsonrpc": "2.0", "id": 1}"#);
+        assert_eq!(error_code(&response), Some(INVALID_REQUEST));
+        let response = respond_to(r#"{"jsonrpc": "2.0", "id": 1, "method": 3}"#);
+        assert_eq!(error_code(&response), Some(INVALID_REQUEST));
+    }
+
+    #[test]
+    fn unknown_method_is_reported_with_the_request_id() {
+        let response = respond_to(r#"{"jsonrpc": "2.0", "id": 42, "method": "list_windows"}"#);
+        assert_eq!(error_code(&response), Some(METHOD_NOT_FOUND));
+        assert_eq!(response.get("id"), Some(&json!(42)));
+    }
+
+    #[test]
+    fn notifications_are_not_answered() {
+        let (response, subscription) =
+            handle_line(r#"{"jsonrpc": "2.0", "method": "list_windows"}"#, &None);
+        assert!(response.is_none());
+        assert!(subscription.is_none());
+    }
+
+    #[test]
+    fn missing_or_mistyped_params_are_invalid() {
+        let requests = [
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "list_tabs"}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "list_panes", "params": {"session": 3}}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "run_action", "params": {"session": "main"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "run_action", "params": {"session": "main", "action": 42}}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "run_cli_action", "params": {"session": "main", "args": "new-tab"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "run_cli_action", "params": {"session": "main", "args": ["no-such-action"]}}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "subscribe_events", "params": {}}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "subscribe_events", "params": {"session": "main", "filter": "tab-created"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "subscribe_events", "params": {"session": "main", "filter": ["no-such-event"]}}"#,
+        ];
+        for request in requests {
+            let response = respond_to(request);
+            assert_eq!(
+                error_code(&response),
+                Some(INVALID_PARAMS),
+                "invalid params in {}",
+                request
+            );
+        }
+    }
+}
@@ -1,113 +1,196 @@
 use dialoguer::Confirm;
-use std::{fs::File, io::prelude::*, path::PathBuf, process, time::Duration};
+use std::io::IsTerminal;
+use std::{fs::File, io::prelude::*, path::PathBuf, time::Duration};
 
+use crate::exit_codes::{
+    error_format, exit_with_code, exit_with_error, report_error, ErrorFormat, ExitCode,
+};
 use crate::sessions::{
-    assert_dead_session, assert_session, assert_session_ne, delete_session as delete_session_impl,
-    get_active_session, get_name_generator, get_resurrectable_session_names,
-    get_resurrectable_sessions, get_sessions, get_sessions_sorted_by_mtime,
-    kill_session as kill_session_impl, match_session_name, print_sessions,
-    print_sessions_with_index, resurrection_layout, session_exists, ActiveSession,
-    SessionNameMatch,
+    assert_dead_session, assert_session, assert_session_ne, clean_up_crashed_session,
+    delete_session as delete_session_impl, get_active_session, get_crashed_sessions,
+    get_resurrectable_session_names, get_resurrectable_sessions, get_session_name_candidates,
+    get_sessions, get_sessions_sorted_by_mtime, kill_session as kill_session_impl,
+    match_session_name, print_all_sessions, print_sessions, print_sessions_with_index,
+    report_sessions_of_other_versions, resurrection_layout, session_exists, session_idle_time,
+    signal_session_panes, wait_for_session_exit, ActiveSession, SessionNameMatch,
 };
 use zellij_client::{
+    cli_client::{start_event_stream_client, ResponseTimeout},
     old_config_converter::{
         config_yaml_to_config_kdl, convert_old_yaml_files, layout_yaml_to_layout_kdl,
+        tmux_project_to_layout_kdl,
     },
     os_input_output::get_client_os_input,
     start_client as start_client_impl, ClientInfo,
 };
 use zellij_server::{os_input_output::get_server_os_input, start_server as start_server_impl};
 use zellij_utils::{
-    cli::{CliArgs, Command, SessionCommand, Sessions},
+    cli::{
+        validate_session, CliArgs, Command, EventFormat, SessionCommand, Sessions,
+        TmuxProjectFormat,
+    },
     data::{ConnectToSession, LayoutInfo},
-    envs,
+    envs, humantime,
     input::{
         actions::Action,
         config::{Config, ConfigError},
         layout::Layout,
         options::Options,
     },
+    ipc::PaneSignal,
     miette::{Report, Result},
     nix,
+    session_events::SESSION_EVENTS,
     setup::{find_default_config_dir, get_layout_dir, Setup},
 };
 
 pub(crate) use crate::sessions::list_sessions;
 
-pub(crate) fn kill_all_sessions(yes: bool) {
+/// Ask the user to confirm a destructive action, exiting if they decline.
+///
+/// When not running in a terminal (eg. in a script) there is nobody to answer, so rather than
+/// hanging on the prompt we fail immediately and ask for `--yes`.
+fn confirm_or_abort(warning: &str) {
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        exit_with_error(
+            ExitCode::InvalidInput,
+            "Cannot ask for confirmation when not running in a terminal, use --yes to confirm.",
+        );
+    }
+    println!("WARNING: {}", warning);
+    match Confirm::new()
+        .with_prompt("Do you want to continue?")
+        .interact()
+    {
+        Ok(true) => {},
+        Ok(false) => exit_with_error(ExitCode::Aborted, "Abort."),
+        Err(e) => exit_with_error(
+            ExitCode::Failure,
+            &format!("Failed to read confirmation: {}", e),
+        ),
+    }
+}
+
+/// Sessions that can be told to have been idle for longer than `older_than` (all of them without
+/// it), so that age based cleanups never touch sessions whose age is unknown
+fn idle_for_longer_than(
+    sessions: Vec<String>,
+    older_than: Option<Duration>,
+    is_running: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    match older_than {
+        Some(older_than) => sessions
+            .into_iter()
+            .filter(|name| {
+                session_idle_time(name, is_running(name))
+                    .map(|idle_time| idle_time > older_than)
+                    .unwrap_or(false)
+            })
+            .collect(),
+        None => sessions,
+    }
+}
+
+pub(crate) fn kill_all_sessions(yes: bool, older_than: Option<Duration>) {
     match get_sessions() {
         Ok(sessions) if sessions.is_empty() => {
-            eprintln!("No active zellij sessions found.");
-            process::exit(1);
+            exit_with_error(ExitCode::NoSession, "No active zellij sessions found.");
         },
         Ok(sessions) => {
+            let sessions = idle_for_longer_than(
+                sessions.into_iter().map(|s| s.0).collect(),
+                older_than,
+                |_| true,
+            );
+            if sessions.is_empty() {
+                // nothing to clean up, which is not an error for (eg.) cron jobs
+                exit_with_code(ExitCode::Success);
+            }
             if !yes {
-                println!("WARNING: this action will kill all sessions.");
-                if !Confirm::new()
-                    .with_prompt("Do you want to continue?")
-                    .interact()
-                    .unwrap()
-                {
-                    println!("Abort.");
-                    process::exit(1);
+                match older_than {
+                    Some(older_than) => confirm_or_abort(&format!(
+                        "this action will kill {} session(s) not attached for more than {}.",
+                        sessions.len(),
+                        humantime::format_duration(older_than)
+                    )),
+                    None => confirm_or_abort("this action will kill all sessions."),
                 }
             }
             for session in &sessions {
-                kill_session_impl(&session.0);
+                kill_session_impl(session);
             }
-            process::exit(0);
-        },
-        Err(e) => {
-            eprintln!("Error occurred: {:?}", e);
-            process::exit(1);
+            exit_with_code(ExitCode::Success);
         },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Error occurred: {:?}", e)),
     }
 }
 
-pub(crate) fn delete_all_sessions(yes: bool, force: bool) {
+pub(crate) fn delete_all_sessions(yes: bool, force: bool, older_than: Option<Duration>) {
     let active_sessions: Vec<String> = get_sessions()
         .unwrap_or_default()
         .iter()
         .map(|s| s.0.clone())
         .collect();
     let resurrectable_sessions = get_resurrectable_sessions();
-    let dead_sessions: Vec<_> = if force {
-        resurrectable_sessions
+    let mut dead_sessions: Vec<_> = if force {
+        resurrectable_sessions.into_iter().map(|s| s.0).collect()
     } else {
         resurrectable_sessions
-            .iter()
-            .filter(|(name, _, _)| !active_sessions.contains(name))
-            .cloned()
+            .into_iter()
+            .map(|s| s.0)
+            .filter(|name| !active_sessions.contains(name))
             .collect()
     };
-    if !yes {
-        println!("WARNING: this action will delete all resurrectable sessions.");
-        if !Confirm::new()
-            .with_prompt("Do you want to continue?")
-            .interact()
-            .unwrap()
-        {
-            println!("Abort.");
-            process::exit(1);
+    // crashed sessions that could not be resurrected are only left behind
+    for (crashed_session, _) in get_crashed_sessions() {
+        if !dead_sessions.contains(&crashed_session) {
+            dead_sessions.push(crashed_session);
         }
     }
+    let dead_sessions = idle_for_longer_than(dead_sessions, older_than, |name| {
+        active_sessions.iter().any(|s| s == name)
+    });
+    if let Some(older_than) = older_than {
+        if dead_sessions.is_empty() {
+            exit_with_code(ExitCode::Success);
+        }
+        if !yes {
+            confirm_or_abort(&format!(
+                "this action will delete {} session(s) not attached for more than {}.",
+                dead_sessions.len(),
+                humantime::format_duration(older_than)
+            ));
+        }
+    } else if !yes {
+        confirm_or_abort("this action will delete all resurrectable sessions.");
+    }
     for session in &dead_sessions {
-        delete_session_impl(&session.0, force);
+        delete_session_impl(session, force);
     }
-    process::exit(0);
+    exit_with_code(ExitCode::Success);
 }
 
-pub(crate) fn kill_session(target_session: &Option<String>) {
+pub(crate) fn kill_session(
+    target_session: &Option<String>,
+    signal: Option<PaneSignal>,
+    grace_period: u64,
+) {
     match target_session {
         Some(target_session) => {
             assert_session(target_session);
+            if let Some(signal) = signal {
+                signal_session_panes(target_session, signal);
+                if wait_for_session_exit(target_session, Duration::from_secs(grace_period)) {
+                    exit_with_code(ExitCode::Success);
+                }
+            }
             kill_session_impl(target_session);
-            process::exit(0);
-        },
-        None => {
-            println!("Please specify the session name to kill.");
-            process::exit(1);
+            exit_with_code(ExitCode::Success);
         },
+        None => exit_with_error(
+            ExitCode::InvalidInput,
+            "Please specify the session name to kill.",
+        ),
     }
 }
 
@@ -116,12 +199,12 @@ pub(crate) fn delete_session(target_session: &Option<String>, force: bool) {
         Some(target_session) => {
             assert_dead_session(target_session, force);
             delete_session_impl(target_session, force);
-            process::exit(0);
-        },
-        None => {
-            println!("Please specify the session name to delete.");
-            process::exit(1);
+            exit_with_code(ExitCode::Success);
         },
+        None => exit_with_error(
+            ExitCode::InvalidInput,
+            "Please specify the session name to delete.",
+        ),
     }
 }
 
@@ -131,8 +214,10 @@ fn get_os_input<OsInputOutput>(
     match fn_get_os_input() {
         Ok(os_input) => os_input,
         Err(e) => {
-            eprintln!("failed to open terminal:\n{}", e);
-            process::exit(1);
+            exit_with_error(
+                ExitCode::Failure,
+                &format!("failed to open terminal:\n{}", e),
+            );
         },
     }
 }
@@ -144,8 +229,8 @@ pub(crate) fn start_server(path: PathBuf, debug: bool) {
     start_server_impl(Box::new(os_input), path);
 }
 
-fn create_new_client() -> ClientInfo {
-    ClientInfo::New(generate_unique_session_name())
+fn create_new_client(config_options: &Options) -> ClientInfo {
+    ClientInfo::New(generate_unique_session_name(config_options))
 }
 
 fn find_indexed_session(
@@ -156,14 +241,19 @@ fn find_indexed_session(
 ) -> ClientInfo {
     match sessions.get(index) {
         Some(session) => ClientInfo::Attach(session.clone(), config_options),
-        None if create => create_new_client(),
+        None if create => create_new_client(&config_options),
         None => {
-            println!(
-                "No session indexed by {} found. The following sessions are active:",
-                index
+            report_error(
+                ExitCode::NoSession,
+                &format!(
+                    "No session indexed by {} found. The following sessions are active:",
+                    index
+                ),
             );
-            print_sessions_with_index(sessions);
-            process::exit(1);
+            if error_format() == ErrorFormat::Human {
+                print_sessions_with_index(sessions);
+            }
+            exit_with_code(ExitCode::NoSession);
         },
     }
 }
@@ -175,24 +265,60 @@ pub(crate) fn send_action_to_session(
     cli_action: zellij_utils::cli::CliAction,
     requested_session_name: Option<String>,
     config: Option<Config>,
+    timeout: Option<Duration>,
 ) {
+    let session_name = cli_target_session(requested_session_name);
+    attach_with_cli_client(cli_action, &session_name, config, timeout);
+}
+
+pub(crate) fn follow_session_events(
+    requested_session_name: Option<String>,
+    follow: bool,
+    filter: Vec<String>,
+    format: EventFormat,
+) {
+    if let Some(unknown_event) = filter
+        .iter()
+        .find(|f| !SESSION_EVENTS.contains(&f.as_str()))
+    {
+        exit_with_error(
+            ExitCode::InvalidInput,
+            &format!(
+                "Unknown event '{}', the events are: {}",
+                unknown_event,
+                SESSION_EVENTS.join(", ")
+            ),
+        );
+    }
+    let session_name = cli_target_session(requested_session_name);
+    let os_input = get_os_input(zellij_client::os_input_output::get_cli_client_os_input);
+    start_event_stream_client(Box::new(os_input), &session_name, follow, filter, format);
+}
+
+/// The session that CLI commands run in the context of (eg. `zellij action`), exits when it is
+/// ambiguous or does not exist
+fn cli_target_session(requested_session_name: Option<String>) -> String {
     match get_active_session() {
         ActiveSession::None => {
-            eprintln!("There is no active session!");
-            std::process::exit(1);
+            exit_with_error(ExitCode::NoSession, "There is no active session!");
         },
         ActiveSession::One(session_name) => {
             if let Some(requested_session_name) = requested_session_name {
                 if requested_session_name != session_name {
-                    eprintln!(
-                        "Session '{}' not found. The following sessions are active:",
-                        requested_session_name
+                    report_error(
+                        ExitCode::NoSession,
+                        &format!(
+                            "Session '{}' not found. The following sessions are active:",
+                            requested_session_name
+                        ),
                     );
-                    eprintln!("{}", session_name);
-                    std::process::exit(1);
+                    if error_format() == ErrorFormat::Human {
+                        eprintln!("{}", session_name);
+                    }
+                    exit_with_code(ExitCode::NoSession);
                 }
             }
-            attach_with_cli_client(cli_action, &session_name, config);
+            session_name
         },
         ActiveSession::Many => {
             let existing_sessions: Vec<String> = get_sessions()
@@ -202,24 +328,31 @@ pub(crate) fn send_action_to_session(
                 .collect();
             if let Some(session_name) = requested_session_name {
                 if existing_sessions.contains(&session_name) {
-                    attach_with_cli_client(cli_action, &session_name, config);
+                    session_name
                 } else {
-                    eprintln!(
-                        "Session '{}' not found. The following sessions are active:",
-                        session_name
+                    report_error(
+                        ExitCode::NoSession,
+                        &format!(
+                            "Session '{}' not found. The following sessions are active:",
+                            session_name
+                        ),
                     );
-                    list_sessions(false, false, true);
-                    std::process::exit(1);
+                    if error_format() == ErrorFormat::Human {
+                        print_all_sessions(false, false, false, true, &[]);
+                    }
+                    exit_with_code(ExitCode::NoSession);
                 }
             } else if let Ok(session_name) = envs::get_session_name() {
-                attach_with_cli_client(cli_action, &session_name, config);
+                session_name
             } else {
-                eprintln!("Please specify the session name to send actions to. The following sessions are active:");
-                list_sessions(false, false, true);
-                std::process::exit(1);
+                report_error(ExitCode::AmbiguousSession, "Please specify the session name to send actions to. The following sessions are active:");
+                if error_format() == ErrorFormat::Human {
+                    print_all_sessions(false, false, false, true, &[]);
+                }
+                exit_with_code(ExitCode::AmbiguousSession);
             }
         },
-    };
+    }
 }
 pub(crate) fn convert_old_config_file(old_config_file: PathBuf) {
     match File::open(&old_config_file) {
@@ -229,41 +362,39 @@ pub(crate) fn convert_old_config_file(old_config_file: PathBuf) {
             match config_yaml_to_config_kdl(&raw_config_file, false) {
                 Ok(kdl_config) => {
                     println!("{}", kdl_config);
-                    process::exit(0);
-                },
-                Err(e) => {
-                    eprintln!("Failed to convert config: {}", e);
-                    process::exit(1);
+                    exit_with_code(ExitCode::Success);
                 },
+                Err(e) => exit_with_error(
+                    ExitCode::ConfigError,
+                    &format!("Failed to convert config: {}", e),
+                ),
             }
         },
-        Err(e) => {
-            eprintln!("Failed to open file: {}", e);
-            process::exit(1);
-        },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Failed to open file: {}", e)),
     }
 }
 
-pub(crate) fn convert_old_layout_file(old_layout_file: PathBuf) {
+pub(crate) fn convert_old_layout_file(old_layout_file: PathBuf, from: Option<TmuxProjectFormat>) {
     match File::open(&old_layout_file) {
         Ok(mut handle) => {
             let mut raw_layout_file = String::new();
             let _ = handle.read_to_string(&mut raw_layout_file);
-            match layout_yaml_to_layout_kdl(&raw_layout_file) {
+            let kdl_layout = match from {
+                Some(format) => tmux_project_to_layout_kdl(&raw_layout_file, format),
+                None => layout_yaml_to_layout_kdl(&raw_layout_file),
+            };
+            match kdl_layout {
                 Ok(kdl_layout) => {
                     println!("{}", kdl_layout);
-                    process::exit(0);
-                },
-                Err(e) => {
-                    eprintln!("Failed to convert layout: {}", e);
-                    process::exit(1);
+                    exit_with_code(ExitCode::Success);
                 },
+                Err(e) => exit_with_error(
+                    ExitCode::ConfigError,
+                    &format!("Failed to convert layout: {}", e),
+                ),
             }
         },
-        Err(e) => {
-            eprintln!("Failed to open file: {}", e);
-            process::exit(1);
-        },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Failed to open file: {}", e)),
     }
 }
 
@@ -275,18 +406,15 @@ pub(crate) fn convert_old_theme_file(old_theme_file: PathBuf) {
             match config_yaml_to_config_kdl(&raw_config_file, true) {
                 Ok(kdl_config) => {
                     println!("{}", kdl_config);
-                    process::exit(0);
-                },
-                Err(e) => {
-                    eprintln!("Failed to convert config: {}", e);
-                    process::exit(1);
+                    exit_with_code(ExitCode::Success);
                 },
+                Err(e) => exit_with_error(
+                    ExitCode::ConfigError,
+                    &format!("Failed to convert config: {}", e),
+                ),
             }
         },
-        Err(e) => {
-            eprintln!("Failed to open file: {}", e);
-            process::exit(1);
-        },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Failed to open file: {}", e)),
     }
 }
 
@@ -294,18 +422,33 @@ fn attach_with_cli_client(
     cli_action: zellij_utils::cli::CliAction,
     session_name: &str,
     config: Option<Config>,
+    timeout: Option<Duration>,
 ) {
     let os_input = get_os_input(zellij_client::os_input_output::get_cli_client_os_input);
     let get_current_dir = || std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     match Action::actions_from_cli(cli_action, Box::new(get_current_dir), config) {
         Ok(actions) => {
-            zellij_client::cli_client::start_cli_client(Box::new(os_input), session_name, actions);
-            std::process::exit(0);
+            let response_timeout = timeout.map(|timeout| {
+                let message = format!(
+                    "Session '{}' did not respond within {}",
+                    session_name,
+                    humantime::format_duration(timeout)
+                );
+                ResponseTimeout::new(timeout, move || {
+                    exit_with_error(ExitCode::Timeout, &message)
+                })
+            });
+            zellij_client::cli_client::start_cli_client(
+                Box::new(os_input),
+                session_name,
+                actions,
+                response_timeout,
+            );
+            exit_with_code(ExitCode::Success);
         },
         Err(e) => {
-            eprintln!("{}", e);
             log::error!("Error sending action: {}", e);
-            std::process::exit(2);
+            exit_with_error(ExitCode::InvalidInput, &e);
         },
     }
 }
@@ -315,17 +458,13 @@ fn attach_with_session_index(config_options: Options, index: usize, create: bool
     match get_sessions_sorted_by_mtime() {
         Ok(sessions) if sessions.is_empty() => {
             if create {
-                create_new_client()
+                create_new_client(&config_options)
             } else {
-                eprintln!("No active zellij sessions found.");
-                process::exit(1);
+                exit_with_error(ExitCode::NoSession, "No active zellij sessions found.");
             }
         },
         Ok(sessions) => find_indexed_session(sessions, config_options, index, create),
-        Err(e) => {
-            eprintln!("Error occurred: {:?}", e);
-            process::exit(1);
-        },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Error occurred: {:?}", e)),
     }
 }
 
@@ -347,37 +486,49 @@ fn attach_with_session_name(
                 ClientInfo::Attach(s, config_options)
             },
             SessionNameMatch::AmbiguousPrefix(sessions) => {
-                println!(
-                    "Ambiguous selection: multiple sessions names start with '{}':",
-                    prefix
-                );
-                print_sessions(
-                    sessions
-                        .iter()
-                        .map(|s| (s.clone(), Duration::default(), false))
-                        .collect(),
-                    false,
-                    false,
-                    true,
+                report_error(
+                    ExitCode::AmbiguousSession,
+                    &format!(
+                        "Ambiguous selection: multiple sessions names start with '{}':",
+                        prefix
+                    ),
                 );
-                process::exit(1);
+                if error_format() == ErrorFormat::Human {
+                    print_sessions(
+                        sessions
+                            .iter()
+                            .map(|s| (s.clone(), Duration::default(), false))
+                            .collect(),
+                        false,
+                        false,
+                        true,
+                    );
+                }
+                exit_with_code(ExitCode::AmbiguousSession);
             },
             SessionNameMatch::None => {
-                eprintln!("No session with the name '{}' found!", prefix);
-                process::exit(1);
+                report_error(
+                    ExitCode::NoSession,
+                    &format!("No session with the name '{}' found!", prefix),
+                );
+                report_sessions_of_other_versions(Some(prefix.as_str()));
+                exit_with_code(ExitCode::NoSession);
             },
         },
         None => match get_active_session() {
-            ActiveSession::None if create => create_new_client(),
+            ActiveSession::None if create => create_new_client(&config_options),
             ActiveSession::None => {
-                eprintln!("No active zellij sessions found.");
-                process::exit(1);
+                report_error(ExitCode::NoSession, "No active zellij sessions found.");
+                report_sessions_of_other_versions(None);
+                exit_with_code(ExitCode::NoSession);
             },
             ActiveSession::One(session_name) => ClientInfo::Attach(session_name, config_options),
             ActiveSession::Many => {
-                println!("Please specify the session to attach to, either by using the full name or a unique prefix.\nThe following sessions are active:");
-                list_sessions(false, false, true);
-                process::exit(1);
+                report_error(ExitCode::AmbiguousSession, "Please specify the session to attach to, either by using the full name or a unique prefix.\nThe following sessions are active:");
+                if error_format() == ErrorFormat::Human {
+                    print_all_sessions(false, false, false, true, &[]);
+                }
+                exit_with_code(ExitCode::AmbiguousSession);
             },
         },
     }
@@ -395,13 +546,7 @@ pub(crate) fn start_client(opts: CliArgs) {
     ) = match Setup::from_cli_args(&opts) {
         Ok(results) => results,
         Err(e) => {
-            if let ConfigError::KdlError(error) = e {
-                let report: Report = error.into();
-                eprintln!("{:?}", report);
-            } else {
-                eprintln!("{}", e);
-            }
-            process::exit(1);
+            report_config_error(e);
         },
     };
 
@@ -434,6 +579,8 @@ pub(crate) fn start_client(opts: CliArgs) {
                     force_run_commands: false,
                     index: None,
                     options: None,
+                    tag: vec![],
+                    read_only: false,
                 }));
             } else {
                 opts.command = None;
@@ -493,6 +640,7 @@ pub(crate) fn start_client(opts: CliArgs) {
         }
 
         let start_client_plan = |session_name: std::string::String| {
+            clean_up_crashed_session(&session_name);
             assert_session_ne(&session_name);
         };
 
@@ -503,6 +651,8 @@ pub(crate) fn start_client(opts: CliArgs) {
             force_run_commands,
             index,
             options,
+            tag: _,
+            read_only: _,
         })) = opts.command.clone()
         {
             let config_options = match options.as_deref() {
@@ -512,6 +662,9 @@ pub(crate) fn start_client(opts: CliArgs) {
                 None => config_options,
             };
             should_create_detached = create_background;
+            if let Some(session_name) = session_name.as_ref() {
+                clean_up_crashed_session(session_name);
+            }
 
             let client = if let Some(idx) = index {
                 attach_with_session_index(
@@ -519,6 +672,12 @@ pub(crate) fn start_client(opts: CliArgs) {
                     idx,
                     create || should_create_detached,
                 )
+            } else if should_create_detached && session_name.is_none() {
+                // a background session is always a new one, its generated name is printed so that
+                // provisioning scripts can address it later
+                let client = create_new_client(&config_options);
+                println!("{}", client.get_session_name());
+                client
             } else {
                 let session_exists = session_name
                     .as_ref()
@@ -547,6 +706,15 @@ pub(crate) fn start_client(opts: CliArgs) {
                 }
             };
 
+            if should_create_detached {
+                if let ClientInfo::Attach(session_name, _) = &client {
+                    exit_with_error(
+                        ExitCode::SessionExists,
+                        &format!("Session already exists: {}", session_name),
+                    );
+                }
+            }
+
             if let Ok(val) = std::env::var(envs::SESSION_NAME_ENV_KEY) {
                 if val == *client.get_session_name() {
                     panic!("You are trying to attach to the current session (\"{}\"). This is not supported.", val);
@@ -605,8 +773,7 @@ pub(crate) fn start_client(opts: CliArgs) {
                         // `zellij_server::terminal_bytes::listen` task, flooding the server and
                         // clients with infinite `Render` requests.
                         if *session_name == val {
-                            eprintln!("You are trying to attach to the current session (\"{}\"). Zellij does not support nesting a session in itself.", session_name);
-                            process::exit(1);
+                            exit_with_error(ExitCode::InvalidInput, &format!("You are trying to attach to the current session (\"{}\"). Zellij does not support nesting a session in itself.", session_name));
                         }
                     }
                     match config_options.attach_to_session {
@@ -657,10 +824,10 @@ pub(crate) fn start_client(opts: CliArgs) {
                     }
                     // after we detach, this happens and so we need to exit before the rest of the
                     // function happens
-                    process::exit(0);
+                    exit_with_code(ExitCode::Success);
                 }
 
-                let session_name = generate_unique_session_name();
+                let session_name = generate_unique_session_name(&config_options);
                 start_client_plan(session_name.clone());
                 reconnect_to_session = start_client_impl(
                     Box::new(os_input),
@@ -682,7 +849,7 @@ pub(crate) fn start_client(opts: CliArgs) {
     }
 }
 
-fn generate_unique_session_name() -> String {
+fn generate_unique_session_name(config_options: &Options) -> String {
     let sessions = get_sessions().map(|sessions| {
         sessions
             .iter()
@@ -691,19 +858,33 @@ fn generate_unique_session_name() -> String {
     });
     let dead_sessions = get_resurrectable_session_names();
     let Ok(sessions) = sessions else {
-        eprintln!("Failed to list existing sessions: {:?}", sessions);
-        process::exit(1);
+        exit_with_error(
+            ExitCode::Failure,
+            &format!("Failed to list existing sessions: {:?}", sessions),
+        );
     };
 
-    let name = get_name_generator()
+    let candidates = match get_session_name_candidates(config_options) {
+        Ok(candidates) => candidates,
+        Err(e) => exit_with_error(ExitCode::ConfigError, &e),
+    };
+    let name = candidates
         .take(1000)
         .find(|name| !sessions.contains(name) && !dead_sessions.contains(name));
 
     if let Some(name) = name {
+        if let Err(e) = validate_session(&name) {
+            exit_with_error(
+                ExitCode::ConfigError,
+                &format!("Generated session name '{}' is invalid: {}", name, e),
+            );
+        }
         return name;
     } else {
-        eprintln!("Failed to generate a unique session name, giving up");
-        process::exit(1);
+        exit_with_error(
+            ExitCode::Failure,
+            "Failed to generate a unique session name, giving up",
+        );
     }
 }
 
@@ -712,19 +893,30 @@ pub(crate) fn list_aliases(opts: CliArgs) {
         match Setup::from_cli_args(&opts) {
             Ok(results) => results,
             Err(e) => {
-                if let ConfigError::KdlError(error) = e {
-                    let report: Report = error.into();
-                    eprintln!("{:?}", report);
-                } else {
-                    eprintln!("{}", e);
-                }
-                process::exit(1);
+                report_config_error(e);
             },
         };
     for alias in config.plugins.list() {
         println!("{}", alias);
     }
-    process::exit(0);
+    exit_with_code(ExitCode::Success);
+}
+
+fn report_config_error(e: ConfigError) -> ! {
+    if let ConfigError::KdlError(error) = e {
+        if error_format() == ErrorFormat::Human {
+            let report: Report = error.into();
+            eprintln!("{:?}", report);
+            exit_with_code(ExitCode::ConfigError);
+        } else {
+            exit_with_error(
+                ExitCode::ConfigError,
+                &format!("{}: {}", error, error.error_message),
+            );
+        }
+    } else {
+        exit_with_error(ExitCode::ConfigError, &e.to_string());
+    }
 }
 
 fn reload_config_from_disk(
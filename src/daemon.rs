@@ -0,0 +1,396 @@
+//! `zellij start-server` runs zellij as a persistent (systemd) user service: it creates the
+//! sessions declared in the `daemon` block of the configuration and keeps running until it is
+//! terminated.
+//!
+//! With `--daemon` it forks into the background, the original process only exits once the
+//! sessions were created (as systemd expects of a `Type=forking` service). When started by
+//! systemd as a `Type=notify` service, readiness is reported over `$NOTIFY_SOCKET` instead.
+//! See `example/zellij.service`.
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+#[cfg(target_os = "linux")]
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::path::{Path, PathBuf};
+use std::process::{self, Stdio};
+
+use crate::exit_codes::{exit_with_error, ExitCode};
+use crate::sessions::session_exists;
+use zellij_utils::{
+    cli::CliArgs,
+    input::{config::Config, daemon::DaemonSession},
+    nix::{
+        errno::Errno,
+        fcntl::{flock, FlockArg},
+        sys::{
+            signal::kill,
+            stat::{umask, Mode},
+            wait::waitpid,
+        },
+        unistd::{chdir, close, dup2, fork, pipe, setsid, ForkResult, Pid},
+    },
+    signal_hook::{consts::signal::*, iterator::Signals},
+};
+
+pub(crate) fn start_server(
+    opts: &CliArgs,
+    daemon: bool,
+    pidfile: Option<PathBuf>,
+    config: Option<Config>,
+) {
+    let config = config.unwrap_or_else(|| {
+        exit_with_error(ExitCode::ConfigError, "Failed to load the configuration")
+    });
+    // a daemon moves to /, relative paths are resolved from where we were started
+    let started_in = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+    let pidfile = pidfile.map(|pidfile| {
+        let pidfile = started_in.join(pidfile);
+        let locked_pidfile =
+            lock_pidfile(&pidfile).unwrap_or_else(|e| exit_with_error(ExitCode::Failure, &e));
+        (pidfile, locked_pidfile)
+    });
+    // the write end of a pipe the original process waits on, when we daemonize
+    let ready = if daemon { Some(daemonize()) } else { None };
+    // set up before the sessions are created, so that no termination request is missed
+    let mut signals = Signals::new(&[SIGINT, SIGTERM, SIGHUP]).unwrap_or_else(|e| {
+        exit_with_error(
+            ExitCode::Failure,
+            &format!("Failed to listen for signals: {}", e),
+        )
+    });
+    let notify_socket = std::env::var("NOTIFY_SOCKET").ok();
+    let served = serve(
+        &config.daemon.sessions,
+        |session| create_session(opts, session, &started_in),
+        pidfile,
+        ready,
+        notify_socket.as_deref(),
+        || {
+            // the sessions are servers of their own, we only have to stay around as long as the
+            // service
+            if let Some(signal) = signals.forever().next() {
+                log::info!("Zellij server stopping (signal {})", signal);
+            }
+        },
+    );
+    if let Err(e) = served {
+        exit_with_error(ExitCode::Failure, &e);
+    }
+}
+
+/// Creates the sessions and reports that we are ready, then waits to be stopped
+fn serve(
+    sessions: &[DaemonSession],
+    create_session: impl Fn(&DaemonSession),
+    pidfile: Option<(PathBuf, File)>,
+    ready: Option<File>,
+    notify_socket: Option<&str>,
+    wait_until_stopped: impl FnOnce(),
+) -> Result<(), String> {
+    if let Some((pidfile, locked_pidfile)) = pidfile.as_ref() {
+        write_pid(locked_pidfile)
+            .map_err(|e| format!("Failed to write pidfile {}: {}", pidfile.display(), e))?;
+    }
+    for session in sessions {
+        create_session(session);
+    }
+
+    if let Some(mut ready) = ready {
+        let _ = ready.write_all(b"1");
+    }
+    notify_systemd(
+        notify_socket,
+        &format!("READY=1\nMAINPID={}", process::id()),
+    );
+    log::info!(
+        "Zellij server started with {} declared session(s)",
+        sessions.len()
+    );
+
+    wait_until_stopped();
+    notify_systemd(notify_socket, "STOPPING=1");
+    // removed while we still hold the lock, a server starting meanwhile creates a new pidfile
+    if let Some((pidfile, _locked_pidfile)) = pidfile {
+        let _ = fs::remove_file(pidfile);
+    }
+    Ok(())
+}
+
+/// Forks, the original process exits once the forked one reports it is ready (or with an error if
+/// it exits before that). The forked process gets its own session and no terminal.
+fn daemonize() -> File {
+    let (read_fd, write_fd) = pipe().unwrap_or_else(|e| {
+        exit_with_error(ExitCode::Failure, &format!("Failed to daemonize: {}", e))
+    });
+    // SAFETY: only this thread carries on in the forked process, and it does not touch any lock
+    // another thread could have held
+    match unsafe { fork() } {
+        Ok(ForkResult::Parent { child }) => {
+            let _ = close(write_fd);
+            // the forked process exits as soon as it forked the server
+            let _ = waitpid(child, None);
+            let mut ready = unsafe { File::from_raw_fd(read_fd) };
+            let mut message = vec![];
+            let _ = ready.read_to_end(&mut message);
+            if message.is_empty() {
+                exit_with_error(ExitCode::Failure, "The server exited before it was ready");
+            }
+            process::exit(0);
+        },
+        Ok(ForkResult::Child) => {
+            let _ = close(read_fd);
+            let _ = setsid();
+            // forking again so that the server is not the leader of its session, which means it
+            // can never acquire a controlling terminal
+            // SAFETY: as above
+            match unsafe { fork() } {
+                Ok(ForkResult::Parent { .. }) => process::exit(0),
+                Ok(ForkResult::Child) => {},
+                Err(e) => {
+                    exit_with_error(ExitCode::Failure, &format!("Failed to daemonize: {}", e))
+                },
+            }
+            // so that we do not keep the directory we were started in from being unmounted, and
+            // do not create files with whatever umask the shell that started us happened to have
+            let _ = chdir("/");
+            umask(Mode::from_bits_truncate(0o022));
+            if let Ok(dev_null) = OpenOptions::new().read(true).write(true).open("/dev/null") {
+                for fd in 0..=2 {
+                    let _ = dup2(dev_null.as_raw_fd(), fd);
+                }
+            }
+            unsafe { File::from_raw_fd(write_fd) }
+        },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Failed to daemonize: {}", e)),
+    }
+}
+
+/// Opens and locks the pidfile, the lock is held for as long as the returned file is open (by us
+/// or the process we daemonize into), so that only one server can run with this pidfile
+fn lock_pidfile(pidfile: &Path) -> Result<File, String> {
+    let locked_pidfile = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(pidfile)
+        .map_err(|e| format!("Failed to open pidfile {}: {}", pidfile.display(), e))?;
+    match flock(locked_pidfile.as_raw_fd(), FlockArg::LockExclusiveNonblock) {
+        Ok(()) => Ok(locked_pidfile),
+        Err(Errno::EWOULDBLOCK) => Err(match pid_in(pidfile) {
+            Some(pid) => format!("Another server is already running (pid {})", pid),
+            None => "Another server is already running".to_owned(),
+        }),
+        // eg. a filesystem without locks, we can only go by the pid in it
+        Err(e) => match running_pid(pidfile) {
+            Some(pid) => Err(format!("Another server is already running (pid {})", pid)),
+            None => {
+                log::warn!("Failed to lock pidfile {}: {}", pidfile.display(), e);
+                Ok(locked_pidfile)
+            },
+        },
+    }
+}
+
+fn write_pid(mut pidfile: &File) -> std::io::Result<()> {
+    pidfile.set_len(0)?;
+    pidfile.seek(SeekFrom::Start(0))?;
+    pidfile.write_all(format!("{}\n", process::id()).as_bytes())
+}
+
+fn pid_in(pidfile: &Path) -> Option<i32> {
+    fs::read_to_string(pidfile).ok()?.trim().parse::<i32>().ok()
+}
+
+fn running_pid(pidfile: &Path) -> Option<i32> {
+    let pid = pid_in(pidfile)?;
+    // a pidfile left behind by a server that did not shut down cleanly is ignored, a process we are
+    // not permitted to signal is running all the same
+    match kill(Pid::from_raw(pid), None) {
+        Ok(()) | Err(Errno::EPERM) => Some(pid),
+        Err(_) => None,
+    }
+}
+
+/// Creates the session in the background (as `zellij attach --create-background` would), unless
+/// it is already running. A session that can be resurrected is resurrected.
+fn create_session(opts: &CliArgs, session: &DaemonSession, started_in: &Path) {
+    if session_exists(&session.name).unwrap_or(false) {
+        log::info!("Session {} is already running", session.name);
+        return;
+    }
+    let zellij = match std::env::current_exe() {
+        Ok(zellij) => zellij,
+        Err(e) => {
+            log::error!("Failed to create session {}: {}", session.name, e);
+            return;
+        },
+    };
+    let mut args: Vec<OsString> = vec![];
+    if let Some(config) = &opts.config {
+        args.extend(["--config".into(), started_in.join(config).into()]);
+    }
+    if let Some(config_dir) = &opts.config_dir {
+        args.extend(["--config-dir".into(), started_in.join(config_dir).into()]);
+    }
+    if let Some(data_dir) = &opts.data_dir {
+        args.extend(["--data-dir".into(), started_in.join(data_dir).into()]);
+    }
+    if let Some(layout) = &session.layout {
+        args.extend(["--layout".into(), layout.into()]);
+    }
+    args.extend([
+        "attach".into(),
+        "--create-background".into(),
+        session.name.as_str().into(),
+    ]);
+    let mut command = process::Command::new(zellij);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command.current_dir(match &session.cwd {
+        Some(cwd) => started_in.join(cwd),
+        None => started_in.to_path_buf(),
+    });
+    match command.status() {
+        Ok(status) if status.success() => log::info!("Created session {}", session.name),
+        Ok(status) => log::error!("Failed to create session {}: {}", session.name, status),
+        Err(e) => log::error!("Failed to create session {}: {}", session.name, e),
+    }
+}
+
+/// Sends a state change to systemd (see sd_notify(3)), when we were started as a notify service
+/// (with the `$NOTIFY_SOCKET` it gave us)
+fn notify_systemd(notify_socket: Option<&str>, state: &str) {
+    let notify_socket = match notify_socket {
+        Some(notify_socket) => notify_socket,
+        None => return,
+    };
+    let address = match notify_socket.strip_prefix('@') {
+        #[cfg(target_os = "linux")]
+        Some(abstract_name) => SocketAddr::from_abstract_name(abstract_name.as_bytes()),
+        // abstract sockets only exist on linux
+        #[cfg(not(target_os = "linux"))]
+        Some(_) => return,
+        None => SocketAddr::from_pathname(notify_socket),
+    };
+    let sent = UnixDatagram::unbound().and_then(|socket| {
+        address.and_then(|address| socket.send_to_addr(state.as_bytes(), &address))
+    });
+    if let Err(e) = sent {
+        log::error!("Failed to notify systemd: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use zellij_utils::tempfile::tempdir;
+
+    fn session(name: &str) -> DaemonSession {
+        DaemonSession {
+            name: name.to_owned(),
+            layout: None,
+            cwd: None,
+        }
+    }
+
+    fn received_states(notify_socket: &UnixDatagram) -> Vec<String> {
+        let mut states = vec![];
+        let mut buf = [0u8; 256];
+        while let Ok(len) = notify_socket.recv(&mut buf) {
+            states.push(String::from_utf8_lossy(&buf[..len]).to_string());
+        }
+        states
+    }
+
+    #[test]
+    fn server_creates_its_sessions_and_reports_when_it_is_ready_and_stopping() {
+        let dir = tempdir().unwrap();
+        let pidfile = dir.path().join("zellij.pid");
+        let notify_socket_path = dir.path().join("notify");
+        let notify_socket = UnixDatagram::bind(&notify_socket_path).unwrap();
+        notify_socket.set_nonblocking(true).unwrap();
+        let (ready_read_fd, ready_write_fd) = pipe().unwrap();
+        let mut ready_read = unsafe { File::from_raw_fd(ready_read_fd) };
+        let ready_write = unsafe { File::from_raw_fd(ready_write_fd) };
+        let created_sessions = RefCell::new(vec![]);
+        let locked_pidfile = lock_pidfile(&pidfile).unwrap();
+
+        serve(
+            &[session("first"), session("second")],
+            |session| created_sessions.borrow_mut().push(session.name.clone()),
+            Some((pidfile.clone(), locked_pidfile)),
+            Some(ready_write),
+            Some(notify_socket_path.to_str().unwrap()),
+            || {
+                assert_eq!(
+                    fs::read_to_string(&pidfile).unwrap(),
+                    format!("{}\n", process::id())
+                );
+                assert_eq!(
+                    received_states(&notify_socket),
+                    vec![format!("READY=1\nMAINPID={}", process::id())]
+                );
+                assert_eq!(
+                    lock_pidfile(&pidfile).err(),
+                    Some(format!(
+                        "Another server is already running (pid {})",
+                        process::id()
+                    )),
+                    "a second server is refused while we run"
+                );
+            },
+        )
+        .unwrap();
+
+        assert_eq!(created_sessions.into_inner(), vec!["first", "second"]);
+        let mut ready = String::new();
+        ready_read.read_to_string(&mut ready).unwrap();
+        assert_eq!(
+            ready, "1",
+            "the process we daemonized from was told we are ready"
+        );
+        assert_eq!(received_states(&notify_socket), vec!["STOPPING=1"]);
+        assert!(!pidfile.exists(), "pidfile removed once stopped");
+    }
+
+    #[test]
+    fn pidfile_left_behind_by_a_server_that_is_gone_is_taken_over() {
+        let dir = tempdir().unwrap();
+        let pidfile = dir.path().join("zellij.pid");
+        fs::write(&pidfile, "4194304\n").unwrap();
+        let locked_pidfile = lock_pidfile(&pidfile).expect("nothing holds the pidfile");
+        write_pid(&locked_pidfile).unwrap();
+        assert_eq!(
+            fs::read_to_string(&pidfile).unwrap(),
+            format!("{}\n", process::id())
+        );
+    }
+
+    #[test]
+    fn server_we_may_not_signal_is_running() {
+        let dir = tempdir().unwrap();
+        let pidfile = dir.path().join("zellij.pid");
+        // init, which runs whether or not we are permitted to signal it
+        fs::write(&pidfile, "1\n").unwrap();
+        assert_eq!(running_pid(&pidfile), Some(1));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn systemd_is_notified_on_an_abstract_socket() {
+        let abstract_name = format!("zellij-test-notify-{}", process::id());
+        let notify_socket = UnixDatagram::bind_addr(
+            &SocketAddr::from_abstract_name(abstract_name.as_bytes()).unwrap(),
+        )
+        .unwrap();
+        notify_socket.set_nonblocking(true).unwrap();
+        notify_systemd(Some(&format!("@{}", abstract_name)), "READY=1");
+        assert_eq!(received_states(&notify_socket), vec!["READY=1"]);
+    }
+}
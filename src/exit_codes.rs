@@ -0,0 +1,100 @@
+//! The exit codes of the zellij CLI and the way errors are reported to the user.
+//!
+//! Scripts rely on these codes, so the value of an existing code must never change. When adding
+//! a code, also document it in `EXIT_CODES_HELP` in `zellij-utils/src/cli.rs`.
+
+use std::process;
+use std::sync::OnceLock;
+use zellij_utils::cli_style::Painter;
+use zellij_utils::serde_json;
+
+static ERROR_FORMAT: OnceLock<ErrorFormat> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExitCode {
+    Success = 0,
+    Failure = 1,
+    InvalidInput = 2,
+    NoSession = 3,
+    AmbiguousSession = 4,
+    IpcFailure = 5,
+    ConfigError = 6,
+    Aborted = 7,
+    SessionExists = 8,
+    Timeout = 9,
+}
+
+impl ExitCode {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExitCode::Success => "success",
+            ExitCode::Failure => "failure",
+            ExitCode::InvalidInput => "invalid_input",
+            ExitCode::NoSession => "no_session",
+            ExitCode::AmbiguousSession => "ambiguous_session",
+            ExitCode::IpcFailure => "ipc_failure",
+            ExitCode::ConfigError => "config_error",
+            ExitCode::Aborted => "aborted",
+            ExitCode::SessionExists => "session_exists",
+            ExitCode::Timeout => "timeout",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorFormat {
+    Human,
+    Quiet,
+    Json,
+}
+
+pub(crate) fn set_error_format(quiet: bool, json_errors: bool) {
+    let error_format = if json_errors {
+        ErrorFormat::Json
+    } else if quiet {
+        ErrorFormat::Quiet
+    } else {
+        ErrorFormat::Human
+    };
+    let _ = ERROR_FORMAT.set(error_format);
+}
+
+pub(crate) fn error_format() -> ErrorFormat {
+    ERROR_FORMAT.get().copied().unwrap_or(ErrorFormat::Human)
+}
+
+/// Report an error according to the requested error format (see `--quiet` and `--json-errors`)
+/// without exiting
+pub(crate) fn report_error(code: ExitCode, message: &str) {
+    match error_format() {
+        ErrorFormat::Human => eprintln!("{}", Painter::stderr().red(message)),
+        ErrorFormat::Quiet => {},
+        ErrorFormat::Json => {
+            let error = serde_json::json!({
+                "error": {
+                    "code": code as i32,
+                    "kind": code.kind(),
+                    "message": message,
+                }
+            });
+            eprintln!("{}", error);
+        },
+    }
+}
+
+/// Print additional human readable context for an error (eg. a list of the existing sessions),
+/// this is omitted in the machine-parsable error formats
+pub(crate) fn report_error_context(message: &str) {
+    if error_format() == ErrorFormat::Human {
+        eprintln!("{}", message);
+    }
+}
+
+pub(crate) fn exit_with_error(code: ExitCode, message: &str) -> ! {
+    report_error(code, message);
+    process::exit(code as i32);
+}
+
+pub(crate) fn exit_with_code(code: ExitCode) -> ! {
+    process::exit(code as i32);
+}
@@ -1,11 +1,17 @@
+mod api_server;
 mod commands;
+mod daemon;
+mod exit_codes;
 mod sessions;
 #[cfg(test)]
 mod tests;
 
+use exit_codes::{exit_with_error, ExitCode};
+use std::path::PathBuf;
 use zellij_utils::{
     clap::Parser,
-    cli::{CliAction, CliArgs, Command, Sessions},
+    cli::{validate_session, CliAction, CliArgs, Command, Sessions},
+    cli_style,
     consts::create_config_and_cache_folders,
     envs,
     input::config::Config,
@@ -17,11 +23,13 @@ fn main() {
     configure_logger();
     create_config_and_cache_folders();
     let opts = CliArgs::parse();
+    exit_codes::set_error_format(opts.quiet, opts.json_errors);
+    cli_style::set_color_choice(opts.color);
 
     {
         let config = Config::try_from(&opts).ok();
         if let Some(Command::Sessions(Sessions::Action(cli_action))) = opts.command {
-            commands::send_action_to_session(cli_action, opts.session, config);
+            commands::send_action_to_session(cli_action, opts.session, config, opts.timeout);
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::Run {
@@ -32,12 +40,25 @@ fn main() {
             in_place,
             name,
             close_on_exit,
+            close_on_success,
+            keep_open,
             start_suspended,
+            start_immediately,
             x,
             y,
             width,
             height,
             pinned,
+            capture_output,
+            tail,
+            stdin_file,
+            stdin,
+            log_output,
+            log_max_size,
+            log_max_files,
+            log_strip_ansi,
+            watch,
+            watch_debounce,
         })) = opts.command
         {
             let cwd = cwd.or_else(|| std::env::current_dir().ok());
@@ -51,7 +72,10 @@ fn main() {
                 in_place,
                 name,
                 close_on_exit,
+                close_on_success,
+                keep_open,
                 start_suspended,
+                start_immediately,
                 configuration: None,
                 skip_plugin_cache,
                 x,
@@ -59,8 +83,23 @@ fn main() {
                 width,
                 height,
                 pinned,
+                capture_output,
+                tail,
+                stdin_file,
+                stdin,
+                log_output,
+                log_max_size,
+                log_max_files,
+                log_strip_ansi,
+                watch,
+                watch_debounce,
             };
-            commands::send_action_to_session(command_cli_action, opts.session, config);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                config,
+                opts.timeout,
+            );
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::Plugin {
@@ -86,7 +125,10 @@ fn main() {
                 in_place,
                 name: None,
                 close_on_exit: false,
+                close_on_success: false,
+                keep_open: false,
                 start_suspended: false,
+                start_immediately: false,
                 configuration,
                 skip_plugin_cache,
                 x,
@@ -94,8 +136,23 @@ fn main() {
                 width,
                 height,
                 pinned,
+                capture_output: false,
+                tail: None,
+                stdin_file: None,
+                stdin: false,
+                log_output: None,
+                log_max_size: None,
+                log_max_files: None,
+                log_strip_ansi: false,
+                watch: vec![],
+                watch_debounce: None,
             };
-            commands::send_action_to_session(command_cli_action, opts.session, config);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                config,
+                opts.timeout,
+            );
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::Edit {
@@ -132,15 +189,43 @@ fn main() {
                 height,
                 pinned,
             };
-            commands::send_action_to_session(command_cli_action, opts.session, config);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                config,
+                opts.timeout,
+            );
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::ConvertConfig { old_config_file })) = opts.command {
             commands::convert_old_config_file(old_config_file);
             std::process::exit(0);
         }
-        if let Some(Command::Sessions(Sessions::ConvertLayout { old_layout_file })) = opts.command {
-            commands::convert_old_layout_file(old_layout_file);
+        if let Some(Command::Sessions(Sessions::ConvertLayout {
+            old_layout_file,
+            from,
+        })) = opts.command
+        {
+            commands::convert_old_layout_file(old_layout_file, from);
+            std::process::exit(0);
+        }
+        if let Some(Command::Sessions(Sessions::Events {
+            follow,
+            filter,
+            format,
+        })) = opts.command
+        {
+            commands::follow_session_events(opts.session, follow, filter, format);
+            std::process::exit(0);
+        }
+        if let Some(Command::Sessions(Sessions::ApiServer { listen })) = opts.command {
+            api_server::start_api_server(&listen, config);
+            std::process::exit(0);
+        }
+        if let Some(Command::Sessions(Sessions::StartServer { daemon, pidfile })) =
+            opts.command.clone()
+        {
+            daemon::start_server(&opts, daemon, pidfile, config);
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::ConvertTheme { old_theme_file })) = opts.command {
@@ -169,7 +254,12 @@ fn main() {
                 plugin_cwd: None,
                 plugin_title: None,
             };
-            commands::send_action_to_session(command_cli_action, opts.session, config);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                config,
+                opts.timeout,
+            );
             std::process::exit(0);
         }
     }
@@ -177,21 +267,32 @@ fn main() {
     if let Some(Command::Sessions(Sessions::ListSessions {
         no_formatting,
         short,
+        long,
         reverse,
+        ref tag,
     })) = opts.command
     {
-        commands::list_sessions(no_formatting, short, reverse);
+        commands::list_sessions(no_formatting, short, long, reverse, tag);
     } else if let Some(Command::Sessions(Sessions::ListAliases)) = opts.command {
         commands::list_aliases(opts);
-    } else if let Some(Command::Sessions(Sessions::KillAllSessions { yes })) = opts.command {
-        commands::kill_all_sessions(yes);
-    } else if let Some(Command::Sessions(Sessions::KillSession { ref target_session })) =
+    } else if let Some(Command::Sessions(Sessions::KillAllSessions { yes, older_than })) =
         opts.command
     {
-        commands::kill_session(target_session);
-    } else if let Some(Command::Sessions(Sessions::DeleteAllSessions { yes, force })) = opts.command
+        commands::kill_all_sessions(yes, older_than);
+    } else if let Some(Command::Sessions(Sessions::KillSession {
+        ref target_session,
+        signal,
+        grace_period,
+    })) = opts.command
     {
-        commands::delete_all_sessions(yes, force);
+        commands::kill_session(target_session, signal, grace_period);
+    } else if let Some(Command::Sessions(Sessions::DeleteAllSessions {
+        yes,
+        force,
+        older_than,
+    })) = opts.command
+    {
+        commands::delete_all_sessions(yes, force, older_than);
     } else if let Some(Command::Sessions(Sessions::DeleteSession {
         ref target_session,
         force,
@@ -212,17 +313,32 @@ fn main() {
             let new_layout_cli_action = CliAction::NewTab {
                 layout: Some(layout.clone()),
                 layout_dir: options.as_ref().and_then(|o| o.layout_dir.clone()),
+                template: None,
                 name: None,
                 cwd: options.as_ref().and_then(|o| o.default_cwd.clone()),
+                vars: vec![],
             };
-            commands::send_action_to_session(new_layout_cli_action, Some(session_name), config);
+            commands::send_action_to_session(
+                new_layout_cli_action,
+                Some(session_name),
+                config,
+                opts.timeout,
+            );
         } else {
             commands::start_client(opts);
         }
-    } else if let Some(layout_for_new_session) = &opts.new_session_with_layout {
+    } else if let Some(new_session_with_layout) = &opts.new_session_with_layout {
         let mut opts = opts.clone();
+        let mut new_session_with_layout = new_session_with_layout.iter();
+        opts.layout = new_session_with_layout.next().map(PathBuf::from);
+        if let Some(session_name) = new_session_with_layout.next() {
+            // existing sessions are rejected when starting the client
+            match validate_session(session_name) {
+                Ok(session_name) => opts.session = Some(session_name),
+                Err(e) => exit_with_error(ExitCode::InvalidInput, &e),
+            }
+        }
         opts.new_session_with_layout = None;
-        opts.layout = Some(layout_for_new_session.clone());
         commands::start_client(opts);
     } else {
         commands::start_client(opts);
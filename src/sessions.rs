@@ -1,19 +1,30 @@
+use crate::exit_codes::{
+    exit_with_code, exit_with_error, report_error, report_error_context, ExitCode,
+};
 use std::collections::HashMap;
 use std::os::unix::fs::FileTypeExt;
-use std::time::{Duration, SystemTime};
-use std::{fs, io, process};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+use std::{fs, io};
 use suggest::Suggest;
 use zellij_utils::{
     anyhow,
+    cli_style::Painter,
     consts::{
-        session_info_folder_for_session, session_layout_cache_file_name,
-        ZELLIJ_SESSION_INFO_CACHE_DIR, ZELLIJ_SOCK_DIR,
+        session_info_cache_file_name, session_info_folder_for_session,
+        session_layout_cache_file_name, SESSION_LAYOUT_FILE_NAME, SESSION_METADATA_FILE_NAME,
+        VERSION, ZELLIJ_SESSION_INFO_CACHE_DIR, ZELLIJ_SOCK_DIR,
     },
+    data::SessionInfo,
     envs,
-    humantime::format_duration,
+    humantime::{format_duration, format_rfc3339_seconds},
     input::layout::Layout,
+    input::options::Options,
     interprocess::local_socket::LocalSocketStream,
-    ipc::{ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg},
+    ipc::{
+        ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, PaneSignal,
+        ServerToClientMsg, SessionStats,
+    },
 };
 
 pub(crate) fn get_sessions() -> Result<Vec<(String, Duration)>, io::ErrorKind> {
@@ -40,6 +51,87 @@ pub(crate) fn get_sessions() -> Result<Vec<(String, Duration)>, io::ErrorKind> {
     }
 }
 
+/// Sessions whose server is gone without having exited cleanly (eg. it crashed or was killed):
+/// the metadata that a running session keeps, and that its server removes when it exits, was
+/// left behind but nothing answers on its socket anymore
+pub(crate) fn get_crashed_sessions() -> Vec<(String, Duration)> {
+    crashed_sessions_in(&ZELLIJ_SESSION_INFO_CACHE_DIR, assert_socket)
+}
+
+fn crashed_sessions_in(
+    session_info_dir: &Path,
+    is_running: impl Fn(&str) -> bool,
+) -> Vec<(String, Duration)> {
+    match fs::read_dir(session_info_dir) {
+        Ok(files_in_session_info_folder) => files_in_session_info_folder
+            .filter_map(|f| f.ok().and_then(|f| f.file_name().into_string().ok()))
+            .filter_map(|session_name| {
+                let metadata = fs::metadata(
+                    session_info_dir
+                        .join(&session_name)
+                        .join(SESSION_METADATA_FILE_NAME),
+                )
+                .ok()?;
+                if is_running(&session_name) {
+                    return None;
+                }
+                // not every filesystem records when a file was created, without it the session
+                // is still listed as crashed
+                let elapsed = metadata
+                    .created()
+                    .ok()
+                    .and_then(|created| created.elapsed().ok())
+                    .unwrap_or_default();
+                Some((session_name, Duration::from_secs(elapsed.as_secs())))
+            })
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Removes what a crashed session left behind (its socket and the metadata of a running session)
+/// so that it can be resurrected, or its name reused if there is nothing to resurrect
+pub(crate) fn clean_up_crashed_session(name: &str) {
+    clean_up_crashed_session_in(
+        name,
+        &ZELLIJ_SESSION_INFO_CACHE_DIR,
+        &ZELLIJ_SOCK_DIR,
+        assert_socket,
+    )
+}
+
+fn clean_up_crashed_session_in(
+    name: &str,
+    session_info_dir: &Path,
+    socket_dir: &Path,
+    is_running: impl Fn(&str) -> bool,
+) {
+    let session_info_folder = session_info_dir.join(name);
+    let metadata_file = session_info_folder.join(SESSION_METADATA_FILE_NAME);
+    if !metadata_file.exists() || is_running(name) {
+        return;
+    }
+    let _ = fs::remove_file(socket_dir.join(name));
+    let _ = fs::remove_file(metadata_file);
+    if session_info_folder.join(SESSION_LAYOUT_FILE_NAME).exists() {
+        report_error_context(&format!(
+            "Session {:?} crashed, it will be resurrected from its last saved state.",
+            name
+        ));
+    } else {
+        let _ = fs::remove_dir_all(session_info_folder);
+        report_error_context(&format!(
+            "Session {:?} crashed before its state could be saved, it was cleaned up.",
+            name
+        ));
+    }
+}
+
+/// Whether a session that is not running crashed rather than exited, see `get_crashed_sessions`
+fn has_crashed(name: &str) -> bool {
+    session_info_cache_file_name(name).exists()
+}
+
 pub(crate) fn get_resurrectable_sessions() -> Vec<(String, Duration, Layout)> {
     match fs::read_dir(&*ZELLIJ_SESSION_INFO_CACHE_DIR) {
         Ok(files_in_session_info_folder) => {
@@ -150,6 +242,67 @@ pub(crate) fn get_sessions_sorted_by_mtime() -> anyhow::Result<Vec<String>> {
     }
 }
 
+/// Running sessions of other zellij versions as (session name, version), their sockets are in a
+/// sibling of our socket folder and this binary cannot attach to them
+fn get_sessions_of_other_versions() -> Vec<(String, String)> {
+    let mut sessions = vec![];
+    let Some(versions_dir) = ZELLIJ_SOCK_DIR.parent() else {
+        return sessions;
+    };
+    let Ok(version_dirs) = fs::read_dir(versions_dir) else {
+        return sessions;
+    };
+    for version_dir in version_dirs.filter_map(|d| d.ok()) {
+        let version = version_dir.file_name().to_string_lossy().to_string();
+        if version == VERSION || !version_dir.path().is_dir() {
+            continue;
+        }
+        let Ok(files) = fs::read_dir(version_dir.path()) else {
+            continue;
+        };
+        for file in files.filter_map(|f| f.ok()) {
+            // we don't know the ipc protocol of other versions, so a socket that accepts
+            // connections is considered a running session
+            let is_socket = file.file_type().map_or(false, |f| f.is_socket());
+            if is_socket && LocalSocketStream::connect(file.path()).is_ok() {
+                let session_name = file.file_name().to_string_lossy().to_string();
+                sessions.push((session_name, version.clone()));
+            }
+        }
+    }
+    sessions.sort();
+    sessions
+}
+
+pub(crate) fn report_sessions_of_other_versions(session_name: Option<&str>) {
+    let sessions_of_other_versions = get_sessions_of_other_versions();
+    match session_name {
+        Some(session_name) => {
+            if let Some((_, version)) = sessions_of_other_versions
+                .iter()
+                .find(|(name, _version)| name == session_name)
+            {
+                report_error_context(&format!(
+                    "  help: {:?} is running under zellij {} (this is zellij {}), attach to it with the zellij {} binary",
+                    session_name, version, VERSION, version
+                ));
+            }
+        },
+        None => {
+            if !sessions_of_other_versions.is_empty() {
+                let sessions: Vec<String> = sessions_of_other_versions
+                    .iter()
+                    .map(|(name, version)| format!("{} ({})", name, version))
+                    .collect();
+                report_error_context(&format!(
+                    "  help: sessions of other zellij versions are running: {}",
+                    sessions.join(", ")
+                ));
+            }
+        },
+    }
+}
+
 fn assert_socket(name: &str) -> bool {
     let path = &*ZELLIJ_SOCK_DIR.join(name);
     match LocalSocketStream::connect(path) {
@@ -170,6 +323,57 @@ fn assert_socket(name: &str) -> bool {
     }
 }
 
+fn read_session_info(name: &str) -> Option<SessionInfo> {
+    fs::read_to_string(session_info_cache_file_name(name))
+        .ok()
+        .and_then(|raw_session_info| SessionInfo::from_string(&raw_session_info, "").ok())
+}
+
+/// Whether the metadata of a running session lists all of the given tags
+fn session_has_tags(name: &str, tags: &[String]) -> bool {
+    read_session_info(name)
+        .map(|session_info| tags.iter().all(|tag| session_info.has_tag(tag)))
+        .unwrap_or(false)
+}
+
+fn get_session_stats(name: &str) -> Option<SessionStats> {
+    let path = &*ZELLIJ_SOCK_DIR.join(name);
+    match LocalSocketStream::connect(path) {
+        Ok(stream) => {
+            let mut sender = IpcSenderWithContext::new(stream);
+            let _ = sender.send(ClientToServerMsg::QuerySessionStats);
+            let mut receiver: IpcReceiverWithContext<ServerToClientMsg> = sender.get_receiver();
+            match receiver.recv() {
+                Some((ServerToClientMsg::SessionStats(session_stats), _)) => Some(session_stats),
+                None | Some((_, _)) => None,
+            }
+        },
+        Err(_) => None,
+    }
+}
+
+/// How long a session has gone without attached clients: since it was last attached (or
+/// created) while it is running, since it last saved its state once it exited
+pub(crate) fn session_idle_time(name: &str, is_running: bool) -> Option<Duration> {
+    let idle_since = if is_running {
+        let session_stats = get_session_stats(name)?;
+        if session_stats.connected_clients > 0 {
+            return Some(Duration::ZERO);
+        }
+        match session_stats.last_attached {
+            Some(last_attached) => last_attached,
+            None => fs::metadata(ZELLIJ_SOCK_DIR.join(name))
+                .and_then(|metadata| metadata.created())
+                .ok()?,
+        }
+    } else {
+        fs::metadata(session_layout_cache_file_name(name))
+            .and_then(|metadata| metadata.modified())
+            .ok()?
+    };
+    idle_since.elapsed().ok()
+}
+
 pub(crate) fn print_sessions(
     mut sessions: Vec<(String, Duration, bool)>,
     no_formatting: bool,
@@ -186,39 +390,182 @@ pub(crate) fn print_sessions(
             b.1.cmp(&a.1)
         }
     });
-    sessions
+    if short {
+        for (session_name, _timestamp, _is_dead) in &sessions {
+            println!("{}", session_name);
+        }
+        return;
+    }
+    let painter = if no_formatting {
+        Painter::plain()
+    } else {
+        Painter::stdout()
+    };
+    let name_width = sessions
         .iter()
-        .for_each(|(session_name, timestamp, is_dead)| {
-            if short {
-                println!("{}", session_name);
-                return;
-            }
-            if no_formatting {
-                let suffix = if curr_session == *session_name {
-                    format!("(current)")
-                } else if *is_dead {
-                    format!("(EXITED - attach to resurrect)")
-                } else {
-                    String::new()
-                };
-                let timestamp = format!("[Created {} ago]", format_duration(*timestamp));
-                println!("{} {} {}", session_name, timestamp, suffix);
+        .map(|(session_name, _timestamp, _is_dead)| session_name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (session_name, timestamp, is_dead) in &sessions {
+        let session_name_column = format!("{:width$}", session_name, width = name_width);
+        let timestamp = format!(
+            "[Created {} ago]",
+            painter.magenta(&format_duration(*timestamp).to_string())
+        );
+        let suffix = if curr_session == *session_name {
+            format!("({})", painter.bold("current"))
+        } else if *is_dead && has_crashed(session_name) {
+            if session_layout_cache_file_name(session_name).exists() {
+                format!("({} - attach to resurrect)", painter.red("CRASHED"))
             } else {
-                let formatted_session_name = format!("\u{1b}[32;1m{}\u{1b}[m", session_name);
-                let suffix = if curr_session == *session_name {
-                    format!("(current)")
-                } else if *is_dead {
-                    format!("(\u{1b}[31;1mEXITED\u{1b}[m - attach to resurrect)")
-                } else {
-                    String::new()
-                };
-                let timestamp = format!(
-                    "[Created \u{1b}[35;1m{}\u{1b}[m ago]",
-                    format_duration(*timestamp)
-                );
-                println!("{} {} {}", formatted_session_name, timestamp, suffix);
+                format!("({} - delete-session to clean up)", painter.red("CRASHED"))
             }
+        } else if *is_dead {
+            format!("({} - attach to resurrect)", painter.red("EXITED"))
+        } else {
+            String::new()
+        };
+        let line = format!(
+            "{} {} {}",
+            painter.green(&session_name_column),
+            timestamp,
+            suffix
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+pub(crate) fn print_sessions_long(
+    mut sessions: Vec<(String, Duration, bool)>,
+    resurrectable_session_names: Vec<String>,
+    no_formatting: bool,
+    reverse: bool,
+) {
+    // (session_name, timestamp, is_dead)
+    let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
+    sessions.sort_by(|a, b| {
+        if reverse {
+            a.1.cmp(&b.1)
+        } else {
+            b.1.cmp(&a.1)
+        }
+    });
+    let format_elapsed = |duration: Duration| {
+        format!(
+            "{} ago",
+            format_duration(Duration::from_secs(duration.as_secs()))
+        )
+    };
+    let header = [
+        "NAME",
+        "CREATED",
+        "LAST ATTACHED",
+        "CLIENTS",
+        "TABS",
+        "PANES",
+        "RESURRECTABLE",
+        "NOTE",
+    ]
+    .map(String::from)
+    .to_vec();
+    let mut rows = vec![header];
+    for (session_name, timestamp, is_dead) in &sessions {
+        let name = if curr_session == *session_name {
+            format!("{} (current)", session_name)
+        } else {
+            session_name.clone()
+        };
+        let is_resurrectable = resurrectable_session_names.contains(session_name);
+        // notes are only known for running sessions, as their metadata is removed when they exit
+        let note = if *is_dead && has_crashed(session_name) {
+            Some("crashed".to_owned())
+        } else if *is_dead {
+            None
+        } else {
+            read_session_info(session_name).and_then(|session_info| session_info.note)
+        };
+        let (last_attached, clients, tabs, panes) = if *is_dead {
+            (
+                "-".to_owned(),
+                "-".to_owned(),
+                "-".to_owned(),
+                "-".to_owned(),
+            )
+        } else {
+            match get_session_stats(session_name) {
+                Some(session_stats) => (
+                    session_stats
+                        .last_attached
+                        .and_then(|t| t.elapsed().ok())
+                        .map(format_elapsed)
+                        .unwrap_or_else(|| "never".to_owned()),
+                    session_stats.connected_clients.to_string(),
+                    session_stats.tab_count.to_string(),
+                    session_stats.pane_count.to_string(),
+                ),
+                None => (
+                    "?".to_owned(),
+                    "?".to_owned(),
+                    "?".to_owned(),
+                    "?".to_owned(),
+                ),
+            }
+        };
+        rows.push(vec![
+            name,
+            format_elapsed(*timestamp),
+            last_attached,
+            clients,
+            tabs,
+            panes,
+            if is_resurrectable { "yes" } else { "no" }.to_owned(),
+            note.unwrap_or_else(|| "-".to_owned()),
+        ]);
+    }
+    let painter = if no_formatting {
+        Painter::plain()
+    } else {
+        Painter::stdout()
+    };
+    for line in format_sessions_table(&rows, painter) {
+        println!("{}", line);
+    }
+}
+
+/// Aligns the columns of the `list-sessions --long` table, the first row being its header
+fn format_sessions_table(rows: &[Vec<String>], painter: Painter) -> Vec<String> {
+    let column_count = rows.first().map(|header| header.len()).unwrap_or(0);
+    let column_widths: Vec<usize> = (0..column_count)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
         })
+        .collect();
+    let resurrectable_column = 6;
+    let mut lines = vec![];
+    for (row_index, row) in rows.iter().enumerate() {
+        let line = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let padded = format!("{:width$}", cell, width = column_widths[i]);
+                if row_index == 0 {
+                    painter.bold(&padded)
+                } else if i == 0 {
+                    painter.green(&padded)
+                } else if i == resurrectable_column && cell == "yes" {
+                    painter.yellow(&padded)
+                } else {
+                    padded
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        lines.push(line.trim_end().to_owned());
+    }
+    lines
 }
 
 pub(crate) fn print_sessions_with_index(sessions: Vec<String>) {
@@ -244,10 +591,7 @@ pub(crate) fn get_active_session() -> ActiveSession {
         Ok(sessions) if sessions.is_empty() => ActiveSession::None,
         Ok(mut sessions) if sessions.len() == 1 => ActiveSession::One(sessions.pop().unwrap().0),
         Ok(_) => ActiveSession::Many,
-        Err(e) => {
-            eprintln!("Error occurred: {:?}", e);
-            process::exit(1);
-        },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Error occurred: {:?}", e)),
     }
 }
 
@@ -257,13 +601,36 @@ pub(crate) fn kill_session(name: &str) {
         Ok(stream) => {
             let _ = IpcSenderWithContext::new(stream).send(ClientToServerMsg::KillSession);
         },
-        Err(e) => {
-            eprintln!("Error occurred: {:?}", e);
-            process::exit(1);
+        Err(e) => exit_with_error(ExitCode::IpcFailure, &format!("Error occurred: {:?}", e)),
+    };
+}
+
+pub(crate) fn signal_session_panes(name: &str, signal: PaneSignal) {
+    let path = &*ZELLIJ_SOCK_DIR.join(name);
+    match LocalSocketStream::connect(path) {
+        Ok(stream) => {
+            let _ = IpcSenderWithContext::new(stream).send(ClientToServerMsg::SignalPanes(signal));
         },
+        Err(e) => exit_with_error(ExitCode::IpcFailure, &format!("Error occurred: {:?}", e)),
     };
 }
 
+/// Wait up to `timeout` for the session to exit on its own, returning whether it did
+pub(crate) fn wait_for_session_exit(name: &str, timeout: Duration) -> bool {
+    let path = &*ZELLIJ_SOCK_DIR.join(name);
+    let started_waiting = Instant::now();
+    loop {
+        // the server removes its socket when it exits
+        if !path.exists() {
+            return true;
+        }
+        if started_waiting.elapsed() >= timeout {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
 pub(crate) fn delete_session(name: &str, force: bool) {
     if force {
         let path = &*ZELLIJ_SOCK_DIR.join(name);
@@ -275,8 +642,10 @@ pub(crate) fn delete_session(name: &str, force: bool) {
     }
     if let Err(e) = std::fs::remove_dir_all(session_info_folder_for_session(name)) {
         if e.kind() == std::io::ErrorKind::NotFound {
-            eprintln!("Session: {:?} not found.", name);
-            process::exit(2);
+            exit_with_error(
+                ExitCode::NoSession,
+                &format!("Session: {:?} not found.", name),
+            );
         } else {
             log::error!("Failed to remove session {:?}: {:?}", name, e);
         }
@@ -285,41 +654,88 @@ pub(crate) fn delete_session(name: &str, force: bool) {
     }
 }
 
-pub(crate) fn list_sessions(no_formatting: bool, short: bool, reverse: bool) {
-    let exit_code = match get_sessions() {
+pub(crate) fn list_sessions(
+    no_formatting: bool,
+    short: bool,
+    long: bool,
+    reverse: bool,
+    tags: &[String],
+) {
+    let exit_code = print_all_sessions(no_formatting, short, long, reverse, tags);
+    exit_with_code(exit_code);
+}
+
+/// Print all running and resurrectable sessions, returning the exit code the listing warrants
+/// without exiting (for when the listing is additional context for another command)
+pub(crate) fn print_all_sessions(
+    no_formatting: bool,
+    short: bool,
+    long: bool,
+    reverse: bool,
+    tags: &[String],
+) -> ExitCode {
+    match get_sessions() {
         Ok(running_sessions) => {
             let resurrectable_sessions = get_resurrectable_sessions();
             let mut all_sessions: HashMap<String, (Duration, bool)> = resurrectable_sessions
                 .iter()
                 .map(|(name, timestamp, _layout)| (name.clone(), (timestamp.clone(), true)))
                 .collect();
+            // including the ones that crashed before their state was saved, and so cannot be
+            // resurrected, for them to be cleaned up
+            for (session_name, duration) in get_crashed_sessions() {
+                all_sessions.entry(session_name).or_insert((duration, true));
+            }
             for (session_name, duration) in running_sessions {
                 all_sessions.insert(session_name.clone(), (duration, false));
             }
-            if all_sessions.is_empty() {
-                eprintln!("No active zellij sessions found.");
-                1
+            if !tags.is_empty() {
+                // tags are only known for running sessions, as their metadata is removed when
+                // they exit
+                all_sessions.retain(|name, (_timestamp, is_dead)| {
+                    !*is_dead && session_has_tags(name, tags)
+                });
+            }
+            if all_sessions.is_empty() && !tags.is_empty() {
+                report_error(
+                    ExitCode::NoSession,
+                    &format!(
+                        "No active zellij sessions found with the tags: {}.",
+                        tags.join(", ")
+                    ),
+                );
+                ExitCode::NoSession
+            } else if all_sessions.is_empty() {
+                report_error(ExitCode::NoSession, "No active zellij sessions found.");
+                report_sessions_of_other_versions(None);
+                ExitCode::NoSession
             } else {
-                print_sessions(
-                    all_sessions
+                let sessions = all_sessions
+                    .iter()
+                    .map(|(name, (timestamp, is_dead))| (name.clone(), timestamp.clone(), *is_dead))
+                    .collect();
+                if long {
+                    let resurrectable_session_names = resurrectable_sessions
                         .iter()
-                        .map(|(name, (timestamp, is_dead))| {
-                            (name.clone(), timestamp.clone(), *is_dead)
-                        })
-                        .collect(),
-                    no_formatting,
-                    short,
-                    reverse,
-                );
-                0
+                        .map(|(name, _timestamp, _layout)| name.clone())
+                        .collect();
+                    print_sessions_long(
+                        sessions,
+                        resurrectable_session_names,
+                        no_formatting,
+                        reverse,
+                    );
+                } else {
+                    print_sessions(sessions, no_formatting, short, reverse);
+                }
+                ExitCode::Success
             }
         },
         Err(e) => {
-            eprintln!("Error occurred: {:?}", e);
-            1
+            report_error(ExitCode::Failure, &format!("Error occurred: {:?}", e));
+            ExitCode::Failure
         },
-    };
-    process::exit(exit_code);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -381,7 +797,10 @@ pub(crate) fn assert_session(name: &str) {
             if result {
                 return;
             } else {
-                println!("No session named {:?} found.", name);
+                report_error(
+                    ExitCode::NoSession,
+                    &format!("No session named {:?} found.", name),
+                );
                 if let Some(sugg) = get_sessions()
                     .unwrap()
                     .iter()
@@ -389,25 +808,27 @@ pub(crate) fn assert_session(name: &str) {
                     .collect::<Vec<_>>()
                     .suggest(name)
                 {
-                    println!("  help: Did you mean `{}`?", sugg);
+                    report_error_context(&format!("  help: Did you mean `{}`?", sugg));
                 }
+                report_sessions_of_other_versions(Some(name));
+                exit_with_code(ExitCode::NoSession);
             }
         },
-        Err(e) => {
-            eprintln!("Error occurred: {:?}", e);
-        },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Error occurred: {:?}", e)),
     };
-    process::exit(1);
 }
 
 pub(crate) fn assert_dead_session(name: &str, force: bool) {
     match session_exists(name) {
         Ok(exists) => {
             if exists && !force {
-                println!(
-                    "A session by the name {:?} exists and is active, use --force to delete it.",
-                    name
-                )
+                exit_with_error(
+                    ExitCode::SessionExists,
+                    &format!(
+                        "A session by the name {:?} exists and is active, use --force to delete it.",
+                        name
+                    ),
+                );
             } else if exists && force {
                 println!("A session by the name {:?} exists and is active, but will be force killed and deleted.", name);
                 return;
@@ -415,40 +836,39 @@ pub(crate) fn assert_dead_session(name: &str, force: bool) {
                 return;
             }
         },
-        Err(e) => {
-            eprintln!("Error occurred: {:?}", e);
-        },
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Error occurred: {:?}", e)),
     };
-    process::exit(1);
 }
 
 pub(crate) fn assert_session_ne(name: &str) {
     if name.trim().is_empty() {
-        eprintln!("Session name cannot be empty. Please provide a specific session name.");
-        process::exit(1);
+        exit_with_error(
+            ExitCode::InvalidInput,
+            "Session name cannot be empty. Please provide a specific session name.",
+        );
     }
     if name == "." || name == ".." {
-        eprintln!("Invalid session name: \"{}\".", name);
-        process::exit(1);
+        exit_with_error(
+            ExitCode::InvalidInput,
+            &format!("Invalid session name: \"{}\".", name),
+        );
     }
     if name.contains('/') {
-        eprintln!("Session name cannot contain '/'.");
-        process::exit(1);
+        exit_with_error(ExitCode::InvalidInput, "Session name cannot contain '/'.");
     }
 
     match session_exists(name) {
         Ok(result) if !result => {
             let resurrectable_sessions = get_resurrectable_session_names();
             if resurrectable_sessions.iter().find(|s| s == &name).is_some() {
-                println!("Session with name {:?} already exists, but is dead. Use the attach command to resurrect it or, the delete-session command to kill it or specify a different name.", name);
+                exit_with_error(ExitCode::SessionExists, &format!("Session with name {:?} already exists, but is dead. Use the attach command to resurrect it or, the delete-session command to kill it or specify a different name.", name));
             } else {
                 return
             }
         }
-        Ok(_) => println!("Session with name {:?} already exists. Use attach command to connect to it or specify a different name.", name),
-        Err(e) => eprintln!("Error occurred: {:?}", e),
+        Ok(_) => exit_with_error(ExitCode::SessionExists, &format!("Session with name {:?} already exists. Use attach command to connect to it or specify a different name.", name)),
+        Err(e) => exit_with_error(ExitCode::Failure, &format!("Error occurred: {:?}", e)),
     };
-    process::exit(1);
 }
 
 /// Create a new random name generator
@@ -463,6 +883,80 @@ pub(crate) fn get_name_generator() -> impl Iterator<Item = String> {
     names::Generator::new(&ADJECTIVES, &NOUNS, names::Name::Plain)
 }
 
+/// Candidate names for a new session, as configured by the `session_name_command` and
+/// `session_name_template` options or random adjective-noun names if neither is set
+///
+/// Names that are not random (eg. derived from the directory) are followed by numbered variations
+/// of themselves, so that a unique one can be picked when sessions with this name exist. Slashes,
+/// which session names cannot have since they are file names, are replaced with dashes.
+pub(crate) fn get_session_name_candidates(
+    config_options: &Options,
+) -> Result<Box<dyn Iterator<Item = String>>, String> {
+    fn with_numbered_variations(name: String) -> impl Iterator<Item = String> {
+        std::iter::once(name.clone()).chain((2..).map(move |i| format!("{}-{}", name, i)))
+    }
+    if let Some(command) = &config_options.session_name_command {
+        let name = session_name_from_command(command)?;
+        return Ok(Box::new(with_numbered_variations(name)));
+    }
+    match &config_options.session_name_template {
+        Some(template) if template.contains("{adjective}") || template.contains("{noun}") => {
+            let template = template.clone();
+            Ok(Box::new(std::iter::repeat_with(move || {
+                session_name_from_template(&template)
+            })))
+        },
+        Some(template) => Ok(Box::new(with_numbered_variations(
+            session_name_from_template(template),
+        ))),
+        None => Ok(Box::new(get_name_generator())),
+    }
+}
+
+fn session_name_from_template(template: &str) -> String {
+    let adjective_and_noun = get_name_generator().next().unwrap_or_default();
+    let (adjective, noun) = adjective_and_noun
+        .split_once('-')
+        .unwrap_or((&adjective_and_noun, ""));
+    // YYYY-MM-DD (UTC)
+    let date: String = format_rfc3339_seconds(SystemTime::now())
+        .to_string()
+        .chars()
+        .take(10)
+        .collect();
+    let directory = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| cwd.file_name().map(|d| d.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    template
+        .replace("{adjective}", adjective)
+        .replace("{noun}", noun)
+        .replace("{date}", &date)
+        .replace("{directory}", &directory)
+        .replace('/', "-")
+}
+
+fn session_name_from_command(command: &str) -> Result<String, String> {
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .output()
+        .map_err(|e| format!("Failed to run session_name_command '{}': {}", command, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "session_name_command '{}' failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().replace('/', "-"))
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| format!("session_name_command '{}' printed no name", command))
+}
+
 const ADJECTIVES: &[&'static str] = &[
     "adamant",
     "adept",
@@ -598,3 +1092,250 @@ const NOUNS: &[&'static str] = &[
     "yak",
     "zebra",
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zellij_utils::cli::ColorChoice;
+    use zellij_utils::cli_style::set_color_choice;
+    use zellij_utils::tempfile::tempdir;
+
+    fn leave_session_info_behind(session_info_dir: &Path, name: &str, files: &[&str]) {
+        let session_info_folder = session_info_dir.join(name);
+        fs::create_dir_all(&session_info_folder).unwrap();
+        for file in files {
+            fs::write(session_info_folder.join(file), "").unwrap();
+        }
+    }
+
+    fn sessions_table_rows() -> Vec<Vec<String>> {
+        vec![
+            vec![
+                "NAME",
+                "CREATED",
+                "LAST ATTACHED",
+                "CLIENTS",
+                "TABS",
+                "PANES",
+                "RESURRECTABLE",
+                "NOTE",
+            ],
+            vec![
+                "work (current)",
+                "2m 3s ago",
+                "never",
+                "1",
+                "2",
+                "3",
+                "no",
+                "-",
+            ],
+            vec![
+                "old-session",
+                "1day ago",
+                "-",
+                "-",
+                "-",
+                "-",
+                "yes",
+                "crashed",
+            ],
+        ]
+        .into_iter()
+        .map(|row| row.into_iter().map(String::from).collect())
+        .collect()
+    }
+
+    fn session_name_candidates(config_options: Options) -> Vec<String> {
+        get_session_name_candidates(&config_options)
+            .unwrap()
+            .take(2)
+            .collect()
+    }
+
+    #[test]
+    fn session_names_from_the_template_are_numbered_and_have_no_slashes() {
+        let config_options = Options {
+            session_name_template: Some("feature/login".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            session_name_candidates(config_options),
+            vec!["feature-login", "feature-login-2"]
+        );
+    }
+
+    #[test]
+    fn session_names_from_the_command_are_numbered_and_have_no_slashes() {
+        let config_options = Options {
+            session_name_command: Some("echo ' feature/login '".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            session_name_candidates(config_options),
+            vec!["feature-login", "feature-login-2"]
+        );
+    }
+
+    #[test]
+    fn failing_session_name_command_is_an_error() {
+        let config_options = Options {
+            session_name_command: Some("echo oops >&2; exit 1".to_owned()),
+            ..Default::default()
+        };
+        let error = get_session_name_candidates(&config_options)
+            .err()
+            .expect("a failing command is an error");
+        assert!(error.ends_with("failed: oops"), "{}", error);
+    }
+
+    #[test]
+    fn session_names_from_the_template_can_be_random() {
+        let config_options = Options {
+            session_name_template: Some("{adjective}-{noun}".to_owned()),
+            ..Default::default()
+        };
+        let candidates = session_name_candidates(config_options);
+        assert!(candidates.iter().all(|name| name.split('-').count() == 2));
+        assert!(
+            !candidates[1].ends_with("-2"),
+            "random names are not numbered"
+        );
+    }
+
+    #[test]
+    fn sessions_that_left_their_metadata_behind_and_do_not_answer_have_crashed() {
+        let session_info_dir = tempdir().unwrap();
+        let session_info_dir = session_info_dir.path();
+        leave_session_info_behind(session_info_dir, "crashed", &[SESSION_METADATA_FILE_NAME]);
+        leave_session_info_behind(
+            session_info_dir,
+            "crashed-after-saving",
+            &[SESSION_METADATA_FILE_NAME, SESSION_LAYOUT_FILE_NAME],
+        );
+        leave_session_info_behind(session_info_dir, "exited", &[SESSION_LAYOUT_FILE_NAME]);
+        leave_session_info_behind(session_info_dir, "running", &[SESSION_METADATA_FILE_NAME]);
+        let mut crashed_sessions: Vec<String> =
+            crashed_sessions_in(session_info_dir, |name| name == "running")
+                .into_iter()
+                .map(|(name, _created)| name)
+                .collect();
+        crashed_sessions.sort();
+        assert_eq!(crashed_sessions, vec!["crashed", "crashed-after-saving"]);
+    }
+
+    #[test]
+    fn crashed_session_with_a_saved_state_is_cleaned_up_to_be_resurrected() {
+        let session_info_dir = tempdir().unwrap();
+        let socket_dir = tempdir().unwrap();
+        leave_session_info_behind(
+            session_info_dir.path(),
+            "crashed",
+            &[SESSION_METADATA_FILE_NAME, SESSION_LAYOUT_FILE_NAME],
+        );
+        fs::write(socket_dir.path().join("crashed"), "").unwrap();
+        clean_up_crashed_session_in(
+            "crashed",
+            session_info_dir.path(),
+            socket_dir.path(),
+            |_| false,
+        );
+        let session_info_folder = session_info_dir.path().join("crashed");
+        assert!(
+            !socket_dir.path().join("crashed").exists(),
+            "socket removed"
+        );
+        assert!(
+            !session_info_folder
+                .join(SESSION_METADATA_FILE_NAME)
+                .exists(),
+            "metadata of the running session removed"
+        );
+        assert!(
+            session_info_folder.join(SESSION_LAYOUT_FILE_NAME).exists(),
+            "saved state kept to resurrect the session from"
+        );
+        assert!(crashed_sessions_in(session_info_dir.path(), |_| false).is_empty());
+    }
+
+    #[test]
+    fn crashed_session_without_a_saved_state_is_removed() {
+        let session_info_dir = tempdir().unwrap();
+        let socket_dir = tempdir().unwrap();
+        leave_session_info_behind(
+            session_info_dir.path(),
+            "crashed",
+            &[SESSION_METADATA_FILE_NAME],
+        );
+        fs::write(socket_dir.path().join("crashed"), "").unwrap();
+        clean_up_crashed_session_in(
+            "crashed",
+            session_info_dir.path(),
+            socket_dir.path(),
+            |_| false,
+        );
+        assert!(
+            !socket_dir.path().join("crashed").exists(),
+            "socket removed"
+        );
+        assert!(
+            !session_info_dir.path().join("crashed").exists(),
+            "nothing left of the session"
+        );
+    }
+
+    #[test]
+    fn running_session_is_not_cleaned_up() {
+        let session_info_dir = tempdir().unwrap();
+        let socket_dir = tempdir().unwrap();
+        leave_session_info_behind(
+            session_info_dir.path(),
+            "running",
+            &[SESSION_METADATA_FILE_NAME],
+        );
+        fs::write(socket_dir.path().join("running"), "").unwrap();
+        clean_up_crashed_session_in(
+            "running",
+            session_info_dir.path(),
+            socket_dir.path(),
+            |_| true,
+        );
+        assert!(socket_dir.path().join("running").exists());
+        assert!(session_info_dir
+            .path()
+            .join("running")
+            .join(SESSION_METADATA_FILE_NAME)
+            .exists());
+    }
+
+    #[test]
+    fn sessions_table_columns_are_aligned() {
+        let lines = format_sessions_table(&sessions_table_rows(), Painter::plain());
+        assert_eq!(
+            lines,
+            vec![
+                "NAME            CREATED    LAST ATTACHED  CLIENTS  TABS  PANES  RESURRECTABLE  NOTE",
+                "work (current)  2m 3s ago  never          1        2     3      no             -",
+                "old-session     1day ago   -              -        -     -      yes            crashed",
+            ]
+        );
+    }
+
+    #[test]
+    fn sessions_table_is_colored_when_the_painter_allows_it() {
+        set_color_choice(ColorChoice::Always);
+        let lines = format_sessions_table(&sessions_table_rows(), Painter::stdout());
+        assert!(
+            lines[0].starts_with("\u{1b}[1mNAME            \u{1b}[m"),
+            "header is bold"
+        );
+        assert!(
+            lines[1].starts_with("\u{1b}[32;1mwork (current)\u{1b}[m"),
+            "session name is green"
+        );
+        assert!(
+            lines[2].contains(&format!("\u{1b}[33;1m{:13}\u{1b}[m", "yes")),
+            "resurrectable sessions are highlighted"
+        );
+    }
+}
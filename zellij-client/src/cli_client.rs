@@ -3,29 +3,82 @@
 use std::collections::BTreeMap;
 use std::io::BufRead;
 use std::process;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 use std::{fs, path::PathBuf};
 
 use crate::os_input_output::ClientOsApi;
 use zellij_utils::{
+    cli::EventFormat,
     errors::prelude::*,
     input::actions::Action,
     ipc::{ClientToServerMsg, ExitReason, ServerToClientMsg},
+    session_events::{event_name, format_as_text, SessionEvents},
     uuid::Uuid,
 };
 
+/// Aborts the cli client if the server does not respond in time, this is armed while waiting for
+/// the server to accept a connection or to acknowledge an action
+pub struct ResponseTimeout {
+    watchdog: mpsc::Sender<bool>,
+}
+
+impl ResponseTimeout {
+    pub fn new(timeout: Duration, on_timeout: impl FnOnce() + Send + 'static) -> Self {
+        let (watchdog, armed) = mpsc::channel::<bool>();
+        let _ = thread::Builder::new()
+            .name("cli_client_timeout".to_string())
+            .spawn(move || loop {
+                match armed.recv() {
+                    Ok(true) => {},
+                    Ok(false) => continue,
+                    Err(_) => return,
+                }
+                loop {
+                    match armed.recv_timeout(timeout) {
+                        Ok(false) => break,
+                        Ok(true) => continue,
+                        Err(RecvTimeoutError::Timeout) => {
+                            on_timeout();
+                            return;
+                        },
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+            });
+        ResponseTimeout { watchdog }
+    }
+    fn arm(&self) {
+        let _ = self.watchdog.send(true);
+    }
+    fn disarm(&self) {
+        let _ = self.watchdog.send(false);
+    }
+}
+
+fn session_ipc_pipe(session_name: &str) -> PathBuf {
+    let mut sock_dir = zellij_utils::consts::ZELLIJ_SOCK_DIR.clone();
+    fs::create_dir_all(&sock_dir).unwrap();
+    zellij_utils::shared::set_permissions(&sock_dir, 0o700).unwrap();
+    sock_dir.push(session_name);
+    sock_dir
+}
+
 pub fn start_cli_client(
     mut os_input: Box<dyn ClientOsApi>,
     session_name: &str,
     actions: Vec<Action>,
+    response_timeout: Option<ResponseTimeout>,
 ) {
-    let zellij_ipc_pipe: PathBuf = {
-        let mut sock_dir = zellij_utils::consts::ZELLIJ_SOCK_DIR.clone();
-        fs::create_dir_all(&sock_dir).unwrap();
-        zellij_utils::shared::set_permissions(&sock_dir, 0o700).unwrap();
-        sock_dir.push(session_name);
-        sock_dir
-    };
+    let zellij_ipc_pipe = session_ipc_pipe(session_name);
+    if let Some(response_timeout) = response_timeout.as_ref() {
+        response_timeout.arm();
+    }
     os_input.connect_to_server(&*zellij_ipc_pipe);
+    if let Some(response_timeout) = response_timeout.as_ref() {
+        response_timeout.disarm();
+    }
     let pane_id = os_input
         .env_variable("ZELLIJ_PANE_ID")
         .and_then(|e| e.trim().parse().ok());
@@ -63,14 +116,150 @@ pub fn start_cli_client(
                     pane_title,
                 );
             },
+            action if action.captures_output() => {
+                capture_output_client(&mut os_input, action, pane_id);
+            },
+            action if action.pipes_stdin() => {
+                stdin_pipe_client(&mut os_input, action, pane_id);
+            },
             action => {
-                individual_messages_client(&mut os_input, action, pane_id);
+                individual_messages_client(
+                    &mut os_input,
+                    action,
+                    pane_id,
+                    response_timeout.as_ref(),
+                );
             },
         }
     }
     os_input.send_to_server(ClientToServerMsg::ClientExited);
 }
 
+/// Sends actions to a session and returns the lines it logged in response (eg. the output of
+/// `list-panes`) instead of printing them, for callers that relay them elsewhere
+pub fn send_actions_and_collect_output(
+    mut os_input: Box<dyn ClientOsApi>,
+    session_name: &str,
+    actions: Vec<Action>,
+) -> Result<Vec<String>, String> {
+    os_input.connect_to_server(&*session_ipc_pipe(session_name));
+    let mut output = vec![];
+    let mut result = Ok(());
+    'actions: for action in actions {
+        if matches!(action, Action::CliPipe { .. })
+            || action.captures_output()
+            || action.pipes_stdin()
+        {
+            result =
+                Err("Pipes, captured and piped commands can only be run from the CLI".to_owned());
+            break;
+        }
+        os_input.send_to_server(ClientToServerMsg::Action(action, None, None));
+        loop {
+            match os_input.recv_from_server() {
+                Some((ServerToClientMsg::UnblockInputThread, _)) => break,
+                Some((ServerToClientMsg::Log(log_lines), _)) => {
+                    output.extend(log_lines);
+                    break;
+                },
+                Some((ServerToClientMsg::LogError(log_lines), _)) => {
+                    result = Err(log_lines.join("\n"));
+                    break 'actions;
+                },
+                Some((ServerToClientMsg::Exit(ExitReason::Error(e)), _)) => {
+                    result = Err(e);
+                    break 'actions;
+                },
+                Some((ServerToClientMsg::Exit(_), _)) => break,
+                None => {
+                    result = Err(format!("Session '{}' went away", session_name));
+                    break 'actions;
+                },
+                _ => {},
+            }
+        }
+    }
+    os_input.send_to_server(ClientToServerMsg::ClientExited);
+    result.map(|_| output)
+}
+
+/// Prints the events of a session (eg. `tab-created`) as they happen, or only its current state
+/// when not following it
+pub fn start_event_stream_client(
+    os_input: Box<dyn ClientOsApi>,
+    session_name: &str,
+    follow: bool,
+    filter: Vec<String>,
+    format: EventFormat,
+) {
+    let mut stdout = os_input.get_stdout_writer();
+    let mut print = |record: &zellij_utils::serde_json::Value| {
+        let line = match format {
+            EventFormat::Json => record.to_string(),
+            EventFormat::Text => format_as_text(record),
+        };
+        // a closed STDOUT (eg. piped to `head`) ends the stream
+        if writeln!(stdout, "{}", line)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            process::exit(0);
+        }
+    };
+    let result = receive_session_events(os_input, session_name, |session_events, events| {
+        if !follow {
+            return match session_events.state() {
+                Some(state) => {
+                    print(&state);
+                    false
+                },
+                None => true,
+            };
+        }
+        for record in events
+            .iter()
+            .filter(|r| filter.is_empty() || filter.iter().any(|f| f == event_name(r)))
+        {
+            print(record);
+        }
+        true
+    });
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(2);
+    }
+}
+
+/// Subscribes to the events of a session and hands them (as json records) to `on_events` along
+/// with the state of the session, until the session ends or `on_events` returns false
+pub fn receive_session_events(
+    mut os_input: Box<dyn ClientOsApi>,
+    session_name: &str,
+    mut on_events: impl FnMut(&SessionEvents, Vec<zellij_utils::serde_json::Value>) -> bool,
+) -> Result<(), String> {
+    os_input.connect_to_server(&*session_ipc_pipe(session_name));
+    os_input.send_to_server(ClientToServerMsg::SubscribeToEvents);
+    let mut session_events = SessionEvents::default();
+    let result = loop {
+        match os_input.recv_from_server() {
+            Some((ServerToClientMsg::Event(event), _)) => {
+                let events = session_events.update(event);
+                if !on_events(&session_events, events) {
+                    break Ok(());
+                }
+            },
+            Some((ServerToClientMsg::Exit(ExitReason::Error(e)), _)) => break Err(e),
+            Some((ServerToClientMsg::Exit(_), _)) | None => {
+                // the session ended
+                break Ok(());
+            },
+            _ => {},
+        }
+    };
+    os_input.send_to_server(ClientToServerMsg::ClientExited);
+    result
+}
+
 fn pipe_client(
     os_input: &mut Box<dyn ClientOsApi>,
     pipe_id: String,
@@ -203,8 +392,12 @@ fn individual_messages_client(
     os_input: &mut Box<dyn ClientOsApi>,
     action: Action,
     pane_id: Option<u32>,
+    response_timeout: Option<&ResponseTimeout>,
 ) {
     let msg = ClientToServerMsg::Action(action, pane_id, None);
+    if let Some(response_timeout) = response_timeout {
+        response_timeout.arm();
+    }
     os_input.send_to_server(msg);
     loop {
         match os_input.recv_from_server() {
@@ -231,4 +424,103 @@ fn individual_messages_client(
             _ => {},
         }
     }
+    if let Some(response_timeout) = response_timeout {
+        response_timeout.disarm();
+    }
+}
+
+fn stdin_pipe_client(os_input: &mut Box<dyn ClientOsApi>, action: Action, pane_id: Option<u32>) {
+    let msg = ClientToServerMsg::Action(action, pane_id, None);
+    os_input.send_to_server(msg);
+    let mut stdin = os_input.get_stdin_reader();
+    let mut terminal_id = None;
+    loop {
+        match os_input.recv_from_server() {
+            Some((ServerToClientMsg::StdinPipeReady(id), _)) => {
+                terminal_id = Some(id);
+            },
+            // the previous chunk was consumed by the command
+            Some((ServerToClientMsg::StdinPipeWritten, _)) => {},
+            Some((ServerToClientMsg::LogError(log_lines), _)) => {
+                log_lines.iter().for_each(|line| eprintln!("{line}"));
+                process::exit(2);
+            },
+            Some((ServerToClientMsg::Exit(exit_reason), _)) => match exit_reason {
+                ExitReason::Error(e) => {
+                    eprintln!("{}", e);
+                    process::exit(2);
+                },
+                _ => {
+                    process::exit(0);
+                },
+            },
+            None => {
+                // the server went away before we were done
+                process::exit(2);
+            },
+            _ => continue,
+        }
+        let Some(terminal_id) = terminal_id else {
+            continue;
+        };
+        // we only send the next chunk once the previous one was written, so that a command that
+        // reads slowly (eg. a pager) does not have all of our STDIN queued up in the session
+        let chunk = match stdin.fill_buf() {
+            Ok(buffer) => buffer.to_vec(),
+            Err(e) => {
+                eprintln!("Failed to read from STDIN: {}", e);
+                vec![]
+            },
+        };
+        stdin.consume(chunk.len());
+        if chunk.is_empty() {
+            // the command reads the end of its STDIN once the pipe is closed
+            os_input.send_to_server(ClientToServerMsg::CloseStdinPipe(terminal_id));
+            os_input.send_to_server(ClientToServerMsg::ClientExited);
+            process::exit(0);
+        }
+        os_input.send_to_server(ClientToServerMsg::WriteStdinToPane(terminal_id, chunk));
+    }
+}
+
+fn capture_output_client(
+    os_input: &mut Box<dyn ClientOsApi>,
+    action: Action,
+    pane_id: Option<u32>,
+) {
+    let msg = ClientToServerMsg::Action(action, pane_id, None);
+    os_input.send_to_server(msg);
+    let err_context = "Failed to write to stdout";
+    loop {
+        match os_input.recv_from_server() {
+            Some((ServerToClientMsg::CapturedPaneOutput(output), _)) => {
+                let mut stdout = os_input.get_stdout_writer();
+                stdout.write_all(&output).context(err_context).non_fatal();
+                stdout.flush().context(err_context).non_fatal();
+            },
+            Some((ServerToClientMsg::CapturedPaneExited(exit_status), _)) => {
+                os_input.send_to_server(ClientToServerMsg::ClientExited);
+                // a command without an exit status was killed by a signal
+                process::exit(exit_status.unwrap_or(1));
+            },
+            Some((ServerToClientMsg::LogError(log_lines), _)) => {
+                log_lines.iter().for_each(|line| eprintln!("{line}"));
+                process::exit(2);
+            },
+            Some((ServerToClientMsg::Exit(exit_reason), _)) => match exit_reason {
+                ExitReason::Error(e) => {
+                    eprintln!("{}", e);
+                    process::exit(2);
+                },
+                _ => {
+                    process::exit(0);
+                },
+            },
+            None => {
+                // the server went away before the command exited
+                process::exit(2);
+            },
+            _ => {},
+        }
+    }
 }
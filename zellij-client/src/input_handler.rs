@@ -12,7 +12,7 @@ use zellij_utils::{
         cast_termwiz_key,
         config::Config,
         mouse::{MouseEvent, MouseEventType},
-        options::Options,
+        options::{Options, PasteSanitization},
     },
     ipc::{ClientToServerMsg, ExitReason},
     position::Position,
@@ -51,6 +51,78 @@ fn termwiz_mouse_convert(original_event: &mut MouseEvent, event: &TermwizMouseEv
     original_event.ctrl = mods.contains(Modifiers::CTRL);
 }
 
+fn is_pasted_control_character(c: char) -> bool {
+    // tabs and newlines are part of any multi-line paste, everything else in C0, DEL and C1 is
+    // interpreted by the program in the pane rather than inserted
+    c != '\t'
+        && c != '\n'
+        && c != '\r'
+        && (c.is_ascii_control() || ('\u{80}'..='\u{9f}').contains(&c))
+}
+
+fn strip_pasted_text(pasted_text: &str) -> String {
+    let mut sanitized = String::with_capacity(pasted_text.len());
+    let mut chars = pasted_text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // drop the whole escape sequence and not only its ESC, so that eg. a nested bracketed
+            // paste terminator does not leave "[201~" behind
+            match chars.next() {
+                Some('[') => {
+                    // CSI: parameters and intermediates up to the final byte
+                    for c in chars.by_ref() {
+                        if ('\u{40}'..='\u{7e}').contains(&c) {
+                            break;
+                        }
+                    }
+                },
+                Some(']') | Some('P') | Some('X') | Some('^') | Some('_') => {
+                    // string sequences (OSC, DCS, SOS, PM, APC) up to BEL or ST
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                },
+                _ => {},
+            }
+        } else if !is_pasted_control_character(c) {
+            sanitized.push(c);
+        }
+    }
+    sanitized
+}
+
+fn escape_pasted_text(pasted_text: &str) -> String {
+    let mut escaped = String::with_capacity(pasted_text.len());
+    for c in pasted_text.chars() {
+        if !is_pasted_control_character(c) {
+            escaped.push(c);
+        } else if c == '\u{7f}' {
+            escaped.push_str("^?");
+        } else if c.is_ascii_control() {
+            escaped.push('^');
+            escaped.push((c as u8 + 0x40) as char);
+        } else {
+            // C1 controls are the 8-bit forms of ESC followed by a character
+            escaped.push_str("^[");
+            escaped.push((c as u8 - 0x40) as char);
+        }
+    }
+    escaped
+}
+
+/// Removes or escapes the control characters and escape sequences in text pasted into a pane
+/// according to the paste_sanitization option, so that the text cannot break out of the bracketed
+/// paste and have the program in the pane act on it
+pub(crate) fn sanitize_pasted_text(pasted_text: &str, sanitization: PasteSanitization) -> String {
+    match sanitization {
+        PasteSanitization::Off => pasted_text.to_owned(),
+        PasteSanitization::Strip => strip_pasted_text(pasted_text),
+        PasteSanitization::Escape => escape_pasted_text(pasted_text),
+    }
+}
+
 fn from_termwiz(old_event: &mut MouseEvent, event: TermwizMouseEvent) -> MouseEvent {
     // We use the state of old_event vs new_event to determine if this
     // event is a Press, Release, or Motion.  This is an unfortunate
@@ -173,6 +245,10 @@ impl InputHandler {
                             self.handle_mouse_event(&mouse_event);
                         },
                         InputEvent::Paste(pasted_text) => {
+                            let pasted_text = sanitize_pasted_text(
+                                &pasted_text,
+                                self.options.paste_sanitization.unwrap_or_default(),
+                            );
                             if self.mode == InputMode::Normal || self.mode == InputMode::Locked {
                                 self.dispatch_action(
                                     Action::Write(None, bracketed_paste_start.clone(), false),
@@ -223,6 +299,10 @@ impl InputHandler {
                         self.handle_stdin_ansi_instruction(ansi_instruction);
                     }
                 },
+                Ok((InputInstruction::TerminalFocusChanged(is_focused), _error_context)) => {
+                    self.os_input
+                        .send_to_server(ClientToServerMsg::TerminalFocusChanged(is_focused));
+                },
                 Ok((InputInstruction::StartedParsing, _error_context)) => {
                     self.send_client_instructions
                         .send(ClientInstruction::StartedParsingStdinQuery)
@@ -281,6 +361,18 @@ impl InputHandler {
                     .send(ClientInstruction::SetSynchronizedOutput(enabled))
                     .unwrap();
             },
+            AnsiStdinInstruction::SixelSupport(supports_sixel) => {
+                self.os_input
+                    .send_to_server(ClientToServerMsg::TerminalSixelSupport(supports_sixel));
+            },
+            AnsiStdinInstruction::KittyGraphicsSupport => {
+                self.os_input
+                    .send_to_server(ClientToServerMsg::TerminalKittyGraphicsSupport);
+            },
+            AnsiStdinInstruction::ColorSupport(color_support) => {
+                self.os_input
+                    .send_to_server(ClientToServerMsg::TerminalColorSupport(color_support));
+            },
         }
     }
     fn handle_mouse_event(&mut self, mouse_event: &MouseEvent) {
@@ -388,3 +480,7 @@ pub(crate) fn input_loop(
     )
     .handle_input();
 }
+
+#[cfg(test)]
+#[path = "./unit/input_handler_tests.rs"]
+mod input_handler_tests;
@@ -32,7 +32,10 @@ use zellij_utils::{
     envs,
     errors::{ClientContext, ContextType, ErrorInstruction},
     input::{config::Config, options::Options},
-    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    ipc::{
+        ClientAttributes, ClientToServerMsg, ColorSupport, ExitReason, ServerToClientMsg,
+        SessionStats,
+    },
     pane_size::Size,
     termwiz::input::InputEvent,
 };
@@ -56,6 +59,14 @@ pub(crate) enum ClientInstruction {
     CliPipeOutput((), ()),   // String -> pipe name, String -> output
     QueryTerminalSize,
     WriteConfigToDisk { config: String },
+    SessionStats(SessionStats),
+    CapturedPaneOutput(()),
+    CapturedPaneExited(()),
+    Event(()),
+    StdinPipeReady(()),
+    StdinPipeWritten,
+    ReadyToAdoptPane,
+    RenamedSession(String),
 }
 
 impl From<ServerToClientMsg> for ClientInstruction {
@@ -80,6 +91,22 @@ impl From<ServerToClientMsg> for ClientInstruction {
             ServerToClientMsg::WriteConfigToDisk { config } => {
                 ClientInstruction::WriteConfigToDisk { config }
             },
+            ServerToClientMsg::SessionStats(session_stats) => {
+                ClientInstruction::SessionStats(session_stats)
+            },
+            ServerToClientMsg::CapturedPaneOutput(_output) => {
+                ClientInstruction::CapturedPaneOutput(())
+            },
+            ServerToClientMsg::CapturedPaneExited(_exit_status) => {
+                ClientInstruction::CapturedPaneExited(())
+            },
+            ServerToClientMsg::Event(_event) => ClientInstruction::Event(()),
+            ServerToClientMsg::StdinPipeReady(_terminal_id) => {
+                ClientInstruction::StdinPipeReady(())
+            },
+            ServerToClientMsg::StdinPipeWritten => ClientInstruction::StdinPipeWritten,
+            ServerToClientMsg::ReadyToAdoptPane => ClientInstruction::ReadyToAdoptPane,
+            ServerToClientMsg::RenamedSession(name) => ClientInstruction::RenamedSession(name),
         }
     }
 }
@@ -102,6 +129,14 @@ impl From<&ClientInstruction> for ClientContext {
             ClientInstruction::CliPipeOutput(..) => ClientContext::CliPipeOutput,
             ClientInstruction::QueryTerminalSize => ClientContext::QueryTerminalSize,
             ClientInstruction::WriteConfigToDisk { .. } => ClientContext::WriteConfigToDisk,
+            ClientInstruction::SessionStats(..) => ClientContext::SessionStats,
+            ClientInstruction::CapturedPaneOutput(..) => ClientContext::CapturedPaneOutput,
+            ClientInstruction::CapturedPaneExited(..) => ClientContext::CapturedPaneExited,
+            ClientInstruction::Event(..) => ClientContext::Event,
+            ClientInstruction::StdinPipeReady(..) => ClientContext::StdinPipeReady,
+            ClientInstruction::StdinPipeWritten => ClientContext::StdinPipeWritten,
+            ClientInstruction::ReadyToAdoptPane => ClientContext::ReadyToAdoptPane,
+            ClientInstruction::RenamedSession(..) => ClientContext::RenamedSession,
         }
     }
 }
@@ -155,6 +190,7 @@ pub(crate) enum InputInstruction {
     KeyEvent(InputEvent, Vec<u8>),
     KeyWithModifierEvent(KeyWithModifier, Vec<u8>),
     AnsiStdinInstructions(Vec<AnsiStdinInstruction>),
+    TerminalFocusChanged(bool), // bool -> is_focused
     StartedParsing,
     DoneParsing,
     Exit,
@@ -182,10 +218,16 @@ pub fn start_client(
         .support_kitty_keyboard_protocol
         .map(|e| !e)
         .unwrap_or(false);
+    let paste_sanitization = config_options.paste_sanitization.unwrap_or_default();
     let mut reconnect_to_session = None;
     let clear_client_terminal_attributes = "\u{1b}[?1l\u{1b}=\u{1b}[r\u{1b}[?1000l\u{1b}[?1002l\u{1b}[?1003l\u{1b}[?1005l\u{1b}[?1006l\u{1b}[?12l";
     let take_snapshot = "\u{1b}[?1049h";
     let bracketed_paste = "\u{1b}[?2004h";
+    // the modes requested by applications are tracked per pane by the server, so the terminal
+    // itself only ever holds the modes we set here - these are undone when we leave so that they
+    // don't leak to the user's shell
+    let restore_client_terminal_modes = "\u{1b}[?2004l\u{1b}[?1l\u{1b}>";
+    let enable_focus_reporting = "\u{1b}[?1004h";
     let enter_kitty_keyboard_mode = "\u{1b}[>1u";
     os_input.unset_raw_mode(0).unwrap();
 
@@ -216,6 +258,15 @@ pub fn start_client(
         .unwrap_or_else(|| os_input.load_palette().into());
 
     let full_screen_ws = os_input.get_terminal_size_using_fd(0);
+    let read_only = matches!(
+        opts.command,
+        Some(zellij_utils::cli::Command::Sessions(
+            zellij_utils::cli::Sessions::Attach {
+                read_only: true,
+                ..
+            }
+        ))
+    );
     let client_attributes = ClientAttributes {
         size: full_screen_ws,
         style: Style {
@@ -223,6 +274,7 @@ pub fn start_client(
             rounded_corners: config.ui.pane_frames.rounded_corners,
             hide_session_name: config.ui.pane_frames.hide_session_name,
         },
+        read_only,
     };
 
     let create_ipc_pipe = || -> std::path::PathBuf {
@@ -283,6 +335,13 @@ pub fn start_client(
 
     os_input.connect_to_server(&*ipc_pipe);
     os_input.send_to_server(first_msg);
+    let guessed_color_support = ColorSupport::from_env(
+        os_input.env_variable("TERM").as_deref(),
+        os_input.env_variable("COLORTERM").as_deref(),
+    );
+    os_input.send_to_server(ClientToServerMsg::TerminalColorSupport(
+        guessed_color_support,
+    ));
 
     let mut command_is_executing = CommandIsExecuting::new();
 
@@ -291,6 +350,10 @@ pub fn start_client(
         .get_stdout_writer()
         .write(bracketed_paste.as_bytes())
         .unwrap();
+    let _ = os_input
+        .get_stdout_writer()
+        .write(enable_focus_reporting.as_bytes())
+        .unwrap();
 
     let (send_client_instructions, receive_client_instructions): ChannelWithContext<
         ClientInstruction,
@@ -314,7 +377,9 @@ pub fn start_client(
     });
 
     let on_force_close = config_options.on_force_close.unwrap_or_default();
-    let stdin_ansi_parser = Arc::new(Mutex::new(StdinAnsiParser::new()));
+    let stdin_ansi_parser = Arc::new(Mutex::new(
+        StdinAnsiParser::new().with_guessed_color_support(guessed_color_support),
+    ));
 
     let _stdin_thread = thread::Builder::new()
         .name("stdin_handler".to_string())
@@ -328,6 +393,7 @@ pub fn start_client(
                     send_input_instructions,
                     stdin_ansi_parser,
                     explicitly_disable_kitty_keyboard_protocol,
+                    paste_sanitization,
                 )
             }
         });
@@ -425,10 +491,15 @@ pub fn start_client(
         os_input.unset_raw_mode(0).unwrap();
         let goto_start_of_last_line = format!("\u{1b}[{};{}H", full_screen_ws.rows, 1);
         let restore_snapshot = "\u{1b}[?1049l";
+        let disable_focus_reporting = "\u{1b}[?1004l";
         os_input.disable_mouse().non_fatal();
         let error = format!(
-            "{}\n{}{}\n",
-            restore_snapshot, goto_start_of_last_line, backtrace
+            "{}{}{}\n{}{}\n",
+            disable_focus_reporting,
+            restore_client_terminal_modes,
+            restore_snapshot,
+            goto_start_of_last_line,
+            backtrace
         );
         let _ = os_input
             .get_stdout_writer()
@@ -544,6 +615,10 @@ pub fn start_client(
                     os_input.get_terminal_size_using_fd(0),
                 ));
             },
+            ClientInstruction::RenamedSession(name) => {
+                // so that we detach from and reattach to the session by its new name
+                envs::set_session_name(name);
+            },
             ClientInstruction::WriteConfigToDisk { config } => {
                 match Config::write_config_to_disk(config, &opts) {
                     Ok(written_config) => {
@@ -571,10 +646,17 @@ pub fn start_client(
         let reset_style = "\u{1b}[m";
         let show_cursor = "\u{1b}[?25h";
         let restore_snapshot = "\u{1b}[?1049l";
+        let disable_focus_reporting = "\u{1b}[?1004l";
         let goto_start_of_last_line = format!("\u{1b}[{};{}H", full_screen_ws.rows, 1);
         let goodbye_message = format!(
-            "{}\n{}{}{}{}\n",
-            goto_start_of_last_line, restore_snapshot, reset_style, show_cursor, exit_msg
+            "{}\n{}{}{}{}{}{}\n",
+            goto_start_of_last_line,
+            disable_focus_reporting,
+            restore_client_terminal_modes,
+            restore_snapshot,
+            reset_style,
+            show_cursor,
+            exit_msg
         );
 
         os_input.disable_mouse().non_fatal();
@@ -623,6 +705,7 @@ pub fn start_server_detached(
             rounded_corners: config.ui.pane_frames.rounded_corners,
             hide_session_name: config.ui.pane_frames.hide_session_name,
         },
+        read_only: false,
     };
 
     let create_ipc_pipe = || -> std::path::PathBuf {
@@ -0,0 +1,371 @@
+// This is a converter from tmuxinator and tmuxp project files to the KDL layout.
+//
+// Windows become tabs and panes become panes, with their commands, names and working directories.
+// Commands are run with `sh -c` in command panes, so panes without commands are plain shells.
+// Commands that tmuxinator (pre_window) or tmuxp (shell_command_before) run before those of every
+// pane are only prepended to panes that have commands of their own. Tmux layouts are
+// approximated, custom layout strings are tiled instead.
+use serde_yaml::Value;
+use zellij_utils::cli::TmuxProjectFormat;
+
+const DEFAULT_TAB_TEMPLATE: &str = "default_tab_template {
+    pane size=1 borderless=true {
+        plugin location=\"zellij:tab-bar\"
+    }
+    children
+    pane size=2 borderless=true {
+        plugin location=\"zellij:status-bar\"
+    }
+}";
+
+#[derive(Debug, Default)]
+struct Project {
+    name: Option<String>,
+    cwd: Option<String>,
+    windows: Vec<Window>,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    name: Option<String>,
+    cwd: Option<String>,
+    layout: Option<String>,
+    focus: bool,
+    panes: Vec<Pane>,
+}
+
+#[derive(Debug, Default)]
+struct Pane {
+    name: Option<String>,
+    cwd: Option<String>,
+    focus: bool,
+    commands: Vec<String>,
+}
+
+fn get<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value
+        .as_mapping()
+        .and_then(|mapping| mapping.get(&Value::String(key.to_owned())))
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn is_true(value: &Value) -> bool {
+    matches!(value, Value::Bool(true)) || value.as_str() == Some("true")
+}
+
+// a single command, a list of them or (tmuxp) a list of `cmd: <command>` entries
+fn to_commands(value: &Value) -> Vec<String> {
+    let commands: Vec<String> = match value {
+        Value::Sequence(commands) => commands.iter().flat_map(to_commands).collect(),
+        Value::Mapping(_) => get(value, "cmd")
+            .and_then(scalar_to_string)
+            .into_iter()
+            .collect(),
+        value => scalar_to_string(value).into_iter().collect(),
+    };
+    commands
+        .into_iter()
+        .filter(|command| !command.trim().is_empty())
+        .collect()
+}
+
+fn prepend_commands(panes: &mut Vec<Pane>, commands: &[String]) {
+    for pane in panes.iter_mut().filter(|p| !p.commands.is_empty()) {
+        pane.commands.splice(0..0, commands.iter().cloned());
+    }
+}
+
+fn parse_tmuxinator_project(project: &Value) -> Result<Project, String> {
+    let windows = get(project, "windows")
+        .or_else(|| get(project, "tabs")) // deprecated tmuxinator name
+        .and_then(Value::as_sequence)
+        .ok_or_else(|| "No windows found in the tmuxinator project".to_owned())?;
+    let pre_window = get(project, "pre_window")
+        .or_else(|| get(project, "pre_tab"))
+        .map(to_commands)
+        .unwrap_or_default();
+    let mut windows = windows
+        .iter()
+        .map(|window| parse_tmuxinator_window(window, &pre_window))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(startup_window) = get(project, "startup_window").and_then(scalar_to_string) {
+        if let Some(window) = windows
+            .iter_mut()
+            .find(|w| w.name.as_ref() == Some(&startup_window))
+        {
+            window.focus = true;
+        }
+    }
+    Ok(Project {
+        name: get(project, "name")
+            .or_else(|| get(project, "project_name"))
+            .and_then(scalar_to_string),
+        cwd: get(project, "root")
+            .or_else(|| get(project, "project_root"))
+            .and_then(scalar_to_string),
+        windows,
+    })
+}
+
+fn parse_tmuxinator_window(window: &Value, pre_window: &[String]) -> Result<Window, String> {
+    // every window is a single entry mapping of its name to either its command(s) or its
+    // definition
+    let (name, definition) = window
+        .as_mapping()
+        .and_then(|window| window.iter().next())
+        .ok_or_else(|| format!("Invalid tmuxinator window: {:?}", window))?;
+    let mut window = Window {
+        name: scalar_to_string(name),
+        // this is what tmuxinator applies when no layout is specified
+        layout: Some("tiled".to_owned()),
+        ..Default::default()
+    };
+    let mut pre_window = pre_window.to_vec();
+    match definition {
+        Value::Mapping(_) => {
+            window.cwd = get(definition, "root").and_then(scalar_to_string);
+            if let Some(layout) = get(definition, "layout").and_then(scalar_to_string) {
+                window.layout = Some(layout);
+            }
+            pre_window.extend(get(definition, "pre").map(to_commands).unwrap_or_default());
+            window.panes = get(definition, "panes")
+                .and_then(Value::as_sequence)
+                .map(|panes| panes.iter().map(parse_tmuxinator_pane).collect())
+                .unwrap_or_default();
+        },
+        definition => {
+            window.panes = vec![Pane {
+                commands: to_commands(definition),
+                ..Default::default()
+            }];
+        },
+    }
+    if window.panes.is_empty() {
+        window.panes.push(Pane::default());
+    }
+    prepend_commands(&mut window.panes, &pre_window);
+    Ok(window)
+}
+
+fn parse_tmuxinator_pane(pane: &Value) -> Pane {
+    match pane {
+        // named pane
+        Value::Mapping(named_pane) => match named_pane.iter().next() {
+            Some((name, commands)) => Pane {
+                name: scalar_to_string(name),
+                commands: to_commands(commands),
+                ..Default::default()
+            },
+            None => Pane::default(),
+        },
+        pane => Pane {
+            commands: to_commands(pane),
+            ..Default::default()
+        },
+    }
+}
+
+fn parse_tmuxp_project(project: &Value) -> Result<Project, String> {
+    let windows = get(project, "windows")
+        .and_then(Value::as_sequence)
+        .ok_or_else(|| "No windows found in the tmuxp project".to_owned())?;
+    let shell_command_before = get(project, "shell_command_before")
+        .map(to_commands)
+        .unwrap_or_default();
+    Ok(Project {
+        name: get(project, "session_name").and_then(scalar_to_string),
+        cwd: get(project, "start_directory").and_then(scalar_to_string),
+        windows: windows
+            .iter()
+            .map(|window| parse_tmuxp_window(window, &shell_command_before))
+            .collect(),
+    })
+}
+
+fn parse_tmuxp_window(window: &Value, shell_command_before: &[String]) -> Window {
+    let mut shell_command_before = shell_command_before.to_vec();
+    shell_command_before.extend(
+        get(window, "shell_command_before")
+            .map(to_commands)
+            .unwrap_or_default(),
+    );
+    let mut panes: Vec<Pane> = get(window, "panes")
+        .and_then(Value::as_sequence)
+        .map(|panes| panes.iter().map(parse_tmuxp_pane).collect())
+        .unwrap_or_default();
+    if panes.is_empty() {
+        panes.push(Pane::default());
+    }
+    prepend_commands(&mut panes, &shell_command_before);
+    Window {
+        name: get(window, "window_name").and_then(scalar_to_string),
+        cwd: get(window, "start_directory").and_then(scalar_to_string),
+        layout: get(window, "layout").and_then(scalar_to_string),
+        focus: get(window, "focus").map(is_true).unwrap_or(false),
+        panes,
+    }
+}
+
+fn parse_tmuxp_pane(pane: &Value) -> Pane {
+    match pane {
+        Value::Mapping(_) => Pane {
+            name: None,
+            cwd: get(pane, "start_directory").and_then(scalar_to_string),
+            focus: get(pane, "focus").map(is_true).unwrap_or(false),
+            commands: get(pane, "shell_command")
+                .map(to_commands)
+                .unwrap_or_default(),
+        },
+        Value::String(s) if s == "pane" || s == "blank" => Pane::default(),
+        pane => Pane {
+            commands: to_commands(pane),
+            ..Default::default()
+        },
+    }
+}
+
+fn node_with_children(node_line: &str, children: &[String]) -> String {
+    let mut node = format!("{} {{", node_line);
+    for child in children {
+        for line in child.lines() {
+            node.push_str("\n    ");
+            node.push_str(line);
+        }
+    }
+    node.push_str("\n}");
+    node
+}
+
+impl Pane {
+    fn to_kdl(&self) -> String {
+        let mut pane_line = String::from("pane");
+        if !self.commands.is_empty() {
+            pane_line.push_str(" command=\"sh\"");
+        }
+        // we use debug print here so that quotes and backslashes will be escaped
+        if let Some(name) = &self.name {
+            pane_line.push_str(&format!(" name={:?}", name));
+        }
+        if let Some(cwd) = &self.cwd {
+            pane_line.push_str(&format!(" cwd={:?}", cwd));
+        }
+        if self.focus {
+            pane_line.push_str(" focus=true");
+        }
+        if self.commands.is_empty() {
+            pane_line
+        } else {
+            node_with_children(
+                &pane_line,
+                &[format!("args \"-c\" {:?}", self.commands.join("\n"))],
+            )
+        }
+    }
+}
+
+impl Window {
+    fn to_kdl(&self) -> String {
+        let mut tab_line = String::from("tab");
+        if let Some(name) = &self.name {
+            tab_line.push_str(&format!(" name={:?}", name));
+        }
+        if let Some(cwd) = &self.cwd {
+            tab_line.push_str(&format!(" cwd={:?}", cwd));
+        }
+        if self.focus {
+            tab_line.push_str(" focus=true");
+        }
+        let mut panes: Vec<String> = self.panes.iter().map(|p| p.to_kdl()).collect();
+        let mut children = vec![];
+        match self.layout.as_deref() {
+            None | Some("even-vertical") => {},
+            Some("even-horizontal") => tab_line.push_str(" split_direction=\"vertical\""),
+            Some("main-vertical") if panes.len() > 2 => {
+                tab_line.push_str(" split_direction=\"vertical\"");
+                let rest = panes.split_off(1);
+                panes.push(node_with_children("pane", &rest));
+            },
+            Some("main-vertical") => tab_line.push_str(" split_direction=\"vertical\""),
+            Some("main-horizontal") if panes.len() > 2 => {
+                let rest = panes.split_off(1);
+                panes.push(node_with_children(
+                    "pane split_direction=\"vertical\"",
+                    &rest,
+                ));
+            },
+            Some("main-horizontal") => {},
+            Some(layout) => {
+                if layout != "tiled" {
+                    children.push(format!(
+                        "// the tmux layout {:?} could not be converted, panes are tiled instead",
+                        layout
+                    ));
+                }
+                // a grid with as many columns as needed to keep it square
+                let columns = (1..).find(|c| c * c >= panes.len()).unwrap_or(1);
+                if panes.len() == 1 {
+                    // nothing to tile
+                } else if panes.len() <= columns {
+                    tab_line.push_str(" split_direction=\"vertical\"");
+                } else {
+                    panes = panes
+                        .chunks(columns)
+                        .map(|row| {
+                            if row.len() == 1 {
+                                row[0].clone()
+                            } else {
+                                node_with_children("pane split_direction=\"vertical\"", row)
+                            }
+                        })
+                        .collect();
+                }
+            },
+        }
+        children.append(&mut panes);
+        node_with_children(&tab_line, &children)
+    }
+}
+
+impl Project {
+    fn to_kdl(&self) -> String {
+        let mut children = vec![];
+        if let Some(cwd) = &self.cwd {
+            children.push(format!("cwd {:?}", cwd));
+        }
+        children.push(DEFAULT_TAB_TEMPLATE.to_owned());
+        children.extend(self.windows.iter().map(|w| w.to_kdl()));
+        let mut kdl_layout = node_with_children("layout", &children);
+        if let Some(name) = &self.name {
+            kdl_layout.push_str(&format!("\nsession_name {:?}", name));
+            kdl_layout.push_str("\nattach_to_session true");
+        }
+        kdl_layout
+    }
+}
+
+pub fn tmux_project_to_layout_kdl(
+    raw_project: &str,
+    format: TmuxProjectFormat,
+) -> Result<String, String> {
+    // returns the raw kdl layout
+    let project: Value =
+        serde_yaml::from_str(raw_project).map_err(|e| format!("Failed to parse yaml: {:?}", e))?;
+    let project = match format {
+        TmuxProjectFormat::Tmuxinator => parse_tmuxinator_project(&project)?,
+        TmuxProjectFormat::Tmuxp => parse_tmuxp_project(&project)?,
+    };
+    Ok(project.to_kdl())
+}
+
+// The unit test location.
+#[path = "./unit/convert_tmux_project_tests.rs"]
+#[cfg(test)]
+mod convert_tmux_project_test;
@@ -0,0 +1,44 @@
+use crate::old_config_converter::tmux_project_to_layout_kdl;
+use insta::assert_snapshot;
+use std::path::PathBuf;
+use std::{fs::File, io::prelude::*};
+use zellij_utils::cli::TmuxProjectFormat;
+
+#[test]
+fn properly_convert_tmuxinator_project() -> Result<(), String> {
+    let fixture = PathBuf::from(format!(
+        "{}/src/old_config_converter/unit/fixtures/tmuxinator_project.yml",
+        env!("CARGO_MANIFEST_DIR")
+    ));
+    let mut handle = File::open(&fixture).map_err(|e| format!("{}", e))?;
+    let mut raw_project_file = String::new();
+    handle
+        .read_to_string(&mut raw_project_file)
+        .map_err(|e| format!("{}", e))?;
+    let kdl_layout = tmux_project_to_layout_kdl(&raw_project_file, TmuxProjectFormat::Tmuxinator)?;
+    assert_snapshot!(format!("{}", kdl_layout));
+    Ok(())
+}
+
+#[test]
+fn properly_convert_tmuxp_project() -> Result<(), String> {
+    let fixture = PathBuf::from(format!(
+        "{}/src/old_config_converter/unit/fixtures/tmuxp_project.yaml",
+        env!("CARGO_MANIFEST_DIR")
+    ));
+    let mut handle = File::open(&fixture).map_err(|e| format!("{}", e))?;
+    let mut raw_project_file = String::new();
+    handle
+        .read_to_string(&mut raw_project_file)
+        .map_err(|e| format!("{}", e))?;
+    let kdl_layout = tmux_project_to_layout_kdl(&raw_project_file, TmuxProjectFormat::Tmuxp)?;
+    assert_snapshot!(format!("{}", kdl_layout));
+    Ok(())
+}
+
+#[test]
+fn tmux_project_without_windows_is_an_error() {
+    let raw_project_file = "name: sample\nroot: ~/projects/sample\n";
+    assert!(tmux_project_to_layout_kdl(raw_project_file, TmuxProjectFormat::Tmuxinator).is_err());
+    assert!(tmux_project_to_layout_kdl(raw_project_file, TmuxProjectFormat::Tmuxp).is_err());
+}
@@ -2,8 +2,11 @@ use std::time::{Duration, Instant};
 
 const STARTUP_PARSE_DEADLINE_MS: u64 = 500;
 use zellij_utils::{
-    consts::ZELLIJ_STDIN_CACHE_FILE, ipc::PixelDimensions, lazy_static::lazy_static,
-    pane_size::SizeInPixels, regex::Regex,
+    consts::ZELLIJ_STDIN_CACHE_FILE,
+    ipc::{ColorSupport, PixelDimensions},
+    lazy_static::lazy_static,
+    pane_size::SizeInPixels,
+    regex::Regex,
 };
 
 use serde::{Deserialize, Serialize};
@@ -44,6 +47,8 @@ pub struct StdinAnsiParser {
     pending_color_sequences: Vec<(usize, String)>,
     pending_events: Vec<AnsiStdinInstruction>,
     parse_deadline: Option<Instant>,
+    // what we guessed from the environment, upgraded by what the terminal reports
+    terminal_color_support: Option<ColorSupport>,
 }
 
 impl StdinAnsiParser {
@@ -53,8 +58,16 @@ impl StdinAnsiParser {
             pending_color_sequences: vec![],
             pending_events: vec![],
             parse_deadline: None,
+            terminal_color_support: None,
         }
     }
+    /// The color support guessed from the environment (`TERM` and `COLORTERM`), which the
+    /// terminal's reply to our query can only upgrade: it may not know about colors the user told
+    /// us it has (eg. with `COLORTERM=truecolor`)
+    pub fn with_guessed_color_support(mut self, color_support: ColorSupport) -> Self {
+        self.terminal_color_support = Some(color_support);
+        self
+    }
     pub fn terminal_emulator_query_string(&mut self) -> String {
         // note that this assumes the String will be sent to the terminal emulator and so starts a
         // deadline timeout (self.parse_deadline)
@@ -64,8 +77,14 @@ impl StdinAnsiParser {
         // <ESC>]11;?<ESC>\ => get background color
         // <ESC>]10;?<ESC>\ => get foreground color
         // <ESC>[?2026$p => get synchronised output mode
+        // <ESC>_Gi=31,...;AAAA<ESC>\ => query kitty graphics support, terminals that do not support
+        // it do not respond
+        // <ESC>P+q524742<ESC>\, <ESC>P+q5463<ESC>\ and <ESC>P+q636f6c6f7273<ESC>\ => get the
+        // "RGB", "Tc" and "colors" terminal capabilities (hex encoded), which tell us the colors
+        // the terminal can display
+        // <ESC>[c => get the device attributes, which tell us whether sixel images are supported
         let mut query_string = String::from(
-            "\u{1b}[14t\u{1b}[16t\u{1b}]11;?\u{1b}\u{5c}\u{1b}]10;?\u{1b}\u{5c}\u{1b}[?2026$p",
+            "\u{1b}[14t\u{1b}[16t\u{1b}]11;?\u{1b}\u{5c}\u{1b}]10;?\u{1b}\u{5c}\u{1b}[?2026$p\u{1b}_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\u{1b}\u{5c}\u{1b}P+q524742\u{1b}\u{5c}\u{1b}P+q5463\u{1b}\u{5c}\u{1b}P+q636f6c6f7273\u{1b}\u{5c}\u{1b}[c",
         );
 
         // query colors
@@ -147,7 +166,19 @@ impl StdinAnsiParser {
             }
         } else if byte == b'\\' {
             self.raw_buffer.push(byte);
-            if let Ok(ansi_sequence) = AnsiStdinInstruction::bg_or_fg_from_bytes(&self.raw_buffer) {
+            if let Some(ansi_sequence) =
+                AnsiStdinInstruction::kitty_graphics_support_from_bytes(&self.raw_buffer)
+            {
+                self.pending_events.push(ansi_sequence);
+                self.raw_buffer.clear();
+            } else if let Some((capability, value)) =
+                terminal_capability_from_bytes(&self.raw_buffer)
+            {
+                self.raw_buffer.clear();
+                self.update_color_support(&capability, value.as_deref());
+            } else if let Ok(ansi_sequence) =
+                AnsiStdinInstruction::bg_or_fg_from_bytes(&self.raw_buffer)
+            {
                 self.pending_events.push(ansi_sequence);
                 self.raw_buffer.clear();
             } else if let Ok((color_register, color_sequence)) =
@@ -167,10 +198,42 @@ impl StdinAnsiParser {
                 self.pending_events.push(ansi_sequence);
                 self.raw_buffer.clear();
             }
+        } else if byte == b'c' {
+            // this can also be part of eg. a color response, so we only clear the buffer once it
+            // parsed as device attributes
+            self.raw_buffer.push(byte);
+            if let Some(ansi_sequence) =
+                AnsiStdinInstruction::sixel_support_from_bytes(&self.raw_buffer)
+            {
+                self.pending_events.push(ansi_sequence);
+                self.raw_buffer.clear();
+            }
         } else {
             self.raw_buffer.push(byte);
         }
     }
+    fn update_color_support(&mut self, capability: &str, value: Option<&str>) {
+        let reported_color_support = match (capability, value) {
+            ("RGB" | "Tc", _) => ColorSupport::TrueColor,
+            ("colors", Some(colors)) => match colors.parse::<usize>() {
+                Ok(colors) => ColorSupport::from_color_count(colors),
+                Err(_) => return,
+            },
+            _ => return,
+        };
+        // terminals reporting "RGB" or "Tc" often also report 256 "colors" (their palette), which
+        // does not take their true color support away
+        let supports_more_colors = self
+            .terminal_color_support
+            .map(|color_support| reported_color_support.color_count() > color_support.color_count())
+            .unwrap_or(true);
+        if !supports_more_colors {
+            return;
+        }
+        self.terminal_color_support = Some(reported_color_support);
+        self.pending_events
+            .push(AnsiStdinInstruction::ColorSupport(reported_color_support));
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -180,6 +243,9 @@ pub enum AnsiStdinInstruction {
     ForegroundColor(String),
     ColorRegisters(Vec<(usize, String)>),
     SynchronizedOutput(Option<SyncOutput>),
+    SixelSupport(bool),
+    KittyGraphicsSupport,
+    ColorSupport(ColorSupport),
 }
 
 impl AnsiStdinInstruction {
@@ -281,6 +347,62 @@ impl AnsiStdinInstruction {
             None
         }
     }
+
+    pub fn sixel_support_from_bytes(bytes: &[u8]) -> Option<Self> {
+        // eg. <ESC>[?62;4;22c - the device attributes, 4 means sixel graphics are supported
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"\u{1b}\[\?([\d;]+)c$").unwrap();
+        }
+        let key_string = String::from_utf8_lossy(bytes);
+        let captures = RE.captures_iter(&key_string).next()?;
+        let supports_sixel = captures[1]
+            .split(';')
+            .skip(1) // the first one is the device class
+            .any(|attribute| attribute == "4");
+        Some(AnsiStdinInstruction::SixelSupport(supports_sixel))
+    }
+
+    pub fn kitty_graphics_support_from_bytes(bytes: &[u8]) -> Option<Self> {
+        // eg. <ESC>_Gi=31;OK<ESC>\ - the response to our query, only sent by terminals that
+        // support the kitty graphics protocol
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"\u{1b}_Gi=31;OK\u{1b}\\$").unwrap();
+        }
+        let key_string = String::from_utf8_lossy(bytes);
+        if RE.is_match(&key_string) {
+            Some(AnsiStdinInstruction::KittyGraphicsSupport)
+        } else {
+            None
+        }
+    }
+}
+
+fn terminal_capability_from_bytes(bytes: &[u8]) -> Option<(String, Option<String>)> {
+    // eg. <ESC>P1+r636f6c6f7273=323536<ESC>\ - the terminal has the capability "colors" with the
+    // value "256", both hex encoded (terminals without the capability respond with <ESC>P0+r...)
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"\u{1b}P1\+r([0-9A-Fa-f]+)(?:=([0-9A-Fa-f]*))?\u{1b}\\$").unwrap();
+    }
+    let key_string = String::from_utf8_lossy(bytes);
+    let captures = RE.captures_iter(&key_string).next()?;
+    let capability = decode_hex(&captures[1])?;
+    let value = match captures.get(2) {
+        Some(value) => Some(decode_hex(value.as_str())?),
+        None => None,
+    };
+    Some((capability, value))
+}
+
+fn decode_hex(hex: &str) -> Option<String> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
 }
 
 fn color_sequence_from_bytes(bytes: &[u8]) -> Result<(usize, String), &'static str> {
@@ -313,3 +435,7 @@ fn color_sequence_from_bytes(bytes: &[u8]) -> Result<(usize, String), &'static s
         Err("invalid_instruction")
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/stdin_ansi_parser_tests.rs"]
+mod stdin_ansi_parser_tests;
@@ -4,6 +4,7 @@ use crate::stdin_ansi_parser::StdinAnsiParser;
 use crate::InputInstruction;
 use std::sync::{Arc, Mutex};
 use zellij_utils::channels::SenderWithContext;
+use zellij_utils::input::options::PasteSanitization;
 use zellij_utils::termwiz::input::{InputEvent, InputParser, MouseButtons};
 
 fn send_done_parsing_after_query_timeout(
@@ -20,15 +21,112 @@ fn send_done_parsing_after_query_timeout(
     });
 }
 
+const FOCUS_IN: &[u8] = b"\x1b[I";
+const FOCUS_OUT: &[u8] = b"\x1b[O";
+
+/// Removes focus in/out reports from the buffer, returning them in the order they were received
+/// (true for focus in) along with the rest of the input. The terminal emulator might send them in
+/// the same read as other input (eg. when keys are pressed right after switching windows).
+fn extract_focus_events(buf: &[u8]) -> (Vec<bool>, Vec<u8>) {
+    let mut focus_events = vec![];
+    let mut rest = Vec::with_capacity(buf.len());
+    let mut i = 0;
+    while i < buf.len() {
+        if buf[i..].starts_with(FOCUS_IN) {
+            focus_events.push(true);
+            i += FOCUS_IN.len();
+        } else if buf[i..].starts_with(FOCUS_OUT) {
+            focus_events.push(false);
+            i += FOCUS_OUT.len();
+        } else {
+            rest.push(buf[i]);
+            i += 1;
+        }
+    }
+    (focus_events, rest)
+}
+
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+const BRACKETED_PASTE_END: &[u8] = b"\x1b[201~";
+
+enum StdinChunk {
+    Input(Vec<u8>),
+    Paste(Vec<u8>), // the pasted bytes, without the bracketed paste start and end
+}
+
+/// Finds the bracketed pastes in the raw bytes read from STDIN. Text copied from a web page can
+/// hide a bracketed paste terminator followed by a command, which would end the paste early and
+/// run the command if we went by the first terminator. Instead, the paste ends at the last
+/// terminator of the read that contains one, so that the hidden ones stay in the pasted text where
+/// they are sanitized.
+#[derive(Default)]
+struct BracketedPaste {
+    pasted_bytes: Option<Vec<u8>>, // Some while in a paste that did not end yet
+}
+
+impl BracketedPaste {
+    fn frame(&mut self, buf: &[u8]) -> Vec<StdinChunk> {
+        let mut stdin_chunks = vec![];
+        let mut rest = buf;
+        loop {
+            match self.pasted_bytes.as_mut() {
+                None => match find_bytes(rest, BRACKETED_PASTE_START) {
+                    Some(paste_start) => {
+                        if paste_start > 0 {
+                            stdin_chunks.push(StdinChunk::Input(rest[..paste_start].to_vec()));
+                        }
+                        self.pasted_bytes = Some(vec![]);
+                        rest = &rest[paste_start + BRACKETED_PASTE_START.len()..];
+                    },
+                    None => {
+                        if !rest.is_empty() {
+                            stdin_chunks.push(StdinChunk::Input(rest.to_vec()));
+                        }
+                        break;
+                    },
+                },
+                Some(pasted_bytes) => match rfind_bytes(rest, BRACKETED_PASTE_END) {
+                    Some(paste_end) => {
+                        pasted_bytes.extend_from_slice(&rest[..paste_end]);
+                        if let Some(pasted_bytes) = self.pasted_bytes.take() {
+                            stdin_chunks.push(StdinChunk::Paste(pasted_bytes));
+                        }
+                        rest = &rest[paste_end + BRACKETED_PASTE_END.len()..];
+                    },
+                    None => {
+                        pasted_bytes.extend_from_slice(rest);
+                        break;
+                    },
+                },
+            }
+        }
+        stdin_chunks
+    }
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn rfind_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
 pub(crate) fn stdin_loop(
     mut os_input: Box<dyn ClientOsApi>,
     send_input_instructions: SenderWithContext<InputInstruction>,
     stdin_ansi_parser: Arc<Mutex<StdinAnsiParser>>,
     explicitly_disable_kitty_keyboard_protocol: bool,
+    paste_sanitization: PasteSanitization,
 ) {
     let mut holding_mouse = false;
     let mut input_parser = InputParser::new();
     let mut current_buffer = vec![];
+    let mut bracketed_paste = BracketedPaste::default();
     {
         // on startup we send a query to the terminal emulator for stuff like the pixel size and colors
         // we get a response through STDIN, so it makes sense to do this here
@@ -86,61 +184,93 @@ pub(crate) fn stdin_loop(
                         .unwrap()
                         .write_cache(ansi_stdin_events.drain(..).collect());
                 }
-                current_buffer.append(&mut buf.to_vec());
-
-                if !explicitly_disable_kitty_keyboard_protocol {
-                    // first we try to parse with the KittyKeyboardParser
-                    // if we fail, we try to parse normally
-                    match KittyKeyboardParser::new().parse(&buf) {
-                        Some(key_with_modifier) => {
+                // the terminal emulator window gained or lost focus (we enable these reports
+                // with CSI ?1004h on startup)
+                let (focus_events, buf) = extract_focus_events(&buf);
+                for is_focused in focus_events {
+                    send_input_instructions
+                        .send(InputInstruction::TerminalFocusChanged(is_focused))
+                        .unwrap();
+                }
+                if buf.is_empty() {
+                    continue;
+                }
+                let stdin_chunks = match paste_sanitization {
+                    PasteSanitization::Off => vec![StdinChunk::Input(buf)],
+                    _ => bracketed_paste.frame(&buf),
+                };
+                for stdin_chunk in stdin_chunks {
+                    let buf = match stdin_chunk {
+                        StdinChunk::Input(buf) => buf,
+                        StdinChunk::Paste(pasted_bytes) => {
+                            let pasted_text = String::from_utf8_lossy(&pasted_bytes).into_owned();
                             send_input_instructions
-                                .send(InputInstruction::KeyWithModifierEvent(
-                                    key_with_modifier,
-                                    current_buffer.drain(..).collect(),
+                                .send(InputInstruction::KeyEvent(
+                                    InputEvent::Paste(pasted_text),
+                                    pasted_bytes,
                                 ))
                                 .unwrap();
                             continue;
                         },
-                        None => {},
+                    };
+                    current_buffer.append(&mut buf.to_vec());
+
+                    if !explicitly_disable_kitty_keyboard_protocol {
+                        // first we try to parse with the KittyKeyboardParser
+                        // if we fail, we try to parse normally
+                        match KittyKeyboardParser::new().parse(&buf) {
+                            Some(key_with_modifier) => {
+                                send_input_instructions
+                                    .send(InputInstruction::KeyWithModifierEvent(
+                                        key_with_modifier,
+                                        current_buffer.drain(..).collect(),
+                                    ))
+                                    .unwrap();
+                                continue;
+                            },
+                            None => {},
+                        }
                     }
-                }
 
-                let maybe_more = false; // read_from_stdin should (hopefully) always empty the STDIN buffer completely
-                let mut events = vec![];
-                input_parser.parse(
-                    &buf,
-                    |input_event: InputEvent| {
-                        events.push(input_event);
-                    },
-                    maybe_more,
-                );
+                    let maybe_more = false; // read_from_stdin should (hopefully) always empty the STDIN buffer completely
+                    let mut events = vec![];
+                    input_parser.parse(
+                        &buf,
+                        |input_event: InputEvent| {
+                            events.push(input_event);
+                        },
+                        maybe_more,
+                    );
 
-                let event_count = events.len();
-                for (i, input_event) in events.into_iter().enumerate() {
-                    if holding_mouse && is_mouse_press_or_hold(&input_event) && i == event_count - 1
-                    {
-                        let mut poller = os_input.stdin_poller();
-                        loop {
-                            if poller.ready() {
-                                break;
+                    let event_count = events.len();
+                    for (i, input_event) in events.into_iter().enumerate() {
+                        if holding_mouse
+                            && is_mouse_press_or_hold(&input_event)
+                            && i == event_count - 1
+                        {
+                            let mut poller = os_input.stdin_poller();
+                            loop {
+                                if poller.ready() {
+                                    break;
+                                }
+                                send_input_instructions
+                                    .send(InputInstruction::KeyEvent(
+                                        input_event.clone(),
+                                        current_buffer.clone(),
+                                    ))
+                                    .unwrap();
                             }
-                            send_input_instructions
-                                .send(InputInstruction::KeyEvent(
-                                    input_event.clone(),
-                                    current_buffer.clone(),
-                                ))
-                                .unwrap();
                         }
-                    }
 
-                    holding_mouse = is_mouse_press_or_hold(&input_event);
+                        holding_mouse = is_mouse_press_or_hold(&input_event);
 
-                    send_input_instructions
-                        .send(InputInstruction::KeyEvent(
-                            input_event,
-                            current_buffer.drain(..).collect(),
-                        ))
-                        .unwrap();
+                        send_input_instructions
+                            .send(InputInstruction::KeyEvent(
+                                input_event,
+                                current_buffer.drain(..).collect(),
+                            ))
+                            .unwrap();
+                    }
                 }
             },
             Err(e) => {
@@ -166,3 +296,7 @@ fn is_mouse_press_or_hold(input_event: &InputEvent) -> bool {
     }
     false
 }
+
+#[cfg(test)]
+#[path = "./unit/stdin_handler_tests.rs"]
+mod stdin_handler_tests;
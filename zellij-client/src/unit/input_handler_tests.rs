@@ -0,0 +1,29 @@
+use super::*;
+
+#[test]
+fn pasted_text_is_left_as_is_without_sanitization() {
+    let pasted_text = "echo hi\u{1b}[201~rm -rf ~\n";
+    assert_eq!(
+        sanitize_pasted_text(pasted_text, PasteSanitization::Off),
+        pasted_text
+    );
+}
+
+#[test]
+fn escape_sequences_are_stripped_from_pasted_text() {
+    let pasted_text =
+        "echo hi\u{1b}[201~rm -rf ~\u{1b}]0;title\u{7}\u{1b}Pq#0\u{1b}\\\u{8}\u{9b}\tdone\r\n";
+    assert_eq!(
+        sanitize_pasted_text(pasted_text, PasteSanitization::Strip),
+        "echo hirm -rf ~\tdone\r\n"
+    );
+}
+
+#[test]
+fn control_characters_in_pasted_text_are_escaped() {
+    let pasted_text = "echo hi\u{1b}[201~\u{3}\u{7f}\u{9b}\tdone\n";
+    assert_eq!(
+        sanitize_pasted_text(pasted_text, PasteSanitization::Escape),
+        "echo hi^[[201~^C^?^[[\tdone\n"
+    );
+}
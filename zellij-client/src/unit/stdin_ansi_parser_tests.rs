@@ -0,0 +1,89 @@
+use super::*;
+
+fn sixel_support(events: Vec<AnsiStdinInstruction>) -> Option<bool> {
+    events.iter().find_map(|event| match event {
+        AnsiStdinInstruction::SixelSupport(supports_sixel) => Some(*supports_sixel),
+        _ => None,
+    })
+}
+
+#[test]
+fn sixel_support_is_parsed_from_device_attributes() {
+    let mut stdin_ansi_parser = StdinAnsiParser::new();
+    let events = stdin_ansi_parser.parse("\u{1b}[?62;4;6;22c".as_bytes().to_vec());
+    assert_eq!(sixel_support(events), Some(true));
+}
+
+#[test]
+fn terminals_without_sixel_attribute_do_not_support_sixel() {
+    let mut stdin_ansi_parser = StdinAnsiParser::new();
+    // a VT100 with advanced video option
+    let events = stdin_ansi_parser.parse("\u{1b}[?1;2c".as_bytes().to_vec());
+    assert_eq!(sixel_support(events), Some(false));
+}
+
+#[test]
+fn device_attributes_are_parsed_after_color_responses() {
+    let mut stdin_ansi_parser = StdinAnsiParser::new();
+    let events = stdin_ansi_parser.parse(
+        "\u{1b}]11;rgb:cccc/cccc/cccc\u{1b}\\\u{1b}[?65;1;4c"
+            .as_bytes()
+            .to_vec(),
+    );
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, AnsiStdinInstruction::BackgroundColor(_))));
+    assert_eq!(sixel_support(events), Some(true));
+}
+
+#[test]
+fn kitty_graphics_support_is_parsed_from_query_response() {
+    let mut stdin_ansi_parser = StdinAnsiParser::new();
+    let events =
+        stdin_ansi_parser.parse("\u{1b}_Gi=31;OK\u{1b}\\\u{1b}[?62;22c".as_bytes().to_vec());
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, AnsiStdinInstruction::KittyGraphicsSupport)));
+    assert_eq!(sixel_support(events), Some(false));
+}
+
+fn color_support(events: Vec<AnsiStdinInstruction>) -> Option<ColorSupport> {
+    events.iter().rev().find_map(|event| match event {
+        AnsiStdinInstruction::ColorSupport(color_support) => Some(*color_support),
+        _ => None,
+    })
+}
+
+#[test]
+fn color_support_is_upgraded_by_the_reported_terminal_capabilities() {
+    let mut stdin_ansi_parser =
+        StdinAnsiParser::new().with_guessed_color_support(ColorSupport::Colors16);
+    // "RGB" is unknown, "colors" is 256
+    let events = stdin_ansi_parser.parse(
+        "\u{1b}P0+r524742\u{1b}\\\u{1b}P1+r636f6c6f7273=323536\u{1b}\\\u{1b}[?62;22c"
+            .as_bytes()
+            .to_vec(),
+    );
+    assert_eq!(color_support(events), Some(ColorSupport::Colors256));
+}
+
+#[test]
+fn reported_true_color_is_not_taken_away_by_the_reported_color_count() {
+    let mut stdin_ansi_parser =
+        StdinAnsiParser::new().with_guessed_color_support(ColorSupport::Colors16);
+    let events = stdin_ansi_parser.parse(
+        "\u{1b}P1+r524742\u{1b}\\\u{1b}P1+r636f6c6f7273=323536\u{1b}\\"
+            .as_bytes()
+            .to_vec(),
+    );
+    assert_eq!(color_support(events), Some(ColorSupport::TrueColor));
+}
+
+#[test]
+fn reported_color_count_does_not_downgrade_the_guessed_color_support() {
+    let mut stdin_ansi_parser =
+        StdinAnsiParser::new().with_guessed_color_support(ColorSupport::TrueColor);
+    let events =
+        stdin_ansi_parser.parse("\u{1b}P1+r636f6c6f7273=323536\u{1b}\\".as_bytes().to_vec());
+    assert_eq!(color_support(events), None);
+}
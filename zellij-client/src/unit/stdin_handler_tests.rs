@@ -0,0 +1,154 @@
+use super::*;
+
+use crate::input_handler::sanitize_pasted_text;
+use crate::os_input_output::StdinPoller;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+use zellij_utils::anyhow;
+use zellij_utils::channels;
+use zellij_utils::data::Palette;
+use zellij_utils::errors::ErrorContext;
+use zellij_utils::ipc::{ClientToServerMsg, ServerToClientMsg};
+use zellij_utils::nix;
+use zellij_utils::pane_size::Size;
+use zellij_utils::termwiz::input::KeyCode;
+
+#[test]
+fn focus_report_on_its_own_is_extracted() {
+    let (focus_events, rest) = extract_focus_events(b"\x1b[I");
+    assert_eq!(focus_events, vec![true]);
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn focus_reports_are_extracted_from_coalesced_input() {
+    let (focus_events, rest) = extract_focus_events(b"\x1b[Iab\x1b[Oc\x1b[A\x1b[I");
+    assert_eq!(focus_events, vec![true, false, true]);
+    assert_eq!(rest, b"abc\x1b[A".to_vec());
+}
+
+#[test]
+fn input_without_focus_reports_is_left_untouched() {
+    let (focus_events, rest) = extract_focus_events(b"\x1b[1;5Ax");
+    assert!(focus_events.is_empty());
+    assert_eq!(rest, b"\x1b[1;5Ax".to_vec());
+}
+
+struct FakeStdin {
+    stdin: Arc<Mutex<std::sync::mpsc::Receiver<Vec<u8>>>>,
+}
+
+impl ClientOsApi for FakeStdin {
+    fn get_terminal_size_using_fd(&self, _fd: RawFd) -> Size {
+        unimplemented!()
+    }
+    fn set_raw_mode(&mut self, _fd: RawFd) {}
+    fn unset_raw_mode(&self, _fd: RawFd) -> Result<(), nix::Error> {
+        Ok(())
+    }
+    fn get_stdout_writer(&self) -> Box<dyn io::Write> {
+        Box::new(io::sink())
+    }
+    fn get_stdin_reader(&self) -> Box<dyn io::BufRead> {
+        unimplemented!()
+    }
+    fn update_session_name(&mut self, _new_session_name: String) {}
+    fn read_from_stdin(&mut self) -> Result<Vec<u8>, &'static str> {
+        self.stdin
+            .lock()
+            .unwrap()
+            .recv()
+            .map_err(|_| "Session ended")
+    }
+    fn box_clone(&self) -> Box<dyn ClientOsApi> {
+        Box::new(FakeStdin {
+            stdin: self.stdin.clone(),
+        })
+    }
+    fn send_to_server(&self, _msg: ClientToServerMsg) {}
+    fn recv_from_server(&self) -> Option<(ServerToClientMsg, ErrorContext)> {
+        None
+    }
+    fn handle_signals(&self, _sigwinch_cb: Box<dyn Fn()>, _quit_cb: Box<dyn Fn()>) {}
+    fn connect_to_server(&self, _path: &Path) {}
+    fn load_palette(&self) -> Palette {
+        unimplemented!()
+    }
+    fn enable_mouse(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    fn disable_mouse(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    fn stdin_poller(&self) -> StdinPoller {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn bracketed_paste_terminator_hidden_in_pasted_text_does_not_end_the_paste() {
+    let (stdin_sender, stdin_receiver) = std::sync::mpsc::channel();
+    let os_input = FakeStdin {
+        stdin: Arc::new(Mutex::new(stdin_receiver)),
+    };
+    let (send_input_instructions, receive_input_instructions) = channels::unbounded();
+    let send_input_instructions = SenderWithContext::new(send_input_instructions);
+    std::thread::spawn(move || {
+        stdin_loop(
+            Box::new(os_input),
+            send_input_instructions,
+            Arc::new(Mutex::new(StdinAnsiParser::new())),
+            true,
+            PasteSanitization::Strip,
+        )
+    });
+    // whatever is read while the terminal emulator is being queried is taken as its reply
+    while !matches!(
+        receive_input_instructions.recv().unwrap().0,
+        InputInstruction::DoneParsing
+    ) {}
+
+    stdin_sender
+        .send(b"\x1b[200~echo hi\x1b[201~rm -rf ~\n\x1b[201~x".to_vec())
+        .unwrap();
+    drop(stdin_sender);
+    let mut pasted_texts = vec![];
+    let mut typed_keys = vec![];
+    loop {
+        match receive_input_instructions.recv().unwrap().0 {
+            InputInstruction::KeyEvent(InputEvent::Paste(pasted_text), _) => {
+                pasted_texts.push(pasted_text)
+            },
+            InputInstruction::KeyEvent(InputEvent::Key(key_event), _) => {
+                typed_keys.push(key_event.key)
+            },
+            InputInstruction::Exit => break,
+            _ => {},
+        }
+    }
+    assert_eq!(pasted_texts, vec!["echo hi\x1b[201~rm -rf ~\n".to_owned()]);
+    assert_eq!(typed_keys, vec![KeyCode::Char('x')]);
+    assert_eq!(
+        sanitize_pasted_text(&pasted_texts[0], PasteSanitization::Strip),
+        "echo hirm -rf ~\n"
+    );
+}
+
+#[test]
+fn bracketed_pastes_are_framed_across_reads() {
+    let mut bracketed_paste = BracketedPaste::default();
+    let stdin_chunks = bracketed_paste.frame(b"a\x1b[200~one\x1b[201~tw");
+    assert!(matches!(
+        &stdin_chunks[..],
+        [StdinChunk::Input(before), StdinChunk::Paste(pasted), StdinChunk::Input(after)]
+            if before == b"a" && pasted == b"one" && after == b"tw"
+    ));
+    let stdin_chunks = bracketed_paste.frame(b"\x1b[200~split");
+    assert!(stdin_chunks.is_empty(), "paste goes on in the next read");
+    let stdin_chunks = bracketed_paste.frame(b" paste\x1b[201~");
+    assert!(matches!(
+        &stdin_chunks[..],
+        [StdinChunk::Paste(pasted)] if pasted == b"split paste"
+    ));
+}
@@ -1,11 +1,13 @@
 use zellij_utils::async_std::task;
 use zellij_utils::consts::{
     session_info_cache_file_name, session_info_folder_for_session, session_layout_cache_file_name,
-    ZELLIJ_SESSION_INFO_CACHE_DIR, ZELLIJ_SOCK_DIR,
+    VERSION, ZELLIJ_SESSION_INFO_CACHE_DIR, ZELLIJ_SOCK_DIR,
 };
 use zellij_utils::data::{Event, HttpVerb, SessionInfo};
 use zellij_utils::errors::{prelude::*, BackgroundJobContext, ContextType};
 use zellij_utils::input::layout::RunPlugin;
+use zellij_utils::input::options::UpdateCheck;
+use zellij_utils::serde_json;
 
 use zellij_utils::isahc::prelude::*;
 use zellij_utils::isahc::AsyncReadResponseExt;
@@ -15,7 +17,7 @@ use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::Write;
 use std::os::unix::fs::FileTypeExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
@@ -25,6 +27,7 @@ use std::time::{Duration, Instant};
 use crate::panes::PaneId;
 use crate::plugins::{PluginId, PluginInstruction};
 use crate::screen::ScreenInstruction;
+use crate::tab::CloseTarget;
 use crate::thread_bus::Bus;
 use crate::ClientId;
 
@@ -35,6 +38,7 @@ pub enum BackgroundJob {
     StopPluginLoadingAnimation(u32),                      // u32 - plugin_id
     ReadAllSessionInfosOnMachine,                         // u32 - plugin_id
     ReportSessionInfo(String, SessionInfo),               // String - session name
+    RenameSession(String, String),                        // old session name, new session name
     ReportPluginList(BTreeMap<PluginId, RunPlugin>),      // String - session name
     ReportLayoutInfo((String, BTreeMap<String, String>)), // BTreeMap<file_name, pane_contents>
     RunCommand(
@@ -55,6 +59,16 @@ pub enum BackgroundJob {
         Vec<u8>,                  // body
         BTreeMap<String, String>, // context
     ),
+    CheckForUpdates(UpdateCheck),
+    MonitorPaneSilence,
+    MonitorIdleClients,
+    MonitorIdleSession,
+    MonitorRunningCommands,
+    MonitorProcessStats,
+    FindRunningProcesses(CloseTarget, ClientId, Vec<(u32, bool)>), // terminal id, whether the
+    // process spawned in it counts (it runs a command rather than a shell)
+    RunHookCommand(String, BTreeMap<String, String>), // command, env_variables
+    StopMonitor(Box<BackgroundJob>),                  // one of the Monitor* jobs above
     Exit,
 }
 
@@ -70,10 +84,20 @@ impl From<&BackgroundJob> for BackgroundJobContext {
                 BackgroundJobContext::ReadAllSessionInfosOnMachine
             },
             BackgroundJob::ReportSessionInfo(..) => BackgroundJobContext::ReportSessionInfo,
+            BackgroundJob::RenameSession(..) => BackgroundJobContext::RenameSession,
             BackgroundJob::ReportLayoutInfo(..) => BackgroundJobContext::ReportLayoutInfo,
             BackgroundJob::RunCommand(..) => BackgroundJobContext::RunCommand,
             BackgroundJob::WebRequest(..) => BackgroundJobContext::WebRequest,
             BackgroundJob::ReportPluginList(..) => BackgroundJobContext::ReportPluginList,
+            BackgroundJob::CheckForUpdates(..) => BackgroundJobContext::CheckForUpdates,
+            BackgroundJob::MonitorPaneSilence => BackgroundJobContext::MonitorPaneSilence,
+            BackgroundJob::MonitorIdleClients => BackgroundJobContext::MonitorIdleClients,
+            BackgroundJob::MonitorIdleSession => BackgroundJobContext::MonitorIdleSession,
+            BackgroundJob::MonitorRunningCommands => BackgroundJobContext::MonitorRunningCommands,
+            BackgroundJob::MonitorProcessStats => BackgroundJobContext::MonitorProcessStats,
+            BackgroundJob::FindRunningProcesses(..) => BackgroundJobContext::FindRunningProcesses,
+            BackgroundJob::RunHookCommand(..) => BackgroundJobContext::RunHookCommand,
+            BackgroundJob::StopMonitor(..) => BackgroundJobContext::StopMonitor,
             BackgroundJob::Exit => BackgroundJobContext::Exit,
         }
     }
@@ -83,6 +107,14 @@ static FLASH_DURATION_MS: u64 = 1000;
 static PLUGIN_ANIMATION_OFFSET_DURATION_MD: u64 = 500;
 static SESSION_READ_DURATION: u64 = 1000;
 static DEFAULT_SERIALIZATION_INTERVAL: u64 = 60000;
+static INSTALLED_VERSION_CHECK_INTERVAL: u64 = 600000; // 10 minutes
+static LATEST_RELEASE_CHECK_INTERVAL: u64 = 86400000; // 24 hours
+static PANE_SILENCE_CHECK_INTERVAL: u64 = 1000;
+static IDLE_CLIENTS_CHECK_INTERVAL: u64 = 1000;
+static IDLE_SESSION_CHECK_INTERVAL: u64 = 30000;
+static RUNNING_COMMANDS_CHECK_INTERVAL: u64 = 1000;
+static PROCESS_STATS_CHECK_INTERVAL: u64 = 2000;
+static LATEST_RELEASE_URL: &str = "https://api.github.com/repos/zellij-org/zellij/releases/latest";
 
 pub(crate) fn background_jobs_main(
     bus: Bus<BackgroundJob>,
@@ -92,6 +124,7 @@ pub(crate) fn background_jobs_main(
     let err_context = || "failed to write to pty".to_string();
     let mut running_jobs: HashMap<BackgroundJob, Instant> = HashMap::new();
     let mut loading_plugins: HashMap<u32, Arc<AtomicBool>> = HashMap::new(); // u32 - plugin_id
+    let mut running_monitors: HashMap<BackgroundJob, Arc<AtomicBool>> = HashMap::new();
     let current_session_name = Arc::new(Mutex::new(String::default()));
     let current_session_info = Arc::new(Mutex::new(SessionInfo::default()));
     let current_session_plugin_list: Arc<Mutex<BTreeMap<PluginId, RunPlugin>>> =
@@ -163,6 +196,20 @@ pub(crate) fn background_jobs_main(
                 *current_session_name.lock().unwrap() = session_name;
                 *current_session_info.lock().unwrap() = session_info;
             },
+            BackgroundJob::RenameSession(old_session_name, new_session_name) => {
+                // the session name stays locked while the session_info folder is moved so that
+                // the session state is not written to the old folder in between
+                let mut current_session_name = current_session_name.lock().unwrap();
+                if !disable_session_metadata {
+                    if let Err(e) = move_session_info_folder(
+                        &session_info_folder_for_session(&old_session_name),
+                        &session_info_folder_for_session(&new_session_name),
+                    ) {
+                        log::error!("Failed to rename session_info folder: {:?}", e);
+                    }
+                }
+                *current_session_name = new_session_name;
+            },
             BackgroundJob::ReportPluginList(plugin_list) => {
                 *current_session_plugin_list.lock().unwrap() = plugin_list;
             },
@@ -186,18 +233,22 @@ pub(crate) fn background_jobs_main(
                     let last_serialization_time = last_serialization_time.clone();
                     async move {
                         loop {
-                            let current_session_name =
-                                current_session_name.lock().unwrap().to_string();
                             let current_session_info = current_session_info.lock().unwrap().clone();
                             let current_session_layout =
                                 current_session_layout.lock().unwrap().clone();
-                            if !disable_session_metadata {
-                                write_session_state_to_disk(
-                                    current_session_name.clone(),
-                                    current_session_info,
-                                    current_session_layout,
-                                );
-                            }
+                            let current_session_name = {
+                                // held while writing so that renaming the session cannot move
+                                // the session_info folder from under us
+                                let current_session_name = current_session_name.lock().unwrap();
+                                if !disable_session_metadata {
+                                    write_session_state_to_disk(
+                                        current_session_name.clone(),
+                                        current_session_info,
+                                        current_session_layout,
+                                    );
+                                }
+                                current_session_name.to_string()
+                            };
                             let mut session_infos_on_machine =
                                 read_other_live_session_states(&current_session_name);
                             for (session_name, session_info) in session_infos_on_machine.iter_mut()
@@ -278,6 +329,28 @@ pub(crate) fn background_jobs_main(
                     }
                 });
             },
+            BackgroundJob::RunHookCommand(command, env_variables) => {
+                // hooks are fire and forget, their output goes nowhere
+                std::thread::spawn(move || {
+                    let status = std::process::Command::new("sh")
+                        .arg("-c")
+                        .arg(&command)
+                        .envs(env_variables)
+                        .stdin(std::process::Stdio::null())
+                        .stdout(std::process::Stdio::null())
+                        .stderr(std::process::Stdio::null())
+                        .status();
+                    match status {
+                        Ok(status) if !status.success() => {
+                            log::error!("Hook command \"{}\" failed: {}", command, status);
+                        },
+                        Ok(_) => {},
+                        Err(e) => {
+                            log::error!("Failed to run hook command \"{}\": {}", command, e);
+                        },
+                    }
+                });
+            },
             BackgroundJob::WebRequest(plugin_id, client_id, url, verb, headers, body, context) => {
                 task::spawn({
                     let senders = bus.senders.clone();
@@ -360,10 +433,199 @@ pub(crate) fn background_jobs_main(
                     }
                 });
             },
+            BackgroundJob::CheckForUpdates(update_check) => {
+                // like ReadAllSessionInfosOnMachine, this job runs for the lifetime of the session
+                if running_jobs.get(&job).is_some() {
+                    continue;
+                }
+                running_jobs.insert(job, Instant::now());
+                // running the installed binary and the (blocking) http request should not block
+                // the async executor
+                std::thread::spawn({
+                    let senders = bus.senders.clone();
+                    let http_client = http_client.clone();
+                    move || {
+                        let mut latest_release: Option<String> = None;
+                        let mut last_release_check: Option<Instant> = None;
+                        loop {
+                            let installed_version = installed_zellij_version();
+                            if update_check == UpdateCheck::Release
+                                && last_release_check.map_or(true, |last_release_check| {
+                                    last_release_check.elapsed()
+                                        >= Duration::from_millis(LATEST_RELEASE_CHECK_INTERVAL)
+                                })
+                            {
+                                if let Some(http_client) = &http_client {
+                                    latest_release = latest_zellij_release(http_client);
+                                }
+                                last_release_check = Some(Instant::now());
+                            }
+                            let latest_release = latest_release.clone().filter(|latest_release| {
+                                latest_release != VERSION
+                                    && Some(latest_release) != installed_version.as_ref()
+                            });
+                            let installed_version = installed_version
+                                .filter(|installed_version| installed_version != VERSION);
+                            let _ =
+                                senders.send_to_screen(ScreenInstruction::UpdateAvailableVersions(
+                                    installed_version,
+                                    latest_release,
+                                ));
+                            std::thread::sleep(Duration::from_millis(
+                                INSTALLED_VERSION_CHECK_INTERVAL,
+                            ));
+                        }
+                    }
+                });
+            },
+            BackgroundJob::MonitorPaneSilence => {
+                // started when the first silence monitor is toggled on and runs for the lifetime
+                // of the session
+                if running_jobs.get(&job).is_some() {
+                    continue;
+                }
+                running_jobs.insert(job, Instant::now());
+                std::thread::spawn({
+                    let senders = bus.senders.clone();
+                    move || loop {
+                        std::thread::sleep(Duration::from_millis(PANE_SILENCE_CHECK_INTERVAL));
+                        if senders
+                            .send_to_screen(ScreenInstruction::CheckPaneSilence)
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                });
+            },
+            BackgroundJob::MonitorIdleClients => {
+                // started when an idle client detach timeout is first configured and runs for the
+                // lifetime of the session
+                if running_jobs.get(&job).is_some() {
+                    continue;
+                }
+                running_jobs.insert(job, Instant::now());
+                std::thread::spawn({
+                    let senders = bus.senders.clone();
+                    move || loop {
+                        std::thread::sleep(Duration::from_millis(IDLE_CLIENTS_CHECK_INTERVAL));
+                        if senders
+                            .send_to_screen(ScreenInstruction::CheckIdleClients)
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                });
+            },
+            BackgroundJob::MonitorIdleSession => {
+                // runs while an idle session timeout is configured
+                let is_running = match start_monitor(job, &mut running_monitors) {
+                    Some(is_running) => is_running,
+                    None => continue,
+                };
+                std::thread::spawn({
+                    let senders = bus.senders.clone();
+                    move || loop {
+                        std::thread::sleep(Duration::from_millis(IDLE_SESSION_CHECK_INTERVAL));
+                        if !is_running.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        if senders
+                            .send_to_screen(ScreenInstruction::CheckIdleSession)
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                });
+            },
+            BackgroundJob::MonitorRunningCommands => {
+                // runs while automatic tab renaming is turned on
+                let is_running = match start_monitor(job, &mut running_monitors) {
+                    Some(is_running) => is_running,
+                    None => continue,
+                };
+                if let Some(os_input) = bus.os_input.as_ref().map(|o| o.box_clone()) {
+                    std::thread::spawn({
+                        let senders = bus.senders.clone();
+                        move || loop {
+                            std::thread::sleep(Duration::from_millis(
+                                RUNNING_COMMANDS_CHECK_INTERVAL,
+                            ));
+                            if !is_running.load(Ordering::SeqCst) {
+                                break;
+                            }
+                            let running_commands = os_input.get_foreground_commands();
+                            if senders
+                                .send_to_screen(ScreenInstruction::UpdateRunningCommands(
+                                    running_commands,
+                                ))
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    });
+                }
+            },
+            BackgroundJob::MonitorProcessStats => {
+                // runs while the pane_frame_process_stats option is turned on
+                let is_running = match start_monitor(job, &mut running_monitors) {
+                    Some(is_running) => is_running,
+                    None => continue,
+                };
+                if let Some(os_input) = bus.os_input.as_ref().map(|o| o.box_clone()) {
+                    std::thread::spawn({
+                        let senders = bus.senders.clone();
+                        move || loop {
+                            std::thread::sleep(Duration::from_millis(PROCESS_STATS_CHECK_INTERVAL));
+                            if !is_running.load(Ordering::SeqCst) {
+                                break;
+                            }
+                            let process_stats = os_input.get_process_stats();
+                            if senders
+                                .send_to_screen(ScreenInstruction::UpdateProcessStats(
+                                    process_stats,
+                                ))
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    });
+                }
+            },
+            BackgroundJob::FindRunningProcesses(close_target, client_id, terminals) => {
+                // scanning the process table can take a while, so it is not done on the screen
+                // thread
+                let os_input = bus.os_input.as_ref().map(|o| o.box_clone());
+                std::thread::spawn({
+                    let senders = bus.senders.clone();
+                    move || {
+                        let running_processes = os_input
+                            .map(|os_input| os_input.get_running_process_names(terminals))
+                            .unwrap_or_default();
+                        let _ = senders.send_to_screen(ScreenInstruction::AskToConfirmClose(
+                            close_target,
+                            client_id,
+                            running_processes,
+                        ));
+                    }
+                });
+            },
+            BackgroundJob::StopMonitor(monitor) => {
+                if let Some(is_running) = running_monitors.remove(&*monitor) {
+                    is_running.store(false, Ordering::SeqCst);
+                }
+            },
             BackgroundJob::Exit => {
                 for loading_plugin in loading_plugins.values() {
                     loading_plugin.store(false, Ordering::SeqCst);
                 }
+                for is_running in running_monitors.values() {
+                    is_running.store(false, Ordering::SeqCst);
+                }
 
                 let cache_file_name =
                     session_info_cache_file_name(&current_session_name.lock().unwrap().to_owned());
@@ -374,6 +636,80 @@ pub(crate) fn background_jobs_main(
     }
 }
 
+// the version of the zellij binary at the path this session was started from, which might have
+// been replaced (eg. upgraded) since
+fn installed_zellij_version() -> Option<String> {
+    let current_exe = std::env::current_exe().ok()?;
+    // on linux, a binary that was replaced on disk is reported as "<path> (deleted)"
+    let current_exe = current_exe.to_string_lossy();
+    let current_exe = current_exe.trim_end_matches(" (deleted)");
+    let output = std::process::Command::new(current_exe)
+        .arg("--version")
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // eg. "zellij 0.42.0"
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .last()
+        .map(|version| version.to_owned())
+}
+
+fn latest_zellij_release(http_client: &HttpClient) -> Option<String> {
+    let request = Request::get(LATEST_RELEASE_URL)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", format!("zellij/{}", VERSION))
+        .body(())
+        .ok()?;
+    let mut response = match http_client.send(request) {
+        Ok(response) => response,
+        Err(e) => {
+            log::error!("Failed to check for the latest zellij release: {}", e);
+            return None;
+        },
+    };
+    if !response.status().is_success() {
+        log::error!(
+            "Failed to check for the latest zellij release: {}",
+            response.status()
+        );
+        return None;
+    }
+    let release: serde_json::Value = serde_json::from_str(&response.text().ok()?).ok()?;
+    release
+        .get("tag_name")?
+        .as_str()
+        .map(|tag_name| tag_name.trim_start_matches('v').to_owned())
+}
+
+// a session that did not write its state yet has no folder to move
+fn move_session_info_folder(
+    old_session_info_folder: &Path,
+    new_session_info_folder: &Path,
+) -> std::io::Result<()> {
+    match std::fs::rename(old_session_info_folder, new_session_info_folder) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+// returns the flag the thread of the monitor keeps running by, unless it is already running
+fn start_monitor(
+    job: BackgroundJob,
+    running_monitors: &mut HashMap<BackgroundJob, Arc<AtomicBool>>,
+) -> Option<Arc<AtomicBool>> {
+    if running_monitors.contains_key(&job) {
+        return None;
+    }
+    let is_running = Arc::new(AtomicBool::new(true));
+    running_monitors.insert(job, is_running.clone());
+    Some(is_running)
+}
+
 fn job_already_running(
     job: BackgroundJob,
     running_jobs: &mut HashMap<BackgroundJob, Instant>,
@@ -505,3 +841,7 @@ fn find_resurrectable_sessions(
         },
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/background_jobs_tests.rs"]
+mod background_jobs_tests;
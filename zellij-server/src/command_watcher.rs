@@ -0,0 +1,140 @@
+//! Watches the files of a command pane (eg. `zellij run --watch '**/*.rs' -- cargo test`) and asks
+//! the screen to re-run its command whenever one of them changes.
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::{screen::ScreenInstruction, thread_bus::ThreadSenders};
+use zellij_utils::notify_debouncer_full::{
+    new_debouncer,
+    notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher},
+    DebounceEventResult, Debouncer, FileIdMap,
+};
+use zellij_utils::{errors::prelude::*, input::command::CommandWatch, regex::Regex};
+
+pub type CommandWatcher = Debouncer<RecommendedWatcher, FileIdMap>;
+
+struct WatchPattern {
+    regex: Regex,
+    // patterns without a slash match file names in any folder (eg. `*.rs`)
+    matches_file_name: bool,
+}
+
+impl WatchPattern {
+    fn new(glob: &str) -> Result<Self> {
+        let regex = Regex::new(&glob_to_regex(glob))
+            .with_context(|| format!("invalid watch pattern '{}'", glob))?;
+        Ok(WatchPattern {
+            regex,
+            matches_file_name: !glob.contains('/'),
+        })
+    }
+    fn matches(&self, relative_path: &Path) -> bool {
+        let path = if self.matches_file_name {
+            relative_path.file_name().map(|f| f.to_string_lossy())
+        } else {
+            Some(relative_path.to_string_lossy())
+        };
+        path.map(|path| self.regex.is_match(&path)).unwrap_or(false)
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    // `**/` also matches no folder at all
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            },
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&zellij_utils::regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+pub fn watch_command_files(
+    senders: ThreadSenders,
+    terminal_id: u32,
+    cwd: &Path,
+    watch: &CommandWatch,
+) -> Result<CommandWatcher> {
+    let patterns = watch
+        .patterns
+        .iter()
+        .map(|pattern| WatchPattern::new(pattern))
+        .collect::<Result<Vec<_>>>()?;
+    let cwd = PathBuf::from(cwd);
+    let watched_dir = cwd.clone();
+    let mut debouncer = new_debouncer(
+        Duration::from_millis(watch.debounce_ms),
+        None,
+        move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                let matching_file_changed = events
+                    .iter()
+                    .filter(|event| {
+                        matches!(
+                            event.kind,
+                            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                        )
+                    })
+                    .flat_map(|event| event.paths.iter())
+                    .filter_map(|path| path.strip_prefix(&cwd).ok())
+                    .any(|path| patterns.iter().any(|pattern| pattern.matches(path)));
+                if matching_file_changed {
+                    let _ =
+                        senders.send_to_screen(ScreenInstruction::WatchedFilesChanged(terminal_id));
+                }
+            },
+            Err(errors) => errors
+                .iter()
+                .for_each(|error| log::error!("watch error: {error:?}")),
+        },
+    )?;
+    debouncer
+        .watcher()
+        .watch(&watched_dir, RecursiveMode::Recursive)?;
+    Ok(debouncer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(glob: &str, path: &str) -> bool {
+        WatchPattern::new(glob).unwrap().matches(Path::new(path))
+    }
+
+    #[test]
+    fn patterns_without_a_slash_match_file_names() {
+        assert!(matches("*.rs", "main.rs"));
+        assert!(matches("*.rs", "src/panes/grid.rs"));
+        assert!(matches("Cargo.toml", "zellij-utils/Cargo.toml"));
+        assert!(!matches("*.rs", "src/main.rs.orig"));
+        assert!(!matches("?.rs", "ab.rs"));
+    }
+
+    #[test]
+    fn patterns_with_a_slash_match_relative_paths() {
+        assert!(matches("src/*.rs", "src/main.rs"));
+        assert!(!matches("src/*.rs", "src/panes/grid.rs"));
+        assert!(matches("src/**/*.rs", "src/panes/grid.rs"));
+        assert!(matches("**/*.rs", "main.rs"), "**/ also matches no folder");
+        assert!(matches("src/**", "src/panes/grid.rs"));
+        assert!(!matches("src/**", "tests/main.rs"));
+        assert!(
+            !matches("src.rs/*", "srcxrs/main"),
+            "dots are not wildcards"
+        );
+    }
+}
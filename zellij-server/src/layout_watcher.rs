@@ -0,0 +1,65 @@
+//! Watches the layout file of the session (and its swap layout file) and asks the server to read
+//! it again whenever it changes, so that swap layouts can be edited without restarting the session.
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::ServerInstruction;
+use zellij_utils::channels::SenderWithContext;
+use zellij_utils::errors::prelude::*;
+use zellij_utils::notify_debouncer_full::{
+    new_debouncer,
+    notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher},
+    DebounceEventResult, Debouncer, FileIdMap,
+};
+
+pub type LayoutWatcher = Debouncer<RecommendedWatcher, FileIdMap>;
+
+const DEBOUNCE_DURATION_MS: u64 = 400;
+
+pub fn watch_layout_files(
+    to_server: SenderWithContext<ServerInstruction>,
+    layout_file_path: PathBuf,
+) -> Result<LayoutWatcher> {
+    let err_context = || format!("failed to watch layout file {:?}", layout_file_path);
+
+    // the paths of file events are absolute
+    let layout_file_path = std::fs::canonicalize(&layout_file_path).with_context(err_context)?;
+    let swap_layout_file_path = layout_file_path.with_extension("swap.kdl");
+    let layout_folder = layout_file_path
+        .parent()
+        .map(|folder| folder.to_path_buf())
+        .context("layout file has no parent folder")?;
+    let watched_files = [layout_file_path.clone(), swap_layout_file_path];
+    let mut debouncer = new_debouncer(
+        Duration::from_millis(DEBOUNCE_DURATION_MS),
+        None,
+        move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                // editors often replace files rather than write to them, so we also look at
+                // files being created
+                let layout_files_changed = events
+                    .iter()
+                    .filter(|event| {
+                        matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    })
+                    .flat_map(|event| event.paths.iter())
+                    .any(|path| watched_files.contains(path));
+                if layout_files_changed {
+                    let _ = to_server.send(ServerInstruction::LayoutFilesChanged(
+                        layout_file_path.clone(),
+                    ));
+                }
+            },
+            Err(errors) => errors
+                .iter()
+                .for_each(|error| log::error!("watch error: {error:?}")),
+        },
+    )
+    .with_context(err_context)?;
+    // we watch the folder rather than the files, because the swap layout file might not exist yet
+    debouncer
+        .watcher()
+        .watch(&layout_folder, RecursiveMode::NonRecursive)
+        .with_context(err_context)?;
+    Ok(debouncer)
+}
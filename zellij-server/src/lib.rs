@@ -1,16 +1,20 @@
 pub mod os_input_output;
 pub mod output;
 pub mod panes;
+pub mod session_layout_metadata;
 pub mod tab;
 
 mod background_jobs;
+mod command_watcher;
+mod layout_watcher;
 mod logging_pipe;
+mod output_log;
 mod plugins;
 mod pty;
 mod pty_writer;
+mod resource_limits;
 mod route;
 mod screen;
-mod session_layout_metadata;
 mod terminal_bytes;
 mod thread_bus;
 mod ui;
@@ -23,6 +27,7 @@ use std::{
     path::PathBuf,
     sync::{Arc, RwLock},
     thread,
+    time::SystemTime,
 };
 use zellij_utils::envs;
 use zellij_utils::nix::sys::stat::{umask, Mode};
@@ -32,15 +37,19 @@ use wasmtime::{Config as WasmtimeConfig, Engine, Strategy};
 
 use crate::{
     os_input_output::ServerOsApi,
+    panes::{AlternateScreenBehavior, UnicodeWidth},
     plugins::{plugin_thread_main, PluginInstruction},
     pty::{get_default_shell, pty_thread_main, Pty, PtyInstruction},
+    resource_limits::ResourceLimits,
     screen::{screen_thread_main, ScreenInstruction},
+    session_layout_metadata::ClientMetadata,
     thread_bus::{Bus, ThreadSenders},
 };
-use route::route_thread_main;
+use layout_watcher::{watch_layout_files, LayoutWatcher};
+use route::{route_action, route_thread_main};
 use zellij_utils::{
     channels::{self, ChannelWithContext, SenderWithContext},
-    cli::CliArgs,
+    cli::{CliArgs, Command, Sessions},
     consts::{
         DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE, ZELLIJ_SEEN_RELEASE_NOTES_CACHE_FILE,
     },
@@ -52,12 +61,16 @@ use zellij_utils::{
         command::{RunCommand, TerminalAction},
         config::Config,
         get_mode_info,
+        hooks::{Hook, Hooks},
         keybinds::Keybinds,
         layout::{FloatingPaneLayout, Layout, PluginAlias, Run, RunPluginOrAlias},
         options::Options,
         plugins::PluginAliases,
     },
-    ipc::{ClientAttributes, ExitReason, ServerToClientMsg},
+    ipc::{ClientAttributes, ExitReason, PaneSignal, ServerToClientMsg, SessionStats},
+    serde_json::{json, Value},
+    session_events::{event_name, format_as_env_vars, record, SessionEvents},
+    setup::Setup,
     shared::default_palette,
 };
 
@@ -83,6 +96,7 @@ pub enum ServerInstruction {
     Error(String),
     KillSession,
     DetachSession(Vec<ClientId>),
+    DetachIdleClient(ClientId),
     AttachClient(
         ClientAttributes,
         Config,              // represents the saved config
@@ -117,6 +131,28 @@ pub enum ServerInstruction {
         keys_to_unbind: Vec<(InputMode, KeyWithModifier)>,
         write_config_to_disk: bool,
     },
+    QuerySessionStats(ClientId),
+    SignalPanes(PaneSignal, ClientId),
+    SessionStats {
+        client_id: ClientId,
+        tab_count: usize,
+        pane_count: usize,
+    },
+    ToggleRawPassthrough(ClientId),
+    LayoutFilesChanged(PathBuf), // PathBuf - the layout file of the session
+    CapturedPaneOutput(ClientId, Vec<u8>),
+    CapturedPaneExited(ClientId, Option<i32>), // Option<i32> -> exit status
+    ClientsMetadata {
+        client_id: ClientId,
+        clients_metadata: BTreeMap<ClientId, ClientMetadata>,
+        default_editor: Option<PathBuf>,
+        json: bool,
+    },
+    SubscribeToEvents(ClientId),
+    PublishEvent(Event),
+    StdinPipeReady(ClientId, u32),             // u32 -> terminal id
+    RefuseNewPanes(HashMap<ClientId, String>), // String -> why (see ResourceLimits)
+    CopyModeClients(HashSet<ClientId>),
 }
 
 impl From<&ServerInstruction> for ServerContext {
@@ -130,6 +166,7 @@ impl From<&ServerInstruction> for ServerContext {
             ServerInstruction::Error(_) => ServerContext::Error,
             ServerInstruction::KillSession => ServerContext::KillSession,
             ServerInstruction::DetachSession(..) => ServerContext::DetachSession,
+            ServerInstruction::DetachIdleClient(..) => ServerContext::DetachIdleClient,
             ServerInstruction::AttachClient(..) => ServerContext::AttachClient,
             ServerInstruction::ConnStatus(..) => ServerContext::ConnStatus,
             ServerInstruction::Log(..) => ServerContext::Log,
@@ -153,6 +190,19 @@ impl From<&ServerInstruction> for ServerContext {
                 ServerContext::FailedToWriteConfigToDisk
             },
             ServerInstruction::RebindKeys { .. } => ServerContext::RebindKeys,
+            ServerInstruction::QuerySessionStats(..) => ServerContext::QuerySessionStats,
+            ServerInstruction::SignalPanes(..) => ServerContext::SignalPanes,
+            ServerInstruction::SessionStats { .. } => ServerContext::SessionStats,
+            ServerInstruction::ToggleRawPassthrough(..) => ServerContext::ToggleRawPassthrough,
+            ServerInstruction::LayoutFilesChanged(..) => ServerContext::LayoutFilesChanged,
+            ServerInstruction::CapturedPaneOutput(..) => ServerContext::CapturedPaneOutput,
+            ServerInstruction::CapturedPaneExited(..) => ServerContext::CapturedPaneExited,
+            ServerInstruction::ClientsMetadata { .. } => ServerContext::ClientsMetadata,
+            ServerInstruction::SubscribeToEvents(..) => ServerContext::SubscribeToEvents,
+            ServerInstruction::PublishEvent(..) => ServerContext::PublishEvent,
+            ServerInstruction::StdinPipeReady(..) => ServerContext::StdinPipeReady,
+            ServerInstruction::RefuseNewPanes(..) => ServerContext::RefuseNewPanes,
+            ServerInstruction::CopyModeClients(..) => ServerContext::CopyModeClients,
         }
     }
 }
@@ -302,6 +352,17 @@ pub(crate) struct SessionMetaData {
     pub layout: Box<Layout>,
     pub current_input_modes: HashMap<ClientId, InputMode>,
     pub session_configuration: SessionConfiguration,
+    // clients whose keys are all sent to their focused pane, except for the one toggling this
+    pub raw_passthrough_clients: HashSet<ClientId>,
+    // why the clients cannot open new panes in the tab they are looking at, if they cannot (see
+    // ResourceLimits)
+    pub refused_new_panes: HashMap<ClientId, String>,
+    // clients with a keyboard selection in progress in their focused pane (see panes::copy_mode),
+    // whose keys drive it rather than their bindings
+    pub copy_mode_clients: HashSet<ClientId>,
+    pub hooks: Hooks,
+    // kept here so that the layout file of the session is watched for as long as it lives
+    pub layout_watcher: Option<LayoutWatcher>,
 
     screen_thread: Option<thread::JoinHandle<()>>,
     pty_thread: Option<thread::JoinHandle<()>>,
@@ -334,12 +395,76 @@ impl SessionMetaData {
             self.current_input_modes.insert(client_id, input_mode);
         }
     }
+    /// Read the layout of the session again after its file (or its swap layout file) changed, so
+    /// that new tabs and the tabs using its swap layouts get its new definitions
+    pub fn reload_layout(&mut self, layout_file_path: &PathBuf) {
+        let config = self
+            .current_input_modes
+            .keys()
+            .min()
+            .map(|client_id| {
+                self.session_configuration
+                    .get_client_configuration(client_id)
+            })
+            .unwrap_or_default();
+        match Layout::from_path_or_default(Some(layout_file_path), None, config) {
+            Ok((layout, _config)) => {
+                self.layout = Box::new(layout.clone());
+                let _ = self
+                    .senders
+                    .send_to_screen(ScreenInstruction::ReloadLayout(Box::new(layout)));
+            },
+            Err(e) => {
+                log::error!("Failed to reload layout {:?}: {}", layout_file_path, e);
+            },
+        }
+    }
+    /// The hooks of the configuration for a session event (see `session_events`), if it has any,
+    /// their actions are run on behalf of the given client or else of the first attached one
+    pub fn hooks_to_run(&self, event: &Value, client_id: Option<ClientId>) -> Option<HooksToRun> {
+        let hooks = self.hooks.for_event(event_name(event));
+        if hooks.is_empty() {
+            return None;
+        }
+        let client = client_id
+            .or_else(|| self.current_input_modes.keys().min().copied())
+            .map(|client_id| {
+                (
+                    client_id,
+                    self.session_configuration
+                        .get_client_configuration(&client_id),
+                )
+            });
+        Some(HooksToRun {
+            event: event.clone(),
+            hooks: hooks.to_vec(),
+            client,
+            senders: self.senders.clone(),
+            capabilities: self.capabilities.clone(),
+            client_attributes: self.client_attributes.clone(),
+            default_shell: self.default_shell.clone(),
+            layout: self.layout.clone(),
+        })
+    }
+    /// The hooks of a client detaching, unless it was never attached (eg. a cli client sending
+    /// the session actions)
+    pub fn detach_client(&mut self, client_id: ClientId) -> Option<HooksToRun> {
+        if self.current_input_modes.remove(&client_id).is_some() {
+            self.hooks_to_run(
+                &record("client-detached", json!({ "client_id": client_id })),
+                None,
+            )
+        } else {
+            None
+        }
+    }
     pub fn propagate_configuration_changes(
         &mut self,
         config_changes: Vec<(ClientId, Config)>,
         config_was_written_to_disk: bool,
     ) {
         for (client_id, new_config) in config_changes {
+            self.hooks = new_config.hooks.clone();
             self.default_shell = new_config.options.default_shell.as_ref().map(|shell| {
                 TerminalAction::RunCommand(RunCommand {
                     command: shell.clone(),
@@ -362,13 +487,46 @@ impl SessionMetaData {
                     default_shell: new_config.options.default_shell,
                     pane_frames: new_config.options.pane_frames.unwrap_or(true),
                     copy_command: new_config.options.copy_command,
+                    copy_providers: new_config.options.copy_providers,
                     copy_to_clipboard: new_config.options.copy_clipboard,
                     copy_on_select: new_config.options.copy_on_select.unwrap_or(true),
+                    osc52_forwarding: new_config.options.osc52_forwarding.unwrap_or(true),
+                    word_separators: new_config.options.word_separators,
                     auto_layout: new_config.options.auto_layout.unwrap_or(true),
                     rounded_corners: new_config.ui.pane_frames.rounded_corners,
                     hide_session_name: new_config.ui.pane_frames.hide_session_name,
                     stacked_resize: new_config.options.stacked_resize.unwrap_or(true),
                     default_editor: new_config.options.scrollback_editor.clone(),
+                    quiet_hours: new_config.options.quiet_hours,
+                    title_precedence: new_config.options.title_precedence.unwrap_or_default(),
+                    tab_name_from_title: new_config.options.tab_name_from_title.unwrap_or(false),
+                    bell_forwarding: new_config.options.bell_forwarding.unwrap_or_default(),
+                    command_exit_banner: new_config.options.command_exit_banner.unwrap_or_default(),
+                    idle_client_detach_timeout: new_config.options.idle_client_detach_timeout,
+                    idle_session_timeout: new_config.options.idle_session_timeout,
+                    mirror_session: new_config.options.mirror_session.unwrap_or(false),
+                    global_floating_panes: new_config
+                        .options
+                        .global_floating_panes
+                        .unwrap_or(false),
+                    automatic_tab_rename: new_config.options.automatic_tab_rename.unwrap_or(false),
+                    pane_frame_process_stats: new_config
+                        .options
+                        .pane_frame_process_stats
+                        .unwrap_or(false),
+                    confirm_close_with_running_processes: new_config
+                        .options
+                        .confirm_close_with_running_processes
+                        .unwrap_or(true),
+                    command_exit_notification_command: new_config
+                        .options
+                        .command_exit_notification_command
+                        .clone(),
+                    resource_limits: ResourceLimits::from_options(&new_config.options),
+                    alternate_screen_behavior: AlternateScreenBehavior::from_options(
+                        &new_config.options,
+                    ),
+                    unicode_width: UnicodeWidth::from_options(&new_config.options),
                 })
                 .unwrap();
             self.senders
@@ -384,12 +542,93 @@ impl SessionMetaData {
                 .send_to_pty(PtyInstruction::Reconfigure {
                     client_id,
                     default_editor: new_config.options.scrollback_editor,
+                    new_pane_cwd: new_config.options.new_pane_cwd.unwrap_or_default(),
                 })
                 .unwrap();
         }
     }
 }
 
+/// The hooks of a session event, gathered while the session data is locked and run once it is
+/// released: their actions are routed like those of a client, which can wait on the server thread
+/// (eg. for room in its queue) while it waits for the lock
+pub(crate) struct HooksToRun {
+    event: Value,
+    hooks: Vec<Hook>,
+    // the client actions are run on behalf of, with its configuration
+    client: Option<(ClientId, Config)>,
+    senders: ThreadSenders,
+    capabilities: PluginCapabilities,
+    client_attributes: ClientAttributes,
+    default_shell: Option<TerminalAction>,
+    layout: Box<Layout>,
+}
+
+impl HooksToRun {
+    pub fn run(self) {
+        for hook in &self.hooks {
+            match hook {
+                Hook::Command(command) => {
+                    let _ = self
+                        .senders
+                        .send_to_background_jobs(BackgroundJob::RunHookCommand(
+                            command.clone(),
+                            format_as_env_vars(&self.event),
+                        ));
+                },
+                Hook::Actions(actions) => {
+                    let (client_id, client_configuration) = match &self.client {
+                        Some(client) => client,
+                        None => continue,
+                    };
+                    for action in actions {
+                        if let Err(e) = route_action(
+                            action.clone(),
+                            *client_id,
+                            None,
+                            self.senders.clone(),
+                            self.capabilities.clone(),
+                            self.client_attributes.clone(),
+                            self.default_shell.clone(),
+                            self.layout.clone(),
+                            None,
+                            client_configuration.keybinds.clone(),
+                            client_configuration
+                                .options
+                                .default_mode
+                                .unwrap_or(InputMode::Normal),
+                        ) {
+                            log::error!("Failed to run hook action: {:?}", e);
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Runs the hooks of session events, the session data is only locked to gather them
+pub(crate) fn run_hooks(
+    session_data: &RwLock<Option<SessionMetaData>>,
+    events: &[Value],
+    client_id: Option<ClientId>,
+) {
+    let hooks_to_run: Vec<HooksToRun> = session_data
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(|session_data| {
+            events
+                .iter()
+                .filter_map(|event| session_data.hooks_to_run(event, client_id))
+                .collect()
+        })
+        .unwrap_or_default();
+    for hooks in hooks_to_run {
+        hooks.run();
+    }
+}
+
 impl Drop for SessionMetaData {
     fn drop(&mut self) {
         let _ = self.senders.send_to_pty(PtyInstruction::Exit);
@@ -450,6 +689,9 @@ macro_rules! send_to_client {
 pub(crate) struct SessionState {
     clients: HashMap<ClientId, Option<Size>>,
     pipes: HashMap<String, ClientId>, // String => pipe_id
+    last_attached: Option<SystemTime>,
+    event_subscribers: HashSet<ClientId>,
+    read_only_clients: HashSet<ClientId>,
 }
 
 impl SessionState {
@@ -457,6 +699,9 @@ impl SessionState {
         SessionState {
             clients: HashMap::new(),
             pipes: HashMap::new(),
+            last_attached: None,
+            event_subscribers: HashSet::new(),
+            read_only_clients: HashSet::new(),
         }
     }
     pub fn new_client(&mut self) -> ClientId {
@@ -476,12 +721,48 @@ impl SessionState {
         self.pipes.insert(pipe_id, client_id);
     }
     pub fn remove_client(&mut self, client_id: ClientId) {
-        self.clients.remove(&client_id);
+        if let Some(Some(_size)) = self.clients.remove(&client_id) {
+            // the session was attached until now, which is what tells idle sessions apart
+            // (eg. `zellij delete-all-sessions --older-than`)
+            self.mark_attached();
+        }
         self.pipes.retain(|_p_id, c_id| c_id != &client_id);
+        self.event_subscribers.remove(&client_id);
+        // client ids are reused, so this must not outlive the client
+        self.read_only_clients.remove(&client_id);
+    }
+    pub fn set_client_read_only(&mut self, client_id: ClientId, read_only: bool) {
+        if read_only {
+            self.read_only_clients.insert(client_id);
+        } else {
+            self.read_only_clients.remove(&client_id);
+        }
+    }
+    pub fn client_is_read_only(&self, client_id: &ClientId) -> bool {
+        self.read_only_clients.contains(client_id)
+    }
+    pub fn read_only_clients(&self) -> HashSet<ClientId> {
+        self.read_only_clients.clone()
+    }
+    pub fn subscribe_to_events(&mut self, client_id: ClientId) {
+        self.event_subscribers.insert(client_id);
+    }
+    pub fn event_subscribers(&self) -> Vec<ClientId> {
+        self.event_subscribers.iter().copied().collect()
+    }
+    pub fn has_event_subscribers(&self) -> bool {
+        !self.event_subscribers.is_empty()
     }
     pub fn set_client_size(&mut self, client_id: ClientId, size: Size) {
         self.clients.insert(client_id, Some(size));
     }
+    pub fn mark_attached(&mut self) {
+        self.last_attached = Some(SystemTime::now());
+    }
+    pub fn attached_clients_count(&self) -> usize {
+        // clients without a size (eg. cli clients) are not attached to the session
+        self.clients.values().filter(|size| size.is_some()).count()
+    }
     pub fn min_client_terminal_size(&self) -> Option<Size> {
         // None if there are no client sizes
         let mut rows: Vec<usize> = self
@@ -509,6 +790,12 @@ impl SessionState {
     pub fn client_ids(&self) -> Vec<ClientId> {
         self.clients.keys().copied().collect()
     }
+    pub fn client_sizes(&self) -> HashMap<ClientId, Size> {
+        self.clients
+            .iter()
+            .filter_map(|(client_id, size)| size.map(|size| (*client_id, size)))
+            .collect()
+    }
     pub fn get_pipe(&self, pipe_name: &str) -> Option<ClientId> {
         self.pipes.get(pipe_name).copied()
     }
@@ -604,6 +891,10 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
             }
         });
 
+    // the events of the session that hooks can run on
+    let mut session_events = SessionEvents::default();
+    // whether the screen publishes its tab and pane updates (see below)
+    let mut session_events_have_listeners = false;
     loop {
         let (instruction, mut err_ctx) = server_receiver.recv().unwrap();
         err_ctx.add_call(ContextType::IPCServer((&instruction).into()));
@@ -650,6 +941,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .write()
                     .unwrap()
                     .set_client_size(client_id, client_attributes.size);
+                session_state.write().unwrap().mark_attached();
 
                 let default_shell = runtime_config_options.default_shell.map(|shell| {
                     TerminalAction::RunCommand(RunCommand {
@@ -735,6 +1027,17 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .senders
                     .send_to_plugin(PluginInstruction::AddClient(client_id))
                     .unwrap();
+                run_hooks(
+                    &session_data,
+                    &[
+                        record(
+                            "session-started",
+                            json!({ "session_name": envs::get_session_name().ok() }),
+                        ),
+                        record("client-attached", json!({ "client_id": client_id })),
+                    ],
+                    Some(client_id),
+                );
             },
             ServerInstruction::AttachClient(
                 attrs,
@@ -765,6 +1068,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .write()
                     .unwrap()
                     .set_client_size(client_id, attrs.size);
+                session_state.write().unwrap().mark_attached();
                 let min_size = session_state
                     .read()
                     .unwrap()
@@ -808,6 +1112,12 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         Event::ModeUpdate(mode_info),
                     )]))
                     .unwrap();
+                drop(rlock);
+                run_hooks(
+                    &session_data,
+                    &[record("client-attached", json!({ "client_id": client_id }))],
+                    Some(client_id),
+                );
             },
             ServerInstruction::UnblockInputThread => {
                 let client_ids = session_state.read().unwrap().client_ids();
@@ -900,6 +1210,14 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .senders
                     .send_to_plugin(PluginInstruction::RemoveClient(client_id))
                     .unwrap();
+                let hooks_to_run = session_data
+                    .write()
+                    .unwrap()
+                    .as_mut()
+                    .and_then(|session_data| session_data.detach_client(client_id));
+                if let Some(hooks) = hooks_to_run {
+                    hooks.run();
+                }
                 if !session_state.read().unwrap().active_clients_are_connected() {
                     *session_data.write().unwrap() = None;
                     let client_ids_to_cleanup: Vec<ClientId> = session_state
@@ -918,6 +1236,18 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
             },
             ServerInstruction::RemoveClient(client_id) => {
                 remove_client!(client_id, os_input, session_state);
+                let hooks_to_run =
+                    session_data
+                        .write()
+                        .unwrap()
+                        .as_mut()
+                        .and_then(|session_data| {
+                            session_data.raw_passthrough_clients.remove(&client_id);
+                            session_data.detach_client(client_id)
+                        });
+                if let Some(hooks) = hooks_to_run {
+                    hooks.run();
+                }
                 if let Some(min_size) = session_state.read().unwrap().min_client_terminal_size() {
                     session_data
                         .write()
@@ -1001,8 +1331,22 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         .senders
                         .send_to_plugin(PluginInstruction::RemoveClient(client_id))
                         .unwrap();
+                    let hooks_to_run = session_data
+                        .write()
+                        .unwrap()
+                        .as_mut()
+                        .and_then(|session_data| session_data.detach_client(client_id));
+                    if let Some(hooks) = hooks_to_run {
+                        hooks.run();
+                    }
                 }
             },
+            ServerInstruction::DetachIdleClient(client_id) => {
+                // the client is told why before it is detached like any other client
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::Exit(ExitReason::IdleDetached));
+                let _ = to_server.send(ServerInstruction::DetachSession(vec![client_id]));
+            },
             ServerInstruction::Render(serialized_output) => {
                 let client_ids = session_state.read().unwrap().client_ids();
                 // If `Some(_)`- unwrap it and forward it to the clients to render.
@@ -1043,6 +1387,142 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 let _ = os_input.send_to_client(client_id, ServerToClientMsg::Connected);
                 remove_client!(client_id, os_input, session_state);
             },
+            ServerInstruction::SignalPanes(signal, client_id) => {
+                if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                    session_data
+                        .senders
+                        .send_to_pty(PtyInstruction::SignalPanes(signal))
+                        .unwrap();
+                }
+                remove_client!(client_id, os_input, session_state);
+            },
+            ServerInstruction::QuerySessionStats(client_id) => {
+                match session_data.read().unwrap().as_ref() {
+                    Some(session_data) => {
+                        // the screen will report back with a SessionStats instruction
+                        session_data
+                            .senders
+                            .send_to_screen(ScreenInstruction::QuerySessionStats(client_id))
+                            .unwrap();
+                    },
+                    None => {
+                        let _ = os_input.send_to_client(
+                            client_id,
+                            ServerToClientMsg::SessionStats(SessionStats::default()),
+                        );
+                        remove_client!(client_id, os_input, session_state);
+                    },
+                }
+            },
+            ServerInstruction::SessionStats {
+                client_id,
+                tab_count,
+                pane_count,
+            } => {
+                let session_stats = {
+                    let session_state = session_state.read().unwrap();
+                    SessionStats {
+                        connected_clients: session_state.attached_clients_count(),
+                        tab_count,
+                        pane_count,
+                        last_attached: session_state.last_attached,
+                    }
+                };
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::SessionStats(session_stats));
+                remove_client!(client_id, os_input, session_state);
+            },
+            ServerInstruction::ToggleRawPassthrough(client_id) => {
+                if let Some(session_data) = session_data.write().unwrap().as_mut() {
+                    if !session_data.raw_passthrough_clients.remove(&client_id) {
+                        session_data.raw_passthrough_clients.insert(client_id);
+                    }
+                }
+            },
+            ServerInstruction::RefuseNewPanes(refused_new_panes) => {
+                if let Some(session_data) = session_data.write().unwrap().as_mut() {
+                    session_data.refused_new_panes = refused_new_panes;
+                }
+            },
+            ServerInstruction::CopyModeClients(copy_mode_clients) => {
+                if let Some(session_data) = session_data.write().unwrap().as_mut() {
+                    session_data.copy_mode_clients = copy_mode_clients;
+                }
+            },
+            ServerInstruction::LayoutFilesChanged(layout_file_path) => {
+                if let Some(session_data) = session_data.write().unwrap().as_mut() {
+                    session_data.reload_layout(&layout_file_path);
+                }
+            },
+            ServerInstruction::CapturedPaneOutput(client_id, output) => {
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::CapturedPaneOutput(output));
+            },
+            ServerInstruction::ClientsMetadata {
+                client_id,
+                clients_metadata,
+                default_editor,
+                json,
+            } => {
+                let client_sizes = session_state.read().unwrap().client_sizes();
+                let read_only_clients = session_state.read().unwrap().read_only_clients();
+                let clients = if json {
+                    ClientMetadata::render_many_as_json(
+                        clients_metadata,
+                        &client_sizes,
+                        &read_only_clients,
+                        &default_editor,
+                    )
+                } else {
+                    ClientMetadata::render_many(
+                        clients_metadata,
+                        &client_sizes,
+                        &read_only_clients,
+                        &default_editor,
+                    )
+                };
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::Log(vec![clients]),
+                    session_state
+                );
+            },
+            ServerInstruction::CapturedPaneExited(client_id, exit_status) => {
+                let _ = os_input.send_to_client(
+                    client_id,
+                    ServerToClientMsg::CapturedPaneExited(exit_status),
+                );
+            },
+            ServerInstruction::SubscribeToEvents(client_id) => {
+                session_state
+                    .write()
+                    .unwrap()
+                    .subscribe_to_events(client_id);
+                // so that the subscriber starts out with the current tabs and panes
+                if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                    session_data
+                        .senders
+                        .send_to_screen(ScreenInstruction::RequestStateUpdateForPlugins)
+                        .unwrap();
+                }
+            },
+            ServerInstruction::PublishEvent(event) => {
+                run_hooks(&session_data, &session_events.update(event.clone()), None);
+                let event_subscribers = session_state.read().unwrap().event_subscribers();
+                for client_id in event_subscribers {
+                    send_to_client!(
+                        client_id,
+                        os_input,
+                        ServerToClientMsg::Event(event.clone()),
+                        session_state
+                    );
+                }
+            },
+            ServerInstruction::StdinPipeReady(client_id, terminal_id) => {
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::StdinPipeReady(terminal_id));
+            },
             ServerInstruction::Log(lines_to_log, client_id) => {
                 send_to_client!(
                     client_id,
@@ -1243,11 +1723,36 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 }
             },
         }
+        // generating tab and pane updates for each state report is costly, so the screen only
+        // publishes them while there are hooks or clients following the session's events
+        let has_listeners = session_state.read().unwrap().has_event_subscribers()
+            || session_data
+                .read()
+                .unwrap()
+                .as_ref()
+                .map(|session_data| !session_data.hooks.is_empty())
+                .unwrap_or(false);
+        if has_listeners != session_events_have_listeners {
+            if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                session_events_have_listeners = has_listeners;
+                if has_listeners {
+                    // updates were not published in the meantime, so the previous state is stale
+                    session_events = SessionEvents::default();
+                }
+                let _ = session_data
+                    .senders
+                    .send_to_screen(ScreenInstruction::PublishSessionEvents(has_listeners));
+            }
+        }
     }
 
     // Drop cached session data before exit.
     *session_data.write().unwrap() = None;
 
+    // the socket moves along with the session if it was renamed
+    let socket_path = envs::get_session_name()
+        .map(|session_name| socket_path.with_file_name(session_name))
+        .unwrap_or(socket_path);
     drop(std::fs::remove_file(&socket_path));
 }
 
@@ -1257,6 +1762,17 @@ pub struct SessionOptions {
     pub layout: Box<Layout>,
 }
 
+fn session_tags(opts: &CliArgs) -> Vec<String> {
+    match &opts.command {
+        Some(Command::Sessions(Sessions::Attach { tag, .. })) => tag
+            .iter()
+            .map(|t| t.trim().to_owned())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        _ => vec![],
+    }
+}
+
 fn init_session(
     os_input: Box<dyn ServerOsApi>,
     to_server: SenderWithContext<ServerInstruction>,
@@ -1300,7 +1816,7 @@ fn init_session(
     let to_background_jobs = SenderWithContext::new(to_background_jobs);
 
     // Determine and initialize the data directory
-    let data_dir = opts.data_dir.unwrap_or_else(get_default_data_dir);
+    let data_dir = opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
 
     let capabilities = PluginCapabilities {
         arrow_fonts: config_options.simplified_ui.unwrap_or_default(),
@@ -1340,6 +1856,11 @@ fn init_session(
                 ),
                 opts.debug,
                 config_options.scrollback_editor.clone(),
+                config_options.new_pane_cwd.unwrap_or_default(),
+                config_options
+                    .default_cwd
+                    .clone()
+                    .or_else(|| std::env::current_dir().ok()),
             );
 
             move || pty_thread_main(pty, layout.clone()).fatal()
@@ -1366,6 +1887,7 @@ fn init_session(
             let debug = opts.debug;
             let layout = layout.clone();
             let config = config.clone();
+            let session_tags = session_tags(&opts);
             move || {
                 screen_thread_main(
                     screen_bus,
@@ -1374,12 +1896,19 @@ fn init_session(
                     config,
                     debug,
                     layout,
+                    session_tags,
                 )
                 .fatal();
             }
         })
         .unwrap();
 
+    let layout_watcher = Setup::layout_file_path(&opts, &config).and_then(|layout_file_path| {
+        watch_layout_files(to_server.clone(), layout_file_path)
+            .map_err(|e| log::error!("Failed to watch the layout file: {:?}", e))
+            .ok()
+    });
+
     let zellij_cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let plugin_thread = thread::Builder::new()
         .name("wasm".to_string())
@@ -1482,6 +2011,11 @@ fn init_session(
         layout,
         session_configuration: Default::default(),
         current_input_modes: HashMap::new(),
+        raw_passthrough_clients: HashSet::new(),
+        refused_new_panes: HashMap::new(),
+        copy_mode_clients: HashSet::new(),
+        hooks: config.hooks,
+        layout_watcher,
         screen_thread: Some(screen_thread),
         pty_thread: Some(pty_thread),
         plugin_thread: Some(plugin_thread),
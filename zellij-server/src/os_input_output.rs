@@ -1,8 +1,10 @@
 use crate::{panes::PaneId, ClientId};
 
-use async_std::{fs::File as AsyncFile, io::ReadExt, os::unix::io::FromRawFd};
+use async_std::{os::unix::io::FromRawFd, task};
 use interprocess::local_socket::LocalSocketStream;
 use nix::{
+    errno::Errno,
+    poll::{poll, PollFd, PollFlags},
     pty::{openpty, OpenptyResult, Winsize},
     sys::{
         signal::{kill, Signal},
@@ -21,7 +23,7 @@ use zellij_utils::{
     input::command::{RunCommand, TerminalAction},
     interprocess,
     ipc::{
-        ClientToServerMsg, ExitReason, IpcReceiverWithContext, IpcSenderWithContext,
+        ClientToServerMsg, ExitReason, IpcReceiverWithContext, IpcSenderWithContext, PaneSignal,
         ServerToClientMsg,
     },
     libc, nix,
@@ -155,6 +157,8 @@ fn handle_openpty(
     cmd: RunCommand,
     quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
     terminal_id: u32,
+    stdin_fd: Option<RawFd>,
+    session_env: &BTreeMap<String, Option<String>>,
 ) -> Result<(RawFd, RawFd)> {
     let err_context = |cmd: &RunCommand| {
         format!(
@@ -171,6 +175,15 @@ fn handle_openpty(
         let mut child = unsafe {
             let cmd = cmd.clone();
             let command = &mut Command::new(cmd.command);
+            // set with the set-session-env action, None removes a variable of the server's
+            command.envs(
+                session_env
+                    .iter()
+                    .filter_map(|(name, value)| value.as_ref().map(|value| (name, value))),
+            );
+            for (name, _) in session_env.iter().filter(|(_, value)| value.is_none()) {
+                command.env_remove(name);
+            }
             if let Some(current_dir) = cmd.cwd {
                 if current_dir.exists() && current_dir.is_dir() {
                     command.current_dir(current_dir);
@@ -188,6 +201,13 @@ fn handle_openpty(
                     if libc::login_tty(pid_secondary) != 0 {
                         panic!("failed to set controlling terminal");
                     }
+                    if let Some(stdin_fd) = stdin_fd {
+                        // a pipe rather than the pty, so that the piped bytes are not echoed or
+                        // interpreted (eg. as ctrl-c) by the line discipline
+                        if libc::dup2(stdin_fd, 0) == -1 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                    }
                     close_fds::close_open_fds(3, &[]);
                     Ok(())
                 })
@@ -197,6 +217,9 @@ fn handle_openpty(
 
         let child_id = child.id();
         std::thread::spawn(move || {
+            let mut cmd = cmd;
+            // STDIN is piped for this run only, running the command again makes it interactive
+            cmd.stdin_pipe = None;
             child.wait().with_context(|| err_context(&cmd)).fatal();
             let exit_status = handle_command_exit(child)
                 .with_context(|| err_context(&cmd))
@@ -224,18 +247,33 @@ fn handle_terminal(
     orig_termios: Option<termios::Termios>,
     quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
     terminal_id: u32,
+    stdin_fd: Option<RawFd>,
+    session_env: &BTreeMap<String, Option<String>>,
 ) -> Result<(RawFd, RawFd)> {
     let err_context = || "failed to spawn child terminal".to_string();
 
     // Create a pipe to allow the child the communicate the shell's pid to its
     // parent.
     match openpty(None, &orig_termios) {
-        Ok(open_pty_res) => handle_openpty(open_pty_res, cmd, quit_cb, terminal_id),
+        Ok(open_pty_res) => handle_openpty(
+            open_pty_res,
+            cmd,
+            quit_cb,
+            terminal_id,
+            stdin_fd,
+            session_env,
+        ),
         Err(e) => match failover_cmd {
-            Some(failover_cmd) => {
-                handle_terminal(failover_cmd, None, orig_termios, quit_cb, terminal_id)
-                    .with_context(err_context)
-            },
+            Some(failover_cmd) => handle_terminal(
+                failover_cmd,
+                None,
+                orig_termios,
+                quit_cb,
+                terminal_id,
+                stdin_fd,
+                session_env,
+            )
+            .with_context(err_context),
             None => Err::<(i32, i32), _>(e)
                 .context("failed to start pty")
                 .with_context(err_context)
@@ -283,6 +321,8 @@ fn spawn_terminal(
     quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit_status
     default_editor: Option<PathBuf>,
     terminal_id: u32,
+    stdin_fd: Option<RawFd>, // the reading end of a pipe to use as the command's STDIN
+    session_env: &BTreeMap<String, Option<String>>,
 ) -> Result<(RawFd, RawFd)> {
     // returns the terminal_id, the primary fd and the
     // secondary fd
@@ -351,7 +391,15 @@ fn spawn_terminal(
         None
     };
 
-    handle_terminal(cmd, failover_cmd, orig_termios, quit_cb, terminal_id)
+    handle_terminal(
+        cmd,
+        failover_cmd,
+        orig_termios,
+        quit_cb,
+        terminal_id,
+        stdin_fd,
+        session_env,
+    )
 }
 
 // The ClientSender is in charge of sending messages to the client on a special thread
@@ -427,6 +475,42 @@ pub struct ServerOsInputOutput {
     // a command pane with a
     // non-existing command)
     cached_resizes: Arc<Mutex<Option<BTreeMap<u32, (u16, u16, Option<u16>, Option<u16>)>>>>, // <terminal_id, (cols, rows, width_in_pixels, height_in_pixels)>
+    // kept between samples of the process stats, because the cpu usage of a process is measured
+    // since the previous one
+    process_stats_system_info: Arc<Mutex<System>>,
+    // terminal_id => the client piping the STDIN of its command and the writing end of the pipe
+    stdin_pipes: Arc<Mutex<HashMap<u32, (ClientId, Arc<File>)>>>,
+}
+
+/// The processes running in a terminal: the name of the one in the foreground, and the cpu and
+/// memory usage of all of them together
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessStats {
+    pub name: String,
+    pub cpu_usage: f32, // percent of one cpu, as in top
+    pub memory: u64,    // resident, in bytes
+}
+
+impl std::fmt::Display for ProcessStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 4] = ["B", "K", "M", "G"];
+        let mut memory = self.memory as f64;
+        let mut unit = 0;
+        while memory >= 1024.0 && unit < UNITS.len() - 1 {
+            memory /= 1024.0;
+            unit += 1;
+        }
+        let memory = if memory < 10.0 && unit > 0 {
+            format!("{:.1}{}", memory, UNITS[unit])
+        } else {
+            format!("{:.0}{}", memory, UNITS[unit])
+        };
+        write!(
+            f,
+            "{} | CPU {:.0}% | MEM {}",
+            self.name, self.cpu_usage, memory
+        )
+    }
 }
 
 // async fn in traits is not supported by rust, so dtolnay's excellent async_trait macro is being
@@ -438,22 +522,39 @@ pub trait AsyncReader: Send + Sync {
 
 /// An `AsyncReader` that wraps a `RawFd`
 struct RawFdAsyncReader {
-    fd: async_std::fs::File,
+    fd: RawFd,
 }
 
 impl RawFdAsyncReader {
     fn new(fd: RawFd) -> RawFdAsyncReader {
-        RawFdAsyncReader {
-            // The supplied `RawFd` is consumed by the created `RawFdAsyncReader`, closing it when dropped
-            fd: unsafe { AsyncFile::from_raw_fd(fd) },
-        }
+        // The supplied `RawFd` is consumed by the created `RawFdAsyncReader`, closing it when dropped
+        RawFdAsyncReader { fd }
+    }
+}
+
+impl Drop for RawFdAsyncReader {
+    fn drop(&mut self) {
+        let _ = unistd::close(self.fd);
     }
 }
 
 #[async_trait]
 impl AsyncReader for RawFdAsyncReader {
     async fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
-        self.fd.read(buf).await
+        // we only wait in the background for the fd to become readable and read from it here, so
+        // that a read we give up on (eg. to stop reading from a pane moved to another session)
+        // cannot swallow bytes that nobody will see
+        let fd = self.fd;
+        task::spawn_blocking(move || loop {
+            let mut poll_fds = [PollFd::new(fd, PollFlags::POLLIN)];
+            match poll(&mut poll_fds, -1) {
+                Err(Errno::EINTR) => continue,
+                result => return result.map(|_| ()),
+            }
+        })
+        .await
+        .map_err(|e| std::io::Error::from_raw_os_error(e as i32))?;
+        unistd::read(self.fd, buf).map_err(|e| std::io::Error::from_raw_os_error(e as i32))
     }
 }
 
@@ -476,11 +577,26 @@ pub trait ServerOsApi: Send + Sync {
         terminal_action: TerminalAction,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
         default_editor: Option<PathBuf>,
+        session_env: &BTreeMap<String, Option<String>>, // name => value, None removes the variable
     ) -> Result<(u32, RawFd, RawFd)>;
     // reserves a terminal id without actually opening a terminal
     fn reserve_terminal_id(&self) -> Result<u32> {
         unimplemented!()
     }
+    /// Stops using the pty of this terminal (eg. to send it to another session) and returns a
+    /// duplicate of its fd
+    fn release_terminal(&self, _terminal_id: u32) -> Result<RawFd> {
+        Err(anyhow!("moving panes between sessions is not supported"))
+    }
+    /// Starts using the pty of a terminal released with `release_terminal` again (eg. when it
+    /// could not be sent to another session)
+    fn restore_terminal(&self, _terminal_id: u32, _pty_fd: RawFd) -> Result<()> {
+        Err(anyhow!("moving panes between sessions is not supported"))
+    }
+    /// Starts using the pty of a terminal spawned by another session, returns its terminal id
+    fn adopt_terminal(&self, _pty_fd: RawFd) -> Result<u32> {
+        Err(anyhow!("moving panes between sessions is not supported"))
+    }
     /// Read bytes from the standard output of the virtual terminal referred to by `fd`.
     fn read_from_tty_stdout(&self, fd: RawFd, buf: &mut [u8]) -> Result<usize>;
     /// Creates an `AsyncReader` that can be used to read from `fd` in an async context
@@ -493,6 +609,10 @@ pub trait ServerOsApi: Send + Sync {
     fn kill(&self, pid: Pid) -> Result<()>;
     /// Terminate the process with process ID `pid`. (SIGKILL)
     fn force_kill(&self, pid: Pid) -> Result<()>;
+    /// Send `signal` to the process with process ID `pid` and to all of its descendants
+    fn signal_process_tree(&self, _pid: Pid, _signal: PaneSignal) -> Result<()> {
+        Ok(())
+    }
     /// Returns a [`Box`] pointer to this [`ServerOsApi`] struct.
     fn box_clone(&self) -> Box<dyn ServerOsApi>;
     fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> Result<()>;
@@ -513,6 +633,21 @@ pub trait ServerOsApi: Send + Sync {
     fn get_all_cmds_by_ppid(&self) -> HashMap<String, Vec<String>> {
         HashMap::new()
     }
+    /// Returns the name of the command in the foreground of each terminal, by terminal id
+    fn get_foreground_commands(&self) -> HashMap<u32, String> {
+        HashMap::new()
+    }
+    /// Returns the stats of the processes running in each terminal, by terminal id
+    fn get_process_stats(&self) -> HashMap<u32, ProcessStats> {
+        HashMap::new()
+    }
+    /// Returns the names of the processes running in these terminals, sorted and without
+    /// duplicates. Those are the ones started by the process spawned in each terminal (eg. the
+    /// programs run from its shell) and, for the terminals paired with true, that process itself
+    /// (eg. the command of a command pane).
+    fn get_running_process_names(&self, _terminals: Vec<(u32, bool)>) -> Vec<String> {
+        vec![]
+    }
     /// Writes the given buffer to a string
     fn write_to_file(&mut self, buf: String, file: Option<String>) -> Result<()>;
 
@@ -521,12 +656,70 @@ pub trait ServerOsApi: Send + Sync {
         terminal_id: u32,
         run_command: RunCommand,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(RawFd, RawFd)>;
     fn clear_terminal_id(&self, terminal_id: u32) -> Result<()>;
+    /// Writes to the STDIN of the command in this terminal, if this client pipes it (eg. `zellij
+    /// run --stdin`), blocking until the command has room for the bytes
+    fn write_to_stdin_pipe(
+        &self,
+        _terminal_id: u32,
+        _client_id: ClientId,
+        _buf: &[u8],
+    ) -> Result<()> {
+        Err(anyhow!("piping STDIN is not supported"))
+    }
+    /// Closes the STDIN of the command in this terminal if this client pipes it, so that the
+    /// command reads its end
+    fn close_stdin_pipe(&self, _terminal_id: u32, _client_id: ClientId) -> Result<()> {
+        Ok(())
+    }
     fn cache_resizes(&mut self) {}
     fn apply_cached_resizes(&mut self) {}
 }
 
+impl ServerOsInputOutput {
+    /// If the STDIN of this command is piped from a client, opens a pipe for it and returns the
+    /// end the command reads from
+    fn open_stdin_pipe(
+        &self,
+        terminal_id: u32,
+        terminal_action: &TerminalAction,
+    ) -> Result<Option<RawFd>> {
+        let err_context = || format!("failed to open STDIN pipe for terminal {terminal_id}");
+
+        let piping_client_id = match terminal_action {
+            TerminalAction::RunCommand(run_command) => run_command
+                .stdin_pipe
+                .as_ref()
+                .and_then(|stdin_pipe| stdin_pipe.client_id),
+            TerminalAction::OpenFile(..) => None,
+        };
+        let Some(client_id) = piping_client_id else {
+            return Ok(None);
+        };
+        let (read_fd, write_fd) = unistd::pipe().with_context(err_context)?;
+        let write_end = unsafe { File::from_raw_fd(write_fd) };
+        self.stdin_pipes
+            .lock()
+            .to_anyhow()
+            .with_context(err_context)?
+            .insert(terminal_id, (client_id, Arc::new(write_end)));
+        Ok(Some(read_fd))
+    }
+    /// The command has its own copy of the reading end once spawned
+    fn finish_opening_stdin_pipe(&self, terminal_id: u32, stdin_fd: Option<RawFd>, spawned: bool) {
+        if let Some(stdin_fd) = stdin_fd {
+            let _ = unistd::close(stdin_fd);
+            if !spawned {
+                if let Ok(mut stdin_pipes) = self.stdin_pipes.lock() {
+                    stdin_pipes.remove(&terminal_id);
+                }
+            }
+        }
+    }
+}
+
 impl ServerOsApi for ServerOsInputOutput {
     fn set_terminal_size_using_terminal_id(
         &self,
@@ -573,6 +766,7 @@ impl ServerOsApi for ServerOsInputOutput {
         terminal_action: TerminalAction,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
         default_editor: Option<PathBuf>,
+        session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(u32, RawFd, RawFd)> {
         let err_context = || "failed to spawn terminal".to_string();
 
@@ -599,21 +793,28 @@ impl ServerOsApi for ServerOsInputOutput {
                     .to_anyhow()
                     .with_context(err_context)?
                     .insert(terminal_id, None);
-                spawn_terminal(
+                let stdin_fd = self
+                    .open_stdin_pipe(terminal_id, &terminal_action)
+                    .with_context(err_context)?;
+                let spawned = spawn_terminal(
                     terminal_action,
                     orig_termios.clone(),
                     quit_cb,
                     default_editor,
                     terminal_id,
-                )
-                .and_then(|(pid_primary, pid_secondary)| {
-                    self.terminal_id_to_raw_fd
-                        .lock()
-                        .to_anyhow()?
-                        .insert(terminal_id, Some(pid_primary));
-                    Ok((terminal_id, pid_primary, pid_secondary))
-                })
-                .with_context(err_context)
+                    stdin_fd,
+                    session_env,
+                );
+                self.finish_opening_stdin_pipe(terminal_id, stdin_fd, spawned.is_ok());
+                spawned
+                    .and_then(|(pid_primary, pid_secondary)| {
+                        self.terminal_id_to_raw_fd
+                            .lock()
+                            .to_anyhow()?
+                            .insert(terminal_id, Some(pid_primary));
+                        Ok((terminal_id, pid_primary, pid_secondary))
+                    })
+                    .with_context(err_context)
             },
             None => Err(anyhow!("no more terminal IDs left to allocate")),
         }
@@ -645,6 +846,43 @@ impl ServerOsApi for ServerOsInputOutput {
             None => Err(anyhow!("no more terminal IDs available")),
         }
     }
+    fn release_terminal(&self, terminal_id: u32) -> Result<RawFd> {
+        let err_context = || format!("failed to release terminal {terminal_id}");
+
+        let mut terminal_id_to_raw_fd = self
+            .terminal_id_to_raw_fd
+            .lock()
+            .to_anyhow()
+            .with_context(err_context)?;
+        let pty_fd = terminal_id_to_raw_fd
+            .get(&terminal_id)
+            .copied()
+            .flatten()
+            .context("terminal has no pty")
+            .with_context(err_context)?;
+        let released_fd = unistd::dup(pty_fd).with_context(err_context)?;
+        // the id stays reserved: its process is still our child and its exit is still reported
+        // under it
+        terminal_id_to_raw_fd.insert(terminal_id, None);
+        Ok(released_fd)
+    }
+    fn restore_terminal(&self, terminal_id: u32, pty_fd: RawFd) -> Result<()> {
+        self.terminal_id_to_raw_fd
+            .lock()
+            .to_anyhow()
+            .with_context(|| format!("failed to restore terminal {terminal_id}"))?
+            .insert(terminal_id, Some(pty_fd));
+        Ok(())
+    }
+    fn adopt_terminal(&self, pty_fd: RawFd) -> Result<u32> {
+        let terminal_id = self.reserve_terminal_id()?;
+        self.terminal_id_to_raw_fd
+            .lock()
+            .to_anyhow()
+            .with_context(|| format!("failed to adopt pty in terminal {terminal_id}"))?
+            .insert(terminal_id, Some(pty_fd));
+        Ok(terminal_id)
+    }
     fn read_from_tty_stdout(&self, fd: RawFd, buf: &mut [u8]) -> Result<usize> {
         unistd::read(fd, buf).with_context(|| format!("failed to read stdout of raw FD {}", fd))
     }
@@ -690,6 +928,31 @@ impl ServerOsApi for ServerOsInputOutput {
         let _ = kill(pid, Some(Signal::SIGKILL));
         Ok(())
     }
+    fn signal_process_tree(&self, pid: Pid, signal: PaneSignal) -> Result<()> {
+        let signal = match signal {
+            PaneSignal::Term => Signal::SIGTERM,
+            PaneSignal::Int => Signal::SIGINT,
+            PaneSignal::Hup => Signal::SIGHUP,
+            PaneSignal::Kill => Signal::SIGKILL,
+        };
+        let mut system_info = System::new();
+        system_info.refresh_processes_specifics(ProcessRefreshKind::new());
+        let mut process_tree = vec![pid.as_raw()];
+        let mut i = 0;
+        while i < process_tree.len() {
+            let parent = process_tree[i];
+            for (child_pid, process) in system_info.processes() {
+                if process.parent() == Some(parent) && !process_tree.contains(child_pid) {
+                    process_tree.push(*child_pid);
+                }
+            }
+            i += 1;
+        }
+        for pid in process_tree {
+            let _ = kill(Pid::from_raw(pid), Some(signal));
+        }
+        Ok(())
+    }
     fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> Result<()> {
         let err_context = || format!("failed to send message to client {client_id}");
 
@@ -775,6 +1038,135 @@ impl ServerOsApi for ServerOsInputOutput {
 
         cwds
     }
+    fn get_foreground_commands(&self) -> HashMap<u32, String> {
+        let terminal_fds: Vec<(u32, RawFd)> = match self.terminal_id_to_raw_fd.lock() {
+            Ok(terminal_id_to_raw_fd) => terminal_id_to_raw_fd
+                .iter()
+                .filter_map(|(terminal_id, fd)| fd.map(|fd| (*terminal_id, fd)))
+                .collect(),
+            Err(_) => return HashMap::new(),
+        };
+        let mut system_info = System::new();
+        let mut commands = HashMap::new();
+        for (terminal_id, fd) in terminal_fds {
+            // the leader of the foreground process group is the shell or the command it runs
+            if let Ok(pid) = unistd::tcgetpgrp(fd) {
+                let is_found = system_info
+                    .refresh_process_specifics(pid.into(), ProcessRefreshKind::default());
+                if is_found {
+                    if let Some(process) = system_info.process(pid.into()) {
+                        commands.insert(terminal_id, process.name().to_owned());
+                    }
+                }
+            }
+        }
+        commands
+    }
+    fn get_process_stats(&self) -> HashMap<u32, ProcessStats> {
+        let terminal_fds: Vec<(u32, RawFd)> = match self.terminal_id_to_raw_fd.lock() {
+            Ok(terminal_id_to_raw_fd) => terminal_id_to_raw_fd
+                .iter()
+                .filter_map(|(terminal_id, fd)| fd.map(|fd| (*terminal_id, fd)))
+                .collect(),
+            Err(_) => return HashMap::new(),
+        };
+        let mut system_info = match self.process_stats_system_info.lock() {
+            Ok(system_info) => system_info,
+            Err(_) => return HashMap::new(),
+        };
+        system_info.refresh_processes_specifics(ProcessRefreshKind::new().with_cpu());
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for (pid, process) in system_info.processes() {
+            if let Some(parent) = process.parent() {
+                children.entry(parent).or_default().push(*pid);
+            }
+        }
+        let mut stats = HashMap::new();
+        for (terminal_id, fd) in terminal_fds {
+            let foreground_pid = match unistd::tcgetpgrp(fd) {
+                Ok(pid) => pid.as_raw(),
+                Err(_) => continue,
+            };
+            // the session leader is the process we spawned in the terminal (eg. the shell), all
+            // the others descend from it
+            let session_leader_pid = termios::tcgetsid(fd)
+                .map(|pid| pid.as_raw())
+                .unwrap_or(foreground_pid);
+            let name = match system_info.process(foreground_pid) {
+                Some(process) => process.name().to_owned(),
+                None => continue,
+            };
+            let mut cpu_usage = 0.0;
+            let mut memory = 0;
+            let mut process_tree = vec![session_leader_pid];
+            while let Some(pid) = process_tree.pop() {
+                if let Some(process) = system_info.process(pid) {
+                    cpu_usage += process.cpu_usage();
+                    memory += process.memory() * 1024; // sysinfo reports kilobytes
+                }
+                if let Some(child_pids) = children.get(&pid) {
+                    process_tree.extend(child_pids);
+                }
+            }
+            stats.insert(
+                terminal_id,
+                ProcessStats {
+                    name,
+                    cpu_usage,
+                    memory,
+                },
+            );
+        }
+        stats
+    }
+    fn get_running_process_names(&self, terminals: Vec<(u32, bool)>) -> Vec<String> {
+        let terminal_fds: Vec<(RawFd, bool)> = match self.terminal_id_to_raw_fd.lock() {
+            Ok(terminal_id_to_raw_fd) => terminals
+                .iter()
+                .filter_map(|(terminal_id, counts_spawned_process)| {
+                    let fd = terminal_id_to_raw_fd.get(terminal_id).copied().flatten()?;
+                    Some((fd, *counts_spawned_process))
+                })
+                .collect(),
+            Err(_) => return vec![],
+        };
+        if terminal_fds.is_empty() {
+            return vec![];
+        }
+        let mut system_info = System::new();
+        system_info.refresh_processes_specifics(ProcessRefreshKind::new());
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for (pid, process) in system_info.processes() {
+            if let Some(parent) = process.parent() {
+                children.entry(parent).or_default().push(*pid);
+            }
+        }
+        let mut names = BTreeSet::new();
+        for (fd, counts_spawned_process) in terminal_fds {
+            // the session leader is the process we spawned in the terminal
+            let session_leader_pid = match termios::tcgetsid(fd) {
+                Ok(pid) => pid.as_raw(),
+                Err(_) => continue,
+            };
+            let mut process_tree = if counts_spawned_process {
+                vec![session_leader_pid]
+            } else {
+                children
+                    .get(&session_leader_pid)
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            while let Some(pid) = process_tree.pop() {
+                if let Some(process) = system_info.process(pid) {
+                    names.insert(process.name().to_owned());
+                }
+                if let Some(child_pids) = children.get(&pid) {
+                    process_tree.extend(child_pids);
+                }
+            }
+        }
+        names.into_iter().collect()
+    }
     fn get_all_cmds_by_ppid(&self) -> HashMap<String, Vec<String>> {
         // the key is the stringified ppid
         let mut cmds = HashMap::new();
@@ -816,20 +1208,28 @@ impl ServerOsApi for ServerOsInputOutput {
         terminal_id: u32,
         run_command: RunCommand,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(RawFd, RawFd)> {
         let default_editor = None; // no need for a default editor when running an explicit command
-        self.orig_termios
+        let terminal_action = TerminalAction::RunCommand(run_command);
+        let stdin_fd = self.open_stdin_pipe(terminal_id, &terminal_action)?;
+        let spawned = self
+            .orig_termios
             .lock()
             .to_anyhow()
             .and_then(|orig_termios| {
                 spawn_terminal(
-                    TerminalAction::RunCommand(run_command),
+                    terminal_action,
                     orig_termios.clone(),
                     quit_cb,
                     default_editor,
                     terminal_id,
+                    stdin_fd,
+                    session_env,
                 )
-            })
+            });
+        self.finish_opening_stdin_pipe(terminal_id, stdin_fd, spawned.is_ok());
+        spawned
             .and_then(|(pid_primary, pid_secondary)| {
                 self.terminal_id_to_raw_fd
                     .lock()
@@ -845,6 +1245,42 @@ impl ServerOsApi for ServerOsInputOutput {
             .to_anyhow()
             .with_context(|| format!("failed to clear terminal ID {}", terminal_id))?
             .remove(&terminal_id);
+        self.stdin_pipes
+            .lock()
+            .to_anyhow()
+            .with_context(|| format!("failed to clear terminal ID {}", terminal_id))?
+            .remove(&terminal_id);
+        Ok(())
+    }
+    fn write_to_stdin_pipe(&self, terminal_id: u32, client_id: ClientId, buf: &[u8]) -> Result<()> {
+        let err_context = || format!("failed to write to piped STDIN of terminal {terminal_id}");
+
+        let pipe = self
+            .stdin_pipes
+            .lock()
+            .to_anyhow()
+            .with_context(err_context)?
+            .get(&terminal_id)
+            .filter(|(piping_client_id, _pipe)| *piping_client_id == client_id)
+            .map(|(_client_id, pipe)| pipe.clone())
+            .context("no STDIN piped from this client")
+            .with_context(err_context)?;
+        // the lock is released while writing, which blocks until the command reads
+        (&*pipe).write_all(buf).with_context(err_context)
+    }
+    fn close_stdin_pipe(&self, terminal_id: u32, client_id: ClientId) -> Result<()> {
+        let mut stdin_pipes =
+            self.stdin_pipes.lock().to_anyhow().with_context(|| {
+                format!("failed to close piped STDIN of terminal {terminal_id}")
+            })?;
+        if stdin_pipes
+            .get(&terminal_id)
+            .map(|(piping_client_id, _pipe)| *piping_client_id == client_id)
+            .unwrap_or(false)
+        {
+            // the command reads the end of its STDIN once no writing end is left open
+            stdin_pipes.remove(&terminal_id);
+        }
         Ok(())
     }
     fn cache_resizes(&mut self) {
@@ -887,6 +1323,8 @@ pub fn get_server_os_input() -> Result<ServerOsInputOutput, nix::Error> {
         client_senders: Arc::new(Mutex::new(HashMap::new())),
         terminal_id_to_raw_fd: Arc::new(Mutex::new(BTreeMap::new())),
         cached_resizes: Arc::new(Mutex::new(None)),
+        process_stats_system_info: Arc::new(Mutex::new(System::new())),
+        stdin_pipes: Arc::new(Mutex::new(HashMap::new())),
     })
 }
 
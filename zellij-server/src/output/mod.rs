@@ -4,6 +4,7 @@ use crate::panes::selection::Selection;
 use crate::panes::Row;
 
 use crate::{
+    panes::kitty_graphics::remove_kitty_graphics_commands,
     panes::sixel::SixelImageStore,
     panes::terminal_character::{AnsiCode, CharacterStyles},
     panes::{LinkHandler, TerminalCharacter, DEFAULT_STYLES, EMPTY_TERMINAL_CHARACTER},
@@ -17,6 +18,7 @@ use std::{
     str,
 };
 use zellij_utils::errors::prelude::*;
+use zellij_utils::ipc::ColorSupport;
 use zellij_utils::pane_size::PaneGeom;
 use zellij_utils::pane_size::SizeInPixels;
 
@@ -61,12 +63,14 @@ fn write_changed_styles(
     current_character_styles: CharacterStyles,
     chunk_changed_colors: Option<[Option<AnsiCode>; 256]>,
     link_handler: Option<&std::cell::Ref<LinkHandler>>,
+    color_support: ColorSupport,
     vte_output: &mut String,
 ) -> Result<()> {
     let err_context = "failed to format changed styles to VTE string";
 
-    if let Some(new_styles) =
-        character_styles.update_and_return_diff(&current_character_styles, chunk_changed_colors)
+    if let Some(new_styles) = character_styles
+        .update_and_return_diff(&current_character_styles, chunk_changed_colors)
+        .map(|new_styles| new_styles.downgrade_colors(color_support))
     {
         if let Some(osc8_link) =
             link_handler.and_then(|l_h| l_h.output_osc8(new_styles.link_anchor))
@@ -89,6 +93,7 @@ fn serialize_chunks_with_newlines(
 
     let mut vte_output = String::new();
     let link_handler = link_handler.map(|l_h| l_h.borrow());
+    let color_support = ColorSupport::TrueColor;
     for character_chunk in character_chunks {
         let chunk_changed_colors = character_chunk.changed_colors();
         let mut character_styles = DEFAULT_STYLES.enable_styled_underlines(styled_underlines);
@@ -106,11 +111,13 @@ fn serialize_chunks_with_newlines(
                 current_character_styles,
                 chunk_changed_colors,
                 link_handler.as_ref(),
+                color_support,
                 &mut vte_output,
             )
             .with_context(err_context)?;
             chunk_width += t_character.width();
             vte_output.push(t_character.character);
+            t_character.push_grapheme_cluster_tail(&mut vte_output);
         }
     }
     Ok(vte_output)
@@ -121,6 +128,7 @@ fn serialize_chunks(
     link_handler: Option<&mut Rc<RefCell<LinkHandler>>>,
     sixel_image_store: Option<&mut SixelImageStore>,
     styled_underlines: bool,
+    color_support: ColorSupport,
 ) -> Result<String> {
     let err_context = || "failed to serialize input chunks".to_string();
 
@@ -145,11 +153,13 @@ fn serialize_chunks(
                 current_character_styles,
                 chunk_changed_colors,
                 link_handler.as_ref(),
+                color_support,
                 &mut vte_output,
             )
             .with_context(err_context)?;
             chunk_width += t_character.width();
             vte_output.push(t_character.character);
+            t_character.push_grapheme_cluster_tail(&mut vte_output);
         }
     }
     if let Some(sixel_image_store) = sixel_image_store {
@@ -246,6 +256,10 @@ pub struct Output {
     character_cell_size: Rc<RefCell<Option<SizeInPixels>>>,
     floating_panes_stack: Option<FloatingPanesStack>,
     styled_underlines: bool,
+    bells_are_muted: bool,
+    clients_without_sixel_support: HashSet<ClientId>,
+    clients_with_kitty_graphics_support: HashSet<ClientId>,
+    client_color_support: HashMap<ClientId, ColorSupport>,
 }
 
 impl Output {
@@ -261,6 +275,30 @@ impl Output {
             ..Default::default()
         }
     }
+    pub fn mute_bells(&mut self, should_mute: bool) {
+        self.bells_are_muted = should_mute;
+    }
+    pub fn bells_are_muted(&self) -> bool {
+        self.bells_are_muted
+    }
+    /// The terminals of these clients cannot display sixel images, so we leave them out of their
+    /// output (the text around the images is still rendered)
+    pub fn omit_sixel_images_for_clients(&mut self, client_ids: HashSet<ClientId>) {
+        self.clients_without_sixel_support = client_ids;
+    }
+    /// The colors the terminals of these clients can display, the colors of their output are
+    /// downgraded to these (clients not in here get the colors as they are)
+    pub fn downgrade_colors_for_clients(
+        &mut self,
+        client_color_support: HashMap<ClientId, ColorSupport>,
+    ) {
+        self.client_color_support = client_color_support;
+    }
+    /// Only the terminals of these clients reported that they support the kitty graphics
+    /// protocol, the protocol's commands are left out of the output of all others
+    pub fn send_kitty_graphics_to_clients(&mut self, client_ids: HashSet<ClientId>) {
+        self.clients_with_kitty_graphics_support = client_ids;
+    }
     pub fn add_clients(
         &mut self,
         client_ids: &HashSet<ClientId>,
@@ -403,24 +441,41 @@ impl Output {
 
         for (client_id, client_character_chunks) in self.client_character_chunks.drain() {
             let mut client_serialized_render_instructions = String::new();
+            let supports_kitty_graphics = self
+                .clients_with_kitty_graphics_support
+                .contains(&client_id);
 
             // append pre-vte instructions for this client
             if let Some(pre_vte_instructions_for_client) =
                 self.pre_vte_instructions.remove(&client_id)
             {
                 for vte_instruction in pre_vte_instructions_for_client {
-                    client_serialized_render_instructions.push_str(&vte_instruction);
+                    if supports_kitty_graphics {
+                        client_serialized_render_instructions.push_str(&vte_instruction);
+                    } else {
+                        client_serialized_render_instructions
+                            .push_str(&remove_kitty_graphics_commands(&vte_instruction));
+                    }
                 }
             }
 
             // append the actual vte
+            let sixel_chunks = if self.clients_without_sixel_support.contains(&client_id) {
+                None
+            } else {
+                self.sixel_chunks.get(&client_id)
+            };
             client_serialized_render_instructions.push_str(
                 &serialize_chunks(
                     client_character_chunks,
-                    self.sixel_chunks.get(&client_id),
+                    sixel_chunks,
                     self.link_handler.as_mut(),
                     Some(&mut self.sixel_image_store.borrow_mut()),
                     self.styled_underlines,
+                    self.client_color_support
+                        .get(&client_id)
+                        .copied()
+                        .unwrap_or_default(),
                 )
                 .with_context(err_context)?,
             ); // TODO: less allocations?
@@ -430,7 +485,12 @@ impl Output {
                 self.post_vte_instructions.remove(&client_id)
             {
                 for vte_instruction in post_vte_instructions_for_client {
-                    client_serialized_render_instructions.push_str(&vte_instruction);
+                    if supports_kitty_graphics {
+                        client_serialized_render_instructions.push_str(&vte_instruction);
+                    } else {
+                        client_serialized_render_instructions
+                            .push_str(&remove_kitty_graphics_commands(&vte_instruction));
+                    }
                 }
             }
 
@@ -455,6 +515,19 @@ impl Output {
             .map(|s| s.cursor_is_visible(cursor_x, cursor_y))
             .unwrap_or(true)
     }
+    /// The geometries of the floating panes above the given z-index
+    pub fn areas_covering(&self, z_index: Option<usize>) -> Vec<PaneGeom> {
+        self.floating_panes_stack
+            .as_ref()
+            .map(|s| {
+                s.layers
+                    .iter()
+                    .skip(z_index.unwrap_or(0))
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 // this struct represents the geometry of a group of floating panes
@@ -1060,3 +1133,7 @@ impl OutputBuffer {
         changed_rects
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/output_tests.rs"]
+mod output_tests;
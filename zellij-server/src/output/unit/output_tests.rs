@@ -0,0 +1,42 @@
+use super::*;
+
+fn red_text_for_clients(client_ids: &[ClientId]) -> Output {
+    let mut output = Output::default();
+    let client_ids: HashSet<ClientId> = client_ids.iter().copied().collect();
+    output.add_clients(
+        &client_ids,
+        Rc::new(RefCell::new(LinkHandler::default())),
+        None,
+    );
+    let red = DEFAULT_STYLES.foreground(Some(AnsiCode::RgbCode((250, 10, 10))));
+    let characters = "red"
+        .chars()
+        .map(|character| TerminalCharacter::new_styled(character, red.into()))
+        .collect();
+    output
+        .add_character_chunks_to_multiple_clients(
+            vec![CharacterChunk::new(characters, 0, 0)],
+            client_ids.into_iter(),
+            None,
+        )
+        .unwrap();
+    output
+}
+
+#[test]
+fn colors_are_downgraded_for_each_client_to_what_its_terminal_supports() {
+    let mut output = red_text_for_clients(&[1, 2, 3]);
+    output.downgrade_colors_for_clients(HashMap::from([
+        (2, ColorSupport::Colors256),
+        (3, ColorSupport::Colors16),
+    ]));
+    let serialized = output.serialize().unwrap();
+    assert!(
+        serialized[&1].contains("\u{1b}[38;2;250;10;10m"),
+        "clients that did not report their color support get the colors as they are"
+    );
+    assert!(serialized[&2].contains("\u{1b}[38;5;196m"));
+    assert!(!serialized[&2].contains("38;2;"));
+    assert!(serialized[&3].contains("\u{1b}[91m"));
+    assert!(!serialized[&3].contains("38;2;") && !serialized[&3].contains("38;5;"));
+}
@@ -0,0 +1,119 @@
+//! Writes the output of a command pane to a file (eg. `zellij run --log-output build.log`),
+//! rotating it once it grows beyond its configured size. Also used for the output of panes logged
+//! with `zellij action toggle-pane-logging`.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use zellij_utils::{errors::prelude::*, input::command::OutputLog, strip_ansi_escapes};
+
+/// Counts the bytes that actually reach the file, which is less than what we were given when
+/// stripping ANSI escapes
+struct CountingFile {
+    file: File,
+    bytes_written: Arc<AtomicU64>,
+}
+
+impl Write for CountingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.bytes_written
+            .fetch_add(written as u64, Ordering::SeqCst);
+        Ok(written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+pub(crate) struct OutputLogger {
+    output_log: OutputLog,
+    writer: Box<dyn Write + Send>,
+    bytes_written: Arc<AtomicU64>,
+}
+
+impl OutputLogger {
+    pub fn new(output_log: OutputLog) -> Result<Self> {
+        let bytes_written = Arc::new(AtomicU64::new(0));
+        let writer = open_log_file(&output_log, bytes_written.clone())?;
+        Ok(OutputLogger {
+            output_log,
+            writer,
+            bytes_written,
+        })
+    }
+    pub fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        let err_context = || format!("failed to write to {}", self.output_log.path.display());
+        if let Some(max_size) = self.output_log.max_size {
+            if self.bytes_written.load(Ordering::SeqCst) >= max_size {
+                self.rotate().with_context(err_context)?;
+            }
+        }
+        self.writer.write_all(bytes).with_context(err_context)?;
+        self.writer.flush().with_context(err_context)?;
+        Ok(())
+    }
+    pub fn path(&self) -> &Path {
+        &self.output_log.path
+    }
+    fn rotate(&mut self) -> Result<()> {
+        let path = &self.output_log.path;
+        let max_files = self.output_log.max_files;
+        if max_files > 0 {
+            // build.log.1 -> build.log.2, etc. - the oldest one is overwritten
+            for index in (1..max_files).rev() {
+                let from = rotated_path(path, index);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(path, index + 1))?;
+                }
+            }
+            fs::rename(path, rotated_path(path, 1))?;
+        } else {
+            fs::remove_file(path)?;
+        }
+        self.bytes_written.store(0, Ordering::SeqCst);
+        self.writer = open_log_file(&self.output_log, self.bytes_written.clone())?;
+        Ok(())
+    }
+}
+
+fn open_log_file(
+    output_log: &OutputLog,
+    bytes_written: Arc<AtomicU64>,
+) -> Result<Box<dyn Write + Send>> {
+    let path = &output_log.path;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory for {}", path.display()))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let existing_size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    bytes_written.store(existing_size, Ordering::SeqCst);
+    let file = CountingFile {
+        file,
+        bytes_written,
+    };
+    if output_log.strip_ansi {
+        Ok(Box::new(strip_ansi_escapes::Writer::new(file)))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated_path = path.as_os_str().to_owned();
+    rotated_path.push(format!(".{}", index));
+    PathBuf::from(rotated_path)
+}
+
+#[cfg(test)]
+#[path = "./unit/output_log_tests.rs"]
+mod output_log_tests;
@@ -0,0 +1,99 @@
+//! What is left of the terminal panes recently closed in a tab (their scrollback, command and
+//! position), so that the last one can be opened again where it was.
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use zellij_utils::input::layout::Run;
+use zellij_utils::pane_size::PaneGeom;
+
+const MAX_CLOSED_PANES: usize = 10;
+const CLOSED_PANE_TTL: Duration = Duration::from_secs(5 * 60);
+/// Only this many lines of scrollback are kept for a closed pane
+pub const CLOSED_PANE_SCROLLBACK_LINES: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct ClosedPane {
+    pub terminal_id: u32,
+    pub tab_index: usize,
+    pub name: Option<String>, // if the user renamed it
+    pub invoked_with: Option<Run>,
+    pub scrollback: Option<String>,
+    pub geom: PaneGeom,
+    pub is_floating: bool,
+    pub closed_at: Instant,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClosedPanes {
+    panes: VecDeque<ClosedPane>, // oldest first
+}
+
+impl ClosedPanes {
+    pub fn push(&mut self, closed_pane: ClosedPane) {
+        self.panes.push_back(closed_pane);
+        if self.panes.len() > MAX_CLOSED_PANES {
+            self.panes.pop_front();
+        }
+    }
+    /// The most recently closed pane, unless it was closed too long ago to be restored
+    pub fn take_newest(&mut self, now: Instant) -> Option<ClosedPane> {
+        self.panes
+            .retain(|closed_pane| now.duration_since(closed_pane.closed_at) < CLOSED_PANE_TTL);
+        self.panes.pop_back()
+    }
+}
+
+/// The cwds of the terminals recently closed, kept by the pty thread for as long as their
+/// `ClosedPane` might still be restored
+#[derive(Debug, Default)]
+pub struct ClosedTerminalCwds {
+    cwds: VecDeque<(u32, PathBuf)>, // terminal_id, oldest first
+}
+
+impl ClosedTerminalCwds {
+    pub fn insert(&mut self, terminal_id: u32, cwd: PathBuf) {
+        self.cwds.push_back((terminal_id, cwd));
+        if self.cwds.len() > MAX_CLOSED_PANES {
+            self.cwds.pop_front();
+        }
+    }
+    pub fn take(&mut self, terminal_id: u32) -> Option<PathBuf> {
+        let index = self.cwds.iter().position(|(id, _)| *id == terminal_id)?;
+        self.cwds.remove(index).map(|(_, cwd)| cwd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn closed_pane(terminal_id: u32, closed_at: Instant) -> ClosedPane {
+        ClosedPane {
+            terminal_id,
+            tab_index: 0,
+            name: None,
+            invoked_with: None,
+            scrollback: None,
+            geom: PaneGeom::default(),
+            is_floating: false,
+            closed_at,
+        }
+    }
+
+    #[test]
+    fn the_newest_closed_pane_is_restored_first_until_it_expires() {
+        let now = Instant::now();
+        let mut closed_panes = ClosedPanes::default();
+        for terminal_id in 0..MAX_CLOSED_PANES as u32 + 2 {
+            closed_panes.push(closed_pane(terminal_id, now));
+        }
+        let newest = closed_panes.take_newest(now).unwrap();
+        assert_eq!(newest.terminal_id, MAX_CLOSED_PANES as u32 + 1);
+        assert_eq!(
+            closed_panes.take_newest(now).unwrap().terminal_id,
+            MAX_CLOSED_PANES as u32
+        );
+        assert!(closed_panes.take_newest(now + CLOSED_PANE_TTL).is_none());
+    }
+}
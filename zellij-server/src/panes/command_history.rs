@@ -0,0 +1,359 @@
+//! The commands a command pane ran, and a small picker (drawn in the alternate screen of the pane
+//! once its command exited) to run one of them again, or to edit its arguments before doing so.
+//!
+//! In the picker, `Up`/`Down` (or `k`/`j`) select a command, `Enter` runs it, `e` edits it and
+//! `Esc` (or `q`) closes the picker. While editing, `Enter` runs the edited command and `Esc`
+//! goes back to the list.
+use std::path::PathBuf;
+
+use unicode_width::UnicodeWidthChar;
+use zellij_utils::data::{BareKey, KeyModifier, KeyWithModifier, Style};
+use zellij_utils::input::command::RunCommand;
+
+use crate::panes::terminal_character::{AnsiCode, RESET_STYLES};
+
+const MAX_COMMAND_HISTORY: usize = 50;
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    commands: Vec<RunCommand>, // oldest first
+}
+
+impl CommandHistory {
+    /// Running a command again moves it to the top of the history rather than adding it twice
+    pub fn record(&mut self, run_command: &RunCommand) {
+        self.commands.retain(|command| {
+            command.command != run_command.command || command.args != run_command.args
+        });
+        self.commands.push(run_command.clone());
+        if self.commands.len() > MAX_COMMAND_HISTORY {
+            self.commands.remove(0);
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+    /// Newest first, as they are listed in the picker
+    pub fn get(&self, index: usize) -> Option<&RunCommand> {
+        self.commands.iter().rev().nth(index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandHistoryOutcome {
+    Continue,
+    Run(RunCommand),
+    Close,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistoryPicker {
+    selected: usize,
+    editing: Option<String>, // the command line being edited
+}
+
+impl CommandHistoryPicker {
+    pub fn handle_key(
+        &mut self,
+        key: &KeyWithModifier,
+        history: &CommandHistory,
+    ) -> CommandHistoryOutcome {
+        if let Some(line) = self.editing.as_mut() {
+            match key.bare_key {
+                BareKey::Enter if key.key_modifiers.is_empty() => {
+                    let edited = history
+                        .get(self.selected)
+                        .and_then(|run_command| with_command_line(run_command, line));
+                    self.editing = None;
+                    // an empty line only stops editing
+                    if let Some(run_command) = edited {
+                        return CommandHistoryOutcome::Run(run_command);
+                    }
+                },
+                BareKey::Esc => self.editing = None,
+                BareKey::Backspace => {
+                    line.pop();
+                },
+                BareKey::Char(character) => {
+                    if key.key_modifiers.is_empty() {
+                        line.push(character);
+                    } else if key.key_modifiers.len() == 1
+                        && key.key_modifiers.contains(&KeyModifier::Shift)
+                    {
+                        line.extend(character.to_uppercase());
+                    }
+                },
+                _ => {},
+            }
+            return CommandHistoryOutcome::Continue;
+        }
+        if !key.key_modifiers.is_empty() {
+            return CommandHistoryOutcome::Continue;
+        }
+        match key.bare_key {
+            BareKey::Up | BareKey::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            },
+            BareKey::Down | BareKey::Char('j') => {
+                self.selected = (self.selected + 1).min(history.len().saturating_sub(1));
+            },
+            BareKey::Enter => {
+                return match history.get(self.selected) {
+                    Some(run_command) => CommandHistoryOutcome::Run(run_command.clone()),
+                    None => CommandHistoryOutcome::Close,
+                };
+            },
+            BareKey::Char('e') => {
+                self.editing = history.get(self.selected).map(command_line);
+            },
+            BareKey::Esc | BareKey::Char('q') => return CommandHistoryOutcome::Close,
+            _ => {},
+        }
+        CommandHistoryOutcome::Continue
+    }
+    /// The picker as ANSI, to be written to the (cleared) alternate screen of the pane
+    pub fn render(
+        &self,
+        history: &CommandHistory,
+        columns: usize,
+        rows: usize,
+        style: &Style,
+    ) -> String {
+        let bold_text = RESET_STYLES.bold(Some(AnsiCode::On));
+        let selected_text = RESET_STYLES
+            .foreground(Some(AnsiCode::from(
+                style.colors.text_unselected.emphasis_2,
+            )))
+            .bold(Some(AnsiCode::On));
+        let controls_color = RESET_STYLES
+            .foreground(Some(AnsiCode::from(
+                style.colors.text_unselected.emphasis_0,
+            )))
+            .bold(Some(AnsiCode::On));
+        let mut rendered = format!(
+            "\u{1b}[?25l\u{1b}[2J\u{1b}[H{}{}{}",
+            bold_text,
+            truncate("Command history", columns),
+            RESET_STYLES
+        );
+        // the title, an empty line and the controls
+        let list_rows = rows.saturating_sub(3).max(1);
+        let first_listed = (self.selected + 1).saturating_sub(list_rows);
+        for index in first_listed..history.len().min(first_listed + list_rows) {
+            let line = history.get(index).map(command_line).unwrap_or_default();
+            let row = index - first_listed + 2;
+            if index == self.selected {
+                let marked_line = format!("> {}", line);
+                rendered.push_str(&format!(
+                    "\u{1b}[{};1H{}{}{}",
+                    row,
+                    selected_text,
+                    truncate(&marked_line, columns),
+                    RESET_STYLES
+                ));
+            } else {
+                let line = format!("  {}", line);
+                rendered.push_str(&format!("\u{1b}[{};1H{}", row, truncate(&line, columns)));
+            }
+        }
+        let controls = match &self.editing {
+            Some(line) => format!(
+                "{}<{}ENTER{}{}> run, <{}ESC{}{}> cancel: {}{}",
+                bold_text,
+                controls_color,
+                RESET_STYLES,
+                bold_text,
+                controls_color,
+                RESET_STYLES,
+                bold_text,
+                RESET_STYLES,
+                // we keep the end of the line in view, it is where the cursor is
+                truncate_start(line, columns.saturating_sub(27)),
+            ),
+            None => format!(
+                "{}<{}↓↑{}{}> select, <{}ENTER{}{}> run, <{}e{}{}> edit, <{}ESC{}{}> close{}",
+                bold_text,
+                controls_color,
+                RESET_STYLES,
+                bold_text,
+                controls_color,
+                RESET_STYLES,
+                bold_text,
+                controls_color,
+                RESET_STYLES,
+                bold_text,
+                controls_color,
+                RESET_STYLES,
+                bold_text,
+                RESET_STYLES,
+            ),
+        };
+        rendered.push_str(&format!("\u{1b}[{};1H{}", rows.max(1), controls));
+        rendered
+    }
+}
+
+/// The command and its arguments as a line that can be edited, quoting the arguments that would
+/// otherwise be split
+pub fn command_line(run_command: &RunCommand) -> String {
+    std::iter::once(run_command.command.to_string_lossy().to_string())
+        .chain(run_command.args.iter().cloned())
+        .map(|word| quote(&word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The command with the command and arguments of this line, keeping everything else (eg. its
+/// cwd)
+pub fn with_command_line(run_command: &RunCommand, line: &str) -> Option<RunCommand> {
+    let mut words = split_words(line).into_iter();
+    let command = words.next()?;
+    let mut edited = run_command.clone();
+    edited.command = PathBuf::from(command);
+    edited.args = words.collect();
+    Some(edited)
+}
+
+fn quote(word: &str) -> String {
+    let needs_quotes = word.is_empty()
+        || word
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\'' || c == '\\');
+    if needs_quotes {
+        format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        word.to_owned()
+    }
+}
+
+/// Splits a line into words as a shell would, with single and double quotes and backslash escapes
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current_word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut characters = line.chars();
+    while let Some(character) = characters.next() {
+        match (quote, character) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), _) => current_word.get_or_insert_with(String::new).push(character),
+            (_, '\\') => {
+                let word = current_word.get_or_insert_with(String::new);
+                if let Some(escaped) = characters.next() {
+                    word.push(escaped);
+                }
+            },
+            (Some(_), _) => current_word.get_or_insert_with(String::new).push(character),
+            (None, '\'') | (None, '"') => {
+                quote = Some(character);
+                current_word.get_or_insert_with(String::new);
+            },
+            (None, c) if c.is_whitespace() => words.extend(current_word.take()),
+            (None, _) => current_word.get_or_insert_with(String::new).push(character),
+        }
+    }
+    words.extend(current_word.take());
+    words
+}
+
+fn truncate(text: &str, columns: usize) -> String {
+    let mut width = 0;
+    text.chars()
+        .take_while(|c| {
+            width += c.width().unwrap_or(0);
+            width <= columns
+        })
+        .collect()
+}
+
+fn truncate_start(text: &str, columns: usize) -> String {
+    let mut width = 0;
+    let mut kept: Vec<char> = text
+        .chars()
+        .rev()
+        .take_while(|c| {
+            width += c.width().unwrap_or(0);
+            width <= columns
+        })
+        .collect();
+    kept.reverse();
+    kept.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_command(command: &str, args: &[&str]) -> RunCommand {
+        RunCommand {
+            command: PathBuf::from(command),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            cwd: Some(PathBuf::from("/tmp")),
+            ..Default::default()
+        }
+    }
+
+    fn key(bare_key: BareKey) -> KeyWithModifier {
+        KeyWithModifier::new(bare_key)
+    }
+
+    #[test]
+    fn running_a_command_again_moves_it_to_the_top_of_the_history() {
+        let mut history = CommandHistory::default();
+        history.record(&run_command("cargo", &["build"]));
+        history.record(&run_command("cargo", &["test"]));
+        history.record(&run_command("cargo", &["build"]));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.get(0).unwrap().args, vec!["build".to_owned()]);
+        assert_eq!(history.get(1).unwrap().args, vec!["test".to_owned()]);
+    }
+
+    #[test]
+    fn command_lines_are_split_back_into_the_same_arguments() {
+        let command = run_command("grep", &["-r", "two words", "say \"hi\"", ""]);
+        let line = command_line(&command);
+        assert_eq!(line, r#"grep -r "two words" "say \"hi\"" """#);
+        assert_eq!(with_command_line(&command, &line).unwrap(), command);
+        assert_eq!(
+            split_words("ls  'a b'\\ c \"d\"e"),
+            vec!["ls".to_owned(), "a b c".to_owned(), "de".to_owned()]
+        );
+        assert!(with_command_line(&command, "   ").is_none());
+    }
+
+    #[test]
+    fn picker_runs_the_selected_command_or_an_edited_one() {
+        let mut history = CommandHistory::default();
+        history.record(&run_command("make", &["all"]));
+        history.record(&run_command("make", &["test"]));
+
+        let mut picker = CommandHistoryPicker::default();
+        assert_eq!(
+            picker.handle_key(&key(BareKey::Down), &history),
+            CommandHistoryOutcome::Continue
+        );
+        assert_eq!(
+            picker.handle_key(&key(BareKey::Enter), &history),
+            CommandHistoryOutcome::Run(run_command("make", &["all"]))
+        );
+
+        let mut picker = CommandHistoryPicker::default();
+        picker.handle_key(&key(BareKey::Char('e')), &history);
+        for _ in 0.."test".len() {
+            picker.handle_key(&key(BareKey::Backspace), &history);
+        }
+        for character in "clean -j 2".chars() {
+            picker.handle_key(&key(BareKey::Char(character)), &history);
+        }
+        assert_eq!(
+            picker.handle_key(&key(BareKey::Enter), &history),
+            CommandHistoryOutcome::Run(run_command("make", &["clean", "-j", "2"]))
+        );
+        assert_eq!(
+            picker.handle_key(&key(BareKey::Esc), &history),
+            CommandHistoryOutcome::Close
+        );
+    }
+}
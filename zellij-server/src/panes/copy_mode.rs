@@ -0,0 +1,530 @@
+//! Keyboard selection in scroll mode, with a subset of vim's visual mode: `v` and `Ctrl-v` start a
+//! selection (`Ctrl-v` a rectangular one), `hjkl`, `w`/`W`, `b`/`B`, `e`/`E`, `0`, `^`, `$` and
+//! `f`/`F`/`t`/`T` (repeated with `;` and `,`) move the cursor, `iw`/`aw`/`iW`/`aW` select a word,
+//! `y` copies the selection and `q` or `Esc` leave.
+//!
+//! Lines are counted from the top of the scrollback, so that positions are stable while scrolling.
+
+const CTRL_V: char = '\u{16}';
+const ESC: char = '\u{1b}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct CopyModePosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl CopyModePosition {
+    pub fn new(line: usize, column: usize) -> Self {
+        CopyModePosition { line, column }
+    }
+}
+
+/// The text the cursor moves over, one `char` per column
+pub trait CopyModeLines {
+    fn line_count(&self) -> usize;
+    fn line(&self, index: usize) -> Vec<char>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyModeOutcome {
+    Continue,
+    Copy,
+    Exit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PendingKey {
+    Find(char),       // f, F, t or T, waiting for the character to find
+    TextObject(char), // i or a, waiting for the object (w or W)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Punctuation,
+    Keyword,
+}
+
+#[derive(Debug, Clone)]
+pub struct CopyMode {
+    cursor: CopyModePosition,
+    wanted_column: usize, // kept when moving through shorter lines, as in vim
+    anchor: Option<CopyModePosition>,
+    block: bool,
+    pending: Option<PendingKey>,
+    last_find: Option<(char, char)>, // (f, F, t or T, the character found)
+}
+
+impl CopyMode {
+    pub fn new(cursor: CopyModePosition) -> Self {
+        CopyMode {
+            cursor,
+            wanted_column: cursor.column,
+            anchor: None,
+            block: false,
+            pending: None,
+            last_find: None,
+        }
+    }
+    pub fn cursor(&self) -> CopyModePosition {
+        self.cursor
+    }
+    /// The selected area as (start, end, is_block), the end column is exclusive - without a
+    /// selection, this is the cell under the cursor
+    pub fn selection(&self) -> (CopyModePosition, CopyModePosition, bool) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        if self.block && self.anchor.is_some() {
+            let start = CopyModePosition::new(
+                anchor.line.min(self.cursor.line),
+                anchor.column.min(self.cursor.column),
+            );
+            let end = CopyModePosition::new(
+                anchor.line.max(self.cursor.line),
+                anchor.column.max(self.cursor.column) + 1,
+            );
+            (start, end, true)
+        } else {
+            let start = anchor.min(self.cursor);
+            let end = anchor.max(self.cursor);
+            (
+                start,
+                CopyModePosition::new(end.line, end.column + 1),
+                false,
+            )
+        }
+    }
+    pub fn has_selection(&self) -> bool {
+        self.anchor.is_some()
+    }
+    pub fn handle_key(&mut self, key: char, lines: &impl CopyModeLines) -> CopyModeOutcome {
+        if lines.line_count() == 0 {
+            return CopyModeOutcome::Exit;
+        }
+        match self.pending.take() {
+            Some(PendingKey::Find(kind)) => {
+                if key != ESC {
+                    self.last_find = Some((kind, key));
+                    self.find(kind, key, lines);
+                }
+                return CopyModeOutcome::Continue;
+            },
+            Some(PendingKey::TextObject(kind)) => {
+                match key {
+                    'w' => self.select_word(kind == 'a', false, lines),
+                    'W' => self.select_word(kind == 'a', true, lines),
+                    _ => {},
+                }
+                return CopyModeOutcome::Continue;
+            },
+            None => {},
+        }
+        match key {
+            'h' => self.move_to_column(self.cursor.column.saturating_sub(1)),
+            'l' => {
+                let last_column = last_column(&lines.line(self.cursor.line));
+                self.move_to_column((self.cursor.column + 1).min(last_column));
+            },
+            'j' => self.move_to_line((self.cursor.line + 1).min(lines.line_count() - 1), lines),
+            'k' => self.move_to_line(self.cursor.line.saturating_sub(1), lines),
+            'w' | 'W' => self.move_to(next_word_start(self.cursor, key == 'W', lines)),
+            'b' | 'B' => self.move_to(previous_word_start(self.cursor, key == 'B', lines)),
+            'e' | 'E' => self.move_to(word_end(self.cursor, key == 'E', lines)),
+            '0' => self.move_to_column(0),
+            '^' => {
+                let line = lines.line(self.cursor.line);
+                let first_non_blank = line.iter().position(|c| !c.is_whitespace());
+                self.move_to_column(first_non_blank.unwrap_or(0));
+            },
+            '$' => self.move_to_column(last_column(&lines.line(self.cursor.line))),
+            'f' | 'F' | 't' | 'T' => self.pending = Some(PendingKey::Find(key)),
+            ';' | ',' => {
+                if let Some((kind, character)) = self.last_find {
+                    let kind = if key == ',' {
+                        reversed_find(kind)
+                    } else {
+                        kind
+                    };
+                    self.find(kind, character, lines);
+                }
+            },
+            'i' | 'a' => self.pending = Some(PendingKey::TextObject(key)),
+            'v' => self.toggle_selection(false),
+            CTRL_V => self.toggle_selection(true),
+            'y' if self.anchor.is_some() => return CopyModeOutcome::Copy,
+            'q' | ESC => return CopyModeOutcome::Exit,
+            _ => {},
+        }
+        CopyModeOutcome::Continue
+    }
+    fn toggle_selection(&mut self, block: bool) {
+        if self.anchor.is_some() && self.block == block {
+            self.anchor = None;
+        } else {
+            self.anchor = self.anchor.or(Some(self.cursor));
+        }
+        self.block = block;
+    }
+    fn move_to(&mut self, position: CopyModePosition) {
+        self.cursor = position;
+        self.wanted_column = position.column;
+    }
+    fn move_to_column(&mut self, column: usize) {
+        self.move_to(CopyModePosition::new(self.cursor.line, column));
+    }
+    fn move_to_line(&mut self, line: usize, lines: &impl CopyModeLines) {
+        let column = self.wanted_column.min(last_column(&lines.line(line)));
+        self.cursor = CopyModePosition::new(line, column);
+    }
+    fn find(&mut self, kind: char, character: char, lines: &impl CopyModeLines) {
+        let line = lines.line(self.cursor.line);
+        let column = self.cursor.column;
+        let found = match kind {
+            // t and T skip an adjacent match, so that repeating them moves on to the next one
+            'f' | 't' => {
+                let skip = if kind == 't' { 2 } else { 1 };
+                line.iter()
+                    .enumerate()
+                    .skip(column + skip)
+                    .find(|(_, c)| **c == character)
+                    .map(|(i, _)| if kind == 't' { i - 1 } else { i })
+            },
+            _ => {
+                let skip = if kind == 'T' { 2 } else { 1 };
+                line.iter()
+                    .enumerate()
+                    .take(column.saturating_sub(skip - 1))
+                    .rev()
+                    .find(|(_, c)| **c == character)
+                    .map(|(i, _)| if kind == 'T' { i + 1 } else { i })
+            },
+        };
+        if let Some(found) = found {
+            self.move_to_column(found);
+        }
+    }
+    fn select_word(&mut self, around: bool, big: bool, lines: &impl CopyModeLines) {
+        let line = lines.line(self.cursor.line);
+        if line.is_empty() {
+            return;
+        }
+        let column = self.cursor.column.min(line.len() - 1);
+        let class = char_class(line[column], big);
+        let mut start = column;
+        while start > 0 && char_class(line[start - 1], big) == class {
+            start -= 1;
+        }
+        let mut end = column;
+        while end + 1 < line.len() && char_class(line[end + 1], big) == class {
+            end += 1;
+        }
+        if around && class != CharClass::Blank {
+            // the trailing blanks, or the leading ones if there are none
+            let mut trailing_end = end;
+            while trailing_end + 1 < line.len() && line[trailing_end + 1].is_whitespace() {
+                trailing_end += 1;
+            }
+            if trailing_end > end {
+                end = trailing_end;
+            } else {
+                while start > 0 && line[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+            }
+        }
+        if !self.block || self.anchor.is_none() {
+            self.block = false;
+        }
+        self.anchor = Some(CopyModePosition::new(self.cursor.line, start));
+        self.move_to_column(end);
+    }
+}
+
+fn reversed_find(kind: char) -> char {
+    match kind {
+        'f' => 'F',
+        'F' => 'f',
+        't' => 'T',
+        _ => 't',
+    }
+}
+
+fn last_column(line: &[char]) -> usize {
+    line.len().saturating_sub(1)
+}
+
+fn char_class(character: char, big: bool) -> CharClass {
+    if character.is_whitespace() {
+        CharClass::Blank
+    } else if big || character.is_alphanumeric() || character == '_' {
+        CharClass::Keyword
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+// Motions walk over the text as if every line ended with a newline, which is the column after
+// its last character (the only column of an empty line)
+struct Walker<'a, L: CopyModeLines> {
+    lines: &'a L,
+    line_index: usize,
+    line: Vec<char>,
+}
+
+impl<'a, L: CopyModeLines> Walker<'a, L> {
+    fn new(lines: &'a L, line_index: usize) -> Self {
+        Walker {
+            lines,
+            line_index,
+            line: lines.line(line_index),
+        }
+    }
+    fn char_at(&self, column: usize) -> char {
+        self.line.get(column).copied().unwrap_or('\n')
+    }
+    fn class_at(&self, column: usize, big: bool) -> CharClass {
+        char_class(self.char_at(column), big)
+    }
+    fn is_empty_line(&self) -> bool {
+        self.line.is_empty()
+    }
+    fn next(&mut self, column: usize) -> Option<usize> {
+        if column < self.line.len() {
+            Some(column + 1)
+        } else if self.line_index + 1 < self.lines.line_count() {
+            self.line_index += 1;
+            self.line = self.lines.line(self.line_index);
+            Some(0)
+        } else {
+            None
+        }
+    }
+    fn previous(&mut self, column: usize) -> Option<usize> {
+        if column > 0 {
+            Some(column - 1)
+        } else if self.line_index > 0 {
+            self.line_index -= 1;
+            self.line = self.lines.line(self.line_index);
+            Some(self.line.len())
+        } else {
+            None
+        }
+    }
+    fn position(&self, column: usize) -> CopyModePosition {
+        // never land on the newline of a line that has characters
+        CopyModePosition::new(self.line_index, column.min(last_column(&self.line)))
+    }
+}
+
+fn next_word_start(
+    cursor: CopyModePosition,
+    big: bool,
+    lines: &impl CopyModeLines,
+) -> CopyModePosition {
+    let mut walker = Walker::new(lines, cursor.line);
+    let mut column = cursor.column;
+    let class = walker.class_at(column, big);
+    if class != CharClass::Blank {
+        loop {
+            match walker.next(column) {
+                Some(next) => {
+                    column = next;
+                    if walker.line_index != cursor.line || walker.class_at(column, big) != class {
+                        break;
+                    }
+                },
+                None => return walker.position(column),
+            }
+        }
+    }
+    while walker.class_at(column, big) == CharClass::Blank {
+        if walker.is_empty_line() && walker.line_index != cursor.line {
+            break;
+        }
+        match walker.next(column) {
+            Some(next) => column = next,
+            None => break,
+        }
+    }
+    walker.position(column)
+}
+
+fn previous_word_start(
+    cursor: CopyModePosition,
+    big: bool,
+    lines: &impl CopyModeLines,
+) -> CopyModePosition {
+    let mut walker = Walker::new(lines, cursor.line);
+    let mut column = match walker.previous(cursor.column) {
+        Some(previous) => previous,
+        None => return cursor,
+    };
+    while walker.class_at(column, big) == CharClass::Blank {
+        if walker.is_empty_line() && walker.line_index != cursor.line {
+            return walker.position(column);
+        }
+        match walker.previous(column) {
+            Some(previous) => column = previous,
+            None => return walker.position(column),
+        }
+    }
+    let class = walker.class_at(column, big);
+    while column > 0 && walker.class_at(column - 1, big) == class {
+        column -= 1;
+    }
+    walker.position(column)
+}
+
+fn word_end(cursor: CopyModePosition, big: bool, lines: &impl CopyModeLines) -> CopyModePosition {
+    let mut walker = Walker::new(lines, cursor.line);
+    let mut column = match walker.next(cursor.column) {
+        Some(next) => next,
+        None => return cursor,
+    };
+    while walker.class_at(column, big) == CharClass::Blank {
+        match walker.next(column) {
+            Some(next) => column = next,
+            None => return walker.position(column),
+        }
+    }
+    let class = walker.class_at(column, big);
+    while column + 1 < walker.line.len() && walker.class_at(column + 1, big) == class {
+        column += 1;
+    }
+    walker.position(column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl CopyModeLines for Vec<&str> {
+        fn line_count(&self) -> usize {
+            self.len()
+        }
+        fn line(&self, index: usize) -> Vec<char> {
+            self[index].chars().collect()
+        }
+    }
+
+    fn press(copy_mode: &mut CopyMode, keys: &str, lines: &Vec<&str>) -> CopyModeOutcome {
+        let mut outcome = CopyModeOutcome::Continue;
+        for key in keys.chars() {
+            outcome = copy_mode.handle_key(key, lines);
+        }
+        outcome
+    }
+
+    fn cursor_after(keys: &str, lines: &Vec<&str>) -> (usize, usize) {
+        let mut copy_mode = CopyMode::new(CopyModePosition::default());
+        press(&mut copy_mode, keys, lines);
+        (copy_mode.cursor().line, copy_mode.cursor().column)
+    }
+
+    #[test]
+    fn word_motions() {
+        let lines = vec!["foo.bar baz", "", "  qux"];
+        assert_eq!(cursor_after("w", &lines), (0, 3), "punctuation is a word");
+        assert_eq!(
+            cursor_after("W", &lines),
+            (0, 8),
+            "WORDs are separated by blanks"
+        );
+        assert_eq!(cursor_after("WW", &lines), (1, 0), "stops at empty lines");
+        assert_eq!(cursor_after("WWW", &lines), (2, 2));
+        assert_eq!(cursor_after("e", &lines), (0, 2));
+        assert_eq!(cursor_after("E", &lines), (0, 6));
+        assert_eq!(cursor_after("EE", &lines), (0, 10));
+        assert_eq!(cursor_after("EEE", &lines), (2, 4), "skips empty lines");
+        assert_eq!(cursor_after("WWWb", &lines), (1, 0));
+        assert_eq!(cursor_after("$B", &lines), (0, 8));
+        assert_eq!(cursor_after("$bb", &lines), (0, 4));
+    }
+
+    #[test]
+    fn find_motions() {
+        let lines = vec!["a,b,c,d"];
+        assert_eq!(cursor_after("f,", &lines), (0, 1));
+        assert_eq!(cursor_after("f,;", &lines), (0, 3));
+        assert_eq!(
+            cursor_after("t,", &lines),
+            (0, 2),
+            "adjacent match is skipped"
+        );
+        assert_eq!(cursor_after("t,;", &lines), (0, 4));
+        assert_eq!(cursor_after("$F,", &lines), (0, 5));
+        assert_eq!(cursor_after("$T,", &lines), (0, 4));
+        assert_eq!(cursor_after("$f,", &lines), (0, 6), "no match, no move");
+        assert_eq!(
+            cursor_after("f,;,", &lines),
+            (0, 1),
+            "comma repeats backwards"
+        );
+    }
+
+    #[test]
+    fn vertical_motions_keep_the_wanted_column() {
+        let lines = vec!["long line", "ab", "another long line"];
+        assert_eq!(cursor_after("$j", &lines), (1, 1));
+        assert_eq!(cursor_after("$jj", &lines), (2, 8));
+    }
+
+    #[test]
+    fn select_inner_and_around_word() {
+        let lines = vec!["one two  three"];
+        let mut copy_mode = CopyMode::new(CopyModePosition::new(0, 5));
+        press(&mut copy_mode, "viw", &lines);
+        assert_eq!(
+            copy_mode.selection(),
+            (
+                CopyModePosition::new(0, 4),
+                CopyModePosition::new(0, 7),
+                false
+            )
+        );
+        press(&mut copy_mode, "aw", &lines);
+        assert_eq!(
+            copy_mode.selection(),
+            (
+                CopyModePosition::new(0, 4),
+                CopyModePosition::new(0, 9),
+                false
+            ),
+            "with the trailing blanks"
+        );
+        assert_eq!(press(&mut copy_mode, "y", &lines), CopyModeOutcome::Copy);
+    }
+
+    #[test]
+    fn block_selection() {
+        let lines = vec!["a 1 x", "b 22 y", "c 333 z"];
+        let mut copy_mode = CopyMode::new(CopyModePosition::new(0, 2));
+        press(&mut copy_mode, "\u{16}jj", &lines);
+        assert_eq!(
+            copy_mode.selection(),
+            (
+                CopyModePosition::new(0, 2),
+                CopyModePosition::new(2, 3),
+                true
+            )
+        );
+        press(&mut copy_mode, "v", &lines);
+        assert_eq!(
+            copy_mode.selection(),
+            (
+                CopyModePosition::new(0, 2),
+                CopyModePosition::new(2, 3),
+                false
+            ),
+            "switched to a character selection"
+        );
+        press(&mut copy_mode, "v", &lines);
+        assert!(!copy_mode.has_selection());
+        assert_eq!(
+            press(&mut copy_mode, "y", &lines),
+            CopyModeOutcome::Continue,
+            "nothing to copy"
+        );
+        assert_eq!(press(&mut copy_mode, "q", &lines), CopyModeOutcome::Exit);
+    }
+}
@@ -5,7 +5,7 @@ use zellij_utils::{
 };
 
 use crate::resize_pty;
-use crate::tab::{pane_info_for_pane, Pane};
+use crate::tab::{pane_info_for_pane, Pane, MIN_TERMINAL_HEIGHT, MIN_TERMINAL_WIDTH};
 use floating_pane_grid::FloatingPaneGrid;
 
 use crate::{
@@ -326,6 +326,9 @@ impl FloatingPanes {
             pane.render_full_viewport();
         }
     }
+    pub fn set_session_is_mirrored(&mut self, session_is_mirrored: bool) {
+        self.session_is_mirrored = session_is_mirrored;
+    }
     pub fn set_pane_frames(&mut self) -> Result<()> {
         let err_context =
             |pane_id: &PaneId| format!("failed to activate frame on pane {pane_id:?}");
@@ -343,7 +346,7 @@ impl FloatingPanes {
         }
         Ok(())
     }
-    pub fn render(&mut self, output: &mut Output) -> Result<()> {
+    pub fn render(&mut self, output: &mut Output, input_is_synced: bool) -> Result<()> {
         let err_context = || "failed to render output";
         let connected_clients: Vec<ClientId> =
             { self.connected_clients.borrow().iter().copied().collect() };
@@ -393,6 +396,7 @@ impl FloatingPanes {
                 false,
                 false,
                 true,
+                input_is_synced,
             );
             for client_id in &connected_clients {
                 let client_mode = self
@@ -997,6 +1001,69 @@ impl FloatingPanes {
         };
         false
     }
+    /// Moves the given edges of a pane to the mouse position, as when dragging its frame
+    pub fn move_pane_edges_to(
+        &mut self,
+        pane_id: PaneId,
+        edges: &[Direction],
+        position: &Position,
+    ) -> Result<bool> {
+        // true => changed size
+        let err_context = || format!("failed to move edges of pane {pane_id:?}");
+        let viewport = *self.viewport.borrow();
+        let Some(pane) = self.panes.get_mut(&pane_id) else {
+            return Ok(false);
+        };
+        let mut geom = pane.position_and_size();
+        let column = position.column();
+        let line = position.line().max(0) as usize;
+        let (mut left, mut right) = (geom.x, geom.x + geom.cols.as_usize());
+        let (mut top, mut bottom) = (geom.y, geom.y + geom.rows.as_usize());
+        for edge in edges {
+            match edge {
+                Direction::Left => {
+                    left = column
+                        .min(right.saturating_sub(MIN_TERMINAL_WIDTH))
+                        .max(viewport.x)
+                },
+                Direction::Right => {
+                    right = (column + 1)
+                        .max(left + MIN_TERMINAL_WIDTH)
+                        .min(viewport.x + viewport.cols)
+                },
+                Direction::Up => {
+                    top = line
+                        .min(bottom.saturating_sub(MIN_TERMINAL_HEIGHT))
+                        .max(viewport.y)
+                },
+                Direction::Down => {
+                    bottom = (line + 1)
+                        .max(top + MIN_TERMINAL_HEIGHT)
+                        .min(viewport.y + viewport.rows)
+                },
+            }
+        }
+        if (left, right, top, bottom)
+            == (
+                geom.x,
+                geom.x + geom.cols.as_usize(),
+                geom.y,
+                geom.y + geom.rows.as_usize(),
+            )
+        {
+            return Ok(false);
+        }
+        geom.x = left;
+        geom.cols.set_inner(right - left);
+        geom.y = top;
+        geom.rows.set_inner(bottom - top);
+        pane.set_geom(geom);
+        resize_pty!(pane, os_api, self.senders, self.character_cell_size)
+            .with_context(err_context)?;
+        self.desired_pane_positions.insert(pane_id, geom);
+        self.set_force_render();
+        Ok(true)
+    }
     pub fn stop_moving_pane_with_mouse(&mut self, position: Position) {
         if self.pane_being_moved_with_mouse.is_some() {
             self.move_pane_to_position(&position);
@@ -1,3 +1,5 @@
+use super::kitty_graphics::KittyGraphics;
+use super::scrollback_spill::{ScrollbackSpill, SPILL_CHUNK_LINES};
 use super::sixel::{PixelRect, SixelGrid, SixelImageStore};
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -11,32 +13,69 @@ use std::{
     cmp::Ordering,
     collections::{BTreeSet, VecDeque},
     fmt::{self, Debug, Formatter},
+    path::PathBuf,
     str,
+    sync::OnceLock,
 };
 
 use zellij_utils::{
     consts::{DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE},
     data::{Palette, PaletteColor, Styling},
-    input::mouse::{MouseEvent, MouseEventType},
-    pane_size::SizeInPixels,
-    position::Position,
+    input::{
+        layout::ScrollbackSize,
+        mouse::{MouseEvent, MouseEventType},
+        options::{AmbiguousWidth, Clipboard, Options},
+    },
+    pane_size::{PaneGeom, SizeInPixels},
+    position::{Column, Line, Position},
     vte,
 };
 
 const TABSTOP_WIDTH: usize = 8; // TODO: is this always right?
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+/// Whether the character continues an emoji sequence after a zero width joiner: the emoji and the
+/// (narrow) symbols of eg. the gender or profession sequences, other characters are not joined
+fn is_emoji_joined_by_zero_width_joiner(c: char) -> bool {
+    UnicodeWidthChar::width(c) == Some(2) || ('\u{2190}'..='\u{2BFF}').contains(&c)
+}
+
+/// How the widths of the characters printed to panes are counted (the ambiguous_width and
+/// grapheme_clusters options)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnicodeWidth {
+    pub ambiguous_is_wide: bool,
+    // emoji sequences take the columns of their first emoji, as most terminal emulators display them
+    pub grapheme_clusters: bool,
+}
+
+impl UnicodeWidth {
+    pub fn from_options(options: &Options) -> Self {
+        UnicodeWidth {
+            ambiguous_is_wide: options.ambiguous_width == Some(AmbiguousWidth::Wide),
+            grapheme_clusters: options.grapheme_clusters.unwrap_or(false),
+        }
+    }
+}
 pub const MAX_TITLE_STACK_SIZE: usize = 1000;
+pub const DEFAULT_WORD_SEPARATORS: &str = "[]{}<>()";
 
+use sysinfo::{System, SystemExt};
+use unicode_width::UnicodeWidthChar;
+use url::Url;
 use vte::{Params, Perform};
 use zellij_utils::{consts::VERSION, shared::version_number};
 
 use crate::output::{CharacterChunk, OutputBuffer, SixelImageChunk};
 use crate::panes::alacritty_functions::{parse_number, xparse_color};
+use crate::panes::copy_mode::{CopyMode, CopyModeLines, CopyModeOutcome, CopyModePosition};
 use crate::panes::link_handler::LinkHandler;
 use crate::panes::search::SearchResult;
 use crate::panes::selection::Selection;
 use crate::panes::terminal_character::{
     AnsiCode, CharsetIndex, Cursor, CursorShape, RcCharacterStyles, StandardCharset,
-    TerminalCharacter, EMPTY_TERMINAL_CHARACTER,
+    TerminalCharacter, EMPTY_TERMINAL_CHARACTER, RESET_STYLES,
 };
 use crate::ui::components::UiComponentParser;
 
@@ -122,6 +161,7 @@ fn transfer_rows_from_lines_above_to_viewport(
     sixel_grid: &mut SixelGrid,
     count: usize,
     max_viewport_width: usize,
+    max_lines_above: usize,
 ) -> usize {
     let mut next_lines: Vec<Row> = vec![];
     let mut lines_added_to_viewport: isize = 0;
@@ -151,7 +191,7 @@ fn transfer_rows_from_lines_above_to_viewport(
     }
     if !next_lines.is_empty() {
         let excess_row = Row::from_rows(next_lines);
-        bounded_push(lines_above, sixel_grid, excess_row);
+        bounded_push(lines_above, sixel_grid, excess_row, max_lines_above);
     }
     match usize::try_from(lines_added_to_viewport) {
         Ok(n) => n,
@@ -165,6 +205,7 @@ fn transfer_rows_from_viewport_to_lines_above(
     sixel_grid: &mut SixelGrid,
     count: usize,
     max_viewport_width: usize,
+    max_lines_above: usize,
 ) -> isize {
     let mut transferred_rows_count: isize = 0;
     let drained_lines = std::cmp::min(count, viewport.len());
@@ -178,7 +219,12 @@ fn transfer_rows_from_viewport_to_lines_above(
             next_lines.append(&mut bottom_canonical_row_and_wraps_in_dst);
         }
         next_lines.push(next_line);
-        let dropped_line_width = bounded_push(lines_above, sixel_grid, Row::from_rows(next_lines));
+        let dropped_line_width = bounded_push(
+            lines_above,
+            sixel_grid,
+            Row::from_rows(next_lines),
+            max_lines_above,
+        );
         if let Some(width) = dropped_line_width {
             transferred_rows_count -=
                 calculate_row_display_height(width, max_viewport_width) as isize;
@@ -227,9 +273,14 @@ fn transfer_rows_from_lines_below_to_viewport(
     }
 }
 
-fn bounded_push(vec: &mut VecDeque<Row>, sixel_grid: &mut SixelGrid, value: Row) -> Option<usize> {
+fn bounded_push(
+    vec: &mut VecDeque<Row>,
+    sixel_grid: &mut SixelGrid,
+    value: Row,
+    max_lines: usize,
+) -> Option<usize> {
     let mut dropped_line_width = None;
-    if vec.len() >= *SCROLL_BUFFER_SIZE.get().unwrap() {
+    if vec.len() >= max_lines {
         let line = vec.pop_front();
         if let Some(line) = line {
             sixel_grid.offset_grid_top();
@@ -253,6 +304,60 @@ pub fn create_horizontal_tabstops(columns: usize) -> BTreeSet<usize> {
     horizontal_tabstops
 }
 
+/// Parse the `file://host/path` url of an OSC 7 sequence, cwds reported from other hosts (eg. over
+/// ssh) are ignored
+fn parse_reported_cwd(url: &str) -> Option<PathBuf> {
+    static LOCAL_HOST_NAME: OnceLock<Option<String>> = OnceLock::new();
+    let mut url = Url::parse(url).ok()?;
+    if url.scheme() != "file" {
+        return None;
+    }
+    let is_local_host = match url.host_str() {
+        None | Some("") | Some("localhost") => true,
+        Some(host) => {
+            LOCAL_HOST_NAME
+                .get_or_init(|| System::new().host_name())
+                .as_deref()
+                == Some(host)
+        },
+    };
+    if !is_local_host {
+        return None;
+    }
+    url.set_host(None).ok()?;
+    url.to_file_path().ok()
+}
+
+/// A port declared (or undeclared) by a program in a pane, see `zellij action declare-port`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortDeclaration {
+    Declared(u16, Option<String>), // port, name
+    Undeclared(u16),
+}
+
+/// Parse the parameters of a `zellij` OSC sequence, eg. `declare-port=8080;name=api` or
+/// `undeclare-port=8080`
+fn parse_port_declaration(params: &[&[u8]]) -> Option<PortDeclaration> {
+    let mut port_declaration = None;
+    let mut name = None;
+    for param in params.iter().flat_map(|p| str::from_utf8(p)) {
+        match param.split_once('=') {
+            Some(("declare-port", port)) => {
+                port_declaration = Some((port.parse().ok()?, true));
+            },
+            Some(("undeclare-port", port)) => {
+                port_declaration = Some((port.parse().ok()?, false));
+            },
+            Some(("name", n)) if !n.is_empty() => name = Some(n.to_owned()),
+            _ => {},
+        }
+    }
+    match port_declaration? {
+        (port, true) => Some(PortDeclaration::Declared(port, name)),
+        (port, false) => Some(PortDeclaration::Undeclared(port)),
+    }
+}
+
 fn calculate_row_display_height(row_width: usize, viewport_width: usize) -> usize {
     if row_width <= viewport_width {
         return 1;
@@ -288,6 +393,49 @@ macro_rules! dump_screen {
     }};
 }
 
+// Like dump_screen!, but keeping the colors and other styles of the characters as SGR sequences
+fn dump_lines_with_styles<'a>(
+    lines: impl Iterator<Item = &'a Row>,
+    styled_underlines: bool,
+) -> String {
+    let reset_styles = RESET_STYLES.enable_styled_underlines(styled_underlines);
+    let mut character_styles = reset_styles;
+    let mut buf = String::new();
+    let mut is_first = true;
+    for line in lines {
+        if line.is_canonical && !is_first {
+            // so that the styles of one line do not leak into the next one
+            if character_styles != reset_styles {
+                buf.push_str("\u{1b}[m");
+                character_styles = reset_styles;
+            }
+            buf.push('\n');
+        }
+        // trailing spaces are dropped as in the plain dump, unless they have a background
+        let content_length = line
+            .columns
+            .iter()
+            .rposition(|c| {
+                c.character != ' ' || !matches!(c.styles.background, None | Some(AnsiCode::Reset))
+            })
+            .map(|last_index| last_index + 1)
+            .unwrap_or(0);
+        for terminal_character in line.columns.iter().take(content_length) {
+            if let Some(new_styles) =
+                character_styles.update_and_return_diff(&terminal_character.styles, None)
+            {
+                buf.push_str(&new_styles.to_string());
+            }
+            buf.push(terminal_character.character);
+        }
+        is_first = false;
+    }
+    if character_styles != reset_styles {
+        buf.push_str("\u{1b}[m");
+    }
+    buf
+}
+
 fn utf8_mouse_coordinates(column: usize, line: isize) -> Vec<u8> {
     let mut coordinates = vec![];
     let mouse_pos_encode = |pos: usize| -> Vec<u8> {
@@ -333,6 +481,10 @@ pub struct Grid {
     title_stack: Vec<String>,
     character_cell_size: Rc<RefCell<Option<SizeInPixels>>>,
     sixel_grid: SixelGrid,
+    kitty_graphics: KittyGraphics,
+    // lines dropped from the top of a scroll region spanning the alternate screen, which images
+    // are anchored past but which do not move sixel images
+    lines_scrolled_off_scroll_region: usize,
     pub changed_colors: Option<[Option<AnsiCode>; 256]>,
     pub should_render: bool,
     pub lock_renders: bool,
@@ -357,7 +509,14 @@ pub struct Grid {
     pub mouse_tracking: MouseTracking,
     pub focus_event_tracking: bool,
     pub search_results: SearchResult,
-    pub pending_clipboard_update: Option<String>,
+    pub pending_clipboard_update: Option<(Clipboard, String)>,
+    pub pending_reported_cwd: Option<PathBuf>,
+    // the last title set by the application (OSC 0/2), until the tab picks it up
+    pub pending_title_update: Option<String>,
+    // a bell rung by the application (BEL), until the tab picks it up - unlike ring_bell, which
+    // is reset once the bell is rendered
+    pub pending_bell: bool,
+    pub pending_port_declarations: Vec<PortDeclaration>,
     ui_component_bytes: Option<Vec<u8>>,
     style: Style,
     debug: bool,
@@ -367,6 +526,15 @@ pub struct Grid {
     explicitly_disable_kitty_keyboard_protocol: bool, // has kitty keyboard support been explicitly
     // disabled by user config?
     click: Click,
+    word_separators: String, // besides whitespace, these end the word selected by double-click
+    copy_mode: Option<CopyMode>, // the keyboard selection of scroll mode
+    scrollback_lines_limit: Option<usize>, // overrides the scroll_buffer_size of the config
+    // the older lines of an "unlimited" scrollback, these do not count in lines_above
+    scrollback_spill: Option<Rc<RefCell<ScrollbackSpill>>>,
+    alternate_screen_behavior: AlternateScreenBehavior,
+    unicode_width: UnicodeWidth,
+    // the last character printed was a zero width joiner following an emoji
+    joining_grapheme_cluster: bool,
 }
 
 const CLICK_TIME_THRESHOLD: u128 = 400; // Doherty Threshold
@@ -408,7 +576,18 @@ impl Click {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TerminalModes {
+    pub bracketed_paste: bool,
+    pub cursor_keys: bool,
+    pub insert: bool,
+    pub mouse_tracking: MouseTracking,
+    pub mouse_mode: MouseMode,
+    pub focus_event_tracking: bool,
+    pub kitty_keyboard_protocol: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MouseMode {
     NoEncoding,
     Utf8,
@@ -421,7 +600,7 @@ impl Default for MouseMode {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MouseTracking {
     Off,
     Normal,
@@ -451,10 +630,11 @@ impl Debug for Grid {
                 .nth(x % sixel_indication_word.len())
                 .unwrap()
         };
-        for image_coordinates in self
-            .sixel_grid
-            .image_cell_coordinates_in_viewport(self.height, self.lines_above.len())
-        {
+        for image_coordinates in self.sixel_grid.image_cell_coordinates_in_viewport(
+            self.height,
+            self.width,
+            self.lines_above.len(),
+        ) {
             let (image_top_edge, image_bottom_edge, image_left_edge, image_right_edge) =
                 image_coordinates;
             for y in image_top_edge..image_bottom_edge {
@@ -544,7 +724,13 @@ impl Grid {
             character_cell_size,
             search_results: Default::default(),
             sixel_grid,
+            kitty_graphics: KittyGraphics::default(),
+            lines_scrolled_off_scroll_region: 0,
             pending_clipboard_update: None,
+            pending_reported_cwd: None,
+            pending_title_update: None,
+            pending_bell: false,
+            pending_port_declarations: vec![],
             ui_component_bytes: None,
             style,
             debug,
@@ -554,10 +740,18 @@ impl Grid {
             supports_kitty_keyboard_protocol: false,
             explicitly_disable_kitty_keyboard_protocol,
             click: Click::default(),
+            word_separators: DEFAULT_WORD_SEPARATORS.to_owned(),
+            copy_mode: None,
+            scrollback_lines_limit: None,
+            scrollback_spill: None,
+            alternate_screen_behavior: AlternateScreenBehavior::default(),
+            unicode_width: UnicodeWidth::default(),
+            joining_grapheme_cluster: false,
         }
     }
     pub fn render_full_viewport(&mut self) {
         self.output_buffer.update_all_lines();
+        self.kitty_graphics.set_force_render();
     }
     pub fn update_line_for_rendering(&mut self, line_index: usize) {
         self.output_buffer.update_line(line_index);
@@ -608,6 +802,18 @@ impl Grid {
         )
     }
 
+    /// Roughly how many bytes the scrollback kept in memory takes (the lines above and below the
+    /// viewport, not those moved to the disk)
+    pub fn scrollback_memory_usage(&self) -> usize {
+        let characters: usize = self
+            .lines_above
+            .iter()
+            .chain(self.lines_below.iter())
+            .map(|row| row.columns.len())
+            .sum();
+        characters * std::mem::size_of::<TerminalCharacter>()
+    }
+
     fn recalculate_scrollback_buffer_count(&self) -> usize {
         let mut scrollback_buffer_count = 0;
         for row in &self.lines_above {
@@ -619,8 +825,104 @@ impl Grid {
                 scrollback_buffer_count += 1;
             }
         }
+        if let Some(scrollback_spill) = &self.scrollback_spill {
+            scrollback_buffer_count += scrollback_spill
+                .borrow()
+                .row_widths()
+                .map(|row_width| calculate_row_display_height(row_width, self.width))
+                .sum::<usize>();
+        }
         scrollback_buffer_count
     }
+    /// Overrides the scroll_buffer_size of the config for this pane, an `Unlimited` scrollback
+    /// keeps its older lines on disk rather than dropping them
+    pub fn set_scrollback_size(&mut self, scrollback_size: ScrollbackSize) -> Result<()> {
+        match scrollback_size {
+            ScrollbackSize::Lines(lines) => {
+                self.scrollback_lines_limit = Some(lines);
+                self.scrollback_spill = None;
+            },
+            ScrollbackSize::Unlimited => {
+                self.scrollback_lines_limit = None;
+                self.scrollback_spill = Some(Rc::new(RefCell::new(ScrollbackSpill::new()?)));
+            },
+        }
+        Ok(())
+    }
+    pub fn set_alternate_screen_behavior(
+        &mut self,
+        alternate_screen_behavior: AlternateScreenBehavior,
+    ) {
+        self.alternate_screen_behavior = alternate_screen_behavior;
+    }
+    /// Applies to the characters printed from now on
+    pub fn set_unicode_width(&mut self, unicode_width: UnicodeWidth) {
+        self.unicode_width = unicode_width;
+    }
+    fn max_lines_above(&self) -> usize {
+        if self.scrollback_spill.is_some() {
+            usize::MAX
+        } else {
+            self.scrollback_lines_limit
+                .unwrap_or_else(|| *SCROLL_BUFFER_SIZE.get().unwrap())
+        }
+    }
+    fn spill_scrollback_to_disk(&mut self) {
+        let scrollback_spill = match &self.scrollback_spill {
+            Some(scrollback_spill) => scrollback_spill.clone(),
+            None => return,
+        };
+        // we keep as many lines in memory as a limited scrollback would have, and move them to
+        // the disk a chunk at a time
+        let lines_in_memory = *SCROLL_BUFFER_SIZE.get().unwrap();
+        if self.lines_above.len() < lines_in_memory + SPILL_CHUNK_LINES {
+            return;
+        }
+        let rows: Vec<Row> = self.lines_above.drain(..SPILL_CHUNK_LINES).collect();
+        let spilled_rows_height: usize = rows
+            .iter()
+            .map(|row| calculate_row_display_height(row.width(), self.width))
+            .sum();
+        for _ in &rows {
+            self.sixel_grid.offset_grid_top();
+        }
+        if let Err(e) = scrollback_spill.borrow_mut().spill(rows) {
+            log::error!("Failed to move scrollback to disk, dropping it: {:?}", e);
+            self.scrollback_buffer_lines = self
+                .scrollback_buffer_lines
+                .saturating_sub(spilled_rows_height);
+        }
+    }
+    fn page_in_scrollback_from_disk(&mut self) {
+        let scrollback_spill = match &self.scrollback_spill {
+            Some(scrollback_spill) => scrollback_spill.clone(),
+            None => return,
+        };
+        let paged_in_rows = scrollback_spill.borrow_mut().page_in();
+        match paged_in_rows {
+            Ok(Some(rows)) => {
+                self.sixel_grid.restore_grid_top(rows.len());
+                for row in rows.into_iter().rev() {
+                    self.lines_above.push_front(row);
+                }
+            },
+            Ok(None) => {},
+            Err(e) => {
+                log::error!("Failed to read scrollback from disk: {:?}", e);
+                self.scrollback_buffer_lines = self.recalculate_scrollback_buffer_count();
+            },
+        }
+    }
+    // the rows that were moved to the disk, oldest first
+    fn spilled_scrollback(&self) -> Vec<Row> {
+        match &self.scrollback_spill {
+            Some(scrollback_spill) => scrollback_spill.borrow().read_all().unwrap_or_else(|e| {
+                log::error!("Failed to read scrollback from disk: {:?}", e);
+                vec![]
+            }),
+            None => vec![],
+        }
+    }
 
     fn set_horizontal_tabstop(&mut self) {
         self.horizontal_tabstops.insert(self.cursor.x);
@@ -712,17 +1014,22 @@ impl Grid {
 
     pub fn scroll_up_one_line(&mut self) -> bool {
         let mut found_something = false;
+        if self.lines_above.is_empty() && self.viewport.len() == self.height {
+            self.page_in_scrollback_from_disk();
+        }
         if !self.lines_above.is_empty() && self.viewport.len() == self.height {
             self.is_scrolled = true;
             let line_to_push_down = self.viewport.pop().unwrap();
             self.lines_below.insert(0, line_to_push_down);
 
+            let max_lines_above = self.max_lines_above();
             let transferred_rows_height = transfer_rows_from_lines_above_to_viewport(
                 &mut self.lines_above,
                 &mut self.viewport,
                 &mut self.sixel_grid,
                 1,
                 self.width,
+                max_lines_above,
             );
             self.scrollback_buffer_lines = self
                 .scrollback_buffer_lines
@@ -752,6 +1059,9 @@ impl Grid {
                     Some(mut last_line_above) => {
                         last_line_above.append(&mut line_to_push_up.columns);
                         last_line_above
+                            .prompt_marks
+                            .merge(line_to_push_up.prompt_marks);
+                        last_line_above
                     },
                     None => {
                         // in this case, this line was not canonical but its beginning line was
@@ -761,8 +1071,13 @@ impl Grid {
                 }
             };
 
-            let dropped_line_width =
-                bounded_push(&mut self.lines_above, &mut self.sixel_grid, line_to_push_up);
+            let max_lines_above = self.max_lines_above();
+            let dropped_line_width = bounded_push(
+                &mut self.lines_above,
+                &mut self.sixel_grid,
+                line_to_push_up,
+                max_lines_above,
+            );
             if let Some(width) = dropped_line_width {
                 let dropped_line_height = calculate_row_display_height(width, self.width);
 
@@ -770,6 +1085,7 @@ impl Grid {
                     .scrollback_buffer_lines
                     .saturating_sub(dropped_line_height);
             }
+            self.spill_scrollback_to_disk();
 
             transfer_rows_from_lines_below_to_viewport(
                 &mut self.lines_below,
@@ -821,8 +1137,11 @@ impl Grid {
             return;
         }
         self.selection.reset();
+        self.copy_mode = None;
         self.sixel_grid.character_cell_size_possibly_changed();
         let cursors = if new_columns != self.width {
+            // the lines the images are anchored to are rewrapped
+            self.kitty_graphics.clear();
             self.horizontal_tabstops = create_horizontal_tabstops(new_columns);
             let mut cursor_canonical_line_index = self.cursor_canonical_line_index();
             let cursor_index_in_canonical_line = self.cursor_index_in_canonical_line();
@@ -835,6 +1154,7 @@ impl Grid {
                 {
                     let mut first_line_above = self.lines_above.pop_back().unwrap();
                     first_line_above.append(&mut row.columns);
+                    first_line_above.prompt_marks.merge(row.prompt_marks);
                     viewport_canonical_lines.push(first_line_above);
                     cursor_canonical_line_index += 1;
                 } else if row.is_canonical {
@@ -843,6 +1163,7 @@ impl Grid {
                     match viewport_canonical_lines.last_mut() {
                         Some(last_line) => {
                             last_line.append(&mut row.columns);
+                            last_line.prompt_marks.merge(row.prompt_marks);
                         },
                         None => {
                             // the state is corrupted somehow
@@ -875,6 +1196,7 @@ impl Grid {
             let mut new_viewport_rows = vec![];
             for mut canonical_line in viewport_canonical_lines {
                 let mut canonical_line_parts: Vec<Row> = vec![];
+                let prompt_marks = canonical_line.prompt_marks;
                 if canonical_line.columns.is_empty() {
                     canonical_line_parts.push(Row::new().canonical());
                 }
@@ -896,6 +1218,9 @@ impl Grid {
                     };
                     canonical_line_parts.push(row);
                 }
+                if let Some(first_part) = canonical_line_parts.first_mut() {
+                    first_part.prompt_marks = prompt_marks;
+                }
                 new_viewport_rows.append(&mut canonical_line_parts);
             }
 
@@ -978,12 +1303,14 @@ impl Grid {
             match current_viewport_row_count.cmp(&new_rows) {
                 Ordering::Less => {
                     let row_count_to_transfer = new_rows - current_viewport_row_count;
+                    let max_lines_above = self.max_lines_above();
                     transfer_rows_from_lines_above_to_viewport(
                         &mut self.lines_above,
                         &mut self.viewport,
                         &mut self.sixel_grid,
                         row_count_to_transfer,
                         new_columns,
+                        max_lines_above,
                     );
                     let rows_pulled = self.viewport.len() - current_viewport_row_count;
                     new_cursor_y += rows_pulled;
@@ -1005,13 +1332,16 @@ impl Grid {
                             *saved_cursor_y_coordinates -= row_count_to_transfer;
                         }
                     }
+                    let max_lines_above = self.max_lines_above();
                     transfer_rows_from_viewport_to_lines_above(
                         &mut self.viewport,
                         &mut self.lines_above,
                         &mut self.sixel_grid,
                         row_count_to_transfer,
                         new_columns,
+                        max_lines_above,
                     );
+                    self.spill_scrollback_to_disk();
                 },
                 Ordering::Equal => {},
             }
@@ -1191,6 +1521,13 @@ impl Grid {
             raw_vte_output.push(ring_bell);
             self.ring_bell = false;
         }
+        raw_vte_output.push_str(&self.kitty_graphics.render(
+            self.viewport_top_line(),
+            self.height,
+            self.width,
+            content_x,
+            content_y,
+        ));
         return Ok(Some((
             character_chunks,
             Some(raw_vte_output),
@@ -1222,13 +1559,38 @@ impl Grid {
         if !full {
             return viewport;
         }
-        let mut scrollback: String = dump_screen!(self.lines_above);
+        let spilled_scrollback = self.spilled_scrollback();
+        let mut scrollback: String = dump_screen!(spilled_scrollback
+            .iter()
+            .chain(self.lines_above.iter())
+            .collect::<Vec<_>>());
         if !scrollback.is_empty() {
             scrollback.push('\n');
         }
         scrollback.push_str(&viewport);
+        // when scrolled up, the rest of the scrollback is below the viewport
+        let lines_below: String = dump_screen!(self.lines_below);
+        if !lines_below.is_empty() {
+            scrollback.push('\n');
+            scrollback.push_str(&lines_below);
+        }
         scrollback
     }
+    pub fn dump_screen_with_styles(&self, full: bool) -> String {
+        if full {
+            let spilled_scrollback = self.spilled_scrollback();
+            dump_lines_with_styles(
+                spilled_scrollback
+                    .iter()
+                    .chain(self.lines_above.iter())
+                    .chain(self.viewport.iter())
+                    .chain(self.lines_below.iter()),
+                self.styled_underlines,
+            )
+        } else {
+            dump_lines_with_styles(self.viewport.iter(), self.styled_underlines)
+        }
+    }
     pub fn move_viewport_up(&mut self, count: usize) {
         for _ in 0..count {
             self.scroll_up_one_line();
@@ -1241,6 +1603,200 @@ impl Grid {
         }
         self.output_buffer.update_all_lines();
     }
+    /// Moves the viewport to the next line below its top (or the previous one above it) matching
+    /// the pattern, returns how many matches there are in the scrollback and the viewport
+    pub fn scroll_to_pattern(&mut self, pattern: &Regex, backwards: bool) -> usize {
+        let (lines, viewport_top) = self.canonical_lines();
+        let match_count = lines
+            .iter()
+            .map(|(_, text)| pattern.find_iter(text).count())
+            .sum();
+        let target_line = if backwards {
+            // when we're not scrolled, the latest match is the interesting one (eg. the last
+            // error) even if it is already in the viewport
+            let search_before = if self.is_scrolled {
+                viewport_top
+            } else {
+                viewport_top + self.viewport.len()
+            };
+            lines
+                .iter()
+                .rev()
+                .find(|(line, text)| *line < search_before && pattern.is_match(text))
+        } else {
+            lines
+                .iter()
+                .find(|(line, text)| *line > viewport_top && pattern.is_match(text))
+        }
+        .map(|(line, _)| *line);
+        match target_line {
+            Some(line) if line < viewport_top => self.move_viewport_up(viewport_top - line),
+            Some(line) => self.move_viewport_down(line - viewport_top),
+            None => {},
+        }
+        match_count
+    }
+    /// The 1-based number (counted from the top of the scrollback) and the text of every line
+    /// matching the pattern
+    pub fn find_pattern(&self, pattern: &Regex) -> Vec<(usize, String)> {
+        let (lines, _viewport_top) = self.canonical_lines();
+        lines
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (_, text))| pattern.is_match(text))
+            .map(|(i, (_, text))| (i + 1, text.trim_end().to_owned()))
+            .collect()
+    }
+    /// Moves the viewport so that this line (as numbered by find_pattern) is at its top
+    pub fn scroll_to_line(&mut self, line_number: usize) {
+        let (lines, viewport_top) = self.canonical_lines();
+        let target_line = lines
+            .get(line_number.saturating_sub(1))
+            .map(|(display_line, _)| *display_line);
+        match target_line {
+            Some(line) if line < viewport_top => self.move_viewport_up(viewport_top - line),
+            Some(line) => self.move_viewport_down(line - viewport_top),
+            None => {},
+        }
+    }
+    /// Moves the viewport so that the previous prompt above its top (or the next one below it) is
+    /// at its top, returns false if the shell did not mark any there
+    pub fn scroll_to_prompt(&mut self, backwards: bool) -> bool {
+        let (lines, viewport_top) = self.canonical_line_prompt_marks();
+        let is_prompt = |(_, prompt_marks): &&(usize, PromptMarks)| {
+            prompt_marks.contains(PromptMark::PromptStart)
+        };
+        let target_line = if backwards {
+            lines
+                .iter()
+                .rev()
+                .filter(is_prompt)
+                .find(|(line, _)| *line < viewport_top)
+        } else {
+            lines
+                .iter()
+                .filter(is_prompt)
+                .find(|(line, _)| *line > viewport_top)
+        }
+        .map(|(line, _)| *line);
+        match target_line {
+            Some(line) if line < viewport_top => self.move_viewport_up(viewport_top - line),
+            Some(line) => self.move_viewport_down(line - viewport_top),
+            None => return false,
+        }
+        true
+    }
+    /// Selects the output of the last command that finished and printed something (from where
+    /// the shell marked its output start to the next prompt), returns false if there is none
+    pub fn select_last_command_output(&mut self) -> bool {
+        let lines_above = self.lines_above.len() as isize;
+        let mut output_start = None;
+        let mut last_output = None;
+        for (index, row) in self
+            .lines_above
+            .iter()
+            .chain(self.viewport.iter())
+            .chain(self.lines_below.iter())
+            .enumerate()
+        {
+            let line = index as isize - lines_above;
+            let prompt_marks = row.prompt_marks;
+            // a command without output is marked as ended on the line its output would start
+            let ends_output = prompt_marks.contains(PromptMark::CommandEnd)
+                || prompt_marks.contains(PromptMark::PromptStart);
+            if ends_output {
+                if let Some(start) = output_start.take() {
+                    if line > start {
+                        last_output = Some((start, line - 1));
+                    }
+                }
+            }
+            if prompt_marks.contains(PromptMark::OutputStart) && !ends_output {
+                output_start = Some(line);
+            }
+        }
+        let (start, end) = match last_output {
+            Some(last_output) => last_output,
+            None => return false,
+        };
+        self.selection.set_start_and_end_positions(
+            Position {
+                line: Line(start),
+                column: Column(0),
+            },
+            Position {
+                line: Line(end),
+                column: Column(self.width),
+            },
+        );
+        if start < 0 {
+            // the selection moves with the viewport
+            self.scroll_to_row((start + lines_above) as usize);
+        }
+        self.output_buffer.update_all_lines();
+        self.mark_for_rerender();
+        true
+    }
+    // the first display line and the prompt marks of every canonical line, as in canonical_lines
+    fn canonical_line_prompt_marks(&self) -> (Vec<(usize, PromptMarks)>, usize) {
+        let mut lines: Vec<(usize, PromptMarks)> = vec![];
+        let mut display_line = 0;
+        for row in &self.lines_above {
+            lines.push((display_line, row.prompt_marks));
+            display_line += calculate_row_display_height(row.width(), self.width);
+        }
+        let viewport_top = display_line;
+        for row in self.viewport.iter().chain(self.lines_below.iter()) {
+            match lines.last_mut() {
+                Some((_, prompt_marks)) if !row.is_canonical => {
+                    prompt_marks.merge(row.prompt_marks)
+                },
+                _ => lines.push((display_line, row.prompt_marks)),
+            }
+            display_line += 1;
+        }
+        (lines, viewport_top)
+    }
+    // the mark belongs to the whole canonical line the cursor is on
+    fn mark_prompt(&mut self, mark: PromptMark) {
+        if self.alternate_screen_state.is_some() {
+            return;
+        }
+        while self.viewport.len() <= self.cursor.y {
+            self.viewport.push(Row::new().canonical());
+        }
+        let canonical_row = match self.viewport[..=self.cursor.y]
+            .iter_mut()
+            .rev()
+            .find(|row| row.is_canonical)
+        {
+            Some(row) => Some(row),
+            None => self.lines_above.back_mut(),
+        };
+        if let Some(row) = canonical_row {
+            row.prompt_marks.insert(mark);
+        }
+    }
+    // the first display line and the text of every canonical line, starting from the top of the
+    // scrollback, and the display line at the top of the viewport
+    fn canonical_lines(&self) -> (Vec<(usize, String)>, usize) {
+        let row_text = |row: &Row| -> String { row.columns.iter().map(|c| c.character).collect() };
+        let mut lines: Vec<(usize, String)> = vec![];
+        let mut display_line = 0;
+        for row in &self.lines_above {
+            lines.push((display_line, row_text(row)));
+            display_line += calculate_row_display_height(row.width(), self.width);
+        }
+        let viewport_top = display_line;
+        for row in self.viewport.iter().chain(self.lines_below.iter()) {
+            match lines.last_mut() {
+                Some((_, text)) if !row.is_canonical => text.push_str(&row_text(row)),
+                _ => lines.push((display_line, row_text(row))),
+            }
+            display_line += 1;
+        }
+        (lines, viewport_top)
+    }
     pub fn reset_viewport(&mut self) {
         let max_lines_to_scroll = *SCROLL_BUFFER_SIZE.get().unwrap() * 2; // while not very elegant, this can prevent minor bugs from becoming showstoppers by sticking the whole app display in an endless loop
         let mut lines_scrolled = 0;
@@ -1321,6 +1877,7 @@ impl Grid {
                     if self.alternate_screen_state.is_none() {
                         self.transfer_rows_to_lines_above(1);
                     } else {
+                        self.lines_scrolled_off_scroll_region += 1;
                         self.viewport.remove(0);
                     }
 
@@ -1526,6 +2083,9 @@ impl Grid {
     }
     fn clear_lines_above(&mut self) {
         self.lines_above.clear();
+        if let Some(scrollback_spill) = &self.scrollback_spill {
+            scrollback_spill.borrow_mut().clear();
+        }
         self.scrollback_buffer_lines = self.recalculate_scrollback_buffer_count();
     }
 
@@ -1756,15 +2316,78 @@ impl Grid {
         self.add_canonical_line();
         self.mark_for_rerender();
     }
+    // the lines of the grid counted from the first line it ever had, which is how images are
+    // anchored to them
+    fn viewport_top_line(&self) -> usize {
+        self.sixel_grid.lines_scrolled_off_top()
+            + self.lines_scrolled_off_scroll_region
+            + self.lines_above.len()
+    }
+    /// The geometries of the floating panes above this pane, which its images should not cover
+    pub fn set_covered_areas(&mut self, covered_areas: Vec<PaneGeom>) {
+        if self.kitty_graphics.set_covered_areas(covered_areas) {
+            self.mark_for_rerender();
+        }
+    }
+    /// The kitty graphics commands are APC sequences which vte ignores, so every byte is also
+    /// handled here
+    pub fn handle_kitty_graphics_byte(&mut self, byte: u8) {
+        if let Some(command) = self.kitty_graphics.handle_byte(byte) {
+            let character_cell_size = *self.character_cell_size.borrow();
+            let result = self.kitty_graphics.handle_command(
+                command,
+                self.viewport_top_line() + self.cursor.y,
+                self.cursor.x,
+                character_cell_size,
+            );
+            if let Some(reply) = result.reply {
+                self.pending_messages_to_pty.push(reply.into_bytes());
+            }
+            if let Some((rows, columns)) = result.cursor_movement {
+                // the cursor is moved to after the image, on its last row
+                for _ in 1..rows {
+                    self.add_newline();
+                }
+                self.move_cursor_forward_until_edge(columns);
+            }
+            self.mark_for_rerender();
+        }
+    }
     pub fn mark_for_rerender(&mut self) {
         self.should_render = true;
     }
+    /// The input related modes requested by the application running in this pane, these are
+    /// applied to the input we send to the pane rather than to the user's terminal
+    pub fn terminal_modes(&self) -> TerminalModes {
+        TerminalModes {
+            bracketed_paste: self.bracketed_paste_mode,
+            cursor_keys: self.cursor_key_mode,
+            insert: self.insert_mode,
+            mouse_tracking: self.mouse_tracking.clone(),
+            mouse_mode: self.mouse_mode.clone(),
+            focus_event_tracking: self.focus_event_tracking,
+            kitty_keyboard_protocol: self.supports_kitty_keyboard_protocol,
+        }
+    }
+    fn reset_terminal_modes(&mut self) {
+        // these must all be reset, otherwise a mode requested by a previous application (eg. a
+        // command that was rerun) would leak to the next one running in this pane
+        self.bracketed_paste_mode = false;
+        self.cursor_key_mode = false;
+        self.insert_mode = false;
+        self.mouse_mode = MouseMode::NoEncoding;
+        self.mouse_tracking = MouseTracking::Off;
+        self.focus_event_tracking = false;
+        self.supports_kitty_keyboard_protocol = false;
+    }
     pub fn reset_terminal_state(&mut self) {
         self.lines_above = VecDeque::new();
+        if let Some(scrollback_spill) = &self.scrollback_spill {
+            scrollback_spill.borrow_mut().clear();
+        }
         self.lines_below = vec![];
         self.viewport = vec![Row::new().canonical()];
         self.alternate_screen_state = None;
-        self.cursor_key_mode = false;
         self.scroll_region = None;
         self.clear_viewport_before_rendering = true;
         self.cursor = Cursor::new(0, 0, self.styled_underlines);
@@ -1779,18 +2402,66 @@ impl Grid {
         self.scrollback_buffer_lines = 0;
         self.search_results = Default::default();
         self.sixel_scrolling = false;
-        self.mouse_mode = MouseMode::NoEncoding;
-        self.mouse_tracking = MouseTracking::Off;
-        self.focus_event_tracking = false;
+        self.reset_terminal_modes();
         self.cursor_is_hidden = false;
-        self.supports_kitty_keyboard_protocol = false;
         if let Some(images_to_reap) = self.sixel_grid.clear() {
             self.sixel_grid.reap_images(images_to_reap);
         }
+        self.kitty_graphics.clear();
     }
     fn set_preceding_character(&mut self, terminal_character: TerminalCharacter) {
         self.preceding_char = Some(terminal_character);
     }
+    /// Whether the character belongs to the emoji sequence the previous character started, in
+    /// which case it is stored with that character rather than added: terminal emulators display
+    /// the sequence in the columns of its first emoji
+    fn join_to_grapheme_cluster(&mut self, c: char) -> bool {
+        let follows_zero_width_joiner =
+            std::mem::replace(&mut self.joining_grapheme_cluster, false);
+        let cursor_x = self.cursor.x;
+        let width = self.width;
+        let Some(preceding_character) = self.last_character_of_cursor_line() else {
+            return false;
+        };
+        let follows_emoji = preceding_character.width() == 2;
+        let joins = match c {
+            _ if follows_zero_width_joiner => is_emoji_joined_by_zero_width_joiner(c),
+            // the joiner, the selector and the skin tone modifiers after an emoji
+            ZERO_WIDTH_JOINER | EMOJI_PRESENTATION_SELECTOR | '\u{1F3FB}'..='\u{1F3FF}' => {
+                follows_emoji
+            },
+            _ => false,
+        };
+        if joins {
+            preceding_character.join_to_grapheme_cluster(c);
+            self.joining_grapheme_cluster = c == ZERO_WIDTH_JOINER;
+            self.output_buffer.update_line(self.cursor.y);
+            return true;
+        }
+        if c == EMOJI_PRESENTATION_SELECTOR {
+            if preceding_character.width() == 1 && cursor_x < width {
+                preceding_character.set_emoji_presentation();
+                if let Some(row) = self.viewport.get_mut(self.cursor.y) {
+                    row.width = None;
+                }
+                self.move_cursor_forward_until_edge(1);
+                self.output_buffer.update_line(self.cursor.y);
+            }
+            // a lone selector does not take any columns either way
+            return true;
+        }
+        false
+    }
+    /// The last character of the line of the cursor, if the cursor is right after it (as it is
+    /// after printing it)
+    fn last_character_of_cursor_line(&mut self) -> Option<&mut TerminalCharacter> {
+        let cursor_x = self.cursor.x;
+        let row = self.viewport.get_mut(self.cursor.y)?;
+        if cursor_x == 0 || row.width() != cursor_x {
+            return None;
+        }
+        row.columns.back_mut()
+    }
     pub fn start_selection(&mut self, start: &Position) {
         let old_selection = self.selection;
         self.click.record_click(*start);
@@ -1854,6 +2525,80 @@ impl Grid {
         self.update_selected_lines(&old_selection, &self.selection.clone());
         self.mark_for_rerender();
     }
+    pub fn copy_mode_is_active(&self) -> bool {
+        self.copy_mode.is_some()
+    }
+    pub fn copy_mode_input(&mut self, key: char) -> CopyModeOutcome {
+        let mut copy_mode = self
+            .copy_mode
+            .take()
+            .unwrap_or_else(|| CopyMode::new(self.copy_mode_start_position()));
+        let outcome = copy_mode.handle_key(key, &*self);
+        if outcome == CopyModeOutcome::Exit {
+            self.reset_selection();
+            return outcome;
+        }
+        self.scroll_to_row(copy_mode.cursor().line);
+        self.select_copy_mode_area(&copy_mode);
+        self.copy_mode = Some(copy_mode);
+        outcome
+    }
+    pub fn exit_copy_mode(&mut self) {
+        if self.copy_mode.take().is_some() {
+            self.reset_selection();
+        }
+    }
+    fn copy_mode_start_position(&self) -> CopyModePosition {
+        // the terminal cursor, unless it was scrolled out of view
+        let (line, column) = if self.is_scrolled {
+            (0, 0)
+        } else {
+            (self.cursor.y, self.cursor.x)
+        };
+        CopyModePosition::new(self.lines_above.len() + line, column)
+    }
+    fn scroll_to_row(&mut self, line: usize) {
+        while line < self.lines_above.len() {
+            let lines_above = self.lines_above.len();
+            self.scroll_up_one_line();
+            if self.lines_above.len() == lines_above {
+                break;
+            }
+        }
+        while line >= self.lines_above.len() + self.height {
+            let lines_below = self.lines_below.len();
+            self.scroll_down_one_line();
+            if self.lines_below.len() == lines_below {
+                break;
+            }
+        }
+    }
+    fn select_copy_mode_area(&mut self, copy_mode: &CopyMode) {
+        let old_selection = self.selection;
+        let lines_above = self.lines_above.len() as isize;
+        let to_position = |position: CopyModePosition| Position {
+            line: Line(position.line as isize - lines_above),
+            column: Column(position.column),
+        };
+        let (start, end, is_block) = copy_mode.selection();
+        if is_block {
+            self.selection
+                .set_block_start_and_end_positions(to_position(start), to_position(end));
+        } else {
+            self.selection
+                .set_start_and_end_positions(to_position(start), to_position(end));
+        }
+        // the columns of a block change on all of its lines, so these are all re-rendered
+        for l in old_selection
+            .line_indices()
+            .chain(self.selection.line_indices())
+        {
+            if l >= 0 && (l as usize) < self.height {
+                self.output_buffer.update_line(l as usize);
+            }
+        }
+        self.mark_for_rerender();
+    }
     pub fn get_selected_text(&self) -> Option<String> {
         if self.selection.is_empty() {
             return None;
@@ -1862,16 +2607,21 @@ impl Grid {
 
         let sorted_selection = self.selection.sorted();
         let (start, end) = (sorted_selection.start, sorted_selection.end);
+        let is_block = sorted_selection.is_block();
 
         for l in sorted_selection.line_indices() {
             let mut line_selection = String::new();
 
             // on the first line of the selection, use the selection start column
-            // otherwise, start at the beginning of the line
-            let start_column = if l == start.line.0 { start.column.0 } else { 0 };
+            // otherwise, start at the beginning of the line (a block uses its columns on all lines)
+            let start_column = if l == start.line.0 || is_block {
+                start.column.0
+            } else {
+                0
+            };
 
             // same thing on the last line, but with the selection end column
-            let end_column = if l == end.line.0 {
+            let end_column = if l == end.line.0 || is_block {
                 end.column.0
             } else {
                 self.width
@@ -1908,12 +2658,13 @@ impl Grid {
             for terminal_character in &row.columns {
                 if (start_column..end_column).contains(&terminal_col) {
                     line_selection.push(terminal_character.character);
+                    terminal_character.push_grapheme_cluster_tail(&mut line_selection);
                 }
 
                 terminal_col += terminal_character.width();
             }
 
-            if row.is_canonical {
+            if row.is_canonical || is_block {
                 selection.push(line_selection);
             } else {
                 // rejoin wrapped lines if possible
@@ -1939,9 +2690,10 @@ impl Grid {
         self.lines_above.len() + self.cursor.y
     }
     pub fn word_around_position(&self, position: &Position) -> Option<(Position, Position)> {
+        let word_separators = &self.word_separators;
         let position_row = self.viewport.get(position.line.0 as usize)?;
         let (index_start, index_end) =
-            position_row.word_indices_around_character_index(position.column.0)?;
+            position_row.word_indices_around_character_index(position.column.0, word_separators)?;
 
         let mut position_start = Position::new(position.line.0 as i32, index_start as u16);
         let mut position_end = Position::new(position.line.0 as i32, index_end as u16);
@@ -1952,7 +2704,8 @@ impl Grid {
                 .viewport
                 .get(position_start.line.0.saturating_sub(1) as usize)
             {
-                let new_start_index = position_row_above.word_start_index_of_last_character();
+                let new_start_index =
+                    position_row_above.word_start_index_of_last_character(word_separators);
                 position_start = Position::new(
                     position_start.line.0.saturating_sub(1) as i32,
                     new_start_index as u16,
@@ -1969,7 +2722,8 @@ impl Grid {
                 if position_row_below.is_canonical {
                     break;
                 }
-                let new_end_index = position_row_below.word_end_index_of_first_character();
+                let new_end_index =
+                    position_row_below.word_end_index_of_first_character(word_separators);
                 position_end = Position::new(position_end.line.0 as i32 + 1, new_end_index as u16);
                 column_count_in_row = position_row_below.columns.len();
             } else {
@@ -2026,6 +2780,7 @@ impl Grid {
         }
     }
     fn set_title(&mut self, title: String) {
+        self.pending_title_update = Some(title.clone());
         self.title = Some(title);
     }
     fn push_current_title_to_stack(&mut self) {
@@ -2038,20 +2793,46 @@ impl Grid {
     }
     fn pop_title_from_stack(&mut self) {
         if let Some(popped_title) = self.title_stack.pop() {
-            self.title = Some(popped_title);
+            self.set_title(popped_title);
         }
     }
+    /// Moves what is on screen to the scrollback and leaves an empty viewport with the cursor at
+    /// its top, trailing blank lines are dropped rather than kept in the scrollback
+    fn move_viewport_to_scrollback(&mut self) {
+        self.reset_viewport();
+        while self
+            .viewport
+            .last()
+            .map(|row| row.columns.iter().all(|c| c.character == ' '))
+            .unwrap_or(false)
+        {
+            self.viewport.pop();
+        }
+        let row_count = self.viewport.len();
+        if row_count > 0 {
+            self.transfer_rows_to_lines_above(row_count);
+        }
+        self.viewport = vec![Row::new().canonical()];
+        self.cursor.x = 0;
+        self.cursor.y = 0;
+        self.clear_viewport_before_rendering = true;
+        self.output_buffer.update_all_lines();
+        self.mark_for_rerender();
+    }
     fn transfer_rows_to_lines_above(&mut self, count: usize) {
+        let max_lines_above = self.max_lines_above();
         let transferred_rows_count = transfer_rows_from_viewport_to_lines_above(
             &mut self.viewport,
             &mut self.lines_above,
             &mut self.sixel_grid,
             count,
             self.width,
+            max_lines_above,
         );
 
         self.scrollback_buffer_lines =
             subtract_isize_from_usize(self.scrollback_buffer_lines, transferred_rows_count);
+        self.spill_scrollback_to_disk();
     }
     fn move_cursor_down_by_pixels(&mut self, pixel_count: usize) {
         if let Some(character_cell_size) = {
@@ -2428,6 +3209,9 @@ impl Grid {
     }
     pub fn delete_viewport_and_scroll(&mut self) {
         self.lines_above.clear();
+        if let Some(scrollback_spill) = &self.scrollback_spill {
+            scrollback_spill.borrow_mut().clear();
+        }
         self.viewport.clear();
         self.lines_below.clear();
     }
@@ -2446,22 +3230,65 @@ impl Grid {
     pub fn update_arrow_fonts(&mut self, should_support_arrow_fonts: bool) {
         self.arrow_fonts = should_support_arrow_fonts;
     }
+    pub fn update_word_separators(&mut self, word_separators: &str) {
+        if self.word_separators != word_separators {
+            self.word_separators = word_separators.to_owned();
+        }
+    }
+}
+
+impl CopyModeLines for Grid {
+    fn line_count(&self) -> usize {
+        self.lines_above.len() + self.viewport.len() + self.lines_below.len()
+    }
+    fn line(&self, index: usize) -> Vec<char> {
+        let lines_above = self.lines_above.len();
+        let row = if index < lines_above {
+            self.lines_above.get(index)
+        } else if index < lines_above + self.viewport.len() {
+            self.viewport.get(index - lines_above)
+        } else {
+            self.lines_below
+                .get(index - lines_above - self.viewport.len())
+        };
+        let mut line: Vec<char> = row
+            .map(|row| {
+                row.columns
+                    .iter()
+                    .flat_map(|c| std::iter::repeat(c.character).take(c.width()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        while line.last().map(|c| c.is_whitespace()).unwrap_or(false) {
+            line.pop();
+        }
+        line
+    }
 }
 
 impl Perform for Grid {
     fn print(&mut self, c: char) {
         let c = self.cursor.charsets[self.active_charset].map(c);
+        let unicode_width = self.unicode_width;
+        if unicode_width.grapheme_clusters && self.join_to_grapheme_cluster(c) {
+            return;
+        }
 
-        let terminal_character =
-            TerminalCharacter::new_styled(c, self.cursor.pending_styles.clone());
+        let terminal_character = if unicode_width.ambiguous_is_wide {
+            TerminalCharacter::new_styled_ambiguous_wide(c, self.cursor.pending_styles.clone())
+        } else {
+            TerminalCharacter::new_styled(c, self.cursor.pending_styles.clone())
+        };
         self.set_preceding_character(terminal_character.clone());
         self.add_character(terminal_character);
     }
 
     fn execute(&mut self, byte: u8) {
+        self.joining_grapheme_cluster = false;
         match byte {
             7 => {
                 self.ring_bell = true;
+                self.pending_bell = true;
             },
             8 => {
                 // backspace
@@ -2565,6 +3392,20 @@ impl Perform for Grid {
                 }
             },
 
+            // Report the current working directory (eg. from the shell integration).
+            b"7" => {
+                if params.len() >= 2 {
+                    let url = params[1..]
+                        .iter()
+                        .flat_map(|x| str::from_utf8(x))
+                        .collect::<Vec<&str>>()
+                        .join(";");
+                    if let Some(cwd) = parse_reported_cwd(&url) {
+                        self.pending_reported_cwd = Some(cwd);
+                    }
+                }
+            },
+
             // Set color index.
             b"4" => {
                 for chunk in params[1..].chunks(2) {
@@ -2670,7 +3511,12 @@ impl Perform for Grid {
                     return;
                 }
 
-                let _clipboard = params[1].get(0).unwrap_or(&b'c');
+                // the selection parameter may name several buffers, we only tell the primary
+                // selection apart from the system clipboard
+                let clipboard = match params[1].get(0) {
+                    Some(b'p') | Some(b's') => Clipboard::Primary,
+                    _ => Clipboard::System,
+                };
                 match params[2] {
                     b"?" => {
                         // TBD: paste from own clipboard - currently unsupported
@@ -2678,7 +3524,7 @@ impl Perform for Grid {
                     base64 => {
                         if let Ok(bytes) = base64::decode(base64) {
                             if let Ok(string) = String::from_utf8(bytes) {
-                                self.pending_clipboard_update = Some(string);
+                                self.pending_clipboard_update = Some((clipboard, string));
                             }
                         };
                     },
@@ -2731,6 +3577,26 @@ impl Perform for Grid {
                 // TBD - reset text cursor color - currently unimplemented
             },
 
+            // Shell integration marks (OSC 133): the start of the prompt (A), of the typed command
+            // (B), of its output (C) and its end (D, followed by the exit status)
+            b"133" => {
+                let mark = match params.get(1).and_then(|param| param.first()) {
+                    Some(b'A') => PromptMark::PromptStart,
+                    Some(b'B') => PromptMark::CommandStart,
+                    Some(b'C') => PromptMark::OutputStart,
+                    Some(b'D') => PromptMark::CommandEnd,
+                    _ => return,
+                };
+                self.mark_prompt(mark);
+            },
+
+            // Declare the ports of services running in this pane (see `zellij action declare-port`)
+            b"zellij" => {
+                if let Some(port_declaration) = parse_port_declaration(&params[1..]) {
+                    self.pending_port_declarations.push(port_declaration);
+                }
+            },
+
             _ => {
                 if self.debug {
                     log::warn!("Unhandled osc: {:?}", params);
@@ -2794,11 +3660,13 @@ impl Perform for Grid {
                     if let Some(images_to_reap) = self.sixel_grid.clear() {
                         self.sixel_grid.reap_images(images_to_reap);
                     }
+                    self.kitty_graphics.clear();
                 } else if clear_type == 3 {
                     self.clear_lines_above();
                     if let Some(images_to_reap) = self.sixel_grid.clear() {
                         self.sixel_grid.reap_images(images_to_reap);
                     }
+                    self.kitty_graphics.clear();
                 }
             };
         } else if c == 'H' || c == 'f' {
@@ -2834,6 +3702,14 @@ impl Perform for Grid {
                         2004 => {
                             self.bracketed_paste_mode = false;
                         },
+                        1049 if self.alternate_screen_behavior.keep_output_in_scrollback
+                            && self.alternate_screen_state.is_none() =>
+                        {
+                            // the application drew on the main screen
+                            if self.alternate_screen_behavior.clear_viewport_on_exit {
+                                self.move_viewport_to_scrollback();
+                            }
+                        },
                         1049 => {
                             if let Some(mut alternate_screen_state) =
                                 self.alternate_screen_state.take()
@@ -2844,6 +3720,7 @@ impl Perform for Grid {
                                     // outside of the alternate_screen_state struct
                                     self.sixel_grid.reap_images(image_ids_to_reap);
                                 }
+                                self.kitty_graphics.clear();
                                 alternate_screen_state.apply_contents_to(
                                     &mut self.lines_above,
                                     &mut self.viewport,
@@ -2855,6 +3732,9 @@ impl Perform for Grid {
                             self.alternate_screen_state = None;
                             self.clear_viewport_before_rendering = true;
                             self.force_change_size(self.height, self.width); // the alternative_viewport might have been of a different size...
+                            if self.alternate_screen_behavior.clear_viewport_on_exit {
+                                self.move_viewport_to_scrollback();
+                            }
                             self.mark_for_rerender();
                         },
                         25 => {
@@ -2933,8 +3813,17 @@ impl Perform for Grid {
                         2004 => {
                             self.bracketed_paste_mode = true;
                         },
+                        1049 if self.alternate_screen_behavior.keep_output_in_scrollback => {
+                            // the application draws on the main screen instead, so that what it
+                            // scrolls off the screen (and what it leaves behind) stays in the
+                            // scrollback
+                            if self.alternate_screen_state.is_none() {
+                                self.move_viewport_to_scrollback();
+                            }
+                        },
                         1049 => {
                             // enter alternate buffer
+                            self.kitty_graphics.clear();
                             let current_lines_above =
                                 std::mem::replace(&mut self.lines_above, VecDeque::new());
                             let current_viewport =
@@ -3432,6 +4321,39 @@ impl Perform for Grid {
     }
 }
 
+/// How a pane handles applications switching to the alternate screen (CSI ? 1049 h), from the
+/// alternate_screen_scrollback and clear_on_alternate_screen_exit options
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AlternateScreenBehavior {
+    // draw on the main screen, so that the output of the application stays in the scrollback
+    pub keep_output_in_scrollback: bool,
+    // move what is on screen to the scrollback once the application leaves the alternate screen
+    pub clear_viewport_on_exit: bool,
+}
+
+impl AlternateScreenBehavior {
+    pub fn from_options(options: &Options) -> Self {
+        AlternateScreenBehavior {
+            keep_output_in_scrollback: options.alternate_screen_scrollback.unwrap_or(false),
+            clear_viewport_on_exit: options.clear_on_alternate_screen_exit.unwrap_or(false),
+        }
+    }
+    /// Applies the overrides of a pane's layout
+    pub fn with_overrides(
+        mut self,
+        keep_output_in_scrollback: Option<bool>,
+        clear_viewport_on_exit: Option<bool>,
+    ) -> Self {
+        if let Some(keep_output_in_scrollback) = keep_output_in_scrollback {
+            self.keep_output_in_scrollback = keep_output_in_scrollback;
+        }
+        if let Some(clear_viewport_on_exit) = clear_viewport_on_exit {
+            self.clear_viewport_on_exit = clear_viewport_on_exit;
+        }
+        self
+    }
+}
+
 #[derive(Clone)]
 pub struct AlternateScreenState {
     lines_above: VecDeque<Row>,
@@ -3475,10 +4397,42 @@ impl AlternateScreenState {
     }
 }
 
+/// The shell integration marks (OSC 133) a shell sends around its prompt and the commands it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptMark {
+    PromptStart = 1,  // A
+    CommandStart = 2, // B, where the prompt ends and the command is typed
+    OutputStart = 4,  // C
+    CommandEnd = 8,   // D
+}
+
+/// The prompt marks received on a canonical line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PromptMarks(u8);
+
+impl PromptMarks {
+    pub fn from_bits(bits: u8) -> Self {
+        PromptMarks(bits)
+    }
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+    pub fn insert(&mut self, mark: PromptMark) {
+        self.0 |= mark as u8;
+    }
+    pub fn contains(&self, mark: PromptMark) -> bool {
+        self.0 & mark as u8 != 0
+    }
+    pub fn merge(&mut self, other: PromptMarks) {
+        self.0 |= other.0;
+    }
+}
+
 #[derive(Clone)]
 pub struct Row {
     pub columns: VecDeque<TerminalCharacter>,
     pub is_canonical: bool,
+    pub prompt_marks: PromptMarks,
     width: Option<usize>,
 }
 
@@ -3496,6 +4450,7 @@ impl Row {
         Row {
             columns: VecDeque::new(),
             is_canonical: false,
+            prompt_marks: PromptMarks::default(),
             width: None,
         }
     }
@@ -3503,6 +4458,7 @@ impl Row {
         Row {
             columns,
             is_canonical: false,
+            prompt_marks: PromptMarks::default(),
             width: None,
         }
     }
@@ -3513,6 +4469,7 @@ impl Row {
             let mut first_row = rows.remove(0);
             for row in &mut rows {
                 first_row.append(&mut row.columns);
+                first_row.prompt_marks.merge(row.prompt_marks);
             }
             first_row
         }
@@ -3803,15 +4760,22 @@ impl Row {
                 part.is_canonical = true;
             }
         }
+        if let Some(part) = parts.get_mut(0) {
+            part.prompt_marks = self.prompt_marks;
+        }
         if parts.is_empty() {
             parts.push(self.clone());
         }
         self.width = None;
         parts
     }
-    pub fn word_indices_around_character_index(&self, index: usize) -> Option<(usize, usize)> {
+    pub fn word_indices_around_character_index(
+        &self,
+        index: usize,
+        word_separators: &str,
+    ) -> Option<(usize, usize)> {
         let character_at_index = self.columns.get(index)?;
-        if is_selection_boundary_character(character_at_index.character) {
+        if is_selection_boundary_character(character_at_index.character, word_separators) {
             return Some((index, index + 1));
         }
         let mut end_position = self
@@ -3820,7 +4784,7 @@ impl Row {
             .enumerate()
             .skip(index)
             .find_map(|(i, t_c)| {
-                if is_selection_boundary_character(t_c.character) {
+                if is_selection_boundary_character(t_c.character, word_separators) {
                     Some(i)
                 } else {
                     None
@@ -3834,7 +4798,7 @@ impl Row {
             .take(index)
             .rev()
             .find_map(|(i, t_c)| {
-                if is_selection_boundary_character(t_c.character) {
+                if is_selection_boundary_character(t_c.character, word_separators) {
                     Some(i + 1)
                 } else {
                     None
@@ -3847,13 +4811,13 @@ impl Row {
         }
         Some((start_position, end_position))
     }
-    pub fn word_start_index_of_last_character(&self) -> usize {
+    pub fn word_start_index_of_last_character(&self, word_separators: &str) -> usize {
         self.columns
             .iter()
             .enumerate()
             .rev()
             .find_map(|(i, t_c)| {
-                if is_selection_boundary_character(t_c.character) {
+                if is_selection_boundary_character(t_c.character, word_separators) {
                     Some(i + 1)
                 } else {
                     None
@@ -3861,12 +4825,12 @@ impl Row {
             })
             .unwrap_or(0)
     }
-    pub fn word_end_index_of_first_character(&self) -> usize {
+    pub fn word_end_index_of_first_character(&self, word_separators: &str) -> usize {
         self.columns
             .iter()
             .enumerate()
             .find_map(|(i, t_c)| {
-                if is_selection_boundary_character(t_c.character) {
+                if is_selection_boundary_character(t_c.character, word_separators) {
                     Some(i)
                 } else {
                     None
@@ -3876,16 +4840,8 @@ impl Row {
     }
 }
 
-fn is_selection_boundary_character(character: char) -> bool {
-    character.is_ascii_whitespace()
-        || character == '['
-        || character == ']'
-        || character == '{'
-        || character == '}'
-        || character == '<'
-        || character == '>'
-        || character == '('
-        || character == ')'
+fn is_selection_boundary_character(character: char, word_separators: &str) -> bool {
+    character.is_ascii_whitespace() || word_separators.contains(character)
 }
 
 #[cfg(test)]
@@ -0,0 +1,915 @@
+//! The kitty graphics protocol (https://sw.kovidgoyal.net/kitty/graphics-protocol/)
+//!
+//! The protocol commands are APC sequences (`ESC _ G <control data> ; <payload> ESC \`), which
+//! our vte parser ignores, so they are picked out of the pane's bytes here. Images transmitted
+//! by the application are stored and their placements tracked in the lines of the grid, so that
+//! they can be (re)placed in the terminals of our clients wherever the lines they are anchored to
+//! are visible, and removed once they're not.
+//!
+//! Only direct transmission (`t=d`) is supported: the file and shared memory transmission media
+//! would be read by the terminals of our clients, which might not be on this machine.
+use base64::{decode_config, STANDARD, STANDARD_NO_PAD};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use zellij_utils::pane_size::{PaneGeom, SizeInPixels};
+
+// images are identified by these ids in the terminals of our clients, which display the images of
+// all panes, so they must be unique across panes
+static NEXT_IMAGE_ID: AtomicU32 = AtomicU32::new(1);
+static NEXT_PLACEMENT_ID: AtomicU32 = AtomicU32::new(1);
+
+// the storage quota of each pane, which is lower than that of kitty (320MiB for all its windows)
+// since a session can have many panes showing images
+const MAX_STORED_BYTES: usize = 64 * 1024 * 1024;
+const MAX_COMMAND_LENGTH: usize = 16 * 1024 * 1024; // chunked commands are much shorter than this
+const RGB_FORMAT: u32 = 24;
+const RGBA_FORMAT: u32 = 32;
+const PNG_FORMAT: u32 = 100;
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// the keys of the placement related control data, which we pass on when placing images
+const PLACEMENT_KEYS: &[char] = &['x', 'y', 'w', 'h', 'X', 'Y', 'c', 'r', 'z'];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ScannerState {
+    #[default]
+    Ground,
+    Escape,
+    ApcStart,
+    Command,
+    CommandEscape,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KittyGraphicsCommand {
+    control_data: Vec<(char, String)>,
+    payload: String,
+}
+
+impl KittyGraphicsCommand {
+    pub fn parse(bytes: &[u8]) -> Self {
+        let command = String::from_utf8_lossy(bytes);
+        let (control_data, payload) = match command.split_once(';') {
+            Some((control_data, payload)) => (control_data, payload),
+            None => (&*command, ""),
+        };
+        let control_data = control_data
+            .split(',')
+            .filter_map(|key_value| {
+                let (key, value) = key_value.split_once('=')?;
+                let mut key_chars = key.chars();
+                match (key_chars.next(), key_chars.next()) {
+                    (Some(key), None) => Some((key, value.to_owned())),
+                    _ => None,
+                }
+            })
+            .collect();
+        KittyGraphicsCommand {
+            control_data,
+            payload: payload.to_owned(),
+        }
+    }
+    pub fn get(&self, key: char) -> Option<&str> {
+        self.control_data
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value.as_str())
+    }
+    pub fn get_number(&self, key: char) -> Option<u32> {
+        self.get(key).and_then(|value| value.parse().ok())
+    }
+    fn get_char(&self, key: char) -> Option<char> {
+        self.get(key).and_then(|value| value.chars().next())
+    }
+    fn action(&self) -> char {
+        self.get_char('a').unwrap_or('t')
+    }
+    fn has_more_chunks(&self) -> bool {
+        self.get_number('m') == Some(1)
+    }
+    fn with(mut self, key: char, value: impl ToString) -> Self {
+        self.control_data.retain(|(k, _)| *k != key);
+        self.control_data.push((key, value.to_string()));
+        self
+    }
+    fn only_keys(mut self, keys: &[char]) -> Self {
+        self.control_data.retain(|(k, _)| keys.contains(k));
+        self
+    }
+    fn without_payload(mut self) -> Self {
+        self.payload.clear();
+        self
+    }
+    pub fn serialize(&self) -> String {
+        let control_data: Vec<String> = self
+            .control_data
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        if self.payload.is_empty() {
+            format!("\u{1b}_G{}\u{1b}\\", control_data.join(","))
+        } else {
+            format!(
+                "\u{1b}_G{};{}\u{1b}\\",
+                control_data.join(","),
+                self.payload
+            )
+        }
+    }
+}
+
+/// Removes the kitty graphics commands from output meant for terminals that do not support them
+pub fn remove_kitty_graphics_commands(vte_output: &str) -> String {
+    let mut remaining = vte_output;
+    let mut stripped = String::with_capacity(vte_output.len());
+    while let Some(command_start) = remaining.find("\u{1b}_G") {
+        stripped.push_str(&remaining[..command_start]);
+        remaining = match remaining[command_start..].find("\u{1b}\\") {
+            Some(command_length) => &remaining[command_start + command_length + 2..],
+            None => "",
+        };
+    }
+    stripped.push_str(remaining);
+    stripped
+}
+
+#[derive(Debug, Clone)]
+struct StoredImage {
+    transmission: Vec<KittyGraphicsCommand>,
+    size_in_pixels: Option<(usize, usize)>, // (width, height)
+    size_in_bytes: usize,
+}
+
+#[derive(Debug, Clone)]
+struct PendingTransmission {
+    image_id: u32,
+    app_image_id: Option<u32>,
+    app_image_number: Option<u32>,
+    command: KittyGraphicsCommand,
+    too_large: bool, // the image was dropped, its remaining chunks are ignored
+}
+
+#[derive(Debug, Clone)]
+struct Placement {
+    image_id: u32,
+    placement_id: u32,
+    app_placement_id: Option<u32>,
+    line: usize, // counted from the first line the pane ever had, so it does not change as lines scroll
+    column: usize,
+    rows: usize,
+    columns: usize,
+    put_command: KittyGraphicsCommand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RenderedPlacement {
+    x: usize,
+    y: usize,
+    put_command: String,
+}
+
+// the cells of a placement on screen, (left, top, right, bottom) with the right and bottom
+// exclusive
+type Area = (usize, usize, usize, usize);
+
+/// The result of a command that the grid should act on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KittyGraphicsCommandResult {
+    pub reply: Option<String>,
+    pub cursor_movement: Option<(usize, usize)>, // (rows, columns) covered by a new placement
+}
+
+#[derive(Debug, Default)]
+pub struct KittyGraphics {
+    scanner_state: ScannerState,
+    command_bytes: Vec<u8>,
+    images: HashMap<u32, StoredImage>,
+    image_order: VecDeque<u32>,
+    stored_bytes: usize,
+    app_image_ids: HashMap<u32, u32>,
+    app_image_numbers: HashMap<u32, u32>,
+    pending_transmission: Option<PendingTransmission>,
+    placements: Vec<Placement>,
+    rendered_placements: HashMap<u32, RenderedPlacement>, // placement id => where it is on screen
+    covered_areas: Vec<PaneGeom>, // of the floating panes above the pane, on screen
+    transmitted_images: HashSet<u32>,
+    placements_to_delete: Vec<(u32, u32)>, // (image id, placement id)
+    images_to_delete: Vec<u32>,
+    force_render: bool,
+}
+
+impl KittyGraphics {
+    /// Returns a complete command once its last byte has been handled
+    pub fn handle_byte(&mut self, byte: u8) -> Option<KittyGraphicsCommand> {
+        match (self.scanner_state, byte) {
+            (ScannerState::Ground, 0x1b) => self.scanner_state = ScannerState::Escape,
+            (ScannerState::Ground, _) => {},
+            (ScannerState::Escape, b'_') => self.scanner_state = ScannerState::ApcStart,
+            (ScannerState::Escape, 0x1b) => {},
+            (ScannerState::ApcStart, b'G') => {
+                self.command_bytes.clear();
+                self.scanner_state = ScannerState::Command;
+            },
+            (ScannerState::Command, 0x1b) => self.scanner_state = ScannerState::CommandEscape,
+            (ScannerState::Command, byte) => {
+                if self.command_bytes.len() >= MAX_COMMAND_LENGTH {
+                    log::error!("Dropping kitty graphics command that is too long");
+                    self.command_bytes = vec![];
+                    self.scanner_state = ScannerState::Ground;
+                } else {
+                    self.command_bytes.push(byte);
+                }
+            },
+            (ScannerState::CommandEscape, b'\\') => {
+                self.scanner_state = ScannerState::Ground;
+                let command_bytes = std::mem::take(&mut self.command_bytes);
+                return Some(KittyGraphicsCommand::parse(&command_bytes));
+            },
+            (ScannerState::CommandEscape, 0x1b) => {
+                self.command_bytes.clear();
+                self.scanner_state = ScannerState::Escape;
+            },
+            _ => {
+                self.command_bytes.clear();
+                self.scanner_state = ScannerState::Ground;
+            },
+        }
+        None
+    }
+    /// `cursor_line` is the line of the cursor counted from the first line the pane ever had
+    pub fn handle_command(
+        &mut self,
+        command: KittyGraphicsCommand,
+        cursor_line: usize,
+        cursor_column: usize,
+        character_cell_size: Option<SizeInPixels>,
+    ) -> KittyGraphicsCommandResult {
+        if let Some(pending_transmission) = self.pending_transmission.take() {
+            // the chunks after the first only have the m (and q) keys
+            if command.get('a').is_none()
+                && command.get('i').is_none()
+                && command.get('I').is_none()
+            {
+                return self.add_chunk(
+                    pending_transmission,
+                    command,
+                    cursor_line,
+                    cursor_column,
+                    character_cell_size,
+                );
+            }
+            self.remove_image(pending_transmission.image_id);
+        }
+        match command.action() {
+            'q' => KittyGraphicsCommandResult {
+                reply: reply_to(&command, command.get_number('i'), None, query(&command)),
+                ..Default::default()
+            },
+            't' | 'T' => {
+                let image_id = NEXT_IMAGE_ID.fetch_add(1, Ordering::SeqCst);
+                let pending_transmission = PendingTransmission {
+                    image_id,
+                    app_image_id: command.get_number('i'),
+                    app_image_number: command.get_number('I'),
+                    command: command.clone().without_payload(),
+                    too_large: false,
+                };
+                let first_chunk = command
+                    .only_keys(&['f', 't', 'o', 's', 'v', 'S', 'O', 'm'])
+                    .with('a', 't')
+                    .with('i', image_id)
+                    .with('q', 2);
+                self.images.insert(
+                    image_id,
+                    StoredImage {
+                        size_in_bytes: first_chunk.payload.len(),
+                        transmission: vec![first_chunk.clone()],
+                        size_in_pixels: None,
+                    },
+                );
+                self.stored_bytes += first_chunk.payload.len();
+                self.image_order.push_back(image_id);
+                self.finish_chunk(
+                    pending_transmission,
+                    &first_chunk,
+                    cursor_line,
+                    cursor_column,
+                    character_cell_size,
+                )
+            },
+            'p' => {
+                let image_id = command
+                    .get_number('i')
+                    .and_then(|app_image_id| self.app_image_ids.get(&app_image_id))
+                    .or_else(|| {
+                        command.get_number('I').and_then(|app_image_number| {
+                            self.app_image_numbers.get(&app_image_number)
+                        })
+                    })
+                    .copied();
+                match image_id {
+                    Some(image_id) => self.place(
+                        image_id,
+                        &command,
+                        cursor_line,
+                        cursor_column,
+                        character_cell_size,
+                    ),
+                    None => KittyGraphicsCommandResult {
+                        reply: reply_to(
+                            &command,
+                            command.get_number('i'),
+                            command.get_number('p'),
+                            Err("ENOENT:image not found"),
+                        ),
+                        ..Default::default()
+                    },
+                }
+            },
+            'd' => {
+                self.delete(&command);
+                Default::default()
+            },
+            _ => Default::default(),
+        }
+    }
+    fn add_chunk(
+        &mut self,
+        mut pending_transmission: PendingTransmission,
+        command: KittyGraphicsCommand,
+        cursor_line: usize,
+        cursor_column: usize,
+        character_cell_size: Option<SizeInPixels>,
+    ) -> KittyGraphicsCommandResult {
+        let chunk = command.only_keys(&['m']).with('q', 2);
+        if !pending_transmission.too_large {
+            match self.images.get_mut(&pending_transmission.image_id) {
+                Some(image) => {
+                    image.size_in_bytes += chunk.payload.len();
+                    image.transmission.push(chunk.clone());
+                },
+                None => return Default::default(),
+            }
+            self.stored_bytes += chunk.payload.len();
+            self.drop_image_if_too_large(&mut pending_transmission);
+        }
+        self.finish_chunk(
+            pending_transmission,
+            &chunk,
+            cursor_line,
+            cursor_column,
+            character_cell_size,
+        )
+    }
+    fn finish_chunk(
+        &mut self,
+        pending_transmission: PendingTransmission,
+        chunk: &KittyGraphicsCommand,
+        cursor_line: usize,
+        cursor_column: usize,
+        character_cell_size: Option<SizeInPixels>,
+    ) -> KittyGraphicsCommandResult {
+        if chunk.has_more_chunks() {
+            self.pending_transmission = Some(pending_transmission);
+            return Default::default();
+        }
+        let PendingTransmission {
+            image_id,
+            app_image_id,
+            app_image_number,
+            command,
+            too_large,
+        } = pending_transmission;
+        let reply_id = match (app_image_id, app_image_number) {
+            (Some(app_image_id), _) => Some(app_image_id),
+            (None, Some(_)) => Some(image_id),
+            (None, None) => None,
+        };
+        if too_large {
+            return KittyGraphicsCommandResult {
+                reply: reply_to(&command, reply_id, None, Err("EFBIG:image is too large")),
+                ..Default::default()
+            };
+        }
+        if command.get_char('t').unwrap_or('d') != 'd' {
+            self.remove_image(image_id);
+            return KittyGraphicsCommandResult {
+                reply: reply_to(
+                    &command,
+                    reply_id,
+                    None,
+                    Err("EINVAL:only direct transmission is supported"),
+                ),
+                ..Default::default()
+            };
+        }
+        if let Some(image) = self.images.get_mut(&image_id) {
+            image.size_in_pixels = size_in_pixels(&command, &image.transmission);
+        }
+        if let Some(app_image_id) = app_image_id {
+            // transmitting an image with an id that is in use replaces that image
+            if let Some(previous_image_id) = self.app_image_ids.insert(app_image_id, image_id) {
+                self.remove_image(previous_image_id);
+            }
+        }
+        if let Some(app_image_number) = app_image_number {
+            self.app_image_numbers.insert(app_image_number, image_id);
+            // we reply with our id, which the application can then use
+            self.app_image_ids.insert(image_id, image_id);
+        }
+        self.evict_images_over_quota();
+        let mut reply = reply_to(&command, reply_id, None, Ok(()));
+        let mut cursor_movement = None;
+        if command.action() == 'T' {
+            let result = self.place(
+                image_id,
+                &command,
+                cursor_line,
+                cursor_column,
+                character_cell_size,
+            );
+            reply = reply.or(result.reply);
+            cursor_movement = result.cursor_movement;
+        }
+        KittyGraphicsCommandResult {
+            reply,
+            cursor_movement,
+        }
+    }
+    fn place(
+        &mut self,
+        image_id: u32,
+        command: &KittyGraphicsCommand,
+        cursor_line: usize,
+        cursor_column: usize,
+        character_cell_size: Option<SizeInPixels>,
+    ) -> KittyGraphicsCommandResult {
+        if command.get_number('U') == Some(1) {
+            // unicode placeholders are placed by the text the application prints
+            return Default::default();
+        }
+        let size_in_pixels = self
+            .images
+            .get(&image_id)
+            .and_then(|image| image.size_in_pixels);
+        let (rows, columns) = size_in_cells(command, size_in_pixels, character_cell_size);
+        let app_placement_id = command.get_number('p');
+        if let Some(app_placement_id) = app_placement_id {
+            // placing an image with a placement id that is in use moves that placement
+            self.delete_placements(|p| {
+                p.image_id == image_id && p.app_placement_id == Some(app_placement_id)
+            });
+        }
+        let placement_id = NEXT_PLACEMENT_ID.fetch_add(1, Ordering::SeqCst);
+        let mut put_command = command
+            .clone()
+            .without_payload()
+            .only_keys(PLACEMENT_KEYS)
+            .with('a', 'p')
+            .with('i', image_id)
+            .with('p', placement_id)
+            .with('C', 1)
+            .with('q', 2);
+        if command.get('c').is_none() || command.get('r').is_none() {
+            // so that the image takes exactly the cells we think it does
+            put_command = put_command.with('c', columns).with('r', rows);
+        }
+        self.placements.push(Placement {
+            image_id,
+            placement_id,
+            app_placement_id,
+            line: cursor_line,
+            column: cursor_column,
+            rows,
+            columns,
+            put_command,
+        });
+        let reply_id = self
+            .app_image_ids
+            .iter()
+            .find(|(_, id)| **id == image_id)
+            .map(|(app_image_id, _)| *app_image_id);
+        KittyGraphicsCommandResult {
+            reply: reply_to(command, reply_id, app_placement_id, Ok(())),
+            cursor_movement: if command.get_number('C') == Some(1) {
+                None
+            } else {
+                Some((rows, columns))
+            },
+        }
+    }
+    fn delete(&mut self, command: &KittyGraphicsCommand) {
+        let deletion = command.get_char('d').unwrap_or('a');
+        let free_images = deletion.is_ascii_uppercase();
+        let image_id = match deletion.to_ascii_lowercase() {
+            'i' => command
+                .get_number('i')
+                .and_then(|app_image_id| self.app_image_ids.get(&app_image_id).copied()),
+            'n' => command
+                .get_number('I')
+                .and_then(|app_image_number| self.app_image_numbers.get(&app_image_number))
+                .copied(),
+            _ => None,
+        };
+        let app_placement_id = command.get_number('p');
+        let deleted_images: HashSet<u32> = match deletion.to_ascii_lowercase() {
+            'a' => self.delete_placements(|_| true),
+            'i' | 'n' => match image_id {
+                Some(image_id) => self.delete_placements(|p| {
+                    p.image_id == image_id
+                        && (app_placement_id.is_none() || p.app_placement_id == app_placement_id)
+                }),
+                None => return,
+            },
+            _ => {
+                log::warn!(
+                    "Unsupported kitty graphics deletion: {}",
+                    command.clone().without_payload().serialize()
+                );
+                return;
+            },
+        };
+        if free_images {
+            for image_id in deleted_images.into_iter().chain(image_id) {
+                if !self.placements.iter().any(|p| p.image_id == image_id) {
+                    self.remove_image(image_id);
+                }
+            }
+        }
+    }
+    // returns the ids of the images whose placements were deleted
+    fn delete_placements(&mut self, should_delete: impl Fn(&Placement) -> bool) -> HashSet<u32> {
+        let mut deleted_images = HashSet::new();
+        let placements_to_delete = &mut self.placements_to_delete;
+        self.placements.retain(|placement| {
+            if should_delete(placement) {
+                placements_to_delete.push((placement.image_id, placement.placement_id));
+                deleted_images.insert(placement.image_id);
+                false
+            } else {
+                true
+            }
+        });
+        deleted_images
+    }
+    fn remove_image(&mut self, image_id: u32) {
+        self.delete_placements(|p| p.image_id == image_id);
+        if let Some(image) = self.images.remove(&image_id) {
+            self.stored_bytes = self.stored_bytes.saturating_sub(image.size_in_bytes);
+        }
+        self.image_order.retain(|id| *id != image_id);
+        self.app_image_ids.retain(|_, id| *id != image_id);
+        self.app_image_numbers.retain(|_, id| *id != image_id);
+        if self.transmitted_images.remove(&image_id) {
+            self.images_to_delete.push(image_id);
+        }
+    }
+    // an image larger than the quota of the pane could only be stored by evicting all others
+    fn drop_image_if_too_large(&mut self, pending_transmission: &mut PendingTransmission) {
+        let image_size = self
+            .images
+            .get(&pending_transmission.image_id)
+            .map(|image| image.size_in_bytes)
+            .unwrap_or(0);
+        if image_size > MAX_STORED_BYTES {
+            self.remove_image(pending_transmission.image_id);
+            pending_transmission.too_large = true;
+        } else {
+            self.evict_images_over_quota();
+        }
+    }
+    fn evict_images_over_quota(&mut self) {
+        while self.stored_bytes > MAX_STORED_BYTES {
+            match self.image_order.front().copied() {
+                Some(oldest_image_id) => self.remove_image(oldest_image_id),
+                None => break,
+            }
+        }
+    }
+    /// Removes all placements, eg. when the screen is cleared (the images themselves are kept so
+    /// that the application can place them again)
+    pub fn clear(&mut self) {
+        self.delete_placements(|_| true);
+    }
+    /// The images' data is sent again along with their placements, eg. to newly attached clients
+    pub fn set_force_render(&mut self) {
+        self.force_render = true;
+    }
+    /// The geometries of the floating panes above this pane, which images are clipped to not
+    /// cover. Returns true if they changed.
+    pub fn set_covered_areas(&mut self, covered_areas: Vec<PaneGeom>) -> bool {
+        if self.covered_areas == covered_areas {
+            false
+        } else {
+            self.covered_areas = covered_areas;
+            true
+        }
+    }
+    /// `viewport_top_line` is the first line of the viewport counted from the first line the pane
+    /// ever had, images are only displayed when they're entirely in the viewport and are clipped
+    /// to the parts of it not covered by floating panes
+    pub fn render(
+        &mut self,
+        viewport_top_line: usize,
+        viewport_height: usize,
+        viewport_width: usize,
+        content_x: usize,
+        content_y: usize,
+    ) -> String {
+        let mut vte_output = String::new();
+        let force_render = std::mem::replace(&mut self.force_render, false);
+        if force_render {
+            // the terminals of our clients might not have our images (eg. newly attached ones),
+            // or might have deleted them before a forced render
+            self.transmitted_images.clear();
+        }
+        for image_id in self.images_to_delete.drain(..) {
+            vte_output.push_str(&format!("\u{1b}_Ga=d,d=I,i={},q=2\u{1b}\\", image_id));
+        }
+        for (image_id, placement_id) in self.placements_to_delete.drain(..) {
+            if self.rendered_placements.remove(&placement_id).is_some() {
+                vte_output.push_str(&delete_placement(image_id, placement_id));
+            }
+        }
+        for placement in &self.placements {
+            let is_visible = placement.line >= viewport_top_line
+                && placement.line + placement.rows <= viewport_top_line + viewport_height
+                && placement.column + placement.columns <= viewport_width;
+            if !is_visible {
+                if self
+                    .rendered_placements
+                    .remove(&placement.placement_id)
+                    .is_some()
+                {
+                    vte_output.push_str(&delete_placement(
+                        placement.image_id,
+                        placement.placement_id,
+                    ));
+                }
+                continue;
+            }
+            let x = content_x + placement.column;
+            let y = content_y + (placement.line - viewport_top_line);
+            let area = (x, y, x + placement.columns, y + placement.rows);
+            let size_in_pixels = self
+                .images
+                .get(&placement.image_id)
+                .and_then(|image| image.size_in_pixels);
+            let rendered_placement =
+                uncovered_area(area, &self.covered_areas).and_then(|visible| {
+                    let put_command = if visible == area {
+                        placement.put_command.clone()
+                    } else {
+                        clip_put_command(&placement.put_command, area, visible, size_in_pixels?)
+                    };
+                    Some(RenderedPlacement {
+                        x: visible.0,
+                        y: visible.1,
+                        put_command: put_command.serialize(),
+                    })
+                });
+            let rendered_placement = match rendered_placement {
+                Some(rendered_placement) => rendered_placement,
+                None => {
+                    if self
+                        .rendered_placements
+                        .remove(&placement.placement_id)
+                        .is_some()
+                    {
+                        vte_output.push_str(&delete_placement(
+                            placement.image_id,
+                            placement.placement_id,
+                        ));
+                    }
+                    continue;
+                },
+            };
+            if !force_render
+                && self.rendered_placements.get(&placement.placement_id)
+                    == Some(&rendered_placement)
+            {
+                continue;
+            }
+            if self.transmitted_images.insert(placement.image_id) {
+                if let Some(image) = self.images.get(&placement.image_id) {
+                    for chunk in &image.transmission {
+                        vte_output.push_str(&chunk.serialize());
+                    }
+                }
+            }
+            // placing an image with a placement id that is in use moves (or re-clips) it
+            vte_output.push_str(&format!(
+                "\u{1b}[{};{}H{}",
+                rendered_placement.y + 1,
+                rendered_placement.x + 1,
+                rendered_placement.put_command
+            ));
+            self.rendered_placements
+                .insert(placement.placement_id, rendered_placement);
+        }
+        vte_output
+    }
+}
+
+// the part of the area that is not covered, if it is a rectangle - an area covered in its middle
+// or corner is not displayed at all, since an image placement can only be clipped to a rectangle
+fn uncovered_area(area: Area, covered_areas: &[PaneGeom]) -> Option<Area> {
+    let (mut left, mut top, mut right, mut bottom) = area;
+    for covered_area in covered_areas {
+        let covered_left = covered_area.x;
+        let covered_top = covered_area.y;
+        let covered_right = covered_area.x + covered_area.cols.as_usize();
+        let covered_bottom = covered_area.y + covered_area.rows.as_usize();
+        let overlaps = covered_left < right
+            && covered_right > left
+            && covered_top < bottom
+            && covered_bottom > top;
+        if !overlaps {
+            continue;
+        }
+        let covers_all_rows = covered_top <= top && covered_bottom >= bottom;
+        let covers_all_columns = covered_left <= left && covered_right >= right;
+        if covers_all_rows && covered_left <= left {
+            left = covered_right;
+        } else if covers_all_rows && covered_right >= right {
+            right = covered_left;
+        } else if covers_all_columns && covered_top <= top {
+            top = covered_bottom;
+        } else if covers_all_columns && covered_bottom >= bottom {
+            bottom = covered_top;
+        } else {
+            return None;
+        }
+        if left >= right || top >= bottom {
+            return None;
+        }
+    }
+    Some((left, top, right, bottom))
+}
+
+// places only the source rectangle of the image that is displayed in the visible part of the area
+fn clip_put_command(
+    put_command: &KittyGraphicsCommand,
+    area: Area,
+    visible: Area,
+    size_in_pixels: (usize, usize),
+) -> KittyGraphicsCommand {
+    let (image_width, image_height) = size_in_pixels;
+    let source_x = put_command.get_number('x').unwrap_or(0) as usize;
+    let source_y = put_command.get_number('y').unwrap_or(0) as usize;
+    let source_width = put_command
+        .get_number('w')
+        .map(|w| w as usize)
+        .unwrap_or_else(|| image_width.saturating_sub(source_x));
+    let source_height = put_command
+        .get_number('h')
+        .map(|h| h as usize)
+        .unwrap_or_else(|| image_height.saturating_sub(source_y));
+    let columns = area.2 - area.0;
+    let rows = area.3 - area.1;
+    let mut clipped = put_command
+        .clone()
+        .with(
+            'x',
+            source_x + (visible.0 - area.0) * source_width / columns,
+        )
+        .with('y', source_y + (visible.1 - area.1) * source_height / rows)
+        .with('w', (visible.2 - visible.0) * source_width / columns)
+        .with('h', (visible.3 - visible.1) * source_height / rows)
+        .with('c', visible.2 - visible.0)
+        .with('r', visible.3 - visible.1);
+    // the pixel offsets are within the first cell, which is clipped away
+    if visible.0 > area.0 {
+        clipped = clipped.with('X', 0);
+    }
+    if visible.1 > area.1 {
+        clipped = clipped.with('Y', 0);
+    }
+    clipped
+}
+
+fn delete_placement(image_id: u32, placement_id: u32) -> String {
+    format!(
+        "\u{1b}_Ga=d,d=i,i={},p={},q=2\u{1b}\\",
+        image_id, placement_id
+    )
+}
+
+// q=1 suppresses OK replies and q=2 all of them, commands without an id are not replied to
+fn reply_to(
+    command: &KittyGraphicsCommand,
+    image_id: Option<u32>,
+    placement_id: Option<u32>,
+    result: Result<(), &str>,
+) -> Option<String> {
+    let image_id = image_id?;
+    let quiet = command.get_number('q').unwrap_or(0);
+    let message = match result {
+        Ok(()) if quiet == 0 => "OK",
+        Err(error) if quiet < 2 => error,
+        _ => return None,
+    };
+    let mut control_data = format!("i={}", image_id);
+    if let Some(image_number) = command.get_number('I') {
+        control_data.push_str(&format!(",I={}", image_number));
+    }
+    if let Some(placement_id) = placement_id {
+        control_data.push_str(&format!(",p={}", placement_id));
+    }
+    Some(format!("\u{1b}_G{};{}\u{1b}\\", control_data, message))
+}
+
+// whether an image transmitted with the same control data and payload would be accepted, which is
+// how applications detect support for the protocol without storing anything
+fn query(command: &KittyGraphicsCommand) -> Result<(), &'static str> {
+    if command.get_char('t').unwrap_or('d') != 'd' {
+        return Err("EINVAL:only direct transmission is supported");
+    }
+    if command.has_more_chunks() {
+        return Err("EINVAL:queries cannot be chunked");
+    }
+    let data = decode_config(command.payload.trim_end_matches('='), STANDARD_NO_PAD)
+        .map_err(|_| "EINVAL:image data is not valid base64")?;
+    if command.get('o').is_some() {
+        // we do not decompress the data, so this is as far as we can check it
+        return if data.is_empty() {
+            Err("ENODATA:no image data")
+        } else {
+            Ok(())
+        };
+    }
+    match command.get_number('f').unwrap_or(RGBA_FORMAT) {
+        format @ (RGB_FORMAT | RGBA_FORMAT) => {
+            let bytes_per_pixel = if format == RGB_FORMAT { 3 } else { 4 };
+            let width = command.get_number('s').unwrap_or(0) as usize;
+            let height = command.get_number('v').unwrap_or(0) as usize;
+            if width == 0 || height == 0 {
+                Err("EINVAL:the width and height of the image are required")
+            } else if data.len() < width * height * bytes_per_pixel {
+                Err("ENODATA:insufficient image data")
+            } else {
+                Ok(())
+            }
+        },
+        PNG_FORMAT if data.starts_with(PNG_SIGNATURE) => Ok(()),
+        PNG_FORMAT => Err("EBADPNG:not a PNG image"),
+        _ => Err("EINVAL:unknown image format"),
+    }
+}
+
+// (width, height) from the s and v keys, or from the header of (uncompressed) PNG images
+fn size_in_pixels(
+    command: &KittyGraphicsCommand,
+    transmission: &[KittyGraphicsCommand],
+) -> Option<(usize, usize)> {
+    if command.get_number('f') != Some(PNG_FORMAT) {
+        let width = command.get_number('s')?;
+        let height = command.get_number('v')?;
+        return Some((width as usize, height as usize));
+    }
+    if command.get('o').is_some() {
+        return None;
+    }
+    // the width and height are the first fields of the IHDR chunk, which follows the signature
+    let first_chunk = transmission.first()?;
+    let header = decode_config(first_chunk.payload.get(..32)?, STANDARD).ok()?;
+    let width = u32::from_be_bytes(header.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(header.get(20..24)?.try_into().ok()?);
+    Some((width as usize, height as usize))
+}
+
+// (rows, columns)
+fn size_in_cells(
+    command: &KittyGraphicsCommand,
+    size_in_pixels: Option<(usize, usize)>,
+    character_cell_size: Option<SizeInPixels>,
+) -> (usize, usize) {
+    let displayed_size = size_in_pixels.map(|(width, height)| {
+        (
+            command.get_number('w').map(|w| w as usize).unwrap_or(width),
+            command
+                .get_number('h')
+                .map(|h| h as usize)
+                .unwrap_or(height),
+        )
+    });
+    let (columns_from_pixels, rows_from_pixels) = match (displayed_size, character_cell_size) {
+        (Some((width, height)), Some(cell_size)) if cell_size.width > 0 && cell_size.height > 0 => {
+            (
+                (width + command.get_number('X').unwrap_or(0) as usize).div_ceil(cell_size.width),
+                (height + command.get_number('Y').unwrap_or(0) as usize).div_ceil(cell_size.height),
+            )
+        },
+        _ => (1, 1),
+    };
+    let rows = command
+        .get_number('r')
+        .map(|r| r as usize)
+        .unwrap_or(rows_from_pixels);
+    let columns = command
+        .get_number('c')
+        .map(|c| c as usize)
+        .unwrap_or(columns_from_pixels);
+    (rows.max(1), columns.max(1))
+}
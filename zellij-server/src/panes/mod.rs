@@ -1,6 +1,12 @@
 pub mod alacritty_functions;
+pub mod closed_panes;
+pub mod command_history;
+pub mod copy_mode;
 pub mod grid;
+pub mod kitty_graphics;
 pub mod link_handler;
+pub mod output_monitor;
+pub mod scrollback_spill;
 pub mod selection;
 pub mod sixel;
 pub mod terminal_character;
@@ -0,0 +1,154 @@
+//! Activity and silence monitors, which raise an alert when a pane produces output or stays
+//! silent for a while. Alerts are only meant for panes the user is not looking at, so the tab
+//! clears them while the pane is focused.
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorAlert {
+    Activity,
+    Silence,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputMonitors {
+    activity: Option<bool>,            // Some(should_notify) while monitored
+    silence: Option<(Duration, bool)>, // Some((interval, should_notify)) while monitored
+    last_output: Instant,
+    silence_alerted: bool, // so that we alert once per silence, rather than once per check
+    alert: Option<MonitorAlert>,
+    new_alert: Option<(MonitorAlert, bool)>,
+}
+
+impl Default for OutputMonitors {
+    fn default() -> Self {
+        OutputMonitors {
+            activity: None,
+            silence: None,
+            last_output: Instant::now(),
+            silence_alerted: false,
+            alert: None,
+            new_alert: None,
+        }
+    }
+}
+
+impl OutputMonitors {
+    /// Returns whether activity is monitored after the toggle
+    pub fn toggle_activity(&mut self, should_notify: bool) -> bool {
+        self.activity = match self.activity {
+            Some(_) => None,
+            None => Some(should_notify),
+        };
+        if self.activity.is_none() && self.alert == Some(MonitorAlert::Activity) {
+            self.clear_alert();
+        }
+        self.activity.is_some()
+    }
+    /// Returns whether silence is monitored after the toggle
+    pub fn toggle_silence(&mut self, interval: Duration, should_notify: bool) -> bool {
+        self.silence = match self.silence {
+            Some(_) => None,
+            None => Some((interval, should_notify)),
+        };
+        if self.silence.is_none() && self.alert == Some(MonitorAlert::Silence) {
+            self.clear_alert();
+        }
+        // the silence is counted from now on
+        self.last_output = Instant::now();
+        self.silence_alerted = false;
+        self.silence.is_some()
+    }
+    pub fn output_received(&mut self, now: Instant) {
+        self.last_output = now;
+        self.silence_alerted = false;
+        if let Some(should_notify) = self.activity {
+            if self.alert != Some(MonitorAlert::Activity) {
+                self.raise(MonitorAlert::Activity, should_notify);
+            }
+        }
+    }
+    pub fn check_silence(&mut self, now: Instant) {
+        if let Some((interval, should_notify)) = self.silence {
+            if !self.silence_alerted && now.saturating_duration_since(self.last_output) >= interval
+            {
+                self.silence_alerted = true;
+                self.raise(MonitorAlert::Silence, should_notify);
+            }
+        }
+    }
+    /// The alert raised since the last time this was called, and whether it should be sent as a
+    /// desktop notification
+    pub fn drain_new_alert(&mut self) -> Option<(MonitorAlert, bool)> {
+        self.new_alert.take()
+    }
+    pub fn alert(&self) -> Option<MonitorAlert> {
+        self.alert
+    }
+    /// Returns whether there was an alert to clear
+    pub fn clear_alert(&mut self) -> bool {
+        self.new_alert = None;
+        self.alert.take().is_some()
+    }
+    fn raise(&mut self, alert: MonitorAlert, should_notify: bool) {
+        self.alert = Some(alert);
+        self.new_alert = Some((alert, should_notify));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activity_alert_is_raised_once_until_cleared() {
+        let mut monitors = OutputMonitors::default();
+        monitors.output_received(Instant::now());
+        assert_eq!(monitors.drain_new_alert(), None, "not monitored yet");
+        assert!(monitors.toggle_activity(true));
+        monitors.output_received(Instant::now());
+        assert_eq!(
+            monitors.drain_new_alert(),
+            Some((MonitorAlert::Activity, true))
+        );
+        monitors.output_received(Instant::now());
+        assert_eq!(monitors.drain_new_alert(), None, "alert is already raised");
+        assert_eq!(monitors.alert(), Some(MonitorAlert::Activity));
+        assert!(monitors.clear_alert());
+        monitors.output_received(Instant::now());
+        assert_eq!(
+            monitors.drain_new_alert(),
+            Some((MonitorAlert::Activity, true))
+        );
+        assert!(!monitors.toggle_activity(true));
+        assert_eq!(monitors.alert(), None, "alert is cleared when toggled off");
+    }
+
+    #[test]
+    fn silence_alert_is_raised_once_per_silence() {
+        let mut monitors = OutputMonitors::default();
+        let interval = Duration::from_secs(10);
+        assert!(monitors.toggle_silence(interval, false));
+        let start = Instant::now();
+        monitors.output_received(start);
+        monitors.check_silence(start + Duration::from_secs(5));
+        assert_eq!(
+            monitors.drain_new_alert(),
+            None,
+            "not silent for long enough"
+        );
+        monitors.check_silence(start + interval);
+        assert_eq!(
+            monitors.drain_new_alert(),
+            Some((MonitorAlert::Silence, false))
+        );
+        assert!(monitors.clear_alert());
+        monitors.check_silence(start + interval * 2);
+        assert_eq!(monitors.drain_new_alert(), None, "same silence");
+        monitors.output_received(start + interval * 2);
+        monitors.check_silence(start + interval * 3);
+        assert_eq!(
+            monitors.drain_new_alert(),
+            Some((MonitorAlert::Silence, false))
+        );
+    }
+}
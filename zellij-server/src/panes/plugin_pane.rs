@@ -10,7 +10,7 @@ use crate::panes::{
 };
 use crate::plugins::PluginInstruction;
 use crate::pty::VteBytes;
-use crate::tab::{AdjustedInput, Pane};
+use crate::tab::{AdjustedInput, Pane, PaneGroup};
 use crate::ui::{
     loading_indication::LoadingIndication,
     pane_boundaries_frame::{FrameParams, PaneFrame},
@@ -27,7 +27,7 @@ use zellij_utils::{
     channels::SenderWithContext,
     data::{Event, InputMode, Mouse, Palette, PaletteColor, Style, Styling},
     errors::prelude::*,
-    input::layout::Run,
+    input::layout::{Run, SizeLimits},
     input::mouse::{MouseEvent, MouseEventType},
     pane_size::PaneGeom,
     shared::make_terminal_title,
@@ -93,7 +93,9 @@ pub(crate) struct PluginPane {
     frame: HashMap<ClientId, PaneFrame>,
     borderless: bool,
     exclude_from_sync: bool,
+    size_limits: Option<SizeLimits>,
     pane_frame_color_override: Option<(PaletteColor, Option<String>)>,
+    group: Option<PaneGroup>,
     invoked_with: Option<Run>,
     loading_indication: LoadingIndication,
     requesting_permissions: Option<PluginPermission>,
@@ -142,6 +144,7 @@ impl PluginPane {
             terminal_emulator_colors,
             terminal_emulator_color_codes,
             exclude_from_sync: false,
+            size_limits: None,
             link_handler,
             character_cell_size,
             sixel_image_store,
@@ -149,6 +152,7 @@ impl PluginPane {
             grids: HashMap::new(),
             style,
             pane_frame_color_override: None,
+            group: None,
             invoked_with,
             loading_indication,
             requesting_permissions: None,
@@ -426,7 +430,8 @@ impl Pane for PluginPane {
                 pane_title,
                 frame_params,
             )
-            .is_pinned(is_pinned);
+            .is_pinned(is_pinned)
+            .group(self.group.as_ref().map(|group| group.name.clone()));
             if let Some((frame_color_override, _text)) = self.pane_frame_color_override.as_ref() {
                 frame.override_color(*frame_color_override);
             }
@@ -636,6 +641,12 @@ impl Pane for PluginPane {
     fn exclude_from_sync(&self) -> bool {
         self.exclude_from_sync
     }
+    fn set_size_limits(&mut self, size_limits: Option<SizeLimits>) {
+        self.size_limits = size_limits;
+    }
+    fn size_limits(&self) -> Option<SizeLimits> {
+        self.size_limits
+    }
     fn handle_right_click(&mut self, to: &Position, client_id: ClientId) {
         self.send_plugin_instructions
             .send(PluginInstruction::Update(vec![(
@@ -655,6 +666,15 @@ impl Pane for PluginPane {
         self.pane_frame_color_override
             .as_ref()
             .map(|(color, _text)| *color)
+            .or_else(|| self.group.as_ref().and_then(|group| group.color))
+    }
+    fn set_group(&mut self, group: Option<PaneGroup>) {
+        self.group = group;
+        self.set_should_render(true);
+        self.set_should_render_boundaries(true);
+    }
+    fn group(&self) -> Option<&PaneGroup> {
+        self.group.as_ref()
     }
     fn invoked_with(&self) -> &Option<Run> {
         &self.invoked_with
@@ -0,0 +1,211 @@
+//! Keeps the older lines of the scrollback of a pane with an "unlimited" scrollback_size in
+//! compressed files on disk, so that they can be paged back in when scrolling far up.
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::PathBuf;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use zellij_utils::errors::prelude::*;
+use zellij_utils::tempfile::{Builder, TempDir};
+
+use crate::panes::grid::{PromptMarks, Row};
+use crate::panes::terminal_character::{RcCharacterStyles, TerminalCharacter};
+
+/// How many lines are moved to (and back from) the disk at a time
+pub const SPILL_CHUNK_LINES: usize = 1000;
+const COMPRESSION_LEVEL: i32 = 3;
+
+struct SpilledChunk {
+    path: PathBuf,
+    // the styles are kept in memory and only referred to by index in the file, there are usually
+    // very few of them
+    styles: Vec<RcCharacterStyles>,
+    row_widths: Vec<usize>,
+}
+
+pub struct ScrollbackSpill {
+    folder: TempDir,
+    chunks: Vec<SpilledChunk>, // oldest first
+    next_chunk_id: usize,
+}
+
+impl ScrollbackSpill {
+    pub fn new() -> Result<Self> {
+        let folder = Builder::new()
+            .prefix("zellij-scrollback-")
+            .tempdir()
+            .context("failed to create scrollback folder")?;
+        Ok(ScrollbackSpill {
+            folder,
+            chunks: vec![],
+            next_chunk_id: 0,
+        })
+    }
+    /// Moves these rows (the oldest in memory) to a new file on disk
+    pub fn spill(&mut self, rows: Vec<Row>) -> Result<()> {
+        let path = self
+            .folder
+            .path()
+            .join(format!("chunk-{}", self.next_chunk_id));
+        self.next_chunk_id += 1;
+        let mut styles: Vec<RcCharacterStyles> = vec![];
+        let mut row_widths = Vec::with_capacity(rows.len());
+        let mut encoded = vec![];
+        for row in &rows {
+            row_widths.push(row.width());
+            // the prompt marks of the row share its first byte
+            encoded.write_u8(row.is_canonical as u8 | row.prompt_marks.bits() << 1)?;
+            encoded.write_u32::<LittleEndian>(row.columns.len() as u32)?;
+            for character in &row.columns {
+                let style_index = style_index(&mut styles, &character.styles);
+                encoded.write_u32::<LittleEndian>(character.character as u32)?;
+                encoded.write_u8(character.width() as u8)?;
+                encoded.write_u32::<LittleEndian>(style_index as u32)?;
+            }
+        }
+        let compressed = zstd::encode_all(encoded.as_slice(), COMPRESSION_LEVEL)
+            .context("failed to compress scrollback")?;
+        std::fs::write(&path, compressed)
+            .with_context(|| format!("failed to write scrollback to {:?}", path))?;
+        self.chunks.push(SpilledChunk {
+            path,
+            styles,
+            row_widths,
+        });
+        Ok(())
+    }
+    /// Takes the newest chunk of rows back from the disk
+    pub fn page_in(&mut self) -> Result<Option<Vec<Row>>> {
+        match self.chunks.pop() {
+            Some(chunk) => {
+                let rows = read_chunk(&chunk);
+                let _ = std::fs::remove_file(&chunk.path);
+                rows.map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+    /// All the rows on disk, oldest first, without removing them
+    pub fn read_all(&self) -> Result<Vec<Row>> {
+        let mut rows = vec![];
+        for chunk in &self.chunks {
+            rows.append(&mut read_chunk(chunk)?);
+        }
+        Ok(rows)
+    }
+    pub fn line_count(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.row_widths.len()).sum()
+    }
+    pub fn row_widths(&self) -> impl Iterator<Item = usize> + '_ {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| chunk.row_widths.iter().copied())
+    }
+    pub fn clear(&mut self) {
+        for chunk in self.chunks.drain(..) {
+            let _ = std::fs::remove_file(&chunk.path);
+        }
+    }
+}
+
+fn style_index(styles: &mut Vec<RcCharacterStyles>, character_styles: &RcCharacterStyles) -> usize {
+    // consecutive characters mostly share their styles, so we look from the end
+    match styles.iter().rposition(|s| s == character_styles) {
+        Some(index) => index,
+        None => {
+            styles.push(character_styles.clone());
+            styles.len() - 1
+        },
+    }
+}
+
+fn read_chunk(chunk: &SpilledChunk) -> Result<Vec<Row>> {
+    let err_context = || format!("failed to read scrollback from {:?}", chunk.path);
+    let file = File::open(&chunk.path).with_context(err_context)?;
+    let mut decoded = vec![];
+    zstd::Decoder::new(BufReader::new(file))
+        .and_then(|mut decoder| decoder.read_to_end(&mut decoded))
+        .with_context(err_context)?;
+    let mut decoded = Cursor::new(decoded);
+    let mut rows = Vec::with_capacity(chunk.row_widths.len());
+    for _ in 0..chunk.row_widths.len() {
+        let flags = decoded.read_u8()?;
+        let character_count = decoded.read_u32::<LittleEndian>()? as usize;
+        let mut row = Row::new();
+        for _ in 0..character_count {
+            let character = char::from_u32(decoded.read_u32::<LittleEndian>()?)
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+            let width = decoded.read_u8()?;
+            let styles = chunk
+                .styles
+                .get(decoded.read_u32::<LittleEndian>()? as usize)
+                .cloned()
+                .unwrap_or_default();
+            let terminal_character = TerminalCharacter::new_styled(character, styles.clone());
+            // characters can be forced to a single column (eg. the ones we draw ourselves)
+            if width == 1 && terminal_character.width() != 1 {
+                row.columns
+                    .push_back(TerminalCharacter::new_singlewidth_styled(character, styles));
+            } else {
+                row.columns.push_back(terminal_character);
+            }
+        }
+        row.is_canonical = flags & 1 == 1;
+        row.prompt_marks = PromptMarks::from_bits(flags >> 1);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::panes::terminal_character::{AnsiCode, RESET_STYLES};
+
+    fn row(text: &str, styles: RcCharacterStyles, is_canonical: bool) -> Row {
+        let mut row = Row::new();
+        for character in text.chars() {
+            row.columns
+                .push_back(TerminalCharacter::new_styled(character, styles.clone()));
+        }
+        row.is_canonical = is_canonical;
+        row
+    }
+
+    #[test]
+    fn spilled_rows_are_paged_back_in_newest_first() {
+        let bold: RcCharacterStyles = RESET_STYLES.bold(Some(AnsiCode::On)).into();
+        let mut spill = ScrollbackSpill::new().unwrap();
+        spill
+            .spill(vec![
+                row("first", RcCharacterStyles::reset(), true),
+                row("second", bold.clone(), false),
+            ])
+            .unwrap();
+        spill
+            .spill(vec![row("third 文字", bold.clone(), true)])
+            .unwrap();
+        assert_eq!(spill.line_count(), 3);
+        assert_eq!(spill.row_widths().collect::<Vec<_>>(), vec![5, 6, 10]);
+        assert_eq!(
+            spill
+                .read_all()
+                .unwrap()
+                .iter()
+                .map(|row| format!("{:?}", row))
+                .collect::<Vec<_>>(),
+            vec!["first", "second", "third 文字"]
+        );
+
+        let newest = spill.page_in().unwrap().unwrap();
+        assert_eq!(format!("{:?}", newest[0]), "third 文字");
+        assert_eq!(newest[0].width(), 10);
+        let oldest = spill.page_in().unwrap().unwrap();
+        assert!(oldest[0].is_canonical);
+        assert!(!oldest[1].is_canonical);
+        assert_eq!(oldest[1].columns[0].styles, bold);
+        assert_eq!(oldest[0].columns[0].styles, RcCharacterStyles::reset());
+        assert!(spill.page_in().unwrap().is_none());
+        assert_eq!(spill.line_count(), 0);
+    }
+}
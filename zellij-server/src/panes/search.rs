@@ -5,6 +5,7 @@ use std::borrow::Cow;
 use std::fmt::Debug;
 use zellij_utils::input::actions::SearchDirection;
 use zellij_utils::position::Position;
+use zellij_utils::regex::{Regex, RegexBuilder};
 
 // If char is neither alphanumeric nor an underscore do we consider it a word-boundary
 fn is_word_boundary(x: &Option<char>) -> bool {
@@ -84,6 +85,51 @@ impl<'a> SearchSource<'a> {
     }
 }
 
+// The tail may have not been wrapped yet (when coming from lines_below),
+// so it could be that the end extends across more characters than the row is wide.
+// Therefore we need to reflow the end:
+fn reflow_selection_ends(selections: &mut [Selection], row: &Row) {
+    for s in selections.iter_mut() {
+        while s.end.column() > row.width() {
+            s.end.column.0 -= row.width();
+            s.end.line.0 += 1;
+        }
+    }
+}
+
+// The row and its tail are searched as one line, so that matches can wrap. Only the matches that
+// start in the row itself are returned, the others are found when searching the following rows.
+fn search_row_with_regex(regex: &Regex, ridx: usize, row: &Row, tail: &[&Row]) -> Vec<Selection> {
+    let mut haystack = String::new();
+    // The byte offset of every character in the haystack and where it is in the viewport
+    let mut positions: Vec<(usize, Position)> = Vec::new();
+    for (line, row) in std::iter::once(row).chain(tail.iter().copied()).enumerate() {
+        for (column, terminal_character) in row.columns.iter().enumerate() {
+            positions.push((
+                haystack.len(),
+                Position::new((ridx + line) as i32, column as u16),
+            ));
+            haystack.push(terminal_character.character);
+        }
+    }
+    let mut res = Vec::new();
+    for found in regex.find_iter(&haystack) {
+        if found.start() == found.end() {
+            continue;
+        }
+        let start = positions[positions.partition_point(|(offset, _)| *offset < found.start())].1;
+        if start.line() as usize != ridx {
+            break;
+        }
+        let end = positions[positions.partition_point(|(offset, _)| *offset < found.end()) - 1].1;
+        let mut selection = Selection::default();
+        selection.start(start);
+        selection.end(Position::new(end.line() as i32, (end.column() + 1) as u16));
+        res.push(selection);
+    }
+    res
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SearchResult {
     // What we have already found in the viewport
@@ -98,6 +144,10 @@ pub struct SearchResult {
     pub whole_word_only: bool, // TODO
     // Jump from the bottom to the top (or vice versa), if we run out of lines to search
     pub wrap_search: bool,
+    // Interpret the needle as a regular expression
+    pub regex_search: bool,
+    // The needle compiled with the current options, None if it is not a valid regex
+    compiled_regex: Option<Regex>,
 }
 
 impl SearchResult {
@@ -141,7 +191,24 @@ impl SearchResult {
     }
 
     pub fn has_modifiers_set(&self) -> bool {
-        self.wrap_search || self.whole_word_only || self.case_insensitive
+        self.wrap_search || self.whole_word_only || self.case_insensitive || self.regex_search
+    }
+
+    /// Needs to be called whenever the needle or one of the options it is compiled with changes
+    pub(crate) fn update_regex(&mut self) {
+        self.compiled_regex = if self.regex_search && !self.needle.is_empty() {
+            let pattern = if self.whole_word_only {
+                format!(r"\b(?:{})\b", self.needle)
+            } else {
+                self.needle.clone()
+            };
+            RegexBuilder::new(&pattern)
+                .case_insensitive(self.case_insensitive)
+                .build()
+                .ok()
+        } else {
+            None
+        };
     }
 
     fn check_if_haystack_char_matches_needle(
@@ -181,6 +248,13 @@ impl SearchResult {
         if self.needle.is_empty() || row.columns.is_empty() {
             return res;
         }
+        if self.regex_search {
+            if let Some(regex) = &self.compiled_regex {
+                res = search_row_with_regex(regex, ridx, row, tail);
+                reflow_selection_ends(&mut res, row);
+            }
+            return res;
+        }
 
         let mut tailit = tail.iter();
         let mut source = SearchSource::Main(row); // Where we currently get the haystack-characters from
@@ -267,15 +341,7 @@ impl SearchResult {
             }
         }
 
-        // The tail may have not been wrapped yet (when coming from lines_below),
-        // so it could be that the end extends across more characters than the row is wide.
-        // Therefore we need to reflow the end:
-        for s in res.iter_mut() {
-            while s.end.column() > row.width() {
-                s.end.column.0 -= row.width();
-                s.end.line.0 += 1;
-            }
-        }
+        reflow_selection_ends(&mut res, row);
         res
     }
 
@@ -412,6 +478,7 @@ impl Grid {
 
     pub fn set_search_string(&mut self, needle: &str) {
         self.search_results.needle = needle.to_string();
+        self.search_results.update_regex();
         self.search_viewport();
         // If the current viewport does not contain any hits,
         // we jump around until we find something. Starting
@@ -454,6 +521,7 @@ impl Grid {
 
     pub fn toggle_search_case_sensitivity(&mut self) {
         self.search_results.case_insensitive = !self.search_results.case_insensitive;
+        self.search_results.update_regex();
         for line in self.search_results.selections.drain(..) {
             self.output_buffer
                 .update_lines(line.start.line() as usize, line.end.line() as usize);
@@ -469,6 +537,20 @@ impl Grid {
 
     pub fn toggle_search_whole_words(&mut self) {
         self.search_results.whole_word_only = !self.search_results.whole_word_only;
+        self.search_results.update_regex();
+        for line in self.search_results.selections.drain(..) {
+            self.output_buffer
+                .update_lines(line.start.line() as usize, line.end.line() as usize);
+        }
+        self.search_results.active = None;
+        self.search_viewport();
+        // Maybe the selection we had is now gone
+        self.search_results.unset_active_selection_if_nonexistent();
+    }
+
+    pub fn toggle_search_regex(&mut self) {
+        self.search_results.regex_search = !self.search_results.regex_search;
+        self.search_results.update_regex();
         for line in self.search_results.selections.drain(..) {
             self.output_buffer
                 .update_lines(line.start.line() as usize, line.end.line() as usize);
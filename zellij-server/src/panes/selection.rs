@@ -9,6 +9,7 @@ pub struct Selection {
     pub start: Position,
     pub end: Position,
     active: bool, // used to handle moving the selection up and down
+    block: bool,  // a rectangle from start to end, rather than a range of text
 }
 
 impl Default for Selection {
@@ -17,6 +18,7 @@ impl Default for Selection {
             start: Position::new(0, 0),
             end: Position::new(0, 0),
             active: false,
+            block: false,
         }
     }
 }
@@ -24,6 +26,7 @@ impl Default for Selection {
 impl Selection {
     pub fn start(&mut self, start: Position) {
         self.active = true;
+        self.block = false;
         self.start = start;
         self.end = start;
     }
@@ -38,10 +41,22 @@ impl Selection {
     }
 
     pub fn set_start_and_end_positions(&mut self, start: Position, end: Position) {
+        self.block = false;
         self.start = start;
         self.end = end;
     }
 
+    /// Selects the columns from start to end (exclusive) on every line between them
+    pub fn set_block_start_and_end_positions(&mut self, start: Position, end: Position) {
+        self.block = true;
+        self.start = start;
+        self.end = end;
+    }
+
+    pub fn is_block(&self) -> bool {
+        self.block
+    }
+
     pub fn contains(&self, row: usize, col: usize) -> bool {
         let row = row as isize;
         let (start, end) = if self.start <= self.end {
@@ -50,6 +65,13 @@ impl Selection {
             (self.end, self.start)
         };
 
+        if self.block {
+            return start.line.0 <= row
+                && row <= end.line.0
+                && start.column.0 <= col
+                && col < end.column.0;
+        }
+
         if (start.line.0) < row && row < end.line.0 {
             return true;
         }
@@ -77,6 +99,7 @@ impl Selection {
     }
 
     pub fn reset(&mut self) {
+        self.block = false;
         self.start = Position::new(0, 0);
         self.end = self.start;
     }
@@ -91,6 +114,7 @@ impl Selection {
             start,
             end,
             active: self.active,
+            block: self.block,
         }
     }
 
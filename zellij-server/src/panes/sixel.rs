@@ -67,6 +67,7 @@ pub struct SixelGrid {
     currently_parsing: Option<SixelDeserializer>,
     image_ids_to_reap: Vec<usize>,
     sixel_parser: Option<sixel_tokenizer::Parser>,
+    lines_scrolled_off_top: usize,
     pub sixel_image_store: Rc<RefCell<SixelImageStore>>,
 }
 
@@ -211,6 +212,7 @@ impl SixelGrid {
         ret
     }
     pub fn offset_grid_top(&mut self) {
+        self.lines_scrolled_off_top += 1;
         if let Some(character_cell_size) = *self.character_cell_size.borrow() {
             let height_to_reduce = character_cell_size.height as isize;
             for (sixel_image_id, pixel_rect) in self.sixel_image_locations.iter_mut() {
@@ -224,6 +226,21 @@ impl SixelGrid {
             }
         }
     }
+    /// Undoes offset_grid_top for lines that came back to the top of the grid (eg. scrollback that
+    /// was paged back in from the disk), images that were scrolled off are not brought back
+    pub fn restore_grid_top(&mut self, line_count: usize) {
+        self.lines_scrolled_off_top = self.lines_scrolled_off_top.saturating_sub(line_count);
+        if let Some(character_cell_size) = *self.character_cell_size.borrow() {
+            let height_to_add = (character_cell_size.height * line_count) as isize;
+            for pixel_rect in self.sixel_image_locations.values_mut() {
+                pixel_rect.y += height_to_add;
+            }
+        }
+    }
+    /// The lines that were dropped from the top of the grid so far
+    pub fn lines_scrolled_off_top(&self) -> usize {
+        self.lines_scrolled_off_top
+    }
     pub fn drain_image_ids_to_reap(&mut self) -> Option<Vec<usize>> {
         let images_to_reap = self.image_ids_to_reap.drain(..);
         if images_to_reap.len() > 0 {
@@ -294,6 +311,7 @@ impl SixelGrid {
     pub fn image_cell_coordinates_in_viewport(
         &self,
         viewport_height: usize,
+        viewport_width: usize,
         scrollback_height: usize,
     ) -> Vec<(usize, usize, usize, usize)> {
         match *self.character_cell_size.borrow() {
@@ -329,7 +347,8 @@ impl SixelGrid {
                     let image_bottom_edge =
                         std::cmp::min(image_y + image_height + height_remainder, viewport_height);
                     let image_left_edge = image_x;
-                    let image_right_edge = image_x + image_width + width_remainder;
+                    let image_right_edge =
+                        std::cmp::min(image_x + image_width + width_remainder, viewport_width);
                     (
                         image_top_edge,
                         image_bottom_edge,
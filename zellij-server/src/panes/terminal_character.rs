@@ -1,7 +1,9 @@
+use std::collections::HashMap;
 use std::convert::From;
 use std::fmt::{self, Debug, Display, Formatter};
 use std::ops::{Index, IndexMut};
 use std::rc::Rc;
+use std::sync::Mutex;
 use unicode_width::UnicodeWidthChar;
 
 use unicode_width::UnicodeWidthStr;
@@ -9,6 +11,8 @@ use zellij_utils::data::StyleDeclaration;
 use zellij_utils::input::command::RunCommand;
 use zellij_utils::{
     data::{PaletteColor, Style},
+    ipc::ColorSupport,
+    lazy_static::lazy_static,
     vte::ParamsIter,
 };
 
@@ -17,9 +21,44 @@ use crate::panes::alacritty_functions::parse_sgr_color;
 pub const EMPTY_TERMINAL_CHARACTER: TerminalCharacter = TerminalCharacter {
     character: ' ',
     width: 1,
+    grapheme_cluster_tail: 0,
     styles: RcCharacterStyles::Reset,
 };
 
+const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+// longer emoji sequences than this are cut, the longest ones in use are around 30 bytes
+const MAX_GRAPHEME_CLUSTER_TAIL_BYTES: usize = 64;
+
+lazy_static! {
+    static ref GRAPHEME_CLUSTER_TAILS: Mutex<GraphemeClusterTails> = Default::default();
+}
+
+/// The codepoints following the first character of the grapheme clusters printed to panes (see
+/// the grapheme_clusters option), interned so that they fit in a `TerminalCharacter`: there are
+/// few distinct emoji sequences, and once 65535 of them have been printed the following ones are
+/// cut to their first character
+#[derive(Default)]
+struct GraphemeClusterTails {
+    ids: HashMap<String, u16>,
+    tails: Vec<String>, // the tail with id n is at n - 1, 0 is no tail
+}
+
+impl GraphemeClusterTails {
+    fn intern(&mut self, tail: String) -> Option<u16> {
+        if let Some(id) = self.ids.get(&tail) {
+            return Some(*id);
+        }
+        let id = u16::try_from(self.tails.len() + 1).ok()?;
+        self.tails.push(tail.clone());
+        self.ids.insert(tail, id);
+        Some(id)
+    }
+    fn get(&self, id: u16) -> Option<&str> {
+        let index = usize::from(id).checked_sub(1)?;
+        self.tails.get(index).map(|tail| tail.as_str())
+    }
+}
+
 pub const RESET_STYLES: CharacterStyles = CharacterStyles {
     foreground: Some(AnsiCode::Reset),
     background: Some(AnsiCode::Reset),
@@ -153,6 +192,120 @@ impl NamedColor {
 
         v.to_string()
     }
+    fn from_index(index: u8) -> Self {
+        match index {
+            0 => NamedColor::Black,
+            1 => NamedColor::Red,
+            2 => NamedColor::Green,
+            3 => NamedColor::Yellow,
+            4 => NamedColor::Blue,
+            5 => NamedColor::Magenta,
+            6 => NamedColor::Cyan,
+            7 => NamedColor::White,
+            8 => NamedColor::BrightBlack,
+            9 => NamedColor::BrightRed,
+            10 => NamedColor::BrightGreen,
+            11 => NamedColor::BrightYellow,
+            12 => NamedColor::BrightBlue,
+            13 => NamedColor::BrightMagenta,
+            14 => NamedColor::BrightCyan,
+            _ => NamedColor::BrightWhite,
+        }
+    }
+    fn closest_to_rgb(rgb: (u8, u8, u8)) -> Self {
+        let closest_index = (0..16)
+            .min_by_key(|index| rgb_distance(rgb, color_index_to_rgb(*index)))
+            .unwrap_or(0);
+        NamedColor::from_index(closest_index)
+    }
+}
+
+// the default xterm colors, which most terminals use for the first 16 color indices
+const NAMED_COLORS_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+const COLOR_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn color_index_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => NAMED_COLORS_RGB[index as usize],
+        16..=231 => {
+            let cube_index = index - 16;
+            (
+                COLOR_CUBE_LEVELS[(cube_index / 36) as usize],
+                COLOR_CUBE_LEVELS[(cube_index / 6 % 6) as usize],
+                COLOR_CUBE_LEVELS[(cube_index % 6) as usize],
+            )
+        },
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        },
+    }
+}
+
+fn rgb_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel_distance = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel_distance(a.0, b.0) + channel_distance(a.1, b.1) + channel_distance(a.2, b.2)
+}
+
+/// The closest color of the 6x6x6 color cube or the grayscale ramp of the 256 color palette
+fn rgb_to_color_index(rgb: (u8, u8, u8)) -> u8 {
+    let cube_level = |channel: u8| -> u8 {
+        COLOR_CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (channel as i32 - **level as i32).abs())
+            .map(|(level_index, _)| level_index as u8)
+            .unwrap_or(0)
+    };
+    let cube_index = 16 + 36 * cube_level(rgb.0) + 6 * cube_level(rgb.1) + cube_level(rgb.2);
+    let average = ((rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3) as u8;
+    let gray_index = 232 + (average.saturating_sub(3) / 10).min(23);
+    if rgb_distance(rgb, color_index_to_rgb(gray_index))
+        < rgb_distance(rgb, color_index_to_rgb(cube_index))
+    {
+        gray_index
+    } else {
+        cube_index
+    }
+}
+
+impl AnsiCode {
+    /// The closest color the terminal of a client can display, codes that are not colors (or
+    /// that it can display) are left as they are
+    pub fn downgrade_color(self, color_support: ColorSupport) -> Self {
+        match (color_support, self) {
+            (ColorSupport::Colors256, AnsiCode::RgbCode(rgb)) => {
+                AnsiCode::ColorIndex(rgb_to_color_index(rgb))
+            },
+            (ColorSupport::Colors16, AnsiCode::RgbCode(rgb)) => {
+                AnsiCode::NamedColor(NamedColor::closest_to_rgb(rgb))
+            },
+            (ColorSupport::Colors16, AnsiCode::ColorIndex(index)) if index < 16 => {
+                AnsiCode::NamedColor(NamedColor::from_index(index))
+            },
+            (ColorSupport::Colors16, AnsiCode::ColorIndex(index)) => {
+                AnsiCode::NamedColor(NamedColor::closest_to_rgb(color_index_to_rgb(index)))
+            },
+            (_, ansi_code) => ansi_code,
+        }
+    }
 }
 
 // This enum carefully only has two variants so
@@ -392,6 +545,17 @@ impl CharacterStyles {
         }
         Some(diff)
     }
+    /// These styles with their colors downgraded to those the terminal of a client can display
+    pub fn downgrade_colors(mut self, color_support: ColorSupport) -> Self {
+        if color_support != ColorSupport::TrueColor {
+            self.foreground = self.foreground.map(|c| c.downgrade_color(color_support));
+            self.background = self.background.map(|c| c.downgrade_color(color_support));
+            self.underline_color = self
+                .underline_color
+                .map(|c| c.downgrade_color(color_support));
+        }
+        self
+    }
     fn reset_ansi(&mut self) {
         self.foreground = Some(AnsiCode::Reset);
         self.background = Some(AnsiCode::Reset);
@@ -927,6 +1091,9 @@ pub struct TerminalCharacter {
     pub character: char,
     pub styles: RcCharacterStyles,
     width: u8,
+    // the codepoints joined to this character in its grapheme cluster (eg. the emoji
+    // presentation selector or the rest of an emoji sequence), see GraphemeClusterTails
+    grapheme_cluster_tail: u16,
 }
 // This size has significant memory and CPU implications for long lines,
 // be careful about allowing it to grow
@@ -944,6 +1111,18 @@ impl TerminalCharacter {
             character,
             styles,
             width: character.width().unwrap_or(0) as u8,
+            grapheme_cluster_tail: 0,
+        }
+    }
+
+    /// Like `new_styled`, but East Asian ambiguous width characters are wide (as in CJK contexts)
+    #[inline]
+    pub fn new_styled_ambiguous_wide(character: char, styles: RcCharacterStyles) -> Self {
+        TerminalCharacter {
+            character,
+            styles,
+            width: character.width_cjk().unwrap_or(0) as u8,
+            grapheme_cluster_tail: 0,
         }
     }
 
@@ -958,12 +1137,54 @@ impl TerminalCharacter {
             character,
             styles,
             width: 1,
+            grapheme_cluster_tail: 0,
         }
     }
 
     pub fn width(&self) -> usize {
         self.width as usize
     }
+    /// A narrow character followed by the emoji presentation selector is displayed as a (wide)
+    /// emoji by the terminal, the selector has to be rendered after it
+    pub fn set_emoji_presentation(&mut self) {
+        self.width = 2;
+        self.join_to_grapheme_cluster(EMOJI_PRESENTATION_SELECTOR);
+    }
+    /// Adds a codepoint to the grapheme cluster this character starts, it is rendered after it
+    /// without taking any columns
+    pub fn join_to_grapheme_cluster(&mut self, c: char) {
+        let mut grapheme_cluster_tails = GRAPHEME_CLUSTER_TAILS.lock().unwrap();
+        let mut tail = grapheme_cluster_tails
+            .get(self.grapheme_cluster_tail)
+            .unwrap_or_default()
+            .to_owned();
+        if tail.len() + c.len_utf8() > MAX_GRAPHEME_CLUSTER_TAIL_BYTES {
+            return;
+        }
+        tail.push(c);
+        if let Some(id) = grapheme_cluster_tails.intern(tail) {
+            self.grapheme_cluster_tail = id;
+        }
+    }
+    /// Writes the codepoints joined to this character, if any, after it
+    pub fn push_grapheme_cluster_tail(&self, string: &mut String) {
+        if self.grapheme_cluster_tail == 0 {
+            return;
+        }
+        if let Some(tail) = GRAPHEME_CLUSTER_TAILS
+            .lock()
+            .unwrap()
+            .get(self.grapheme_cluster_tail)
+        {
+            string.push_str(tail);
+        }
+    }
+    /// The character along with the codepoints joined to it
+    pub fn grapheme_cluster(&self) -> String {
+        let mut grapheme_cluster = self.character.to_string();
+        self.push_grapheme_cluster_tail(&mut grapheme_cluster);
+        grapheme_cluster
+    }
 }
 
 impl ::std::fmt::Debug for TerminalCharacter {
@@ -1103,3 +1324,50 @@ pub fn render_first_run_banner(
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_downgraded_to_those_the_client_can_display() {
+        let red = AnsiCode::RgbCode((255, 0, 0));
+        let gray = AnsiCode::RgbCode((128, 128, 128));
+        assert_eq!(red.downgrade_color(ColorSupport::TrueColor), red);
+        assert_eq!(
+            red.downgrade_color(ColorSupport::Colors256),
+            AnsiCode::ColorIndex(196)
+        );
+        assert_eq!(
+            gray.downgrade_color(ColorSupport::Colors256),
+            AnsiCode::ColorIndex(244),
+            "grays go to the grayscale ramp rather than to the color cube"
+        );
+        assert_eq!(
+            AnsiCode::RgbCode((250, 10, 10)).downgrade_color(ColorSupport::Colors16),
+            AnsiCode::NamedColor(NamedColor::BrightRed)
+        );
+        assert_eq!(
+            AnsiCode::ColorIndex(4).downgrade_color(ColorSupport::Colors16),
+            AnsiCode::NamedColor(NamedColor::Blue)
+        );
+        assert_eq!(
+            AnsiCode::ColorIndex(196).downgrade_color(ColorSupport::Colors16),
+            AnsiCode::NamedColor(NamedColor::BrightRed)
+        );
+        assert_eq!(
+            AnsiCode::On.downgrade_color(ColorSupport::Colors16),
+            AnsiCode::On
+        );
+
+        let styles = DEFAULT_STYLES
+            .foreground(Some(red))
+            .background(Some(AnsiCode::ColorIndex(2)))
+            .downgrade_colors(ColorSupport::Colors16);
+        assert_eq!(
+            styles.to_string(),
+            "\u{1b}[91m\u{1b}[42m",
+            "no 24 bit or 256 color sequences are rendered"
+        );
+    }
+}
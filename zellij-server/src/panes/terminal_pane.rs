@@ -1,20 +1,28 @@
+use crate::os_input_output::ProcessStats;
 use crate::output::{CharacterChunk, SixelImageChunk};
+use crate::output_log::OutputLogger;
+use crate::panes::command_history::{CommandHistory, CommandHistoryOutcome, CommandHistoryPicker};
+use crate::panes::copy_mode::CopyModeOutcome;
+use crate::panes::output_monitor::{MonitorAlert, OutputMonitors};
 use crate::panes::sixel::SixelImageStore;
 use crate::panes::LinkHandler;
 use crate::panes::{
-    grid::Grid,
+    grid::{AlternateScreenBehavior, Grid, MouseTracking, PortDeclaration, UnicodeWidth},
     terminal_character::{render_first_run_banner, TerminalCharacter, EMPTY_TERMINAL_CHARACTER},
 };
 use crate::pty::VteBytes;
-use crate::tab::{AdjustedInput, Pane};
+use crate::tab::{AdjustedInput, CloseTarget, Pane, PaneGroup};
 use crate::ClientId;
+use chrono::Local;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time::{self, Instant};
-use zellij_utils::input::command::RunCommand;
+use zellij_utils::input::command::{OutputLog, RunCommand};
 use zellij_utils::input::mouse::{MouseEvent, MouseEventType};
+use zellij_utils::input::options::{Clipboard, CommandExitBanner};
 use zellij_utils::pane_size::Offset;
 use zellij_utils::{
     data::{
@@ -22,10 +30,11 @@ use zellij_utils::{
         Style, Styling,
     },
     errors::prelude::*,
-    input::layout::Run,
+    input::layout::{Run, ScrollbackSize, SizeLimits},
     pane_size::PaneGeom,
     pane_size::SizeInPixels,
     position::Position,
+    regex::Regex,
     shared::make_terminal_title,
     vte,
 };
@@ -130,6 +139,7 @@ pub struct TerminalPane {
     frame: HashMap<ClientId, PaneFrame>,
     borderless: bool,
     exclude_from_sync: bool,
+    size_limits: Option<SizeLimits>,
     fake_cursor_locations: HashSet<(usize, usize)>, // (x, y) - these hold a record of previous fake cursors which we need to clear on render
     search_term: String,
     is_held: Option<(Option<i32>, IsFirstRun, RunCommand)>, // a "held" pane means that its command has either exited and the pane is waiting for a
@@ -137,9 +147,28 @@ pub struct TerminalPane {
     banner: Option<String>, // a banner to be rendered inside this TerminalPane, used for panes
     // held on startup and can possibly be used to display some errors
     pane_frame_color_override: Option<(PaletteColor, Option<String>)>,
+    group: Option<PaneGroup>,
     invoked_with: Option<Run>,
     #[allow(dead_code)]
     arrow_fonts: bool,
+    // when true, mouse events are handled by zellij (eg. for selection) even if the application
+    // running in the pane requested to receive them
+    override_mouse_reporting: bool,
+    // when true, keys typed into the pane are dropped rather than sent to its process
+    input_is_locked: bool,
+    output_logger: Option<OutputLogger>,
+    output_monitors: OutputMonitors,
+    command_started_at: Instant,
+    command_runtime: Option<time::Duration>, // how long the command ran, once it exited
+    command_exit_banner: CommandExitBanner,
+    reported_cwd: Option<PathBuf>, // the last cwd reported by the shell (OSC 7)
+    command_history: CommandHistory,
+    command_history_picker: Option<CommandHistoryPicker>, // shown in the alternate screen
+    process_stats: Option<ProcessStats>, // shown in the frame, with the pane_frame_process_stats option
+    linked_view: bool, // shows the pty of a pane in another tab, rather than owning one
+    close_confirmation: Option<(CloseTarget, Vec<String>)>, // the programs still running in it
+    // the keys pressed while asking (with their raw bytes and whether those are kitty)
+    input_held_for_close_confirmation: Vec<(Option<KeyWithModifier>, Vec<u8>, bool)>,
 }
 
 impl Pane for TerminalPane {
@@ -190,9 +219,17 @@ impl Pane for TerminalPane {
     }
     fn handle_pty_bytes(&mut self, bytes: VteBytes) {
         self.set_should_render(true);
+        if let Some(output_logger) = self.output_logger.as_mut() {
+            if let Err(e) = output_logger.write(&bytes) {
+                log::error!("Stopped logging the output of the pane: {:?}", e);
+                self.output_logger = None;
+            }
+        }
         for &byte in &bytes {
             self.vte_parser.advance(&mut self.grid, byte);
+            self.grid.handle_kitty_graphics_byte(byte);
         }
+        self.output_monitors.output_received(Instant::now());
     }
     fn cursor_coordinates(&self) -> Option<(usize, usize)> {
         // (x, y)
@@ -213,7 +250,7 @@ impl Pane for TerminalPane {
         key_with_modifier: &Option<KeyWithModifier>,
         raw_input_bytes: Vec<u8>,
         raw_input_bytes_are_kitty: bool,
-        _client_id: Option<ClientId>,
+        client_id: Option<ClientId>,
     ) -> Option<AdjustedInput> {
         // there are some cases in which the terminal state means that input sent to it
         // needs to be adjusted.
@@ -221,6 +258,14 @@ impl Pane for TerminalPane {
         // we send back the original input
 
         self.reset_selection();
+        if self.close_confirmation.is_some() {
+            return self.handle_close_confirmation_key(
+                key_with_modifier,
+                raw_input_bytes,
+                raw_input_bytes_are_kitty,
+                client_id,
+            );
+        }
         if !self.grid.bracketed_paste_mode {
             // Zellij itself operates in bracketed paste mode, so the terminal sends these
             // instructions (bracketed paste start and bracketed paste end respectively)
@@ -234,7 +279,12 @@ impl Pane for TerminalPane {
             }
         }
 
-        if self.is_held.is_some() {
+        if self.command_history_picker.is_some() {
+            match key_with_modifier {
+                Some(key_with_modifier) => self.handle_command_history_key(key_with_modifier),
+                None => None,
+            }
+        } else if self.is_held.is_some() {
             if key_with_modifier
                 .as_ref()
                 .map(|k| k.is_key_without_modifier(BareKey::Enter))
@@ -298,6 +348,9 @@ impl Pane for TerminalPane {
         self.frame.clear();
         self.grid.render_full_viewport();
     }
+    fn set_covered_areas(&mut self, covered_areas: Vec<PaneGeom>) {
+        self.grid.set_covered_areas(covered_areas);
+    }
     fn selectable(&self) -> bool {
         self.selectable
     }
@@ -367,6 +420,9 @@ impl Pane for TerminalPane {
                 if self.grid.search_results.wrap_search {
                     modifiers.push("w")
                 }
+                if self.grid.search_results.regex_search {
+                    modifiers.push("r")
+                }
                 modifier_text.push_str(&modifiers.join(", "));
                 modifier_text.push(']');
             }
@@ -379,6 +435,11 @@ impl Pane for TerminalPane {
         } else {
             self.pane_name.clone()
         };
+        let pane_title = if self.linked_view {
+            format!("{} (linked)", pane_title)
+        } else {
+            pane_title
+        };
 
         let frame_geom = self.current_geom();
         let is_pinned = frame_geom.is_pinned;
@@ -388,12 +449,29 @@ impl Pane for TerminalPane {
             pane_title,
             frame_params,
         )
-        .is_pinned(is_pinned);
+        .is_pinned(is_pinned)
+        .input_is_locked(self.input_is_locked)
+        .mouse_reporting(
+            !matches!(self.grid.mouse_tracking, MouseTracking::Off),
+            self.override_mouse_reporting,
+        )
+        .group(self.group.as_ref().map(|group| group.name.clone()))
+        .command_exit_banner(self.command_exit_banner)
+        .process_stats(self.process_stats.as_ref().map(|stats| stats.to_string()))
+        .close_confirmation(self.close_confirmation.as_ref().map(
+            |(close_target, running_processes)| {
+                let closing = match close_target {
+                    CloseTarget::Pane(_) => "pane",
+                    CloseTarget::Tab(_) => "tab",
+                };
+                (closing.to_owned(), running_processes.join(", "))
+            },
+        ));
         if let Some((exit_status, is_first_run, _run_command)) = &self.is_held {
             if *is_first_run {
                 frame.indicate_first_run();
             } else {
-                frame.add_exit_status(exit_status.as_ref().copied());
+                frame.add_exit_status(exit_status.as_ref().copied(), self.command_runtime);
             }
         }
         if let Some((frame_color_override, _text)) = self.pane_frame_color_override.as_ref() {
@@ -525,6 +603,26 @@ impl Pane for TerminalPane {
     fn dump_screen(&self, full: bool) -> String {
         self.grid.dump_screen(full)
     }
+    fn dump_screen_with_styles(&self, full: bool) -> String {
+        self.grid.dump_screen_with_styles(full)
+    }
+    fn start_output_log(&mut self, log_folder: &Path) -> Result<Option<PathBuf>> {
+        let path = log_folder.join(format!(
+            "terminal_{}_{}.log",
+            self.pid,
+            Local::now().format("%Y-%m-%d_%H-%M-%S")
+        ));
+        self.output_logger = Some(OutputLogger::new(OutputLog {
+            path: path.clone(),
+            ..Default::default()
+        })?);
+        Ok(Some(path))
+    }
+    fn stop_output_log(&mut self) -> Option<PathBuf> {
+        self.output_logger
+            .take()
+            .map(|output_logger| output_logger.path().to_path_buf())
+    }
     fn clear_screen(&mut self) {
         self.grid.clear_screen()
     }
@@ -532,6 +630,28 @@ impl Pane for TerminalPane {
         self.grid.move_viewport_up(count);
         self.set_should_render(true);
     }
+    fn scroll_to_pattern(&mut self, pattern: &Regex, backwards: bool) -> Option<usize> {
+        let match_count = self.grid.scroll_to_pattern(pattern, backwards);
+        self.set_should_render(true);
+        Some(match_count)
+    }
+    fn find_pattern(&self, pattern: &Regex) -> Vec<(usize, String)> {
+        self.grid.find_pattern(pattern)
+    }
+    fn scroll_to_line(&mut self, line_number: usize) {
+        self.grid.scroll_to_line(line_number);
+        self.set_should_render(true);
+    }
+    fn scroll_to_prompt(&mut self, backwards: bool) {
+        if self.grid.scroll_to_prompt(backwards) {
+            self.set_should_render(true);
+        }
+    }
+    fn select_last_command_output(&mut self) {
+        if self.grid.select_last_command_output() {
+            self.set_should_render(true);
+        }
+    }
     fn scroll_down(&mut self, count: usize, _client_id: ClientId) {
         self.grid.move_viewport_down(count);
         self.set_should_render(true);
@@ -558,9 +678,53 @@ impl Pane for TerminalPane {
         self.grid.pending_messages_to_pty.drain(..).collect()
     }
 
-    fn drain_clipboard_update(&mut self) -> Option<String> {
+    fn drain_clipboard_update(&mut self) -> Option<(Clipboard, String)> {
         self.grid.pending_clipboard_update.take()
     }
+    fn drain_reported_cwd(&mut self) -> Option<PathBuf> {
+        let cwd = self.grid.pending_reported_cwd.take();
+        if cwd.is_some() {
+            self.reported_cwd = cwd.clone();
+        }
+        cwd
+    }
+    fn reported_cwd(&self) -> Option<PathBuf> {
+        self.reported_cwd.clone()
+    }
+    fn application_title(&self) -> Option<String> {
+        self.grid.title.clone()
+    }
+    fn drain_port_declarations(&mut self) -> Vec<PortDeclaration> {
+        self.grid.pending_port_declarations.drain(..).collect()
+    }
+    fn drain_title_update(&mut self) -> Option<String> {
+        self.grid.pending_title_update.take()
+    }
+    fn drain_bell(&mut self) -> bool {
+        std::mem::take(&mut self.grid.pending_bell)
+    }
+    fn toggle_activity_monitor(&mut self, should_notify: bool) -> Option<bool> {
+        Some(self.output_monitors.toggle_activity(should_notify))
+    }
+    fn toggle_silence_monitor(
+        &mut self,
+        interval: time::Duration,
+        should_notify: bool,
+    ) -> Option<bool> {
+        Some(self.output_monitors.toggle_silence(interval, should_notify))
+    }
+    fn check_silence(&mut self, now: Instant) {
+        self.output_monitors.check_silence(now);
+    }
+    fn drain_monitor_alert(&mut self) -> Option<(MonitorAlert, bool)> {
+        self.output_monitors.drain_new_alert()
+    }
+    fn monitor_alert(&self) -> Option<MonitorAlert> {
+        self.output_monitors.alert()
+    }
+    fn clear_monitor_alert(&mut self) -> bool {
+        self.output_monitors.clear_alert()
+    }
 
     fn start_selection(&mut self, start: &Position, _client_id: ClientId) {
         self.grid.start_selection(start);
@@ -603,6 +767,18 @@ impl Pane for TerminalPane {
         self.grid.get_selected_text()
     }
 
+    fn copy_mode_input(&mut self, key: char) -> CopyModeOutcome {
+        self.grid.copy_mode_input(key)
+    }
+
+    fn copy_mode_is_active(&self) -> bool {
+        self.grid.copy_mode_is_active()
+    }
+
+    fn exit_copy_mode(&mut self) {
+        self.grid.exit_copy_mode();
+    }
+
     fn set_frame(&mut self, _frame: bool) {
         self.frame.clear();
     }
@@ -638,32 +814,67 @@ impl Pane for TerminalPane {
         self.exclude_from_sync
     }
 
+    fn set_size_limits(&mut self, size_limits: Option<SizeLimits>) {
+        self.size_limits = size_limits;
+    }
+
+    fn size_limits(&self) -> Option<SizeLimits> {
+        self.size_limits
+    }
+
     fn mouse_event(&self, event: &MouseEvent, _client_id: ClientId) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_event_signal(event)
     }
 
     fn mouse_left_click(&self, position: &Position, is_held: bool) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_left_click_signal(position, is_held)
     }
     fn mouse_left_click_release(&self, position: &Position) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_left_click_release_signal(position)
     }
     fn mouse_right_click(&self, position: &Position, is_held: bool) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_right_click_signal(position, is_held)
     }
     fn mouse_right_click_release(&self, position: &Position) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_right_click_release_signal(position)
     }
     fn mouse_middle_click(&self, position: &Position, is_held: bool) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_middle_click_signal(position, is_held)
     }
     fn mouse_middle_click_release(&self, position: &Position) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_middle_click_release_signal(position)
     }
     fn mouse_scroll_up(&self, position: &Position) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_scroll_up_signal(position)
     }
     fn mouse_scroll_down(&self, position: &Position) -> Option<String> {
+        if self.override_mouse_reporting {
+            return None;
+        }
         self.grid.mouse_scroll_down_signal(position)
     }
     fn focus_event(&self) -> Option<String> {
@@ -720,6 +931,10 @@ impl Pane for TerminalPane {
     fn toggle_search_wrap(&mut self) {
         self.grid.toggle_search_wrap();
     }
+    fn toggle_search_regex(&mut self) {
+        self.grid.toggle_search_regex();
+        self.set_should_render(true);
+    }
     fn clear_search(&mut self) {
         self.grid.clear_search();
         self.search_term.clear();
@@ -728,8 +943,16 @@ impl Pane for TerminalPane {
         self.grid.is_alternate_mode_active()
     }
     fn hold(&mut self, exit_status: Option<i32>, is_first_run: bool, run_command: RunCommand) {
+        if !is_first_run {
+            self.command_history.record(&run_command);
+        }
         self.invoked_with = Some(Run::Command(run_command.clone()));
         self.is_held = Some((exit_status, is_first_run, run_command));
+        self.command_runtime = if is_first_run {
+            None
+        } else {
+            Some(self.command_started_at.elapsed())
+        };
         if is_first_run {
             self.render_first_run_banner();
         }
@@ -745,6 +968,15 @@ impl Pane for TerminalPane {
         self.pane_frame_color_override
             .as_ref()
             .map(|(color, _text)| *color)
+            .or_else(|| self.group.as_ref().and_then(|group| group.color))
+    }
+    fn set_group(&mut self, group: Option<PaneGroup>) {
+        self.group = group;
+        self.set_should_render(true);
+        self.set_should_render_boundaries(true);
+    }
+    fn group(&self) -> Option<&PaneGroup> {
+        self.group.as_ref()
     }
     fn invoked_with(&self) -> &Option<Run> {
         &self.invoked_with
@@ -770,6 +1002,13 @@ impl Pane for TerminalPane {
             Some(self.pane_name.clone())
         }
     }
+    fn scrollback_length(&self) -> usize {
+        let (_position, length) = self.grid.scrollback_position_and_length();
+        length
+    }
+    fn scrollback_memory_usage(&self) -> usize {
+        self.grid.scrollback_memory_usage()
+    }
     fn exit_status(&self) -> Option<i32> {
         self.is_held
             .as_ref()
@@ -778,6 +1017,16 @@ impl Pane for TerminalPane {
     fn is_held(&self) -> bool {
         self.is_held.is_some()
     }
+    fn is_linked_view(&self) -> bool {
+        self.linked_view
+    }
+    fn command_runtime(&self) -> Option<time::Duration> {
+        self.command_runtime
+    }
+    fn set_command_exit_banner(&mut self, command_exit_banner: CommandExitBanner) {
+        self.command_exit_banner = command_exit_banner;
+        self.set_should_render(true);
+    }
     fn exited(&self) -> bool {
         match self.is_held {
             Some((_, is_first_run, _)) => !is_first_run,
@@ -795,14 +1044,46 @@ impl Pane for TerminalPane {
         // if this is a command pane that has exited or is waiting to be rerun, will return its
         // RunCommand, otherwise it is safe to assume this is not the right sort of pane or that it
         // is not in the right sort of state
+        self.close_command_history();
         self.is_held.take().map(|(_, _, run_command)| {
             self.is_held = None;
+            self.command_started_at = Instant::now();
             self.grid.reset_terminal_state();
             self.set_should_render(true);
             self.remove_banner();
             run_command.clone()
         })
     }
+    fn restore_scrollback(&mut self, scrollback: &str) {
+        // every serialized line starts with a newline, we leave the cursor at the start of the
+        // line after the last one for the output of the pane
+        let scrollback = scrollback.strip_prefix("\n\r").unwrap_or(scrollback);
+        self.write_to_grid(scrollback.as_bytes());
+        self.write_to_grid("\u{1b}[m\n\r".as_bytes());
+    }
+    fn set_process_stats(&mut self, process_stats: Option<ProcessStats>) {
+        self.process_stats = process_stats;
+    }
+    fn open_command_history(&mut self) -> bool {
+        if self.command_history.is_empty() {
+            return false;
+        }
+        if self.command_history_picker.is_none() {
+            self.command_history_picker = Some(CommandHistoryPicker::default());
+            self.write_to_grid("\u{1b}[?1049h".as_bytes());
+            self.render_command_history_picker();
+        }
+        true
+    }
+    fn ask_to_confirm_close(
+        &mut self,
+        close_target: CloseTarget,
+        running_processes: Vec<String>,
+    ) -> bool {
+        self.close_confirmation = Some((close_target, running_processes));
+        self.set_should_render(true);
+        true
+    }
     fn update_theme(&mut self, theme: Styling) {
         self.style.colors = theme.clone();
         self.grid.update_theme(theme);
@@ -815,6 +1096,12 @@ impl Pane for TerminalPane {
         self.arrow_fonts = should_support_arrow_fonts;
         self.grid.update_arrow_fonts(should_support_arrow_fonts);
     }
+    fn update_word_separators(&mut self, word_separators: &str) {
+        self.grid.update_word_separators(word_separators);
+    }
+    fn update_unicode_width(&mut self, unicode_width: UnicodeWidth) {
+        self.grid.set_unicode_width(unicode_width);
+    }
     fn update_rounded_corners(&mut self, rounded_corners: bool) {
         self.style.rounded_corners = rounded_corners;
         self.frame.clear();
@@ -839,6 +1126,17 @@ impl Pane for TerminalPane {
     fn set_pinned(&mut self, should_be_pinned: bool) {
         self.geom.is_pinned = should_be_pinned;
     }
+    fn toggle_mouse_reporting_override(&mut self) {
+        self.override_mouse_reporting = !self.override_mouse_reporting;
+        self.set_should_render(true);
+    }
+    fn toggle_input_lock(&mut self) {
+        self.input_is_locked = !self.input_is_locked;
+        self.set_should_render(true);
+    }
+    fn input_is_locked(&self) -> bool {
+        self.input_is_locked
+    }
     fn intercept_left_mouse_click(&mut self, position: &Position, client_id: ClientId) -> bool {
         if self.position_is_on_frame(position) {
             let relative_position = self.relative_position(position);
@@ -923,15 +1221,34 @@ impl TerminalPane {
             prev_pane_name: pane_name,
             borderless: false,
             exclude_from_sync: false,
+            size_limits: None,
             fake_cursor_locations: HashSet::new(),
             search_term: String::new(),
             is_held: None,
             banner: None,
             pane_frame_color_override: None,
+            group: None,
             invoked_with,
             arrow_fonts,
+            override_mouse_reporting: false,
+            input_is_locked: false,
+            output_logger: None,
+            output_monitors: OutputMonitors::default(),
+            command_started_at: Instant::now(),
+            command_runtime: None,
+            command_exit_banner: CommandExitBanner::default(),
+            reported_cwd: None,
+            command_history: CommandHistory::default(),
+            command_history_picker: None,
+            process_stats: None,
+            linked_view: false,
+            close_confirmation: None,
+            input_held_for_close_confirmation: vec![],
         }
     }
+    pub fn set_linked_view(&mut self, linked_view: bool) {
+        self.linked_view = linked_view;
+    }
     pub fn get_x(&self) -> usize {
         match self.geom_override {
             Some(position_and_size_override) => position_and_size_override.x,
@@ -956,6 +1273,16 @@ impl TerminalPane {
             None => self.geom.rows.as_usize(),
         }
     }
+    pub fn set_scrollback_size(&mut self, scrollback_size: ScrollbackSize) -> Result<()> {
+        self.grid.set_scrollback_size(scrollback_size)
+    }
+    pub fn set_alternate_screen_behavior(
+        &mut self,
+        alternate_screen_behavior: AlternateScreenBehavior,
+    ) {
+        self.grid
+            .set_alternate_screen_behavior(alternate_screen_behavior);
+    }
     fn reflow_lines(&mut self) {
         let rows = self.get_content_rows();
         let cols = self.get_content_columns();
@@ -964,8 +1291,119 @@ impl TerminalPane {
             self.grid.reset_terminal_state();
             self.render_first_run_banner();
         }
+        self.render_command_history_picker();
         self.set_should_render(true);
     }
+    fn write_to_grid(&mut self, bytes: &[u8]) {
+        // unlike handle_pty_bytes, this is not output of the pane (eg. for its log)
+        for &byte in bytes {
+            self.vte_parser.advance(&mut self.grid, byte);
+        }
+        self.set_should_render(true);
+    }
+    fn render_command_history_picker(&mut self) {
+        if let Some(picker) = self.command_history_picker.as_ref() {
+            let picker = picker.render(
+                &self.command_history,
+                self.get_content_columns(),
+                self.get_content_rows(),
+                &self.style,
+            );
+            self.write_to_grid(picker.as_bytes());
+        }
+    }
+    fn close_command_history(&mut self) {
+        if self.command_history_picker.take().is_some() {
+            // leaving the alternate screen brings back the output of the command
+            self.write_to_grid("\u{1b}[?1049l\u{1b}[?25h".as_bytes());
+        }
+    }
+    fn handle_close_confirmation_key(
+        &mut self,
+        key_with_modifier: &Option<KeyWithModifier>,
+        raw_input_bytes: Vec<u8>,
+        raw_input_bytes_are_kitty: bool,
+        client_id: Option<ClientId>,
+    ) -> Option<AdjustedInput> {
+        let (confirmed, cancelled) = match key_with_modifier {
+            Some(key) => (
+                key.is_key_without_modifier(BareKey::Char('y'))
+                    || key.is_key_without_modifier(BareKey::Enter),
+                key.is_key_without_modifier(BareKey::Char('n'))
+                    || key.is_key_without_modifier(BareKey::Esc)
+                    || key.is_key_with_ctrl_modifier(BareKey::Char('c')),
+            ),
+            None => (
+                // y / n
+                matches!(
+                    raw_input_bytes.as_slice(),
+                    &[121] | ENTER_CARRIAGE_RETURN | ENTER_NEWLINE
+                ),
+                matches!(raw_input_bytes.as_slice(), &[110] | ESC | CTRL_C),
+            ),
+        };
+        if confirmed {
+            // the keys held for the pane go away with it
+            self.input_held_for_close_confirmation.clear();
+            self.set_should_render(true);
+            self.close_confirmation
+                .take()
+                .map(|(close_target, _)| AdjustedInput::ConfirmClose(close_target))
+        } else if cancelled {
+            self.close_confirmation = None;
+            self.set_should_render(true);
+            // the keys pressed while asking are written to the pane once the user answers
+            let mut held_bytes = vec![];
+            for (key_with_modifier, raw_input_bytes, raw_input_bytes_are_kitty) in
+                std::mem::take(&mut self.input_held_for_close_confirmation)
+            {
+                if let Some(AdjustedInput::WriteBytesToTerminal(bytes)) = self
+                    .adjust_input_to_terminal(
+                        &key_with_modifier,
+                        raw_input_bytes,
+                        raw_input_bytes_are_kitty,
+                        client_id,
+                    )
+                {
+                    held_bytes.extend(bytes);
+                }
+            }
+            Some(AdjustedInput::WriteBytesToTerminal(held_bytes))
+        } else {
+            self.input_held_for_close_confirmation.push((
+                key_with_modifier.clone(),
+                raw_input_bytes,
+                raw_input_bytes_are_kitty,
+            ));
+            None
+        }
+    }
+    fn handle_command_history_key(
+        &mut self,
+        key_with_modifier: &KeyWithModifier,
+    ) -> Option<AdjustedInput> {
+        let outcome = self
+            .command_history_picker
+            .as_mut()
+            .map(|picker| picker.handle_key(key_with_modifier, &self.command_history))?;
+        match outcome {
+            CommandHistoryOutcome::Continue => {
+                self.render_command_history_picker();
+                None
+            },
+            CommandHistoryOutcome::Close => {
+                self.close_command_history();
+                None
+            },
+            CommandHistoryOutcome::Run(run_command) => {
+                self.close_command_history();
+                self.handle_held_run().map(|_| {
+                    self.invoked_with = Some(Run::Command(run_command.clone()));
+                    AdjustedInput::ReRunCommandInThisPane(run_command)
+                })
+            },
+        }
+    }
     pub fn read_buffer_as_lines(&self) -> Vec<Vec<TerminalCharacter>> {
         self.grid.as_character_lines()
     }
@@ -1104,6 +1542,7 @@ impl TerminalPane {
     fn handle_held_run(&mut self) -> Option<AdjustedInput> {
         self.is_held.take().map(|(_, _, run_command)| {
             self.is_held = None;
+            self.command_started_at = Instant::now();
             self.grid.reset_terminal_state();
             self.set_should_render(true);
             self.remove_banner();
@@ -22,9 +22,10 @@ use zellij_utils::{
     errors::prelude::*,
     input::{
         command::RunCommand,
-        layout::{Run, RunPluginOrAlias, SplitDirection},
+        layout::{Run, RunPluginOrAlias, SizeLimits, SplitDirection},
     },
     pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
+    position::Position,
 };
 
 use std::{
@@ -69,6 +70,7 @@ pub struct TiledPanes {
     draw_pane_frames: bool,
     panes_to_hide: HashSet<PaneId>,
     fullscreen_is_active: Option<PaneId>,
+    expanded_stacks: Vec<Vec<PaneId>>, // top to bottom, see toggle_expand_stack
     senders: ThreadSenders,
     window_title: Option<String>,
     client_id_to_boundaries: HashMap<ClientId, Boundaries>,
@@ -109,6 +111,7 @@ impl TiledPanes {
             draw_pane_frames,
             panes_to_hide: HashSet::new(),
             fullscreen_is_active: None,
+            expanded_stacks: vec![],
             senders,
             window_title: None,
             client_id_to_boundaries: HashMap::new(),
@@ -187,6 +190,75 @@ impl TiledPanes {
         let should_relayout = false;
         self.add_pane(pane_id, pane, should_relayout, client_id);
     }
+    /// Puts a pane where a pane with this geom used to be (eg. a closed pane being restored),
+    /// taking the space back from the single pane that covers it now. Gives the pane back if no
+    /// pane can make room for it this way.
+    pub fn insert_pane_at_geom(
+        &mut self,
+        pane_id: PaneId,
+        mut pane: Box<dyn Pane>,
+        geom: PaneGeom,
+    ) -> Option<Box<dyn Pane>> {
+        if geom.is_stacked() {
+            return Some(pane);
+        }
+        // the covering pane has to be split along one of its edges for the remaining space to
+        // stay a rectangle
+        let covering_pane = self.panes.iter().find_map(|(id, covering_pane)| {
+            let covering_geom = covering_pane.position_and_size();
+            if covering_geom.is_stacked() {
+                return None;
+            }
+            let rows_split = covering_geom.x == geom.x
+                && covering_geom.cols.as_usize() == geom.cols.as_usize()
+                && covering_geom.rows.as_usize() > geom.rows.as_usize()
+                && (covering_geom.y == geom.y
+                    || covering_geom.y + covering_geom.rows.as_usize()
+                        == geom.y + geom.rows.as_usize());
+            let cols_split = covering_geom.y == geom.y
+                && covering_geom.rows.as_usize() == geom.rows.as_usize()
+                && covering_geom.cols.as_usize() > geom.cols.as_usize()
+                && (covering_geom.x == geom.x
+                    || covering_geom.x + covering_geom.cols.as_usize()
+                        == geom.x + geom.cols.as_usize());
+            if rows_split {
+                Some((*id, covering_geom, SplitDirection::Horizontal))
+            } else if cols_split {
+                Some((*id, covering_geom, SplitDirection::Vertical))
+            } else {
+                None
+            }
+        });
+        let Some((covering_pane_id, mut covering_geom, split_direction)) = covering_pane else {
+            return Some(pane);
+        };
+        let (covering_dimension, dimension) = match split_direction {
+            SplitDirection::Horizontal => (&mut covering_geom.rows, geom.rows),
+            SplitDirection::Vertical => (&mut covering_geom.cols, geom.cols),
+        };
+        match (covering_dimension.as_percent(), dimension.as_percent()) {
+            (Some(covering_percent), Some(percent)) if covering_percent > percent => {
+                covering_dimension.reduce_by(percent, dimension.as_usize());
+            },
+            _ => return Some(pane),
+        }
+        match split_direction {
+            SplitDirection::Horizontal if covering_geom.y == geom.y => {
+                covering_geom.y += geom.rows.as_usize();
+            },
+            SplitDirection::Vertical if covering_geom.x == geom.x => {
+                covering_geom.x += geom.cols.as_usize();
+            },
+            _ => {},
+        }
+        if let Some(covering_pane) = self.panes.get_mut(&covering_pane_id) {
+            covering_pane.set_geom(covering_geom);
+        }
+        pane.set_geom(geom);
+        self.panes.insert(pane_id, pane);
+        self.relayout(!split_direction);
+        None
+    }
     pub fn has_room_for_new_pane(&mut self) -> bool {
         let cursor_height_width_ratio = self.cursor_height_width_ratio();
         let pane_grid = TiledPaneGrid::new(
@@ -436,6 +508,27 @@ impl TiledPanes {
 
         self.set_pane_frames(self.draw_pane_frames);
     }
+    pub fn set_session_is_mirrored(&mut self, session_is_mirrored: bool, client_id: ClientId) {
+        self.session_is_mirrored = session_is_mirrored;
+        if session_is_mirrored {
+            // from now on all clients follow the focus of the client that mirrored the session, or
+            // of the first one if that client is not in this tab
+            let connected_clients: Vec<ClientId> =
+                self.connected_clients.borrow().iter().copied().collect();
+            let focused_pane_id = self.active_panes.get(&client_id).copied().or_else(|| {
+                connected_clients
+                    .first()
+                    .and_then(|client_id| self.active_panes.get(client_id).copied())
+            });
+            if let Some(pane_id) = focused_pane_id {
+                for client_id in connected_clients {
+                    self.active_panes
+                        .insert(client_id, pane_id, &mut self.panes);
+                }
+                self.reset_boundaries();
+            }
+        }
+    }
     pub fn reapply_pane_frames(&mut self) {
         // same as set_pane_frames except it reapplies the current situation
         self.set_pane_frames(self.draw_pane_frames);
@@ -748,6 +841,47 @@ impl TiledPanes {
         }
         self.reset_boundaries();
     }
+    pub fn toggle_expand_stack(&mut self, client_id: ClientId) -> Result<()> {
+        // expands all the panes of the stack of the focused pane, or stacks them back if they
+        // were expanded this way
+        let err_context = || format!("failed to toggle expanding stack for client {client_id}");
+        let active_pane_id = self
+            .get_active_pane_id(client_id)
+            .with_context(err_context)?;
+        let expanded_stack_index = self
+            .expanded_stacks
+            .iter()
+            .position(|pane_ids| pane_ids.contains(&active_pane_id));
+        if let Some(expanded_stack_index) = expanded_stack_index {
+            let pane_ids: Vec<PaneId> = self
+                .expanded_stacks
+                .remove(expanded_stack_index)
+                .into_iter()
+                .filter(|pane_id| self.panes.contains_key(pane_id))
+                .collect();
+            StackedPanes::new_from_btreemap(&mut self.panes, &self.panes_to_hide)
+                .stack_expanded_panes(&pane_ids, &active_pane_id)
+                .with_context(err_context)?;
+        } else {
+            let pane_ids = StackedPanes::new_from_btreemap(&mut self.panes, &self.panes_to_hide)
+                .expand_all_panes_in_stack(&active_pane_id)
+                .with_context(err_context)?;
+            self.expanded_stacks.push(pane_ids);
+        }
+        self.set_force_render();
+        self.reapply_pane_frames();
+        Ok(())
+    }
+    pub fn move_pane_in_stack(&mut self, pane_id: PaneId, up: bool) -> Result<()> {
+        let moved = StackedPanes::new_from_btreemap(&mut self.panes, &self.panes_to_hide)
+            .move_pane_in_stack(&pane_id, up)
+            .with_context(|| format!("failed to move pane {pane_id:?} in stack"))?;
+        if moved {
+            self.set_force_render();
+            self.reapply_pane_frames();
+        }
+        Ok(())
+    }
     pub fn focus_pane_if_exists(&mut self, pane_id: PaneId, client_id: ClientId) -> Result<()> {
         if self.panes.get(&pane_id).is_some() {
             self.focus_pane(pane_id, client_id);
@@ -831,7 +965,12 @@ impl TiledPanes {
     pub fn has_panes(&self) -> bool {
         !self.panes.is_empty()
     }
-    pub fn render(&mut self, output: &mut Output, floating_panes_are_visible: bool) -> Result<()> {
+    pub fn render(
+        &mut self,
+        output: &mut Output,
+        floating_panes_are_visible: bool,
+        input_is_synced: bool,
+    ) -> Result<()> {
         let err_context = || "failed to render tiled panes";
 
         let connected_clients: Vec<ClientId> =
@@ -871,6 +1010,7 @@ impl TiledPanes {
                     pane_is_stacked_under,
                     pane_is_stacked_over,
                     should_draw_pane_frames,
+                    input_is_synced,
                 );
                 for client_id in &connected_clients {
                     let client_mode = self
@@ -966,6 +1106,7 @@ impl TiledPanes {
         run: Option<Run>,
         geom: PaneGeom,
         borderless: bool,
+        size_limits: Option<SizeLimits>,
     ) {
         match self
             .panes
@@ -975,6 +1116,7 @@ impl TiledPanes {
             Some((_, pane)) => {
                 pane.set_geom(geom);
                 pane.set_borderless(borderless);
+                pane.set_size_limits(size_limits);
                 if self.draw_pane_frames {
                     pane.set_content_offset(Offset::frame(1));
                 }
@@ -1494,6 +1636,69 @@ impl TiledPanes {
         Ok(pane_size_changed)
     }
 
+    /// Moves an edge of a pane to the given position, as when dragging its border with the mouse.
+    /// Unlike resize_pane_with_id, this resizes by exactly the distance to the position and does
+    /// not retry with a bigger step when that is too small to change anything.
+    pub fn move_pane_edge_to(
+        &mut self,
+        pane_id: PaneId,
+        edge: Direction,
+        position: &Position,
+    ) -> Result<bool> {
+        let err_context = || format!("failed to move edge {edge} of pane {pane_id:?}");
+        let Some(geom) = self.panes.get(&pane_id).map(|pane| pane.current_geom()) else {
+            return Ok(false);
+        };
+        let viewport = *self.viewport.borrow();
+        let (distance, space) = match edge {
+            Direction::Left => (geom.x as isize - position.column() as isize, viewport.cols),
+            Direction::Right => (
+                position.column() as isize + 1 - (geom.x + geom.cols.as_usize()) as isize,
+                viewport.cols,
+            ),
+            Direction::Up => (geom.y as isize - position.line(), viewport.rows),
+            Direction::Down => (
+                position.line() + 1 - (geom.y + geom.rows.as_usize()) as isize,
+                viewport.rows,
+            ),
+        };
+        if distance == 0 || space == 0 {
+            return Ok(false);
+        }
+        let resize = if distance > 0 {
+            Resize::Increase
+        } else {
+            Resize::Decrease
+        };
+        let mut strategy = ResizeStrategy::new(resize, Some(edge));
+        strategy.invert_on_boundaries = false;
+        let percent = distance.unsigned_abs() as f64 / space as f64 * 100.0;
+        let pane_size_changed = {
+            let mut pane_grid = TiledPaneGrid::new(
+                &mut self.panes,
+                &self.panes_to_hide,
+                *self.display_area.borrow(),
+                *self.viewport.borrow(),
+            );
+            match pane_grid.change_pane_size(&pane_id, &strategy, (percent, percent)) {
+                Ok(changed) => changed,
+                Err(err) => match err.downcast_ref::<ZellijError>() {
+                    Some(ZellijError::PaneSizeUnchanged)
+                    | Some(ZellijError::CantResizeFixedPanes { .. }) => false,
+                    _ => return Err(err).with_context(err_context),
+                },
+            }
+        };
+        if pane_size_changed {
+            for pane in self.panes.values_mut() {
+                resize_pty!(pane, self.os_api, self.senders, self.character_cell_size)
+                    .with_context(err_context)?;
+            }
+            self.reset_boundaries();
+        }
+        Ok(pane_size_changed)
+    }
+
     pub fn focus_next_pane(&mut self, client_id: ClientId) {
         let connected_clients: Vec<ClientId> =
             { self.connected_clients.borrow().iter().copied().collect() };
@@ -2196,6 +2401,9 @@ impl TiledPanes {
     pub fn fullscreen_is_active(&self) -> bool {
         self.fullscreen_is_active.is_some()
     }
+    pub fn fullscreen_pane_id(&self) -> Option<PaneId> {
+        self.fullscreen_is_active
+    }
     pub fn unset_fullscreen(&mut self) {
         if let Some(fullscreen_pane_id) = self.fullscreen_is_active {
             let panes_to_hide: Vec<_> = self.panes_to_hide.iter().copied().collect();
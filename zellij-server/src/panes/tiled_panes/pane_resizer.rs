@@ -1,9 +1,9 @@
 use super::stacked_panes::StackedPanes;
 use crate::{panes::PaneId, tab::Pane};
 use cassowary::{
-    strength::{REQUIRED, STRONG},
+    strength::{self, REQUIRED, STRONG},
     Expression, Solver, Variable,
-    WeightedRelation::EQ,
+    WeightedRelation::{EQ, GE, LE},
 };
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -29,10 +29,18 @@ struct Span {
     pos: usize,
     size: Dimension,
     size_var: Variable,
+    // the size limits of the pane in this direction
+    min_size: Option<usize>,
+    max_size: Option<usize>,
 }
 
 type Grid = Vec<Vec<Span>>;
 
+// The min/max sizes of panes win over their percentages, but give way if they cannot all fit
+fn size_limit_strength() -> f64 {
+    strength::create(100.0, 0.0, 0.0, 1.0)
+}
+
 impl<'a> PaneResizer<'a> {
     pub fn new(panes: Rc<RefCell<HashMap<PaneId, &'a mut Box<dyn Pane>>>>) -> Self {
         let mut vars = HashMap::new();
@@ -102,6 +110,15 @@ impl<'a> PaneResizer<'a> {
             if error < 0 {
                 flex_spans.reverse();
             }
+            // prefer rounding the spans that can take it without going past their size limits
+            flex_spans.sort_by_key(|s| {
+                let size = rounded_sizes[&s.size_var];
+                if error > 0 {
+                    s.max_size.map(|max| size >= max as isize).unwrap_or(false)
+                } else {
+                    s.min_size.map(|min| size <= min as isize).unwrap_or(false)
+                }
+            });
             for span in flex_spans {
                 rounded_sizes
                     .entry(span.size_var)
@@ -260,21 +277,39 @@ impl<'a> PaneResizer<'a> {
             }
         }?;
         let size_var = *self.vars.get(&pane.pid()).unwrap();
+        // stacks are sized as a whole, so the limits of the panes inside them do not apply
+        let size_limits = pane
+            .size_limits()
+            .filter(|_| !pane.current_geom().is_stacked());
         match direction {
-            SplitDirection::Horizontal => Some(Span {
-                pid: pane.pid(),
-                direction,
-                pos: position_and_size.x,
-                size: position_and_size.cols,
-                size_var,
-            }),
-            SplitDirection::Vertical => Some(Span {
-                pid: pane.pid(),
-                direction,
-                pos: position_and_size.y,
-                size: position_and_size.rows,
-                size_var,
-            }),
+            SplitDirection::Horizontal => {
+                let (min_size, max_size) = size_limits
+                    .map(|size_limits| size_limits.cols())
+                    .unwrap_or_default();
+                Some(Span {
+                    pid: pane.pid(),
+                    direction,
+                    pos: position_and_size.x,
+                    size: position_and_size.cols,
+                    size_var,
+                    min_size,
+                    max_size,
+                })
+            },
+            SplitDirection::Vertical => {
+                let (min_size, max_size) = size_limits
+                    .map(|size_limits| size_limits.rows())
+                    .unwrap_or_default();
+                Some(Span {
+                    pid: pane.pid(),
+                    direction,
+                    pos: position_and_size.y,
+                    size: position_and_size.rows,
+                    size_var,
+                    min_size,
+                    max_size,
+                })
+            },
         }
     }
 }
@@ -304,6 +339,14 @@ fn constrain_spans(space: usize, spans: &[Span]) -> HashSet<cassowary::Constrain
             Constraint::Percent(p) => constraints
                 .insert((span.size_var / new_flex_space as f64) | EQ(STRONG) | (p / 100.0)),
         };
+        if !span.size.is_fixed() {
+            if let Some(min_size) = span.min_size {
+                constraints.insert(span.size_var | GE(size_limit_strength()) | min_size as f64);
+            }
+            if let Some(max_size) = span.max_size {
+                constraints.insert(span.size_var | LE(size_limit_strength()) | max_size as f64);
+            }
+        }
     }
 
     constraints
@@ -794,6 +794,122 @@ impl<'a> StackedPanes<'a> {
         );
         Some(pane_ids_that_were_resized)
     }
+    pub fn expand_all_panes_in_stack(&mut self, pane_id: &PaneId) -> Result<Vec<PaneId>> {
+        // breaks all the panes out of the stack, each taking an even share of its rows, and
+        // returns their ids top to bottom so that they can be stacked again later
+        let err_context = || format!("Failed to expand all panes in stack");
+        let all_stacked_pane_positions =
+            self.positions_in_stack(pane_id).with_context(err_context)?;
+        let stack_rows = self
+            .position_and_size_of_stack(pane_id)
+            .with_context(err_context)?
+            .rows
+            .as_usize();
+        if all_stacked_pane_positions.len() < 2 {
+            return Err(anyhow!("Pane is not in a stack")).with_context(err_context);
+        }
+        if stack_rows < all_stacked_pane_positions.len() * MIN_TERMINAL_HEIGHT {
+            return Err(anyhow!("Not enough room to expand all panes of the stack"))
+                .with_context(err_context);
+        }
+        while self.pane_is_stacked(pane_id) {
+            self.break_pane_out_of_stack(pane_id)
+                .with_context(err_context)?;
+        }
+        Ok(all_stacked_pane_positions
+            .iter()
+            .map(|(pane_id, _)| *pane_id)
+            .collect())
+    }
+    pub fn stack_expanded_panes(
+        &mut self,
+        pane_ids: &[PaneId],
+        flexible_pane_id: &PaneId,
+    ) -> Result<()> {
+        // the reverse of expand_all_panes_in_stack, as long as the panes still form a single
+        // column in the same order
+        let err_context = || format!("Failed to stack expanded panes");
+        let geoms: Vec<PaneGeom> = {
+            let panes = self.panes.borrow();
+            pane_ids
+                .iter()
+                .map(|pane_id| panes.get(pane_id).map(|p| p.position_and_size()))
+                .collect::<Option<Vec<_>>>()
+                .with_context(err_context)?
+        };
+        let (first_geom, geoms_below) = geoms.split_first().with_context(err_context)?;
+        let form_a_column = !first_geom.is_stacked()
+            && geoms.windows(2).all(|pair| {
+                !pair[1].is_stacked()
+                    && pair[0].x == pair[1].x
+                    && pair[0].cols == pair[1].cols
+                    && pair[0].y + pair[0].rows.as_usize() == pair[1].y
+            });
+        if !form_a_column || !pane_ids.contains(flexible_pane_id) {
+            return Err(anyhow!("Expanded panes were moved out of their column"))
+                .with_context(err_context);
+        }
+        let mut position_and_size_of_stack = first_geom
+            .combine_vertically_with_many(&geoms_below.to_vec())
+            .with_context(err_context)?;
+        position_and_size_of_stack.stacked = Some(self.next_stack_id());
+        let all_stacked_pane_positions: Vec<(PaneId, PaneGeom)> =
+            pane_ids.iter().copied().zip(geoms.into_iter()).collect();
+        let mut pane_ids_that_were_resized = vec![];
+        self.reset_stack_size(
+            &position_and_size_of_stack,
+            &all_stacked_pane_positions,
+            *flexible_pane_id,
+            &mut pane_ids_that_were_resized,
+        );
+        Ok(())
+    }
+    pub fn move_pane_in_stack(&mut self, pane_id: &PaneId, up: bool) -> Result<bool> {
+        // swaps the pane with the one above (or below) it in the stack, each keeping its size,
+        // returns false if it is already the first (or last) pane
+        let err_context = || format!("Failed to move pane in stack");
+        let all_stacked_pane_positions =
+            self.positions_in_stack(pane_id).with_context(err_context)?;
+        let position_in_stack = all_stacked_pane_positions
+            .iter()
+            .position(|(pid, _)| pid == pane_id)
+            .with_context(err_context)?;
+        let position_of_neighbor = if up {
+            position_in_stack.checked_sub(1)
+        } else {
+            Some(position_in_stack + 1).filter(|p| *p < all_stacked_pane_positions.len())
+        };
+        let Some(position_of_neighbor) = position_of_neighbor else {
+            return Ok(false);
+        };
+        let (upper_pane_id, mut upper_pane_geom) =
+            all_stacked_pane_positions[std::cmp::min(position_in_stack, position_of_neighbor)];
+        let (lower_pane_id, mut lower_pane_geom) =
+            all_stacked_pane_positions[std::cmp::max(position_in_stack, position_of_neighbor)];
+        lower_pane_geom.y = upper_pane_geom.y;
+        upper_pane_geom.y = lower_pane_geom.y + lower_pane_geom.rows.as_usize();
+        std::mem::swap(
+            &mut upper_pane_geom.logical_position,
+            &mut lower_pane_geom.logical_position,
+        );
+        let mut panes = self.panes.borrow_mut();
+        panes
+            .get_mut(&upper_pane_id)
+            .with_context(err_context)?
+            .set_geom(upper_pane_geom);
+        panes
+            .get_mut(&lower_pane_id)
+            .with_context(err_context)?
+            .set_geom(lower_pane_geom);
+        Ok(true)
+    }
+    fn pane_is_stacked(&self, id: &PaneId) -> bool {
+        self.panes
+            .borrow()
+            .get(id)
+            .map(|p| p.position_and_size().is_stacked())
+            .unwrap_or(false)
+    }
     pub fn next_stack_id(&self) -> usize {
         let mut highest_stack_id = 0;
         let panes = self.panes.borrow();
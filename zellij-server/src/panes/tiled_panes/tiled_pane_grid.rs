@@ -8,7 +8,7 @@ use std::collections::{HashMap, HashSet};
 use zellij_utils::data::{Direction, Resize, ResizeStrategy};
 use zellij_utils::{
     errors::prelude::*,
-    input::layout::SplitDirection,
+    input::layout::{SizeLimits, SplitDirection},
     pane_size::{Dimension, PaneGeom, Size, Viewport},
 };
 
@@ -185,6 +185,12 @@ impl<'a> TiledPaneGrid<'a> {
             if direction.is_horizontal() {
                 match strategy.resize {
                     Resize::Increase => {
+                        if !self
+                            .can_increase_pane_width(pane_id, change_by.0 as f64)
+                            .with_context(err_context)?
+                        {
+                            return Ok(false);
+                        }
                         for id in pane_ids {
                             if !self
                                 .can_reduce_pane_width(&id, change_by.0 as f64)
@@ -195,13 +201,28 @@ impl<'a> TiledPaneGrid<'a> {
                         }
                         Ok(true)
                     },
-                    Resize::Decrease => self
-                        .can_reduce_pane_width(pane_id, change_by.0 as f64)
-                        .with_context(err_context),
+                    Resize::Decrease => {
+                        for id in pane_ids {
+                            if !self
+                                .can_increase_pane_width(&id, change_by.0 as f64)
+                                .with_context(err_context)?
+                            {
+                                return Ok(false);
+                            }
+                        }
+                        self.can_reduce_pane_width(pane_id, change_by.0 as f64)
+                            .with_context(err_context)
+                    },
                 }
             } else {
                 match strategy.resize {
                     Resize::Increase => {
+                        if !self
+                            .can_increase_pane_height(pane_id, change_by.1 as f64)
+                            .with_context(err_context)?
+                        {
+                            return Ok(false);
+                        }
                         for id in pane_ids {
                             if !self
                                 .can_reduce_pane_height(&id, change_by.1 as f64)
@@ -212,9 +233,18 @@ impl<'a> TiledPaneGrid<'a> {
                         }
                         Ok(true)
                     },
-                    Resize::Decrease => self
-                        .can_reduce_pane_height(pane_id, change_by.1 as f64)
-                        .with_context(err_context),
+                    Resize::Decrease => {
+                        for id in pane_ids {
+                            if !self
+                                .can_increase_pane_height(&id, change_by.1 as f64)
+                                .with_context(err_context)?
+                            {
+                                return Ok(false);
+                            }
+                        }
+                        self.can_reduce_pane_height(pane_id, change_by.1 as f64)
+                            .with_context(err_context)
+                    },
                 }
             }
         } else {
@@ -526,9 +556,14 @@ impl<'a> TiledPaneGrid<'a> {
             .get_pane_geom(pane_id)
             .with_context(|| no_pane_id(pane_id))
             .with_context(err_context)?;
+        let min_cols = self
+            .size_limits_of_pane(pane_id)
+            .and_then(|size_limits| size_limits.cols().0)
+            .map(|min_cols| min_cols.max(MIN_TERMINAL_WIDTH))
+            .unwrap_or(MIN_TERMINAL_WIDTH);
         let current_fixed_cols = pane.cols.as_usize();
         let will_reduce_by = ((self.display_area.cols as f64 / 100.0) * reduce_by) as usize;
-        if current_fixed_cols.saturating_sub(will_reduce_by) < MIN_TERMINAL_WIDTH {
+        if current_fixed_cols.saturating_sub(will_reduce_by) < min_cols {
             Ok(false)
         } else if let Some(cols) = pane.cols.as_percent() {
             Ok(cols - reduce_by >= RESIZE_PERCENT)
@@ -548,7 +583,10 @@ impl<'a> TiledPaneGrid<'a> {
         let min_terminal_height = if pane.is_stacked() {
             StackedPanes::new(self.panes.clone()).min_stack_height(pane_id)?
         } else {
-            MIN_TERMINAL_HEIGHT
+            self.size_limits_of_pane(pane_id)
+                .and_then(|size_limits| size_limits.rows().0)
+                .map(|min_rows| min_rows.max(MIN_TERMINAL_HEIGHT))
+                .unwrap_or(MIN_TERMINAL_HEIGHT)
         };
         let current_fixed_rows = pane.rows.as_usize();
         let will_reduce_by = ((self.display_area.rows as f64 / 100.0) * reduce_by) as usize;
@@ -560,6 +598,52 @@ impl<'a> TiledPaneGrid<'a> {
             Ok(false)
         }
     }
+    fn can_increase_pane_width(&self, pane_id: &PaneId, increase_by: f64) -> Result<bool> {
+        let err_context = || {
+            format!("failed to determine if pane {pane_id:?} can increase width by {increase_by} %")
+        };
+
+        let pane = self
+            .get_pane_geom(pane_id)
+            .with_context(|| no_pane_id(pane_id))
+            .with_context(err_context)?;
+        let will_increase_by = ((self.display_area.cols as f64 / 100.0) * increase_by) as usize;
+        match self
+            .size_limits_of_pane(pane_id)
+            .and_then(|size_limits| size_limits.cols().1)
+        {
+            Some(max_cols) => Ok(pane.cols.as_usize() + will_increase_by <= max_cols),
+            None => Ok(true),
+        }
+    }
+    fn can_increase_pane_height(&self, pane_id: &PaneId, increase_by: f64) -> Result<bool> {
+        let err_context = || {
+            format!(
+                "failed to determine if pane {pane_id:?} can increase height by {increase_by} %"
+            )
+        };
+
+        let pane = self
+            .get_pane_geom(pane_id)
+            .with_context(|| no_pane_id(pane_id))
+            .with_context(err_context)?;
+        let will_increase_by = ((self.display_area.rows as f64 / 100.0) * increase_by) as usize;
+        match self
+            .size_limits_of_pane(pane_id)
+            .and_then(|size_limits| size_limits.rows().1)
+        {
+            Some(max_rows) => Ok(pane.rows.as_usize() + will_increase_by <= max_rows),
+            None => Ok(true),
+        }
+    }
+    fn size_limits_of_pane(&self, pane_id: &PaneId) -> Option<SizeLimits> {
+        // stacks are sized as a whole, so the limits of the panes inside them do not apply
+        self.panes
+            .borrow()
+            .get(pane_id)
+            .filter(|pane| !pane.current_geom().is_stacked())
+            .and_then(|pane| pane.size_limits())
+    }
 
     fn reduce_pane_height(&mut self, id: &PaneId, percent: f64) {
         if self.can_reduce_pane_height(id, percent).unwrap_or(false) {
@@ -1035,6 +1035,149 @@ fn break_next_to_last_pane_out_of_stack() {
     assert_snapshot!(format!("{:#?}", pane_geoms_after));
 }
 
+#[test]
+fn expand_all_panes_in_stack_and_stack_them_back() {
+    let mut mock_panes: HashMap<PaneId, &mut Box<dyn Pane>> = HashMap::new();
+
+    mock_stacked_pane!(
+        PaneId::Terminal(1),
+        Dimension::percent(100.0),
+        28,
+        0,
+        0,
+        Some(1),
+        mock_panes
+    );
+    mock_stacked_pane!(
+        PaneId::Terminal(2),
+        Dimension::fixed(1),
+        1,
+        0,
+        28,
+        Some(2),
+        mock_panes
+    );
+    mock_stacked_pane!(
+        PaneId::Terminal(3),
+        Dimension::fixed(1),
+        1,
+        0,
+        29,
+        Some(3),
+        mock_panes
+    );
+
+    let mock_panes = Rc::new(RefCell::new(mock_panes));
+    let geom_of = |pane_id: u32| {
+        mock_panes
+            .borrow()
+            .get(&PaneId::Terminal(pane_id))
+            .unwrap()
+            .current_geom()
+    };
+
+    let expanded_pane_ids = StackedPanes::new(mock_panes.clone())
+        .expand_all_panes_in_stack(&PaneId::Terminal(1))
+        .unwrap();
+    assert_eq!(
+        expanded_pane_ids,
+        vec![
+            PaneId::Terminal(1),
+            PaneId::Terminal(2),
+            PaneId::Terminal(3)
+        ]
+    );
+    for (pane_id, y) in [(1, 0), (2, 10), (3, 20)] {
+        let geom = geom_of(pane_id);
+        assert!(
+            !geom.is_stacked(),
+            "pane {} was broken out of the stack",
+            pane_id
+        );
+        assert_eq!((geom.y, geom.rows.as_usize()), (y, 10), "pane {}", pane_id);
+    }
+
+    StackedPanes::new(mock_panes.clone())
+        .stack_expanded_panes(&expanded_pane_ids, &PaneId::Terminal(2))
+        .unwrap();
+    for (pane_id, y, rows) in [(1, 0, 1), (2, 1, 28), (3, 29, 1)] {
+        let geom = geom_of(pane_id);
+        assert!(geom.is_stacked(), "pane {} is stacked again", pane_id);
+        assert_eq!(
+            (geom.y, geom.rows.as_usize()),
+            (y, rows),
+            "pane {}",
+            pane_id
+        );
+    }
+    assert!(
+        geom_of(2).rows.is_percent(),
+        "focused pane is the expanded one"
+    );
+}
+
+#[test]
+fn move_pane_in_stack() {
+    let mut mock_panes: HashMap<PaneId, &mut Box<dyn Pane>> = HashMap::new();
+
+    mock_stacked_pane!(
+        PaneId::Terminal(1),
+        Dimension::fixed(1),
+        1,
+        0,
+        0,
+        Some(1),
+        mock_panes
+    );
+    mock_stacked_pane!(
+        PaneId::Terminal(2),
+        Dimension::percent(100.0),
+        28,
+        0,
+        1,
+        Some(2),
+        mock_panes
+    );
+    mock_stacked_pane!(
+        PaneId::Terminal(3),
+        Dimension::fixed(1),
+        1,
+        0,
+        29,
+        Some(3),
+        mock_panes
+    );
+
+    let mock_panes = Rc::new(RefCell::new(mock_panes));
+    let geom_of = |pane_id: u32| {
+        mock_panes
+            .borrow()
+            .get(&PaneId::Terminal(pane_id))
+            .unwrap()
+            .current_geom()
+    };
+
+    let moved = StackedPanes::new(mock_panes.clone())
+        .move_pane_in_stack(&PaneId::Terminal(2), true)
+        .unwrap();
+    assert!(moved);
+    assert_eq!((geom_of(2).y, geom_of(2).rows.as_usize()), (0, 28));
+    assert_eq!((geom_of(1).y, geom_of(1).rows.as_usize()), (28, 1));
+    assert_eq!(geom_of(2).logical_position, Some(1));
+    assert_eq!(geom_of(1).logical_position, Some(2));
+
+    let moved = StackedPanes::new(mock_panes.clone())
+        .move_pane_in_stack(&PaneId::Terminal(2), true)
+        .unwrap();
+    assert!(!moved, "first pane of the stack cannot move up");
+
+    StackedPanes::new(mock_panes.clone())
+        .move_pane_in_stack(&PaneId::Terminal(1), false)
+        .unwrap();
+    assert_eq!((geom_of(3).y, geom_of(3).rows.as_usize()), (28, 1));
+    assert_eq!((geom_of(1).y, geom_of(1).rows.as_usize()), (29, 1));
+}
+
 struct MockPane {
     pane_geom: PaneGeom,
 }
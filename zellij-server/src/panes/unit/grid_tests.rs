@@ -1,14 +1,21 @@
 use super::super::Grid;
-use crate::panes::grid::SixelImageStore;
+use crate::panes::copy_mode::CopyModeOutcome;
+use crate::panes::grid::{
+    AlternateScreenBehavior, MouseMode, MouseTracking, PortDeclaration, SixelImageStore,
+    TerminalModes, UnicodeWidth,
+};
 use crate::panes::link_handler::LinkHandler;
 use ::insta::assert_snapshot;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 use zellij_utils::{
     data::{Palette, Style},
-    pane_size::SizeInPixels,
+    input::{layout::ScrollbackSize, options::Clipboard},
+    pane_size::{Dimension, PaneGeom, SizeInPixels},
     position::Position,
+    regex::Regex,
     vte,
 };
 
@@ -1592,6 +1599,44 @@ fn copy_wrapped_selected_text_from_viewport() {
     );
 }
 
+#[test]
+fn copy_block_selected_with_the_keyboard() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        10,
+        40,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    for byte in "a 1 x\r\nb 22 y\r\nc 333 z".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+
+    // from the end of the last line: to the `1` of the first line, then down to the end of `333`
+    for key in ['0', 'k', 'k', 'w', '\u{16}', 'j', 'j', 'e'] {
+        assert_eq!(grid.copy_mode_input(key), CopyModeOutcome::Continue);
+    }
+    assert_eq!(grid.get_selected_text().unwrap(), "1 x\n22\n333");
+    assert_eq!(grid.copy_mode_input('y'), CopyModeOutcome::Copy);
+    grid.exit_copy_mode();
+    assert!(!grid.copy_mode_is_active());
+    assert_eq!(grid.get_selected_text(), None);
+}
+
 #[test]
 fn copy_selected_text_from_lines_above() {
     let mut vte_parser = vte::Parser::new();
@@ -3900,3 +3945,775 @@ fn cannot_escape_scroll_region() {
     }
     assert_snapshot!(format!("{:?}", grid));
 }
+
+#[test]
+fn terminal_reset_clears_terminal_modes() {
+    // modes requested by an application must not survive a reset (eg. when rerunning a command
+    // pane), otherwise they would leak to the next application running in the pane
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        41,
+        120,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    let content =
+        "\u{1b}[?2004h\u{1b}[?1h\u{1b}[4h\u{1b}[?1002h\u{1b}[?1006h\u{1b}[?1004h".as_bytes();
+    for byte in content {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.terminal_modes(),
+        TerminalModes {
+            bracketed_paste: true,
+            cursor_keys: true,
+            insert: true,
+            mouse_tracking: MouseTracking::ButtonEventTracking,
+            mouse_mode: MouseMode::Sgr,
+            focus_event_tracking: true,
+            kitty_keyboard_protocol: false,
+        },
+        "modes requested by the application"
+    );
+    for byte in "\u{1b}c".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.terminal_modes(),
+        TerminalModes::default(),
+        "modes cleared after reset"
+    );
+}
+
+#[test]
+pub fn osc_7_reports_cwd() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        41,
+        120,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    for byte in "\u{1b}]7;file://localhost/tmp/my%20project\u{1b}\\".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.pending_reported_cwd.take(),
+        Some(PathBuf::from("/tmp/my project")),
+        "cwd reported from the local host"
+    );
+    for byte in "\u{1b}]7;file:///tmp\u{7}".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.pending_reported_cwd.take(),
+        Some(PathBuf::from("/tmp")),
+        "cwd reported without a host"
+    );
+    for byte in "\u{1b}]7;file://some-remote-host.invalid/tmp\u{1b}\\".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.pending_reported_cwd.take(),
+        None,
+        "cwd reported from a remote host is ignored"
+    );
+}
+
+#[test]
+pub fn osc_zellij_declares_ports() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        41,
+        120,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    let declarations = "\u{1b}]zellij;declare-port=8080;name=api\u{1b}\\\
+        \u{1b}]zellij;declare-port=5432\u{7}\
+        \u{1b}]zellij;declare-port=not-a-port\u{7}\
+        \u{1b}]zellij;undeclare-port=8080\u{7}";
+    for byte in declarations.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.pending_port_declarations,
+        vec![
+            PortDeclaration::Declared(8080, Some("api".to_owned())),
+            PortDeclaration::Declared(5432, None),
+            PortDeclaration::Undeclared(8080),
+        ],
+        "malformed declarations are ignored"
+    );
+}
+
+#[test]
+pub fn osc_52_keeps_the_clipboard_selected_by_the_application() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        41,
+        120,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    for byte in "\u{1b}]52;p;aGVsbG8=\u{1b}\\".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.pending_clipboard_update.take(),
+        Some((Clipboard::Primary, "hello".to_owned()))
+    );
+    for byte in "\u{1b}]52;c;d29ybGQ=\u{7}\u{1b}]52;c;?\u{7}".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.pending_clipboard_update.take(),
+        Some((Clipboard::System, "world".to_owned())),
+        "clipboard queries are not answered"
+    );
+}
+
+#[test]
+pub fn double_click_word_ends_at_configured_separators() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        41,
+        120,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    for byte in "ls foo/bar.rs:12,baz".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.word_around_position(&Position::new(0, 8)),
+        Some((Position::new(0, 3), Position::new(0, 20))),
+        "only whitespace and brackets end words by default"
+    );
+    grid.update_word_separators("/:,");
+    assert_eq!(
+        grid.word_around_position(&Position::new(0, 8)),
+        Some((Position::new(0, 7), Position::new(0, 13)))
+    );
+    assert_eq!(
+        grid.word_around_position(&Position::new(0, 6)),
+        Some((Position::new(0, 6), Position::new(0, 7))),
+        "a separator is selected on its own"
+    );
+}
+
+#[test]
+pub fn scroll_to_pattern_moves_viewport_to_matches() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        10,
+        40,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    for i in 0..50 {
+        let line = if i == 5 || i == 30 {
+            format!("error {}\r\n", i)
+        } else {
+            format!("line {}\r\n", i)
+        };
+        for byte in line.as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    let viewport_top = |grid: &Grid| -> String {
+        grid.viewport[0]
+            .columns
+            .iter()
+            .map(|c| c.character)
+            .collect::<String>()
+            .trim_end()
+            .to_owned()
+    };
+    let pattern = Regex::new("error [0-9]+").unwrap();
+    assert_eq!(grid.scroll_to_pattern(&pattern, true), 2);
+    assert_eq!(viewport_top(&grid), "error 30", "jumped to the last match");
+    grid.scroll_to_pattern(&pattern, true);
+    assert_eq!(
+        viewport_top(&grid),
+        "error 5",
+        "jumped to the previous match"
+    );
+    grid.scroll_to_pattern(&pattern, false);
+    assert_eq!(viewport_top(&grid), "error 30", "jumped to the next match");
+    let no_matches = Regex::new("warning").unwrap();
+    assert_eq!(grid.scroll_to_pattern(&no_matches, false), 0);
+    assert_eq!(viewport_top(&grid), "error 30", "viewport did not move");
+}
+
+#[test]
+pub fn scroll_to_prompt_and_select_last_command_output() {
+    let mut grid = grid_with_kitty_graphics(10, 40);
+    let mut vte_parser = vte::Parser::new();
+    let mut output = String::new();
+    for i in 0..5 {
+        output.push_str(&format!(
+            "\u{1b}]133;A\u{7}$ \u{1b}]133;B\u{7}cmd {}\r\n\u{1b}]133;C\u{7}",
+            i
+        ));
+        // the last command prints nothing
+        if i < 4 {
+            for j in 0..9 {
+                output.push_str(&format!("out {} {}\r\n", i, j));
+            }
+        }
+        output.push_str("\u{1b}]133;D;0\u{7}");
+    }
+    output.push_str("\u{1b}]133;A\u{7}$ ");
+    for byte in output.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    let viewport_top = |grid: &Grid| -> String {
+        grid.viewport[0]
+            .columns
+            .iter()
+            .map(|c| c.character)
+            .collect::<String>()
+            .trim_end()
+            .to_owned()
+    };
+
+    assert!(grid.select_last_command_output());
+    assert_eq!(
+        grid.get_selected_text(),
+        Some(
+            (0..9)
+                .map(|j| format!("out 3 {}", j))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+        "the output of the last command that printed something is selected"
+    );
+    assert_eq!(
+        viewport_top(&grid),
+        "out 3 0",
+        "scrolled to the selected output"
+    );
+    grid.reset_viewport();
+
+    assert!(grid.scroll_to_prompt(true));
+    assert_eq!(viewport_top(&grid), "$ cmd 3");
+    assert!(grid.scroll_to_prompt(true));
+    assert_eq!(viewport_top(&grid), "$ cmd 2");
+    assert!(grid.scroll_to_prompt(false));
+    assert_eq!(viewport_top(&grid), "$ cmd 3");
+
+    let mut unmarked_grid = grid_with_kitty_graphics(10, 40);
+    for byte in "$ ls\r\nfile\r\n$ ".as_bytes() {
+        vte_parser.advance(&mut unmarked_grid, *byte);
+    }
+    assert!(!unmarked_grid.scroll_to_prompt(true));
+    assert!(!unmarked_grid.select_last_command_output());
+}
+
+#[test]
+pub fn alternate_screen_output_can_be_kept_in_the_scrollback() {
+    let run_app = |alternate_screen_behavior: AlternateScreenBehavior| -> Grid {
+        let mut grid = grid_with_kitty_graphics(10, 40);
+        grid.set_alternate_screen_behavior(alternate_screen_behavior);
+        let mut vte_parser = vte::Parser::new();
+        let output = "$ before\r\n\u{1b}[?1049happ frame\r\n\u{1b}[?1049l$ ";
+        for byte in output.as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+        grid
+    };
+
+    let grid = run_app(AlternateScreenBehavior::default());
+    assert!(grid.dump_screen(false).starts_with("$ before\n$ "));
+    assert!(!grid.dump_screen(true).contains("app frame"));
+
+    let grid = run_app(AlternateScreenBehavior {
+        keep_output_in_scrollback: false,
+        clear_viewport_on_exit: true,
+    });
+    assert_eq!(grid.dump_screen(false).trim_end(), "$");
+    assert!(grid.dump_screen(true).starts_with("$ before\n$"));
+    assert!(!grid.dump_screen(true).contains("app frame"));
+
+    let grid = run_app(AlternateScreenBehavior {
+        keep_output_in_scrollback: true,
+        clear_viewport_on_exit: false,
+    });
+    assert!(!grid.is_alternate_mode_active());
+    assert!(grid.dump_screen(false).starts_with("app frame\n$ "));
+    assert!(grid.dump_screen(true).starts_with("$ before\napp frame\n"));
+
+    let grid = run_app(AlternateScreenBehavior {
+        keep_output_in_scrollback: true,
+        clear_viewport_on_exit: true,
+    });
+    assert_eq!(grid.dump_screen(false).trim_end(), "$");
+    assert!(grid.dump_screen(true).starts_with("$ before\napp frame\n$"));
+}
+
+#[test]
+pub fn find_pattern_numbers_lines_from_the_top_of_the_scrollback() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    let mut vte_parser = vte::Parser::new();
+    for i in 0..50 {
+        let line = if i == 5 {
+            // wraps over two rows of the viewport
+            format!("error {} {}\r\n", i, "x".repeat(20))
+        } else if i == 30 {
+            format!("error {}\r\n", i)
+        } else {
+            format!("line {}\r\n", i)
+        };
+        for byte in line.as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    let pattern = Regex::new("error [0-9]+").unwrap();
+    assert_eq!(
+        grid.find_pattern(&pattern),
+        vec![
+            (6, format!("error 5 {}", "x".repeat(20))),
+            (31, "error 30".to_owned())
+        ]
+    );
+    grid.scroll_to_line(31);
+    assert!(grid.dump_screen(false).starts_with("error 30\nline 31\n"));
+    grid.scroll_to_line(6);
+    assert!(grid.dump_screen(false).starts_with("error 5 "));
+    grid.scroll_to_line(8);
+    assert!(
+        grid.dump_screen(false).starts_with("line 7\n"),
+        "the wrapped line counts as one line"
+    );
+}
+
+fn grid_with_kitty_graphics(rows: usize, columns: usize) -> Grid {
+    let character_cell_size = Rc::new(RefCell::new(Some(SizeInPixels {
+        width: 8,
+        height: 16,
+    })));
+    Grid::new(
+        rows,
+        columns,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        character_cell_size,
+        Rc::new(RefCell::new(SixelImageStore::default())),
+        Style::default(),
+        false,
+        true,
+        true,
+        false,
+    )
+}
+
+fn feed_with_kitty_graphics(grid: &mut Grid, content: &str) {
+    let mut vte_parser = vte::Parser::new();
+    for byte in content.as_bytes() {
+        vte_parser.advance(grid, *byte);
+        grid.handle_kitty_graphics_byte(*byte);
+    }
+}
+
+fn raw_vte_output(grid: &mut Grid) -> String {
+    grid.render(0, 0, &Style::default())
+        .unwrap()
+        .and_then(|(_, raw_vte_output, _)| raw_vte_output)
+        .unwrap_or_default()
+}
+
+#[test]
+pub fn kitty_graphics_images_are_placed_where_they_were_transmitted() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    // a 16x32 pixel image, which covers 2x2 cells
+    feed_with_kitty_graphics(
+        &mut grid,
+        "line\n\r\u{1b}_Ga=T,f=24,s=16,v=32,i=5;AAAA\u{1b}\\",
+    );
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec!["\u{1b}_Gi=5;OK\u{1b}\\".as_bytes().to_vec()],
+        "transmission was acknowledged"
+    );
+    assert_eq!(
+        grid.cursor_coordinates(),
+        Some((2, 2)),
+        "cursor moved past the image"
+    );
+    let vte_output = raw_vte_output(&mut grid);
+    assert!(
+        vte_output.contains(";AAAA\u{1b}\\"),
+        "image was transmitted"
+    );
+    assert!(
+        vte_output.contains("\u{1b}[2;1H\u{1b}_Ga=p,i="),
+        "image was placed where it was transmitted"
+    );
+    assert!(vte_output.contains(",C=1,q=2,c=2,r=2\u{1b}\\"));
+    assert_eq!(
+        raw_vte_output(&mut grid),
+        "",
+        "image is not placed again until it moves"
+    );
+}
+
+#[test]
+pub fn kitty_graphics_placements_are_deleted_when_scrolled_off() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    feed_with_kitty_graphics(
+        &mut grid,
+        &format!(
+            "{}\u{1b}_Ga=T,f=24,s=16,v=32,i=5,q=2;AAAA\u{1b}\\",
+            "\n\r".repeat(5)
+        ),
+    );
+    assert!(
+        grid.pending_messages_to_pty.is_empty(),
+        "replies were muted"
+    );
+    let _ = raw_vte_output(&mut grid);
+    // the cursor is on the line below the image, 3 lines above the bottom of the viewport
+    feed_with_kitty_graphics(&mut grid, &"\n\r".repeat(4));
+    let vte_output = raw_vte_output(&mut grid);
+    assert!(
+        vte_output.contains("\u{1b}[5;1H\u{1b}_Ga=p,i="),
+        "image moved up along with its line"
+    );
+    feed_with_kitty_graphics(&mut grid, &"\n\r".repeat(10));
+    let vte_output = raw_vte_output(&mut grid);
+    assert!(
+        vte_output.contains("\u{1b}_Ga=d,d=i,i="),
+        "image was deleted once it was no longer entirely visible"
+    );
+}
+
+#[test]
+pub fn kitty_graphics_placements_are_deleted_when_clearing_the_screen() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    feed_with_kitty_graphics(&mut grid, "\u{1b}_Ga=T,f=24,s=16,v=32;AAAA\u{1b}\\");
+    assert!(
+        grid.pending_messages_to_pty.is_empty(),
+        "commands without an id are not replied to"
+    );
+    let _ = raw_vte_output(&mut grid);
+    feed_with_kitty_graphics(&mut grid, "\u{1b}[2J");
+    let vte_output = raw_vte_output(&mut grid);
+    assert!(vte_output.contains("\u{1b}_Ga=d,d=i,i="));
+    assert!(!vte_output.contains("a=p"));
+}
+
+#[test]
+pub fn kitty_graphics_queries_are_answered_with_whether_the_image_would_be_accepted() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    feed_with_kitty_graphics(
+        &mut grid,
+        "\u{1b}_Ga=q,i=31,s=1,v=1,f=24;AAAA\u{1b}\\\
+         \u{1b}_Ga=q,i=32,s=2,v=2,f=24;AAAA\u{1b}\\\
+         \u{1b}_Ga=q,i=33,f=100;AAAA\u{1b}\\\
+         \u{1b}_Ga=q,i=34,t=f;AAAA\u{1b}\\",
+    );
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec![
+            "\u{1b}_Gi=31;OK\u{1b}\\".as_bytes().to_vec(),
+            "\u{1b}_Gi=32;ENODATA:insufficient image data\u{1b}\\"
+                .as_bytes()
+                .to_vec(),
+            "\u{1b}_Gi=33;EBADPNG:not a PNG image\u{1b}\\"
+                .as_bytes()
+                .to_vec(),
+            "\u{1b}_Gi=34;EINVAL:only direct transmission is supported\u{1b}\\"
+                .as_bytes()
+                .to_vec(),
+        ],
+    );
+    assert!(
+        !raw_vte_output(&mut grid).contains("\u{1b}_G"),
+        "queried images are not stored"
+    );
+}
+
+#[test]
+pub fn kitty_graphics_placements_are_clipped_to_the_floating_panes_covering_them() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    // a 16x32 pixel image, which covers 2x2 cells on the second line
+    feed_with_kitty_graphics(&mut grid, "\n\r\u{1b}_Ga=T,f=24,s=16,v=32;AAAA\u{1b}\\");
+    let _ = raw_vte_output(&mut grid);
+    let floating_pane = |x: usize, y: usize, cols: usize, rows: usize| PaneGeom {
+        x,
+        y,
+        cols: Dimension::fixed(cols),
+        rows: Dimension::fixed(rows),
+        ..Default::default()
+    };
+    grid.set_covered_areas(vec![floating_pane(1, 0, 5, 10)]);
+    let vte_output = raw_vte_output(&mut grid);
+    assert!(
+        vte_output.contains("\u{1b}[2;1H\u{1b}_Ga=p,i="),
+        "image was placed again"
+    );
+    assert!(
+        vte_output.contains(",x=0,y=0,w=8,h=32,c=1,r=2\u{1b}\\"),
+        "only its left column is displayed"
+    );
+    grid.set_covered_areas(vec![floating_pane(0, 0, 5, 2)]);
+    let vte_output = raw_vte_output(&mut grid);
+    assert!(
+        vte_output.contains("\u{1b}[3;1H\u{1b}_Ga=p,i="),
+        "only its bottom row is displayed, below the floating pane"
+    );
+    assert!(vte_output.contains(",x=0,y=16,w=16,h=16,c=2,r=1\u{1b}\\"));
+    grid.set_covered_areas(vec![floating_pane(1, 2, 5, 5)]);
+    let vte_output = raw_vte_output(&mut grid);
+    assert!(
+        vte_output.contains("\u{1b}_Ga=d,d=i,i="),
+        "image covered on its corner was deleted"
+    );
+    assert!(!vte_output.contains("a=p"));
+    grid.set_covered_areas(vec![]);
+    let vte_output = raw_vte_output(&mut grid);
+    assert!(
+        vte_output.contains(",C=1,q=2,c=2,r=2\u{1b}\\"),
+        "image was placed whole once no longer covered"
+    );
+}
+
+#[test]
+pub fn kitty_graphics_images_over_the_storage_quota_of_the_pane_are_dropped() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    let chunk_data = "A".repeat(14 * 1024 * 1024);
+    let mut transmission = format!(
+        "\u{1b}_Ga=t,f=24,s=4096,v=4096,i=7,m=1;{}\u{1b}\\",
+        chunk_data
+    );
+    for _ in 0..4 {
+        transmission.push_str(&format!("\u{1b}_Gm=1;{}\u{1b}\\", chunk_data));
+    }
+    transmission.push_str("\u{1b}_Gm=0;AAAA\u{1b}\\\u{1b}_Ga=p,i=7\u{1b}\\");
+    // only the kitty graphics scanner, the text parser would take long to skip all this data
+    for byte in transmission.as_bytes() {
+        grid.handle_kitty_graphics_byte(*byte);
+    }
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec![
+            "\u{1b}_Gi=7;EFBIG:image is too large\u{1b}\\"
+                .as_bytes()
+                .to_vec(),
+            "\u{1b}_Gi=7;ENOENT:image not found\u{1b}\\"
+                .as_bytes()
+                .to_vec(),
+        ],
+        "the transmission failed and the image was not stored"
+    );
+    assert!(!raw_vte_output(&mut grid).contains("\u{1b}_G"));
+}
+
+fn plain_grid(rows: usize, columns: usize) -> Grid {
+    Grid::new(
+        rows,
+        columns,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+        Style::default(),
+        false,
+        true,
+        true,
+        false,
+    )
+}
+
+#[test]
+pub fn unicode_width_options_change_how_wide_printed_characters_are() {
+    let print = |content: &str, unicode_width: UnicodeWidth| -> Grid {
+        let mut grid = plain_grid(10, 40);
+        grid.set_unicode_width(unicode_width);
+        let mut vte_parser = vte::Parser::new();
+        for byte in content.as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+        grid
+    };
+    let cursor_x_after = |content: &str, unicode_width: UnicodeWidth| -> usize {
+        print(content, unicode_width).cursor.x
+    };
+    let ambiguous_wide = UnicodeWidth {
+        ambiguous_is_wide: true,
+        grapheme_clusters: false,
+    };
+    let grapheme_clusters = UnicodeWidth {
+        ambiguous_is_wide: false,
+        grapheme_clusters: true,
+    };
+    let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+    let heart = "\u{2764}\u{FE0F}";
+    let thumbs_up = "\u{1F44D}\u{1F3FD}";
+    let woman_running = "\u{1F3C3}\u{200D}\u{2640}\u{FE0F}";
+
+    assert_eq!(cursor_x_after("αb", UnicodeWidth::default()), 2);
+    assert_eq!(cursor_x_after("αb", ambiguous_wide), 3);
+    assert_eq!(
+        cursor_x_after(&format!("{family}x"), UnicodeWidth::default()),
+        7
+    );
+    assert_eq!(cursor_x_after(&format!("{family}x"), grapheme_clusters), 3);
+    assert_eq!(
+        cursor_x_after(&format!("{heart}x"), UnicodeWidth::default()),
+        2
+    );
+    assert_eq!(cursor_x_after(&format!("{heart}x"), grapheme_clusters), 3);
+    assert_eq!(
+        cursor_x_after(&format!("{thumbs_up}x"), UnicodeWidth::default()),
+        5
+    );
+    assert_eq!(
+        cursor_x_after(&format!("{thumbs_up}x"), grapheme_clusters),
+        3
+    );
+    assert_eq!(
+        cursor_x_after(&format!("{woman_running}x"), grapheme_clusters),
+        3
+    );
+
+    // the whole sequences are kept with their first character and rendered, for the terminal to
+    // display them
+    for emoji in [family, heart, thumbs_up, woman_running] {
+        let mut grid = print(&format!("{emoji}x"), grapheme_clusters);
+        assert_eq!(grid.viewport[0].columns[0].grapheme_cluster(), emoji);
+        assert_eq!(grid.viewport[0].columns[1].character, 'x');
+        assert!(raw_vte_output(&mut grid).contains(&format!("{emoji}x")));
+    }
+
+    // a zero width joiner followed by a character that is not an emoji does not join it
+    let grid = print("\u{1F468}\u{200D}xy", grapheme_clusters);
+    assert_eq!(grid.cursor.x, 4);
+    assert_eq!(grid.viewport[0].columns[1].character, 'x');
+    assert_eq!(grid.viewport[0].columns[2].character, 'y');
+}
+
+fn grid_with_lines(grid: &mut Grid, line_count: usize) {
+    let mut vte_parser = vte::Parser::new();
+    for i in 0..line_count {
+        for byte in format!("line {}\n\r", i).as_bytes() {
+            vte_parser.advance(grid, *byte);
+        }
+    }
+}
+
+#[test]
+pub fn pane_scrollback_size_overrides_the_scroll_buffer_size() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    grid.set_scrollback_size(ScrollbackSize::Lines(5)).unwrap();
+    grid_with_lines(&mut grid, 20);
+    assert_eq!(grid.scrollback_position_and_length(), (0, 5));
+    assert!(grid.dump_screen(true).starts_with("line 6\nline 7\n"));
+}
+
+#[test]
+pub fn unlimited_scrollback_is_paged_back_in_from_the_disk() {
+    let mut grid = grid_with_kitty_graphics(10, 20);
+    grid.set_scrollback_size(ScrollbackSize::Unlimited).unwrap();
+    grid_with_lines(&mut grid, 12_000);
+    assert!(
+        grid.lines_above.len() < 11_991,
+        "the oldest lines were moved to the disk"
+    );
+    assert_eq!(
+        grid.scrollback_position_and_length(),
+        (0, 11_991),
+        "lines on disk are part of the scrollback"
+    );
+    assert!(grid.dump_screen(true).starts_with("line 0\nline 1\n"));
+    grid.move_viewport_up(11_991);
+    assert!(grid.dump_screen(false).starts_with("line 0\nline 1\n"));
+    assert_eq!(grid.scrollback_position_and_length(), (11_991, 11_991));
+}
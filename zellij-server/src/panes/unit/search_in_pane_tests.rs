@@ -344,6 +344,27 @@ pub fn searching_across_line_wrap() {
     );
 }
 
+#[test]
+pub fn searching_with_a_regex() {
+    let mut terminal_pane = create_pane();
+    terminal_pane.update_search_term("tort[o]r");
+    assert!(terminal_pane.grid.search_results.selections.is_empty());
+
+    terminal_pane.toggle_search_regex();
+    assert_snapshot!(
+        "grid_copy_tortor_highlighted",
+        format!("{:?}", terminal_pane.grid)
+    );
+    terminal_pane.search_up();
+    assert_snapshot!(
+        "grid_copy_search_cursor_at_bottom",
+        format!("{:?}", terminal_pane.grid)
+    );
+
+    terminal_pane.toggle_search_regex();
+    assert!(terminal_pane.grid.search_results.selections.is_empty());
+}
+
 #[test]
 pub fn searching_whole_word() {
     let mut terminal_pane = create_pane();
@@ -43,6 +43,7 @@ fn contains() {
         start: Position::new(10, 5),
         end: Position::new(40, 20),
         active: false,
+        block: false,
     };
 
     let test_cases = vec![
@@ -93,6 +94,7 @@ fn sorted() {
         start: Position::new(1, 1),
         end: Position::new(10, 2),
         active: false,
+        block: false,
     };
     let sorted_selection = selection.sorted();
     assert_eq!(selection.start, sorted_selection.start);
@@ -102,6 +104,7 @@ fn sorted() {
         start: Position::new(10, 2),
         end: Position::new(1, 1),
         active: false,
+        block: false,
     };
     let sorted_selection = selection.sorted();
     assert_eq!(selection.end, sorted_selection.start);
@@ -114,6 +117,7 @@ fn line_indices() {
         start: Position::new(1, 1),
         end: Position::new(10, 2),
         active: false,
+        block: false,
     };
 
     assert_eq!(selection.line_indices(), (1..=10))
@@ -127,6 +131,7 @@ fn move_up_inactive() {
         start,
         end,
         active: false,
+        block: false,
     };
 
     inactive_selection.move_up(2);
@@ -145,6 +150,7 @@ fn move_up_active() {
         start,
         end,
         active: true,
+        block: false,
     };
 
     inactive_selection.move_up(2);
@@ -160,6 +166,7 @@ fn move_down_inactive() {
         start,
         end,
         active: false,
+        block: false,
     };
 
     inactive_selection.move_down(2);
@@ -178,9 +185,25 @@ fn move_down_active() {
         start,
         end,
         active: true,
+        block: false,
     };
 
     inactive_selection.move_down(2);
     assert_eq!(inactive_selection.start, Position::new(12, 1));
     assert_eq!(inactive_selection.end, end);
 }
+
+#[test]
+fn block_contains_only_its_columns() {
+    let mut selection = Selection::default();
+    selection.set_block_start_and_end_positions(Position::new(10, 5), Position::new(12, 8));
+
+    assert!(selection.contains(10, 5));
+    assert!(selection.contains(11, 7));
+    assert!(!selection.contains(11, 8), "end column is exclusive");
+    assert!(
+        !selection.contains(11, 2),
+        "text between the lines is not selected"
+    );
+    assert!(!selection.contains(13, 6));
+}
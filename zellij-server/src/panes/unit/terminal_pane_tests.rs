@@ -1,13 +1,15 @@
 use super::super::TerminalPane;
 use crate::panes::sixel::SixelImageStore;
-use crate::panes::LinkHandler;
-use crate::tab::Pane;
+use crate::panes::{LinkHandler, PaneId};
+use crate::tab::{AdjustedInput, CloseTarget, Pane};
 use ::insta::assert_snapshot;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 use zellij_utils::{
-    data::{Palette, Style},
+    data::{BareKey, KeyWithModifier, Palette, Style},
+    input::command::RunCommand,
     pane_size::{Offset, PaneGeom, SizeInPixels},
     position::Position,
 };
@@ -764,3 +766,212 @@ pub fn frameless_pane_position_is_on_frame() {
     assert!(!terminal_pane.position_is_on_frame(&Position::new(30, 130)));
     assert!(!terminal_pane.position_is_on_frame(&Position::new(30, 131)));
 }
+
+#[test]
+pub fn pane_output_is_logged_until_logging_stops() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let pid = 1;
+    let style = Style::default();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let character_cell_size = Rc::new(RefCell::new(Some(SizeInPixels {
+        width: 8,
+        height: 21,
+    })));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut terminal_pane = TerminalPane::new(
+        pid,
+        fake_win_size,
+        style,
+        0,
+        String::new(),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        character_cell_size,
+        sixel_image_store,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        None,
+        None,
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    ); // 0 is the pane index
+
+    let log_folder = zellij_utils::tempfile::tempdir().unwrap();
+    terminal_pane.handle_pty_bytes("before\n\r".as_bytes().to_vec());
+    let log_path = terminal_pane
+        .start_output_log(log_folder.path())
+        .unwrap()
+        .unwrap();
+    terminal_pane.handle_pty_bytes("\u{1b}[31mlogged\u{1b}[m\n\r".as_bytes().to_vec());
+    assert_eq!(terminal_pane.stop_output_log(), Some(log_path.clone()));
+    terminal_pane.handle_pty_bytes("after\n\r".as_bytes().to_vec());
+    assert!(log_path.starts_with(log_folder.path()));
+    assert_eq!(
+        std::fs::read_to_string(&log_path).unwrap(),
+        "\u{1b}[31mlogged\u{1b}[m\n\r",
+        "only the output while logging is written, as is"
+    );
+    assert_eq!(terminal_pane.stop_output_log(), None, "no longer logging");
+}
+
+#[test]
+pub fn command_history_picker_reruns_a_previous_command_of_the_pane() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let pid = 1;
+    let style = Style::default();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut terminal_pane = TerminalPane::new(
+        pid,
+        fake_win_size,
+        style,
+        0,
+        String::new(),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        None,
+        None,
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    ); // 0 is the pane index
+    let run_command = |args: &[&str]| RunCommand {
+        command: PathBuf::from("cargo"),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        ..Default::default()
+    };
+    let key = |bare_key| Some(KeyWithModifier::new(bare_key));
+
+    assert!(
+        !terminal_pane.open_command_history(),
+        "nothing ran in the pane yet"
+    );
+    terminal_pane.hold(Some(0), false, run_command(&["build"]));
+    terminal_pane.hold(Some(1), false, run_command(&["test"]));
+    terminal_pane.handle_pty_bytes("test output\n\r".as_bytes().to_vec());
+    assert!(terminal_pane.open_command_history());
+    assert!(format!("{:?}", terminal_pane.grid).contains("> cargo test"));
+
+    terminal_pane.adjust_input_to_terminal(&key(BareKey::Down), vec![], false, None);
+    assert!(format!("{:?}", terminal_pane.grid).contains("> cargo build"));
+    terminal_pane.adjust_input_to_terminal(&key(BareKey::Esc), vec![], false, None);
+    assert!(
+        format!("{:?}", terminal_pane.grid).contains("test output"),
+        "closing the picker brings back the output of the command"
+    );
+    assert!(
+        terminal_pane.is_held(),
+        "closing the picker does not run anything"
+    );
+
+    terminal_pane.open_command_history();
+    terminal_pane.adjust_input_to_terminal(&key(BareKey::Char('e')), vec![], false, None);
+    for character in " --release".chars() {
+        terminal_pane.adjust_input_to_terminal(&key(BareKey::Char(character)), vec![], false, None);
+    }
+    match terminal_pane.adjust_input_to_terminal(&key(BareKey::Enter), vec![], false, None) {
+        Some(AdjustedInput::ReRunCommandInThisPane(command)) => {
+            assert_eq!(command, run_command(&["test", "--release"]))
+        },
+        other => panic!("expected the edited command to run, got: {:?}", other),
+    }
+    assert!(!terminal_pane.is_held());
+}
+
+#[test]
+pub fn close_confirmation_holds_input_until_the_user_answers() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let pid = 1;
+    let style = Style::default();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut terminal_pane = TerminalPane::new(
+        pid,
+        fake_win_size,
+        style,
+        0,
+        String::new(),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        None,
+        None,
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+    ); // 0 is the pane index
+    let key = |bare_key| Some(KeyWithModifier::new(bare_key));
+    let close_target = CloseTarget::Pane(PaneId::Terminal(pid));
+
+    assert!(terminal_pane.ask_to_confirm_close(close_target, vec!["vim".to_owned()]));
+    assert!(
+        terminal_pane
+            .adjust_input_to_terminal(&key(BareKey::Char('x')), b"x".to_vec(), false, None)
+            .is_none(),
+        "other keys are not written to the pane while it asks"
+    );
+    match terminal_pane.adjust_input_to_terminal(
+        &key(BareKey::Char('n')),
+        b"n".to_vec(),
+        false,
+        None,
+    ) {
+        Some(AdjustedInput::WriteBytesToTerminal(bytes)) => assert_eq!(bytes, b"x".to_vec()),
+        other => panic!(
+            "expected the held keys to reach the pane once cancelled, got: {:?}",
+            other
+        ),
+    }
+    match terminal_pane.adjust_input_to_terminal(
+        &key(BareKey::Char('y')),
+        b"y".to_vec(),
+        false,
+        None,
+    ) {
+        Some(AdjustedInput::WriteBytesToTerminal(bytes)) => assert_eq!(bytes, b"y".to_vec()),
+        other => panic!(
+            "expected the key to reach the pane once cancelled, got: {:?}",
+            other
+        ),
+    }
+
+    terminal_pane.ask_to_confirm_close(close_target, vec!["vim".to_owned()]);
+    match terminal_pane.adjust_input_to_terminal(&None, vec![13], false, None) {
+        Some(AdjustedInput::ConfirmClose(confirmed_target)) => {
+            assert_eq!(confirmed_target, close_target)
+        },
+        other => panic!("expected the close to be confirmed, got: {:?}", other),
+    }
+}
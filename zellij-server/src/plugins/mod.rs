@@ -108,7 +108,7 @@ pub enum PluginInstruction {
         Option<PathBuf>,
     ),
     DumpLayout(SessionLayoutMetadata, ClientId),
-    ListClientsMetadata(SessionLayoutMetadata, ClientId),
+    ListClientsMetadata(SessionLayoutMetadata, bool, ClientId), // bool - as json
     DumpLayoutToPlugin(SessionLayoutMetadata, PluginId),
     LogLayoutToHd(SessionLayoutMetadata),
     CliPipe {
@@ -158,6 +158,8 @@ pub enum PluginInstruction {
         file_path: Option<PathBuf>,
     },
     WatchFilesystem,
+    WatchPaths(PluginId, ClientId, PathBuf, Vec<PathBuf>), // PathBuf -> plugin host folder,
+    // Vec<PathBuf> -> paths or glob patterns relative to it
     ListClientsToPlugin(SessionLayoutMetadata, PluginId, ClientId),
     ChangePluginHostDir(PathBuf, PluginId, ClientId),
     Exit,
@@ -199,6 +201,7 @@ impl From<&PluginInstruction> for PluginContext {
             PluginInstruction::MessageFromPlugin { .. } => PluginContext::MessageFromPlugin,
             PluginInstruction::UnblockCliPipes { .. } => PluginContext::UnblockCliPipes,
             PluginInstruction::WatchFilesystem => PluginContext::WatchFilesystem,
+            PluginInstruction::WatchPaths(..) => PluginContext::WatchPaths,
             PluginInstruction::KeybindPipe { .. } => PluginContext::KeybindPipe,
             PluginInstruction::DumpLayoutToPlugin(..) => PluginContext::DumpLayoutToPlugin,
             PluginInstruction::Reconfigure { .. } => PluginContext::Reconfigure,
@@ -324,6 +327,15 @@ pub(crate) fn plugin_thread_main(
                 );
             },
             PluginInstruction::Update(updates) => {
+                // mode changes are also published to clients following the session's events
+                // (eg. `zellij events`), tab and pane updates are published by the screen
+                for (plugin_id, _client_id, event) in &updates {
+                    if plugin_id.is_none() && matches!(event, Event::ModeUpdate(..)) {
+                        let _ = bus
+                            .senders
+                            .send_to_server(ServerInstruction::PublishEvent(event.clone()));
+                    }
+                }
                 wasm_bridge.update_plugins(updates, shutdown_send.clone())?;
             },
             PluginInstruction::Unload(pid) => {
@@ -570,7 +582,11 @@ pub(crate) fn plugin_thread_main(
                     client_id,
                 )));
             },
-            PluginInstruction::ListClientsMetadata(mut session_layout_metadata, client_id) => {
+            PluginInstruction::ListClientsMetadata(
+                mut session_layout_metadata,
+                json,
+                client_id,
+            ) => {
                 populate_session_layout_metadata(
                     &mut session_layout_metadata,
                     &wasm_bridge,
@@ -578,6 +594,7 @@ pub(crate) fn plugin_thread_main(
                 );
                 drop(bus.senders.send_to_pty(PtyInstruction::ListClientsMetadata(
                     session_layout_metadata,
+                    json,
                     client_id,
                 )));
             },
@@ -790,6 +807,8 @@ pub(crate) fn plugin_thread_main(
                     .new_plugin_args
                     .as_ref()
                     .and_then(|n| n.pane_id_to_replace);
+                let request_id = message.request_id.clone();
+                let in_reply_to = message.in_reply_to.clone();
                 match (message.plugin_url, message.destination_plugin_id) {
                     (Some(plugin_url), None) => {
                         // send to specific plugin(s)
@@ -853,6 +872,16 @@ pub(crate) fn plugin_thread_main(
                         );
                     },
                 }
+                let pipe_messages = pipe_messages
+                    .into_iter()
+                    .map(|(plugin_id, client_id, pipe_message)| {
+                        (
+                            plugin_id,
+                            client_id,
+                            pipe_message.with_correlation(request_id.clone(), in_reply_to.clone()),
+                        )
+                    })
+                    .collect();
                 wasm_bridge.pipe_messages(pipe_messages, shutdown_send.clone())?;
             },
             PluginInstruction::UnblockCliPipes(pipes_to_unblock) => {
@@ -896,6 +925,9 @@ pub(crate) fn plugin_thread_main(
             PluginInstruction::WatchFilesystem => {
                 wasm_bridge.start_fs_watcher_if_not_started();
             },
+            PluginInstruction::WatchPaths(plugin_id, client_id, plugin_host_folder, paths) => {
+                wasm_bridge.watch_paths(plugin_id, client_id, plugin_host_folder, paths);
+            },
             PluginInstruction::ChangePluginHostDir(new_host_folder, plugin_id, client_id) => {
                 wasm_bridge
                     .change_plugin_host_dir(new_host_folder, plugin_id, client_id)
@@ -866,6 +866,7 @@ impl<'a> PluginLoader<'a> {
             layout_dir: self.layout_dir.clone(),
             default_mode: self.default_mode.clone(),
             subscriptions: Arc::new(Mutex::new(HashSet::new())),
+            timers: Arc::new(Mutex::new(HashMap::new())),
             keybinds: self.keybinds.clone(),
             stdin_pipe,
             stdout_pipe,
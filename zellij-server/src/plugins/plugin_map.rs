@@ -307,6 +307,8 @@ pub struct PluginEnv {
     pub input_pipes_to_block: Arc<Mutex<HashSet<String>>>,
     pub default_mode: InputMode,
     pub subscriptions: Arc<Mutex<Subscriptions>>,
+    pub timers: Arc<Mutex<HashMap<String, u64>>>, // timer name -> id of the timer currently set
+    // under it
     pub stdin_pipe: Arc<Mutex<VecDeque<u8>>>,
     pub stdout_pipe: Arc<Mutex<VecDeque<u8>>>,
     pub keybinds: Keybinds,
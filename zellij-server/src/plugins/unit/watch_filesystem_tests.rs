@@ -0,0 +1,68 @@
+use super::*;
+
+#[test]
+fn paths_match_a_watched_path_without_wildcards() {
+    let pattern = Path::new("src/plugins");
+    assert!(path_matches_pattern(Path::new("src/plugins"), pattern));
+    assert!(path_matches_pattern(
+        Path::new("src/plugins/watch_filesystem.rs"),
+        pattern
+    ));
+    assert!(!path_matches_pattern(
+        Path::new("src/plugins_old/mod.rs"),
+        pattern
+    ));
+    assert!(!path_matches_pattern(Path::new("src/lib.rs"), pattern));
+}
+
+#[test]
+fn paths_match_watched_glob_patterns() {
+    assert!(path_matches_pattern(
+        Path::new("src/lib.rs"),
+        Path::new("src/*.rs")
+    ));
+    assert!(!path_matches_pattern(
+        Path::new("src/plugins/mod.rs"),
+        Path::new("src/*.rs")
+    ));
+    assert!(path_matches_pattern(
+        Path::new("src/plugins/unit/plugin_tests.rs"),
+        Path::new("src/**/*.rs")
+    ));
+    assert!(path_matches_pattern(
+        Path::new("src/lib.rs"),
+        Path::new("src/**/*.rs")
+    ));
+    assert!(!path_matches_pattern(
+        Path::new("src/plugins/mod.toml"),
+        Path::new("src/**/*.rs")
+    ));
+    assert!(path_matches_pattern(
+        Path::new("target/debug/zellij"),
+        Path::new("target/debu?/zellij")
+    ));
+}
+
+#[test]
+fn paths_that_do_not_exist_are_watched_from_their_closest_existing_folder() {
+    let host_folder = tempfile::tempdir().unwrap();
+    let host_folder = host_folder.path();
+    std::fs::create_dir_all(host_folder.join("src/plugins")).unwrap();
+    std::fs::write(host_folder.join("src/lib.rs"), "").unwrap();
+    assert_eq!(
+        folder_to_watch(host_folder, Path::new("src/lib.rs")),
+        host_folder.join("src/lib.rs")
+    );
+    assert_eq!(
+        folder_to_watch(host_folder, Path::new("src/plugins/not_yet_created.rs")),
+        host_folder.join("src/plugins")
+    );
+    assert_eq!(
+        folder_to_watch(host_folder, Path::new("not_yet_created/mod.rs")),
+        host_folder.to_path_buf()
+    );
+    assert_eq!(
+        folder_to_watch(host_folder, Path::new("src/**/*.rs")),
+        host_folder.join("src")
+    );
+}
@@ -6,7 +6,7 @@ use crate::plugins::plugin_loader::PluginLoader;
 use crate::plugins::plugin_map::{AtomicEvent, PluginEnv, PluginMap, RunningPlugin, Subscriptions};
 
 use crate::plugins::plugin_worker::MessageToWorker;
-use crate::plugins::watch_filesystem::watch_filesystem;
+use crate::plugins::watch_filesystem::{watch_filesystem, watch_paths};
 use crate::plugins::zellij_exports::{wasi_read_string, wasi_write_object};
 use highway::{HighwayHash, PortableHash};
 use log::info;
@@ -95,6 +95,7 @@ pub struct WasmBridge {
     pending_plugin_reloads: HashSet<RunPlugin>,
     path_to_default_shell: PathBuf,
     watcher: Option<Debouncer<RecommendedWatcher, FileIdMap>>,
+    path_watchers: HashMap<(PluginId, ClientId), Debouncer<RecommendedWatcher, FileIdMap>>,
     zellij_cwd: PathBuf,
     capabilities: PluginCapabilities,
     client_attributes: ClientAttributes,
@@ -141,6 +142,7 @@ impl WasmBridge {
             plugin_map,
             path_to_default_shell,
             watcher,
+            path_watchers: HashMap::new(),
             next_plugin_id: 0,
             cached_events_for_pending_plugins: HashMap::new(),
             plugin_ids_waiting_for_permission_request: HashSet::new(),
@@ -329,6 +331,8 @@ impl WasmBridge {
             }
         }
         self.cached_plugin_map.clear();
+        self.path_watchers
+            .retain(|(plugin_id, _client_id), _watcher| *plugin_id != pid);
         let mut pipes_to_unblock = self.pending_pipes.unload_plugin(&pid);
         for pipe_name in pipes_to_unblock.drain(..) {
             let _ = self
@@ -990,6 +994,9 @@ impl WasmBridge {
         if let Some(watcher) = self.watcher.take() {
             watcher.stop_nonblocking();
         }
+        for (_plugin, watcher) in self.path_watchers.drain() {
+            watcher.stop_nonblocking();
+        }
     }
     pub fn run_plugin_of_loading_plugin_id(&self, plugin_id: PluginId) -> Option<&RunPlugin> {
         self.loading_plugins
@@ -1312,6 +1319,35 @@ impl WasmBridge {
             };
         }
     }
+    pub fn watch_paths(
+        &mut self,
+        plugin_id: PluginId,
+        client_id: ClientId,
+        plugin_host_folder: PathBuf,
+        paths: Vec<PathBuf>,
+    ) {
+        // the new paths replace the ones the plugin watched so far
+        if let Some(watcher) = self.path_watchers.remove(&(plugin_id, client_id)) {
+            watcher.stop_nonblocking();
+        }
+        if paths.is_empty() {
+            return;
+        }
+        match watch_paths(
+            self.senders.clone(),
+            plugin_id,
+            client_id,
+            &plugin_host_folder,
+            paths,
+        ) {
+            Ok(watcher) => {
+                self.path_watchers.insert((plugin_id, client_id), watcher);
+            },
+            Err(e) => {
+                log::error!("Failed to watch paths for plugin {}: {:?}", plugin_id, e);
+            },
+        }
+    }
     pub fn cache_plugin_permissions(
         &mut self,
         plugin_id: PluginId,
@@ -1542,6 +1578,7 @@ fn check_event_permission(
         | Event::EditPaneExited(..)
         | Event::FailedToWriteConfigToDisk(..)
         | Event::CommandPaneReRun(..)
+        | Event::CommandPaneFinished(..)
         | Event::InputReceived => PermissionType::ReadApplicationState,
         _ => return (PermissionStatus::Granted, None),
     };
@@ -1,7 +1,8 @@
-use super::PluginInstruction;
+use super::{PluginId, PluginInstruction};
 use std::path::PathBuf;
 
 use crate::thread_bus::ThreadSenders;
+use crate::ClientId;
 use std::path::Path;
 use std::time::Duration;
 
@@ -133,3 +134,156 @@ pub fn watch_filesystem(
         .watch(zellij_cwd, RecursiveMode::Recursive)?;
     Ok(debouncer)
 }
+
+/// Watches paths or glob patterns relative to the host folder of a plugin, sending only the
+/// changes to the paths that match them and only to that plugin
+pub fn watch_paths(
+    senders: ThreadSenders,
+    plugin_id: PluginId,
+    client_id: ClientId,
+    plugin_host_folder: &Path,
+    patterns: Vec<PathBuf>,
+) -> Result<Debouncer<RecommendedWatcher, FileIdMap>> {
+    let path_prefix_in_plugins = PathBuf::from("/host");
+    let host_folder = PathBuf::from(plugin_host_folder);
+    let watched_patterns = patterns.clone();
+    let mut debouncer = new_debouncer(
+        Duration::from_millis(DEBOUNCE_DURATION_MS),
+        None,
+        move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                let mut create_paths = vec![];
+                let mut update_paths = vec![];
+                let mut delete_paths = vec![];
+                for event in events {
+                    let changed_paths = match event.kind {
+                        EventKind::Create(_) => &mut create_paths,
+                        EventKind::Modify(_) => &mut update_paths,
+                        EventKind::Remove(_) => &mut delete_paths,
+                        _ => continue,
+                    };
+                    for path in &event.paths {
+                        let Ok(path_in_host_folder) = path.strip_prefix(&host_folder) else {
+                            continue;
+                        };
+                        let matches_pattern = watched_patterns
+                            .iter()
+                            .any(|pattern| path_matches_pattern(path_in_host_folder, pattern));
+                        let path_in_plugin =
+                            (path_prefix_in_plugins.join(path_in_host_folder), None);
+                        if matches_pattern && !changed_paths.contains(&path_in_plugin) {
+                            changed_paths.push(path_in_plugin);
+                        }
+                    }
+                }
+                let mut updates = vec![];
+                if !create_paths.is_empty() {
+                    updates.push((
+                        Some(plugin_id),
+                        Some(client_id),
+                        Event::FileSystemCreate(create_paths),
+                    ));
+                }
+                if !update_paths.is_empty() {
+                    updates.push((
+                        Some(plugin_id),
+                        Some(client_id),
+                        Event::FileSystemUpdate(update_paths),
+                    ));
+                }
+                if !delete_paths.is_empty() {
+                    updates.push((
+                        Some(plugin_id),
+                        Some(client_id),
+                        Event::FileSystemDelete(delete_paths),
+                    ));
+                }
+                if !updates.is_empty() {
+                    let _ = senders.send_to_plugin(PluginInstruction::Update(updates));
+                }
+            },
+            Err(errors) => errors
+                .iter()
+                .for_each(|error| log::error!("watch error: {error:?}")),
+        },
+    )?;
+
+    for pattern in &patterns {
+        let folder_to_watch = folder_to_watch(plugin_host_folder, pattern);
+        if let Err(e) = debouncer
+            .watcher()
+            .watch(&folder_to_watch, RecursiveMode::Recursive)
+        {
+            log::error!("Failed to watch {}: {:?}", folder_to_watch.display(), e);
+        }
+    }
+    Ok(debouncer)
+}
+
+// a glob is watched from the folder its first wildcard is in, and a path that does not exist (yet)
+// from its closest existing parent folder so that we see it being created
+fn folder_to_watch(plugin_host_folder: &Path, pattern: &Path) -> PathBuf {
+    let folder_to_watch: PathBuf = pattern
+        .components()
+        .take_while(|c| !is_glob(&c.as_os_str().to_string_lossy()))
+        .collect();
+    plugin_host_folder
+        .join(folder_to_watch)
+        .ancestors()
+        .find(|path| path.exists())
+        .unwrap_or(plugin_host_folder)
+        .to_path_buf()
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains('*') || pattern.contains('?')
+}
+
+// a path matches a pattern without wildcards if it is that path or inside it, in a pattern `*`
+// and `?` match within a path component and `**` matches any number of components
+fn path_matches_pattern(path: &Path, pattern: &Path) -> bool {
+    if !is_glob(&pattern.to_string_lossy()) {
+        return path.starts_with(pattern);
+    }
+    let path: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let pattern: Vec<String> = pattern
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    components_match(&pattern, &path)
+}
+
+fn components_match(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((component_pattern, pattern)) if component_pattern == "**" => {
+            (0..=path.len()).any(|skipped| components_match(pattern, &path[skipped..]))
+        },
+        Some((component_pattern, pattern)) => match path.split_first() {
+            Some((component, path)) => {
+                let component_pattern: Vec<char> = component_pattern.chars().collect();
+                let component: Vec<char> = component.chars().collect();
+                wildcards_match(&component_pattern, &component) && components_match(pattern, path)
+            },
+            None => false,
+        },
+    }
+}
+
+fn wildcards_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', pattern)) => {
+            (0..=name.len()).any(|skipped| wildcards_match(pattern, &name[skipped..]))
+        },
+        Some(('?', pattern)) => !name.is_empty() && wildcards_match(pattern, &name[1..]),
+        Some((c, pattern)) => name.first() == Some(c) && wildcards_match(pattern, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/watch_filesystem_tests.rs"]
+mod watch_filesystem_tests;
@@ -13,6 +13,10 @@ use std::{
     path::PathBuf,
     process,
     str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     thread,
     time::{Duration, Instant},
 };
@@ -43,7 +47,7 @@ use zellij_utils::{
         layout::{Layout, RunPluginOrAlias},
     },
     plugin_api::{
-        plugin_command::ProtobufPluginCommand,
+        plugin_command::{ProtobufPluginCommand, ProtobufWatchPathsResult},
         plugin_ids::{ProtobufPluginIds, ProtobufZellijVersion},
     },
     prost::Message,
@@ -143,6 +147,10 @@ fn host_run_plugin_command(caller: Caller<'_, PluginEnv>) {
                     ),
                     PluginCommand::SwitchTabTo(tab_index) => switch_tab_to(env, tab_index),
                     PluginCommand::SetTimeout(seconds) => set_timeout(env, seconds),
+                    PluginCommand::SetTimer(name, seconds, repeat) => {
+                        set_timer(env, name, seconds, repeat)
+                    },
+                    PluginCommand::CancelTimer(name) => cancel_timer(env, name),
                     PluginCommand::ExecCmd(command_line) => exec_cmd(env, command_line),
                     PluginCommand::RunCommand(command_line, env_variables, cwd, context) => {
                         run_command(env, command_line, env_variables, cwd, context)
@@ -295,6 +303,7 @@ fn host_run_plugin_command(caller: Caller<'_, PluginEnv>) {
                         scan_host_folder(env, folder_to_scan)
                     },
                     PluginCommand::WatchFilesystem => watch_filesystem(env),
+                    PluginCommand::WatchPaths(paths) => watch_paths(env, paths),
                     PluginCommand::DumpSessionLayout => dump_session_layout(env),
                     PluginCommand::CloseSelf => close_self(env),
                     PluginCommand::Reconfigure(new_config, write_config_to_disk) => {
@@ -892,11 +901,17 @@ fn open_command_pane_in_place_of_plugin(
         direction,
         hold_on_close,
         hold_on_start,
+        close_on_success: false,
         originating_plugin: Some(OriginatingPlugin::new(
             env.plugin_id,
             env.client_id,
             context,
         )),
+        capture_output: None,
+        stdin_file: None,
+        stdin_pipe: None,
+        log_output: None,
+        watch: None,
     };
     let run_cmd = TerminalAction::RunCommand(run_command_action.into());
     let _ = env
@@ -929,11 +944,17 @@ fn open_command_pane(
         direction,
         hold_on_close,
         hold_on_start,
+        close_on_success: false,
         originating_plugin: Some(OriginatingPlugin::new(
             env.plugin_id,
             env.client_id,
             context,
         )),
+        capture_output: None,
+        stdin_file: None,
+        stdin_pipe: None,
+        log_output: None,
+        watch: None,
     };
     let action = Action::NewTiledPane(direction, Some(run_command_action), name);
     apply_action!(action, error_msg, env);
@@ -959,11 +980,17 @@ fn open_command_pane_near_plugin(
         direction,
         hold_on_close,
         hold_on_start,
+        close_on_success: false,
         originating_plugin: Some(OriginatingPlugin::new(
             env.plugin_id,
             env.client_id,
             context,
         )),
+        capture_output: None,
+        stdin_file: None,
+        stdin_pipe: None,
+        log_output: None,
+        watch: None,
     };
     let run_cmd = TerminalAction::RunCommand(run_command_action.into());
     let _ = env.senders.send_to_pty(PtyInstruction::SpawnTerminal(
@@ -997,11 +1024,17 @@ fn open_command_pane_floating(
         direction,
         hold_on_close,
         hold_on_start,
+        close_on_success: false,
         originating_plugin: Some(OriginatingPlugin::new(
             env.plugin_id,
             env.client_id,
             context,
         )),
+        capture_output: None,
+        stdin_file: None,
+        stdin_pipe: None,
+        log_output: None,
+        watch: None,
     };
     let action = Action::NewFloatingPane(Some(run_command_action), name, floating_pane_coordinates);
     apply_action!(action, error_msg, env);
@@ -1028,11 +1061,17 @@ fn open_command_pane_floating_near_plugin(
         direction,
         hold_on_close,
         hold_on_start,
+        close_on_success: false,
         originating_plugin: Some(OriginatingPlugin::new(
             env.plugin_id,
             env.client_id,
             context,
         )),
+        capture_output: None,
+        stdin_file: None,
+        stdin_pipe: None,
+        log_output: None,
+        watch: None,
     };
     let run_cmd = TerminalAction::RunCommand(run_command_action.into());
     let _ = env.senders.send_to_pty(PtyInstruction::SpawnTerminal(
@@ -1065,11 +1104,17 @@ fn open_command_pane_in_place(
         direction,
         hold_on_close,
         hold_on_start,
+        close_on_success: false,
         originating_plugin: Some(OriginatingPlugin::new(
             env.plugin_id,
             env.client_id,
             context,
         )),
+        capture_output: None,
+        stdin_file: None,
+        stdin_pipe: None,
+        log_output: None,
+        watch: None,
     };
     let action = Action::NewInPlacePane(Some(run_command_action), name);
     apply_action!(action, error_msg, env);
@@ -1098,11 +1143,17 @@ fn open_command_pane_background(
         direction,
         hold_on_close,
         hold_on_start,
+        close_on_success: false,
         originating_plugin: Some(OriginatingPlugin::new(
             env.plugin_id,
             env.client_id,
             context,
         )),
+        capture_output: None,
+        stdin_file: None,
+        stdin_pipe: None,
+        log_output: None,
+        watch: None,
     };
     let run_cmd = TerminalAction::RunCommand(run_command_action.into());
     let _ = env.senders.send_to_pty(PtyInstruction::SpawnTerminal(
@@ -1166,6 +1217,79 @@ fn set_timeout(env: &PluginEnv, secs: f64) {
     });
 }
 
+static NEXT_TIMER_ID: AtomicU64 = AtomicU64::new(0);
+// so that a repeating timer cannot keep the plugin thread busy
+const MIN_TIMER_INTERVAL_SECS: f64 = 0.01;
+
+fn set_timer(env: &PluginEnv, name: String, secs: f64, repeat: bool) {
+    if !secs.is_finite() || secs < 0.0 {
+        log::error!("Invalid duration for timer {}: {}", name, secs);
+        return;
+    }
+    let interval = Duration::from_secs_f64(if repeat {
+        secs.max(MIN_TIMER_INTERVAL_SECS)
+    } else {
+        secs
+    });
+    // setting a timer under a name that is already set replaces it
+    let timer_id = NEXT_TIMER_ID.fetch_add(1, Ordering::SeqCst);
+    env.timers.lock().unwrap().insert(name.clone(), timer_id);
+    // the timer stops with the plugin, when its env (and with it the timers) is dropped
+    let timers = Arc::downgrade(&env.timers);
+    let send_plugin_instructions = env.senders.to_plugin.clone();
+    let update_target = Some(env.plugin_id);
+    let client_id = env.client_id;
+    let plugin_name = env.name();
+    task::spawn(async move {
+        let mut last_expired = Instant::now();
+        let mut next_expiry = last_expired + interval;
+        loop {
+            // sleeping until the next expiry rather than for the interval keeps the cadence of
+            // repeating timers from drifting
+            task::sleep(next_expiry.saturating_duration_since(Instant::now())).await;
+            let Some(timers) = timers.upgrade() else {
+                break;
+            };
+            {
+                let mut timers = timers.lock().unwrap();
+                if timers.get(&name) != Some(&timer_id) {
+                    // cancelled or replaced
+                    break;
+                }
+                if !repeat {
+                    timers.remove(&name);
+                }
+            }
+            let now = Instant::now();
+            let elapsed_time = now.duration_since(last_expired).as_secs_f64();
+            last_expired = now;
+            next_expiry += interval;
+
+            let sent = send_plugin_instructions
+                .as_ref()
+                .ok_or(anyhow!("found no sender to send plugin instruction to"))
+                .and_then(|sender| {
+                    sender
+                        .send(PluginInstruction::Update(vec![(
+                            update_target,
+                            Some(client_id),
+                            Event::NamedTimer(name.clone(), elapsed_time),
+                        )]))
+                        .to_anyhow()
+                })
+                .with_context(|| format!("failed to fire timer {name} for plugin {plugin_name}"));
+            if sent.is_err() || !repeat {
+                sent.non_fatal();
+                break;
+            }
+        }
+    });
+}
+
+fn cancel_timer(env: &PluginEnv, name: String) {
+    env.timers.lock().unwrap().remove(&name);
+}
+
 fn exec_cmd(env: &PluginEnv, mut command_line: Vec<String>) {
     log::warn!("The ExecCmd plugin command is deprecated and will be removed in a future version. Please use RunCmd instead (it has all the things and can even show you STDOUT/STDERR and an exit code!)");
     let err_context = || {
@@ -1828,6 +1952,58 @@ fn watch_filesystem(env: &PluginEnv) {
         .map(|sender| sender.send(PluginInstruction::WatchFilesystem));
 }
 
+fn watch_paths(env: &PluginEnv, paths: Vec<PathBuf>) {
+    let error = match paths_in_host_folder(env, paths) {
+        Ok(paths_in_host_folder) => {
+            let _ = env.senders.send_to_plugin(PluginInstruction::WatchPaths(
+                env.plugin_id,
+                env.client_id,
+                env.plugin_cwd.clone(),
+                paths_in_host_folder,
+            ));
+            None
+        },
+        Err(e) => {
+            log::error!("{}", e);
+            Some(e)
+        },
+    };
+    wasi_write_object(env, &ProtobufWatchPathsResult { error }.encode_to_vec())
+        .with_context(|| format!("failed to send watch_paths result to plugin {}", env.name()))
+        .non_fatal();
+}
+
+// the paths (or glob patterns) relative to the plugin's host folder, none are watched if one of
+// them is outside of it
+fn paths_in_host_folder(env: &PluginEnv, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, String> {
+    let mut paths_in_host_folder = vec![];
+    for path in paths {
+        // relative paths are relative to /host, like the plugin's own working directory
+        let path_in_host_folder = match path.strip_prefix("/host") {
+            Ok(path_in_host_folder) => path_in_host_folder.to_path_buf(),
+            Err(_) if path.is_relative() => path,
+            Err(_) => {
+                return Err(format!(
+                    "Can only watch files in the /host filesystem, found: {}",
+                    path.display()
+                ));
+            },
+        };
+        if path_in_host_folder
+            .components()
+            .any(|c| c == std::path::Component::ParentDir)
+        {
+            return Err(format!(
+                "Can only watch files in the plugin filesystem: {}, found: {}",
+                env.plugin_cwd.display(),
+                path.display()
+            ));
+        }
+        paths_in_host_folder.push(path_in_host_folder);
+    }
+    Ok(paths_in_host_folder)
+}
+
 fn dump_session_layout(env: &PluginEnv) {
     let _ = env
         .senders
@@ -1,6 +1,9 @@
 use crate::background_jobs::BackgroundJob;
+use crate::command_watcher::{watch_command_files, CommandWatcher};
+use crate::pty_writer::PtyWriteInstruction;
 use crate::terminal_bytes::TerminalBytes;
 use crate::{
+    panes::closed_panes::{ClosedPane, ClosedTerminalCwds},
     panes::PaneId,
     plugins::{PluginId, PluginInstruction},
     screen::ScreenInstruction,
@@ -10,22 +13,38 @@ use crate::{
 };
 use async_std::task::{self, JoinHandle};
 use std::sync::Arc;
-use std::{collections::HashMap, os::unix::io::RawFd, path::PathBuf};
-use zellij_utils::nix::unistd::Pid;
+use std::{
+    collections::{BTreeMap, HashMap},
+    os::unix::io::RawFd,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use zellij_utils::nix::unistd::{self, Pid};
 use zellij_utils::{
+    async_channel::{self, Sender},
     async_std,
-    data::{Event, FloatingPaneCoordinates, OriginatingPlugin},
+    consts::ZELLIJ_SOCK_DIR,
+    data::{Direction, Event, FloatingPaneCoordinates, OriginatingPlugin},
+    envs,
     errors::prelude::*,
     errors::{ContextType, PtyContext},
     input::{
-        command::{OpenFilePayload, RunCommand, TerminalAction},
+        command::{CommandWatch, OpenFilePayload, OutputLog, RunCommand, TerminalAction},
         layout::{FloatingPaneLayout, Layout, Run, RunPluginOrAlias, TiledPaneLayout},
+        options::NewPaneCwd,
     },
+    interprocess::local_socket::LocalSocketStream,
+    ipc::{ClientToServerMsg, IpcSenderWithContext, MovedPane, PaneSignal, ServerToClientMsg},
     pane_size::Size,
     session_serialization,
 };
 
 pub type VteBytes = Vec<u8>;
+
+// how long the server of another session has to accept a pane moved to it
+const MOVE_PANE_TIMEOUT_SECS: u64 = 5;
+// how long a stopped command has to exit before it is sent a harsher signal
+const STOP_COMMAND_TIMEOUT_SECS: u64 = 3;
 pub type TabIndex = u32;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -55,7 +74,7 @@ pub enum PtyInstruction {
     SpawnTerminalHorizontally(Option<TerminalAction>, Option<String>, ClientId), // String is an
     // optional pane
     // name
-    UpdateActivePane(Option<PaneId>, ClientId),
+    UpdateActivePane(Option<PaneId>, usize, ClientId), // usize is the index of the client's tab
     GoToTab(TabIndex, ClientId),
     NewTab(
         Option<PathBuf>,
@@ -97,12 +116,27 @@ pub enum PtyInstruction {
         Option<PathBuf>, // if Some, will not fill cwd but just forward the message
         Option<FloatingPaneCoordinates>,
     ),
-    ListClientsMetadata(SessionLayoutMetadata, ClientId),
+    ListClientsMetadata(SessionLayoutMetadata, bool, ClientId), // bool - as json
     Reconfigure {
         client_id: ClientId,
         default_editor: Option<PathBuf>,
+        new_pane_cwd: NewPaneCwd,
     },
     ListClientsToPlugin(SessionLayoutMetadata, PluginId, ClientId),
+    ReportCwd(u32, PathBuf), // terminal_id, the cwd reported by the shell (OSC 7)
+    SignalPanes(PaneSignal),
+    StopCommand(u32, PaneSignal), // terminal_id, so that its watched command can be re-run or
+    // replaced
+    SetSessionEnv(Vec<(String, Option<String>)>), // None removes the variable
+    SetTabCwd(PathBuf, ClientId),                 // the default cwd of the client's tab
+    RestoreClosedPane(Box<ClosedPane>, ClientId),
+    DuplicatePane(u32, Option<TerminalAction>, Option<Direction>, ClientId), // terminal id of the
+    // duplicated pane, the terminal action is None for the default shell
+    MovePaneToSession(u32, String), // terminal id, the session name
+    SendPaneToSession(u32, Box<MovedPane>, String), // terminal id, the pane, the session name
+    PaneMovedToSession(u32),        // terminal id
+    PaneNotMovedToSession(u32, RawFd, String), // terminal id, its pty fd, the error
+    AdoptPane(RawFd, i32, Box<MovedPane>, ClientId), // the pty fd, the pid of its process
     Exit,
 }
 
@@ -128,6 +162,18 @@ impl From<&PtyInstruction> for PtyContext {
             PtyInstruction::ListClientsMetadata(..) => PtyContext::ListClientsMetadata,
             PtyInstruction::Reconfigure { .. } => PtyContext::Reconfigure,
             PtyInstruction::ListClientsToPlugin(..) => PtyContext::ListClientsToPlugin,
+            PtyInstruction::ReportCwd(..) => PtyContext::ReportCwd,
+            PtyInstruction::SignalPanes(..) => PtyContext::SignalPanes,
+            PtyInstruction::StopCommand(..) => PtyContext::StopCommand,
+            PtyInstruction::SetSessionEnv(..) => PtyContext::SetSessionEnv,
+            PtyInstruction::SetTabCwd(..) => PtyContext::SetTabCwd,
+            PtyInstruction::RestoreClosedPane(..) => PtyContext::RestoreClosedPane,
+            PtyInstruction::DuplicatePane(..) => PtyContext::DuplicatePane,
+            PtyInstruction::MovePaneToSession(..) => PtyContext::MovePaneToSession,
+            PtyInstruction::SendPaneToSession(..) => PtyContext::SendPaneToSession,
+            PtyInstruction::PaneMovedToSession(..) => PtyContext::PaneMovedToSession,
+            PtyInstruction::PaneNotMovedToSession(..) => PtyContext::PaneNotMovedToSession,
+            PtyInstruction::AdoptPane(..) => PtyContext::AdoptPane,
             PtyInstruction::Exit => PtyContext::Exit,
         }
     }
@@ -140,7 +186,23 @@ pub(crate) struct Pty {
     originating_plugins: HashMap<u32, OriginatingPlugin>,
     debug_to_file: bool,
     task_handles: HashMap<u32, JoinHandle<()>>, // terminal_id to join-handle
+    stop_reading: HashMap<u32, Sender<()>>,     // terminal_id => stops its task once sent to
+    // the connection to the session a pane is moved to and the pty fd to send it, until screen
+    // serialized the pane
+    panes_moving_to_sessions: HashMap<u32, (IpcSenderWithContext<ClientToServerMsg>, RawFd)>,
     default_editor: Option<PathBuf>,
+    reported_cwds: HashMap<u32, PathBuf>, // terminal_id => cwd reported by the shell (OSC 7)
+    command_watchers: HashMap<u32, CommandWatcher>, // terminal_id => watcher of its command's files
+    active_tabs: HashMap<ClientId, usize>, // client_id => index of its tab
+    tab_cwds: HashMap<usize, PathBuf>,    // tab_index => the cwd the tab was opened in
+    default_tab_cwds: HashMap<usize, PathBuf>, // tab_index => the cwd given to the tab by its
+    // layout or the set-tab-cwd action
+    new_pane_cwd: NewPaneCwd,
+    session_cwd: Option<PathBuf>,
+    closed_terminal_cwds: ClosedTerminalCwds,
+    session_env: BTreeMap<String, Option<String>>, // name => value, None removes the variable
+    terminal_envs: HashMap<u32, BTreeMap<String, Option<String>>>, // terminal_id => the session
+                                                   // env it was spawned with
 }
 
 pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
@@ -525,8 +587,9 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                     },
                 }
             },
-            PtyInstruction::UpdateActivePane(pane_id, client_id) => {
+            PtyInstruction::UpdateActivePane(pane_id, tab_index, client_id) => {
                 pty.set_active_pane(pane_id, client_id);
+                pty.active_tabs.insert(client_id, tab_index);
             },
             PtyInstruction::GoToTab(tab_index, client_id) => {
                 pty.bus
@@ -693,18 +756,18 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                     },
                 }
             },
-            PtyInstruction::ListClientsMetadata(mut session_layout_metadata, client_id) => {
+            PtyInstruction::ListClientsMetadata(mut session_layout_metadata, json, client_id) => {
                 let err_context = || format!("Failed to dump layout");
                 pty.populate_session_layout_metadata(&mut session_layout_metadata);
+                // the server renders these, as only it knows the terminal size of each client
                 pty.bus
                     .senders
-                    .send_to_server(ServerInstruction::Log(
-                        vec![format!(
-                            "{}",
-                            session_layout_metadata.list_clients_metadata()
-                        )],
+                    .send_to_server(ServerInstruction::ClientsMetadata {
                         client_id,
-                    ))
+                        clients_metadata: session_layout_metadata.all_clients_metadata(),
+                        default_editor: session_layout_metadata.default_editor.clone(),
+                        json,
+                    })
                     .with_context(err_context)
                     .non_fatal();
             },
@@ -737,6 +800,226 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                     .with_context(err_context)
                     .non_fatal();
             },
+            PtyInstruction::ReportCwd(terminal_id, cwd) => {
+                if pty.id_to_child_pid.contains_key(&terminal_id) {
+                    pty.reported_cwds.insert(terminal_id, cwd);
+                }
+            },
+            PtyInstruction::SignalPanes(signal) => {
+                pty.signal_panes(signal)
+                    .with_context(|| format!("failed to send {:?} to panes", signal))
+                    .non_fatal();
+            },
+            PtyInstruction::StopCommand(terminal_id, signal) => {
+                pty.stop_command(terminal_id, signal)
+                    .with_context(|| format!("failed to stop command in terminal {terminal_id}"))
+                    .non_fatal();
+            },
+            PtyInstruction::DuplicatePane(terminal_id, terminal_action, direction, client_id) => {
+                let err_context = || format!("failed to duplicate pane of terminal {terminal_id}");
+                let is_default_shell = terminal_action.is_none();
+                // unless its command was given one, the duplicate starts in the current cwd of
+                // the duplicated pane (eg. where its shell was cd'ed to)
+                let mut terminal_action =
+                    terminal_action.unwrap_or_else(|| pty.get_default_terminal(None, None));
+                pty.fill_cwd_from_pane_id(&mut terminal_action, &terminal_id);
+                let (run_command, pane_title, invoked_with) = match &terminal_action {
+                    _ if is_default_shell => (None, None, None),
+                    TerminalAction::RunCommand(run_command) => (
+                        Some(run_command.clone()),
+                        Some(run_command.to_string()),
+                        Some(Run::Command(run_command.clone())),
+                    ),
+                    TerminalAction::OpenFile(payload) => (
+                        None,
+                        None,
+                        Some(Run::EditFile(
+                            payload.path.clone(),
+                            payload.line_number,
+                            payload.cwd.clone(),
+                        )),
+                    ),
+                };
+                // the duplicate gets the env the duplicated pane was spawned with, even if the
+                // session env changed since
+                let env = pty
+                    .terminal_envs
+                    .get(&terminal_id)
+                    .cloned()
+                    .unwrap_or_else(|| pty.session_env.clone());
+                let spawned = pty.spawn_terminal_with_env(
+                    Some(terminal_action),
+                    ClientTabIndexOrPaneId::ClientId(client_id),
+                    env,
+                );
+                // (terminal id, command to hold the pane with, command that was not found)
+                let (pid, hold_for_command, command_not_found) = match spawned {
+                    Ok((pid, starts_held)) => {
+                        let hold_for_command = if starts_held { run_command } else { None };
+                        (pid, hold_for_command, None)
+                    },
+                    Err(err) => {
+                        let not_found_terminal_id = match err.downcast_ref::<ZellijError>() {
+                            Some(ZellijError::CommandNotFound { terminal_id, .. }) => {
+                                Some(*terminal_id)
+                            },
+                            _ => None,
+                        };
+                        let holds_on_close =
+                            run_command.as_ref().map_or(false, |r| r.hold_on_close);
+                        match not_found_terminal_id {
+                            // like the pane it duplicates would, it stays open to say so
+                            Some(not_found_terminal_id) if holds_on_close => {
+                                (not_found_terminal_id, None, run_command)
+                            },
+                            _ => {
+                                if let Some(not_found_terminal_id) = not_found_terminal_id {
+                                    pty.close_pane(PaneId::Terminal(not_found_terminal_id))
+                                        .with_context(err_context)?;
+                                }
+                                pty.bus
+                                    .senders
+                                    .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                                        vec![PaneId::Terminal(terminal_id)],
+                                        format!("Failed to duplicate pane: {err}"),
+                                    ))
+                                    .with_context(err_context)?;
+                                continue;
+                            },
+                        }
+                    },
+                };
+                let screen_instruction = match direction {
+                    Some(Direction::Left) | Some(Direction::Right) => {
+                        ScreenInstruction::VerticalSplit(
+                            PaneId::Terminal(pid),
+                            pane_title,
+                            hold_for_command,
+                            client_id,
+                        )
+                    },
+                    Some(Direction::Up) | Some(Direction::Down) => {
+                        ScreenInstruction::HorizontalSplit(
+                            PaneId::Terminal(pid),
+                            pane_title,
+                            hold_for_command,
+                            client_id,
+                        )
+                    },
+                    None => ScreenInstruction::NewPane(
+                        PaneId::Terminal(pid),
+                        pane_title,
+                        None,
+                        hold_for_command,
+                        invoked_with,
+                        None,
+                        false,
+                        ClientTabIndexOrPaneId::ClientId(client_id),
+                    ),
+                };
+                pty.bus
+                    .senders
+                    .send_to_screen(screen_instruction)
+                    .with_context(err_context)?;
+                if let Some(run_command) = command_not_found {
+                    send_command_not_found_to_screen(pty.bus.senders.clone(), pid, run_command)
+                        .with_context(err_context)?;
+                }
+            },
+            PtyInstruction::RestoreClosedPane(closed_pane, client_id) => {
+                let err_context =
+                    || format!("failed to restore closed pane for client {client_id}");
+                let cwd = pty.closed_terminal_cwds.take(closed_pane.terminal_id);
+                let terminal_action = match closed_pane.invoked_with.clone() {
+                    Some(Run::Command(mut run_command)) => {
+                        if run_command.cwd.is_none() {
+                            run_command.cwd = cwd;
+                        }
+                        TerminalAction::RunCommand(run_command)
+                    },
+                    Some(Run::EditFile(path, line_number, file_cwd)) => TerminalAction::OpenFile(
+                        OpenFilePayload::new(path, line_number, file_cwd.or(cwd)),
+                    ),
+                    _ => pty.get_default_terminal(cwd, None),
+                };
+                let run_command = match &terminal_action {
+                    TerminalAction::RunCommand(run_command) => Some(run_command.clone()),
+                    TerminalAction::OpenFile(..) => None,
+                };
+                match pty
+                    .spawn_terminal(
+                        Some(terminal_action),
+                        ClientTabIndexOrPaneId::ClientId(client_id),
+                    )
+                    .with_context(err_context)
+                {
+                    Ok((terminal_id, starts_held)) => {
+                        let hold_for_command = if starts_held { run_command } else { None };
+                        pty.bus
+                            .senders
+                            .send_to_screen(ScreenInstruction::ClosedPaneRespawned(
+                                terminal_id,
+                                hold_for_command,
+                                closed_pane,
+                                client_id,
+                            ))
+                            .with_context(err_context)?;
+                    },
+                    Err(err) => match err.downcast_ref::<ZellijError>() {
+                        Some(ZellijError::CommandNotFound { terminal_id, .. }) => {
+                            log::error!("Failed to restore closed pane: {:?}", err);
+                            pty.close_pane(PaneId::Terminal(*terminal_id))
+                                .with_context(err_context)?;
+                        },
+                        _ => Err::<(), _>(err).non_fatal(),
+                    },
+                }
+            },
+            PtyInstruction::MovePaneToSession(terminal_id, session_name) => {
+                if let Err(e) = pty.move_pane_to_session(terminal_id, &session_name) {
+                    log::error!("Failed to move pane to session {}: {:?}", session_name, e);
+                    let _ =
+                        pty.bus
+                            .senders
+                            .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                                vec![PaneId::Terminal(terminal_id)],
+                                format!("Failed to move pane: {}", e),
+                            ));
+                }
+            },
+            PtyInstruction::SendPaneToSession(terminal_id, moved_pane, session_name) => {
+                pty.send_pane_to_session(terminal_id, *moved_pane, session_name)
+                    .with_context(|| format!("failed to move terminal {terminal_id}"))
+                    .non_fatal();
+            },
+            PtyInstruction::PaneMovedToSession(terminal_id) => {
+                pty.pane_moved_to_session(terminal_id)
+                    .with_context(|| format!("failed to move terminal {terminal_id}"))?;
+            },
+            PtyInstruction::PaneNotMovedToSession(terminal_id, pty_fd, error) => {
+                log::error!("Failed to move pane to session: {}", error);
+                pty.resume_reading_from_terminal(terminal_id, pty_fd)
+                    .with_context(|| format!("failed to restore terminal {terminal_id}"))
+                    .non_fatal();
+                let _ = pty
+                    .bus
+                    .senders
+                    .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                        vec![PaneId::Terminal(terminal_id)],
+                        format!("Failed to move pane: {}", error),
+                    ));
+            },
+            PtyInstruction::AdoptPane(pty_fd, child_pid, moved_pane, client_id) => {
+                pty.adopt_pane(pty_fd, child_pid, *moved_pane, client_id)
+                    .with_context(|| format!("failed to adopt pane for client {client_id}"))
+                    .non_fatal();
+            },
+            PtyInstruction::SetTabCwd(cwd, client_id) => {
+                pty.set_tab_cwd(cwd, client_id)?;
+            },
+            PtyInstruction::SetSessionEnv(env_vars) => {
+                pty.set_session_env(env_vars);
+            },
             PtyInstruction::LogLayoutToHd(mut session_layout_metadata) => {
                 let err_context = || format!("Failed to dump layout");
                 pty.populate_session_layout_metadata(&mut session_layout_metadata);
@@ -787,9 +1070,10 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
             },
             PtyInstruction::Reconfigure {
                 default_editor,
+                new_pane_cwd,
                 client_id: _,
             } => {
-                pty.reconfigure(default_editor);
+                pty.reconfigure(default_editor, new_pane_cwd);
             },
             PtyInstruction::Exit => break,
         }
@@ -802,6 +1086,8 @@ impl Pty {
         bus: Bus<PtyInstruction>,
         debug_to_file: bool,
         default_editor: Option<PathBuf>,
+        new_pane_cwd: NewPaneCwd,
+        session_cwd: Option<PathBuf>,
     ) -> Self {
         Pty {
             active_panes: HashMap::new(),
@@ -809,8 +1095,20 @@ impl Pty {
             id_to_child_pid: HashMap::new(),
             debug_to_file,
             task_handles: HashMap::new(),
+            stop_reading: HashMap::new(),
+            panes_moving_to_sessions: HashMap::new(),
             default_editor,
             originating_plugins: HashMap::new(),
+            reported_cwds: HashMap::new(),
+            closed_terminal_cwds: ClosedTerminalCwds::default(),
+            command_watchers: HashMap::new(),
+            active_tabs: HashMap::new(),
+            tab_cwds: HashMap::new(),
+            default_tab_cwds: HashMap::new(),
+            new_pane_cwd,
+            session_cwd,
+            session_env: BTreeMap::new(),
+            terminal_envs: HashMap::new(),
         }
     }
     pub fn get_default_terminal(
@@ -852,34 +1150,84 @@ impl Pty {
             },
         }
     }
+    /// The cwd of the terminal pane, as reported by its shell through the shell integration if
+    /// this directory exists, otherwise as queried from the os
+    fn terminal_cwd(&self, terminal_id: &u32) -> Option<PathBuf> {
+        self.reported_cwds
+            .get(terminal_id)
+            .filter(|cwd| cwd.is_dir())
+            .cloned()
+            .or_else(|| {
+                self.id_to_child_pid.get(terminal_id).and_then(|&id| {
+                    self.bus
+                        .os_input
+                        .as_ref()
+                        .and_then(|input| input.get_cwd(Pid::from_raw(id)))
+                })
+            })
+    }
+    /// Sets (or with None, removes) environment variables of the panes spawned from now on
+    pub fn set_session_env(&mut self, env_vars: Vec<(String, Option<String>)>) {
+        self.session_env.extend(env_vars);
+    }
+    /// Gives the tab of the client a default cwd, which panes opened in it from now on start in
+    pub fn set_tab_cwd(&mut self, cwd: PathBuf, client_id: ClientId) -> Result<()> {
+        match self.active_tabs.get(&client_id) {
+            Some(tab_index) if cwd.is_dir() => {
+                self.default_tab_cwds.insert(*tab_index, cwd);
+                Ok(())
+            },
+            _ => self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::LogError(
+                    vec![format!("No such directory: {}", cwd.display())],
+                    client_id,
+                ))
+                .with_context(|| format!("failed to set tab cwd to {:?}", cwd)),
+        }
+    }
+    /// The cwd new panes of this client open in when none is given: the default cwd of its tab
+    /// if it has one, otherwise according to the `new_pane_cwd` option
+    fn new_pane_cwd(&self, client_id: ClientId) -> Option<PathBuf> {
+        let default_tab_cwd = self
+            .active_tabs
+            .get(&client_id)
+            .and_then(|tab_index| self.default_tab_cwds.get(tab_index))
+            .filter(|cwd| cwd.is_dir());
+        if let Some(default_tab_cwd) = default_tab_cwd {
+            return Some(default_tab_cwd.clone());
+        }
+        let focused_pane_cwd = || {
+            self.active_panes
+                .get(&client_id)
+                .and_then(|pane| match pane {
+                    PaneId::Plugin(..) => None,
+                    PaneId::Terminal(id) => self.terminal_cwd(id),
+                })
+        };
+        match self.new_pane_cwd {
+            NewPaneCwd::FocusedPane => focused_pane_cwd(),
+            NewPaneCwd::Tab => self
+                .active_tabs
+                .get(&client_id)
+                .and_then(|tab_index| self.tab_cwds.get(tab_index))
+                .cloned()
+                .or_else(focused_pane_cwd),
+            NewPaneCwd::Session => self.session_cwd.clone(),
+        }
+    }
     fn fill_cwd(&self, terminal_action: &mut TerminalAction, client_id: ClientId) {
         if let TerminalAction::RunCommand(run_command) = terminal_action {
             if run_command.cwd.is_none() {
-                run_command.cwd = self
-                    .active_panes
-                    .get(&client_id)
-                    .and_then(|pane| match pane {
-                        PaneId::Plugin(..) => None,
-                        PaneId::Terminal(id) => self.id_to_child_pid.get(id),
-                    })
-                    .and_then(|&id| {
-                        self.bus
-                            .os_input
-                            .as_ref()
-                            .and_then(|input| input.get_cwd(Pid::from_raw(id)))
-                    });
+                run_command.cwd = self.new_pane_cwd(client_id);
             };
         };
     }
     fn fill_cwd_from_pane_id(&self, terminal_action: &mut TerminalAction, pane_id: &u32) {
         if let TerminalAction::RunCommand(run_command) = terminal_action {
             if run_command.cwd.is_none() {
-                run_command.cwd = self.id_to_child_pid.get(pane_id).and_then(|&id| {
-                    self.bus
-                        .os_input
-                        .as_ref()
-                        .and_then(|input| input.get_cwd(Pid::from_raw(id)))
-                });
+                run_command.cwd = self.terminal_cwd(pane_id);
             };
         };
     }
@@ -887,6 +1235,16 @@ impl Pty {
         &mut self,
         terminal_action: Option<TerminalAction>,
         client_or_tab_index: ClientTabIndexOrPaneId,
+    ) -> Result<(u32, bool)> {
+        let env = self.session_env.clone();
+        self.spawn_terminal_with_env(terminal_action, client_or_tab_index, env)
+    }
+    /// Like `spawn_terminal`, with the given env rather than the current one of the session
+    pub fn spawn_terminal_with_env(
+        &mut self,
+        terminal_action: Option<TerminalAction>,
+        client_or_tab_index: ClientTabIndexOrPaneId,
+        env: BTreeMap<String, Option<String>>,
     ) -> Result<(u32, bool)> {
         // bool is starts_held
         let err_context = || format!("failed to spawn terminal for {:?}", client_or_tab_index);
@@ -926,6 +1284,29 @@ impl Pty {
                     open_file_payload.originating_plugin.clone(),
                 ),
             };
+        let output_capture = match &terminal_action {
+            TerminalAction::RunCommand(run_command) => run_command
+                .capture_output
+                .as_ref()
+                .and_then(|c| c.client_id.map(|client_id| (client_id, c.tail_lines))),
+            TerminalAction::OpenFile(..) => None,
+        };
+        let (stdin_file, stdin_client, output_log) = match &terminal_action {
+            TerminalAction::RunCommand(run_command) => (
+                stdin_file_path(run_command),
+                stdin_pipe_client(run_command),
+                command_output_log(run_command),
+            ),
+            TerminalAction::OpenFile(..) => (None, None, None),
+        };
+        let close_on_success = match &terminal_action {
+            TerminalAction::RunCommand(run_command) => run_command.close_on_success,
+            TerminalAction::OpenFile(..) => false,
+        };
+        let command_watch = match &terminal_action {
+            TerminalAction::RunCommand(run_command) => command_watch(run_command),
+            TerminalAction::OpenFile(..) => None,
+        };
 
         if hold_on_start {
             // we don't actually open a terminal in this case, just wait for the user to run it
@@ -937,6 +1318,16 @@ impl Pty {
                 .context("couldn't get mutable reference to OS interface")
                 .and_then(|os_input| os_input.reserve_terminal_id())
                 .with_context(err_context)?;
+            if let Some((client_id, tail_lines)) = output_capture {
+                self.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::CapturePaneOutput(
+                        terminal_id,
+                        client_id,
+                        tail_lines,
+                    ))
+                    .with_context(err_context)?;
+            }
             return Ok((terminal_id, starts_held));
         }
 
@@ -972,9 +1363,17 @@ impl Pty {
                             update_event,
                         )]));
                     }
+                    if output_capture.is_some() {
+                        // this needs to arrive before the pane is closed, so that its final
+                        // lines can still be captured
+                        let _ = senders.send_to_screen(ScreenInstruction::CapturedPaneExited(
+                            pane_id,
+                            exit_status,
+                        ));
+                    }
                 }
 
-                if hold_on_close {
+                if holds_after_exit(hold_on_close, close_on_success, exit_status) {
                     let _ = senders.send_to_screen(ScreenInstruction::HoldPane(
                         pane_id,
                         exit_status,
@@ -991,9 +1390,22 @@ impl Pty {
             .as_mut()
             .context("no OS I/O interface found")
             .and_then(|os_input| {
-                os_input.spawn_terminal(terminal_action, quit_cb, self.default_editor.clone())
+                os_input.spawn_terminal(terminal_action, quit_cb, self.default_editor.clone(), &env)
             })
             .with_context(err_context)?;
+        if let Some((client_id, tail_lines)) = output_capture {
+            // we register the capture before we start reading from the terminal so that we
+            // don't miss any of its output
+            self.bus
+                .senders
+                .send_to_screen(ScreenInstruction::CapturePaneOutput(
+                    terminal_id,
+                    client_id,
+                    tail_lines,
+                ))
+                .with_context(err_context)?;
+        }
+        let (stop_reading, stop_signal) = async_channel::bounded(1);
         let terminal_bytes = task::spawn({
             let err_context =
                 |terminal_id: u32| format!("failed to run async task for terminal {terminal_id}");
@@ -1008,6 +1420,8 @@ impl Pty {
             let debug_to_file = self.debug_to_file;
             async move {
                 TerminalBytes::new(pid_primary, senders, os_input, debug_to_file, terminal_id)
+                    .with_output_log(output_log)
+                    .with_stop_signal(stop_signal)
                     .listen()
                     .await
                     .with_context(|| err_context(terminal_id))
@@ -1016,7 +1430,12 @@ impl Pty {
         });
 
         self.task_handles.insert(terminal_id, terminal_bytes);
+        self.stop_reading.insert(terminal_id, stop_reading);
         self.id_to_child_pid.insert(terminal_id, child_fd);
+        self.terminal_envs.insert(terminal_id, env);
+        self.write_stdin_file(terminal_id, stdin_file);
+        self.start_stdin_pipe(terminal_id, stdin_client);
+        self.watch_command_files(terminal_id, command_watch);
         let starts_held = false;
         Ok((terminal_id, starts_held))
     }
@@ -1033,9 +1452,25 @@ impl Pty {
     ) -> Result<()> {
         let err_context = || format!("failed to spawn terminals for layout for client {client_id}");
 
+        // a cwd given to the tab (eg. `tab cwd="..."` in the layout or `new-tab --cwd`) is where
+        // the panes opened in it later start too
+        let default_tab_cwd = match &layout.run {
+            Some(Run::Cwd(layout_cwd)) => Some(layout_cwd.clone()),
+            _ => cwd.clone(),
+        };
+        if let Some(default_tab_cwd) = default_tab_cwd {
+            self.default_tab_cwds.insert(tab_index, default_tab_cwd);
+        }
         let mut default_shell =
             default_shell.unwrap_or_else(|| self.get_default_terminal(cwd, None));
         self.fill_cwd(&mut default_shell, client_id);
+        let tab_cwd = match &default_shell {
+            TerminalAction::RunCommand(run_command) => run_command.cwd.clone(),
+            TerminalAction::OpenFile(..) => None,
+        };
+        if let Some(tab_cwd) = tab_cwd.or_else(|| self.session_cwd.clone()) {
+            self.tab_cwds.insert(tab_index, tab_cwd);
+        }
         let extracted_run_instructions = layout.extract_run_instructions();
         let extracted_floating_run_instructions = floating_panes_layout
             .iter()
@@ -1107,6 +1542,8 @@ impl Pty {
             }
             match pid_primary {
                 Ok(pid_primary) => {
+                    let output_log = run_command.as_ref().and_then(command_output_log);
+                    let (stop_reading, stop_signal) = async_channel::bounded(1);
                     let terminal_bytes = task::spawn({
                         let senders = self.bus.senders.clone();
                         let os_input = self
@@ -1124,6 +1561,8 @@ impl Pty {
                                 debug_to_file,
                                 terminal_id,
                             )
+                            .with_output_log(output_log)
+                            .with_stop_signal(stop_signal)
                             .listen()
                             .await
                             .context("failed to spawn terminals for layout")
@@ -1131,6 +1570,7 @@ impl Pty {
                         }
                     });
                     self.task_handles.insert(terminal_id, terminal_bytes);
+                    self.stop_reading.insert(terminal_id, stop_reading);
                 },
                 _ => match run_command {
                     Some(run_command) => {
@@ -1175,10 +1615,11 @@ impl Pty {
             Some(Run::Command(mut command)) => {
                 let starts_held = command.hold_on_start;
                 let hold_on_close = command.hold_on_close;
+                let close_on_success = command.close_on_success;
                 let quit_cb = Box::new({
                     let senders = self.bus.senders.clone();
                     move |pane_id, exit_status, command| {
-                        if hold_on_close {
+                        if holds_after_exit(hold_on_close, close_on_success, exit_status) {
                             let _ = senders.send_to_screen(ScreenInstruction::HoldPane(
                                 pane_id,
                                 exit_status,
@@ -1224,11 +1665,20 @@ impl Pty {
                         .as_mut()
                         .context("no OS I/O interface found")
                         .with_context(err_context)?
-                        .spawn_terminal(cmd, quit_cb, self.default_editor.clone())
+                        .spawn_terminal(
+                            cmd,
+                            quit_cb,
+                            self.default_editor.clone(),
+                            &self.session_env,
+                        )
                         .with_context(err_context)
                     {
                         Ok((terminal_id, pid_primary, child_fd)) => {
                             self.id_to_child_pid.insert(terminal_id, child_fd);
+                            self.terminal_envs
+                                .insert(terminal_id, self.session_env.clone());
+                            self.write_stdin_file(terminal_id, stdin_file_path(&command));
+                            self.watch_command_files(terminal_id, command_watch(&command));
                             Ok(Some((
                                 terminal_id,
                                 starts_held,
@@ -1256,11 +1706,18 @@ impl Pty {
                     .as_mut()
                     .context("no OS I/O interface found")
                     .with_context(err_context)?
-                    .spawn_terminal(shell, quit_cb, self.default_editor.clone())
+                    .spawn_terminal(
+                        shell,
+                        quit_cb,
+                        self.default_editor.clone(),
+                        &self.session_env,
+                    )
                     .with_context(err_context)
                 {
                     Ok((terminal_id, pid_primary, child_fd)) => {
                         self.id_to_child_pid.insert(terminal_id, child_fd);
+                        self.terminal_envs
+                            .insert(terminal_id, self.session_env.clone());
                         Ok(Some((terminal_id, starts_held, None, Ok(pid_primary))))
                     },
                     Err(err) => match err.downcast_ref::<ZellijError>() {
@@ -1287,11 +1744,14 @@ impl Pty {
                         )),
                         quit_cb,
                         self.default_editor.clone(),
+                        &self.session_env,
                     )
                     .with_context(err_context)
                 {
                     Ok((terminal_id, pid_primary, child_fd)) => {
                         self.id_to_child_pid.insert(terminal_id, child_fd);
+                        self.terminal_envs
+                            .insert(terminal_id, self.session_env.clone());
                         Ok(Some((terminal_id, starts_held, None, Ok(pid_primary))))
                     },
                     Err(err) => match err.downcast_ref::<ZellijError>() {
@@ -1310,11 +1770,18 @@ impl Pty {
                     .as_mut()
                     .context("no OS I/O interface found")
                     .with_context(err_context)?
-                    .spawn_terminal(default_shell.clone(), quit_cb, self.default_editor.clone())
+                    .spawn_terminal(
+                        default_shell.clone(),
+                        quit_cb,
+                        self.default_editor.clone(),
+                        &self.session_env,
+                    )
                     .with_context(err_context)
                 {
                     Ok((terminal_id, pid_primary, child_fd)) => {
                         self.id_to_child_pid.insert(terminal_id, child_fd);
+                        self.terminal_envs
+                            .insert(terminal_id, self.session_env.clone());
                         Ok(Some((terminal_id, starts_held, None, Ok(pid_primary))))
                     },
                     Err(err) => match err.downcast_ref::<ZellijError>() {
@@ -1333,7 +1800,18 @@ impl Pty {
         let err_context = || format!("failed to close for pane {id:?}");
         match id {
             PaneId::Terminal(id) => {
+                // the pane might be restored in the same cwd
+                if let Some(cwd) = self.terminal_cwd(&id) {
+                    self.closed_terminal_cwds.insert(id, cwd);
+                }
                 self.task_handles.remove(&id);
+                self.stop_reading.remove(&id);
+                if let Some((_, pty_fd)) = self.panes_moving_to_sessions.remove(&id) {
+                    let _ = unistd::close(pty_fd);
+                }
+                self.reported_cwds.remove(&id);
+                self.command_watchers.remove(&id);
+                self.terminal_envs.remove(&id);
                 if let Some(child_fd) = self.id_to_child_pid.remove(&id) {
                     task::block_on(async {
                         let err_context = || format!("failed to run async task for pane {id}");
@@ -1374,6 +1852,269 @@ impl Pty {
             self.active_panes.insert(client_id, pane_id);
         }
     }
+    fn write_stdin_file(&self, terminal_id: u32, stdin_file: Option<PathBuf>) {
+        // the contents are written to the pty rather than replacing the command's STDIN, so that
+        // the command can still be interacted with once they have been consumed
+        if let Some(stdin_file) = stdin_file {
+            match std::fs::read(&stdin_file) {
+                Ok(contents) => {
+                    let _ = self
+                        .bus
+                        .senders
+                        .send_to_pty_writer(PtyWriteInstruction::Write(contents, terminal_id));
+                },
+                Err(e) => {
+                    log::error!(
+                        "Failed to read stdin file {} for terminal {}: {}",
+                        stdin_file.display(),
+                        terminal_id,
+                        e
+                    );
+                },
+            }
+        }
+    }
+    fn watch_command_files(
+        &mut self,
+        terminal_id: u32,
+        command_watch: Option<(PathBuf, CommandWatch)>,
+    ) {
+        // the watcher outlives the runs of the command it re-runs, until its pane is closed or
+        // runs something else (eg. after dropping to a shell)
+        let (cwd, watch) = match command_watch {
+            Some(command_watch) => command_watch,
+            None => {
+                self.command_watchers.remove(&terminal_id);
+                return;
+            },
+        };
+        if self.command_watchers.contains_key(&terminal_id) {
+            return;
+        }
+        match watch_command_files(self.bus.senders.clone(), terminal_id, &cwd, &watch) {
+            Ok(watcher) => {
+                self.command_watchers.insert(terminal_id, watcher);
+            },
+            Err(e) => {
+                log::error!(
+                    "Failed to watch files in {} for terminal {}: {:?}",
+                    cwd.display(),
+                    terminal_id,
+                    e
+                );
+            },
+        }
+    }
+    pub fn stop_command(&self, terminal_id: u32, signal: PaneSignal) -> Result<()> {
+        // the command exits as it would on its own, so that its pane holds and can re-run it
+        if let Some(child_pid) = self.id_to_child_pid.get(&terminal_id) {
+            let os_input = self.bus.os_input.as_ref().context("no os_input")?;
+            os_input.signal_process_tree(Pid::from_raw(*child_pid), signal)?;
+            // screen knows whether it exited by then, and has it sent the next signal if not
+            let senders = self.bus.senders.clone();
+            task::spawn(async move {
+                task::sleep(Duration::from_secs(STOP_COMMAND_TIMEOUT_SECS)).await;
+                let _ = senders
+                    .send_to_screen(ScreenInstruction::StopCommandTimedOut(terminal_id, signal));
+            });
+        }
+        Ok(())
+    }
+    /// Starts moving the pty of this terminal to the server of another session, which shows it in
+    /// a new pane. We stop reading from it first so that screen serializes the pane with
+    /// everything it output, while what it outputs from now on is read by the other session.
+    pub fn move_pane_to_session(&mut self, terminal_id: u32, session_name: &str) -> Result<()> {
+        if envs::get_session_name().ok().as_deref() == Some(session_name) {
+            bail!("the pane is already in session {session_name}");
+        }
+        if !self.id_to_child_pid.contains_key(&terminal_id) {
+            bail!("the pane has no running process");
+        }
+        let stream = LocalSocketStream::connect(&*ZELLIJ_SOCK_DIR.join(session_name))
+            .with_context(|| format!("session {session_name} not found"))?;
+        let pty_fd = self
+            .bus
+            .os_input
+            .as_ref()
+            .context("no OS I/O interface found")
+            .and_then(|os_input| os_input.release_terminal(terminal_id))?;
+        self.panes_moving_to_sessions
+            .insert(terminal_id, (IpcSenderWithContext::new(stream), pty_fd));
+        let stop_reading = self.stop_reading.remove(&terminal_id);
+        let terminal_bytes = self.task_handles.remove(&terminal_id);
+        let senders = self.bus.senders.clone();
+        let session_name = session_name.to_owned();
+        task::spawn(async move {
+            if let Some(stop_reading) = stop_reading {
+                let _ = stop_reading.send(()).await;
+            }
+            // once it stopped, all it read is queued to screen ahead of this
+            if let Some(terminal_bytes) = terminal_bytes {
+                terminal_bytes.await;
+            }
+            let _ = senders.send_to_screen(ScreenInstruction::SerializeMovedPane(
+                terminal_id,
+                session_name,
+            ));
+        });
+        Ok(())
+    }
+    /// Sends a pane serialized by screen along with its pty to the server of another session, in
+    /// the background since that server may take a while to accept it
+    pub fn send_pane_to_session(
+        &mut self,
+        terminal_id: u32,
+        moved_pane: MovedPane,
+        session_name: String,
+    ) -> Result<()> {
+        let (mut sender, pty_fd) = self
+            .panes_moving_to_sessions
+            .remove(&terminal_id)
+            .context("the pane is not being moved")?;
+        let child_pid = *self
+            .id_to_child_pid
+            .get(&terminal_id)
+            .context("the pane has no running process")?;
+        let senders = self.bus.senders.clone();
+        std::thread::Builder::new()
+            .name("move_pane".to_string())
+            .spawn(
+                move || match send_pane_to_session(&mut sender, moved_pane, child_pid, pty_fd) {
+                    Ok(()) => {
+                        let _ = unistd::close(pty_fd);
+                        let _ =
+                            senders.send_to_pty(PtyInstruction::PaneMovedToSession(terminal_id));
+                    },
+                    Err(e) => {
+                        let _ = senders.send_to_pty(PtyInstruction::PaneNotMovedToSession(
+                            terminal_id,
+                            pty_fd,
+                            format!("session {session_name} did not accept the pane: {e}"),
+                        ));
+                    },
+                },
+            )
+            .context("failed to spawn thread")?;
+        Ok(())
+    }
+    /// Forgets a terminal sent to another session. Its process stays our child, so we keep its
+    /// terminal id reserved until it exits.
+    pub fn pane_moved_to_session(&mut self, terminal_id: u32) -> Result<()> {
+        self.id_to_child_pid.remove(&terminal_id);
+        self.reported_cwds.remove(&terminal_id);
+        self.command_watchers.remove(&terminal_id);
+        self.terminal_envs.remove(&terminal_id);
+        self.bus
+            .senders
+            .send_to_screen(ScreenInstruction::PaneMovedToSession(terminal_id))
+    }
+    /// Reads from the pty of a terminal that could not be moved to another session again
+    pub fn resume_reading_from_terminal(&mut self, terminal_id: u32, pty_fd: RawFd) -> Result<()> {
+        let err_context = || format!("failed to resume reading from terminal {terminal_id}");
+        let os_input = self
+            .bus
+            .os_input
+            .as_ref()
+            .context("no OS I/O interface found")
+            .with_context(err_context)?
+            .clone();
+        os_input
+            .restore_terminal(terminal_id, pty_fd)
+            .with_context(err_context)?;
+        let (stop_reading, stop_signal) = async_channel::bounded(1);
+        let terminal_bytes = task::spawn({
+            let senders = self.bus.senders.clone();
+            let debug_to_file = self.debug_to_file;
+            async move {
+                TerminalBytes::new(pty_fd, senders, os_input, debug_to_file, terminal_id)
+                    .with_stop_signal(stop_signal)
+                    .listen()
+                    .await
+                    .with_context(err_context)
+                    .non_fatal();
+            }
+        });
+        self.task_handles.insert(terminal_id, terminal_bytes);
+        self.stop_reading.insert(terminal_id, stop_reading);
+        Ok(())
+    }
+    /// Reads from the pty of a terminal moved here from another session, and has screen show it
+    /// in a new pane
+    pub fn adopt_pane(
+        &mut self,
+        pty_fd: RawFd,
+        child_pid: i32,
+        moved_pane: MovedPane,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || "failed to adopt pane moved from another session".to_string();
+        let terminal_id = self
+            .bus
+            .os_input
+            .as_ref()
+            .context("no OS I/O interface found")
+            .and_then(|os_input| os_input.adopt_terminal(pty_fd))
+            .with_context(err_context)?;
+        let (stop_reading, stop_signal) = async_channel::bounded(1);
+        let terminal_bytes = task::spawn({
+            let err_context =
+                |terminal_id: u32| format!("failed to run async task for terminal {terminal_id}");
+            let senders = self.bus.senders.clone();
+            let os_input = self
+                .bus
+                .os_input
+                .as_ref()
+                .with_context(|| err_context(terminal_id))
+                .fatal()
+                .clone();
+            let debug_to_file = self.debug_to_file;
+            async move {
+                let mut terminal_bytes = TerminalBytes::new(
+                    pty_fd,
+                    senders.clone(),
+                    os_input,
+                    debug_to_file,
+                    terminal_id,
+                )
+                .with_stop_signal(stop_signal);
+                terminal_bytes
+                    .listen()
+                    .await
+                    .with_context(|| err_context(terminal_id))
+                    .non_fatal();
+                // its process is not our child and cannot be waited for, the pty closing is how
+                // we know that it exited - unless we stopped reading from it (eg. to move it on to
+                // yet another session)
+                if !terminal_bytes.stopped() {
+                    let pane_id = PaneId::Terminal(terminal_id);
+                    let _ = senders.send_to_screen(ScreenInstruction::ClosePane(pane_id, None));
+                    // for us to stop tracking it and release its terminal id
+                    let _ = senders.send_to_pty(PtyInstruction::ClosePane(pane_id));
+                }
+            }
+        });
+        self.task_handles.insert(terminal_id, terminal_bytes);
+        self.stop_reading.insert(terminal_id, stop_reading);
+        self.id_to_child_pid.insert(terminal_id, child_pid);
+        self.bus
+            .senders
+            .send_to_screen(ScreenInstruction::AdoptPane(
+                terminal_id,
+                Box::new(moved_pane),
+                client_id,
+            ))
+            .with_context(err_context)
+    }
+    fn start_stdin_pipe(&self, terminal_id: u32, stdin_client: Option<ClientId>) {
+        // the client only starts sending its STDIN once the command is running (eg. not while it
+        // is suspended)
+        if let Some(client_id) = stdin_client {
+            let _ = self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::StdinPipeReady(client_id, terminal_id));
+        }
+    }
     pub fn rerun_command_in_pane(
         &mut self,
         pane_id: PaneId,
@@ -1390,7 +2131,13 @@ impl Pty {
                 let _ = self.id_to_child_pid.remove(&id); // if all is wlel, this shouldn't be here
 
                 let hold_on_close = run_command.hold_on_close;
+                let close_on_success = run_command.close_on_success;
                 let originating_plugin = Arc::new(run_command.originating_plugin.clone());
+                let captures_output = run_command.capture_output.is_some();
+                let stdin_file = stdin_file_path(&run_command);
+                let stdin_client = stdin_pipe_client(&run_command);
+                let output_log = command_output_log(&run_command);
+                let command_watch = command_watch(&run_command);
                 let quit_cb = Box::new({
                     let senders = self.bus.senders.clone();
                     move |pane_id, exit_status, command| {
@@ -1407,8 +2154,13 @@ impl Pty {
                                     update_event,
                                 )]));
                             }
+                            if captures_output {
+                                let _ = senders.send_to_screen(
+                                    ScreenInstruction::CapturedPaneExited(pane_id, exit_status),
+                                );
+                            }
                         }
-                        if hold_on_close {
+                        if holds_after_exit(hold_on_close, close_on_success, exit_status) {
                             let _ = senders.send_to_screen(ScreenInstruction::HoldPane(
                                 pane_id,
                                 exit_status,
@@ -1426,9 +2178,15 @@ impl Pty {
                     .as_mut()
                     .context("no OS I/O interface found")
                     .and_then(|os_input| {
-                        os_input.re_run_command_in_terminal(id, run_command, quit_cb)
+                        os_input.re_run_command_in_terminal(
+                            id,
+                            run_command,
+                            quit_cb,
+                            &self.session_env,
+                        )
                     })
                     .with_context(err_context)?;
+                let (stop_reading, stop_signal) = async_channel::bounded(1);
                 let terminal_bytes = task::spawn({
                     let err_context =
                         |pane_id| format!("failed to run async task for pane {pane_id:?}");
@@ -1443,6 +2201,8 @@ impl Pty {
                     let debug_to_file = self.debug_to_file;
                     async move {
                         TerminalBytes::new(pid_primary, senders, os_input, debug_to_file, id)
+                            .with_output_log(output_log)
+                            .with_stop_signal(stop_signal)
                             .listen()
                             .await
                             .with_context(|| err_context(pane_id))
@@ -1451,7 +2211,12 @@ impl Pty {
                 });
 
                 self.task_handles.insert(id, terminal_bytes);
+                self.stop_reading.insert(id, stop_reading);
                 self.id_to_child_pid.insert(id, child_fd);
+                self.terminal_envs.insert(id, self.session_env.clone());
+                self.write_stdin_file(id, stdin_file);
+                self.start_stdin_pipe(id, stdin_client);
+                self.watch_command_files(id, command_watch);
                 if let Some(originating_plugin) = self.originating_plugins.get(&id) {
                     self.bus
                         .senders
@@ -1504,7 +2269,12 @@ impl Pty {
             if let Some(cmd) = cmd {
                 terminal_ids_to_commands.insert(terminal_id, cmd.clone());
             }
-            if let Some(cwd) = cwd {
+            if let Some(cwd) = self
+                .reported_cwds
+                .get(&terminal_id)
+                .filter(|cwd| cwd.is_dir())
+                .or(cwd)
+            {
                 terminal_ids_to_cwds.insert(terminal_id, cwd.clone());
             }
         }
@@ -1534,13 +2304,7 @@ impl Pty {
                 .get(&client_id)
                 .and_then(|pane| match pane {
                     PaneId::Plugin(..) => None,
-                    PaneId::Terminal(id) => self.id_to_child_pid.get(id),
-                })
-                .and_then(|&id| {
-                    self.bus
-                        .os_input
-                        .as_ref()
-                        .and_then(|input| input.get_cwd(Pid::from_raw(id)))
+                    PaneId::Terminal(id) => self.terminal_cwd(id),
                 })
         };
 
@@ -1564,8 +2328,16 @@ impl Pty {
         ))?;
         Ok(())
     }
-    pub fn reconfigure(&mut self, default_editor: Option<PathBuf>) {
+    pub fn signal_panes(&self, signal: PaneSignal) -> Result<()> {
+        let os_input = self.bus.os_input.as_ref().context("no os_input")?;
+        for child_pid in self.id_to_child_pid.values() {
+            os_input.signal_process_tree(Pid::from_raw(*child_pid), signal)?;
+        }
+        Ok(())
+    }
+    pub fn reconfigure(&mut self, default_editor: Option<PathBuf>, new_pane_cwd: NewPaneCwd) {
         self.default_editor = default_editor;
+        self.new_pane_cwd = new_pane_cwd;
     }
 }
 
@@ -1604,9 +2376,77 @@ fn send_command_not_found_to_screen(
     Ok(())
 }
 
+// panes that close on success only stay open to show the exit status of a failed command
+fn holds_after_exit(hold_on_close: bool, close_on_success: bool, exit_status: Option<i32>) -> bool {
+    hold_on_close && !(close_on_success && exit_status == Some(0))
+}
+
+fn stdin_file_path(run_command: &RunCommand) -> Option<PathBuf> {
+    run_command
+        .stdin_file
+        .as_ref()
+        .map(|stdin_file| relative_to_command_cwd(run_command, stdin_file))
+}
+
+fn stdin_pipe_client(run_command: &RunCommand) -> Option<ClientId> {
+    run_command
+        .stdin_pipe
+        .as_ref()
+        .and_then(|stdin_pipe| stdin_pipe.client_id)
+}
+
+fn command_output_log(run_command: &RunCommand) -> Option<OutputLog> {
+    run_command.log_output.as_ref().map(|log_output| OutputLog {
+        path: relative_to_command_cwd(run_command, &log_output.path),
+        ..log_output.clone()
+    })
+}
+
+fn command_watch(run_command: &RunCommand) -> Option<(PathBuf, CommandWatch)> {
+    // commands without a cwd run in the cwd of the server
+    let cwd = run_command
+        .cwd
+        .clone()
+        .or_else(|| std::env::current_dir().ok())?;
+    run_command.watch.clone().map(|watch| (cwd, watch))
+}
+
+fn relative_to_command_cwd(run_command: &RunCommand, path: &Path) -> PathBuf {
+    // relative paths (eg. from layouts) are relative to the command's cwd
+    match run_command.cwd.as_ref() {
+        Some(cwd) => cwd.join(path),
+        None => path.to_path_buf(),
+    }
+}
+
+fn send_pane_to_session(
+    sender: &mut IpcSenderWithContext<ClientToServerMsg>,
+    moved_pane: MovedPane,
+    child_pid: i32,
+    pty_fd: RawFd,
+) -> Result<()> {
+    let mut receiver = sender.get_receiver::<ServerToClientMsg>();
+    receiver.set_timeout(Duration::from_secs(MOVE_PANE_TIMEOUT_SECS))?;
+    sender.send(ClientToServerMsg::AdoptPane(moved_pane, child_pid))?;
+    loop {
+        match receiver.recv() {
+            Some((ServerToClientMsg::ReadyToAdoptPane, _)) => break,
+            Some(_) => {},
+            None => bail!("no reply"),
+        }
+    }
+    sender.send_fd(pty_fd)?;
+    let _ = sender.send(ClientToServerMsg::ClientExited);
+    Ok(())
+}
+
 pub fn get_default_shell() -> PathBuf {
     PathBuf::from(std::env::var("SHELL").unwrap_or_else(|_| {
         log::warn!("Cannot read SHELL env, falling back to use /bin/sh");
         "/bin/sh".to_string()
     }))
 }
+
+#[cfg(test)]
+#[path = "./unit/pty_tests.rs"]
+mod pty_tests;
@@ -0,0 +1,159 @@
+//! Caps on how many panes and tabs a session can have and how much memory their scrollback can
+//! take (the max_panes_per_tab, max_tabs and max_scrollback_memory options), so that a runaway
+//! script opening panes in a loop does not take down the machine.
+use zellij_utils::input::{
+    layout::{FloatingPaneLayout, TiledPaneLayout},
+    options::Options,
+};
+
+const BYTES_IN_MB: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+    pub max_panes_per_tab: Option<usize>,
+    pub max_tabs: Option<usize>,
+    pub max_scrollback_memory: Option<usize>, // MB
+}
+
+impl ResourceLimits {
+    pub fn from_options(options: &Options) -> Self {
+        ResourceLimits {
+            max_panes_per_tab: options.max_panes_per_tab,
+            max_tabs: options.max_tabs,
+            max_scrollback_memory: options.max_scrollback_memory,
+        }
+    }
+    /// Why a pane cannot be added to a tab that has this many panes, if it cannot
+    pub fn refuse_new_pane(&self, panes_in_tab: usize, scrollback_memory: usize) -> Option<String> {
+        if let Some(max_panes_per_tab) = self.max_panes_per_tab {
+            if panes_in_tab >= max_panes_per_tab {
+                return Some(format!(
+                    "Cannot open a new pane: this tab already has {} panes (max_panes_per_tab is {})",
+                    panes_in_tab, max_panes_per_tab
+                ));
+            }
+        }
+        self.refuse_over_scrollback_memory("pane", scrollback_memory)
+    }
+    /// Why a tab cannot be opened with a layout of this many panes, if it cannot
+    pub fn refuse_new_tab_layout(&self, panes_in_layout: usize) -> Option<String> {
+        let max_panes_per_tab = self.max_panes_per_tab?;
+        if panes_in_layout > max_panes_per_tab {
+            Some(format!(
+                "Cannot open a new tab: its layout has {} panes (max_panes_per_tab is {})",
+                panes_in_layout, max_panes_per_tab
+            ))
+        } else {
+            None
+        }
+    }
+    /// Why a tab cannot be added to a session that has this many tabs, if it cannot
+    pub fn refuse_new_tab(&self, tab_count: usize, scrollback_memory: usize) -> Option<String> {
+        if let Some(max_tabs) = self.max_tabs {
+            if tab_count >= max_tabs {
+                return Some(format!(
+                    "Cannot open a new tab: this session already has {} tabs (max_tabs is {})",
+                    tab_count, max_tabs
+                ));
+            }
+        }
+        self.refuse_over_scrollback_memory("tab", scrollback_memory)
+    }
+    fn refuse_over_scrollback_memory(
+        &self,
+        opening: &str,
+        scrollback_memory: usize,
+    ) -> Option<String> {
+        let max_scrollback_memory = self.max_scrollback_memory?;
+        if scrollback_memory >= max_scrollback_memory.saturating_mul(BYTES_IN_MB) {
+            Some(format!(
+                "Cannot open a new {}: the scrollback of this session already takes {} MB (max_scrollback_memory is {} MB), close some panes or clear their scrollback first",
+                opening,
+                scrollback_memory / BYTES_IN_MB,
+                max_scrollback_memory
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// How many panes a tab opened with this layout counts towards max_panes_per_tab: borderless
+/// panes (eg. the tab-bar and status-bar) are not selectable and are left out, as they are in
+/// the pane count of the tab
+pub fn panes_in_layout(
+    tiled_panes_layout: &TiledPaneLayout,
+    floating_panes_layout: &[FloatingPaneLayout],
+) -> usize {
+    fn selectable_tiled_panes(layout: &TiledPaneLayout) -> usize {
+        if layout.children.is_empty() {
+            if layout.borderless {
+                0
+            } else {
+                1
+            }
+        } else {
+            layout.children.iter().map(selectable_tiled_panes).sum()
+        }
+    }
+    selectable_tiled_panes(tiled_panes_layout) + floating_panes_layout.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panes_and_tabs_are_refused_over_the_limits() {
+        let no_limits = ResourceLimits::default();
+        assert!(no_limits.refuse_new_pane(1000, usize::MAX).is_none());
+        assert!(no_limits.refuse_new_tab(1000, usize::MAX).is_none());
+
+        let limits = ResourceLimits {
+            max_panes_per_tab: Some(4),
+            max_tabs: Some(2),
+            max_scrollback_memory: Some(10),
+        };
+        assert!(limits.refuse_new_pane(3, 0).is_none());
+        assert_eq!(
+            limits.refuse_new_pane(4, 0).unwrap(),
+            "Cannot open a new pane: this tab already has 4 panes (max_panes_per_tab is 4)"
+        );
+        assert!(limits.refuse_new_tab(1, 0).is_none());
+        assert!(limits.refuse_new_tab(2, 0).is_some());
+        assert!(limits.refuse_new_pane(1, 10 * BYTES_IN_MB - 1).is_none());
+        assert!(limits.refuse_new_pane(1, 10 * BYTES_IN_MB).is_some());
+        assert!(limits.refuse_new_tab(1, 12 * BYTES_IN_MB).is_some());
+        assert!(limits.refuse_new_tab_layout(4).is_none());
+        assert_eq!(
+            limits.refuse_new_tab_layout(5).unwrap(),
+            "Cannot open a new tab: its layout has 5 panes (max_panes_per_tab is 4)"
+        );
+        assert!(no_limits.refuse_new_tab_layout(1000).is_none());
+    }
+
+    #[test]
+    fn borderless_panes_of_a_layout_are_not_counted() {
+        let bar = TiledPaneLayout {
+            borderless: true,
+            ..Default::default()
+        };
+        let tiled_panes_layout = TiledPaneLayout {
+            children: vec![
+                bar.clone(),
+                TiledPaneLayout {
+                    children: vec![TiledPaneLayout::default(), TiledPaneLayout::default()],
+                    ..Default::default()
+                },
+                bar,
+            ],
+            ..Default::default()
+        };
+        assert_eq!(panes_in_layout(&tiled_panes_layout, &[]), 2);
+        assert_eq!(
+            panes_in_layout(&tiled_panes_layout, &[FloatingPaneLayout::default()]),
+            3
+        );
+        assert_eq!(panes_in_layout(&TiledPaneLayout::default(), &[]), 1);
+    }
+}
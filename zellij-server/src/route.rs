@@ -15,7 +15,10 @@ use std::time::Duration;
 use uuid::Uuid;
 use zellij_utils::{
     channels::SenderWithContext,
-    data::{Direction, Event, InputMode, PluginCapabilities, ResizeStrategy},
+    data::{
+        BareKey, Direction, Event, InputMode, KeyModifier, KeyWithModifier, PluginCapabilities,
+        ResizeStrategy,
+    },
     errors::prelude::*,
     input::{
         actions::{Action, SearchDirection, SearchOption},
@@ -78,6 +81,23 @@ pub(crate) fn route_action(
                 ))
                 .with_context(err_context)?;
         },
+        Action::WriteRaw(raw_bytes, pane_id) => match pane_id {
+            Some(pane_id) => {
+                senders
+                    .send_to_screen(ScreenInstruction::WriteToPaneId(raw_bytes, pane_id.into()))
+                    .with_context(err_context)?;
+            },
+            None => {
+                senders
+                    .send_to_screen(ScreenInstruction::ClearScroll(client_id))
+                    .with_context(err_context)?;
+                senders
+                    .send_to_screen(ScreenInstruction::WriteCharacter(
+                        None, raw_bytes, false, client_id,
+                    ))
+                    .with_context(err_context)?;
+            },
+        },
         Action::WriteChars(val) => {
             senders
                 .send_to_screen(ScreenInstruction::ClearScroll(client_id))
@@ -176,9 +196,9 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::ClearScreen(client_id))
                 .with_context(err_context)?;
         },
-        Action::DumpScreen(val, full) => {
+        Action::DumpScreen(val, full, ansi) => {
             senders
-                .send_to_screen(ScreenInstruction::DumpScreen(val, client_id, full))
+                .send_to_screen(ScreenInstruction::DumpScreen(val, client_id, full, ansi))
                 .with_context(err_context)?;
         },
         Action::DumpLayout => {
@@ -225,6 +245,21 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::ScrollToTop(client_id))
                 .with_context(err_context)?;
         },
+        Action::ScrollToPreviousPrompt => {
+            senders
+                .send_to_screen(ScreenInstruction::ScrollToPreviousPrompt(client_id))
+                .with_context(err_context)?;
+        },
+        Action::ScrollToNextPrompt => {
+            senders
+                .send_to_screen(ScreenInstruction::ScrollToNextPrompt(client_id))
+                .with_context(err_context)?;
+        },
+        Action::SelectLastCommandOutput => {
+            senders
+                .send_to_screen(ScreenInstruction::SelectLastCommandOutput(client_id))
+                .with_context(err_context)?;
+        },
         Action::PageScrollUp => {
             senders
                 .send_to_screen(ScreenInstruction::PageScrollUp(client_id))
@@ -370,7 +405,7 @@ pub(crate) fn route_action(
         Action::NewFloatingPane(run_command, name, floating_pane_coordinates) => {
             let should_float = true;
             let run_cmd = run_command
-                .map(|cmd| TerminalAction::RunCommand(cmd.into()))
+                .map(|cmd| TerminalAction::RunCommand(cmd.with_requesting_client(client_id).into()))
                 .or_else(|| default_shell.clone());
             senders
                 .send_to_pty(PtyInstruction::SpawnTerminal(
@@ -385,7 +420,7 @@ pub(crate) fn route_action(
         },
         Action::NewInPlacePane(run_command, name) => {
             let run_cmd = run_command
-                .map(|cmd| TerminalAction::RunCommand(cmd.into()))
+                .map(|cmd| TerminalAction::RunCommand(cmd.with_requesting_client(client_id).into()))
                 .or_else(|| default_shell.clone());
             match pane_id {
                 Some(pane_id) => {
@@ -413,7 +448,7 @@ pub(crate) fn route_action(
         Action::NewTiledPane(direction, run_command, name) => {
             let should_float = false;
             let run_cmd = run_command
-                .map(|cmd| TerminalAction::RunCommand(cmd.into()))
+                .map(|cmd| TerminalAction::RunCommand(cmd.with_requesting_client(client_id).into()))
                 .or_else(|| default_shell.clone());
             let pty_instr = match direction {
                 Some(Direction::Left) => {
@@ -521,6 +556,17 @@ pub(crate) fn route_action(
                 ))
                 .with_context(err_context)?;
         },
+        Action::NewTabFromLayout(layout_name, tab_name, cwd) => {
+            senders
+                .send_to_screen(ScreenInstruction::NewTabFromLayout(
+                    layout_name,
+                    tab_name,
+                    cwd,
+                    default_shell.clone(),
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
         Action::GoToNextTab => {
             senders
                 .send_to_screen(ScreenInstruction::SwitchTabNext(client_id))
@@ -628,6 +674,11 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::UpdateSearch(c, client_id))
                 .with_context(err_context)?;
         },
+        Action::CopyModeInput(bytes) => {
+            senders
+                .send_to_screen(ScreenInstruction::CopyModeInput(bytes, client_id))
+                .with_context(err_context)?;
+        },
         Action::Search(d) => {
             let instruction = match d {
                 SearchDirection::Down => ScreenInstruction::SearchDown(client_id),
@@ -644,6 +695,7 @@ pub(crate) fn route_action(
                 },
                 SearchOption::WholeWord => ScreenInstruction::SearchToggleWholeWord(client_id),
                 SearchOption::Wrap => ScreenInstruction::SearchToggleWrap(client_id),
+                SearchOption::Regex => ScreenInstruction::SearchToggleRegex(client_id),
             };
             senders
                 .send_to_screen(instruction)
@@ -917,7 +969,7 @@ pub(crate) fn route_action(
                 log::error!("Message must have a name");
             }
         },
-        Action::ListClients => {
+        Action::ListClients { json } => {
             let default_shell = match default_shell {
                 Some(TerminalAction::RunCommand(run_command)) => Some(run_command.command),
                 _ => None,
@@ -925,6 +977,7 @@ pub(crate) fn route_action(
             senders
                 .send_to_screen(ScreenInstruction::ListClientsMetadata(
                     default_shell,
+                    json,
                     client_id,
                 ))
                 .with_context(err_context)?;
@@ -934,6 +987,320 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::TogglePanePinned(client_id))
                 .with_context(err_context)?;
         },
+        Action::ToggleFloatingPaneScope => {
+            senders
+                .send_to_screen(ScreenInstruction::ToggleFloatingPaneScope(client_id))
+                .with_context(err_context)?;
+        },
+        Action::ToggleDoNotDisturb => {
+            senders
+                .send_to_screen(ScreenInstruction::ToggleDoNotDisturb)
+                .with_context(err_context)?;
+        },
+        Action::SelectSwapLayout(swap_layout_name) => {
+            senders
+                .send_to_screen(ScreenInstruction::SelectSwapLayout(
+                    swap_layout_name,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::QuerySwapLayoutNames => {
+            senders
+                .send_to_screen(ScreenInstruction::QuerySwapLayoutNames(client_id))
+                .with_context(err_context)?;
+        },
+        Action::SetUi(ui_settings) => {
+            senders
+                .send_to_screen(ScreenInstruction::SetUi(ui_settings, client_id))
+                .with_context(err_context)?;
+        },
+        Action::ClosePaneWithId(pane_id) => {
+            let pane_id = pane_id.into();
+            senders
+                .send_to_screen(ScreenInstruction::ClosePane(pane_id, None))
+                .with_context(err_context)?;
+            // as when a plugin closes a pane, its process is killed or its plugin unloaded
+            senders
+                .send_to_pty(PtyInstruction::ClosePane(pane_id))
+                .with_context(err_context)?;
+        },
+        Action::RenamePaneWithId(pane_id, name) => {
+            senders
+                .send_to_screen(ScreenInstruction::RenamePane(
+                    pane_id.into(),
+                    name.as_bytes().to_vec(),
+                ))
+                .with_context(err_context)?;
+        },
+        Action::TogglePaneIdFullscreen(pane_id) => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneIdFullscreen(pane_id.into()))
+                .with_context(err_context)?;
+        },
+        Action::FocusPaneWithId(pane_id) => {
+            let should_float_if_hidden = true;
+            senders
+                .send_to_screen(ScreenInstruction::FocusPaneWithId(
+                    pane_id.into(),
+                    should_float_if_hidden,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::CloseTabWithName(tab_name) => {
+            senders
+                .send_to_screen(ScreenInstruction::CloseTabWithName(tab_name, client_id))
+                .with_context(err_context)?;
+        },
+        Action::RenameTabWithName(tab_name, new_name) => {
+            senders
+                .send_to_screen(ScreenInstruction::RenameTabWithName(
+                    tab_name, new_name, client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::ListPanes { json } => {
+            senders
+                .send_to_screen(ScreenInstruction::ListPanes(json, client_id))
+                .with_context(err_context)?;
+        },
+        Action::SetSessionNote(note) => {
+            senders
+                .send_to_screen(ScreenInstruction::SetSessionNote(note))
+                .with_context(err_context)?;
+        },
+        Action::SetSessionEnv(env_vars) => {
+            senders
+                .send_to_pty(PtyInstruction::SetSessionEnv(env_vars))
+                .with_context(err_context)?;
+        },
+        Action::SetTabCwd(cwd) => {
+            senders
+                .send_to_pty(PtyInstruction::SetTabCwd(cwd, client_id))
+                .with_context(err_context)?;
+        },
+        Action::MovePaneToTab(pane_id, tab_target) => {
+            senders
+                .send_to_screen(ScreenInstruction::MovePaneToTab(
+                    pane_id.map(|p| p.into()),
+                    tab_target,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::JoinTab(tab_index, direction) => {
+            senders
+                .send_to_screen(ScreenInstruction::JoinTab(tab_index, direction, client_id))
+                .with_context(err_context)?;
+        },
+        Action::MovePaneToSession(session_name) => {
+            senders
+                .send_to_screen(ScreenInstruction::MovePaneToSession(
+                    session_name,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::LinkPaneToTab(tab_target) => {
+            senders
+                .send_to_screen(ScreenInstruction::LinkPaneToTab(tab_target, client_id))
+                .with_context(err_context)?;
+        },
+        Action::MoveTabToIndex(tab_index) => {
+            senders
+                .send_to_screen(ScreenInstruction::MoveTabToIndex(tab_index, client_id))
+                .with_context(err_context)?;
+        },
+        Action::DeclarePort(port, name, service_pane_id) => {
+            // the service runs in the pane declaring it unless told otherwise
+            let service_pane_id = service_pane_id.map(|p| p.into()).or(pane_id);
+            senders
+                .send_to_screen(ScreenInstruction::DeclarePort(port, name, service_pane_id))
+                .with_context(err_context)?;
+        },
+        Action::UndeclarePort(port, service_pane_id) => {
+            // like when declaring it, only the port of the service in this pane unless told
+            // otherwise
+            let service_pane_id = service_pane_id.map(|p| p.into()).or(pane_id);
+            senders
+                .send_to_screen(ScreenInstruction::UndeclarePort(port, service_pane_id))
+                .with_context(err_context)?;
+        },
+        Action::ListPorts { json } => {
+            senders
+                .send_to_screen(ScreenInstruction::ListPorts(json, client_id))
+                .with_context(err_context)?;
+        },
+        Action::DumpSession => {
+            senders
+                .send_to_screen(ScreenInstruction::DumpSession(client_id))
+                .with_context(err_context)?;
+        },
+        Action::SearchScrollback {
+            pattern,
+            backwards,
+            pane_id,
+        } => {
+            senders
+                .send_to_screen(ScreenInstruction::SearchScrollback(
+                    pattern,
+                    backwards,
+                    pane_id.map(|p| p.into()),
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::SearchPanes {
+            pattern,
+            all_tabs,
+            json,
+        } => {
+            senders
+                .send_to_screen(ScreenInstruction::SearchPanes(
+                    pattern, all_tabs, json, client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::ScrollToLine { line, pane_id } => {
+            senders
+                .send_to_screen(ScreenInstruction::ScrollPaneToLine(
+                    line,
+                    pane_id.map(|p| p.into()),
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::TogglePaneLogging { pane_id } => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneLogging(
+                    pane_id.map(|p| p.into()),
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::RerunCommandPane { pane_id } => {
+            senders
+                .send_to_screen(ScreenInstruction::RerunCommandPaneOrFocused(
+                    pane_id.map(|p| p.into()),
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::UndoClosePane => {
+            senders
+                .send_to_screen(ScreenInstruction::UndoClosePane(client_id))
+                .with_context(err_context)?;
+        },
+        Action::DuplicatePane(direction) => {
+            senders
+                .send_to_screen(ScreenInstruction::DuplicatePane(
+                    direction,
+                    default_shell.clone(),
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::OpenCommandHistory { pane_id } => {
+            senders
+                .send_to_screen(ScreenInstruction::OpenCommandHistory(
+                    pane_id.map(|p| p.into()),
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::ToggleActivityMonitor { pane_id, notify } => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneActivityMonitor(
+                    pane_id.map(|p| p.into()),
+                    notify,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::ToggleSilenceMonitor {
+            pane_id,
+            seconds,
+            notify,
+        } => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneSilenceMonitor(
+                    pane_id.map(|p| p.into()),
+                    seconds,
+                    notify,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::TogglePaneInGroup { group, pane_id } => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneInGroup(
+                    group,
+                    pane_id.map(|p| p.into()),
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::MultiExec {
+            chars,
+            group,
+            exclude_focused,
+        } => {
+            senders
+                .send_to_screen(ScreenInstruction::MultiExec(
+                    chars,
+                    group,
+                    exclude_focused,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::ApplyToPaneGroup { group, action } => {
+            senders
+                .send_to_screen(ScreenInstruction::ApplyToPaneGroup(
+                    group, action, client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::ToggleRawPassthrough => {
+            senders
+                .send_to_server(ServerInstruction::ToggleRawPassthrough(client_id))
+                .with_context(err_context)?;
+        },
+        Action::ToggleMouseReportingOverride => {
+            senders
+                .send_to_screen(ScreenInstruction::ToggleMouseReportingOverride(client_id))
+                .with_context(err_context)?;
+        },
+        Action::TogglePaneInputLock => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneInputLock(client_id))
+                .with_context(err_context)?;
+        },
+        Action::ToggleExpandStack => {
+            senders
+                .send_to_screen(ScreenInstruction::ToggleExpandStack(client_id))
+                .with_context(err_context)?;
+        },
+        Action::MovePaneInStack(direction) => {
+            senders
+                .send_to_screen(ScreenInstruction::MovePaneInStack(direction, client_id))
+                .with_context(err_context)?;
+        },
+        Action::FocusStackedPaneByTitle(title) => {
+            senders
+                .send_to_screen(ScreenInstruction::FocusStackedPaneByTitle(title, client_id))
+                .with_context(err_context)?;
+        },
+        Action::ReplacePaneCommand(run_command, keep_scrollback) => {
+            senders
+                .send_to_screen(ScreenInstruction::ReplacePaneCommand(
+                    run_command.with_requesting_client(client_id).into(),
+                    keep_scrollback,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
         Action::StackPanes(pane_ids_to_stack) => {
             senders
                 .send_to_screen(ScreenInstruction::StackPanes(
@@ -953,6 +1320,88 @@ pub(crate) fn route_action(
     Ok(should_break)
 }
 
+// so that clients can be detached once they are idle, see the idle_client_detach_timeout option
+fn report_client_input(session_metadata: &SessionMetaData, client_id: ClientId) -> Result<()> {
+    let detaches_idle_clients = session_metadata
+        .session_configuration
+        .get_client_configuration(&client_id)
+        .options
+        .idle_client_detach_timeout
+        .is_some();
+    if detaches_idle_clients {
+        session_metadata
+            .senders
+            .send_to_screen(ScreenInstruction::ClientInput(client_id))?;
+    }
+    Ok(())
+}
+
+// refuses the actions opening a pane in a tab over the resource limits of the session before its
+// process is spawned, telling the client why (see the max_panes_per_tab and max_scrollback_memory
+// options)
+fn refuse_new_pane(
+    session_metadata: &SessionMetaData,
+    action: &Action,
+    client_id: ClientId,
+) -> Result<bool> {
+    let opens_new_pane = match action {
+        Action::NewPane(..)
+        | Action::NewFloatingPane(..)
+        | Action::NewTiledPane(..)
+        | Action::Run(..)
+        | Action::NewTiledPluginPane(..)
+        | Action::NewFloatingPluginPane(..)
+        | Action::UndoClosePane
+        | Action::DuplicatePane(..) => true,
+        Action::EditFile(_, _, _, should_open_in_place, _, _) => !should_open_in_place,
+        Action::LaunchPlugin(_, _, should_open_in_place, _, _) => !should_open_in_place,
+        _ => false,
+    };
+    if !opens_new_pane {
+        return Ok(false);
+    }
+    match session_metadata.refused_new_panes.get(&client_id) {
+        Some(refusal) => {
+            log::warn!("{}", refusal);
+            session_metadata
+                .senders
+                .send_to_server(ServerInstruction::LogError(
+                    vec![refusal.clone()],
+                    client_id,
+                ))?;
+            session_metadata
+                .senders
+                .send_to_server(ServerInstruction::UnblockInputThread)?;
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+// the bytes of the keys driving the keyboard selection of scroll mode (see panes::copy_mode), which
+// are sent to it rather than to their bindings once a selection is in progress so that eg. `e`,
+// `h`, `l` or `Esc` move it instead of leaving scroll mode
+fn copy_mode_key_bytes(key: &KeyWithModifier) -> Option<Vec<u8>> {
+    match key.bare_key {
+        BareKey::Char(c) if c.is_ascii() && !c.is_ascii_control() => {
+            if key.has_no_modifiers() {
+                Some(vec![c as u8])
+            } else if key.key_modifiers.len() == 1 && key.has_modifiers(&[KeyModifier::Shift]) {
+                Some(vec![c.to_ascii_uppercase() as u8])
+            } else if key.key_modifiers.len() == 1
+                && key.has_modifiers(&[KeyModifier::Ctrl])
+                && c == 'v'
+            {
+                Some(vec![0x16])
+            } else {
+                None
+            }
+        },
+        BareKey::Esc if key.has_no_modifiers() => Some(vec![0x1b]),
+        _ => None,
+    }
+}
+
 // this should only be used for one-off startup instructions
 macro_rules! send_to_screen_or_retry_queue {
     ($rlocked_sessions:expr, $message:expr, $instruction: expr, $retry_queue:expr) => {{
@@ -969,6 +1418,45 @@ macro_rules! send_to_screen_or_retry_queue {
     }};
 }
 
+fn client_is_read_only(session_state: &Arc<RwLock<SessionState>>, client_id: ClientId) -> bool {
+    session_state
+        .read()
+        .map(|session_state| session_state.client_is_read_only(&client_id))
+        .unwrap_or(false)
+}
+
+/// Whether a read-only client may send this message: viewers can look at the session and tell us
+/// about their own terminal, but not change the session
+fn read_only_client_may_send(msg: &ClientToServerMsg) -> bool {
+    match msg {
+        ClientToServerMsg::TerminalPixelDimensions(..)
+        | ClientToServerMsg::BackgroundColor(..)
+        | ClientToServerMsg::ForegroundColor(..)
+        | ClientToServerMsg::ColorRegisters(..)
+        | ClientToServerMsg::TerminalResize(..)
+        | ClientToServerMsg::TerminalFocusChanged(..)
+        | ClientToServerMsg::TerminalSixelSupport(..)
+        | ClientToServerMsg::TerminalKittyGraphicsSupport
+        | ClientToServerMsg::TerminalColorSupport(..)
+        | ClientToServerMsg::NewClient(..)
+        | ClientToServerMsg::AttachClient(..)
+        | ClientToServerMsg::ClientExited
+        | ClientToServerMsg::ConnStatus
+        | ClientToServerMsg::QuerySessionStats
+        | ClientToServerMsg::SubscribeToEvents
+        | ClientToServerMsg::ConfigWrittenToDisk(..)
+        | ClientToServerMsg::FailedToWriteConfigToDisk(..) => true,
+        ClientToServerMsg::Action(..)
+        | ClientToServerMsg::Key(..)
+        | ClientToServerMsg::KillSession
+        | ClientToServerMsg::SignalPanes(..)
+        | ClientToServerMsg::WriteStdinToPane(..)
+        | ClientToServerMsg::CloseStdinPipe(..)
+        | ClientToServerMsg::DetachSession(..)
+        | ClientToServerMsg::AdoptPane(..) => false,
+    }
+}
+
 pub(crate) fn route_thread_main(
     session_data: Arc<RwLock<Option<SessionMetaData>>>,
     session_state: Arc<RwLock<SessionState>>,
@@ -990,22 +1478,62 @@ pub(crate) fn route_thread_main(
                 >|
                  -> Result<bool> {
                     let mut should_break = false;
+                    if !read_only_client_may_send(&instruction)
+                        && client_is_read_only(&session_state, client_id)
+                    {
+                        log::warn!("Rejected message of read-only client {}", client_id);
+                        return Ok(should_break);
+                    }
                     let rlocked_sessions =
                         session_data.read().to_anyhow().with_context(err_context)?;
                     match instruction {
                         ClientToServerMsg::Key(key, raw_bytes, is_kitty_keyboard_protocol) => {
                             if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
+                                report_client_input(rlocked_sessions, client_id)
+                                    .with_context(err_context)?;
                                 match rlocked_sessions.get_client_keybinds_and_mode(&client_id) {
                                     Some((keybinds, input_mode, default_input_mode)) => {
-                                        for action in keybinds
-                                            .get_actions_for_key_in_mode_or_default_action(
-                                                &input_mode,
-                                                &key,
+                                        let copy_mode_key_bytes = if input_mode == InputMode::Scroll
+                                            && rlocked_sessions
+                                                .copy_mode_clients
+                                                .contains(&client_id)
+                                        {
+                                            copy_mode_key_bytes(&key)
+                                        } else {
+                                            None
+                                        };
+                                        let mut actions = match copy_mode_key_bytes {
+                                            Some(bytes) => vec![Action::CopyModeInput(bytes)],
+                                            None => keybinds
+                                                .get_actions_for_key_in_mode_or_default_action(
+                                                    &input_mode,
+                                                    &key,
+                                                    raw_bytes.clone(),
+                                                    default_input_mode,
+                                                    is_kitty_keyboard_protocol,
+                                                ),
+                                        };
+                                        if rlocked_sessions
+                                            .raw_passthrough_clients
+                                            .contains(&client_id)
+                                            && !actions.contains(&Action::ToggleRawPassthrough)
+                                        {
+                                            // the only key we intercept in raw passthrough is
+                                            // the one toggling it off
+                                            actions = vec![Action::Write(
+                                                Some(key),
                                                 raw_bytes,
-                                                default_input_mode,
                                                 is_kitty_keyboard_protocol,
-                                            )
-                                        {
+                                            )];
+                                        }
+                                        for action in actions {
+                                            if refuse_new_pane(
+                                                rlocked_sessions,
+                                                &action,
+                                                client_id,
+                                            )? {
+                                                continue;
+                                            }
                                             if route_action(
                                                 action,
                                                 client_id,
@@ -1036,8 +1564,19 @@ pub(crate) fn route_thread_main(
                             }
                         },
                         ClientToServerMsg::Action(action, maybe_pane_id, maybe_client_id) => {
+                            if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
+                                // eg. mouse events, rather than actions of cli clients on behalf
+                                // of this client
+                                if maybe_client_id.is_none() {
+                                    report_client_input(rlocked_sessions, client_id)
+                                        .with_context(err_context)?;
+                                }
+                            }
                             let client_id = maybe_client_id.unwrap_or(client_id);
                             if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
+                                if refuse_new_pane(rlocked_sessions, &action, client_id)? {
+                                    return Ok(should_break);
+                                }
                                 if route_action(
                                     action,
                                     client_id,
@@ -1087,6 +1626,16 @@ pub(crate) fn route_thread_main(
                                 })
                                 .with_context(err_context)?;
                         },
+                        ClientToServerMsg::TerminalFocusChanged(is_focused) => {
+                            if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
+                                rlocked_sessions
+                                    .senders
+                                    .send_to_screen(ScreenInstruction::ClientTerminalFocusChanged(
+                                        client_id, is_focused,
+                                    ))
+                                    .with_context(err_context)?;
+                            }
+                        },
                         ClientToServerMsg::TerminalPixelDimensions(pixel_dimensions) => {
                             send_to_screen_or_retry_queue!(
                                 rlocked_sessions,
@@ -1118,6 +1667,33 @@ pub(crate) fn route_thread_main(
                             )
                             .with_context(err_context)?;
                         },
+                        ClientToServerMsg::TerminalSixelSupport(supports_sixel) => {
+                            send_to_screen_or_retry_queue!(
+                                rlocked_sessions,
+                                ScreenInstruction::ClientSixelSupport(client_id, supports_sixel),
+                                instruction,
+                                retry_queue
+                            )
+                            .with_context(err_context)?;
+                        },
+                        ClientToServerMsg::TerminalKittyGraphicsSupport => {
+                            send_to_screen_or_retry_queue!(
+                                rlocked_sessions,
+                                ScreenInstruction::ClientKittyGraphicsSupport(client_id),
+                                instruction,
+                                retry_queue
+                            )
+                            .with_context(err_context)?;
+                        },
+                        ClientToServerMsg::TerminalColorSupport(color_support) => {
+                            send_to_screen_or_retry_queue!(
+                                rlocked_sessions,
+                                ScreenInstruction::ClientColorSupport(client_id, color_support),
+                                instruction,
+                                retry_queue
+                            )
+                            .with_context(err_context)?;
+                        },
                         ClientToServerMsg::ColorRegisters(ref color_registers) => {
                             send_to_screen_or_retry_queue!(
                                 rlocked_sessions,
@@ -1157,6 +1733,13 @@ pub(crate) fn route_thread_main(
                             tab_position_to_focus,
                             pane_id_to_focus,
                         ) => {
+                            // set here rather than in the server thread, so that it applies to
+                            // the very next message of this client
+                            session_state
+                                .write()
+                                .to_anyhow()
+                                .with_context(err_context)?
+                                .set_client_read_only(client_id, client_attributes.read_only);
                             let attach_client_instruction = ServerInstruction::AttachClient(
                                 client_attributes,
                                 config,
@@ -1184,6 +1767,44 @@ pub(crate) fn route_thread_main(
                             let _ = to_server.send(ServerInstruction::ConnStatus(client_id));
                             should_break = true;
                         },
+                        ClientToServerMsg::QuerySessionStats => {
+                            let _ = to_server.send(ServerInstruction::QuerySessionStats(client_id));
+                            should_break = true;
+                        },
+                        ClientToServerMsg::SignalPanes(signal) => {
+                            let _ =
+                                to_server.send(ServerInstruction::SignalPanes(signal, client_id));
+                            should_break = true;
+                        },
+                        ClientToServerMsg::SubscribeToEvents => {
+                            let _ = to_server.send(ServerInstruction::SubscribeToEvents(client_id));
+                        },
+                        ClientToServerMsg::WriteStdinToPane(terminal_id, bytes) => {
+                            // written on this client's own thread: a command reading slowly only
+                            // holds up the client piping to it
+                            match os_input.write_to_stdin_pipe(terminal_id, client_id, &bytes) {
+                                Ok(()) => {
+                                    let _ = os_input.send_to_client(
+                                        client_id,
+                                        ServerToClientMsg::StdinPipeWritten,
+                                    );
+                                },
+                                Err(e) => {
+                                    // eg. the command exited or stopped reading its STDIN
+                                    log::error!("{:?}", e);
+                                    let _ = os_input.close_stdin_pipe(terminal_id, client_id);
+                                    let _ = os_input.send_to_client(
+                                        client_id,
+                                        ServerToClientMsg::Exit(ExitReason::Normal),
+                                    );
+                                },
+                            }
+                        },
+                        ClientToServerMsg::CloseStdinPipe(terminal_id) => {
+                            os_input
+                                .close_stdin_pipe(terminal_id, client_id)
+                                .with_context(err_context)?;
+                        },
                         ClientToServerMsg::DetachSession(client_id) => {
                             let _ = to_server.send(ServerInstruction::DetachSession(client_id));
                             should_break = true;
@@ -1198,6 +1819,29 @@ pub(crate) fn route_thread_main(
                                 failed_path,
                             ));
                         },
+                        ClientToServerMsg::AdoptPane(moved_pane, child_pid) => {
+                            // sent by the server of another session, which sends the pty of the
+                            // pane once we are ready to receive it
+                            if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
+                                os_input
+                                    .send_to_client(client_id, ServerToClientMsg::ReadyToAdoptPane)
+                                    .with_context(err_context)?;
+                                match receiver.recv_fd() {
+                                    Ok(pty_fd) => {
+                                        rlocked_sessions
+                                            .senders
+                                            .send_to_pty(PtyInstruction::AdoptPane(
+                                                pty_fd,
+                                                child_pid,
+                                                Box::new(moved_pane),
+                                                client_id,
+                                            ))
+                                            .with_context(err_context)?;
+                                    },
+                                    Err(e) => log::error!("Failed to adopt pane: {:?}", e),
+                                }
+                            }
+                        },
                     }
                     Ok(should_break)
                 };
@@ -1233,3 +1877,7 @@ pub(crate) fn route_thread_main(
     }
     Ok(())
 }
+
+#[cfg(test)]
+#[path = "./unit/route_tests.rs"]
+mod route_tests;
@@ -2,47 +2,60 @@
 
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use chrono::{Local, Timelike};
 use log::{debug, warn};
 use zellij_utils::data::{
-    Direction, KeyWithModifier, PaneManifest, PluginPermission, Resize, ResizeStrategy,
+    Direction, KeyWithModifier, LayoutInfo, PaneManifest, PluginPermission, Resize, ResizeStrategy,
     SessionInfo, Styling,
 };
 use zellij_utils::errors::prelude::*;
+use zellij_utils::input::actions::{BarVisibility, PaneGroupAction, TabTarget, UiSettings};
 use zellij_utils::input::command::RunCommand;
 use zellij_utils::input::config::Config;
 use zellij_utils::input::keybinds::Keybinds;
 use zellij_utils::input::mouse::MouseEvent;
-use zellij_utils::input::options::Clipboard;
+use zellij_utils::input::options::{
+    BellForwarding, Clipboard, CommandExitBanner, CopyProvider, QuietHours, TitlePrecedence,
+    UpdateCheck,
+};
 use zellij_utils::pane_size::{Size, SizeInPixels};
 use zellij_utils::{
-    consts::{session_info_folder_for_session, ZELLIJ_SOCK_DIR},
+    consts::{pane_logs_folder_for_session, ZELLIJ_SOCK_DIR},
     envs::set_session_name,
-    input::command::TerminalAction,
+    input::command::{OpenFilePayload, TerminalAction},
     input::layout::{
         FloatingPaneLayout, Layout, Run, RunPluginOrAlias, SwapFloatingLayout, SwapTiledLayout,
         TiledPaneLayout,
     },
+    ipc::PaneSignal,
     position::Position,
+    regex::Regex,
+    serde_json,
 };
 
 use crate::background_jobs::BackgroundJob;
-use crate::os_input_output::ResizeCache;
+use crate::os_input_output::{ProcessStats, ResizeCache};
 use crate::panes::alacritty_functions::xparse_color;
 use crate::panes::terminal_character::AnsiCode;
 use crate::session_layout_metadata::{PaneLayoutMetadata, SessionLayoutMetadata};
 
 use crate::{
     output::Output,
+    panes::closed_panes::ClosedPane,
+    panes::command_history::command_line,
+    panes::grid::{AlternateScreenBehavior, UnicodeWidth, DEFAULT_WORD_SEPARATORS},
+    panes::output_monitor::MonitorAlert,
     panes::sixel::SixelImageStore,
     panes::PaneId,
     plugins::{PluginId, PluginInstruction, PluginRenderAsset},
     pty::{get_default_shell, ClientTabIndexOrPaneId, PtyInstruction, VteBytes},
-    tab::{SuppressedPanes, Tab},
+    resource_limits::{panes_in_layout, ResourceLimits},
+    tab::{CloseTarget, Pane, PaneGroup, SuppressedPanes, Tab},
     thread_bus::Bus,
     ui::{
         loading_indication::LoadingIndication,
@@ -57,7 +70,7 @@ use zellij_utils::{
     },
     errors::{ContextType, ScreenContext},
     input::get_mode_info,
-    ipc::{ClientAttributes, PixelDimensions, ServerToClientMsg},
+    ipc::{ClientAttributes, ColorSupport, MovedPane, PixelDimensions, ServerToClientMsg},
 };
 
 /// Get the active tab and call a closure on it
@@ -183,8 +196,8 @@ pub enum ScreenInstruction {
     MovePaneLeft(ClientId),
     Exit,
     ClearScreen(ClientId),
-    DumpScreen(String, ClientId, bool),
-    DumpLayout(Option<PathBuf>, ClientId), // PathBuf is the default configured
+    DumpScreen(String, ClientId, bool, bool), // bools are full and ansi
+    DumpLayout(Option<PathBuf>, ClientId),    // PathBuf is the default configured
     // shell
     DumpLayoutToPlugin(PluginId),
     EditScrollback(ClientId),
@@ -200,6 +213,9 @@ pub enum ScreenInstruction {
     HalfPageScrollDown(ClientId),
     ClearScroll(ClientId),
     CloseFocusedPane(ClientId),
+    CloseConfirmed(CloseTarget),
+    AskToConfirmClose(CloseTarget, ClientId, Vec<String>), // the programs still running in the
+    // target, it is closed right away if there are none
     ToggleActiveTerminalFullscreen(ClientId),
     TogglePaneFrames,
     SetSelectable(PaneId, bool),
@@ -217,6 +233,13 @@ pub enum ScreenInstruction {
         bool,                                            // should_change_focus_to_new_tab
         ClientId,
     ),
+    NewTabFromLayout(
+        String,         // the layout name
+        Option<String>, // the tab name
+        Option<PathBuf>,
+        Option<TerminalAction>,
+        ClientId,
+    ),
     ApplyLayout(
         TiledPaneLayout,
         Vec<FloatingPaneLayout>,
@@ -264,10 +287,12 @@ pub enum ScreenInstruction {
     ConfirmPrompt(ClientId),
     DenyPrompt(ClientId),
     UpdateSearch(Vec<u8>, ClientId),
+    CopyModeInput(Vec<u8>, ClientId),
     SearchDown(ClientId),
     SearchUp(ClientId),
     SearchToggleCaseSensitivity(ClientId),
     SearchToggleWholeWord(ClientId),
+    SearchToggleRegex(ClientId),
     SearchToggleWrap(ClientId),
     AddRedPaneFrameColorOverride(Vec<PaneId>, Option<String>), // Option<String> => optional error text
     ClearPaneFrameColorOverride(Vec<PaneId>),
@@ -340,6 +365,11 @@ pub enum ScreenInstruction {
     BreakPane(Box<Layout>, Option<TerminalAction>, ClientId),
     BreakPaneRight(ClientId),
     BreakPaneLeft(ClientId),
+    JoinTab(usize, Option<Direction>, ClientId), // usize is the tab index, starting at 1
+    MovePaneToSession(String, ClientId),         // String is the session name
+    SerializeMovedPane(u32, String),             // terminal id, the session name
+    PaneMovedToSession(u32),                     // terminal id
+    AdoptPane(u32, Box<MovedPane>, ClientId),    // terminal id
     UpdateSessionInfos(
         BTreeMap<String, SessionInfo>, // String is the session name
         BTreeMap<String, Duration>,    // resurrectable sessions - <name, created>
@@ -354,7 +384,7 @@ pub enum ScreenInstruction {
     ),
     DumpLayoutToHd,
     RenameSession(String, ClientId), // String -> new name
-    ListClientsMetadata(Option<PathBuf>, ClientId), // Option<PathBuf> - default shell
+    ListClientsMetadata(Option<PathBuf>, bool, ClientId), // Option<PathBuf> - default shell, bool - as json
     Reconfigure {
         client_id: ClientId,
         keybinds: Keybinds,
@@ -364,13 +394,32 @@ pub enum ScreenInstruction {
         default_shell: Option<PathBuf>,
         pane_frames: bool,
         copy_command: Option<String>,
+        copy_providers: Option<Vec<CopyProvider>>,
         copy_to_clipboard: Option<Clipboard>,
         copy_on_select: bool,
+        osc52_forwarding: bool,
+        word_separators: Option<String>,
         auto_layout: bool,
         rounded_corners: bool,
         hide_session_name: bool,
         stacked_resize: bool,
         default_editor: Option<PathBuf>,
+        quiet_hours: Option<QuietHours>,
+        title_precedence: TitlePrecedence,
+        tab_name_from_title: bool,
+        bell_forwarding: BellForwarding,
+        command_exit_banner: CommandExitBanner,
+        idle_client_detach_timeout: Option<u64>, // minutes
+        idle_session_timeout: Option<u64>,       // minutes
+        mirror_session: bool,
+        global_floating_panes: bool,
+        automatic_tab_rename: bool,
+        pane_frame_process_stats: bool,
+        confirm_close_with_running_processes: bool,
+        command_exit_notification_command: Option<String>,
+        resource_limits: ResourceLimits,
+        alternate_screen_behavior: AlternateScreenBehavior,
+        unicode_width: UnicodeWidth,
     },
     RerunCommandPane(u32), // u32 - terminal pane id
     ResizePaneWithId(ResizeStrategy, PaneId),
@@ -403,9 +452,71 @@ pub enum ScreenInstruction {
     },
     ListClientsToPlugin(PluginId, ClientId),
     TogglePanePinned(ClientId),
+    ToggleFloatingPaneScope(ClientId),
     SetFloatingPanePinned(PaneId, bool),
     StackPanes(Vec<PaneId>),
+    ToggleExpandStack(ClientId),
+    MovePaneInStack(Direction, ClientId),
+    FocusStackedPaneByTitle(String, ClientId),
+    ReplacePaneCommand(RunCommand, bool, ClientId), // bool - keep scrollback
+    StopCommandTimedOut(u32, PaneSignal),           // terminal_id, the signal it did not exit after
+    TogglePaneInputLock(ClientId),
+    ScrollToPreviousPrompt(ClientId),
+    ScrollToNextPrompt(ClientId),
+    SelectLastCommandOutput(ClientId),
     ChangeFloatingPanesCoordinates(Vec<(PaneId, FloatingPaneCoordinates)>),
+    QuerySessionStats(ClientId),
+    ClientTerminalFocusChanged(ClientId, bool), // bool -> is_focused
+    ClientSixelSupport(ClientId, bool),
+    ClientKittyGraphicsSupport(ClientId),
+    ClientColorSupport(ClientId, ColorSupport),
+    ToggleMouseReportingOverride(ClientId),
+    CapturePaneOutput(u32, ClientId, Option<usize>), // u32 - terminal id, Option<usize> - only
+    // send these last lines once the command exits
+    CapturedPaneExited(u32, Option<i32>), // u32 - terminal id, Option<i32> - exit status
+    ToggleDoNotDisturb,
+    SelectSwapLayout(String, ClientId),
+    QuerySwapLayoutNames(ClientId),
+    ReloadLayout(Box<Layout>),
+    SetUi(UiSettings, ClientId),
+    CloseTabWithName(String, ClientId),
+    RenameTabWithName(String, String, ClientId), // current name, new name
+    ListPanes(bool, ClientId),                   // bool - as json
+    SetSessionNote(String),
+    MovePaneToTab(Option<PaneId>, TabTarget, ClientId),
+    LinkPaneToTab(TabTarget, ClientId),
+    MoveTabToIndex(usize, ClientId), // 1 based index
+    UpdateAvailableVersions(Option<String>, Option<String>), // installed version, latest release
+    DeclarePort(u16, Option<String>, Option<PaneId>), // port, name, pane of the service
+    UndeclarePort(u16, Option<PaneId>), // port, pane of the service it was declared for
+    ListPorts(bool, ClientId),       // bool - as json
+    DumpSession(ClientId),
+    SearchScrollback(String, bool, Option<PaneId>, ClientId), // String - regex, bool - backwards
+    SearchPanes(String, bool, bool, ClientId), // String - regex, bool - all tabs, bool - as json
+    ScrollPaneToLine(usize, Option<PaneId>, ClientId),
+    TogglePaneLogging(Option<PaneId>, ClientId),
+    TogglePaneInGroup(String, Option<PaneId>, ClientId),
+    ApplyToPaneGroup(String, PaneGroupAction, ClientId),
+    PublishSessionEvents(bool), // whether there are hooks or clients following the session's events
+    MultiExec(String, Option<String>, bool, ClientId), // chars, group, exclude focused
+    PaneTitleChanged(PaneId, String), // title set by the program in the pane (OSC 0/2)
+    TogglePaneActivityMonitor(Option<PaneId>, bool, ClientId), // bool - should notify
+    TogglePaneSilenceMonitor(Option<PaneId>, u64, bool, ClientId), // u64 - seconds, bool - should notify
+    PaneMonitorAlert(PaneId, MonitorAlert, bool),                  // bool - should notify
+    CheckPaneSilence,
+    PaneBell(PaneId), // rung while the pane was not focused
+    RerunCommandPaneOrFocused(Option<PaneId>, ClientId),
+    OpenCommandHistory(Option<PaneId>, ClientId),
+    UndoClosePane(ClientId),
+    DuplicatePane(Option<Direction>, Option<TerminalAction>, ClientId), // the default shell
+    ClosedPaneRespawned(u32, Option<RunCommand>, Box<ClosedPane>, ClientId), // terminal id, the
+    // command to hold the pane with if it starts held
+    WatchedFilesChanged(u32), // terminal id
+    ClientInput(ClientId),
+    CheckIdleClients,
+    CheckIdleSession,
+    UpdateRunningCommands(HashMap<u32, String>), // terminal id => command in its foreground
+    UpdateProcessStats(HashMap<u32, ProcessStats>), // terminal id => stats of its processes
 }
 
 impl From<&ScreenInstruction> for ScreenContext {
@@ -482,6 +593,8 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::HalfPageScrollDown(..) => ScreenContext::HalfPageScrollDown,
             ScreenInstruction::ClearScroll(..) => ScreenContext::ClearScroll,
             ScreenInstruction::CloseFocusedPane(..) => ScreenContext::CloseFocusedPane,
+            ScreenInstruction::CloseConfirmed(..) => ScreenContext::CloseConfirmed,
+            ScreenInstruction::AskToConfirmClose(..) => ScreenContext::AskToConfirmClose,
             ScreenInstruction::ToggleActiveTerminalFullscreen(..) => {
                 ScreenContext::ToggleActiveTerminalFullscreen
             },
@@ -492,6 +605,7 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::UpdatePaneName(..) => ScreenContext::UpdatePaneName,
             ScreenInstruction::UndoRenamePane(..) => ScreenContext::UndoRenamePane,
             ScreenInstruction::NewTab(..) => ScreenContext::NewTab,
+            ScreenInstruction::NewTabFromLayout(..) => ScreenContext::NewTabFromLayout,
             ScreenInstruction::ApplyLayout(..) => ScreenContext::ApplyLayout,
             ScreenInstruction::SwitchTabNext(..) => ScreenContext::SwitchTabNext,
             ScreenInstruction::SwitchTabPrev(..) => ScreenContext::SwitchTabPrev,
@@ -530,12 +644,14 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::ConfirmPrompt(..) => ScreenContext::ConfirmPrompt,
             ScreenInstruction::DenyPrompt(..) => ScreenContext::DenyPrompt,
             ScreenInstruction::UpdateSearch(..) => ScreenContext::UpdateSearch,
+            ScreenInstruction::CopyModeInput(..) => ScreenContext::CopyModeInput,
             ScreenInstruction::SearchDown(..) => ScreenContext::SearchDown,
             ScreenInstruction::SearchUp(..) => ScreenContext::SearchUp,
             ScreenInstruction::SearchToggleCaseSensitivity(..) => {
                 ScreenContext::SearchToggleCaseSensitivity
             },
             ScreenInstruction::SearchToggleWholeWord(..) => ScreenContext::SearchToggleWholeWord,
+            ScreenInstruction::SearchToggleRegex(..) => ScreenContext::SearchToggleRegex,
             ScreenInstruction::SearchToggleWrap(..) => ScreenContext::SearchToggleWrap,
             ScreenInstruction::AddRedPaneFrameColorOverride(..) => {
                 ScreenContext::AddRedPaneFrameColorOverride
@@ -576,6 +692,11 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::BreakPane(..) => ScreenContext::BreakPane,
             ScreenInstruction::BreakPaneRight(..) => ScreenContext::BreakPaneRight,
             ScreenInstruction::BreakPaneLeft(..) => ScreenContext::BreakPaneLeft,
+            ScreenInstruction::JoinTab(..) => ScreenContext::JoinTab,
+            ScreenInstruction::MovePaneToSession(..) => ScreenContext::MovePaneToSession,
+            ScreenInstruction::SerializeMovedPane(..) => ScreenContext::SerializeMovedPane,
+            ScreenInstruction::PaneMovedToSession(..) => ScreenContext::PaneMovedToSession,
+            ScreenInstruction::AdoptPane(..) => ScreenContext::AdoptPane,
             ScreenInstruction::UpdateSessionInfos(..) => ScreenContext::UpdateSessionInfos,
             ScreenInstruction::ReplacePane(..) => ScreenContext::ReplacePane,
             ScreenInstruction::NewInPlacePluginPane(..) => ScreenContext::NewInPlacePluginPane,
@@ -611,32 +732,127 @@ impl From<&ScreenInstruction> for ScreenContext {
             },
             ScreenInstruction::ListClientsToPlugin(..) => ScreenContext::ListClientsToPlugin,
             ScreenInstruction::TogglePanePinned(..) => ScreenContext::TogglePanePinned,
+            ScreenInstruction::ToggleFloatingPaneScope(..) => {
+                ScreenContext::ToggleFloatingPaneScope
+            },
             ScreenInstruction::SetFloatingPanePinned(..) => ScreenContext::SetFloatingPanePinned,
             ScreenInstruction::StackPanes(..) => ScreenContext::StackPanes,
+            ScreenInstruction::ToggleExpandStack(..) => ScreenContext::ToggleExpandStack,
+            ScreenInstruction::MovePaneInStack(..) => ScreenContext::MovePaneInStack,
+            ScreenInstruction::FocusStackedPaneByTitle(..) => {
+                ScreenContext::FocusStackedPaneByTitle
+            },
+            ScreenInstruction::ReplacePaneCommand(..) => ScreenContext::ReplacePaneCommand,
+            ScreenInstruction::StopCommandTimedOut(..) => ScreenContext::StopCommandTimedOut,
+            ScreenInstruction::TogglePaneInputLock(..) => ScreenContext::TogglePaneInputLock,
+            ScreenInstruction::ScrollToPreviousPrompt(..) => ScreenContext::ScrollToPreviousPrompt,
+            ScreenInstruction::ScrollToNextPrompt(..) => ScreenContext::ScrollToNextPrompt,
+            ScreenInstruction::SelectLastCommandOutput(..) => {
+                ScreenContext::SelectLastCommandOutput
+            },
             ScreenInstruction::ChangeFloatingPanesCoordinates(..) => {
                 ScreenContext::ChangeFloatingPanesCoordinates
             },
+            ScreenInstruction::QuerySessionStats(..) => ScreenContext::QuerySessionStats,
+            ScreenInstruction::ClientTerminalFocusChanged(..) => {
+                ScreenContext::ClientTerminalFocusChanged
+            },
+            ScreenInstruction::ClientSixelSupport(..) => ScreenContext::ClientSixelSupport,
+            ScreenInstruction::ClientKittyGraphicsSupport(..) => {
+                ScreenContext::ClientKittyGraphicsSupport
+            },
+            ScreenInstruction::ClientColorSupport(..) => ScreenContext::ClientColorSupport,
+            ScreenInstruction::ToggleMouseReportingOverride(..) => {
+                ScreenContext::ToggleMouseReportingOverride
+            },
+            ScreenInstruction::CapturePaneOutput(..) => ScreenContext::CapturePaneOutput,
+            ScreenInstruction::CapturedPaneExited(..) => ScreenContext::CapturedPaneExited,
+            ScreenInstruction::ToggleDoNotDisturb => ScreenContext::ToggleDoNotDisturb,
+            ScreenInstruction::SelectSwapLayout(..) => ScreenContext::SelectSwapLayout,
+            ScreenInstruction::ReloadLayout(..) => ScreenContext::ReloadLayout,
+            ScreenInstruction::QuerySwapLayoutNames(..) => ScreenContext::QuerySwapLayoutNames,
+            ScreenInstruction::SetUi(..) => ScreenContext::SetUi,
+            ScreenInstruction::CloseTabWithName(..) => ScreenContext::CloseTabWithName,
+            ScreenInstruction::RenameTabWithName(..) => ScreenContext::RenameTabWithName,
+            ScreenInstruction::ListPanes(..) => ScreenContext::ListPanes,
+            ScreenInstruction::SetSessionNote(..) => ScreenContext::SetSessionNote,
+            ScreenInstruction::MovePaneToTab(..) => ScreenContext::MovePaneToTab,
+            ScreenInstruction::LinkPaneToTab(..) => ScreenContext::LinkPaneToTab,
+            ScreenInstruction::MoveTabToIndex(..) => ScreenContext::MoveTabToIndex,
+            ScreenInstruction::UpdateAvailableVersions(..) => {
+                ScreenContext::UpdateAvailableVersions
+            },
+            ScreenInstruction::DeclarePort(..) => ScreenContext::DeclarePort,
+            ScreenInstruction::UndeclarePort(..) => ScreenContext::UndeclarePort,
+            ScreenInstruction::ListPorts(..) => ScreenContext::ListPorts,
+            ScreenInstruction::DumpSession(..) => ScreenContext::DumpSession,
+            ScreenInstruction::SearchScrollback(..) => ScreenContext::SearchScrollback,
+            ScreenInstruction::SearchPanes(..) => ScreenContext::SearchPanes,
+            ScreenInstruction::ScrollPaneToLine(..) => ScreenContext::ScrollPaneToLine,
+            ScreenInstruction::TogglePaneLogging(..) => ScreenContext::TogglePaneLogging,
+            ScreenInstruction::TogglePaneInGroup(..) => ScreenContext::TogglePaneInGroup,
+            ScreenInstruction::ApplyToPaneGroup(..) => ScreenContext::ApplyToPaneGroup,
+            ScreenInstruction::PublishSessionEvents(..) => ScreenContext::PublishSessionEvents,
+            ScreenInstruction::MultiExec(..) => ScreenContext::MultiExec,
+            ScreenInstruction::PaneTitleChanged(..) => ScreenContext::PaneTitleChanged,
+            ScreenInstruction::TogglePaneActivityMonitor(..) => {
+                ScreenContext::TogglePaneActivityMonitor
+            },
+            ScreenInstruction::TogglePaneSilenceMonitor(..) => {
+                ScreenContext::TogglePaneSilenceMonitor
+            },
+            ScreenInstruction::PaneMonitorAlert(..) => ScreenContext::PaneMonitorAlert,
+            ScreenInstruction::CheckPaneSilence => ScreenContext::CheckPaneSilence,
+            ScreenInstruction::PaneBell(..) => ScreenContext::PaneBell,
+            ScreenInstruction::RerunCommandPaneOrFocused(..) => {
+                ScreenContext::RerunCommandPaneOrFocused
+            },
+            ScreenInstruction::OpenCommandHistory(..) => ScreenContext::OpenCommandHistory,
+            ScreenInstruction::UndoClosePane(..) => ScreenContext::UndoClosePane,
+            ScreenInstruction::DuplicatePane(..) => ScreenContext::DuplicatePane,
+            ScreenInstruction::ClosedPaneRespawned(..) => ScreenContext::ClosedPaneRespawned,
+            ScreenInstruction::WatchedFilesChanged(..) => ScreenContext::WatchedFilesChanged,
+            ScreenInstruction::ClientInput(..) => ScreenContext::ClientInput,
+            ScreenInstruction::CheckIdleClients => ScreenContext::CheckIdleClients,
+            ScreenInstruction::CheckIdleSession => ScreenContext::CheckIdleSession,
+            ScreenInstruction::UpdateRunningCommands(..) => ScreenContext::UpdateRunningCommands,
+            ScreenInstruction::UpdateProcessStats(..) => ScreenContext::UpdateProcessStats,
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct DeclaredPort {
+    name: Option<String>,
+    pane_id: Option<PaneId>,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct CopyOptions {
     pub command: Option<String>,
+    pub providers: Vec<CopyProvider>,
     pub clipboard: Clipboard,
     pub copy_on_select: bool,
+    pub osc52_forwarding: bool,
+    pub word_separators: String,
 }
 
 impl CopyOptions {
     pub(crate) fn new(
         copy_command: Option<String>,
+        copy_providers: Vec<CopyProvider>,
         copy_clipboard: Clipboard,
         copy_on_select: bool,
+        osc52_forwarding: bool,
+        word_separators: String,
     ) -> Self {
         Self {
             command: copy_command,
+            providers: copy_providers,
             clipboard: copy_clipboard,
             copy_on_select,
+            osc52_forwarding,
+            word_separators,
         }
     }
 
@@ -644,8 +860,11 @@ impl CopyOptions {
     pub(crate) fn default() -> Self {
         Self {
             command: None,
+            providers: CopyProvider::default_providers(),
             clipboard: Clipboard::default(),
             copy_on_select: true,
+            osc52_forwarding: true,
+            word_separators: DEFAULT_WORD_SEPARATORS.to_owned(),
         }
     }
 }
@@ -665,6 +884,13 @@ pub(crate) struct Screen {
     character_cell_size: Rc<RefCell<Option<SizeInPixels>>>,
     stacked_resize: Rc<RefCell<bool>>,
     sixel_image_store: Rc<RefCell<SixelImageStore>>,
+    /// Clients whose terminal told us it cannot display sixel images, which are not sent to them
+    clients_without_sixel_support: HashSet<ClientId>,
+    /// Clients whose terminal told us it supports the kitty graphics protocol, which is only used
+    /// with them
+    clients_with_kitty_graphics_support: HashSet<ClientId>,
+    /// The colors the terminal of each client told us it can display
+    client_color_support: HashMap<ClientId, ColorSupport>,
     /// The overlay that is drawn on top of [`Pane`]'s', [`Tab`]'s and the [`Screen`]
     overlay: OverlayWindow,
     terminal_emulator_colors: Rc<RefCell<Palette>>,
@@ -685,6 +911,8 @@ pub(crate) struct Screen {
     copy_options: CopyOptions,
     debug: bool,
     session_name: String,
+    // where the ipc socket of the session is, it is renamed along with the session
+    session_socket_dir: PathBuf,
     session_infos_on_machine: BTreeMap<String, SessionInfo>, // String is the session name, can
     // also be this session
     resurrectable_sessions: BTreeMap<String, Duration>, // String is the session name, duration is
@@ -697,6 +925,69 @@ pub(crate) struct Screen {
     default_layout_name: Option<String>,
     explicitly_disable_kitty_keyboard_protocol: bool,
     default_editor: Option<PathBuf>,
+    session_tags: Vec<String>,
+    session_note: Option<String>,
+    // ports that services of this session listen on, for users forwarding them to a remote
+    // session (see `zellij action declare-port`)
+    declared_ports: BTreeMap<u16, DeclaredPort>,
+    quiet_hours: Option<QuietHours>,
+    do_not_disturb: bool,
+    title_precedence: TitlePrecedence,
+    tab_name_from_title: bool,
+    bell_forwarding: BellForwarding,
+    command_exit_banner: CommandExitBanner,
+    // watched commands that were stopped because their files changed, to be re-run once they exit
+    watched_commands_to_rerun: HashSet<u32>,
+    // commands to run in terminals in place of the ones that were stopped for them, and whether
+    // to keep the scrollback of the terminal
+    commands_replacing_stopped_ones: HashMap<u32, (RunCommand, bool)>,
+    idle_client_detach_timeout: Option<Duration>,
+    clients_last_input: HashMap<ClientId, Instant>,
+    // clients that are shown the countdown to being detached for being idle
+    clients_warned_of_idle_detach: HashSet<ClientId>,
+    idle_session_timeout: Option<Duration>,
+    // the last time a client detached or a pane printed something, see idle_session_timeout
+    last_session_activity: Instant,
+    // the idle session was serialized and is killed at the next check if it is still idle
+    idle_session_serialized: bool,
+    // terminal id => the client capturing its output and the number of final lines it wants (if
+    // it doesn't want the output streamed)
+    output_captures: HashMap<u32, (ClientId, Option<usize>)>,
+    // set by the update_check background job, see ModeInfo
+    installed_version: Option<String>,
+    latest_release: Option<String>,
+    // group name => the frame color of its panes (see `zellij action set-group-color`)
+    pane_group_colors: HashMap<String, PaletteColor>,
+    // desktop notifications of monitor alerts and forwarded bells, sent to these clients with the
+    // next render
+    pending_vte_instructions: Vec<(Vec<ClientId>, String)>,
+    // whether floating panes follow the focused tab (the global_floating_panes option), and the
+    // panes for which the ToggleFloatingPaneScope action reversed this
+    floating_panes_are_global: bool,
+    floating_panes_with_toggled_scope: HashSet<PaneId>,
+    // whether tabs are named after their focused pane (the automatic_tab_rename option), and the
+    // commands in the foreground of the terminal panes, by terminal id
+    automatic_tab_rename: bool,
+    running_commands: HashMap<u32, String>,
+    // whether the frames of terminal panes show the stats of their processes (the
+    // pane_frame_process_stats option)
+    pane_frame_process_stats: bool,
+    confirm_close_with_running_processes: bool,
+    command_exit_notification_command: Option<String>,
+    resource_limits: ResourceLimits,
+    // how the widths of the characters printed to panes are counted, the same for all panes since
+    // it has to match the terminal emulators of the clients
+    unicode_width: UnicodeWidth,
+    // why new panes are refused to the clients whose tab is over the resource limits, as last
+    // sent to the server so that their actions are refused before spawning anything
+    refused_new_panes: HashMap<ClientId, String>,
+    // the clients with a keyboard selection in progress in their focused pane, as last sent to the
+    // server so that their keys drive it rather than their bindings
+    copy_mode_clients: HashSet<ClientId>,
+    alternate_screen_behavior: AlternateScreenBehavior,
+    // whether tab and pane updates are sent to the server for hooks and clients following the
+    // session's events (eg. `zellij events`)
+    publish_session_events: bool,
 }
 
 impl Screen {
@@ -723,6 +1014,13 @@ impl Screen {
         explicitly_disable_kitty_keyboard_protocol: bool,
         stacked_resize: bool,
         default_editor: Option<PathBuf>,
+        session_tags: Vec<String>,
+        quiet_hours: Option<QuietHours>,
+        title_precedence: TitlePrecedence,
+        tab_name_from_title: bool,
+        bell_forwarding: BellForwarding,
+        command_exit_banner: CommandExitBanner,
+        idle_client_detach_timeout: Option<u64>,
     ) -> Self {
         let session_name = mode_info.session_name.clone().unwrap_or_default();
         let session_info = SessionInfo::new(session_name.clone());
@@ -753,6 +1051,7 @@ impl Screen {
             copy_options,
             debug,
             session_name,
+            session_socket_dir: ZELLIJ_SOCK_DIR.clone(),
             session_infos_on_machine,
             default_layout,
             default_layout_name,
@@ -766,6 +1065,44 @@ impl Screen {
             layout_dir,
             explicitly_disable_kitty_keyboard_protocol,
             default_editor,
+            session_tags,
+            session_note: None,
+            declared_ports: BTreeMap::new(),
+            quiet_hours,
+            do_not_disturb: false,
+            title_precedence,
+            tab_name_from_title,
+            bell_forwarding,
+            command_exit_banner,
+            watched_commands_to_rerun: HashSet::new(),
+            commands_replacing_stopped_ones: HashMap::new(),
+            idle_client_detach_timeout: idle_timeout_duration(idle_client_detach_timeout),
+            clients_last_input: HashMap::new(),
+            clients_warned_of_idle_detach: HashSet::new(),
+            idle_session_timeout: None,
+            last_session_activity: Instant::now(),
+            idle_session_serialized: false,
+            output_captures: HashMap::new(),
+            clients_without_sixel_support: HashSet::new(),
+            clients_with_kitty_graphics_support: HashSet::new(),
+            client_color_support: HashMap::new(),
+            installed_version: None,
+            latest_release: None,
+            pane_group_colors: HashMap::new(),
+            pending_vte_instructions: vec![],
+            floating_panes_are_global: false,
+            floating_panes_with_toggled_scope: HashSet::new(),
+            automatic_tab_rename: false,
+            running_commands: HashMap::new(),
+            pane_frame_process_stats: false,
+            confirm_close_with_running_processes: false,
+            command_exit_notification_command: None,
+            resource_limits: ResourceLimits::default(),
+            unicode_width: UnicodeWidth::default(),
+            refused_new_panes: HashMap::new(),
+            copy_mode_clients: HashSet::new(),
+            alternate_screen_behavior: AlternateScreenBehavior::default(),
+            publish_session_events: false,
         }
     }
 
@@ -877,6 +1214,8 @@ impl Screen {
             }
             destination_tab.set_force_render();
             destination_tab.visible(true).with_context(err_context)?;
+            self.bring_global_floating_panes_to_tab(destination_tab_index)
+                .with_context(err_context)?;
         }
         Ok(())
     }
@@ -1076,6 +1415,26 @@ impl Screen {
         let err_context = || format!("failed to close tab at index {tab_index:?}");
 
         let mut tab_to_close = self.tabs.remove(&tab_index).with_context(err_context)?;
+
+        // global floating panes are not closed with the tab, but follow the clients to their new
+        // tab
+        let global_floating_pane_ids: Vec<PaneId> = if self.tabs.is_empty() {
+            vec![]
+        } else {
+            tab_to_close
+                .get_floating_panes()
+                .map(|(pane_id, _)| *pane_id)
+                .filter(|pane_id| self.is_global_floating_pane(pane_id))
+                .collect()
+        };
+        let global_floating_panes: Vec<(PaneId, Box<dyn Pane>)> = global_floating_pane_ids
+            .into_iter()
+            .filter_map(|pane_id| {
+                tab_to_close
+                    .extract_pane(pane_id, true)
+                    .map(|pane| (pane_id, pane))
+            })
+            .collect();
         let mut pane_ids = tab_to_close.get_all_pane_ids();
 
         // here we extract the suppressed panes (these are background panes that don't care which
@@ -1085,6 +1444,8 @@ impl Screen {
         for suppressed_pane_id in suppressed_panes.keys() {
             pane_ids.retain(|p| p != suppressed_pane_id);
         }
+        // the ptys of linked views belong to panes in other tabs
+        pane_ids.retain(|p| !tab_to_close.pane_is_linked_view(*p));
 
         let _ = self.bus.senders.send_to_plugin(PluginInstruction::Update(
             pane_ids
@@ -1101,6 +1462,7 @@ impl Screen {
             .senders
             .send_to_pty(PtyInstruction::CloseTab(pane_ids))
             .with_context(err_context)?;
+        self.close_orphaned_linked_views();
         if self.tabs.is_empty() {
             self.active_tab_indices.clear();
             self.bus
@@ -1113,6 +1475,18 @@ impl Screen {
                 .with_context(err_context)?;
             self.move_suppressed_panes_from_closed_tab(suppressed_panes)
                 .with_context(err_context)?;
+            let destination_tab_index = self
+                .active_tab_indices
+                .values()
+                .next()
+                .or_else(|| self.tabs.keys().next())
+                .copied();
+            if let Some(tab) = destination_tab_index.and_then(|i| self.tabs.get_mut(&i)) {
+                for (pane_id, pane) in global_floating_panes {
+                    tab.add_floating_pane_keeping_position(pane, pane_id)
+                        .with_context(err_context)?;
+                }
+            }
             let visible_tab_indices: HashSet<usize> =
                 self.active_tab_indices.values().copied().collect();
             for t in self.tabs.values_mut() {
@@ -1130,6 +1504,143 @@ impl Screen {
         }
     }
 
+    /// If closing the focused pane (or the tab, when closing it) could hang up programs other than
+    /// the shell, has the background jobs look for them in the process table rather than closing
+    /// it right away. It is closed or the user is asked to confirm once they reply with
+    /// AskToConfirmClose. Returns true if it was sent to them.
+    fn find_running_processes_before_close(
+        &mut self,
+        closing_tab: bool,
+        client_id: ClientId,
+    ) -> bool {
+        if !self.confirm_close_with_running_processes {
+            return false;
+        }
+        let active_tab = match self.get_active_tab_mut(client_id) {
+            Ok(active_tab) => active_tab,
+            Err(_) => return false,
+        };
+        let focused_pane_id = match active_tab.get_active_pane_id(client_id) {
+            Some(focused_pane_id) => focused_pane_id,
+            None => return false,
+        };
+        let (close_target, pane_ids) = if closing_tab {
+            (
+                CloseTarget::Tab(active_tab.index),
+                active_tab.get_all_pane_ids(),
+            )
+        } else {
+            (CloseTarget::Pane(focused_pane_id), vec![focused_pane_id])
+        };
+        // closing a linked view does not hang up the programs it shows and the command of a held
+        // pane already exited
+        let terminals: Vec<(u32, bool)> = pane_ids
+            .into_iter()
+            .filter(|pane_id| !active_tab.pane_is_linked_view(*pane_id))
+            .filter_map(|pane_id| match pane_id {
+                PaneId::Terminal(terminal_id) => {
+                    let pane = active_tab.get_pane_with_id(pane_id)?;
+                    if pane.is_held() {
+                        return None;
+                    }
+                    // the process spawned in a command pane is the command itself, rather than
+                    // a shell
+                    let runs_command = matches!(pane.invoked_with(), Some(Run::Command(_)));
+                    Some((terminal_id, runs_command))
+                },
+                PaneId::Plugin(_) => None,
+            })
+            .collect();
+        if terminals.is_empty() {
+            return false;
+        }
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::FindRunningProcesses(
+                close_target,
+                client_id,
+                terminals,
+            ))
+            .is_ok()
+    }
+
+    /// Closes the target, or asks the user to confirm closing it if programs are still running in
+    /// it. This is asked in the pane being closed or, when closing a tab, in the focused pane of
+    /// the client. If that pane cannot ask (eg. it is a plugin pane), the first terminal pane of
+    /// the tab that can is focused to ask instead.
+    fn close_or_ask_to_confirm(
+        &mut self,
+        close_target: CloseTarget,
+        client_id: ClientId,
+        running_processes: Vec<String>,
+    ) -> Result<()> {
+        if running_processes.is_empty() {
+            return self.close_confirmed(close_target);
+        }
+        let tab = match close_target {
+            CloseTarget::Pane(pane_id) => self
+                .tabs
+                .values_mut()
+                .find(|tab| tab.has_pane_with_pid(&pane_id) && !tab.pane_is_linked_view(pane_id)),
+            CloseTarget::Tab(tab_index) => self.tabs.get_mut(&tab_index),
+        };
+        let tab = match tab {
+            Some(tab) => tab,
+            // it was closed in the meantime
+            None => return Ok(()),
+        };
+        let focused_pane_id = tab.get_active_pane_id(client_id);
+        let asking_pane_ids: Vec<PaneId> = match close_target {
+            CloseTarget::Pane(pane_id) => vec![pane_id],
+            CloseTarget::Tab(_) => focused_pane_id
+                .into_iter()
+                .chain(
+                    tab.get_static_and_floating_pane_ids()
+                        .into_iter()
+                        .filter(|pane_id| {
+                            matches!(pane_id, PaneId::Terminal(_))
+                                && !tab.pane_is_linked_view(*pane_id)
+                        }),
+                )
+                .collect(),
+        };
+        for pane_id in asking_pane_ids {
+            let asked = tab
+                .get_pane_with_id_mut(pane_id)
+                .map(|pane| pane.ask_to_confirm_close(close_target, running_processes.clone()))
+                .unwrap_or(false);
+            if asked {
+                if focused_pane_id != Some(pane_id) {
+                    tab.focus_pane_with_id(pane_id, false, client_id)?;
+                }
+                return Ok(());
+            }
+        }
+        // no pane can ask
+        self.close_confirmed(close_target)
+    }
+
+    fn close_confirmed(&mut self, close_target: CloseTarget) -> Result<()> {
+        match close_target {
+            CloseTarget::Pane(pane_id) => {
+                let tab = self.tabs.values_mut().find(|tab| {
+                    tab.has_pane_with_pid(&pane_id) && !tab.pane_is_linked_view(pane_id)
+                });
+                if let Some(tab) = tab {
+                    tab.close_pane_and_its_pty(pane_id)?;
+                }
+                self.close_orphaned_linked_views();
+                Ok(())
+            },
+            CloseTarget::Tab(tab_index) => {
+                if self.tabs.contains_key(&tab_index) {
+                    self.close_tab_at_index(tab_index)?;
+                }
+                Ok(())
+            },
+        }
+    }
+
     // Closes the client_id's focused tab
     pub fn close_tab(&mut self, client_id: ClientId) -> Result<()> {
         let err_context = || format!("failed to close tab for client {client_id:?}");
@@ -1153,854 +1664,1732 @@ impl Screen {
         }
     }
 
-    pub fn resize_to_screen(&mut self, new_screen_size: Size) -> Result<()> {
-        let err_context = || format!("failed to resize to screen size: {new_screen_size:#?}");
+    /// Close the tab with this name, returns false if there is no such tab
+    pub fn close_tab_with_name(&mut self, name: &str) -> Result<bool> {
+        match self.tabs.iter().find(|(_, tab)| tab.name == name) {
+            Some((tab_index, _)) => {
+                let tab_index = *tab_index;
+                self.close_tab_at_index(tab_index)
+                    .with_context(|| format!("failed to close tab with name {name:?}"))?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
 
-        if self.size != new_screen_size {
-            self.size = new_screen_size;
-            for tab in self.tabs.values_mut() {
-                tab.resize_whole_tab(new_screen_size)
-                    .with_context(err_context)?;
-                tab.set_force_render();
+    /// Rename the tab with this name, returns false if there is no such tab
+    pub fn rename_tab_with_name(&mut self, name: &str, new_name: String) -> bool {
+        match self.tabs.values_mut().find(|tab| tab.name == name) {
+            Some(tab) => {
+                tab.name = new_name;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Render the panes of all tabs, ordered by tab position, for `zellij action list-panes`
+    pub fn render_pane_list(&self, json: bool) -> String {
+        let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+        tabs.sort_by_key(|tab| tab.position);
+        let mut panes = vec![];
+        for tab in tabs {
+            for pane_info in tab.pane_infos() {
+                let pane_id = if pane_info.is_plugin {
+                    format!("plugin_{}", pane_info.id)
+                } else {
+                    format!("terminal_{}", pane_info.id)
+                };
+                panes.push((pane_id, tab.position, tab.name.clone(), pane_info));
             }
-            self.log_and_report_session_state()
-                .with_context(err_context)?;
-            self.render(None).with_context(err_context)
+        }
+        if json {
+            let panes: Vec<serde_json::Value> = panes
+                .iter()
+                .map(|(pane_id, tab_position, tab_name, pane_info)| {
+                    serde_json::json!({
+                        "pane_id": pane_id,
+                        // tab indices are 1 based, like in go-to-tab
+                        "tab_index": tab_position + 1,
+                        "tab_name": tab_name,
+                        "title": pane_info.title,
+                        "is_focused": pane_info.is_focused,
+                        "is_floating": pane_info.is_floating,
+                        "is_suppressed": pane_info.is_suppressed,
+                        "command": pane_info.terminal_command,
+                        "plugin_url": pane_info.plugin_url,
+                        "cwd": pane_info.cwd,
+                    })
+                })
+                .collect();
+            serde_json::Value::Array(panes).to_string()
         } else {
-            Ok(())
+            let mut lines = vec![String::from(
+                "ZELLIJ_PANE_ID TAB_INDEX TAB_NAME        FLOATING TITLE",
+            )];
+            for (pane_id, tab_position, tab_name, pane_info) in panes {
+                // 14 - ZELLIJ_PANE_ID, 9 - TAB_INDEX, 15 - TAB_NAME, 8 - FLOATING
+                lines.push(format!(
+                    "{0: <14} {1: <9} {2: <15} {3: <8} {4}",
+                    pane_id,
+                    tab_position + 1,
+                    tab_name,
+                    pane_info.is_floating,
+                    pane_info.title
+                ));
+            }
+            lines.join("\n")
         }
     }
 
-    pub fn update_pixel_dimensions(&mut self, pixel_dimensions: PixelDimensions) {
-        self.pixel_dimensions.merge(pixel_dimensions);
-        if let Some(character_cell_size) = self.pixel_dimensions.character_cell_size {
-            *self.character_cell_size.borrow_mut() = Some(character_cell_size);
-        } else if let Some(text_area_size) = self.pixel_dimensions.text_area_size {
-            let character_cell_size_height = text_area_size.height / self.size.rows;
-            let character_cell_size_width = text_area_size.width / self.size.cols;
-            let character_cell_size = SizeInPixels {
-                height: character_cell_size_height,
-                width: character_cell_size_width,
-            };
-            *self.character_cell_size.borrow_mut() = Some(character_cell_size);
+    /// Render the lines matching the pattern in the scrollback of the panes of the focused tab (or
+    /// of all tabs), for `zellij action search-panes`
+    pub fn render_pane_search_results(
+        &self,
+        pattern: &str,
+        all_tabs: bool,
+        json: bool,
+        client_id: ClientId,
+    ) -> std::result::Result<String, String> {
+        let pattern = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+        let mut tabs: Vec<&Tab> = if all_tabs {
+            self.tabs.values().collect()
+        } else {
+            // the cli client is not focused on any tab, so we use the first client
+            let active_tab = self.get_active_tab(client_id).ok().or_else(|| {
+                self.get_first_client_id()
+                    .and_then(|client_id| self.get_active_tab(client_id).ok())
+            });
+            active_tab.into_iter().collect()
+        };
+        tabs.sort_by_key(|tab| tab.position);
+        let mut matches = vec![];
+        for tab in tabs {
+            for (pane_id, line, text) in tab.find_pattern_in_panes(&pattern) {
+                let pane_id = match pane_id {
+                    PaneId::Terminal(id) => format!("terminal_{}", id),
+                    PaneId::Plugin(id) => format!("plugin_{}", id),
+                };
+                matches.push((pane_id, tab.position, line, text));
+            }
+        }
+        if json {
+            let matches: Vec<serde_json::Value> = matches
+                .iter()
+                .map(|(pane_id, tab_position, line, text)| {
+                    serde_json::json!({
+                        "pane_id": pane_id,
+                        // tab indices are 1 based, like in go-to-tab
+                        "tab_index": tab_position + 1,
+                        "line": line,
+                        "text": text,
+                    })
+                })
+                .collect();
+            Ok(serde_json::Value::Array(matches).to_string())
+        } else {
+            let mut lines = vec![String::from("ZELLIJ_PANE_ID TAB_INDEX LINE   TEXT")];
+            for (pane_id, tab_position, line, text) in matches {
+                // 14 - ZELLIJ_PANE_ID, 9 - TAB_INDEX, 6 - LINE
+                lines.push(format!(
+                    "{0: <14} {1: <9} {2: <6} {3}",
+                    pane_id,
+                    tab_position + 1,
+                    line,
+                    text
+                ));
+            }
+            Ok(lines.join("\n"))
         }
     }
 
-    pub fn update_terminal_background_color(&mut self, background_color_instruction: String) {
-        if let Some(AnsiCode::RgbCode((r, g, b))) =
-            xparse_color(background_color_instruction.as_bytes())
-        {
-            let bg_palette_color = PaletteColor::Rgb((r, g, b));
-            self.terminal_emulator_colors.borrow_mut().bg = bg_palette_color;
+    /// Returns how many matches the scrollback of the pane has, or why it could not be searched
+    pub fn scroll_pane_to_pattern(
+        &mut self,
+        pattern: &str,
+        backwards: bool,
+        pane_id: Option<PaneId>,
+        client_id: ClientId,
+    ) -> std::result::Result<usize, String> {
+        let pattern = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+        self.get_pane_or_active_pane_mut(pane_id, client_id)
+            .ok_or_else(|| "Pane not found".to_owned())?
+            .scroll_to_pattern(&pattern, backwards)
+            .ok_or_else(|| "Only terminal panes have a scrollback to search".to_owned())
+    }
+
+    /// Starts logging the output of the pane to a file in the session's cache folder, or stops
+    /// doing so if it already is. Returns a message to show the user.
+    pub fn toggle_pane_output_log(
+        &mut self,
+        pane_id: Option<PaneId>,
+        client_id: ClientId,
+    ) -> std::result::Result<String, String> {
+        let log_folder = pane_logs_folder_for_session(&self.session_name);
+        let pane = self
+            .get_pane_or_active_pane_mut(pane_id, client_id)
+            .ok_or_else(|| "Pane not found".to_owned())?;
+        if let Some(path) = pane.stop_output_log() {
+            return Ok(format!("Stopped logging output to {}", path.display()));
+        }
+        match pane.start_output_log(&log_folder) {
+            Ok(Some(path)) => Ok(format!("Logging output to {}", path.display())),
+            Ok(None) => Err("Only the output of terminal panes can be logged".to_owned()),
+            Err(e) => Err(format!("Failed to log output: {:?}", e)),
         }
     }
 
-    pub fn update_terminal_foreground_color(&mut self, foreground_color_instruction: String) {
-        if let Some(AnsiCode::RgbCode((r, g, b))) =
-            xparse_color(foreground_color_instruction.as_bytes())
-        {
-            let fg_palette_color = PaletteColor::Rgb((r, g, b));
-            self.terminal_emulator_colors.borrow_mut().fg = fg_palette_color;
+    /// Adds the pane to the group, or removes it from the group if it is already in it. A pane
+    /// can only be in one group at a time. Returns a message to show the user.
+    pub fn toggle_pane_in_group(
+        &mut self,
+        group: String,
+        pane_id: Option<PaneId>,
+        client_id: ClientId,
+    ) -> std::result::Result<String, String> {
+        let color = self.pane_group_colors.get(&group).copied();
+        let pane = self
+            .get_pane_or_active_pane_mut(pane_id, client_id)
+            .ok_or_else(|| "Pane not found".to_owned())?;
+        if pane.group().map(|g| g.name == group).unwrap_or(false) {
+            pane.set_group(None);
+            Ok(format!("Removed pane from group '{}'", group))
+        } else {
+            pane.set_group(Some(PaneGroup {
+                name: group.clone(),
+                color,
+            }));
+            Ok(format!("Added pane to group '{}'", group))
         }
     }
 
-    pub fn update_terminal_color_registers(&mut self, color_registers: Vec<(usize, String)>) {
-        let mut terminal_emulator_color_codes = self.terminal_emulator_color_codes.borrow_mut();
-        for (color_register, color_sequence) in color_registers {
-            terminal_emulator_color_codes.insert(color_register, color_sequence);
+    /// Returns a message to show the user
+    pub fn toggle_pane_activity_monitor(
+        &mut self,
+        pane_id: Option<PaneId>,
+        should_notify: bool,
+        client_id: ClientId,
+    ) -> std::result::Result<String, String> {
+        let pane = self
+            .get_pane_or_active_pane_mut(pane_id, client_id)
+            .ok_or_else(|| "Pane not found".to_owned())?;
+        match pane.toggle_activity_monitor(should_notify) {
+            Some(true) => Ok("Monitoring the pane for activity".to_owned()),
+            Some(false) => Ok("Stopped monitoring the pane for activity".to_owned()),
+            None => Err("Only terminal panes can be monitored".to_owned()),
         }
     }
 
-    /// Renders this [`Screen`], which amounts to rendering its active [`Tab`].
-    pub fn render(&mut self, plugin_render_assets: Option<Vec<PluginRenderAsset>>) -> Result<()> {
-        let err_context = "failed to render screen";
+    /// Returns a message to show the user
+    pub fn toggle_pane_silence_monitor(
+        &mut self,
+        pane_id: Option<PaneId>,
+        seconds: u64,
+        should_notify: bool,
+        client_id: ClientId,
+    ) -> std::result::Result<String, String> {
+        let pane = self
+            .get_pane_or_active_pane_mut(pane_id, client_id)
+            .ok_or_else(|| "Pane not found".to_owned())?;
+        match pane.toggle_silence_monitor(Duration::from_secs(seconds), should_notify) {
+            Some(true) => {
+                // silence is checked periodically, from the first time it is monitored on
+                self.bus
+                    .senders
+                    .send_to_background_jobs(BackgroundJob::MonitorPaneSilence)
+                    .map_err(|e| format!("Failed to monitor silence: {:?}", e))?;
+                Ok(format!(
+                    "Monitoring the pane for {} seconds of silence",
+                    seconds
+                ))
+            },
+            Some(false) => Ok("Stopped monitoring the pane for silence".to_owned()),
+            None => Err("Only terminal panes can be monitored".to_owned()),
+        }
+    }
 
-        let mut output = Output::new(
-            self.sixel_image_store.clone(),
-            self.character_cell_size.clone(),
-            self.styled_underlines,
-        );
-        let mut tabs_to_close = vec![];
-        for (tab_index, tab) in &mut self.tabs {
-            if tab.has_selectable_tiled_panes() {
-                tab.render(&mut output).context(err_context)?;
-            } else if !tab.is_pending() {
-                tabs_to_close.push(*tab_index);
+    /// Updates the tab bars with the alert, and queues a desktop notification of it if it should
+    /// be sent one
+    pub fn raise_pane_monitor_alert(
+        &mut self,
+        pane_id: PaneId,
+        alert: MonitorAlert,
+        should_notify: bool,
+    ) -> Result<()> {
+        if should_notify && !self.bells_are_muted() {
+            if let Some(tab) = self.tabs.values().find(|t| t.has_pane_with_pid(&pane_id)) {
+                let pane_title = tab
+                    .get_pane_with_id(pane_id)
+                    .map(|pane| pane.current_title())
+                    .unwrap_or_default();
+                let description = match alert {
+                    MonitorAlert::Activity => "Activity",
+                    MonitorAlert::Silence => "Silence",
+                };
+                let text: String = format!(
+                    "{} in pane '{}' (tab '{}')",
+                    description, pane_title, tab.name
+                )
+                .chars()
+                .filter(|c| !c.is_control())
+                .collect();
+                // OSC 777, understood by most terminals that display desktop notifications
+                let all_clients = self.connected_clients.borrow().iter().copied().collect();
+                self.pending_vte_instructions.push((
+                    all_clients,
+                    format!("\u{1b}]777;notify;Zellij;{}\u{1b}\\", text),
+                ));
             }
         }
-        for tab_index in tabs_to_close {
-            // cleanup as needed
-            self.close_tab_at_index(tab_index)
-                .context(err_context)
-                .non_fatal();
+        self.generate_and_report_tab_state()
+            .context("failed to raise pane monitor alert")?;
+        Ok(())
+    }
+
+    /// Marks the tab of the pane that rang the bell, and passes the bell on to the clients that
+    /// do not see the pane if all bells are forwarded (the others ring it as the pane is rendered)
+    pub fn ring_bell_of_pane(&mut self, pane_id: PaneId) -> Result<()> {
+        if self.bell_forwarding == BellForwarding::All && !self.bells_are_muted() {
+            if let Some(tab_index) = self
+                .tabs
+                .values()
+                .find(|t| t.has_pane_with_pid(&pane_id))
+                .map(|t| t.index)
+            {
+                let clients_elsewhere: Vec<ClientId> = self
+                    .active_tab_indices
+                    .iter()
+                    .filter(|(_, active_tab_index)| **active_tab_index != tab_index)
+                    .map(|(client_id, _)| *client_id)
+                    .collect();
+                if !clients_elsewhere.is_empty() {
+                    self.pending_vte_instructions
+                        .push((clients_elsewhere, "\u{7}".to_owned()));
+                }
+            }
         }
-        if output.is_dirty() {
-            let serialized_output = output.serialize().context(err_context)?;
-            let _ = self
-                .bus
+        self.generate_and_report_tab_state()
+            .context("failed to mark the tab of a pane that rang the bell")?;
+        Ok(())
+    }
+
+    /// Returns whether any alert was raised
+    pub fn check_pane_silence(&mut self) -> Result<bool> {
+        let now = Instant::now();
+        let mut alerts = vec![];
+        for tab in self.tabs.values_mut() {
+            alerts.append(&mut tab.check_pane_silence(now));
+        }
+        let raised_alert = !alerts.is_empty();
+        for (pane_id, alert, should_notify) in alerts {
+            self.raise_pane_monitor_alert(pane_id, alert, should_notify)?;
+        }
+        Ok(raised_alert)
+    }
+
+    fn set_automatic_tab_rename(&mut self, automatic_tab_rename: bool) -> Result<()> {
+        if automatic_tab_rename && !self.automatic_tab_rename {
+            self.bus
                 .senders
-                .send_to_server(ServerInstruction::Render(Some(serialized_output)))
-                .context(err_context);
+                .send_to_background_jobs(BackgroundJob::MonitorRunningCommands)
+                .context("failed to start monitoring the commands running in panes")?;
+        } else if !automatic_tab_rename && self.automatic_tab_rename {
+            self.running_commands.clear();
+            self.bus
+                .senders
+                .send_to_background_jobs(BackgroundJob::StopMonitor(Box::new(
+                    BackgroundJob::MonitorRunningCommands,
+                )))
+                .context("failed to stop monitoring the commands running in panes")?;
         }
-        if let Some(plugin_render_assets) = plugin_render_assets {
-            let _ = self
-                .bus
+        self.automatic_tab_rename = automatic_tab_rename;
+        Ok(())
+    }
+
+    fn set_pane_frame_process_stats(&mut self, pane_frame_process_stats: bool) -> Result<()> {
+        if pane_frame_process_stats && !self.pane_frame_process_stats {
+            self.bus
                 .senders
-                .send_to_plugin(PluginInstruction::UnblockCliPipes(plugin_render_assets))
-                .context("failed to unblock input pipe");
+                .send_to_background_jobs(BackgroundJob::MonitorProcessStats)
+                .context("failed to start monitoring the processes running in panes")?;
+        } else if !pane_frame_process_stats && self.pane_frame_process_stats {
+            self.update_process_stats(HashMap::new());
+            self.bus
+                .senders
+                .send_to_background_jobs(BackgroundJob::StopMonitor(Box::new(
+                    BackgroundJob::MonitorProcessStats,
+                )))
+                .context("failed to stop monitoring the processes running in panes")?;
         }
+        self.pane_frame_process_stats = pane_frame_process_stats;
         Ok(())
     }
 
-    /// Returns a mutable reference to this [`Screen`]'s tabs.
-    pub fn get_tabs_mut(&mut self) -> &mut BTreeMap<usize, Tab> {
-        &mut self.tabs
+    /// Closes a new pane (whose process or plugin was already started) rather than adding it to
+    /// a tab that is already full, or when the scrollback of the session takes too much memory,
+    /// telling the client why. Panes are mostly refused by the route thread before being spawned
+    /// (see `report_refused_new_panes`), this catches the ones opened by plugins or in between.
+    /// Closing the pane unblocks the input of the client.
+    fn refuse_pane_over_limits(
+        &mut self,
+        pid: PaneId,
+        client_tab_index_or_pane_id: &ClientTabIndexOrPaneId,
+    ) -> Result<bool> {
+        if self.resource_limits == ResourceLimits::default() {
+            return Ok(false);
+        }
+        let (tab, client_id) = match client_tab_index_or_pane_id {
+            ClientTabIndexOrPaneId::ClientId(client_id) => {
+                (self.get_active_tab(*client_id).ok(), Some(*client_id))
+            },
+            ClientTabIndexOrPaneId::TabIndex(tab_index) => (self.tabs.get(tab_index), None),
+            ClientTabIndexOrPaneId::PaneId(pane_id) => (
+                self.tabs
+                    .values()
+                    .find(|tab| tab.has_pane_with_pid(pane_id)),
+                None,
+            ),
+        };
+        let panes_in_tab = match tab {
+            Some(tab) => tab.pane_count(),
+            None => return Ok(false),
+        };
+        match self
+            .resource_limits
+            .refuse_new_pane(panes_in_tab, self.scrollback_memory_usage())
+        {
+            Some(refusal) => {
+                self.bus
+                    .senders
+                    .send_to_pty(PtyInstruction::ClosePane(pid))
+                    .with_context(|| format!("failed to close refused pane {:?}", pid))?;
+                self.report_over_limits(refusal, client_id)?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
     }
 
-    /// Returns an immutable reference to this [`Screen`]'s active [`Tab`].
-    pub fn get_active_tab(&self, client_id: ClientId) -> Result<&Tab> {
-        match self.active_tab_indices.get(&client_id) {
-            Some(tab) => self
-                .tabs
-                .get(tab)
-                .ok_or_else(|| anyhow!("active tab {} does not exist", tab)),
-            None => Err(anyhow!("active tab not found for client {:?}", client_id)),
+    /// Why a new tab cannot be opened, if it cannot (the first tab of the session always can)
+    fn refuse_tab_over_limits(&self) -> Option<String> {
+        if self.tabs.is_empty() {
+            return None;
         }
+        self.resource_limits
+            .refuse_new_tab(self.tabs.len(), self.scrollback_memory_usage())
     }
 
-    pub fn get_first_client_id(&self) -> Option<ClientId> {
-        self.active_tab_indices.keys().next().copied()
+    /// Why a new tab cannot be opened with this layout (or the default one), if it has more panes
+    /// than max_panes_per_tab
+    fn refuse_tab_layout_over_limits(
+        &self,
+        tiled_panes_layout: Option<&TiledPaneLayout>,
+        floating_panes_layout: &[FloatingPaneLayout],
+    ) -> Option<String> {
+        if self.tabs.is_empty() || self.resource_limits.max_panes_per_tab.is_none() {
+            return None;
+        }
+        let (default_tiled_panes_layout, default_floating_panes_layout) =
+            self.default_layout.new_tab();
+        let tiled_panes_layout = tiled_panes_layout.unwrap_or(&default_tiled_panes_layout);
+        let floating_panes_layout = if floating_panes_layout.is_empty() {
+            &default_floating_panes_layout
+        } else {
+            floating_panes_layout
+        };
+        self.resource_limits
+            .refuse_new_tab_layout(panes_in_layout(tiled_panes_layout, floating_panes_layout))
     }
 
-    /// Returns an immutable reference to this [`Screen`]'s previous active [`Tab`].
-    /// Consumes the last entry in tab history.
-    pub fn get_previous_tab(&mut self, client_id: ClientId) -> Result<Option<&Tab>> {
-        Ok(
-            match self
-                .tab_history
-                .get_mut(&client_id)
-                .with_context(|| {
-                    format!("failed to retrieve tab history for client {client_id:?}")
-                })?
-                .pop()
-            {
-                Some(tab) => self.tabs.get(&tab),
-                None => None,
-            },
-        )
+    /// Sends the server why new panes are refused to each client (the tab they are looking at is
+    /// full or the scrollback takes too much memory) when this changes, so that the route thread
+    /// refuses their actions before their process is spawned
+    fn report_refused_new_panes(&mut self) -> Result<()> {
+        if self.resource_limits == ResourceLimits::default() && self.refused_new_panes.is_empty() {
+            return Ok(());
+        }
+        let scrollback_memory = self.scrollback_memory_usage();
+        let mut refused_new_panes = HashMap::new();
+        for (client_id, tab_index) in &self.active_tab_indices {
+            if let Some(tab) = self.tabs.get(tab_index) {
+                if let Some(refusal) = self
+                    .resource_limits
+                    .refuse_new_pane(tab.pane_count(), scrollback_memory)
+                {
+                    refused_new_panes.insert(*client_id, refusal);
+                }
+            }
+        }
+        if refused_new_panes != self.refused_new_panes {
+            self.refused_new_panes = refused_new_panes.clone();
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::RefuseNewPanes(refused_new_panes))
+                .context("failed to report refused new panes")?;
+        }
+        Ok(())
     }
 
-    /// Returns a mutable reference to this [`Screen`]'s active [`Tab`].
-    pub fn get_active_tab_mut(&mut self, client_id: ClientId) -> Result<&mut Tab> {
-        match self.active_tab_indices.get(&client_id) {
-            Some(tab) => self
-                .tabs
-                .get_mut(tab)
-                .ok_or_else(|| anyhow!("active tab {} does not exist", tab)),
-            None => Err(anyhow!("active tab not found for client {:?}", client_id)),
+    fn report_copy_mode_clients(&mut self) -> Result<()> {
+        let mut copy_mode_clients = HashSet::new();
+        for (client_id, tab_index) in &self.active_tab_indices {
+            if let Some(tab) = self.tabs.get(tab_index) {
+                if tab.copy_mode_is_active(*client_id) {
+                    copy_mode_clients.insert(*client_id);
+                }
+            }
+        }
+        if copy_mode_clients != self.copy_mode_clients {
+            self.copy_mode_clients = copy_mode_clients.clone();
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::CopyModeClients(copy_mode_clients))
+                .context("failed to report copy mode clients")?;
         }
+        Ok(())
     }
 
-    /// Returns a mutable reference to this [`Screen`]'s active [`Overlays`].
-    pub fn get_active_overlays_mut(&mut self) -> &mut Vec<Overlay> {
-        &mut self.overlay.overlay_stack
+    fn report_over_limits(&self, refusal: String, client_id: Option<ClientId>) -> Result<()> {
+        log::warn!("{}", refusal);
+        match client_id {
+            Some(client_id) => self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::LogError(vec![refusal], client_id))
+                .context("failed to report resource limits"),
+            None => Ok(()),
+        }
     }
 
-    /// Returns a mutable reference to this [`Screen`]'s indexed [`Tab`].
-    pub fn get_indexed_tab_mut(&mut self, tab_index: usize) -> Option<&mut Tab> {
-        self.get_tabs_mut().get_mut(&tab_index)
+    fn scrollback_memory_usage(&self) -> usize {
+        if self.resource_limits.max_scrollback_memory.is_none() {
+            // no need to go through all of the scrollback
+            return 0;
+        }
+        self.tabs
+            .values()
+            .map(|tab| tab.scrollback_memory_usage())
+            .sum()
     }
 
-    /// Creates a new [`Tab`] in this [`Screen`]
-    pub fn new_tab(
-        &mut self,
-        tab_index: usize,
-        swap_layouts: (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
-        tab_name: Option<String>,
-        client_id: Option<ClientId>,
-    ) -> Result<()> {
-        let err_context = || format!("failed to create new tab for client {client_id:?}",);
+    fn update_process_stats(&mut self, process_stats: HashMap<u32, ProcessStats>) {
+        for tab in self.tabs.values_mut() {
+            tab.update_process_stats(&process_stats);
+        }
+    }
 
-        let client_id = client_id.map(|client_id| {
-            if self.get_active_tab(client_id).is_ok() {
-                client_id
-            } else if let Some(first_client_id) = self.get_first_client_id() {
-                first_client_id
-            } else {
-                client_id
+    /// Names the tabs the clients are looking at after their focused pane: after the title the
+    /// program running in it set with the tab_name_from_title option, and with the
+    /// automatic_tab_rename option after the name the user gave the pane, that title or else the
+    /// command running in it. Tabs renamed by the user keep their name. Returns whether a tab was
+    /// renamed.
+    fn rename_tabs_automatically(&mut self) -> bool {
+        if !self.automatic_tab_rename && !self.tab_name_from_title {
+            return false;
+        }
+        let mut renamed_tab = false;
+        for tab in self.tabs.values_mut() {
+            if tab.has_user_set_name() {
+                continue;
             }
-        });
-
-        let tab_name = tab_name.unwrap_or_else(|| String::new());
+            let name = tab.focused_pane_id().and_then(|pane_id| {
+                let pane = tab.get_pane_with_id(pane_id)?;
+                if !self.automatic_tab_rename {
+                    return pane.application_title();
+                }
+                pane.custom_title()
+                    .or_else(|| pane.application_title())
+                    .or_else(|| match pane_id {
+                        PaneId::Terminal(id) => self.running_commands.get(&id).cloned(),
+                        PaneId::Plugin(_) => None,
+                    })
+            });
+            if let Some(name) = name.filter(|name| !name.is_empty() && *name != tab.name) {
+                tab.set_automatic_name(name);
+                renamed_tab = true;
+            }
+        }
+        renamed_tab
+    }
 
-        let position = self.tabs.len();
-        let mut tab = Tab::new(
-            tab_index,
-            position,
-            tab_name,
-            self.size,
-            self.character_cell_size.clone(),
-            self.stacked_resize.clone(),
-            self.sixel_image_store.clone(),
+    fn set_idle_client_detach_timeout(&mut self, minutes: Option<u64>) -> Result<()> {
+        let was_monitoring = self.idle_client_detach_timeout.is_some();
+        self.idle_client_detach_timeout = idle_timeout_duration(minutes);
+        if self.idle_client_detach_timeout.is_none() {
+            self.clear_idle_detach_warnings();
+        } else if !was_monitoring {
+            // clients are idle from the moment the timeout is configured
+            let now = Instant::now();
+            for last_input in self.clients_last_input.values_mut() {
+                *last_input = now;
+            }
             self.bus
-                .os_input
-                .as_ref()
-                .with_context(err_context)?
-                .clone(),
-            self.bus.senders.clone(),
-            self.max_panes,
-            self.style,
-            self.default_mode_info.clone(),
-            self.draw_pane_frames,
-            self.auto_layout,
-            self.connected_clients.clone(),
-            self.session_is_mirrored,
-            client_id,
-            self.copy_options.clone(),
-            self.terminal_emulator_colors.clone(),
-            self.terminal_emulator_color_codes.clone(),
-            swap_layouts,
-            self.default_shell.clone(),
-            self.debug,
-            self.arrow_fonts,
-            self.styled_underlines,
-            self.explicitly_disable_kitty_keyboard_protocol,
-            self.default_editor.clone(),
-        );
-        for (client_id, mode_info) in &self.mode_info {
-            tab.change_mode_info(mode_info.clone(), *client_id);
+                .senders
+                .send_to_background_jobs(BackgroundJob::MonitorIdleClients)
+                .context("failed to start monitoring idle clients")?;
         }
-        self.tabs.insert(tab_index, tab);
         Ok(())
     }
-    pub fn apply_layout(
-        &mut self,
-        layout: TiledPaneLayout,
-        floating_panes_layout: Vec<FloatingPaneLayout>,
-        new_terminal_ids: Vec<(u32, HoldForCommand)>,
-        new_floating_terminal_ids: Vec<(u32, HoldForCommand)>,
-        new_plugin_ids: HashMap<RunPluginOrAlias, Vec<u32>>,
-        tab_index: usize,
-        should_change_client_focus: bool,
-        client_id: ClientId,
-    ) -> Result<()> {
-        if self.tabs.get(&tab_index).is_none() {
-            // TODO: we should prevent this situation with a UI - eg. cannot close tabs with a
-            // pending state
-            log::error!("Tab with index {tab_index} not found. Cannot apply layout!");
-            return Ok(());
+
+    /// Returns whether the client was shown a countdown that should now be cleared
+    pub fn client_input(&mut self, client_id: ClientId) -> bool {
+        if let Some(last_input) = self.clients_last_input.get_mut(&client_id) {
+            *last_input = Instant::now();
         }
-        let client_id = if self.get_active_tab(client_id).is_ok() {
-            client_id
-        } else if let Some(first_client_id) = self.get_first_client_id() {
-            first_client_id
+        if self.clients_warned_of_idle_detach.remove(&client_id) {
+            self.force_render_all_tabs();
+            true
         } else {
-            client_id
-        };
-        let err_context = || format!("failed to apply layout for tab {tab_index:?}",);
+            false
+        }
+    }
 
-        // move the relevant clients out of the current tab and place them in the new one
-        let drained_clients = if should_change_client_focus {
-            if self.session_is_mirrored {
-                let client_mode_infos_in_source_tab = if let Ok(active_tab) =
-                    self.get_active_tab_mut(client_id)
-                {
-                    let client_mode_infos_in_source_tab = active_tab.drain_connected_clients(None);
-                    if active_tab.has_no_connected_clients() {
-                        active_tab
-                            .visible(false)
-                            .with_context(err_context)
-                            .non_fatal();
-                    }
-                    Some(client_mode_infos_in_source_tab)
-                } else {
-                    None
-                };
-                let all_connected_clients: Vec<ClientId> =
-                    self.connected_clients.borrow().iter().copied().collect();
-                for client_id in all_connected_clients {
-                    self.update_client_tab_focus(client_id, tab_index);
-                }
-                client_mode_infos_in_source_tab
-            } else if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
-                let client_mode_info_in_source_tab =
-                    active_tab.drain_connected_clients(Some(vec![client_id]));
-                if active_tab.has_no_connected_clients() {
-                    active_tab
-                        .visible(false)
-                        .with_context(err_context)
-                        .non_fatal();
-                }
-                self.update_client_tab_focus(client_id, tab_index);
-                Some(client_mode_info_in_source_tab)
-            } else {
-                None
-            }
-        } else {
-            None
+    /// Detaches the clients that have been idle for longer than the timeout, and counts down the
+    /// last minute for those about to be. Returns whether there is something to render.
+    pub fn check_idle_clients(&mut self) -> Result<bool> {
+        let timeout = match self.idle_client_detach_timeout {
+            Some(timeout) => timeout,
+            None => return Ok(false),
         };
+        let warning_duration = IDLE_DETACH_WARNING.min(timeout);
+        let now = Instant::now();
+        let mut should_render = false;
+        let mut idle_clients = vec![];
+        for (client_id, last_input) in &self.clients_last_input {
+            let idle_for = now.saturating_duration_since(*last_input);
+            if idle_for >= timeout {
+                idle_clients.push(*client_id);
+            } else if idle_for + warning_duration >= timeout {
+                let seconds_left = (timeout - idle_for).as_secs_f64().ceil() as u64;
+                let warning = idle_detach_warning(seconds_left, self.size);
+                self.pending_vte_instructions
+                    .push((vec![*client_id], warning));
+                self.clients_warned_of_idle_detach.insert(*client_id);
+                should_render = true;
+            }
+        }
+        for client_id in idle_clients {
+            // so that it is only detached once, it is forgotten completely once it is removed
+            self.clients_last_input.remove(&client_id);
+            self.clients_warned_of_idle_detach.remove(&client_id);
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::DetachIdleClient(client_id))
+                .context("failed to detach an idle client")?;
+        }
+        Ok(should_render)
+    }
 
-        // apply the layout to the new tab
-        self.tabs
-            .get_mut(&tab_index)
-            .context("couldn't find tab with index {tab_index}")
-            .and_then(|tab| {
-                tab.apply_layout(
-                    layout,
-                    floating_panes_layout,
-                    new_terminal_ids,
-                    new_floating_terminal_ids,
-                    new_plugin_ids,
-                    client_id,
-                )?;
-                tab.update_input_modes()?;
-
-                if let Some(drained_clients) = drained_clients {
-                    tab.visible(true)?;
-                    tab.add_multiple_clients(drained_clients)?;
-                }
-                tab.resize_whole_tab(self.size).with_context(err_context)?;
-                tab.set_force_render();
-                Ok(())
-            })
-            .with_context(err_context)?;
-
-        if !self.active_tab_indices.contains_key(&client_id) {
-            // this means this is a new client and we need to add it to our state properly
-            self.add_client(client_id).with_context(err_context)?;
+    fn set_idle_session_timeout(&mut self, minutes: Option<u64>) -> Result<()> {
+        let was_monitoring = self.idle_session_timeout.is_some();
+        self.idle_session_timeout = idle_timeout_duration(minutes);
+        if self.idle_session_timeout.is_some() && !was_monitoring {
+            // the session is idle from the moment the timeout is configured
+            self.session_activity();
+            self.bus
+                .senders
+                .send_to_background_jobs(BackgroundJob::MonitorIdleSession)
+                .context("failed to start monitoring the idle session")?;
+        } else if self.idle_session_timeout.is_none() && was_monitoring {
+            self.bus
+                .senders
+                .send_to_background_jobs(BackgroundJob::StopMonitor(Box::new(
+                    BackgroundJob::MonitorIdleSession,
+                )))
+                .context("failed to stop monitoring the idle session")?;
         }
+        Ok(())
+    }
 
-        self.log_and_report_session_state()
-            .and_then(|_| self.render(None))
-            .with_context(err_context)
+    fn session_activity(&mut self) {
+        self.last_session_activity = Instant::now();
+        self.idle_session_serialized = false;
     }
 
-    pub fn add_client(&mut self, client_id: ClientId) -> Result<()> {
-        let err_context = |tab_index| {
-            format!("failed to attach client {client_id} to tab with index {tab_index}")
+    /// Kills the session once it has had no attached client and no pane output for longer than
+    /// the timeout. It is serialized first (if serialization is on) so that it can be resurrected,
+    /// and only killed at the next check so that the serialized layout has reached the disk.
+    pub fn check_idle_session(&mut self) -> Result<()> {
+        let timeout = match self.idle_session_timeout {
+            Some(timeout) => timeout,
+            None => return Ok(()),
         };
-
-        let mut tab_history = vec![];
-        if let Some((_first_client, first_tab_history)) = self.tab_history.iter().next() {
-            tab_history = first_tab_history.clone();
-        }
-
-        let tab_index = if let Some((_first_client, first_active_tab_index)) =
-            self.active_tab_indices.iter().next()
+        if !self.connected_clients.borrow().is_empty()
+            || self.last_session_activity.elapsed() < timeout
         {
-            *first_active_tab_index
-        } else if self.tabs.contains_key(&0) {
-            0
-        } else if let Some(tab_index) = self.tabs.keys().next() {
-            tab_index.to_owned()
+            return Ok(());
+        }
+        if self.idle_session_serialized || !self.session_serialization {
+            log::info!(
+                "Killing the session after {} minute(s) without clients or pane output",
+                timeout.as_secs() / 60
+            );
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::KillSession)
+                .context("failed to kill the idle session")?;
         } else {
-            bail!("Can't find a valid tab to attach client to!");
-        };
-
-        self.active_tab_indices.insert(client_id, tab_index);
-        self.connected_clients.borrow_mut().insert(client_id);
-        self.tab_history.insert(client_id, tab_history);
-        self.tabs
-            .get_mut(&tab_index)
-            .with_context(|| err_context(tab_index))?
-            .add_client(client_id, None)
-            .with_context(|| err_context(tab_index))
+            self.dump_layout_to_hd()
+                .context("failed to serialize the idle session")?;
+            self.idle_session_serialized = true;
+        }
+        Ok(())
     }
 
-    pub fn remove_client(&mut self, client_id: ClientId) -> Result<()> {
-        let err_context = || format!("failed to remove client {client_id}");
+    fn clear_idle_detach_warnings(&mut self) {
+        if !self.clients_warned_of_idle_detach.is_empty() {
+            self.clients_warned_of_idle_detach.clear();
+            self.force_render_all_tabs();
+        }
+    }
 
-        for (_, tab) in self.tabs.iter_mut() {
-            tab.remove_client(client_id);
-            if tab.has_no_connected_clients() {
-                tab.visible(false).with_context(err_context)?;
-            }
+    fn force_render_all_tabs(&mut self) {
+        for tab in self.tabs.values_mut() {
+            tab.set_force_render();
         }
-        if self.active_tab_indices.contains_key(&client_id) {
-            self.active_tab_indices.remove(&client_id);
+    }
+
+    /// Applies the action to every pane of the group, in all tabs. Returns a message to show the
+    /// user.
+    pub fn apply_to_pane_group(
+        &mut self,
+        group: &str,
+        action: PaneGroupAction,
+    ) -> std::result::Result<String, String> {
+        let members = self.pane_group_members(group);
+        if members.is_empty() {
+            return Err(format!("No panes in group '{}'", group));
         }
-        if self.tab_history.contains_key(&client_id) {
-            self.tab_history.remove(&client_id);
+        for (tab_index, pane_id) in &members {
+            let tab = match self.tabs.get_mut(tab_index) {
+                Some(tab) => tab,
+                None => continue,
+            };
+            match &action {
+                PaneGroupAction::WriteChars(chars) => {
+                    tab.write_to_pane_id(&None, chars.as_bytes().to_vec(), false, *pane_id, None)
+                        .map_err(|e| format!("Failed to write to pane: {:?}", e))?;
+                },
+                PaneGroupAction::Close => {
+                    tab.close_pane(*pane_id, false);
+                    self.bus
+                        .senders
+                        .send_to_pty(PtyInstruction::ClosePane(*pane_id))
+                        .map_err(|e| format!("Failed to close pane: {:?}", e))?;
+                },
+                PaneGroupAction::Resize(resize, direction) => {
+                    tab.resize_pane_with_id(ResizeStrategy::new(*resize, *direction), *pane_id)
+                        .non_fatal();
+                },
+                PaneGroupAction::SetColor(color) => {
+                    if let Some(pane) = tab.get_pane_with_id_mut(*pane_id) {
+                        pane.set_group(Some(PaneGroup {
+                            name: group.to_owned(),
+                            color: *color,
+                        }));
+                    }
+                },
+            }
         }
-        self.connected_clients.borrow_mut().remove(&client_id);
-        self.log_and_report_session_state()
-            .with_context(err_context)
+        if let PaneGroupAction::SetColor(color) = action {
+            match color {
+                Some(color) => self.pane_group_colors.insert(group.to_owned(), color),
+                None => self.pane_group_colors.remove(group),
+            };
+        }
+        Ok(format!(
+            "Applied to {} pane(s) in group '{}'",
+            members.len(),
+            group
+        ))
     }
 
-    pub fn generate_and_report_tab_state(&mut self) -> Result<Vec<TabInfo>> {
-        let mut plugin_updates = vec![];
-        let mut tab_infos_for_screen_state = BTreeMap::new();
-        for tab in self.tabs.values() {
-            let all_focused_clients: Vec<ClientId> = self
-                .active_tab_indices
-                .iter()
-                .filter(|(_c_id, tab_position)| **tab_position == tab.index)
-                .map(|(c_id, _)| c_id)
-                .copied()
-                .collect();
-            let (active_swap_layout_name, is_swap_layout_dirty) = tab.swap_layout_info();
-            let tab_viewport = tab.get_viewport();
-            let tab_display_area = tab.get_display_area();
-            let selectable_tiled_panes_count = tab.get_selectable_tiled_panes_count();
-            let selectable_floating_panes_count = tab.get_selectable_floating_panes_count();
-            let tab_info_for_screen = TabInfo {
-                position: tab.position,
-                name: tab.name.clone(),
-                active: self.active_tab_indices.values().any(|i| i == &tab.index),
-                panes_to_hide: tab.panes_to_hide_count(),
-                is_fullscreen_active: tab.is_fullscreen_active(),
-                is_sync_panes_active: tab.is_sync_panes_active(),
-                are_floating_panes_visible: tab.are_floating_panes_visible(),
-                other_focused_clients: all_focused_clients,
-                active_swap_layout_name,
-                is_swap_layout_dirty,
-                viewport_rows: tab_viewport.rows,
-                viewport_columns: tab_viewport.cols,
-                display_area_rows: tab_display_area.rows,
-                display_area_columns: tab_display_area.cols,
-                selectable_tiled_panes_count,
-                selectable_floating_panes_count,
+    /// Writes these characters to every terminal pane of the client's tab, or of the group (in
+    /// all tabs) if one is given. Exited command panes are skipped, ENTER would re-run their
+    /// command. Returns a message to show the user.
+    pub fn multi_exec(
+        &mut self,
+        chars: String,
+        group: Option<String>,
+        exclude_focused: bool,
+        client_id: ClientId,
+    ) -> std::result::Result<String, String> {
+        let active_tab = self
+            .get_active_tab(client_id)
+            .map_err(|_| "No active tab".to_owned())?;
+        let focused_pane_id = active_tab.get_active_pane_id(client_id);
+        let candidates: Vec<(usize, PaneId)> = match &group {
+            Some(group) => self.pane_group_members(group),
+            None => active_tab
+                .get_static_and_floating_pane_ids()
+                .into_iter()
+                .map(|pane_id| (active_tab.index, pane_id))
+                .collect(),
+        };
+        // a pane linked to another tab can be in the group twice, but has a single pty
+        let mut written_to = HashSet::new();
+        let mut failed_to_write_to = vec![];
+        for (tab_index, pane_id) in candidates {
+            if exclude_focused && Some(pane_id) == focused_pane_id {
+                continue;
+            }
+            let tab = match self.tabs.get_mut(&tab_index) {
+                Some(tab) => tab,
+                None => continue,
             };
-            tab_infos_for_screen_state.insert(tab.position, tab_info_for_screen);
-        }
-        for (client_id, active_tab_index) in self.active_tab_indices.iter() {
-            let mut plugin_tab_updates = vec![];
-            for tab in self.tabs.values() {
-                let other_focused_clients: Vec<ClientId> = if self.session_is_mirrored {
-                    vec![]
-                } else {
-                    self.active_tab_indices
-                        .iter()
-                        .filter(|(c_id, tab_position)| {
-                            **tab_position == tab.index && *c_id != client_id
-                        })
-                        .map(|(c_id, _)| c_id)
-                        .copied()
-                        .collect()
-                };
-                let (active_swap_layout_name, is_swap_layout_dirty) = tab.swap_layout_info();
-                let tab_viewport = tab.get_viewport();
-                let tab_display_area = tab.get_display_area();
-                let selectable_tiled_panes_count = tab.get_selectable_tiled_panes_count();
-                let selectable_floating_panes_count = tab.get_selectable_floating_panes_count();
-                let tab_info_for_plugins = TabInfo {
-                    position: tab.position,
-                    name: tab.name.clone(),
-                    active: *active_tab_index == tab.index,
-                    panes_to_hide: tab.panes_to_hide_count(),
-                    is_fullscreen_active: tab.is_fullscreen_active(),
-                    is_sync_panes_active: tab.is_sync_panes_active(),
-                    are_floating_panes_visible: tab.are_floating_panes_visible(),
-                    other_focused_clients,
-                    active_swap_layout_name,
-                    is_swap_layout_dirty,
-                    viewport_rows: tab_viewport.rows,
-                    viewport_columns: tab_viewport.cols,
-                    display_area_rows: tab_display_area.rows,
-                    display_area_columns: tab_display_area.cols,
-                    selectable_tiled_panes_count,
-                    selectable_floating_panes_count,
-                };
-                plugin_tab_updates.push(tab_info_for_plugins);
+            let is_running_terminal = matches!(pane_id, PaneId::Terminal(_))
+                && tab
+                    .get_pane_with_id(pane_id)
+                    .map(|pane| !pane.is_held())
+                    .unwrap_or(false);
+            if !is_running_terminal || !written_to.insert(pane_id) {
+                continue;
+            }
+            // a pane failing to take the input does not keep it from the others
+            if let Err(e) =
+                tab.write_to_pane_id(&None, chars.as_bytes().to_vec(), false, pane_id, None)
+            {
+                log::error!("Failed to multi-exec in pane {:?}: {:?}", pane_id, e);
+                failed_to_write_to.push(pane_id);
             }
-            plugin_updates.push((None, Some(*client_id), Event::TabUpdate(plugin_tab_updates)));
         }
-        self.bus
-            .senders
-            .send_to_plugin(PluginInstruction::Update(plugin_updates))
-            .context("failed to update tabs")?;
-        Ok(tab_infos_for_screen_state.values().cloned().collect())
-    }
-    fn generate_and_report_pane_state(&mut self) -> Result<PaneManifest> {
-        let mut pane_manifest = PaneManifest::default();
-        for tab in self.tabs.values() {
-            pane_manifest.panes.insert(tab.position, tab.pane_infos());
+        let sent_to = written_to.len() - failed_to_write_to.len();
+        if !failed_to_write_to.is_empty() {
+            failed_to_write_to.sort();
+            let failed_to_write_to: Vec<String> = failed_to_write_to
+                .iter()
+                .map(|pane_id| match pane_id {
+                    PaneId::Terminal(id) => format!("terminal_{}", id),
+                    PaneId::Plugin(id) => format!("plugin_{}", id),
+                })
+                .collect();
+            return Err(format!(
+                "Sent to {} pane(s), failed to write to {}",
+                sent_to,
+                failed_to_write_to.join(", ")
+            ));
+        }
+        match (sent_to, group) {
+            (0, Some(group)) => Err(format!("No running terminal panes in group '{}'", group)),
+            (0, None) => Err("No running terminal panes in this tab".to_owned()),
+            (count, _) => Ok(format!("Sent to {} pane(s)", count)),
         }
-        self.bus
-            .senders
-            .send_to_plugin(PluginInstruction::Update(vec![(
-                None,
-                None,
-                Event::PaneUpdate(pane_manifest.clone()),
-            )]))
-            .context("failed to update tabs")?;
-
-        Ok(pane_manifest)
     }
-    fn log_and_report_session_state(&mut self) -> Result<()> {
-        let err_context = || format!("Failed to log and report session state");
-        // generate own session info
-        let pane_manifest = self.generate_and_report_pane_state()?;
-        let tab_infos = self.generate_and_report_tab_state()?;
-        // in the context of unit/integration tests, we don't need to list available layouts
-        // because this is mostly about HD access - it does however throw off the timing in the
-        // tests and causes them to flake, which is why we skip it here
-        #[cfg(not(test))]
-        let available_layouts =
-            Layout::list_available_layouts(self.layout_dir.clone(), &self.default_layout_name);
-        #[cfg(test)]
-        let available_layouts = vec![];
-        let session_info = SessionInfo {
-            name: self.session_name.clone(),
-            tabs: tab_infos,
-            panes: pane_manifest,
-            connected_clients: self.active_tab_indices.keys().len(),
-            is_current_session: true,
-            available_layouts,
-            plugins: Default::default(), // these are filled in by the wasm thread
-            tab_history: self.tab_history.clone(),
-        };
-        self.bus
-            .senders
-            .send_to_background_jobs(BackgroundJob::ReportSessionInfo(
-                self.session_name.to_owned(),
-                session_info,
-            ))
-            .with_context(err_context)?;
 
-        self.bus
-            .senders
-            .send_to_background_jobs(BackgroundJob::ReadAllSessionInfosOnMachine)
-            .with_context(err_context)?;
+    /// Applies a title set by the program running in a pane (OSC 0/2) to the name of the pane and
+    /// of its tab, according to the title_precedence and tab_name_from_title options. The pane
+    /// frame shows the title by itself unless the pane was renamed.
+    pub fn apply_application_title(&mut self, pane_id: PaneId, title: String) -> Result<()> {
+        let application_titles_win = self.title_precedence == TitlePrecedence::Application;
+        if let Some(tab) = self
+            .tabs
+            .values_mut()
+            .find(|tab| tab.has_pane_with_pid(&pane_id))
+        {
+            if application_titles_win {
+                if let Some(pane) = tab.get_pane_with_id_mut(pane_id) {
+                    if pane.custom_title().is_some() {
+                        pane.rename(vec![]);
+                    }
+                }
+                if self.tab_name_from_title && !title.is_empty() && tab.pane_is_focused(pane_id) {
+                    // the new title replaces the name the user gave the tab
+                    tab.forget_user_set_name();
+                }
+            }
+        }
+        if self.rename_tabs_automatically() {
+            self.log_and_report_session_state()
+                .context("failed to rename tab after its pane's title")?;
+        }
         Ok(())
     }
-    fn dump_layout_to_hd(&mut self) -> Result<()> {
-        let err_context = || format!("Failed to log and report session state");
-        let session_layout_metadata = self.get_layout_metadata(Some(self.default_shell.clone()));
-        self.bus
-            .senders
-            .send_to_plugin(PluginInstruction::LogLayoutToHd(session_layout_metadata))
-            .with_context(err_context)?;
 
-        Ok(())
+    // (tab index, pane id) of every pane in the group
+    fn pane_group_members(&self, group: &str) -> Vec<(usize, PaneId)> {
+        self.tabs
+            .iter()
+            .flat_map(|(tab_index, tab)| {
+                tab.get_all_pane_ids()
+                    .into_iter()
+                    .filter(move |pane_id| {
+                        tab.get_pane_with_id(*pane_id)
+                            .and_then(|pane| pane.group())
+                            .map(|g| g.name == group)
+                            .unwrap_or(false)
+                    })
+                    .map(move |pane_id| (*tab_index, pane_id))
+            })
+            .collect()
     }
-    pub fn update_session_infos(
+
+    fn get_pane_or_active_pane_mut(
         &mut self,
-        new_session_infos: BTreeMap<String, SessionInfo>,
-        resurrectable_sessions: BTreeMap<String, Duration>,
-    ) -> Result<()> {
-        self.session_infos_on_machine = new_session_infos;
-        self.resurrectable_sessions = resurrectable_sessions;
-        self.bus
-            .senders
-            .send_to_plugin(PluginInstruction::Update(vec![(
-                None,
-                None,
-                Event::SessionUpdate(
-                    self.session_infos_on_machine.values().cloned().collect(),
-                    self.resurrectable_sessions
-                        .iter()
-                        .map(|(n, c)| (n.clone(), c.clone()))
-                        .collect(),
-                ),
-            )]))
-            .context("failed to update session info")?;
-        Ok(())
+        pane_id: Option<PaneId>,
+        client_id: ClientId,
+    ) -> Option<&mut Box<dyn Pane>> {
+        match pane_id {
+            Some(pane_id) => self
+                .tabs
+                .values_mut()
+                .find_map(|tab| tab.get_pane_with_id_mut(pane_id)),
+            None => {
+                // the cli client is not focused on any pane, so we use the first client
+                let client_id = if self.get_active_tab(client_id).is_ok() {
+                    Some(client_id)
+                } else {
+                    self.get_first_client_id()
+                };
+                client_id.and_then(|client_id| {
+                    self.get_active_tab_mut(client_id)
+                        .ok()
+                        .and_then(|tab| tab.get_active_pane_mut(client_id))
+                })
+            },
+        }
     }
 
-    pub fn update_active_tab_name(&mut self, buf: Vec<u8>, client_id: ClientId) -> Result<()> {
-        let err_context =
-            || format!("failed to update active tabs name for client id: {client_id:?}");
+    pub fn declare_port(&mut self, port: u16, name: Option<String>, pane_id: Option<PaneId>) {
+        self.declared_ports
+            .insert(port, DeclaredPort { name, pane_id });
+    }
 
-        let client_id = if self.get_active_tab(client_id).is_ok() {
-            Some(client_id)
+    /// Removes a declared port, unless it was declared for the service of another pane
+    pub fn undeclare_port(&mut self, port: u16, pane_id: Option<PaneId>) {
+        let is_declared_for_pane = self
+            .declared_ports
+            .get(&port)
+            .map_or(false, |declared_port| declared_port.pane_id == pane_id);
+        if is_declared_for_pane {
+            self.declared_ports.remove(&port);
+        }
+    }
+
+    pub fn render_port_list(&mut self, json: bool) -> String {
+        // services go away with their panes
+        let tabs = &self.tabs;
+        self.declared_ports.retain(|_port, declared_port| {
+            declared_port
+                .pane_id
+                .map(|pane_id| tabs.values().any(|tab| tab.has_pane_with_pid(&pane_id)))
+                .unwrap_or(true)
+        });
+        let ports = self.declared_ports.iter().map(|(port, declared_port)| {
+            let pane_id = declared_port.pane_id.map(|pane_id| match pane_id {
+                PaneId::Terminal(id) => format!("terminal_{}", id),
+                PaneId::Plugin(id) => format!("plugin_{}", id),
+            });
+            (port, declared_port.name.as_deref(), pane_id)
+        });
+        if json {
+            let ports: Vec<serde_json::Value> = ports
+                .map(|(port, name, pane_id)| {
+                    serde_json::json!({
+                        "port": port,
+                        "name": name,
+                        "pane_id": pane_id,
+                    })
+                })
+                .collect();
+            serde_json::Value::Array(ports).to_string()
         } else {
-            self.get_first_client_id()
+            let mut lines = vec![String::from("PORT  NAME            ZELLIJ_PANE_ID")];
+            for (port, name, pane_id) in ports {
+                // 5 - PORT, 15 - NAME
+                lines.push(format!(
+                    "{0: <5} {1: <15} {2}",
+                    port,
+                    name.unwrap_or("-"),
+                    pane_id.as_deref().unwrap_or("-")
+                ));
+            }
+            lines.join("\n")
+        }
+    }
+
+    /// A json snapshot of the session (its tabs, their panes and which of them the clients focus),
+    /// with more detail than the layout of `dump-layout` for external tooling and backups
+    pub fn render_session_dump(&self) -> String {
+        let pane_id_string = |pane_id: PaneId| match pane_id {
+            PaneId::Terminal(id) => format!("terminal_{}", id),
+            PaneId::Plugin(id) => format!("plugin_{}", id),
         };
+        let geometry = |x: usize, y: usize, rows: usize, columns: usize| serde_json::json!({ "x": x, "y": y, "rows": rows, "columns": columns });
+        let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+        tabs.sort_by_key(|tab| tab.position);
+        let tabs: Vec<serde_json::Value> = tabs
+            .iter()
+            .map(|tab| {
+                let scrollback_lengths = tab.pane_scrollback_lengths();
+                let panes: Vec<serde_json::Value> = tab
+                    .pane_infos()
+                    .iter()
+                    .map(|pane_info| {
+                        let pane_id = if pane_info.is_plugin {
+                            PaneId::Plugin(pane_info.id)
+                        } else {
+                            PaneId::Terminal(pane_info.id)
+                        };
+                        serde_json::json!({
+                            "pane_id": pane_id_string(pane_id),
+                            "title": pane_info.title,
+                            "command": pane_info.terminal_command,
+                            "plugin_url": pane_info.plugin_url,
+                            "exited": pane_info.exited,
+                            "exit_status": pane_info.exit_status,
+                            "is_held": pane_info.is_held,
+                            "is_focused": pane_info.is_focused,
+                            "is_floating": pane_info.is_floating,
+                            "is_fullscreen": pane_info.is_fullscreen,
+                            "is_suppressed": pane_info.is_suppressed,
+                            "is_selectable": pane_info.is_selectable,
+                            "scrollback_lines": scrollback_lengths.get(&pane_id).copied().unwrap_or(0),
+                            "geometry": geometry(
+                                pane_info.pane_x,
+                                pane_info.pane_y,
+                                pane_info.pane_rows,
+                                pane_info.pane_columns,
+                            ),
+                            "content_geometry": geometry(
+                                pane_info.pane_content_x,
+                                pane_info.pane_content_y,
+                                pane_info.pane_content_rows,
+                                pane_info.pane_content_columns,
+                            ),
+                        })
+                    })
+                    .collect();
+                let (swap_layout_name, is_swap_layout_dirty) = tab.swap_layout_info();
+                serde_json::json!({
+                    // tab indices are 1 based, like in go-to-tab
+                    "tab_index": tab.position + 1,
+                    "name": tab.name,
+                    "is_fullscreen_active": tab.is_fullscreen_active(),
+                    "is_sync_panes_active": tab.is_sync_panes_active(),
+                    "are_floating_panes_visible": tab.are_floating_panes_visible(),
+                    "swap_layout": swap_layout_name,
+                    "is_swap_layout_dirty": is_swap_layout_dirty,
+                    "panes": panes,
+                })
+            })
+            .collect();
+        let clients: Vec<serde_json::Value> = self
+            .active_tab_indices
+            .iter()
+            .filter_map(|(client_id, tab_index)| {
+                let tab = self.tabs.get(tab_index)?;
+                Some(serde_json::json!({
+                    "client_id": client_id,
+                    "tab_index": tab.position + 1,
+                    "focused_pane_id": tab.get_active_pane_id(*client_id).map(pane_id_string),
+                }))
+            })
+            .collect();
+        serde_json::json!({
+            "session_name": self.session_name,
+            "tabs": tabs,
+            "clients": clients,
+        })
+        .to_string()
+    }
 
-        match client_id {
-            Some(client_id) => {
-                let s = str::from_utf8(&buf)
-                    .with_context(|| format!("failed to construct tab name from buf: {buf:?}"))
-                    .with_context(err_context)?;
-                match self.get_active_tab_mut(client_id) {
-                    Ok(active_tab) => {
-                        match s {
-                            "\0" => {
-                                active_tab.name = String::new();
-                            },
-                            "\u{007F}" | "\u{0008}" => {
-                                // delete and backspace keys
-                                active_tab.name.pop();
-                            },
-                            c => {
-                                // It only allows printable unicode
-                                if buf.iter().all(|u| matches!(u, 0x20..=0x7E | 0xA0..=0xFF)) {
-                                    active_tab.name.push_str(c);
-                                }
-                            },
-                        }
-                        self.log_and_report_session_state()
-                            .with_context(err_context)
-                    },
-                    Err(err) => {
-                        Err::<(), _>(err).with_context(err_context).non_fatal();
-                        Ok(())
-                    },
-                }
-            },
-            None => Ok(()),
-        }
-    }
-    pub fn undo_active_rename_tab(&mut self, client_id: ClientId) -> Result<()> {
-        let err_context = || format!("failed to undo active tab rename for client {}", client_id);
+    pub fn resize_to_screen(&mut self, new_screen_size: Size) -> Result<()> {
+        let err_context = || format!("failed to resize to screen size: {new_screen_size:#?}");
 
-        let client_id = if self.get_active_tab(client_id).is_ok() {
-            Some(client_id)
+        if self.size != new_screen_size {
+            self.size = new_screen_size;
+            for tab in self.tabs.values_mut() {
+                tab.resize_whole_tab(new_screen_size)
+                    .with_context(err_context)?;
+                tab.set_force_render();
+            }
+            self.log_and_report_session_state()
+                .with_context(err_context)?;
+            self.render(None).with_context(err_context)
         } else {
-            self.get_first_client_id()
-        };
-        match client_id {
-            Some(client_id) => {
-                match self.get_active_tab_mut(client_id) {
-                    Ok(active_tab) => {
-                        if active_tab.name != active_tab.prev_name {
-                            active_tab.name = active_tab.prev_name.clone();
-                            self.log_and_report_session_state()
-                                .context("failed to undo renaming of active tab")?;
-                        }
-                    },
-                    Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
-                };
-                Ok(())
-            },
-            None => Ok(()),
+            Ok(())
         }
     }
 
-    pub fn move_active_tab_to_left(&mut self, client_id: ClientId) -> Result<()> {
-        let err_context = || "Failed to move active tab left";
-        if self.tabs.len() < 2 {
-            debug!("cannot move tab to left: only one tab exists");
-            return Ok(());
+    pub fn update_pixel_dimensions(&mut self, pixel_dimensions: PixelDimensions) {
+        self.pixel_dimensions.merge(pixel_dimensions);
+        if let Some(character_cell_size) = self.pixel_dimensions.character_cell_size {
+            *self.character_cell_size.borrow_mut() = Some(character_cell_size);
+        } else if let Some(text_area_size) = self.pixel_dimensions.text_area_size {
+            let character_cell_size_height = text_area_size.height / self.size.rows;
+            let character_cell_size_width = text_area_size.width / self.size.cols;
+            let character_cell_size = SizeInPixels {
+                height: character_cell_size_height,
+                width: character_cell_size_width,
+            };
+            *self.character_cell_size.borrow_mut() = Some(character_cell_size);
         }
-        let Some(client_id) = self.client_id(client_id) else {
-            return Ok(());
-        };
+    }
 
-        match self.get_active_tab(client_id) {
-            Ok(active_tab) => {
-                let active_tab_pos = active_tab.position;
-                let left_tab_pos = if active_tab_pos == 0 {
-                    self.tabs.len() - 1
-                } else {
-                    active_tab_pos - 1
-                };
+    pub fn update_terminal_background_color(&mut self, background_color_instruction: String) {
+        if let Some(AnsiCode::RgbCode((r, g, b))) =
+            xparse_color(background_color_instruction.as_bytes())
+        {
+            let bg_palette_color = PaletteColor::Rgb((r, g, b));
+            self.terminal_emulator_colors.borrow_mut().bg = bg_palette_color;
+        }
+    }
 
-                self.switch_tabs(active_tab_pos, left_tab_pos, client_id);
-                self.log_and_report_session_state()
-                    .context("failed to move tab to left")?;
-            },
-            Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
+    pub fn update_terminal_foreground_color(&mut self, foreground_color_instruction: String) {
+        if let Some(AnsiCode::RgbCode((r, g, b))) =
+            xparse_color(foreground_color_instruction.as_bytes())
+        {
+            let fg_palette_color = PaletteColor::Rgb((r, g, b));
+            self.terminal_emulator_colors.borrow_mut().fg = fg_palette_color;
         }
-        Ok(())
     }
 
-    fn client_id(&mut self, client_id: ClientId) -> Option<u16> {
-        if self.get_active_tab(client_id).is_ok() {
-            Some(client_id)
-        } else {
-            self.get_first_client_id()
+    pub fn update_terminal_color_registers(&mut self, color_registers: Vec<(usize, String)>) {
+        let mut terminal_emulator_color_codes = self.terminal_emulator_color_codes.borrow_mut();
+        for (color_register, color_sequence) in color_registers {
+            terminal_emulator_color_codes.insert(color_register, color_sequence);
         }
     }
 
-    fn switch_tabs(&mut self, active_tab_pos: usize, other_tab_pos: usize, client_id: u16) {
-        let Some(active_tab_idx) = self
-            .tabs
-            .values()
-            .find(|t| t.position == active_tab_pos)
-            .map(|t| t.index)
-        else {
-            log::error!("Failed to find active tab at position: {}", active_tab_pos);
-            return;
-        };
-        let Some(other_tab_idx) = self
-            .tabs
-            .values()
-            .find(|t| t.position == other_tab_pos)
-            .map(|t| t.index)
-        else {
-            log::error!(
-                "Failed to find tab to switch to at position: {}",
-                other_tab_pos
-            );
-            return;
-        };
+    /// Renders this [`Screen`], which amounts to rendering its active [`Tab`].
+    pub fn render(&mut self, plugin_render_assets: Option<Vec<PluginRenderAsset>>) -> Result<()> {
+        let err_context = "failed to render screen";
 
-        if !self.tabs.contains_key(&active_tab_idx) || !self.tabs.contains_key(&other_tab_idx) {
-            warn!(
-                "failed to switch tabs: index {} or {} not found in {:?}",
-                active_tab_idx,
-                other_tab_idx,
-                self.tabs.keys()
+        let mut output = Output::new(
+            self.sixel_image_store.clone(),
+            self.character_cell_size.clone(),
+            self.styled_underlines,
+        );
+        output.mute_bells(self.bells_are_muted() || self.bell_forwarding == BellForwarding::Off);
+        output.omit_sixel_images_for_clients(self.clients_without_sixel_support.clone());
+        output.send_kitty_graphics_to_clients(self.clients_with_kitty_graphics_support.clone());
+        output.downgrade_colors_for_clients(self.client_color_support.clone());
+        let mut cleared_alert = false;
+        for tab in self.tabs.values_mut() {
+            cleared_alert = tab.clear_alerts_of_focused_panes() || cleared_alert;
+        }
+        let renamed_tab = self.rename_tabs_automatically();
+        let mut tabs_to_close = vec![];
+        for (tab_index, tab) in &mut self.tabs {
+            if tab.has_selectable_tiled_panes() {
+                tab.render(&mut output).context(err_context)?;
+            } else if !tab.is_pending() {
+                tabs_to_close.push(*tab_index);
+            }
+        }
+        for tab_index in tabs_to_close {
+            // cleanup as needed
+            self.close_tab_at_index(tab_index)
+                .context(err_context)
+                .non_fatal();
+        }
+        for (client_ids, vte_instruction) in self.pending_vte_instructions.drain(..) {
+            output.add_post_vte_instruction_to_multiple_clients(
+                client_ids.into_iter(),
+                &vte_instruction,
             );
-            return;
         }
+        if output.is_dirty() {
+            let serialized_output = output.serialize().context(err_context)?;
+            let _ = self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::Render(Some(serialized_output)))
+                .context(err_context);
+        }
+        if let Some(plugin_render_assets) = plugin_render_assets {
+            let _ = self
+                .bus
+                .senders
+                .send_to_plugin(PluginInstruction::UnblockCliPipes(plugin_render_assets))
+                .context("failed to unblock input pipe");
+        }
+        if renamed_tab {
+            self.log_and_report_session_state().context(err_context)?;
+        } else if cleared_alert {
+            self.generate_and_report_tab_state().context(err_context)?;
+        }
+        Ok(())
+    }
 
-        // NOTE: Can `expect` here, because we checked that the keys exist above
-        let mut active_tab = self
-            .tabs
-            .remove(&active_tab_idx)
-            .expect("active tab not found");
-        let mut other_tab = self
-            .tabs
-            .remove(&other_tab_idx)
-            .expect("other tab not found");
-
-        std::mem::swap(&mut active_tab.index, &mut other_tab.index);
-        std::mem::swap(&mut active_tab.position, &mut other_tab.position);
-
-        // now, `active_tab.index` is changed, so we need to update it
-        self.active_tab_indices.insert(client_id, active_tab.index);
+    /// Bells are muted while do not disturb is toggled on or during the configured quiet hours
+    fn bells_are_muted(&self) -> bool {
+        self.do_not_disturb
+            || self.quiet_hours.map_or(false, |quiet_hours| {
+                let now = Local::now();
+                quiet_hours.contains((now.hour() * 60 + now.minute()) as u16)
+            })
+    }
 
-        self.tabs.insert(active_tab.index, active_tab);
-        self.tabs.insert(other_tab.index, other_tab);
+    /// Returns a mutable reference to this [`Screen`]'s tabs.
+    pub fn get_tabs_mut(&mut self) -> &mut BTreeMap<usize, Tab> {
+        &mut self.tabs
     }
 
-    pub fn move_active_tab_to_right(&mut self, client_id: ClientId) -> Result<()> {
-        let err_context = || "Failed to move active tab right ";
-        if self.tabs.len() < 2 {
-            debug!("cannot move tab to right: only one tab exists");
-            return Ok(());
+    /// Returns an immutable reference to this [`Screen`]'s active [`Tab`].
+    pub fn get_active_tab(&self, client_id: ClientId) -> Result<&Tab> {
+        match self.active_tab_indices.get(&client_id) {
+            Some(tab) => self
+                .tabs
+                .get(tab)
+                .ok_or_else(|| anyhow!("active tab {} does not exist", tab)),
+            None => Err(anyhow!("active tab not found for client {:?}", client_id)),
         }
-        let Some(client_id) = self.client_id(client_id) else {
-            return Ok(());
-        };
+    }
 
-        match self.get_active_tab(client_id) {
-            Ok(active_tab) => {
-                let active_tab_pos = active_tab.position;
-                let right_tab_pos = (active_tab_pos + 1) % self.tabs.len();
+    pub fn get_first_client_id(&self) -> Option<ClientId> {
+        self.active_tab_indices.keys().next().copied()
+    }
 
-                self.switch_tabs(active_tab_pos, right_tab_pos, client_id);
-                self.log_and_report_session_state()
-                    .context("failed to move tab to the right")?;
+    /// Returns an immutable reference to this [`Screen`]'s previous active [`Tab`].
+    /// Consumes the last entry in tab history.
+    pub fn get_previous_tab(&mut self, client_id: ClientId) -> Result<Option<&Tab>> {
+        Ok(
+            match self
+                .tab_history
+                .get_mut(&client_id)
+                .with_context(|| {
+                    format!("failed to retrieve tab history for client {client_id:?}")
+                })?
+                .pop()
+            {
+                Some(tab) => self.tabs.get(&tab),
+                None => None,
             },
-            Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
-        }
-        Ok(())
+        )
     }
 
-    pub fn change_mode(&mut self, mut mode_info: ModeInfo, client_id: ClientId) -> Result<()> {
-        if mode_info.session_name.as_ref() != Some(&self.session_name) {
-            mode_info.session_name = Some(self.session_name.clone());
+    /// Returns a mutable reference to this [`Screen`]'s active [`Tab`].
+    pub fn get_active_tab_mut(&mut self, client_id: ClientId) -> Result<&mut Tab> {
+        match self.active_tab_indices.get(&client_id) {
+            Some(tab) => self
+                .tabs
+                .get_mut(tab)
+                .ok_or_else(|| anyhow!("active tab {} does not exist", tab)),
+            None => Err(anyhow!("active tab not found for client {:?}", client_id)),
         }
+    }
 
-        let previous_mode_info = self
-            .mode_info
-            .get(&client_id)
-            .unwrap_or(&self.default_mode_info);
-        let previous_mode = previous_mode_info.mode;
-        mode_info.style = previous_mode_info.style;
-        mode_info.capabilities = previous_mode_info.capabilities;
+    /// Returns a mutable reference to this [`Screen`]'s active [`Overlays`].
+    pub fn get_active_overlays_mut(&mut self) -> &mut Vec<Overlay> {
+        &mut self.overlay.overlay_stack
+    }
 
-        let err_context = || {
-            format!(
-                "failed to change from mode '{:?}' to mode '{:?}' for client {client_id}",
-                previous_mode, mode_info.mode
-            )
-        };
+    /// Returns a mutable reference to this [`Screen`]'s indexed [`Tab`].
+    pub fn get_indexed_tab_mut(&mut self, tab_index: usize) -> Option<&mut Tab> {
+        self.get_tabs_mut().get_mut(&tab_index)
+    }
 
-        // If we leave the Search-related modes, we need to clear all previous searches
-        let search_related_modes = [InputMode::EnterSearch, InputMode::Search, InputMode::Scroll];
-        if search_related_modes.contains(&previous_mode)
-            && !search_related_modes.contains(&mode_info.mode)
+    /// Looks up the layout with this name in the layout dir (or among the built-in layouts) and
+    /// opens its tabs, or a single tab of its template if it has none
+    pub fn new_tabs_from_layout(
+        &mut self,
+        layout_name: String,
+        tab_name: Option<String>,
+        cwd: Option<PathBuf>,
+        default_shell: Option<TerminalAction>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to open new tab from layout {layout_name}");
+        let layout =
+            match Layout::from_layout_info(&self.layout_dir, LayoutInfo::File(layout_name.clone()))
+            {
+                Ok(layout) => layout,
+                Err(e) => {
+                    return self
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::LogError(
+                            vec![format!("Failed to load layout {}: {}", layout_name, e)],
+                            client_id,
+                        ))
+                        .with_context(err_context);
+                },
+            };
+        let swap_layouts = (
+            layout.swap_tiled_layouts.clone(),
+            layout.swap_floating_layouts.clone(),
+        );
+        let tabs = layout.tabs();
+        let new_tabs = if tabs.is_empty() {
+            let (tiled_panes_layout, floating_panes_layout) = layout.new_tab();
+            vec![(tab_name, tiled_panes_layout, floating_panes_layout, true)]
+        } else {
+            let focused_tab_index = layout.focused_tab_index().unwrap_or(0);
+            tabs.into_iter()
+                .enumerate()
+                .map(|(i, (name, tiled_panes_layout, floating_panes_layout))| {
+                    // the name given to the action only names the tab that gets focused
+                    let name = if i == focused_tab_index {
+                        name.or_else(|| tab_name.clone())
+                    } else {
+                        name
+                    };
+                    (
+                        name,
+                        tiled_panes_layout,
+                        floating_panes_layout,
+                        i == focused_tab_index,
+                    )
+                })
+                .collect()
+        };
+        for (name, tiled_panes_layout, floating_panes_layout, should_change_focus_to_new_tab) in
+            new_tabs
         {
-            active_tab!(self, client_id, |tab: &mut Tab| tab.clear_search(client_id));
+            self.bus
+                .senders
+                .send_to_screen(ScreenInstruction::NewTab(
+                    cwd.clone(),
+                    default_shell.clone(),
+                    Some(tiled_panes_layout),
+                    floating_panes_layout,
+                    name,
+                    swap_layouts.clone(),
+                    should_change_focus_to_new_tab,
+                    client_id,
+                ))
+                .with_context(err_context)?;
         }
+        Ok(())
+    }
 
-        if previous_mode == InputMode::Scroll
-            && (mode_info.mode == InputMode::Normal || mode_info.mode == InputMode::Locked)
-        {
-            if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
-                active_tab
-                    .clear_active_terminal_scroll(client_id)
-                    .with_context(err_context)?;
-            }
-        }
+    /// Creates a new [`Tab`] in this [`Screen`]
+    pub fn new_tab(
+        &mut self,
+        tab_index: usize,
+        swap_layouts: (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+        tab_name: Option<String>,
+        client_id: Option<ClientId>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to create new tab for client {client_id:?}",);
 
-        if mode_info.mode == InputMode::RenameTab {
-            if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
-                active_tab.prev_name = active_tab.name.clone();
+        let client_id = client_id.map(|client_id| {
+            if self.get_active_tab(client_id).is_ok() {
+                client_id
+            } else if let Some(first_client_id) = self.get_first_client_id() {
+                first_client_id
+            } else {
+                client_id
             }
+        });
+
+        let tab_name = tab_name.unwrap_or_else(|| String::new());
+
+        let position = self.tabs.len();
+        let mut tab = Tab::new(
+            tab_index,
+            position,
+            tab_name,
+            self.size,
+            self.character_cell_size.clone(),
+            self.stacked_resize.clone(),
+            self.sixel_image_store.clone(),
+            self.bus
+                .os_input
+                .as_ref()
+                .with_context(err_context)?
+                .clone(),
+            self.bus.senders.clone(),
+            self.max_panes,
+            self.style,
+            self.default_mode_info.clone(),
+            self.draw_pane_frames,
+            self.auto_layout,
+            self.connected_clients.clone(),
+            self.session_is_mirrored,
+            client_id,
+            self.copy_options.clone(),
+            self.terminal_emulator_colors.clone(),
+            self.terminal_emulator_color_codes.clone(),
+            swap_layouts,
+            self.default_shell.clone(),
+            self.debug,
+            self.arrow_fonts,
+            self.styled_underlines,
+            self.explicitly_disable_kitty_keyboard_protocol,
+            self.default_editor.clone(),
+        );
+        tab.set_alternate_screen_behavior(self.alternate_screen_behavior);
+        tab.update_unicode_width(self.unicode_width);
+        for (client_id, mode_info) in &self.mode_info {
+            tab.change_mode_info(mode_info.clone(), *client_id);
+        }
+        self.tabs.insert(tab_index, tab);
+        Ok(())
+    }
+    pub fn apply_layout(
+        &mut self,
+        layout: TiledPaneLayout,
+        floating_panes_layout: Vec<FloatingPaneLayout>,
+        new_terminal_ids: Vec<(u32, HoldForCommand)>,
+        new_floating_terminal_ids: Vec<(u32, HoldForCommand)>,
+        new_plugin_ids: HashMap<RunPluginOrAlias, Vec<u32>>,
+        tab_index: usize,
+        should_change_client_focus: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        if self.tabs.get(&tab_index).is_none() {
+            // TODO: we should prevent this situation with a UI - eg. cannot close tabs with a
+            // pending state
+            log::error!("Tab with index {tab_index} not found. Cannot apply layout!");
+            return Ok(());
         }
+        let client_id = if self.get_active_tab(client_id).is_ok() {
+            client_id
+        } else if let Some(first_client_id) = self.get_first_client_id() {
+            first_client_id
+        } else {
+            client_id
+        };
+        let err_context = || format!("failed to apply layout for tab {tab_index:?}",);
 
-        if mode_info.mode == InputMode::RenamePane {
-            if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
-                if let Some(active_pane) =
-                    active_tab.get_active_pane_or_floating_pane_mut(client_id)
+        // move the relevant clients out of the current tab and place them in the new one
+        let drained_clients = if should_change_client_focus {
+            if self.session_is_mirrored {
+                let client_mode_infos_in_source_tab = if let Ok(active_tab) =
+                    self.get_active_tab_mut(client_id)
                 {
-                    active_pane.store_pane_name();
+                    let client_mode_infos_in_source_tab = active_tab.drain_connected_clients(None);
+                    if active_tab.has_no_connected_clients() {
+                        active_tab
+                            .visible(false)
+                            .with_context(err_context)
+                            .non_fatal();
+                    }
+                    Some(client_mode_infos_in_source_tab)
+                } else {
+                    None
+                };
+                let all_connected_clients: Vec<ClientId> =
+                    self.connected_clients.borrow().iter().copied().collect();
+                for client_id in all_connected_clients {
+                    self.update_client_tab_focus(client_id, tab_index);
+                }
+                client_mode_infos_in_source_tab
+            } else if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
+                let client_mode_info_in_source_tab =
+                    active_tab.drain_connected_clients(Some(vec![client_id]));
+                if active_tab.has_no_connected_clients() {
+                    active_tab
+                        .visible(false)
+                        .with_context(err_context)
+                        .non_fatal();
                 }
+                self.update_client_tab_focus(client_id, tab_index);
+                Some(client_mode_info_in_source_tab)
+            } else {
+                None
             }
+        } else {
+            None
+        };
+
+        // apply the layout to the new tab
+        let clients_moved_to_new_tab = drained_clients.is_some();
+        self.tabs
+            .get_mut(&tab_index)
+            .context("couldn't find tab with index {tab_index}")
+            .and_then(|tab| {
+                tab.apply_layout(
+                    layout,
+                    floating_panes_layout,
+                    new_terminal_ids,
+                    new_floating_terminal_ids,
+                    new_plugin_ids,
+                    client_id,
+                )?;
+                tab.update_input_modes()?;
+
+                if let Some(drained_clients) = drained_clients {
+                    tab.visible(true)?;
+                    tab.add_multiple_clients(drained_clients)?;
+                }
+                tab.resize_whole_tab(self.size).with_context(err_context)?;
+                tab.set_force_render();
+                Ok(())
+            })
+            .with_context(err_context)?;
+        if clients_moved_to_new_tab {
+            self.bring_global_floating_panes_to_tab(tab_index)
+                .with_context(err_context)?;
         }
 
-        self.style = mode_info.style;
-        self.mode_info.insert(client_id, mode_info.clone());
-        for tab in self.tabs.values_mut() {
-            tab.change_mode_info(mode_info.clone(), client_id);
-            tab.mark_active_pane_for_rerender(client_id);
-            tab.update_input_modes()?;
+        if !self.active_tab_indices.contains_key(&client_id) {
+            // this means this is a new client and we need to add it to our state properly
+            self.add_client(client_id).with_context(err_context)?;
         }
-        Ok(())
+
+        self.log_and_report_session_state()
+            .and_then(|_| self.render(None))
+            .with_context(err_context)
     }
-    pub fn change_mode_for_all_clients(&mut self, mode_info: ModeInfo) -> Result<()> {
-        let err_context = || {
-            format!(
-                "failed to change input mode to {:?} for all clients",
-                mode_info.mode
-            )
+
+    pub fn add_client(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context = |tab_index| {
+            format!("failed to attach client {client_id} to tab with index {tab_index}")
         };
 
-        let connected_client_ids: Vec<ClientId> = self.active_tab_indices.keys().copied().collect();
-        for client_id in connected_client_ids {
-            self.change_mode(mode_info.clone(), client_id)
-                .with_context(err_context)?;
+        let mut tab_history = vec![];
+        if let Some((_first_client, first_tab_history)) = self.tab_history.iter().next() {
+            tab_history = first_tab_history.clone();
         }
-        Ok(())
-    }
-    pub fn move_focus_left_or_previous_tab(&mut self, client_id: ClientId) -> Result<()> {
-        let err_context = || {
-            format!(
-                "failed to move focus left or to previous tab for client {}",
-                client_id
-            )
-        };
 
-        let client_id = if self.get_active_tab(client_id).is_ok() {
-            Some(client_id)
+        let tab_index = if let Some((_first_client, first_active_tab_index)) =
+            self.active_tab_indices.iter().next()
+        {
+            *first_active_tab_index
+        } else if self.tabs.contains_key(&0) {
+            0
+        } else if let Some(tab_index) = self.tabs.keys().next() {
+            tab_index.to_owned()
         } else {
-            self.get_first_client_id()
+            bail!("Can't find a valid tab to attach client to!");
         };
-        if let Some(client_id) = client_id {
-            match self.get_active_tab_mut(client_id) {
-                Ok(active_tab) => {
-                    active_tab
-                        .move_focus_left(client_id)
-                        .and_then(|success| {
-                            if !success {
-                                self.switch_tab_prev(Some(Direction::Left), true, client_id)
-                                    .context("failed to move focus to previous tab")
-                            } else {
-                                Ok(())
-                            }
-                        })
-                        .with_context(err_context)?;
-                },
-                Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
-            };
+
+        self.active_tab_indices.insert(client_id, tab_index);
+        self.connected_clients.borrow_mut().insert(client_id);
+        self.tab_history.insert(client_id, tab_history);
+        self.clients_last_input.insert(client_id, Instant::now());
+        self.session_activity();
+        self.tabs
+            .get_mut(&tab_index)
+            .with_context(|| err_context(tab_index))?
+            .add_client(client_id, None)
+            .with_context(|| err_context(tab_index))
+    }
+
+    pub fn remove_client(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context = || format!("failed to remove client {client_id}");
+
+        for (_, tab) in self.tabs.iter_mut() {
+            tab.remove_client(client_id);
+            if tab.has_no_connected_clients() {
+                tab.visible(false).with_context(err_context)?;
+            }
+        }
+        if self.active_tab_indices.contains_key(&client_id) {
+            self.active_tab_indices.remove(&client_id);
         }
+        if self.tab_history.contains_key(&client_id) {
+            self.tab_history.remove(&client_id);
+        }
+        self.connected_clients.borrow_mut().remove(&client_id);
+        self.clients_without_sixel_support.remove(&client_id);
+        self.clients_with_kitty_graphics_support.remove(&client_id);
+        self.client_color_support.remove(&client_id);
+        self.clients_last_input.remove(&client_id);
+        self.clients_warned_of_idle_detach.remove(&client_id);
+        // the session is idle from the moment its last client leaves
+        self.session_activity();
         self.log_and_report_session_state()
+            .with_context(err_context)
+    }
+
+    pub fn generate_and_report_tab_state(&mut self) -> Result<Vec<TabInfo>> {
+        let mut plugin_updates = vec![];
+        let mut tab_infos_for_screen_state = BTreeMap::new();
+        for tab in self.tabs.values() {
+            let all_focused_clients: Vec<ClientId> = self
+                .active_tab_indices
+                .iter()
+                .filter(|(_c_id, tab_position)| **tab_position == tab.index)
+                .map(|(c_id, _)| c_id)
+                .copied()
+                .collect();
+            let (active_swap_layout_name, is_swap_layout_dirty) = tab.swap_layout_info();
+            let tab_viewport = tab.get_viewport();
+            let tab_display_area = tab.get_display_area();
+            let selectable_tiled_panes_count = tab.get_selectable_tiled_panes_count();
+            let selectable_floating_panes_count = tab.get_selectable_floating_panes_count();
+            let tab_info_for_screen = TabInfo {
+                position: tab.position,
+                name: tab.name.clone(),
+                active: self.active_tab_indices.values().any(|i| i == &tab.index),
+                panes_to_hide: tab.panes_to_hide_count(),
+                is_fullscreen_active: tab.is_fullscreen_active(),
+                is_sync_panes_active: tab.is_sync_panes_active(),
+                are_floating_panes_visible: tab.are_floating_panes_visible(),
+                other_focused_clients: all_focused_clients,
+                active_swap_layout_name,
+                is_swap_layout_dirty,
+                viewport_rows: tab_viewport.rows,
+                viewport_columns: tab_viewport.cols,
+                display_area_rows: tab_display_area.rows,
+                display_area_columns: tab_display_area.cols,
+                selectable_tiled_panes_count,
+                selectable_floating_panes_count,
+                has_activity_alert: tab.has_monitor_alert(MonitorAlert::Activity),
+                has_silence_alert: tab.has_monitor_alert(MonitorAlert::Silence),
+                has_bell_alert: tab.has_bell_alert(),
+                has_command_exit_alert: tab.has_command_exit_alert(),
+                has_input_locked_pane: tab.has_input_locked_pane(),
+            };
+            tab_infos_for_screen_state.insert(tab.position, tab_info_for_screen);
+        }
+        for (client_id, active_tab_index) in self.active_tab_indices.iter() {
+            let mut plugin_tab_updates = vec![];
+            for tab in self.tabs.values() {
+                let other_focused_clients: Vec<ClientId> = if self.session_is_mirrored {
+                    vec![]
+                } else {
+                    self.active_tab_indices
+                        .iter()
+                        .filter(|(c_id, tab_position)| {
+                            **tab_position == tab.index && *c_id != client_id
+                        })
+                        .map(|(c_id, _)| c_id)
+                        .copied()
+                        .collect()
+                };
+                let (active_swap_layout_name, is_swap_layout_dirty) = tab.swap_layout_info();
+                let tab_viewport = tab.get_viewport();
+                let tab_display_area = tab.get_display_area();
+                let selectable_tiled_panes_count = tab.get_selectable_tiled_panes_count();
+                let selectable_floating_panes_count = tab.get_selectable_floating_panes_count();
+                let tab_info_for_plugins = TabInfo {
+                    position: tab.position,
+                    name: tab.name.clone(),
+                    active: *active_tab_index == tab.index,
+                    panes_to_hide: tab.panes_to_hide_count(),
+                    is_fullscreen_active: tab.is_fullscreen_active(),
+                    is_sync_panes_active: tab.is_sync_panes_active(),
+                    are_floating_panes_visible: tab.are_floating_panes_visible(),
+                    other_focused_clients,
+                    active_swap_layout_name,
+                    is_swap_layout_dirty,
+                    viewport_rows: tab_viewport.rows,
+                    viewport_columns: tab_viewport.cols,
+                    display_area_rows: tab_display_area.rows,
+                    display_area_columns: tab_display_area.cols,
+                    selectable_tiled_panes_count,
+                    selectable_floating_panes_count,
+                    has_activity_alert: tab.has_monitor_alert(MonitorAlert::Activity),
+                    has_silence_alert: tab.has_monitor_alert(MonitorAlert::Silence),
+                    has_bell_alert: tab.has_bell_alert(),
+                    has_command_exit_alert: tab.has_command_exit_alert(),
+                    has_input_locked_pane: tab.has_input_locked_pane(),
+                };
+                plugin_tab_updates.push(tab_info_for_plugins);
+            }
+            plugin_updates.push((None, Some(*client_id), Event::TabUpdate(plugin_tab_updates)));
+        }
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(plugin_updates))
+            .context("failed to update tabs")?;
+        Ok(tab_infos_for_screen_state.values().cloned().collect())
+    }
+    fn generate_and_report_pane_state(&mut self) -> Result<PaneManifest> {
+        let mut pane_manifest = PaneManifest::default();
+        for tab in self.tabs.values() {
+            pane_manifest.panes.insert(tab.position, tab.pane_infos());
+        }
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(vec![(
+                None,
+                None,
+                Event::PaneUpdate(pane_manifest.clone()),
+            )]))
+            .context("failed to update tabs")?;
+
+        Ok(pane_manifest)
+    }
+    fn log_and_report_session_state(&mut self) -> Result<()> {
+        let err_context = || format!("Failed to log and report session state");
+        // generate own session info
+        let pane_manifest = self.generate_and_report_pane_state()?;
+        let tab_infos = self.generate_and_report_tab_state()?;
+        // for hooks and clients following the session's events (eg. `zellij events`), this is
+        // best effort
+        if self.publish_session_events {
+            let _ = self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::PublishEvent(Event::TabUpdate(
+                    tab_infos.clone(),
+                )));
+            let _ = self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::PublishEvent(Event::PaneUpdate(
+                    pane_manifest.clone(),
+                )));
+        }
+        // in the context of unit/integration tests, we don't need to list available layouts
+        // because this is mostly about HD access - it does however throw off the timing in the
+        // tests and causes them to flake, which is why we skip it here
+        #[cfg(not(test))]
+        let available_layouts =
+            Layout::list_available_layouts(self.layout_dir.clone(), &self.default_layout_name);
+        #[cfg(test)]
+        let available_layouts = vec![];
+        let session_info = SessionInfo {
+            name: self.session_name.clone(),
+            tabs: tab_infos,
+            panes: pane_manifest,
+            connected_clients: self.active_tab_indices.keys().len(),
+            is_current_session: true,
+            available_layouts,
+            plugins: Default::default(), // these are filled in by the wasm thread
+            tab_history: self.tab_history.clone(),
+            tags: self.session_tags.clone(),
+            note: self.session_note.clone(),
+        };
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::ReportSessionInfo(
+                self.session_name.to_owned(),
+                session_info,
+            ))
+            .with_context(err_context)?;
+
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::ReadAllSessionInfosOnMachine)
             .with_context(err_context)?;
+        self.report_refused_new_panes().with_context(err_context)?;
+        self.report_copy_mode_clients().with_context(err_context)?;
         Ok(())
     }
-    pub fn move_focus_right_or_next_tab(&mut self, client_id: ClientId) -> Result<()> {
-        let err_context = || {
-            format!(
-                "failed to move focus right or to next tab for client {}",
-                client_id
-            )
-        };
+    fn dump_layout_to_hd(&mut self) -> Result<()> {
+        let err_context = || format!("Failed to log and report session state");
+        let session_layout_metadata = self.get_layout_metadata(Some(self.default_shell.clone()));
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::LogLayoutToHd(session_layout_metadata))
+            .with_context(err_context)?;
+
+        Ok(())
+    }
+    pub fn update_session_infos(
+        &mut self,
+        new_session_infos: BTreeMap<String, SessionInfo>,
+        resurrectable_sessions: BTreeMap<String, Duration>,
+    ) -> Result<()> {
+        self.session_infos_on_machine = new_session_infos;
+        self.resurrectable_sessions = resurrectable_sessions;
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(vec![(
+                None,
+                None,
+                Event::SessionUpdate(
+                    self.session_infos_on_machine.values().cloned().collect(),
+                    self.resurrectable_sessions
+                        .iter()
+                        .map(|(n, c)| (n.clone(), c.clone()))
+                        .collect(),
+                ),
+            )]))
+            .context("failed to update session info")?;
+        Ok(())
+    }
+
+    pub fn update_active_tab_name(&mut self, buf: Vec<u8>, client_id: ClientId) -> Result<()> {
+        let err_context =
+            || format!("failed to update active tabs name for client id: {client_id:?}");
 
         let client_id = if self.get_active_tab(client_id).is_ok() {
             Some(client_id)
@@ -2008,2884 +3397,5080 @@ impl Screen {
             self.get_first_client_id()
         };
 
-        if let Some(client_id) = client_id {
-            match self.get_active_tab_mut(client_id) {
-                Ok(active_tab) => {
-                    active_tab
-                        .move_focus_right(client_id)
-                        .and_then(|success| {
-                            if !success {
-                                self.switch_tab_next(Some(Direction::Right), true, client_id)
-                                    .context("failed to move focus to next tab")
-                            } else {
-                                Ok(())
-                            }
-                        })
-                        .with_context(err_context)?;
-                },
-                Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
-            };
+        match client_id {
+            Some(client_id) => {
+                let s = str::from_utf8(&buf)
+                    .with_context(|| format!("failed to construct tab name from buf: {buf:?}"))
+                    .with_context(err_context)?;
+                match self.get_active_tab_mut(client_id) {
+                    Ok(active_tab) => {
+                        match s {
+                            "\0" => {
+                                active_tab.name = String::new();
+                            },
+                            "\u{007F}" | "\u{0008}" => {
+                                // delete and backspace keys
+                                active_tab.name.pop();
+                            },
+                            c => {
+                                // It only allows printable unicode
+                                if buf.iter().all(|u| matches!(u, 0x20..=0x7E | 0xA0..=0xFF)) {
+                                    active_tab.name.push_str(c);
+                                }
+                            },
+                        }
+                        self.log_and_report_session_state()
+                            .with_context(err_context)
+                    },
+                    Err(err) => {
+                        Err::<(), _>(err).with_context(err_context).non_fatal();
+                        Ok(())
+                    },
+                }
+            },
+            None => Ok(()),
         }
-        self.log_and_report_session_state()
-            .with_context(err_context)?;
-        Ok(())
     }
-    pub fn toggle_tab(&mut self, client_id: ClientId) -> Result<()> {
-        let tab = self
-            .get_previous_tab(client_id)
-            .context("failed to toggle tabs")?;
-        if let Some(t) = tab {
-            let position = t.position;
-            self.go_to_tab(position + 1, client_id)
-                .context("failed to toggle tabs")?;
-        };
+    pub fn undo_active_rename_tab(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context = || format!("failed to undo active tab rename for client {}", client_id);
 
-        self.log_and_report_session_state()
-            .context("failed to toggle tabs")?;
-        self.render(None)
+        let client_id = if self.get_active_tab(client_id).is_ok() {
+            Some(client_id)
+        } else {
+            self.get_first_client_id()
+        };
+        match client_id {
+            Some(client_id) => {
+                match self.get_active_tab_mut(client_id) {
+                    Ok(active_tab) => {
+                        if active_tab.name != active_tab.prev_name {
+                            active_tab.name = active_tab.prev_name.clone();
+                            self.log_and_report_session_state()
+                                .context("failed to undo renaming of active tab")?;
+                        }
+                    },
+                    Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
+                };
+                Ok(())
+            },
+            None => Ok(()),
+        }
     }
 
-    pub fn focus_plugin_pane(
-        &mut self,
-        run_plugin: &RunPluginOrAlias,
-        should_float: bool,
-        move_to_focused_tab: bool,
-        client_id: ClientId,
-    ) -> Result<bool> {
-        // true => found and focused, false => not
-        let err_context = || format!("failed to focus_plugin_pane");
-        let mut tab_index_and_plugin_pane_id = None;
-        let mut plugin_pane_to_move_to_active_tab = None;
-        let focused_tab_index = *self.active_tab_indices.get(&client_id).unwrap_or(&0);
-        let all_tabs = self.get_tabs_mut();
-        for (tab_index, tab) in all_tabs.iter_mut() {
-            if let Some(plugin_pane_id) = tab.find_plugin(&run_plugin) {
-                tab_index_and_plugin_pane_id = Some((*tab_index, plugin_pane_id));
-                if move_to_focused_tab && focused_tab_index != *tab_index {
-                    plugin_pane_to_move_to_active_tab = tab.extract_pane(plugin_pane_id, true);
-                }
-
-                break;
-            }
+    pub fn move_active_tab_to_left(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context = || "Failed to move active tab left";
+        if self.tabs.len() < 2 {
+            debug!("cannot move tab to left: only one tab exists");
+            return Ok(());
         }
-        if let Some(plugin_pane_to_move_to_active_tab) = plugin_pane_to_move_to_active_tab.take() {
-            let pane_id = plugin_pane_to_move_to_active_tab.pid();
-            let new_active_tab = self.get_active_tab_mut(client_id)?;
+        let Some(client_id) = self.client_id(client_id) else {
+            return Ok(());
+        };
 
-            if should_float {
-                new_active_tab.show_floating_panes();
-                new_active_tab.add_floating_pane(
-                    plugin_pane_to_move_to_active_tab,
-                    pane_id,
-                    None,
-                    true,
-                )?;
-            } else {
-                new_active_tab.hide_floating_panes();
-                new_active_tab.add_tiled_pane(
-                    plugin_pane_to_move_to_active_tab,
-                    pane_id,
-                    Some(client_id),
-                )?;
-            }
-            return Ok(true);
-        }
-        match tab_index_and_plugin_pane_id {
-            Some((tab_index, plugin_pane_id)) => {
-                self.go_to_tab(tab_index + 1, client_id)?;
-                self.tabs
-                    .get_mut(&tab_index)
-                    .with_context(err_context)?
-                    .focus_pane_with_id(plugin_pane_id, should_float, client_id)
-                    .context("failed to focus plugin pane")?;
+        match self.get_active_tab(client_id) {
+            Ok(active_tab) => {
+                let active_tab_pos = active_tab.position;
+                let left_tab_pos = if active_tab_pos == 0 {
+                    self.tabs.len() - 1
+                } else {
+                    active_tab_pos - 1
+                };
+
+                self.switch_tabs(active_tab_pos, left_tab_pos, client_id);
                 self.log_and_report_session_state()
-                    .with_context(err_context)?;
-                Ok(true)
+                    .context("failed to move tab to left")?;
             },
-            None => Ok(false),
+            Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
         }
+        Ok(())
     }
 
-    pub fn focus_pane_with_id(
+    /// Move the active tab to a 0 based position, shifting the tabs in between
+    pub fn move_active_tab_to_position(
         &mut self,
-        pane_id: PaneId,
-        should_float_if_hidden: bool,
+        new_position: usize,
         client_id: ClientId,
     ) -> Result<()> {
-        let err_context = || format!("failed to focus_plugin_pane");
-        let tab_index = self
-            .tabs
-            .iter()
-            .find(|(_tab_index, tab)| tab.has_pane_with_pid(&pane_id))
-            .map(|(_tab_index, tab)| tab.position);
-        match tab_index {
-            Some(tab_index) => {
-                self.go_to_tab(tab_index + 1, client_id)?;
-                self.tabs
-                    .iter_mut()
-                    .find(|(_, t)| t.position == tab_index)
-                    .map(|(_, t)| t.focus_pane_with_id(pane_id, should_float_if_hidden, client_id))
-                    .with_context(err_context)
-                    .non_fatal();
-            },
-            None => {
-                log::error!("Could not find pane with id: {:?}", pane_id);
-            },
+        let Some(client_id) = self.client_id(client_id) else {
+            return Ok(());
         };
-        Ok(())
-    }
-    pub fn rerun_command_pane_with_id(&mut self, terminal_pane_id: u32) {
-        let mut found = false;
-        for tab in self.tabs.values_mut() {
-            if tab.has_pane_with_pid(&PaneId::Terminal(terminal_pane_id)) {
-                tab.rerun_terminal_pane_with_id(terminal_pane_id);
-                found = true;
-                break;
-            }
-        }
-        if !found {
-            log::error!(
-                "Failed to find terminal pane with id: {} to run",
-                terminal_pane_id
-            );
+        let new_position = new_position.min(self.tabs.len().saturating_sub(1));
+        let mut active_tab_pos = self
+            .get_active_tab(client_id)
+            .context("failed to move tab to position")?
+            .position;
+        while active_tab_pos != new_position {
+            let other_tab_pos = if active_tab_pos > new_position {
+                active_tab_pos - 1
+            } else {
+                active_tab_pos + 1
+            };
+            self.switch_tabs(active_tab_pos, other_tab_pos, client_id);
+            active_tab_pos = other_tab_pos;
         }
+        self.log_and_report_session_state()
+            .context("failed to move tab to position")
     }
-    pub fn resize_pane_with_id(&mut self, resize: ResizeStrategy, pane_id: PaneId) {
-        let mut found = false;
-        for tab in self.tabs.values_mut() {
-            if tab.has_pane_with_pid(&pane_id) {
-                tab.resize_pane_with_id(resize, pane_id).non_fatal();
-                found = true;
-                break;
-            }
-        }
-        if !found {
-            log::error!("Failed to find pane with id: {:?} to resize", pane_id);
+
+    /// The position of the tab with this 1 based index or name, if there is one
+    pub fn tab_position_for_target(&self, tab_target: &TabTarget) -> Option<usize> {
+        match tab_target {
+            TabTarget::Index(index) => self
+                .tabs
+                .values()
+                .find(|t| t.position + 1 == *index)
+                .map(|t| t.position),
+            TabTarget::Name(name) => self
+                .tabs
+                .values()
+                .find(|t| t.name == *name)
+                .map(|t| t.position),
         }
     }
-    pub fn break_pane(
+
+    /// Move a pane (by default the focused pane of the client) to the tab at this position,
+    /// without changing the focused tab
+    pub fn move_pane_to_tab(
         &mut self,
-        default_shell: Option<TerminalAction>,
-        default_layout: Box<Layout>,
+        pane_id: Option<PaneId>,
+        tab_position: usize,
         client_id: ClientId,
     ) -> Result<()> {
-        let err_context = || "failed break pane out of tab".to_string();
-        let active_tab = self.get_active_tab_mut(client_id)?;
-        if active_tab.get_selectable_tiled_panes_count() > 1
-            || active_tab.get_visible_selectable_floating_panes_count() > 0
-        {
-            let active_pane_id = active_tab
-                .get_active_pane_id(client_id)
-                .with_context(err_context)?;
-            let pane_to_break_is_floating = active_tab.are_floating_panes_visible();
-            let active_pane = active_tab
-                .extract_pane(active_pane_id, false)
-                .with_context(err_context)?;
-            let active_pane_run_instruction = active_pane.invoked_with().clone();
-            let tab_index = self.get_new_tab_index();
-            let swap_layouts = (
-                default_layout.swap_tiled_layouts.clone(),
-                default_layout.swap_floating_layouts.clone(),
-            );
-            self.new_tab(tab_index, swap_layouts, None, Some(client_id))?;
-            let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
-            let (mut tiled_panes_layout, mut floating_panes_layout) = default_layout.new_tab();
-            if pane_to_break_is_floating {
-                tab.show_floating_panes();
-                tab.add_floating_pane(active_pane, active_pane_id, None, true)?;
-                if let Some(already_running_layout) = floating_panes_layout
-                    .iter_mut()
-                    .find(|i| i.run == active_pane_run_instruction)
-                {
-                    already_running_layout.already_running = true;
-                }
-            } else {
-                tab.add_tiled_pane(active_pane, active_pane_id, Some(client_id))?;
-                tiled_panes_layout.ignore_run_instruction(active_pane_run_instruction.clone());
-            }
-            let should_change_focus_to_new_tab = true;
-            self.bus.senders.send_to_plugin(PluginInstruction::NewTab(
-                None,
-                default_shell,
-                Some(tiled_panes_layout),
-                floating_panes_layout,
-                tab_index,
-                should_change_focus_to_new_tab,
-                client_id,
-            ))?;
-        } else {
-            let active_pane_id = active_tab
-                .get_active_pane_id(client_id)
-                .with_context(err_context)?;
-            self.bus
-                .senders
-                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
-                    vec![active_pane_id],
-                    "Cannot break single pane out!".into(),
-                ))
-                .with_context(err_context)?;
-            self.unblock_input()?;
+        let pane_id = match pane_id {
+            Some(pane_id) => Some(pane_id),
+            None => self.client_id(client_id).and_then(|client_id| {
+                self.get_active_tab(client_id)
+                    .ok()
+                    .and_then(|tab| tab.get_active_pane_id(client_id))
+            }),
+        };
+        match pane_id {
+            Some(pane_id) => {
+                let should_change_focus_to_new_tab = false;
+                self.break_multiple_panes_to_tab_with_index(
+                    vec![pane_id],
+                    tab_position,
+                    should_change_focus_to_new_tab,
+                    client_id,
+                )
+            },
+            None => Ok(()),
         }
-        Ok(())
     }
-    pub fn break_multiple_panes_to_new_tab(
-        &mut self,
-        pane_ids: Vec<PaneId>,
-        default_shell: Option<TerminalAction>,
-        should_change_focus_to_new_tab: bool,
-        new_tab_name: Option<String>,
-        client_id: ClientId,
-    ) -> Result<()> {
-        let err_context = || "failed break multiple panes to a new tab".to_string();
-
-        let all_tabs = self.get_tabs_mut();
-        let mut extracted_panes = vec![];
-        for pane_id in pane_ids {
-            for tab in all_tabs.values_mut() {
-                // here we pass None instead of the client_id we have because we do not need to
-                // necessarily trigger a relayout for this tab
-                if let Some(pane) = tab.extract_pane(pane_id, true).take() {
-                    extracted_panes.push(pane);
-                    break;
-                }
-            }
-        }
 
-        let (mut tiled_panes_layout, floating_panes_layout) = self.default_layout.new_tab();
-        let tab_index = self.get_new_tab_index();
-        let swap_layouts = (
-            self.default_layout.swap_tiled_layouts.clone(),
-            self.default_layout.swap_floating_layouts.clone(),
-        );
-        if should_change_focus_to_new_tab {
-            self.new_tab(tab_index, swap_layouts, None, Some(client_id))?;
+    fn client_id(&mut self, client_id: ClientId) -> Option<u16> {
+        if self.get_active_tab(client_id).is_ok() {
+            Some(client_id)
         } else {
-            self.new_tab(tab_index, swap_layouts, None, None)?;
-        }
-        let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
-        if let Some(new_tab_name) = new_tab_name {
-            tab.name = new_tab_name.clone();
-        }
-        for pane in extracted_panes {
-            let run_instruction = pane.invoked_with().clone();
-            let pane_id = pane.pid();
-            // here we pass None instead of the ClientId, because we do not want this pane to be
-            // necessarily focused
-            tab.add_tiled_pane(pane, pane_id, None)?;
-            tiled_panes_layout.ignore_run_instruction(run_instruction.clone());
+            self.get_first_client_id()
         }
-        self.bus.senders.send_to_plugin(PluginInstruction::NewTab(
-            None,
-            default_shell,
-            Some(tiled_panes_layout),
-            floating_panes_layout,
-            tab_index,
-            should_change_focus_to_new_tab,
-            client_id,
-        ))?;
-        Ok(())
     }
-    pub fn break_pane_to_new_tab(
-        &mut self,
-        direction: Direction,
-        client_id: ClientId,
-    ) -> Result<()> {
-        let err_context = || "failed break pane out of tab".to_string();
-        if self.tabs.len() > 1 {
-            let (active_pane_id, active_pane, pane_to_break_is_floating) = {
-                let active_tab = self.get_active_tab_mut(client_id)?;
-                let active_pane_id = active_tab
-                    .get_active_pane_id(client_id)
-                    .with_context(err_context)?;
-                let pane_to_break_is_floating = active_tab.are_floating_panes_visible();
-                let active_pane = active_tab
-                    .extract_pane(active_pane_id, false)
-                    .with_context(err_context)?;
-                (active_pane_id, active_pane, pane_to_break_is_floating)
-            };
-            let update_mode_infos = false;
-            match direction {
-                Direction::Right | Direction::Down => {
-                    self.switch_tab_next(None, update_mode_infos, client_id)?;
-                },
-                Direction::Left | Direction::Up => {
-                    self.switch_tab_prev(None, update_mode_infos, client_id)?;
-                },
-            };
-            let new_active_tab = self.get_active_tab_mut(client_id)?;
 
-            if pane_to_break_is_floating {
-                new_active_tab.show_floating_panes();
-                new_active_tab.add_floating_pane(active_pane, active_pane_id, None, true)?;
-            } else {
-                new_active_tab.hide_floating_panes();
-                new_active_tab.add_tiled_pane(active_pane, active_pane_id, Some(client_id))?;
-            }
+    fn switch_tabs(&mut self, active_tab_pos: usize, other_tab_pos: usize, client_id: u16) {
+        let Some(active_tab_idx) = self
+            .tabs
+            .values()
+            .find(|t| t.position == active_tab_pos)
+            .map(|t| t.index)
+        else {
+            log::error!("Failed to find active tab at position: {}", active_tab_pos);
+            return;
+        };
+        let Some(other_tab_idx) = self
+            .tabs
+            .values()
+            .find(|t| t.position == other_tab_pos)
+            .map(|t| t.index)
+        else {
+            log::error!(
+                "Failed to find tab to switch to at position: {}",
+                other_tab_pos
+            );
+            return;
+        };
 
-            self.log_and_report_session_state()?;
-        } else {
-            let active_pane_id = {
-                let active_tab = self.get_active_tab_mut(client_id)?;
-                active_tab
-                    .get_active_pane_id(client_id)
-                    .with_context(err_context)?
-            };
-            self.bus
-                .senders
-                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
-                    vec![active_pane_id],
-                    "No other tabs to add pane to!".into(),
-                ))
-                .with_context(err_context)?;
+        if !self.tabs.contains_key(&active_tab_idx) || !self.tabs.contains_key(&other_tab_idx) {
+            warn!(
+                "failed to switch tabs: index {} or {} not found in {:?}",
+                active_tab_idx,
+                other_tab_idx,
+                self.tabs.keys()
+            );
+            return;
         }
-        self.unblock_input()?;
-        self.render(None)?;
-        Ok(())
+
+        // NOTE: Can `expect` here, because we checked that the keys exist above
+        let mut active_tab = self
+            .tabs
+            .remove(&active_tab_idx)
+            .expect("active tab not found");
+        let mut other_tab = self
+            .tabs
+            .remove(&other_tab_idx)
+            .expect("other tab not found");
+
+        std::mem::swap(&mut active_tab.index, &mut other_tab.index);
+        std::mem::swap(&mut active_tab.position, &mut other_tab.position);
+
+        // now, `active_tab.index` is changed, so we need to update it
+        self.active_tab_indices.insert(client_id, active_tab.index);
+
+        self.tabs.insert(active_tab.index, active_tab);
+        self.tabs.insert(other_tab.index, other_tab);
     }
-    pub fn break_multiple_panes_to_tab_with_index(
-        &mut self,
-        pane_ids: Vec<PaneId>,
-        tab_index: usize,
-        should_change_focus_to_new_tab: bool,
-        client_id: ClientId,
-    ) -> Result<()> {
-        let all_tabs = self.get_tabs_mut();
-        let has_tab_with_index = all_tabs
-            .values()
-            .find(|t| t.position == tab_index)
-            .is_some();
-        if !has_tab_with_index {
-            log::error!("Cannot find tab with index: {tab_index}");
-            return Ok(());
-        }
-        let mut extracted_panes = vec![];
-        for pane_id in pane_ids {
-            for tab in all_tabs.values_mut() {
-                if tab.position == tab_index {
-                    continue;
-                }
-                // here we pass None instead of the client_id we have because we do not need to
-                // necessarily trigger a relayout for this tab
-                if let Some(pane) = tab.extract_pane(pane_id, true).take() {
-                    extracted_panes.push(pane);
-                    break;
-                }
-            }
-        }
 
-        if should_change_focus_to_new_tab {
-            self.go_to_tab(tab_index + 1, client_id)?;
-        }
-        if extracted_panes.is_empty() {
-            // nothing to do here...
+    pub fn move_active_tab_to_right(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context = || "Failed to move active tab right ";
+        if self.tabs.len() < 2 {
+            debug!("cannot move tab to right: only one tab exists");
             return Ok(());
         }
-        if let Some(new_active_tab) = self.get_indexed_tab_mut(tab_index) {
-            for pane in extracted_panes {
-                let pane_id = pane.pid();
-                // here we pass None instead of the ClientId, because we do not want this pane to be
-                // necessarily focused
-                new_active_tab.add_tiled_pane(pane, pane_id, None)?;
-            }
-        } else {
-            log::error!("Could not find tab with index: {:?}", tab_index);
+        let Some(client_id) = self.client_id(client_id) else {
+            return Ok(());
+        };
+
+        match self.get_active_tab(client_id) {
+            Ok(active_tab) => {
+                let active_tab_pos = active_tab.position;
+                let right_tab_pos = (active_tab_pos + 1) % self.tabs.len();
+
+                self.switch_tabs(active_tab_pos, right_tab_pos, client_id);
+                self.log_and_report_session_state()
+                    .context("failed to move tab to the right")?;
+            },
+            Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
         }
-        self.log_and_report_session_state()?;
         Ok(())
     }
-    pub fn replace_pane(
-        &mut self,
-        new_pane_id: PaneId,
-        hold_for_command: HoldForCommand,
-        run: Option<Run>,
-        pane_title: Option<InitialTitle>,
-        close_replaced_pane: bool,
-        client_id_tab_index_or_pane_id: ClientTabIndexOrPaneId,
-    ) -> Result<()> {
-        let suppress_pane = |tab: &mut Tab, pane_id: PaneId, new_pane_id: PaneId| {
-            let _ = tab.suppress_pane_and_replace_with_pid(
-                pane_id,
-                new_pane_id,
-                close_replaced_pane,
-                run,
-            );
-            if let Some(pane_title) = pane_title {
-                let _ = tab.rename_pane(pane_title.as_bytes().to_vec(), new_pane_id);
-            }
-            if let Some(hold_for_command) = hold_for_command {
-                let is_first_run = true;
-                tab.hold_pane(new_pane_id, None, is_first_run, hold_for_command)
-            }
-        };
-        match client_id_tab_index_or_pane_id {
-            ClientTabIndexOrPaneId::ClientId(client_id) => {
-                active_tab!(self, client_id, |tab: &mut Tab| {
-                    match tab.get_active_pane_id(client_id) {
-                        Some(pane_id) => {
-                            suppress_pane(tab, pane_id, new_pane_id);
-                        },
-                        None => {
-                            log::error!(
-                                "Failed to find active pane for client id: {:?}",
-                                client_id
-                            );
-                        },
-                    }
-                });
-            },
-            ClientTabIndexOrPaneId::PaneId(pane_id) => {
-                let tab_index = self
-                    .tabs
-                    .iter()
-                    .find(|(_tab_index, tab)| tab.has_pane_with_pid(&pane_id))
-                    .map(|(_tab_index, tab)| tab.position);
-                match tab_index {
-                    Some(tab_index) => {
-                        if let Some(tab) =
-                            self.tabs.iter_mut().find(|(_, t)| t.position == tab_index)
-                        {
-                            suppress_pane(tab.1, pane_id, new_pane_id);
-                        }
-                    },
-                    None => {
-                        log::error!("Could not find pane with id: {:?}", pane_id);
-                    },
-                };
-            },
-            ClientTabIndexOrPaneId::TabIndex(_tab_index) => {
-                log::error!("Cannot replace pane with tab index");
-            },
+
+    pub fn change_mode(&mut self, mut mode_info: ModeInfo, client_id: ClientId) -> Result<()> {
+        if mode_info.session_name.as_ref() != Some(&self.session_name) {
+            mode_info.session_name = Some(self.session_name.clone());
         }
-        Ok(())
-    }
-    pub fn reconfigure(
-        &mut self,
-        new_keybinds: Keybinds,
-        new_default_mode: InputMode,
-        theme: Styling,
-        simplified_ui: bool,
-        default_shell: Option<PathBuf>,
-        pane_frames: bool,
-        copy_command: Option<String>,
-        copy_to_clipboard: Option<Clipboard>,
-        copy_on_select: bool,
-        auto_layout: bool,
-        rounded_corners: bool,
-        hide_session_name: bool,
-        stacked_resize: bool,
-        default_editor: Option<PathBuf>,
-        client_id: ClientId,
-    ) -> Result<()> {
-        let should_support_arrow_fonts = !simplified_ui;
+        mode_info.installed_version = self.installed_version.clone();
+        mode_info.latest_release = self.latest_release.clone();
 
-        // global configuration
-        self.default_mode_info.update_theme(theme);
-        self.default_mode_info
-            .update_rounded_corners(rounded_corners);
-        self.default_shell = default_shell.clone().unwrap_or_else(|| get_default_shell());
-        self.default_editor = default_editor.clone().or_else(|| get_default_editor());
-        self.auto_layout = auto_layout;
-        self.copy_options.command = copy_command.clone();
-        self.copy_options.copy_on_select = copy_on_select;
-        self.draw_pane_frames = pane_frames;
-        self.default_mode_info
-            .update_arrow_fonts(should_support_arrow_fonts);
-        self.default_mode_info
-            .update_hide_session_name(hide_session_name);
+        let previous_mode_info = self
+            .mode_info
+            .get(&client_id)
+            .unwrap_or(&self.default_mode_info);
+        let previous_mode = previous_mode_info.mode;
+        mode_info.style = previous_mode_info.style;
+        mode_info.capabilities = previous_mode_info.capabilities;
+
+        let err_context = || {
+            format!(
+                "failed to change from mode '{:?}' to mode '{:?}' for client {client_id}",
+                previous_mode, mode_info.mode
+            )
+        };
+
+        // If we leave the Search-related modes, we need to clear all previous searches
+        let search_related_modes = [InputMode::EnterSearch, InputMode::Search, InputMode::Scroll];
+        if search_related_modes.contains(&previous_mode)
+            && !search_related_modes.contains(&mode_info.mode)
         {
-            *self.stacked_resize.borrow_mut() = stacked_resize;
+            active_tab!(self, client_id, |tab: &mut Tab| tab.clear_search(client_id));
         }
-        if let Some(copy_to_clipboard) = copy_to_clipboard {
-            self.copy_options.clipboard = copy_to_clipboard;
+
+        if previous_mode == InputMode::Scroll
+            && (mode_info.mode == InputMode::Normal || mode_info.mode == InputMode::Locked)
+        {
+            if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
+                active_tab
+                    .clear_active_terminal_scroll(client_id)
+                    .with_context(err_context)?;
+            }
         }
-        for tab in self.tabs.values_mut() {
-            tab.update_theme(theme);
-            tab.update_rounded_corners(rounded_corners);
-            tab.update_default_shell(default_shell.clone());
-            tab.update_default_editor(self.default_editor.clone());
-            tab.update_auto_layout(auto_layout);
-            tab.update_copy_options(&self.copy_options);
-            tab.set_pane_frames(pane_frames);
-            tab.update_arrow_fonts(should_support_arrow_fonts);
+
+        if mode_info.mode == InputMode::RenameTab {
+            if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
+                active_tab.prev_name = active_tab.name.clone();
+            }
         }
 
-        // client specific configuration
-        if self.connected_clients_contains(&client_id) {
-            let mode_info = self
-                .mode_info
-                .entry(client_id)
-                .or_insert_with(|| self.default_mode_info.clone());
-            mode_info.update_keybinds(new_keybinds);
-            mode_info.update_default_mode(new_default_mode);
-            mode_info.update_theme(theme);
-            mode_info.update_arrow_fonts(should_support_arrow_fonts);
-            mode_info.update_hide_session_name(hide_session_name);
-            for tab in self.tabs.values_mut() {
-                tab.change_mode_info(mode_info.clone(), client_id);
-                tab.mark_active_pane_for_rerender(client_id);
+        if mode_info.mode == InputMode::RenamePane {
+            if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
+                if let Some(active_pane) =
+                    active_tab.get_active_pane_or_floating_pane_mut(client_id)
+                {
+                    active_pane.store_pane_name();
+                }
             }
         }
 
-        // this needs to be done separately at the end because it applies some of the above changes
-        // and propagates them to plugins
+        self.style = mode_info.style;
+        self.mode_info.insert(client_id, mode_info.clone());
         for tab in self.tabs.values_mut() {
+            tab.change_mode_info(mode_info.clone(), client_id);
+            tab.mark_active_pane_for_rerender(client_id);
             tab.update_input_modes()?;
         }
         Ok(())
     }
-    pub fn toggle_pane_pinned(&mut self, client_id: ClientId) {
-        active_tab_and_connected_client_id!(
-            self,
-            client_id,
-            |tab: &mut Tab, client_id: ClientId| {
-                tab.toggle_pane_pinned(client_id);
-            }
-        );
-        self.unblock_input().non_fatal();
-    }
-    pub fn set_floating_pane_pinned(&mut self, pane_id: PaneId, should_be_pinned: bool) {
-        let mut found = false;
-        for tab in self.tabs.values_mut() {
-            if tab.has_pane_with_pid(&pane_id) {
-                tab.set_floating_pane_pinned(pane_id, should_be_pinned);
-                found = true;
-                break;
-            }
+    pub fn update_available_versions(
+        &mut self,
+        installed_version: Option<String>,
+        latest_release: Option<String>,
+    ) -> Result<()> {
+        if self.installed_version == installed_version && self.latest_release == latest_release {
+            return Ok(());
         }
-        if !found {
-            log::error!(
-                "Failed to find pane with id: {:?} to set as pinned",
-                pane_id
-            );
+        self.installed_version = installed_version.clone();
+        self.latest_release = latest_release.clone();
+        self.default_mode_info.installed_version = installed_version.clone();
+        self.default_mode_info.latest_release = latest_release.clone();
+        for (_client_id, mode_info) in self.mode_info.iter_mut() {
+            mode_info.installed_version = installed_version.clone();
+            mode_info.latest_release = latest_release.clone();
         }
-    }
-    pub fn stack_panes(&mut self, mut pane_ids_to_stack: Vec<PaneId>) {
-        if pane_ids_to_stack.is_empty() {
-            log::error!("Got an empty list of pane_ids to stack");
-            return;
+        for (_, tab) in self.tabs.iter_mut() {
+            tab.update_available_versions(installed_version.clone(), latest_release.clone())
+                .with_context(|| format!("failed to update available versions"))?;
         }
-        let stack_size = pane_ids_to_stack.len();
-        let root_pane_id = pane_ids_to_stack.remove(0);
-        let Some(root_tab_id) = self
-            .tabs
-            .iter()
-            .find_map(|(tab_id, tab)| {
-                if tab.has_pane_with_pid(&root_pane_id) {
-                    Some(tab_id)
-                } else {
-                    None
-                }
-            })
-            .copied()
-        else {
-            log::error!("Failed to find tab for root_pane_id: {:?}", root_pane_id);
-            return;
+        Ok(())
+    }
+    pub fn change_mode_for_all_clients(&mut self, mode_info: ModeInfo) -> Result<()> {
+        let err_context = || {
+            format!(
+                "failed to change input mode to {:?} for all clients",
+                mode_info.mode
+            )
         };
-        let target_tab_has_room_for_stack = self
-            .tabs
-            .get(&root_tab_id)
-            .map(|t| t.has_room_for_stack(root_pane_id, stack_size))
-            .unwrap_or(false);
-        if !target_tab_has_room_for_stack {
-            log::error!("No room for stack with root pane id: {:?}", root_pane_id);
-            return;
+
+        let connected_client_ids: Vec<ClientId> = self.active_tab_indices.keys().copied().collect();
+        for client_id in connected_client_ids {
+            self.change_mode(mode_info.clone(), client_id)
+                .with_context(err_context)?;
         }
+        Ok(())
+    }
+    pub fn move_focus_left_or_previous_tab(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context = || {
+            format!(
+                "failed to move focus left or to previous tab for client {}",
+                client_id
+            )
+        };
 
-        let mut panes_to_stack = vec![];
-        for (tab_id, tab) in self.tabs.iter_mut() {
-            if tab_id == &root_tab_id {
-                // we do this before we extract panes so that the extraction won't trigger a
-                // relayout according to the next swapped tiled pane
-                tab.set_tiled_panes_damaged();
-            }
-            for pane_id in &pane_ids_to_stack {
-                if tab.has_pane_with_pid(&pane_id) {
-                    match tab.extract_pane(*pane_id, false) {
-                        Some(pane) => {
-                            panes_to_stack.push(pane);
-                        },
-                        None => {
-                            log::error!("Failed to extract pane: {:?}", pane_id);
-                        },
-                    }
-                }
-            }
-        }
-        self.tabs
-            .get_mut(&root_tab_id)
-            .map(|t| t.stack_panes(root_pane_id, panes_to_stack));
-    }
-    pub fn change_floating_panes_coordinates(
-        &mut self,
-        pane_ids_and_coordinates: Vec<(PaneId, FloatingPaneCoordinates)>,
-    ) {
-        for (pane_id, coordinates) in pane_ids_and_coordinates {
-            for (_tab_id, tab) in self.tabs.iter_mut() {
-                if tab.has_pane_with_pid(&pane_id) {
-                    tab.change_floating_pane_coordinates(&pane_id, coordinates)
-                        .non_fatal();
-                    break;
-                }
-            }
+        let client_id = if self.get_active_tab(client_id).is_ok() {
+            Some(client_id)
+        } else {
+            self.get_first_client_id()
+        };
+        if let Some(client_id) = client_id {
+            match self.get_active_tab_mut(client_id) {
+                Ok(active_tab) => {
+                    active_tab
+                        .move_focus_left(client_id)
+                        .and_then(|success| {
+                            if !success {
+                                self.switch_tab_prev(Some(Direction::Left), true, client_id)
+                                    .context("failed to move focus to previous tab")
+                            } else {
+                                Ok(())
+                            }
+                        })
+                        .with_context(err_context)?;
+                },
+                Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
+            };
         }
+        self.log_and_report_session_state()
+            .with_context(err_context)?;
+        Ok(())
     }
-    fn unblock_input(&self) -> Result<()> {
-        self.bus
-            .senders
-            .send_to_server(ServerInstruction::UnblockInputThread)
-            .context("failed to unblock input")
-    }
-    fn get_layout_metadata(&self, default_shell: Option<PathBuf>) -> SessionLayoutMetadata {
-        let mut session_layout_metadata = SessionLayoutMetadata::new(self.default_layout.clone());
-        if let Some(default_shell) = default_shell {
-            session_layout_metadata.update_default_shell(default_shell);
-        }
-        let first_client_id = self.get_first_client_id();
-        let active_tab_index =
-            first_client_id.and_then(|client_id| self.active_tab_indices.get(&client_id));
-
-        for (tab_index, tab) in self.tabs.iter() {
-            let tab_is_focused = active_tab_index == Some(&tab_index);
-            let hide_floating_panes = !tab.are_floating_panes_visible();
-            let mut suppressed_panes = HashMap::new();
-            for (triggering_pane_id, p) in tab.get_suppressed_panes() {
-                suppressed_panes.insert(*triggering_pane_id, p);
-            }
-
-            let all_connected_clients: Vec<ClientId> = self
-                .connected_clients
-                .borrow()
-                .iter()
-                .copied()
-                .filter(|c| self.active_tab_indices.get(&c) == Some(&tab_index))
-                .collect();
+    pub fn move_focus_right_or_next_tab(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context = || {
+            format!(
+                "failed to move focus right or to next tab for client {}",
+                client_id
+            )
+        };
 
-            let mut active_pane_ids: HashMap<ClientId, Option<PaneId>> = HashMap::new();
-            for connected_client_id in &all_connected_clients {
-                active_pane_ids.insert(
-                    *connected_client_id,
-                    tab.get_active_pane_id(*connected_client_id),
-                );
-            }
+        let client_id = if self.get_active_tab(client_id).is_ok() {
+            Some(client_id)
+        } else {
+            self.get_first_client_id()
+        };
 
-            let tiled_panes: Vec<PaneLayoutMetadata> = tab
-                .get_tiled_panes()
-                .map(|(pane_id, p)| {
-                    // here we look to see if this pane triggers any suppressed pane,
-                    // and if so we take that suppressed pane - we do this because this
-                    // is currently only the case the scrollback editing panes, and
-                    // when dumping the layout we want the "real" pane and not the
-                    // editor pane
-                    match suppressed_panes.remove(pane_id) {
-                        Some((is_scrollback_editor, suppressed_pane)) if *is_scrollback_editor => {
-                            (suppressed_pane.pid(), suppressed_pane)
-                        },
-                        _ => (*pane_id, p),
-                    }
-                })
-                .map(|(pane_id, p)| {
-                    let focused_clients: Vec<ClientId> = active_pane_ids
-                        .iter()
-                        .filter_map(|(c_id, p_id)| {
-                            p_id.and_then(|p_id| if p_id == pane_id { Some(*c_id) } else { None })
-                        })
-                        .collect();
-                    PaneLayoutMetadata::new(
-                        pane_id,
-                        p.position_and_size(),
-                        p.borderless(),
-                        p.invoked_with().clone(),
-                        p.custom_title(),
-                        !focused_clients.is_empty(),
-                        if self.serialize_pane_viewport {
-                            p.serialize(self.scrollback_lines_to_serialize)
-                        } else {
-                            None
-                        },
-                        focused_clients,
-                    )
-                })
-                .collect();
-            let floating_panes: Vec<PaneLayoutMetadata> = tab
-                .get_floating_panes()
-                .map(|(pane_id, p)| {
-                    // here we look to see if this pane triggers any suppressed pane,
-                    // and if so we take that suppressed pane - we do this because this
-                    // is currently only the case the scrollback editing panes, and
-                    // when dumping the layout we want the "real" pane and not the
-                    // editor pane
-                    match suppressed_panes.remove(pane_id) {
-                        Some((is_scrollback_editor, suppressed_pane)) if *is_scrollback_editor => {
-                            (suppressed_pane.pid(), suppressed_pane)
-                        },
-                        _ => (*pane_id, p),
-                    }
-                })
-                .map(|(pane_id, p)| {
-                    let focused_clients: Vec<ClientId> = active_pane_ids
-                        .iter()
-                        .filter_map(|(c_id, p_id)| {
-                            p_id.and_then(|p_id| if p_id == pane_id { Some(*c_id) } else { None })
+        if let Some(client_id) = client_id {
+            match self.get_active_tab_mut(client_id) {
+                Ok(active_tab) => {
+                    active_tab
+                        .move_focus_right(client_id)
+                        .and_then(|success| {
+                            if !success {
+                                self.switch_tab_next(Some(Direction::Right), true, client_id)
+                                    .context("failed to move focus to next tab")
+                            } else {
+                                Ok(())
+                            }
                         })
-                        .collect();
-                    PaneLayoutMetadata::new(
-                        pane_id,
-                        p.position_and_size(),
-                        false, // floating panes are never borderless
-                        p.invoked_with().clone(),
-                        p.custom_title(),
-                        !focused_clients.is_empty(),
-                        if self.serialize_pane_viewport {
-                            p.serialize(self.scrollback_lines_to_serialize)
-                        } else {
-                            None
-                        },
-                        focused_clients,
-                    )
-                })
-                .collect();
-            session_layout_metadata.add_tab(
-                tab.name.clone(),
-                tab_is_focused,
-                hide_floating_panes,
-                tiled_panes,
-                floating_panes,
-            );
+                        .with_context(err_context)?;
+                },
+                Err(err) => Err::<(), _>(err).with_context(err_context).non_fatal(),
+            };
         }
-        session_layout_metadata
+        self.log_and_report_session_state()
+            .with_context(err_context)?;
+        Ok(())
     }
-    fn update_plugin_loading_stage(
+    pub fn toggle_tab(&mut self, client_id: ClientId) -> Result<()> {
+        let tab = self
+            .get_previous_tab(client_id)
+            .context("failed to toggle tabs")?;
+        if let Some(t) = tab {
+            let position = t.position;
+            self.go_to_tab(position + 1, client_id)
+                .context("failed to toggle tabs")?;
+        };
+
+        self.log_and_report_session_state()
+            .context("failed to toggle tabs")?;
+        self.render(None)
+    }
+
+    pub fn focus_plugin_pane(
         &mut self,
-        pid: u32,
-        loading_indication: LoadingIndication,
-    ) -> bool {
+        run_plugin: &RunPluginOrAlias,
+        should_float: bool,
+        move_to_focused_tab: bool,
+        client_id: ClientId,
+    ) -> Result<bool> {
+        // true => found and focused, false => not
+        let err_context = || format!("failed to focus_plugin_pane");
+        let mut tab_index_and_plugin_pane_id = None;
+        let mut plugin_pane_to_move_to_active_tab = None;
+        let focused_tab_index = *self.active_tab_indices.get(&client_id).unwrap_or(&0);
         let all_tabs = self.get_tabs_mut();
-        let mut found_plugin = false;
-        for tab in all_tabs.values_mut() {
-            if tab.has_plugin(pid) {
-                found_plugin = true;
-                tab.update_plugin_loading_stage(pid, loading_indication);
+        for (tab_index, tab) in all_tabs.iter_mut() {
+            if let Some(plugin_pane_id) = tab.find_plugin(&run_plugin) {
+                tab_index_and_plugin_pane_id = Some((*tab_index, plugin_pane_id));
+                if move_to_focused_tab && focused_tab_index != *tab_index {
+                    plugin_pane_to_move_to_active_tab = tab.extract_pane(plugin_pane_id, true);
+                }
+
                 break;
             }
         }
-        found_plugin
-    }
-    fn connected_clients_contains(&self, client_id: &ClientId) -> bool {
-        self.connected_clients.borrow().contains(client_id)
-    }
-}
+        if let Some(plugin_pane_to_move_to_active_tab) = plugin_pane_to_move_to_active_tab.take() {
+            let pane_id = plugin_pane_to_move_to_active_tab.pid();
+            let new_active_tab = self.get_active_tab_mut(client_id)?;
 
-#[cfg(not(test))]
-fn get_default_editor() -> Option<PathBuf> {
-    std::env::var("EDITOR")
-        .or_else(|_| std::env::var("VISUAL"))
-        .map(|e| PathBuf::from(e))
-        .ok()
-}
-
-#[cfg(test)]
-fn get_default_editor() -> Option<PathBuf> {
-    None
-}
+            if should_float {
+                new_active_tab.show_floating_panes();
+                new_active_tab.add_floating_pane(
+                    plugin_pane_to_move_to_active_tab,
+                    pane_id,
+                    None,
+                    true,
+                )?;
+            } else {
+                new_active_tab.hide_floating_panes();
+                new_active_tab.add_tiled_pane(
+                    plugin_pane_to_move_to_active_tab,
+                    pane_id,
+                    Some(client_id),
+                )?;
+            }
+            return Ok(true);
+        }
+        match tab_index_and_plugin_pane_id {
+            Some((tab_index, plugin_pane_id)) => {
+                self.go_to_tab(tab_index + 1, client_id)?;
+                self.tabs
+                    .get_mut(&tab_index)
+                    .with_context(err_context)?
+                    .focus_pane_with_id(plugin_pane_id, should_float, client_id)
+                    .context("failed to focus plugin pane")?;
+                self.log_and_report_session_state()
+                    .with_context(err_context)?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
 
-// The box is here in order to make the
-// NewClient enum smaller
-#[allow(clippy::boxed_local)]
-pub(crate) fn screen_thread_main(
-    bus: Bus<ScreenInstruction>,
-    max_panes: Option<usize>,
-    client_attributes: ClientAttributes,
-    config: Config,
-    debug: bool,
-    default_layout: Box<Layout>,
-) -> Result<()> {
-    let config_options = config.options;
-    let arrow_fonts = !config_options.simplified_ui.unwrap_or_default();
-    let draw_pane_frames = config_options.pane_frames.unwrap_or(true);
-    let auto_layout = config_options.auto_layout.unwrap_or(true);
-    let session_serialization = config_options.session_serialization.unwrap_or(true);
-    let serialize_pane_viewport = config_options.serialize_pane_viewport.unwrap_or(false);
-    let scrollback_lines_to_serialize = config_options.scrollback_lines_to_serialize;
-    let session_is_mirrored = config_options.mirror_session.unwrap_or(false);
-    let layout_dir = config_options.layout_dir;
-    #[cfg(test)]
-    let default_shell = config_options
-        .default_shell
-        .clone()
-        .unwrap_or(PathBuf::from("/bin/sh"));
-    #[cfg(not(test))]
-    let default_shell = config_options
-        .default_shell
-        .clone()
-        .unwrap_or_else(|| get_default_shell());
-    let default_editor = config_options
-        .scrollback_editor
-        .clone()
-        .or_else(|| get_default_editor());
-    let default_layout_name = config_options
-        .default_layout
-        .map(|l| format!("{}", l.display()));
-    let copy_options = CopyOptions::new(
-        config_options.copy_command,
-        config_options.copy_clipboard.unwrap_or_default(),
-        config_options.copy_on_select.unwrap_or(true),
-    );
-    let styled_underlines = config_options.styled_underlines.unwrap_or(true);
-    let explicitly_disable_kitty_keyboard_protocol = config_options
-        .support_kitty_keyboard_protocol
-        .map(|e| !e) // this is due to the config options wording, if
-        // "support_kitty_keyboard_protocol" is true,
-        // explicitly_disable_kitty_keyboard_protocol is false and vice versa
-        .unwrap_or(false); // by default, we try to support this if the terminal supports it and
-                           // the program running inside a pane requests it
-    let stacked_resize = config_options.stacked_resize.unwrap_or(true);
+    /// Change the UI elements of all tabs, the status bar and tab bar are hidden and shown by
+    /// suppressing their panes and the compact tab bar is opened in place of the tab bar
+    pub fn set_ui(&mut self, ui_settings: UiSettings, client_id: ClientId) -> Result<()> {
+        let err_context = || format!("failed to set ui to: {}", ui_settings);
+        const STATUS_BAR: &[&str] = &["status-bar", "zellij:status-bar"];
+        const TAB_BAR: &[&str] = &["tab-bar", "zellij:tab-bar"];
+        const COMPACT_BAR: &[&str] = &["compact-bar", "zellij:compact-bar"];
 
-    let thread_senders = bus.senders.clone();
-    let mut screen = Screen::new(
-        bus,
-        &client_attributes,
-        max_panes,
-        get_mode_info(
-            config_options.default_mode.unwrap_or_default(),
-            &client_attributes,
-            PluginCapabilities {
-                //  ¯\_(ツ)_/¯
-                arrow_fonts: !arrow_fonts,
+        if let Some(pane_frames) = ui_settings.pane_frames {
+            self.draw_pane_frames = pane_frames;
+            for tab in self.tabs.values_mut() {
+                tab.set_pane_frames(pane_frames);
+            }
+        }
+        for (tab_index, tab) in self.tabs.iter_mut() {
+            match ui_settings.status_bar {
+                Some(BarVisibility::Hidden) => {
+                    for pane_id in tab.tiled_pane_ids_running_plugin(STATUS_BAR) {
+                        tab.suppress_pane(pane_id, None);
+                    }
+                },
+                Some(_) => {
+                    for pane_id in tab.suppressed_pane_ids_running_plugin(STATUS_BAR) {
+                        tab.unsuppress_tiled_pane(pane_id)
+                            .with_context(err_context)?;
+                    }
+                },
+                None => {},
+            }
+            match ui_settings.tab_bar {
+                Some(BarVisibility::Hidden) => {
+                    let mut tab_bar = TAB_BAR.to_vec();
+                    tab_bar.extend_from_slice(COMPACT_BAR);
+                    for pane_id in tab.tiled_pane_ids_running_plugin(&tab_bar) {
+                        tab.suppress_pane(pane_id, None);
+                    }
+                },
+                Some(visibility) => {
+                    for pane_id in tab.suppressed_pane_ids_running_plugin(TAB_BAR) {
+                        tab.unsuppress_tiled_pane(pane_id)
+                            .with_context(err_context)?;
+                    }
+                    let compact_bars =
+                        tab.pane_ids_running_plugin_in_place_of(COMPACT_BAR, TAB_BAR);
+                    if visibility == BarVisibility::Visible {
+                        // closing the compact bar brings back the tab bar it replaced
+                        for pane_id in compact_bars {
+                            tab.close_pane(pane_id, false);
+                            self.bus
+                                .senders
+                                .send_to_pty(PtyInstruction::ClosePane(pane_id))
+                                .with_context(err_context)?;
+                        }
+                    } else if compact_bars.is_empty() {
+                        let compact_bar =
+                            RunPluginOrAlias::from_url("zellij:compact-bar", &None, None, None)
+                                .map_err(|e| anyhow!(e))
+                                .with_context(err_context)?;
+                        for pane_id in tab.tiled_pane_ids_running_plugin(TAB_BAR) {
+                            let should_float = None;
+                            let should_be_in_place = true;
+                            let pane_title = None;
+                            let skip_cache = false;
+                            self.bus
+                                .senders
+                                .send_to_pty(PtyInstruction::FillPluginCwd(
+                                    should_float,
+                                    should_be_in_place,
+                                    pane_title,
+                                    compact_bar.clone(),
+                                    *tab_index,
+                                    Some(pane_id),
+                                    client_id,
+                                    Size::default(),
+                                    skip_cache,
+                                    None,
+                                    None,
+                                ))
+                                .with_context(err_context)?;
+                        }
+                    }
+                },
+                None => {},
+            }
+        }
+        Ok(())
+    }
+    pub fn focus_pane_with_id(
+        &mut self,
+        pane_id: PaneId,
+        should_float_if_hidden: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to focus_plugin_pane");
+        let tab_index = self
+            .tabs
+            .iter()
+            .find(|(_tab_index, tab)| tab.has_pane_with_pid(&pane_id))
+            .map(|(_tab_index, tab)| tab.position);
+        match tab_index {
+            Some(tab_index) => {
+                self.go_to_tab(tab_index + 1, client_id)?;
+                self.tabs
+                    .iter_mut()
+                    .find(|(_, t)| t.position == tab_index)
+                    .map(|(_, t)| t.focus_pane_with_id(pane_id, should_float_if_hidden, client_id))
+                    .with_context(err_context)
+                    .non_fatal();
+            },
+            None => {
+                log::error!("Could not find pane with id: {:?}", pane_id);
+            },
+        };
+        Ok(())
+    }
+    /// Returns a message to show the user if the pane cannot be re-run
+    pub fn rerun_command_pane_or_focused(
+        &mut self,
+        pane_id: Option<PaneId>,
+        client_id: ClientId,
+    ) -> std::result::Result<(), String> {
+        let pane = self
+            .get_pane_or_active_pane_mut(pane_id, client_id)
+            .ok_or_else(|| "Pane not found".to_owned())?;
+        match (pane.pid(), pane.is_held()) {
+            (PaneId::Terminal(terminal_pane_id), true) => {
+                self.rerun_command_pane_with_id(terminal_pane_id);
+                Ok(())
+            },
+            _ => Err("Only command panes whose command is not running can be re-run".to_owned()),
+        }
+    }
+    /// Returns a message to show the user if the command history of the pane cannot be opened
+    pub fn open_command_history(
+        &mut self,
+        pane_id: Option<PaneId>,
+        client_id: ClientId,
+    ) -> std::result::Result<(), String> {
+        let pane = self
+            .get_pane_or_active_pane_mut(pane_id, client_id)
+            .ok_or_else(|| "Pane not found".to_owned())?;
+        match (pane.pid(), pane.is_held()) {
+            (PaneId::Terminal(_), true) => {
+                if pane.open_command_history() {
+                    Ok(())
+                } else {
+                    Err("This command pane has not run a command yet".to_owned())
+                }
+            },
+            _ => Err(
+                "Only command panes whose command is not running have a command history".to_owned(),
+            ),
+        }
+    }
+    pub fn undo_close_pane(&mut self, client_id: ClientId) -> std::result::Result<(), String> {
+        let closed_pane = self
+            .get_active_tab_mut(client_id)
+            .ok()
+            .and_then(|tab| tab.take_closed_pane())
+            .ok_or_else(|| "No recently closed pane to restore in this tab".to_owned())?;
+        // the pty thread spawns a terminal for it and sends it back to us
+        self.bus
+            .senders
+            .send_to_pty(PtyInstruction::RestoreClosedPane(
+                Box::new(closed_pane),
+                client_id,
+            ))
+            .map_err(|e| e.to_string())
+    }
+    /// Opens a new pane next to the focused terminal pane, running the same command (or editing
+    /// the same file) in the same cwd, or a new shell there if the pane runs the default shell
+    pub fn duplicate_pane(
+        &mut self,
+        direction: Option<Direction>,
+        default_shell: Option<TerminalAction>,
+        client_id: ClientId,
+    ) -> std::result::Result<(), String> {
+        let active_tab = self
+            .get_active_tab(client_id)
+            .map_err(|_| "No active tab".to_owned())?;
+        let (terminal_id, invoked_with) = match active_tab.get_active_pane_id(client_id) {
+            Some(PaneId::Terminal(terminal_id)) => active_tab
+                .get_pane_with_id(PaneId::Terminal(terminal_id))
+                .map(|pane| (terminal_id, pane.invoked_with().clone()))
+                .ok_or_else(|| "Pane not found".to_owned())?,
+            _ => return Err("Only terminal panes can be duplicated".to_owned()),
+        };
+        let terminal_action = match invoked_with {
+            Some(Run::Command(run_command)) => Some(TerminalAction::RunCommand(RunCommand {
+                // these belong to the run of the command in the duplicated pane
+                hold_on_start: false,
+                originating_plugin: None,
+                capture_output: None,
+                stdin_pipe: None,
+                log_output: None,
+                ..run_command
+            })),
+            Some(Run::EditFile(path, line_number, cwd)) => Some(TerminalAction::OpenFile(
+                OpenFilePayload::new(path, line_number, cwd),
+            )),
+            _ => default_shell,
+        };
+        // the pty thread fills in the cwd of the duplicated pane
+        self.bus
+            .senders
+            .send_to_pty(PtyInstruction::DuplicatePane(
+                terminal_id,
+                terminal_action,
+                direction,
+                client_id,
+            ))
+            .map_err(|e| e.to_string())
+    }
+    fn restore_closed_pane(
+        &mut self,
+        terminal_id: u32,
+        hold_for_command: Option<RunCommand>,
+        closed_pane: ClosedPane,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to restore closed pane in terminal {terminal_id}");
+        // the tab might have been closed in the meantime
+        let tab_index = if self.tabs.contains_key(&closed_pane.tab_index) {
+            closed_pane.tab_index
+        } else {
+            self.get_active_tab(client_id)
+                .with_context(err_context)?
+                .index
+        };
+        let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
+        tab.restore_closed_pane(terminal_id, closed_pane, client_id)
+            .with_context(err_context)?;
+        if let Some(run_command) = hold_for_command {
+            let is_first_run = true;
+            tab.hold_pane(
+                PaneId::Terminal(terminal_id),
+                None,
+                is_first_run,
+                run_command,
+            );
+        }
+        Ok(())
+    }
+    fn hold_exited_command_pane(
+        &mut self,
+        id: PaneId,
+        exit_status: Option<i32>,
+        run_command: RunCommand,
+    ) -> Result<()> {
+        if self.replace_stopped_command(id) {
+            return Ok(());
+        }
+        let is_first_run = false;
+        let command_exit_banner = self.command_exit_banner;
+        let mut runtime = None;
+        let mut exited_in_background_tab = None;
+        let mut exited_in_active_tab = false;
+        // the pane can be in several tabs, as linked views of it
+        for tab in self.tabs.values_mut() {
+            if tab.get_all_pane_ids().contains(&id) {
+                let tab_is_active = self
+                    .active_tab_indices
+                    .values()
+                    .any(|active_tab_index| *active_tab_index == tab.index);
+                if tab_is_active {
+                    exited_in_active_tab = true;
+                } else {
+                    tab.mark_exited_command(id);
+                    exited_in_background_tab.get_or_insert_with(|| tab.name.clone());
+                }
+                tab.hold_pane(id, exit_status, is_first_run, run_command.clone());
+                let is_linked_view = tab.pane_is_linked_view(id);
+                if let Some(pane) = tab.get_pane_with_id_mut(id) {
+                    pane.set_command_exit_banner(command_exit_banner);
+                    if !is_linked_view {
+                        runtime = pane.command_runtime();
+                    }
+                }
+            }
+        }
+        if let Some(tab_name) = exited_in_background_tab {
+            if !exited_in_active_tab {
+                self.notify_of_exited_command(&run_command, exit_status, tab_name);
+            }
+            self.generate_and_report_tab_state()
+                .context("failed to mark the tab of an exited command pane")?;
+        }
+        if let PaneId::Terminal(terminal_pane_id) = id {
+            self.bus
+                .senders
+                .send_to_plugin(PluginInstruction::Update(vec![(
+                    None,
+                    None,
+                    Event::CommandPaneFinished(
+                        terminal_pane_id,
+                        exit_status,
+                        runtime.unwrap_or_default(),
+                    ),
+                )]))
+                .context("failed to report an exited command pane")?;
+            if self.watched_commands_to_rerun.remove(&terminal_pane_id) {
+                self.rerun_command_pane_with_id(terminal_pane_id);
+            }
+        }
+        Ok(())
+    }
+    /// Runs the command_exit_notification_command, if there is one
+    fn notify_of_exited_command(
+        &self,
+        run_command: &RunCommand,
+        exit_status: Option<i32>,
+        tab_name: String,
+    ) {
+        let notification_command = match &self.command_exit_notification_command {
+            Some(notification_command) if !self.bells_are_muted() => notification_command,
+            _ => return,
+        };
+        let command = command_line(run_command);
+        let text = match exit_status {
+            Some(exit_code) => format!(
+                "'{}' exited with code {} in tab '{}'",
+                command, exit_code, tab_name
+            ),
+            None => format!("'{}' exited in tab '{}'", command, tab_name),
+        };
+        let mut env_variables = BTreeMap::new();
+        env_variables.insert("ZELLIJ_NOTIFICATION_TEXT".to_owned(), text);
+        env_variables.insert("ZELLIJ_NOTIFICATION_COMMAND".to_owned(), command);
+        if let Some(exit_code) = exit_status {
+            env_variables.insert(
+                "ZELLIJ_NOTIFICATION_EXIT_CODE".to_owned(),
+                exit_code.to_string(),
+            );
+        }
+        env_variables.insert("ZELLIJ_NOTIFICATION_TAB".to_owned(), tab_name);
+        let _ = self
+            .bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::RunHookCommand(
+                notification_command.clone(),
+                env_variables,
+            ));
+    }
+    fn rerun_watched_command(&mut self, terminal_pane_id: u32) -> Result<()> {
+        let pane_state = self
+            .tabs
+            .values()
+            .find_map(|tab| tab.get_pane_with_id(PaneId::Terminal(terminal_pane_id)))
+            .map(|pane| (pane.is_held(), pane.exited()));
+        match pane_state {
+            // the command has not been run yet (eg. it was started suspended)
+            Some((true, false)) => {},
+            Some((true, true)) => self.rerun_command_pane_with_id(terminal_pane_id),
+            Some((false, _)) => {
+                // it is re-run once it exits
+                if self.watched_commands_to_rerun.insert(terminal_pane_id) {
+                    self.bus
+                        .senders
+                        .send_to_pty(PtyInstruction::StopCommand(
+                            terminal_pane_id,
+                            PaneSignal::Term,
+                        ))
+                        .context("failed to stop a watched command")?;
+                }
+            },
+            None => {
+                self.watched_commands_to_rerun.remove(&terminal_pane_id);
+            },
+        }
+        Ok(())
+    }
+    /// Runs a command in the focused terminal pane in place of its current one, stopping the
+    /// current one first if it is still running. Returns a message to show the user if the pane
+    /// cannot run it.
+    pub fn replace_pane_command(
+        &mut self,
+        mut run_command: RunCommand,
+        keep_scrollback: bool,
+        client_id: ClientId,
+    ) -> std::result::Result<(), String> {
+        let pane = self
+            .get_pane_or_active_pane_mut(None, client_id)
+            .ok_or_else(|| "Pane not found".to_owned())?;
+        let terminal_pane_id = match pane.pid() {
+            PaneId::Terminal(terminal_pane_id) => terminal_pane_id,
+            PaneId::Plugin(_) => {
+                return Err("Only terminal panes can run another command".to_owned())
+            },
+        };
+        if run_command.cwd.is_none() {
+            // where the command it replaces ran
+            if let Some(Run::Command(replaced_command)) = pane.invoked_with() {
+                run_command.cwd = replaced_command.cwd.clone();
+            }
+        }
+        let is_running = !pane.is_held();
+        self.commands_replacing_stopped_ones
+            .insert(terminal_pane_id, (run_command, keep_scrollback));
+        if is_running {
+            // as if the pane was closed, the new command runs once the process exited
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::StopCommand(
+                    terminal_pane_id,
+                    PaneSignal::Hup,
+                ))
+                .map_err(|e| e.to_string())?;
+        } else {
+            self.replace_stopped_command(PaneId::Terminal(terminal_pane_id));
+        }
+        Ok(())
+    }
+    /// Sends the next, harsher signal to a command that was stopped to be replaced or re-run but
+    /// did not exit, or gives up on replacing or re-running it if it ignored even SIGKILL
+    pub fn stop_command_timed_out(
+        &mut self,
+        terminal_pane_id: u32,
+        signal: PaneSignal,
+    ) -> Result<()> {
+        if !self
+            .commands_replacing_stopped_ones
+            .contains_key(&terminal_pane_id)
+            && !self.watched_commands_to_rerun.contains(&terminal_pane_id)
+        {
+            // it exited
+            return Ok(());
+        }
+        match signal.escalated() {
+            Some(signal) => self
+                .bus
+                .senders
+                .send_to_pty(PtyInstruction::StopCommand(terminal_pane_id, signal))
+                .context("failed to stop a command being replaced or re-run"),
+            None => {
+                log::error!(
+                    "Command of terminal pane {} did not exit, not replacing or re-running it",
+                    terminal_pane_id
+                );
+                self.commands_replacing_stopped_ones
+                    .remove(&terminal_pane_id);
+                // so that the next change of its files tries to stop it again
+                self.watched_commands_to_rerun.remove(&terminal_pane_id);
+                Ok(())
+            },
+        }
+    }
+    /// Runs the command replacing the one of this pane, once it was stopped. Returns false if
+    /// there is none, in which case the pane is held or closed as usual.
+    fn replace_stopped_command(&mut self, pane_id: PaneId) -> bool {
+        let PaneId::Terminal(terminal_pane_id) = pane_id else {
+            return false;
+        };
+        let Some((run_command, keep_scrollback)) = self
+            .commands_replacing_stopped_ones
+            .remove(&terminal_pane_id)
+        else {
+            return false;
+        };
+        match self
+            .tabs
+            .values_mut()
+            .find(|tab| tab.has_pane_with_pid(&pane_id))
+        {
+            Some(tab) => {
+                tab.replace_terminal_pane_command(terminal_pane_id, run_command, keep_scrollback)
+            },
+            None => log::error!(
+                "Failed to find terminal pane with id: {} to replace its command",
+                terminal_pane_id
+            ),
+        }
+        true
+    }
+    pub fn rerun_command_pane_with_id(&mut self, terminal_pane_id: u32) {
+        let mut found = false;
+        for tab in self.tabs.values_mut() {
+            if tab.has_pane_with_pid(&PaneId::Terminal(terminal_pane_id)) {
+                tab.rerun_terminal_pane_with_id(terminal_pane_id);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            log::error!(
+                "Failed to find terminal pane with id: {} to run",
+                terminal_pane_id
+            );
+        }
+    }
+    pub fn resize_pane_with_id(&mut self, resize: ResizeStrategy, pane_id: PaneId) {
+        let mut found = false;
+        for tab in self.tabs.values_mut() {
+            if tab.has_pane_with_pid(&pane_id) {
+                tab.resize_pane_with_id(resize, pane_id).non_fatal();
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            log::error!("Failed to find pane with id: {:?} to resize", pane_id);
+        }
+    }
+    pub fn break_pane(
+        &mut self,
+        default_shell: Option<TerminalAction>,
+        default_layout: Box<Layout>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || "failed break pane out of tab".to_string();
+        let active_tab = self.get_active_tab_mut(client_id)?;
+        if active_tab.get_selectable_tiled_panes_count() > 1
+            || active_tab.get_visible_selectable_floating_panes_count() > 0
+        {
+            let active_pane_id = active_tab
+                .get_active_pane_id(client_id)
+                .with_context(err_context)?;
+            let pane_to_break_is_floating = active_tab.are_floating_panes_visible();
+            let active_pane = active_tab
+                .extract_pane(active_pane_id, false)
+                .with_context(err_context)?;
+            let active_pane_run_instruction = active_pane.invoked_with().clone();
+            let tab_index = self.get_new_tab_index();
+            let swap_layouts = (
+                default_layout.swap_tiled_layouts.clone(),
+                default_layout.swap_floating_layouts.clone(),
+            );
+            self.new_tab(tab_index, swap_layouts, None, Some(client_id))?;
+            let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
+            let (mut tiled_panes_layout, mut floating_panes_layout) = default_layout.new_tab();
+            if pane_to_break_is_floating {
+                tab.show_floating_panes();
+                tab.add_floating_pane(active_pane, active_pane_id, None, true)?;
+                if let Some(already_running_layout) = floating_panes_layout
+                    .iter_mut()
+                    .find(|i| i.run == active_pane_run_instruction)
+                {
+                    already_running_layout.already_running = true;
+                }
+            } else {
+                tab.add_tiled_pane(active_pane, active_pane_id, Some(client_id))?;
+                tiled_panes_layout.ignore_run_instruction(active_pane_run_instruction.clone());
+            }
+            let should_change_focus_to_new_tab = true;
+            self.bus.senders.send_to_plugin(PluginInstruction::NewTab(
+                None,
+                default_shell,
+                Some(tiled_panes_layout),
+                floating_panes_layout,
+                tab_index,
+                should_change_focus_to_new_tab,
+                client_id,
+            ))?;
+        } else {
+            let active_pane_id = active_tab
+                .get_active_pane_id(client_id)
+                .with_context(err_context)?;
+            self.bus
+                .senders
+                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                    vec![active_pane_id],
+                    "Cannot break single pane out!".into(),
+                ))
+                .with_context(err_context)?;
+            self.unblock_input()?;
+        }
+        Ok(())
+    }
+    pub fn break_multiple_panes_to_new_tab(
+        &mut self,
+        pane_ids: Vec<PaneId>,
+        default_shell: Option<TerminalAction>,
+        should_change_focus_to_new_tab: bool,
+        new_tab_name: Option<String>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || "failed break multiple panes to a new tab".to_string();
+
+        let all_tabs = self.get_tabs_mut();
+        let mut extracted_panes = vec![];
+        for pane_id in pane_ids {
+            for tab in all_tabs.values_mut() {
+                // here we pass None instead of the client_id we have because we do not need to
+                // necessarily trigger a relayout for this tab
+                if let Some(pane) = tab.extract_pane(pane_id, true).take() {
+                    extracted_panes.push(pane);
+                    break;
+                }
+            }
+        }
+
+        let (mut tiled_panes_layout, floating_panes_layout) = self.default_layout.new_tab();
+        let tab_index = self.get_new_tab_index();
+        let swap_layouts = (
+            self.default_layout.swap_tiled_layouts.clone(),
+            self.default_layout.swap_floating_layouts.clone(),
+        );
+        if should_change_focus_to_new_tab {
+            self.new_tab(tab_index, swap_layouts, None, Some(client_id))?;
+        } else {
+            self.new_tab(tab_index, swap_layouts, None, None)?;
+        }
+        let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
+        if let Some(new_tab_name) = new_tab_name {
+            tab.name = new_tab_name.clone();
+        }
+        for pane in extracted_panes {
+            let run_instruction = pane.invoked_with().clone();
+            let pane_id = pane.pid();
+            // here we pass None instead of the ClientId, because we do not want this pane to be
+            // necessarily focused
+            tab.add_tiled_pane(pane, pane_id, None)?;
+            tiled_panes_layout.ignore_run_instruction(run_instruction.clone());
+        }
+        self.bus.senders.send_to_plugin(PluginInstruction::NewTab(
+            None,
+            default_shell,
+            Some(tiled_panes_layout),
+            floating_panes_layout,
+            tab_index,
+            should_change_focus_to_new_tab,
+            client_id,
+        ))?;
+        Ok(())
+    }
+    /// Replace the layout of the session with a new definition of it (eg. after its file was
+    /// edited), the tabs using its swap layouts get the new swap layouts
+    pub fn reload_layout(&mut self, new_layout: Layout) -> Result<()> {
+        let err_context = || "failed to reload layout";
+        let previous_swap_layouts = (
+            self.default_layout.swap_tiled_layouts.clone(),
+            self.default_layout.swap_floating_layouts.clone(),
+        );
+        let new_swap_layouts = (
+            new_layout.swap_tiled_layouts.clone(),
+            new_layout.swap_floating_layouts.clone(),
+        );
+        for tab in self.tabs.values_mut() {
+            tab.reload_swap_layouts(&previous_swap_layouts, new_swap_layouts.clone())
+                .with_context(err_context)?;
+        }
+        self.default_layout = Box::new(new_layout);
+        Ok(())
+    }
+    pub fn break_pane_to_new_tab(
+        &mut self,
+        direction: Direction,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || "failed break pane out of tab".to_string();
+        if self.tabs.len() > 1 {
+            let (active_pane_id, active_pane, pane_to_break_is_floating) = {
+                let active_tab = self.get_active_tab_mut(client_id)?;
+                let active_pane_id = active_tab
+                    .get_active_pane_id(client_id)
+                    .with_context(err_context)?;
+                let pane_to_break_is_floating = active_tab.are_floating_panes_visible();
+                let active_pane = active_tab
+                    .extract_pane(active_pane_id, false)
+                    .with_context(err_context)?;
+                (active_pane_id, active_pane, pane_to_break_is_floating)
+            };
+            let update_mode_infos = false;
+            match direction {
+                Direction::Right | Direction::Down => {
+                    self.switch_tab_next(None, update_mode_infos, client_id)?;
+                },
+                Direction::Left | Direction::Up => {
+                    self.switch_tab_prev(None, update_mode_infos, client_id)?;
+                },
+            };
+            let new_active_tab = self.get_active_tab_mut(client_id)?;
+
+            if pane_to_break_is_floating {
+                new_active_tab.show_floating_panes();
+                new_active_tab.add_floating_pane(active_pane, active_pane_id, None, true)?;
+            } else {
+                new_active_tab.hide_floating_panes();
+                new_active_tab.add_tiled_pane(active_pane, active_pane_id, Some(client_id))?;
+            }
+
+            self.log_and_report_session_state()?;
+        } else {
+            let active_pane_id = {
+                let active_tab = self.get_active_tab_mut(client_id)?;
+                active_tab
+                    .get_active_pane_id(client_id)
+                    .with_context(err_context)?
+            };
+            self.bus
+                .senders
+                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                    vec![active_pane_id],
+                    "No other tabs to add pane to!".into(),
+                ))
+                .with_context(err_context)?;
+        }
+        self.unblock_input()?;
+        self.render(None)?;
+        Ok(())
+    }
+    /// Has the pty thread move the focused terminal pane to the server of another session
+    pub fn move_pane_to_session(
+        &mut self,
+        session_name: String,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to move pane to session {session_name}");
+        let active_tab = self.get_active_tab(client_id).with_context(err_context)?;
+        let active_pane_id = active_tab.get_active_pane_id(client_id);
+        let moved_terminal_id = match active_pane_id {
+            Some(PaneId::Terminal(terminal_id)) => active_tab
+                .get_pane_with_id(PaneId::Terminal(terminal_id))
+                // a linked view does not own the pty, the pane it shows does
+                .filter(|pane| !pane.is_linked_view())
+                .map(|_| terminal_id),
+            _ => None,
+        };
+        match (moved_terminal_id, active_pane_id) {
+            (Some(terminal_id), _) => self
+                .bus
+                .senders
+                .send_to_pty(PtyInstruction::MovePaneToSession(terminal_id, session_name))
+                .with_context(err_context),
+            (None, Some(active_pane_id)) => self
+                .bus
+                .senders
+                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                    vec![active_pane_id],
+                    "Only terminal panes can be moved to another session!".into(),
+                ))
+                .with_context(err_context),
+            (None, None) => Ok(()),
+        }
+    }
+    /// Shows the focused terminal pane in the tab at this position too, as a linked view of its
+    /// pty: what the pane outputs is rendered in both tabs, and what is typed in either goes to
+    /// the same pty. The pty keeps the size of the pane, the view wraps its output to its own.
+    pub fn link_pane_to_tab(&mut self, tab_position: usize, client_id: ClientId) -> Result<()> {
+        let err_context = || format!("failed to link pane to tab at position {tab_position}");
+        let active_tab = self.get_active_tab(client_id).with_context(err_context)?;
+        let active_pane_id = active_tab.get_active_pane_id(client_id);
+        let linked_pane = match active_pane_id {
+            Some(PaneId::Terminal(terminal_id)) => active_tab
+                .get_pane_with_id(PaneId::Terminal(terminal_id))
+                .map(|pane| {
+                    (
+                        terminal_id,
+                        pane.current_title(),
+                        pane.invoked_with().clone(),
+                        pane.serialize(Some(COPIED_SCROLLBACK_LINES)),
+                    )
+                }),
+            _ => None,
+        };
+        let target_tab = self
+            .tabs
+            .values_mut()
+            .find(|tab| tab.position == tab_position)
+            .with_context(err_context)?;
+        let linked = match linked_pane {
+            None => Err("Only terminal panes can be linked to another tab!"),
+            Some((terminal_id, ..))
+                if target_tab
+                    .get_all_pane_ids()
+                    .contains(&PaneId::Terminal(terminal_id)) =>
+            {
+                Err("The pane is already in this tab!")
+            },
+            Some((terminal_id, title, invoked_with, scrollback)) => {
+                target_tab
+                    .add_linked_view(terminal_id, title, invoked_with, scrollback)
+                    .with_context(err_context)?;
+                Ok(())
+            },
+        };
+        match (linked, active_pane_id) {
+            (Err(error), Some(active_pane_id)) => self
+                .bus
+                .senders
+                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                    vec![active_pane_id],
+                    error.into(),
+                ))
+                .with_context(err_context),
+            _ => self
+                .log_and_report_session_state()
+                .with_context(err_context),
+        }
+    }
+    /// Closes the linked views whose pane is gone (eg. it was closed or its tab was), they would
+    /// otherwise show a pty that is no longer there
+    fn close_orphaned_linked_views(&mut self) {
+        let owned_pane_ids: HashSet<PaneId> = self
+            .tabs
+            .values()
+            .flat_map(|tab| {
+                tab.get_all_pane_ids()
+                    .into_iter()
+                    .filter(|pane_id| !tab.pane_is_linked_view(*pane_id))
+            })
+            .collect();
+        for tab in self.tabs.values_mut() {
+            let orphaned_views: Vec<PaneId> = tab
+                .get_all_pane_ids()
+                .into_iter()
+                .filter(|pane_id| {
+                    tab.pane_is_linked_view(*pane_id) && !owned_pane_ids.contains(pane_id)
+                })
+                .collect();
+            for pane_id in orphaned_views {
+                tab.close_pane(pane_id, false);
+            }
+        }
+    }
+    /// Removes a pane whose pty was sent to another session, without closing it: its process
+    /// keeps running there
+    /// Hands the pane of a terminal the pty thread stopped reading from over to it, along with
+    /// its scrollback, to be sent to the server of another session
+    fn serialize_moved_pane(&mut self, terminal_id: u32, session_name: String) -> Result<()> {
+        let pane_id = PaneId::Terminal(terminal_id);
+        let moved_pane = self
+            .tabs
+            .values()
+            .filter_map(|tab| tab.get_pane_with_id(pane_id))
+            .find(|pane| !pane.is_linked_view())
+            .map(|pane| MovedPane {
+                name: pane.custom_title(),
+                invoked_with: pane.invoked_with().clone(),
+                scrollback: pane.serialize(Some(COPIED_SCROLLBACK_LINES)),
+            });
+        match moved_pane {
+            Some(moved_pane) => self
+                .bus
+                .senders
+                .send_to_pty(PtyInstruction::SendPaneToSession(
+                    terminal_id,
+                    Box::new(moved_pane),
+                    session_name,
+                ))
+                .with_context(|| format!("failed to move terminal {terminal_id}")),
+            // the pane was closed in the meantime
+            None => Ok(()),
+        }
+    }
+    fn remove_pane_moved_to_session(&mut self, terminal_id: u32) -> Result<()> {
+        let err_context = || format!("failed to remove moved pane of terminal {terminal_id}");
+        let pane_id = PaneId::Terminal(terminal_id);
+        let tab_index = self
+            .tabs
+            .values()
+            .find(|tab| {
+                tab.get_all_pane_ids().contains(&pane_id) && !tab.pane_is_linked_view(pane_id)
+            })
+            .map(|tab| tab.index);
+        if let Some(tab_index) = tab_index {
+            let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
+            drop(tab.extract_pane(pane_id, false));
+            if tab.get_selectable_tiled_panes_count() == 0
+                && tab.get_selectable_floating_panes_count() == 0
+            {
+                self.close_tab_at_index(tab_index)
+                    .with_context(err_context)?;
+            }
+        }
+        self.close_orphaned_linked_views();
+        self.log_and_report_session_state()
+            .with_context(err_context)?;
+        self.render(None)
+    }
+    /// Opens a terminal moved here from another session (already adopted by the pty thread) in a
+    /// new pane, with its scrollback
+    fn adopt_pane(
+        &mut self,
+        terminal_id: u32,
+        moved_pane: MovedPane,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to adopt pane in terminal {terminal_id}");
+        let pane_id = PaneId::Terminal(terminal_id);
+        // the pane is sent by the server of the other session, rather than by a client attached
+        // to this one
+        let client_id = if self.get_active_tab(client_id).is_ok() {
+            Some(client_id)
+        } else {
+            self.get_first_client_id()
+        };
+        let tab = match client_id {
+            Some(client_id) => self.get_active_tab_mut(client_id).ok(),
+            None => self.tabs.values_mut().find(|tab| tab.position == 0),
+        }
+        .with_context(err_context)?;
+        let initial_pane_title = match &moved_pane.invoked_with {
+            Some(Run::Command(run_command)) => Some(run_command.to_string()),
+            _ => None,
+        };
+        let should_float = None;
+        let start_suppressed = false;
+        tab.new_pane(
+            pane_id,
+            initial_pane_title,
+            should_float,
+            moved_pane.invoked_with,
+            None,
+            start_suppressed,
+            client_id,
+        )
+        .with_context(err_context)?;
+        if let Some(pane) = tab.get_pane_with_id_mut(pane_id) {
+            if let Some(name) = moved_pane.name {
+                pane.rename(name.into_bytes());
+            }
+            if let Some(scrollback) = moved_pane.scrollback.as_ref() {
+                pane.restore_scrollback(scrollback);
+            }
+        }
+        Ok(())
+    }
+    /// Moves the only pane of the tab at this position into the active tab, next to its focused
+    /// pane, and closes the tab it was in (the inverse of breaking a pane out to a new tab)
+    pub fn join_tab(
+        &mut self,
+        tab_position: usize,
+        direction: Option<Direction>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to join tab at position {tab_position}");
+        let active_tab = self.get_active_tab(client_id).with_context(err_context)?;
+        let active_tab_index = active_tab.index;
+        let active_pane_id = active_tab.get_active_pane_id(client_id);
+        let joined_tab = self.tabs.values().find(|tab| tab.position == tab_position);
+        let joined_pane = match joined_tab {
+            None => Err("No tab at this position!"),
+            Some(tab) if tab.index == active_tab_index => Err("Cannot join a tab to itself!"),
+            Some(tab) => tab
+                .get_single_pane_id()
+                .map(|pane_id| (tab.index, pane_id))
+                .ok_or("Can only join a tab with a single pane!"),
+        };
+        let (joined_tab_index, joined_pane_id) = match joined_pane {
+            Ok(joined_pane) => joined_pane,
+            Err(error) => {
+                if let Some(active_pane_id) = active_pane_id {
+                    self.bus
+                        .senders
+                        .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                            vec![active_pane_id],
+                            error.into(),
+                        ))
+                        .with_context(err_context)?;
+                }
+                return Ok(());
+            },
+        };
+        let joined_pane = self
+            .tabs
+            .get_mut(&joined_tab_index)
+            .and_then(|tab| tab.extract_pane(joined_pane_id, false))
+            .with_context(err_context)?;
+        // what is left in the tab are its ui panes (eg. the tab-bar)
+        self.close_tab_at_index(joined_tab_index)
+            .with_context(err_context)?;
+        self.get_active_tab_mut(client_id)
+            .and_then(|active_tab| {
+                active_tab.add_tiled_pane_next_to_focused_pane(
+                    joined_pane,
+                    joined_pane_id,
+                    direction,
+                    client_id,
+                )
+            })
+            .with_context(err_context)?;
+        self.log_and_report_session_state()
+            .with_context(err_context)?;
+        self.render(None)
+    }
+    pub fn break_multiple_panes_to_tab_with_index(
+        &mut self,
+        pane_ids: Vec<PaneId>,
+        tab_index: usize,
+        should_change_focus_to_new_tab: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let all_tabs = self.get_tabs_mut();
+        let has_tab_with_index = all_tabs
+            .values()
+            .find(|t| t.position == tab_index)
+            .is_some();
+        if !has_tab_with_index {
+            log::error!("Cannot find tab with index: {tab_index}");
+            return Ok(());
+        }
+        let mut extracted_panes = vec![];
+        for pane_id in pane_ids {
+            for tab in all_tabs.values_mut() {
+                if tab.position == tab_index {
+                    continue;
+                }
+                // here we pass None instead of the client_id we have because we do not need to
+                // necessarily trigger a relayout for this tab
+                if let Some(pane) = tab.extract_pane(pane_id, true).take() {
+                    extracted_panes.push(pane);
+                    break;
+                }
+            }
+        }
+
+        if should_change_focus_to_new_tab {
+            self.go_to_tab(tab_index + 1, client_id)?;
+        }
+        if extracted_panes.is_empty() {
+            // nothing to do here...
+            return Ok(());
+        }
+        if let Some(new_active_tab) = self.get_indexed_tab_mut(tab_index) {
+            for pane in extracted_panes {
+                let pane_id = pane.pid();
+                // here we pass None instead of the ClientId, because we do not want this pane to be
+                // necessarily focused
+                new_active_tab.add_tiled_pane(pane, pane_id, None)?;
+            }
+        } else {
+            log::error!("Could not find tab with index: {:?}", tab_index);
+        }
+        self.log_and_report_session_state()?;
+        Ok(())
+    }
+    pub fn replace_pane(
+        &mut self,
+        new_pane_id: PaneId,
+        hold_for_command: HoldForCommand,
+        run: Option<Run>,
+        pane_title: Option<InitialTitle>,
+        close_replaced_pane: bool,
+        client_id_tab_index_or_pane_id: ClientTabIndexOrPaneId,
+    ) -> Result<()> {
+        let suppress_pane = |tab: &mut Tab, pane_id: PaneId, new_pane_id: PaneId| {
+            let _ = tab.suppress_pane_and_replace_with_pid(
+                pane_id,
+                new_pane_id,
+                close_replaced_pane,
+                run,
+            );
+            if let Some(pane_title) = pane_title {
+                let _ = tab.rename_pane(pane_title.as_bytes().to_vec(), new_pane_id);
+            }
+            if let Some(hold_for_command) = hold_for_command {
+                let is_first_run = true;
+                tab.hold_pane(new_pane_id, None, is_first_run, hold_for_command)
+            }
+        };
+        match client_id_tab_index_or_pane_id {
+            ClientTabIndexOrPaneId::ClientId(client_id) => {
+                active_tab!(self, client_id, |tab: &mut Tab| {
+                    match tab.get_active_pane_id(client_id) {
+                        Some(pane_id) => {
+                            suppress_pane(tab, pane_id, new_pane_id);
+                        },
+                        None => {
+                            log::error!(
+                                "Failed to find active pane for client id: {:?}",
+                                client_id
+                            );
+                        },
+                    }
+                });
+            },
+            ClientTabIndexOrPaneId::PaneId(pane_id) => {
+                let tab_index = self
+                    .tabs
+                    .iter()
+                    .find(|(_tab_index, tab)| tab.has_pane_with_pid(&pane_id))
+                    .map(|(_tab_index, tab)| tab.position);
+                match tab_index {
+                    Some(tab_index) => {
+                        if let Some(tab) =
+                            self.tabs.iter_mut().find(|(_, t)| t.position == tab_index)
+                        {
+                            suppress_pane(tab.1, pane_id, new_pane_id);
+                        }
+                    },
+                    None => {
+                        log::error!("Could not find pane with id: {:?}", pane_id);
+                    },
+                };
+            },
+            ClientTabIndexOrPaneId::TabIndex(_tab_index) => {
+                log::error!("Cannot replace pane with tab index");
+            },
+        }
+        Ok(())
+    }
+    pub fn reconfigure(
+        &mut self,
+        new_keybinds: Keybinds,
+        new_default_mode: InputMode,
+        theme: Styling,
+        simplified_ui: bool,
+        default_shell: Option<PathBuf>,
+        pane_frames: bool,
+        copy_command: Option<String>,
+        copy_providers: Option<Vec<CopyProvider>>,
+        copy_to_clipboard: Option<Clipboard>,
+        copy_on_select: bool,
+        osc52_forwarding: bool,
+        word_separators: Option<String>,
+        auto_layout: bool,
+        rounded_corners: bool,
+        hide_session_name: bool,
+        stacked_resize: bool,
+        default_editor: Option<PathBuf>,
+        quiet_hours: Option<QuietHours>,
+        title_precedence: TitlePrecedence,
+        tab_name_from_title: bool,
+        bell_forwarding: BellForwarding,
+        command_exit_banner: CommandExitBanner,
+        idle_client_detach_timeout: Option<u64>,
+        idle_session_timeout: Option<u64>,
+        mirror_session: bool,
+        global_floating_panes: bool,
+        automatic_tab_rename: bool,
+        pane_frame_process_stats: bool,
+        confirm_close_with_running_processes: bool,
+        command_exit_notification_command: Option<String>,
+        resource_limits: ResourceLimits,
+        alternate_screen_behavior: AlternateScreenBehavior,
+        unicode_width: UnicodeWidth,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let should_support_arrow_fonts = !simplified_ui;
+
+        // global configuration
+        self.default_mode_info.update_theme(theme);
+        self.default_mode_info
+            .update_rounded_corners(rounded_corners);
+        self.default_shell = default_shell.clone().unwrap_or_else(|| get_default_shell());
+        self.default_editor = default_editor.clone().or_else(|| get_default_editor());
+        self.auto_layout = auto_layout;
+        self.quiet_hours = quiet_hours;
+        self.title_precedence = title_precedence;
+        self.tab_name_from_title = tab_name_from_title;
+        self.bell_forwarding = bell_forwarding;
+        self.command_exit_banner = command_exit_banner;
+        self.set_idle_client_detach_timeout(idle_client_detach_timeout)?;
+        self.set_idle_session_timeout(idle_session_timeout)?;
+        self.floating_panes_are_global = global_floating_panes;
+        self.set_automatic_tab_rename(automatic_tab_rename)?;
+        self.set_pane_frame_process_stats(pane_frame_process_stats)?;
+        self.confirm_close_with_running_processes = confirm_close_with_running_processes;
+        self.command_exit_notification_command = command_exit_notification_command;
+        self.resource_limits = resource_limits;
+        self.alternate_screen_behavior = alternate_screen_behavior;
+        if unicode_width != self.unicode_width {
+            self.unicode_width = unicode_width;
+            for tab in self.tabs.values_mut() {
+                tab.update_unicode_width(unicode_width);
+            }
+        }
+        self.copy_options.command = copy_command.clone();
+        self.copy_options.providers =
+            copy_providers.unwrap_or_else(CopyProvider::default_providers);
+        self.copy_options.copy_on_select = copy_on_select;
+        self.copy_options.osc52_forwarding = osc52_forwarding;
+        self.copy_options.word_separators =
+            word_separators.unwrap_or_else(|| DEFAULT_WORD_SEPARATORS.to_owned());
+        self.draw_pane_frames = pane_frames;
+        self.default_mode_info
+            .update_arrow_fonts(should_support_arrow_fonts);
+        self.default_mode_info
+            .update_hide_session_name(hide_session_name);
+        {
+            *self.stacked_resize.borrow_mut() = stacked_resize;
+        }
+        if let Some(copy_to_clipboard) = copy_to_clipboard {
+            self.copy_options.clipboard = copy_to_clipboard;
+        }
+        for tab in self.tabs.values_mut() {
+            tab.update_theme(theme);
+            tab.update_rounded_corners(rounded_corners);
+            tab.update_default_shell(default_shell.clone());
+            tab.update_default_editor(self.default_editor.clone());
+            tab.update_auto_layout(auto_layout);
+            tab.set_alternate_screen_behavior(alternate_screen_behavior);
+            tab.update_copy_options(&self.copy_options);
+            tab.set_pane_frames(pane_frames);
+            tab.update_arrow_fonts(should_support_arrow_fonts);
+        }
+
+        // client specific configuration
+        if self.connected_clients_contains(&client_id) {
+            let mode_info = self
+                .mode_info
+                .entry(client_id)
+                .or_insert_with(|| self.default_mode_info.clone());
+            mode_info.update_keybinds(new_keybinds);
+            mode_info.update_default_mode(new_default_mode);
+            mode_info.update_theme(theme);
+            mode_info.update_arrow_fonts(should_support_arrow_fonts);
+            mode_info.update_hide_session_name(hide_session_name);
+            for tab in self.tabs.values_mut() {
+                tab.change_mode_info(mode_info.clone(), client_id);
+                tab.mark_active_pane_for_rerender(client_id);
+            }
+        }
+
+        // this needs to be done separately at the end because it applies some of the above changes
+        // and propagates them to plugins
+        for tab in self.tabs.values_mut() {
+            tab.update_input_modes()?;
+        }
+        self.set_session_is_mirrored(mirror_session, client_id)
+    }
+    /// When mirrored, all clients share the same focused tab and pane, otherwise each client
+    /// focuses its own. Turning mirroring on gathers the clients in the tab of the given client.
+    fn set_session_is_mirrored(
+        &mut self,
+        session_is_mirrored: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || "failed to change whether the session is mirrored";
+        if self.session_is_mirrored == session_is_mirrored {
+            return Ok(());
+        }
+        self.session_is_mirrored = session_is_mirrored;
+        for tab in self.tabs.values_mut() {
+            tab.set_session_is_mirrored(session_is_mirrored, client_id);
+        }
+        if !session_is_mirrored {
+            return Ok(());
+        }
+        let destination_tab_index = match self
+            .active_tab_indices
+            .get(&client_id)
+            .or_else(|| self.active_tab_indices.values().next())
+        {
+            Some(tab_index) => *tab_index,
+            None => return Ok(()),
+        };
+        let clients_in_other_tabs: Vec<(ClientId, usize)> = self
+            .active_tab_indices
+            .iter()
+            .filter(|(_, tab_index)| **tab_index != destination_tab_index)
+            .map(|(client_id, tab_index)| (*client_id, *tab_index))
+            .collect();
+        for (moved_client_id, source_tab_index) in clients_in_other_tabs {
+            self.move_clients_between_tabs(
+                source_tab_index,
+                destination_tab_index,
+                true,
+                Some(vec![moved_client_id]),
+            )
+            .with_context(err_context)?;
+            self.update_client_tab_focus(moved_client_id, destination_tab_index);
+            if let Some(source_tab) = self.get_indexed_tab_mut(source_tab_index) {
+                if source_tab.has_no_connected_clients() {
+                    source_tab.visible(false).with_context(err_context)?;
+                }
+            }
+        }
+        if let Some(destination_tab) = self.get_indexed_tab_mut(destination_tab_index) {
+            // align the pane focus of the clients that were already in this tab
+            destination_tab.set_session_is_mirrored(true, client_id);
+            destination_tab.visible(true).with_context(err_context)?;
+        }
+        self.log_and_report_session_state()
+            .with_context(err_context)
+    }
+    pub fn report_captured_pane_exit(
+        &mut self,
+        terminal_id: u32,
+        exit_status: Option<i32>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to report exit of captured pane {terminal_id}");
+        let Some((client_id, tail_lines)) = self.output_captures.remove(&terminal_id) else {
+            return Ok(());
+        };
+        if let Some(tail_lines) = tail_lines {
+            let pane_contents = self.tabs.values().find_map(|tab| {
+                tab.get_pane_with_id(PaneId::Terminal(terminal_id))
+                    .map(|pane| pane.dump_screen(true))
+            });
+            if let Some(pane_contents) = pane_contents {
+                let lines: Vec<&str> = pane_contents.trim_end().lines().collect();
+                let mut output = lines[lines.len().saturating_sub(tail_lines)..].join("\n");
+                output.push('\n');
+                self.bus
+                    .senders
+                    .send_to_server(ServerInstruction::CapturedPaneOutput(
+                        client_id,
+                        output.into_bytes(),
+                    ))
+                    .with_context(err_context)?;
+            }
+        }
+        self.bus
+            .senders
+            .send_to_server(ServerInstruction::CapturedPaneExited(
+                client_id,
+                exit_status,
+            ))
+            .with_context(err_context)
+    }
+    pub fn toggle_pane_pinned(&mut self, client_id: ClientId) {
+        active_tab_and_connected_client_id!(
+            self,
+            client_id,
+            |tab: &mut Tab, client_id: ClientId| {
+                tab.toggle_pane_pinned(client_id);
+            }
+        );
+        self.unblock_input().non_fatal();
+    }
+    pub fn set_floating_pane_pinned(&mut self, pane_id: PaneId, should_be_pinned: bool) {
+        let mut found = false;
+        for tab in self.tabs.values_mut() {
+            if tab.has_pane_with_pid(&pane_id) {
+                tab.set_floating_pane_pinned(pane_id, should_be_pinned);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            log::error!(
+                "Failed to find pane with id: {:?} to set as pinned",
+                pane_id
+            );
+        }
+    }
+    /// Makes the focused floating pane of the client follow the focused tab, or return it to the
+    /// tab it is in if it already does
+    pub fn toggle_floating_pane_scope(&mut self, client_id: ClientId) -> Result<()> {
+        let floating_pane_id = self.get_active_tab(client_id).ok().and_then(|tab| {
+            tab.get_active_pane_id(client_id)
+                .filter(|pane_id| tab.get_floating_panes().any(|(id, _)| id == pane_id))
+        });
+        match floating_pane_id {
+            Some(pane_id) => {
+                if !self.floating_panes_with_toggled_scope.remove(&pane_id) {
+                    self.floating_panes_with_toggled_scope.insert(pane_id);
+                }
+            },
+            None => log::error!("The focused pane of client {client_id} is not floating"),
+        }
+        self.unblock_input()
+    }
+    fn is_global_floating_pane(&self, pane_id: &PaneId) -> bool {
+        self.floating_panes_are_global != self.floating_panes_with_toggled_scope.contains(pane_id)
+    }
+    /// Moves the global floating panes of the other tabs to this one, so that they follow the
+    /// focused tab. They keep their position and are shown along with the other floating panes of
+    /// the tab (or always, if they are pinned).
+    fn bring_global_floating_panes_to_tab(&mut self, tab_index: usize) -> Result<()> {
+        let err_context = || format!("failed to bring global floating panes to tab {tab_index}");
+        let global_floating_panes: Vec<(usize, PaneId)> = self
+            .tabs
+            .values()
+            .filter(|tab| tab.index != tab_index)
+            .flat_map(|tab| {
+                tab.get_floating_panes()
+                    .map(|(pane_id, _)| (tab.index, *pane_id))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|(_, pane_id)| self.is_global_floating_pane(pane_id))
+            .collect();
+        for (source_tab_index, pane_id) in global_floating_panes {
+            let pane = self
+                .tabs
+                .get_mut(&source_tab_index)
+                .and_then(|tab| tab.extract_pane(pane_id, true));
+            if let Some(pane) = pane {
+                self.tabs
+                    .get_mut(&tab_index)
+                    .with_context(err_context)?
+                    .add_floating_pane_keeping_position(pane, pane_id)
+                    .with_context(err_context)?;
+            }
+        }
+        Ok(())
+    }
+    /// Focuses the stacked pane of the active tab whose title best matches the query, telling the
+    /// user if there is none
+    pub fn focus_stacked_pane_by_title(&mut self, query: &str, client_id: ClientId) -> Result<()> {
+        let err_context =
+            || format!("failed to focus stacked pane by title for client {client_id}");
+        let focused_pane_id = self
+            .get_active_tab_mut(client_id)
+            .and_then(|tab| tab.focus_stacked_pane_by_title(query, client_id))
+            .with_context(err_context)?;
+        if focused_pane_id.is_none() {
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::LogError(
+                    vec![format!("No stacked pane with a title matching: {}", query)],
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
+    pub fn stack_panes(&mut self, mut pane_ids_to_stack: Vec<PaneId>) {
+        if pane_ids_to_stack.is_empty() {
+            log::error!("Got an empty list of pane_ids to stack");
+            return;
+        }
+        let stack_size = pane_ids_to_stack.len();
+        let root_pane_id = pane_ids_to_stack.remove(0);
+        let Some(root_tab_id) = self
+            .tabs
+            .iter()
+            .find_map(|(tab_id, tab)| {
+                if tab.has_pane_with_pid(&root_pane_id) {
+                    Some(tab_id)
+                } else {
+                    None
+                }
+            })
+            .copied()
+        else {
+            log::error!("Failed to find tab for root_pane_id: {:?}", root_pane_id);
+            return;
+        };
+        let target_tab_has_room_for_stack = self
+            .tabs
+            .get(&root_tab_id)
+            .map(|t| t.has_room_for_stack(root_pane_id, stack_size))
+            .unwrap_or(false);
+        if !target_tab_has_room_for_stack {
+            log::error!("No room for stack with root pane id: {:?}", root_pane_id);
+            return;
+        }
+
+        let mut panes_to_stack = vec![];
+        for (tab_id, tab) in self.tabs.iter_mut() {
+            if tab_id == &root_tab_id {
+                // we do this before we extract panes so that the extraction won't trigger a
+                // relayout according to the next swapped tiled pane
+                tab.set_tiled_panes_damaged();
+            }
+            for pane_id in &pane_ids_to_stack {
+                if tab.has_pane_with_pid(&pane_id) {
+                    match tab.extract_pane(*pane_id, false) {
+                        Some(pane) => {
+                            panes_to_stack.push(pane);
+                        },
+                        None => {
+                            log::error!("Failed to extract pane: {:?}", pane_id);
+                        },
+                    }
+                }
+            }
+        }
+        self.tabs
+            .get_mut(&root_tab_id)
+            .map(|t| t.stack_panes(root_pane_id, panes_to_stack));
+    }
+    pub fn change_floating_panes_coordinates(
+        &mut self,
+        pane_ids_and_coordinates: Vec<(PaneId, FloatingPaneCoordinates)>,
+    ) {
+        for (pane_id, coordinates) in pane_ids_and_coordinates {
+            for (_tab_id, tab) in self.tabs.iter_mut() {
+                if tab.has_pane_with_pid(&pane_id) {
+                    tab.change_floating_pane_coordinates(&pane_id, coordinates)
+                        .non_fatal();
+                    break;
+                }
+            }
+        }
+    }
+    /// Renames the session, along with its ipc socket, its resurrection metadata and the name its
+    /// clients reattach by. The client is told why when the name cannot be used.
+    pub fn rename_session(&mut self, name: String, client_id: ClientId) -> Result<()> {
+        let err_context = || "failed to rename session";
+        let refusal = if name == self.session_name {
+            // renaming the session to its own name changes nothing
+            return Ok(());
+        } else if self.session_infos_on_machine.contains_key(&name) {
+            Some("A session by this name already exists.".to_owned())
+        } else if self.resurrectable_sessions.contains_key(&name) {
+            Some("A resurrectable session by this name exists, cannot use this name.".to_owned())
+        } else if let Err(e) =
+            rename_session_socket(&self.session_socket_dir, &self.session_name, &name)
+        {
+            // nothing was renamed yet, the session keeps its old name
+            Some(format!("Failed to rename session: {}", e))
+        } else {
+            None
+        };
+        if let Some(error_text) = refusal {
+            log::error!("{}", error_text);
+            if let Some(os_input) = &mut self.bus.os_input {
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::LogError(vec![error_text]));
+            }
+            return Ok(());
+        }
+        let old_session_name = self.session_name.clone();
+
+        // update state
+        self.session_name = name.clone();
+        self.default_mode_info.session_name = Some(name.clone());
+        for (_client_id, mode_info) in self.mode_info.iter_mut() {
+            mode_info.session_name = Some(name.clone());
+        }
+        for (_, tab) in self.tabs.iter_mut() {
+            tab.rename_session(name.clone()).with_context(err_context)?;
+        }
+
+        // move the resurrection metadata, background_jobs does this so that it does not write
+        // the session state under the old name in the meantime
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::RenameSession(old_session_name, name.clone()))
+            .with_context(err_context)?;
+
+        // report
+        self.log_and_report_session_state()
+            .with_context(err_context)?;
+
+        // set the env variable (for new panes) and let the clients know so that they reattach by
+        // the new name
+        set_session_name(name.clone());
+        if let Some(os_input) = &mut self.bus.os_input {
+            for client_id in self.connected_clients.borrow().iter() {
+                let _ = os_input
+                    .send_to_client(*client_id, ServerToClientMsg::RenamedSession(name.clone()));
+            }
+        }
+        self.render(None).with_context(err_context)
+    }
+    fn unblock_input(&self) -> Result<()> {
+        self.bus
+            .senders
+            .send_to_server(ServerInstruction::UnblockInputThread)
+            .context("failed to unblock input")
+    }
+    fn get_layout_metadata(&self, default_shell: Option<PathBuf>) -> SessionLayoutMetadata {
+        let mut session_layout_metadata = SessionLayoutMetadata::new(self.default_layout.clone());
+        if let Some(default_shell) = default_shell {
+            session_layout_metadata.update_default_shell(default_shell);
+        }
+        let first_client_id = self.get_first_client_id();
+        let active_tab_index =
+            first_client_id.and_then(|client_id| self.active_tab_indices.get(&client_id));
+
+        for (tab_index, tab) in self.tabs.iter() {
+            let tab_is_focused = active_tab_index == Some(&tab_index);
+            let hide_floating_panes = !tab.are_floating_panes_visible();
+            let mut suppressed_panes = HashMap::new();
+            for (triggering_pane_id, p) in tab.get_suppressed_panes() {
+                suppressed_panes.insert(*triggering_pane_id, p);
+            }
+
+            let all_connected_clients: Vec<ClientId> = self
+                .connected_clients
+                .borrow()
+                .iter()
+                .copied()
+                .filter(|c| self.active_tab_indices.get(&c) == Some(&tab_index))
+                .collect();
+
+            let mut active_pane_ids: HashMap<ClientId, Option<PaneId>> = HashMap::new();
+            for connected_client_id in &all_connected_clients {
+                active_pane_ids.insert(
+                    *connected_client_id,
+                    tab.get_active_pane_id(*connected_client_id),
+                );
+            }
+
+            let tiled_panes: Vec<PaneLayoutMetadata> = tab
+                .get_tiled_panes()
+                .map(|(pane_id, p)| {
+                    // here we look to see if this pane triggers any suppressed pane,
+                    // and if so we take that suppressed pane - we do this because this
+                    // is currently only the case the scrollback editing panes, and
+                    // when dumping the layout we want the "real" pane and not the
+                    // editor pane
+                    match suppressed_panes.remove(pane_id) {
+                        Some((is_scrollback_editor, suppressed_pane)) if *is_scrollback_editor => {
+                            (suppressed_pane.pid(), suppressed_pane)
+                        },
+                        _ => (*pane_id, p),
+                    }
+                })
+                .map(|(pane_id, p)| {
+                    let focused_clients: Vec<ClientId> = active_pane_ids
+                        .iter()
+                        .filter_map(|(c_id, p_id)| {
+                            p_id.and_then(|p_id| if p_id == pane_id { Some(*c_id) } else { None })
+                        })
+                        .collect();
+                    if p.is_linked_view() {
+                        // we only keep its place, so that the other panes keep their sizes
+                        return PaneLayoutMetadata::new_linked_view(
+                            pane_id,
+                            p.position_and_size(),
+                            p.borderless(),
+                            !focused_clients.is_empty(),
+                            focused_clients,
+                        );
+                    }
+                    PaneLayoutMetadata::new(
+                        pane_id,
+                        p.position_and_size(),
+                        p.borderless(),
+                        p.invoked_with().clone(),
+                        p.custom_title(),
+                        !focused_clients.is_empty(),
+                        tab.fullscreen_pane_id() == Some(pane_id),
+                        if self.serialize_pane_viewport {
+                            p.serialize(self.scrollback_lines_to_serialize)
+                        } else {
+                            None
+                        },
+                        focused_clients,
+                    )
+                })
+                .collect();
+            let floating_panes: Vec<PaneLayoutMetadata> = tab
+                .get_floating_panes()
+                .map(|(pane_id, p)| {
+                    // here we look to see if this pane triggers any suppressed pane,
+                    // and if so we take that suppressed pane - we do this because this
+                    // is currently only the case the scrollback editing panes, and
+                    // when dumping the layout we want the "real" pane and not the
+                    // editor pane
+                    match suppressed_panes.remove(pane_id) {
+                        Some((is_scrollback_editor, suppressed_pane)) if *is_scrollback_editor => {
+                            (suppressed_pane.pid(), suppressed_pane)
+                        },
+                        _ => (*pane_id, p),
+                    }
+                })
+                .map(|(pane_id, p)| {
+                    let focused_clients: Vec<ClientId> = active_pane_ids
+                        .iter()
+                        .filter_map(|(c_id, p_id)| {
+                            p_id.and_then(|p_id| if p_id == pane_id { Some(*c_id) } else { None })
+                        })
+                        .collect();
+                    PaneLayoutMetadata::new(
+                        pane_id,
+                        p.position_and_size(),
+                        false, // floating panes are never borderless
+                        p.invoked_with().clone(),
+                        p.custom_title(),
+                        !focused_clients.is_empty(),
+                        false, // floating panes are never fullscreen
+                        if self.serialize_pane_viewport {
+                            p.serialize(self.scrollback_lines_to_serialize)
+                        } else {
+                            None
+                        },
+                        focused_clients,
+                    )
+                })
+                .collect();
+            session_layout_metadata.add_tab(
+                tab.name.clone(),
+                tab_is_focused,
+                hide_floating_panes,
+                tiled_panes,
+                floating_panes,
+            );
+        }
+        session_layout_metadata
+    }
+    fn update_plugin_loading_stage(
+        &mut self,
+        pid: u32,
+        loading_indication: LoadingIndication,
+    ) -> bool {
+        let all_tabs = self.get_tabs_mut();
+        let mut found_plugin = false;
+        for tab in all_tabs.values_mut() {
+            if tab.has_plugin(pid) {
+                found_plugin = true;
+                tab.update_plugin_loading_stage(pid, loading_indication);
+                break;
+            }
+        }
+        found_plugin
+    }
+    fn connected_clients_contains(&self, client_id: &ClientId) -> bool {
+        self.connected_clients.borrow().contains(client_id)
+    }
+    pub fn report_session_stats(&self, client_id: ClientId) -> Result<()> {
+        let err_context = || format!("failed to report session stats to client {client_id}");
+        let tab_count = self.tabs.len();
+        let pane_count = self
+            .tabs
+            .values()
+            .flat_map(|tab| tab.get_all_pane_ids())
+            .filter(|pane_id| matches!(pane_id, PaneId::Terminal(_)))
+            .count();
+        self.bus
+            .senders
+            .send_to_server(ServerInstruction::SessionStats {
+                client_id,
+                tab_count,
+                pane_count,
+            })
+            .with_context(err_context)
+    }
+}
+
+// how long before detaching an idle client it is shown a countdown
+const IDLE_DETACH_WARNING: Duration = Duration::from_secs(60);
+// only this many lines of scrollback are copied along with a pane moved to another session, or
+// into a linked view of it
+const COPIED_SCROLLBACK_LINES: usize = 10_000;
+
+fn idle_timeout_duration(minutes: Option<u64>) -> Option<Duration> {
+    minutes
+        .filter(|minutes| *minutes > 0)
+        .map(|minutes| Duration::from_secs(minutes * 60))
+}
+
+// the socket is linked under its new name before the old one is removed, so that it is always
+// reachable and linking fails rather than replace the socket of a session that took the name
+fn rename_session_socket(
+    socket_dir: &Path,
+    old_session_name: &str,
+    new_session_name: &str,
+) -> std::io::Result<()> {
+    let old_socket_file_path = socket_dir.join(old_session_name);
+    let new_socket_file_path = socket_dir.join(new_session_name);
+    std::fs::hard_link(&old_socket_file_path, &new_socket_file_path)?;
+    std::fs::remove_file(&old_socket_file_path).map_err(|e| {
+        let _ = std::fs::remove_file(&new_socket_file_path);
+        e
+    })
+}
+
+// drawn over the middle of the screen, leaving the cursor as it was
+fn idle_detach_warning(seconds_left: u64, size: Size) -> String {
+    let text: String = format!(
+        " Detaching in {}s for inactivity, press any key to stay attached ",
+        seconds_left
+    )
+    .chars()
+    .take(size.cols)
+    .collect();
+    let text_width = text.chars().count();
+    let row = size.rows / 2 + 1;
+    let col = size.cols.saturating_sub(text_width) / 2 + 1;
+    format!(
+        "\u{1b}7\u{1b}[{};{}H\u{1b}[1;7m{}\u{1b}[m\u{1b}8",
+        row, col, text
+    )
+}
+
+#[cfg(not(test))]
+fn get_default_editor() -> Option<PathBuf> {
+    std::env::var("EDITOR")
+        .or_else(|_| std::env::var("VISUAL"))
+        .map(|e| PathBuf::from(e))
+        .ok()
+}
+
+#[cfg(test)]
+fn get_default_editor() -> Option<PathBuf> {
+    None
+}
+
+// The box is here in order to make the
+// NewClient enum smaller
+#[allow(clippy::boxed_local)]
+pub(crate) fn screen_thread_main(
+    bus: Bus<ScreenInstruction>,
+    max_panes: Option<usize>,
+    client_attributes: ClientAttributes,
+    config: Config,
+    debug: bool,
+    default_layout: Box<Layout>,
+    session_tags: Vec<String>,
+) -> Result<()> {
+    let config_options = config.options;
+    let arrow_fonts = !config_options.simplified_ui.unwrap_or_default();
+    let draw_pane_frames = config_options.pane_frames.unwrap_or(true);
+    let auto_layout = config_options.auto_layout.unwrap_or(true);
+    let session_serialization = config_options.session_serialization.unwrap_or(true);
+    let serialize_pane_viewport = config_options.serialize_pane_viewport.unwrap_or(false);
+    let scrollback_lines_to_serialize = config_options.scrollback_lines_to_serialize;
+    let session_is_mirrored = config_options.mirror_session.unwrap_or(false);
+    let layout_dir = config_options.layout_dir;
+    #[cfg(test)]
+    let default_shell = config_options
+        .default_shell
+        .clone()
+        .unwrap_or(PathBuf::from("/bin/sh"));
+    #[cfg(not(test))]
+    let default_shell = config_options
+        .default_shell
+        .clone()
+        .unwrap_or_else(|| get_default_shell());
+    let default_editor = config_options
+        .scrollback_editor
+        .clone()
+        .or_else(|| get_default_editor());
+    let default_layout_name = config_options
+        .default_layout
+        .map(|l| format!("{}", l.display()));
+    let copy_options = CopyOptions::new(
+        config_options.copy_command,
+        config_options
+            .copy_providers
+            .unwrap_or_else(CopyProvider::default_providers),
+        config_options.copy_clipboard.unwrap_or_default(),
+        config_options.copy_on_select.unwrap_or(true),
+        config_options.osc52_forwarding.unwrap_or(true),
+        config_options
+            .word_separators
+            .unwrap_or_else(|| DEFAULT_WORD_SEPARATORS.to_owned()),
+    );
+    let styled_underlines = config_options.styled_underlines.unwrap_or(true);
+    let explicitly_disable_kitty_keyboard_protocol = config_options
+        .support_kitty_keyboard_protocol
+        .map(|e| !e) // this is due to the config options wording, if
+        // "support_kitty_keyboard_protocol" is true,
+        // explicitly_disable_kitty_keyboard_protocol is false and vice versa
+        .unwrap_or(false); // by default, we try to support this if the terminal supports it and
+                           // the program running inside a pane requests it
+    let stacked_resize = config_options.stacked_resize.unwrap_or(true);
+    let quiet_hours = config_options.quiet_hours;
+    let title_precedence = config_options.title_precedence.unwrap_or_default();
+    let tab_name_from_title = config_options.tab_name_from_title.unwrap_or(false);
+    let bell_forwarding = config_options.bell_forwarding.unwrap_or_default();
+    let command_exit_banner = config_options.command_exit_banner.unwrap_or_default();
+    let idle_client_detach_timeout = config_options.idle_client_detach_timeout;
+    let idle_session_timeout = config_options.idle_session_timeout;
+    let global_floating_panes = config_options.global_floating_panes.unwrap_or(false);
+    let automatic_tab_rename = config_options.automatic_tab_rename.unwrap_or(false);
+    let pane_frame_process_stats = config_options.pane_frame_process_stats.unwrap_or(false);
+    let confirm_close_with_running_processes = config_options
+        .confirm_close_with_running_processes
+        .unwrap_or(true);
+    let command_exit_notification_command =
+        config_options.command_exit_notification_command.clone();
+    let resource_limits = ResourceLimits::from_options(&config_options);
+    let alternate_screen_behavior = AlternateScreenBehavior::from_options(&config_options);
+    let unicode_width = UnicodeWidth::from_options(&config_options);
+    let update_check = config_options.update_check.unwrap_or_default();
+
+    let thread_senders = bus.senders.clone();
+    let mut screen = Screen::new(
+        bus,
+        &client_attributes,
+        max_panes,
+        get_mode_info(
+            config_options.default_mode.unwrap_or_default(),
+            &client_attributes,
+            PluginCapabilities {
+                //  ¯\_(ツ)_/¯
+                arrow_fonts: !arrow_fonts,
+            },
+            &config.keybinds,
+            config_options.default_mode,
+        ),
+        draw_pane_frames,
+        auto_layout,
+        session_is_mirrored,
+        copy_options,
+        debug,
+        default_layout,
+        default_layout_name,
+        default_shell,
+        session_serialization,
+        serialize_pane_viewport,
+        scrollback_lines_to_serialize,
+        styled_underlines,
+        arrow_fonts,
+        layout_dir,
+        explicitly_disable_kitty_keyboard_protocol,
+        stacked_resize,
+        default_editor,
+        session_tags,
+        quiet_hours,
+        title_precedence,
+        tab_name_from_title,
+        bell_forwarding,
+        command_exit_banner,
+        idle_client_detach_timeout,
+    );
+    screen.floating_panes_are_global = global_floating_panes;
+    screen.set_automatic_tab_rename(automatic_tab_rename)?;
+    screen.set_pane_frame_process_stats(pane_frame_process_stats)?;
+    screen.confirm_close_with_running_processes = confirm_close_with_running_processes;
+    screen.command_exit_notification_command = command_exit_notification_command;
+    screen.resource_limits = resource_limits;
+    screen.alternate_screen_behavior = alternate_screen_behavior;
+    screen.unicode_width = unicode_width;
+    if screen.idle_client_detach_timeout.is_some() {
+        thread_senders
+            .send_to_background_jobs(BackgroundJob::MonitorIdleClients)
+            .context("failed to start monitoring idle clients")?;
+    }
+    screen.set_idle_session_timeout(idle_session_timeout)?;
+    if update_check != UpdateCheck::Off {
+        thread_senders
+            .send_to_background_jobs(BackgroundJob::CheckForUpdates(update_check))
+            .context("failed to start checking for updates")?;
+    }
+
+    let mut pending_tab_ids: HashSet<usize> = HashSet::new();
+    let mut pending_tab_switches: HashSet<(usize, ClientId)> = HashSet::new(); // usize is the
+                                                                               // tab_index
+    let mut pending_events_waiting_for_tab: Vec<ScreenInstruction> = vec![];
+    let mut pending_events_waiting_for_client: Vec<ScreenInstruction> = vec![];
+    let mut plugin_loading_message_cache = HashMap::new();
+    loop {
+        let (event, mut err_ctx) = screen
+            .bus
+            .recv()
+            .context("failed to receive event on channel")?;
+        err_ctx.add_call(ContextType::Screen((&event).into()));
+        // here we start caching resizes, so that we'll send them in bulk at the end of each event
+        // when this cache is Dropped, for more information, see the comments in PtyWriter
+        let _resize_cache = ResizeCache::new(thread_senders.clone());
+
+        match event {
+            ScreenInstruction::PtyBytes(pid, vte_bytes) => {
+                if let Some((client_id, None)) = screen.output_captures.get(&pid) {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::CapturedPaneOutput(
+                            *client_id,
+                            vte_bytes.clone(),
+                        ))
+                        .non_fatal();
+                }
+                screen.session_activity();
+                // the pane might be shown in other tabs too, as linked views
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_terminal_pid(pid) {
+                        tab.handle_pty_bytes(pid, vte_bytes.clone())
+                            .context("failed to process pty bytes")?;
+                    }
+                }
+            },
+            ScreenInstruction::PluginBytes(mut plugin_render_assets) => {
+                for plugin_render_asset in plugin_render_assets.iter_mut() {
+                    let plugin_id = plugin_render_asset.plugin_id;
+                    let client_id = plugin_render_asset.client_id;
+                    let vte_bytes = plugin_render_asset.bytes.drain(..).collect();
+
+                    let all_tabs = screen.get_tabs_mut();
+                    for tab in all_tabs.values_mut() {
+                        if tab.has_plugin(plugin_id) {
+                            tab.handle_plugin_bytes(plugin_id, client_id, vte_bytes)
+                                .context("failed to process plugin bytes")?;
+                            break;
+                        }
+                    }
+                }
+                screen.render(Some(plugin_render_assets))?;
+            },
+            ScreenInstruction::Render => {
+                screen.render(None)?;
+            },
+            ScreenInstruction::NewPane(
+                pid,
+                initial_pane_title,
+                should_float,
+                hold_for_command,
+                invoked_with,
+                floating_pane_coordinates,
+                start_suppressed,
+                client_or_tab_index,
+            ) => {
+                if screen.refuse_pane_over_limits(pid, &client_or_tab_index)? {
+                    continue;
+                }
+                match client_or_tab_index {
+                    ClientTabIndexOrPaneId::ClientId(client_id) => {
+                        active_tab_and_connected_client_id!(screen, client_id, |tab: &mut Tab, client_id: ClientId| {
+                            tab.new_pane(pid,
+                               initial_pane_title,
+                               should_float,
+                               invoked_with,
+                               floating_pane_coordinates,
+                               start_suppressed,
+                               Some(client_id)
+                           )
+                        }, ?);
+                        if let Some(hold_for_command) = hold_for_command {
+                            let is_first_run = true;
+                            active_tab_and_connected_client_id!(
+                                screen,
+                                client_id,
+                                |tab: &mut Tab, _client_id: ClientId| tab.hold_pane(
+                                    pid,
+                                    None,
+                                    is_first_run,
+                                    hold_for_command
+                                )
+                            )
+                        }
+                    },
+                    ClientTabIndexOrPaneId::TabIndex(tab_index) => {
+                        if let Some(active_tab) = screen.tabs.get_mut(&tab_index) {
+                            active_tab.new_pane(
+                                pid,
+                                initial_pane_title,
+                                should_float,
+                                invoked_with,
+                                floating_pane_coordinates,
+                                start_suppressed,
+                                None,
+                            )?;
+                            if let Some(hold_for_command) = hold_for_command {
+                                let is_first_run = true;
+                                active_tab.hold_pane(pid, None, is_first_run, hold_for_command);
+                            }
+                        } else {
+                            log::error!("Tab index not found: {:?}", tab_index);
+                        }
+                    },
+                    ClientTabIndexOrPaneId::PaneId(pane_id) => {
+                        let mut found = false;
+                        let all_tabs = screen.get_tabs_mut();
+                        for tab in all_tabs.values_mut() {
+                            if tab.has_pane_with_pid(&pane_id) {
+                                tab.new_pane(
+                                    pid,
+                                    initial_pane_title,
+                                    should_float,
+                                    invoked_with,
+                                    floating_pane_coordinates,
+                                    start_suppressed,
+                                    None,
+                                )?;
+                                if let Some(hold_for_command) = hold_for_command {
+                                    let is_first_run = true;
+                                    tab.hold_pane(pid, None, is_first_run, hold_for_command);
+                                }
+                                found = true;
+                                break;
+                            }
+                        }
+                        if !found {
+                            log::error!(
+                                "Failed to find tab containing pane with id: {:?}",
+                                pane_id
+                            );
+                        }
+                    },
+                };
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+
+                screen.render(None)?;
+            },
+            ScreenInstruction::OpenInPlaceEditor(pid, client_tab_index_or_pane_id) => {
+                match client_tab_index_or_pane_id {
+                    ClientTabIndexOrPaneId::ClientId(client_id) => {
+                        active_tab!(screen, client_id, |tab: &mut Tab| tab
+                            .replace_active_pane_with_editor_pane(pid, client_id), ?);
+                        screen.unblock_input()?;
+                        screen.log_and_report_session_state()?;
+                    },
+                    ClientTabIndexOrPaneId::TabIndex(_tab_index) => {
+                        log::error!("Cannot OpenInPlaceEditor with a TabIndex");
+                    },
+                    ClientTabIndexOrPaneId::PaneId(pane_id_to_replace) => {
+                        let mut found = false;
+                        let all_tabs = screen.get_tabs_mut();
+                        for tab in all_tabs.values_mut() {
+                            if tab.has_pane_with_pid(&pane_id_to_replace) {
+                                tab.replace_pane_with_editor_pane(pid, pane_id_to_replace)
+                                    .non_fatal();
+                                found = true;
+                                break;
+                            }
+                        }
+                        if !found {
+                            log::error!(
+                                "Could not find pane with id {:?} to replace",
+                                pane_id_to_replace
+                            );
+                        }
+                    },
+                }
+
+                screen.render(None)?;
+            },
+            ScreenInstruction::TogglePaneEmbedOrFloating(client_id) => {
+                active_tab_and_connected_client_id!(screen, client_id, |tab: &mut Tab, client_id: ClientId| tab
+                    .toggle_pane_embed_or_floating(client_id), ?);
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+
+                screen.render(None)?;
+            },
+            ScreenInstruction::ToggleFloatingPanes(client_id, default_shell) => {
+                active_tab_and_connected_client_id!(screen, client_id, |tab: &mut Tab, client_id: ClientId| tab
+                    .toggle_floating_panes(Some(client_id), default_shell), ?);
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+
+                screen.render(None)?;
+            },
+            ScreenInstruction::HorizontalSplit(
+                pid,
+                initial_pane_title,
+                hold_for_command,
+                client_id,
+            ) => {
+                if screen
+                    .refuse_pane_over_limits(pid, &ClientTabIndexOrPaneId::ClientId(client_id))?
+                {
+                    continue;
+                }
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.horizontal_split(pid, initial_pane_title, client_id),
+                    ?
+                );
+                if let Some(hold_for_command) = hold_for_command {
+                    let is_first_run = true;
+                    active_tab_and_connected_client_id!(
+                        screen,
+                        client_id,
+                        |tab: &mut Tab, _client_id: ClientId| tab.hold_pane(
+                            pid,
+                            None,
+                            is_first_run,
+                            hold_for_command
+                        )
+                    );
+                }
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+                screen.render(None)?;
+            },
+            ScreenInstruction::VerticalSplit(
+                pid,
+                initial_pane_title,
+                hold_for_command,
+                client_id,
+            ) => {
+                if screen
+                    .refuse_pane_over_limits(pid, &ClientTabIndexOrPaneId::ClientId(client_id))?
+                {
+                    continue;
+                }
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.vertical_split(pid, initial_pane_title, client_id),
+                    ?
+                );
+                if let Some(hold_for_command) = hold_for_command {
+                    let is_first_run = true;
+                    active_tab_and_connected_client_id!(
+                        screen,
+                        client_id,
+                        |tab: &mut Tab, _client_id: ClientId| tab.hold_pane(
+                            pid,
+                            None,
+                            is_first_run,
+                            hold_for_command
+                        )
+                    );
+                }
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+                screen.render(None)?;
+            },
+            ScreenInstruction::WriteCharacter(
+                key_with_modifier,
+                raw_bytes,
+                is_kitty_keyboard_protocol,
+                client_id,
+            ) => {
+                let mut state_changed = false;
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| {
+                        let write_result = tab.write_keys_from_client(&key_with_modifier, raw_bytes, is_kitty_keyboard_protocol, client_id);
+                        if let Ok(true) = write_result {
+                            state_changed = true;
+                        }
+                        write_result
+                    },
+                    ?
+                );
+                if state_changed {
+                    screen.log_and_report_session_state()?;
+                }
+            },
+            ScreenInstruction::Resize(client_id, strategy) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.resize(client_id, strategy),
+                    ?
+                );
+                screen.unblock_input()?;
+                screen.render(None)?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::SwitchFocus(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.focus_next_pane(client_id)
+                );
+                screen.unblock_input()?;
+                screen.render(None)?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::FocusNextPane(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.focus_next_pane(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::FocusPreviousPane(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.focus_previous_pane(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MoveFocusLeft(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_focus_left(client_id),
+                    ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MoveFocusLeftOrPreviousTab(client_id) => {
+                screen.move_focus_left_or_previous_tab(client_id)?;
+                screen.unblock_input()?;
+                screen.render(None)?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MoveFocusDown(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_focus_down(client_id),
+                    ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MoveFocusRight(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_focus_right(client_id),
+                    ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MoveFocusRightOrNextTab(client_id) => {
+                screen.move_focus_right_or_next_tab(client_id)?;
+                screen.unblock_input()?;
+                screen.render(None)?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MoveFocusUp(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_focus_up(client_id),
+                    ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::ClearScreen(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.clear_active_terminal_screen(
+                        client_id,
+                    ),
+                    ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::DumpScreen(file, client_id, full, ansi) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.dump_active_terminal_screen(
+                        Some(file.to_string()),
+                        client_id,
+                        full,
+                        ansi
+                    ),
+                    ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::DumpLayout(default_shell, client_id) => {
+                let err_context = || format!("Failed to dump layout");
+                let session_layout_metadata = screen.get_layout_metadata(default_shell);
+                screen
+                    .bus
+                    .senders
+                    .send_to_plugin(PluginInstruction::DumpLayout(
+                        session_layout_metadata,
+                        client_id,
+                    ))
+                    .with_context(err_context)?;
+            },
+            ScreenInstruction::ListClientsMetadata(default_shell, json, client_id) => {
+                let err_context = || format!("Failed to dump layout");
+                let session_layout_metadata = screen.get_layout_metadata(default_shell);
+                screen
+                    .bus
+                    .senders
+                    .send_to_plugin(PluginInstruction::ListClientsMetadata(
+                        session_layout_metadata,
+                        json,
+                        client_id,
+                    ))
+                    .with_context(err_context)?;
+            },
+            ScreenInstruction::DumpLayoutToPlugin(plugin_id) => {
+                let err_context = || format!("Failed to dump layout");
+                let session_layout_metadata =
+                    screen.get_layout_metadata(Some(screen.default_shell.clone()));
+                screen
+                    .bus
+                    .senders
+                    .send_to_pty(PtyInstruction::DumpLayoutToPlugin(
+                        session_layout_metadata,
+                        plugin_id,
+                    ))
+                    .with_context(err_context)
+                    .non_fatal();
+            },
+            ScreenInstruction::ListClientsToPlugin(plugin_id, client_id) => {
+                let err_context = || format!("Failed to dump layout");
+                let session_layout_metadata =
+                    screen.get_layout_metadata(Some(screen.default_shell.clone()));
+                screen
+                    .bus
+                    .senders
+                    .send_to_pty(PtyInstruction::ListClientsToPlugin(
+                        session_layout_metadata,
+                        plugin_id,
+                        client_id,
+                    ))
+                    .with_context(err_context)
+                    .non_fatal();
+            },
+            ScreenInstruction::EditScrollback(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.edit_scrollback(client_id),
+                    ?
+                );
+                screen.render(None)?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::ScrollUp(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.scroll_active_terminal_up(client_id)
+                );
+                screen.unblock_input()?;
+                screen.render(None)?;
+            },
+            ScreenInstruction::MovePane(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MovePaneBackwards(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_backwards(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MovePaneDown(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_down(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MovePaneUp(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_up(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MovePaneRight(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_right(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MovePaneLeft(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_left(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::ScrollUpAt(point, client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .handle_scrollwheel_up(&point, 3, client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ScrollDown(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.scroll_active_terminal_down(client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ScrollDownAt(point, client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .handle_scrollwheel_down(&point, 3, client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ScrollToBottom(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_to_bottom(client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ScrollToPreviousPrompt(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_to_prompt(true, client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ScrollToNextPrompt(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_to_prompt(false, client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::SelectLastCommandOutput(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .select_last_command_output_in_active_pane(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ScrollToTop(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_to_top(client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
             },
-            &config.keybinds,
-            config_options.default_mode,
-        ),
-        draw_pane_frames,
-        auto_layout,
-        session_is_mirrored,
-        copy_options,
-        debug,
-        default_layout,
-        default_layout_name,
-        default_shell,
-        session_serialization,
-        serialize_pane_viewport,
-        scrollback_lines_to_serialize,
-        styled_underlines,
-        arrow_fonts,
-        layout_dir,
-        explicitly_disable_kitty_keyboard_protocol,
-        stacked_resize,
-        default_editor,
-    );
-
-    let mut pending_tab_ids: HashSet<usize> = HashSet::new();
-    let mut pending_tab_switches: HashSet<(usize, ClientId)> = HashSet::new(); // usize is the
-                                                                               // tab_index
-    let mut pending_events_waiting_for_tab: Vec<ScreenInstruction> = vec![];
-    let mut pending_events_waiting_for_client: Vec<ScreenInstruction> = vec![];
-    let mut plugin_loading_message_cache = HashMap::new();
-    loop {
-        let (event, mut err_ctx) = screen
-            .bus
-            .recv()
-            .context("failed to receive event on channel")?;
-        err_ctx.add_call(ContextType::Screen((&event).into()));
-        // here we start caching resizes, so that we'll send them in bulk at the end of each event
-        // when this cache is Dropped, for more information, see the comments in PtyWriter
-        let _resize_cache = ResizeCache::new(thread_senders.clone());
-
-        match event {
-            ScreenInstruction::PtyBytes(pid, vte_bytes) => {
+            ScreenInstruction::PageScrollUp(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_up_page(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::PageScrollDown(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_down_page(client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::HalfPageScrollUp(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_up_half_page(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::HalfPageScrollDown(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_down_half_page(client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ClearScroll(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .clear_active_terminal_scroll(client_id), ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::CloseFocusedPane(client_id) => {
+                if screen.find_running_processes_before_close(false, client_id) {
+                    screen.unblock_input()?;
+                    continue;
+                }
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.close_focused_pane(client_id), ?
+                );
+                screen.close_orphaned_linked_views();
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::CloseConfirmed(close_target) => {
+                screen.close_confirmed(close_target)?;
+                screen.render(None)?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::AskToConfirmClose(close_target, client_id, running_processes) => {
+                screen.close_or_ask_to_confirm(close_target, client_id, running_processes)?;
+                screen.render(None)?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::SetSelectable(pid, selectable) => {
                 let all_tabs = screen.get_tabs_mut();
+                let mut found_plugin = false;
                 for tab in all_tabs.values_mut() {
-                    if tab.has_terminal_pid(pid) {
-                        tab.handle_pty_bytes(pid, vte_bytes)
-                            .context("failed to process pty bytes")?;
+                    if tab.has_pane_with_pid(&pid) {
+                        tab.set_pane_selectable(pid, selectable);
+                        found_plugin = true;
                         break;
                     }
                 }
-            },
-            ScreenInstruction::PluginBytes(mut plugin_render_assets) => {
-                for plugin_render_asset in plugin_render_assets.iter_mut() {
-                    let plugin_id = plugin_render_asset.plugin_id;
-                    let client_id = plugin_render_asset.client_id;
-                    let vte_bytes = plugin_render_asset.bytes.drain(..).collect();
-
-                    let all_tabs = screen.get_tabs_mut();
-                    for tab in all_tabs.values_mut() {
-                        if tab.has_plugin(plugin_id) {
-                            tab.handle_plugin_bytes(plugin_id, client_id, vte_bytes)
-                                .context("failed to process plugin bytes")?;
-                            break;
-                        }
-                    }
+                if !found_plugin {
+                    pending_events_waiting_for_tab
+                        .push(ScreenInstruction::SetSelectable(pid, selectable));
                 }
-                screen.render(Some(plugin_render_assets))?;
-            },
-            ScreenInstruction::Render => {
                 screen.render(None)?;
-            },
-            ScreenInstruction::NewPane(
-                pid,
-                initial_pane_title,
-                should_float,
-                hold_for_command,
-                invoked_with,
-                floating_pane_coordinates,
-                start_suppressed,
-                client_or_tab_index,
-            ) => {
-                match client_or_tab_index {
-                    ClientTabIndexOrPaneId::ClientId(client_id) => {
-                        active_tab_and_connected_client_id!(screen, client_id, |tab: &mut Tab, client_id: ClientId| {
-                            tab.new_pane(pid,
-                               initial_pane_title,
-                               should_float,
-                               invoked_with,
-                               floating_pane_coordinates,
-                               start_suppressed,
-                               Some(client_id)
-                           )
-                        }, ?);
-                        if let Some(hold_for_command) = hold_for_command {
-                            let is_first_run = true;
-                            active_tab_and_connected_client_id!(
-                                screen,
-                                client_id,
-                                |tab: &mut Tab, _client_id: ClientId| tab.hold_pane(
-                                    pid,
-                                    None,
-                                    is_first_run,
-                                    hold_for_command
-                                )
-                            )
-                        }
-                    },
-                    ClientTabIndexOrPaneId::TabIndex(tab_index) => {
-                        if let Some(active_tab) = screen.tabs.get_mut(&tab_index) {
-                            active_tab.new_pane(
-                                pid,
-                                initial_pane_title,
-                                should_float,
-                                invoked_with,
-                                floating_pane_coordinates,
-                                start_suppressed,
-                                None,
-                            )?;
-                            if let Some(hold_for_command) = hold_for_command {
-                                let is_first_run = true;
-                                active_tab.hold_pane(pid, None, is_first_run, hold_for_command);
-                            }
-                        } else {
-                            log::error!("Tab index not found: {:?}", tab_index);
-                        }
-                    },
-                    ClientTabIndexOrPaneId::PaneId(pane_id) => {
-                        let mut found = false;
-                        let all_tabs = screen.get_tabs_mut();
-                        for tab in all_tabs.values_mut() {
-                            if tab.has_pane_with_pid(&pane_id) {
-                                tab.new_pane(
-                                    pid,
-                                    initial_pane_title,
-                                    should_float,
-                                    invoked_with,
-                                    floating_pane_coordinates,
-                                    start_suppressed,
-                                    None,
-                                )?;
-                                if let Some(hold_for_command) = hold_for_command {
-                                    let is_first_run = true;
-                                    tab.hold_pane(pid, None, is_first_run, hold_for_command);
-                                }
-                                found = true;
-                                break;
-                            }
-                        }
-                        if !found {
-                            log::error!(
-                                "Failed to find tab containing pane with id: {:?}",
-                                pane_id
-                            );
-                        }
-                    },
-                };
-                screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
-
-                screen.render(None)?;
             },
-            ScreenInstruction::OpenInPlaceEditor(pid, client_tab_index_or_pane_id) => {
-                match client_tab_index_or_pane_id {
-                    ClientTabIndexOrPaneId::ClientId(client_id) => {
+            ScreenInstruction::ClosePane(id, client_id) => {
+                match client_id {
+                    Some(client_id) => {
                         active_tab!(screen, client_id, |tab: &mut Tab| tab
-                            .replace_active_pane_with_editor_pane(pid, client_id), ?);
-                        screen.unblock_input()?;
-                        screen.log_and_report_session_state()?;
-                    },
-                    ClientTabIndexOrPaneId::TabIndex(_tab_index) => {
-                        log::error!("Cannot OpenInPlaceEditor with a TabIndex");
-                    },
-                    ClientTabIndexOrPaneId::PaneId(pane_id_to_replace) => {
-                        let mut found = false;
-                        let all_tabs = screen.get_tabs_mut();
-                        for tab in all_tabs.values_mut() {
-                            if tab.has_pane_with_pid(&pane_id_to_replace) {
-                                tab.replace_pane_with_editor_pane(pid, pane_id_to_replace)
-                                    .non_fatal();
-                                found = true;
-                                break;
+                            .close_pane(id, false,));
+                    },
+                    // its process was stopped to run another command in its place
+                    None if screen.replace_stopped_command(id) => {
+                        screen.render(None)?;
+                    },
+                    None => {
+                        // along with its linked views
+                        for tab in screen.tabs.values_mut() {
+                            if tab.get_all_pane_ids().contains(&id) {
+                                tab.close_pane(id, false);
                             }
                         }
-                        if !found {
-                            log::error!(
-                                "Could not find pane with id {:?} to replace",
-                                pane_id_to_replace
-                            );
-                        }
                     },
                 }
 
-                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::TogglePaneEmbedOrFloating(client_id) => {
-                active_tab_and_connected_client_id!(screen, client_id, |tab: &mut Tab, client_id: ClientId| tab
-                    .toggle_pane_embed_or_floating(client_id), ?);
+            ScreenInstruction::HoldPane(id, exit_status, run_command) => {
+                screen.hold_exited_command_pane(id, exit_status, run_command)?;
                 screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
-
-                screen.render(None)?;
             },
-            ScreenInstruction::ToggleFloatingPanes(client_id, default_shell) => {
-                active_tab_and_connected_client_id!(screen, client_id, |tab: &mut Tab, client_id: ClientId| tab
-                    .toggle_floating_panes(Some(client_id), default_shell), ?);
+            ScreenInstruction::UpdatePaneName(c, client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.update_active_pane_name(c, client_id), ?
+                );
+                screen.render(None)?;
                 screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
-
+            },
+            ScreenInstruction::UndoRenamePane(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.undo_active_rename_pane(client_id), ?
+                );
                 screen.render(None)?;
+                screen.unblock_input()?;
             },
-            ScreenInstruction::HorizontalSplit(
-                pid,
-                initial_pane_title,
-                hold_for_command,
-                client_id,
-            ) => {
+            ScreenInstruction::ToggleActiveTerminalFullscreen(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.horizontal_split(pid, initial_pane_title, client_id),
-                    ?
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .toggle_active_pane_fullscreen(client_id)
                 );
-                if let Some(hold_for_command) = hold_for_command {
-                    let is_first_run = true;
-                    active_tab_and_connected_client_id!(
-                        screen,
-                        client_id,
-                        |tab: &mut Tab, _client_id: ClientId| tab.hold_pane(
-                            pid,
-                            None,
-                            is_first_run,
-                            hold_for_command
-                        )
-                    );
+                screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::TogglePaneFrames => {
+                screen.draw_pane_frames = !screen.draw_pane_frames;
+                for tab in screen.tabs.values_mut() {
+                    tab.set_pane_frames(screen.draw_pane_frames);
                 }
+                screen.render(None)?;
                 screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::SwitchTabNext(client_id) => {
+                screen.switch_tab_next(None, true, client_id)?;
+                screen.unblock_input()?;
                 screen.render(None)?;
             },
-            ScreenInstruction::VerticalSplit(
-                pid,
-                initial_pane_title,
-                hold_for_command,
+            ScreenInstruction::SwitchTabPrev(client_id) => {
+                screen.switch_tab_prev(None, true, client_id)?;
+                screen.unblock_input()?;
+                screen.render(None)?;
+            },
+            ScreenInstruction::CloseTab(client_id) => {
+                if screen.find_running_processes_before_close(true, client_id) {
+                    screen.unblock_input()?;
+                    continue;
+                }
+                screen.close_tab(client_id)?;
+                screen.unblock_input()?;
+                screen.render(None)?;
+            },
+            ScreenInstruction::NewTab(
+                cwd,
+                default_shell,
+                layout,
+                floating_panes_layout,
+                tab_name,
+                swap_layouts,
+                should_change_focus_to_new_tab,
                 client_id,
             ) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.vertical_split(pid, initial_pane_title, client_id),
-                    ?
-                );
-                if let Some(hold_for_command) = hold_for_command {
-                    let is_first_run = true;
-                    active_tab_and_connected_client_id!(
-                        screen,
+                let refusal = screen.refuse_tab_over_limits().or_else(|| {
+                    screen.refuse_tab_layout_over_limits(layout.as_ref(), &floating_panes_layout)
+                });
+                if let Some(refusal) = refusal {
+                    screen.report_over_limits(refusal, Some(client_id))?;
+                    screen.unblock_input()?;
+                    continue;
+                }
+                let tab_index = screen.get_new_tab_index();
+                pending_tab_ids.insert(tab_index);
+                let client_id_for_new_tab = if should_change_focus_to_new_tab {
+                    Some(client_id)
+                } else {
+                    None
+                };
+                screen.new_tab(
+                    tab_index,
+                    swap_layouts,
+                    tab_name.clone(),
+                    client_id_for_new_tab,
+                )?;
+                screen
+                    .bus
+                    .senders
+                    .send_to_plugin(PluginInstruction::NewTab(
+                        cwd,
+                        default_shell,
+                        layout,
+                        floating_panes_layout,
+                        tab_index,
+                        should_change_focus_to_new_tab,
                         client_id,
-                        |tab: &mut Tab, _client_id: ClientId| tab.hold_pane(
-                            pid,
-                            None,
-                            is_first_run,
-                            hold_for_command
-                        )
-                    );
+                    ))?;
+            },
+            ScreenInstruction::NewTabFromLayout(
+                layout_name,
+                tab_name,
+                cwd,
+                default_shell,
+                client_id,
+            ) => {
+                if let Some(refusal) = screen.refuse_tab_over_limits() {
+                    screen.report_over_limits(refusal, Some(client_id))?;
+                    screen.unblock_input()?;
+                    continue;
+                }
+                screen.new_tabs_from_layout(
+                    layout_name,
+                    tab_name,
+                    cwd,
+                    default_shell,
+                    client_id,
+                )?;
+            },
+            ScreenInstruction::ApplyLayout(
+                layout,
+                floating_panes_layout,
+                new_pane_pids,
+                new_floating_pane_pids,
+                new_plugin_ids,
+                tab_index,
+                should_change_focus_to_new_tab,
+                client_id,
+            ) => {
+                screen.apply_layout(
+                    layout,
+                    floating_panes_layout,
+                    new_pane_pids.clone(),
+                    new_floating_pane_pids,
+                    new_plugin_ids.clone(),
+                    tab_index,
+                    should_change_focus_to_new_tab,
+                    client_id,
+                )?;
+                pending_tab_ids.remove(&tab_index);
+                if pending_tab_ids.is_empty() {
+                    for (tab_index, client_id) in pending_tab_switches.drain() {
+                        screen.go_to_tab(tab_index as usize + 1, client_id)?;
+                    }
+                    if should_change_focus_to_new_tab {
+                        screen.go_to_tab(tab_index as usize + 1, client_id)?;
+                    }
+                } else if should_change_focus_to_new_tab {
+                    let client_id_to_switch = if screen.active_tab_indices.contains_key(&client_id)
+                    {
+                        Some(client_id)
+                    } else {
+                        screen.active_tab_indices.keys().next().copied()
+                    };
+                    if let Some(client_id_to_switch) = client_id_to_switch {
+                        pending_tab_switches.insert((tab_index as usize, client_id_to_switch));
+                    }
+                }
+
+                for plugin_ids in new_plugin_ids.values() {
+                    for plugin_id in plugin_ids {
+                        if let Some(loading_indication) =
+                            plugin_loading_message_cache.remove(plugin_id)
+                        {
+                            screen.update_plugin_loading_stage(*plugin_id, loading_indication);
+                            screen.render(None)?;
+                        }
+                    }
+                }
+
+                for event in pending_events_waiting_for_client.drain(..) {
+                    screen.bus.senders.send_to_screen(event).non_fatal();
+                }
+
+                for event in pending_events_waiting_for_tab.drain(..) {
+                    screen.bus.senders.send_to_screen(event).non_fatal();
+                }
+
+                screen.unblock_input()?;
+                screen.render(None)?;
+                // we do this here in order to recover from a race condition on app start
+                // that sometimes causes Zellij to think the terminal window is a different size
+                // than it actually is - here, we query the client for its terminal size after
+                // we've finished the setup and handle it as we handle a normal resize,
+                // while this can affect other instances of a layout being applied, the query is
+                // very short and cheap and shouldn't cause any trouble
+                if let Some(os_input) = &mut screen.bus.os_input {
+                    for client_id in screen.connected_clients.borrow().iter() {
+                        let _ = os_input
+                            .send_to_client(*client_id, ServerToClientMsg::QueryTerminalSize);
+                    }
+                }
+            },
+            ScreenInstruction::GoToTab(tab_index, client_id) => {
+                let client_id_to_switch = if client_id.is_none() {
+                    None
+                } else if screen
+                    .active_tab_indices
+                    .contains_key(&client_id.expect("This is checked above"))
+                {
+                    client_id
+                } else {
+                    screen.active_tab_indices.keys().next().copied()
+                };
+                match client_id_to_switch {
+                    // we must make sure pending_tab_ids is empty because otherwise we cannot be
+                    // sure this instruction is applied at the right time (eg. we might have a
+                    // pending tab that will become not-pending after this instruction and change
+                    // the client focus, which should have happened before this instruction and not
+                    // after)
+                    Some(client_id) if pending_tab_ids.is_empty() => {
+                        screen.go_to_tab(tab_index as usize, client_id)?;
+                        screen.unblock_input()?;
+                        screen.render(None)?;
+                    },
+                    _ => {
+                        if let Some(client_id) = client_id {
+                            pending_tab_switches.insert((tab_index as usize, client_id));
+                        }
+                    },
                 }
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
-                screen.render(None)?;
             },
-            ScreenInstruction::WriteCharacter(
-                key_with_modifier,
-                raw_bytes,
-                is_kitty_keyboard_protocol,
+            ScreenInstruction::GoToTabName(
+                tab_name,
+                swap_layouts,
+                default_shell,
+                create,
                 client_id,
             ) => {
-                let mut state_changed = false;
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| {
-                        let write_result = match tab.is_sync_panes_active() {
-                            true => tab.write_to_terminals_on_current_tab(&key_with_modifier, raw_bytes, is_kitty_keyboard_protocol, client_id),
-                            false => tab.write_to_active_terminal(&key_with_modifier, raw_bytes, is_kitty_keyboard_protocol, client_id),
-                        };
-                        if let Ok(true) = write_result {
-                            state_changed = true;
+                let client_id = if client_id.is_none() {
+                    None
+                } else if screen
+                    .active_tab_indices
+                    .contains_key(&client_id.expect("This is checked above"))
+                {
+                    client_id
+                } else {
+                    screen.active_tab_indices.keys().next().copied()
+                };
+                if let Some(client_id) = client_id {
+                    if let Ok(tab_exists) = screen.go_to_tab_name(tab_name.clone(), client_id) {
+                        screen.unblock_input()?;
+                        screen.render(None)?;
+                        if create && !tab_exists {
+                            let tab_index = screen.get_new_tab_index();
+                            let should_change_focus_to_new_tab = true;
+                            screen.new_tab(
+                                tab_index,
+                                swap_layouts,
+                                Some(tab_name),
+                                Some(client_id),
+                            )?;
+                            screen
+                                .bus
+                                .senders
+                                .send_to_plugin(PluginInstruction::NewTab(
+                                    None,
+                                    default_shell,
+                                    None,
+                                    vec![],
+                                    tab_index,
+                                    should_change_focus_to_new_tab,
+                                    client_id,
+                                ))?;
                         }
-                        write_result
-                    },
-                    ?
-                );
-                if state_changed {
-                    screen.log_and_report_session_state()?;
+                    }
                 }
             },
-            ScreenInstruction::Resize(client_id, strategy) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.resize(client_id, strategy),
-                    ?
-                );
-                screen.unblock_input()?;
-                screen.render(None)?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::SwitchFocus(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.focus_next_pane(client_id)
-                );
-                screen.unblock_input()?;
-                screen.render(None)?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::FocusNextPane(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.focus_next_pane(client_id)
-                );
-                screen.render(None)?;
-                screen.unblock_input()?;
-            },
-            ScreenInstruction::FocusPreviousPane(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.focus_previous_pane(client_id)
-                );
-                screen.render(None)?;
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::MoveFocusLeft(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_focus_left(client_id),
-                    ?
-                );
-                screen.render(None)?;
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::MoveFocusLeftOrPreviousTab(client_id) => {
-                screen.move_focus_left_or_previous_tab(client_id)?;
-                screen.unblock_input()?;
-                screen.render(None)?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::MoveFocusDown(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_focus_down(client_id),
-                    ?
-                );
-                screen.render(None)?;
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::MoveFocusRight(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_focus_right(client_id),
-                    ?
-                );
-                screen.render(None)?;
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::MoveFocusRightOrNextTab(client_id) => {
-                screen.move_focus_right_or_next_tab(client_id)?;
-                screen.unblock_input()?;
-                screen.render(None)?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::MoveFocusUp(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_focus_up(client_id),
-                    ?
-                );
-                screen.render(None)?;
+            ScreenInstruction::UpdateTabName(c, client_id) => {
+                screen.update_active_tab_name(c, client_id)?;
                 screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::ClearScreen(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.clear_active_terminal_screen(
-                        client_id,
-                    ),
-                    ?
-                );
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::DumpScreen(file, client_id, full) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.dump_active_terminal_screen(
-                        Some(file.to_string()),
-                        client_id,
-                        full
-                    ),
-                    ?
-                );
-                screen.render(None)?;
+            ScreenInstruction::UndoRenameTab(client_id) => {
+                screen.undo_active_rename_tab(client_id)?;
                 screen.unblock_input()?;
-            },
-            ScreenInstruction::DumpLayout(default_shell, client_id) => {
-                let err_context = || format!("Failed to dump layout");
-                let session_layout_metadata = screen.get_layout_metadata(default_shell);
-                screen
-                    .bus
-                    .senders
-                    .send_to_plugin(PluginInstruction::DumpLayout(
-                        session_layout_metadata,
-                        client_id,
-                    ))
-                    .with_context(err_context)?;
-            },
-            ScreenInstruction::ListClientsMetadata(default_shell, client_id) => {
-                let err_context = || format!("Failed to dump layout");
-                let session_layout_metadata = screen.get_layout_metadata(default_shell);
-                screen
-                    .bus
-                    .senders
-                    .send_to_plugin(PluginInstruction::ListClientsMetadata(
-                        session_layout_metadata,
-                        client_id,
-                    ))
-                    .with_context(err_context)?;
-            },
-            ScreenInstruction::DumpLayoutToPlugin(plugin_id) => {
-                let err_context = || format!("Failed to dump layout");
-                let session_layout_metadata =
-                    screen.get_layout_metadata(Some(screen.default_shell.clone()));
-                screen
-                    .bus
-                    .senders
-                    .send_to_pty(PtyInstruction::DumpLayoutToPlugin(
-                        session_layout_metadata,
-                        plugin_id,
-                    ))
-                    .with_context(err_context)
-                    .non_fatal();
-            },
-            ScreenInstruction::ListClientsToPlugin(plugin_id, client_id) => {
-                let err_context = || format!("Failed to dump layout");
-                let session_layout_metadata =
-                    screen.get_layout_metadata(Some(screen.default_shell.clone()));
-                screen
-                    .bus
-                    .senders
-                    .send_to_pty(PtyInstruction::ListClientsToPlugin(
-                        session_layout_metadata,
-                        plugin_id,
-                        client_id,
-                    ))
-                    .with_context(err_context)
-                    .non_fatal();
-            },
-            ScreenInstruction::EditScrollback(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.edit_scrollback(client_id),
-                    ?
-                );
                 screen.render(None)?;
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::ScrollUp(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.scroll_active_terminal_up(client_id)
-                );
+            ScreenInstruction::MoveTabLeft(client_id) => {
+                if pending_tab_ids.is_empty() {
+                    screen.move_active_tab_to_left(client_id)?;
+                    screen.render(None)?;
+                } else {
+                    pending_events_waiting_for_tab.push(ScreenInstruction::MoveTabLeft(client_id));
+                }
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::MoveTabRight(client_id) => {
+                if pending_tab_ids.is_empty() {
+                    screen.move_active_tab_to_right(client_id)?;
+                    screen.render(None)?;
+                } else {
+                    pending_events_waiting_for_tab.push(ScreenInstruction::MoveTabRight(client_id));
+                }
                 screen.unblock_input()?;
+            },
+            ScreenInstruction::TerminalResize(new_size) => {
+                screen.resize_to_screen(new_size)?;
+                screen.log_and_report_session_state()?; // update tabs so that the ui indication will be send to the plugins
                 screen.render(None)?;
             },
-            ScreenInstruction::MovePane(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane(client_id)
-                );
+            ScreenInstruction::TerminalPixelDimensions(pixel_dimensions) => {
+                screen.update_pixel_dimensions(pixel_dimensions);
+            },
+            ScreenInstruction::TerminalBackgroundColor(background_color_instruction) => {
+                screen.update_terminal_background_color(background_color_instruction);
+            },
+            ScreenInstruction::TerminalForegroundColor(background_color_instruction) => {
+                screen.update_terminal_foreground_color(background_color_instruction);
+            },
+            ScreenInstruction::TerminalColorRegisters(color_registers) => {
+                screen.update_terminal_color_registers(color_registers);
+            },
+            ScreenInstruction::ChangeMode(mode_info, client_id) => {
+                screen.change_mode(mode_info, client_id)?;
+                screen.report_copy_mode_clients()?;
                 screen.render(None)?;
                 screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::MovePaneBackwards(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_backwards(client_id)
-                );
+            ScreenInstruction::ChangeModeForAllClients(mode_info) => {
+                screen.change_mode_for_all_clients(mode_info)?;
+                screen.report_copy_mode_clients()?;
                 screen.render(None)?;
                 screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::MovePaneDown(client_id) => {
+            ScreenInstruction::ToggleActiveSyncTab(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_down(client_id)
+                    |tab: &mut Tab, _client_id: ClientId| tab.toggle_sync_panes_is_active()
                 );
+                screen.log_and_report_session_state()?;
                 screen.render(None)?;
                 screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::MovePaneUp(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_up(client_id)
-                );
+            ScreenInstruction::MouseEvent(event, client_id) => {
+                let mouse_effect = screen
+                    .get_active_tab_mut(client_id)
+                    .and_then(|tab| tab.handle_mouse_event(&event, client_id))?;
+                if mouse_effect.state_changed {
+                    screen.log_and_report_session_state()?;
+                }
+                if !mouse_effect.leave_clipboard_message {
+                    let _ = screen
+                        .bus
+                        .senders
+                        .send_to_plugin(PluginInstruction::Update(vec![(
+                            None,
+                            Some(client_id),
+                            Event::InputReceived,
+                        )]));
+                }
                 screen.render(None)?;
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::MovePaneRight(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_right(client_id)
-                );
+            ScreenInstruction::Copy(client_id) => {
+                active_tab!(screen, client_id, |tab: &mut Tab| tab
+                    .copy_selection(client_id), ?);
                 screen.render(None)?;
+            },
+            ScreenInstruction::Exit => {
+                break;
+            },
+            ScreenInstruction::ToggleTab(client_id) => {
+                screen.toggle_tab(client_id)?;
                 screen.unblock_input()?;
+                screen.render(None)?;
+            },
+            ScreenInstruction::AddClient(client_id, tab_position_to_focus, pane_id_to_focus) => {
+                screen.add_client(client_id)?;
+                let pane_id = pane_id_to_focus.map(|(pane_id, is_plugin)| {
+                    if is_plugin {
+                        PaneId::Plugin(pane_id)
+                    } else {
+                        PaneId::Terminal(pane_id)
+                    }
+                });
+                if let Some(pane_id) = pane_id {
+                    screen.focus_pane_with_id(pane_id, true, client_id)?;
+                } else if let Some(tab_position_to_focus) = tab_position_to_focus {
+                    screen.go_to_tab(tab_position_to_focus, client_id)?;
+                }
+                for event in pending_events_waiting_for_client.drain(..) {
+                    screen.bus.senders.send_to_screen(event).non_fatal();
+                }
                 screen.log_and_report_session_state()?;
+                screen.render(None)?;
             },
-            ScreenInstruction::MovePaneLeft(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.move_active_pane_left(client_id)
-                );
+            ScreenInstruction::RemoveClient(client_id) => {
+                screen.remove_client(client_id)?;
+                screen.log_and_report_session_state()?;
                 screen.render(None)?;
+            },
+            ScreenInstruction::AddOverlay(overlay, _client_id) => {
+                screen.get_active_overlays_mut().pop();
+                screen.get_active_overlays_mut().push(overlay);
                 screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::ScrollUpAt(point, client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .handle_scrollwheel_up(&point, 3, client_id), ?
-                );
+            ScreenInstruction::RemoveOverlay(_client_id) => {
+                screen.get_active_overlays_mut().pop();
                 screen.render(None)?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::ScrollDown(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.scroll_active_terminal_down(client_id), ?
-                );
-                screen.render(None)?;
+            ScreenInstruction::ConfirmPrompt(_client_id) => {
+                let overlay = screen.get_active_overlays_mut().pop();
+                let instruction = overlay.and_then(|o| o.prompt_confirm());
+                if let Some(instruction) = instruction {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(*instruction)
+                        .context("failed to confirm prompt")?;
+                }
                 screen.unblock_input()?;
             },
-            ScreenInstruction::ScrollDownAt(point, client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .handle_scrollwheel_down(&point, 3, client_id), ?
-                );
+            ScreenInstruction::DenyPrompt(_client_id) => {
+                screen.get_active_overlays_mut().pop();
                 screen.render(None)?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::ScrollToBottom(client_id) => {
+            ScreenInstruction::UpdateSearch(c, client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .scroll_active_terminal_to_bottom(client_id), ?
+                    |tab: &mut Tab, client_id: ClientId| tab.update_search_term(c, client_id), ?
                 );
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::ScrollToTop(client_id) => {
+            ScreenInstruction::CopyModeInput(bytes, client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .scroll_active_terminal_to_top(client_id), ?
+                    |tab: &mut Tab, client_id: ClientId| tab.copy_mode_input(bytes, client_id), ?
                 );
+                screen.report_copy_mode_clients()?;
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::PageScrollUp(client_id) => {
+            ScreenInstruction::SearchDown(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .scroll_active_terminal_up_page(client_id)
+                    |tab: &mut Tab, client_id: ClientId| tab.search_down(client_id)
                 );
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::PageScrollDown(client_id) => {
+            ScreenInstruction::SearchUp(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .scroll_active_terminal_down_page(client_id), ?
+                    |tab: &mut Tab, client_id: ClientId| tab.search_up(client_id)
                 );
                 screen.render(None)?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::HalfPageScrollUp(client_id) => {
+            ScreenInstruction::SearchToggleCaseSensitivity(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
                     |tab: &mut Tab, client_id: ClientId| tab
-                        .scroll_active_terminal_up_half_page(client_id)
+                        .toggle_search_case_sensitivity(client_id)
                 );
                 screen.render(None)?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::HalfPageScrollDown(client_id) => {
+            ScreenInstruction::SearchToggleWrap(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .scroll_active_terminal_down_half_page(client_id), ?
+                    |tab: &mut Tab, client_id: ClientId| tab.toggle_search_wrap(client_id)
                 );
                 screen.render(None)?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::ClearScroll(client_id) => {
+            ScreenInstruction::SearchToggleWholeWord(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .clear_active_terminal_scroll(client_id), ?
+                    |tab: &mut Tab, client_id: ClientId| tab.toggle_search_whole_words(client_id)
                 );
                 screen.render(None)?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::CloseFocusedPane(client_id) => {
+            ScreenInstruction::SearchToggleRegex(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.close_focused_pane(client_id), ?
+                    |tab: &mut Tab, client_id: ClientId| tab.toggle_search_regex(client_id)
                 );
                 screen.render(None)?;
                 screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::SetSelectable(pid, selectable) => {
+            ScreenInstruction::AddRedPaneFrameColorOverride(pane_ids, error_text) => {
                 let all_tabs = screen.get_tabs_mut();
-                let mut found_plugin = false;
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pid) {
-                        tab.set_pane_selectable(pid, selectable);
-                        found_plugin = true;
-                        break;
-                    }
-                }
-                if !found_plugin {
-                    pending_events_waiting_for_tab
-                        .push(ScreenInstruction::SetSelectable(pid, selectable));
-                }
-                screen.render(None)?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::ClosePane(id, client_id) => {
-                match client_id {
-                    Some(client_id) => {
-                        active_tab!(screen, client_id, |tab: &mut Tab| tab
-                            .close_pane(id, false,));
-                    },
-                    None => {
-                        for tab in screen.tabs.values_mut() {
-                            if tab.get_all_pane_ids().contains(&id) {
-                                tab.close_pane(id, false);
-                                break;
-                            }
+                for pane_id in pane_ids {
+                    for tab in all_tabs.values_mut() {
+                        if tab.has_pane_with_pid(&pane_id) {
+                            tab.add_red_pane_frame_color_override(pane_id, error_text.clone());
+                            break;
                         }
-                    },
-                }
-
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::HoldPane(id, exit_status, run_command) => {
-                let is_first_run = false;
-                for tab in screen.tabs.values_mut() {
-                    if tab.get_all_pane_ids().contains(&id) {
-                        tab.hold_pane(id, exit_status, is_first_run, run_command);
-                        break;
                     }
                 }
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
+                screen.render(None)?;
             },
-            ScreenInstruction::UpdatePaneName(c, client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.update_active_pane_name(c, client_id), ?
-                );
+            ScreenInstruction::ClearPaneFrameColorOverride(pane_ids) => {
+                let all_tabs = screen.get_tabs_mut();
+                for pane_id in pane_ids {
+                    for tab in all_tabs.values_mut() {
+                        if tab.has_pane_with_pid(&pane_id) {
+                            tab.clear_pane_frame_color_override(pane_id);
+                            break;
+                        }
+                    }
+                }
                 screen.render(None)?;
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::UndoRenamePane(client_id) => {
+            ScreenInstruction::PreviousSwapLayout(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.undo_active_rename_pane(client_id), ?
+                    |tab: &mut Tab, _client_id: ClientId| tab.previous_swap_layout(),
+                    ?
                 );
                 screen.render(None)?;
+                screen.log_and_report_session_state()?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::ToggleActiveTerminalFullscreen(client_id) => {
+            ScreenInstruction::NextSwapLayout(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .toggle_active_pane_fullscreen(client_id)
+                    |tab: &mut Tab, _client_id: ClientId| tab.next_swap_layout(),
+                    ?
                 );
                 screen.render(None)?;
-                screen.unblock_input()?;
-                screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::TogglePaneFrames => {
-                screen.draw_pane_frames = !screen.draw_pane_frames;
-                for tab in screen.tabs.values_mut() {
-                    tab.set_pane_frames(screen.draw_pane_frames);
-                }
-                screen.render(None)?;
-                screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
-            },
-            ScreenInstruction::SwitchTabNext(client_id) => {
-                screen.switch_tab_next(None, true, client_id)?;
-                screen.unblock_input()?;
-                screen.render(None)?;
-            },
-            ScreenInstruction::SwitchTabPrev(client_id) => {
-                screen.switch_tab_prev(None, true, client_id)?;
                 screen.unblock_input()?;
-                screen.render(None)?;
             },
-            ScreenInstruction::CloseTab(client_id) => {
-                screen.close_tab(client_id)?;
-                screen.unblock_input()?;
-                screen.render(None)?;
+            ScreenInstruction::QueryTabNames(client_id) => {
+                let tab_names = screen
+                    .get_tabs_mut()
+                    .values()
+                    .map(|tab| tab.name.clone())
+                    .collect::<Vec<String>>();
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::Log(tab_names, client_id))?;
             },
-            ScreenInstruction::NewTab(
+            ScreenInstruction::NewTiledPluginPane(
+                run_plugin,
+                pane_title,
+                skip_cache,
                 cwd,
-                default_shell,
-                layout,
-                floating_panes_layout,
-                tab_name,
-                swap_layouts,
-                should_change_focus_to_new_tab,
                 client_id,
             ) => {
-                let tab_index = screen.get_new_tab_index();
-                pending_tab_ids.insert(tab_index);
-                let client_id_for_new_tab = if should_change_focus_to_new_tab {
-                    Some(client_id)
-                } else {
-                    None
-                };
-                screen.new_tab(
-                    tab_index,
-                    swap_layouts,
-                    tab_name.clone(),
-                    client_id_for_new_tab,
-                )?;
+                let tab_index = screen.active_tab_indices.values().next().unwrap_or(&1);
+                let size = Size::default();
+                let should_float = Some(false);
+                let should_be_opened_in_place = false;
                 screen
                     .bus
                     .senders
-                    .send_to_plugin(PluginInstruction::NewTab(
-                        cwd,
-                        default_shell,
-                        layout,
-                        floating_panes_layout,
-                        tab_index,
-                        should_change_focus_to_new_tab,
+                    .send_to_pty(PtyInstruction::FillPluginCwd(
+                        should_float,
+                        should_be_opened_in_place,
+                        pane_title,
+                        run_plugin,
+                        *tab_index,
+                        None,
                         client_id,
+                        size,
+                        skip_cache,
+                        cwd,
+                        None,
                     ))?;
             },
-            ScreenInstruction::ApplyLayout(
-                layout,
-                floating_panes_layout,
-                new_pane_pids,
-                new_floating_pane_pids,
-                new_plugin_ids,
+            ScreenInstruction::NewFloatingPluginPane(
+                run_plugin,
+                pane_title,
+                skip_cache,
+                cwd,
+                floating_pane_coordinates,
+                client_id,
+            ) => match screen.active_tab_indices.values().next() {
+                Some(tab_index) => {
+                    let size = Size::default();
+                    let should_float = Some(true);
+                    let should_be_opened_in_place = false;
+                    screen
+                        .bus
+                        .senders
+                        .send_to_pty(PtyInstruction::FillPluginCwd(
+                            should_float,
+                            should_be_opened_in_place,
+                            pane_title,
+                            run_plugin,
+                            *tab_index,
+                            None,
+                            client_id,
+                            size,
+                            skip_cache,
+                            cwd,
+                            floating_pane_coordinates,
+                        ))?;
+                },
+                None => {
+                    log::error!(
+                        "Could not find an active tab - is there at least 1 connected user?"
+                    );
+                },
+            },
+            ScreenInstruction::NewInPlacePluginPane(
+                run_plugin,
+                pane_title,
+                pane_id_to_replace,
+                skip_cache,
+                client_id,
+            ) => match screen.active_tab_indices.values().next() {
+                Some(tab_index) => {
+                    let size = Size::default();
+                    let should_float = None;
+                    let should_be_in_place = true;
+                    screen
+                        .bus
+                        .senders
+                        .send_to_pty(PtyInstruction::FillPluginCwd(
+                            should_float,
+                            should_be_in_place,
+                            pane_title,
+                            run_plugin,
+                            *tab_index,
+                            Some(pane_id_to_replace),
+                            client_id,
+                            size,
+                            skip_cache,
+                            None,
+                            None,
+                        ))?;
+                },
+                None => {
+                    log::error!(
+                        "Could not find an active tab - is there at least 1 connected user?"
+                    );
+                },
+            },
+            ScreenInstruction::StartOrReloadPluginPane(run_plugin, pane_title) => {
+                let tab_index = screen.active_tab_indices.values().next().unwrap_or(&1);
+                let size = Size::default();
+                let should_float = Some(false);
+
+                screen
+                    .bus
+                    .senders
+                    .send_to_plugin(PluginInstruction::Reload(
+                        should_float,
+                        pane_title,
+                        run_plugin,
+                        *tab_index,
+                        size,
+                    ))?;
+            },
+            ScreenInstruction::AddPlugin(
+                should_float,
+                should_be_in_place,
+                run_plugin_or_alias,
+                pane_title,
                 tab_index,
-                should_change_focus_to_new_tab,
+                plugin_id,
+                pane_id_to_replace,
+                cwd,
+                start_suppressed,
                 client_id,
             ) => {
-                screen.apply_layout(
-                    layout,
-                    floating_panes_layout,
-                    new_pane_pids.clone(),
-                    new_floating_pane_pids,
-                    new_plugin_ids.clone(),
-                    tab_index,
-                    should_change_focus_to_new_tab,
-                    client_id,
-                )?;
-                pending_tab_ids.remove(&tab_index);
-                if pending_tab_ids.is_empty() {
-                    for (tab_index, client_id) in pending_tab_switches.drain() {
-                        screen.go_to_tab(tab_index as usize + 1, client_id)?;
-                    }
-                    if should_change_focus_to_new_tab {
-                        screen.go_to_tab(tab_index as usize + 1, client_id)?;
-                    }
-                } else if should_change_focus_to_new_tab {
-                    let client_id_to_switch = if screen.active_tab_indices.contains_key(&client_id)
-                    {
-                        Some(client_id)
-                    } else {
-                        screen.active_tab_indices.keys().next().copied()
-                    };
-                    if let Some(client_id_to_switch) = client_id_to_switch {
-                        pending_tab_switches.insert((tab_index as usize, client_id_to_switch));
-                    }
+                if screen.active_tab_indices.is_empty() && tab_index.is_none() {
+                    pending_events_waiting_for_client.push(ScreenInstruction::AddPlugin(
+                        should_float,
+                        should_be_in_place,
+                        run_plugin_or_alias,
+                        pane_title,
+                        tab_index,
+                        plugin_id,
+                        pane_id_to_replace,
+                        cwd,
+                        start_suppressed,
+                        client_id,
+                    ));
+                    continue;
                 }
-
-                for plugin_ids in new_plugin_ids.values() {
-                    for plugin_id in plugin_ids {
-                        if let Some(loading_indication) =
-                            plugin_loading_message_cache.remove(plugin_id)
-                        {
-                            screen.update_plugin_loading_stage(*plugin_id, loading_indication);
-                            screen.render(None)?;
-                        }
+                let pane_title = pane_title.unwrap_or_else(|| {
+                    format!(
+                        "({}) - {}",
+                        cwd.map(|cwd| cwd.display().to_string())
+                            .unwrap_or(".".to_owned()),
+                        run_plugin_or_alias.location_string()
+                    )
+                });
+                let run_plugin = Run::Plugin(run_plugin_or_alias);
+
+                // a plugin opened in place of another pane does not add to the panes of its tab
+                let new_pane_target = client_id
+                    .map(ClientTabIndexOrPaneId::ClientId)
+                    .or(tab_index.map(ClientTabIndexOrPaneId::TabIndex))
+                    .filter(|_| !should_be_in_place);
+                if let Some(new_pane_target) = new_pane_target {
+                    if screen
+                        .refuse_pane_over_limits(PaneId::Plugin(plugin_id), &new_pane_target)?
+                    {
+                        continue;
                     }
                 }
 
-                for event in pending_events_waiting_for_client.drain(..) {
-                    screen.bus.senders.send_to_screen(event).non_fatal();
+                let close_replaced_pane = false;
+                if should_be_in_place {
+                    if let Some(pane_id_to_replace) = pane_id_to_replace {
+                        let client_tab_index_or_pane_id =
+                            ClientTabIndexOrPaneId::PaneId(pane_id_to_replace);
+                        screen.replace_pane(
+                            PaneId::Plugin(plugin_id),
+                            None,
+                            Some(run_plugin),
+                            Some(pane_title),
+                            close_replaced_pane,
+                            client_tab_index_or_pane_id,
+                        )?;
+                    } else if let Some(client_id) = client_id {
+                        let client_tab_index_or_pane_id =
+                            ClientTabIndexOrPaneId::ClientId(client_id);
+                        screen.replace_pane(
+                            PaneId::Plugin(plugin_id),
+                            None,
+                            Some(run_plugin),
+                            Some(pane_title),
+                            close_replaced_pane,
+                            client_tab_index_or_pane_id,
+                        )?;
+                    } else {
+                        log::error!("Must have pane id to replace or connected client_id if replacing a pane");
+                    }
+                } else if let Some(client_id) = client_id {
+                    active_tab_and_connected_client_id!(screen, client_id, |active_tab: &mut Tab, _client_id: ClientId| {
+                        active_tab.new_pane(
+                            PaneId::Plugin(plugin_id),
+                            Some(pane_title),
+                            should_float,
+                            Some(run_plugin),
+                            None,
+                            start_suppressed,
+                            Some(client_id),
+                        )
+                    }, ?);
+                } else if let Some(active_tab) =
+                    tab_index.and_then(|tab_index| screen.tabs.get_mut(&tab_index))
+                {
+                    active_tab.new_pane(
+                        PaneId::Plugin(plugin_id),
+                        Some(pane_title),
+                        should_float,
+                        Some(run_plugin),
+                        None,
+                        start_suppressed,
+                        None,
+                    )?;
+                } else {
+                    log::error!("Tab index not found: {:?}", tab_index);
                 }
-
-                for event in pending_events_waiting_for_tab.drain(..) {
-                    screen.bus.senders.send_to_screen(event).non_fatal();
+                if let Some(loading_indication) = plugin_loading_message_cache.remove(&plugin_id) {
+                    screen.update_plugin_loading_stage(plugin_id, loading_indication);
+                    screen.render(None)?;
                 }
-
+                screen.log_and_report_session_state()?;
                 screen.unblock_input()?;
+            },
+            ScreenInstruction::UpdatePluginLoadingStage(pid, loading_indication) => {
+                let found_plugin =
+                    screen.update_plugin_loading_stage(pid, loading_indication.clone());
+                if !found_plugin {
+                    plugin_loading_message_cache.insert(pid, loading_indication);
+                }
                 screen.render(None)?;
-                // we do this here in order to recover from a race condition on app start
-                // that sometimes causes Zellij to think the terminal window is a different size
-                // than it actually is - here, we query the client for its terminal size after
-                // we've finished the setup and handle it as we handle a normal resize,
-                // while this can affect other instances of a layout being applied, the query is
-                // very short and cheap and shouldn't cause any trouble
-                if let Some(os_input) = &mut screen.bus.os_input {
-                    for client_id in screen.connected_clients.borrow().iter() {
-                        let _ = os_input
-                            .send_to_client(*client_id, ServerToClientMsg::QueryTerminalSize);
+            },
+            ScreenInstruction::StartPluginLoadingIndication(pid, loading_indication) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_plugin(pid) {
+                        tab.start_plugin_loading_indication(pid, loading_indication);
+                        break;
                     }
                 }
+                screen.render(None)?;
             },
-            ScreenInstruction::GoToTab(tab_index, client_id) => {
-                let client_id_to_switch = if client_id.is_none() {
-                    None
-                } else if screen
-                    .active_tab_indices
-                    .contains_key(&client_id.expect("This is checked above"))
-                {
-                    client_id
-                } else {
-                    screen.active_tab_indices.keys().next().copied()
-                };
-                match client_id_to_switch {
-                    // we must make sure pending_tab_ids is empty because otherwise we cannot be
-                    // sure this instruction is applied at the right time (eg. we might have a
-                    // pending tab that will become not-pending after this instruction and change
-                    // the client focus, which should have happened before this instruction and not
-                    // after)
-                    Some(client_id) if pending_tab_ids.is_empty() => {
-                        screen.go_to_tab(tab_index as usize, client_id)?;
-                        screen.unblock_input()?;
-                        screen.render(None)?;
-                    },
-                    _ => {
-                        if let Some(client_id) = client_id {
-                            pending_tab_switches.insert((tab_index as usize, client_id));
-                        }
-                    },
+            ScreenInstruction::ProgressPluginLoadingOffset(pid) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_plugin(pid) {
+                        tab.progress_plugin_loading_offset(pid);
+                        break;
+                    }
                 }
+                screen.render(None)?;
             },
-            ScreenInstruction::GoToTabName(
-                tab_name,
-                swap_layouts,
-                default_shell,
-                create,
+            ScreenInstruction::RequestStateUpdateForPlugins => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    tab.update_input_modes()?;
+                }
+                screen.log_and_report_session_state()?;
+                screen.render(None)?;
+            },
+            ScreenInstruction::LaunchOrFocusPlugin(
+                run_plugin,
+                should_float,
+                move_to_focused_tab,
+                should_open_in_place,
+                pane_id_to_replace,
+                skip_cache,
                 client_id,
-            ) => {
-                let client_id = if client_id.is_none() {
-                    None
-                } else if screen
-                    .active_tab_indices
-                    .contains_key(&client_id.expect("This is checked above"))
-                {
-                    client_id
-                } else {
-                    screen.active_tab_indices.keys().next().copied()
-                };
-                if let Some(client_id) = client_id {
-                    if let Ok(tab_exists) = screen.go_to_tab_name(tab_name.clone(), client_id) {
-                        screen.unblock_input()?;
-                        screen.render(None)?;
-                        if create && !tab_exists {
-                            let tab_index = screen.get_new_tab_index();
-                            let should_change_focus_to_new_tab = true;
-                            screen.new_tab(
-                                tab_index,
-                                swap_layouts,
-                                Some(tab_name),
-                                Some(client_id),
-                            )?;
+            ) => match pane_id_to_replace {
+                Some(pane_id_to_replace) if should_open_in_place => {
+                    match screen.active_tab_indices.values().next() {
+                        Some(tab_index) => {
+                            let size = Size::default();
+                            screen
+                                .bus
+                                .senders
+                                .send_to_pty(PtyInstruction::FillPluginCwd(
+                                    Some(should_float),
+                                    should_open_in_place,
+                                    None,
+                                    run_plugin,
+                                    *tab_index,
+                                    Some(pane_id_to_replace),
+                                    client_id,
+                                    size,
+                                    skip_cache,
+                                    None,
+                                    None,
+                                ))?;
+                        },
+                        None => {
+                            log::error!(
+                            "Could not find an active tab - is there at least 1 connected user?"
+                        );
+                        },
+                    }
+                },
+                _ => {
+                    let client_id = if screen.active_tab_indices.contains_key(&client_id) {
+                        Some(client_id)
+                    } else {
+                        screen.get_first_client_id()
+                    };
+                    let client_id_and_focused_tab = client_id.and_then(|client_id| {
+                        screen
+                            .active_tab_indices
+                            .get(&client_id)
+                            .map(|tab_index| (*tab_index, client_id))
+                    });
+                    match client_id_and_focused_tab {
+                        Some((tab_index, client_id)) => {
+                            if screen.focus_plugin_pane(
+                                &run_plugin,
+                                should_float,
+                                move_to_focused_tab,
+                                client_id,
+                            )? {
+                                screen.render(None)?;
+                                screen.log_and_report_session_state()?;
+                            } else {
+                                screen
+                                    .bus
+                                    .senders
+                                    .send_to_pty(PtyInstruction::FillPluginCwd(
+                                        Some(should_float),
+                                        should_open_in_place,
+                                        None,
+                                        run_plugin,
+                                        tab_index,
+                                        None,
+                                        client_id,
+                                        Size::default(),
+                                        skip_cache,
+                                        None,
+                                        None,
+                                    ))?;
+                            }
+                        },
+                        None => {
+                            log::error!("No connected clients found - cannot load or focus plugin")
+                        },
+                    }
+                },
+            },
+            ScreenInstruction::LaunchPlugin(
+                run_plugin,
+                should_float,
+                should_open_in_place,
+                pane_id_to_replace,
+                skip_cache,
+                cwd,
+                client_id,
+            ) => match pane_id_to_replace {
+                Some(pane_id_to_replace) => match screen.active_tab_indices.values().next() {
+                    Some(tab_index) => {
+                        let size = Size::default();
+                        screen
+                            .bus
+                            .senders
+                            .send_to_pty(PtyInstruction::FillPluginCwd(
+                                Some(should_float),
+                                should_open_in_place,
+                                None,
+                                run_plugin,
+                                *tab_index,
+                                Some(pane_id_to_replace),
+                                client_id,
+                                size,
+                                skip_cache,
+                                cwd,
+                                None,
+                            ))?;
+                    },
+                    None => {
+                        log::error!(
+                            "Could not find an active tab - is there at least 1 connected user?"
+                        );
+                    },
+                },
+                None => {
+                    let client_id = if screen.active_tab_indices.contains_key(&client_id) {
+                        Some(client_id)
+                    } else {
+                        screen.get_first_client_id()
+                    };
+                    let client_id_and_focused_tab = client_id.and_then(|client_id| {
+                        screen
+                            .active_tab_indices
+                            .get(&client_id)
+                            .map(|tab_index| (*tab_index, client_id))
+                    });
+                    match client_id_and_focused_tab {
+                        Some((tab_index, client_id)) => {
                             screen
                                 .bus
                                 .senders
-                                .send_to_plugin(PluginInstruction::NewTab(
-                                    None,
-                                    default_shell,
+                                .send_to_pty(PtyInstruction::FillPluginCwd(
+                                    Some(should_float),
+                                    should_open_in_place,
                                     None,
-                                    vec![],
+                                    run_plugin,
                                     tab_index,
-                                    should_change_focus_to_new_tab,
+                                    None,
                                     client_id,
+                                    Size::default(),
+                                    skip_cache,
+                                    cwd,
+                                    None,
                                 ))?;
-                        }
+                        },
+                        None => {
+                            log::error!("No connected clients found - cannot load or focus plugin")
+                        },
+                    }
+                },
+            },
+            ScreenInstruction::SuppressPane(pane_id, client_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_non_suppressed_pane_with_pid(&pane_id) {
+                        tab.suppress_pane(pane_id, Some(client_id));
+                        drop(screen.render(None));
+                        break;
                     }
                 }
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::UpdateTabName(c, client_id) => {
-                screen.update_active_tab_name(c, client_id)?;
+            ScreenInstruction::FocusPaneWithId(pane_id, should_float_if_hidden, client_id) => {
+                screen.focus_pane_with_id(pane_id, should_float_if_hidden, client_id)?;
                 screen.unblock_input()?;
-                screen.render(None)?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::UndoRenameTab(client_id) => {
-                screen.undo_active_rename_tab(client_id)?;
+            ScreenInstruction::RenamePane(pane_id, new_name) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        match tab.rename_pane(new_name, pane_id) {
+                            Ok(()) => drop(screen.render(None)),
+                            Err(e) => log::error!("Failed to rename pane: {:?}", e),
+                        }
+                        break;
+                    }
+                }
                 screen.unblock_input()?;
-                screen.render(None)?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::MoveTabLeft(client_id) => {
-                if pending_tab_ids.is_empty() {
-                    screen.move_active_tab_to_left(client_id)?;
-                    screen.render(None)?;
-                } else {
-                    pending_events_waiting_for_tab.push(ScreenInstruction::MoveTabLeft(client_id));
+            ScreenInstruction::RenameTab(tab_index, new_name) => {
+                match screen.tabs.get_mut(&tab_index.saturating_sub(1)) {
+                    Some(tab) => {
+                        tab.name = String::from_utf8_lossy(&new_name).to_string();
+                    },
+                    None => {
+                        log::error!("Failed to find tab with index: {:?}", tab_index);
+                    },
                 }
-                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::MoveTabRight(client_id) => {
-                if pending_tab_ids.is_empty() {
-                    screen.move_active_tab_to_right(client_id)?;
-                    screen.render(None)?;
-                } else {
-                    pending_events_waiting_for_tab.push(ScreenInstruction::MoveTabRight(client_id));
+            ScreenInstruction::RequestPluginPermissions(plugin_id, plugin_permission) => {
+                let all_tabs = screen.get_tabs_mut();
+                let found = all_tabs.values_mut().any(|tab| {
+                    if tab.has_plugin(plugin_id) {
+                        tab.request_plugin_permissions(plugin_id, Some(plugin_permission.clone()));
+                        true
+                    } else {
+                        false
+                    }
+                });
+
+                if !found {
+                    log::error!("PluginId '{}' not found - caching request", plugin_id);
+                    pending_events_waiting_for_client.push(
+                        ScreenInstruction::RequestPluginPermissions(plugin_id, plugin_permission),
+                    );
                 }
-                screen.unblock_input()?;
             },
-            ScreenInstruction::TerminalResize(new_size) => {
-                screen.resize_to_screen(new_size)?;
-                screen.log_and_report_session_state()?; // update tabs so that the ui indication will be send to the plugins
-                screen.render(None)?;
+            ScreenInstruction::BreakPane(default_layout, default_shell, client_id) => {
+                screen.break_pane(default_shell, default_layout, client_id)?;
             },
-            ScreenInstruction::TerminalPixelDimensions(pixel_dimensions) => {
-                screen.update_pixel_dimensions(pixel_dimensions);
+            ScreenInstruction::BreakPaneRight(client_id) => {
+                screen.break_pane_to_new_tab(Direction::Right, client_id)?;
             },
-            ScreenInstruction::TerminalBackgroundColor(background_color_instruction) => {
-                screen.update_terminal_background_color(background_color_instruction);
+            ScreenInstruction::BreakPaneLeft(client_id) => {
+                screen.break_pane_to_new_tab(Direction::Left, client_id)?;
             },
-            ScreenInstruction::TerminalForegroundColor(background_color_instruction) => {
-                screen.update_terminal_foreground_color(background_color_instruction);
+            ScreenInstruction::JoinTab(tab_index, direction, client_id) => {
+                screen.join_tab(tab_index.saturating_sub(1), direction, client_id)?;
             },
-            ScreenInstruction::TerminalColorRegisters(color_registers) => {
-                screen.update_terminal_color_registers(color_registers);
+            ScreenInstruction::MovePaneToSession(session_name, client_id) => {
+                screen.move_pane_to_session(session_name, client_id)?;
             },
-            ScreenInstruction::ChangeMode(mode_info, client_id) => {
-                screen.change_mode(mode_info, client_id)?;
-                screen.render(None)?;
-                screen.unblock_input()?;
+            ScreenInstruction::SerializeMovedPane(terminal_id, session_name) => {
+                screen.serialize_moved_pane(terminal_id, session_name)?;
             },
-            ScreenInstruction::ChangeModeForAllClients(mode_info) => {
-                screen.change_mode_for_all_clients(mode_info)?;
+            ScreenInstruction::PaneMovedToSession(terminal_id) => {
+                screen.remove_pane_moved_to_session(terminal_id)?;
+            },
+            ScreenInstruction::AdoptPane(terminal_id, moved_pane, client_id) => {
+                screen
+                    .adopt_pane(terminal_id, *moved_pane, client_id)
+                    .non_fatal();
                 screen.render(None)?;
-                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::ToggleActiveSyncTab(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, _client_id: ClientId| tab.toggle_sync_panes_is_active()
-                );
+            ScreenInstruction::UpdateSessionInfos(new_session_infos, resurrectable_sessions) => {
+                screen.update_session_infos(new_session_infos, resurrectable_sessions)?;
+            },
+            ScreenInstruction::ReplacePane(
+                new_pane_id,
+                hold_for_command,
+                pane_title,
+                invoked_with,
+                close_replaced_pane,
+                client_id_tab_index_or_pane_id,
+            ) => {
+                screen.replace_pane(
+                    new_pane_id,
+                    hold_for_command,
+                    invoked_with,
+                    pane_title,
+                    close_replaced_pane,
+                    client_id_tab_index_or_pane_id,
+                )?;
+
+                screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
+
                 screen.render(None)?;
+            },
+            ScreenInstruction::DumpLayoutToHd => {
+                if screen.session_serialization {
+                    screen.dump_layout_to_hd()?;
+                }
+            },
+            ScreenInstruction::RenameSession(name, client_id) => {
+                screen.rename_session(name, client_id)?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::MouseEvent(event, client_id) => {
-                let mouse_effect = screen
-                    .get_active_tab_mut(client_id)
-                    .and_then(|tab| tab.handle_mouse_event(&event, client_id))?;
-                if mouse_effect.state_changed {
-                    screen.log_and_report_session_state()?;
+            ScreenInstruction::Reconfigure {
+                client_id,
+                keybinds,
+                default_mode,
+                theme,
+                simplified_ui,
+                default_shell,
+                pane_frames,
+                copy_to_clipboard,
+                copy_command,
+                copy_providers,
+                copy_on_select,
+                osc52_forwarding,
+                word_separators,
+                auto_layout,
+                rounded_corners,
+                hide_session_name,
+                stacked_resize,
+                default_editor,
+                quiet_hours,
+                title_precedence,
+                tab_name_from_title,
+                bell_forwarding,
+                command_exit_banner,
+                idle_client_detach_timeout,
+                idle_session_timeout,
+                mirror_session,
+                global_floating_panes,
+                automatic_tab_rename,
+                pane_frame_process_stats,
+                confirm_close_with_running_processes,
+                command_exit_notification_command,
+                resource_limits,
+                alternate_screen_behavior,
+                unicode_width,
+            } => {
+                screen
+                    .reconfigure(
+                        keybinds,
+                        default_mode,
+                        theme,
+                        simplified_ui,
+                        default_shell,
+                        pane_frames,
+                        copy_command,
+                        copy_providers,
+                        copy_to_clipboard,
+                        copy_on_select,
+                        osc52_forwarding,
+                        word_separators,
+                        auto_layout,
+                        rounded_corners,
+                        hide_session_name,
+                        stacked_resize,
+                        default_editor,
+                        quiet_hours,
+                        title_precedence,
+                        tab_name_from_title,
+                        bell_forwarding,
+                        command_exit_banner,
+                        idle_client_detach_timeout,
+                        idle_session_timeout,
+                        mirror_session,
+                        global_floating_panes,
+                        automatic_tab_rename,
+                        pane_frame_process_stats,
+                        confirm_close_with_running_processes,
+                        command_exit_notification_command,
+                        resource_limits,
+                        alternate_screen_behavior,
+                        unicode_width,
+                        client_id,
+                    )
+                    .non_fatal();
+            },
+            ScreenInstruction::RerunCommandPane(terminal_pane_id) => {
+                screen.rerun_command_pane_with_id(terminal_pane_id)
+            },
+            ScreenInstruction::WatchedFilesChanged(terminal_pane_id) => {
+                screen.rerun_watched_command(terminal_pane_id)?;
+                screen.render(None)?;
+            },
+            ScreenInstruction::RerunCommandPaneOrFocused(pane_id, client_id) => {
+                if let Err(e) = screen.rerun_command_pane_or_focused(pane_id, client_id) {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                }
+                screen.render(None)?;
+            },
+            ScreenInstruction::OpenCommandHistory(pane_id, client_id) => {
+                if let Err(e) = screen.open_command_history(pane_id, client_id) {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                }
+                screen.render(None)?;
+            },
+            ScreenInstruction::UndoClosePane(client_id) => {
+                if let Err(e) = screen.undo_close_pane(client_id) {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
                 }
-                if !mouse_effect.leave_clipboard_message {
-                    let _ = screen
+            },
+            ScreenInstruction::DuplicatePane(direction, default_shell, client_id) => {
+                if let Err(e) = screen.duplicate_pane(direction, default_shell, client_id) {
+                    screen
                         .bus
                         .senders
-                        .send_to_plugin(PluginInstruction::Update(vec![(
-                            None,
-                            Some(client_id),
-                            Event::InputReceived,
-                        )]));
+                        .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
                 }
-                screen.render(None)?;
             },
-            ScreenInstruction::Copy(client_id) => {
-                active_tab!(screen, client_id, |tab: &mut Tab| tab
-                    .copy_selection(client_id), ?);
+            ScreenInstruction::ClosedPaneRespawned(
+                terminal_id,
+                hold_for_command,
+                closed_pane,
+                client_id,
+            ) => {
+                screen
+                    .restore_closed_pane(terminal_id, hold_for_command, *closed_pane, client_id)
+                    .non_fatal();
                 screen.render(None)?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::Exit => {
-                break;
-            },
-            ScreenInstruction::ToggleTab(client_id) => {
-                screen.toggle_tab(client_id)?;
-                screen.unblock_input()?;
-                screen.render(None)?;
+            ScreenInstruction::ResizePaneWithId(resize, pane_id) => {
+                screen.resize_pane_with_id(resize, pane_id)
             },
-            ScreenInstruction::AddClient(client_id, tab_position_to_focus, pane_id_to_focus) => {
-                screen.add_client(client_id)?;
-                let pane_id = pane_id_to_focus.map(|(pane_id, is_plugin)| {
-                    if is_plugin {
-                        PaneId::Plugin(pane_id)
-                    } else {
-                        PaneId::Terminal(pane_id)
+            ScreenInstruction::EditScrollbackForPaneWithId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        tab.edit_scrollback_for_pane_with_id(pane_id).non_fatal();
+                        break;
                     }
-                });
-                if let Some(pane_id) = pane_id {
-                    screen.focus_pane_with_id(pane_id, true, client_id)?;
-                } else if let Some(tab_position_to_focus) = tab_position_to_focus {
-                    screen.go_to_tab(tab_position_to_focus, client_id)?;
-                }
-                for event in pending_events_waiting_for_client.drain(..) {
-                    screen.bus.senders.send_to_screen(event).non_fatal();
                 }
-                screen.log_and_report_session_state()?;
                 screen.render(None)?;
             },
-            ScreenInstruction::RemoveClient(client_id) => {
-                screen.remove_client(client_id)?;
-                screen.log_and_report_session_state()?;
+            ScreenInstruction::WriteToPaneId(bytes, pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        tab.write_to_pane_id(&None, bytes, false, pane_id, None)
+                            .non_fatal();
+                        break;
+                    }
+                }
                 screen.render(None)?;
-            },
-            ScreenInstruction::AddOverlay(overlay, _client_id) => {
-                screen.get_active_overlays_mut().pop();
-                screen.get_active_overlays_mut().push(overlay);
                 screen.unblock_input()?;
             },
-            ScreenInstruction::RemoveOverlay(_client_id) => {
-                screen.get_active_overlays_mut().pop();
+            ScreenInstruction::MovePaneWithPaneId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        tab.move_pane(pane_id);
+                        break;
+                    }
+                }
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::ConfirmPrompt(_client_id) => {
-                let overlay = screen.get_active_overlays_mut().pop();
-                let instruction = overlay.and_then(|o| o.prompt_confirm());
-                if let Some(instruction) = instruction {
-                    screen
-                        .bus
-                        .senders
-                        .send_to_server(*instruction)
-                        .context("failed to confirm prompt")?;
+            ScreenInstruction::MovePaneWithPaneIdInDirection(pane_id, direction) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        match direction {
+                            Direction::Down => tab.move_pane_down(pane_id),
+                            Direction::Up => tab.move_pane_up(pane_id),
+                            Direction::Left => tab.move_pane_left(pane_id),
+                            Direction::Right => tab.move_pane_right(pane_id),
+                        }
+                        break;
+                    }
                 }
-                screen.unblock_input()?;
-            },
-            ScreenInstruction::DenyPrompt(_client_id) => {
-                screen.get_active_overlays_mut().pop();
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::UpdateSearch(c, client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.update_search_term(c, client_id), ?
-                );
+            ScreenInstruction::ClearScreenForPaneId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        tab.clear_screen_for_pane_id(pane_id);
+                        break;
+                    }
+                }
                 screen.render(None)?;
             },
-            ScreenInstruction::SearchDown(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.search_down(client_id)
-                );
+            ScreenInstruction::ScrollUpInPaneId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
+                            tab.scroll_terminal_up(terminal_pane_id);
+                        } else {
+                            // this is because to do this with plugins, we need the client_id -
+                            // which we do not have (yet?) in this context...
+                            log::error!(
+                                "Currently only terminal panes are supported for scrolling up"
+                            );
+                        }
+                        break;
+                    }
+                }
                 screen.render(None)?;
             },
-            ScreenInstruction::SearchUp(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.search_up(client_id)
-                );
+            ScreenInstruction::ScrollDownInPaneId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
+                            tab.scroll_terminal_down(terminal_pane_id);
+                        } else {
+                            // this is because to do this with plugins, we need the client_id -
+                            // which we do not have (yet?) in this context...
+                            log::error!(
+                                "Currently only terminal panes are supported for scrolling down"
+                            );
+                        }
+                        break;
+                    }
+                }
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::SearchToggleCaseSensitivity(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab
-                        .toggle_search_case_sensitivity(client_id)
-                );
+            ScreenInstruction::ScrollToTopInPaneId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
+                            tab.scroll_terminal_to_top(terminal_pane_id);
+                        } else {
+                            // this is because to do this with plugins, we need the client_id -
+                            // which we do not have (yet?) in this context...
+                            log::error!(
+                                "Currently only terminal panes are supported for scrolling to top"
+                            );
+                        }
+                        break;
+                    }
+                }
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::SearchToggleWrap(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.toggle_search_wrap(client_id)
-                );
+            ScreenInstruction::ScrollToBottomInPaneId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
+                            tab.scroll_terminal_to_bottom(terminal_pane_id);
+                        } else {
+                            // this is because to do this with plugins, we need the client_id -
+                            // which we do not have (yet?) in this context...
+                            log::error!("Currently only terminal panes are supported for scrolling to bottom");
+                        }
+                        break;
+                    }
+                }
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::SearchToggleWholeWord(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.toggle_search_whole_words(client_id)
-                );
+            ScreenInstruction::PageScrollUpInPaneId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
+                            tab.scroll_terminal_page_up(terminal_pane_id);
+                        } else {
+                            // this is because to do this with plugins, we need the client_id -
+                            // which we do not have (yet?) in this context...
+                            log::error!(
+                                "Currently only terminal panes are supported for scrolling"
+                            );
+                        }
+                        break;
+                    }
+                }
                 screen.render(None)?;
-                screen.unblock_input()?;
             },
-            ScreenInstruction::AddRedPaneFrameColorOverride(pane_ids, error_text) => {
+            ScreenInstruction::PageScrollDownInPaneId(pane_id) => {
                 let all_tabs = screen.get_tabs_mut();
-                for pane_id in pane_ids {
-                    for tab in all_tabs.values_mut() {
-                        if tab.has_pane_with_pid(&pane_id) {
-                            tab.add_red_pane_frame_color_override(pane_id, error_text.clone());
-                            break;
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
+                            tab.scroll_terminal_page_down(terminal_pane_id);
+                        } else {
+                            // this is because to do this with plugins, we need the client_id -
+                            // which we do not have (yet?) in this context...
+                            log::error!(
+                                "Currently only terminal panes are supported for scrolling"
+                            );
                         }
+                        break;
                     }
                 }
                 screen.render(None)?;
             },
-            ScreenInstruction::ClearPaneFrameColorOverride(pane_ids) => {
+            ScreenInstruction::TogglePaneIdFullscreen(pane_id) => {
                 let all_tabs = screen.get_tabs_mut();
-                for pane_id in pane_ids {
-                    for tab in all_tabs.values_mut() {
-                        if tab.has_pane_with_pid(&pane_id) {
-                            tab.clear_pane_frame_color_override(pane_id);
-                            break;
-                        }
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        tab.toggle_pane_fullscreen(pane_id);
+                        break;
                     }
                 }
                 screen.render(None)?;
-            },
-            ScreenInstruction::PreviousSwapLayout(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, _client_id: ClientId| tab.previous_swap_layout(),
-                    ?
-                );
-                screen.render(None)?;
-                screen.log_and_report_session_state()?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::NextSwapLayout(client_id) => {
-                active_tab_and_connected_client_id!(
-                    screen,
-                    client_id,
-                    |tab: &mut Tab, _client_id: ClientId| tab.next_swap_layout(),
-                    ?
-                );
+            ScreenInstruction::TogglePaneEmbedOrEjectForPaneId(pane_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        tab.toggle_pane_embed_or_floating_for_pane_id(pane_id)
+                            .non_fatal();
+                        break;
+                    }
+                }
                 screen.render(None)?;
-                screen.log_and_report_session_state()?;
-                screen.unblock_input()?;
-            },
-            ScreenInstruction::QueryTabNames(client_id) => {
-                let tab_names = screen
-                    .get_tabs_mut()
-                    .values()
-                    .map(|tab| tab.name.clone())
-                    .collect::<Vec<String>>();
-                screen
-                    .bus
-                    .senders
-                    .send_to_server(ServerInstruction::Log(tab_names, client_id))?;
-            },
-            ScreenInstruction::NewTiledPluginPane(
-                run_plugin,
-                pane_title,
-                skip_cache,
-                cwd,
-                client_id,
-            ) => {
-                let tab_index = screen.active_tab_indices.values().next().unwrap_or(&1);
-                let size = Size::default();
-                let should_float = Some(false);
-                let should_be_opened_in_place = false;
-                screen
-                    .bus
-                    .senders
-                    .send_to_pty(PtyInstruction::FillPluginCwd(
-                        should_float,
-                        should_be_opened_in_place,
-                        pane_title,
-                        run_plugin,
-                        *tab_index,
-                        None,
-                        client_id,
-                        size,
-                        skip_cache,
-                        cwd,
-                        None,
-                    ))?;
-            },
-            ScreenInstruction::NewFloatingPluginPane(
-                run_plugin,
-                pane_title,
-                skip_cache,
-                cwd,
-                floating_pane_coordinates,
-                client_id,
-            ) => match screen.active_tab_indices.values().next() {
-                Some(tab_index) => {
-                    let size = Size::default();
-                    let should_float = Some(true);
-                    let should_be_opened_in_place = false;
-                    screen
-                        .bus
-                        .senders
-                        .send_to_pty(PtyInstruction::FillPluginCwd(
-                            should_float,
-                            should_be_opened_in_place,
-                            pane_title,
-                            run_plugin,
-                            *tab_index,
-                            None,
-                            client_id,
-                            size,
-                            skip_cache,
-                            cwd,
-                            floating_pane_coordinates,
-                        ))?;
-                },
-                None => {
-                    log::error!(
-                        "Could not find an active tab - is there at least 1 connected user?"
-                    );
-                },
-            },
-            ScreenInstruction::NewInPlacePluginPane(
-                run_plugin,
-                pane_title,
-                pane_id_to_replace,
-                skip_cache,
-                client_id,
-            ) => match screen.active_tab_indices.values().next() {
-                Some(tab_index) => {
-                    let size = Size::default();
-                    let should_float = None;
-                    let should_be_in_place = true;
-                    screen
-                        .bus
-                        .senders
-                        .send_to_pty(PtyInstruction::FillPluginCwd(
-                            should_float,
-                            should_be_in_place,
-                            pane_title,
-                            run_plugin,
-                            *tab_index,
-                            Some(pane_id_to_replace),
-                            client_id,
-                            size,
-                            skip_cache,
-                            None,
-                            None,
-                        ))?;
-                },
-                None => {
-                    log::error!(
-                        "Could not find an active tab - is there at least 1 connected user?"
-                    );
-                },
             },
-            ScreenInstruction::StartOrReloadPluginPane(run_plugin, pane_title) => {
-                let tab_index = screen.active_tab_indices.values().next().unwrap_or(&1);
-                let size = Size::default();
-                let should_float = Some(false);
-
-                screen
-                    .bus
-                    .senders
-                    .send_to_plugin(PluginInstruction::Reload(
-                        should_float,
-                        pane_title,
-                        run_plugin,
-                        *tab_index,
-                        size,
-                    ))?;
+            ScreenInstruction::CloseTabWithIndex(tab_index) => {
+                screen.close_tab_at_index(tab_index).non_fatal()
             },
-            ScreenInstruction::AddPlugin(
-                should_float,
-                should_be_in_place,
-                run_plugin_or_alias,
-                pane_title,
-                tab_index,
-                plugin_id,
-                pane_id_to_replace,
-                cwd,
-                start_suppressed,
+            ScreenInstruction::BreakPanesToNewTab {
+                pane_ids,
+                default_shell,
+                should_change_focus_to_new_tab,
+                new_tab_name,
                 client_id,
-            ) => {
-                if screen.active_tab_indices.is_empty() && tab_index.is_none() {
-                    pending_events_waiting_for_client.push(ScreenInstruction::AddPlugin(
-                        should_float,
-                        should_be_in_place,
-                        run_plugin_or_alias,
-                        pane_title,
-                        tab_index,
-                        plugin_id,
-                        pane_id_to_replace,
-                        cwd,
-                        start_suppressed,
-                        client_id,
-                    ));
-                    continue;
-                }
-                let pane_title = pane_title.unwrap_or_else(|| {
-                    format!(
-                        "({}) - {}",
-                        cwd.map(|cwd| cwd.display().to_string())
-                            .unwrap_or(".".to_owned()),
-                        run_plugin_or_alias.location_string()
-                    )
-                });
-                let run_plugin = Run::Plugin(run_plugin_or_alias);
-
-                let close_replaced_pane = false;
-                if should_be_in_place {
-                    if let Some(pane_id_to_replace) = pane_id_to_replace {
-                        let client_tab_index_or_pane_id =
-                            ClientTabIndexOrPaneId::PaneId(pane_id_to_replace);
-                        screen.replace_pane(
-                            PaneId::Plugin(plugin_id),
-                            None,
-                            Some(run_plugin),
-                            Some(pane_title),
-                            close_replaced_pane,
-                            client_tab_index_or_pane_id,
-                        )?;
-                    } else if let Some(client_id) = client_id {
-                        let client_tab_index_or_pane_id =
-                            ClientTabIndexOrPaneId::ClientId(client_id);
-                        screen.replace_pane(
-                            PaneId::Plugin(plugin_id),
-                            None,
-                            Some(run_plugin),
-                            Some(pane_title),
-                            close_replaced_pane,
-                            client_tab_index_or_pane_id,
-                        )?;
-                    } else {
-                        log::error!("Must have pane id to replace or connected client_id if replacing a pane");
-                    }
-                } else if let Some(client_id) = client_id {
-                    active_tab_and_connected_client_id!(screen, client_id, |active_tab: &mut Tab, _client_id: ClientId| {
-                        active_tab.new_pane(
-                            PaneId::Plugin(plugin_id),
-                            Some(pane_title),
-                            should_float,
-                            Some(run_plugin),
-                            None,
-                            start_suppressed,
-                            Some(client_id),
-                        )
-                    }, ?);
-                } else if let Some(active_tab) =
-                    tab_index.and_then(|tab_index| screen.tabs.get_mut(&tab_index))
-                {
-                    active_tab.new_pane(
-                        PaneId::Plugin(plugin_id),
-                        Some(pane_title),
-                        should_float,
-                        Some(run_plugin),
-                        None,
-                        start_suppressed,
-                        None,
-                    )?;
-                } else {
-                    log::error!("Tab index not found: {:?}", tab_index);
-                }
-                if let Some(loading_indication) = plugin_loading_message_cache.remove(&plugin_id) {
-                    screen.update_plugin_loading_stage(plugin_id, loading_indication);
-                    screen.render(None)?;
-                }
+            } => {
+                screen.break_multiple_panes_to_new_tab(
+                    pane_ids,
+                    default_shell,
+                    should_change_focus_to_new_tab,
+                    new_tab_name,
+                    client_id,
+                )?;
+            },
+            ScreenInstruction::BreakPanesToTabWithIndex {
+                pane_ids,
+                tab_index,
+                should_change_focus_to_new_tab,
+                client_id,
+            } => {
+                screen.break_multiple_panes_to_tab_with_index(
+                    pane_ids,
+                    tab_index,
+                    should_change_focus_to_new_tab,
+                    client_id,
+                )?;
+            },
+            ScreenInstruction::TogglePanePinned(client_id) => {
+                screen.toggle_pane_pinned(client_id);
+            },
+            ScreenInstruction::ToggleFloatingPaneScope(client_id) => {
+                screen.toggle_floating_pane_scope(client_id)?;
+            },
+            ScreenInstruction::SetFloatingPanePinned(pane_id, should_be_pinned) => {
+                screen.set_floating_pane_pinned(pane_id, should_be_pinned);
+            },
+            ScreenInstruction::StackPanes(pane_ids_to_stack) => {
+                screen.stack_panes(pane_ids_to_stack);
+                let _ = screen.unblock_input();
+                let _ = screen.render(None);
+            },
+            ScreenInstruction::ToggleExpandStack(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.toggle_expand_stack(client_id),
+                    ?
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
+            },
+            ScreenInstruction::MovePaneInStack(direction, client_id) => {
+                let up = direction == Direction::Up;
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .move_focused_pane_in_stack(up, client_id),
+                    ?
+                );
+                screen.render(None)?;
                 screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::UpdatePluginLoadingStage(pid, loading_indication) => {
-                let found_plugin =
-                    screen.update_plugin_loading_stage(pid, loading_indication.clone());
-                if !found_plugin {
-                    plugin_loading_message_cache.insert(pid, loading_indication);
-                }
+            ScreenInstruction::FocusStackedPaneByTitle(title, client_id) => {
+                screen.focus_stacked_pane_by_title(&title, client_id)?;
                 screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::StartPluginLoadingIndication(pid, loading_indication) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_plugin(pid) {
-                        tab.start_plugin_loading_indication(pid, loading_indication);
-                        break;
-                    }
+            ScreenInstruction::ReplacePaneCommand(run_command, keep_scrollback, client_id) => {
+                if let Err(e) = screen.replace_pane_command(run_command, keep_scrollback, client_id)
+                {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
                 }
                 screen.render(None)?;
+                screen.unblock_input()?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::ProgressPluginLoadingOffset(pid) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_plugin(pid) {
-                        tab.progress_plugin_loading_offset(pid);
-                        break;
-                    }
+            ScreenInstruction::StopCommandTimedOut(terminal_pane_id, signal) => {
+                screen.stop_command_timed_out(terminal_pane_id, signal)?;
+            },
+            ScreenInstruction::ChangeFloatingPanesCoordinates(pane_ids_and_coordinates) => {
+                screen.change_floating_panes_coordinates(pane_ids_and_coordinates);
+                let _ = screen.unblock_input();
+                let _ = screen.render(None);
+            },
+            ScreenInstruction::QuerySessionStats(client_id) => {
+                screen.report_session_stats(client_id).non_fatal();
+            },
+            ScreenInstruction::ClientTerminalFocusChanged(client_id, is_focused) => {
+                active_tab!(screen, client_id, |tab: &mut Tab| tab
+                    .client_terminal_focus_changed(client_id, is_focused));
+            },
+            ScreenInstruction::ClientSixelSupport(client_id, supports_sixel) => {
+                if supports_sixel {
+                    screen.clients_without_sixel_support.remove(&client_id);
+                } else {
+                    screen.clients_without_sixel_support.insert(client_id);
                 }
+            },
+            ScreenInstruction::ClientKittyGraphicsSupport(client_id) => {
+                screen.clients_with_kitty_graphics_support.insert(client_id);
+                // the images of the panes are only sent to this client from now on
+                active_tab!(screen, client_id, |tab: &mut Tab| tab.set_force_render());
                 screen.render(None)?;
             },
-            ScreenInstruction::RequestStateUpdateForPlugins => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    tab.update_input_modes()?;
-                }
-                screen.log_and_report_session_state()?;
+            ScreenInstruction::ClientColorSupport(client_id, color_support) => {
+                screen.client_color_support.insert(client_id, color_support);
+                active_tab!(screen, client_id, |tab: &mut Tab| tab.set_force_render());
                 screen.render(None)?;
             },
-            ScreenInstruction::LaunchOrFocusPlugin(
-                run_plugin,
-                should_float,
-                move_to_focused_tab,
-                should_open_in_place,
-                pane_id_to_replace,
-                skip_cache,
-                client_id,
-            ) => match pane_id_to_replace {
-                Some(pane_id_to_replace) if should_open_in_place => {
-                    match screen.active_tab_indices.values().next() {
-                        Some(tab_index) => {
-                            let size = Size::default();
-                            screen
-                                .bus
-                                .senders
-                                .send_to_pty(PtyInstruction::FillPluginCwd(
-                                    Some(should_float),
-                                    should_open_in_place,
-                                    None,
-                                    run_plugin,
-                                    *tab_index,
-                                    Some(pane_id_to_replace),
-                                    client_id,
-                                    size,
-                                    skip_cache,
-                                    None,
-                                    None,
-                                ))?;
-                        },
-                        None => {
-                            log::error!(
-                            "Could not find an active tab - is there at least 1 connected user?"
-                        );
-                        },
-                    }
-                },
-                _ => {
-                    let client_id = if screen.active_tab_indices.contains_key(&client_id) {
-                        Some(client_id)
-                    } else {
-                        screen.get_first_client_id()
-                    };
-                    let client_id_and_focused_tab = client_id.and_then(|client_id| {
-                        screen
-                            .active_tab_indices
-                            .get(&client_id)
-                            .map(|tab_index| (*tab_index, client_id))
-                    });
-                    match client_id_and_focused_tab {
-                        Some((tab_index, client_id)) => {
-                            if screen.focus_plugin_pane(
-                                &run_plugin,
-                                should_float,
-                                move_to_focused_tab,
-                                client_id,
-                            )? {
-                                screen.render(None)?;
-                                screen.log_and_report_session_state()?;
-                            } else {
-                                screen
-                                    .bus
-                                    .senders
-                                    .send_to_pty(PtyInstruction::FillPluginCwd(
-                                        Some(should_float),
-                                        should_open_in_place,
-                                        None,
-                                        run_plugin,
-                                        tab_index,
-                                        None,
-                                        client_id,
-                                        Size::default(),
-                                        skip_cache,
-                                        None,
-                                        None,
-                                    ))?;
-                            }
-                        },
-                        None => {
-                            log::error!("No connected clients found - cannot load or focus plugin")
-                        },
-                    }
-                },
+            ScreenInstruction::CapturePaneOutput(terminal_id, client_id, tail_lines) => {
+                screen
+                    .output_captures
+                    .insert(terminal_id, (client_id, tail_lines));
             },
-            ScreenInstruction::LaunchPlugin(
-                run_plugin,
-                should_float,
-                should_open_in_place,
-                pane_id_to_replace,
-                skip_cache,
-                cwd,
-                client_id,
-            ) => match pane_id_to_replace {
-                Some(pane_id_to_replace) => match screen.active_tab_indices.values().next() {
-                    Some(tab_index) => {
-                        let size = Size::default();
-                        screen
-                            .bus
-                            .senders
-                            .send_to_pty(PtyInstruction::FillPluginCwd(
-                                Some(should_float),
-                                should_open_in_place,
-                                None,
-                                run_plugin,
-                                *tab_index,
-                                Some(pane_id_to_replace),
-                                client_id,
-                                size,
-                                skip_cache,
-                                cwd,
-                                None,
-                            ))?;
-                    },
-                    None => {
-                        log::error!(
-                            "Could not find an active tab - is there at least 1 connected user?"
-                        );
-                    },
-                },
-                None => {
-                    let client_id = if screen.active_tab_indices.contains_key(&client_id) {
-                        Some(client_id)
-                    } else {
-                        screen.get_first_client_id()
-                    };
-                    let client_id_and_focused_tab = client_id.and_then(|client_id| {
-                        screen
-                            .active_tab_indices
-                            .get(&client_id)
-                            .map(|tab_index| (*tab_index, client_id))
-                    });
-                    match client_id_and_focused_tab {
-                        Some((tab_index, client_id)) => {
-                            screen
-                                .bus
-                                .senders
-                                .send_to_pty(PtyInstruction::FillPluginCwd(
-                                    Some(should_float),
-                                    should_open_in_place,
-                                    None,
-                                    run_plugin,
-                                    tab_index,
-                                    None,
-                                    client_id,
-                                    Size::default(),
-                                    skip_cache,
-                                    cwd,
-                                    None,
-                                ))?;
-                        },
-                        None => {
-                            log::error!("No connected clients found - cannot load or focus plugin")
-                        },
-                    }
-                },
+            ScreenInstruction::CapturedPaneExited(terminal_id, exit_status) => {
+                screen
+                    .report_captured_pane_exit(terminal_id, exit_status)
+                    .non_fatal();
+            },
+            ScreenInstruction::ToggleMouseReportingOverride(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .toggle_active_pane_mouse_reporting_override(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
             },
-            ScreenInstruction::SuppressPane(pane_id, client_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_non_suppressed_pane_with_pid(&pane_id) {
-                        tab.suppress_pane(pane_id, Some(client_id));
-                        drop(screen.render(None));
-                        break;
-                    }
-                }
+            ScreenInstruction::TogglePaneInputLock(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .toggle_active_pane_input_lock(client_id)
+                );
+                screen.render(None)?;
+                screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::FocusPaneWithId(pane_id, should_float_if_hidden, client_id) => {
-                screen.focus_pane_with_id(pane_id, should_float_if_hidden, client_id)?;
-                screen.log_and_report_session_state()?;
+            ScreenInstruction::ToggleDoNotDisturb => {
+                screen.do_not_disturb = !screen.do_not_disturb;
+                screen.unblock_input()?;
             },
-            ScreenInstruction::RenamePane(pane_id, new_name) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        match tab.rename_pane(new_name, pane_id) {
-                            Ok(()) => drop(screen.render(None)),
-                            Err(e) => log::error!("Failed to rename pane: {:?}", e),
+            ScreenInstruction::SelectSwapLayout(swap_layout_name, client_id) => {
+                let mut found_swap_layout = false;
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, _client_id: ClientId| {
+                        match tab.select_swap_layout(&swap_layout_name) {
+                            Ok(found) => found_swap_layout = found,
+                            Err(e) => Err::<(), _>(e).non_fatal(),
                         }
-                        break;
                     }
+                );
+                if found_swap_layout {
+                    screen.render(None)?;
+                    screen.log_and_report_session_state()?;
+                    screen.unblock_input()?;
+                } else {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::LogError(
+                            vec![format!("No swap layout named: {}", swap_layout_name)],
+                            client_id,
+                        ))?;
                 }
-                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::RenameTab(tab_index, new_name) => {
-                match screen.tabs.get_mut(&tab_index.saturating_sub(1)) {
-                    Some(tab) => {
-                        tab.name = String::from_utf8_lossy(&new_name).to_string();
-                    },
-                    None => {
-                        log::error!("Failed to find tab with index: {:?}", tab_index);
-                    },
-                }
+            ScreenInstruction::ReloadLayout(new_layout) => {
+                screen.reload_layout(*new_layout)?;
+                screen.render(None)?;
                 screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::RequestPluginPermissions(plugin_id, plugin_permission) => {
-                let all_tabs = screen.get_tabs_mut();
-                let found = all_tabs.values_mut().any(|tab| {
-                    if tab.has_plugin(plugin_id) {
-                        tab.request_plugin_permissions(plugin_id, Some(plugin_permission.clone()));
-                        true
-                    } else {
-                        false
+            ScreenInstruction::QuerySwapLayoutNames(client_id) => {
+                let mut swap_layout_names = vec![];
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, _client_id: ClientId| {
+                        swap_layout_names = tab.swap_layout_names();
                     }
-                });
-
-                if !found {
-                    log::error!("PluginId '{}' not found - caching request", plugin_id);
-                    pending_events_waiting_for_client.push(
-                        ScreenInstruction::RequestPluginPermissions(plugin_id, plugin_permission),
-                    );
-                }
-            },
-            ScreenInstruction::BreakPane(default_layout, default_shell, client_id) => {
-                screen.break_pane(default_shell, default_layout, client_id)?;
-            },
-            ScreenInstruction::BreakPaneRight(client_id) => {
-                screen.break_pane_to_new_tab(Direction::Right, client_id)?;
-            },
-            ScreenInstruction::BreakPaneLeft(client_id) => {
-                screen.break_pane_to_new_tab(Direction::Left, client_id)?;
-            },
-            ScreenInstruction::UpdateSessionInfos(new_session_infos, resurrectable_sessions) => {
-                screen.update_session_infos(new_session_infos, resurrectable_sessions)?;
+                );
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::Log(swap_layout_names, client_id))?;
             },
-            ScreenInstruction::ReplacePane(
-                new_pane_id,
-                hold_for_command,
-                pane_title,
-                invoked_with,
-                close_replaced_pane,
-                client_id_tab_index_or_pane_id,
-            ) => {
-                screen.replace_pane(
-                    new_pane_id,
-                    hold_for_command,
-                    invoked_with,
-                    pane_title,
-                    close_replaced_pane,
-                    client_id_tab_index_or_pane_id,
-                )?;
-
+            ScreenInstruction::SetUi(ui_settings, client_id) => {
+                screen.set_ui(ui_settings, client_id)?;
+                screen.render(None)?;
                 screen.unblock_input()?;
                 screen.log_and_report_session_state()?;
-
-                screen.render(None)?;
             },
-            ScreenInstruction::DumpLayoutToHd => {
-                if screen.session_serialization {
-                    screen.dump_layout_to_hd()?;
+            ScreenInstruction::CloseTabWithName(tab_name, client_id) => {
+                if screen.close_tab_with_name(&tab_name)? {
+                    screen.unblock_input()?;
+                    screen.render(None)?;
+                } else {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::LogError(
+                            vec![format!("No tab named: {}", tab_name)],
+                            client_id,
+                        ))?;
                 }
             },
-            ScreenInstruction::RenameSession(name, client_id) => {
-                if screen.session_infos_on_machine.contains_key(&name) {
-                    let error_text = "A session by this name already exists.";
-                    log::error!("{}", error_text);
-                    if let Some(os_input) = &mut screen.bus.os_input {
-                        let _ = os_input.send_to_client(
-                            client_id,
-                            ServerToClientMsg::LogError(vec![error_text.to_owned()]),
-                        );
-                    }
-                } else if screen.resurrectable_sessions.contains_key(&name) {
-                    let error_text =
-                        "A resurrectable session by this name exists, cannot use this name.";
-                    log::error!("{}", error_text);
-                    if let Some(os_input) = &mut screen.bus.os_input {
-                        let _ = os_input.send_to_client(
-                            client_id,
-                            ServerToClientMsg::LogError(vec![error_text.to_owned()]),
-                        );
-                    }
+            ScreenInstruction::RenameTabWithName(tab_name, new_name, client_id) => {
+                if screen.rename_tab_with_name(&tab_name, new_name) {
+                    screen.unblock_input()?;
+                    screen.render(None)?;
+                    screen.log_and_report_session_state()?;
                 } else {
-                    let err_context = || format!("Failed to rename session");
-                    let old_session_name = screen.session_name.clone();
-
-                    // update state
-                    screen.session_name = name.clone();
-                    screen.default_mode_info.session_name = Some(name.clone());
-                    for (_client_id, mode_info) in screen.mode_info.iter_mut() {
-                        mode_info.session_name = Some(name.clone());
-                    }
-                    for (_, tab) in screen.tabs.iter_mut() {
-                        tab.rename_session(name.clone()).with_context(err_context)?;
-                    }
-
-                    // rename socket file
-                    let old_socket_file_path = ZELLIJ_SOCK_DIR.join(&old_session_name);
-                    let new_socket_file_path = ZELLIJ_SOCK_DIR.join(&name);
-                    if let Err(e) = std::fs::rename(old_socket_file_path, new_socket_file_path) {
-                        log::error!("Failed to rename ipc socket: {:?}", e);
-                    }
-
-                    // rename session_info folder (TODO: make this atomic, right now there is a
-                    // chance background_jobs will re-create this folder before it knows the
-                    // session was renamed)
-                    let old_session_info_folder =
-                        session_info_folder_for_session(&old_session_name);
-                    let new_session_info_folder = session_info_folder_for_session(&name);
-                    if let Err(e) =
-                        std::fs::rename(old_session_info_folder, new_session_info_folder)
-                    {
-                        log::error!("Failed to rename session_info folder: {:?}", e);
-                    }
-
-                    // report
                     screen
-                        .log_and_report_session_state()
-                        .with_context(err_context)?;
-
-                    // set the env variable
-                    set_session_name(name);
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::LogError(
+                            vec![format!("No tab named: {}", tab_name)],
+                            client_id,
+                        ))?;
                 }
-                screen.unblock_input()?;
             },
-            ScreenInstruction::Reconfigure {
-                client_id,
-                keybinds,
-                default_mode,
-                theme,
-                simplified_ui,
-                default_shell,
-                pane_frames,
-                copy_to_clipboard,
-                copy_command,
-                copy_on_select,
-                auto_layout,
-                rounded_corners,
-                hide_session_name,
-                stacked_resize,
-                default_editor,
-            } => {
+            ScreenInstruction::ListPanes(json, client_id) => {
+                let pane_list = screen.render_pane_list(json);
                 screen
-                    .reconfigure(
-                        keybinds,
-                        default_mode,
-                        theme,
-                        simplified_ui,
-                        default_shell,
-                        pane_frames,
-                        copy_command,
-                        copy_to_clipboard,
-                        copy_on_select,
-                        auto_layout,
-                        rounded_corners,
-                        hide_session_name,
-                        stacked_resize,
-                        default_editor,
-                        client_id,
-                    )
-                    .non_fatal();
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::Log(vec![pane_list], client_id))?;
             },
-            ScreenInstruction::RerunCommandPane(terminal_pane_id) => {
-                screen.rerun_command_pane_with_id(terminal_pane_id)
+            ScreenInstruction::SetSessionNote(note) => {
+                let note = note.trim();
+                screen.session_note = if note.is_empty() {
+                    None
+                } else {
+                    Some(note.to_owned())
+                };
+                screen.log_and_report_session_state()?;
+                screen.unblock_input()?;
             },
-            ScreenInstruction::ResizePaneWithId(resize, pane_id) => {
-                screen.resize_pane_with_id(resize, pane_id)
+            ScreenInstruction::MovePaneToTab(pane_id, tab_target, client_id) => {
+                match screen.tab_position_for_target(&tab_target) {
+                    Some(tab_position) => {
+                        screen.move_pane_to_tab(pane_id, tab_position, client_id)?;
+                        screen.render(None)?;
+                        screen.unblock_input()?;
+                    },
+                    None => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(
+                                vec![format!("No tab {}", tab_target)],
+                                client_id,
+                            ))?;
+                    },
+                }
             },
-            ScreenInstruction::EditScrollbackForPaneWithId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        tab.edit_scrollback_for_pane_with_id(pane_id).non_fatal();
-                        break;
-                    }
+            ScreenInstruction::LinkPaneToTab(tab_target, client_id) => {
+                match screen.tab_position_for_target(&tab_target) {
+                    Some(tab_position) => {
+                        screen.link_pane_to_tab(tab_position, client_id)?;
+                        screen.render(None)?;
+                    },
+                    None => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(
+                                vec![format!("No tab {}", tab_target)],
+                                client_id,
+                            ))?;
+                    },
                 }
-                screen.render(None)?;
             },
-            ScreenInstruction::WriteToPaneId(bytes, pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        tab.write_to_pane_id(&None, bytes, false, pane_id, None)
-                            .non_fatal();
-                        break;
-                    }
+            ScreenInstruction::MoveTabToIndex(tab_index, client_id) => {
+                if pending_tab_ids.is_empty() {
+                    screen.move_active_tab_to_position(tab_index.saturating_sub(1), client_id)?;
+                    screen.render(None)?;
+                } else {
+                    pending_events_waiting_for_tab
+                        .push(ScreenInstruction::MoveTabToIndex(tab_index, client_id));
                 }
-                screen.render(None)?;
+                screen.unblock_input()?;
             },
-            ScreenInstruction::MovePaneWithPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        tab.move_pane(pane_id);
-                        break;
-                    }
+            ScreenInstruction::UpdateAvailableVersions(installed_version, latest_release) => {
+                screen.update_available_versions(installed_version, latest_release)?;
+            },
+            ScreenInstruction::DeclarePort(port, name, pane_id) => {
+                screen.declare_port(port, name, pane_id);
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::UndeclarePort(port, pane_id) => {
+                screen.undeclare_port(port, pane_id);
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ListPorts(json, client_id) => {
+                let port_list = screen.render_port_list(json);
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::Log(vec![port_list], client_id))?;
+            },
+            ScreenInstruction::DumpSession(client_id) => {
+                let session_dump = screen.render_session_dump();
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::Log(vec![session_dump], client_id))?;
+            },
+            ScreenInstruction::SearchScrollback(pattern, backwards, pane_id, client_id) => {
+                match screen.scroll_pane_to_pattern(&pattern, backwards, pane_id, client_id) {
+                    Ok(match_count) => {
+                        screen.bus.senders.send_to_server(ServerInstruction::Log(
+                            vec![match_count.to_string()],
+                            client_id,
+                        ))?;
+                    },
+                    Err(e) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                    },
                 }
                 screen.render(None)?;
             },
-            ScreenInstruction::MovePaneWithPaneIdInDirection(pane_id, direction) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        match direction {
-                            Direction::Down => tab.move_pane_down(pane_id),
-                            Direction::Up => tab.move_pane_up(pane_id),
-                            Direction::Left => tab.move_pane_left(pane_id),
-                            Direction::Right => tab.move_pane_right(pane_id),
-                        }
-                        break;
-                    }
+            ScreenInstruction::SearchPanes(pattern, all_tabs, json, client_id) => {
+                match screen.render_pane_search_results(&pattern, all_tabs, json, client_id) {
+                    Ok(search_results) => {
+                        screen.bus.senders.send_to_server(ServerInstruction::Log(
+                            vec![search_results],
+                            client_id,
+                        ))?;
+                    },
+                    Err(e) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                    },
                 }
-                screen.render(None)?;
             },
-            ScreenInstruction::ClearScreenForPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        tab.clear_screen_for_pane_id(pane_id);
-                        break;
-                    }
+            ScreenInstruction::ScrollPaneToLine(line, pane_id, client_id) => {
+                match screen.get_pane_or_active_pane_mut(pane_id, client_id) {
+                    Some(pane) => pane.scroll_to_line(line),
+                    None => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(
+                                vec!["Pane not found".to_owned()],
+                                client_id,
+                            ))?;
+                    },
                 }
                 screen.render(None)?;
+                screen.unblock_input()?;
             },
-            ScreenInstruction::ScrollUpInPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
-                            tab.scroll_terminal_up(terminal_pane_id);
-                        } else {
-                            // this is because to do this with plugins, we need the client_id -
-                            // which we do not have (yet?) in this context...
-                            log::error!(
-                                "Currently only terminal panes are supported for scrolling up"
-                            );
-                        }
-                        break;
-                    }
+            ScreenInstruction::TogglePaneLogging(pane_id, client_id) => {
+                match screen.toggle_pane_output_log(pane_id, client_id) {
+                    Ok(message) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::Log(vec![message], client_id))?;
+                    },
+                    Err(e) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                    },
                 }
-                screen.render(None)?;
             },
-            ScreenInstruction::ScrollDownInPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
-                            tab.scroll_terminal_down(terminal_pane_id);
-                        } else {
-                            // this is because to do this with plugins, we need the client_id -
-                            // which we do not have (yet?) in this context...
-                            log::error!(
-                                "Currently only terminal panes are supported for scrolling down"
-                            );
-                        }
-                        break;
-                    }
+            ScreenInstruction::TogglePaneInGroup(group, pane_id, client_id) => {
+                match screen.toggle_pane_in_group(group, pane_id, client_id) {
+                    Ok(message) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::Log(vec![message], client_id))?;
+                    },
+                    Err(e) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                    },
                 }
                 screen.render(None)?;
             },
-            ScreenInstruction::ScrollToTopInPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
-                            tab.scroll_terminal_to_top(terminal_pane_id);
-                        } else {
-                            // this is because to do this with plugins, we need the client_id -
-                            // which we do not have (yet?) in this context...
-                            log::error!(
-                                "Currently only terminal panes are supported for scrolling to top"
-                            );
-                        }
-                        break;
-                    }
-                }
-                screen.render(None)?;
+            ScreenInstruction::PaneTitleChanged(pane_id, title) => {
+                screen.apply_application_title(pane_id, title)?;
             },
-            ScreenInstruction::ScrollToBottomInPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
-                            tab.scroll_terminal_to_bottom(terminal_pane_id);
-                        } else {
-                            // this is because to do this with plugins, we need the client_id -
-                            // which we do not have (yet?) in this context...
-                            log::error!("Currently only terminal panes are supported for scrolling to bottom");
-                        }
-                        break;
-                    }
+            ScreenInstruction::TogglePaneActivityMonitor(pane_id, should_notify, client_id) => {
+                match screen.toggle_pane_activity_monitor(pane_id, should_notify, client_id) {
+                    Ok(message) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::Log(vec![message], client_id))?;
+                    },
+                    Err(e) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                    },
                 }
-                screen.render(None)?;
+                screen.generate_and_report_tab_state()?;
             },
-            ScreenInstruction::PageScrollUpInPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
-                            tab.scroll_terminal_page_up(terminal_pane_id);
-                        } else {
-                            // this is because to do this with plugins, we need the client_id -
-                            // which we do not have (yet?) in this context...
-                            log::error!(
-                                "Currently only terminal panes are supported for scrolling"
-                            );
-                        }
-                        break;
-                    }
+            ScreenInstruction::TogglePaneSilenceMonitor(
+                pane_id,
+                seconds,
+                should_notify,
+                client_id,
+            ) => {
+                match screen.toggle_pane_silence_monitor(pane_id, seconds, should_notify, client_id)
+                {
+                    Ok(message) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::Log(vec![message], client_id))?;
+                    },
+                    Err(e) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                    },
                 }
+                screen.generate_and_report_tab_state()?;
+            },
+            ScreenInstruction::PaneMonitorAlert(pane_id, alert, should_notify) => {
+                screen.raise_pane_monitor_alert(pane_id, alert, should_notify)?;
                 screen.render(None)?;
             },
-            ScreenInstruction::PageScrollDownInPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        if let PaneId::Terminal(terminal_pane_id) = pane_id {
-                            tab.scroll_terminal_page_down(terminal_pane_id);
-                        } else {
-                            // this is because to do this with plugins, we need the client_id -
-                            // which we do not have (yet?) in this context...
-                            log::error!(
-                                "Currently only terminal panes are supported for scrolling"
-                            );
-                        }
-                        break;
-                    }
-                }
+            ScreenInstruction::PaneBell(pane_id) => {
+                screen.ring_bell_of_pane(pane_id)?;
                 screen.render(None)?;
             },
-            ScreenInstruction::TogglePaneIdFullscreen(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        tab.toggle_pane_fullscreen(pane_id);
-                        break;
-                    }
+            ScreenInstruction::CheckPaneSilence => {
+                if screen.check_pane_silence()? {
+                    screen.render(None)?;
                 }
-                screen.render(None)?;
             },
-            ScreenInstruction::TogglePaneEmbedOrEjectForPaneId(pane_id) => {
-                let all_tabs = screen.get_tabs_mut();
-                for tab in all_tabs.values_mut() {
-                    if tab.has_pane_with_pid(&pane_id) {
-                        tab.toggle_pane_embed_or_floating_for_pane_id(pane_id)
-                            .non_fatal();
-                        break;
-                    }
+            ScreenInstruction::ClientInput(client_id) => {
+                if screen.client_input(client_id) {
+                    screen.render(None)?;
                 }
-                screen.render(None)?;
             },
-            ScreenInstruction::CloseTabWithIndex(tab_index) => {
-                screen.close_tab_at_index(tab_index).non_fatal()
+            ScreenInstruction::CheckIdleClients => {
+                if screen.check_idle_clients()? {
+                    screen.render(None)?;
+                }
             },
-            ScreenInstruction::BreakPanesToNewTab {
-                pane_ids,
-                default_shell,
-                should_change_focus_to_new_tab,
-                new_tab_name,
-                client_id,
-            } => {
-                screen.break_multiple_panes_to_new_tab(
-                    pane_ids,
-                    default_shell,
-                    should_change_focus_to_new_tab,
-                    new_tab_name,
-                    client_id,
-                )?;
+            ScreenInstruction::CheckIdleSession => {
+                screen.check_idle_session()?;
             },
-            ScreenInstruction::BreakPanesToTabWithIndex {
-                pane_ids,
-                tab_index,
-                should_change_focus_to_new_tab,
-                client_id,
-            } => {
-                screen.break_multiple_panes_to_tab_with_index(
-                    pane_ids,
-                    tab_index,
-                    should_change_focus_to_new_tab,
-                    client_id,
-                )?;
+            ScreenInstruction::UpdateProcessStats(process_stats) => {
+                // a sample taken just before the option was turned off
+                if screen.pane_frame_process_stats {
+                    screen.update_process_stats(process_stats);
+                    screen.render(None)?;
+                }
             },
-            ScreenInstruction::TogglePanePinned(client_id) => {
-                screen.toggle_pane_pinned(client_id);
+            ScreenInstruction::UpdateRunningCommands(running_commands) => {
+                screen.running_commands = running_commands;
+                if screen.rename_tabs_automatically() {
+                    screen.log_and_report_session_state()?;
+                }
             },
-            ScreenInstruction::SetFloatingPanePinned(pane_id, should_be_pinned) => {
-                screen.set_floating_pane_pinned(pane_id, should_be_pinned);
+            ScreenInstruction::MultiExec(chars, group, exclude_focused, client_id) => {
+                match screen.multi_exec(chars, group, exclude_focused, client_id) {
+                    Ok(message) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::Log(vec![message], client_id))?;
+                    },
+                    Err(e) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                    },
+                }
+                screen.render(None)?;
             },
-            ScreenInstruction::StackPanes(pane_ids_to_stack) => {
-                screen.stack_panes(pane_ids_to_stack);
-                let _ = screen.unblock_input();
-                let _ = screen.render(None);
+            ScreenInstruction::ApplyToPaneGroup(group, action, client_id) => {
+                match screen.apply_to_pane_group(&group, action) {
+                    Ok(message) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::Log(vec![message], client_id))?;
+                    },
+                    Err(e) => {
+                        screen
+                            .bus
+                            .senders
+                            .send_to_server(ServerInstruction::LogError(vec![e], client_id))?;
+                    },
+                }
+                screen.render(None)?;
+                screen.log_and_report_session_state()?;
             },
-            ScreenInstruction::ChangeFloatingPanesCoordinates(pane_ids_and_coordinates) => {
-                screen.change_floating_panes_coordinates(pane_ids_and_coordinates);
-                let _ = screen.unblock_input();
-                let _ = screen.render(None);
+            ScreenInstruction::PublishSessionEvents(should_publish) => {
+                screen.publish_session_events = should_publish;
+                if should_publish {
+                    // so that listeners start out with the current tabs and panes
+                    screen.log_and_report_session_state()?;
+                }
             },
         }
     }
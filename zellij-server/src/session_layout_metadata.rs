@@ -1,9 +1,9 @@
 use crate::panes::PaneId;
 use crate::ClientId;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use zellij_utils::common_path::common_path_all;
-use zellij_utils::pane_size::PaneGeom;
+use zellij_utils::pane_size::{PaneGeom, Size};
 use zellij_utils::{
     input::command::RunCommand,
     input::layout::{Layout, Run, RunPlugin, RunPluginOrAlias},
@@ -59,32 +59,9 @@ impl SessionLayoutMetadata {
             }
         }
     }
-    pub fn list_clients_metadata(&self) -> String {
-        let mut clients_metadata: BTreeMap<ClientId, ClientMetadata> = BTreeMap::new();
-        for tab in &self.tabs {
-            let panes = if tab.hide_floating_panes {
-                &tab.tiled_panes
-            } else {
-                &tab.floating_panes
-            };
-            for pane in panes {
-                for focused_client in &pane.focused_clients {
-                    clients_metadata.insert(
-                        *focused_client,
-                        ClientMetadata {
-                            pane_id: pane.id.clone(),
-                            command: pane.run.clone(),
-                        },
-                    );
-                }
-            }
-        }
-
-        ClientMetadata::render_many(clients_metadata, &self.default_editor)
-    }
     pub fn all_clients_metadata(&self) -> BTreeMap<ClientId, ClientMetadata> {
         let mut clients_metadata: BTreeMap<ClientId, ClientMetadata> = BTreeMap::new();
-        for tab in &self.tabs {
+        for (tab_position, tab) in self.tabs.iter().enumerate() {
             let panes = if tab.hide_floating_panes {
                 &tab.tiled_panes
             } else {
@@ -97,6 +74,8 @@ impl SessionLayoutMetadata {
                         ClientMetadata {
                             pane_id: pane.id.clone(),
                             command: pane.run.clone(),
+                            tab_position,
+                            tab_name: tab.name.clone(),
                         },
                     );
                 }
@@ -188,12 +167,12 @@ impl SessionLayoutMetadata {
         let mut terminal_ids = vec![];
         for tab in &self.tabs {
             for pane_layout_metadata in &tab.tiled_panes {
-                if let PaneId::Terminal(id) = pane_layout_metadata.id {
+                if let Some(id) = pane_layout_metadata.terminal_id() {
                     terminal_ids.push(id);
                 }
             }
             for pane_layout_metadata in &tab.floating_panes {
-                if let PaneId::Terminal(id) = pane_layout_metadata.id {
+                if let Some(id) = pane_layout_metadata.terminal_id() {
                     terminal_ids.push(id);
                 }
             }
@@ -221,7 +200,7 @@ impl SessionLayoutMetadata {
         mut terminal_ids_to_commands: HashMap<u32, Vec<String>>,
     ) {
         let mut update_cmd_in_pane_metadata = |pane_layout_metadata: &mut PaneLayoutMetadata| {
-            if let PaneId::Terminal(id) = pane_layout_metadata.id {
+            if let Some(id) = pane_layout_metadata.terminal_id() {
                 if let Some(command) = terminal_ids_to_commands.remove(&id) {
                     let mut command_line = command.iter();
                     if let Some(command_name) = command_line.next() {
@@ -259,7 +238,7 @@ impl SessionLayoutMetadata {
             self.global_cwd = Some(PathBuf::from(common_path_between_cwds));
         }
         let mut update_cwd_in_pane_metadata = |pane_layout_metadata: &mut PaneLayoutMetadata| {
-            if let PaneId::Terminal(id) = pane_layout_metadata.id {
+            if let Some(id) = pane_layout_metadata.terminal_id() {
                 if let Some(cwd) = terminal_ids_to_cwds.remove(&id) {
                     pane_layout_metadata.cwd = Some(cwd);
                 }
@@ -342,6 +321,7 @@ impl Into<PaneLayoutManifest> for PaneLayoutMetadata {
             is_borderless: self.is_borderless,
             title: self.title,
             is_focused: self.is_focused,
+            is_fullscreen: self.is_fullscreen,
             pane_contents: self.pane_contents,
         }
     }
@@ -365,8 +345,10 @@ pub struct PaneLayoutMetadata {
     is_borderless: bool,
     title: Option<String>,
     is_focused: bool,
+    is_fullscreen: bool,
     pane_contents: Option<String>,
     focused_clients: Vec<ClientId>,
+    is_linked_view: bool,
 }
 
 impl PaneLayoutMetadata {
@@ -377,6 +359,7 @@ impl PaneLayoutMetadata {
         run: Option<Run>,
         title: Option<String>,
         is_focused: bool,
+        is_fullscreen: bool,
         pane_contents: Option<String>,
         focused_clients: Vec<ClientId>,
     ) -> Self {
@@ -388,15 +371,51 @@ impl PaneLayoutMetadata {
             is_borderless,
             title,
             is_focused,
+            is_fullscreen,
             pane_contents,
             focused_clients,
+            is_linked_view: false,
+        }
+    }
+    /// The place of a linked view in the layout of its tab, which is serialized as an empty pane:
+    /// the pane it shows is serialized in its own tab
+    pub fn new_linked_view(
+        id: PaneId,
+        geom: PaneGeom,
+        is_borderless: bool,
+        is_focused: bool,
+        focused_clients: Vec<ClientId>,
+    ) -> Self {
+        PaneLayoutMetadata {
+            is_linked_view: true,
+            ..PaneLayoutMetadata::new(
+                id,
+                geom,
+                is_borderless,
+                None,
+                None,
+                is_focused,
+                false,
+                None,
+                focused_clients,
+            )
+        }
+    }
+    // the id of the terminal whose command and cwd are serialized with this pane
+    fn terminal_id(&self) -> Option<u32> {
+        match self.id {
+            PaneId::Terminal(id) if !self.is_linked_view => Some(id),
+            _ => None,
         }
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct ClientMetadata {
     pane_id: PaneId,
     command: Option<Run>,
+    tab_position: usize,
+    tab_name: Option<String>,
 }
 impl ClientMetadata {
     pub fn stringify_pane_id(&self) -> String {
@@ -431,17 +450,34 @@ impl ClientMetadata {
     }
     pub fn render_many(
         clients_metadata: BTreeMap<ClientId, ClientMetadata>,
+        client_sizes: &HashMap<ClientId, Size>,
+        read_only_clients: &HashSet<ClientId>,
         default_editor: &Option<PathBuf>,
     ) -> String {
         let mut lines = vec![];
-        lines.push(String::from("CLIENT_ID ZELLIJ_PANE_ID RUNNING_COMMAND"));
+        lines.push(String::from(
+            "CLIENT_ID ZELLIJ_PANE_ID TAB_INDEX TERMINAL_SIZE ROLE   RUNNING_COMMAND",
+        ));
 
         for (client_id, client_metadata) in clients_metadata.iter() {
-            // 9 - CLIENT_ID, 14 - ZELLIJ_PANE_ID, 15 - RUNNING_COMMAND
+            // 9 - CLIENT_ID, 14 - ZELLIJ_PANE_ID, 9 - TAB_INDEX, 13 - TERMINAL_SIZE, 6 - ROLE, 15 - RUNNING_COMMAND
+            let terminal_size = client_sizes
+                .get(client_id)
+                .map(|size| format!("{}x{}", size.cols, size.rows))
+                .unwrap_or_else(|| "N/A".to_owned());
+            let role = if read_only_clients.contains(client_id) {
+                "viewer"
+            } else {
+                "editor"
+            };
             lines.push(format!(
-                "{} {} {}",
+                "{} {} {} {} {} {}",
                 format!("{0: <9}", client_id),
                 format!("{0: <14}", client_metadata.stringify_pane_id()),
+                // tab indices are 1 based, like in go-to-tab
+                format!("{0: <9}", client_metadata.tab_position + 1),
+                format!("{0: <13}", terminal_size),
+                format!("{0: <6}", role),
                 format!(
                     "{0: <15}",
                     client_metadata.stringify_command(default_editor)
@@ -450,4 +486,33 @@ impl ClientMetadata {
         }
         lines.join("\n")
     }
+    pub fn render_many_as_json(
+        clients_metadata: BTreeMap<ClientId, ClientMetadata>,
+        client_sizes: &HashMap<ClientId, Size>,
+        read_only_clients: &HashSet<ClientId>,
+        default_editor: &Option<PathBuf>,
+    ) -> String {
+        let clients: Vec<serde_json::Value> = clients_metadata
+            .iter()
+            .map(|(client_id, client_metadata)| {
+                serde_json::json!({
+                    "client_id": client_id,
+                    "pane_id": client_metadata.stringify_pane_id(),
+                    "tab_index": client_metadata.tab_position + 1,
+                    "tab_name": client_metadata.tab_name,
+                    "terminal_size": client_sizes.get(client_id).map(|size| serde_json::json!({
+                        "rows": size.rows,
+                        "cols": size.cols,
+                    })),
+                    "running_command": client_metadata.stringify_command(default_editor),
+                    "read_only": read_only_clients.contains(client_id),
+                })
+            })
+            .collect();
+        serde_json::Value::Array(clients).to_string()
+    }
 }
+
+#[cfg(test)]
+#[path = "./unit/session_layout_metadata_tests.rs"]
+mod session_layout_metadata_tests;
@@ -1,6 +1,10 @@
-use zellij_utils::{anyhow::Result, data::CopyDestination, input::options::Clipboard};
+use zellij_utils::{
+    anyhow::{anyhow, Result},
+    data::CopyDestination,
+    input::options::{Clipboard, CopyProvider},
+};
 
-use crate::ClientId;
+use crate::{screen::CopyOptions, ClientId};
 
 use super::{copy_command::CopyCommand, Output};
 
@@ -47,3 +51,72 @@ impl ClipboardProvider {
         }
     }
 }
+
+/// The clipboard providers copied text is handed to, in order, until one of them takes it
+pub(crate) struct ClipboardChain {
+    providers: Vec<ClipboardProvider>,
+}
+
+impl ClipboardChain {
+    pub(crate) fn new(copy_options: &CopyOptions) -> Self {
+        let mut providers = vec![];
+        if let Some(command) = &copy_options.command {
+            providers.push(ClipboardProvider::Command(CopyCommand::new(
+                command.clone(),
+            )));
+        }
+        for copy_provider in &copy_options.providers {
+            if let Err(reason) = copy_provider.availability() {
+                log::info!(
+                    "Skipping clipboard provider {}: {}",
+                    copy_provider.as_str(),
+                    reason
+                );
+                continue;
+            }
+            providers.push(match copy_provider.command(copy_options.clipboard) {
+                Some(command) => ClipboardProvider::Command(CopyCommand::new(command.to_owned())),
+                None => ClipboardProvider::Osc52(copy_options.clipboard),
+            });
+        }
+        if providers.is_empty() {
+            providers.push(ClipboardProvider::Osc52(copy_options.clipboard));
+        }
+        ClipboardChain { providers }
+    }
+    pub(crate) fn osc52(clipboard: Clipboard) -> Self {
+        ClipboardChain {
+            providers: vec![ClipboardProvider::Osc52(clipboard)],
+        }
+    }
+    /// Whether some of the providers copy through a command run on the server host
+    pub(crate) fn runs_commands(&self) -> bool {
+        self.providers
+            .iter()
+            .any(|provider| matches!(provider, ClipboardProvider::Command(_)))
+    }
+    /// Returns the destination of the provider that took the content
+    pub(crate) fn set_content(
+        &self,
+        content: &str,
+        output: &mut Output,
+        client_ids: impl Iterator<Item = ClientId>,
+    ) -> Result<CopyDestination> {
+        let client_ids: Vec<ClientId> = client_ids.collect();
+        let mut last_error = None;
+        for provider in &self.providers {
+            match provider.set_content(content, output, client_ids.iter().copied()) {
+                Ok(()) => return Ok(provider.as_copy_destination()),
+                Err(err) => {
+                    log::warn!("Clipboard provider failed, trying the next one: {:?}", err);
+                    last_error = Some(err);
+                },
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow!("no clipboard provider")))
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/clipboard_tests.rs"]
+mod clipboard_tests;
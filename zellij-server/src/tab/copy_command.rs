@@ -1,7 +1,11 @@
 use std::io::prelude::*;
 use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
-use zellij_utils::anyhow::{Context, Result};
+use zellij_utils::anyhow::{anyhow, Context, Result};
+
+// commands that stay around to serve the clipboard (eg. xclip) are not waited for any longer
+const EXIT_TIMEOUT: Duration = Duration::from_millis(500);
 
 pub struct CopyCommand {
     command: String,
@@ -18,17 +22,34 @@ impl CopyCommand {
         }
     }
     pub fn set(&self, value: String) -> Result<()> {
-        let process = Command::new(self.command.clone())
+        let mut process = Command::new(self.command.clone())
             .args(self.args.clone())
             .stdin(Stdio::piped())
             .spawn()
             .with_context(|| format!("couldn't spawn {}", self.command))?;
+        // stdin is closed once it is dropped here, so that the command knows it has all of the
+        // value
         process
             .stdin
+            .take()
             .context("could not get stdin")?
             .write_all(value.as_bytes())
             .with_context(|| format!("couldn't write to {} stdin", self.command))?;
 
-        Ok(())
+        let started_waiting = Instant::now();
+        loop {
+            match process
+                .try_wait()
+                .with_context(|| format!("couldn't wait for {}", self.command))?
+            {
+                Some(status) if status.success() => return Ok(()),
+                Some(status) => return Err(anyhow!("{} failed: {}", self.command, status)),
+                None if started_waiting.elapsed() >= EXIT_TIMEOUT => {
+                    std::thread::spawn(move || process.wait());
+                    return Ok(());
+                },
+                None => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
     }
 }
@@ -6,8 +6,8 @@ use crate::tab::{get_next_terminal_position, HoldForCommand, Pane};
 use crate::{
     os_input_output::ServerOsApi,
     panes::sixel::SixelImageStore,
+    panes::{AlternateScreenBehavior, LinkHandler, PaneId, PluginPane, TerminalPane, UnicodeWidth},
     panes::{FloatingPanes, TiledPanes},
-    panes::{LinkHandler, PaneId, PluginPane, TerminalPane},
     plugins::PluginInstruction,
     pty::PtyInstruction,
     thread_bus::ThreadSenders,
@@ -15,10 +15,11 @@ use crate::{
 };
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
 use std::rc::Rc;
 use zellij_utils::{
     data::{Palette, Style},
-    input::layout::{FloatingPaneLayout, Run, RunPluginOrAlias, TiledPaneLayout},
+    input::layout::{FloatingPaneLayout, Run, RunPluginOrAlias, ScrollbackSize, TiledPaneLayout},
     pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
 };
 
@@ -42,6 +43,9 @@ pub struct LayoutApplier<'a> {
     arrow_fonts: bool,
     styled_underlines: bool,
     explicitly_disable_kitty_keyboard_protocol: bool,
+    alternate_screen_behavior: AlternateScreenBehavior,
+    unicode_width: UnicodeWidth,
+    pane_logs_folder: PathBuf,
 }
 
 impl<'a> LayoutApplier<'a> {
@@ -65,6 +69,9 @@ impl<'a> LayoutApplier<'a> {
         arrow_fonts: bool,
         styled_underlines: bool,
         explicitly_disable_kitty_keyboard_protocol: bool,
+        alternate_screen_behavior: AlternateScreenBehavior,
+        unicode_width: UnicodeWidth,
+        pane_logs_folder: PathBuf,
     ) -> Self {
         let viewport = viewport.clone();
         let senders = senders.clone();
@@ -97,6 +104,9 @@ impl<'a> LayoutApplier<'a> {
             arrow_fonts,
             styled_underlines,
             explicitly_disable_kitty_keyboard_protocol,
+            alternate_screen_behavior,
+            unicode_width,
+            pane_logs_folder,
         }
     }
     pub fn apply_layout(
@@ -240,7 +250,7 @@ impl<'a> LayoutApplier<'a> {
             &layout.run_instructions_to_ignore,
             &mut positions_in_layout,
         );
-        let focus_pane_id = self.position_new_panes(
+        let (focus_pane_id, fullscreen_pane_id) = self.position_new_panes(
             &mut new_terminal_ids,
             &mut new_plugin_ids,
             &mut positions_in_layout,
@@ -250,7 +260,11 @@ impl<'a> LayoutApplier<'a> {
             &mut new_terminal_ids,
         );
         self.adjust_viewport().with_context(err_context)?;
-        self.set_focused_tiled_pane(focus_pane_id, client_id);
+        // a fullscreen pane is focused, otherwise focusing another pane would undo the fullscreen
+        self.set_focused_tiled_pane(fullscreen_pane_id.or(focus_pane_id), client_id);
+        if let Some(fullscreen_pane_id) = fullscreen_pane_id {
+            self.tiled_panes.toggle_pane_fullscreen(fullscreen_pane_id);
+        }
         Ok(())
     }
     fn position_run_instructions_to_ignore(
@@ -285,15 +299,20 @@ impl<'a> LayoutApplier<'a> {
         new_terminal_ids: &mut Vec<(u32, HoldForCommand)>,
         new_plugin_ids: &mut HashMap<RunPluginOrAlias, Vec<u32>>,
         positions_in_layout: &mut Vec<(TiledPaneLayout, PaneGeom)>,
-    ) -> Result<Option<PaneId>> {
+    ) -> Result<(Option<PaneId>, Option<PaneId>)> {
         // here we open new panes for each run instruction in the layout with the details
         // we got from the plugin thread and pty thread
         // let positions_and_size = positions_in_layout.iter();
+        // returns the panes to focus and to make fullscreen
         let mut focus_pane_id: Option<PaneId> = None;
+        let mut fullscreen_pane_id: Option<PaneId> = None;
         let mut set_focus_pane_id = |layout: &TiledPaneLayout, pane_id: PaneId| {
             if layout.focus.unwrap_or(false) && focus_pane_id.is_none() {
                 focus_pane_id = Some(pane_id);
             }
+            if layout.fullscreen && fullscreen_pane_id.is_none() {
+                fullscreen_pane_id = Some(pane_id);
+            }
         };
         for (layout, position_and_size) in positions_in_layout {
             if let Some(Run::Plugin(run)) = layout.run.clone() {
@@ -307,7 +326,7 @@ impl<'a> LayoutApplier<'a> {
                 set_focus_pane_id(&layout, PaneId::Terminal(pid));
             }
         }
-        Ok(focus_pane_id)
+        Ok((focus_pane_id, fullscreen_pane_id))
     }
     fn handle_run_instructions_without_a_location(
         &mut self,
@@ -365,6 +384,7 @@ impl<'a> LayoutApplier<'a> {
         }
 
         new_plugin.set_borderless(layout.borderless);
+        new_plugin.set_size_limits(layout.size_limits);
         if let Some(exclude_from_sync) = layout.exclude_from_sync {
             new_plugin.set_exclude_from_sync(exclude_from_sync);
         }
@@ -459,10 +479,21 @@ impl<'a> LayoutApplier<'a> {
             self.styled_underlines,
             self.explicitly_disable_kitty_keyboard_protocol,
         );
+        if let Some(scrollback_size) = floating_pane_layout.scrollback_size {
+            self.set_scrollback_size(&mut new_pane, scrollback_size);
+        }
+        new_pane.set_alternate_screen_behavior(self.alternate_screen_behavior.with_overrides(
+            floating_pane_layout.alternate_screen_scrollback,
+            floating_pane_layout.clear_on_alternate_screen_exit,
+        ));
+        new_pane.update_unicode_width(self.unicode_width);
         if let Some(pane_initial_contents) = &floating_pane_layout.pane_initial_contents {
             new_pane.handle_pty_bytes(pane_initial_contents.as_bytes().into());
             new_pane.handle_pty_bytes("\n\r".as_bytes().into());
         }
+        if floating_pane_layout.log {
+            self.start_output_log(&mut new_pane);
+        }
         new_pane.set_borderless(false);
         new_pane.set_content_offset(Offset::frame(1));
         if let Some(held_command) = hold_for_command {
@@ -512,11 +543,23 @@ impl<'a> LayoutApplier<'a> {
             self.styled_underlines,
             self.explicitly_disable_kitty_keyboard_protocol,
         );
+        if let Some(scrollback_size) = layout.scrollback_size {
+            self.set_scrollback_size(&mut new_pane, scrollback_size);
+        }
+        new_pane.set_alternate_screen_behavior(self.alternate_screen_behavior.with_overrides(
+            layout.alternate_screen_scrollback,
+            layout.clear_on_alternate_screen_exit,
+        ));
+        new_pane.update_unicode_width(self.unicode_width);
         if let Some(pane_initial_contents) = &layout.pane_initial_contents {
             new_pane.handle_pty_bytes(pane_initial_contents.as_bytes().into());
             new_pane.handle_pty_bytes("\n\r".as_bytes().into());
         }
+        if layout.log {
+            self.start_output_log(&mut new_pane);
+        }
         new_pane.set_borderless(layout.borderless);
+        new_pane.set_size_limits(layout.size_limits);
         if let Some(exclude_from_sync) = layout.exclude_from_sync {
             new_pane.set_exclude_from_sync(exclude_from_sync);
         }
@@ -527,6 +570,18 @@ impl<'a> LayoutApplier<'a> {
             .add_pane_with_existing_geom(PaneId::Terminal(pid), Box::new(new_pane));
         Ok(())
     }
+    fn set_scrollback_size(&self, pane: &mut TerminalPane, scrollback_size: ScrollbackSize) {
+        // the pane falls back to the scroll_buffer_size of the config
+        if let Err(e) = pane.set_scrollback_size(scrollback_size) {
+            log::error!("Failed to set the scrollback size of the pane: {:?}", e);
+        }
+    }
+    fn start_output_log(&self, pane: &mut TerminalPane) {
+        // a pane that cannot be logged is still better than no pane at all
+        if let Err(e) = pane.start_output_log(&self.pane_logs_folder) {
+            log::error!("Failed to log the output of the pane: {:?}", e);
+        }
+    }
     fn place_running_pane_in_exact_match_location(
         &mut self,
         run_instruction: &Option<Run>,
@@ -543,6 +598,7 @@ impl<'a> LayoutApplier<'a> {
                 layout.run,
                 position_and_size,
                 layout.borderless,
+                layout.size_limits,
             );
             found_exact_match = true;
         }
@@ -563,6 +619,7 @@ impl<'a> LayoutApplier<'a> {
                 run_instruction.clone(),
                 position_and_size,
                 layout.borderless,
+                layout.size_limits,
             );
             found_empty_location = true;
         }
@@ -1051,6 +1108,7 @@ impl<'a> PaneApplier<'a> {
             pane.set_geom(position_and_size);
         }
         pane.set_borderless(layout.borderless);
+        pane.set_size_limits(layout.size_limits);
         if let Some(pane_title) = layout.name.as_ref() {
             pane.set_title(pane_title.into());
         }
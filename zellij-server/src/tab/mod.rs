@@ -6,10 +6,10 @@ mod copy_command;
 mod layout_applier;
 mod swap_layouts;
 
-use copy_command::CopyCommand;
 use std::env::temp_dir;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
+use zellij_utils::consts::pane_logs_folder_for_session;
 use zellij_utils::data::{
     Direction, KeyWithModifier, PaneInfo, PermissionStatus, PermissionType, PluginPermission,
     ResizeStrategy,
@@ -18,23 +18,30 @@ use zellij_utils::errors::prelude::*;
 use zellij_utils::input::command::RunCommand;
 use zellij_utils::input::mouse::{MouseEvent, MouseEventType};
 use zellij_utils::position::{Column, Line};
+use zellij_utils::regex::Regex;
 use zellij_utils::{position::Position, serde};
 
 use crate::background_jobs::BackgroundJob;
 use crate::pty_writer::PtyWriteInstruction;
-use crate::screen::CopyOptions;
+use crate::screen::{CopyOptions, ScreenInstruction};
 use crate::ui::{loading_indication::LoadingIndication, pane_boundaries_frame::FrameParams};
 use layout_applier::LayoutApplier;
 use swap_layouts::SwapLayouts;
 
-use self::clipboard::ClipboardProvider;
+use self::clipboard::ClipboardChain;
 use crate::{
-    os_input_output::ServerOsApi,
+    os_input_output::{ProcessStats, ServerOsApi},
     output::{CharacterChunk, Output, SixelImageChunk},
+    panes::closed_panes::{ClosedPane, ClosedPanes, CLOSED_PANE_SCROLLBACK_LINES},
+    panes::copy_mode::CopyModeOutcome,
     panes::floating_panes::floating_pane_grid::half_size_middle_geom,
+    panes::output_monitor::MonitorAlert,
     panes::sixel::SixelImageStore,
+    panes::{
+        AlternateScreenBehavior, LinkHandler, PaneId, PluginPane, PortDeclaration, TerminalPane,
+        UnicodeWidth,
+    },
     panes::{FloatingPanes, TiledPanes},
-    panes::{LinkHandler, PaneId, PluginPane, TerminalPane},
     plugins::PluginInstruction,
     pty::{ClientTabIndexOrPaneId, PtyInstruction, VteBytes},
     thread_bus::ThreadSenders,
@@ -43,7 +50,7 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{
     collections::{HashMap, HashSet},
     str,
@@ -55,9 +62,10 @@ use zellij_utils::{
     input::{
         command::TerminalAction,
         layout::{
-            FloatingPaneLayout, Run, RunPluginOrAlias, SwapFloatingLayout, SwapTiledLayout,
-            TiledPaneLayout,
+            FloatingPaneLayout, Run, RunPluginOrAlias, SizeLimits, SwapFloatingLayout,
+            SwapTiledLayout, TiledPaneLayout,
         },
+        options::{Clipboard, CommandExitBanner},
         parse_keys,
     },
     pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
@@ -105,6 +113,8 @@ macro_rules! resize_pty {
             }
         };
         match $pane.pid() {
+            // the pty keeps the size of the pane it is a view of
+            PaneId::Terminal(_) if $pane.is_linked_view() => Ok(()),
             PaneId::Terminal(ref pid) => {
                 use crate::PtyWriteInstruction;
                 let err_context = || format!("Failed to send resize pty instruction");
@@ -179,6 +189,15 @@ pub(crate) struct Tab {
     pub position: usize,
     pub name: String,
     pub prev_name: String,
+    // the name zellij gave the tab (its default name or a title of its focused pane), if the user
+    // has not renamed it since
+    automatic_name: Option<String>,
+    // panes that rang the bell while they were not focused, until they are
+    panes_with_bell: HashSet<PaneId>,
+    // command panes whose command exited while the tab was not active, until they are focused
+    panes_with_exited_command: HashSet<PaneId>,
+    // terminal panes the user closed, so that they can be restored
+    closed_panes: ClosedPanes,
     tiled_panes: TiledPanes,
     floating_panes: FloatingPanes,
     suppressed_panes: SuppressedPanes,
@@ -191,6 +210,7 @@ pub(crate) struct Tab {
     pub senders: ThreadSenders,
     synchronize_is_active: bool,
     should_clear_display_before_rendering: bool,
+    should_delete_images_before_rendering: bool,
     mode_info: Rc<RefCell<HashMap<ClientId, ModeInfo>>>,
     default_mode_info: ModeInfo,
     pub style: Style,
@@ -199,12 +219,15 @@ pub(crate) struct Tab {
     auto_layout: bool,
     pending_vte_events: HashMap<u32, Vec<VteBytes>>,
     pub selecting_with_mouse_in_pane: Option<PaneId>, // this is only pub for the tests
+    pane_being_resized_with_mouse: Option<(PaneId, Vec<Direction>)>, // the edges being dragged
     link_handler: Rc<RefCell<LinkHandler>>,
-    clipboard_provider: ClipboardProvider,
+    clipboard_chain: ClipboardChain,
     // TODO: used only to focus the pane when the layout is loaded
     // it seems that optimization is possible using `active_panes`
     focus_pane_id: Option<PaneId>,
     copy_on_select: bool,
+    osc52_forwarding: bool,
+    word_separators: String,
     terminal_emulator_colors: Rc<RefCell<Palette>>,
     terminal_emulator_color_codes: Rc<RefCell<HashMap<usize, String>>>,
     pids_waiting_resize: HashSet<u32>, // u32 is the terminal_id
@@ -221,6 +244,8 @@ pub(crate) struct Tab {
     arrow_fonts: bool,
     styled_underlines: bool,
     explicitly_disable_kitty_keyboard_protocol: bool,
+    alternate_screen_behavior: AlternateScreenBehavior,
+    unicode_width: UnicodeWidth,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -233,6 +258,13 @@ pub(crate) struct TabData {
     pub colors: Styling,
 }
 
+/// A named group of panes that actions can be applied to all at once
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaneGroup {
+    pub name: String,
+    pub color: Option<PaletteColor>,
+}
+
 // FIXME: Use a struct that has a pane_type enum, to reduce all of the duplication
 pub trait Pane {
     fn x(&self) -> usize;
@@ -300,7 +332,37 @@ pub trait Pane {
     fn dump_screen(&self, _full: bool) -> String {
         "".to_owned()
     }
+    fn dump_screen_with_styles(&self, _full: bool) -> String {
+        "".to_owned()
+    }
+    /// Starts writing everything the pane outputs to a new file in `log_folder`, returning its
+    /// path, or None for panes that do not have such output
+    fn start_output_log(&mut self, _log_folder: &Path) -> Result<Option<PathBuf>> {
+        Ok(None)
+    }
+    /// Returns the path of the file the pane's output was written to, if it was
+    fn stop_output_log(&mut self) -> Option<PathBuf> {
+        None
+    }
+    fn set_group(&mut self, _group: Option<PaneGroup>) {}
+    fn group(&self) -> Option<&PaneGroup> {
+        None
+    }
     fn scroll_up(&mut self, count: usize, client_id: ClientId);
+    /// Returns how many matches of the pattern there are, or None for panes without a scrollback
+    fn scroll_to_pattern(&mut self, _pattern: &Regex, _backwards: bool) -> Option<usize> {
+        None
+    }
+    /// The number and text of the lines of the scrollback matching the pattern
+    fn find_pattern(&self, _pattern: &Regex) -> Vec<(usize, String)> {
+        vec![]
+    }
+    /// Moves the viewport to this line of the scrollback, as numbered by find_pattern
+    fn scroll_to_line(&mut self, _line_number: usize) {}
+    /// Moves the viewport to the previous (or next) prompt marked by the shell (OSC 133)
+    fn scroll_to_prompt(&mut self, _backwards: bool) {}
+    /// Selects the output of the last command marked by the shell (OSC 133)
+    fn select_last_command_output(&mut self) {}
     fn scroll_down(&mut self, count: usize, client_id: ClientId);
     fn clear_scroll(&mut self);
     fn is_scrolled(&self) -> bool;
@@ -324,6 +386,14 @@ pub trait Pane {
     fn get_selected_text(&self) -> Option<String> {
         None
     }
+    fn copy_mode_input(&mut self, _key: char) -> CopyModeOutcome {
+        // only terminal panes have a keyboard selection
+        CopyModeOutcome::Exit
+    }
+    fn copy_mode_is_active(&self) -> bool {
+        false
+    }
+    fn exit_copy_mode(&mut self) {}
 
     fn right_boundary_x_coords(&self) -> usize {
         self.x() + self.cols()
@@ -394,10 +464,62 @@ pub trait Pane {
         // we should probably refactor away from this trait at some point
         vec![]
     }
-    fn drain_clipboard_update(&mut self) -> Option<String> {
+    fn drain_clipboard_update(&mut self) -> Option<(Clipboard, String)> {
+        None
+    }
+    fn drain_reported_cwd(&mut self) -> Option<PathBuf> {
+        None
+    }
+    /// The cwd last reported by the shell running in the pane, if any
+    fn reported_cwd(&self) -> Option<PathBuf> {
+        None
+    }
+    fn set_process_stats(&mut self, _process_stats: Option<ProcessStats>) {} // only relevant to terminal panes
+    /// The title the program running in the pane set (OSC 0/2), if any
+    fn application_title(&self) -> Option<String> {
+        None
+    }
+    fn drain_port_declarations(&mut self) -> Vec<PortDeclaration> {
+        vec![]
+    }
+    /// The title the program running in the pane set since this was last called, if any
+    fn drain_title_update(&mut self) -> Option<String> {
+        None
+    }
+    /// Whether the program running in the pane rang the bell since this was last called
+    fn drain_bell(&mut self) -> bool {
+        false
+    }
+    /// Returns whether the pane's activity is monitored after the toggle, or None for panes that
+    /// cannot be monitored
+    fn toggle_activity_monitor(&mut self, _should_notify: bool) -> Option<bool> {
         None
     }
+    /// Returns whether the pane's silence is monitored after the toggle, or None for panes that
+    /// cannot be monitored
+    fn toggle_silence_monitor(
+        &mut self,
+        _interval: Duration,
+        _should_notify: bool,
+    ) -> Option<bool> {
+        None
+    }
+    fn check_silence(&mut self, _now: Instant) {}
+    /// The monitor alert raised since this was last called, if any, and whether it should be
+    /// sent as a desktop notification
+    fn drain_monitor_alert(&mut self) -> Option<(MonitorAlert, bool)> {
+        None
+    }
+    fn monitor_alert(&self) -> Option<MonitorAlert> {
+        None
+    }
+    /// Returns whether there was an alert to clear
+    fn clear_monitor_alert(&mut self) -> bool {
+        false
+    }
     fn render_full_viewport(&mut self) {}
+    // the geometries of the floating panes above this pane
+    fn set_covered_areas(&mut self, _covered_areas: Vec<PaneGeom>) {}
     fn relative_position(&self, position_on_screen: &Position) -> Position {
         position_on_screen.relative_to(self.get_content_y(), self.get_content_x())
     }
@@ -428,6 +550,24 @@ pub trait Pane {
         }
         false
     }
+    /// The edges of the pane's frame the position is on, two of them on a corner
+    fn frame_edges_at(&self, position: &Position) -> Vec<Direction> {
+        let mut edges = vec![];
+        if !self.position_is_on_frame(position) {
+            return edges;
+        }
+        if position.column() == self.x() {
+            edges.push(Direction::Left);
+        } else if position.column() + 1 == self.x() + self.cols() {
+            edges.push(Direction::Right);
+        }
+        if position.line() == self.y() as isize {
+            edges.push(Direction::Up);
+        } else if position.line() + 1 == (self.y() + self.rows()) as isize {
+            edges.push(Direction::Down);
+        }
+        edges
+    }
     // TODO: get rid of this in favor of intercept_mouse_event_on_frame
     fn intercept_left_mouse_click(&mut self, _position: &Position, _client_id: ClientId) -> bool {
         let intercepted = false;
@@ -447,6 +587,10 @@ pub trait Pane {
     fn borderless(&self) -> bool;
     fn set_exclude_from_sync(&mut self, exclude_from_sync: bool);
     fn exclude_from_sync(&self) -> bool;
+    fn set_size_limits(&mut self, _size_limits: Option<SizeLimits>) {}
+    fn size_limits(&self) -> Option<SizeLimits> {
+        None
+    }
 
     // TODO: this should probably be merged with the mouse_right_click
     fn handle_right_click(&mut self, _to: &Position, _client_id: ClientId) {}
@@ -504,6 +648,9 @@ pub trait Pane {
     fn toggle_search_wrap(&mut self) {
         // No-op by default (only terminal-panes currently have search capability)
     }
+    fn toggle_search_regex(&mut self) {
+        // No-op by default (only terminal-panes currently have search capability)
+    }
     fn clear_search(&mut self) {
         // No-op by default (only terminal-panes currently have search capability)
     }
@@ -527,12 +674,28 @@ pub trait Pane {
     fn is_held(&self) -> bool {
         false
     }
+    /// A view of the pty of a pane in another tab: it is rendered from the same output, but does
+    /// not resize, answer or close that pty
+    fn is_linked_view(&self) -> bool {
+        false
+    }
     fn exited(&self) -> bool {
         false
     }
     fn exit_status(&self) -> Option<i32> {
         None
     }
+    fn command_runtime(&self) -> Option<Duration> {
+        // how long the command of an exited command pane ran
+        None
+    }
+    fn set_command_exit_banner(&mut self, _command_exit_banner: CommandExitBanner) {} // only relevant to terminal panes
+    fn scrollback_length(&self) -> usize {
+        0
+    }
+    fn scrollback_memory_usage(&self) -> usize {
+        0
+    } // only relevant to terminal panes
     fn rename(&mut self, _buf: Vec<u8>) {}
     fn serialize(&self, _scrollback_lines_to_serialize: Option<usize>) -> Option<String> {
         None
@@ -540,8 +703,25 @@ pub trait Pane {
     fn rerun(&mut self) -> Option<RunCommand> {
         None
     } // only relevant to terminal panes
+    /// Returns false if there is no command to pick from
+    fn open_command_history(&mut self) -> bool {
+        false
+    } // only relevant to terminal panes
+    /// Writes the serialized scrollback of a closed pane into this one, which takes its place
+    fn restore_scrollback(&mut self, _scrollback: &str) {} // only relevant to terminal panes
+    /// Shows which programs are still running and asks the user to confirm closing the target,
+    /// returns false if this pane cannot ask
+    fn ask_to_confirm_close(
+        &mut self,
+        _close_target: CloseTarget,
+        _running_processes: Vec<String>,
+    ) -> bool {
+        false
+    } // only relevant to terminal panes
     fn update_theme(&mut self, _theme: Styling) {}
     fn update_arrow_fonts(&mut self, _should_support_arrow_fonts: bool) {}
+    fn update_word_separators(&mut self, _word_separators: &str) {}
+    fn update_unicode_width(&mut self, _unicode_width: UnicodeWidth) {}
     fn update_rounded_corners(&mut self, _rounded_corners: bool) {}
     fn set_should_be_suppressed(&mut self, _should_be_suppressed: bool) {}
     fn query_should_be_suppressed(&self) -> bool {
@@ -552,6 +732,11 @@ pub trait Pane {
     }
     fn toggle_pinned(&mut self) {}
     fn set_pinned(&mut self, _should_be_pinned: bool) {}
+    fn toggle_mouse_reporting_override(&mut self) {} // only relevant to terminal panes
+    fn toggle_input_lock(&mut self) {} // only relevant to terminal panes
+    fn input_is_locked(&self) -> bool {
+        false
+    }
     fn reset_logical_position(&mut self) {}
 }
 
@@ -563,6 +748,14 @@ pub enum AdjustedInput {
     CloseThisPane,
     DropToShellInThisPane { working_dir: Option<PathBuf> },
     WriteKeyToPlugin(KeyWithModifier),
+    ConfirmClose(CloseTarget),
+}
+
+/// What the user asked to close while programs were running in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CloseTarget {
+    Pane(PaneId),
+    Tab(usize), // tab index
 }
 pub fn get_next_terminal_position(
     tiled_panes: &TiledPanes,
@@ -617,10 +810,11 @@ impl Tab {
         explicitly_disable_kitty_keyboard_protocol: bool,
         default_editor: Option<PathBuf>,
     ) -> Self {
-        let name = if name.is_empty() {
-            format!("Tab #{}", index + 1)
+        let (name, automatic_name) = if name.is_empty() {
+            let name = format!("Tab #{}", index + 1);
+            (name.clone(), Some(name))
         } else {
-            name
+            (name, None)
         };
 
         let mut connected_clients = HashSet::new();
@@ -662,10 +856,7 @@ impl Tab {
             senders.clone(),
         );
 
-        let clipboard_provider = match copy_options.command {
-            Some(command) => ClipboardProvider::Command(CopyCommand::new(command)),
-            None => ClipboardProvider::Osc52(copy_options.clipboard),
-        };
+        let clipboard_chain = ClipboardChain::new(&copy_options);
         let swap_layouts = SwapLayouts::new(swap_layouts, display_area.clone());
 
         Tab {
@@ -676,6 +867,10 @@ impl Tab {
             suppressed_panes: HashMap::new(),
             name: name.clone(),
             prev_name: name,
+            automatic_name,
+            panes_with_bell: HashSet::new(),
+            panes_with_exited_command: HashSet::new(),
+            closed_panes: ClosedPanes::default(),
             max_panes,
             viewport,
             display_area,
@@ -685,6 +880,7 @@ impl Tab {
             os_api,
             senders,
             should_clear_display_before_rendering: false,
+            should_delete_images_before_rendering: false,
             style,
             mode_info,
             default_mode_info,
@@ -693,10 +889,13 @@ impl Tab {
             pending_vte_events: HashMap::new(),
             connected_clients,
             selecting_with_mouse_in_pane: None,
+            pane_being_resized_with_mouse: None,
             link_handler: Rc::new(RefCell::new(LinkHandler::new())),
-            clipboard_provider,
+            clipboard_chain,
             focus_pane_id: None,
             copy_on_select: copy_options.copy_on_select,
+            osc52_forwarding: copy_options.osc52_forwarding,
+            word_separators: copy_options.word_separators.clone(),
             terminal_emulator_colors,
             terminal_emulator_color_codes,
             pids_waiting_resize: HashSet::new(),
@@ -710,6 +909,8 @@ impl Tab {
             styled_underlines,
             explicitly_disable_kitty_keyboard_protocol,
             default_editor,
+            alternate_screen_behavior: AlternateScreenBehavior::default(),
+            unicode_width: UnicodeWidth::default(),
         }
     }
 
@@ -724,6 +925,7 @@ impl Tab {
     ) -> Result<()> {
         self.swap_layouts
             .set_base_layout((layout.clone(), floating_panes_layout.clone()));
+        let pane_logs_folder = self.pane_logs_folder();
         if let Ok(should_show_floating_panes) = LayoutApplier::new(
             &self.viewport,
             &self.senders,
@@ -744,6 +946,9 @@ impl Tab {
             self.arrow_fonts,
             self.styled_underlines,
             self.explicitly_disable_kitty_keyboard_protocol,
+            self.alternate_screen_behavior,
+            self.unicode_width,
+            pane_logs_folder,
         )
         .apply_layout(
             layout,
@@ -781,11 +986,20 @@ impl Tab {
             }
         }
     }
+    fn pane_logs_folder(&self) -> PathBuf {
+        pane_logs_folder_for_session(
+            self.default_mode_info
+                .session_name
+                .as_deref()
+                .unwrap_or_default(),
+        )
+    }
     fn relayout_floating_panes(&mut self, search_backwards: bool) -> Result<()> {
         if let Some(layout_candidate) = self
             .swap_layouts
             .swap_floating_panes(&self.floating_panes, search_backwards)
         {
+            let pane_logs_folder = self.pane_logs_folder();
             LayoutApplier::new(
                 &self.viewport,
                 &self.senders,
@@ -806,6 +1020,9 @@ impl Tab {
                 self.arrow_fonts,
                 self.styled_underlines,
                 self.explicitly_disable_kitty_keyboard_protocol,
+                self.alternate_screen_behavior,
+                self.unicode_width,
+                pane_logs_folder,
             )
             .apply_floating_panes_layout_to_existing_panes(&layout_candidate)
             .non_fatal();
@@ -821,6 +1038,7 @@ impl Tab {
             .swap_layouts
             .swap_tiled_panes(&self.tiled_panes, search_backwards)
         {
+            let pane_logs_folder = self.pane_logs_folder();
             let application_res = LayoutApplier::new(
                 &self.viewport,
                 &self.senders,
@@ -841,6 +1059,9 @@ impl Tab {
                 self.arrow_fonts,
                 self.styled_underlines,
                 self.explicitly_disable_kitty_keyboard_protocol,
+                self.alternate_screen_behavior,
+                self.unicode_width,
+                pane_logs_folder,
             )
             .apply_tiled_panes_layout_to_existing_panes(&layout_candidate);
             if application_res.is_err() {
@@ -881,6 +1102,59 @@ impl Tab {
             .with_context(|| format!("failed to update plugins with mode info"))?;
         Ok(())
     }
+    pub fn swap_layout_names(&self) -> Vec<String> {
+        if self.floating_panes.panes_are_visible() {
+            self.swap_layouts.floating_layout_names()
+        } else {
+            self.swap_layouts.tiled_layout_names()
+        }
+    }
+    /// Replace the swap layouts of the tab if they are the `previous` ones, applying the current
+    /// one again in case its definition changed
+    pub fn reload_swap_layouts(
+        &mut self,
+        previous: &(Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+        new: (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+    ) -> Result<()> {
+        let search_backwards = false;
+        let tiled_swap_layout_was_applied = self.swap_layouts.tiled_swap_layout_is_applied();
+        let floating_swap_layout_was_applied = self.swap_layouts.floating_swap_layout_is_applied();
+        if !self.swap_layouts.reload(previous, new) {
+            return Ok(());
+        }
+        if tiled_swap_layout_was_applied {
+            // marking the layout as damaged makes us apply the current one rather than the next
+            self.swap_layouts.set_is_tiled_damaged();
+            self.relayout_tiled_panes(search_backwards)?;
+        }
+        if floating_swap_layout_was_applied && self.floating_panes.has_panes() {
+            self.swap_layouts.set_is_floating_damaged();
+            self.relayout_floating_panes(search_backwards)?;
+        }
+        self.senders
+            .send_to_pty_writer(PtyWriteInstruction::ApplyCachedResizes)
+            .with_context(|| format!("failed to update plugins with mode info"))?;
+        Ok(())
+    }
+    /// Returns false if there is no swap layout with this name
+    pub fn select_swap_layout(&mut self, swap_layout_name: &str) -> Result<bool> {
+        let search_backwards = false;
+        if self.floating_panes.panes_are_visible() {
+            if !self.swap_layouts.select_floating_layout(swap_layout_name) {
+                return Ok(false);
+            }
+            self.relayout_floating_panes(search_backwards)?;
+        } else {
+            if !self.swap_layouts.select_tiled_layout(swap_layout_name) {
+                return Ok(false);
+            }
+            self.relayout_tiled_panes(search_backwards)?;
+        }
+        self.senders
+            .send_to_pty_writer(PtyWriteInstruction::ApplyCachedResizes)
+            .with_context(|| format!("failed to update plugins with mode info"))?;
+        Ok(true)
+    }
     pub fn apply_buffered_instructions(&mut self) -> Result<()> {
         let buffered_instructions: Vec<BufferedTabInstruction> =
             self.pending_instructions.drain(..).collect();
@@ -914,6 +1188,22 @@ impl Tab {
         }
         self.update_input_modes()
     }
+    pub fn update_available_versions(
+        &mut self,
+        installed_version: Option<String>,
+        latest_release: Option<String>,
+    ) -> Result<()> {
+        {
+            let mode_infos = &mut self.mode_info.borrow_mut();
+            for (_client_id, mode_info) in mode_infos.iter_mut() {
+                mode_info.installed_version = installed_version.clone();
+                mode_info.latest_release = latest_release.clone();
+            }
+            self.default_mode_info.installed_version = installed_version;
+            self.default_mode_info.latest_release = latest_release;
+        }
+        self.update_input_modes()
+    }
     pub fn update_input_modes(&mut self) -> Result<()> {
         // this updates all plugins with the client's input mode
         let mode_infos = self.mode_info.borrow();
@@ -980,6 +1270,12 @@ impl Tab {
     }
 
     pub fn change_mode_info(&mut self, mode_info: ModeInfo, client_id: ClientId) {
+        if mode_info.mode != InputMode::Scroll {
+            // the keyboard selection belongs to scroll mode
+            if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+                active_pane.exit_copy_mode();
+            }
+        }
         self.mode_info.borrow_mut().insert(client_id, mode_info);
     }
 
@@ -1163,7 +1459,7 @@ impl Tab {
         let mut new_pane = match pid {
             PaneId::Terminal(term_pid) => {
                 let next_terminal_position = self.get_next_terminal_position();
-                Box::new(TerminalPane::new(
+                let mut new_pane = TerminalPane::new(
                     term_pid,
                     PaneGeom::default(), // this will be filled out later
                     self.style,
@@ -1180,7 +1476,10 @@ impl Tab {
                     self.arrow_fonts,
                     self.styled_underlines,
                     self.explicitly_disable_kitty_keyboard_protocol,
-                )) as Box<dyn Pane>
+                );
+                new_pane.set_alternate_screen_behavior(self.alternate_screen_behavior);
+                new_pane.update_unicode_width(self.unicode_width);
+                Box::new(new_pane) as Box<dyn Pane>
             },
             PaneId::Plugin(plugin_pid) => {
                 Box::new(PluginPane::new(
@@ -1359,7 +1658,7 @@ impl Tab {
         match new_pane_id {
             PaneId::Terminal(new_pane_id) => {
                 let next_terminal_position = self.get_next_terminal_position(); // TODO: this is not accurate in this case
-                let new_pane = TerminalPane::new(
+                let mut new_pane = TerminalPane::new(
                     new_pane_id,
                     PaneGeom::default(), // the initial size will be set later
                     self.style,
@@ -1377,6 +1676,8 @@ impl Tab {
                     self.styled_underlines,
                     self.explicitly_disable_kitty_keyboard_protocol,
                 );
+                new_pane.set_alternate_screen_behavior(self.alternate_screen_behavior);
+                new_pane.update_unicode_width(self.unicode_width);
                 let replaced_pane = if self.floating_panes.panes_contain(&old_pane_id) {
                     self.floating_panes
                         .replace_pane(old_pane_id, Box::new(new_pane))
@@ -1497,7 +1798,7 @@ impl Tab {
         if self.tiled_panes.can_split_pane_horizontally(client_id) {
             if let PaneId::Terminal(term_pid) = pid {
                 let next_terminal_position = self.get_next_terminal_position();
-                let new_terminal = TerminalPane::new(
+                let mut new_terminal = TerminalPane::new(
                     term_pid,
                     PaneGeom::default(), // the initial size will be set later
                     self.style,
@@ -1515,6 +1816,8 @@ impl Tab {
                     self.styled_underlines,
                     self.explicitly_disable_kitty_keyboard_protocol,
                 );
+                new_terminal.set_alternate_screen_behavior(self.alternate_screen_behavior);
+                new_terminal.update_unicode_width(self.unicode_width);
                 self.tiled_panes
                     .split_pane_horizontally(pid, Box::new(new_terminal), client_id);
                 self.set_should_clear_display_before_rendering();
@@ -1557,7 +1860,7 @@ impl Tab {
         if self.tiled_panes.can_split_pane_vertically(client_id) {
             if let PaneId::Terminal(term_pid) = pid {
                 let next_terminal_position = self.get_next_terminal_position();
-                let new_terminal = TerminalPane::new(
+                let mut new_terminal = TerminalPane::new(
                     term_pid,
                     PaneGeom::default(), // the initial size will be set later
                     self.style,
@@ -1575,6 +1878,8 @@ impl Tab {
                     self.styled_underlines,
                     self.explicitly_disable_kitty_keyboard_protocol,
                 );
+                new_terminal.set_alternate_screen_behavior(self.alternate_screen_behavior);
+                new_terminal.update_unicode_width(self.unicode_width);
                 self.tiled_panes
                     .split_pane_vertically(pid, Box::new(new_terminal), client_id);
                 self.set_should_clear_display_before_rendering();
@@ -1651,6 +1956,113 @@ impl Tab {
             self.tiled_panes.get_active_pane_id(client_id)
         }
     }
+    /// The pane focused by the connected client with the lowest id, if any client is connected
+    pub fn focused_pane_id(&self) -> Option<PaneId> {
+        let client_id = self.connected_clients.borrow().iter().min().copied()?;
+        self.get_active_pane_id(client_id)
+    }
+    pub fn pane_is_focused(&self, pane_id: PaneId) -> bool {
+        self.connected_clients
+            .borrow()
+            .iter()
+            .any(|client_id| self.get_active_pane_id(*client_id) == Some(pane_id))
+    }
+    /// Checks the silence monitors of the panes and returns the alerts they raised, with whether
+    /// they should be sent as desktop notifications. Alerts of focused panes are dropped, as the
+    /// user is already looking at them.
+    pub fn check_pane_silence(&mut self, now: Instant) -> Vec<(PaneId, MonitorAlert, bool)> {
+        let mut alerts = vec![];
+        for pane_id in self.get_all_pane_ids() {
+            let pane_is_focused = self.pane_is_focused(pane_id);
+            if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
+                pane.check_silence(now);
+                if pane_is_focused {
+                    pane.clear_monitor_alert();
+                } else if let Some((alert, should_notify)) = pane.drain_monitor_alert() {
+                    alerts.push((pane_id, alert, should_notify));
+                }
+            }
+        }
+        alerts
+    }
+    /// Clears the monitor alerts and bells of the focused panes. Returns whether any alert was
+    /// cleared.
+    pub fn clear_alerts_of_focused_panes(&mut self) -> bool {
+        let focused_pane_ids: Vec<PaneId> = self
+            .connected_clients
+            .borrow()
+            .iter()
+            .filter_map(|client_id| self.get_active_pane_id(*client_id))
+            .collect();
+        let mut cleared_alert = false;
+        for pane_id in focused_pane_ids {
+            cleared_alert = self.panes_with_bell.remove(&pane_id) || cleared_alert;
+            cleared_alert = self.panes_with_exited_command.remove(&pane_id) || cleared_alert;
+            if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
+                cleared_alert = pane.clear_monitor_alert() || cleared_alert;
+            }
+        }
+        cleared_alert
+    }
+    /// Whether a pane of this tab rang the bell while it was not focused
+    pub fn has_bell_alert(&self) -> bool {
+        self.panes_with_bell
+            .iter()
+            .any(|pane_id| self.has_pane_with_pid(pane_id))
+    }
+    /// Whether the command of a command pane of this tab exited while the tab was not active
+    pub fn has_command_exit_alert(&self) -> bool {
+        self.panes_with_exited_command
+            .iter()
+            .any(|pane_id| self.has_pane_with_pid(pane_id))
+    }
+    /// Whether the input of a pane of this tab is locked
+    pub fn has_input_locked_pane(&self) -> bool {
+        self.get_static_and_floating_pane_ids()
+            .into_iter()
+            .filter_map(|pane_id| self.get_pane_with_id(pane_id))
+            .any(|pane| pane.input_is_locked())
+    }
+    pub fn mark_exited_command(&mut self, pane_id: PaneId) {
+        self.panes_with_exited_command.insert(pane_id);
+    }
+    pub fn has_monitor_alert(&self, alert: MonitorAlert) -> bool {
+        self.get_all_pane_ids()
+            .into_iter()
+            .filter_map(|pane_id| self.get_pane_with_id(pane_id))
+            .any(|pane| pane.monitor_alert() == Some(alert))
+    }
+    /// Whether the tab was renamed by the user, rather than named by zellij
+    pub fn has_user_set_name(&self) -> bool {
+        self.automatic_name.as_ref() != Some(&self.name)
+    }
+    /// Names the tab without considering it renamed by the user
+    pub fn set_automatic_name(&mut self, name: String) {
+        self.automatic_name = Some(name.clone());
+        self.name = name;
+    }
+    /// Lets the tab be named automatically again, after it was renamed by the user
+    pub fn forget_user_set_name(&mut self) {
+        self.automatic_name = Some(self.name.clone());
+    }
+    pub fn client_terminal_focus_changed(&self, client_id: ClientId, is_focused: bool) {
+        // forward the focus change of the client's own terminal window to its focused pane, if
+        // the application running inside it requested focus reporting (CSI ?1004h)
+        if let Some(terminal_id) = self.get_active_terminal_id(client_id) {
+            let focus_event = self.get_active_pane(client_id).and_then(|pane| {
+                if is_focused {
+                    pane.focus_event()
+                } else {
+                    pane.unfocus_event()
+                }
+            });
+            if let Some(focus_event) = focus_event {
+                let _ = self
+                    .os_api
+                    .write_to_tty_stdin(terminal_id, focus_event.as_bytes());
+            }
+        }
+    }
     fn get_active_terminal_id(&self, client_id: ClientId) -> Option<u32> {
         if let Some(PaneId::Terminal(pid)) = self.get_active_pane_id(client_id) {
             Some(pid)
@@ -1755,6 +2167,7 @@ impl Tab {
     fn process_pty_bytes(&mut self, pid: u32, bytes: VteBytes) -> Result<()> {
         let err_context = || format!("failed to process pty bytes from pid {pid}");
 
+        let pane_is_focused = self.pane_is_focused(PaneId::Terminal(pid));
         if let Some(terminal_output) = self
             .tiled_panes
             .get_pane_mut(PaneId::Terminal(pid))
@@ -1776,14 +2189,68 @@ impl Tab {
                 .with_context(err_context)?;
             }
             terminal_output.handle_pty_bytes(bytes);
+            if terminal_output.is_linked_view() {
+                // the pane it is a view of answers the pty and reports what was output
+                terminal_output.drain_messages_to_pty();
+                terminal_output.drain_clipboard_update();
+                terminal_output.drain_reported_cwd();
+                terminal_output.drain_port_declarations();
+                terminal_output.drain_title_update();
+                terminal_output.drain_bell();
+                terminal_output.drain_monitor_alert();
+                return Ok(());
+            }
             let messages_to_pty = terminal_output.drain_messages_to_pty();
             let clipboard_update = terminal_output.drain_clipboard_update();
+            if let Some(cwd) = terminal_output.drain_reported_cwd() {
+                self.senders
+                    .send_to_pty(PtyInstruction::ReportCwd(pid, cwd))
+                    .with_context(err_context)?;
+            }
+            for port_declaration in terminal_output.drain_port_declarations() {
+                let instruction = match port_declaration {
+                    PortDeclaration::Declared(port, name) => {
+                        ScreenInstruction::DeclarePort(port, name, Some(PaneId::Terminal(pid)))
+                    },
+                    PortDeclaration::Undeclared(port) => {
+                        ScreenInstruction::UndeclarePort(port, Some(PaneId::Terminal(pid)))
+                    },
+                };
+                self.senders
+                    .send_to_screen(instruction)
+                    .with_context(err_context)?;
+            }
+            if let Some(title) = terminal_output.drain_title_update() {
+                self.senders
+                    .send_to_screen(ScreenInstruction::PaneTitleChanged(
+                        PaneId::Terminal(pid),
+                        title,
+                    ))
+                    .with_context(err_context)?;
+            }
+            if terminal_output.drain_bell() && !pane_is_focused {
+                self.panes_with_bell.insert(PaneId::Terminal(pid));
+                self.senders
+                    .send_to_screen(ScreenInstruction::PaneBell(PaneId::Terminal(pid)))
+                    .with_context(err_context)?;
+            }
+            if pane_is_focused {
+                terminal_output.clear_monitor_alert();
+            } else if let Some((alert, should_notify)) = terminal_output.drain_monitor_alert() {
+                self.senders
+                    .send_to_screen(ScreenInstruction::PaneMonitorAlert(
+                        PaneId::Terminal(pid),
+                        alert,
+                        should_notify,
+                    ))
+                    .with_context(err_context)?;
+            }
             for message in messages_to_pty {
                 self.write_to_pane_id_without_preprocessing(message, PaneId::Terminal(pid))
                     .with_context(err_context)?;
             }
-            if let Some(string) = clipboard_update {
-                self.write_selection_to_clipboard(&string)
+            if let Some((clipboard, string)) = clipboard_update {
+                self.forward_clipboard_update(clipboard, &string)
                     .with_context(err_context)?;
             }
         }
@@ -1800,7 +2267,16 @@ impl Tab {
         // returns true if a UI update should be triggered (eg. when closing a command pane with
         // ctrl-c)
         let mut should_trigger_ui_change = false;
-        let pane_ids = self.get_static_and_floating_pane_ids();
+        // panes whose input is locked do not receive synced keys either
+        let pane_ids: Vec<PaneId> = self
+            .get_static_and_floating_pane_ids()
+            .into_iter()
+            .filter(|pane_id| {
+                !self
+                    .get_pane_with_id(*pane_id)
+                    .map_or(false, |pane| pane.input_is_locked())
+            })
+            .collect();
         for pane_id in pane_ids {
             let ui_change_triggered = self
                 .write_to_pane_id(
@@ -1818,6 +2294,38 @@ impl Tab {
         Ok(should_trigger_ui_change)
     }
 
+    /// Writes the keys typed by a client to its focused pane, or to all panes of the tab when their
+    /// input is synced. A pane whose input is locked ignores them (eg. a running migration), but
+    /// not what is written to it otherwise (eg. by a plugin).
+    pub fn write_keys_from_client(
+        &mut self,
+        key_with_modifier: &Option<KeyWithModifier>,
+        raw_input_bytes: Vec<u8>,
+        raw_input_bytes_are_kitty: bool,
+        client_id: ClientId,
+    ) -> Result<bool> {
+        if self.is_sync_panes_active() {
+            return self.write_to_terminals_on_current_tab(
+                key_with_modifier,
+                raw_input_bytes,
+                raw_input_bytes_are_kitty,
+                client_id,
+            );
+        }
+        let focused_pane_is_locked = self
+            .get_active_pane_or_floating_pane_mut(client_id)
+            .map_or(false, |pane| pane.input_is_locked());
+        if focused_pane_is_locked {
+            return Ok(false);
+        }
+        self.write_to_active_terminal(
+            key_with_modifier,
+            raw_input_bytes,
+            raw_input_bytes_are_kitty,
+            client_id,
+        )
+    }
+
     pub fn write_to_active_terminal(
         &mut self,
         key_with_modifier: &Option<KeyWithModifier>,
@@ -1964,6 +2472,11 @@ impl Tab {
                             .with_context(err_context)?;
                         should_update_ui = true;
                     },
+                    Some(AdjustedInput::ConfirmClose(close_target)) => {
+                        self.senders
+                            .send_to_screen(ScreenInstruction::CloseConfirmed(close_target))
+                            .with_context(err_context)?;
+                    },
                     Some(_) => {},
                     None => {},
                 }
@@ -2095,6 +2608,9 @@ impl Tab {
     pub fn is_fullscreen_active(&self) -> bool {
         self.tiled_panes.fullscreen_is_active()
     }
+    pub fn fullscreen_pane_id(&self) -> Option<PaneId> {
+        self.tiled_panes.fullscreen_pane_id()
+    }
     pub fn are_floating_panes_visible(&self) -> bool {
         self.floating_panes.panes_are_visible()
     }
@@ -2141,6 +2657,9 @@ impl Tab {
     pub fn set_force_render(&mut self) {
         self.tiled_panes.set_force_render();
         self.floating_panes.set_force_render();
+        // the panes place their (kitty graphics) images again, so we remove those that might
+        // belong to panes which are no longer visible
+        self.should_delete_images_before_rendering = true;
     }
     pub fn set_should_clear_display_before_rendering(&mut self) {
         self.should_clear_display_before_rendering = true;
@@ -2152,6 +2671,8 @@ impl Tab {
     }
     pub fn toggle_sync_panes_is_active(&mut self) {
         self.synchronize_is_active = !self.synchronize_is_active;
+        // so that the sync indication is added to or removed from all pane frames
+        self.set_force_render();
     }
     pub fn mark_active_pane_for_rerender(&mut self, client_id: ClientId) {
         if let Some(active_pane) = self.get_active_pane_mut(client_id) {
@@ -2167,6 +2688,7 @@ impl Tab {
             self.senders
                 .send_to_pty(PtyInstruction::UpdateActivePane(
                     self.get_active_pane_id(client_id),
+                    self.index,
                     client_id,
                 ))
                 .with_context(|| format!("failed to update active pane for client {client_id}"))?;
@@ -2193,13 +2715,17 @@ impl Tab {
         );
 
         self.tiled_panes
-            .render(output, self.floating_panes.panes_are_visible())
+            .render(
+                output,
+                self.floating_panes.panes_are_visible(),
+                self.synchronize_is_active,
+            )
             .with_context(err_context)?;
         if (self.floating_panes.panes_are_visible() && self.floating_panes.has_active_panes())
             || self.floating_panes.has_pinned_panes()
         {
             self.floating_panes
-                .render(output)
+                .render(output, self.synchronize_is_active)
                 .with_context(err_context)?;
         }
 
@@ -2227,6 +2753,14 @@ impl Tab {
             );
             self.should_clear_display_before_rendering = false;
         }
+        if self.should_delete_images_before_rendering {
+            let delete_all_images = "\u{1b}_Ga=d,d=A,q=2\u{1b}\\";
+            output.add_pre_vte_instruction_to_multiple_clients(
+                connected_clients.iter().copied(),
+                delete_all_images,
+            );
+            self.should_delete_images_before_rendering = false;
+        }
     }
     fn render_cursor(&mut self, output: &mut Output) {
         let connected_clients: Vec<ClientId> =
@@ -2310,6 +2844,33 @@ impl Tab {
     fn get_selectable_floating_panes(&self) -> impl Iterator<Item = (&PaneId, &Box<dyn Pane>)> {
         self.get_floating_panes().filter(|(_, p)| p.selectable())
     }
+    /// The pane of a tab that has only one (selectable) pane
+    pub fn get_single_pane_id(&self) -> Option<PaneId> {
+        let mut pane_ids = self
+            .get_selectable_tiled_panes()
+            .chain(self.get_selectable_floating_panes())
+            .map(|(pane_id, _)| *pane_id);
+        match (pane_ids.next(), pane_ids.next()) {
+            (Some(pane_id), None) => Some(pane_id),
+            _ => None,
+        }
+    }
+    /// The panes counted against the max_panes_per_tab limit, which leaves out the bars
+    pub fn pane_count(&self) -> usize {
+        self.get_selectable_tiled_panes_count()
+            + self.get_selectable_floating_panes_count()
+            + self.suppressed_panes.len()
+    }
+    pub fn scrollback_memory_usage(&self) -> usize {
+        self.get_tiled_panes()
+            .chain(self.get_floating_panes())
+            .map(|(_, pane)| pane.scrollback_memory_usage())
+            .chain(
+                self.get_suppressed_panes()
+                    .map(|(_, (_, pane))| pane.scrollback_memory_usage()),
+            )
+            .sum()
+    }
     pub fn get_selectable_tiled_panes_count(&self) -> usize {
         self.get_selectable_tiled_panes().count()
     }
@@ -2973,56 +3534,229 @@ impl Tab {
 
         if self.floating_panes.panes_are_visible() {
             if let Some(active_floating_pane_id) = self.floating_panes.active_pane_id(client_id) {
-                self.close_pane(active_floating_pane_id, false);
-                self.senders
-                    .send_to_pty(PtyInstruction::ClosePane(active_floating_pane_id))
-                    .with_context(|| err_context(active_floating_pane_id))?;
-                return Ok(());
+                return self
+                    .close_pane_and_its_pty(active_floating_pane_id)
+                    .with_context(|| err_context(active_floating_pane_id));
             }
         }
         if let Some(active_pane_id) = self.tiled_panes.get_active_pane_id(client_id) {
-            self.close_pane(active_pane_id, false);
-            self.senders
-                .send_to_pty(PtyInstruction::ClosePane(active_pane_id))
+            self.close_pane_and_its_pty(active_pane_id)
                 .with_context(|| err_context(active_pane_id))?;
         }
         Ok(())
     }
-    pub fn clear_active_terminal_screen(&mut self, client_id: ClientId) -> Result<()> {
-        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
-            active_pane.clear_screen();
+    /// Closes a pane as the user asked to, so that it can be reopened
+    pub fn close_pane_and_its_pty(&mut self, pane_id: PaneId) -> Result<()> {
+        if self.pane_is_linked_view(pane_id) {
+            // closing a view leaves the pane it shows running
+            self.close_pane(pane_id, false);
+            return Ok(());
         }
-        Ok(())
+        self.record_closed_pane(pane_id);
+        self.close_pane(pane_id, false);
+        self.senders
+            .send_to_pty(PtyInstruction::ClosePane(pane_id))
+            .with_context(|| format!("failed to close pane {pane_id:?}"))
     }
-    pub fn clear_screen_for_pane_id(&mut self, pane_id: PaneId) {
-        if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
-            pane.clear_screen();
+    fn record_closed_pane(&mut self, pane_id: PaneId) {
+        if self.suppressed_panes.contains_key(&pane_id) {
+            // closing this pane brings back the one it replaced (eg. the scrollback editor)
+            return;
         }
+        let is_floating = self.floating_panes.panes_contain(&pane_id);
+        let closed_pane = match (pane_id, self.get_pane_with_id(pane_id)) {
+            (PaneId::Terminal(terminal_id), Some(pane)) => ClosedPane {
+                terminal_id,
+                tab_index: self.index,
+                name: pane.custom_title(),
+                invoked_with: pane.invoked_with().clone(),
+                scrollback: pane.serialize(Some(CLOSED_PANE_SCROLLBACK_LINES)),
+                geom: pane.position_and_size(),
+                is_floating,
+                closed_at: Instant::now(),
+            },
+            _ => return,
+        };
+        self.closed_panes.push(closed_pane);
     }
-    pub fn dump_active_terminal_screen(
+    pub fn take_closed_pane(&mut self) -> Option<ClosedPane> {
+        self.closed_panes.take_newest(Instant::now())
+    }
+    /// Opens a new terminal pane (already spawned) in place of a closed pane, where it was and
+    /// with its scrollback
+    pub fn restore_closed_pane(
         &mut self,
-        file: Option<String>,
+        terminal_id: u32,
+        closed_pane: ClosedPane,
         client_id: ClientId,
-        full: bool,
     ) -> Result<()> {
-        let err_context =
-            || format!("failed to dump active terminal screen for client {client_id}");
-
-        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
-            let dump = active_pane.dump_screen(full);
-            self.os_api
-                .write_to_file(dump, file)
+        let err_context = || format!("failed to restore closed pane in terminal {terminal_id}");
+        let pane_id = PaneId::Terminal(terminal_id);
+        self.close_down_to_max_terminals()
+            .with_context(err_context)?;
+        let next_terminal_position = self.get_next_terminal_position();
+        let initial_pane_title = match &closed_pane.invoked_with {
+            Some(Run::Command(run_command)) => Some(run_command.to_string()),
+            _ => None,
+        };
+        let mut new_terminal = TerminalPane::new(
+            terminal_id,
+            PaneGeom::default(), // this will be filled out later
+            self.style,
+            next_terminal_position,
+            String::new(),
+            self.link_handler.clone(),
+            self.character_cell_size.clone(),
+            self.sixel_image_store.clone(),
+            self.terminal_emulator_colors.clone(),
+            self.terminal_emulator_color_codes.clone(),
+            initial_pane_title,
+            closed_pane.invoked_with.clone(),
+            self.debug,
+            self.arrow_fonts,
+            self.styled_underlines,
+            self.explicitly_disable_kitty_keyboard_protocol,
+        );
+        new_terminal.set_alternate_screen_behavior(self.alternate_screen_behavior);
+        new_terminal.update_unicode_width(self.unicode_width);
+        let mut new_pane = Box::new(new_terminal) as Box<dyn Pane>;
+        if let Some(name) = closed_pane.name.as_ref() {
+            new_pane.rename(name.as_bytes().to_vec());
+        }
+        new_pane.set_active_at(Instant::now());
+        if closed_pane.is_floating {
+            self.show_floating_panes();
+            new_pane.set_geom(closed_pane.geom);
+            new_pane.set_content_offset(Offset::frame(1)); // floating panes always have a frame
+            resize_pty!(
+                new_pane,
+                self.os_api,
+                self.senders,
+                self.character_cell_size
+            )
+            .with_context(err_context)?;
+            // if the tab got smaller since, the pane is placed as a new floating pane would be
+            self.add_floating_pane_keeping_position(new_pane, pane_id)
                 .with_context(err_context)?;
-        }
-        Ok(())
-    }
-    pub fn dump_terminal_screen(
-        &mut self,
-        file: Option<String>,
-        pane_id: PaneId,
-        full: bool,
-    ) -> Result<()> {
-        if let Some(pane) = self.get_pane_with_id(pane_id) {
+            self.floating_panes.focus_pane_for_all_clients(pane_id);
+            self.swap_layouts.set_is_floating_damaged();
+        } else {
+            self.hide_floating_panes();
+            if self.tiled_panes.fullscreen_is_active() {
+                self.tiled_panes.unset_fullscreen();
+            }
+            match self
+                .tiled_panes
+                .insert_pane_at_geom(pane_id, new_pane, closed_pane.geom)
+            {
+                Some(new_pane) => {
+                    // the panes around it changed too much since it was closed
+                    self.add_tiled_pane(new_pane, pane_id, Some(client_id))
+                        .with_context(err_context)?;
+                },
+                None => {
+                    self.set_should_clear_display_before_rendering();
+                    self.tiled_panes.focus_pane(pane_id, client_id);
+                    self.swap_layouts.set_is_tiled_damaged();
+                },
+            }
+        }
+        if let Some(scrollback) = closed_pane.scrollback.as_ref() {
+            if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
+                pane.restore_scrollback(scrollback);
+            }
+        }
+        self.set_force_render();
+        Ok(())
+    }
+    /// Adds a view of the pty of a terminal pane of another tab, which starts out with a copy of
+    /// its scrollback
+    pub fn add_linked_view(
+        &mut self,
+        terminal_id: u32,
+        title: String,
+        invoked_with: Option<Run>,
+        scrollback: Option<String>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to add linked view of terminal {terminal_id}");
+        let pane_id = PaneId::Terminal(terminal_id);
+        let next_terminal_position = self.get_next_terminal_position();
+        let mut new_pane = TerminalPane::new(
+            terminal_id,
+            PaneGeom::default(), // this will be filled out later
+            self.style,
+            next_terminal_position,
+            String::new(),
+            self.link_handler.clone(),
+            self.character_cell_size.clone(),
+            self.sixel_image_store.clone(),
+            self.terminal_emulator_colors.clone(),
+            self.terminal_emulator_color_codes.clone(),
+            Some(title),
+            invoked_with,
+            self.debug,
+            self.arrow_fonts,
+            self.styled_underlines,
+            self.explicitly_disable_kitty_keyboard_protocol,
+        );
+        new_pane.set_linked_view(true);
+        new_pane.set_alternate_screen_behavior(self.alternate_screen_behavior);
+        new_pane.update_unicode_width(self.unicode_width);
+        self.add_tiled_pane(Box::new(new_pane), pane_id, None)
+            .with_context(err_context)?;
+        if let Some(scrollback) = scrollback.as_ref() {
+            if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
+                pane.restore_scrollback(scrollback);
+            }
+        }
+        self.set_force_render();
+        Ok(())
+    }
+    pub fn pane_is_linked_view(&self, pane_id: PaneId) -> bool {
+        self.get_pane_with_id(pane_id)
+            .map(|pane| pane.is_linked_view())
+            .unwrap_or(false)
+    }
+    pub fn clear_active_terminal_screen(&mut self, client_id: ClientId) -> Result<()> {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.clear_screen();
+        }
+        Ok(())
+    }
+    pub fn clear_screen_for_pane_id(&mut self, pane_id: PaneId) {
+        if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
+            pane.clear_screen();
+        }
+    }
+    pub fn dump_active_terminal_screen(
+        &mut self,
+        file: Option<String>,
+        client_id: ClientId,
+        full: bool,
+        ansi: bool,
+    ) -> Result<()> {
+        let err_context =
+            || format!("failed to dump active terminal screen for client {client_id}");
+
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            let dump = if ansi {
+                active_pane.dump_screen_with_styles(full)
+            } else {
+                active_pane.dump_screen(full)
+            };
+            self.os_api
+                .write_to_file(dump, file)
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
+    pub fn dump_terminal_screen(
+        &mut self,
+        file: Option<String>,
+        pane_id: PaneId,
+        full: bool,
+    ) -> Result<()> {
+        if let Some(pane) = self.get_pane_with_id(pane_id) {
             let dump = pane.dump_screen(full);
             self.os_api.write_to_file(dump, file).non_fatal()
         }
@@ -3037,6 +3771,7 @@ impl Tab {
             Some(String::from(file.to_string_lossy())),
             client_id,
             true,
+            false,
         )
         .with_context(err_context)?;
         let line_number = self
@@ -3071,7 +3806,12 @@ impl Tab {
     }
     pub fn scroll_active_terminal_up(&mut self, client_id: ClientId) {
         if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
-            active_pane.scroll_up(1, client_id);
+            if active_pane.copy_mode_is_active() {
+                // the keyboard selection scrolls the pane as its cursor moves
+                active_pane.copy_mode_input('k');
+            } else {
+                active_pane.scroll_up(1, client_id);
+            }
         }
     }
 
@@ -3088,7 +3828,11 @@ impl Tab {
         let err_context = || format!("failed to scroll down active pane for client {client_id}");
 
         if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
-            active_pane.scroll_down(1, client_id);
+            if active_pane.copy_mode_is_active() {
+                active_pane.copy_mode_input('j');
+            } else {
+                active_pane.scroll_down(1, client_id);
+            }
             if !active_pane.is_scrolled() {
                 if let PaneId::Terminal(raw_fd) = active_pane.pid() {
                     self.process_pending_vte_events(raw_fd)
@@ -3184,6 +3928,32 @@ impl Tab {
         Ok(())
     }
 
+    pub fn scroll_active_terminal_to_prompt(
+        &mut self,
+        backwards: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context =
+            || format!("failed to scroll to a prompt in active pane for client {client_id}");
+
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.scroll_to_prompt(backwards);
+            if !active_pane.is_scrolled() {
+                if let PaneId::Terminal(raw_fd) = active_pane.pid() {
+                    self.process_pending_vte_events(raw_fd)
+                        .with_context(err_context)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn select_last_command_output_in_active_pane(&mut self, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.select_last_command_output();
+        }
+    }
+
     pub fn scroll_active_terminal_to_bottom(&mut self, client_id: ClientId) -> Result<()> {
         let err_context =
             || format!("failed to scroll to bottom in active pane for client {client_id}");
@@ -3404,6 +4174,9 @@ impl Tab {
                 .pid();
             match event.event_type {
                 MouseEventType::Press => {
+                    // a press without a release (eg. released outside of the terminal) ends the
+                    // previous drag
+                    self.pane_being_resized_with_mouse = None;
                     if pane_id_at_position == active_pane_id {
                         self.handle_active_pane_left_mouse_press(event, client_id)
                     } else {
@@ -3461,6 +4234,7 @@ impl Tab {
         let err_context =
             || format!("failed to handle mouse event {event:?} for client {client_id}");
         let floating_panes_are_visible = self.floating_panes.panes_are_visible();
+        let word_separators = self.word_separators.clone();
         let pane_at_position = self
             .get_pane_at(&event.position, false)
             .with_context(err_context)?
@@ -3471,6 +4245,11 @@ impl Tab {
             if intercepted {
                 self.set_force_render();
                 return Ok(MouseEffect::state_changed());
+            } else if self
+                .start_resizing_pane_with_mouse(&event.position)
+                .with_context(err_context)?
+            {
+                return Ok(MouseEffect::default());
             } else if floating_panes_are_visible {
                 // start moving if floating pane
                 let search_selectable = false;
@@ -3501,6 +4280,7 @@ impl Tab {
             } else {
                 // start selection for copy/paste
                 let mut leave_clipboard_message = false;
+                pane_at_position.update_word_separators(&word_separators);
                 pane_at_position.start_selection(&relative_position, client_id);
                 if pane_at_position.get_selected_text().is_some() {
                     leave_clipboard_message = true;
@@ -3550,6 +4330,12 @@ impl Tab {
             pane_at_position.start_selection(&relative_position, client_id);
         }
 
+        if self
+            .start_resizing_pane_with_mouse(&event.position)
+            .with_context(err_context)?
+        {
+            return Ok(MouseEffect::state_changed());
+        }
         if self.floating_panes.panes_are_visible() {
             let search_selectable = false;
             // we do this because this might be the beginning of the user dragging a pane
@@ -3585,7 +4371,14 @@ impl Tab {
         let active_pane_id = self
             .get_active_pane_id(client_id)
             .ok_or_else(|| anyhow!("Failed to find pane at position"))?;
-        if pane_is_being_moved_with_mouse {
+        if self.pane_being_resized_with_mouse.is_some() {
+            if self
+                .resize_pane_with_mouse(&event.position)
+                .with_context(err_context)?
+            {
+                return Ok(MouseEffect::state_changed());
+            }
+        } else if pane_is_being_moved_with_mouse {
             let search_selectable = false;
             if self
                 .floating_panes
@@ -3622,6 +4415,18 @@ impl Tab {
         let floating_panes_are_visible = self.floating_panes.panes_are_visible();
         let copy_on_release = self.copy_on_select;
 
+        if self.pane_being_resized_with_mouse.is_some() {
+            let resized = self
+                .resize_pane_with_mouse(&event.position)
+                .with_context(err_context)?;
+            self.pane_being_resized_with_mouse = None;
+            if resized {
+                return Ok(MouseEffect::state_changed());
+            } else {
+                return Ok(MouseEffect::default());
+            }
+        }
+
         if let Some(pane_with_selection) = self
             .selecting_with_mouse_in_pane
             .and_then(|p_id| self.get_pane_with_id_mut(p_id))
@@ -3676,6 +4481,76 @@ impl Tab {
         }
     }
 
+    /// Starts resizing the pane under the mouse if the position is on one of the edges of its
+    /// frame, returns true if it did. Floating panes dragged by their title bar are moved instead,
+    /// and the edges of tiled panes that have no neighbor to resize against are ignored.
+    fn start_resizing_pane_with_mouse(&mut self, position: &Position) -> Result<bool> {
+        let err_context = || format!("failed to start resizing pane at {position:?}");
+        let viewport = *self.viewport.borrow();
+        let fullscreen_is_active = self.is_fullscreen_active();
+        let Some(pane) = self
+            .get_pane_at(position, false)
+            .with_context(err_context)?
+        else {
+            return Ok(false);
+        };
+        let pane_id = pane.pid();
+        let geom = pane.current_geom();
+        let mut edges = pane.frame_edges_at(position);
+        if self.floating_panes.panes_contain(&pane_id) {
+            if edges == [Direction::Up] {
+                edges.clear();
+            }
+        } else if fullscreen_is_active || geom.is_stacked() {
+            edges.clear();
+        } else {
+            edges.retain(|edge| match edge {
+                Direction::Left => geom.x > viewport.x,
+                Direction::Right => geom.x + geom.cols.as_usize() < viewport.x + viewport.cols,
+                Direction::Up => geom.y > viewport.y,
+                Direction::Down => geom.y + geom.rows.as_usize() < viewport.y + viewport.rows,
+            });
+        }
+        if edges.is_empty() {
+            return Ok(false);
+        }
+        self.pane_being_resized_with_mouse = Some((pane_id, edges));
+        Ok(true)
+    }
+    fn resize_pane_with_mouse(&mut self, position: &Position) -> Result<bool> {
+        // true => the pane was resized
+        let err_context = || format!("failed to resize pane with mouse at {position:?}");
+        let Some((pane_id, edges)) = self.pane_being_resized_with_mouse.clone() else {
+            return Ok(false);
+        };
+        let resized = if self.floating_panes.panes_contain(&pane_id) {
+            let resized = self
+                .floating_panes
+                .move_pane_edges_to(pane_id, &edges, position)
+                .with_context(err_context)?;
+            if resized {
+                self.swap_layouts.set_is_floating_damaged();
+            }
+            resized
+        } else {
+            let mut resized = false;
+            for edge in edges {
+                resized |= self
+                    .tiled_panes
+                    .move_pane_edge_to(pane_id, edge, position)
+                    .with_context(err_context)?;
+            }
+            if resized {
+                self.swap_layouts.set_is_tiled_damaged();
+            }
+            resized
+        };
+        if resized {
+            self.set_force_render();
+        }
+        Ok(resized)
+    }
+
     pub fn handle_right_click(
         &mut self,
         event: &MouseEvent,
@@ -3903,6 +4778,32 @@ impl Tab {
         }
         Ok(())
     }
+    pub fn copy_mode_input(&mut self, buf: Vec<u8>, client_id: ClientId) -> Result<()> {
+        // the keys of the keyboard selection are all single bytes (eg. `w`, `Ctrl-v` or `Esc`)
+        let key = match buf.as_slice() {
+            [byte] if byte.is_ascii() => *byte as char,
+            _ => return Ok(()),
+        };
+        let outcome = match self.get_active_pane_or_floating_pane_mut(client_id) {
+            Some(active_pane) => active_pane.copy_mode_input(key),
+            None => return Ok(()),
+        };
+        if outcome == CopyModeOutcome::Copy {
+            self.copy_selection(client_id)
+                .with_context(|| format!("failed to copy selection of client {client_id}"))?;
+            if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+                active_pane.exit_copy_mode();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn copy_mode_is_active(&self, client_id: ClientId) -> bool {
+        self.get_active_pane(client_id)
+            .map(|active_pane| active_pane.copy_mode_is_active())
+            .unwrap_or(false)
+    }
+
     pub fn copy_selection(&self, client_id: ClientId) -> Result<()> {
         let selected_text = self
             .get_active_pane(client_id)
@@ -3912,21 +4813,28 @@ impl Tab {
                 .with_context(|| {
                     format!("failed to write selection to clipboard for client {client_id}")
                 })?;
-            self.senders
-                .send_to_plugin(PluginInstruction::Update(vec![(
-                    None,
-                    None,
-                    Event::CopyToClipboard(self.clipboard_provider.as_copy_destination()),
-                )]))
-                .with_context(|| {
-                    format!("failed to inform plugins about copy selection for client {client_id}")
-                })
-                .non_fatal();
         }
         Ok(())
     }
 
     fn write_selection_to_clipboard(&self, selection: &str) -> Result<()> {
+        self.write_to_clipboard(selection, &self.clipboard_chain)
+    }
+    /// Writes the OSC 52 clipboard write of an application to the clipboard as a selection of the
+    /// user would be, and re-emits it to all connected clients to the clipboard the application
+    /// asked for unless the clipboard providers already did
+    fn forward_clipboard_update(&self, clipboard: Clipboard, content: &str) -> Result<()> {
+        if !self.osc52_forwarding {
+            return self.write_selection_to_clipboard(content);
+        }
+        if self.clipboard_chain.runs_commands() {
+            // copy_command runs on the server host, so it does not reach the clipboard of remote
+            // clients
+            self.write_selection_to_clipboard(content)?;
+        }
+        self.write_to_clipboard(content, &ClipboardChain::osc52(clipboard))
+    }
+    fn write_to_clipboard(&self, selection: &str, clipboard_chain: &ClipboardChain) -> Result<()> {
         let err_context = || format!("failed to write selection to clipboard: '{}'", selection);
 
         let mut output = Output::default();
@@ -3934,28 +4842,21 @@ impl Tab {
             { self.connected_clients.borrow().iter().copied().collect() };
         output.add_clients(&connected_clients, self.link_handler.clone(), None);
         let client_ids = connected_clients.iter().copied();
-        let clipboard_event =
-            match self
-                .clipboard_provider
-                .set_content(selection, &mut output, client_ids)
-            {
-                Ok(_) => output
-                    .serialize()
-                    .and_then(|serialized_output| {
-                        self.senders
-                            .send_to_server(ServerInstruction::Render(Some(serialized_output)))
-                    })
-                    .and_then(|_| {
-                        Ok(Event::CopyToClipboard(
-                            self.clipboard_provider.as_copy_destination(),
-                        ))
-                    })
-                    .with_context(err_context)?,
-                Err(err) => {
-                    Err::<(), _>(err).with_context(err_context).non_fatal();
-                    Event::SystemClipboardFailure
-                },
-            };
+        let clipboard_event = match clipboard_chain.set_content(selection, &mut output, client_ids)
+        {
+            Ok(copy_destination) => output
+                .serialize()
+                .and_then(|serialized_output| {
+                    self.senders
+                        .send_to_server(ServerInstruction::Render(Some(serialized_output)))
+                })
+                .and_then(|_| Ok(Event::CopyToClipboard(copy_destination)))
+                .with_context(err_context)?,
+            Err(err) => {
+                Err::<(), _>(err).with_context(err_context).non_fatal();
+                Event::SystemClipboardFailure
+            },
+        };
         self.senders
             .send_to_plugin(PluginInstruction::Update(vec![(
                 None,
@@ -4064,6 +4965,13 @@ impl Tab {
         self.set_should_clear_display_before_rendering();
         self.set_force_render();
     }
+    pub fn set_session_is_mirrored(&mut self, session_is_mirrored: bool, client_id: ClientId) {
+        self.tiled_panes
+            .set_session_is_mirrored(session_is_mirrored, client_id);
+        self.floating_panes
+            .set_session_is_mirrored(session_is_mirrored);
+        self.set_force_render();
+    }
     pub fn panes_to_hide_count(&self) -> usize {
         self.tiled_panes.panes_to_hide_count()
     }
@@ -4114,6 +5022,12 @@ impl Tab {
         }
     }
 
+    pub fn toggle_search_regex(&mut self, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.toggle_search_regex();
+        }
+    }
+
     pub fn clear_search(&mut self, client_id: ClientId) {
         if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
             active_pane.clear_search();
@@ -4292,6 +5206,62 @@ impl Tab {
                 .insert(pane_id, (is_scrollback_editor, pane));
         }
     }
+    /// Show a pane hidden with `suppress_pane` in the tiled panes, if the tab is in a swap layout
+    /// it is re-applied so that the pane returns to its place in it
+    pub fn unsuppress_tiled_pane(&mut self, pane_id: PaneId) -> Result<()> {
+        let err_context = || format!("failed to unsuppress pane {:?}", pane_id);
+        if let Some((_is_scrollback_editor, pane)) = self.suppressed_panes.remove(&pane_id) {
+            self.add_tiled_pane(pane, pane_id, None)
+                .with_context(err_context)?;
+            if self.auto_layout {
+                self.swap_layouts.set_is_tiled_damaged();
+                self.relayout_tiled_panes(false).with_context(err_context)?;
+                self.senders
+                    .send_to_pty_writer(PtyWriteInstruction::ApplyCachedResizes)
+                    .with_context(err_context)?;
+            }
+        }
+        Ok(())
+    }
+    /// The tiled panes running one of these plugins (eg. "tab-bar" or "zellij:tab-bar")
+    pub fn tiled_pane_ids_running_plugin(&self, plugin_locations: &[&str]) -> Vec<PaneId> {
+        self.tiled_panes
+            .get_panes()
+            .filter(|(_pane_id, pane)| pane_runs_plugin(pane, plugin_locations))
+            .map(|(pane_id, _pane)| *pane_id)
+            .collect()
+    }
+    /// The panes running one of these plugins that were hidden with `suppress_pane`
+    pub fn suppressed_pane_ids_running_plugin(&self, plugin_locations: &[&str]) -> Vec<PaneId> {
+        self.suppressed_panes
+            .iter()
+            .filter(|(pane_id, (_is_scrollback_editor, pane))| {
+                **pane_id == pane.pid() && pane_runs_plugin(pane, plugin_locations)
+            })
+            .map(|(pane_id, _)| *pane_id)
+            .collect()
+    }
+    /// The tiled panes running one of `plugin_locations` that were opened in place of a pane
+    /// running one of `replaced_plugin_locations`
+    pub fn pane_ids_running_plugin_in_place_of(
+        &self,
+        plugin_locations: &[&str],
+        replaced_plugin_locations: &[&str],
+    ) -> Vec<PaneId> {
+        self.suppressed_panes
+            .iter()
+            .filter(|(pane_id, (_is_scrollback_editor, replaced_pane))| {
+                **pane_id != replaced_pane.pid()
+                    && pane_runs_plugin(replaced_pane, replaced_plugin_locations)
+                    && self
+                        .tiled_panes
+                        .get_pane(**pane_id)
+                        .map(|pane| pane_runs_plugin(pane, plugin_locations))
+                        .unwrap_or(false)
+            })
+            .map(|(pane_id, _)| *pane_id)
+            .collect()
+    }
     pub fn pane_infos(&self) -> Vec<PaneInfo> {
         let mut pane_info = vec![];
         let mut tiled_pane_info = self.tiled_panes.pane_info();
@@ -4308,6 +5278,39 @@ impl Tab {
         }
         pane_info
     }
+    /// The lines matching the pattern in the scrollback of every pane of the tab (pane id, line
+    /// number, text)
+    pub fn find_pattern_in_panes(&self, pattern: &Regex) -> Vec<(PaneId, usize, String)> {
+        let mut matches: Vec<(PaneId, usize, String)> = self
+            .tiled_panes
+            .get_panes()
+            .chain(self.floating_panes.get_panes())
+            .chain(
+                self.suppressed_panes
+                    .iter()
+                    .map(|(pane_id, (_is_scrollback_editor, pane))| (pane_id, pane)),
+            )
+            .flat_map(|(pane_id, pane)| {
+                pane.find_pattern(pattern)
+                    .into_iter()
+                    .map(move |(line, text)| (*pane_id, line, text))
+            })
+            .collect();
+        matches.sort_by_key(|(pane_id, line, _text)| (*pane_id, *line));
+        matches
+    }
+    pub fn pane_scrollback_lengths(&self) -> HashMap<PaneId, usize> {
+        self.tiled_panes
+            .get_panes()
+            .chain(self.floating_panes.get_panes())
+            .chain(
+                self.suppressed_panes
+                    .iter()
+                    .map(|(pane_id, (_is_scrollback_editor, pane))| (pane_id, pane)),
+            )
+            .map(|(pane_id, pane)| (*pane_id, pane.scrollback_length()))
+            .collect()
+    }
     pub fn add_floating_pane(
         &mut self,
         mut pane: Box<dyn Pane>,
@@ -4344,6 +5347,28 @@ impl Tab {
         }
         Ok(())
     }
+    /// Adds a floating pane taken out of another tab where it was, if it fits in this tab's
+    /// viewport (eg. a global floating pane following the focused tab)
+    pub fn add_floating_pane_keeping_position(
+        &mut self,
+        pane: Box<dyn Pane>,
+        pane_id: PaneId,
+    ) -> Result<()> {
+        let viewport = *self.viewport.borrow();
+        let geom = pane.position_and_size();
+        let fits_in_viewport = geom.x >= viewport.x
+            && geom.y >= viewport.y
+            && geom.x + geom.cols.as_usize() <= viewport.x + viewport.cols
+            && geom.y + geom.rows.as_usize() <= viewport.y + viewport.rows;
+        if !fits_in_viewport {
+            let should_focus_new_pane = false;
+            return self.add_floating_pane(pane, pane_id, None, should_focus_new_pane);
+        }
+        self.floating_panes.add_pane(pane_id, pane);
+        self.floating_panes.set_force_render();
+        self.set_force_render();
+        Ok(())
+    }
     pub fn add_tiled_pane(
         &mut self,
         mut pane: Box<dyn Pane>,
@@ -4378,6 +5403,70 @@ impl Tab {
         }
         Ok(())
     }
+    /// Adds a pane taken out of another tab next to the focused pane, splitting the focused pane
+    /// in this direction, or wherever a new pane would go
+    pub fn add_tiled_pane_next_to_focused_pane(
+        &mut self,
+        mut pane: Box<dyn Pane>,
+        pane_id: PaneId,
+        direction: Option<Direction>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        self.hide_floating_panes();
+        if self.tiled_panes.fullscreen_is_active() {
+            self.toggle_active_pane_fullscreen(client_id);
+        }
+        let focused_pane_id = self.tiled_panes.get_active_pane_id(client_id);
+        let can_split = match direction {
+            Some(Direction::Left) | Some(Direction::Right) => {
+                self.tiled_panes.can_split_pane_vertically(client_id)
+            },
+            Some(Direction::Up) | Some(Direction::Down) => {
+                self.tiled_panes.can_split_pane_horizontally(client_id)
+            },
+            None => false,
+        };
+        let (Some(direction), Some(focused_pane_id), true) =
+            (direction, focused_pane_id, can_split)
+        else {
+            return self.add_tiled_pane(pane, pane_id, Some(client_id));
+        };
+        pane.set_active_at(Instant::now());
+        match direction {
+            Direction::Left | Direction::Right => {
+                self.tiled_panes
+                    .split_pane_vertically(pane_id, pane, client_id);
+            },
+            Direction::Up | Direction::Down => {
+                self.tiled_panes
+                    .split_pane_horizontally(pane_id, pane, client_id);
+            },
+        }
+        if matches!(direction, Direction::Left | Direction::Up) {
+            // splitting puts the new pane after the focused one, so they trade places
+            let focused_geom = self
+                .tiled_panes
+                .get_pane(focused_pane_id)
+                .map(|pane| pane.position_and_size());
+            let new_geom = self
+                .tiled_panes
+                .get_pane(pane_id)
+                .map(|pane| pane.position_and_size());
+            if let (Some(focused_geom), Some(new_geom)) = (focused_geom, new_geom) {
+                if !focused_geom.is_stacked() {
+                    self.tiled_panes
+                        .set_geom_for_pane_with_id(&focused_pane_id, new_geom);
+                    self.tiled_panes
+                        .set_geom_for_pane_with_id(&pane_id, focused_geom);
+                    self.tiled_panes.reapply_pane_frames();
+                }
+            }
+        }
+        self.set_should_clear_display_before_rendering();
+        self.tiled_panes.focus_pane(pane_id, client_id);
+        self.swap_layouts.set_is_tiled_damaged();
+        Ok(())
+    }
     pub fn request_plugin_permissions(&mut self, pid: u32, permissions: Option<PluginPermission>) {
         let mut should_focus_pane = false;
         if let Some(plugin_pane) = self
@@ -4437,6 +5526,48 @@ impl Tab {
             },
         }
     }
+    /// Runs another command in this terminal pane, whose own command exited (or was stopped),
+    /// optionally keeping what the pane showed so far
+    pub fn replace_terminal_pane_command(
+        &mut self,
+        terminal_pane_id: u32,
+        run_command: RunCommand,
+        keep_scrollback: bool,
+    ) {
+        let pane_id = PaneId::Terminal(terminal_pane_id);
+        match self
+            .floating_panes
+            .get_mut(&pane_id)
+            .or_else(|| self.tiled_panes.get_pane_mut(pane_id))
+            .or_else(|| self.suppressed_panes.get_mut(&pane_id).map(|p| &mut p.1))
+        {
+            Some(pane) => {
+                let scrollback = if keep_scrollback {
+                    pane.serialize(Some(CLOSED_PANE_SCROLLBACK_LINES))
+                } else {
+                    None
+                };
+                // held with the new command, the pane re-runs it (and is reset) like any other
+                let is_first_run = true;
+                pane.hold(None, is_first_run, run_command);
+                if let Some(command_to_run) = pane.rerun() {
+                    if let Some(scrollback) = scrollback.as_ref() {
+                        pane.restore_scrollback(scrollback);
+                    }
+                    self.pids_waiting_resize.insert(terminal_pane_id);
+                    let _ = self
+                        .senders
+                        .send_to_pty(PtyInstruction::ReRunCommandInPane(pane_id, command_to_run));
+                }
+            },
+            None => {
+                log::error!(
+                    "Failed to find terminal pane with id {} to replace its command in tab",
+                    terminal_pane_id
+                );
+            },
+        }
+    }
     pub fn resize_pane_with_id(&mut self, strategy: ResizeStrategy, pane_id: PaneId) -> Result<()> {
         let err_context = || format!("unable to resize pane");
         if self.floating_panes.panes_contain(&pane_id) {
@@ -4492,6 +5623,17 @@ impl Tab {
             pane.update_theme(theme);
         }
     }
+    /// Shows these stats (by terminal id) in the frames of the terminal panes, the panes without
+    /// stats stop showing them
+    pub fn update_process_stats(&mut self, process_stats: &HashMap<u32, ProcessStats>) {
+        for pane_id in self.get_all_pane_ids() {
+            if let PaneId::Terminal(terminal_id) = pane_id {
+                if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
+                    pane.set_process_stats(process_stats.get(&terminal_id).cloned());
+                }
+            }
+        }
+    }
     pub fn update_rounded_corners(&mut self, rounded_corners: bool) {
         self.style.rounded_corners = rounded_corners;
         self.floating_panes
@@ -4523,15 +5665,32 @@ impl Tab {
         }
     }
     pub fn update_copy_options(&mut self, copy_options: &CopyOptions) {
-        self.clipboard_provider = match &copy_options.command {
-            Some(command) => ClipboardProvider::Command(CopyCommand::new(command.clone())),
-            None => ClipboardProvider::Osc52(copy_options.clipboard),
-        };
+        self.clipboard_chain = ClipboardChain::new(copy_options);
         self.copy_on_select = copy_options.copy_on_select;
+        self.osc52_forwarding = copy_options.osc52_forwarding;
+        self.word_separators = copy_options.word_separators.clone();
     }
     pub fn update_auto_layout(&mut self, auto_layout: bool) {
         self.auto_layout = auto_layout;
     }
+    /// Applies to the terminal panes opened from now on
+    pub fn set_alternate_screen_behavior(
+        &mut self,
+        alternate_screen_behavior: AlternateScreenBehavior,
+    ) {
+        self.alternate_screen_behavior = alternate_screen_behavior;
+    }
+    /// Applies to the characters printed to the terminal panes of this tab from now on, what they
+    /// show is redrawn
+    pub fn update_unicode_width(&mut self, unicode_width: UnicodeWidth) {
+        self.unicode_width = unicode_width;
+        for pane_id in self.get_all_pane_ids() {
+            if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
+                pane.update_unicode_width(unicode_width);
+            }
+        }
+        self.set_force_render();
+    }
     pub fn extract_suppressed_panes(&mut self) -> SuppressedPanes {
         self.suppressed_panes.drain().collect()
     }
@@ -4546,6 +5705,18 @@ impl Tab {
             self.set_force_render();
         }
     }
+    pub fn toggle_active_pane_mouse_reporting_override(&mut self, client_id: ClientId) {
+        if let Some(pane) = self.get_active_pane_mut(client_id) {
+            pane.toggle_mouse_reporting_override();
+            self.set_force_render();
+        }
+    }
+    pub fn toggle_active_pane_input_lock(&mut self, client_id: ClientId) {
+        if let Some(pane) = self.get_active_pane_mut(client_id) {
+            pane.toggle_input_lock();
+            self.set_force_render();
+        }
+    }
     pub fn set_floating_pane_pinned(&mut self, pane_id: PaneId, should_be_pinned: bool) {
         if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
             pane.set_pinned(should_be_pinned);
@@ -4601,6 +5772,47 @@ impl Tab {
             self.tiled_panes.expand_pane_in_stack(root_pane_id);
         }
     }
+    pub fn toggle_expand_stack(&mut self, client_id: ClientId) -> Result<()> {
+        if self.floating_panes.panes_are_visible() || self.tiled_panes.fullscreen_is_active() {
+            return Ok(());
+        }
+        self.tiled_panes.toggle_expand_stack(client_id)?;
+        self.swap_layouts.set_is_tiled_damaged();
+        Ok(())
+    }
+    pub fn move_focused_pane_in_stack(&mut self, up: bool, client_id: ClientId) -> Result<()> {
+        if self.floating_panes.panes_are_visible() || self.tiled_panes.fullscreen_is_active() {
+            return Ok(());
+        }
+        let Some(active_pane_id) = self.tiled_panes.get_active_pane_id(client_id) else {
+            return Ok(());
+        };
+        self.tiled_panes.move_pane_in_stack(active_pane_id, up)?;
+        self.swap_layouts.set_is_tiled_damaged();
+        Ok(())
+    }
+    /// Focuses (and expands) the stacked pane whose title best matches the query, returns its id
+    /// if there is one
+    pub fn focus_stacked_pane_by_title(
+        &mut self,
+        query: &str,
+        client_id: ClientId,
+    ) -> Result<Option<PaneId>> {
+        let best_match = self
+            .tiled_panes
+            .get_panes()
+            .filter(|(_, pane)| pane.selectable() && pane.current_geom().is_stacked())
+            .filter_map(|(pane_id, pane)| {
+                title_match_score(&pane.current_title(), query).map(|score| (score, *pane_id))
+            })
+            .min()
+            .map(|(_, pane_id)| pane_id);
+        if let Some(pane_id) = best_match {
+            self.tiled_panes.unset_fullscreen();
+            self.focus_pane_with_id(pane_id, false, client_id)?;
+        }
+        Ok(best_match)
+    }
     pub fn change_floating_pane_coordinates(
         &mut self,
         pane_id: &PaneId,
@@ -4650,6 +5862,8 @@ impl Tab {
             self.styled_underlines,
             self.explicitly_disable_kitty_keyboard_protocol,
         );
+        new_pane.set_alternate_screen_behavior(self.alternate_screen_behavior);
+        new_pane.update_unicode_width(self.unicode_width);
         new_pane.update_name("EDITING SCROLLBACK"); // we do this here and not in the
                                                     // constructor so it won't be overrided
                                                     // by the editor
@@ -4668,6 +5882,44 @@ impl Tab {
     }
 }
 
+fn pane_runs_plugin(pane: &Box<dyn Pane>, plugin_locations: &[&str]) -> bool {
+    match pane.invoked_with() {
+        Some(Run::Plugin(run_plugin_or_alias)) => {
+            plugin_locations.contains(&run_plugin_or_alias.location_string().as_str())
+        },
+        _ => false,
+    }
+}
+
+/// How well a pane title matches a (case insensitive) query, lower is better: titles containing
+/// the query come first (the earlier it appears the better), then those containing its characters
+/// in order (the closer together the better)
+fn title_match_score(title: &str, query: &str) -> Option<(usize, usize)> {
+    let title: Vec<char> = title.to_lowercase().chars().collect();
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+    if let Some(index) = title
+        .windows(query.len())
+        .position(|window| window == query)
+    {
+        return Some((0, index));
+    }
+    let mut query_chars = query.iter().peekable();
+    let mut first_match = None;
+    for (index, character) in title.iter().enumerate() {
+        if query_chars.peek() == Some(&character) {
+            query_chars.next();
+            first_match.get_or_insert(index);
+            if query_chars.peek().is_none() {
+                return first_match.map(|first_match| (1, index - first_match));
+            }
+        }
+    }
+    None
+}
+
 pub fn pane_info_for_pane(pane_id: &PaneId, pane: &Box<dyn Pane>) -> PaneInfo {
     let mut pane_info = PaneInfo::default();
     pane_info.pane_x = pane.x();
@@ -4684,6 +5936,7 @@ pub fn pane_info_for_pane(pane_id: &PaneId, pane: &Box<dyn Pane>) -> PaneInfo {
     pane_info.exited = pane.exited();
     pane_info.exit_status = pane.exit_status();
     pane_info.is_held = pane.is_held();
+    pane_info.cwd = pane.reported_cwd();
 
     match pane_id {
         PaneId::Terminal(terminal_id) => {
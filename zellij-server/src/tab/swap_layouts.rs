@@ -17,6 +17,7 @@ pub struct SwapLayouts {
     current_tiled_layout_position: usize,
     is_floating_damaged: bool,
     is_tiled_damaged: bool,
+    has_base_layout: bool,
     display_area: Rc<RefCell<Size>>, // includes all panes (including eg. the status bar and tab bar in the default layout)
 }
 
@@ -54,6 +55,47 @@ impl SwapLayouts {
             .insert(0, (base_swap_floating_layout, Some("BASE".into())));
         self.current_tiled_layout_position = 0;
         self.current_floating_layout_position = 0;
+        self.has_base_layout = true;
+    }
+    /// Replace the swap layouts with new definitions of them (eg. after their file was edited),
+    /// if they are the `previous` ones - returns false if they are not
+    pub fn reload(
+        &mut self,
+        previous: &(Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+        new: (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+    ) -> bool {
+        let base_layout_count = if self.has_base_layout { 1 } else { 0 };
+        if self.swap_tiled_layouts.get(base_layout_count..) != Some(&previous.0[..])
+            || self.swap_floating_layouts.get(base_layout_count..) != Some(&previous.1[..])
+        {
+            return false;
+        }
+        self.swap_tiled_layouts.truncate(base_layout_count);
+        self.swap_tiled_layouts.extend(new.0);
+        self.swap_floating_layouts.truncate(base_layout_count);
+        self.swap_floating_layouts.extend(new.1);
+        // the layouts we were in might not exist anymore
+        if self.current_tiled_layout_position >= self.swap_tiled_layouts.len() {
+            self.current_tiled_layout_position = 0;
+            self.is_tiled_damaged = true;
+        }
+        if self.current_floating_layout_position >= self.swap_floating_layouts.len() {
+            self.current_floating_layout_position = 0;
+            self.is_floating_damaged = true;
+        }
+        true
+    }
+    /// Whether the panes are currently laid out by one of the swap layouts (rather than by the
+    /// base layout of the tab or by the user)
+    pub fn tiled_swap_layout_is_applied(&self) -> bool {
+        !self.is_tiled_damaged
+            && !(self.has_base_layout && self.current_tiled_layout_position == 0)
+            && self.current_tiled_layout_position < self.swap_tiled_layouts.len()
+    }
+    pub fn floating_swap_layout_is_applied(&self) -> bool {
+        !self.is_floating_damaged
+            && !(self.has_base_layout && self.current_floating_layout_position == 0)
+            && self.current_floating_layout_position < self.swap_floating_layouts.len()
     }
     pub fn set_is_floating_damaged(&mut self) {
         self.is_floating_damaged = true;
@@ -108,6 +150,50 @@ impl SwapLayouts {
             None => (None, self.is_floating_damaged),
         }
     }
+    pub fn tiled_layout_names(&self) -> Vec<String> {
+        Self::layout_names(self.swap_tiled_layouts.iter().map(|l| &l.1))
+    }
+    pub fn floating_layout_names(&self) -> Vec<String> {
+        Self::layout_names(self.swap_floating_layouts.iter().map(|l| &l.1))
+    }
+    fn layout_names<'a>(names: impl Iterator<Item = &'a Option<String>>) -> Vec<String> {
+        // unnamed swap layouts are referred to by their position, as they are in the status bar
+        names
+            .enumerate()
+            .map(|(i, name)| name.clone().unwrap_or_else(|| format!("Layout #{}", i + 1)))
+            .collect()
+    }
+    /// Make the swap layout with this name the next one to be tried when swapping (this is the
+    /// one that will be applied if it fits the panes in the tab), returns false if there is no
+    /// such layout
+    pub fn select_tiled_layout(&mut self, swap_layout_name: &str) -> bool {
+        match self
+            .tiled_layout_names()
+            .iter()
+            .position(|name| name == swap_layout_name)
+        {
+            Some(position) => {
+                self.current_tiled_layout_position = position;
+                self.is_tiled_damaged = true;
+                true
+            },
+            None => false,
+        }
+    }
+    pub fn select_floating_layout(&mut self, swap_layout_name: &str) -> bool {
+        match self
+            .floating_layout_names()
+            .iter()
+            .position(|name| name == swap_layout_name)
+        {
+            Some(position) => {
+                self.current_floating_layout_position = position;
+                self.is_floating_damaged = true;
+                true
+            },
+            None => false,
+        }
+    }
     pub fn swap_floating_panes(
         &mut self,
         floating_panes: &FloatingPanes,
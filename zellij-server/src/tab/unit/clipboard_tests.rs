@@ -0,0 +1,68 @@
+use super::ClipboardChain;
+use crate::output::Output;
+use crate::screen::CopyOptions;
+use zellij_utils::data::CopyDestination;
+use zellij_utils::input::options::{Clipboard, CopyProvider};
+
+fn clipboard_chain(command: Option<&str>, providers: Vec<CopyProvider>) -> ClipboardChain {
+    ClipboardChain::new(&CopyOptions::new(
+        command.map(String::from),
+        providers,
+        Clipboard::System,
+        true,
+        true,
+        String::new(),
+    ))
+}
+
+#[test]
+fn copy_command_takes_the_content() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let copied_file = temp_dir.path().join("copied");
+    let chain = clipboard_chain(
+        Some(&format!("tee {}", copied_file.display())),
+        vec![CopyProvider::Osc52],
+    );
+    let destination = chain
+        .set_content("copied text", &mut Output::default(), vec![1].into_iter())
+        .unwrap();
+    assert_eq!(destination, CopyDestination::Command);
+    assert_eq!(
+        std::fs::read_to_string(&copied_file).unwrap(),
+        "copied text"
+    );
+}
+
+#[test]
+fn failing_copy_command_falls_through_to_the_next_provider() {
+    let chain = clipboard_chain(Some("false"), vec![CopyProvider::Osc52]);
+    let destination = chain
+        .set_content("copied text", &mut Output::default(), vec![1].into_iter())
+        .unwrap();
+    assert_eq!(destination, CopyDestination::System);
+}
+
+#[test]
+fn copy_command_that_cannot_be_run_falls_through_to_the_next_provider() {
+    let chain = clipboard_chain(
+        Some("zellij-no-such-copy-command"),
+        vec![CopyProvider::Osc52],
+    );
+    let destination = chain
+        .set_content("copied text", &mut Output::default(), vec![1].into_iter())
+        .unwrap();
+    assert_eq!(destination, CopyDestination::System);
+}
+
+#[test]
+fn copying_fails_when_every_provider_fails() {
+    let chain = clipboard_chain(Some("false"), vec![]);
+    let result = chain.set_content("copied text", &mut Output::default(), vec![1].into_iter());
+    assert!(result.is_err());
+}
+
+#[test]
+fn copy_command_and_command_providers_run_commands() {
+    assert!(clipboard_chain(Some("tee copied"), vec![CopyProvider::Osc52]).runs_commands());
+    assert!(!clipboard_chain(None, vec![CopyProvider::Osc52]).runs_commands());
+}
@@ -1,4 +1,4 @@
-use super::{Output, Tab};
+use super::{CloseTarget, Output, Pane, Tab};
 use crate::panes::sixel::SixelImageStore;
 use crate::screen::CopyOptions;
 use crate::Arc;
@@ -8,7 +8,7 @@ use crate::{
     panes::PaneId,
     plugins::PluginInstruction,
     thread_bus::ThreadSenders,
-    ClientId,
+    ClientId, ServerInstruction,
 };
 use std::path::PathBuf;
 use std::sync::Mutex;
@@ -24,6 +24,7 @@ use zellij_utils::input::layout::{
     SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
 };
 use zellij_utils::input::mouse::MouseEvent;
+use zellij_utils::input::options::{Clipboard, CopyProvider};
 use zellij_utils::input::plugins::PluginTag;
 use zellij_utils::ipc::IpcReceiverWithContext;
 use zellij_utils::pane_size::{Size, SizeInPixels};
@@ -67,6 +68,7 @@ impl ServerOsApi for FakeInputOutput {
         _file_to_open: TerminalAction,
         _quit_db: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
         _default_editor: Option<PathBuf>,
+        _session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(u32, RawFd, RawFd)> {
         unimplemented!()
     }
@@ -129,6 +131,7 @@ impl ServerOsApi for FakeInputOutput {
         _terminal_id: u32,
         _run_command: RunCommand,
         _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        _session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(RawFd, RawFd)> {
         unimplemented!()
     }
@@ -591,6 +594,92 @@ fn create_new_tab_with_mock_pty_writer(
     tab
 }
 
+fn create_new_tab_with_copy_options(
+    size: Size,
+    copy_options: CopyOptions,
+    to_server: SenderWithContext<ServerInstruction>,
+) -> Tab {
+    set_session_name("test".into());
+    let index = 0;
+    let position = 0;
+    let name = String::new();
+    let os_api = Box::new(FakeInputOutput::default());
+    let mut senders = ThreadSenders::default().silently_fail_on_send();
+    senders.replace_to_server(to_server);
+    let max_panes = None;
+    let mode_info = ModeInfo::default();
+    let style = Style::default();
+    let draw_pane_frames = true;
+    let auto_layout = true;
+    let client_id = 1;
+    let session_is_mirrored = true;
+    let mut connected_clients = HashSet::new();
+    connected_clients.insert(client_id);
+    let connected_clients = Rc::new(RefCell::new(connected_clients));
+    let character_cell_info = Rc::new(RefCell::new(None));
+    let stacked_resize = Rc::new(RefCell::new(true));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut tab = Tab::new(
+        index,
+        position,
+        name,
+        size,
+        character_cell_info,
+        stacked_resize,
+        sixel_image_store,
+        os_api,
+        senders,
+        max_panes,
+        style,
+        mode_info,
+        draw_pane_frames,
+        auto_layout,
+        connected_clients,
+        session_is_mirrored,
+        Some(client_id),
+        copy_options,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        (vec![], vec![]), // swap layouts
+        PathBuf::from("my_default_shell"),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        explicitly_disable_kitty_keyboard_protocol,
+        None,
+    );
+    tab.apply_layout(
+        TiledPaneLayout::default(),
+        vec![],
+        vec![(1, None)],
+        vec![],
+        HashMap::new(),
+        client_id,
+    )
+    .unwrap();
+    tab
+}
+
+// the clipboard writes rendered to the clients by the tab, as OSC 52 sequences
+fn rendered_clipboard_writes(
+    server_receiver: &Receiver<(ServerInstruction, ErrorContext)>,
+) -> Vec<String> {
+    server_receiver
+        .try_iter()
+        .filter_map(|(instruction, _)| match instruction {
+            ServerInstruction::Render(Some(output)) => output.get(&1).cloned(),
+            _ => None,
+        })
+        .filter(|output| output.contains("\u{1b}]52;"))
+        .collect()
+}
+
 fn create_new_tab_with_sixel_support(
     size: Size,
     sixel_image_store: Rc<RefCell<SixelImageStore>>,
@@ -999,7 +1088,7 @@ fn dump_screen() {
     tab.handle_pty_bytes(2, Vec::from("scratch".as_bytes()))
         .unwrap();
     let file = "/tmp/log.sh";
-    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, false)
+    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, false, false)
         .unwrap();
     assert_eq!(
         map.lock().unwrap().get(file).unwrap(),
@@ -1008,6 +1097,34 @@ fn dump_screen() {
     );
 }
 
+#[test]
+fn dump_screen_with_ansi_styles() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    let map = Arc::new(Mutex::new(HashMap::new()));
+    tab.os_api = Box::new(FakeInputOutput {
+        file_dumps: map.clone(),
+        ..Default::default()
+    });
+    let new_pane_id = PaneId::Terminal(2);
+    tab.new_pane(new_pane_id, None, None, None, None, false, Some(client_id))
+        .unwrap();
+    tab.handle_pty_bytes(2, Vec::from("\u{1b}[31mred\u{1b}[m plain".as_bytes()))
+        .unwrap();
+    let file = "/tmp/log-with-ansi.sh";
+    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, true, true)
+        .unwrap();
+    assert_eq!(
+        map.lock().unwrap().get(file).unwrap(),
+        "\u{1b}[31mred\u{1b}[m plain",
+        "screen was dumped with its styles"
+    );
+}
+
 #[test]
 fn clear_screen() {
     let size = Size {
@@ -1028,7 +1145,7 @@ fn clear_screen() {
         .unwrap();
     let file = "/tmp/log-clear-screen.sh";
     tab.clear_active_terminal_screen(client_id).unwrap();
-    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, false)
+    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, false, false)
         .unwrap();
     assert_eq!(
         map.lock().unwrap().get(file).unwrap(),
@@ -1037,6 +1154,98 @@ fn clear_screen() {
     );
 }
 
+#[test]
+fn sync_input_is_indicated_in_pane_frames() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    let mut output = Output::default();
+    tab.new_pane(
+        PaneId::Terminal(2),
+        None,
+        None,
+        None,
+        None,
+        false,
+        Some(client_id),
+    )
+    .unwrap();
+    tab.toggle_sync_panes_is_active();
+    tab.render(&mut output).unwrap();
+    let snapshot = take_snapshot(
+        output.serialize().unwrap().get(&client_id).unwrap(),
+        size.rows,
+        size.cols,
+        Palette::default(),
+    );
+    assert_eq!(
+        snapshot.matches("SYNC INPUT").count(),
+        2,
+        "both pane frames indicate that input is synced"
+    );
+
+    tab.toggle_sync_panes_is_active();
+    let mut output = Output::default();
+    tab.render(&mut output).unwrap();
+    let snapshot = take_snapshot(
+        output.serialize().unwrap().get(&client_id).unwrap(),
+        size.rows,
+        size.cols,
+        Palette::default(),
+    );
+    assert!(
+        !snapshot.contains("SYNC INPUT"),
+        "indication removed once sync is toggled off"
+    );
+}
+
+#[test]
+fn close_confirmation_is_shown_in_the_frame_of_the_asking_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    tab.new_pane(
+        PaneId::Terminal(2),
+        None,
+        None,
+        None,
+        None,
+        false,
+        Some(client_id),
+    )
+    .unwrap();
+    let asked = tab
+        .get_pane_with_id_mut(PaneId::Terminal(2))
+        .unwrap()
+        .ask_to_confirm_close(
+            CloseTarget::Tab(0),
+            vec!["htop".to_owned(), "vim".to_owned()],
+        );
+    assert!(asked, "terminal panes can ask to confirm a close");
+    let mut output = Output::default();
+    tab.render(&mut output).unwrap();
+    let snapshot = take_snapshot(
+        output.serialize().unwrap().get(&client_id).unwrap(),
+        size.rows,
+        size.cols,
+        Palette::default(),
+    );
+    assert_eq!(
+        snapshot
+            .matches("[ RUNNING: htop, vim ] <y> close tab, <n> cancel")
+            .count(),
+        1,
+        "only the asking pane shows the running programs and how to answer, got:\n{}",
+        snapshot
+    );
+}
+
 #[test]
 fn new_floating_pane() {
     let size = Size {
@@ -2178,6 +2387,111 @@ fn drag_pane_with_mouse() {
     assert_snapshot!(snapshot);
 }
 
+#[test]
+fn resize_tiled_panes_by_dragging_their_border_with_mouse() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    tab.vertical_split(PaneId::Terminal(2), None, client_id)
+        .unwrap();
+    let pane_geom = |tab: &Tab, pane_id: u32| {
+        tab.tiled_panes
+            .panes
+            .get(&PaneId::Terminal(pane_id))
+            .unwrap()
+            .position_and_size()
+    };
+    let right_border = pane_geom(&tab, 1).cols.as_usize() - 1;
+    tab.handle_mouse_event(
+        &MouseEvent::new_left_press_event(Position::new(10, right_border as u16)),
+        client_id,
+    )
+    .unwrap();
+    tab.handle_mouse_event(
+        &MouseEvent::new_left_motion_event(Position::new(10, 70)),
+        client_id,
+    )
+    .unwrap();
+    tab.handle_mouse_event(
+        &MouseEvent::new_left_release_event(Position::new(10, 70)),
+        client_id,
+    )
+    .unwrap();
+    let left_pane_cols = pane_geom(&tab, 1).cols.as_usize();
+    assert!(
+        (70..=72).contains(&left_pane_cols),
+        "border dragged to the mouse position, got {} columns",
+        left_pane_cols
+    );
+    assert_eq!(
+        pane_geom(&tab, 2).x,
+        left_pane_cols,
+        "the neighboring pane shrunk"
+    );
+    tab.handle_mouse_event(
+        &MouseEvent::new_left_motion_event(Position::new(10, 30)),
+        client_id,
+    )
+    .unwrap();
+    assert_eq!(
+        pane_geom(&tab, 1).cols.as_usize(),
+        left_pane_cols,
+        "no resizing once the mouse was released"
+    );
+}
+
+#[test]
+fn resize_floating_pane_by_dragging_its_corner_with_mouse() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    let new_pane_id = PaneId::Terminal(2);
+    tab.toggle_floating_panes(Some(client_id), None).unwrap();
+    tab.new_pane(new_pane_id, None, None, None, None, false, Some(client_id))
+        .unwrap();
+    let pane_geom = |tab: &Tab| {
+        tab.floating_panes
+            .get_pane(new_pane_id)
+            .unwrap()
+            .position_and_size()
+    };
+    let geom = pane_geom(&tab);
+    let bottom_right_corner = Position::new(
+        (geom.y + geom.rows.as_usize() - 1) as i32,
+        (geom.x + geom.cols.as_usize() - 1) as u16,
+    );
+    tab.handle_mouse_event(
+        &MouseEvent::new_left_press_event(bottom_right_corner),
+        client_id,
+    )
+    .unwrap();
+    tab.handle_mouse_event(
+        &MouseEvent::new_left_release_event(Position::new(
+            bottom_right_corner.line() as i32 + 2,
+            bottom_right_corner.column() as u16 + 10,
+        )),
+        client_id,
+    )
+    .unwrap();
+    let resized_geom = pane_geom(&tab);
+    assert_eq!(
+        (resized_geom.x, resized_geom.y),
+        (geom.x, geom.y),
+        "the opposite corner did not move"
+    );
+    assert_eq!(
+        (resized_geom.cols.as_usize(), resized_geom.rows.as_usize()),
+        (geom.cols.as_usize() + 10, geom.rows.as_usize() + 2),
+        "pane grew with the dragged corner"
+    );
+}
+
 #[test]
 fn mark_text_inside_floating_pane() {
     let size = Size {
@@ -3410,40 +3724,131 @@ fn pane_in_sgr_normal_event_tracking_mouse_mode() {
     .unwrap();
     tab.handle_right_mouse_release(&Position::new(7, 75), client_id)
         .unwrap();
-    tab.handle_middle_click(
-        &MouseEvent::new_middle_press_event(Position::new(5, 71)),
-        client_id,
-    )
-    .unwrap();
-    tab.handle_mouse_event(
-        &MouseEvent::new_middle_motion_event(Position::new(9, 72)),
-        client_id,
+    tab.handle_middle_click(
+        &MouseEvent::new_middle_press_event(Position::new(5, 71)),
+        client_id,
+    )
+    .unwrap();
+    tab.handle_mouse_event(
+        &MouseEvent::new_middle_motion_event(Position::new(9, 72)),
+        client_id,
+    )
+    .unwrap();
+    tab.handle_middle_mouse_release(&Position::new(7, 75), client_id)
+        .unwrap();
+    tab.handle_scrollwheel_up(&Position::new(5, 71), 1, client_id)
+        .unwrap();
+    tab.handle_scrollwheel_down(&Position::new(5, 71), 1, client_id)
+        .unwrap();
+
+    pty_instruction_bus.exit();
+
+    assert_eq!(
+        pty_instruction_bus.clone_output(),
+        vec![
+            "\u{1b}[<0;71;5M".to_string(), // SGR left click
+            // no hold event here, as hold events are not reported in normal mode
+            "\u{1b}[<0;75;7m".to_string(), // SGR left button release
+            "\u{1b}[<2;71;5M".to_string(), // SGR right click
+            // no hold event here, as hold events are not reported in normal mode
+            "\u{1b}[<2;75;7m".to_string(), // SGR right button release
+            "\u{1b}[<1;71;5M".to_string(), // SGR middle click
+            // no hold event here, as hold events are not reported in normal mode
+            "\u{1b}[<1;75;7m".to_string(),  // SGR middle button release
+            "\u{1b}[<64;71;5M".to_string(), // SGR scroll up
+            "\u{1b}[<65;71;5M".to_string(), // SGR scroll down
+        ]
+    );
+}
+
+#[test]
+fn pane_with_mouse_reporting_override_does_not_forward_mouse_events() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+
+    let mut pty_instruction_bus = MockPtyInstructionBus::new();
+    let mut tab = create_new_tab_with_mock_pty_writer(
+        size,
+        ModeInfo::default(),
+        pty_instruction_bus.pty_write_sender(),
+    );
+    pty_instruction_bus.start();
+
+    let sgr_mouse_mode_any_button = String::from("\u{1b}[?1000;1006h"); // normal event tracking (1000) with sgr encoding (1006)
+    tab.handle_pty_bytes(1, sgr_mouse_mode_any_button.as_bytes().to_vec())
+        .unwrap();
+    tab.toggle_active_pane_mouse_reporting_override(client_id);
+    tab.handle_mouse_event(
+        &MouseEvent::new_left_press_event(Position::new(5, 71)),
+        client_id,
+    )
+    .unwrap();
+    tab.handle_left_mouse_release(
+        &MouseEvent::new_left_release_event(Position::new(7, 75)),
+        client_id,
+    )
+    .unwrap();
+    tab.handle_scrollwheel_up(&Position::new(5, 71), 1, client_id)
+        .unwrap();
+    tab.toggle_active_pane_mouse_reporting_override(client_id);
+    tab.handle_scrollwheel_down(&Position::new(5, 71), 1, client_id)
+        .unwrap();
+
+    pty_instruction_bus.exit();
+
+    assert_eq!(
+        pty_instruction_bus.clone_output(),
+        vec![
+            "\u{1b}[<65;71;5M".to_string(), // SGR scroll down, after the override was toggled off
+        ]
+    );
+}
+
+#[test]
+fn pane_with_locked_input_ignores_typed_keys_only() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+
+    let mut pty_instruction_bus = MockPtyInstructionBus::new();
+    let mut tab = create_new_tab_with_mock_pty_writer(
+        size,
+        ModeInfo::default(),
+        pty_instruction_bus.pty_write_sender(),
+    );
+    pty_instruction_bus.start();
+
+    tab.toggle_active_pane_input_lock(client_id);
+    assert!(tab.has_input_locked_pane());
+    tab.write_keys_from_client(&None, "locked".as_bytes().to_vec(), false, client_id)
+        .unwrap();
+    tab.toggle_sync_panes_is_active();
+    tab.write_keys_from_client(&None, "synced".as_bytes().to_vec(), false, client_id)
+        .unwrap();
+    tab.toggle_sync_panes_is_active();
+    tab.write_to_pane_id(
+        &None,
+        "written by a plugin".as_bytes().to_vec(),
+        false,
+        PaneId::Terminal(1),
+        None,
     )
     .unwrap();
-    tab.handle_middle_mouse_release(&Position::new(7, 75), client_id)
-        .unwrap();
-    tab.handle_scrollwheel_up(&Position::new(5, 71), 1, client_id)
-        .unwrap();
-    tab.handle_scrollwheel_down(&Position::new(5, 71), 1, client_id)
+    tab.toggle_active_pane_input_lock(client_id);
+    assert!(!tab.has_input_locked_pane());
+    tab.write_keys_from_client(&None, "unlocked".as_bytes().to_vec(), false, client_id)
         .unwrap();
 
     pty_instruction_bus.exit();
 
     assert_eq!(
         pty_instruction_bus.clone_output(),
-        vec![
-            "\u{1b}[<0;71;5M".to_string(), // SGR left click
-            // no hold event here, as hold events are not reported in normal mode
-            "\u{1b}[<0;75;7m".to_string(), // SGR left button release
-            "\u{1b}[<2;71;5M".to_string(), // SGR right click
-            // no hold event here, as hold events are not reported in normal mode
-            "\u{1b}[<2;75;7m".to_string(), // SGR right button release
-            "\u{1b}[<1;71;5M".to_string(), // SGR middle click
-            // no hold event here, as hold events are not reported in normal mode
-            "\u{1b}[<1;75;7m".to_string(),  // SGR middle button release
-            "\u{1b}[<64;71;5M".to_string(), // SGR scroll up
-            "\u{1b}[<65;71;5M".to_string(), // SGR scroll down
-        ]
+        vec!["written by a plugin", "unlocked"]
     );
 }
 
@@ -4106,6 +4511,34 @@ fn move_floating_pane_focus_sends_tty_csi_event() {
     assert_snapshot!(format!("{:?}", *tty_stdin_bytes.lock().unwrap()));
 }
 
+#[test]
+fn client_terminal_focus_change_sends_tty_csi_event_to_active_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let tty_stdin_bytes = Arc::new(Mutex::new(BTreeMap::new()));
+    let os_api = Box::new(FakeInputOutput {
+        tty_stdin_bytes: tty_stdin_bytes.clone(),
+        ..Default::default()
+    });
+    let mut tab = create_new_tab_with_os_api(size, ModeInfo::default(), &os_api);
+    tab.handle_pty_bytes(
+        1,
+        // subscribe to focus events
+        Vec::from("\u{1b}[?1004h".as_bytes()),
+    )
+    .unwrap();
+    tab.client_terminal_focus_changed(client_id, false);
+    tab.client_terminal_focus_changed(client_id, true);
+    assert_eq!(
+        tty_stdin_bytes.lock().unwrap().get(&1),
+        Some(&Vec::from("\u{1b}[O\u{1b}[I".as_bytes())),
+        "focus out and focus in events sent to the active pane"
+    );
+}
+
 #[test]
 fn toggle_floating_panes_on_sends_tty_csi_event() {
     let size = Size {
@@ -4726,6 +5159,101 @@ fn move_focus_up_with_stacked_panes() {
     assert_snapshot!(snapshot);
 }
 
+#[test]
+fn expand_reorder_and_focus_panes_of_a_stack() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let swap_layouts = r#"
+        layout {
+            swap_tiled_layout {
+                tab {
+                    pane split_direction="vertical" {
+                        pane focus=true
+                        pane stacked=true { children; }
+                    }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(swap_layouts, Some("file_name.kdl".into()), None, None).unwrap();
+    let swap_tiled_layouts = layout.swap_tiled_layouts.clone();
+    let swap_floating_layouts = layout.swap_floating_layouts.clone();
+    let stacked_resize = true;
+    let mut tab = create_new_tab_with_swap_layouts(
+        size,
+        ModeInfo::default(),
+        (swap_tiled_layouts, swap_floating_layouts),
+        None,
+        true,
+        stacked_resize,
+    );
+    for pid in [2, 3, 4] {
+        tab.new_pane(
+            PaneId::Terminal(pid),
+            None,
+            None,
+            None,
+            None,
+            false,
+            Some(client_id),
+        )
+        .unwrap();
+    }
+    let geom = |tab: &Tab, pid: u32| {
+        tab.get_pane_with_id(PaneId::Terminal(pid))
+            .unwrap()
+            .position_and_size()
+    };
+    let stack_is_stacked = |tab: &Tab| [2, 3, 4].iter().all(|pid| geom(tab, *pid).is_stacked());
+    assert!(stack_is_stacked(&tab), "panes start out in a stack");
+
+    tab.focus_pane_with_id(PaneId::Terminal(3), false, client_id)
+        .unwrap();
+    tab.toggle_expand_stack(client_id).unwrap();
+    assert!(
+        [2, 3, 4].iter().all(|pid| !geom(&tab, *pid).is_stacked()),
+        "all the panes of the stack are expanded"
+    );
+    assert!(
+        geom(&tab, 2).y < geom(&tab, 3).y && geom(&tab, 3).y < geom(&tab, 4).y,
+        "expanded panes keep the order of the stack"
+    );
+    tab.toggle_expand_stack(client_id).unwrap();
+    assert!(stack_is_stacked(&tab), "expanded panes are stacked back");
+
+    tab.move_focused_pane_in_stack(true, client_id).unwrap();
+    assert!(
+        geom(&tab, 3).y < geom(&tab, 2).y,
+        "focused pane swapped with the one above it"
+    );
+    assert_eq!(
+        tab.get_active_pane_id(client_id),
+        Some(PaneId::Terminal(3)),
+        "moved pane stays focused"
+    );
+
+    tab.rename_pane("server logs".as_bytes().to_vec(), PaneId::Terminal(4))
+        .unwrap();
+    assert_eq!(
+        tab.focus_stacked_pane_by_title("logs", client_id).unwrap(),
+        Some(PaneId::Terminal(4))
+    );
+    assert_eq!(
+        tab.get_active_pane_id(client_id),
+        Some(PaneId::Terminal(4)),
+        "best matching stacked pane focused"
+    );
+    assert_eq!(
+        tab.focus_stacked_pane_by_title("zzz", client_id).unwrap(),
+        None,
+        "no stacked pane matches"
+    );
+    assert_eq!(tab.get_active_pane_id(client_id), Some(PaneId::Terminal(4)));
+}
+
 #[test]
 fn move_focus_down_with_stacked_panes() {
     let size = Size {
@@ -7256,6 +7784,168 @@ fn base_layout_is_included_in_swap_layouts() {
     assert_snapshot!(snapshot);
 }
 
+#[test]
+fn select_swap_layout_by_name() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let base_layout = r#"
+        layout {
+            pane
+            pane
+        }
+    "#;
+    let swap_layouts = r#"
+        layout {
+            swap_tiled_layout name="vertical" {
+                tab split_direction="vertical" {
+                    pane
+                    pane
+                }
+            }
+        }
+    "#;
+    let render_tab = |select_by_name: bool| {
+        let (base_layout, base_floating_layout) =
+            Layout::from_kdl(base_layout, Some("file_name.kdl".into()), None, None)
+                .unwrap()
+                .template
+                .unwrap();
+        let swap_layout =
+            Layout::from_kdl(swap_layouts, Some("file_name.kdl".into()), None, None).unwrap();
+        let mut tab = create_new_tab_with_swap_layouts(
+            size,
+            ModeInfo::default(),
+            (
+                swap_layout.swap_tiled_layouts.clone(),
+                swap_layout.swap_floating_layouts.clone(),
+            ),
+            Some((
+                base_layout,
+                base_floating_layout,
+                vec![(1, None), (2, None)],
+                vec![],
+                HashMap::new(),
+            )),
+            true,
+            true,
+        );
+        assert_eq!(
+            tab.swap_layout_names(),
+            vec!["BASE".to_owned(), "vertical".to_owned()],
+            "swap layout names include the base layout"
+        );
+        if select_by_name {
+            assert!(
+                !tab.select_swap_layout("no-such-layout").unwrap(),
+                "unknown swap layout names are reported"
+            );
+            assert!(tab.select_swap_layout("vertical").unwrap());
+        } else {
+            tab.next_swap_layout().unwrap();
+        }
+        let mut output = Output::default();
+        tab.render(&mut output).unwrap();
+        take_snapshot(
+            output.serialize().unwrap().get(&client_id).unwrap(),
+            size.rows,
+            size.cols,
+            Palette::default(),
+        )
+    };
+    assert_eq!(
+        render_tab(true),
+        render_tab(false),
+        "selecting a swap layout by name applies it"
+    );
+}
+
+#[test]
+fn reloading_swap_layouts_applies_the_new_definition_of_the_current_one() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let base_layout = r#"
+        layout {
+            pane
+            pane
+        }
+    "#;
+    let swap_layouts = |first_pane_size: &str| {
+        let swap_layouts = format!(
+            r#"
+            layout {{
+                swap_tiled_layout name="side-by-side" {{
+                    tab split_direction="vertical" {{
+                        pane size="{}"
+                        pane
+                    }}
+                }}
+            }}
+        "#,
+            first_pane_size
+        );
+        let swap_layout =
+            Layout::from_kdl(&swap_layouts, Some("file_name.kdl".into()), None, None).unwrap();
+        (
+            swap_layout.swap_tiled_layouts.clone(),
+            swap_layout.swap_floating_layouts.clone(),
+        )
+    };
+    let (base_layout, base_floating_layout) =
+        Layout::from_kdl(base_layout, Some("file_name.kdl".into()), None, None)
+            .unwrap()
+            .template
+            .unwrap();
+    let mut tab = create_new_tab_with_swap_layouts(
+        size,
+        ModeInfo::default(),
+        swap_layouts("50%"),
+        Some((
+            base_layout,
+            base_floating_layout,
+            vec![(1, None), (2, None)],
+            vec![],
+            HashMap::new(),
+        )),
+        true,
+        true,
+    );
+    let narrowest_pane_cols = |tab: &Tab| {
+        tab.tiled_panes
+            .panes
+            .values()
+            .map(|pane| pane.position_and_size().cols.as_usize())
+            .min()
+            .unwrap()
+    };
+    assert!(tab.select_swap_layout("side-by-side").unwrap());
+    assert_eq!(narrowest_pane_cols(&tab), 60, "swap layout is applied");
+
+    tab.reload_swap_layouts(&swap_layouts("20%"), swap_layouts("30%"))
+        .unwrap();
+    assert_eq!(
+        narrowest_pane_cols(&tab),
+        60,
+        "swap layouts that are not the ones of the tab are ignored"
+    );
+
+    tab.reload_swap_layouts(&swap_layouts("50%"), swap_layouts("30%"))
+        .unwrap();
+    assert_eq!(
+        narrowest_pane_cols(&tab),
+        36,
+        "new definition of the current swap layout is applied"
+    );
+    assert_eq!(
+        tab.swap_layout_names(),
+        vec!["BASE".to_owned(), "side-by-side".to_owned()]
+    );
+}
+
 #[test]
 fn swap_layouts_including_command_panes_absent_from_existing_layout() {
     let size = Size {
@@ -9183,3 +9873,95 @@ fn can_define_expanded_pane_in_stack() {
     );
     assert_snapshot!(snapshot);
 }
+
+#[test]
+fn clipboard_writes_of_applications_are_forwarded_to_the_clients() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let (to_server, server_receiver) = channels::unbounded();
+    let mut tab = create_new_tab_with_copy_options(
+        size,
+        CopyOptions::default(),
+        SenderWithContext::new(to_server),
+    );
+    tab.handle_pty_bytes(
+        1,
+        format!("\u{1b}]52;p;{}\u{1b}\\", base64::encode("copied")).into_bytes(),
+    )
+    .unwrap();
+    let clipboard_writes = rendered_clipboard_writes(&server_receiver);
+    assert_eq!(clipboard_writes.len(), 1);
+    #[cfg(not(target_os = "macos"))]
+    assert!(
+        clipboard_writes[0].contains(&format!("\u{1b}]52;p;{}", base64::encode("copied"))),
+        "to the clipboard the application asked for"
+    );
+}
+
+#[test]
+fn clipboard_writes_of_applications_go_through_the_copy_command() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let temp_dir = tempfile::tempdir().unwrap();
+    let copied_file = temp_dir.path().join("copied");
+    let copy_options = CopyOptions::new(
+        Some(format!("tee {}", copied_file.display())),
+        vec![CopyProvider::Osc52],
+        Clipboard::System,
+        true,
+        true,
+        String::new(),
+    );
+    let (to_server, server_receiver) = channels::unbounded();
+    let mut tab =
+        create_new_tab_with_copy_options(size, copy_options, SenderWithContext::new(to_server));
+    tab.handle_pty_bytes(
+        1,
+        format!("\u{1b}]52;c;{}\u{1b}\\", base64::encode("copied")).into_bytes(),
+    )
+    .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&copied_file).unwrap(),
+        "copied",
+        "the copy command is run"
+    );
+    assert_eq!(
+        rendered_clipboard_writes(&server_receiver).len(),
+        1,
+        "and the write still reaches the clients"
+    );
+}
+
+#[test]
+fn clipboard_writes_of_applications_are_not_dropped_without_forwarding() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let copy_options = CopyOptions::new(
+        None,
+        vec![CopyProvider::Osc52],
+        Clipboard::System,
+        true,
+        false,
+        String::new(),
+    );
+    let (to_server, server_receiver) = channels::unbounded();
+    let mut tab =
+        create_new_tab_with_copy_options(size, copy_options, SenderWithContext::new(to_server));
+    tab.handle_pty_bytes(
+        1,
+        format!("\u{1b}]52;p;{}\u{1b}\\", base64::encode("copied")).into_bytes(),
+    )
+    .unwrap();
+    let clipboard_writes = rendered_clipboard_writes(&server_receiver);
+    assert_eq!(clipboard_writes.len(), 1);
+    assert!(
+        clipboard_writes[0].contains(&format!("\u{1b}]52;c;{}", base64::encode("copied"))),
+        "to the configured clipboard, as before the option"
+    );
+}
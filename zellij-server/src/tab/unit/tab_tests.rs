@@ -10,12 +10,12 @@ use crate::{
 use std::path::PathBuf;
 use zellij_utils::data::{Direction, Resize, ResizeStrategy};
 use zellij_utils::errors::prelude::*;
-use zellij_utils::input::layout::{SplitDirection, SplitSize, TiledPaneLayout};
+use zellij_utils::input::layout::{SizeLimits, SplitDirection, SplitSize, TiledPaneLayout};
 use zellij_utils::ipc::IpcReceiverWithContext;
 use zellij_utils::pane_size::{Size, SizeInPixels};
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::os::unix::io::RawFd;
 use std::rc::Rc;
 
@@ -46,6 +46,7 @@ impl ServerOsApi for FakeInputOutput {
         _file_to_open: TerminalAction,
         _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
         _default_editor: Option<PathBuf>,
+        _session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(u32, RawFd, RawFd)> {
         unimplemented!()
     }
@@ -98,6 +99,7 @@ impl ServerOsApi for FakeInputOutput {
         _terminal_id: u32,
         _run_command: RunCommand,
         _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        _session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(RawFd, RawFd)> {
         unimplemented!()
     }
@@ -6168,6 +6170,97 @@ pub fn cannot_resize_down_when_pane_has_fixed_rows() {
     );
 }
 
+#[test]
+pub fn cannot_resize_pane_past_its_max_size() {
+    // ┌─────┬─────┐                   ┌──────┬────┐
+    // │█████│     │                   │██████│    │
+    // │█████│     │ ==resize=right==> │██████│    │
+    // │█████│     │                   │██████│    │
+    // └─────┴─────┘                   └──────┴────┘
+    // █ == focused pane, with a max_size of 70 columns
+
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    let mut limited_child = TiledPaneLayout::default();
+    limited_child.size_limits = Some(SizeLimits {
+        max: Some(70),
+        ..Default::default()
+    });
+    initial_layout.children = vec![limited_child, TiledPaneLayout::default()];
+    let mut tab = create_new_tab_with_layout(size, initial_layout);
+    let pane_cols = |tab: &Tab| {
+        tab.tiled_panes
+            .panes
+            .get(&PaneId::Terminal(0))
+            .unwrap()
+            .position_and_size()
+            .cols
+            .as_usize()
+    };
+    let initial_cols = pane_cols(&tab);
+    tab_resize_right(&mut tab, 1);
+    let resized_cols = pane_cols(&tab);
+    assert!(
+        resized_cols > initial_cols,
+        "pane can grow up to its max size"
+    );
+    tab_resize_right(&mut tab, 1);
+    tab_resize_right(&mut tab, 1);
+    assert_eq!(
+        pane_cols(&tab),
+        resized_cols,
+        "pane did not grow past its max size"
+    );
+    assert!(pane_cols(&tab) <= 70);
+}
+
+#[test]
+pub fn pane_keeps_its_min_size_when_the_window_shrinks() {
+    let size = Size {
+        cols: 121,
+        rows: 40,
+    };
+
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Horizontal;
+    let mut limited_child = TiledPaneLayout::default();
+    limited_child.size_limits = Some(SizeLimits {
+        min: Some(15),
+        ..Default::default()
+    });
+    initial_layout.children = vec![limited_child, TiledPaneLayout::default()];
+    let mut tab = create_new_tab_with_layout(size, initial_layout);
+    tab.resize_whole_tab(Size {
+        cols: 121,
+        rows: 24,
+    })
+    .unwrap();
+    let pane_rows = |tab: &Tab, pane_id| {
+        tab.tiled_panes
+            .panes
+            .get(&pane_id)
+            .unwrap()
+            .position_and_size()
+            .rows
+            .as_usize()
+    };
+    assert_eq!(
+        pane_rows(&tab, PaneId::Terminal(0)),
+        15,
+        "pane was not shrunk below its min size"
+    );
+    assert_eq!(
+        pane_rows(&tab, PaneId::Terminal(1)),
+        9,
+        "the other pane took the rest of the space"
+    );
+}
+
 #[test]
 pub fn cannot_resize_down_when_pane_below_has_fixed_rows() {
     // ┌───────────┐                  ┌───────────┐
@@ -14641,3 +14734,62 @@ fn correctly_resize_frameless_panes_on_pane_close() {
     let content_size = (pane.get_content_columns(), pane.get_content_rows());
     assert_eq!(content_size, (cols, rows));
 }
+
+#[test]
+pub fn undo_close_pane_restores_the_pane_where_it_was() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let stacked_resize = false;
+    let mut tab = create_new_tab(size, stacked_resize);
+    let first_pane_geom = tab
+        .tiled_panes
+        .panes
+        .get(&PaneId::Terminal(1))
+        .unwrap()
+        .position_and_size();
+    tab.vertical_split(PaneId::Terminal(2), None, 1).unwrap();
+    tab.get_pane_with_id_mut(PaneId::Terminal(2))
+        .unwrap()
+        .handle_pty_bytes("restored line".as_bytes().to_vec());
+    let geom_of = |tab: &Tab, pane_id: PaneId| {
+        tab.tiled_panes
+            .panes
+            .get(&pane_id)
+            .unwrap()
+            .position_and_size()
+    };
+    let closed_pane_geom = geom_of(&tab, PaneId::Terminal(2));
+    let remaining_pane_geom = geom_of(&tab, PaneId::Terminal(1));
+    tab.close_focused_pane(1).unwrap();
+    assert_eq!(geom_of(&tab, PaneId::Terminal(1)), first_pane_geom);
+
+    let closed_pane = tab.take_closed_pane().unwrap();
+    assert!(
+        tab.take_closed_pane().is_none(),
+        "pane is only restored once"
+    );
+    tab.restore_closed_pane(3, closed_pane, 1).unwrap();
+    assert_eq!(geom_of(&tab, PaneId::Terminal(3)), closed_pane_geom);
+    assert_eq!(geom_of(&tab, PaneId::Terminal(1)), remaining_pane_geom);
+    assert_eq!(tab.get_active_pane_id(1), Some(PaneId::Terminal(3)));
+    assert!(tab
+        .get_pane_with_id(PaneId::Terminal(3))
+        .unwrap()
+        .dump_screen(false)
+        .contains("restored line"));
+}
+
+#[test]
+fn stacked_panes_are_matched_by_title() {
+    use super::title_match_score;
+    assert_eq!(title_match_score("Server Logs", "logs"), Some((0, 7)));
+    assert_eq!(title_match_score("server logs", "srvlg"), Some((1, 9)));
+    assert_eq!(title_match_score("server logs", "gol"), None);
+    assert_eq!(title_match_score("server logs", ""), None);
+    assert!(
+        title_match_score("logs", "logs") < title_match_score("log stream", "logs"),
+        "a title containing the query is a better match"
+    );
+}
@@ -1,22 +1,89 @@
 use crate::{
     os_input_output::{AsyncReader, ServerOsApi},
+    output_log::OutputLogger,
     screen::ScreenInstruction,
     thread_bus::ThreadSenders,
 };
-use async_std::{future::timeout as async_timeout, task};
+use async_std::{
+    future::{timeout as async_timeout, FutureExt},
+    task,
+};
 use std::{
     os::unix::io::RawFd,
     time::{Duration, Instant},
 };
 use zellij_utils::{
+    async_channel::Receiver,
     async_std,
     errors::{get_current_ctx, prelude::*, ContextType},
+    input::command::OutputLog,
     logging::debug_to_file,
 };
 
+// a pane producing more output in one frame than the screen thread can handle (eg. `yes`) stops
+// being read from until the next frame, so that it cannot keep the screen thread from rendering or
+// from handling the output of other panes and user input - the program writing to it blocks
+// meanwhile
+//
+// how much output that is depends on the screen thread: the budget of a pane halves after every
+// frame in which screen was backed up and doubles after every frame in which it kept up
+const FRAME_DURATION: Duration = Duration::from_millis(16);
+const MIN_BYTES_PER_FRAME: usize = 64 * 1024;
+pub(crate) const MAX_BYTES_PER_FRAME: usize = 4 * 1024 * 1024;
+
+/// Counts the bytes a pane outputs in each frame
+struct OutputThrottle {
+    frame_started_at: Instant,
+    bytes_in_frame: usize,
+    bytes_per_frame: usize,
+    screen_backed_up_in_frame: bool,
+}
+
+impl OutputThrottle {
+    pub fn new(now: Instant) -> Self {
+        OutputThrottle {
+            frame_started_at: now,
+            bytes_in_frame: 0,
+            bytes_per_frame: MAX_BYTES_PER_FRAME,
+            screen_backed_up_in_frame: false,
+        }
+    }
+    /// Returns how long to stop reading for, if these bytes used up the budget of the frame
+    pub fn bytes_read(
+        &mut self,
+        byte_count: usize,
+        now: Instant,
+        screen_backed_up: bool,
+    ) -> Option<Duration> {
+        if now.duration_since(self.frame_started_at) >= FRAME_DURATION {
+            self.start_frame(now);
+        }
+        self.screen_backed_up_in_frame |= screen_backed_up;
+        self.bytes_in_frame += byte_count;
+        if self.bytes_in_frame >= self.bytes_per_frame {
+            let pause = (self.frame_started_at + FRAME_DURATION).saturating_duration_since(now);
+            self.start_frame(now + pause);
+            Some(pause)
+        } else {
+            None
+        }
+    }
+    fn start_frame(&mut self, now: Instant) {
+        self.bytes_per_frame = if self.screen_backed_up_in_frame {
+            (self.bytes_per_frame / 2).max(MIN_BYTES_PER_FRAME)
+        } else {
+            (self.bytes_per_frame * 2).min(MAX_BYTES_PER_FRAME)
+        };
+        self.frame_started_at = now;
+        self.bytes_in_frame = 0;
+        self.screen_backed_up_in_frame = false;
+    }
+}
+
 enum ReadResult {
     Ok(usize),
     Timeout,
+    Stopped,
     Err(std::io::Error),
 }
 
@@ -40,6 +107,10 @@ pub(crate) struct TerminalBytes {
     minimum_render_send_time: Option<Duration>,
     buffering_pause: Duration,
     last_render: Instant,
+    output_log: Option<OutputLog>,
+    output_throttle: OutputThrottle,
+    stop_signal: Option<Receiver<()>>,
+    stopped: bool,
 }
 
 impl TerminalBytes {
@@ -61,8 +132,26 @@ impl TerminalBytes {
             minimum_render_send_time: None,
             buffering_pause: Duration::from_millis(30),
             last_render: Instant::now(),
+            output_log: None,
+            output_throttle: OutputThrottle::new(Instant::now()),
+            stop_signal: None,
+            stopped: false,
         }
     }
+    pub fn with_output_log(mut self, output_log: Option<OutputLog>) -> Self {
+        self.output_log = output_log;
+        self
+    }
+    /// Stops listening once a message is received here (or its sender is dropped), after
+    /// everything read so far was sent to screen
+    pub fn with_stop_signal(mut self, stop_signal: Receiver<()>) -> Self {
+        self.stop_signal = Some(stop_signal);
+        self
+    }
+    /// Whether we stopped listening because of the stop signal rather than the pty closing
+    pub fn stopped(&self) -> bool {
+        self.stopped
+    }
     pub async fn listen(&mut self) -> Result<()> {
         // This function reads bytes from the pty and then sends them as
         // ScreenInstruction::PtyBytes to screen to be parsed there
@@ -80,10 +169,36 @@ impl TerminalBytes {
         let mut err_ctx = get_current_ctx();
         err_ctx.add_call(ContextType::AsyncTask);
         let mut buf = [0u8; 65536];
+        let mut output_logger = match self.output_log.take().map(OutputLogger::new) {
+            Some(Ok(output_logger)) => Some(output_logger),
+            Some(Err(e)) => {
+                log::error!(
+                    "Failed to log output of terminal {}: {:?}",
+                    self.terminal_id,
+                    e
+                );
+                None
+            },
+            None => None,
+        };
         loop {
-            match self.deadline_read(&mut buf).await {
+            let read_result = match self.stop_signal.clone() {
+                Some(stop_signal) => {
+                    let stopped = async move {
+                        let _ = stop_signal.recv().await;
+                        ReadResult::Stopped
+                    };
+                    self.deadline_read(&mut buf).race(stopped).await
+                },
+                None => self.deadline_read(&mut buf).await,
+            };
+            match read_result {
                 // EOF
                 ReadResult::Ok(0) => break,
+                ReadResult::Stopped => {
+                    self.stopped = true;
+                    break;
+                },
                 // Some error occured
                 ReadResult::Err(err) => {
                     log::error!("{}", err);
@@ -104,13 +219,42 @@ impl TerminalBytes {
                     if self.debug {
                         let _ = debug_to_file(bytes, self.pid);
                     }
-                    self.async_send_to_screen(ScreenInstruction::PtyBytes(
-                        self.terminal_id,
-                        bytes.to_vec(),
-                    ))
-                    .await
-                    .with_context(err_context)?;
-                    if !self.backed_up {
+                    if let Some(logger) = output_logger.as_mut() {
+                        if let Err(e) = logger.write(bytes) {
+                            // we stop logging rather than fill the log with an error for every
+                            // read
+                            log::error!(
+                                "Failed to log output of terminal {}: {:?}",
+                                self.terminal_id,
+                                e
+                            );
+                            output_logger = None;
+                        }
+                    }
+                    let time_to_send_bytes = self
+                        .async_send_to_screen(ScreenInstruction::PtyBytes(
+                            self.terminal_id,
+                            bytes.to_vec(),
+                        ))
+                        .await
+                        .with_context(err_context)?;
+                    // the queue of the screen thread is bounded, so waiting a whole frame for it
+                    // to take our bytes means it is behind
+                    let screen_backed_up = self.backed_up || time_to_send_bytes >= FRAME_DURATION;
+                    if let Some(pause) =
+                        self.output_throttle
+                            .bytes_read(n_bytes, Instant::now(), screen_backed_up)
+                    {
+                        // we render what we have and skip the rest of this frame
+                        let time_to_send_render = self
+                            .async_send_to_screen(ScreenInstruction::Render)
+                            .await
+                            .with_context(err_context)?;
+                        self.update_render_send_time(time_to_send_render);
+                        self.render_deadline = None;
+                        self.last_render = Instant::now();
+                        task::sleep(pause).await;
+                    } else if !self.backed_up {
                         // we're not backed up, let's send an immediate render instruction
                         let time_to_send_render = self
                             .async_send_to_screen(ScreenInstruction::Render)
@@ -198,3 +342,72 @@ impl TerminalBytes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_throttle_pauses_until_the_end_of_the_frame() {
+        let start = Instant::now();
+        let mut throttle = OutputThrottle::new(start);
+        assert_eq!(
+            throttle.bytes_read(MAX_BYTES_PER_FRAME / 2, start, false),
+            None
+        );
+        let pause = throttle.bytes_read(
+            MAX_BYTES_PER_FRAME / 2,
+            start + Duration::from_millis(6),
+            false,
+        );
+        assert_eq!(pause, Some(Duration::from_millis(10)));
+
+        // the next frame starts with a new budget
+        let next_frame = start + Duration::from_millis(16);
+        assert_eq!(
+            throttle.bytes_read(MAX_BYTES_PER_FRAME - 1, next_frame, false),
+            None
+        );
+        // and output spread over frames is never paused
+        let later = next_frame + Duration::from_millis(20);
+        assert_eq!(
+            throttle.bytes_read(MAX_BYTES_PER_FRAME - 1, later, false),
+            None
+        );
+    }
+
+    #[test]
+    fn output_throttle_budget_follows_the_backpressure_of_screen() {
+        let mut now = Instant::now();
+        let mut throttle = OutputThrottle::new(now);
+        let mut budgets = vec![];
+        for _ in 0..8 {
+            now += throttle
+                .bytes_read(throttle.bytes_per_frame, now, true)
+                .expect("budget used up");
+            budgets.push(throttle.bytes_per_frame);
+        }
+        assert_eq!(
+            budgets,
+            vec![
+                MAX_BYTES_PER_FRAME / 2,
+                MAX_BYTES_PER_FRAME / 4,
+                MAX_BYTES_PER_FRAME / 8,
+                MAX_BYTES_PER_FRAME / 16,
+                MAX_BYTES_PER_FRAME / 32,
+                MAX_BYTES_PER_FRAME / 64,
+                MIN_BYTES_PER_FRAME,
+                MIN_BYTES_PER_FRAME,
+            ],
+            "budget halves while screen is backed up, down to the minimum"
+        );
+
+        // every frame in which screen kept up doubles it again
+        now += FRAME_DURATION;
+        assert_eq!(throttle.bytes_read(1, now, false), None);
+        assert_eq!(throttle.bytes_per_frame, MIN_BYTES_PER_FRAME * 2);
+        now += FRAME_DURATION;
+        assert_eq!(throttle.bytes_read(1, now, false), None);
+        assert_eq!(throttle.bytes_per_frame, MIN_BYTES_PER_FRAME * 4);
+    }
+}
@@ -144,6 +144,12 @@ impl ThreadSenders {
         self.to_pty_writer.replace(new_pty_writer);
     }
 
+    #[allow(unused)]
+    pub fn replace_to_server(&mut self, new_to_server: SenderWithContext<ServerInstruction>) {
+        // this is mostly used for the tests, see struct
+        self.to_server.replace(new_to_server);
+    }
+
     #[allow(unused)]
     pub fn replace_to_plugin(&mut self, new_to_plugin: SenderWithContext<PluginInstruction>) {
         // this is mostly used for the tests, see struct
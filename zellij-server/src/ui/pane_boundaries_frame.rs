@@ -2,8 +2,10 @@ use crate::output::CharacterChunk;
 use crate::panes::{AnsiCode, RcCharacterStyles, TerminalCharacter, EMPTY_TERMINAL_CHARACTER};
 use crate::ui::boundaries::boundary_type;
 use crate::ClientId;
+use std::time::Duration;
 use zellij_utils::data::{client_id_to_colors, PaletteColor, Style};
 use zellij_utils::errors::prelude::*;
+use zellij_utils::input::options::CommandExitBanner;
 use zellij_utils::pane_size::Viewport;
 use zellij_utils::position::Position;
 
@@ -51,6 +53,17 @@ enum ExitStatus {
     Exited,
 }
 
+fn format_runtime(runtime: Duration) -> String {
+    let seconds = runtime.as_secs();
+    if seconds < 60 {
+        format!("{:.1}s", runtime.as_secs_f64())
+    } else if seconds < 60 * 60 {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}h{:02}m", seconds / (60 * 60), (seconds % (60 * 60)) / 60)
+    }
+}
+
 pub struct FrameParams {
     pub focused_client: Option<ClientId>,
     pub is_main_client: bool,
@@ -62,6 +75,7 @@ pub struct FrameParams {
     pub pane_is_stacked_over: bool,
     pub should_draw_pane_frames: bool,
     pub pane_is_floating: bool,
+    pub input_is_synced: bool,
 }
 
 #[derive(Default, PartialEq)]
@@ -76,12 +90,21 @@ pub struct PaneFrame {
     pub other_cursors_exist_in_session: bool,
     pub other_focused_clients: Vec<ClientId>,
     exit_status: Option<ExitStatus>,
+    command_runtime: Option<Duration>,
+    command_exit_banner: CommandExitBanner,
     is_first_run: bool,
     pane_is_stacked_over: bool,
     pane_is_stacked_under: bool,
     should_draw_pane_frames: bool,
     is_pinned: bool,
     is_floating: bool,
+    input_is_synced: bool,
+    input_is_locked: bool,
+    app_requested_mouse_reporting: bool,
+    mouse_reporting_overridden: bool,
+    group: Option<String>,
+    process_stats: Option<String>,
+    close_confirmation: Option<(String, String)>, // what is being closed, the programs running in it
 }
 
 impl PaneFrame {
@@ -102,23 +125,62 @@ impl PaneFrame {
             other_focused_clients: frame_params.other_focused_clients,
             other_cursors_exist_in_session: frame_params.other_cursors_exist_in_session,
             exit_status: None,
+            command_runtime: None,
+            command_exit_banner: CommandExitBanner::default(),
             is_first_run: false,
             pane_is_stacked_over: frame_params.pane_is_stacked_over,
             pane_is_stacked_under: frame_params.pane_is_stacked_under,
             should_draw_pane_frames: frame_params.should_draw_pane_frames,
             is_pinned: false,
             is_floating: frame_params.pane_is_floating,
+            input_is_synced: frame_params.input_is_synced,
+            input_is_locked: false,
+            app_requested_mouse_reporting: false,
+            mouse_reporting_overridden: false,
+            group: None,
+            process_stats: None,
+            close_confirmation: None,
         }
     }
     pub fn is_pinned(mut self, is_pinned: bool) -> Self {
         self.is_pinned = is_pinned;
         self
     }
-    pub fn add_exit_status(&mut self, exit_status: Option<i32>) {
+    pub fn input_is_locked(mut self, input_is_locked: bool) -> Self {
+        self.input_is_locked = input_is_locked;
+        self
+    }
+    pub fn mouse_reporting(
+        mut self,
+        app_requested_mouse_reporting: bool,
+        mouse_reporting_overridden: bool,
+    ) -> Self {
+        self.app_requested_mouse_reporting = app_requested_mouse_reporting;
+        self.mouse_reporting_overridden = mouse_reporting_overridden;
+        self
+    }
+    pub fn group(mut self, group: Option<String>) -> Self {
+        self.group = group;
+        self
+    }
+    pub fn process_stats(mut self, process_stats: Option<String>) -> Self {
+        self.process_stats = process_stats;
+        self
+    }
+    pub fn close_confirmation(mut self, close_confirmation: Option<(String, String)>) -> Self {
+        self.close_confirmation = close_confirmation;
+        self
+    }
+    pub fn command_exit_banner(mut self, command_exit_banner: CommandExitBanner) -> Self {
+        self.command_exit_banner = command_exit_banner;
+        self
+    }
+    pub fn add_exit_status(&mut self, exit_status: Option<i32>, runtime: Option<Duration>) {
         self.exit_status = match exit_status {
             Some(exit_status) => Some(ExitStatus::Code(exit_status)),
             None => Some(ExitStatus::Exited),
         };
+        self.command_runtime = runtime;
     }
     pub fn indicate_first_run(&mut self) {
         self.is_first_run = true;
@@ -159,6 +221,83 @@ impl PaneFrame {
         max_length: usize,
     ) -> Option<(Vec<TerminalCharacter>, usize)> {
         // string and length because of color
+        let group_indication = self.render_group_indication(max_length);
+        let space_for_other_indications = group_indication
+            .as_ref()
+            .map(|(_, length)| max_length.saturating_sub(*length + 1))
+            .unwrap_or(max_length);
+        let other_indications =
+            self.render_sync_mouse_scroll_and_pin_indications(space_for_other_indications);
+        match (group_indication, other_indications) {
+            (
+                Some((mut group_indication, group_indication_len)),
+                Some((mut other_indications, other_indications_len)),
+            ) => {
+                let mut characters: Vec<_> = group_indication.drain(..).collect();
+                let mut separator = foreground_color("|", self.color);
+                characters.append(&mut separator);
+                characters.append(&mut other_indications);
+                Some((characters, group_indication_len + other_indications_len + 1))
+            },
+            (Some(group_indication), None) => Some(group_indication),
+            (None, other_indications) => other_indications,
+        }
+    }
+    fn render_sync_mouse_scroll_and_pin_indications(
+        &self,
+        max_length: usize,
+    ) -> Option<(Vec<TerminalCharacter>, usize)> {
+        let sync_indication = self.render_input_indication(max_length);
+        let space_for_other_indications = sync_indication
+            .as_ref()
+            .map(|(_, length)| max_length.saturating_sub(*length + 1))
+            .unwrap_or(max_length);
+        let other_indications =
+            self.render_mouse_scroll_and_pin_indications(space_for_other_indications);
+        match (sync_indication, other_indications) {
+            (
+                Some((mut sync_indication, sync_indication_len)),
+                Some((mut other_indications, other_indications_len)),
+            ) => {
+                let mut characters: Vec<_> = sync_indication.drain(..).collect();
+                let mut separator = foreground_color("|", self.color);
+                characters.append(&mut separator);
+                characters.append(&mut other_indications);
+                Some((characters, sync_indication_len + other_indications_len + 1))
+            },
+            (Some(sync_indication), None) => Some(sync_indication),
+            (None, other_indications) => other_indications,
+        }
+    }
+    fn render_mouse_scroll_and_pin_indications(
+        &self,
+        max_length: usize,
+    ) -> Option<(Vec<TerminalCharacter>, usize)> {
+        let mouse_indication = self.render_mouse_reporting_indication(max_length);
+        let space_for_other_indications = mouse_indication
+            .as_ref()
+            .map(|(_, length)| max_length.saturating_sub(*length + 1))
+            .unwrap_or(max_length);
+        let other_indications = self.render_scroll_and_pin_indications(space_for_other_indications);
+        match (mouse_indication, other_indications) {
+            (
+                Some((mut mouse_indication, mouse_indication_len)),
+                Some((mut other_indications, other_indications_len)),
+            ) => {
+                let mut characters: Vec<_> = mouse_indication.drain(..).collect();
+                let mut separator = foreground_color("|", self.color);
+                characters.append(&mut separator);
+                characters.append(&mut other_indications);
+                Some((characters, mouse_indication_len + other_indications_len + 1))
+            },
+            (Some(mouse_indication), None) => Some(mouse_indication),
+            (None, other_indications) => other_indications,
+        }
+    }
+    fn render_scroll_and_pin_indications(
+        &self,
+        max_length: usize,
+    ) -> Option<(Vec<TerminalCharacter>, usize)> {
         let has_scroll = self.scroll_position.0 > 0 || self.scroll_position.1 > 0;
         if has_scroll {
             let pin_indication = if self.is_floating {
@@ -177,7 +316,7 @@ impl PaneFrame {
                     Some((mut scroll_indication, scroll_indication_len)),
                 ) => {
                     let mut characters: Vec<_> = scroll_indication.drain(..).collect();
-                    let mut separator = foreground_color(&format!("|"), self.color);
+                    let mut separator = foreground_color("|", self.color);
                     characters.append(&mut separator);
                     characters.append(&mut pin_indication);
                     Some((characters, pin_indication_len + scroll_indication_len + 1))
@@ -237,6 +376,88 @@ impl PaneFrame {
             None
         }
     }
+    fn render_group_indication(
+        &self,
+        max_length: usize,
+    ) -> Option<(Vec<TerminalCharacter>, usize)> {
+        let group = self.group.as_ref()?;
+        let full_indication = format!(" GROUP: {} ", group);
+        let short_indication = format!(" {} ", group);
+        let full_indication_len = full_indication.width();
+        let short_indication_len = short_indication.width();
+        if full_indication_len <= max_length {
+            Some((
+                foreground_color(&full_indication, self.color),
+                full_indication_len,
+            ))
+        } else if short_indication_len <= max_length {
+            Some((
+                foreground_color(&short_indication, self.color),
+                short_indication_len,
+            ))
+        } else {
+            None
+        }
+    }
+    fn render_input_indication(
+        &self,
+        max_length: usize,
+    ) -> Option<(Vec<TerminalCharacter>, usize)> {
+        // input typed into any pane on this tab is sent to all of them (or none is sent to this
+        // pane), so this stands out from the frame color regardless of focus
+        let color = Some(self.style.colors.exit_code_error.base);
+        // a locked pane does not receive synced input either
+        let (full_indication, short_indication) = if self.input_is_locked {
+            (" INPUT LOCKED ", " LOCKED ")
+        } else if self.input_is_synced {
+            (" SYNC INPUT ", " SYNC ")
+        } else {
+            return None;
+        };
+        let full_indication_len = full_indication.chars().count();
+        let short_indication_len = short_indication.chars().count();
+        if full_indication_len <= max_length {
+            Some((
+                foreground_color(full_indication, color),
+                full_indication_len,
+            ))
+        } else if short_indication_len <= max_length {
+            Some((
+                foreground_color(short_indication, color),
+                short_indication_len,
+            ))
+        } else {
+            None
+        }
+    }
+    fn render_mouse_reporting_indication(
+        &self,
+        max_length: usize,
+    ) -> Option<(Vec<TerminalCharacter>, usize)> {
+        let full_indication = if self.mouse_reporting_overridden {
+            " MOUSE: ZELLIJ "
+        } else if self.app_requested_mouse_reporting {
+            " MOUSE: APP "
+        } else {
+            return None;
+        };
+        let short_indication = " M ";
+        let full_indication_len = full_indication.chars().count();
+        let short_indication_len = short_indication.chars().count();
+        if full_indication_len <= max_length {
+            Some((
+                foreground_color(full_indication, self.color),
+                full_indication_len,
+            ))
+        } else if short_indication_len <= max_length {
+            Some((
+                foreground_color(short_indication, self.color),
+                short_indication_len,
+            ))
+        } else {
+            None
+        }
+    }
     fn render_my_focus(&self, max_length: usize) -> Option<(Vec<TerminalCharacter>, usize)> {
         let mut left_separator = foreground_color(boundary_type::VERTICAL_LEFT, self.color);
         let mut right_separator = foreground_color(boundary_type::VERTICAL_RIGHT, self.color);
@@ -385,6 +606,7 @@ impl PaneFrame {
             self.render_other_focused_users(max_length)
         } else if (self.pane_is_stacked_under || self.pane_is_stacked_over)
             && self.exit_status.is_some()
+            && self.command_exit_banner != CommandExitBanner::Hidden
         {
             let (first_part, first_part_len) = self.first_exited_held_title_part_full();
             if first_part_len <= max_length {
@@ -701,7 +923,8 @@ impl PaneFrame {
             foreground_color(self.get_corner(boundary_type::BOTTOM_LEFT), self.color);
         let mut right_boundary =
             foreground_color(self.get_corner(boundary_type::BOTTOM_RIGHT), self.color);
-        let res = if self.is_main_client {
+        let shows_tips = self.is_first_run || self.command_exit_banner == CommandExitBanner::Full;
+        let res = if self.is_main_client && shows_tips {
             let (mut second_part, second_part_len) = self.second_held_title_part_full();
             let full_text_len = first_part_len + second_part_len;
             if full_text_len <= max_undertitle_length {
@@ -792,7 +1015,17 @@ impl PaneFrame {
                     character_chunks.push(CharacterChunk::new(title, x, y));
                 } else if row == self.geom.rows - 1 {
                     // bottom row
-                    if self.exit_status.is_some() || self.is_first_run {
+                    if let Some(close_confirmation_undertitle) =
+                        self.render_close_confirmation_undertitle()
+                    {
+                        let x = self.geom.x;
+                        let y = self.geom.y + row;
+                        character_chunks.push(CharacterChunk::new(
+                            close_confirmation_undertitle,
+                            x,
+                            y,
+                        ));
+                    } else if self.shows_held_undertitle() {
                         let x = self.geom.x;
                         let y = self.geom.y + row;
                         character_chunks.push(CharacterChunk::new(
@@ -800,6 +1033,12 @@ impl PaneFrame {
                             x,
                             y,
                         ));
+                    } else if let Some(process_stats_undertitle) =
+                        self.render_process_stats_undertitle()
+                    {
+                        let x = self.geom.x;
+                        let y = self.geom.y + row;
+                        character_chunks.push(CharacterChunk::new(process_stats_undertitle, x, y));
                     } else {
                         let mut bottom_row = vec![];
                         for col in 0..self.geom.cols {
@@ -838,8 +1077,16 @@ impl PaneFrame {
         }
         Ok((character_chunks, None))
     }
+    fn shows_held_undertitle(&self) -> bool {
+        self.is_first_run
+            || (self.exit_status.is_some() && self.command_exit_banner != CommandExitBanner::Hidden)
+    }
     fn first_exited_held_title_part_full(&self) -> (Vec<TerminalCharacter>, usize) {
         // (title part, length)
+        let runtime_text = self
+            .command_runtime
+            .map(|runtime| format!(" | {}", format_runtime(runtime)))
+            .unwrap_or_default();
         match self.exit_status {
             Some(ExitStatus::Code(exit_code)) => {
                 let mut first_part = vec![];
@@ -858,12 +1105,14 @@ impl PaneFrame {
                     &exit_code_text,
                     Some(exit_code_color),
                 ));
+                first_part.append(&mut foreground_color(&runtime_text, self.color));
                 first_part.append(&mut foreground_color(right_bracket, self.color));
                 (
                     first_part,
                     left_bracket.len()
                         + exited_text.len()
                         + exit_code_text.len()
+                        + runtime_text.len()
                         + right_bracket.len(),
                 )
             },
@@ -877,10 +1126,14 @@ impl PaneFrame {
                     exited_text,
                     Some(self.style.colors.exit_code_error.base),
                 ));
+                first_part.append(&mut foreground_color(&runtime_text, self.color));
                 first_part.append(&mut foreground_color(right_bracket, self.color));
                 (
                     first_part,
-                    left_bracket.len() + exited_text.len() + right_bracket.len(),
+                    left_bracket.len()
+                        + exited_text.len()
+                        + runtime_text.len()
+                        + right_bracket.len(),
                 )
             },
             None => (foreground_color(boundary_type::HORIZONTAL, self.color), 1),
@@ -946,6 +1199,77 @@ impl PaneFrame {
                 + break_tip.len(),
         )
     }
+    fn render_process_stats_undertitle(&self) -> Option<Vec<TerminalCharacter>> {
+        let process_stats = self.process_stats.as_ref()?;
+        let max_undertitle_length = self.geom.cols.saturating_sub(2); // 2 for the left and right corners
+        let text = format!(" {} ", process_stats);
+        let text_len = text.width();
+        if text_len > max_undertitle_length {
+            return None;
+        }
+        let mut padding = String::new();
+        for _ in text_len..max_undertitle_length {
+            padding.push_str(boundary_type::HORIZONTAL);
+        }
+        let mut ret = vec![];
+        ret.append(&mut foreground_color(
+            self.get_corner(boundary_type::BOTTOM_LEFT),
+            self.color,
+        ));
+        ret.append(&mut foreground_color(&text, self.color));
+        ret.append(&mut foreground_color(&padding, self.color));
+        ret.append(&mut foreground_color(
+            self.get_corner(boundary_type::BOTTOM_RIGHT),
+            self.color,
+        ));
+        Some(ret)
+    }
+    fn render_close_confirmation_undertitle(&self) -> Option<Vec<TerminalCharacter>> {
+        let (closing, running_processes) = self.close_confirmation.as_ref()?;
+        let max_undertitle_length = self.geom.cols.saturating_sub(2); // 2 for the left and right corners
+        let running_text = format!(" [ RUNNING: {} ] ", running_processes);
+        let key_color = Some(self.style.colors.text_unselected.emphasis_0);
+        let mut tips = vec![];
+        tips.append(&mut foreground_color("<", self.color));
+        tips.append(&mut foreground_color("y", key_color));
+        tips.append(&mut foreground_color(">", self.color));
+        let confirm_tip = format!(" close {}, ", closing);
+        tips.append(&mut foreground_color(&confirm_tip, self.color));
+        tips.append(&mut foreground_color("<", self.color));
+        tips.append(&mut foreground_color("n", key_color));
+        tips.append(&mut foreground_color(">", self.color));
+        tips.append(&mut foreground_color(" cancel ", self.color));
+        let tips_len = 3 + confirm_tip.width() + 3 + " cancel ".len();
+        let mut text = vec![];
+        let mut text_len = tips_len;
+        // the running programs are left out if they do not fit
+        if running_text.width() + tips_len <= max_undertitle_length {
+            text.append(&mut foreground_color(
+                &running_text,
+                Some(self.style.colors.exit_code_error.base),
+            ));
+            text_len += running_text.width();
+        } else if tips_len > max_undertitle_length {
+            return None;
+        }
+        text.append(&mut tips);
+        let mut padding = String::new();
+        for _ in text_len..max_undertitle_length {
+            padding.push_str(boundary_type::HORIZONTAL);
+        }
+        let mut ret = vec![];
+        ret.append(&mut foreground_color(
+            self.get_corner(boundary_type::BOTTOM_LEFT),
+            self.color,
+        ));
+        ret.append(&mut text);
+        ret.append(&mut foreground_color(&padding, self.color));
+        ret.append(&mut foreground_color(
+            self.get_corner(boundary_type::BOTTOM_RIGHT),
+            self.color,
+        ));
+        Some(ret)
+    }
     fn empty_undertitle(&self, max_undertitle_length: usize) -> Vec<TerminalCharacter> {
         let mut left_boundary =
             foreground_color(self.get_corner(boundary_type::BOTTOM_LEFT), self.color);
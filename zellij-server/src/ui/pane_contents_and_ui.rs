@@ -17,6 +17,7 @@ pub struct PaneContentsAndUi<'a> {
     pane_is_stacked_under: bool,
     pane_is_stacked_over: bool,
     should_draw_pane_frames: bool,
+    input_is_synced: bool,
 }
 
 impl<'a> PaneContentsAndUi<'a> {
@@ -30,6 +31,7 @@ impl<'a> PaneContentsAndUi<'a> {
         pane_is_stacked_under: bool,
         pane_is_stacked_over: bool,
         should_draw_pane_frames: bool,
+        input_is_synced: bool,
     ) -> Self {
         let mut focused_clients: Vec<ClientId> = active_panes
             .iter()
@@ -47,6 +49,7 @@ impl<'a> PaneContentsAndUi<'a> {
             pane_is_stacked_under,
             pane_is_stacked_over,
             should_draw_pane_frames,
+            input_is_synced,
         }
     }
     pub fn render_pane_contents_to_multiple_clients(
@@ -59,6 +62,8 @@ impl<'a> PaneContentsAndUi<'a> {
         // and we can clear them from the UI below
         drop(self.pane.drain_fake_cursors());
 
+        self.pane
+            .set_covered_areas(self.output.areas_covering(self.z_index));
         if let Some((character_chunks, raw_vte_output, sixel_image_chunks)) =
             self.pane.render(None).context(err_context)?
         {
@@ -75,7 +80,10 @@ impl<'a> PaneContentsAndUi<'a> {
                 clients.iter().copied(),
                 self.z_index,
             );
-            if let Some(raw_vte_output) = raw_vte_output {
+            if let Some(mut raw_vte_output) = raw_vte_output {
+                if self.output.bells_are_muted() {
+                    raw_vte_output.retain(|c| c != '\u{7}');
+                }
                 if !raw_vte_output.is_empty() {
                     self.output.add_post_vte_instruction_to_multiple_clients(
                         clients.iter().copied(),
@@ -94,6 +102,8 @@ impl<'a> PaneContentsAndUi<'a> {
     pub fn render_pane_contents_for_client(&mut self, client_id: ClientId) -> Result<()> {
         let err_context = || format!("failed to render pane contents for client {client_id}");
 
+        self.pane
+            .set_covered_areas(self.output.areas_covering(self.z_index));
         if let Some((character_chunks, raw_vte_output, sixel_image_chunks)) = self
             .pane
             .render(Some(client_id))
@@ -107,7 +117,10 @@ impl<'a> PaneContentsAndUi<'a> {
                 sixel_image_chunks,
                 self.z_index,
             );
-            if let Some(raw_vte_output) = raw_vte_output {
+            if let Some(mut raw_vte_output) = raw_vte_output {
+                if self.output.bells_are_muted() {
+                    raw_vte_output.retain(|c| c != '\u{7}');
+                }
                 self.output.add_post_vte_instruction_to_client(
                     client_id,
                     &format!(
@@ -223,6 +236,7 @@ impl<'a> PaneContentsAndUi<'a> {
                 pane_is_stacked_under: self.pane_is_stacked_under,
                 should_draw_pane_frames: self.should_draw_pane_frames,
                 pane_is_floating,
+                input_is_synced: self.input_is_synced,
             }
         } else {
             FrameParams {
@@ -236,6 +250,7 @@ impl<'a> PaneContentsAndUi<'a> {
                 pane_is_stacked_under: self.pane_is_stacked_under,
                 should_draw_pane_frames: self.should_draw_pane_frames,
                 pane_is_floating,
+                input_is_synced: self.input_is_synced,
             }
         };
 
@@ -0,0 +1,35 @@
+use super::move_session_info_folder;
+
+#[test]
+fn session_info_folder_moves_with_the_session_name() {
+    let session_info_cache_dir = tempfile::tempdir().unwrap();
+    let old_session_info_folder = session_info_cache_dir.path().join("old-name");
+    let new_session_info_folder = session_info_cache_dir.path().join("new-name");
+    std::fs::create_dir(&old_session_info_folder).unwrap();
+    std::fs::write(
+        old_session_info_folder.join("session-layout.kdl"),
+        "layout {}",
+    )
+    .unwrap();
+
+    move_session_info_folder(&old_session_info_folder, &new_session_info_folder).unwrap();
+
+    assert!(!old_session_info_folder.exists());
+    assert_eq!(
+        std::fs::read_to_string(new_session_info_folder.join("session-layout.kdl")).unwrap(),
+        "layout {}",
+        "the resurrection metadata is kept"
+    );
+}
+
+#[test]
+fn session_without_a_session_info_folder_has_nothing_to_move() {
+    let session_info_cache_dir = tempfile::tempdir().unwrap();
+    let new_session_info_folder = session_info_cache_dir.path().join("new-name");
+    move_session_info_folder(
+        &session_info_cache_dir.path().join("old-name"),
+        &new_session_info_folder,
+    )
+    .unwrap();
+    assert!(!new_session_info_folder.exists());
+}
@@ -1,6 +1,9 @@
 use super::*;
 
 use nix::{pty::openpty, unistd::close};
+use std::time::Duration;
+use zellij_utils::input::command::StdinPipe;
+use zellij_utils::tempfile::tempdir;
 
 struct TestTerminal {
     openpty: OpenptyResult,
@@ -40,6 +43,8 @@ fn get_cwd() {
         client_senders: Arc::default(),
         terminal_id_to_raw_fd: Arc::default(),
         cached_resizes: Arc::default(),
+        process_stats_system_info: Arc::default(),
+        stdin_pipes: Arc::default(),
     };
 
     let pid = nix::unistd::getpid();
@@ -49,3 +54,72 @@ fn get_cwd() {
         pid
     );
 }
+
+#[test]
+fn process_stats_are_shown_with_a_readable_memory_size() {
+    let stats = |memory| {
+        ProcessStats {
+            name: "cargo".to_owned(),
+            cpu_usage: 123.4,
+            memory,
+        }
+        .to_string()
+    };
+    assert_eq!(stats(512), "cargo | CPU 123% | MEM 512B");
+    assert_eq!(stats(3 * 1024 * 1024 / 2), "cargo | CPU 123% | MEM 1.5M");
+    assert_eq!(stats(640 * 1024 * 1024), "cargo | CPU 123% | MEM 640M");
+    assert_eq!(stats(3 * 1024 * 1024 * 1024), "cargo | CPU 123% | MEM 3.0G");
+}
+
+#[test]
+fn piped_stdin_reaches_the_command_byte_for_byte() {
+    let output_dir = tempdir().unwrap();
+    let output_path = output_dir.path().join("stdin");
+    let server = get_server_os_input().unwrap();
+    let (exit_sender, exit_receiver) = std::sync::mpsc::channel();
+    let quit_cb = Box::new(
+        move |_pane_id: PaneId, exit_status: Option<i32>, _run_command: RunCommand| {
+            let _ = exit_sender.send(exit_status);
+        },
+    );
+    let piping_client_id = 1;
+    let run_command = RunCommand {
+        command: PathBuf::from("sh"),
+        args: vec![
+            "-c".to_owned(),
+            format!("cat > '{}'", output_path.display()),
+        ],
+        stdin_pipe: Some(StdinPipe {
+            client_id: Some(piping_client_id),
+        }),
+        ..Default::default()
+    };
+    let (terminal_id, _pty_fd, _child_pid) = server
+        .spawn_terminal(
+            TerminalAction::RunCommand(run_command),
+            quit_cb,
+            None,
+            &BTreeMap::new(),
+        )
+        .unwrap();
+
+    // binary, with the bytes of ctrl-c, ctrl-d and ctrl-z, and longer than a line of a pty
+    let input: Vec<u8> = (0..20_000u32).map(|i| (i % 256) as u8).collect();
+    assert!(server
+        .write_to_stdin_pipe(terminal_id, piping_client_id + 1, &input)
+        .is_err());
+    for chunk in input.chunks(3000) {
+        server
+            .write_to_stdin_pipe(terminal_id, piping_client_id, chunk)
+            .unwrap();
+    }
+    server
+        .close_stdin_pipe(terminal_id, piping_client_id)
+        .unwrap();
+
+    let exit_status = exit_receiver
+        .recv_timeout(Duration::from_secs(10))
+        .expect("command exited at the end of its STDIN");
+    assert_eq!(exit_status, Some(0));
+    assert_eq!(std::fs::read(&output_path).unwrap(), input);
+}
@@ -0,0 +1,71 @@
+use super::*;
+
+use zellij_utils::tempfile::tempdir;
+
+#[test]
+fn output_is_written_to_log_file() {
+    let log_dir = tempdir().unwrap();
+    let path = log_dir.path().join("build.log");
+    let mut logger = OutputLogger::new(OutputLog {
+        path: path.clone(),
+        ..Default::default()
+    })
+    .unwrap();
+    logger
+        .write("\u{1b}[31mcompiling\u{1b}[m\n".as_bytes())
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "\u{1b}[31mcompiling\u{1b}[m\n",
+        "output logged as is"
+    );
+}
+
+#[test]
+fn ansi_escapes_are_stripped_from_log_file() {
+    let log_dir = tempdir().unwrap();
+    let path = log_dir.path().join("build.log");
+    let mut logger = OutputLogger::new(OutputLog {
+        path: path.clone(),
+        strip_ansi: true,
+        ..Default::default()
+    })
+    .unwrap();
+    logger.write("\u{1b}[31mcompil".as_bytes()).unwrap();
+    logger.write("ing\u{1b}[".as_bytes()).unwrap();
+    logger.write("m\n".as_bytes()).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "compiling\n",
+        "escapes stripped, even when split between writes"
+    );
+}
+
+#[test]
+fn log_file_is_rotated_when_it_exceeds_max_size() {
+    let log_dir = tempdir().unwrap();
+    let path = log_dir.path().join("build.log");
+    let mut logger = OutputLogger::new(OutputLog {
+        path: path.clone(),
+        max_size: Some(6),
+        max_files: 2,
+        ..Default::default()
+    })
+    .unwrap();
+    for line in ["line 1\n", "line 2\n", "line 3\n", "line 4\n"] {
+        logger.write(line.as_bytes()).unwrap();
+    }
+    assert_eq!(fs::read_to_string(&path).unwrap(), "line 4\n");
+    assert_eq!(
+        fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+        "line 3\n"
+    );
+    assert_eq!(
+        fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+        "line 2\n"
+    );
+    assert!(
+        !rotated_path(&path, 3).exists(),
+        "only max_files rotated files are kept"
+    );
+}
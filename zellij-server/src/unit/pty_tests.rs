@@ -0,0 +1,425 @@
+use super::*;
+
+use crate::os_input_output::get_server_os_input;
+use std::time::{Duration, Instant};
+use zellij_utils::errors::ErrorContext;
+use zellij_utils::tempfile::tempdir;
+
+fn pty_with_two_clients_in_different_tabs(focused_pane_cwd: PathBuf) -> Pty {
+    let mut pty = Pty::new(Bus::empty(), false, None, NewPaneCwd::FocusedPane, None);
+    pty.active_tabs.insert(1, 0);
+    pty.active_tabs.insert(2, 1);
+    pty.active_panes.insert(1, PaneId::Terminal(1));
+    pty.active_panes.insert(2, PaneId::Terminal(2));
+    pty.reported_cwds.insert(1, focused_pane_cwd.clone());
+    pty.reported_cwds.insert(2, focused_pane_cwd);
+    pty
+}
+
+fn cwd_of_new_pane(pty: &Pty, client_id: ClientId, cwd: Option<PathBuf>) -> Option<PathBuf> {
+    let mut terminal_action = TerminalAction::RunCommand(RunCommand {
+        command: PathBuf::from("bash"),
+        cwd,
+        ..Default::default()
+    });
+    pty.fill_cwd(&mut terminal_action, client_id);
+    match terminal_action {
+        TerminalAction::RunCommand(run_command) => run_command.cwd,
+        TerminalAction::OpenFile(payload) => payload.cwd,
+    }
+}
+
+#[test]
+fn panes_opened_after_setting_the_tab_cwd_start_in_it() {
+    let focused_pane_dir = tempdir().unwrap();
+    let tab_dir = tempdir().unwrap();
+    let mut pty = pty_with_two_clients_in_different_tabs(focused_pane_dir.path().to_path_buf());
+    assert_eq!(
+        cwd_of_new_pane(&pty, 1, None),
+        Some(focused_pane_dir.path().to_path_buf()),
+        "new pane opened in the cwd of the focused pane before the tab cwd is set"
+    );
+    pty.set_tab_cwd(tab_dir.path().to_path_buf(), 1).unwrap();
+    assert_eq!(
+        cwd_of_new_pane(&pty, 1, None),
+        Some(tab_dir.path().to_path_buf()),
+        "new pane opened in the tab cwd"
+    );
+    assert_eq!(
+        cwd_of_new_pane(&pty, 1, Some(focused_pane_dir.path().to_path_buf())),
+        Some(focused_pane_dir.path().to_path_buf()),
+        "explicitly given cwd takes precedence over the tab cwd"
+    );
+}
+
+#[test]
+fn setting_the_tab_cwd_does_not_affect_panes_opened_earlier_or_other_tabs() {
+    let focused_pane_dir = tempdir().unwrap();
+    let tab_dir = tempdir().unwrap();
+    let mut pty = pty_with_two_clients_in_different_tabs(focused_pane_dir.path().to_path_buf());
+    pty.set_tab_cwd(tab_dir.path().to_path_buf(), 1).unwrap();
+    assert_eq!(
+        pty.terminal_cwd(&1),
+        Some(focused_pane_dir.path().to_path_buf()),
+        "pane opened before the tab cwd was set stays in its cwd"
+    );
+    assert_eq!(
+        cwd_of_new_pane(&pty, 2, None),
+        Some(focused_pane_dir.path().to_path_buf()),
+        "new pane in another tab does not open in the tab cwd"
+    );
+}
+
+#[test]
+fn setting_a_nonexistent_tab_cwd_is_ignored() {
+    let focused_pane_dir = tempdir().unwrap();
+    let mut pty = pty_with_two_clients_in_different_tabs(focused_pane_dir.path().to_path_buf());
+    let nonexistent_dir = focused_pane_dir.path().join("does-not-exist");
+    pty.set_tab_cwd(nonexistent_dir, 1).unwrap();
+    assert_eq!(
+        cwd_of_new_pane(&pty, 1, None),
+        Some(focused_pane_dir.path().to_path_buf()),
+        "new pane opened in the cwd of the focused pane"
+    );
+}
+
+#[test]
+fn panes_spawned_after_setting_the_session_env_see_it() {
+    let output_dir = tempdir().unwrap();
+    let output_path = output_dir.path().join("env");
+    let mut bus = Bus::empty();
+    bus.os_input = Some(Box::new(get_server_os_input().unwrap()));
+    let mut pty = Pty::new(bus, false, None, NewPaneCwd::FocusedPane, None);
+    pty.set_session_env(vec![
+        (
+            "ZELLIJ_TEST_SESSION_ENV".to_owned(),
+            Some("session value".to_owned()),
+        ),
+        ("HOME".to_owned(), None),
+    ]);
+    let terminal_action = TerminalAction::RunCommand(RunCommand {
+        command: PathBuf::from("sh"),
+        args: vec![
+            "-c".to_owned(),
+            format!(
+                "printf '%s|%s' \"$ZELLIJ_TEST_SESSION_ENV\" \"${{HOME-removed}}\" > '{}'",
+                output_path.display()
+            ),
+        ],
+        ..Default::default()
+    });
+    pty.spawn_terminal(Some(terminal_action), ClientTabIndexOrPaneId::TabIndex(0))
+        .unwrap();
+
+    let started = Instant::now();
+    let env_of_pane = loop {
+        match std::fs::read_to_string(&output_path) {
+            Ok(contents) if !contents.is_empty() => break contents,
+            _ if started.elapsed() > Duration::from_secs(10) => panic!("pane did not start"),
+            _ => std::thread::sleep(Duration::from_millis(10)),
+        }
+    };
+    assert_eq!(env_of_pane, "session value|removed");
+    assert!(
+        std::env::var("ZELLIJ_TEST_SESSION_ENV").is_err(),
+        "the environment of the server itself is untouched"
+    );
+}
+
+#[test]
+fn pane_adopted_from_another_session_is_closed_and_forgotten_once_its_pty_closes() {
+    use std::os::unix::process::ExitStatusExt;
+    use zellij_utils::{channels, channels::SenderWithContext, libc, nix::pty::openpty};
+
+    let (to_screen, screen_receiver) = channels::unbounded();
+    let (to_pty, pty_receiver) = channels::unbounded();
+    let bus = Bus::new(
+        vec![],
+        Some(&SenderWithContext::new(to_screen)),
+        Some(&SenderWithContext::new(to_pty)),
+        None,
+        None,
+        None,
+        None,
+        Some(Box::new(get_server_os_input().unwrap())),
+    );
+    let mut pty = Pty::new(bus, false, None, NewPaneCwd::FocusedPane, None);
+    let pty_pair = openpty(None, None).unwrap();
+    // stands in for the process of the pane, started by the server of the other session
+    let mut process = std::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .unwrap();
+    let moved_pane = MovedPane {
+        name: None,
+        invoked_with: None,
+        scrollback: None,
+    };
+    pty.adopt_pane(pty_pair.master, process.id() as i32, moved_pane, 1)
+        .unwrap();
+    let terminal_id = match screen_receiver.recv_timeout(Duration::from_secs(5)) {
+        Ok((ScreenInstruction::AdoptPane(terminal_id, ..), _)) => terminal_id,
+        other => panic!("pane was not adopted, got: {:?}", other),
+    };
+    let pane_id = PaneId::Terminal(terminal_id);
+
+    unistd::write(pty_pair.slave, b"adopted").unwrap();
+    let mut output = vec![];
+    while !String::from_utf8_lossy(&output).contains("adopted") {
+        match screen_receiver.recv_timeout(Duration::from_secs(5)) {
+            Ok((ScreenInstruction::PtyBytes(id, bytes), _)) if id == terminal_id => {
+                output.extend(bytes)
+            },
+            Ok(_) => {},
+            Err(_) => panic!("output of the adopted pane was not read"),
+        }
+    }
+
+    unistd::close(pty_pair.slave).unwrap();
+    loop {
+        match screen_receiver.recv_timeout(Duration::from_secs(5)) {
+            Ok((ScreenInstruction::ClosePane(id, None), _)) if id == pane_id => break,
+            Ok(_) => {},
+            Err(_) => panic!("pane was not closed once its pty closed"),
+        }
+    }
+    match pty_receiver.recv_timeout(Duration::from_secs(5)) {
+        Ok((PtyInstruction::ClosePane(id), _)) if id == pane_id => {},
+        other => panic!(
+            "pty thread was not told to close the pane, got: {:?}",
+            other
+        ),
+    }
+    pty.close_pane(pane_id).unwrap();
+    assert!(!pty.task_handles.contains_key(&terminal_id));
+    assert!(!pty.stop_reading.contains_key(&terminal_id));
+    assert!(!pty.id_to_child_pid.contains_key(&terminal_id));
+    assert_eq!(
+        process.wait().unwrap().signal(),
+        Some(libc::SIGHUP),
+        "the process of the pane was hung up on"
+    );
+}
+
+#[test]
+fn output_of_a_flooding_pane_reaches_a_slow_screen_whole_and_is_rendered_along_the_way() {
+    use crate::terminal_bytes::MAX_BYTES_PER_FRAME;
+    use zellij_utils::{channels, channels::SenderWithContext, nix::pty::openpty};
+
+    // the queue of the screen thread is bounded like it is in the server
+    let (to_screen, screen_receiver) = channels::bounded(50);
+    let bus = Bus::new(
+        vec![],
+        Some(&SenderWithContext::new(to_screen)),
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(Box::new(get_server_os_input().unwrap())),
+    );
+    let mut pty = Pty::new(bus, false, None, NewPaneCwd::FocusedPane, None);
+    let pty_pair = openpty(None, None).unwrap();
+    let mut process = std::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .unwrap();
+    let moved_pane = MovedPane {
+        name: None,
+        invoked_with: None,
+        scrollback: None,
+    };
+    pty.adopt_pane(pty_pair.master, process.id() as i32, moved_pane, 1)
+        .unwrap();
+    let terminal_id = match screen_receiver.recv_timeout(Duration::from_secs(5)) {
+        Ok((ScreenInstruction::AdoptPane(terminal_id, ..), _)) => terminal_id,
+        other => panic!("pane was not adopted, got: {:?}", other),
+    };
+
+    let flood_size = 2 * MAX_BYTES_PER_FRAME;
+    let slave = pty_pair.slave;
+    let flood = std::thread::spawn(move || {
+        let line = [b'y'; 1023];
+        let mut written = 0;
+        while written < flood_size {
+            let chunk = &line[..(flood_size - written).min(line.len())];
+            written += unistd::write(slave, chunk).unwrap();
+        }
+    });
+
+    let mut received = 0;
+    let mut received_since_render = 0;
+    let mut most_received_between_renders = 0;
+    while received < flood_size {
+        match screen_receiver.recv_timeout(Duration::from_secs(10)) {
+            Ok((ScreenInstruction::PtyBytes(id, bytes), _)) if id == terminal_id => {
+                assert!(bytes.iter().all(|b| *b == b'y'), "output arrived intact");
+                received += bytes.len();
+                received_since_render += bytes.len();
+                // a screen that takes its time with every batch of output
+                std::thread::sleep(Duration::from_millis(1));
+            },
+            Ok((ScreenInstruction::Render, _)) => {
+                most_received_between_renders =
+                    most_received_between_renders.max(received_since_render);
+                received_since_render = 0;
+            },
+            Ok(_) => {},
+            Err(_) => panic!(
+                "only {} of the {} bytes of output reached screen",
+                received, flood_size
+            ),
+        }
+    }
+    flood.join().unwrap();
+    assert_eq!(received, flood_size, "all output reached screen");
+    assert!(
+        most_received_between_renders <= MAX_BYTES_PER_FRAME + 65536,
+        "screen was told to render at least once per frame budget, but received {} bytes without rendering",
+        most_received_between_renders
+    );
+
+    unistd::close(pty_pair.slave).unwrap();
+    let _ = process.kill();
+    let _ = process.wait();
+}
+
+fn duplicate_pane_in_pty_thread(
+    pty: Pty,
+    to_pty: &zellij_utils::channels::SenderWithContext<PtyInstruction>,
+    terminal_id: u32,
+    run_command: RunCommand,
+) {
+    let pty_thread = std::thread::spawn(move || pty_thread_main(pty, Box::new(Layout::default())));
+    to_pty
+        .send(PtyInstruction::DuplicatePane(
+            terminal_id,
+            Some(TerminalAction::RunCommand(run_command)),
+            None,
+            1,
+        ))
+        .unwrap();
+    to_pty.send(PtyInstruction::Exit).unwrap();
+    pty_thread.join().unwrap().unwrap();
+}
+
+fn pty_with_receivers() -> (
+    Pty,
+    zellij_utils::channels::SenderWithContext<PtyInstruction>,
+    zellij_utils::channels::Receiver<(ScreenInstruction, ErrorContext)>,
+    zellij_utils::channels::Receiver<(BackgroundJob, ErrorContext)>,
+) {
+    use zellij_utils::{channels, channels::SenderWithContext};
+
+    let (to_screen, screen_receiver) = channels::unbounded();
+    let (to_pty, pty_receiver) = channels::unbounded();
+    let (to_background_jobs, background_jobs_receiver) = channels::unbounded();
+    let to_pty = SenderWithContext::new(to_pty);
+    let bus = Bus::new(
+        vec![pty_receiver],
+        Some(&SenderWithContext::new(to_screen)),
+        Some(&to_pty),
+        None,
+        None,
+        None,
+        Some(&SenderWithContext::new(to_background_jobs)),
+        Some(Box::new(get_server_os_input().unwrap())),
+    );
+    let pty = Pty::new(bus, false, None, NewPaneCwd::FocusedPane, None);
+    (pty, to_pty, screen_receiver, background_jobs_receiver)
+}
+
+#[test]
+fn duplicate_of_a_command_that_is_gone_is_held_to_say_so() {
+    let (pty, to_pty, screen_receiver, _background_jobs_receiver) = pty_with_receivers();
+    let run_command = RunCommand {
+        command: PathBuf::from("zellij-test-command-that-does-not-exist"),
+        hold_on_close: true,
+        ..Default::default()
+    };
+    duplicate_pane_in_pty_thread(pty, &to_pty, 1, run_command);
+    let screen_instructions: Vec<ScreenInstruction> =
+        screen_receiver.try_iter().map(|(i, _)| i).collect();
+    match &screen_instructions[..] {
+        [ScreenInstruction::NewPane(PaneId::Terminal(new_pane), ..), ScreenInstruction::PtyBytes(not_found_pane, bytes), ScreenInstruction::HoldPane(PaneId::Terminal(held_pane), Some(2), _)] =>
+        {
+            assert_eq!(new_pane, not_found_pane);
+            assert_eq!(new_pane, held_pane);
+            assert!(String::from_utf8_lossy(bytes).starts_with("Command not found"));
+        },
+        _ => panic!(
+            "duplicate was not opened and held, got: {:?}",
+            screen_instructions
+        ),
+    }
+}
+
+#[test]
+fn duplicate_of_a_command_that_is_gone_and_does_not_hold_is_not_opened() {
+    let (pty, to_pty, screen_receiver, background_jobs_receiver) = pty_with_receivers();
+    let run_command = RunCommand {
+        command: PathBuf::from("zellij-test-command-that-does-not-exist"),
+        hold_on_close: false,
+        ..Default::default()
+    };
+    duplicate_pane_in_pty_thread(pty, &to_pty, 1, run_command);
+    let screen_instructions: Vec<ScreenInstruction> =
+        screen_receiver.try_iter().map(|(i, _)| i).collect();
+    assert!(
+        screen_instructions.is_empty(),
+        "no pane opened, got: {:?}",
+        screen_instructions
+    );
+    assert!(matches!(
+        background_jobs_receiver.try_recv(),
+        Ok((BackgroundJob::DisplayPaneError(ref pane_ids, _), _)) if pane_ids == &vec![PaneId::Terminal(1)]
+    ));
+}
+
+#[test]
+fn duplicate_gets_the_env_the_duplicated_pane_was_spawned_with() {
+    let output_dir = tempdir().unwrap();
+    let output_path = output_dir.path().join("env");
+    let (mut pty, to_pty, _screen_receiver, _background_jobs_receiver) = pty_with_receivers();
+    pty.set_session_env(vec![(
+        "ZELLIJ_TEST_DUPLICATED_ENV".to_owned(),
+        Some("before".to_owned()),
+    )]);
+    let run_command = RunCommand {
+        command: PathBuf::from("sh"),
+        args: vec![
+            "-c".to_owned(),
+            format!(
+                "printf '%s,' \"$ZELLIJ_TEST_DUPLICATED_ENV\" >> '{}'",
+                output_path.display()
+            ),
+        ],
+        ..Default::default()
+    };
+    let read_output = |expected: &str| {
+        let started = Instant::now();
+        loop {
+            match std::fs::read_to_string(&output_path) {
+                Ok(contents) if contents == expected => break,
+                _ if started.elapsed() > Duration::from_secs(10) => {
+                    panic!("pane did not write {:?}", expected)
+                },
+                _ => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    };
+    let (terminal_id, _starts_held) = pty
+        .spawn_terminal(
+            Some(TerminalAction::RunCommand(run_command.clone())),
+            ClientTabIndexOrPaneId::TabIndex(0),
+        )
+        .unwrap();
+    read_output("before,");
+
+    pty.set_session_env(vec![(
+        "ZELLIJ_TEST_DUPLICATED_ENV".to_owned(),
+        Some("after".to_owned()),
+    )]);
+    duplicate_pane_in_pty_thread(pty, &to_pty, terminal_id, run_command);
+    read_output("before,before,");
+}
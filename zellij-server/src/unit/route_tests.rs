@@ -0,0 +1,420 @@
+use super::*;
+
+use crate::os_input_output::{AsyncReader, Pid};
+use std::os::unix::io::{FromRawFd, IntoRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use zellij_utils::{
+    channels,
+    data::{BareKey, KeyWithModifier, Palette},
+    input::command::RunCommand,
+    interprocess::local_socket::LocalSocketStream,
+    ipc::{IpcSenderWithContext, MovedPane, PaneSignal},
+};
+
+#[derive(Clone, Default)]
+struct FakeInputOutput {
+    stdin_pipe_calls: Arc<Mutex<Vec<u32>>>, // terminal ids written to or closed
+}
+
+impl ServerOsApi for FakeInputOutput {
+    fn set_terminal_size_using_terminal_id(
+        &self,
+        _terminal_id: u32,
+        _cols: u16,
+        _rows: u16,
+        _width_in_pixels: Option<u16>,
+        _height_in_pixels: Option<u16>,
+    ) -> Result<()> {
+        unimplemented!()
+    }
+    fn spawn_terminal(
+        &self,
+        _file_to_open: TerminalAction,
+        _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
+        _default_editor: Option<PathBuf>,
+        _session_env: &BTreeMap<String, Option<String>>,
+    ) -> Result<(u32, RawFd, RawFd)> {
+        unimplemented!()
+    }
+    fn read_from_tty_stdout(&self, _fd: RawFd, _buf: &mut [u8]) -> Result<usize> {
+        unimplemented!()
+    }
+    fn async_file_reader(&self, _fd: RawFd) -> Box<dyn AsyncReader> {
+        unimplemented!()
+    }
+    fn write_to_tty_stdin(&self, _id: u32, _buf: &[u8]) -> Result<usize> {
+        unimplemented!()
+    }
+    fn tcdrain(&self, _id: u32) -> Result<()> {
+        unimplemented!()
+    }
+    fn kill(&self, _pid: Pid) -> Result<()> {
+        unimplemented!()
+    }
+    fn force_kill(&self, _pid: Pid) -> Result<()> {
+        unimplemented!()
+    }
+    fn box_clone(&self) -> Box<dyn ServerOsApi> {
+        Box::new((*self).clone())
+    }
+    fn send_to_client(&self, _client_id: ClientId, _msg: ServerToClientMsg) -> Result<()> {
+        Ok(())
+    }
+    fn new_client(
+        &mut self,
+        _client_id: ClientId,
+        _stream: LocalSocketStream,
+    ) -> Result<IpcReceiverWithContext<ClientToServerMsg>> {
+        unimplemented!()
+    }
+    fn remove_client(&mut self, _client_id: ClientId) -> Result<()> {
+        unimplemented!()
+    }
+    fn load_palette(&self) -> Palette {
+        unimplemented!()
+    }
+    fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
+        unimplemented!()
+    }
+    fn write_to_file(&mut self, _buf: String, _file: Option<String>) -> Result<()> {
+        unimplemented!()
+    }
+    fn re_run_command_in_terminal(
+        &self,
+        _terminal_id: u32,
+        _run_command: RunCommand,
+        _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        _session_env: &BTreeMap<String, Option<String>>,
+    ) -> Result<(RawFd, RawFd)> {
+        unimplemented!()
+    }
+    fn clear_terminal_id(&self, _terminal_id: u32) -> Result<()> {
+        unimplemented!()
+    }
+    fn write_to_stdin_pipe(
+        &self,
+        terminal_id: u32,
+        _client_id: ClientId,
+        _buf: &[u8],
+    ) -> Result<()> {
+        self.stdin_pipe_calls.lock().unwrap().push(terminal_id);
+        Ok(())
+    }
+    fn close_stdin_pipe(&self, terminal_id: u32, _client_id: ClientId) -> Result<()> {
+        self.stdin_pipe_calls.lock().unwrap().push(terminal_id);
+        Ok(())
+    }
+}
+
+/// Routes the messages of a client until it is done, returning what was sent to the server
+/// thread and the terminal ids whose STDIN pipe was written to or closed
+fn route_messages_of_client(
+    messages: Vec<ClientToServerMsg>,
+    read_only: bool,
+) -> (Vec<ServerInstruction>, Vec<u32>) {
+    let (server_side, client_side) = UnixStream::pair().unwrap();
+    let mut sender: IpcSenderWithContext<ClientToServerMsg> = IpcSenderWithContext::new(unsafe {
+        LocalSocketStream::from_raw_fd(client_side.into_raw_fd())
+    });
+    let receiver: IpcReceiverWithContext<ClientToServerMsg> = IpcReceiverWithContext::new(unsafe {
+        LocalSocketStream::from_raw_fd(server_side.into_raw_fd())
+    });
+    let session_state = Arc::new(RwLock::new(SessionState::new()));
+    let client_id = session_state.write().unwrap().new_client();
+    session_state
+        .write()
+        .unwrap()
+        .set_client_read_only(client_id, read_only);
+    let (to_server, server_receiver) = channels::unbounded();
+    let os_input = FakeInputOutput::default();
+    let stdin_pipe_calls = os_input.stdin_pipe_calls.clone();
+
+    for message in messages {
+        sender.send(message).unwrap();
+    }
+    sender.send(ClientToServerMsg::ClientExited).unwrap();
+    route_thread_main(
+        Arc::new(RwLock::new(None)),
+        session_state,
+        Box::new(os_input),
+        SenderWithContext::new(to_server),
+        receiver,
+        client_id,
+    )
+    .unwrap();
+
+    let server_instructions = server_receiver
+        .try_iter()
+        .map(|(instruction, _)| instruction)
+        .collect();
+    let stdin_pipe_calls = stdin_pipe_calls.lock().unwrap().clone();
+    (server_instructions, stdin_pipe_calls)
+}
+
+#[test]
+fn read_only_clients_cannot_change_the_session() {
+    let messages = vec![
+        ClientToServerMsg::Key(KeyWithModifier::new(BareKey::Char('a')), vec![b'a'], false),
+        ClientToServerMsg::Action(
+            Action::MultiExec {
+                chars: "rm -rf ~\n".to_owned(),
+                group: None,
+                exclude_focused: false,
+            },
+            None,
+            None,
+        ),
+        ClientToServerMsg::WriteStdinToPane(1, b"input".to_vec()),
+        ClientToServerMsg::CloseStdinPipe(1),
+        ClientToServerMsg::SignalPanes(PaneSignal::Term),
+        ClientToServerMsg::KillSession,
+        ClientToServerMsg::DetachSession(vec![1, 2]),
+        ClientToServerMsg::AdoptPane(
+            MovedPane {
+                name: None,
+                invoked_with: None,
+                scrollback: None,
+            },
+            1,
+        ),
+    ];
+    let (server_instructions, stdin_pipe_calls) = route_messages_of_client(messages, true);
+    assert!(
+        matches!(
+            &server_instructions[..],
+            [ServerInstruction::RemoveClient(..)]
+        ),
+        "only the client leaving reached the server, got: {:?}",
+        server_instructions
+    );
+    assert!(stdin_pipe_calls.is_empty());
+}
+
+#[test]
+fn clients_that_are_not_read_only_can_change_the_session() {
+    let messages = vec![
+        ClientToServerMsg::WriteStdinToPane(1, b"input".to_vec()),
+        ClientToServerMsg::CloseStdinPipe(1),
+        ClientToServerMsg::KillSession,
+        ClientToServerMsg::SignalPanes(PaneSignal::Term), // ends the connection of the client
+    ];
+    let (server_instructions, stdin_pipe_calls) = route_messages_of_client(messages, false);
+    assert!(
+        matches!(
+            &server_instructions[..],
+            [
+                ServerInstruction::KillSession,
+                ServerInstruction::SignalPanes(PaneSignal::Term, _)
+            ]
+        ),
+        "got: {:?}",
+        server_instructions
+    );
+    assert_eq!(stdin_pipe_calls, vec![1, 1]);
+}
+
+#[test]
+fn closing_a_pane_by_id_also_closes_its_pty() {
+    let (to_screen, screen_receiver) = channels::unbounded();
+    let (to_pty, pty_receiver) = channels::unbounded();
+    let (to_plugin, _plugin_receiver) = channels::unbounded();
+    let senders = ThreadSenders {
+        to_screen: Some(SenderWithContext::new(to_screen)),
+        to_pty: Some(SenderWithContext::new(to_pty)),
+        to_plugin: Some(SenderWithContext::new(to_plugin)),
+        to_server: None,
+        to_pty_writer: None,
+        to_background_jobs: None,
+        should_silently_fail: false,
+    };
+    route_action(
+        Action::ClosePaneWithId(zellij_utils::data::PaneId::Terminal(1)),
+        1,
+        None,
+        senders,
+        PluginCapabilities::default(),
+        ClientAttributes::default(),
+        None,
+        Box::new(Layout::default()),
+        None,
+        Keybinds::default(),
+        InputMode::Normal,
+    )
+    .unwrap();
+    assert!(matches!(
+        screen_receiver.try_recv(),
+        Ok((ScreenInstruction::ClosePane(PaneId::Terminal(1), None), _))
+    ));
+    assert!(
+        matches!(
+            pty_receiver.try_recv(),
+            Ok((PtyInstruction::ClosePane(PaneId::Terminal(1)), _))
+        ),
+        "the process of the pane is killed"
+    );
+}
+
+#[test]
+fn new_panes_are_refused_before_being_spawned_over_the_resource_limits() {
+    let (to_server, server_receiver) = channels::unbounded();
+    let session_metadata = SessionMetaData {
+        senders: ThreadSenders {
+            to_screen: None,
+            to_pty: None,
+            to_plugin: None,
+            to_server: Some(SenderWithContext::new(to_server)),
+            to_pty_writer: None,
+            to_background_jobs: None,
+            should_silently_fail: false,
+        },
+        capabilities: PluginCapabilities::default(),
+        client_attributes: ClientAttributes::default(),
+        default_shell: None,
+        layout: Box::new(Layout::default()),
+        current_input_modes: Default::default(),
+        session_configuration: Default::default(),
+        raw_passthrough_clients: HashSet::new(),
+        refused_new_panes: [(1, "this tab is full".to_owned())].into_iter().collect(),
+        copy_mode_clients: HashSet::new(),
+        hooks: Default::default(),
+        layout_watcher: None,
+        screen_thread: None,
+        pty_thread: None,
+        plugin_thread: None,
+        pty_writer_thread: None,
+        background_jobs_thread: None,
+    };
+    let new_pane = Action::NewPane(None, None, false);
+    assert!(refuse_new_pane(&session_metadata, &new_pane, 1).unwrap());
+    assert!(
+        !refuse_new_pane(&session_metadata, &new_pane, 2).unwrap(),
+        "the tab of this client is not full"
+    );
+    assert!(
+        !refuse_new_pane(&session_metadata, &Action::FocusNextPane, 1).unwrap(),
+        "only actions opening a pane are refused"
+    );
+    let server_instructions: Vec<ServerInstruction> = server_receiver
+        .try_iter()
+        .map(|(instruction, _)| instruction)
+        .collect();
+    assert!(
+        matches!(
+            &server_instructions[..],
+            [
+                ServerInstruction::LogError(refusal, 1),
+                ServerInstruction::UnblockInputThread
+            ] if refusal == &vec!["this tab is full".to_owned()]
+        ),
+        "the client is told why and its input is unblocked once, got: {:?}",
+        server_instructions
+    );
+}
+
+#[test]
+fn keys_of_the_keyboard_selection_are_sent_to_it_rather_than_to_their_bindings() {
+    let key = |bare_key: BareKey| KeyWithModifier::new(bare_key);
+    assert_eq!(
+        copy_mode_key_bytes(&key(BareKey::Char('e'))),
+        Some(vec![b'e'])
+    );
+    assert_eq!(
+        copy_mode_key_bytes(&key(BareKey::Char('h'))),
+        Some(vec![b'h'])
+    );
+    assert_eq!(
+        copy_mode_key_bytes(&key(BareKey::Char('l'))),
+        Some(vec![b'l'])
+    );
+    assert_eq!(
+        copy_mode_key_bytes(&key(BareKey::Char('e')).with_shift_modifier()),
+        Some(vec![b'E'])
+    );
+    assert_eq!(
+        copy_mode_key_bytes(&key(BareKey::Char('v')).with_ctrl_modifier()),
+        Some(vec![0x16]),
+        "Ctrl-v starts a block selection"
+    );
+    assert_eq!(copy_mode_key_bytes(&key(BareKey::Esc)), Some(vec![0x1b]));
+    assert_eq!(
+        copy_mode_key_bytes(&key(BareKey::Char('f')).with_ctrl_modifier()),
+        None,
+        "other keys with modifiers keep their bindings"
+    );
+    assert_eq!(
+        copy_mode_key_bytes(&key(BareKey::PageDown)),
+        None,
+        "keys the keyboard selection does not use keep their bindings"
+    );
+}
+
+#[test]
+fn hooks_are_run_without_holding_the_session_data() {
+    use std::sync::RwLock;
+    use zellij_utils::{
+        input::hooks::{Hook, Hooks},
+        serde_json::json,
+        session_events::record,
+    };
+
+    // nothing is queued for screen, so sending it the action of the hook waits until it is taken
+    let (to_screen, screen_receiver) = channels::bounded(0);
+    let (to_plugin, _plugin_receiver) = channels::unbounded();
+    let mut hooks = Hooks::default();
+    hooks.hooks.insert(
+        "client-attached".to_owned(),
+        vec![Hook::Actions(vec![Action::FocusNextPane])],
+    );
+    let session_metadata = SessionMetaData {
+        senders: ThreadSenders {
+            to_screen: Some(SenderWithContext::new(to_screen)),
+            to_pty: None,
+            to_plugin: Some(SenderWithContext::new(to_plugin)),
+            to_server: None,
+            to_pty_writer: None,
+            to_background_jobs: None,
+            should_silently_fail: false,
+        },
+        capabilities: PluginCapabilities::default(),
+        client_attributes: ClientAttributes::default(),
+        default_shell: None,
+        layout: Box::new(Layout::default()),
+        current_input_modes: [(1, InputMode::Normal)].into_iter().collect(),
+        session_configuration: Default::default(),
+        raw_passthrough_clients: HashSet::new(),
+        refused_new_panes: Default::default(),
+        copy_mode_clients: HashSet::new(),
+        hooks,
+        layout_watcher: None,
+        screen_thread: None,
+        pty_thread: None,
+        plugin_thread: None,
+        pty_writer_thread: None,
+        background_jobs_thread: None,
+    };
+    let session_data = Arc::new(RwLock::new(Some(session_metadata)));
+    let hook_thread = std::thread::spawn({
+        let session_data = session_data.clone();
+        move || {
+            crate::run_hooks(
+                &session_data,
+                &[record("client-attached", json!({ "client_id": 1 }))],
+                Some(1),
+            )
+        }
+    });
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(
+        session_data.try_write().is_ok(),
+        "the session data can be changed while the action of a hook waits for screen"
+    );
+    assert!(matches!(
+        screen_receiver.recv_timeout(std::time::Duration::from_secs(5)),
+        Ok((ScreenInstruction::FocusNextPane(1), _))
+    ));
+    hook_thread.join().unwrap();
+    // so that the session data does not wait for screen to take its exit instruction
+    drop(screen_receiver);
+}
@@ -12,16 +12,16 @@ use std::path::PathBuf;
 use zellij_utils::cli::CliAction;
 use zellij_utils::data::{Event, Resize, Style};
 use zellij_utils::errors::{prelude::*, ErrorContext};
-use zellij_utils::input::actions::Action;
+use zellij_utils::input::actions::{Action, PaneGroupAction, TabTarget};
 use zellij_utils::input::command::{RunCommand, TerminalAction};
 use zellij_utils::input::config::Config;
 use zellij_utils::input::layout::{
     FloatingPaneLayout, Layout, PluginAlias, PluginUserConfiguration, Run, RunPlugin,
     RunPluginLocation, RunPluginOrAlias, SplitDirection, SplitSize, TiledPaneLayout,
 };
-use zellij_utils::input::options::Options;
+use zellij_utils::input::options::{BellForwarding, CommandExitBanner, Options, TitlePrecedence};
 use zellij_utils::ipc::IpcReceiverWithContext;
-use zellij_utils::pane_size::{Size, SizeInPixels};
+use zellij_utils::pane_size::{PaneGeom, Size, SizeInPixels};
 
 use crate::background_jobs::BackgroundJob;
 use crate::pty_writer::PtyWriteInstruction;
@@ -33,20 +33,21 @@ use crate::{
     plugins::PluginInstruction,
     pty::{ClientTabIndexOrPaneId, PtyInstruction},
 };
-use zellij_utils::ipc::PixelDimensions;
+use zellij_utils::ipc::{MovedPane, PaneSignal, PixelDimensions};
 
 use zellij_utils::{
     channels::{self, ChannelWithContext, Receiver},
     data::{Direction, FloatingPaneCoordinates, InputMode, ModeInfo, Palette, PluginCapabilities},
     interprocess::local_socket::LocalSocketStream,
     ipc::{ClientAttributes, ClientToServerMsg, ServerToClientMsg},
+    serde_json,
 };
 
 use crate::panes::grid::Grid;
 use crate::panes::link_handler::LinkHandler;
 use crate::panes::sixel::SixelImageStore;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::rc::Rc;
 use zellij_utils::vte;
 
@@ -173,6 +174,7 @@ impl ServerOsApi for FakeInputOutput {
         _file_to_open: TerminalAction,
         _quit_db: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
         _default_editor: Option<PathBuf>,
+        _session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(u32, RawFd, RawFd)> {
         unimplemented!()
     }
@@ -236,6 +238,7 @@ impl ServerOsApi for FakeInputOutput {
         _terminal_id: u32,
         _run_command: RunCommand,
         _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        _session_env: &BTreeMap<String, Option<String>>,
     ) -> Result<(RawFd, RawFd)> {
         unimplemented!()
     }
@@ -294,6 +297,13 @@ fn create_new_screen(size: Size) -> Screen {
         explicitly_disable_kitty_keyboard_protocol,
         stacked_resize,
         None,
+        vec![],
+        None,
+        TitlePrecedence::default(),
+        false,
+        BellForwarding::default(),
+        CommandExitBanner::default(),
+        None,
     );
     screen
 }
@@ -351,6 +361,7 @@ impl MockScreen {
                     config,
                     debug,
                     Box::new(Layout::default()),
+                    vec![],
                 )
                 .expect("TEST")
             })
@@ -429,6 +440,7 @@ impl MockScreen {
                     config,
                     debug,
                     Box::new(Layout::default()),
+                    vec![],
                 )
                 .expect("TEST")
             })
@@ -544,6 +556,11 @@ impl MockScreen {
             session_configuration: self.session_metadata.session_configuration.clone(),
             layout,
             current_input_modes: self.session_metadata.current_input_modes.clone(),
+            raw_passthrough_clients: self.session_metadata.raw_passthrough_clients.clone(),
+            refused_new_panes: self.session_metadata.refused_new_panes.clone(),
+            copy_mode_clients: self.session_metadata.copy_mode_clients.clone(),
+            hooks: self.session_metadata.hooks.clone(),
+            layout_watcher: None,
         }
     }
 }
@@ -602,6 +619,11 @@ impl MockScreen {
             layout,
             session_configuration: Default::default(),
             current_input_modes: HashMap::new(),
+            raw_passthrough_clients: HashSet::new(),
+            refused_new_panes: HashMap::new(),
+            copy_mode_clients: HashSet::new(),
+            hooks: Default::default(),
+            layout_watcher: None,
         };
 
         let os_input = FakeInputOutput::default();
@@ -789,6 +811,328 @@ pub fn close_tab() {
     );
 }
 
+#[test]
+pub fn close_tab_with_name() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+
+    assert!(
+        !screen.close_tab_with_name("Tab #1").expect("TEST"),
+        "No tab closed for an unknown name"
+    );
+    assert!(
+        screen.close_tab_with_name("Tab #2").expect("TEST"),
+        "Tab closed by name"
+    );
+    let tab_names: Vec<String> = screen.tabs.values().map(|t| t.name.clone()).collect();
+    assert_eq!(
+        tab_names,
+        vec!["Tab #3".to_owned()],
+        "Only the other tab left"
+    );
+}
+
+#[test]
+pub fn join_tab_with_a_single_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 0);
+    new_tab(&mut screen, 2, 1);
+
+    screen.join_tab(0, Some(Direction::Left), 1).expect("TEST");
+
+    assert_eq!(screen.tabs.len(), 1, "Joined tab closed");
+    let active_tab = screen.get_active_tab(1).unwrap();
+    let first_pane_x = active_tab
+        .get_pane_with_id(PaneId::Terminal(1))
+        .expect("joined pane is in the tab")
+        .x();
+    let second_pane_x = active_tab
+        .get_pane_with_id(PaneId::Terminal(2))
+        .expect("focused pane is still in the tab")
+        .x();
+    assert!(
+        first_pane_x < second_pane_x,
+        "Joined pane placed to the left of the focused pane"
+    );
+}
+
+#[test]
+pub fn pane_moved_between_sessions() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 0);
+
+    let moved_pane = MovedPane {
+        name: Some("long job".to_owned()),
+        invoked_with: None,
+        scrollback: Some("building...".to_owned()),
+    };
+    screen.adopt_pane(2, moved_pane, 1).expect("TEST");
+    let adopted_pane = screen
+        .get_active_tab(1)
+        .unwrap()
+        .get_pane_with_id(PaneId::Terminal(2))
+        .expect("adopted pane is in the active tab");
+    assert_eq!(
+        adopted_pane.custom_title(),
+        Some("long job".to_owned()),
+        "Adopted pane keeps its name"
+    );
+
+    screen.remove_pane_moved_to_session(2).expect("TEST");
+    assert_eq!(
+        screen.get_active_tab(1).unwrap().get_all_pane_ids(),
+        vec![PaneId::Terminal(1)],
+        "Pane moved away is removed"
+    );
+}
+
+#[test]
+pub fn link_pane_to_another_tab() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+
+    screen.link_pane_to_tab(0, 1).expect("TEST");
+    let first_tab = screen.tabs.values().find(|tab| tab.position == 0).unwrap();
+    assert!(
+        first_tab.get_all_pane_ids().contains(&PaneId::Terminal(2)),
+        "Linked pane is shown in the other tab"
+    );
+    assert!(
+        first_tab.pane_is_linked_view(PaneId::Terminal(2)),
+        "Pane in the other tab is a linked view"
+    );
+    assert!(
+        !screen
+            .get_active_tab(1)
+            .unwrap()
+            .pane_is_linked_view(PaneId::Terminal(2)),
+        "Original pane is not a linked view"
+    );
+}
+
+#[test]
+pub fn exited_command_is_held_in_every_tab_linking_its_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+    screen.link_pane_to_tab(0, 1).expect("TEST");
+    screen
+        .hold_exited_command_pane(PaneId::Terminal(2), Some(1), RunCommand::default())
+        .expect("TEST");
+
+    for tab in screen.tabs.values() {
+        assert!(
+            tab.get_pane_with_id(PaneId::Terminal(2))
+                .map(|pane| pane.is_held())
+                .unwrap_or(false),
+            "pane is held in tab {}",
+            tab.position
+        );
+    }
+}
+
+#[test]
+pub fn linked_views_are_not_serialized_as_their_pane() {
+    use zellij_utils::session_serialization::{GlobalLayoutManifest, PaneLayoutManifest};
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+    screen.link_pane_to_tab(0, 1).expect("TEST");
+    let mut session_layout_metadata = screen.get_layout_metadata(None);
+    assert_eq!(
+        session_layout_metadata.all_terminal_ids(),
+        vec![1, 2],
+        "the command and cwd of each terminal are looked up once"
+    );
+    let mut terminal_commands = HashMap::new();
+    terminal_commands.insert(2, vec!["htop".to_owned()]);
+    session_layout_metadata.update_terminal_commands(terminal_commands);
+
+    let global_layout_manifest: GlobalLayoutManifest = session_layout_metadata.into();
+    let tiled_panes: Vec<&PaneLayoutManifest> = global_layout_manifest
+        .tabs
+        .iter()
+        .flat_map(|(_tab_name, tab)| tab.tiled_panes.iter())
+        .collect();
+    assert_eq!(
+        tiled_panes.len(),
+        3,
+        "the linked view keeps its place in the layout of its tab"
+    );
+    assert_eq!(
+        tiled_panes.iter().filter(|pane| pane.run.is_some()).count(),
+        1,
+        "the command of the linked pane is serialized once"
+    );
+    assert!(global_layout_manifest.tabs[1].1.tiled_panes[0]
+        .run
+        .is_some());
+}
+
+#[test]
+pub fn rename_tab_with_name() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+
+    assert!(
+        !screen.rename_tab_with_name("Tab #1", "logs".to_owned()),
+        "No tab renamed for an unknown name"
+    );
+    assert!(
+        screen.rename_tab_with_name("Tab #2", "logs".to_owned()),
+        "Tab renamed by name"
+    );
+    let mut tab_names: Vec<String> = screen.tabs.values().map(|t| t.name.clone()).collect();
+    tab_names.sort();
+    assert_eq!(
+        tab_names,
+        vec!["Tab #3".to_owned(), "logs".to_owned()],
+        "Only the addressed tab was renamed"
+    );
+}
+
+#[test]
+pub fn list_panes_with_their_ids_and_tabs() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+
+    let panes: serde_json::Value =
+        serde_json::from_str(&screen.render_pane_list(true)).expect("TEST");
+    let panes: Vec<(String, u64, String)> = panes
+        .as_array()
+        .expect("TEST")
+        .iter()
+        .map(|pane| {
+            (
+                pane["pane_id"].as_str().unwrap().to_owned(),
+                pane["tab_index"].as_u64().unwrap(),
+                pane["tab_name"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        panes,
+        vec![
+            ("terminal_1".to_owned(), 1, "Tab #2".to_owned()),
+            ("terminal_2".to_owned(), 2, "Tab #3".to_owned()),
+        ],
+        "Panes listed by tab position"
+    );
+    assert!(
+        screen
+            .render_pane_list(false)
+            .starts_with("ZELLIJ_PANE_ID TAB_INDEX"),
+        "Panes listed as text with a header"
+    );
+}
+
+#[test]
+pub fn list_panes_with_the_cwd_reported_by_their_shell() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+    screen
+        .get_tabs_mut()
+        .get_mut(&1)
+        .unwrap()
+        .handle_pty_bytes(
+            1,
+            "\u{1b}]7;file:///tmp/my%20project\u{7}".as_bytes().to_vec(),
+        )
+        .expect("TEST");
+
+    let panes: serde_json::Value =
+        serde_json::from_str(&screen.render_pane_list(true)).expect("TEST");
+    let cwds: Vec<Option<&str>> = panes
+        .as_array()
+        .expect("TEST")
+        .iter()
+        .map(|pane| pane["cwd"].as_str())
+        .collect();
+    assert_eq!(
+        cwds,
+        vec![Some("/tmp/my project"), None],
+        "Only the pane whose shell reported its cwd has one"
+    );
+}
+
+#[test]
+pub fn dump_session_describes_tabs_panes_and_focus() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+
+    let session_dump: serde_json::Value =
+        serde_json::from_str(&screen.render_session_dump()).expect("TEST");
+    let tabs = session_dump["tabs"].as_array().expect("TEST");
+    assert_eq!(tabs.len(), 2, "All tabs dumped");
+    assert_eq!(tabs[1]["tab_index"], 2);
+    let pane = &tabs[1]["panes"][0];
+    assert_eq!(pane["pane_id"], "terminal_2");
+    assert_eq!(pane["exited"], false);
+    assert_eq!(pane["scrollback_lines"], 0);
+    assert_eq!(pane["geometry"]["columns"], 121);
+    assert_eq!(
+        session_dump["clients"],
+        serde_json::json!([{ "client_id": 1, "tab_index": 2, "focused_pane_id": "terminal_2" }]),
+        "Clients dumped with their focused tab and pane"
+    );
+}
+
 #[test]
 pub fn close_the_middle_tab() {
     let size = Size {
@@ -871,40 +1215,1325 @@ fn move_of_active_tab_to_left_when_there_is_only_one_tab() {
 }
 
 #[test]
-fn move_of_active_tab_to_left_multiple_times() {
+fn move_of_active_tab_to_left_multiple_times() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    new_tab(&mut screen, 2, 1);
+    new_tab(&mut screen, 3, 2);
+    assert_eq!(screen.get_active_tab(1).unwrap().position, 2);
+
+    screen.move_active_tab_to_left(1).expect("TEST");
+    screen.move_active_tab_to_left(1).expect("TEST");
+
+    assert_eq!(
+        screen.get_active_tab(1).unwrap().position,
+        0,
+        "Active tab moved to left twice"
+    );
+}
+
+#[test]
+fn wrapping_move_of_active_tab_to_left() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    new_tab(&mut screen, 2, 1);
+    new_tab(&mut screen, 3, 2);
+    screen.move_focus_left_or_previous_tab(1).expect("TEST");
+    screen.move_focus_left_or_previous_tab(1).expect("TEST");
+    assert_eq!(screen.get_active_tab(1).unwrap().position, 0);
+
+    screen.move_active_tab_to_left(1).expect("TEST");
+
+    assert_eq!(
+        screen.get_active_tab(1).unwrap().position,
+        2,
+        "Active tab moved to left until wrapped around"
+    );
+}
+
+#[test]
+fn move_of_active_tab_to_position() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    new_tab(&mut screen, 2, 1);
+    new_tab(&mut screen, 3, 2);
+    assert_eq!(screen.get_active_tab(1).unwrap().position, 2);
+
+    screen.move_active_tab_to_position(0, 1).expect("TEST");
+
+    let mut tabs: Vec<_> = screen.tabs.values().collect();
+    tabs.sort_by_key(|t| t.position);
+    let tab_names: Vec<&str> = tabs.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(
+        tab_names,
+        vec!["Tab #3", "Tab #1", "Tab #2"],
+        "Active tab moved to the first position, the other tabs shifted right"
+    );
+    assert_eq!(
+        screen.get_active_tab(1).unwrap().position,
+        0,
+        "Moved tab is still active"
+    );
+}
+
+// a screen whose session socket is in a temporary folder, with the messages it sends to its
+// clients and the background jobs it starts
+fn screen_with_session_socket(
+    socket_dir: &std::path::Path,
+) -> (
+    Screen,
+    Arc<Mutex<HashMap<ClientId, Vec<ServerToClientMsg>>>>,
+    Receiver<(BackgroundJob, ErrorContext)>,
+) {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    screen.session_socket_dir = socket_dir.to_path_buf();
+    std::fs::write(socket_dir.join(&screen.session_name), "").unwrap();
+    let os_input = FakeInputOutput::default();
+    let server_to_client_messages = os_input.server_to_client_messages.clone();
+    screen.bus.os_input = Some(Box::new(os_input));
+    let (to_background_jobs, background_jobs_receiver): ChannelWithContext<BackgroundJob> =
+        channels::unbounded();
+    screen.bus.senders.to_background_jobs = Some(SenderWithContext::new(to_background_jobs));
+    (screen, server_to_client_messages, background_jobs_receiver)
+}
+
+#[test]
+fn renaming_the_session_moves_its_socket_metadata_and_clients() {
+    let socket_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let (mut screen, server_to_client_messages, background_jobs_receiver) =
+        screen_with_session_socket(socket_dir.path());
+    let old_session_name = screen.session_name.clone();
+
+    screen
+        .rename_session("renamed-session".to_owned(), 1)
+        .expect("TEST");
+
+    assert_eq!(screen.session_name, "renamed-session");
+    assert!(
+        socket_dir.path().join("renamed-session").exists(),
+        "the socket is reachable by the new name"
+    );
+    assert!(!socket_dir.path().join(&old_session_name).exists());
+    assert!(
+        background_jobs_receiver
+            .try_iter()
+            .any(|(background_job, _)| background_job
+                == BackgroundJob::RenameSession(
+                    old_session_name.clone(),
+                    "renamed-session".to_owned()
+                )),
+        "the resurrection metadata is moved"
+    );
+    assert!(
+        server_to_client_messages
+            .lock()
+            .unwrap()
+            .get(&1)
+            .unwrap()
+            .iter()
+            .any(|msg| matches!(msg, ServerToClientMsg::RenamedSession(name) if name == "renamed-session")),
+        "the clients reattach by the new name"
+    );
+}
+
+#[test]
+fn renaming_the_session_to_its_own_name_changes_nothing() {
+    let socket_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let (mut screen, server_to_client_messages, background_jobs_receiver) =
+        screen_with_session_socket(socket_dir.path());
+    let session_name = screen.session_name.clone();
+
+    screen
+        .rename_session(session_name.clone(), 1)
+        .expect("TEST");
+
+    assert!(socket_dir.path().join(&session_name).exists());
+    assert!(
+        server_to_client_messages.lock().unwrap().is_empty(),
+        "no error, the session already has this name"
+    );
+    assert_eq!(background_jobs_receiver.try_iter().count(), 0);
+}
+
+#[test]
+fn renaming_the_session_to_the_name_of_another_socket_is_refused() {
+    let socket_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let (mut screen, server_to_client_messages, background_jobs_receiver) =
+        screen_with_session_socket(socket_dir.path());
+    let session_name = screen.session_name.clone();
+    std::fs::write(socket_dir.path().join("taken"), "").unwrap();
+
+    screen.rename_session("taken".to_owned(), 1).expect("TEST");
+
+    assert_eq!(
+        screen.session_name, session_name,
+        "the session keeps its name"
+    );
+    assert!(socket_dir.path().join(&session_name).exists());
+    assert!(
+        server_to_client_messages
+            .lock()
+            .unwrap()
+            .get(&1)
+            .unwrap()
+            .iter()
+            .any(|msg| matches!(msg, ServerToClientMsg::LogError(..))),
+        "the client is told why"
+    );
+    assert_eq!(background_jobs_receiver.try_iter().count(), 0);
+}
+
+#[test]
+fn running_commands_are_only_monitored_while_tabs_are_renamed_automatically() {
+    let mut screen = create_fixed_size_screen();
+    let (to_background_jobs, background_jobs_receiver): ChannelWithContext<BackgroundJob> =
+        channels::unbounded();
+    screen.bus.senders.to_background_jobs = Some(SenderWithContext::new(to_background_jobs));
+    screen.set_automatic_tab_rename(true).expect("TEST");
+    screen.running_commands.insert(1, "htop".to_owned());
+    screen.set_automatic_tab_rename(false).expect("TEST");
+    let background_jobs: Vec<BackgroundJob> = background_jobs_receiver
+        .try_iter()
+        .map(|(background_job, _)| background_job)
+        .collect();
+    assert_eq!(
+        background_jobs,
+        vec![
+            BackgroundJob::MonitorRunningCommands,
+            BackgroundJob::StopMonitor(Box::new(BackgroundJob::MonitorRunningCommands)),
+        ]
+    );
+    assert!(screen.running_commands.is_empty());
+}
+
+#[test]
+fn process_stats_are_only_monitored_while_shown_in_pane_frames() {
+    let mut screen = create_fixed_size_screen();
+    let (to_background_jobs, background_jobs_receiver): ChannelWithContext<BackgroundJob> =
+        channels::unbounded();
+    screen.bus.senders.to_background_jobs = Some(SenderWithContext::new(to_background_jobs));
+    screen.set_pane_frame_process_stats(true).expect("TEST");
+    screen.set_pane_frame_process_stats(false).expect("TEST");
+    let background_jobs: Vec<BackgroundJob> = background_jobs_receiver
+        .try_iter()
+        .map(|(background_job, _)| background_job)
+        .collect();
+    assert_eq!(
+        background_jobs,
+        vec![
+            BackgroundJob::MonitorProcessStats,
+            BackgroundJob::StopMonitor(Box::new(BackgroundJob::MonitorProcessStats)),
+        ]
+    );
+}
+
+#[test]
+fn tabs_are_renamed_after_their_focused_pane_until_renamed_by_the_user() {
+    let mut screen = create_fixed_size_screen();
+    screen.automatic_tab_rename = true;
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+    let tab_name =
+        |screen: &Screen, tab_index: usize| screen.tabs.get(&tab_index).unwrap().name.clone();
+
+    screen.running_commands.insert(2, "htop".to_owned());
+    screen.render(None).expect("TEST");
+    assert_eq!(
+        tab_name(&screen, 2),
+        "htop",
+        "Named after the running command"
+    );
+
+    screen
+        .get_tabs_mut()
+        .get_mut(&2)
+        .unwrap()
+        .handle_pty_bytes(2, "\u{1b}]2;my title\u{7}".as_bytes().to_vec())
+        .expect("TEST");
+    screen.render(None).expect("TEST");
+    assert_eq!(
+        tab_name(&screen, 2),
+        "my title",
+        "Title set by the program preferred over the command"
+    );
+
+    screen
+        .update_active_tab_name("!".as_bytes().to_vec(), 1)
+        .expect("TEST");
+    screen.running_commands.insert(2, "vim".to_owned());
+    screen.render(None).expect("TEST");
+    assert_eq!(
+        tab_name(&screen, 2),
+        "my title!",
+        "Tab renamed by the user keeps its name"
+    );
+}
+
+#[test]
+fn bell_in_background_tab_marks_it_until_focused() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+    let has_bell_alert =
+        |screen: &Screen, tab_index: usize| screen.tabs.get(&tab_index).unwrap().has_bell_alert();
+
+    screen
+        .get_tabs_mut()
+        .get_mut(&1)
+        .unwrap()
+        .handle_pty_bytes(1, vec![7])
+        .expect("TEST");
+    screen
+        .get_tabs_mut()
+        .get_mut(&2)
+        .unwrap()
+        .handle_pty_bytes(2, vec![7])
+        .expect("TEST");
+    assert!(has_bell_alert(&screen, 1), "Background tab marked");
+    assert!(!has_bell_alert(&screen, 2), "Bell of focused pane ignored");
+
+    screen.switch_tab_prev(None, true, 1).expect("TEST");
+    screen.render(None).expect("TEST");
+    assert!(
+        !has_bell_alert(&screen, 1),
+        "Mark cleared once the pane is focused"
+    );
+}
+
+#[test]
+fn command_exiting_in_background_tab_marks_it_until_focused() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+    let has_command_exit_alert = |screen: &Screen, tab_index: usize| {
+        screen
+            .tabs
+            .get(&tab_index)
+            .unwrap()
+            .has_command_exit_alert()
+    };
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["build".to_owned()],
+        ..Default::default()
+    };
+
+    screen
+        .hold_exited_command_pane(PaneId::Terminal(1), Some(0), run_command.clone())
+        .expect("TEST");
+    screen
+        .hold_exited_command_pane(PaneId::Terminal(2), Some(1), run_command)
+        .expect("TEST");
+    assert!(has_command_exit_alert(&screen, 1), "Background tab marked");
+    assert!(
+        !has_command_exit_alert(&screen, 2),
+        "Command of the active tab ignored"
+    );
+
+    screen.switch_tab_prev(None, true, 1).expect("TEST");
+    screen.render(None).expect("TEST");
+    assert!(
+        !has_command_exit_alert(&screen, 1),
+        "Mark cleared once the pane is focused"
+    );
+}
+
+#[test]
+fn new_panes_are_refused_over_the_resource_limits() {
+    let size = Size { cols: 80, rows: 20 };
+    let client_id = 1;
+    let mut mock_screen = MockScreen::new(size);
+    mock_screen.config.options.max_panes_per_tab = Some(1);
+    let pty_receiver = mock_screen.pty_receiver.take().unwrap();
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_pty_instructions = Arc::new(Mutex::new(vec![]));
+    let pty_thread = log_actions_in_thread!(
+        received_pty_instructions,
+        PtyInstruction::Exit,
+        pty_receiver
+    );
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let unblock_count = |received_server_instructions: &Arc<Mutex<Vec<ServerInstruction>>>| {
+        received_server_instructions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|i| matches!(i, ServerInstruction::UnblockInputThread))
+            .count()
+    };
+    let unblocked_before_new_pane = unblock_count(&received_server_instructions);
+    let _ = mock_screen.to_screen.send(ScreenInstruction::NewPane(
+        PaneId::Terminal(2),
+        None,
+        None,
+        None,
+        None,
+        None,
+        false,
+        ClientTabIndexOrPaneId::ClientId(client_id),
+    ));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let unblocked_after_new_pane = unblock_count(&received_server_instructions);
+    mock_screen.teardown(vec![pty_thread, server_thread, screen_thread]);
+
+    let received_server_instructions = received_server_instructions.lock().unwrap();
+    assert!(
+        received_server_instructions.iter().any(|i| matches!(
+            i,
+            ServerInstruction::RefuseNewPanes(refused_new_panes)
+                if refused_new_panes.contains_key(&client_id)
+        )),
+        "the route thread is told to refuse new panes in the full tab"
+    );
+    assert!(
+        received_server_instructions.iter().any(|i| matches!(
+            i,
+            ServerInstruction::LogError(refusal, 1)
+                if refusal[0].contains("max_panes_per_tab is 1")
+        )),
+        "the client is told why its pane was refused"
+    );
+    assert!(
+        received_pty_instructions
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|i| matches!(i, PtyInstruction::ClosePane(PaneId::Terminal(2)))),
+        "the pane that got through is closed"
+    );
+    assert_eq!(
+        unblocked_before_new_pane, unblocked_after_new_pane,
+        "closing the pane unblocks the input of the client, screen does not do it again"
+    );
+}
+
+#[test]
+fn new_tabs_are_refused_when_their_layout_is_over_the_resource_limits() {
+    let size = Size { cols: 80, rows: 20 };
+    let client_id = 1;
+    let mut mock_screen = MockScreen::new(size);
+    mock_screen.config.options.max_panes_per_tab = Some(2);
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let mut layout_of_three_panes = TiledPaneLayout::default();
+    layout_of_three_panes.children = vec![
+        TiledPaneLayout::default(),
+        TiledPaneLayout::default(),
+        TiledPaneLayout::default(),
+    ];
+    let _ = mock_screen.to_screen.send(ScreenInstruction::NewTab(
+        None,
+        None,
+        Some(layout_of_three_panes),
+        vec![],
+        None,
+        (vec![], vec![]),
+        true,
+        client_id,
+    ));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, server_thread, screen_thread]);
+
+    assert!(
+        received_server_instructions
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|i| matches!(
+                i,
+                ServerInstruction::LogError(refusal, 1)
+                    if refusal[0] == "Cannot open a new tab: its layout has 3 panes (max_panes_per_tab is 2)"
+            )),
+        "the client is told why its tab was refused"
+    );
+    assert_eq!(
+        received_plugin_instructions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|i| matches!(i, PluginInstruction::NewTab(..)))
+            .count(),
+        1,
+        "only the panes of the first tab were spawned"
+    );
+}
+
+#[test]
+fn clients_with_a_keyboard_selection_are_reported_to_the_server() {
+    let size = Size { cols: 80, rows: 20 };
+    let client_id = 1;
+    let mut mock_screen = MockScreen::new(size);
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+        0,
+        "some text to select".as_bytes().to_vec(),
+    ));
+    let mut scroll_mode_info = ModeInfo::default();
+    scroll_mode_info.mode = InputMode::Scroll;
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::ChangeMode(scroll_mode_info, client_id));
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::CopyModeInput(vec![b'v'], client_id));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::ChangeMode(
+        ModeInfo::default(),
+        client_id,
+    ));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+
+    let copy_mode_clients: Vec<HashSet<ClientId>> = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|i| match i {
+            ServerInstruction::CopyModeClients(copy_mode_clients) => {
+                Some(copy_mode_clients.clone())
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        copy_mode_clients,
+        vec![[client_id].into_iter().collect(), HashSet::new()],
+        "the keys of the client drive its selection until it leaves scroll mode"
+    );
+}
+
+#[test]
+fn idle_clients_are_counted_down_until_they_send_input() {
+    use std::time::{Duration, Instant};
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    screen.idle_client_detach_timeout = Some(Duration::from_secs(300));
+    let idle_since = |seconds: u64| {
+        Instant::now()
+            .checked_sub(Duration::from_secs(seconds))
+            .expect("TEST")
+    };
+
+    screen.clients_last_input.insert(1, idle_since(100));
+    assert!(
+        !screen.check_idle_clients().expect("TEST"),
+        "not idle for long enough to be warned"
+    );
+    screen.clients_last_input.insert(1, idle_since(270));
+    assert!(screen.check_idle_clients().expect("TEST"));
+    assert!(
+        screen
+            .pending_vte_instructions
+            .iter()
+            .any(|(client_ids, warning)| client_ids == &vec![1]
+                && warning.contains("Detaching in 30s")),
+        "countdown rendered to the idle client"
+    );
+    assert!(screen.client_input(1), "countdown cleared by input");
+    assert!(!screen.check_idle_clients().expect("TEST"));
+}
+
+#[test]
+fn idle_session_is_only_monitored_while_a_timeout_is_configured() {
+    let mut screen = create_fixed_size_screen();
+    let (to_background_jobs, background_jobs_receiver): ChannelWithContext<BackgroundJob> =
+        channels::unbounded();
+    screen.bus.senders.to_background_jobs = Some(SenderWithContext::new(to_background_jobs));
+    screen.set_idle_session_timeout(Some(5)).expect("TEST");
+    screen.set_idle_session_timeout(Some(10)).expect("TEST");
+    screen.set_idle_session_timeout(None).expect("TEST");
+    let background_jobs: Vec<BackgroundJob> = background_jobs_receiver
+        .try_iter()
+        .map(|(background_job, _)| background_job)
+        .collect();
+    assert_eq!(
+        background_jobs,
+        vec![
+            BackgroundJob::MonitorIdleSession,
+            BackgroundJob::StopMonitor(Box::new(BackgroundJob::MonitorIdleSession)),
+        ]
+    );
+}
+
+#[test]
+fn idle_session_is_serialized_before_it_is_killed() {
+    use std::time::{Duration, Instant};
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    screen.session_serialization = true;
+    screen.idle_session_timeout = Some(Duration::from_secs(300));
+    let idle_since = |seconds: u64| {
+        Instant::now()
+            .checked_sub(Duration::from_secs(seconds))
+            .expect("TEST")
+    };
+
+    screen.last_session_activity = idle_since(400);
+    screen.check_idle_session().expect("TEST");
+    assert!(
+        !screen.idle_session_serialized,
+        "a session with an attached client is not idle"
+    );
+
+    screen.remove_client(1).expect("TEST");
+    screen.check_idle_session().expect("TEST");
+    assert!(
+        !screen.idle_session_serialized,
+        "idle from the moment the last client detached"
+    );
+    screen.last_session_activity = idle_since(400);
+    screen.check_idle_session().expect("TEST");
+    assert!(
+        screen.idle_session_serialized,
+        "serialized before being killed"
+    );
+    screen.session_activity();
+    assert!(
+        !screen.idle_session_serialized,
+        "pane output makes the session active again"
+    );
+}
+
+#[test]
+fn replaced_pane_command_runs_once_the_stopped_one_exited() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    let pane_id = PaneId::Terminal(1);
+    let run_command = |command: &str| RunCommand {
+        command: PathBuf::from(command),
+        hold_on_close: true,
+        ..Default::default()
+    };
+    let invoked_with = |screen: &Screen| {
+        screen
+            .get_active_tab(1)
+            .unwrap()
+            .get_pane_with_id(pane_id)
+            .unwrap()
+            .invoked_with()
+            .clone()
+    };
+
+    screen
+        .replace_pane_command(run_command("npm"), false, 1)
+        .expect("TEST");
+    assert!(
+        screen.commands_replacing_stopped_ones.contains_key(&1),
+        "a running command is stopped first"
+    );
+    screen
+        .hold_exited_command_pane(pane_id, None, run_command("bash"))
+        .expect("TEST");
+    assert!(screen.commands_replacing_stopped_ones.is_empty());
+    assert_eq!(
+        invoked_with(&screen),
+        Some(Run::Command(run_command("npm")))
+    );
+    assert!(
+        !screen
+            .get_active_tab(1)
+            .unwrap()
+            .get_pane_with_id(pane_id)
+            .unwrap()
+            .is_held(),
+        "the new command runs instead of the pane holding"
+    );
+}
+
+#[test]
+fn replacing_a_command_that_does_not_exit_is_given_up_after_sigkill() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    screen
+        .replace_pane_command(
+            RunCommand {
+                command: PathBuf::from("npm"),
+                ..Default::default()
+            },
+            false,
+            1,
+        )
+        .expect("TEST");
+    for signal in [PaneSignal::Hup, PaneSignal::Term] {
+        screen.stop_command_timed_out(1, signal).expect("TEST");
+        assert!(
+            screen.commands_replacing_stopped_ones.contains_key(&1),
+            "the command is sent a harsher signal after {:?}",
+            signal
+        );
+    }
+    screen
+        .stop_command_timed_out(1, PaneSignal::Kill)
+        .expect("TEST");
+    assert!(
+        screen.commands_replacing_stopped_ones.is_empty(),
+        "the replacement is dropped once even SIGKILL did not stop the command"
+    );
+}
+
+#[test]
+fn rerunning_a_watched_command_that_does_not_exit_is_given_up_after_sigkill() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    screen.rerun_watched_command(1).expect("TEST");
+    assert!(
+        screen.watched_commands_to_rerun.contains(&1),
+        "a running command is stopped first"
+    );
+    screen
+        .stop_command_timed_out(1, PaneSignal::Term)
+        .expect("TEST");
+    assert!(
+        screen.watched_commands_to_rerun.contains(&1),
+        "the command is sent SIGKILL after SIGTERM"
+    );
+    screen
+        .stop_command_timed_out(1, PaneSignal::Kill)
+        .expect("TEST");
+    assert!(
+        screen.watched_commands_to_rerun.is_empty(),
+        "the re-run is dropped once even SIGKILL did not stop the command"
+    );
+    screen.rerun_watched_command(1).expect("TEST");
+    assert!(
+        screen.watched_commands_to_rerun.contains(&1),
+        "the next change of its files tries to stop it again"
+    );
+}
+
+#[test]
+fn a_port_is_only_undeclared_for_the_pane_it_was_declared_for() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    screen.declare_port(8080, Some("api".to_owned()), Some(PaneId::Terminal(1)));
+    screen.declare_port(9090, None, None);
+
+    screen.undeclare_port(8080, Some(PaneId::Terminal(2)));
+    screen.undeclare_port(8080, None);
+    assert!(
+        screen.declared_ports.contains_key(&8080),
+        "another pane cannot remove the port of a service"
+    );
+    screen.undeclare_port(9090, Some(PaneId::Terminal(1)));
+    assert!(
+        screen.declared_ports.contains_key(&9090),
+        "a pane cannot remove a port declared for no pane"
+    );
+
+    screen.undeclare_port(8080, Some(PaneId::Terminal(1)));
+    screen.undeclare_port(9090, None);
+    assert!(screen.declared_ports.is_empty());
+}
+
+#[test]
+fn mirroring_the_session_gathers_clients_in_one_tab() {
+    let mut screen = create_fixed_size_screen();
+    screen.set_session_is_mirrored(false, 1).expect("TEST");
+    new_tab(&mut screen, 1, 0);
+    new_tab(&mut screen, 2, 1);
+    screen.add_client(2).expect("TEST");
+    screen.go_to_tab(1, 2).expect("TEST");
+    assert_eq!(screen.active_tab_indices.get(&1), Some(&1));
+    assert_eq!(
+        screen.active_tab_indices.get(&2),
+        Some(&0),
+        "clients focus tabs independently"
+    );
+
+    screen.set_session_is_mirrored(true, 2).expect("TEST");
+    assert_eq!(
+        screen.active_tab_indices.get(&1),
+        Some(&0),
+        "client joined the tab of the reconfiguring client"
+    );
+    assert_eq!(screen.active_tab_indices.get(&2), Some(&0));
+}
+
+#[test]
+fn mirroring_the_session_aligns_pane_focus_to_the_reconfiguring_client() {
+    let mut screen = create_fixed_size_screen();
+    screen.set_session_is_mirrored(false, 1).expect("TEST");
+    new_tab(&mut screen, 1, 0);
+    screen.add_client(2).expect("TEST");
+    {
+        let active_tab = screen.get_active_tab_mut(1).unwrap();
+        active_tab
+            .new_pane(PaneId::Terminal(2), None, None, None, None, false, Some(1))
+            .unwrap();
+        active_tab
+            .focus_pane_with_id(PaneId::Terminal(1), false, 1)
+            .unwrap();
+        active_tab
+            .focus_pane_with_id(PaneId::Terminal(2), false, 2)
+            .unwrap();
+    }
+    let active_pane_id = |screen: &Screen, client_id: ClientId| {
+        screen
+            .get_active_tab(client_id)
+            .unwrap()
+            .get_active_pane_id(client_id)
+    };
+    assert_eq!(active_pane_id(&screen, 1), Some(PaneId::Terminal(1)));
+    assert_eq!(
+        active_pane_id(&screen, 2),
+        Some(PaneId::Terminal(2)),
+        "clients focus panes independently"
+    );
+
+    screen.set_session_is_mirrored(true, 2).expect("TEST");
+    assert_eq!(
+        active_pane_id(&screen, 1),
+        Some(PaneId::Terminal(2)),
+        "client follows the pane focus of the reconfiguring client"
+    );
+    assert_eq!(active_pane_id(&screen, 2), Some(PaneId::Terminal(2)));
+}
+
+#[test]
+fn tab_named_after_title_of_focused_pane() {
+    let mut screen = create_fixed_size_screen();
+    screen.tab_name_from_title = true;
+    new_tab(&mut screen, 1, 0);
+    let tab_name = |screen: &Screen| screen.tabs.get(&0).unwrap().name.clone();
+    let set_title = |screen: &mut Screen, title: &str| {
+        screen
+            .get_tabs_mut()
+            .get_mut(&0)
+            .unwrap()
+            .handle_pty_bytes(1, format!("\u{1b}]2;{}\u{7}", title).into_bytes())
+            .expect("TEST");
+        screen
+            .apply_application_title(PaneId::Terminal(1), title.to_owned())
+            .expect("TEST");
+    };
+
+    set_title(&mut screen, "vim");
+    assert_eq!(
+        tab_name(&screen),
+        "vim",
+        "Default tab name replaced by title"
+    );
+
+    assert!(screen.rename_tab_with_name("vim", "mine".to_owned()));
+    set_title(&mut screen, "htop");
+    assert_eq!(tab_name(&screen), "mine", "Name set by the user wins");
+
+    screen.title_precedence = TitlePrecedence::Application;
+    set_title(&mut screen, "htop");
+    assert_eq!(
+        tab_name(&screen),
+        "htop",
+        "Title set by the application wins"
+    );
+}
+
+#[test]
+fn pane_group_spans_tabs_and_can_be_recolored() {
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 0);
+    new_tab(&mut screen, 2, 1);
+    screen
+        .toggle_pane_in_group("build".to_owned(), Some(PaneId::Terminal(1)), 1)
+        .expect("TEST");
+    screen
+        .toggle_pane_in_group("build".to_owned(), None, 1)
+        .expect("TEST");
+
+    let color = zellij_utils::data::PaletteColor::Rgb((255, 0, 0));
+    let message = screen
+        .apply_to_pane_group("build", PaneGroupAction::SetColor(Some(color)))
+        .expect("TEST");
+    assert_eq!(message, "Applied to 2 pane(s) in group 'build'");
+    for tab in screen.tabs.values() {
+        let pane = tab.get_pane_with_id(tab.get_all_pane_ids()[0]).unwrap();
+        assert_eq!(
+            pane.frame_color_override(),
+            Some(color),
+            "Group color applied to panes in all tabs"
+        );
+    }
+
+    screen
+        .toggle_pane_in_group("build".to_owned(), Some(PaneId::Terminal(2)), 1)
+        .expect("TEST");
+    let message = screen
+        .apply_to_pane_group("build", PaneGroupAction::SetColor(None))
+        .expect("TEST");
+    assert_eq!(
+        message, "Applied to 1 pane(s) in group 'build'",
+        "Toggling a pane that is in the group removes it"
+    );
+    assert!(screen
+        .apply_to_pane_group("other", PaneGroupAction::Close)
+        .is_err());
+}
+
+fn screen_with_pty_receiver() -> (Screen, Receiver<(PtyInstruction, ErrorContext)>) {
+    let mut screen = create_fixed_size_screen();
+    let (to_pty, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    screen.bus.senders.to_pty = Some(SenderWithContext::new(to_pty));
+    (screen, pty_receiver)
+}
+
+fn duplicated_pane_instruction(
+    pty_receiver: &Receiver<(PtyInstruction, ErrorContext)>,
+) -> Option<(u32, Option<TerminalAction>, Option<Direction>, ClientId)> {
+    pty_receiver
+        .try_iter()
+        .find_map(|(instruction, _err_ctx)| match instruction {
+            PtyInstruction::DuplicatePane(terminal_id, terminal_action, direction, client_id) => {
+                Some((terminal_id, terminal_action, direction, client_id))
+            },
+            _ => None,
+        })
+}
+
+#[test]
+fn duplicating_a_command_pane_runs_the_command_without_its_per_run_options() {
+    let (mut screen, pty_receiver) = screen_with_pty_receiver();
+    new_tab(&mut screen, 1, 0);
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["test".to_owned()],
+        cwd: Some(PathBuf::from("/tmp")),
+        hold_on_close: true,
+        hold_on_start: true,
+        capture_output: Some(Default::default()),
+        stdin_pipe: Some(Default::default()),
+        log_output: Some(Default::default()),
+        ..Default::default()
+    };
+    screen
+        .get_active_tab_mut(1)
+        .unwrap()
+        .new_pane(
+            PaneId::Terminal(2),
+            None,
+            None,
+            Some(Run::Command(run_command.clone())),
+            None,
+            false,
+            Some(1),
+        )
+        .unwrap();
+    screen
+        .get_active_tab_mut(1)
+        .unwrap()
+        .focus_pane_with_id(PaneId::Terminal(2), false, 1)
+        .unwrap();
+
+    screen
+        .duplicate_pane(Some(Direction::Right), None, 1)
+        .expect("TEST");
+    let (terminal_id, terminal_action, direction, client_id) =
+        duplicated_pane_instruction(&pty_receiver).expect("pane duplicated");
+    assert_eq!(terminal_id, 2, "the focused pane is duplicated");
+    assert_eq!(direction, Some(Direction::Right));
+    assert_eq!(client_id, 1);
+    match terminal_action {
+        Some(TerminalAction::RunCommand(duplicated_command)) => assert_eq!(
+            duplicated_command,
+            RunCommand {
+                hold_on_start: false,
+                capture_output: None,
+                stdin_pipe: None,
+                log_output: None,
+                ..run_command
+            },
+            "the options of the run of the command are cleared"
+        ),
+        terminal_action => panic!("command not duplicated: {:?}", terminal_action),
+    }
+}
+
+#[test]
+fn duplicating_a_shell_pane_opens_the_default_shell() {
+    let (mut screen, pty_receiver) = screen_with_pty_receiver();
+    new_tab(&mut screen, 1, 0);
+    screen.duplicate_pane(None, None, 1).expect("TEST");
+    let (terminal_id, terminal_action, _direction, _client_id) =
+        duplicated_pane_instruction(&pty_receiver).expect("pane duplicated");
+    assert_eq!(terminal_id, 1);
+    assert!(terminal_action.is_none(), "the default shell is opened");
+}
+
+#[test]
+fn only_terminal_panes_can_be_duplicated() {
+    let (mut screen, pty_receiver) = screen_with_pty_receiver();
+    let (to_plugin, _plugin_receiver): ChannelWithContext<PluginInstruction> =
+        channels::unbounded();
+    screen.bus.senders.to_plugin = Some(SenderWithContext::new(to_plugin));
+    new_tab(&mut screen, 1, 0);
+    screen
+        .get_active_tab_mut(1)
+        .unwrap()
+        .new_pane(PaneId::Plugin(1), None, None, None, None, false, Some(1))
+        .unwrap();
+    screen
+        .get_active_tab_mut(1)
+        .unwrap()
+        .focus_pane_with_id(PaneId::Plugin(1), false, 1)
+        .unwrap();
+    assert_eq!(
+        screen.duplicate_pane(None, None, 1),
+        Err("Only terminal panes can be duplicated".to_owned())
+    );
+    assert!(duplicated_pane_instruction(&pty_receiver).is_none());
+}
+
+fn new_tab_instructions(
+    screen_receiver: &Receiver<(ScreenInstruction, ErrorContext)>,
+) -> Vec<(Option<String>, bool)> {
+    screen_receiver
+        .try_iter()
+        .filter_map(|(instruction, _err_ctx)| match instruction {
+            ScreenInstruction::NewTab(
+                _,
+                _,
+                _,
+                _,
+                tab_name,
+                _,
+                should_change_focus_to_new_tab,
+                _,
+            ) => Some((tab_name, should_change_focus_to_new_tab)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn new_tab_layout_name_is_looked_up_in_the_layout_dir_when_the_action_runs() {
+    let layout_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let mut screen = create_fixed_size_screen();
+    screen.layout_dir = Some(layout_dir.path().to_path_buf());
+    let (to_screen, screen_receiver): ChannelWithContext<ScreenInstruction> = channels::unbounded();
+    screen.bus.senders.to_screen = Some(SenderWithContext::new(to_screen));
+    new_tab(&mut screen, 1, 0);
+    // the layout is created only after the screen, as if it were added after the config loaded
+    std::fs::write(
+        layout_dir.path().join("dev.kdl"),
+        r#"
+        layout {
+            tab name="build" {
+                pane
+            }
+            tab name="logs" focus=true {
+                pane
+            }
+        }
+    "#,
+    )
+    .unwrap();
+    screen
+        .new_tabs_from_layout("dev".to_owned(), None, None, None, 1)
+        .unwrap();
+    assert_eq!(
+        new_tab_instructions(&screen_receiver),
+        vec![
+            (Some("build".to_owned()), false),
+            (Some("logs".to_owned()), true)
+        ],
+        "opened the tabs of the layout from the layout dir, focusing the one it focuses"
+    );
+}
+
+#[test]
+fn new_tab_with_unknown_layout_name_reports_an_error_instead_of_opening_a_tab() {
+    let layout_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let mut screen = create_fixed_size_screen();
+    screen.layout_dir = Some(layout_dir.path().to_path_buf());
+    let (to_screen, screen_receiver): ChannelWithContext<ScreenInstruction> = channels::unbounded();
+    screen.bus.senders.to_screen = Some(SenderWithContext::new(to_screen));
+    let (to_server, server_receiver): ChannelWithContext<ServerInstruction> = channels::unbounded();
+    screen.bus.senders.to_server = Some(SenderWithContext::new(to_server));
+    new_tab(&mut screen, 1, 0);
+    screen
+        .new_tabs_from_layout("no-such-layout".to_owned(), None, None, None, 1)
+        .unwrap();
+    assert!(
+        new_tab_instructions(&screen_receiver).is_empty(),
+        "no tab opened"
+    );
+    let logged_error = server_receiver
+        .try_iter()
+        .find_map(|(instruction, _err_ctx)| match instruction {
+            ServerInstruction::LogError(lines, client_id) => Some((lines, client_id)),
+            _ => None,
+        });
+    match logged_error {
+        Some((lines, client_id)) => {
+            assert_eq!(
+                client_id, 1,
+                "error shown to the client that ran the action"
+            );
+            assert!(
+                lines[0].starts_with("Failed to load layout no-such-layout"),
+                "error names the layout: {:?}",
+                lines
+            );
+        },
+        None => panic!("no error reported for the unknown layout"),
+    }
+}
+
+fn screen_with_pty_writer_receiver() -> (Screen, Receiver<(PtyWriteInstruction, ErrorContext)>) {
+    let mut screen = create_fixed_size_screen();
+    let (to_pty_writer, pty_writer_receiver): ChannelWithContext<PtyWriteInstruction> =
+        channels::unbounded();
+    screen.bus.senders.to_pty_writer = Some(SenderWithContext::new(to_pty_writer));
+    (screen, pty_writer_receiver)
+}
+
+fn terminals_written_to(
+    pty_writer_receiver: &Receiver<(PtyWriteInstruction, ErrorContext)>,
+    expected_bytes: &str,
+) -> Vec<u32> {
+    let mut terminal_ids: Vec<u32> = pty_writer_receiver
+        .try_iter()
+        .filter_map(|(instruction, _err_ctx)| match instruction {
+            PtyWriteInstruction::Write(bytes, terminal_id) => {
+                assert_eq!(bytes, expected_bytes.as_bytes(), "bytes written as is");
+                Some(terminal_id)
+            },
+            _ => None,
+        })
+        .collect();
+    terminal_ids.sort();
+    terminal_ids
+}
+
+#[test]
+fn multi_exec_writes_to_every_terminal_pane_in_the_tab() {
+    let (mut screen, pty_writer_receiver) = screen_with_pty_writer_receiver();
+    new_tab(&mut screen, 1, 0);
+    {
+        let active_tab = screen.get_active_tab_mut(1).unwrap();
+        for pid in [2, 3] {
+            active_tab
+                .new_pane(
+                    PaneId::Terminal(pid),
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    Some(1),
+                )
+                .unwrap();
+        }
+        active_tab
+            .focus_pane_with_id(PaneId::Terminal(1), false, 1)
+            .unwrap();
+    }
+    // only the writes of multi_exec are of interest
+    pty_writer_receiver.try_iter().for_each(drop);
+
+    let message = screen
+        .multi_exec("make\n".to_owned(), None, false, 1)
+        .expect("TEST");
+    assert_eq!(message, "Sent to 3 pane(s)");
+    assert_eq!(
+        terminals_written_to(&pty_writer_receiver, "make\n"),
+        vec![1, 2, 3],
+        "written to every pane in the tab"
+    );
+
+    let message = screen
+        .multi_exec("make\n".to_owned(), None, true, 1)
+        .expect("TEST");
+    assert_eq!(message, "Sent to 2 pane(s)");
+    assert_eq!(
+        terminals_written_to(&pty_writer_receiver, "make\n"),
+        vec![2, 3],
+        "focused pane excluded"
+    );
+}
+
+#[test]
+fn multi_exec_writes_to_the_other_panes_when_one_fails() {
+    let (mut screen, pty_writer_receiver) = screen_with_pty_writer_receiver();
+    new_tab(&mut screen, 1, 0);
+    {
+        let active_tab = screen.get_active_tab_mut(1).unwrap();
+        for pid in [2, 3] {
+            active_tab
+                .new_pane(
+                    PaneId::Terminal(pid),
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    Some(1),
+                )
+                .unwrap();
+        }
+        active_tab.senders.should_silently_fail = false;
+    }
+    // every write fails without a pty writer to take it
+    drop(pty_writer_receiver);
+
+    let error = screen
+        .multi_exec("make\n".to_owned(), None, false, 1)
+        .unwrap_err();
+    assert_eq!(
+        error, "Sent to 0 pane(s), failed to write to terminal_1, terminal_2, terminal_3",
+        "every pane was tried"
+    );
+}
+
+#[test]
+fn multi_exec_writes_to_every_pane_in_the_group() {
+    let (mut screen, pty_writer_receiver) = screen_with_pty_writer_receiver();
+    new_tab(&mut screen, 1, 0);
+    new_tab(&mut screen, 2, 1);
+    {
+        let active_tab = screen.get_active_tab_mut(1).unwrap();
+        active_tab
+            .new_pane(PaneId::Terminal(3), None, None, None, None, false, Some(1))
+            .unwrap();
+        active_tab
+            .focus_pane_with_id(PaneId::Terminal(2), false, 1)
+            .unwrap();
+    }
+    for pid in [1, 2] {
+        screen
+            .toggle_pane_in_group("build".to_owned(), Some(PaneId::Terminal(pid)), 1)
+            .expect("TEST");
+    }
+    // only the writes of multi_exec are of interest
+    pty_writer_receiver.try_iter().for_each(drop);
+
+    let message = screen
+        .multi_exec("make\n".to_owned(), Some("build".to_owned()), false, 1)
+        .expect("TEST");
+    assert_eq!(message, "Sent to 2 pane(s)");
+    assert_eq!(
+        terminals_written_to(&pty_writer_receiver, "make\n"),
+        vec![1, 2],
+        "written to the panes of the group in all tabs, and not to the others"
+    );
+
+    let message = screen
+        .multi_exec("make\n".to_owned(), Some("build".to_owned()), true, 1)
+        .expect("TEST");
+    assert_eq!(message, "Sent to 1 pane(s)");
+    assert_eq!(
+        terminals_written_to(&pty_writer_receiver, "make\n"),
+        vec![1],
+        "focused pane excluded"
+    );
+
+    assert!(screen
+        .multi_exec("make\n".to_owned(), Some("other".to_owned()), false, 1)
+        .is_err());
+}
+
+#[test]
+fn move_focused_pane_to_tab() {
     let mut screen = create_fixed_size_screen();
     new_tab(&mut screen, 1, 0);
     new_tab(&mut screen, 2, 1);
-    new_tab(&mut screen, 3, 2);
-    assert_eq!(screen.get_active_tab(1).unwrap().position, 2);
+    assert_eq!(
+        screen.tab_position_for_target(&TabTarget::Name("Tab #1".to_owned())),
+        Some(0)
+    );
+    assert_eq!(
+        screen.tab_position_for_target(&TabTarget::Index(2)),
+        Some(1)
+    );
+    assert_eq!(screen.tab_position_for_target(&TabTarget::Index(3)), None);
 
-    screen.move_active_tab_to_left(1).expect("TEST");
-    screen.move_active_tab_to_left(1).expect("TEST");
+    screen.move_pane_to_tab(None, 0, 1).expect("TEST");
 
+    let first_tab = screen.tabs.values().find(|t| t.position == 0).unwrap();
+    assert!(
+        first_tab.get_all_pane_ids().contains(&PaneId::Terminal(2)),
+        "Focused pane moved to the first tab"
+    );
     assert_eq!(
         screen.get_active_tab(1).unwrap().position,
-        0,
-        "Active tab moved to left twice"
+        1,
+        "Focused tab did not change"
     );
 }
 
 #[test]
-fn wrapping_move_of_active_tab_to_left() {
+fn global_floating_pane_follows_the_focused_tab() {
     let mut screen = create_fixed_size_screen();
     new_tab(&mut screen, 1, 0);
-    new_tab(&mut screen, 2, 1);
-    new_tab(&mut screen, 3, 2);
-    screen.move_focus_left_or_previous_tab(1).expect("TEST");
-    screen.move_focus_left_or_previous_tab(1).expect("TEST");
-    assert_eq!(screen.get_active_tab(1).unwrap().position, 0);
+    let should_float = Some(true);
+    screen
+        .get_active_tab_mut(1)
+        .unwrap()
+        .new_pane(
+            PaneId::Terminal(2),
+            None,
+            should_float,
+            None,
+            None,
+            false,
+            Some(1),
+        )
+        .unwrap();
+    screen.toggle_floating_pane_scope(1).expect("TEST");
+    let floating_panes = |screen: &Screen, tab_index: usize| -> Vec<(PaneId, PaneGeom)> {
+        screen
+            .tabs
+            .get(&tab_index)
+            .unwrap()
+            .get_floating_panes()
+            .map(|(pane_id, pane)| (*pane_id, pane.position_and_size()))
+            .collect()
+    };
+    let floating_panes_before_switch = floating_panes(&screen, 0);
 
-    screen.move_active_tab_to_left(1).expect("TEST");
+    new_tab(&mut screen, 3, 1);
+    assert_eq!(
+        floating_panes(&screen, 1),
+        floating_panes_before_switch,
+        "Floating pane moved to the new tab in the same position"
+    );
+    assert!(floating_panes(&screen, 0).is_empty());
 
+    screen.go_to_tab(1, 1).expect("TEST");
     assert_eq!(
-        screen.get_active_tab(1).unwrap().position,
-        2,
-        "Active tab moved to left until wrapped around"
+        floating_panes(&screen, 0),
+        floating_panes_before_switch,
+        "Floating pane followed the client back"
     );
+    assert!(floating_panes(&screen, 1).is_empty());
 }
 
 #[test]
@@ -1207,6 +2836,47 @@ fn switch_to_tab_with_fullscreen() {
     );
 }
 
+#[test]
+fn fullscreen_is_kept_across_reattach_and_serialized() {
+    use zellij_utils::session_serialization::GlobalLayoutManifest;
+    let mut screen = create_fixed_size_screen();
+    new_tab(&mut screen, 1, 1);
+    {
+        let active_tab = screen.get_active_tab_mut(1).unwrap();
+        active_tab
+            .new_pane(PaneId::Terminal(2), None, None, None, None, false, Some(1))
+            .unwrap();
+        active_tab.toggle_active_pane_fullscreen(1);
+    }
+
+    screen.add_client(2).expect("TEST");
+    screen.remove_client(1).expect("TEST");
+    screen.remove_client(2).expect("TEST");
+    screen.add_client(3).expect("TEST");
+    let active_tab = screen.get_active_tab(3).unwrap();
+    assert!(
+        active_tab.is_fullscreen_active(),
+        "Zoom survives reattaching"
+    );
+    assert_eq!(
+        active_tab.get_active_pane_id(3),
+        Some(PaneId::Terminal(2)),
+        "The reattached client sees the fullscreen pane"
+    );
+
+    let manifest: GlobalLayoutManifest = screen.get_layout_metadata(None).into();
+    let fullscreen_panes = manifest.tabs[0]
+        .1
+        .tiled_panes
+        .iter()
+        .filter(|pane| pane.is_fullscreen)
+        .count();
+    assert_eq!(
+        fullscreen_panes, 1,
+        "Zoom is part of the serialized session"
+    );
+}
+
 #[test]
 fn update_screen_pixel_dimensions() {
     let size = Size {
@@ -1438,6 +3108,70 @@ pub fn send_cli_write_action_to_screen() {
     assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
 }
 
+#[test]
+pub fn send_cli_write_raw_hex_action_to_screen() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let pty_writer_receiver = mock_screen.pty_writer_receiver.take().unwrap();
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_pty_instructions = Arc::new(Mutex::new(vec![]));
+    let pty_writer_thread = log_actions_in_thread!(
+        received_pty_instructions,
+        PtyWriteInstruction::Exit,
+        pty_writer_receiver
+    );
+    let cli_action = CliAction::WriteRaw {
+        data: "1b 5b 41".into(),
+        base64: false,
+        pane_id: None,
+    };
+    send_cli_action_to_server(&session_metadata, cli_action, client_id);
+    std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
+    mock_screen.teardown(vec![pty_writer_thread, screen_thread]);
+    assert!(
+        format!("{:?}", *received_pty_instructions.lock().unwrap())
+            .contains("Write([27, 91, 65], 0)"),
+        "decoded bytes written to the focused pane"
+    );
+}
+
+#[test]
+pub fn send_cli_write_raw_base64_action_to_pane_id() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let pty_writer_receiver = mock_screen.pty_writer_receiver.take().unwrap();
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_pty_instructions = Arc::new(Mutex::new(vec![]));
+    let pty_writer_thread = log_actions_in_thread!(
+        received_pty_instructions,
+        PtyWriteInstruction::Exit,
+        pty_writer_receiver
+    );
+    let cli_action = CliAction::WriteRaw {
+        data: "G1tB".into(),
+        base64: true,
+        pane_id: Some("terminal_0".into()),
+    };
+    send_cli_action_to_server(&session_metadata, cli_action, client_id);
+    std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
+    mock_screen.teardown(vec![pty_writer_thread, screen_thread]);
+    assert!(
+        format!("{:?}", *received_pty_instructions.lock().unwrap())
+            .contains("Write([27, 91, 65], 0)"),
+        "decoded bytes written to the requested pane"
+    );
+}
+
 #[test]
 pub fn send_cli_resize_action_to_screen() {
     let size = Size { cols: 80, rows: 20 };
@@ -1662,6 +3396,7 @@ pub fn send_cli_dump_screen_action() {
     let cli_action = CliAction::DumpScreen {
         path: PathBuf::from("/tmp/foo"),
         full: true,
+        ansi: false,
     };
     let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
         0,
@@ -1676,6 +3411,154 @@ pub fn send_cli_dump_screen_action() {
     ));
 }
 
+#[test]
+pub fn captured_pane_output_is_streamed_to_the_capturing_client() {
+    let size = Size { cols: 80, rows: 20 };
+    let capturing_client_id = 10;
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::CapturePaneOutput(
+            0,
+            capturing_client_id,
+            None,
+        ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+        0,
+        "output of the command".as_bytes().to_vec(),
+    ));
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::CapturedPaneExited(0, Some(3)));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let received_server_instructions = received_server_instructions.lock().unwrap();
+    let captured_output: Vec<&Vec<u8>> = received_server_instructions
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::CapturedPaneOutput(client_id, output) => {
+                assert_eq!(
+                    *client_id, capturing_client_id,
+                    "sent to the capturing client"
+                );
+                Some(output)
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        captured_output,
+        vec![&"output of the command".as_bytes().to_vec()],
+        "output streamed to the capturing client"
+    );
+    assert!(
+        received_server_instructions
+            .iter()
+            .any(|instruction| matches!(
+                instruction,
+                ServerInstruction::CapturedPaneExited(10, Some(3))
+            )),
+        "exit status sent to the capturing client"
+    );
+}
+
+#[test]
+pub fn captured_pane_output_tail_is_sent_when_command_exits() {
+    let size = Size { cols: 80, rows: 20 };
+    let capturing_client_id = 10;
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::CapturePaneOutput(
+            0,
+            capturing_client_id,
+            Some(2),
+        ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+        0,
+        "line 1\n\rline 2\n\rline 3\n\r".as_bytes().to_vec(),
+    ));
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::CapturedPaneExited(0, Some(0)));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let received_server_instructions = received_server_instructions.lock().unwrap();
+    let captured_output: Vec<String> = received_server_instructions
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::CapturedPaneOutput(_client_id, output) => {
+                Some(String::from_utf8_lossy(output).to_string())
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        captured_output,
+        vec!["line 2\nline 3\n".to_owned()],
+        "only the last lines sent once the command exited"
+    );
+}
+
+#[test]
+pub fn bells_are_muted_while_do_not_disturb_is_on() {
+    let size = Size { cols: 80, rows: 20 };
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::PtyBytes(0, vec![7]));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::Render);
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::ToggleDoNotDisturb);
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::PtyBytes(0, vec![7]));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::Render);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    // the bell is rendered as raw vte output after moving the cursor to the pane
+    let rendered_bell_count = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|instruction| match instruction {
+            ServerInstruction::Render(Some(output)) => output
+                .values()
+                .any(|client_output| client_output.contains("H\u{1b}[m\u{7}")),
+            _ => false,
+        })
+        .count();
+    assert_eq!(
+        rendered_bell_count, 1,
+        "only the bell rung before do not disturb was toggled on was rendered"
+    );
+}
+
 #[test]
 pub fn send_cli_edit_scrollback_action() {
     let size = Size { cols: 80, rows: 20 };
@@ -2116,7 +3999,7 @@ pub fn send_cli_toggle_full_screen_action() {
         ServerInstruction::KillSession,
         server_receiver
     );
-    let toggle_full_screen_action = CliAction::ToggleFullscreen;
+    let toggle_full_screen_action = CliAction::ToggleFullscreen { pane_id: None };
     send_cli_action_to_server(&session_metadata, toggle_full_screen_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100));
     mock_screen.teardown(vec![server_instruction, screen_thread]);
@@ -2163,6 +4046,46 @@ pub fn send_cli_toggle_pane_frames_action() {
     assert_snapshot!(format!("{}", snapshot_count));
 }
 
+#[test]
+pub fn send_cli_set_ui_pane_frames_action() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let last_snapshot_after = |cli_action: CliAction| {
+        let mut initial_layout = TiledPaneLayout::default();
+        initial_layout.children_split_direction = SplitDirection::Vertical;
+        initial_layout.children = vec![TiledPaneLayout::default(), TiledPaneLayout::default()];
+        let mut mock_screen = MockScreen::new(size);
+        let session_metadata = mock_screen.clone_session_metadata();
+        let screen_thread = mock_screen.run(Some(initial_layout), vec![]);
+        let received_server_instructions = Arc::new(Mutex::new(vec![]));
+        let server_receiver = mock_screen.server_receiver.take().unwrap();
+        let server_instruction = log_actions_in_thread!(
+            received_server_instructions,
+            ServerInstruction::KillSession,
+            server_receiver
+        );
+        send_cli_action_to_server(&session_metadata, cli_action, client_id);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        mock_screen.teardown(vec![server_instruction, screen_thread]);
+        let snapshots = take_snapshots_and_cursor_coordinates_from_render_events(
+            received_server_instructions.lock().unwrap().iter(),
+            size,
+        );
+        snapshots
+            .last()
+            .map(|(_cursor_coordinates, snapshot)| snapshot.clone())
+    };
+    let after_set_ui = last_snapshot_after(CliAction::SetUi {
+        settings: vec!["pane-frames=off".to_owned()],
+    });
+    let after_toggle = last_snapshot_after(CliAction::TogglePaneFrames);
+    assert!(after_set_ui.is_some(), "screen rendered after set-ui");
+    assert_eq!(
+        after_set_ui, after_toggle,
+        "set-ui pane-frames=off hides pane frames"
+    );
+}
+
 #[test]
 pub fn send_cli_toggle_active_tab_sync_action() {
     let size = Size {
@@ -2227,7 +4150,10 @@ pub fn send_cli_new_pane_action_with_default_parameters() {
         in_place: false,
         name: None,
         close_on_exit: false,
+        close_on_success: false,
+        keep_open: false,
         start_suspended: false,
+        start_immediately: false,
         configuration: None,
         skip_plugin_cache: false,
         x: None,
@@ -2235,6 +4161,16 @@ pub fn send_cli_new_pane_action_with_default_parameters() {
         width: None,
         height: None,
         pinned: None,
+        capture_output: false,
+        tail: None,
+        stdin_file: None,
+        stdin: false,
+        log_output: None,
+        log_max_size: None,
+        log_max_files: None,
+        log_strip_ansi: false,
+        watch: vec![],
+        watch_debounce: None,
     };
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -2271,7 +4207,10 @@ pub fn send_cli_new_pane_action_with_split_direction() {
         in_place: false,
         name: None,
         close_on_exit: false,
+        close_on_success: false,
+        keep_open: false,
         start_suspended: false,
+        start_immediately: false,
         configuration: None,
         skip_plugin_cache: false,
         x: None,
@@ -2279,6 +4218,16 @@ pub fn send_cli_new_pane_action_with_split_direction() {
         width: None,
         height: None,
         pinned: None,
+        capture_output: false,
+        tail: None,
+        stdin_file: None,
+        stdin: false,
+        log_output: None,
+        log_max_size: None,
+        log_max_files: None,
+        log_strip_ansi: false,
+        watch: vec![],
+        watch_debounce: None,
     };
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -2315,7 +4264,10 @@ pub fn send_cli_new_pane_action_with_command_and_cwd() {
         in_place: false,
         name: None,
         close_on_exit: false,
+        close_on_success: false,
+        keep_open: false,
         start_suspended: false,
+        start_immediately: false,
         configuration: None,
         skip_plugin_cache: false,
         x: None,
@@ -2323,6 +4275,16 @@ pub fn send_cli_new_pane_action_with_command_and_cwd() {
         width: None,
         height: None,
         pinned: None,
+        capture_output: false,
+        tail: None,
+        stdin_file: None,
+        stdin: false,
+        log_output: None,
+        log_max_size: None,
+        log_max_files: None,
+        log_strip_ansi: false,
+        watch: vec![],
+        watch_debounce: None,
     };
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -2370,7 +4332,10 @@ pub fn send_cli_new_pane_action_with_floating_pane_and_coordinates() {
         in_place: false,
         name: None,
         close_on_exit: false,
+        close_on_success: false,
+        keep_open: false,
         start_suspended: false,
+        start_immediately: false,
         configuration: None,
         skip_plugin_cache: false,
         x: Some("10".to_owned()),
@@ -2378,6 +4343,16 @@ pub fn send_cli_new_pane_action_with_floating_pane_and_coordinates() {
         width: Some("20%".to_owned()),
         height: None,
         pinned: None,
+        capture_output: false,
+        tail: None,
+        stdin_file: None,
+        stdin: false,
+        log_output: None,
+        log_max_size: None,
+        log_max_files: None,
+        log_strip_ansi: false,
+        watch: vec![],
+        watch_debounce: None,
     };
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -2642,7 +4617,7 @@ pub fn send_cli_close_pane_action() {
         ServerInstruction::KillSession,
         server_receiver
     );
-    let close_pane_action = CliAction::ClosePane;
+    let close_pane_action = CliAction::ClosePane { pane_id: None };
     send_cli_action_to_server(&session_metadata, close_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100));
     mock_screen.teardown(vec![server_instruction, screen_thread]);
@@ -2678,7 +4653,9 @@ pub fn send_cli_new_tab_action_default_params() {
         name: None,
         layout: None,
         layout_dir: None,
+        template: None,
         cwd: None,
+        vars: vec![],
     };
     send_cli_action_to_server(&session_metadata, new_tab_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100));
@@ -2718,7 +4695,9 @@ pub fn send_cli_new_tab_action_with_name_and_layout() {
             env!("CARGO_MANIFEST_DIR")
         ))),
         layout_dir: None,
+        template: None,
         cwd: None,
+        vars: vec![],
     };
     send_cli_action_to_server(&session_metadata, new_tab_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100));
@@ -2869,7 +4848,7 @@ pub fn send_cli_close_tab_action() {
         ServerInstruction::KillSession,
         server_receiver
     );
-    let close_tab = CliAction::CloseTab;
+    let close_tab = CliAction::CloseTab { tab_name: None };
     send_cli_action_to_server(&session_metadata, close_tab, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100));
     mock_screen.teardown(vec![server_thread, screen_thread]);
@@ -2907,6 +4886,7 @@ pub fn send_cli_rename_tab() {
     );
     let rename_tab = CliAction::RenameTab {
         name: "new-tab-name".into(),
+        tab_name: None,
     };
     send_cli_action_to_server(&session_metadata, rename_tab, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100));
@@ -2952,6 +4932,7 @@ pub fn send_cli_undo_rename_tab() {
     );
     let rename_tab = CliAction::RenameTab {
         name: "new-tab-name".into(),
+        tab_name: None,
     };
     let undo_rename_tab = CliAction::UndoRenameTab;
     // first rename the tab
@@ -3733,3 +5714,55 @@ pub fn send_cli_change_floating_pane_coordinates_action() {
     }
     assert_snapshot!(format!("{}", snapshot_count));
 }
+
+#[test]
+pub fn tab_and_pane_updates_are_only_published_while_session_events_have_listeners() {
+    let size = Size { cols: 80, rows: 20 };
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::RequestStateUpdateForPlugins);
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::PublishSessionEvents(true));
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::RequestStateUpdateForPlugins);
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::PublishSessionEvents(false));
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::RequestStateUpdateForPlugins);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let published_updates = |is_update: fn(&Event) -> bool| {
+        received_server_instructions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|instruction| {
+                matches!(instruction, ServerInstruction::PublishEvent(event) if is_update(event))
+            })
+            .count()
+    };
+    // once when the listeners appear and once for the state update while they are there
+    assert_eq!(
+        published_updates(|e| matches!(e, Event::TabUpdate(..))),
+        2,
+        "tab updates published only while there were listeners"
+    );
+    assert_eq!(
+        published_updates(|e| matches!(e, Event::PaneUpdate(..))),
+        2,
+        "pane updates published only while there were listeners"
+    );
+}
@@ -0,0 +1,81 @@
+use super::*;
+
+fn clients_metadata() -> BTreeMap<ClientId, ClientMetadata> {
+    let mut clients_metadata = BTreeMap::new();
+    clients_metadata.insert(
+        1,
+        ClientMetadata {
+            pane_id: PaneId::Terminal(2),
+            command: None,
+            tab_position: 0,
+            tab_name: Some("Tab #1".to_owned()),
+        },
+    );
+    clients_metadata.insert(
+        2,
+        ClientMetadata {
+            pane_id: PaneId::Plugin(1),
+            command: None,
+            tab_position: 1,
+            tab_name: None,
+        },
+    );
+    clients_metadata
+}
+
+#[test]
+fn clients_are_listed_with_their_tab_and_terminal_size() {
+    let mut client_sizes = HashMap::new();
+    client_sizes.insert(1, Size { rows: 24, cols: 80 });
+    let read_only_clients = HashSet::from([2]);
+    let rendered =
+        ClientMetadata::render_many(clients_metadata(), &client_sizes, &read_only_clients, &None);
+    let lines: Vec<&str> = rendered.lines().map(|l| l.trim_end()).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "CLIENT_ID ZELLIJ_PANE_ID TAB_INDEX TERMINAL_SIZE ROLE   RUNNING_COMMAND",
+            "1         terminal_2     1         80x24         editor N/A",
+            "2         plugin_1       2         N/A           viewer N/A",
+        ],
+        "clients rendered as text"
+    );
+}
+
+#[test]
+fn clients_are_listed_as_json() {
+    let mut client_sizes = HashMap::new();
+    client_sizes.insert(1, Size { rows: 24, cols: 80 });
+    let read_only_clients = HashSet::from([2]);
+    let rendered = ClientMetadata::render_many_as_json(
+        clients_metadata(),
+        &client_sizes,
+        &read_only_clients,
+        &None,
+    );
+    let clients: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+    assert_eq!(
+        clients,
+        serde_json::json!([
+            {
+                "client_id": 1,
+                "pane_id": "terminal_2",
+                "tab_index": 1,
+                "tab_name": "Tab #1",
+                "terminal_size": { "rows": 24, "cols": 80 },
+                "running_command": "N/A",
+                "read_only": false,
+            },
+            {
+                "client_id": 2,
+                "pane_id": "plugin_1",
+                "tab_index": 2,
+                "tab_name": null,
+                "terminal_size": null,
+                "running_command": "N/A",
+                "read_only": true,
+            },
+        ]),
+        "clients rendered as json"
+    );
+}
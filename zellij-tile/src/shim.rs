@@ -8,7 +8,7 @@ use zellij_utils::data::*;
 use zellij_utils::errors::prelude::*;
 use zellij_utils::input::actions::Action;
 pub use zellij_utils::plugin_api;
-use zellij_utils::plugin_api::plugin_command::ProtobufPluginCommand;
+use zellij_utils::plugin_api::plugin_command::{ProtobufPluginCommand, ProtobufWatchPathsResult};
 use zellij_utils::plugin_api::plugin_ids::{ProtobufPluginIds, ProtobufZellijVersion};
 
 pub use super::ui_components::*;
@@ -306,6 +306,24 @@ pub fn set_timeout(secs: f64) {
     unsafe { host_run_plugin_command() };
 }
 
+/// Set a named timer in seconds (or fractions thereof) after which the plugins [update](./plugin-api-events#update) method will be called with the [`NamedTimer`](./plugin-api-events.md#namedtimer) event carrying its name.
+/// A repeating timer keeps firing every `secs` seconds until it is cancelled. Setting a timer with
+/// the name of one that is already set replaces it.
+pub fn set_timer(name: &str, secs: f64, repeat: bool) {
+    let plugin_command = PluginCommand::SetTimer(name.to_owned(), secs, repeat);
+    let protobuf_plugin_command: ProtobufPluginCommand = plugin_command.try_into().unwrap();
+    object_to_stdout(&protobuf_plugin_command.encode_to_vec());
+    unsafe { host_run_plugin_command() };
+}
+
+/// Cancel the named timer set with [`set_timer`], if it did not fire yet (or repeats)
+pub fn cancel_timer(name: &str) {
+    let plugin_command = PluginCommand::CancelTimer(name.to_owned());
+    let protobuf_plugin_command: ProtobufPluginCommand = plugin_command.try_into().unwrap();
+    object_to_stdout(&protobuf_plugin_command.encode_to_vec());
+    unsafe { host_run_plugin_command() };
+}
+
 #[doc(hidden)]
 pub fn exec_cmd(cmd: &[&str]) {
     let plugin_command =
@@ -923,6 +941,21 @@ pub fn pipe_message_to_plugin(message_to_plugin: MessageToPlugin) {
     unsafe { host_run_plugin_command() };
 }
 
+/// Reply to a message received from another plugin, the reply will carry the same name and will
+/// be marked as `in_reply_to` the `request_id` of the original message (if it had one). Messages
+/// that did not come from a plugin are ignored.
+pub fn reply_to_plugin_message(pipe_message: &PipeMessage, payload: impl Into<String>) {
+    if let PipeSource::Plugin(source_plugin_id) = pipe_message.source {
+        let mut reply = MessageToPlugin::new(&pipe_message.name)
+            .with_destination_plugin_id(source_plugin_id)
+            .with_payload(payload);
+        if let Some(request_id) = &pipe_message.request_id {
+            reply = reply.in_reply_to(request_id);
+        }
+        pipe_message_to_plugin(reply);
+    }
+}
+
 /// Disconnect all other clients from the current session
 pub fn disconnect_other_clients() {
     let plugin_command = PluginCommand::DisconnectOtherClients;
@@ -961,6 +994,26 @@ pub fn watch_filesystem() {
     unsafe { host_run_plugin_command() };
 }
 
+/// Watch paths or glob patterns (eg. `/host/src/**/*.rs`) in the host folder for changes, being
+/// notified of them through the `FileSystemCreate`, `FileSystemUpdate` and `FileSystemDelete`
+/// Events (if subscribed to them). Unlike [`watch_filesystem`], only the changes to these paths
+/// are sent and only to this plugin. Each call replaces the previously watched paths, an empty
+/// list stops watching. Paths that do not exist yet are watched for being created. Returns an
+/// error (and watches none of the paths) if one of them is outside of the host folder.
+pub fn watch_paths<P: AsRef<Path>>(paths: &[P]) -> Result<(), String> {
+    let plugin_command =
+        PluginCommand::WatchPaths(paths.iter().map(|p| p.as_ref().to_path_buf()).collect());
+    let protobuf_plugin_command: ProtobufPluginCommand = plugin_command.try_into().unwrap();
+    object_to_stdout(&protobuf_plugin_command.encode_to_vec());
+    unsafe { host_run_plugin_command() };
+    let protobuf_watch_paths_result =
+        ProtobufWatchPathsResult::decode(bytes_from_stdin().unwrap().as_slice()).unwrap();
+    match protobuf_watch_paths_result.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
 /// Get the serialized session layout in KDL format as a CustomMessage Event
 pub fn dump_session_layout() {
     let plugin_command = PluginCommand::DumpSessionLayout;
@@ -11,7 +11,7 @@ pub struct Event {
     pub name: i32,
     #[prost(
         oneof = "event::Payload",
-        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26"
+        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28"
     )]
     pub payload: ::core::option::Option<event::Payload>,
 }
@@ -70,10 +70,32 @@ pub mod event {
         FailedToChangeHostFolderPayload(super::FailedToChangeHostFolderPayload),
         #[prost(message, tag = "26")]
         PastedTextPayload(super::PastedTextPayload),
+        #[prost(message, tag = "27")]
+        CommandPaneFinishedPayload(super::CommandPaneFinishedPayload),
+        #[prost(message, tag = "28")]
+        NamedTimerPayload(super::NamedTimerPayload),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NamedTimerPayload {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(double, tag = "2")]
+    pub elapsed: f64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommandPaneFinishedPayload {
+    #[prost(uint32, tag = "1")]
+    pub terminal_pane_id: u32,
+    #[prost(int32, optional, tag = "2")]
+    pub exit_code: ::core::option::Option<i32>,
+    #[prost(uint64, tag = "3")]
+    pub runtime_ms: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PastedTextPayload {
     #[prost(string, tag = "1")]
     pub pasted_text: ::prost::alloc::string::String,
@@ -318,6 +340,10 @@ pub struct SessionManifest {
     pub plugins: ::prost::alloc::vec::Vec<PluginInfo>,
     #[prost(message, repeated, tag = "8")]
     pub tab_history: ::prost::alloc::vec::Vec<ClientTabHistory>,
+    #[prost(string, repeated, tag = "9")]
+    pub tags: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "10")]
+    pub note: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -400,6 +426,8 @@ pub struct PaneInfo {
     pub plugin_url: ::core::option::Option<::prost::alloc::string::String>,
     #[prost(bool, tag = "22")]
     pub is_selectable: bool,
+    #[prost(string, optional, tag = "23")]
+    pub cwd: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -436,6 +464,16 @@ pub struct TabInfo {
     pub selectable_tiled_panes_count: u32,
     #[prost(uint32, tag = "16")]
     pub selectable_floating_panes_count: u32,
+    #[prost(bool, tag = "17")]
+    pub has_activity_alert: bool,
+    #[prost(bool, tag = "18")]
+    pub has_silence_alert: bool,
+    #[prost(bool, tag = "19")]
+    pub has_bell_alert: bool,
+    #[prost(bool, tag = "20")]
+    pub has_command_exit_alert: bool,
+    #[prost(bool, tag = "21")]
+    pub has_input_locked_pane: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -456,6 +494,10 @@ pub struct ModeUpdatePayload {
     pub editor: ::core::option::Option<::prost::alloc::string::String>,
     #[prost(string, optional, tag = "8")]
     pub shell: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "9")]
+    pub installed_version: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "10")]
+    pub latest_release: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -522,6 +564,9 @@ pub enum EventType {
     FailedToChangeHostFolder = 28,
     PastedText = 29,
     ConfigWasWrittenToDisk = 30,
+    CommandPaneFinished = 31,
+    /// A timer expired set by the `set_timer` method exported by `zellij-tile`.
+    NamedTimer = 32,
 }
 impl EventType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -561,6 +606,8 @@ impl EventType {
             EventType::FailedToChangeHostFolder => "FailedToChangeHostFolder",
             EventType::PastedText => "PastedText",
             EventType::ConfigWasWrittenToDisk => "ConfigWasWrittenToDisk",
+            EventType::CommandPaneFinished => "CommandPaneFinished",
+            EventType::NamedTimer => "NamedTimer",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -597,6 +644,8 @@ impl EventType {
             "FailedToChangeHostFolder" => Some(Self::FailedToChangeHostFolder),
             "PastedText" => Some(Self::PastedText),
             "ConfigWasWrittenToDisk" => Some(Self::ConfigWasWrittenToDisk),
+            "CommandPaneFinished" => Some(Self::CommandPaneFinished),
+            "NamedTimer" => Some(Self::NamedTimer),
             _ => None,
         }
     }
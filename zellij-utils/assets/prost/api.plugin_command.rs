@@ -5,7 +5,7 @@ pub struct PluginCommand {
     pub name: i32,
     #[prost(
         oneof = "plugin_command::Payload",
-        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101"
+        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104"
     )]
     pub payload: ::core::option::Option<plugin_command::Payload>,
 }
@@ -208,10 +208,38 @@ pub mod plugin_command {
         OpenFileFloatingNearPluginPayload(super::OpenFileFloatingNearPluginPayload),
         #[prost(message, tag = "101")]
         OpenFileInPlaceOfPluginPayload(super::OpenFileInPlaceOfPluginPayload),
+        #[prost(message, tag = "102")]
+        WatchPathsPayload(super::WatchPathsPayload),
+        #[prost(message, tag = "103")]
+        SetTimerPayload(super::SetTimerPayload),
+        #[prost(string, tag = "104")]
+        CancelTimerPayload(::prost::alloc::string::String),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetTimerPayload {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(double, tag = "2")]
+    pub seconds: f64,
+    #[prost(bool, tag = "3")]
+    pub repeat: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchPathsPayload {
+    #[prost(string, repeated, tag = "1")]
+    pub paths: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchPathsResult {
+    #[prost(string, optional, tag = "1")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpenFileInPlaceOfPluginPayload {
     #[prost(message, optional, tag = "1")]
     pub file_to_open: ::core::option::Option<super::file::File>,
@@ -563,6 +591,10 @@ pub struct MessageToPluginPayload {
     pub new_plugin_args: ::core::option::Option<NewPluginArgs>,
     #[prost(uint32, optional, tag = "7")]
     pub destination_plugin_id: ::core::option::Option<u32>,
+    #[prost(string, optional, tag = "8")]
+    pub request_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "9")]
+    pub in_reply_to: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -891,6 +923,9 @@ pub enum CommandName {
     OpenFileNearPlugin = 124,
     OpenFileFloatingNearPlugin = 125,
     OpenFileInPlaceOfPlugin = 126,
+    WatchPaths = 127,
+    SetTimer = 128,
+    CancelTimer = 129,
 }
 impl CommandName {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -1036,6 +1071,9 @@ impl CommandName {
             CommandName::OpenFileNearPlugin => "OpenFileNearPlugin",
             CommandName::OpenFileFloatingNearPlugin => "OpenFileFloatingNearPlugin",
             CommandName::OpenFileInPlaceOfPlugin => "OpenFileInPlaceOfPlugin",
+            CommandName::WatchPaths => "WatchPaths",
+            CommandName::SetTimer => "SetTimer",
+            CommandName::CancelTimer => "CancelTimer",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -1178,6 +1216,9 @@ impl CommandName {
             "OpenFileNearPlugin" => Some(Self::OpenFileNearPlugin),
             "OpenFileFloatingNearPlugin" => Some(Self::OpenFileFloatingNearPlugin),
             "OpenFileInPlaceOfPlugin" => Some(Self::OpenFileInPlaceOfPlugin),
+            "WatchPaths" => Some(Self::WatchPaths),
+            "SetTimer" => Some(Self::SetTimer),
+            "CancelTimer" => Some(Self::CancelTimer),
             _ => None,
         }
     }
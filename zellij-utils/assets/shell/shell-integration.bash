@@ -0,0 +1,58 @@
+# The following snippet is meant to be used like this in your .bashrc:
+#
+# # Configure auto-attach on cd to your likings (default is off).
+# # export ZELLIJ_AUTO_ATTACH_ON_CD=true
+# eval "$(zellij setup --shell-integration bash)"
+#
+# Inside zellij, the current working directory is reported to zellij (OSC 7) so that new panes
+# open in it, and the pane title is set to the running command.
+# Outside zellij, if ZELLIJ_AUTO_ATTACH_ON_CD is true, changing into a directory containing a
+# `.zellij-session` file attaches to the session named in this file (or after the directory if the
+# file is empty), creating it if it does not exist.
+#
+# Note: this uses the DEBUG trap to detect the running command.
+
+__zellij_report_cwd() {
+    local LC_ALL=C
+    local path="" c i
+    for ((i = 0; i < ${#PWD}; i++)); do
+        c="${PWD:i:1}"
+        case "$c" in
+            [-/:_.\!\'\(\)~[:alnum:]]) path+="$c" ;;
+            *) printf -v c '%%%02X' "'$c"; path+="$c" ;;
+        esac
+    done
+    printf '\e]7;file://%s%s\e\\' "$HOSTNAME" "$path"
+}
+
+__zellij_set_title() {
+    printf '\e]2;%s\e\\' "$1"
+}
+
+__zellij_precmd() {
+    __zellij_report_cwd
+    __zellij_set_title "${PWD/#$HOME/\~}"
+    __zellij_at_prompt=1
+}
+
+__zellij_preexec() {
+    [[ -n "$COMP_LINE" || -z "$__zellij_at_prompt" ]] && return
+    __zellij_at_prompt=
+    __zellij_set_title "$BASH_COMMAND"
+}
+
+__zellij_auto_attach() {
+    [[ "$ZELLIJ_AUTO_ATTACH_ON_CD" == "true" && -f .zellij-session ]] || return
+    [[ "$PWD" == "$__zellij_last_auto_attach_dir" ]] && return
+    __zellij_last_auto_attach_dir="$PWD"
+    local session_name
+    session_name="$(head -n 1 .zellij-session)"
+    zellij attach --create "${session_name:-${PWD##*/}}"
+}
+
+if [[ -n "$ZELLIJ" ]]; then
+    PROMPT_COMMAND="${PROMPT_COMMAND:+$PROMPT_COMMAND; }__zellij_precmd"
+    trap '__zellij_preexec' DEBUG
+else
+    PROMPT_COMMAND="${PROMPT_COMMAND:+$PROMPT_COMMAND; }__zellij_auto_attach"
+fi
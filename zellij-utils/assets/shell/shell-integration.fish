@@ -0,0 +1,43 @@
+# The following snippet is meant to be used like this in your fish config:
+#
+# if status is-interactive
+#     # Configure auto-attach on cd to your likings (default is off).
+#     # set -gx ZELLIJ_AUTO_ATTACH_ON_CD true
+#     zellij setup --shell-integration fish | source
+# end
+#
+# Inside zellij, the current working directory is reported to zellij (OSC 7) so that new panes
+# open in it, and the pane title is set to the running command.
+# Outside zellij, if ZELLIJ_AUTO_ATTACH_ON_CD is true, changing into a directory containing a
+# `.zellij-session` file attaches to the session named in this file (or after the directory if the
+# file is empty), creating it if it does not exist.
+
+function __zellij_set_title
+    printf '\e]2;%s\e\\' $argv[1]
+end
+
+if set -q ZELLIJ
+    function __zellij_report_cwd --on-variable PWD
+        printf '\e]7;file://%s%s\e\\' $hostname (string escape --style=url -- $PWD)
+    end
+
+    function __zellij_preexec --on-event fish_preexec
+        __zellij_set_title $argv[1]
+    end
+
+    function __zellij_postexec --on-event fish_postexec
+        __zellij_set_title (prompt_pwd)
+    end
+
+    __zellij_report_cwd
+else
+    function __zellij_auto_attach --on-variable PWD
+        if test "$ZELLIJ_AUTO_ATTACH_ON_CD" = "true"; and test -f .zellij-session
+            set -l session_name (head -n 1 .zellij-session)
+            if test -z "$session_name"
+                set session_name (basename $PWD)
+            end
+            zellij attach --create $session_name
+        end
+    end
+end
@@ -3,13 +3,15 @@ use crate::setup::Setup;
 use crate::{
     consts::{ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV},
     input::{layout::PluginUserConfiguration, options::CliOptions},
+    ipc::PaneSignal,
 };
-use clap::{Parser, Subcommand};
+use clap::{ArgEnum, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
 use url::Url;
 
-fn validate_session(name: &str) -> Result<String, String> {
+pub fn validate_session(name: &str) -> Result<String, String> {
     #[cfg(unix)]
     {
         use crate::consts::ZELLIJ_SOCK_MAX_LENGTH;
@@ -33,8 +35,32 @@ fn validate_session(name: &str) -> Result<String, String> {
     Ok(name.to_owned())
 }
 
+fn parse_layout_variable(variable: &str) -> Result<(String, String), String> {
+    match variable.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_owned(), value.to_owned()))
+        },
+        _ => Err(format!(
+            "layout variables must be given as name=value, got '{}'",
+            variable
+        )),
+    }
+}
+
+pub const EXIT_CODES_HELP: &str = "EXIT CODES:
+    0    Success
+    1    General failure
+    2    Invalid input (eg. a malformed action or session name)
+    3    No such session (or no active sessions at all)
+    4    Ambiguous session (several sessions match and none was specified)
+    5    Failed to communicate with the session
+    6    Failed to load or convert a configuration or layout
+    7    Aborted by the user
+    8    Session already exists
+    9    Timed out waiting for the session to respond";
+
 #[derive(Parser, Default, Debug, Clone, Serialize, Deserialize)]
-#[clap(version, name = "zellij")]
+#[clap(version, name = "zellij", after_help = EXIT_CODES_HELP)]
 pub struct CliArgs {
     /// Maximum panes on screen, caution: opening more panes will close old ones
     #[clap(long, value_parser)]
@@ -58,10 +84,20 @@ pub struct CliArgs {
     #[clap(short, long, value_parser, overrides_with = "layout")]
     pub layout: Option<PathBuf>,
 
-    /// Name of a predefined layout inside the layout directory or the path to a layout file
-    /// Will always start a new session, even if inside an existing session
-    #[clap(short, long, value_parser, overrides_with = "new_session_with_layout")]
-    pub new_session_with_layout: Option<PathBuf>,
+    /// Name of a predefined layout inside the layout directory or the path to a layout file,
+    /// optionally followed by the name of the session to create (eg. -n compact my-session)
+    /// Will always start a new session, even if inside an existing session, and fails if a
+    /// session with this name already exists
+    #[clap(
+        short,
+        long,
+        value_parser,
+        value_names(&["LAYOUT", "SESSION_NAME"]),
+        min_values(1),
+        max_values(2),
+        overrides_with = "new_session_with_layout"
+    )]
+    pub new_session_with_layout: Option<Vec<String>>,
 
     /// Change where zellij looks for the configuration file
     #[clap(short, long, overrides_with = "config", env = ZELLIJ_CONFIG_FILE_ENV, value_parser)]
@@ -77,6 +113,51 @@ pub struct CliArgs {
     /// Specify emitting additional debug information
     #[clap(short, long, value_parser)]
     pub debug: bool,
+
+    /// Do not print error messages, only exit with the relevant exit code
+    #[clap(long, value_parser, global = true)]
+    pub quiet: bool,
+
+    /// Print errors to STDERR as JSON objects (eg. `{"error":{"code":3,"kind":"no_session","message":"..."}}`)
+    #[clap(long, value_parser, global = true)]
+    pub json_errors: bool,
+
+    /// When sending actions to a session (eg. `zellij action ...` or `zellij run ...`), give up
+    /// if the session does not respond within this time (eg. 500ms, 5s, 1m)
+    #[clap(long, value_parser = humantime::parse_duration, global = true)]
+    pub timeout: Option<Duration>,
+
+    /// When to color the output of CLI commands, `auto` colors it only when printing to a terminal
+    /// and the NO_COLOR environment variable is not set
+    #[clap(long, arg_enum, value_parser, global = true, default_value("auto"))]
+    pub color: ColorChoice,
+}
+
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl Default for ColorChoice {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TmuxProjectFormat {
+    Tmuxinator,
+    Tmuxp,
+}
+
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventFormat {
+    /// `<event> key=value...`
+    Text,
+    /// Newline delimited json objects
+    Json,
 }
 
 #[derive(Debug, Subcommand, Clone, Serialize, Deserialize)]
@@ -114,9 +195,24 @@ pub enum Sessions {
         #[clap(short, long, value_parser, takes_value(false), default_value("false"))]
         short: bool,
 
+        /// Print a table with metadata about each session (attached clients, tabs, panes...)
+        #[clap(
+            short,
+            long,
+            value_parser,
+            takes_value(false),
+            default_value("false"),
+            conflicts_with("short")
+        )]
+        long: bool,
+
         /// List the sessions in reverse order (default is ascending order)
         #[clap(short, long, value_parser, takes_value(false), default_value("false"))]
         reverse: bool,
+
+        /// Only list running sessions that have all of these tags (comma separated)
+        #[clap(short, long, value_parser, value_delimiter(','))]
+        tag: Vec<String>,
     },
     /// List existing plugin aliases
     #[clap(visible_alias = "la")]
@@ -132,7 +228,9 @@ pub enum Sessions {
         #[clap(short, long, value_parser)]
         create: bool,
 
-        /// Create a detached session in the background if one does not exist
+        /// Create a detached session in the background and return without attaching to it, the
+        /// commands of its layout (see the global --layout option) start running right away.
+        /// Without a session name, a new session is created and its generated name is printed.
         #[clap(short('b'), long, value_parser)]
         create_background: bool,
 
@@ -147,6 +245,15 @@ pub enum Sessions {
         /// If resurrecting a dead session, immediately run all its commands on startup
         #[clap(short, long, value_parser, takes_value(false), default_value("false"))]
         force_run_commands: bool,
+
+        /// Tags to attach to the session if it is created (comma separated, eg. work,db)
+        #[clap(short, long, value_parser, value_delimiter(','))]
+        tag: Vec<String>,
+
+        /// Attach as a viewer: the session is displayed, but all keys and actions of this client
+        /// are rejected. Close the terminal to detach.
+        #[clap(long, value_parser, conflicts_with_all(&["create", "create_background"]))]
+        read_only: bool,
     },
 
     /// Kill a specific session
@@ -155,6 +262,13 @@ pub enum Sessions {
         /// Name of target session
         #[clap(value_parser)]
         target_session: Option<String>,
+        /// Send this signal (TERM, INT or HUP) to the process tree of every pane first, so that
+        /// long running jobs get a chance to clean up before the session is killed
+        #[clap(long, value_parser)]
+        signal: Option<PaneSignal>,
+        /// Seconds to wait for the session to exit after signaling its panes before killing it
+        #[clap(long, value_parser, default_value("10"), requires("signal"))]
+        grace_period: u64,
     },
 
     /// Delete a specific session
@@ -171,20 +285,26 @@ pub enum Sessions {
     /// Kill all sessions
     #[clap(visible_alias = "ka")]
     KillAllSessions {
-        /// Automatic yes to prompts
+        /// Automatic yes to prompts (required when not running in a terminal)
         #[clap(short, long, value_parser)]
         yes: bool,
+        /// Only kill sessions that have not been attached for longer than this (eg. 30d, 12h)
+        #[clap(long, value_parser = humantime::parse_duration)]
+        older_than: Option<Duration>,
     },
 
     /// Delete all sessions
     #[clap(visible_alias = "da")]
     DeleteAllSessions {
-        /// Automatic yes to prompts
+        /// Automatic yes to prompts (required when not running in a terminal)
         #[clap(short, long, value_parser)]
         yes: bool,
         /// Kill the sessions if they're running before deleting them
         #[clap(short, long, value_parser, takes_value(false), default_value("false"))]
         force: bool,
+        /// Only delete sessions that have not been attached for longer than this (eg. 30d, 12h)
+        #[clap(long, value_parser = humantime::parse_duration)]
+        older_than: Option<Duration>,
     },
 
     /// Send actions to a specific session
@@ -227,13 +347,46 @@ pub enum Sessions {
         name: Option<String>,
 
         /// Close the pane immediately when its command exits
-        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            conflicts_with_all(&["close-on-success", "keep-open"])
+        )]
         close_on_exit: bool,
 
+        /// Close the pane when its command exits successfully, keeping it open if it fails
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            conflicts_with("keep-open")
+        )]
+        close_on_success: bool,
+
+        /// Keep the pane open showing the exit status when its command exits (the default, unless
+        /// command_pane_on_exit is configured otherwise)
+        #[clap(long, value_parser, default_value("false"), takes_value(false))]
+        keep_open: bool,
+
         /// Start the command suspended, only running after you first presses ENTER
-        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            conflicts_with("start-immediately")
+        )]
         start_suspended: bool,
 
+        /// Run the command right away, even if start_command_panes_suspended is configured
+        #[clap(long, value_parser, default_value("false"), takes_value(false))]
+        start_immediately: bool,
+
         /// The x coordinates if the pane is floating as a bare integer (eg. 1) or percent (eg. 10%)
         #[clap(short, long, requires("floating"))]
         x: Option<String>,
@@ -249,6 +402,62 @@ pub enum Sessions {
         /// Whether to pin a floating pane so that it is always on top
         #[clap(long, requires("floating"))]
         pinned: Option<bool>,
+
+        /// Stay connected and stream the output of the command to stdout, exiting with its exit
+        /// status when it exits
+        #[clap(long, value_parser, default_value("false"), takes_value(false))]
+        capture_output: bool,
+
+        /// Only print the last N lines of output once the command exits
+        #[clap(long, value_parser, requires("capture-output"))]
+        tail: Option<usize>,
+
+        /// Write the contents of this file to the command's STDIN when it starts
+        #[clap(long, value_parser)]
+        stdin_file: Option<PathBuf>,
+
+        /// Stay connected and write our STDIN to the command's STDIN once it starts (eg. `cat
+        /// app.log | zellij run --stdin -- less`)
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            conflicts_with_all(&["stdin-file", "capture-output"])
+        )]
+        stdin: bool,
+
+        /// Also write the output of the command to this file (on the machine running the session)
+        #[clap(long, value_parser)]
+        log_output: Option<PathBuf>,
+
+        /// Rotate the log file once it grows beyond this many bytes
+        #[clap(long, value_parser, requires("log-output"))]
+        log_max_size: Option<u64>,
+
+        /// How many rotated log files to keep (default: 1)
+        #[clap(long, value_parser, requires("log-max-size"))]
+        log_max_files: Option<usize>,
+
+        /// Strip ANSI escape sequences (eg. colors) from the log file
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("log-output")
+        )]
+        log_strip_ansi: bool,
+
+        /// Re-run the command whenever files under its cwd that match this glob change (eg.
+        /// '**/*.rs'), can be repeated
+        #[clap(long, value_parser)]
+        watch: Vec<String>,
+
+        /// How long to wait for file changes to settle before re-running the command, in
+        /// milliseconds (default: 300)
+        #[clap(long, value_parser, requires("watch"))]
+        watch_debounce: Option<u64>,
     },
     /// Load a plugin
     #[clap(visible_alias = "p")]
@@ -300,8 +509,8 @@ pub enum Sessions {
     Edit {
         file: PathBuf,
 
-        /// Open the file in the specified line number
-        #[clap(short, long, value_parser)]
+        /// Open the file in the specified line number (a FILE:LINE argument also works)
+        #[clap(short, long, alias("line"), value_parser)]
         line_number: Option<usize>,
 
         /// Direction to open the new pane in
@@ -346,8 +555,13 @@ pub enum Sessions {
     ConvertConfig {
         old_config_file: PathBuf,
     },
+    /// Convert a YAML layout of an older zellij version, or a tmuxinator/tmuxp project file, to a
+    /// KDL layout
     ConvertLayout {
         old_layout_file: PathBuf,
+        /// Convert a tmuxinator or tmuxp project file instead of an older zellij layout
+        #[clap(long, arg_enum, value_parser)]
+        from: Option<TmuxProjectFormat>,
     },
     ConvertTheme {
         old_theme_file: PathBuf,
@@ -389,6 +603,36 @@ tail -f /tmp/my-live-logfile | zellij pipe --name logs --plugin https://example.
         #[clap(short('c'), long, value_parser, display_order(4))]
         plugin_configuration: Option<PluginUserConfiguration>,
     },
+    /// Serve a JSON-RPC 2.0 interface to the running sessions (listing sessions, tabs, panes and
+    /// clients, and sending them actions) for scripts and editor plugins
+    ApiServer {
+        /// Where to listen for newline delimited requests (eg. unix:/run/zellij-api.sock)
+        #[clap(long, value_parser)]
+        listen: String,
+    },
+    /// Print what happens in a session (eg. tabs being created or command panes exiting), one
+    /// event per line
+    Events {
+        /// Keep printing events until interrupted, rather than only the current state of the
+        /// session
+        #[clap(short, long, value_parser)]
+        follow: bool,
+        /// Only print these events (eg. pane-exited,tab-created)
+        #[clap(long, value_parser, value_delimiter(','))]
+        filter: Vec<String>,
+        #[clap(long, arg_enum, value_parser, default_value("text"))]
+        format: EventFormat,
+    },
+    /// Create the sessions declared in the `daemon` block of the configuration and keep running
+    /// until terminated, so that zellij can be run as a (systemd) user service
+    StartServer {
+        /// Fork into the background and detach from the terminal
+        #[clap(short, long, value_parser)]
+        daemon: bool,
+        /// Write the pid of the server to this file, it is removed when the server stops
+        #[clap(long, value_parser)]
+        pidfile: Option<PathBuf>,
+    },
 }
 
 #[derive(Debug, Subcommand, Clone, Serialize, Deserialize)]
@@ -401,6 +645,22 @@ pub enum CliAction {
     WriteChars {
         chars: String,
     },
+    /// Write raw bytes to the focused (or a specific) pane, bypassing key parsing.
+    ///
+    /// Example: zellij action write-raw 1b5b41 (hex, the default)
+    ///          zellij action write-raw --base64 G1tB
+    WriteRaw {
+        /// The bytes to write, hex encoded (whitespace between bytes is ignored) unless --base64
+        /// is specified
+        data: String,
+        /// Interpret the data as base64 rather than hex
+        #[clap(long, value_parser)]
+        base64: bool,
+        /// The pane_id of the pane to write to, eg. terminal_1, plugin_2 or 3 (equivalent to
+        /// terminal_3), defaults to the focused pane
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
     /// [increase|decrease] the focused panes area at the [left|down|up|right] border.
     Resize {
         resize: Resize,
@@ -422,7 +682,11 @@ pub enum CliAction {
     /// Change the location of the focused pane in the specified direction or rotate forwrads
     /// [right|left|up|down]
     MovePane {
+        #[clap(conflicts_with("to_session"))]
         direction: Option<Direction>,
+        /// Move the focused terminal pane (with its running process) to this other session
+        #[clap(long, value_parser)]
+        to_session: Option<String>,
     },
     /// Rotate the location of the previous pane backwards
     MovePaneBackwards,
@@ -435,6 +699,10 @@ pub enum CliAction {
         /// Dump the pane with full scrollback
         #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
         full: bool,
+
+        /// Keep the colors and other styles of the text as ANSI escape sequences
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        ansi: bool,
     },
     /// Dump current layout to stdout
     DumpLayout,
@@ -457,10 +725,16 @@ pub enum CliAction {
     /// Scroll down half page in focus pane.
     HalfPageScrollDown,
     /// Toggle between fullscreen focus pane and normal layout.
-    ToggleFullscreen,
+    ToggleFullscreen {
+        /// The pane_id of the pane to toggle, eg. terminal_1, plugin_2 or 3 (equivalent to
+        /// terminal_3), defaults to the focused pane
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
     /// Toggle frames around panes in the UI
     TogglePaneFrames,
     /// Toggle between sending text commands to all panes on the current tab and normal mode.
+    #[clap(visible_alias = "toggle-sync-input")]
     ToggleActiveSyncTab,
     /// Open a new pane in the specified direction [right|down]
     /// If no direction is specified, will try to use the biggest available space.
@@ -506,9 +780,30 @@ pub enum CliAction {
             value_parser,
             default_value("false"),
             takes_value(false),
-            requires("command")
+            requires("command"),
+            conflicts_with_all(&["close-on-success", "keep-open"])
         )]
         close_on_exit: bool,
+        /// Close the pane when its command exits successfully, keeping it open if it fails
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("command"),
+            conflicts_with("keep-open")
+        )]
+        close_on_success: bool,
+        /// Keep the pane open showing the exit status when its command exits (the default, unless
+        /// command_pane_on_exit is configured otherwise)
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("command")
+        )]
+        keep_open: bool,
         /// Start the command suspended, only running it after the you first press ENTER
         #[clap(
             short,
@@ -516,9 +811,19 @@ pub enum CliAction {
             value_parser,
             default_value("false"),
             takes_value(false),
-            requires("command")
+            requires("command"),
+            conflicts_with("start-immediately")
         )]
         start_suspended: bool,
+        /// Run the command right away, even if start_command_panes_suspended is configured
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("command")
+        )]
+        start_immediately: bool,
         #[clap(long, value_parser)]
         configuration: Option<PluginUserConfiguration>,
         #[clap(long, value_parser)]
@@ -538,6 +843,67 @@ pub enum CliAction {
         /// Whether to pin a floating pane so that it is always on top
         #[clap(long, requires("floating"))]
         pinned: Option<bool>,
+        /// Stay connected and stream the output of the command to stdout, exiting with its exit
+        /// status when it exits
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("command")
+        )]
+        capture_output: bool,
+        /// Only print the last N lines of output once the command exits
+        #[clap(long, value_parser, requires("capture-output"))]
+        tail: Option<usize>,
+        /// Write the contents of this file to the command's STDIN when it starts
+        #[clap(long, value_parser, requires("command"))]
+        stdin_file: Option<PathBuf>,
+        /// Stay connected and write our STDIN to the command's STDIN once it starts
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("command"),
+            conflicts_with_all(&["stdin-file", "capture-output"])
+        )]
+        stdin: bool,
+        /// Also write the output of the command to this file (on the machine running the session)
+        #[clap(long, value_parser, requires("command"))]
+        log_output: Option<PathBuf>,
+        /// Rotate the log file once it grows beyond this many bytes
+        #[clap(long, value_parser, requires("log-output"))]
+        log_max_size: Option<u64>,
+        /// How many rotated log files to keep (default: 1)
+        #[clap(long, value_parser, requires("log-max-size"))]
+        log_max_files: Option<usize>,
+        /// Strip ANSI escape sequences (eg. colors) from the log file
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("log-output")
+        )]
+        log_strip_ansi: bool,
+        /// Re-run the command whenever files under its cwd that match this glob change (eg.
+        /// '**/*.rs'), can be repeated
+        #[clap(long, value_parser, requires("command"))]
+        watch: Vec<String>,
+        /// How long to wait for file changes to settle before re-running the command, in
+        /// milliseconds (default: 300)
+        #[clap(long, value_parser, requires("watch"))]
+        watch_debounce: Option<u64>,
+    },
+    /// Open a new pane running the same command as the focused pane, or a new shell if it runs
+    /// one, in the same working directory
+    ///
+    /// Example: zellij action duplicate-pane --direction right
+    DuplicatePane {
+        /// Direction to open the new pane in, by default it uses the biggest available space
+        #[clap(short, long, value_parser)]
+        direction: Option<Direction>,
     },
     /// Open the specified file in a new zellij pane with your default EDITOR
     Edit {
@@ -547,8 +913,8 @@ pub enum CliAction {
         #[clap(short, long, value_parser, conflicts_with("floating"))]
         direction: Option<Direction>,
 
-        /// Open the file in the specified line number
-        #[clap(short, long, value_parser)]
+        /// Open the file in the specified line number (a FILE:LINE argument also works)
+        #[clap(short, long, alias("line"), value_parser)]
         line_number: Option<usize>,
 
         /// Open the new pane in floating mode
@@ -594,11 +960,23 @@ pub enum CliAction {
     TogglePaneEmbedOrFloating,
     /// Toggle the visibility of all floating panes in the current Tab, open one if none exist
     ToggleFloatingPanes,
-    /// Close the focused pane.
-    ClosePane,
-    /// Renames the focused pane
+    /// Close the focused (or a specific) pane.
+    ClosePane {
+        /// The pane_id of the pane to close, eg. terminal_1, plugin_2 or 3 (equivalent to
+        /// terminal_3), defaults to the focused pane
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Open the pane most recently closed in the current tab again, where it was and with its
+    /// scrollback, command and cwd.
+    UndoClosePane,
+    /// Renames the focused (or a specific) pane
     RenamePane {
         name: String,
+        /// The pane_id of the pane to rename, eg. terminal_1, plugin_2 or 3 (equivalent to
+        /// terminal_3), defaults to the focused pane
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
     },
     /// Remove a previously set pane name
     UndoRenamePane,
@@ -606,8 +984,12 @@ pub enum CliAction {
     GoToNextTab,
     /// Go to the previous tab.
     GoToPreviousTab,
-    /// Close the current tab.
-    CloseTab,
+    /// Close the current tab, or the tab with the specified name
+    CloseTab {
+        /// The name of the tab to close, defaults to the current tab
+        #[clap(short, long, value_parser)]
+        tab_name: Option<String>,
+    },
     /// Go to tab with index [index]
     GoToTab {
         index: u32,
@@ -619,9 +1001,12 @@ pub enum CliAction {
         #[clap(short, long, value_parser)]
         create: bool,
     },
-    /// Renames the focused pane
+    /// Renames the focused tab, or the tab with the specified name
     RenameTab {
         name: String,
+        /// The current name of the tab to rename, defaults to the focused tab
+        #[clap(short, long, value_parser)]
+        tab_name: Option<String>,
     },
     /// Remove a previously set tab name
     UndoRenameTab,
@@ -635,6 +1020,11 @@ pub enum CliAction {
         #[clap(long, value_parser, requires("layout"))]
         layout_dir: Option<PathBuf>,
 
+        /// Name of a pane_template or tab_template from the "templates" block of the
+        /// configuration to use for the new tab
+        #[clap(short, long, value_parser, conflicts_with("layout"))]
+        template: Option<String>,
+
         /// Name of the new tab
         #[clap(short, long, value_parser)]
         name: Option<String>,
@@ -642,6 +1032,11 @@ pub enum CliAction {
         /// Change the working directory of the new tab
         #[clap(short, long, value_parser, requires("layout"))]
         cwd: Option<PathBuf>,
+
+        /// Set a variable of the layout, replacing its `{{name}}` placeholders (eg. --var
+        /// project=zellij), can be given multiple times
+        #[clap(long = "var", value_parser = parse_layout_variable, requires("layout"))]
+        vars: Vec<(String, String)>,
     },
     /// Move the focused tab in the specified direction. [right|left]
     MoveTab {
@@ -758,8 +1153,268 @@ tail -f /tmp/my-live-logfile | zellij action pipe --name logs --plugin https://e
         #[clap(short('t'), long, value_parser, display_order(10))]
         plugin_title: Option<String>,
     },
-    ListClients,
+    /// List the clients attached to the session, with their focused pane and tab and their
+    /// terminal size
+    ListClients {
+        /// Print the clients as JSON
+        #[clap(long, value_parser)]
+        json: bool,
+    },
+    /// List the panes in all tabs with their ids, to be used with the --pane-id option of other
+    /// actions
+    ListPanes {
+        /// Print the panes as JSON
+        #[clap(long, value_parser)]
+        json: bool,
+    },
+    /// Focus the pane with the specified id, switching to its tab if needed
+    ///
+    /// Example: zellij action focus-pane --pane-id terminal_3
+    FocusPane {
+        /// The pane_id of the pane to focus, eg. terminal_1, plugin_2 or 3 (equivalent to
+        /// terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: String,
+        /// Also scroll the pane to this line of its scrollback, as numbered by search-panes
+        #[clap(short, long, value_parser)]
+        line: Option<usize>,
+    },
+    /// Attach a note describing the current session, shown in `zellij list-sessions --long` and
+    /// the session-manager. An empty note removes it.
+    ///
+    /// Example: zellij action set-session-note "deploying payments v2"
+    SetSessionNote {
+        note: String,
+    },
+    /// Set environment variables on the current session, so that every pane created from now on
+    /// gets them (eg. to refresh SSH_AUTH_SOCK after reattaching from a new ssh connection).
+    /// Panes that are already running are not affected.
+    ///
+    /// Example: zellij action set-env SSH_AUTH_SOCK="$SSH_AUTH_SOCK" DISPLAY=:1
+    ///          zellij action set-env --unset DISPLAY
+    SetEnv {
+        /// The variables to set, as NAME=VALUE
+        #[clap(value_parser)]
+        variables: Vec<String>,
+        /// The name of a variable to remove, can be repeated
+        #[clap(short, long, value_parser)]
+        unset: Vec<String>,
+    },
+    /// Set the default working directory of the focused tab: the panes opened in it from now on
+    /// start there, whichever pane is focused
+    ///
+    /// Example: zellij action set-tab-cwd ~/projects/backend
+    SetTabCwd {
+        #[clap(value_parser)]
+        cwd: PathBuf,
+    },
+    /// Move the focused (or a specific) pane to another tab, without changing the focused tab
+    ///
+    /// Example: zellij action move-pane-to-tab 2
+    ///          zellij action move-pane-to-tab logs --pane-id terminal_3
+    MovePaneToTab {
+        /// The 1-based index of the tab or its name
+        tab: String,
+        /// Treat TAB as a tab name even if it is a number
+        #[clap(short, long, value_parser)]
+        name: bool,
+        /// The pane_id of the pane to move, eg. terminal_1, plugin_2 or 3 (equivalent to
+        /// terminal_3), defaults to the focused pane
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Show the focused terminal pane in another tab too: both panes show the same running
+    /// process, and closing the original pane closes the linked one
+    ///
+    /// Example: zellij action link-pane-to-tab 2
+    LinkPaneToTab {
+        /// The 1-based index of the tab or its name
+        tab: String,
+        /// Treat TAB as a tab name even if it is a number
+        #[clap(short, long, value_parser)]
+        name: bool,
+    },
+    /// Move the focused tab to a 1-based index, shifting the tabs in between
+    ///
+    /// Example: zellij action move-tab-to-index 1
+    MoveTabToIndex {
+        index: usize,
+    },
+    /// Move the focused pane to a new tab of its own, keeping its process and scrollback
+    BreakPane,
+    /// Move the only pane of the tab at a 1-based index into the focused tab, next to the focused
+    /// pane, and close the tab it was in
+    ///
+    /// Example: zellij action join-tab 3 --direction right
+    JoinTab {
+        index: usize,
+        /// Split the focused pane in this direction, by default the pane goes wherever a new
+        /// pane would
+        #[clap(short, long, value_parser)]
+        direction: Option<Direction>,
+    },
+    /// Declare that a service of this session listens on a port, so that users of a remote session
+    /// know which ports to forward (see list-ports). Programs in a pane can also declare their
+    /// ports with an escape sequence, eg. printf '\e]zellij;declare-port=8080;name=api\e\\'
+    ///
+    /// Example: zellij action declare-port 8080 --name api
+    DeclarePort {
+        port: u16,
+        /// What the service is (eg. api)
+        #[clap(short, long, value_parser)]
+        name: Option<String>,
+        /// The pane_id of the pane running the service, eg. terminal_1, plugin_2 or 3 (equivalent
+        /// to terminal_3), defaults to the pane this command runs in
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Remove a port declared with declare-port
+    UndeclarePort {
+        port: u16,
+        /// The pane_id of the pane running the service the port was declared for (see
+        /// declare-port), defaults to the pane this command runs in
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// List the ports declared in this session, with the panes of their services
+    ListPorts {
+        /// Output as json
+        #[clap(short, long, value_parser)]
+        json: bool,
+    },
+    /// Dump the state of the session as json: its tabs and panes with their commands, exit
+    /// statuses, scrollback lengths, geometry and which of them are focused
+    DumpSession,
+    /// Search the scrollback of the focused (or a specific) pane for a regular expression, moving
+    /// its viewport to the next match and printing how many matches there are
+    ///
+    /// Example: zellij action search 'error|panicked' --backwards
+    Search {
+        pattern: String,
+        /// Search upwards for the previous match instead, starting from the bottom of the
+        /// scrollback unless the pane is scrolled
+        #[clap(short, long, value_parser)]
+        backwards: bool,
+        /// The pane_id of the pane to search, eg. terminal_1 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Search the scrollback of every pane in the focused tab (or in the whole session) for a
+    /// regular expression, printing the pane_id, tab and line number of every matching line.
+    /// Jump to a match with `zellij action focus-pane --pane-id <PANE_ID> --line <LINE>`.
+    ///
+    /// Example: zellij action search-panes 'error|panicked' --session
+    SearchPanes {
+        pattern: String,
+        /// Search the panes of all tabs rather than only those of the focused tab
+        #[clap(short, long, value_parser)]
+        session: bool,
+        /// Print the matches as JSON
+        #[clap(long, value_parser)]
+        json: bool,
+    },
+    /// Start writing everything the focused (or a specific) pane outputs to a timestamped file in
+    /// the session's cache folder, or stop doing so. Logging continues while the session is
+    /// detached.
+    TogglePaneLogging {
+        /// The pane_id of the pane to log, eg. terminal_1 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Run the command of the focused (or a specific) command pane again, in place, after it
+    /// exited.
+    RerunCommandPane {
+        /// The pane_id of the command pane, eg. terminal_1 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Open a picker in the focused (or a specific) command pane, once its command exited, to run
+    /// one of the commands it ran before again, or to edit its arguments before doing so.
+    CommandHistory {
+        /// The pane_id of the command pane, eg. terminal_1 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Mark the tab of the focused (or a specific) pane when the pane produces output while it is
+    /// not focused, or stop doing so.
+    ToggleActivityMonitor {
+        /// The pane_id of the pane to monitor, eg. terminal_1 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+        /// Also send a desktop notification through the terminals of the connected clients
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        notify: bool,
+    },
+    /// Mark the tab of the focused (or a specific) pane when the pane produces no output for a
+    /// number of seconds while it is not focused, or stop doing so.
+    ///
+    /// Example: zellij action toggle-silence-monitor 30
+    ToggleSilenceMonitor {
+        /// Seconds without output after which the tab is marked
+        #[clap(value_parser, default_value("30"))]
+        seconds: u64,
+        /// The pane_id of the pane to monitor, eg. terminal_1 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+        /// Also send a desktop notification through the terminals of the connected clients
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        notify: bool,
+    },
+    /// Add the focused (or a specific) pane to a named group, or remove it if it's already in it.
+    /// A pane is in one group at most, which is shown in its frame.
+    ///
+    /// Example: zellij action toggle-pane-in-group servers
+    TogglePaneInGroup {
+        group: String,
+        /// The pane_id of the pane, eg. terminal_1 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Write characters to all the panes of a group.
+    WriteCharsToGroup {
+        group: String,
+        chars: String,
+    },
+    /// Close all the panes of a group.
+    CloseGroup {
+        group: String,
+    },
+    /// [increase|decrease] the area of all the panes of a group at the [left|down|up|right]
+    /// border.
+    ResizeGroup {
+        group: String,
+        resize: Resize,
+        direction: Option<Direction>,
+    },
+    /// Change the frame color of all the panes of a group, or reset it when no color is given.
+    /// Colors are either hex (eg. "#ff8800") or 256 color indices (eg. 208).
+    SetGroupColor {
+        group: String,
+        color: Option<String>,
+    },
+    /// Run a command line in every terminal pane of the focused tab (or of a group), as if it
+    /// was typed in each of them followed by ENTER. Exited command panes are skipped. A single
+    /// argument is typed as it is, several are quoted so that each stays one word.
+    ///
+    /// Example: zellij action multi-exec -- source .env
+    ///          zellij action multi-exec --group hosts --exclude-focused -- sudo systemctl restart app
+    MultiExec {
+        #[clap(last(true), required(true))]
+        command: Vec<String>,
+        /// Run it in the panes of this group, in all tabs, instead of those of the focused tab
+        #[clap(short, long, value_parser)]
+        group: Option<String>,
+        /// Do not run it in the focused pane
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        exclude_focused: bool,
+        /// Only type the command line (or key sequence), without pressing ENTER
+        #[clap(long, value_parser, default_value("false"), takes_value(false))]
+        no_enter: bool,
+    },
     TogglePanePinned,
+    /// Make the focused floating pane global to the session, so that it follows the focused tab,
+    /// or return it to the tab it is in
+    ToggleFloatingPaneScope,
     /// Stack pane ids
     /// Ids are a space separated list of pane ids.
     /// They should either be in the form of `terminal_<int>` (eg. terminal_1), `plugin_<int>` (eg.
@@ -792,4 +1447,67 @@ tail -f /tmp/my-live-logfile | zellij action pipe --name logs --plugin https://e
         #[clap(long)]
         pinned: Option<bool>,
     },
+    /// Toggle handling the mouse in zellij (eg. for selecting text) for the focused pane, even if
+    /// the application running inside it requested to receive mouse events
+    ToggleMouseReportingOverride,
+    /// Toggle do not disturb, muting bells in all panes of the session until it is toggled off
+    ToggleDoNotDisturb,
+    /// Switch the focused tab to the swap layout with this name (see query-swap-layout-names)
+    SelectSwapLayout {
+        name: String,
+    },
+    /// Query the swap layout names of the focused tab
+    QuerySwapLayoutNames,
+    /// Change UI elements of the session at runtime for all connected clients
+    /// (eg. set-ui pane-frames=off status-bar=hidden tab-bar=compact)
+    #[clap(arg_required_else_help = true)]
+    SetUi {
+        /// ELEMENT=VALUE pairs, where pane-frames can be on/off, status-bar can be
+        /// visible/hidden and tab-bar can be visible/hidden/compact
+        #[clap(value_parser, required = true)]
+        settings: Vec<String>,
+    },
+    /// Expand all the panes of the stack of the focused pane, each taking an even share of its
+    /// space, or stack them back if they were expanded this way
+    ToggleExpandStack,
+    /// Move the focused stacked pane [up|down] in its stack
+    MovePaneInStack {
+        direction: Direction,
+    },
+    /// Focus the stacked pane of the focused tab whose title best matches this (fuzzy) query
+    ///
+    /// Example: zellij action focus-stacked-pane server logs
+    FocusStackedPane {
+        #[clap(value_parser, required = true)]
+        title: Vec<String>,
+    },
+    /// Run a command in the focused terminal pane in place of its current one, in the same
+    /// position and size. A command that is still running is stopped first, as if the pane was
+    /// closed.
+    ///
+    /// Example: zellij action replace-pane-command --keep-scrollback -- npm run dev
+    ReplacePaneCommand {
+        #[clap(last(true), required(true))]
+        command: Vec<String>,
+        /// Change the working directory of the command
+        #[clap(long, value_parser)]
+        cwd: Option<PathBuf>,
+        /// Keep what the pane showed so far above the output of the new command
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        keep_scrollback: bool,
+        /// Close the pane immediately when the command exits
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        close_on_exit: bool,
+    },
+    /// Toggle ignoring keyboard input to the focused pane (marking it as locked on its frame), so
+    /// that a long running process in it cannot be typed into by accident while it is watched
+    TogglePaneInputLock,
+    /// Scroll the focused pane to the previous prompt of its shell. The shell needs to mark its
+    /// prompts (OSC 133 shell integration, as used by kitty or WezTerm)
+    ScrollToPreviousPrompt,
+    /// Scroll the focused pane to the next prompt of its shell (OSC 133 shell integration)
+    ScrollToNextPrompt,
+    /// Select the output of the last command run by the shell of the focused pane (OSC 133 shell
+    /// integration), eg. to copy it
+    SelectLastCommandOutput,
 }
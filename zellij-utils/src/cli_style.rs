@@ -0,0 +1,127 @@
+//! Styling of the output of CLI commands (session listings, errors, `setup --check`), which is
+//! only colored when `--color` and the `NO_COLOR` environment variable allow it.
+
+use crate::cli::ColorChoice;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+static COLOR_CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+
+pub fn set_color_choice(color_choice: ColorChoice) {
+    let _ = COLOR_CHOICE.set(color_choice);
+}
+
+fn color_choice() -> ColorChoice {
+    COLOR_CHOICE.get().copied().unwrap_or_default()
+}
+
+/// See https://no-color.org: an explicit `--color always` still wins over `NO_COLOR`
+fn colors_enabled(color_choice: ColorChoice, no_color: Option<String>, is_terminal: bool) -> bool {
+    match color_choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => is_terminal && no_color.map_or(true, |no_color| no_color.is_empty()),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Painter {
+    enabled: bool,
+}
+
+impl Painter {
+    /// For output written to STDOUT
+    pub fn stdout() -> Self {
+        Painter {
+            enabled: colors_enabled(
+                color_choice(),
+                std::env::var("NO_COLOR").ok(),
+                std::io::stdout().is_terminal(),
+            ),
+        }
+    }
+    /// For output written to STDERR
+    pub fn stderr() -> Self {
+        Painter {
+            enabled: colors_enabled(
+                color_choice(),
+                std::env::var("NO_COLOR").ok(),
+                std::io::stderr().is_terminal(),
+            ),
+        }
+    }
+    pub fn plain() -> Self {
+        Painter { enabled: false }
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    fn paint(&self, sgr: &str, text: &str) -> String {
+        if self.enabled {
+            format!("\u{1b}[{}m{}\u{1b}[m", sgr, text)
+        } else {
+            text.to_owned()
+        }
+    }
+    pub fn bold(&self, text: &str) -> String {
+        self.paint("1", text)
+    }
+    pub fn green(&self, text: &str) -> String {
+        self.paint("32;1", text)
+    }
+    pub fn red(&self, text: &str) -> String {
+        self.paint("31;1", text)
+    }
+    pub fn magenta(&self, text: &str) -> String {
+        self.paint("35;1", text)
+    }
+    pub fn yellow(&self, text: &str) -> String {
+        self.paint("33;1", text)
+    }
+    /// A terminal hyperlink, or just its text when colors are disabled (eg. when piped)
+    pub fn hyperlink(&self, url: &str, text: &str) -> String {
+        if self.enabled {
+            format!("\u{1b}]8;;{}\u{1b}\\{}\u{1b}]8;;\u{1b}\\", url, text)
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_follow_the_terminal_unless_explicitly_chosen() {
+        assert!(colors_enabled(ColorChoice::Auto, None, true));
+        assert!(!colors_enabled(ColorChoice::Auto, None, false));
+        assert!(colors_enabled(ColorChoice::Always, None, false));
+        assert!(!colors_enabled(ColorChoice::Never, None, true));
+    }
+
+    #[test]
+    fn no_color_disables_automatic_colors_only() {
+        assert!(!colors_enabled(
+            ColorChoice::Auto,
+            Some("1".to_owned()),
+            true
+        ));
+        assert!(colors_enabled(ColorChoice::Auto, Some("".to_owned()), true));
+        assert!(colors_enabled(
+            ColorChoice::Always,
+            Some("1".to_owned()),
+            true
+        ));
+    }
+
+    #[test]
+    fn disabled_painter_leaves_text_untouched() {
+        let painter = Painter::plain();
+        assert_eq!(painter.green("name"), "name");
+        assert_eq!(
+            painter.hyperlink("https://zellij.dev", "zellij.dev"),
+            "zellij.dev"
+        );
+    }
+}
@@ -21,18 +21,25 @@ pub const SYSTEM_DEFAULT_DATA_DIR_PREFIX: &str = system_default_data_dir();
 
 pub static ZELLIJ_DEFAULT_THEMES: Dir = include_dir!("$CARGO_MANIFEST_DIR/assets/themes");
 
+pub const SESSION_METADATA_FILE_NAME: &str = "session-metadata.kdl";
+pub const SESSION_LAYOUT_FILE_NAME: &str = "session-layout.kdl";
+
 pub fn session_info_cache_file_name(session_name: &str) -> PathBuf {
-    session_info_folder_for_session(session_name).join("session-metadata.kdl")
+    session_info_folder_for_session(session_name).join(SESSION_METADATA_FILE_NAME)
 }
 
 pub fn session_layout_cache_file_name(session_name: &str) -> PathBuf {
-    session_info_folder_for_session(session_name).join("session-layout.kdl")
+    session_info_folder_for_session(session_name).join(SESSION_LAYOUT_FILE_NAME)
 }
 
 pub fn session_info_folder_for_session(session_name: &str) -> PathBuf {
     ZELLIJ_SESSION_INFO_CACHE_DIR.join(session_name)
 }
 
+pub fn pane_logs_folder_for_session(session_name: &str) -> PathBuf {
+    session_info_folder_for_session(session_name).join("pane-logs")
+}
+
 pub fn create_config_and_cache_folders() {
     if let Err(e) = std::fs::create_dir_all(&ZELLIJ_CACHE_DIR.as_path()) {
         log::error!("Failed to create cache dir: {:?}", e);
@@ -933,6 +933,12 @@ pub enum Event {
     FailedToChangeHostFolder(Option<String>), // String -> the error we got when changing
     PastedText(String),
     ConfigWasWrittenToDisk,
+    /// A timer expired set by the `set_timer` method exported by `zellij-tile`.
+    NamedTimer(String, f64), // String - timer name, f64 - seconds since it was set or last expired
+    /// The command of a command pane exited and the pane was kept open so that it can be re-run,
+    /// sent for all command panes regardless of which plugin opened them
+    CommandPaneFinished(u32, Option<i32>, Duration), // u32 - terminal_pane_id, Option<i32> - exit
+                                                     // code, Duration - how long the command ran
 }
 
 #[derive(
@@ -1508,6 +1514,12 @@ pub struct ModeInfo {
     pub session_name: Option<String>,
     pub editor: Option<PathBuf>,
     pub shell: Option<PathBuf>,
+    /// The version of the zellij binary installed on disk, when it differs from the version of
+    /// this session (only checked with the `update_check` option)
+    pub installed_version: Option<String>,
+    /// The latest published zellij release, when it differs from the version of this session
+    /// and from the installed one (only checked with `update_check "release"`)
+    pub latest_release: Option<String>,
 }
 
 impl ModeInfo {
@@ -1555,6 +1567,8 @@ pub struct SessionInfo {
     pub available_layouts: Vec<LayoutInfo>,
     pub plugins: BTreeMap<u32, PluginInfo>,
     pub tab_history: BTreeMap<ClientId, Vec<usize>>,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -1632,6 +1646,9 @@ impl SessionInfo {
         }
         self.plugins = plugin_list;
     }
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
 }
 
 /// Contains all the information for a currently opened tab.
@@ -1668,6 +1685,17 @@ pub struct TabInfo {
     pub selectable_tiled_panes_count: usize,
     /// The number of selectable (eg. not the UI bars) floating panes currently in this tab
     pub selectable_floating_panes_count: usize,
+    /// Whether a monitored pane in this tab produced output while it was not focused
+    pub has_activity_alert: bool,
+    /// Whether a monitored pane in this tab was silent for longer than its interval while it was
+    /// not focused
+    pub has_silence_alert: bool,
+    /// Whether a pane in this tab rang the bell while it was not focused
+    pub has_bell_alert: bool,
+    /// Whether the command of a command pane in this tab exited while the tab was not active
+    pub has_command_exit_alert: bool,
+    /// Whether the input of a pane in this tab is locked, so that keys typed into it are ignored
+    pub has_input_locked_pane: bool,
 }
 
 /// The `PaneManifest` contains a dictionary of panes, indexed by the tab position (0 indexed).
@@ -1733,6 +1761,9 @@ pub struct PaneInfo {
     /// Unselectable panes are often used for UI elements that do not have direct user interaction
     /// (eg. the default `status-bar` or `tab-bar`).
     pub is_selectable: bool,
+    /// The working directory of a terminal pane, as last reported by its shell (OSC 7), if its
+    /// shell reports it
+    pub cwd: Option<PathBuf>,
 }
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ClientInfo {
@@ -1870,6 +1901,11 @@ pub struct MessageToPlugin {
     /// these will only be used in case we need to launch a new plugin to send this message to,
     /// since none are running
     pub new_plugin_args: Option<NewPluginArgs>,
+    /// an id chosen by the sender, echoed back by the destination plugin in `in_reply_to` so that
+    /// the sender can correlate the reply with this request
+    pub request_id: Option<String>,
+    /// the `request_id` of the message this one is a reply to
+    pub in_reply_to: Option<String>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -1933,6 +1969,14 @@ impl MessageToPlugin {
         self.message_args = args;
         self
     }
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+    pub fn in_reply_to(mut self, request_id: impl Into<String>) -> Self {
+        self.in_reply_to = Some(request_id.into());
+        self
+    }
     pub fn new_plugin_instance_should_float(mut self, should_float: bool) -> Self {
         let new_plugin_args = self.new_plugin_args.get_or_insert_with(Default::default);
         new_plugin_args.should_float = Some(should_float);
@@ -2028,6 +2072,8 @@ pub struct PipeMessage {
     pub payload: Option<String>,
     pub args: BTreeMap<String, String>,
     pub is_private: bool,
+    pub request_id: Option<String>,
+    pub in_reply_to: Option<String>,
 }
 
 impl PipeMessage {
@@ -2044,8 +2090,19 @@ impl PipeMessage {
             payload: payload.clone(),
             args: args.clone().unwrap_or_else(|| Default::default()),
             is_private,
+            request_id: None,
+            in_reply_to: None,
         }
     }
+    pub fn with_correlation(
+        mut self,
+        request_id: Option<String>,
+        in_reply_to: Option<String>,
+    ) -> Self {
+        self.request_id = request_id;
+        self.in_reply_to = in_reply_to;
+        self
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Default)]
@@ -2307,4 +2364,8 @@ pub enum PluginCommand {
     OpenFileNearPlugin(FileToOpen, Context),
     OpenFileFloatingNearPlugin(FileToOpen, Option<FloatingPaneCoordinates>, Context),
     OpenFileInPlaceOfPlugin(FileToOpen, bool, Context), // bool -> close_plugin_after_replace
+    SetTimer(String, f64, bool), // String - timer name, f64 - seconds, bool - repeat
+    CancelTimer(String),         // String - timer name
+    WatchPaths(Vec<PathBuf>),    // paths or glob patterns in the /host filesystem, empty to stop
+                                 // watching
 }
@@ -271,6 +271,8 @@ pub enum ScreenContext {
     HalfPageScrollDown,
     ClearScroll,
     CloseFocusedPane,
+    CloseConfirmed,
+    AskToConfirmClose,
     ToggleActiveSyncTab,
     ToggleActiveTerminalFullscreen,
     TogglePaneFrames,
@@ -283,6 +285,7 @@ pub enum ScreenContext {
     UpdatePaneName,
     UndoRenamePane,
     NewTab,
+    NewTabFromLayout,
     ApplyLayout,
     SwitchTabNext,
     SwitchTabPrev,
@@ -316,10 +319,12 @@ pub enum ScreenContext {
     ConfirmPrompt,
     DenyPrompt,
     UpdateSearch,
+    CopyModeInput,
     SearchDown,
     SearchUp,
     SearchToggleCaseSensitivity,
     SearchToggleWholeWord,
+    SearchToggleRegex,
     SearchToggleWrap,
     AddRedPaneFrameColorOverride,
     ClearPaneFrameColorOverride,
@@ -344,6 +349,11 @@ pub enum ScreenContext {
     BreakPane,
     BreakPaneRight,
     BreakPaneLeft,
+    JoinTab,
+    MovePaneToSession,
+    SerializeMovedPane,
+    PaneMovedToSession,
+    AdoptPane,
     UpdateSessionInfos,
     ReplacePane,
     NewInPlacePluginPane,
@@ -372,9 +382,69 @@ pub enum ScreenContext {
     BreakPanesToTabWithIndex,
     ListClientsToPlugin,
     TogglePanePinned,
+    ToggleFloatingPaneScope,
     SetFloatingPanePinned,
     StackPanes,
     ChangeFloatingPanesCoordinates,
+    QuerySessionStats,
+    ClientTerminalFocusChanged,
+    ClientSixelSupport,
+    ClientKittyGraphicsSupport,
+    ClientColorSupport,
+    ToggleMouseReportingOverride,
+    CapturePaneOutput,
+    CapturedPaneExited,
+    ToggleDoNotDisturb,
+    SelectSwapLayout,
+    ReloadLayout,
+    QuerySwapLayoutNames,
+    SetUi,
+    CloseTabWithName,
+    RenameTabWithName,
+    ListPanes,
+    SetSessionNote,
+    MovePaneToTab,
+    LinkPaneToTab,
+    MoveTabToIndex,
+    UpdateAvailableVersions,
+    DeclarePort,
+    UndeclarePort,
+    ListPorts,
+    DumpSession,
+    SearchScrollback,
+    SearchPanes,
+    ScrollPaneToLine,
+    TogglePaneLogging,
+    TogglePaneInGroup,
+    ApplyToPaneGroup,
+    PublishSessionEvents,
+    MultiExec,
+    PaneTitleChanged,
+    TogglePaneActivityMonitor,
+    TogglePaneSilenceMonitor,
+    PaneMonitorAlert,
+    CheckPaneSilence,
+    PaneBell,
+    RerunCommandPaneOrFocused,
+    OpenCommandHistory,
+    UndoClosePane,
+    DuplicatePane,
+    ClosedPaneRespawned,
+    WatchedFilesChanged,
+    ClientInput,
+    CheckIdleClients,
+    CheckIdleSession,
+    ToggleExpandStack,
+    MovePaneInStack,
+    FocusStackedPaneByTitle,
+    ReplacePaneCommand,
+    StopCommandTimedOut,
+    TogglePaneInputLock,
+    ScrollToPreviousPrompt,
+    ScrollToNextPrompt,
+    SelectLastCommandOutput,
+    UpdateRunningCommands,
+    UpdateProcessStats,
 }
 
 /// Stack call representations corresponding to the different types of [`PtyInstruction`]s.
@@ -399,6 +469,18 @@ pub enum PtyContext {
     ListClientsMetadata,
     Reconfigure,
     ListClientsToPlugin,
+    ReportCwd,
+    SignalPanes,
+    StopCommand,
+    SetSessionEnv,
+    SetTabCwd,
+    RestoreClosedPane,
+    DuplicatePane,
+    MovePaneToSession,
+    SendPaneToSession,
+    PaneMovedToSession,
+    PaneNotMovedToSession,
+    AdoptPane,
     Exit,
 }
 
@@ -431,6 +513,7 @@ pub enum PluginContext {
     MessageFromPlugin,
     UnblockCliPipes,
     WatchFilesystem,
+    WatchPaths,
     KeybindPipe,
     DumpLayoutToPlugin,
     ListClientsMetadata,
@@ -461,6 +544,14 @@ pub enum ClientContext {
     CliPipeOutput,
     QueryTerminalSize,
     WriteConfigToDisk,
+    SessionStats,
+    CapturedPaneOutput,
+    CapturedPaneExited,
+    Event,
+    StdinPipeReady,
+    StdinPipeWritten,
+    ReadyToAdoptPane,
+    RenamedSession,
 }
 
 /// Stack call representations corresponding to the different types of [`ServerInstruction`]s.
@@ -474,6 +565,7 @@ pub enum ServerContext {
     Error,
     KillSession,
     DetachSession,
+    DetachIdleClient,
     AttachClient,
     ConnStatus,
     Log,
@@ -489,6 +581,19 @@ pub enum ServerContext {
     ConfigWrittenToDisk,
     FailedToWriteConfigToDisk,
     RebindKeys,
+    QuerySessionStats,
+    SignalPanes,
+    SessionStats,
+    ToggleRawPassthrough,
+    LayoutFilesChanged,
+    CapturedPaneOutput,
+    CapturedPaneExited,
+    ClientsMetadata,
+    SubscribeToEvents,
+    PublishEvent,
+    StdinPipeReady,
+    RefuseNewPanes,
+    CopyModeClients,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -507,10 +612,20 @@ pub enum BackgroundJobContext {
     StopPluginLoadingAnimation,
     ReadAllSessionInfosOnMachine,
     ReportSessionInfo,
+    RenameSession,
     ReportLayoutInfo,
     RunCommand,
     WebRequest,
     ReportPluginList,
+    CheckForUpdates,
+    MonitorPaneSilence,
+    MonitorIdleClients,
+    MonitorIdleSession,
+    MonitorRunningCommands,
+    MonitorProcessStats,
+    FindRunningProcesses,
+    RunHookCommand,
+    StopMonitor,
     Exit,
 }
 
@@ -1,22 +1,26 @@
 //! Definition of the actions that can be bound to keys.
 
-use super::command::{OpenFilePayload, RunCommandAction};
+use super::command::{
+    CommandWatch, OpenFilePayload, OutputCapture, OutputLog, RunCommandAction, StdinPipe,
+};
 use super::layout::{
     FloatingPaneLayout, Layout, PluginAlias, RunPlugin, RunPluginLocation, RunPluginOrAlias,
     SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
 };
 use crate::cli::CliAction;
-use crate::data::{Direction, KeyWithModifier, PaneId, Resize};
+use crate::data::{Direction, KeyWithModifier, PaletteColor, PaneId, Resize};
 use crate::data::{FloatingPaneCoordinates, InputMode};
 use crate::home::{find_default_config_dir, get_layout_dir};
 use crate::input::config::{Config, ConfigError, KdlError};
 use crate::input::mouse::MouseEvent;
-use crate::input::options::OnForceClose;
+use crate::input::options::{CommandPaneExit, OnForceClose};
 use miette::{NamedSource, Report};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use uuid::Uuid;
 
+use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -74,6 +78,7 @@ pub enum SearchOption {
     CaseSensitivity,
     WholeWord,
     Wrap,
+    Regex,
 }
 
 impl FromStr for SearchOption {
@@ -85,6 +90,7 @@ impl FromStr for SearchOption {
             },
             "WholeWord" | "wholeword" | "Wholeword" => Ok(SearchOption::WholeWord),
             "Wrap" | "wrap" => Ok(SearchOption::Wrap),
+            "Regex" | "regex" => Ok(SearchOption::Regex),
             _ => Err(format!(
                 "Failed to parse SearchOption. Unknown SearchOption: {}",
                 s
@@ -93,6 +99,125 @@ impl FromStr for SearchOption {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BarVisibility {
+    Visible,
+    Hidden,
+    Compact,
+}
+
+impl FromStr for BarVisibility {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "visible" | "on" | "shown" => Ok(BarVisibility::Visible),
+            "hidden" | "off" => Ok(BarVisibility::Hidden),
+            "compact" => Ok(BarVisibility::Compact),
+            _ => Err(format!(
+                "Failed to parse bar visibility. Unknown value: {} (expected visible, hidden or compact)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BarVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BarVisibility::Visible => write!(f, "visible"),
+            BarVisibility::Hidden => write!(f, "hidden"),
+            BarVisibility::Compact => write!(f, "compact"),
+        }
+    }
+}
+
+/// UI elements of a running session to change (eg. `pane-frames=off status-bar=hidden
+/// tab-bar=compact`), elements that are not specified are left as they are
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct UiSettings {
+    pub pane_frames: Option<bool>,
+    pub status_bar: Option<BarVisibility>,
+    pub tab_bar: Option<BarVisibility>,
+}
+
+impl FromStr for UiSettings {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ui_settings = UiSettings::default();
+        for setting in s.split_whitespace() {
+            let (element, value) = setting
+                .split_once('=')
+                .ok_or_else(|| format!("Expected ELEMENT=VALUE, found: {}", setting))?;
+            match element {
+                "pane-frames" => {
+                    ui_settings.pane_frames = match value {
+                        "on" | "true" => Some(true),
+                        "off" | "false" => Some(false),
+                        _ => {
+                            return Err(format!(
+                                "Unknown value for pane-frames: {} (expected on or off)",
+                                value
+                            ))
+                        },
+                    }
+                },
+                "status-bar" => match BarVisibility::from_str(value)? {
+                    BarVisibility::Compact => {
+                        return Err("The status-bar cannot be compact, use tab-bar=compact".into())
+                    },
+                    visibility => ui_settings.status_bar = Some(visibility),
+                },
+                "tab-bar" => ui_settings.tab_bar = Some(BarVisibility::from_str(value)?),
+                _ => {
+                    return Err(format!(
+                        "Unknown UI element: {} (expected pane-frames, status-bar or tab-bar)",
+                        element
+                    ))
+                },
+            }
+        }
+        if ui_settings == UiSettings::default() {
+            return Err("No UI elements to set".into());
+        }
+        Ok(ui_settings)
+    }
+}
+
+impl fmt::Display for UiSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut settings = vec![];
+        if let Some(pane_frames) = self.pane_frames {
+            settings.push(format!(
+                "pane-frames={}",
+                if pane_frames { "on" } else { "off" }
+            ));
+        }
+        if let Some(status_bar) = self.status_bar {
+            settings.push(format!("status-bar={}", status_bar));
+        }
+        if let Some(tab_bar) = self.tab_bar {
+            settings.push(format!("tab-bar={}", tab_bar));
+        }
+        write!(f, "{}", settings.join(" "))
+    }
+}
+
+/// A tab addressed either by its 1-based index or by its name
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TabTarget {
+    Index(usize),
+    Name(String),
+}
+
+impl fmt::Display for TabTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TabTarget::Index(index) => write!(f, "with index: {}", index),
+            TabTarget::Name(name) => write!(f, "named: {}", name),
+        }
+    }
+}
+
 // As these actions are bound to the default config, please
 // do take care when refactoring - or renaming.
 // They might need to be adjusted in the default config
@@ -106,6 +231,8 @@ pub enum Action {
     Write(Option<KeyWithModifier>, Vec<u8>, bool), // bool -> is_kitty_keyboard_protocol
     /// Write Characters to the terminal.
     WriteChars(String),
+    /// Write raw bytes to the focused pane, or to the pane with the given id.
+    WriteRaw(Vec<u8>, Option<PaneId>),
     /// Switch to the specified input mode.
     SwitchToMode(InputMode),
     /// Switch all connected clients to the specified input mode.
@@ -125,8 +252,8 @@ pub enum Action {
     MovePaneBackwards,
     /// Clear all buffers of a current screen
     ClearScreen,
-    /// Dumps the screen to a file
-    DumpScreen(String, bool),
+    /// Dumps the screen to a file, optionally with the full scrollback and the ANSI styles
+    DumpScreen(String, bool, bool),
     /// Dumps
     DumpLayout,
     /// Scroll up in focus pane.
@@ -159,6 +286,9 @@ pub enum Action {
     /// Open a new pane in the specified direction (relative to focus).
     /// If no direction is specified, will try to use the biggest available space.
     NewPane(Option<Direction>, Option<String>, bool), // String is an optional pane name
+    /// Open a new pane running the same command as the focused pane (or the same shell) in the
+    /// same cwd, in the specified direction or the biggest available space
+    DuplicatePane(Option<Direction>),
     /// Open the file in a new pane using the default editor, bool -> start suppressed
     EditFile(
         OpenFilePayload,
@@ -187,6 +317,9 @@ pub enum Action {
     ToggleFloatingPanes,
     /// Close the focus pane.
     CloseFocus,
+    /// Open the terminal pane most recently closed in the current tab again where it was, with
+    /// its scrollback, command and cwd
+    UndoClosePane,
     PaneNameInput(Vec<u8>),
     UndoRenamePane,
     /// Create a new tab, optionally with a specified tab layout.
@@ -198,6 +331,9 @@ pub enum Action {
         Option<String>,
         bool, // should_change_focus_to_new_tab
     ), // the String is the tab name
+    /// Create new tabs from the layout with this name, looked up in the layout dir when the
+    /// action runs (layout name, tab name, cwd)
+    NewTabFromLayout(String, Option<String>, Option<PathBuf>),
     /// Do nothing.
     NoOp,
     /// Go to the next tab.
@@ -230,6 +366,8 @@ pub enum Action {
     SkipConfirm(Box<Action>),
     /// Search for String
     SearchInput(Vec<u8>),
+    /// A key of the keyboard selection of scroll mode (eg. `v`, `w` or `y`)
+    CopyModeInput(Vec<u8>),
     /// Search for something
     Search(SearchDirection),
     /// Toggle case sensitivity of search
@@ -292,10 +430,170 @@ pub enum Action {
         cwd: Option<PathBuf>,
         pane_title: Option<String>,
     },
-    ListClients,
+    ListClients {
+        json: bool,
+    },
     TogglePanePinned,
+    /// Make the focused floating pane global to the session, following the focused tab, or
+    /// return it to the tab it is in
+    ToggleFloatingPaneScope,
     StackPanes(Vec<PaneId>),
     ChangeFloatingPaneCoordinates(PaneId, FloatingPaneCoordinates),
+    /// Toggle handling mouse events in zellij for the focused pane, even if its application
+    /// requested mouse reporting
+    ToggleMouseReportingOverride,
+    /// Toggle sending all keys to the focused pane without interpreting them as keybindings,
+    /// except for the key bound to this action which toggles it back off
+    ToggleRawPassthrough,
+    /// Toggle muting bells in all panes of the session
+    ToggleDoNotDisturb,
+    /// Switch the focused tab to the swap layout with this name
+    SelectSwapLayout(String),
+    /// Query the swap layout names of the focused tab
+    QuerySwapLayoutNames,
+    /// Change UI elements (pane frames, status bar, tab bar) of the session at runtime
+    SetUi(UiSettings),
+    /// Close the pane with this id, in whichever tab it is
+    ClosePaneWithId(PaneId),
+    /// Rename the pane with this id, in whichever tab it is
+    RenamePaneWithId(PaneId, String),
+    /// Toggle fullscreen for the pane with this id
+    TogglePaneIdFullscreen(PaneId),
+    /// Focus the pane with this id, switching to its tab if needed
+    FocusPaneWithId(PaneId),
+    /// Close the tab with this name
+    CloseTabWithName(String),
+    /// Rename the tab with this name (current name, new name)
+    RenameTabWithName(String, String),
+    /// List the panes of all tabs
+    ListPanes {
+        json: bool,
+    },
+    /// Attach a note describing the session to its metadata, an empty note removes it
+    SetSessionNote(String),
+    /// Set (Some) or remove (None) environment variables of the session, which the panes created
+    /// afterwards inherit
+    SetSessionEnv(Vec<(String, Option<String>)>),
+    /// Set the default cwd of the focused tab, where the panes opened in it from now on start
+    SetTabCwd(PathBuf),
+    /// Move the focused (or a specific) pane to another tab
+    MovePaneToTab(Option<PaneId>, TabTarget),
+    /// Move the focused tab to this 1-based index, shifting the tabs in between
+    MoveTabToIndex(usize),
+    /// Move the only pane of the tab at this 1-based index next to the focused pane, splitting it
+    /// in this direction (or wherever a new pane would go), and close that tab
+    JoinTab(usize, Option<Direction>),
+    /// Move the focused terminal pane, with its running process and scrollback, to another
+    /// session
+    MovePaneToSession(String),
+    /// Show the focused terminal pane in another tab too, as a view of the same pty
+    LinkPaneToTab(TabTarget),
+    /// Declare a port listened on by a service of the session (port, name, pane running the
+    /// service)
+    DeclarePort(u16, Option<String>, Option<PaneId>),
+    /// Remove a declared port (port, pane running the service it was declared for)
+    UndeclarePort(u16, Option<PaneId>),
+    /// List the declared ports
+    ListPorts {
+        json: bool,
+    },
+    /// Dump the state of the session as json
+    DumpSession,
+    /// Move the viewport of the focused (or a specific) pane to the next (or previous) match of a
+    /// regular expression in its scrollback
+    SearchScrollback {
+        pattern: String,
+        backwards: bool,
+        pane_id: Option<PaneId>,
+    },
+    /// List the lines matching a regular expression in the scrollback of the panes of the focused
+    /// tab (or of all tabs)
+    SearchPanes {
+        pattern: String,
+        all_tabs: bool,
+        json: bool,
+    },
+    /// Move the viewport of the focused (or a specific) pane to this line of its scrollback, as
+    /// numbered by SearchPanes
+    ScrollToLine {
+        line: usize,
+        pane_id: Option<PaneId>,
+    },
+    /// Start writing everything the focused (or a specific) pane outputs to a file in the
+    /// session's cache folder, or stop doing so
+    TogglePaneLogging {
+        pane_id: Option<PaneId>,
+    },
+    /// Run the command of the focused (or a specific) command pane again, in place, after it
+    /// exited
+    RerunCommandPane {
+        pane_id: Option<PaneId>,
+    },
+    /// Open a picker in the focused (or a specific) command pane, once its command exited, to run
+    /// one of the commands it ran before again, or to edit it before doing so
+    OpenCommandHistory {
+        pane_id: Option<PaneId>,
+    },
+    /// Mark the tab of the focused (or a specific) pane when the pane produces output while it is
+    /// not focused
+    ToggleActivityMonitor {
+        pane_id: Option<PaneId>,
+        notify: bool,
+    },
+    /// Mark the tab of the focused (or a specific) pane when the pane produces no output for this
+    /// many seconds while it is not focused
+    ToggleSilenceMonitor {
+        pane_id: Option<PaneId>,
+        seconds: u64,
+        notify: bool,
+    },
+    /// Add the focused (or a specific) pane to a named group, or remove it if it's already in it
+    TogglePaneInGroup {
+        group: String,
+        pane_id: Option<PaneId>,
+    },
+    /// Apply an action to all the panes of a group, in all tabs
+    ApplyToPaneGroup {
+        group: String,
+        action: PaneGroupAction,
+    },
+    /// Write characters (eg. a command line ending with a newline) to every terminal pane of the
+    /// focused tab, or of a group (in all tabs) if one is given
+    MultiExec {
+        chars: String,
+        group: Option<String>,
+        exclude_focused: bool,
+    },
+    /// Temporarily expand all the panes of the stack of the focused pane, each taking an even
+    /// share of its space, or stack them back
+    ToggleExpandStack,
+    /// Swap the focused stacked pane with the one above or below it in its stack
+    MovePaneInStack(Direction),
+    /// Focus the stacked pane of the focused tab whose title best matches this (fuzzy) query
+    FocusStackedPaneByTitle(String),
+    /// Run a command in the focused terminal pane in place of its current one, stopping it first
+    /// if it is still running, bool -> keep the scrollback of the pane
+    ReplacePaneCommand(RunCommandAction, bool),
+    /// Toggle ignoring the keys typed into the focused pane, so that a process running in it
+    /// cannot be typed into by accident
+    TogglePaneInputLock,
+    /// Scroll the focused pane to the previous prompt marked by its shell (OSC 133)
+    ScrollToPreviousPrompt,
+    /// Scroll the focused pane to the next prompt marked by its shell (OSC 133)
+    ScrollToNextPrompt,
+    /// Select the output of the last command the shell of the focused pane ran, as marked by it
+    /// (OSC 133)
+    SelectLastCommandOutput,
+}
+
+/// The actions that can be applied to all the panes of a group at once
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PaneGroupAction {
+    WriteChars(String),
+    Close,
+    Resize(Resize, Option<Direction>),
+    /// The color of the frames of the group's panes, `None` resets it
+    SetColor(Option<PaletteColor>),
 }
 
 impl Action {
@@ -317,18 +615,42 @@ impl Action {
         match cli_action {
             CliAction::Write { bytes } => Ok(vec![Action::Write(None, bytes, false)]),
             CliAction::WriteChars { chars } => Ok(vec![Action::WriteChars(chars)]),
+            CliAction::WriteRaw {
+                data,
+                base64: is_base64,
+                pane_id,
+            } => {
+                let bytes = if is_base64 {
+                    base64::decode(data.trim())
+                        .map_err(|e| format!("Failed to decode base64 bytes: {}", e))?
+                } else {
+                    decode_hex_bytes(&data)?
+                };
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::WriteRaw(bytes, pane_id)])
+            },
             CliAction::Resize { resize, direction } => Ok(vec![Action::Resize(resize, direction)]),
             CliAction::FocusNextPane => Ok(vec![Action::FocusNextPane]),
             CliAction::FocusPreviousPane => Ok(vec![Action::FocusPreviousPane]),
             CliAction::MoveFocus { direction } => Ok(vec![Action::MoveFocus(direction)]),
             CliAction::MoveFocusOrTab { direction } => Ok(vec![Action::MoveFocusOrTab(direction)]),
-            CliAction::MovePane { direction } => Ok(vec![Action::MovePane(direction)]),
+            CliAction::MovePane {
+                direction,
+                to_session,
+            } => match to_session {
+                Some(session_name) => Ok(vec![Action::MovePaneToSession(session_name)]),
+                None => Ok(vec![Action::MovePane(direction)]),
+            },
             CliAction::MovePaneBackwards => Ok(vec![Action::MovePaneBackwards]),
             CliAction::MoveTab { direction } => Ok(vec![Action::MoveTab(direction)]),
             CliAction::Clear => Ok(vec![Action::ClearScreen]),
-            CliAction::DumpScreen { path, full } => Ok(vec![Action::DumpScreen(
+            CliAction::DumpScreen { path, full, ansi } => Ok(vec![Action::DumpScreen(
                 path.as_os_str().to_string_lossy().into(),
                 full,
+                ansi,
             )]),
             CliAction::DumpLayout => Ok(vec![Action::DumpLayout]),
             CliAction::EditScrollback => Ok(vec![Action::EditScrollback]),
@@ -340,7 +662,12 @@ impl Action {
             CliAction::PageScrollDown => Ok(vec![Action::PageScrollDown]),
             CliAction::HalfPageScrollUp => Ok(vec![Action::HalfPageScrollUp]),
             CliAction::HalfPageScrollDown => Ok(vec![Action::HalfPageScrollDown]),
-            CliAction::ToggleFullscreen => Ok(vec![Action::ToggleFocusFullscreen]),
+            CliAction::ToggleFullscreen { pane_id } => match pane_id {
+                Some(pane_id) => Ok(vec![Action::TogglePaneIdFullscreen(parse_cli_pane_id(
+                    &pane_id,
+                )?)]),
+                None => Ok(vec![Action::ToggleFocusFullscreen]),
+            },
             CliAction::TogglePaneFrames => Ok(vec![Action::TogglePaneFrames]),
             CliAction::ToggleActiveSyncTab => Ok(vec![Action::ToggleActiveSyncTab]),
             CliAction::NewPane {
@@ -352,7 +679,10 @@ impl Action {
                 in_place,
                 name,
                 close_on_exit,
+                close_on_success,
+                keep_open,
                 start_suspended,
+                start_immediately,
                 configuration,
                 skip_plugin_cache,
                 x,
@@ -360,6 +690,16 @@ impl Action {
                 width,
                 height,
                 pinned,
+                capture_output,
+                tail,
+                stdin_file,
+                stdin,
+                log_output,
+                log_max_size,
+                log_max_files,
+                log_strip_ansi,
+                watch,
+                watch_debounce,
             } => {
                 let current_dir = get_current_dir();
                 // cwd should only be specified in a plugin alias if it was explicitly given to us,
@@ -422,8 +762,40 @@ impl Action {
                 } else if !command.is_empty() {
                     let mut command = command.clone();
                     let (command, args) = (PathBuf::from(command.remove(0)), command);
-                    let hold_on_start = start_suspended;
-                    let hold_on_close = !close_on_exit;
+                    // the flags of this invocation take precedence over the configured defaults
+                    let configured_options = config.as_ref().map(|c| &c.options);
+                    let on_exit = if close_on_exit {
+                        CommandPaneExit::Close
+                    } else if close_on_success {
+                        CommandPaneExit::CloseOnSuccess
+                    } else if keep_open {
+                        CommandPaneExit::Hold
+                    } else {
+                        configured_options
+                            .and_then(|o| o.command_pane_on_exit)
+                            .unwrap_or_default()
+                    };
+                    let hold_on_start = if start_suspended || start_immediately {
+                        start_suspended
+                    } else {
+                        configured_options
+                            .and_then(|o| o.start_command_panes_suspended)
+                            .unwrap_or(false)
+                    };
+                    let watch = CommandWatch::new(watch, watch_debounce);
+                    // a watched command is re-run in its pane, so the pane stays open when it exits
+                    let hold_on_close = on_exit != CommandPaneExit::Close || watch.is_some();
+                    let close_on_success =
+                        on_exit == CommandPaneExit::CloseOnSuccess && watch.is_none();
+                    let stdin_file = stdin_file.map(|stdin_file| current_dir.join(stdin_file));
+                    if let Some(stdin_file) = stdin_file.as_ref() {
+                        if !stdin_file.is_file() {
+                            return Err(format!(
+                                "Stdin file {} does not exist",
+                                stdin_file.display()
+                            ));
+                        }
+                    }
                     let run_command_action = RunCommandAction {
                         command,
                         args,
@@ -431,6 +803,20 @@ impl Action {
                         direction,
                         hold_on_close,
                         hold_on_start,
+                        close_on_success,
+                        capture_output: capture_output.then(|| OutputCapture {
+                            tail_lines: tail,
+                            client_id: None,
+                        }),
+                        stdin_file,
+                        stdin_pipe: stdin.then(|| StdinPipe { client_id: None }),
+                        log_output: log_output.map(|log_output| OutputLog {
+                            path: current_dir.join(log_output),
+                            max_size: log_max_size,
+                            max_files: log_max_files.unwrap_or(1),
+                            strip_ansi: log_strip_ansi,
+                        }),
+                        watch,
                         ..Default::default()
                     };
                     if floating {
@@ -475,7 +861,10 @@ impl Action {
                 height,
                 pinned,
             } => {
-                let mut file = file;
+                let (mut file, line_number) = match line_number {
+                    Some(line_number) => (file, Some(line_number)),
+                    None => OpenFilePayload::split_line_number(file),
+                };
                 let current_dir = get_current_dir();
                 let cwd = cwd
                     .map(|cwd| current_dir.join(cwd))
@@ -500,33 +889,61 @@ impl Action {
             },
             CliAction::TogglePaneEmbedOrFloating => Ok(vec![Action::TogglePaneEmbedOrFloating]),
             CliAction::ToggleFloatingPanes => Ok(vec![Action::ToggleFloatingPanes]),
-            CliAction::ClosePane => Ok(vec![Action::CloseFocus]),
-            CliAction::RenamePane { name } => Ok(vec![
-                Action::UndoRenamePane,
-                Action::PaneNameInput(name.as_bytes().to_vec()),
-            ]),
+            CliAction::ClosePane { pane_id } => match pane_id {
+                Some(pane_id) => Ok(vec![Action::ClosePaneWithId(parse_cli_pane_id(&pane_id)?)]),
+                None => Ok(vec![Action::CloseFocus]),
+            },
+            CliAction::RenamePane { name, pane_id } => match pane_id {
+                Some(pane_id) => Ok(vec![Action::RenamePaneWithId(
+                    parse_cli_pane_id(&pane_id)?,
+                    name,
+                )]),
+                None => Ok(vec![
+                    Action::UndoRenamePane,
+                    Action::PaneNameInput(name.as_bytes().to_vec()),
+                ]),
+            },
             CliAction::UndoRenamePane => Ok(vec![Action::UndoRenamePane]),
+            CliAction::UndoClosePane => Ok(vec![Action::UndoClosePane]),
+            CliAction::DuplicatePane { direction } => Ok(vec![Action::DuplicatePane(direction)]),
             CliAction::GoToNextTab => Ok(vec![Action::GoToNextTab]),
             CliAction::GoToPreviousTab => Ok(vec![Action::GoToPreviousTab]),
-            CliAction::CloseTab => Ok(vec![Action::CloseTab]),
+            CliAction::CloseTab { tab_name } => match tab_name {
+                Some(tab_name) => Ok(vec![Action::CloseTabWithName(tab_name)]),
+                None => Ok(vec![Action::CloseTab]),
+            },
             CliAction::GoToTab { index } => Ok(vec![Action::GoToTab(index)]),
             CliAction::GoToTabName { name, create } => Ok(vec![Action::GoToTabName(name, create)]),
-            CliAction::RenameTab { name } => Ok(vec![
-                Action::TabNameInput(vec![0]),
-                Action::TabNameInput(name.as_bytes().to_vec()),
-            ]),
+            CliAction::RenameTab { name, tab_name } => match tab_name {
+                Some(tab_name) => Ok(vec![Action::RenameTabWithName(tab_name, name)]),
+                None => Ok(vec![
+                    Action::TabNameInput(vec![0]),
+                    Action::TabNameInput(name.as_bytes().to_vec()),
+                ]),
+            },
             CliAction::UndoRenameTab => Ok(vec![Action::UndoRenameTab]),
             CliAction::NewTab {
                 name,
                 layout,
                 layout_dir,
+                template,
                 cwd,
+                vars,
             } => {
                 let current_dir = get_current_dir();
                 let cwd = cwd
                     .map(|cwd| current_dir.join(cwd))
                     .or_else(|| Some(current_dir));
-                if let Some(layout_path) = layout {
+                let templates = config
+                    .as_ref()
+                    .map(|c| c.templates.clone())
+                    .unwrap_or_default();
+                let layout = if let Some(template_name) = template {
+                    Some(
+                        Layout::from_template_name(&template_name, &templates, cwd)
+                            .map_err(|e| format!("Failed to load template: {}", e))?,
+                    )
+                } else if let Some(layout_path) = layout {
                     let layout_dir = layout_dir
                         .or_else(|| config.and_then(|c| c.options.layout_dir))
                         .or_else(|| get_layout_dir(find_default_config_dir()));
@@ -551,7 +968,34 @@ impl Action {
                         Layout::stringified_from_path_or_default(Some(&layout_path), layout_dir)
                             .map_err(|e| format!("Failed to load layout: {}", e))?
                     };
-                    let mut layout = Layout::from_str(&raw_layout, path_to_raw_layout, swap_layouts.as_ref().map(|(f, p)| (f.as_str(), p.as_str())), cwd).map_err(|e| {
+                    let variables: BTreeMap<String, String> = vars.into_iter().collect();
+                    let substitute_variables = |raw_layout: &str, path: String| {
+                        Layout::substitute_variables(raw_layout, &variables).map_err(|e| match e {
+                            ConfigError::KdlError(kdl_error) => {
+                                let error = kdl_error.add_src(path, raw_layout.to_owned());
+                                let report: Report = error.into();
+                                format!("{:?}", report)
+                            },
+                            e => format!("Failed to load layout: {}", e),
+                        })
+                    };
+                    let raw_layout = substitute_variables(
+                        &raw_layout,
+                        layout_path
+                            .as_path()
+                            .as_os_str()
+                            .to_string_lossy()
+                            .to_string(),
+                    )?;
+                    let swap_layouts = match swap_layouts {
+                        Some((swap_layout_path, raw_swap_layout)) => {
+                            let raw_swap_layout =
+                                substitute_variables(&raw_swap_layout, swap_layout_path.clone())?;
+                            Some((swap_layout_path, raw_swap_layout))
+                        },
+                        None => None,
+                    };
+                    let mut layout = Layout::from_kdl_with_templates(&raw_layout, Some(path_to_raw_layout), swap_layouts.as_ref().map(|(f, p)| (f.as_str(), p.as_str())), cwd, &templates).map_err(|e| {
                         let stringified_error = match e {
                             ConfigError::KdlError(kdl_error) => {
                                 let error = kdl_error.add_src(layout_path.as_path().as_os_str().to_string_lossy().to_string(), String::from(raw_layout));
@@ -586,6 +1030,11 @@ impl Action {
                     if should_start_layout_commands_suspended {
                         layout.recursively_add_start_suspended_including_template(Some(true));
                     }
+                    Some(layout)
+                } else {
+                    None
+                };
+                if let Some(layout) = layout {
                     let mut tabs = layout.tabs();
                     if !tabs.is_empty() {
                         let swap_tiled_layouts = Some(layout.swap_tiled_layouts.clone());
@@ -735,8 +1184,213 @@ impl Action {
                     skip_cache,
                 }])
             },
-            CliAction::ListClients => Ok(vec![Action::ListClients]),
+            CliAction::ListClients { json } => Ok(vec![Action::ListClients { json }]),
+            CliAction::ListPanes { json } => Ok(vec![Action::ListPanes { json }]),
+            CliAction::FocusPane { pane_id, line } => {
+                let pane_id = parse_cli_pane_id(&pane_id)?;
+                let mut actions = vec![Action::FocusPaneWithId(pane_id)];
+                if let Some(line) = line {
+                    actions.push(Action::ScrollToLine {
+                        line,
+                        pane_id: Some(pane_id),
+                    });
+                }
+                Ok(actions)
+            },
+            CliAction::SetSessionNote { note } => Ok(vec![Action::SetSessionNote(note)]),
+            CliAction::SetEnv { variables, unset } => {
+                let mut env_vars = vec![];
+                for variable in variables {
+                    let (name, value) = variable.split_once('=').ok_or_else(|| {
+                        format!("Malformed variable: {}, expecting NAME=VALUE", variable)
+                    })?;
+                    env_vars.push((
+                        parse_cli_env_var_name(name)?,
+                        Some(parse_cli_env_var_value(value)?),
+                    ));
+                }
+                for name in unset {
+                    env_vars.push((parse_cli_env_var_name(&name)?, None));
+                }
+                if env_vars.is_empty() {
+                    return Err("No variables to set or unset".to_owned());
+                }
+                Ok(vec![Action::SetSessionEnv(env_vars)])
+            },
+            CliAction::SetTabCwd { cwd } => {
+                let cwd = get_current_dir().join(cwd);
+                Ok(vec![Action::SetTabCwd(cwd)])
+            },
+            CliAction::MovePaneToTab { tab, name, pane_id } => {
+                let tab = match tab.parse::<usize>() {
+                    Ok(index) if !name => TabTarget::Index(index),
+                    _ => TabTarget::Name(tab),
+                };
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::MovePaneToTab(pane_id, tab)])
+            },
+            CliAction::LinkPaneToTab { tab, name } => {
+                let tab = match tab.parse::<usize>() {
+                    Ok(index) if !name => TabTarget::Index(index),
+                    _ => TabTarget::Name(tab),
+                };
+                Ok(vec![Action::LinkPaneToTab(tab)])
+            },
+            CliAction::MoveTabToIndex { index } => Ok(vec![Action::MoveTabToIndex(index)]),
+            CliAction::BreakPane => Ok(vec![Action::BreakPane]),
+            CliAction::JoinTab { index, direction } => Ok(vec![Action::JoinTab(index, direction)]),
+            CliAction::DeclarePort {
+                port,
+                name,
+                pane_id,
+            } => {
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::DeclarePort(port, name, pane_id)])
+            },
+            CliAction::UndeclarePort { port, pane_id } => {
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::UndeclarePort(port, pane_id)])
+            },
+            CliAction::ListPorts { json } => Ok(vec![Action::ListPorts { json }]),
+            CliAction::DumpSession => Ok(vec![Action::DumpSession]),
+            CliAction::Search {
+                pattern,
+                backwards,
+                pane_id,
+            } => {
+                if let Err(e) = Regex::new(&pattern) {
+                    return Err(format!("Invalid pattern: {}", e));
+                }
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::SearchScrollback {
+                    pattern,
+                    backwards,
+                    pane_id,
+                }])
+            },
+            CliAction::SearchPanes {
+                pattern,
+                session,
+                json,
+            } => {
+                if let Err(e) = Regex::new(&pattern) {
+                    return Err(format!("Invalid pattern: {}", e));
+                }
+                Ok(vec![Action::SearchPanes {
+                    pattern,
+                    all_tabs: session,
+                    json,
+                }])
+            },
+            CliAction::TogglePaneLogging { pane_id } => {
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::TogglePaneLogging { pane_id }])
+            },
+            CliAction::RerunCommandPane { pane_id } => {
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::RerunCommandPane { pane_id }])
+            },
+            CliAction::CommandHistory { pane_id } => {
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::OpenCommandHistory { pane_id }])
+            },
+            CliAction::ToggleActivityMonitor { pane_id, notify } => {
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::ToggleActivityMonitor { pane_id, notify }])
+            },
+            CliAction::ToggleSilenceMonitor {
+                seconds,
+                pane_id,
+                notify,
+            } => {
+                if seconds == 0 {
+                    return Err("The silence interval must be at least one second".to_owned());
+                }
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::ToggleSilenceMonitor {
+                    pane_id,
+                    seconds,
+                    notify,
+                }])
+            },
+            CliAction::TogglePaneInGroup { group, pane_id } => {
+                let pane_id = match pane_id {
+                    Some(pane_id) => Some(parse_cli_pane_id(&pane_id)?),
+                    None => None,
+                };
+                Ok(vec![Action::TogglePaneInGroup { group, pane_id }])
+            },
+            CliAction::WriteCharsToGroup { group, chars } => Ok(vec![Action::ApplyToPaneGroup {
+                group,
+                action: PaneGroupAction::WriteChars(chars),
+            }]),
+            CliAction::CloseGroup { group } => Ok(vec![Action::ApplyToPaneGroup {
+                group,
+                action: PaneGroupAction::Close,
+            }]),
+            CliAction::ResizeGroup {
+                group,
+                resize,
+                direction,
+            } => Ok(vec![Action::ApplyToPaneGroup {
+                group,
+                action: PaneGroupAction::Resize(resize, direction),
+            }]),
+            CliAction::SetGroupColor { group, color } => {
+                let color = match color {
+                    Some(color) => Some(parse_cli_color(&color)?),
+                    None => None,
+                };
+                Ok(vec![Action::ApplyToPaneGroup {
+                    group,
+                    action: PaneGroupAction::SetColor(color),
+                }])
+            },
+            CliAction::MultiExec {
+                command,
+                group,
+                exclude_focused,
+                no_enter,
+            } => {
+                let mut chars = command_line(&command);
+                if !no_enter {
+                    chars.push('\r');
+                }
+                Ok(vec![Action::MultiExec {
+                    chars,
+                    group,
+                    exclude_focused,
+                }])
+            },
             CliAction::TogglePanePinned => Ok(vec![Action::TogglePanePinned]),
+            CliAction::ToggleFloatingPaneScope => Ok(vec![Action::ToggleFloatingPaneScope]),
             CliAction::StackPanes { pane_ids } => {
                 let mut malformed_ids = vec![];
                 let pane_ids = pane_ids
@@ -787,6 +1441,72 @@ impl Action {
                     }
                 }
             },
+            CliAction::ToggleMouseReportingOverride => {
+                Ok(vec![Action::ToggleMouseReportingOverride])
+            },
+            CliAction::ToggleDoNotDisturb => Ok(vec![Action::ToggleDoNotDisturb]),
+            CliAction::SelectSwapLayout { name } => Ok(vec![Action::SelectSwapLayout(name)]),
+            CliAction::QuerySwapLayoutNames => Ok(vec![Action::QuerySwapLayoutNames]),
+            CliAction::SetUi { settings } => {
+                let ui_settings = UiSettings::from_str(&settings.join(" "))?;
+                Ok(vec![Action::SetUi(ui_settings)])
+            },
+            CliAction::ToggleExpandStack => Ok(vec![Action::ToggleExpandStack]),
+            CliAction::MovePaneInStack { direction } => {
+                if direction.is_vertical() {
+                    Ok(vec![Action::MovePaneInStack(direction)])
+                } else {
+                    Err(format!(
+                        "Panes can only be moved up or down in a stack, not {}",
+                        direction
+                    ))
+                }
+            },
+            CliAction::FocusStackedPane { title } => {
+                Ok(vec![Action::FocusStackedPaneByTitle(title.join(" "))])
+            },
+            CliAction::ReplacePaneCommand {
+                mut command,
+                cwd,
+                keep_scrollback,
+                close_on_exit,
+            } => {
+                let current_dir = get_current_dir();
+                let cwd = cwd
+                    .map(|cwd| current_dir.join(cwd))
+                    .or_else(|| Some(current_dir));
+                let run_command_action = RunCommandAction {
+                    command: PathBuf::from(command.remove(0)),
+                    args: command,
+                    cwd,
+                    hold_on_close: !close_on_exit,
+                    ..Default::default()
+                };
+                Ok(vec![Action::ReplacePaneCommand(
+                    run_command_action,
+                    keep_scrollback,
+                )])
+            },
+            CliAction::TogglePaneInputLock => Ok(vec![Action::TogglePaneInputLock]),
+            CliAction::ScrollToPreviousPrompt => Ok(vec![Action::ScrollToPreviousPrompt]),
+            CliAction::ScrollToNextPrompt => Ok(vec![Action::ScrollToNextPrompt]),
+            CliAction::SelectLastCommandOutput => Ok(vec![Action::SelectLastCommandOutput]),
+        }
+    }
+    pub fn captures_output(&self) -> bool {
+        match self {
+            Action::NewFloatingPane(Some(run_command), ..)
+            | Action::NewTiledPane(_, Some(run_command), _)
+            | Action::NewInPlacePane(Some(run_command), _) => run_command.capture_output.is_some(),
+            _ => false,
+        }
+    }
+    pub fn pipes_stdin(&self) -> bool {
+        match self {
+            Action::NewFloatingPane(Some(run_command), ..)
+            | Action::NewTiledPane(_, Some(run_command), _)
+            | Action::NewInPlacePane(Some(run_command), _) => run_command.stdin_pipe.is_some(),
+            _ => false,
         }
     }
     pub fn launches_plugin(&self, plugin_url: &str) -> bool {
@@ -808,6 +1528,95 @@ impl Action {
     }
 }
 
+fn parse_cli_pane_id(pane_id: &str) -> Result<PaneId, String> {
+    PaneId::from_str(pane_id).map_err(|_| {
+        format!(
+            "Malformed pane id: {}, expecting either a bare integer (eg. 1), a terminal pane id (eg. terminal_1) or a plugin pane id (eg. plugin_1)",
+            pane_id
+        )
+    })
+}
+
+fn parse_cli_env_var_name(name: &str) -> Result<String, String> {
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        Err(format!("Malformed variable name: {:?}", name))
+    } else {
+        Ok(name.to_owned())
+    }
+}
+
+fn parse_cli_env_var_value(value: &str) -> Result<String, String> {
+    if value.contains('\0') {
+        Err(format!("Malformed variable value: {:?}", value))
+    } else {
+        Ok(value.to_owned())
+    }
+}
+
+fn parse_cli_color(color: &str) -> Result<PaletteColor, String> {
+    let malformed_color = || {
+        format!(
+            "Malformed color: {}, expecting either a hex color (eg. #ff8800) or a 256 color index (eg. 208)",
+            color
+        )
+    };
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 => {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+                    .ok_or_else(malformed_color)
+            };
+            Ok(PaletteColor::Rgb((channel(0)?, channel(2)?, channel(4)?)))
+        },
+        Some(_) => Err(malformed_color()),
+        None => color
+            .parse::<u8>()
+            .map(PaletteColor::EightBit)
+            .map_err(|_| malformed_color()),
+    }
+}
+
+// a single argument is typed as it is, as a whole command line (or key sequence), several are
+// quoted for the shell so that each of them stays one word
+fn command_line(command: &[String]) -> String {
+    match command {
+        [command_line] => command_line.to_owned(),
+        words => words
+            .iter()
+            .map(|word| shell_quote(word))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn shell_quote(word: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%^".contains(c);
+    if !word.is_empty() && word.chars().all(is_safe) {
+        word.to_owned()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+fn decode_hex_bytes(data: &str) -> Result<Vec<u8>, String> {
+    let hex_digits: Vec<char> = data.chars().filter(|c| !c.is_whitespace()).collect();
+    if hex_digits.len() % 2 != 0 {
+        return Err(format!(
+            "Failed to decode hex bytes: odd number of hex digits in {:?}",
+            data
+        ));
+    }
+    hex_digits
+        .chunks(2)
+        .map(|pair| {
+            let byte: String = pair.iter().collect();
+            u8::from_str_radix(&byte, 16)
+                .map_err(|_| format!("Failed to decode hex bytes: invalid hex byte {:?}", byte))
+        })
+        .collect()
+}
+
 impl From<OnForceClose> for Action {
     fn from(ofc: OnForceClose) -> Action {
         match ofc {
@@ -816,3 +1625,8 @@ impl From<OnForceClose> for Action {
         }
     }
 }
+
+// The unit test location.
+#[cfg(test)]
+#[path = "./unit/actions_test.rs"]
+mod actions_test;
@@ -1,5 +1,5 @@
 //! Trigger a command
-use crate::data::{Direction, OriginatingPlugin};
+use crate::data::{ClientId, Direction, OriginatingPlugin};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -43,6 +43,28 @@ impl OpenFilePayload {
         self.originating_plugin = Some(originating_plugin);
         self
     }
+    /// Split a trailing line number off a path in the `path:line` or `path:line:column` format
+    /// (eg. as printed by compilers), unless a file with this exact name exists
+    pub fn split_line_number(path: PathBuf) -> (PathBuf, Option<usize>) {
+        if path.exists() {
+            return (path, None);
+        }
+        let split_number = |path: &str| -> Option<(String, usize)> {
+            let (rest, number) = path.rsplit_once(':')?;
+            Some((rest.to_owned(), number.parse::<usize>().ok()?))
+        };
+        // in the `path:line:column` format, the column is ignored
+        let split = path
+            .to_str()
+            .and_then(split_number)
+            .map(|(file, number)| split_number(&file).unwrap_or((file, number)));
+        match split {
+            Some((file, line_number)) if !file.is_empty() => {
+                (PathBuf::from(file), Some(line_number))
+            },
+            _ => (path, None),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Default, Serialize, PartialEq, Eq)]
@@ -57,8 +79,89 @@ pub struct RunCommand {
     pub hold_on_close: bool,
     #[serde(default)]
     pub hold_on_start: bool,
+    /// Close the pane even though it holds on close if the command exits successfully
+    #[serde(default)]
+    pub close_on_success: bool,
     #[serde(default)]
     pub originating_plugin: Option<OriginatingPlugin>,
+    #[serde(default)]
+    pub capture_output: Option<OutputCapture>,
+    /// A file whose contents are written to the command's STDIN when it starts
+    #[serde(default)]
+    pub stdin_file: Option<PathBuf>,
+    /// The STDIN of a CLI client, written to the command's STDIN once it starts
+    #[serde(default)]
+    pub stdin_pipe: Option<StdinPipe>,
+    #[serde(default)]
+    pub log_output: Option<OutputLog>,
+    /// Re-run the command whenever files matching these patterns change
+    #[serde(default)]
+    pub watch: Option<CommandWatch>,
+}
+
+/// The output of a command pane being streamed to a CLI client (eg. `zellij run --capture-output`)
+#[derive(Clone, Debug, Deserialize, Default, Serialize, PartialEq, Eq)]
+pub struct OutputCapture {
+    /// Only send the last N lines of output once the command exits, instead of streaming it
+    pub tail_lines: Option<usize>,
+    /// The client receiving the output, this is filled in by the server
+    pub client_id: Option<ClientId>,
+}
+
+/// The STDIN of a CLI client being written to a command pane (eg. `cat log | zellij run --stdin
+/// -- less`)
+#[derive(Clone, Debug, Deserialize, Default, Serialize, PartialEq, Eq)]
+pub struct StdinPipe {
+    /// The client whose STDIN is piped, this is filled in by the server
+    pub client_id: Option<ClientId>,
+}
+
+/// A file on the server host to which the output of a command pane is written as it arrives (eg.
+/// `zellij run --log-output build.log`)
+#[derive(Clone, Debug, Deserialize, Default, Serialize, PartialEq, Eq)]
+pub struct OutputLog {
+    pub path: PathBuf,
+    /// Rotate the file once it grows beyond this size in bytes
+    pub max_size: Option<u64>,
+    /// How many rotated files (eg. build.log.1, build.log.2) to keep, older ones are deleted
+    pub max_files: usize,
+    /// Strip ANSI escape sequences (colors, cursor movement, etc.) from the logged output
+    pub strip_ansi: bool,
+}
+
+pub const DEFAULT_WATCH_DEBOUNCE_MS: u64 = 300;
+
+/// Files under the cwd of a command pane whose changes re-run its command (eg. `zellij run --watch
+/// '**/*.rs' -- cargo test`)
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CommandWatch {
+    /// Glob patterns (`*`, `**` and `?`), matched against paths relative to the cwd, patterns
+    /// without a `/` are matched against file names in any folder
+    pub patterns: Vec<String>,
+    /// Wait for changes to settle for this long before re-running the command
+    pub debounce_ms: u64,
+}
+
+impl Default for CommandWatch {
+    fn default() -> Self {
+        CommandWatch {
+            patterns: vec![],
+            debounce_ms: DEFAULT_WATCH_DEBOUNCE_MS,
+        }
+    }
+}
+
+impl CommandWatch {
+    pub fn new(patterns: Vec<String>, debounce_ms: Option<u64>) -> Option<Self> {
+        if patterns.is_empty() {
+            None
+        } else {
+            Some(CommandWatch {
+                patterns,
+                debounce_ms: debounce_ms.unwrap_or(DEFAULT_WATCH_DEBOUNCE_MS),
+            })
+        }
+    }
 }
 
 impl std::fmt::Display for RunCommand {
@@ -92,8 +195,37 @@ pub struct RunCommandAction {
     pub hold_on_close: bool,
     #[serde(default)]
     pub hold_on_start: bool,
+    /// Close the pane even though it holds on close if the command exits successfully
+    #[serde(default)]
+    pub close_on_success: bool,
     #[serde(default)]
     pub originating_plugin: Option<OriginatingPlugin>,
+    #[serde(default)]
+    pub capture_output: Option<OutputCapture>,
+    /// A file whose contents are written to the command's STDIN when it starts
+    #[serde(default)]
+    pub stdin_file: Option<PathBuf>,
+    /// The STDIN of a CLI client, written to the command's STDIN once it starts
+    #[serde(default)]
+    pub stdin_pipe: Option<StdinPipe>,
+    #[serde(default)]
+    pub log_output: Option<OutputLog>,
+    /// Re-run the command whenever files matching these patterns change
+    #[serde(default)]
+    pub watch: Option<CommandWatch>,
+}
+
+impl RunCommandAction {
+    /// If this command's output is captured or its STDIN is piped, do so with the given client
+    pub fn with_requesting_client(mut self, client_id: ClientId) -> Self {
+        if let Some(capture_output) = self.capture_output.as_mut() {
+            capture_output.client_id = Some(client_id);
+        }
+        if let Some(stdin_pipe) = self.stdin_pipe.as_mut() {
+            stdin_pipe.client_id = Some(client_id);
+        }
+        self
+    }
 }
 
 impl From<RunCommandAction> for RunCommand {
@@ -104,7 +236,13 @@ impl From<RunCommandAction> for RunCommand {
             cwd: action.cwd,
             hold_on_close: action.hold_on_close,
             hold_on_start: action.hold_on_start,
+            close_on_success: action.close_on_success,
             originating_plugin: action.originating_plugin,
+            capture_output: action.capture_output,
+            stdin_file: action.stdin_file,
+            stdin_pipe: action.stdin_pipe,
+            log_output: action.log_output,
+            watch: action.watch,
         }
     }
 }
@@ -118,7 +256,13 @@ impl From<RunCommand> for RunCommandAction {
             direction: None,
             hold_on_close: run_command.hold_on_close,
             hold_on_start: run_command.hold_on_start,
+            close_on_success: run_command.close_on_success,
             originating_plugin: run_command.originating_plugin,
+            capture_output: run_command.capture_output,
+            stdin_file: run_command.stdin_file,
+            stdin_pipe: run_command.stdin_pipe,
+            log_output: run_command.log_output,
+            watch: run_command.watch,
         }
     }
 }
@@ -135,3 +279,32 @@ impl RunCommand {
         self
     }
 }
+
+#[cfg(test)]
+mod command_test {
+    use super::*;
+
+    #[test]
+    fn line_number_is_split_off_file_path() {
+        assert_eq!(
+            OpenFilePayload::split_line_number(PathBuf::from("/i/do/not/exist.rs:42")),
+            (PathBuf::from("/i/do/not/exist.rs"), Some(42)),
+            "path:line"
+        );
+        assert_eq!(
+            OpenFilePayload::split_line_number(PathBuf::from("/i/do/not/exist.rs:42:7")),
+            (PathBuf::from("/i/do/not/exist.rs"), Some(42)),
+            "path:line:column"
+        );
+        assert_eq!(
+            OpenFilePayload::split_line_number(PathBuf::from("/i/do/not/exist.rs")),
+            (PathBuf::from("/i/do/not/exist.rs"), None),
+            "path without a line number"
+        );
+        assert_eq!(
+            OpenFilePayload::split_line_number(PathBuf::from("/i/do/not/exist:rs")),
+            (PathBuf::from("/i/do/not/exist:rs"), None),
+            "path with a colon"
+        );
+    }
+}
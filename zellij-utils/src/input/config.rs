@@ -9,8 +9,10 @@ use thiserror::Error;
 
 use std::convert::TryFrom;
 
+use super::daemon::DaemonConfig;
+use super::hooks::Hooks;
 use super::keybinds::Keybinds;
-use super::layout::RunPluginOrAlias;
+use super::layout::{LayoutTemplates, RunPluginOrAlias};
 use super::options::Options;
 use super::plugins::{PluginAliases, PluginsConfigError};
 use super::theme::{Themes, UiConfig};
@@ -32,6 +34,9 @@ pub struct Config {
     pub ui: UiConfig,
     pub env: EnvironmentVariables,
     pub background_plugins: HashSet<RunPluginOrAlias>,
+    pub templates: LayoutTemplates,
+    pub hooks: Hooks,
+    pub daemon: DaemonConfig,
 }
 
 #[derive(Error, Debug)]
@@ -235,6 +240,8 @@ impl Config {
         self.plugins.merge(other.plugins);
         self.ui = self.ui.merge(other.ui);
         self.env = self.env.merge(other.env);
+        self.hooks.merge(other.hooks);
+        self.daemon.merge(other.daemon);
         Ok(())
     }
     pub fn config_file_path(opts: &CliArgs) -> Option<PathBuf> {
@@ -408,8 +415,11 @@ impl Config {
 mod config_test {
     use super::*;
     use crate::data::{InputMode, Palette, PaletteColor, PluginTag, StyleDeclaration, Styling};
+    use crate::input::actions::Action;
+    use crate::input::daemon::DaemonSession;
+    use crate::input::hooks::Hook;
     use crate::input::layout::{RunPlugin, RunPluginLocation};
-    use crate::input::options::{Clipboard, OnForceClose};
+    use crate::input::options::{Clipboard, CopyProvider, OnForceClose};
     use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
     use std::collections::{BTreeMap, HashMap};
     use std::io::Write;
@@ -489,6 +499,7 @@ mod config_test {
             on_force_close "quit"
             scroll_buffer_size 100000
             copy_command "/path/to/my/copy-command"
+            copy_providers "xsel" "osc52"
             copy_clipboard "primary"
             copy_on_select false
             scrollback_editor "/path/to/my/scrollback-editor"
@@ -566,6 +577,11 @@ mod config_test {
             Some(String::from("/path/to/my/copy-command")),
             "Option set in config"
         );
+        assert_eq!(
+            config.options.copy_providers,
+            Some(vec![CopyProvider::Xsel, CopyProvider::Osc52]),
+            "Option set in config"
+        );
         assert_eq!(
             config.options.copy_clipboard,
             Some(Clipboard::Primary),
@@ -1157,4 +1173,128 @@ mod config_test {
             "Env variables defined in config"
         );
     }
+
+    #[test]
+    fn can_define_layout_templates_in_config_file() {
+        let config_contents = r#"
+            templates {
+                pane_template name="htop" command="htop"
+                tab_template name="monitoring" {
+                    htop
+                    pane
+                }
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        assert_eq!(
+            config.templates.list(),
+            vec!["htop".to_owned(), "monitoring".to_owned()],
+            "Templates defined in config"
+        );
+        let serialized_config = Config::from_kdl(&config.to_string(false), None).unwrap();
+        assert_eq!(
+            serialized_config.templates, config.templates,
+            "Templates survive serialization"
+        );
+    }
+
+    #[test]
+    fn only_pane_and_tab_templates_can_be_defined_in_config_file() {
+        let config_contents = r#"
+            templates {
+                default_tab_template {
+                    pane
+                }
+            }
+        "#;
+        assert!(Config::from_kdl(config_contents, None).is_err());
+    }
+
+    #[test]
+    fn can_define_hooks_in_config_file() {
+        let config_contents = r#"
+            hooks {
+                pane-exited "notify-send \"$ZELLIJ_HOOK_COMMAND exited\""
+                tab-created {
+                    NewPane "Down"
+                }
+                tab-created "echo $ZELLIJ_HOOK_NAME >> tabs.log"
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        assert_eq!(
+            config.hooks.for_event("pane-exited"),
+            &[Hook::Command(
+                "notify-send \"$ZELLIJ_HOOK_COMMAND exited\"".to_owned()
+            )],
+            "Command hook defined in config"
+        );
+        assert_eq!(
+            config.hooks.for_event("tab-created"),
+            &[
+                Hook::Actions(vec![Action::NewPane(
+                    Some(crate::data::Direction::Down),
+                    None,
+                    false
+                )]),
+                Hook::Command("echo $ZELLIJ_HOOK_NAME >> tabs.log".to_owned()),
+            ],
+            "Hooks of the same event run in order"
+        );
+        assert!(config.hooks.for_event("pane-closed").is_empty());
+        let serialized_config = Config::from_kdl(&config.to_string(false), None).unwrap();
+        assert_eq!(
+            serialized_config.hooks, config.hooks,
+            "Hooks survive serialization"
+        );
+    }
+
+    #[test]
+    fn hooks_of_unknown_events_are_rejected() {
+        let config_contents = r#"
+            hooks {
+                pane-renamed "echo renamed"
+            }
+        "#;
+        assert!(Config::from_kdl(config_contents, None).is_err());
+    }
+
+    #[test]
+    fn can_declare_daemon_sessions_in_config_file() {
+        let config_contents = r#"
+            daemon {
+                session "main" layout="compact" cwd="/home/me/src"
+                session "logs"
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        assert_eq!(
+            config.daemon.sessions,
+            vec![
+                DaemonSession {
+                    name: "main".to_owned(),
+                    layout: Some(PathBuf::from("compact")),
+                    cwd: Some(PathBuf::from("/home/me/src")),
+                },
+                DaemonSession {
+                    name: "logs".to_owned(),
+                    layout: None,
+                    cwd: None,
+                },
+            ],
+            "Daemon sessions defined in config, in order"
+        );
+        let serialized_config = Config::from_kdl(&config.to_string(false), None).unwrap();
+        assert_eq!(
+            serialized_config.daemon, config.daemon,
+            "Daemon sessions survive serialization"
+        );
+        let duplicate_sessions = r#"
+            daemon {
+                session "main"
+                session "main"
+            }
+        "#;
+        assert!(Config::from_kdl(duplicate_sessions, None).is_err());
+    }
 }
@@ -0,0 +1,29 @@
+//! The sessions `zellij start-server` creates when it starts (eg. on boot, as a user service),
+//! defined in the `daemon` block of the configuration.
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonSession {
+    pub name: String,
+    /// A layout name or path, as given to `zellij --layout`
+    pub layout: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    pub sessions: Vec<DaemonSession>,
+}
+
+impl DaemonConfig {
+    /// The sessions of a `daemon` block replace those of the base configuration
+    pub fn merge(&mut self, other: Self) {
+        if !other.is_empty() {
+            self.sessions = other.sessions;
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
@@ -0,0 +1,34 @@
+//! Hooks that the server runs on session events (eg. a pane exiting), defined in the `hooks`
+//! block of the configuration.
+use super::actions::Action;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Hook {
+    /// A shell command, with the fields of the event in `ZELLIJ_HOOK_*` environment variables
+    Command(String),
+    Actions(Vec<Action>),
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Hooks {
+    // event name (eg. "pane-exited") => the hooks to run, in order
+    pub hooks: BTreeMap<String, Vec<Hook>>,
+}
+
+impl Hooks {
+    /// The hooks of an event replace those the base configuration defines for it
+    pub fn merge(&mut self, other: Self) {
+        self.hooks.extend(other.hooks);
+    }
+    pub fn for_event(&self, event_name: &str) -> &[Hook] {
+        self.hooks
+            .get(event_name)
+            .map(|hooks| hooks.as_slice())
+            .unwrap_or(&[])
+    }
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+}
@@ -85,6 +85,7 @@ impl Keybinds {
             InputMode::RenameTab => Action::TabNameInput(raw_bytes),
             InputMode::RenamePane => Action::PaneNameInput(raw_bytes),
             InputMode::EnterSearch => Action::SearchInput(raw_bytes),
+            InputMode::Scroll => Action::CopyModeInput(raw_bytes),
             _ => Action::NoOp,
         }
     }
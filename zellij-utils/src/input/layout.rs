@@ -14,7 +14,7 @@ use crate::{
     data::{Direction, LayoutInfo},
     home::{default_layout_dir, find_default_config_dir},
     input::{
-        command::RunCommand,
+        command::{OutputLog, RunCommand},
         config::{Config, ConfigError},
     },
     pane_size::{Constraint, Dimension, PaneGeom},
@@ -84,6 +84,41 @@ impl SplitSize {
     }
 }
 
+/// The `min_size` and `max_size` of a tiled pane, these are in rows or columns depending on the
+/// split direction of its parent (like its `size`)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SizeLimits {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+    /// The split direction of the parent of the pane, only known once the layout is positioned
+    pub split_direction: Option<SplitDirection>,
+}
+
+impl SizeLimits {
+    /// The (min, max) number of rows of the pane
+    pub fn rows(&self) -> (Option<usize>, Option<usize>) {
+        match self.split_direction {
+            Some(SplitDirection::Horizontal) => (self.min, self.max),
+            _ => (None, None),
+        }
+    }
+    /// The (min, max) number of columns of the pane
+    pub fn cols(&self) -> (Option<usize>, Option<usize>) {
+        match self.split_direction {
+            Some(SplitDirection::Vertical) => (self.min, self.max),
+            _ => (None, None),
+        }
+    }
+}
+
+/// The `scrollback_size` of a pane, overriding the `scroll_buffer_size` of the configuration
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollbackSize {
+    Lines(usize),
+    /// Keep the whole scrollback, moving its older lines to compressed files on disk
+    Unlimited,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub enum RunPluginOrAlias {
     RunPlugin(RunPlugin),
@@ -256,6 +291,16 @@ impl Run {
                 if merged.args.is_empty() && !base_run_command.args.is_empty() {
                     merged.args = base_run_command.args.clone();
                 }
+                if merged.stdin_file.is_none() && base_run_command.stdin_file.is_some() {
+                    merged.stdin_file = base_run_command.stdin_file.clone();
+                }
+                if merged.log_output.is_none() && base_run_command.log_output.is_some() {
+                    merged.log_output = base_run_command.log_output.clone();
+                }
+                if merged.watch.is_none() && base_run_command.watch.is_some() {
+                    merged.watch = base_run_command.watch.clone();
+                    merged.hold_on_close = true;
+                }
                 Some(Run::Command(merged))
             },
             (Some(Run::Command(base_run_command)), Some(Run::Cwd(other_cwd))) => {
@@ -339,6 +384,22 @@ impl Run {
             }
         }
     }
+    pub fn add_stdin_file(&mut self, stdin_file: Option<PathBuf>) {
+        // overrides the stdin_file of a Run::Command if it is Some
+        if let Some(stdin_file) = stdin_file {
+            if let Run::Command(run_command) = self {
+                run_command.stdin_file = Some(stdin_file);
+            }
+        }
+    }
+    pub fn add_log_output(&mut self, log_output: Option<OutputLog>) {
+        // overrides the log_output of a Run::Command if it is Some
+        if let Some(log_output) = log_output {
+            if let Run::Command(run_command) = self {
+                run_command.log_output = Some(log_output);
+            }
+        }
+    }
     pub fn add_close_on_exit(&mut self, close_on_exit: Option<bool>) {
         // overrides the hold_on_close of a Run::Command if it is Some
         // and not empty
@@ -671,6 +732,37 @@ pub type SwapFloatingLayout = (
     Option<String>,
 ); // Option<String> is the swap layout name
 
+/// Pane and tab templates defined in the `templates` block of the configuration, these can be
+/// referenced by name from any layout (templates defined in the layout itself take precedence)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct LayoutTemplates {
+    // template name => the raw KDL of its pane_template or tab_template node, these are parsed
+    // together with each layout so that they can use the full layout syntax
+    pub templates: BTreeMap<String, String>,
+}
+
+impl LayoutTemplates {
+    pub fn merge(&mut self, other: Self) {
+        self.templates.extend(other.templates);
+    }
+    pub fn contains(&self, template_name: &str) -> bool {
+        self.templates.contains_key(template_name)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+    pub fn list(&self) -> Vec<String> {
+        self.templates.keys().cloned().collect()
+    }
+    pub fn raw_templates(&self) -> String {
+        self.templates
+            .values()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
 pub struct Layout {
     pub tabs: Vec<(Option<String>, TiledPaneLayout, Vec<FloatingPaneLayout>)>,
@@ -751,6 +843,13 @@ pub struct FloatingPaneLayout {
     pub run: Option<Run>,
     pub focus: Option<bool>,
     pub already_running: bool,
+    /// Log the output of the pane to the session's cache folder from the start
+    pub log: bool,
+    pub scrollback_size: Option<ScrollbackSize>,
+    /// Override the `alternate_screen_scrollback` option of the configuration for this pane
+    pub alternate_screen_scrollback: Option<bool>,
+    /// Override the `clear_on_alternate_screen_exit` option of the configuration for this pane
+    pub clear_on_alternate_screen_exit: Option<bool>,
     pub pane_initial_contents: Option<String>,
     pub logical_position: Option<usize>,
 }
@@ -767,6 +866,10 @@ impl FloatingPaneLayout {
             run: None,
             focus: None,
             already_running: false,
+            log: false,
+            scrollback_size: None,
+            alternate_screen_scrollback: None,
+            clear_on_alternate_screen_exit: None,
             pane_initial_contents: None,
             logical_position: None,
         }
@@ -792,6 +895,10 @@ impl From<&TiledPaneLayout> for FloatingPaneLayout {
             name: pane_layout.name.clone(),
             run: pane_layout.run.clone(),
             focus: pane_layout.focus,
+            log: pane_layout.log,
+            scrollback_size: pane_layout.scrollback_size,
+            alternate_screen_scrollback: pane_layout.alternate_screen_scrollback,
+            clear_on_alternate_screen_exit: pane_layout.clear_on_alternate_screen_exit,
             ..Default::default()
         }
     }
@@ -812,6 +919,16 @@ pub struct TiledPaneLayout {
     pub exclude_from_sync: Option<bool>,
     pub run_instructions_to_ignore: Vec<Option<Run>>,
     pub hide_floating_panes: bool, // only relevant if this is the base layout
+    /// Log the output of the pane to the session's cache folder from the start
+    pub log: bool,
+    /// Zoom the pane to the whole tab (only the first such pane of a tab is)
+    pub fullscreen: bool,
+    pub size_limits: Option<SizeLimits>,
+    pub scrollback_size: Option<ScrollbackSize>,
+    /// Override the `alternate_screen_scrollback` option of the configuration for this pane
+    pub alternate_screen_scrollback: Option<bool>,
+    /// Override the `clear_on_alternate_screen_exit` option of the configuration for this pane
+    pub clear_on_alternate_screen_exit: Option<bool>,
     pub pane_initial_contents: Option<String>,
 }
 
@@ -1277,17 +1394,71 @@ impl Layout {
     ) -> Result<(Layout, Config), ConfigError> {
         let (path_to_raw_layout, raw_layout, raw_swap_layouts) =
             Layout::stringified_from_path_or_default(layout_path, layout_dir)?;
-        let layout = Layout::from_kdl(
+        let layout = Layout::from_kdl_with_templates(
             &raw_layout,
             Some(path_to_raw_layout),
             raw_swap_layouts
                 .as_ref()
                 .map(|(r, f)| (r.as_str(), f.as_str())),
             None,
+            &config.templates,
         )?;
         let config = Config::from_kdl(&raw_layout, Some(config))?; // this merges the two config, with
         Ok((layout, config))
     }
+    /// Replace the `{{name}}` placeholders of a raw layout with the values of its variables (eg.
+    /// `new-tab --layout project.kdl --var name=my-project`), placeholders without a value are an
+    /// error. Without any variables the layout is left as is, since it might well contain `{{`
+    /// for other reasons (eg. in the arguments of a command).
+    pub fn substitute_variables(
+        raw_layout: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<String, ConfigError> {
+        if variables.is_empty() {
+            return Ok(raw_layout.to_owned());
+        }
+        let mut substituted = String::with_capacity(raw_layout.len());
+        let mut position = 0;
+        while let Some(start) = raw_layout[position..].find("{{").map(|i| position + i) {
+            let Some(end) = raw_layout[start..].find("}}").map(|i| start + i + 2) else {
+                break;
+            };
+            let name = raw_layout[start + 2..end - 2].trim();
+            let value = variables.get(name).ok_or_else(|| {
+                ConfigError::new_layout_kdl_error(
+                    format!("The layout variable '{}' has no value", name),
+                    start,
+                    end - start,
+                )
+            })?;
+            substituted.push_str(&raw_layout[position..start]);
+            // placeholders are usually inside of kdl strings
+            substituted.push_str(&value.replace('\\', "\\\\").replace('"', "\\\""));
+            position = end;
+        }
+        substituted.push_str(&raw_layout[position..]);
+        Ok(substituted)
+    }
+    /// Create a layout with a single tab from a pane or tab template defined in the `templates`
+    /// block of the configuration
+    pub fn from_template_name(
+        template_name: &str,
+        templates: &LayoutTemplates,
+        cwd: Option<PathBuf>,
+    ) -> Result<Layout, ConfigError> {
+        if !templates.contains(template_name) {
+            return Err(ConfigError::Std(
+                format!(
+                    "No template named \"{}\" in the configuration (available templates: {})",
+                    template_name,
+                    templates.list().join(", ")
+                )
+                .into(),
+            ));
+        }
+        let raw_layout = format!("layout {{\n    {}\n}}", template_name);
+        Layout::from_kdl_with_templates(&raw_layout, None, None, cwd, templates)
+    }
     #[cfg(not(target_family = "wasm"))]
     pub fn from_url(url: &str, config: Config) -> Result<(Layout, Config), ConfigError> {
         let raw_layout = task::block_on(async move {
@@ -1297,7 +1468,13 @@ impl Layout {
                 Err(e) => Err(ConfigError::DownloadError(format!("{}", e))),
             }
         })?;
-        let mut layout = Layout::from_kdl(&raw_layout, Some(url.into()), None, None)?;
+        let mut layout = Layout::from_kdl_with_templates(
+            &raw_layout,
+            Some(url.into()),
+            None,
+            None,
+            &config.templates,
+        )?;
         layout.recursively_add_start_suspended_including_template(Some(true));
         let config = Config::from_kdl(&raw_layout, Some(config))?; // this merges the two config, with
         Ok((layout, config))
@@ -1306,7 +1483,13 @@ impl Layout {
         stringified_layout: &str,
         config: Config,
     ) -> Result<(Layout, Config), ConfigError> {
-        let layout = Layout::from_kdl(&stringified_layout, None, None, None)?;
+        let layout = Layout::from_kdl_with_templates(
+            &stringified_layout,
+            None,
+            None,
+            None,
+            &config.templates,
+        )?;
         let config = Config::from_kdl(&stringified_layout, Some(config))?; // this merges the two config, with
         Ok((layout, config))
     }
@@ -1339,13 +1522,14 @@ impl Layout {
     ) -> Result<(Layout, Config), ConfigError> {
         let (path_to_raw_layout, raw_layout, raw_swap_layouts) =
             Layout::stringified_from_default_assets(layout_name)?;
-        let layout = Layout::from_kdl(
+        let layout = Layout::from_kdl_with_templates(
             &raw_layout,
             Some(path_to_raw_layout),
             raw_swap_layouts
                 .as_ref()
                 .map(|(r, f)| (r.as_str(), f.as_str())),
             None,
+            &config.templates,
         )?;
         let config = Config::from_kdl(&raw_layout, Some(config))?; // this merges the two config, with
         Ok((layout, config))
@@ -1770,7 +1954,10 @@ fn split_space(
             }
             pane_positions_with_children.append(&mut part_positions);
         } else {
-            let part = part.clone();
+            let mut part = part.clone();
+            if let Some(size_limits) = part.size_limits.as_mut() {
+                size_limits.split_direction = Some(layout.children_split_direction);
+            }
             pane_positions.push((part, *part_position_and_size));
         }
     }
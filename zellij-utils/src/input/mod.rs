@@ -1,6 +1,8 @@
 pub mod actions;
 pub mod command;
 pub mod config;
+pub mod daemon;
+pub mod hooks;
 pub mod keybinds;
 pub mod layout;
 pub mod mouse;
@@ -45,6 +47,8 @@ mod not_wasm {
             session_name,
             editor: None,
             shell: None,
+            installed_version: None,
+            latest_release: None,
         }
     }
 
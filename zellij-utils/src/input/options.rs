@@ -3,6 +3,7 @@ use crate::cli::Command;
 use crate::data::InputMode;
 use clap::{ArgEnum, Args};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -32,6 +33,308 @@ impl FromStr for OnForceClose {
     }
 }
 
+/// A daily range of local time (eg. "22:00-07:00") during which bells are muted
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct QuietHours {
+    // minutes since midnight
+    start: u16,
+    end: u16,
+}
+
+impl QuietHours {
+    /// Whether the given local time (in minutes since midnight) falls within the quiet hours, the
+    /// range may wrap around midnight
+    pub fn contains(&self, minutes_since_midnight: u16) -> bool {
+        if self.start <= self.end {
+            minutes_since_midnight >= self.start && minutes_since_midnight < self.end
+        } else {
+            minutes_since_midnight >= self.start || minutes_since_midnight < self.end
+        }
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_time = |time: &str| -> Option<u16> {
+            let (hours, minutes) = time.trim().split_once(':')?;
+            let hours: u16 = hours.parse().ok()?;
+            let minutes: u16 = minutes.parse().ok()?;
+            if hours < 24 && minutes < 60 {
+                Some(hours * 60 + minutes)
+            } else {
+                None
+            }
+        };
+        s.split_once('-')
+            .and_then(|(start, end)| {
+                Some(QuietHours {
+                    start: parse_time(start)?,
+                    end: parse_time(end)?,
+                })
+            })
+            .ok_or_else(|| format!("Invalid quiet hours: '{}', expected eg. 22:00-07:00", s))
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start / 60,
+            self.start % 60,
+            self.end / 60,
+            self.end % 60
+        )
+    }
+}
+
+/// What a session compares its own version against, to warn about the zellij binary having been
+/// upgraded underneath it
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize, ArgEnum)]
+pub enum UpdateCheck {
+    #[serde(alias = "off")]
+    Off,
+    /// The zellij binary installed on disk
+    #[serde(alias = "installed")]
+    Installed,
+    /// The zellij binary installed on disk and the latest published release
+    #[serde(alias = "release")]
+    Release,
+}
+
+impl Default for UpdateCheck {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+impl FromStr for UpdateCheck {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Off" | "off" => Ok(Self::Off),
+            "Installed" | "installed" => Ok(Self::Installed),
+            "Release" | "release" => Ok(Self::Release),
+            _ => Err(format!("No such update check: {}", s)),
+        }
+    }
+}
+
+/// What happens to a command pane (eg. one opened with `zellij run`) when its command exits
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandPaneExit {
+    /// Keep the pane open, showing the exit status of the command
+    #[serde(alias = "hold")]
+    Hold,
+    #[serde(alias = "close")]
+    Close,
+    /// Close the pane if the command succeeded, keep it open otherwise
+    #[serde(alias = "close_on_success")]
+    CloseOnSuccess,
+}
+
+impl Default for CommandPaneExit {
+    fn default() -> Self {
+        Self::Hold
+    }
+}
+
+impl FromStr for CommandPaneExit {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Hold" | "hold" => Ok(Self::Hold),
+            "Close" | "close" => Ok(Self::Close),
+            "CloseOnSuccess" | "close_on_success" => Ok(Self::CloseOnSuccess),
+            _ => Err(format!("No such command pane exit behavior: {}", s)),
+        }
+    }
+}
+
+/// Whether names set by the user or titles set by the programs running in panes (OSC 0/2) are
+/// shown when a pane or tab has both
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitlePrecedence {
+    #[serde(alias = "user")]
+    User,
+    #[serde(alias = "application")]
+    Application,
+}
+
+impl Default for TitlePrecedence {
+    fn default() -> Self {
+        Self::User
+    }
+}
+
+impl FromStr for TitlePrecedence {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "User" | "user" => Ok(Self::User),
+            "Application" | "application" => Ok(Self::Application),
+            _ => Err(format!("No such title precedence: {}", s)),
+        }
+    }
+}
+
+/// How many columns East Asian ambiguous width characters printed to panes take
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmbiguousWidth {
+    #[serde(alias = "narrow")]
+    Narrow,
+    #[serde(alias = "wide")]
+    Wide,
+}
+
+impl Default for AmbiguousWidth {
+    fn default() -> Self {
+        Self::Narrow
+    }
+}
+
+impl FromStr for AmbiguousWidth {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Narrow" | "narrow" => Ok(Self::Narrow),
+            "Wide" | "wide" => Ok(Self::Wide),
+            _ => Err(format!("No such ambiguous width: {}", s)),
+        }
+    }
+}
+
+/// What is done with the control characters and escape sequences in text pasted into panes
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteSanitization {
+    /// Paste the text as is
+    #[serde(alias = "off")]
+    Off,
+    /// Remove them from the pasted text
+    #[serde(alias = "strip")]
+    Strip,
+    /// Replace them with their visible caret notation (eg. ^[)
+    #[serde(alias = "escape")]
+    Escape,
+}
+
+impl Default for PasteSanitization {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+impl FromStr for PasteSanitization {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Off" | "off" => Ok(Self::Off),
+            "Strip" | "strip" => Ok(Self::Strip),
+            "Escape" | "escape" => Ok(Self::Escape),
+            _ => Err(format!("No such paste sanitization: {}", s)),
+        }
+    }
+}
+
+/// Which bells (BEL) rung by the programs running in panes are passed on to the terminals of the
+/// clients
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BellForwarding {
+    /// Only bells of the panes the client is looking at
+    #[serde(alias = "visible")]
+    Visible,
+    /// Bells of all panes, in all tabs
+    #[serde(alias = "all")]
+    All,
+    /// No bells, tabs are still marked
+    #[serde(alias = "off")]
+    Off,
+}
+
+impl Default for BellForwarding {
+    fn default() -> Self {
+        Self::Visible
+    }
+}
+
+impl FromStr for BellForwarding {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Visible" | "visible" => Ok(Self::Visible),
+            "All" | "all" => Ok(Self::All),
+            "Off" | "off" => Ok(Self::Off),
+            _ => Err(format!("No such bell forwarding: {}", s)),
+        }
+    }
+}
+
+/// Which working directory new panes open in when none is given explicitly
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewPaneCwd {
+    /// The cwd of the focused pane, as reported by its shell (OSC 7) or queried from the os
+    #[serde(alias = "focused_pane")]
+    FocusedPane,
+    /// The cwd the tab was opened in
+    #[serde(alias = "tab")]
+    Tab,
+    /// The cwd the session was started in (or the default_cwd, if set)
+    #[serde(alias = "session")]
+    Session,
+}
+
+impl Default for NewPaneCwd {
+    fn default() -> Self {
+        Self::FocusedPane
+    }
+}
+
+impl FromStr for NewPaneCwd {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "FocusedPane" | "focused_pane" => Ok(Self::FocusedPane),
+            "Tab" | "tab" => Ok(Self::Tab),
+            "Session" | "session" => Ok(Self::Session),
+            _ => Err(format!("No such new pane cwd: {}", s)),
+        }
+    }
+}
+
+/// What the bottom frame of a command pane shows after its command exited
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandExitBanner {
+    /// The exit code, how long the command ran and the keys to re-run it
+    #[serde(alias = "full")]
+    Full,
+    /// The exit code and how long the command ran
+    #[serde(alias = "compact")]
+    Compact,
+    /// Nothing
+    #[serde(alias = "hidden")]
+    Hidden,
+}
+
+impl Default for CommandExitBanner {
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
+impl FromStr for CommandExitBanner {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Full" | "full" => Ok(Self::Full),
+            "Compact" | "compact" => Ok(Self::Compact),
+            "Hidden" | "hidden" => Ok(Self::Hidden),
+            _ => Err(format!("No such command exit banner: {}", s)),
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, PartialEq, Deserialize, Serialize, Args)]
 /// Options that can be set either through the config file,
 /// or cli flags - cli flags should take precedence over the config file
@@ -86,10 +389,17 @@ pub struct Options {
     pub scroll_buffer_size: Option<usize>,
 
     /// Switch to using a user supplied command for clipboard instead of OSC52
+    /// (deprecated, prefer copy_providers; when set it is tried before them)
     #[clap(long, value_parser)]
     #[serde(default)]
     pub copy_command: Option<String>,
 
+    /// The clipboard providers to try when copying, in order, skipping those not available
+    /// (wl-copy, xclip, xsel, pbcopy, osc52; default: osc52)
+    #[clap(long, arg_enum, use_value_delimiter = true, value_parser)]
+    #[serde(default)]
+    pub copy_providers: Option<Vec<CopyProvider>>,
+
     /// OSC52 destination clipboard
     #[clap(
         long,
@@ -179,6 +489,193 @@ pub struct Options {
     #[clap(long, value_parser)]
     #[serde(default)]
     pub show_release_notes: Option<bool>,
+
+    /// A daily range of local time during which bells are muted (eg. 22:00-07:00)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+
+    /// A template for the names of new sessions, with the {adjective}, {noun}, {date} and
+    /// {directory} placeholders (eg. "{directory}-{date}")
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub session_name_template: Option<String>,
+
+    /// A command whose output is the name of new sessions, takes precedence over
+    /// session_name_template
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub session_name_command: Option<String>,
+
+    /// Warn in the status bar when the zellij binary installed on disk (or the latest release)
+    /// differs from the version of the running session
+    /// default is off
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    #[serde(default)]
+    pub update_check: Option<UpdateCheck>,
+
+    /// What happens to the panes of `zellij run` when their command exits, unless overridden by
+    /// its flags
+    /// default is hold
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    #[serde(default)]
+    pub command_pane_on_exit: Option<CommandPaneExit>,
+
+    /// Start the commands of `zellij run` suspended, only running them after ENTER is pressed,
+    /// unless overridden by its flags
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub start_command_panes_suspended: Option<bool>,
+
+    /// Whether a name set by the user (user) or a title set by the program running in the pane
+    /// (application) is shown when a pane or tab has both
+    /// default is user
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    #[serde(default)]
+    pub title_precedence: Option<TitlePrecedence>,
+
+    /// Name tabs after the title that the program in their focused pane sets
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub tab_name_from_title: Option<bool>,
+
+    /// Which bells rung in panes are passed on to the terminals of the clients: those of the panes
+    /// the client is looking at (visible), of all panes (all) or none (off)
+    /// default is visible
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    #[serde(default)]
+    pub bell_forwarding: Option<BellForwarding>,
+
+    /// What the frame of a command pane shows after its command exited: the exit code, runtime
+    /// and keys to re-run it (full), only the exit code and runtime (compact) or nothing (hidden)
+    /// default is full
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    #[serde(default)]
+    pub command_exit_banner: Option<CommandExitBanner>,
+
+    /// Detach clients that have sent no input for this many minutes, after counting down the
+    /// last minute on their screen (default: never)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub idle_client_detach_timeout: Option<u64>,
+
+    /// Re-emit the clipboard writes (OSC 52) of programs running inside panes to the attached
+    /// clients, besides copy_command if set (true or false, default: true)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub osc52_forwarding: Option<bool>,
+
+    /// The characters that, besides whitespace, end the word selected by double-clicking in a pane
+    /// default is []{}<>()
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub word_separators: Option<String>,
+
+    /// Make floating panes global to the session, so that they follow the focused tab, instead of
+    /// belonging to the tab they were opened in (the ToggleFloatingPaneScope action changes this
+    /// for a single pane)
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub global_floating_panes: Option<bool>,
+
+    /// Which working directory new panes open in: that of the focused pane (focused_pane), the
+    /// one the tab was opened in (tab) or the one the session was started in (session)
+    /// default is focused_pane
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    #[serde(default)]
+    pub new_pane_cwd: Option<NewPaneCwd>,
+
+    /// Name tabs after their focused pane: the title set by the program running in it or else the
+    /// command running in it, following focus changes. A tab renamed by the user keeps its name
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub automatic_tab_rename: Option<bool>,
+
+    /// Show the name of the foreground process, and the CPU and memory usage of all the processes
+    /// running in each terminal pane, at the bottom of its frame (sampled every few seconds)
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub pane_frame_process_stats: Option<bool>,
+
+    /// Ask for confirmation (in the focused pane) before closing a pane or a tab in which programs
+    /// other than the shell are still running, rather than hanging them up
+    /// default is true
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub confirm_close_with_running_processes: Option<bool>,
+
+    /// A command to run (with `sh -c`) when a command pane exits in a tab that no client is
+    /// looking at, eg. to send a desktop notification. The notification is in the
+    /// ZELLIJ_NOTIFICATION_TEXT environment variable, its parts in ZELLIJ_NOTIFICATION_COMMAND,
+    /// ZELLIJ_NOTIFICATION_EXIT_CODE and ZELLIJ_NOTIFICATION_TAB
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub command_exit_notification_command: Option<String>,
+
+    /// The most panes a tab can have (the bars are not counted), new ones are refused
+    /// beyond that
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub max_panes_per_tab: Option<usize>,
+
+    /// The most tabs a session can have, new ones are refused beyond that
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub max_tabs: Option<usize>,
+
+    /// Once the scrollback of all the panes of a session takes this much memory (in MB), new
+    /// panes and tabs are refused
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub max_scrollback_memory: Option<usize>,
+
+    /// Serialize and kill the session once it has had no attached client and no pane output for
+    /// this many minutes, it can be resurrected later (default: never)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub idle_session_timeout: Option<u64>,
+
+    /// Let full screen applications (eg. vim, less, htop) draw on the main screen of a pane rather
+    /// than on an alternate screen, so that what they printed can be scrolled back to after they
+    /// exit (default: false)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub alternate_screen_scrollback: Option<bool>,
+
+    /// Clear the viewport of a pane (moving what it showed to the scrollback) when a full screen
+    /// application exits (default: false)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub clear_on_alternate_screen_exit: Option<bool>,
+
+    /// Whether East Asian ambiguous width characters (eg. some Greek, Cyrillic and symbol
+    /// characters) printed to panes take one (narrow) or two (wide) columns, this should match the
+    /// setting of the terminal emulator
+    /// default is narrow
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    #[serde(default)]
+    pub ambiguous_width: Option<AmbiguousWidth>,
+
+    /// Count emoji sequences printed to panes the way modern terminal emulators display them: an
+    /// emoji joined to the previous one (ZWJ) or a skin tone modifier takes no columns of its own and
+    /// a variation selector for emoji presentation makes the character before it wide
+    /// (default: false)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub grapheme_clusters: Option<bool>,
+
+    /// What to do with the control characters and escape sequences (eg. an embedded bracketed paste
+    /// terminator) in text pasted into panes, stripping or escaping them protects against paste
+    /// injection from copied web pages
+    /// default is off
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    #[serde(default)]
+    pub paste_sanitization: Option<PasteSanitization>,
 }
 
 #[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
@@ -206,6 +703,91 @@ impl FromStr for Clipboard {
     }
 }
 
+/// A way of putting copied text in the clipboard
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyProvider {
+    #[serde(alias = "wl-copy")]
+    WlCopy,
+    #[serde(alias = "xclip")]
+    Xclip,
+    #[serde(alias = "xsel")]
+    Xsel,
+    #[serde(alias = "pbcopy")]
+    Pbcopy,
+    /// The OSC 52 sequence, which has the terminal of the client set the clipboard
+    #[serde(alias = "osc52")]
+    Osc52,
+}
+
+impl CopyProvider {
+    /// The providers used when none are configured
+    pub fn default_providers() -> Vec<Self> {
+        vec![Self::Osc52]
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WlCopy => "wl-copy",
+            Self::Xclip => "xclip",
+            Self::Xsel => "xsel",
+            Self::Pbcopy => "pbcopy",
+            Self::Osc52 => "osc52",
+        }
+    }
+    /// The command the copied text is piped to, or None for OSC 52
+    pub fn command(&self, clipboard: Clipboard) -> Option<&'static str> {
+        match (self, clipboard) {
+            (Self::WlCopy, Clipboard::System) => Some("wl-copy"),
+            (Self::WlCopy, Clipboard::Primary) => Some("wl-copy --primary"),
+            (Self::Xclip, Clipboard::System) => Some("xclip -selection clipboard"),
+            (Self::Xclip, Clipboard::Primary) => Some("xclip -selection primary"),
+            (Self::Xsel, Clipboard::System) => Some("xsel --clipboard --input"),
+            (Self::Xsel, Clipboard::Primary) => Some("xsel --primary --input"),
+            // macOS has no primary selection
+            (Self::Pbcopy, _) => Some("pbcopy"),
+            (Self::Osc52, _) => None,
+        }
+    }
+    /// Whether this provider can be used in the current environment, or why not
+    pub fn availability(&self) -> Result<(), String> {
+        let display_variable = match self {
+            Self::WlCopy => Some("WAYLAND_DISPLAY"),
+            Self::Xclip | Self::Xsel => Some("DISPLAY"),
+            Self::Pbcopy | Self::Osc52 => None,
+        };
+        if let Some(display_variable) = display_variable {
+            if std::env::var_os(display_variable).is_none() {
+                return Err(format!("{} is not set", display_variable));
+            }
+        }
+        let program = match self.command(Clipboard::System) {
+            Some(command) => command.split(' ').next().unwrap_or(command),
+            None => return Ok(()),
+        };
+        let program_in_path = std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+            .unwrap_or(false);
+        if program_in_path {
+            Ok(())
+        } else {
+            Err(format!("{} was not found in PATH", program))
+        }
+    }
+}
+
+impl FromStr for CopyProvider {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "WlCopy" | "wl-copy" => Ok(Self::WlCopy),
+            "Xclip" | "xclip" => Ok(Self::Xclip),
+            "Xsel" | "xsel" => Ok(Self::Xsel),
+            "Pbcopy" | "pbcopy" => Ok(Self::Pbcopy),
+            "Osc52" | "osc52" => Ok(Self::Osc52),
+            _ => Err(format!("No such copy provider: {}", s)),
+        }
+    }
+}
+
 impl Options {
     pub fn from_yaml(from_yaml: Option<Options>) -> Options {
         if let Some(opts) = from_yaml {
@@ -233,6 +815,7 @@ impl Options {
         let on_force_close = other.on_force_close.or(self.on_force_close);
         let scroll_buffer_size = other.scroll_buffer_size.or(self.scroll_buffer_size);
         let copy_command = other.copy_command.or_else(|| self.copy_command.clone());
+        let copy_providers = other.copy_providers.or_else(|| self.copy_providers.clone());
         let copy_clipboard = other.copy_clipboard.or(self.copy_clipboard);
         let copy_on_select = other.copy_on_select.or(self.copy_on_select);
         let scrollback_editor = other
@@ -260,6 +843,54 @@ impl Options {
         let stacked_resize = other.stacked_resize.or(self.stacked_resize);
         let show_startup_tips = other.show_startup_tips.or(self.show_startup_tips);
         let show_release_notes = other.show_release_notes.or(self.show_release_notes);
+        let quiet_hours = other.quiet_hours.or(self.quiet_hours);
+        let session_name_template = other
+            .session_name_template
+            .or_else(|| self.session_name_template.clone());
+        let session_name_command = other
+            .session_name_command
+            .or_else(|| self.session_name_command.clone());
+        let update_check = other.update_check.or(self.update_check);
+        let command_pane_on_exit = other.command_pane_on_exit.or(self.command_pane_on_exit);
+        let start_command_panes_suspended = other
+            .start_command_panes_suspended
+            .or(self.start_command_panes_suspended);
+        let title_precedence = other.title_precedence.or(self.title_precedence);
+        let tab_name_from_title = other.tab_name_from_title.or(self.tab_name_from_title);
+        let bell_forwarding = other.bell_forwarding.or(self.bell_forwarding);
+        let command_exit_banner = other.command_exit_banner.or(self.command_exit_banner);
+        let idle_client_detach_timeout = other
+            .idle_client_detach_timeout
+            .or(self.idle_client_detach_timeout);
+        let osc52_forwarding = other.osc52_forwarding.or(self.osc52_forwarding);
+        let word_separators = other
+            .word_separators
+            .or_else(|| self.word_separators.clone());
+        let global_floating_panes = other.global_floating_panes.or(self.global_floating_panes);
+        let new_pane_cwd = other.new_pane_cwd.or(self.new_pane_cwd);
+        let automatic_tab_rename = other.automatic_tab_rename.or(self.automatic_tab_rename);
+        let pane_frame_process_stats = other
+            .pane_frame_process_stats
+            .or(self.pane_frame_process_stats);
+        let confirm_close_with_running_processes = other
+            .confirm_close_with_running_processes
+            .or(self.confirm_close_with_running_processes);
+        let command_exit_notification_command = other
+            .command_exit_notification_command
+            .or_else(|| self.command_exit_notification_command.clone());
+        let max_panes_per_tab = other.max_panes_per_tab.or(self.max_panes_per_tab);
+        let max_tabs = other.max_tabs.or(self.max_tabs);
+        let max_scrollback_memory = other.max_scrollback_memory.or(self.max_scrollback_memory);
+        let idle_session_timeout = other.idle_session_timeout.or(self.idle_session_timeout);
+        let alternate_screen_scrollback = other
+            .alternate_screen_scrollback
+            .or(self.alternate_screen_scrollback);
+        let clear_on_alternate_screen_exit = other
+            .clear_on_alternate_screen_exit
+            .or(self.clear_on_alternate_screen_exit);
+        let ambiguous_width = other.ambiguous_width.or(self.ambiguous_width);
+        let grapheme_clusters = other.grapheme_clusters.or(self.grapheme_clusters);
+        let paste_sanitization = other.paste_sanitization.or(self.paste_sanitization);
 
         Options {
             simplified_ui,
@@ -276,6 +907,7 @@ impl Options {
             on_force_close,
             scroll_buffer_size,
             copy_command,
+            copy_providers,
             copy_clipboard,
             copy_on_select,
             scrollback_editor,
@@ -292,6 +924,34 @@ impl Options {
             stacked_resize,
             show_startup_tips,
             show_release_notes,
+            quiet_hours,
+            session_name_template,
+            session_name_command,
+            update_check,
+            command_pane_on_exit,
+            start_command_panes_suspended,
+            title_precedence,
+            tab_name_from_title,
+            bell_forwarding,
+            command_exit_banner,
+            idle_client_detach_timeout,
+            osc52_forwarding,
+            word_separators,
+            global_floating_panes,
+            new_pane_cwd,
+            automatic_tab_rename,
+            pane_frame_process_stats,
+            confirm_close_with_running_processes,
+            command_exit_notification_command,
+            max_panes_per_tab,
+            max_tabs,
+            max_scrollback_memory,
+            idle_session_timeout,
+            alternate_screen_scrollback,
+            clear_on_alternate_screen_exit,
+            ambiguous_width,
+            grapheme_clusters,
+            paste_sanitization,
         }
     }
 
@@ -330,6 +990,7 @@ impl Options {
         let on_force_close = other.on_force_close.or(self.on_force_close);
         let scroll_buffer_size = other.scroll_buffer_size.or(self.scroll_buffer_size);
         let copy_command = other.copy_command.or_else(|| self.copy_command.clone());
+        let copy_providers = other.copy_providers.or_else(|| self.copy_providers.clone());
         let copy_clipboard = other.copy_clipboard.or(self.copy_clipboard);
         let copy_on_select = other.copy_on_select.or(self.copy_on_select);
         let scrollback_editor = other
@@ -353,6 +1014,54 @@ impl Options {
         let stacked_resize = other.stacked_resize.or(self.stacked_resize);
         let show_startup_tips = other.show_startup_tips.or(self.show_startup_tips);
         let show_release_notes = other.show_release_notes.or(self.show_release_notes);
+        let quiet_hours = other.quiet_hours.or(self.quiet_hours);
+        let session_name_template = other
+            .session_name_template
+            .or_else(|| self.session_name_template.clone());
+        let session_name_command = other
+            .session_name_command
+            .or_else(|| self.session_name_command.clone());
+        let update_check = other.update_check.or(self.update_check);
+        let command_pane_on_exit = other.command_pane_on_exit.or(self.command_pane_on_exit);
+        let start_command_panes_suspended = other
+            .start_command_panes_suspended
+            .or(self.start_command_panes_suspended);
+        let title_precedence = other.title_precedence.or(self.title_precedence);
+        let tab_name_from_title = other.tab_name_from_title.or(self.tab_name_from_title);
+        let bell_forwarding = other.bell_forwarding.or(self.bell_forwarding);
+        let command_exit_banner = other.command_exit_banner.or(self.command_exit_banner);
+        let idle_client_detach_timeout = other
+            .idle_client_detach_timeout
+            .or(self.idle_client_detach_timeout);
+        let osc52_forwarding = other.osc52_forwarding.or(self.osc52_forwarding);
+        let word_separators = other
+            .word_separators
+            .or_else(|| self.word_separators.clone());
+        let global_floating_panes = other.global_floating_panes.or(self.global_floating_panes);
+        let new_pane_cwd = other.new_pane_cwd.or(self.new_pane_cwd);
+        let automatic_tab_rename = other.automatic_tab_rename.or(self.automatic_tab_rename);
+        let pane_frame_process_stats = other
+            .pane_frame_process_stats
+            .or(self.pane_frame_process_stats);
+        let confirm_close_with_running_processes = other
+            .confirm_close_with_running_processes
+            .or(self.confirm_close_with_running_processes);
+        let command_exit_notification_command = other
+            .command_exit_notification_command
+            .or_else(|| self.command_exit_notification_command.clone());
+        let max_panes_per_tab = other.max_panes_per_tab.or(self.max_panes_per_tab);
+        let max_tabs = other.max_tabs.or(self.max_tabs);
+        let max_scrollback_memory = other.max_scrollback_memory.or(self.max_scrollback_memory);
+        let idle_session_timeout = other.idle_session_timeout.or(self.idle_session_timeout);
+        let alternate_screen_scrollback = other
+            .alternate_screen_scrollback
+            .or(self.alternate_screen_scrollback);
+        let clear_on_alternate_screen_exit = other
+            .clear_on_alternate_screen_exit
+            .or(self.clear_on_alternate_screen_exit);
+        let ambiguous_width = other.ambiguous_width.or(self.ambiguous_width);
+        let grapheme_clusters = other.grapheme_clusters.or(self.grapheme_clusters);
+        let paste_sanitization = other.paste_sanitization.or(self.paste_sanitization);
 
         Options {
             simplified_ui,
@@ -369,6 +1078,7 @@ impl Options {
             on_force_close,
             scroll_buffer_size,
             copy_command,
+            copy_providers,
             copy_clipboard,
             copy_on_select,
             scrollback_editor,
@@ -385,6 +1095,34 @@ impl Options {
             stacked_resize,
             show_startup_tips,
             show_release_notes,
+            quiet_hours,
+            session_name_template,
+            session_name_command,
+            update_check,
+            command_pane_on_exit,
+            start_command_panes_suspended,
+            title_precedence,
+            tab_name_from_title,
+            bell_forwarding,
+            command_exit_banner,
+            idle_client_detach_timeout,
+            osc52_forwarding,
+            word_separators,
+            global_floating_panes,
+            new_pane_cwd,
+            automatic_tab_rename,
+            pane_frame_process_stats,
+            confirm_close_with_running_processes,
+            command_exit_notification_command,
+            max_panes_per_tab,
+            max_tabs,
+            max_scrollback_memory,
+            idle_session_timeout,
+            alternate_screen_scrollback,
+            clear_on_alternate_screen_exit,
+            ambiguous_width,
+            grapheme_clusters,
+            paste_sanitization,
         }
     }
 
@@ -438,6 +1176,7 @@ impl From<CliOptions> for Options {
             on_force_close: opts.on_force_close,
             scroll_buffer_size: opts.scroll_buffer_size,
             copy_command: opts.copy_command,
+            copy_providers: opts.copy_providers,
             copy_clipboard: opts.copy_clipboard,
             copy_on_select: opts.copy_on_select,
             scrollback_editor: opts.scrollback_editor,
@@ -453,6 +1192,34 @@ impl From<CliOptions> for Options {
             stacked_resize: opts.stacked_resize,
             show_release_notes: opts.show_release_notes,
             show_startup_tips: opts.show_startup_tips,
+            quiet_hours: opts.quiet_hours,
+            session_name_template: opts.session_name_template,
+            session_name_command: opts.session_name_command,
+            update_check: opts.update_check,
+            command_pane_on_exit: opts.command_pane_on_exit,
+            start_command_panes_suspended: opts.start_command_panes_suspended,
+            title_precedence: opts.title_precedence,
+            tab_name_from_title: opts.tab_name_from_title,
+            bell_forwarding: opts.bell_forwarding,
+            command_exit_banner: opts.command_exit_banner,
+            idle_client_detach_timeout: opts.idle_client_detach_timeout,
+            osc52_forwarding: opts.osc52_forwarding,
+            word_separators: opts.word_separators,
+            global_floating_panes: opts.global_floating_panes,
+            new_pane_cwd: opts.new_pane_cwd,
+            automatic_tab_rename: opts.automatic_tab_rename,
+            pane_frame_process_stats: opts.pane_frame_process_stats,
+            confirm_close_with_running_processes: opts.confirm_close_with_running_processes,
+            command_exit_notification_command: opts.command_exit_notification_command,
+            max_panes_per_tab: opts.max_panes_per_tab,
+            max_tabs: opts.max_tabs,
+            max_scrollback_memory: opts.max_scrollback_memory,
+            idle_session_timeout: opts.idle_session_timeout,
+            alternate_screen_scrollback: opts.alternate_screen_scrollback,
+            clear_on_alternate_screen_exit: opts.clear_on_alternate_screen_exit,
+            ambiguous_width: opts.ambiguous_width,
+            grapheme_clusters: opts.grapheme_clusters,
+            paste_sanitization: opts.paste_sanitization,
             ..Default::default()
         }
     }
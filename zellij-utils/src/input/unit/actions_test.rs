@@ -0,0 +1,60 @@
+use super::{Action, CliAction};
+use std::path::PathBuf;
+
+fn multi_exec_chars(command: &[&str]) -> String {
+    let cli_action = CliAction::MultiExec {
+        command: command.iter().map(|word| word.to_string()).collect(),
+        group: None,
+        exclude_focused: false,
+        no_enter: true,
+    };
+    match Action::actions_from_cli(cli_action, Box::new(|| PathBuf::from("/")), None)
+        .unwrap()
+        .as_slice()
+    {
+        [Action::MultiExec { chars, .. }] => chars.clone(),
+        actions => panic!("unexpected actions: {:?}", actions),
+    }
+}
+
+#[test]
+fn multi_exec_keeps_the_words_of_the_command() {
+    assert_eq!(
+        multi_exec_chars(&["source", ".env"]),
+        "source .env",
+        "plain words are typed as they are"
+    );
+    assert_eq!(
+        multi_exec_chars(&["git", "commit", "-m", "fix the build"]),
+        "git commit -m 'fix the build'"
+    );
+    assert_eq!(
+        multi_exec_chars(&["echo", "it's", ""]),
+        "echo 'it'\\''s' ''"
+    );
+    assert_eq!(
+        multi_exec_chars(&["cd /tmp && make"]),
+        "cd /tmp && make",
+        "a single argument is a whole command line"
+    );
+}
+
+#[test]
+fn multi_exec_presses_enter_unless_told_not_to() {
+    let cli_action = CliAction::MultiExec {
+        command: vec!["make".to_owned()],
+        group: None,
+        exclude_focused: false,
+        no_enter: false,
+    };
+    let actions =
+        Action::actions_from_cli(cli_action, Box::new(|| PathBuf::from("/")), None).unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::MultiExec {
+            chars: "make\r".to_owned(),
+            group: None,
+            exclude_focused: false,
+        }]
+    );
+}
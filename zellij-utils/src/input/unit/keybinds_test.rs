@@ -3,6 +3,7 @@ use super::super::keybinds::*;
 use crate::data::{BareKey, Direction, KeyWithModifier};
 use crate::input::config::Config;
 use insta::assert_snapshot;
+use std::str::FromStr;
 use strum::IntoEnumIterator;
 
 #[test]
@@ -554,3 +555,11 @@ fn error_received_on_unknown_key_instruction() {
     let config_error = Config::from_kdl(config_contents, None).unwrap_err();
     assert_snapshot!(format!("{:?}", config_error));
 }
+
+#[test]
+fn set_ui_rejects_unknown_elements_and_values() {
+    assert!(UiSettings::from_str("pane-frames=maybe").is_err());
+    assert!(UiSettings::from_str("status-bar=compact").is_err());
+    assert!(UiSettings::from_str("title-bar=hidden").is_err());
+    assert!(UiSettings::from_str("").is_err());
+}
@@ -1,4 +1,6 @@
 use super::super::layout::*;
+use crate::input::command::{CommandWatch, OutputLog, DEFAULT_WATCH_DEBOUNCE_MS};
+use crate::pane_size::PaneGeom;
 use insta::assert_snapshot;
 
 #[test]
@@ -524,6 +526,167 @@ fn layout_with_command_panes_and_start_suspended() {
     assert_snapshot!(format!("{:#?}", layout));
 }
 
+#[test]
+fn layout_with_command_panes_and_stdin_file() {
+    let kdl_layout = r#"
+        layout {
+            pane command="psql" stdin_file="script.sql"
+            pane_template name="psql" {
+                command "psql"
+                stdin_file "template_script.sql"
+            }
+            psql
+            psql stdin_file="other_script.sql"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let stdin_files: Vec<Option<PathBuf>> = tiled_panes
+        .children
+        .iter()
+        .map(|pane| match &pane.run {
+            Some(Run::Command(run_command)) => run_command.stdin_file.clone(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        stdin_files,
+        vec![
+            Some(PathBuf::from("script.sql")),
+            Some(PathBuf::from("template_script.sql")),
+            Some(PathBuf::from("other_script.sql")),
+        ]
+    );
+}
+
+#[test]
+fn layout_with_command_panes_logging_their_output() {
+    let kdl_layout = r#"
+        layout {
+            pane command="cargo" log_output="build.log" log_max_size=1024 log_max_files=3 log_strip_ansi=true
+            pane_template name="cargo" {
+                command "cargo"
+                log_output "template.log"
+            }
+            cargo
+            cargo log_output="other.log" log_max_files=0
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let log_outputs: Vec<Option<OutputLog>> = tiled_panes
+        .children
+        .iter()
+        .map(|pane| match &pane.run {
+            Some(Run::Command(run_command)) => run_command.log_output.clone(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        log_outputs,
+        vec![
+            Some(OutputLog {
+                path: PathBuf::from("build.log"),
+                max_size: Some(1024),
+                max_files: 3,
+                strip_ansi: true,
+            }),
+            Some(OutputLog {
+                path: PathBuf::from("template.log"),
+                max_size: None,
+                max_files: 1,
+                strip_ansi: false,
+            }),
+            Some(OutputLog {
+                path: PathBuf::from("other.log"),
+                max_size: None,
+                max_files: 0,
+                strip_ansi: false,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn layout_with_negative_output_log_limits_is_an_error() {
+    for pane in [
+        r#"pane command="cargo" log_output="build.log" log_max_size=-1"#,
+        r#"pane command="cargo" log_output="build.log" log_max_size=0"#,
+        r#"pane command="cargo" log_output="build.log" log_max_files=-1"#,
+    ] {
+        let kdl_layout = format!("layout {{ {} }}", pane);
+        let layout = Layout::from_kdl(&kdl_layout, Some("layout_file_name".into()), None, None);
+        assert!(layout.is_err(), "{} should be an error", pane);
+    }
+}
+
+#[test]
+fn layout_with_watched_command_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane command="cargo" close_on_exit=true {
+                args "test"
+                watch "**/*.rs" "Cargo.toml"
+                watch_debounce 500
+            }
+            pane_template name="make" {
+                command "make"
+                watch "Makefile"
+            }
+            make
+            pane command="htop"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let watched: Vec<Option<(CommandWatch, bool)>> = tiled_panes
+        .children
+        .iter()
+        .map(|pane| match &pane.run {
+            Some(Run::Command(run_command)) => run_command
+                .watch
+                .clone()
+                .map(|watch| (watch, run_command.hold_on_close)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        watched,
+        vec![
+            Some((
+                CommandWatch {
+                    patterns: vec!["**/*.rs".to_owned(), "Cargo.toml".to_owned()],
+                    debounce_ms: 500,
+                },
+                true
+            )),
+            Some((
+                CommandWatch {
+                    patterns: vec!["Makefile".to_owned()],
+                    debounce_ms: DEFAULT_WATCH_DEBOUNCE_MS,
+                },
+                true
+            )),
+            None,
+        ],
+        "watched panes stay open when their command exits"
+    );
+}
+
+#[test]
+fn layout_with_negative_watch_debounce_is_an_error() {
+    let kdl_layout = r#"
+        layout {
+            pane command="cargo" {
+                watch "**/*.rs"
+                watch_debounce -1
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None);
+    assert!(layout.is_err(), "a negative debounce does not wrap around");
+}
+
 #[test]
 fn layout_with_plugin_panes() {
     let kdl_layout = r#"
@@ -913,6 +1076,143 @@ fn layout_with_nested_pane_templates() {
     assert_snapshot!(format!("{:#?}", layout));
 }
 
+#[test]
+fn layout_with_global_pane_and_tab_templates() {
+    let config_templates = r#"
+        templates {
+            pane_template name="left-and-right" split_direction="Vertical" {
+                pane
+                children
+                pane
+            }
+            tab_template name="monitoring" {
+                left-and-right {
+                    pane command="htop"
+                }
+            }
+        }
+    "#;
+    let templates = crate::input::config::Config::from_kdl(config_templates, None)
+        .unwrap()
+        .templates;
+    let kdl_layout = r#"
+        layout {
+            monitoring
+            tab {
+                left-and-right
+            }
+        }
+    "#;
+    let kdl_layout_with_local_templates = r#"
+        layout {
+            pane_template name="left-and-right" split_direction="Vertical" {
+                pane
+                children
+                pane
+            }
+            tab_template name="monitoring" {
+                left-and-right {
+                    pane command="htop"
+                }
+            }
+            monitoring
+            tab {
+                left-and-right
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl_with_templates(
+        kdl_layout,
+        Some("layout_file_name".into()),
+        None,
+        None,
+        &templates,
+    )
+    .unwrap();
+    let expected_layout = Layout::from_kdl(
+        kdl_layout_with_local_templates,
+        Some("layout_file_name".into()),
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(layout, expected_layout);
+}
+
+#[test]
+fn layout_templates_take_precedence_over_global_templates() {
+    let config_templates = r#"
+        templates {
+            pane_template name="left-and-right" split_direction="Vertical" {
+                pane
+                children
+                pane
+            }
+        }
+    "#;
+    let templates = crate::input::config::Config::from_kdl(config_templates, None)
+        .unwrap()
+        .templates;
+    let kdl_layout = r#"
+        layout {
+            tab_template name="left-and-right" {
+                pane
+                children
+            }
+            left-and-right {
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl_with_templates(
+        kdl_layout,
+        Some("layout_file_name".into()),
+        None,
+        None,
+        &templates,
+    )
+    .unwrap();
+    let expected_layout =
+        Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    assert_eq!(layout, expected_layout);
+}
+
+#[test]
+fn new_tab_from_global_template() {
+    let config_templates = r#"
+        templates {
+            tab_template name="monitoring" split_direction="Vertical" {
+                pane command="htop"
+                pane
+            }
+        }
+    "#;
+    let templates = crate::input::config::Config::from_kdl(config_templates, None)
+        .unwrap()
+        .templates;
+    let layout = Layout::from_template_name("monitoring", &templates, None).unwrap();
+    let expected_layout = Layout::from_kdl(
+        r#"
+        layout {
+            tab_template name="monitoring" split_direction="Vertical" {
+                pane command="htop"
+                pane
+            }
+            monitoring
+        }
+    "#,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(layout, expected_layout);
+    assert!(
+        Layout::from_template_name("no-such-template", &templates, None).is_err(),
+        "unknown template names are an error"
+    );
+}
+
 #[test]
 fn layout_with_nested_branched_pane_templates() {
     let kdl_layout = r#"
@@ -1096,6 +1396,170 @@ fn layout_with_pane_excluded_from_sync() {
     assert_snapshot!(format!("{:#?}", layout));
 }
 
+#[test]
+fn layout_with_logged_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="logged" log=true
+            pane log=true
+            logged
+            pane
+            floating_panes {
+                pane log=true
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    let (tiled_layout, floating_layout) = layout.template.unwrap();
+    let logged_tiled_panes: Vec<bool> = tiled_layout.children.iter().map(|p| p.log).collect();
+    let logged_floating_panes: Vec<bool> = floating_layout.iter().map(|p| p.log).collect();
+    assert_eq!(logged_tiled_panes, vec![true, true, false]);
+    assert_eq!(logged_floating_panes, vec![true, false]);
+}
+
+#[test]
+fn layout_with_pane_size_limits() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="repl" min_size=10
+            pane split_direction="vertical" {
+                pane max_size=80
+                pane
+            }
+            repl max_size=20
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    let tiled_layout = layout.template.unwrap().0;
+    let mut space = PaneGeom::default();
+    space.rows.set_inner(50);
+    space.cols.set_inner(200);
+    let size_limits: Vec<Option<SizeLimits>> = tiled_layout
+        .position_panes_in_space(&space, None, false, false)
+        .unwrap()
+        .into_iter()
+        .map(|(pane_layout, _geom)| pane_layout.size_limits)
+        .collect();
+    assert_eq!(
+        size_limits,
+        vec![
+            Some(SizeLimits {
+                min: None,
+                max: Some(80),
+                split_direction: Some(SplitDirection::Vertical),
+            }),
+            Some(SizeLimits {
+                min: Some(10),
+                max: Some(20),
+                split_direction: Some(SplitDirection::Horizontal),
+            }),
+            None,
+        ],
+        "limits are along the split direction of the parent"
+    );
+    assert_eq!(size_limits[0].unwrap().cols(), (None, Some(80)));
+    assert_eq!(size_limits[0].unwrap().rows(), (None, None));
+    assert_eq!(size_limits[1].unwrap().rows(), (Some(10), Some(20)));
+}
+
+#[test]
+fn pane_min_size_cannot_be_greater_than_its_max_size() {
+    let kdl_layout = r#"
+        layout {
+            pane min_size=20 max_size=10
+        }
+    "#;
+    assert!(Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).is_err());
+}
+
+#[test]
+fn layout_with_pane_scrollback_sizes() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="build" scrollback_size="unlimited"
+            pane scrollback_size=500
+            build
+            build scrollback_size=100
+            pane
+            floating_panes {
+                pane scrollback_size="unlimited"
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    let (tiled_layout, floating_layout) = layout.template.unwrap();
+    let tiled_scrollback_sizes: Vec<Option<ScrollbackSize>> = tiled_layout
+        .children
+        .iter()
+        .map(|p| p.scrollback_size)
+        .collect();
+    let floating_scrollback_sizes: Vec<Option<ScrollbackSize>> =
+        floating_layout.iter().map(|p| p.scrollback_size).collect();
+    assert_eq!(
+        tiled_scrollback_sizes,
+        vec![
+            Some(ScrollbackSize::Lines(500)),
+            Some(ScrollbackSize::Unlimited),
+            Some(ScrollbackSize::Lines(100)),
+            None
+        ]
+    );
+    assert_eq!(
+        floating_scrollback_sizes,
+        vec![Some(ScrollbackSize::Unlimited), None]
+    );
+}
+
+#[test]
+fn layout_with_pane_alternate_screen_overrides() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="pager" alternate_screen_scrollback=true
+            pane clear_on_alternate_screen_exit=true
+            pager
+            pager alternate_screen_scrollback=false
+            floating_panes {
+                pane alternate_screen_scrollback=true clear_on_alternate_screen_exit=false
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    let (tiled_layout, floating_layout) = layout.template.unwrap();
+    let tiled_overrides: Vec<(Option<bool>, Option<bool>)> = tiled_layout
+        .children
+        .iter()
+        .map(|p| {
+            (
+                p.alternate_screen_scrollback,
+                p.clear_on_alternate_screen_exit,
+            )
+        })
+        .collect();
+    assert_eq!(
+        tiled_overrides,
+        vec![(None, Some(true)), (Some(true), None), (Some(false), None)]
+    );
+    assert_eq!(floating_layout[0].alternate_screen_scrollback, Some(true));
+    assert_eq!(
+        floating_layout[0].clear_on_alternate_screen_exit,
+        Some(false)
+    );
+}
+
+#[test]
+fn pane_scrollback_size_must_be_a_number_of_lines_or_unlimited() {
+    for scrollback_size in ["0", "\"lots\""] {
+        let kdl_layout = format!("layout {{\n pane scrollback_size={}\n}}", scrollback_size);
+        assert!(
+            Layout::from_kdl(&kdl_layout, Some("layout_file_name".into()), None, None).is_err(),
+            "scrollback_size={} is an error",
+            scrollback_size
+        );
+    }
+}
+
 #[test]
 fn cannot_define_tab_template_name_with_space() {
     let kdl_layout = r#"
@@ -2242,3 +2706,84 @@ fn env_var_missing() {
     let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None);
     assert!(layout.is_err(), "invalid env var lookup should fail");
 }
+
+#[test]
+fn layout_variables_are_substituted() {
+    let raw_layout = r#"
+        layout {
+            tab name="{{ project }}" cwd="/projects/{{project}}" {
+                pane command="make" {
+                    args "{{target}}"
+                }
+            }
+        }
+    "#;
+    let variables = BTreeMap::from([
+        ("project".to_owned(), "zellij".to_owned()),
+        ("target".to_owned(), "say \"hi\"".to_owned()),
+    ]);
+    let raw_layout = Layout::substitute_variables(raw_layout, &variables).unwrap();
+    assert!(raw_layout.contains(r#"tab name="zellij" cwd="/projects/zellij""#));
+    assert!(raw_layout.contains(r#"args "say \"hi\"""#));
+    let layout = Layout::from_kdl(&raw_layout, Some("layout_file_name".into()), None, None);
+    assert!(layout.is_ok(), "substituted layout should parse");
+}
+
+#[test]
+fn layout_variables_without_a_value_are_an_error() {
+    let raw_layout = r#"layout { pane cwd="/projects/{{project}}"; }"#;
+    let variables = BTreeMap::from([("target".to_owned(), "build".to_owned())]);
+    let result = Layout::substitute_variables(raw_layout, &variables);
+    assert!(result.is_err(), "placeholders without a value should fail");
+}
+
+#[test]
+fn layouts_are_left_as_is_without_variables() {
+    let raw_layout = r#"layout { pane command="sh" { args "-c" "echo '{{not a variable}}'"; }; }"#;
+    let result = Layout::substitute_variables(raw_layout, &BTreeMap::new());
+    assert_eq!(result.unwrap(), raw_layout);
+}
+
+#[test]
+fn layout_variables_are_substituted_in_swap_layouts() {
+    use crate::{cli::CliAction, input::actions::Action};
+
+    let layout_dir = tempfile::tempdir().unwrap();
+    let layout_path = layout_dir.path().join("project.kdl");
+    std::fs::write(
+        &layout_path,
+        r#"layout { pane cwd="/projects/{{project}}"; }"#,
+    )
+    .unwrap();
+    std::fs::write(
+        layout_dir.path().join("project.swap.kdl"),
+        r#"swap_tiled_layout name="{{project}} vertical" {
+            tab max_panes=5 {
+                pane split_direction="vertical" {
+                    pane
+                    pane { children; }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let new_tab = CliAction::NewTab {
+        layout: Some(layout_path),
+        layout_dir: None,
+        template: None,
+        name: None,
+        cwd: None,
+        vars: vec![("project".to_owned(), "zellij".to_owned())],
+    };
+    let actions = Action::actions_from_cli(new_tab, Box::new(|| PathBuf::from("/")), None).unwrap();
+    match actions.first() {
+        Some(Action::NewTab(_, _, Some(swap_tiled_layouts), _, _, _)) => {
+            let swap_layout_names: Vec<_> = swap_tiled_layouts
+                .iter()
+                .map(|(_, name)| name.clone())
+                .collect();
+            assert_eq!(swap_layout_names, vec![Some("zellij vertical".to_owned())]);
+        },
+        other => panic!("expected a new tab with swap layouts, got: {:?}", other),
+    }
+}
@@ -1,22 +1,37 @@
 //! IPC stuff for starting to split things into a client and server model.
 use crate::{
     cli::CliArgs,
-    data::{ClientId, ConnectToSession, KeyWithModifier, Style},
+    data::{ClientId, ConnectToSession, Event, KeyWithModifier, Style},
     errors::{get_current_ctx, prelude::*, ErrorContext},
     input::config::Config,
-    input::{actions::Action, layout::Layout, options::Options, plugins::PluginAliases},
+    input::{
+        actions::Action,
+        layout::{Layout, Run},
+        options::Options,
+        plugins::PluginAliases,
+    },
     pane_size::{Size, SizeInPixels},
 };
 use interprocess::local_socket::LocalSocketStream;
 use log::warn;
-use nix::unistd::dup;
+use nix::{
+    sys::{
+        socket::{
+            recvmsg, sendmsg, setsockopt, sockopt, ControlMessage, ControlMessageOwned, MsgFlags,
+        },
+        time::{TimeVal, TimeValLike},
+        uio::IoVec,
+    },
+    unistd::dup,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Display, Error, Formatter},
     io::{self, Write},
     marker::PhantomData,
-    os::unix::io::{AsRawFd, FromRawFd},
+    os::unix::io::{AsRawFd, FromRawFd, RawFd},
     path::PathBuf,
+    time::{Duration, SystemTime},
 };
 
 type SessionId = u64;
@@ -42,6 +57,116 @@ pub enum ClientType {
 pub struct ClientAttributes {
     pub size: Size,
     pub style: Style,
+    // the session rejects the keys and actions of read-only clients (`zellij attach --read-only`)
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+// Live metadata about a running session, reported to `zellij list-sessions --long`
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionStats {
+    pub connected_clients: usize,
+    pub tab_count: usize,
+    pub pane_count: usize,
+    pub last_attached: Option<SystemTime>,
+}
+
+// A signal sent to the processes of every pane before killing a session (see
+// `zellij kill-session --signal`)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneSignal {
+    Term,
+    Int,
+    Hup,
+    Kill, // only sent by us, to a stopped command that ignored the other signals
+}
+
+impl PaneSignal {
+    /// The harsher signal to send to a command that did not exit after this one, if any
+    pub fn escalated(&self) -> Option<PaneSignal> {
+        match self {
+            PaneSignal::Hup | PaneSignal::Int => Some(PaneSignal::Term),
+            PaneSignal::Term => Some(PaneSignal::Kill),
+            PaneSignal::Kill => None,
+        }
+    }
+}
+
+impl std::str::FromStr for PaneSignal {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let signal = s.to_uppercase();
+        match signal.strip_prefix("SIG").unwrap_or(&signal) {
+            "TERM" => Ok(PaneSignal::Term),
+            "INT" => Ok(PaneSignal::Int),
+            "HUP" => Ok(PaneSignal::Hup),
+            _ => Err(format!(
+                "Unsupported signal: {}, expected one of TERM, INT or HUP",
+                s
+            )),
+        }
+    }
+}
+
+/// The colors the terminal of a client can display, the colors rendered to it are downgraded to
+/// these
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    #[default]
+    TrueColor,
+    Colors256,
+    Colors16,
+}
+
+impl ColorSupport {
+    /// Guesses the color support of a terminal from its `TERM` and `COLORTERM` environment
+    /// variables, terminals we know nothing about are assumed to support true color (which is
+    /// what we render without a downgrade). A plain `xterm` or `screen` only promises 8 colors,
+    /// terminals setting it while supporting more tell us so when asked for their capabilities
+    /// (see [`ColorSupport::from_color_count`])
+    pub fn from_env(term: Option<&str>, colorterm: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor") | Some("24bit")) {
+            return ColorSupport::TrueColor;
+        }
+        let term = match term {
+            Some(term) => term,
+            None => return ColorSupport::TrueColor,
+        };
+        if term.contains("truecolor") || term.contains("24bit") || term.contains("direct") {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Colors256
+        } else if matches!(
+            term,
+            "xterm" | "xterm-color" | "screen" | "tmux" | "rxvt" | "linux" | "ansi" | "dumb"
+        ) || term.starts_with("screen.")
+            || term.starts_with("vt")
+            || term.starts_with("cons")
+            || term.ends_with("16color")
+        {
+            ColorSupport::Colors16
+        } else {
+            ColorSupport::TrueColor
+        }
+    }
+    /// The color support of a terminal that reported how many colors it can display (eg. the
+    /// `colors` capability of its terminfo entry)
+    pub fn from_color_count(colors: usize) -> Self {
+        if colors >= ColorSupport::TrueColor.color_count() {
+            ColorSupport::TrueColor
+        } else if colors >= 256 {
+            ColorSupport::Colors256
+        } else {
+            ColorSupport::Colors16
+        }
+    }
+    pub fn color_count(&self) -> usize {
+        match self {
+            ColorSupport::TrueColor => 1 << 24,
+            ColorSupport::Colors256 => 256,
+            ColorSupport::Colors16 => 16,
+        }
+    }
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -61,6 +186,15 @@ impl PixelDimensions {
     }
 }
 
+// A running terminal pane moved to this session from another one (`zellij action move-pane
+// --to-session`), the file descriptor of its pty is sent right after it is accepted
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MovedPane {
+    pub name: Option<String>, // if the user renamed it
+    pub invoked_with: Option<Run>,
+    pub scrollback: Option<String>,
+}
+
 // Types of messages sent from the client to the server
 #[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -71,6 +205,10 @@ pub enum ClientToServerMsg {
     ForegroundColor(String),
     ColorRegisters(Vec<(usize, String)>),
     TerminalResize(Size),
+    TerminalFocusChanged(bool), // bool -> is_focused
+    TerminalSixelSupport(bool),
+    TerminalKittyGraphicsSupport,
+    TerminalColorSupport(ColorSupport),
     NewClient(
         ClientAttributes,
         Box<CliArgs>,
@@ -92,8 +230,14 @@ pub enum ClientToServerMsg {
     ClientExited,
     KillSession,
     ConnStatus,
+    QuerySessionStats,
+    SignalPanes(PaneSignal),
+    SubscribeToEvents,
+    WriteStdinToPane(u32, Vec<u8>), // u32 is the terminal id
+    CloseStdinPipe(u32),            // u32 is the terminal id
     ConfigWrittenToDisk(Config),
     FailedToWriteConfigToDisk(Option<PathBuf>),
+    AdoptPane(MovedPane, i32), // i32 is the pid of the process running in the pane
 }
 
 // Types of messages sent from the server to the client
@@ -110,6 +254,14 @@ pub enum ServerToClientMsg {
     CliPipeOutput(String, String), // String -> pipe name, String -> Output
     QueryTerminalSize,
     WriteConfigToDisk { config: String },
+    SessionStats(SessionStats),
+    CapturedPaneOutput(Vec<u8>),
+    CapturedPaneExited(Option<i32>), // exit status of the command
+    Event(Event),
+    StdinPipeReady(u32), // u32 is the terminal id
+    StdinPipeWritten,
+    ReadyToAdoptPane,
+    RenamedSession(String), // String -> new session name
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -117,6 +269,7 @@ pub enum ExitReason {
     Normal,
     NormalDetached,
     ForceDetached,
+    IdleDetached,
     CannotAttach,
     Disconnect,
     Error(String),
@@ -131,6 +284,10 @@ impl Display for ExitReason {
                 f,
                 "Session was detached from this client (possibly because another client connected)"
             ),
+            Self::IdleDetached => write!(
+                f,
+                "Session detached from this client after it was idle for too long"
+            ),
             Self::CannotAttach => write!(
                 f,
                 "Session attached to another client. Use --force flag to force connect."
@@ -192,6 +349,24 @@ impl<T: Serialize> IpcSenderWithContext<T> {
         }
     }
 
+    /// Sends a file descriptor (eg. of a pty) to the process on the other end of this socket,
+    /// where it is received with [`IpcReceiverWithContext::recv_fd`].
+    pub fn send_fd(&mut self, fd: RawFd) -> Result<()> {
+        self.sender.flush().context("failed to flush ipc sender")?;
+        // the file descriptor travels along with a single byte
+        let iov = [IoVec::from_slice(&[0u8])];
+        let fds = [fd];
+        sendmsg(
+            self.sender.get_ref().as_raw_fd(),
+            &iov,
+            &[ControlMessage::ScmRights(&fds)],
+            MsgFlags::empty(),
+            None,
+        )
+        .context("failed to send file descriptor")?;
+        Ok(())
+    }
+
     /// Returns an [`IpcReceiverWithContext`] with the same socket as this sender.
     pub fn get_receiver<F>(&self) -> IpcReceiverWithContext<F>
     where
@@ -233,6 +408,41 @@ where
         }
     }
 
+    /// Receives a file descriptor sent with [`IpcSenderWithContext::send_fd`]. The other end must
+    /// wait for us to ask for it, because it would be lost if it arrived while we read a message.
+    pub fn recv_fd(&mut self) -> Result<RawFd> {
+        if !self.receiver.buffer().is_empty() {
+            bail!("received a message instead of a file descriptor");
+        }
+        let mut byte = [0u8; 1];
+        let iov = [IoVec::from_mut_slice(&mut byte)];
+        let mut cmsg_buffer = nix::cmsg_space!([RawFd; 1]);
+        let message = recvmsg(
+            self.receiver.get_ref().as_raw_fd(),
+            &iov,
+            Some(&mut cmsg_buffer),
+            MsgFlags::empty(),
+        )
+        .context("failed to receive file descriptor")?;
+        message
+            .cmsgs()
+            .find_map(|cmsg| match cmsg {
+                ControlMessageOwned::ScmRights(fds) => fds.first().copied(),
+                _ => None,
+            })
+            .context("no file descriptor received")
+    }
+
+    /// Makes [`recv`](Self::recv) give up if nothing was received for this long.
+    pub fn set_timeout(&self, timeout: Duration) -> Result<()> {
+        setsockopt(
+            self.receiver.get_ref().as_raw_fd(),
+            sockopt::ReceiveTimeout,
+            &TimeVal::milliseconds(timeout.as_millis() as i64),
+        )
+        .context("failed to set ipc receiver timeout")
+    }
+
     /// Returns an [`IpcSenderWithContext`] with the same socket as this receiver.
     pub fn get_sender<F: Serialize>(&self) -> IpcSenderWithContext<F> {
         let sock_fd = self.receiver.get_ref().as_raw_fd();
@@ -241,3 +451,93 @@ where
         IpcSenderWithContext::new(socket)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::os::unix::{io::IntoRawFd, net::UnixStream};
+
+    #[test]
+    fn file_descriptors_are_received_after_the_message_announcing_them() {
+        let (server_side, client_side) = UnixStream::pair().unwrap();
+        let mut sender: IpcSenderWithContext<ClientToServerMsg> =
+            IpcSenderWithContext::new(unsafe {
+                LocalSocketStream::from_raw_fd(client_side.into_raw_fd())
+            });
+        let mut receiver: IpcReceiverWithContext<ClientToServerMsg> =
+            IpcReceiverWithContext::new(unsafe {
+                LocalSocketStream::from_raw_fd(server_side.into_raw_fd())
+            });
+        let (read_end, write_end) = nix::unistd::pipe().unwrap();
+
+        sender.send(ClientToServerMsg::KillSession).unwrap();
+        assert!(matches!(
+            receiver.recv(),
+            Some((ClientToServerMsg::KillSession, _))
+        ));
+        sender.send_fd(read_end).unwrap();
+        let received_fd = receiver.recv_fd().unwrap();
+        nix::unistd::close(read_end).unwrap();
+
+        nix::unistd::write(write_end, b"hi").unwrap();
+        nix::unistd::close(write_end).unwrap();
+        let mut received = String::new();
+        unsafe { std::fs::File::from_raw_fd(received_fd) }
+            .read_to_string(&mut received)
+            .unwrap();
+        assert_eq!(received, "hi");
+    }
+
+    #[test]
+    fn color_support_is_guessed_from_the_terminal_environment() {
+        assert_eq!(
+            ColorSupport::from_env(Some("xterm-256color"), Some("truecolor")),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("xterm-256color"), None),
+            ColorSupport::Colors256
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("screen-256color"), None),
+            ColorSupport::Colors256
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("linux"), None),
+            ColorSupport::Colors16
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("vt220"), None),
+            ColorSupport::Colors16
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("xterm"), None),
+            ColorSupport::Colors16
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("screen"), None),
+            ColorSupport::Colors16
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("xterm"), Some("truecolor")),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("alacritty"), None),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(ColorSupport::from_env(None, None), ColorSupport::TrueColor);
+    }
+
+    #[test]
+    fn color_support_is_derived_from_the_reported_color_count() {
+        assert_eq!(ColorSupport::from_color_count(8), ColorSupport::Colors16);
+        assert_eq!(ColorSupport::from_color_count(16), ColorSupport::Colors16);
+        assert_eq!(ColorSupport::from_color_count(256), ColorSupport::Colors256);
+        assert_eq!(
+            ColorSupport::from_color_count(16777216),
+            ColorSupport::TrueColor
+        );
+    }
+}
@@ -1,10 +1,10 @@
 use crate::input::{
-    command::RunCommand,
+    command::{CommandWatch, OutputLog, RunCommand},
     config::ConfigError,
     layout::{
-        FloatingPaneLayout, Layout, LayoutConstraint, PercentOrFixed, PluginUserConfiguration, Run,
-        RunPluginOrAlias, SplitDirection, SplitSize, SwapFloatingLayout, SwapTiledLayout,
-        TiledPaneLayout,
+        FloatingPaneLayout, Layout, LayoutConstraint, LayoutTemplates, PercentOrFixed,
+        PluginUserConfiguration, Run, RunPluginOrAlias, ScrollbackSize, SizeLimits, SplitDirection,
+        SplitSize, SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
     },
 };
 
@@ -41,6 +41,7 @@ pub struct KdlLayoutParser<'a> {
     default_tab_template: Option<(TiledPaneLayout, Vec<FloatingPaneLayout>, KdlNode)>,
     new_tab_template: Option<(TiledPaneLayout, Vec<FloatingPaneLayout>)>,
     file_name: Option<PathBuf>,
+    global_templates: &'a LayoutTemplates,
 }
 
 impl<'a> KdlLayoutParser<'a> {
@@ -48,9 +49,11 @@ impl<'a> KdlLayoutParser<'a> {
         raw_layout: &'a str,
         global_cwd: Option<PathBuf>,
         file_name: Option<String>,
+        global_templates: &'a LayoutTemplates,
     ) -> Self {
         KdlLayoutParser {
             raw_layout,
+            global_templates,
             tab_templates: HashMap::new(),
             pane_templates: HashMap::new(),
             default_tab_template: None,
@@ -76,6 +79,13 @@ impl<'a> KdlLayoutParser<'a> {
             || word == "args"
             || word == "close_on_exit"
             || word == "start_suspended"
+            || word == "stdin_file"
+            || word == "log_output"
+            || word == "log_max_size"
+            || word == "log_max_files"
+            || word == "log_strip_ansi"
+            || word == "watch"
+            || word == "watch_debounce"
             || word == "borderless"
             || word == "focus"
             || word == "name"
@@ -86,6 +96,13 @@ impl<'a> KdlLayoutParser<'a> {
             || word == "swap_floating_layout"
             || word == "hide_floating_panes"
             || word == "contents_file"
+            || word == "log"
+            || word == "fullscreen"
+            || word == "min_size"
+            || word == "max_size"
+            || word == "scrollback_size"
+            || word == "alternate_screen_scrollback"
+            || word == "clear_on_alternate_screen_exit"
     }
     fn is_a_valid_pane_property(&self, property_name: &str) -> bool {
         property_name == "borderless"
@@ -99,6 +116,13 @@ impl<'a> KdlLayoutParser<'a> {
             || property_name == "args"
             || property_name == "close_on_exit"
             || property_name == "start_suspended"
+            || property_name == "stdin_file"
+            || property_name == "log_output"
+            || property_name == "log_max_size"
+            || property_name == "log_max_files"
+            || property_name == "log_strip_ansi"
+            || property_name == "watch"
+            || property_name == "watch_debounce"
             || property_name == "split_direction"
             || property_name == "pane"
             || property_name == "children"
@@ -106,6 +130,13 @@ impl<'a> KdlLayoutParser<'a> {
             || property_name == "expanded"
             || property_name == "exclude_from_sync"
             || property_name == "contents_file"
+            || property_name == "log"
+            || property_name == "fullscreen"
+            || property_name == "min_size"
+            || property_name == "max_size"
+            || property_name == "scrollback_size"
+            || property_name == "alternate_screen_scrollback"
+            || property_name == "clear_on_alternate_screen_exit"
     }
     fn is_a_valid_floating_pane_property(&self, property_name: &str) -> bool {
         property_name == "borderless"
@@ -118,12 +149,23 @@ impl<'a> KdlLayoutParser<'a> {
             || property_name == "args"
             || property_name == "close_on_exit"
             || property_name == "start_suspended"
+            || property_name == "stdin_file"
+            || property_name == "log_output"
+            || property_name == "log_max_size"
+            || property_name == "log_max_files"
+            || property_name == "log_strip_ansi"
+            || property_name == "watch"
+            || property_name == "watch_debounce"
             || property_name == "x"
             || property_name == "y"
             || property_name == "width"
             || property_name == "height"
             || property_name == "pinned"
             || property_name == "contents_file"
+            || property_name == "log"
+            || property_name == "scrollback_size"
+            || property_name == "alternate_screen_scrollback"
+            || property_name == "clear_on_alternate_screen_exit"
     }
     fn is_a_valid_tab_property(&self, property_name: &str) -> bool {
         property_name == "focus"
@@ -245,6 +287,64 @@ impl<'a> KdlLayoutParser<'a> {
             Ok(None)
         }
     }
+    fn parse_size_limits(&self, kdl_node: &KdlNode) -> Result<Option<SizeLimits>, ConfigError> {
+        let mut size_limits = SizeLimits::default();
+        for (value_name, limit) in [
+            ("min_size", &mut size_limits.min),
+            ("max_size", &mut size_limits.max),
+        ] {
+            match kdl_get_int_property_or_child_value!(kdl_node, value_name) {
+                Some(size) if size > 0 => *limit = Some(size as usize),
+                Some(_) => {
+                    return Err(kdl_parsing_error!(
+                        format!("{} should be greater than 0", value_name),
+                        kdl_node
+                    ));
+                },
+                None => {
+                    if let Some(node) = kdl_property_or_child_value_node!(kdl_node, value_name) {
+                        return Err(kdl_parsing_error!(
+                            format!(
+                                "{} should be a fixed number of rows or columns (eg. 10)",
+                                value_name
+                            ),
+                            node
+                        ));
+                    }
+                },
+            }
+        }
+        match (size_limits.min, size_limits.max) {
+            (None, None) => Ok(None),
+            (Some(min), Some(max)) if min > max => Err(kdl_parsing_error!(
+                format!("min_size cannot be greater than max_size"),
+                kdl_node
+            )),
+            _ => Ok(Some(size_limits)),
+        }
+    }
+    fn parse_scrollback_size(
+        &self,
+        kdl_node: &KdlNode,
+    ) -> Result<Option<ScrollbackSize>, ConfigError> {
+        match kdl_get_int_property_or_child_value!(kdl_node, "scrollback_size") {
+            Some(lines) if lines > 0 => Ok(Some(ScrollbackSize::Lines(lines as usize))),
+            Some(_) => Err(kdl_parsing_error!(
+                format!("scrollback_size should be greater than 0"),
+                kdl_node
+            )),
+            None => match kdl_get_string_property_or_child_value!(kdl_node, "scrollback_size") {
+                Some("unlimited") => Ok(Some(ScrollbackSize::Unlimited)),
+                _ => match kdl_property_or_child_value_node!(kdl_node, "scrollback_size") {
+                    Some(node) => Err(kdl_parsing_error!(
+                        format!("scrollback_size should be a number of lines (eg. 10000) or \"unlimited\""),
+                        node
+                    )),
+                    None => Ok(None),
+                },
+            },
+        }
+    }
     fn parse_percent_or_fixed(
         &self,
         kdl_node: &KdlNode,
@@ -424,6 +524,70 @@ impl<'a> KdlLayoutParser<'a> {
             None => Ok(None),
         }
     }
+    fn parse_output_log(&self, pane_node: &KdlNode) -> Result<Option<OutputLog>, ConfigError> {
+        let path = match self.parse_path(pane_node, "log_output")? {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let max_size = match kdl_get_int_property_or_child_value!(pane_node, "log_max_size") {
+            Some(max_size) if max_size > 0 => Some(max_size as u64),
+            Some(_) => {
+                return Err(kdl_parsing_error!(
+                    format!("log_max_size should be greater than 0"),
+                    pane_node
+                ))
+            },
+            None => None,
+        };
+        let max_files = match kdl_get_int_property_or_child_value!(pane_node, "log_max_files") {
+            Some(max_files) if max_files >= 0 => max_files as usize,
+            Some(_) => {
+                return Err(kdl_parsing_error!(
+                    format!("log_max_files should not be negative"),
+                    pane_node
+                ))
+            },
+            None => 1,
+        };
+        let strip_ansi =
+            kdl_get_bool_property_or_child_value_with_error!(pane_node, "log_strip_ansi")
+                .unwrap_or(false);
+        Ok(Some(OutputLog {
+            path,
+            max_size,
+            max_files,
+            strip_ansi,
+        }))
+    }
+    fn parse_command_watch(
+        &self,
+        pane_node: &KdlNode,
+    ) -> Result<Option<CommandWatch>, ConfigError> {
+        match kdl_get_child!(pane_node, "watch") {
+            Some(kdl_watch) => {
+                if kdl_watch.entries().is_empty() {
+                    return Err(kdl_parsing_error!(format!("watch cannot be empty and should contain one or more glob patterns (eg. watch \"**/*.rs\" \"Cargo.toml\")"), kdl_watch));
+                }
+                let patterns = kdl_string_arguments!(kdl_watch)
+                    .iter()
+                    .map(|s| String::from(*s))
+                    .collect();
+                let debounce_ms =
+                    match kdl_get_int_property_or_child_value!(pane_node, "watch_debounce") {
+                        Some(debounce_ms) if debounce_ms >= 0 => Some(debounce_ms as u64),
+                        Some(_) => {
+                            return Err(kdl_parsing_error!(
+                                format!("watch_debounce should not be negative"),
+                                pane_node
+                            ))
+                        },
+                        None => None,
+                    };
+                Ok(CommandWatch::new(patterns, debounce_ms))
+            },
+            None => Ok(None),
+        }
+    }
     fn parse_pane_command(
         &self,
         pane_node: &KdlNode,
@@ -437,6 +601,9 @@ impl<'a> KdlLayoutParser<'a> {
             kdl_get_bool_property_or_child_value_with_error!(pane_node, "close_on_exit");
         let start_suspended =
             kdl_get_bool_property_or_child_value_with_error!(pane_node, "start_suspended");
+        let stdin_file = self.parse_path(pane_node, "stdin_file")?;
+        let log_output = self.parse_output_log(pane_node)?;
+        let watch = self.parse_command_watch(pane_node)?;
         if !is_template {
             self.assert_no_bare_attributes_in_pane_node(
                 &command,
@@ -446,7 +613,8 @@ impl<'a> KdlLayoutParser<'a> {
                 pane_node,
             )?;
         }
-        let hold_on_close = close_on_exit.map(|c| !c).unwrap_or(true);
+        // a watched command is re-run in its pane, so the pane stays open when it exits
+        let hold_on_close = close_on_exit.map(|c| !c).unwrap_or(true) || watch.is_some();
         let hold_on_start = start_suspended.map(|c| c).unwrap_or(false);
         match (command, edit, cwd) {
             (None, None, Some(cwd)) => Ok(Some(Run::Cwd(cwd))),
@@ -456,6 +624,9 @@ impl<'a> KdlLayoutParser<'a> {
                 cwd,
                 hold_on_close,
                 hold_on_start,
+                stdin_file,
+                log_output,
+                watch,
                 ..Default::default()
             }))),
             (None, Some(edit), Some(cwd)) => {
@@ -534,7 +705,19 @@ impl<'a> KdlLayoutParser<'a> {
             kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_sync");
         let contents_file =
             kdl_get_string_property_or_child_value_with_error!(kdl_node, "contents_file");
+        let log = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "log");
+        let fullscreen = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "fullscreen");
+        let scrollback_size = self.parse_scrollback_size(kdl_node)?;
+        let alternate_screen_scrollback = kdl_get_bool_property_or_child_value_with_error!(
+            kdl_node,
+            "alternate_screen_scrollback"
+        );
+        let clear_on_alternate_screen_exit = kdl_get_bool_property_or_child_value_with_error!(
+            kdl_node,
+            "clear_on_alternate_screen_exit"
+        );
         let split_size = self.parse_split_size(kdl_node)?;
+        let size_limits = self.parse_size_limits(kdl_node)?;
         let run = self.parse_command_plugin_or_edit_block(kdl_node)?;
         let children_split_direction = self.parse_split_direction(kdl_node)?;
         let (external_children_index, children) = match kdl_children_nodes!(kdl_node) {
@@ -584,6 +767,12 @@ impl<'a> KdlLayoutParser<'a> {
             children,
             children_are_stacked,
             is_expanded_in_stack,
+            log: log.unwrap_or_default(),
+            fullscreen: fullscreen.unwrap_or_default(),
+            size_limits,
+            scrollback_size,
+            alternate_screen_scrollback,
+            clear_on_alternate_screen_exit,
             pane_initial_contents,
             ..Default::default()
         })
@@ -604,6 +793,16 @@ impl<'a> KdlLayoutParser<'a> {
             .map(|name| name.to_string());
         let contents_file =
             kdl_get_string_property_or_child_value_with_error!(kdl_node, "contents_file");
+        let log = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "log");
+        let scrollback_size = self.parse_scrollback_size(kdl_node)?;
+        let alternate_screen_scrollback = kdl_get_bool_property_or_child_value_with_error!(
+            kdl_node,
+            "alternate_screen_scrollback"
+        );
+        let clear_on_alternate_screen_exit = kdl_get_bool_property_or_child_value_with_error!(
+            kdl_node,
+            "clear_on_alternate_screen_exit"
+        );
         self.assert_no_mixed_children_and_properties(kdl_node)?;
         let pane_initial_contents = contents_file.and_then(|contents_file| {
             self.file_name
@@ -622,6 +821,10 @@ impl<'a> KdlLayoutParser<'a> {
             run,
             focus,
             pinned,
+            log: log.unwrap_or_default(),
+            scrollback_size,
+            alternate_screen_scrollback,
+            clear_on_alternate_screen_exit,
             pane_initial_contents,
             ..Default::default()
         })
@@ -715,10 +918,25 @@ impl<'a> KdlLayoutParser<'a> {
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "close_on_exit");
                 let start_suspended =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "start_suspended");
+                let stdin_file = self.parse_path(kdl_node, "stdin_file")?;
+                let log_output = self.parse_output_log(kdl_node)?;
                 let split_size = self.parse_split_size(kdl_node)?;
+                let size_limits = self.parse_size_limits(kdl_node)?;
                 let run = self.parse_command_plugin_or_edit_block_for_template(kdl_node)?;
                 let exclude_from_sync =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_sync");
+                let log = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "log");
+                let fullscreen =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "fullscreen");
+                let scrollback_size = self.parse_scrollback_size(kdl_node)?;
+                let alternate_screen_scrollback = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "alternate_screen_scrollback"
+                );
+                let clear_on_alternate_screen_exit = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "clear_on_alternate_screen_exit"
+                );
 
                 let external_children_index = if should_mark_external_children_index {
                     self.populate_external_children_index(kdl_node)?
@@ -745,6 +963,8 @@ impl<'a> KdlLayoutParser<'a> {
                     pane_template_run_command.add_args(args);
                     pane_template_run_command.add_close_on_exit(close_on_exit);
                     pane_template_run_command.add_start_suspended(start_suspended);
+                    pane_template_run_command.add_stdin_file(stdin_file);
+                    pane_template_run_command.add_log_output(log_output);
                 };
                 if let Some(borderless) = borderless {
                     pane_template.borderless = borderless;
@@ -758,9 +978,34 @@ impl<'a> KdlLayoutParser<'a> {
                 if let Some(exclude_from_sync) = exclude_from_sync {
                     pane_template.exclude_from_sync = Some(exclude_from_sync);
                 }
+                if let Some(log) = log {
+                    pane_template.log = log;
+                }
+                if let Some(fullscreen) = fullscreen {
+                    pane_template.fullscreen = fullscreen;
+                }
+                if let Some(scrollback_size) = scrollback_size {
+                    pane_template.scrollback_size = Some(scrollback_size);
+                }
+                if let Some(alternate_screen_scrollback) = alternate_screen_scrollback {
+                    pane_template.alternate_screen_scrollback = Some(alternate_screen_scrollback);
+                }
+                if let Some(clear_on_alternate_screen_exit) = clear_on_alternate_screen_exit {
+                    pane_template.clear_on_alternate_screen_exit =
+                        Some(clear_on_alternate_screen_exit);
+                }
                 if let Some(split_size) = split_size {
                     pane_template.split_size = Some(split_size);
                 }
+                if let Some(size_limits) = size_limits {
+                    // each limit overrides the one of the template separately
+                    let template_limits = pane_template.size_limits.unwrap_or_default();
+                    pane_template.size_limits = Some(SizeLimits {
+                        min: size_limits.min.or(template_limits.min),
+                        max: size_limits.max.or(template_limits.max),
+                        split_direction: None,
+                    });
+                }
                 if let Some(index_of_children) = pane_template.external_children_index {
                     pane_template.children.insert(
                         index_of_children,
@@ -821,7 +1066,19 @@ impl<'a> KdlLayoutParser<'a> {
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "close_on_exit");
                 let start_suspended =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "start_suspended");
+                let stdin_file = self.parse_path(kdl_node, "stdin_file")?;
+                let log_output = self.parse_output_log(kdl_node)?;
                 let run = self.parse_command_plugin_or_edit_block_for_template(kdl_node)?;
+                let log = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "log");
+                let scrollback_size = self.parse_scrollback_size(kdl_node)?;
+                let alternate_screen_scrollback = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "alternate_screen_scrollback"
+                );
+                let clear_on_alternate_screen_exit = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "clear_on_alternate_screen_exit"
+                );
                 self.assert_no_bare_attributes_in_pane_node_with_template(
                     &run,
                     &pane_template.run,
@@ -837,6 +1094,8 @@ impl<'a> KdlLayoutParser<'a> {
                     pane_template_run_command.add_args(args);
                     pane_template_run_command.add_close_on_exit(close_on_exit);
                     pane_template_run_command.add_start_suspended(start_suspended);
+                    pane_template_run_command.add_stdin_file(stdin_file);
+                    pane_template_run_command.add_log_output(log_output);
                 };
                 if let Some(focus) = focus {
                     pane_template.focus = Some(focus);
@@ -844,6 +1103,19 @@ impl<'a> KdlLayoutParser<'a> {
                 if let Some(name) = name {
                     pane_template.name = Some(name);
                 }
+                if let Some(log) = log {
+                    pane_template.log = log;
+                }
+                if let Some(scrollback_size) = scrollback_size {
+                    pane_template.scrollback_size = Some(scrollback_size);
+                }
+                if let Some(alternate_screen_scrollback) = alternate_screen_scrollback {
+                    pane_template.alternate_screen_scrollback = Some(alternate_screen_scrollback);
+                }
+                if let Some(clear_on_alternate_screen_exit) = clear_on_alternate_screen_exit {
+                    pane_template.clear_on_alternate_screen_exit =
+                        Some(clear_on_alternate_screen_exit);
+                }
                 let height = self.parse_percent_or_fixed(kdl_node, "height", false)?;
                 let width = self.parse_percent_or_fixed(kdl_node, "width", false)?;
                 let x = self.parse_percent_or_fixed(kdl_node, "x", true)?;
@@ -875,7 +1147,19 @@ impl<'a> KdlLayoutParser<'a> {
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "close_on_exit");
                 let start_suspended =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "start_suspended");
+                let stdin_file = self.parse_path(kdl_node, "stdin_file")?;
+                let log_output = self.parse_output_log(kdl_node)?;
                 let run = self.parse_command_plugin_or_edit_block_for_template(kdl_node)?;
+                let log = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "log");
+                let scrollback_size = self.parse_scrollback_size(kdl_node)?;
+                let alternate_screen_scrollback = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "alternate_screen_scrollback"
+                );
+                let clear_on_alternate_screen_exit = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "clear_on_alternate_screen_exit"
+                );
                 self.assert_no_bare_attributes_in_pane_node_with_template(
                     &run,
                     &pane_template.run,
@@ -891,6 +1175,8 @@ impl<'a> KdlLayoutParser<'a> {
                     pane_template_run_command.add_args(args);
                     pane_template_run_command.add_close_on_exit(close_on_exit);
                     pane_template_run_command.add_start_suspended(start_suspended);
+                    pane_template_run_command.add_stdin_file(stdin_file);
+                    pane_template_run_command.add_log_output(log_output);
                 };
                 if let Some(focus) = focus {
                     pane_template.focus = Some(focus);
@@ -898,6 +1184,19 @@ impl<'a> KdlLayoutParser<'a> {
                 if let Some(name) = name {
                     pane_template.name = Some(name);
                 }
+                if let Some(log) = log {
+                    pane_template.log = log;
+                }
+                if let Some(scrollback_size) = scrollback_size {
+                    pane_template.scrollback_size = Some(scrollback_size);
+                }
+                if let Some(alternate_screen_scrollback) = alternate_screen_scrollback {
+                    pane_template.alternate_screen_scrollback = Some(alternate_screen_scrollback);
+                }
+                if let Some(clear_on_alternate_screen_exit) = clear_on_alternate_screen_exit {
+                    pane_template.clear_on_alternate_screen_exit =
+                        Some(clear_on_alternate_screen_exit);
+                }
                 let height = self.parse_percent_or_fixed(kdl_node, "height", false)?;
                 let width = self.parse_percent_or_fixed(kdl_node, "width", false)?;
                 let x = self.parse_percent_or_fixed(kdl_node, "x", true)?;
@@ -949,6 +1248,7 @@ impl<'a> KdlLayoutParser<'a> {
         let is_expanded_in_stack =
             kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded");
         let split_size = self.parse_split_size(kdl_node)?;
+        let size_limits = self.parse_size_limits(kdl_node)?;
         let split_direction =
             kdl_get_string_property_or_child_value_with_error!(kdl_node, "split_direction");
         let has_children_nodes = self.has_child_nodes(kdl_node);
@@ -962,6 +1262,7 @@ impl<'a> KdlLayoutParser<'a> {
 
         let has_pane_properties = borderless.is_some()
             || split_size.is_some()
+            || size_limits.is_some()
             || split_direction.is_some()
             || children_are_stacked.is_some()
             || is_expanded_in_stack.is_some()
@@ -987,6 +1288,7 @@ impl<'a> KdlLayoutParser<'a> {
         let is_expanded_in_stack =
             kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded");
         let split_size = self.parse_split_size(kdl_node)?;
+        let size_limits = self.parse_size_limits(kdl_node)?;
         let split_direction =
             kdl_get_string_property_or_child_value_with_error!(kdl_node, "split_direction");
         let has_children_nodes = self.has_child_nodes(kdl_node);
@@ -1000,6 +1302,7 @@ impl<'a> KdlLayoutParser<'a> {
 
         let has_pane_properties = borderless.is_some()
             || split_size.is_some()
+            || size_limits.is_some()
             || split_direction.is_some()
             || children_are_stacked.is_some()
             || is_expanded_in_stack.is_some()
@@ -1070,6 +1373,15 @@ impl<'a> KdlLayoutParser<'a> {
         self.assert_legal_template_name(&template_name, kdl_node)?;
         let focus = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "focus");
         let run = self.parse_command_plugin_or_edit_block(kdl_node)?;
+        let scrollback_size = self.parse_scrollback_size(kdl_node)?;
+        let alternate_screen_scrollback = kdl_get_bool_property_or_child_value_with_error!(
+            kdl_node,
+            "alternate_screen_scrollback"
+        );
+        let clear_on_alternate_screen_exit = kdl_get_bool_property_or_child_value_with_error!(
+            kdl_node,
+            "clear_on_alternate_screen_exit"
+        );
 
         let is_floating = self.differentiate_pane_and_floating_pane_template(&kdl_node)?;
         let can_be_either_floating_or_tiled =
@@ -1082,6 +1394,9 @@ impl<'a> KdlLayoutParser<'a> {
                     PaneOrFloatingPane::Either(TiledPaneLayout {
                         focus,
                         run,
+                        scrollback_size,
+                        alternate_screen_scrollback,
+                        clear_on_alternate_screen_exit,
                         ..Default::default()
                     }),
                     kdl_node.clone(),
@@ -1106,6 +1421,9 @@ impl<'a> KdlLayoutParser<'a> {
                         x,
                         y,
                         pinned,
+                        scrollback_size,
+                        alternate_screen_scrollback,
+                        clear_on_alternate_screen_exit,
                         ..Default::default()
                     }),
                     kdl_node.clone(),
@@ -1123,6 +1441,7 @@ impl<'a> KdlLayoutParser<'a> {
                 kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded")
                     .unwrap_or(false);
             let split_size = self.parse_split_size(kdl_node)?;
+            let size_limits = self.parse_size_limits(kdl_node)?;
             let children_split_direction = self.parse_split_direction(kdl_node)?;
             let (external_children_index, pane_parts) = match kdl_children_nodes!(kdl_node) {
                 Some(children) => {
@@ -1138,6 +1457,10 @@ impl<'a> KdlLayoutParser<'a> {
                         borderless: borderless.unwrap_or_default(),
                         focus,
                         split_size,
+                        size_limits,
+                        scrollback_size,
+                        alternate_screen_scrollback,
+                        clear_on_alternate_screen_exit,
                         run,
                         children_split_direction,
                         external_children_index,
@@ -1855,6 +2178,54 @@ impl<'a> KdlLayoutParser<'a> {
         }
         Ok(())
     }
+    fn populate_global_templates(
+        &mut self,
+        layout_node: &KdlNode,
+        layout_children: &[KdlNode],
+    ) -> Result<(), ConfigError> {
+        if self.global_templates.is_empty() {
+            return Ok(());
+        }
+        // templates defined in the layout itself take precedence over the global ones
+        let local_template_names: HashSet<&str> = layout_children
+            .iter()
+            .filter(|c| kdl_name!(c) == "pane_template" || kdl_name!(c) == "tab_template")
+            .filter_map(|c| kdl_get_string_property_or_child_value!(c, "name"))
+            .collect();
+        let raw_global_templates = self.global_templates.raw_templates();
+        let global_templates_error = |e: ConfigError| {
+            let error_message = match e {
+                ConfigError::KdlError(kdl_error) => kdl_error.error_message,
+                e => e.to_string(),
+            };
+            ConfigError::new_layout_kdl_error(
+                format!(
+                    "Failed to parse the templates defined in the configuration: {}",
+                    error_message
+                ),
+                layout_node.span().offset(),
+                layout_node.span().len(),
+            )
+        };
+        let kdl_global_templates: KdlDocument = raw_global_templates
+            .parse()
+            .map_err(|e: KdlError| global_templates_error(e.into()))?;
+        let global_templates: Vec<KdlNode> = kdl_global_templates
+            .nodes()
+            .iter()
+            .filter(|t| {
+                kdl_get_string_property_or_child_value!(t, "name")
+                    .map(|name| !local_template_names.contains(name))
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+        self.populate_pane_templates(&global_templates, &kdl_global_templates)
+            .map_err(global_templates_error)?;
+        self.populate_tab_templates(&global_templates)
+            .map_err(global_templates_error)?;
+        Ok(())
+    }
     fn populate_tab_templates(&mut self, layout_children: &[KdlNode]) -> Result<(), ConfigError> {
         for child in layout_children.iter() {
             let child_name = kdl_name!(child);
@@ -2409,6 +2780,7 @@ impl<'a> KdlLayoutParser<'a> {
         let mut swap_floating_layouts = vec![];
         if let Some(children) = kdl_children_nodes!(layout_node) {
             self.populate_global_cwd(layout_node)?;
+            self.populate_global_templates(layout_node, children)?;
             self.populate_pane_templates(children, &kdl_layout)?;
             self.populate_tab_templates(children)?;
             self.populate_swap_tiled_layouts(children, &mut swap_tiled_layouts)?;
@@ -7,14 +7,20 @@ use crate::data::{
 use crate::envs::EnvironmentVariables;
 use crate::home::{find_default_config_dir, get_layout_dir};
 use crate::input::config::{Config, ConfigError, KdlError};
+use crate::input::daemon::{DaemonConfig, DaemonSession};
+use crate::input::hooks::{Hook, Hooks};
 use crate::input::keybinds::Keybinds;
 use crate::input::layout::{
-    Layout, PluginUserConfiguration, RunPlugin, RunPluginOrAlias, SplitSize,
+    Layout, LayoutTemplates, PluginUserConfiguration, RunPlugin, RunPluginOrAlias, SplitSize,
+};
+use crate::input::options::{
+    AmbiguousWidth, BellForwarding, Clipboard, CommandExitBanner, CommandPaneExit, CopyProvider,
+    NewPaneCwd, OnForceClose, Options, PasteSanitization, QuietHours, TitlePrecedence, UpdateCheck,
 };
-use crate::input::options::{Clipboard, OnForceClose, Options};
 use crate::input::permission::{GrantedPermission, PermissionCache};
 use crate::input::plugins::PluginAliases;
 use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
+use crate::session_events::{SERVER_EVENTS, SESSION_EVENTS};
 use kdl_layout_parser::KdlLayoutParser;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use strum::IntoEnumIterator;
@@ -24,10 +30,10 @@ use miette::NamedSource;
 
 use kdl::{KdlDocument, KdlEntry, KdlNode, KdlValue};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use crate::input::actions::{Action, SearchDirection, SearchOption};
+use crate::input::actions::{Action, SearchDirection, SearchOption, TabTarget, UiSettings};
 use crate::input::command::RunCommandAction;
 
 #[macro_export]
@@ -56,10 +62,11 @@ macro_rules! parse_kdl_action_arguments {
                 "HalfPageScrollDown" => Ok(Action::HalfPageScrollDown),
                 "ToggleFocusFullscreen" => Ok(Action::ToggleFocusFullscreen),
                 "TogglePaneFrames" => Ok(Action::TogglePaneFrames),
-                "ToggleActiveSyncTab" => Ok(Action::ToggleActiveSyncTab),
+                "ToggleActiveSyncTab" | "ToggleSyncInput" => Ok(Action::ToggleActiveSyncTab),
                 "TogglePaneEmbedOrFloating" => Ok(Action::TogglePaneEmbedOrFloating),
                 "ToggleFloatingPanes" => Ok(Action::ToggleFloatingPanes),
                 "CloseFocus" => Ok(Action::CloseFocus),
+                "UndoClosePane" => Ok(Action::UndoClosePane),
                 "UndoRenamePane" => Ok(Action::UndoRenamePane),
                 "NoOp" => Ok(Action::NoOp),
                 "GoToNextTab" => Ok(Action::GoToNextTab),
@@ -416,6 +423,7 @@ impl Action {
             "PaneNameInput" => Ok(Action::PaneNameInput(bytes)),
             "TabNameInput" => Ok(Action::TabNameInput(bytes)),
             "SearchInput" => Ok(Action::SearchInput(bytes)),
+            "CopyModeInput" => Ok(Action::CopyModeInput(bytes)),
             "GoToTab" => {
                 let tab_index = *bytes.get(0).ok_or_else(|| {
                     ConfigError::new_kdl_error(
@@ -426,6 +434,26 @@ impl Action {
                 })? as u32;
                 Ok(Action::GoToTab(tab_index))
             },
+            "MoveTabToIndex" => {
+                let tab_index = *bytes.get(0).ok_or_else(|| {
+                    ConfigError::new_kdl_error(
+                        format!("Missing tab index"),
+                        action_node.span().offset(),
+                        action_node.span().len(),
+                    )
+                })? as usize;
+                Ok(Action::MoveTabToIndex(tab_index))
+            },
+            "JoinTab" => {
+                let tab_index = *bytes.get(0).ok_or_else(|| {
+                    ConfigError::new_kdl_error(
+                        format!("Missing tab index"),
+                        action_node.span().offset(),
+                        action_node.span().len(),
+                    )
+                })? as usize;
+                Ok(Action::JoinTab(tab_index, None))
+            },
             _ => Err(ConfigError::new_kdl_error(
                 "Failed to parse action".into(),
                 action_node.span().offset(),
@@ -527,7 +555,7 @@ impl Action {
                 }
             },
             "MovePaneBackwards" => Ok(Action::MovePaneBackwards),
-            "DumpScreen" => Ok(Action::DumpScreen(string, false)),
+            "DumpScreen" => Ok(Action::DumpScreen(string, false, false)),
             "DumpLayout" => Ok(Action::DumpLayout),
             "NewPane" => {
                 if string.is_empty() {
@@ -543,6 +571,20 @@ impl Action {
                     Ok(Action::NewPane(Some(direction), None, false))
                 }
             },
+            "DuplicatePane" => {
+                if string.is_empty() {
+                    return Ok(Action::DuplicatePane(None));
+                } else {
+                    let direction = Direction::from_str(string.as_str()).map_err(|_| {
+                        ConfigError::new_kdl_error(
+                            format!("Invalid direction: '{}'", string),
+                            action_node.span().offset(),
+                            action_node.span().len(),
+                        )
+                    })?;
+                    Ok(Action::DuplicatePane(Some(direction)))
+                }
+            },
             "SearchToggleOption" => {
                 let toggle_option = SearchOption::from_str(string.as_str()).map_err(|_| {
                     ConfigError::new_kdl_error(
@@ -565,6 +607,42 @@ impl Action {
                 Ok(Action::Search(search_direction))
             },
             "RenameSession" => Ok(Action::RenameSession(string)),
+            "MovePaneToSession" => Ok(Action::MovePaneToSession(string)),
+            "SetTabCwd" => Ok(Action::SetTabCwd(PathBuf::from(string))),
+            "SelectSwapLayout" => Ok(Action::SelectSwapLayout(string)),
+            "TogglePaneInGroup" => Ok(Action::TogglePaneInGroup {
+                group: string,
+                pane_id: None,
+            }),
+            "SetUi" => {
+                let ui_settings = UiSettings::from_str(string.as_str()).map_err(|e| {
+                    ConfigError::new_kdl_error(
+                        e,
+                        action_node.span().offset(),
+                        action_node.span().len(),
+                    )
+                })?;
+                Ok(Action::SetUi(ui_settings))
+            },
+            "MovePaneInStack" => {
+                let direction = Direction::from_str(string.as_str()).map_err(|_| {
+                    ConfigError::new_kdl_error(
+                        format!("Invalid direction: '{}'", string),
+                        action_node.span().offset(),
+                        action_node.span().len(),
+                    )
+                })?;
+                if direction.is_vertical() {
+                    Ok(Action::MovePaneInStack(direction))
+                } else {
+                    Err(ConfigError::new_kdl_error(
+                        format!("Invalid vertical direction: '{}'", string),
+                        action_node.span().offset(),
+                        action_node.span().len(),
+                    ))
+                }
+            },
+            "FocusStackedPaneByTitle" => Ok(Action::FocusStackedPaneByTitle(string)),
             _ => Err(ConfigError::new_kdl_error(
                 format!("Unsupported action: {}", action_name),
                 action_node.span().offset(),
@@ -582,6 +660,21 @@ impl Action {
                 }
                 Some(node)
             },
+            Action::MultiExec {
+                chars,
+                group,
+                exclude_focused,
+            } => {
+                let mut node = KdlNode::new("MultiExec");
+                node.push(chars.clone());
+                if let Some(group) = group {
+                    node.push(KdlEntry::new_prop("group", group.clone()));
+                }
+                if *exclude_focused {
+                    node.push(KdlEntry::new_prop("exclude_focused", KdlValue::Bool(true)));
+                }
+                Some(node)
+            },
             Action::WriteChars(string) => {
                 let mut node = KdlNode::new("WriteChars");
                 node.push(string.clone());
@@ -650,7 +743,7 @@ impl Action {
                 Some(node)
             },
             Action::MovePaneBackwards => Some(KdlNode::new("MovePaneBackwards")),
-            Action::DumpScreen(file, _) => {
+            Action::DumpScreen(file, _, _) => {
                 let mut node = KdlNode::new("DumpScreen");
                 node.push(file.clone());
                 Some(node)
@@ -681,9 +774,28 @@ impl Action {
                 }
                 Some(node)
             },
+            Action::SetTabCwd(cwd) => {
+                let mut node = KdlNode::new("SetTabCwd");
+                node.push(cwd.display().to_string());
+                Some(node)
+            },
+            Action::DuplicatePane(direction) => {
+                let mut node = KdlNode::new("DuplicatePane");
+                if let Some(direction) = direction {
+                    let direction = match direction {
+                        Direction::Left => "left",
+                        Direction::Right => "right",
+                        Direction::Up => "up",
+                        Direction::Down => "down",
+                    };
+                    node.push(direction);
+                }
+                Some(node)
+            },
             Action::TogglePaneEmbedOrFloating => Some(KdlNode::new("TogglePaneEmbedOrFloating")),
             Action::ToggleFloatingPanes => Some(KdlNode::new("ToggleFloatingPanes")),
             Action::CloseFocus => Some(KdlNode::new("CloseFocus")),
+            Action::UndoClosePane => Some(KdlNode::new("UndoClosePane")),
             Action::PaneNameInput(bytes) => {
                 let mut node = KdlNode::new("PaneNameInput");
                 for byte in bytes {
@@ -712,6 +824,25 @@ impl Action {
                 }
                 Some(node)
             },
+            Action::NewTabFromLayout(layout_name, name, cwd) => {
+                let mut node = KdlNode::new("NewTab");
+                let mut children = KdlDocument::new();
+                let mut layout_node = KdlNode::new("layout");
+                layout_node.push(layout_name.clone());
+                children.nodes_mut().push(layout_node);
+                if let Some(name) = name {
+                    let mut name_node = KdlNode::new("name");
+                    name_node.push(name.clone());
+                    children.nodes_mut().push(name_node);
+                }
+                if let Some(cwd) = cwd {
+                    let mut cwd_node = KdlNode::new("cwd");
+                    cwd_node.push(cwd.display().to_string());
+                    children.nodes_mut().push(cwd_node);
+                }
+                node.set_children(children);
+                Some(node)
+            },
             Action::GoToNextTab => Some(KdlNode::new("GoToNextTab")),
             Action::GoToPreviousTab => Some(KdlNode::new("GoToPreviousTab")),
             Action::CloseTab => Some(KdlNode::new("CloseTab")),
@@ -909,6 +1040,34 @@ impl Action {
                 }
                 Some(node)
             },
+            Action::ReplacePaneCommand(run_command_action, keep_scrollback) => {
+                let mut node = KdlNode::new("Run");
+                let mut node_children = KdlDocument::new();
+                node.push(run_command_action.command.display().to_string());
+                for arg in &run_command_action.args {
+                    node.push(arg.clone());
+                }
+                let mut replace_node = KdlNode::new("replace");
+                replace_node.push(KdlValue::Bool(true));
+                node_children.nodes_mut().push(replace_node);
+                if *keep_scrollback {
+                    let mut keep_scrollback_node = KdlNode::new("keep_scrollback");
+                    keep_scrollback_node.push(KdlValue::Bool(true));
+                    node_children.nodes_mut().push(keep_scrollback_node);
+                }
+                if let Some(cwd) = &run_command_action.cwd {
+                    let mut cwd_node = KdlNode::new("cwd");
+                    cwd_node.push(cwd.display().to_string());
+                    node_children.nodes_mut().push(cwd_node);
+                }
+                if !run_command_action.hold_on_close {
+                    let mut close_on_exit_node = KdlNode::new("close_on_exit");
+                    close_on_exit_node.push(KdlValue::Bool(true));
+                    node_children.nodes_mut().push(close_on_exit_node);
+                }
+                node.set_children(node_children);
+                Some(node)
+            },
             Action::Detach => Some(KdlNode::new("Detach")),
             Action::LaunchOrFocusPlugin(
                 run_plugin_or_alias,
@@ -1008,6 +1167,13 @@ impl Action {
                 }
                 Some(node)
             },
+            Action::CopyModeInput(bytes) => {
+                let mut node = KdlNode::new("CopyModeInput");
+                for byte in bytes {
+                    node.push(KdlValue::Base10(*byte as i64));
+                }
+                Some(node)
+            },
             Action::Search(search_direction) => {
                 let mut node = KdlNode::new("Search");
                 let direction = match search_direction {
@@ -1103,6 +1269,105 @@ impl Action {
                 Some(node)
             },
             Action::TogglePanePinned => Some(KdlNode::new("TogglePanePinned")),
+            Action::ToggleFloatingPaneScope => Some(KdlNode::new("ToggleFloatingPaneScope")),
+            Action::ToggleMouseReportingOverride => {
+                Some(KdlNode::new("ToggleMouseReportingOverride"))
+            },
+            Action::TogglePaneInputLock => Some(KdlNode::new("TogglePaneInputLock")),
+            Action::ScrollToPreviousPrompt => Some(KdlNode::new("ScrollToPreviousPrompt")),
+            Action::ScrollToNextPrompt => Some(KdlNode::new("ScrollToNextPrompt")),
+            Action::SelectLastCommandOutput => Some(KdlNode::new("SelectLastCommandOutput")),
+            Action::ToggleRawPassthrough => Some(KdlNode::new("ToggleRawPassthrough")),
+            Action::ToggleDoNotDisturb => Some(KdlNode::new("ToggleDoNotDisturb")),
+            Action::TogglePaneLogging { pane_id: None } => Some(KdlNode::new("TogglePaneLogging")),
+            Action::RerunCommandPane { pane_id: None } => Some(KdlNode::new("RerunCommandPane")),
+            Action::OpenCommandHistory { pane_id: None } => {
+                Some(KdlNode::new("OpenCommandHistory"))
+            },
+            Action::ToggleActivityMonitor {
+                pane_id: None,
+                notify,
+            } => {
+                let mut node = KdlNode::new("ToggleActivityMonitor");
+                if *notify {
+                    node.insert("notify", true);
+                }
+                Some(node)
+            },
+            Action::ToggleSilenceMonitor {
+                pane_id: None,
+                seconds,
+                notify,
+            } => {
+                let mut node = KdlNode::new("ToggleSilenceMonitor");
+                node.push(*seconds as i64);
+                if *notify {
+                    node.insert("notify", true);
+                }
+                Some(node)
+            },
+            Action::TogglePaneInGroup {
+                group,
+                pane_id: None,
+            } => {
+                let mut node = KdlNode::new("TogglePaneInGroup");
+                node.push(group.clone());
+                Some(node)
+            },
+            Action::SelectSwapLayout(swap_layout_name) => {
+                let mut node = KdlNode::new("SelectSwapLayout");
+                node.push(swap_layout_name.clone());
+                Some(node)
+            },
+            Action::QuerySwapLayoutNames => Some(KdlNode::new("QuerySwapLayoutNames")),
+            Action::SetUi(ui_settings) => {
+                let mut node = KdlNode::new("SetUi");
+                node.push(ui_settings.to_string());
+                Some(node)
+            },
+            Action::ToggleExpandStack => Some(KdlNode::new("ToggleExpandStack")),
+            Action::MovePaneInStack(direction) => {
+                let mut node = KdlNode::new("MovePaneInStack");
+                let direction = match direction {
+                    Direction::Up => "up",
+                    Direction::Down => "down",
+                    Direction::Left => "left",
+                    Direction::Right => "right",
+                };
+                node.push(direction);
+                Some(node)
+            },
+            Action::FocusStackedPaneByTitle(title) => {
+                let mut node = KdlNode::new("FocusStackedPaneByTitle");
+                node.push(title.clone());
+                Some(node)
+            },
+            Action::MovePaneToTab(None, tab_target) => {
+                let mut node = KdlNode::new("MovePaneToTab");
+                match tab_target {
+                    TabTarget::Index(index) => node.push(KdlValue::Base10(*index as i64)),
+                    TabTarget::Name(name) => node.push(name.clone()),
+                }
+                Some(node)
+            },
+            Action::LinkPaneToTab(tab_target) => {
+                let mut node = KdlNode::new("LinkPaneToTab");
+                match tab_target {
+                    TabTarget::Index(index) => node.push(KdlValue::Base10(*index as i64)),
+                    TabTarget::Name(name) => node.push(name.clone()),
+                }
+                Some(node)
+            },
+            Action::MoveTabToIndex(index) => {
+                let mut node = KdlNode::new("MoveTabToIndex");
+                node.push(KdlValue::Base10(*index as i64));
+                Some(node)
+            },
+            Action::JoinTab(index, None) => {
+                let mut node = KdlNode::new("JoinTab");
+                node.push(KdlValue::Base10(*index as i64));
+                Some(node)
+            },
             _ => None,
         }
     }
@@ -1321,7 +1586,7 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
             "TogglePaneFrames" => {
                 parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
             },
-            "ToggleActiveSyncTab" => {
+            "ToggleActiveSyncTab" | "ToggleSyncInput" => {
                 parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
             },
             "TogglePaneEmbedOrFloating" => {
@@ -1331,6 +1596,9 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
             },
             "CloseFocus" => parse_kdl_action_arguments!(action_name, action_arguments, kdl_action),
+            "UndoClosePane" => {
+                parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
+            },
             "UndoRenamePane" => {
                 parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
             },
@@ -1413,7 +1681,7 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 action_arguments,
                 kdl_action
             ),
-            "NewPane" => parse_kdl_action_char_or_string_arguments!(
+            "NewPane" | "DuplicatePane" => parse_kdl_action_char_or_string_arguments!(
                 action_name,
                 action_arguments,
                 kdl_action
@@ -1440,6 +1708,17 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 let name = command_metadata
                     .and_then(|c_m| kdl_child_string_value_for_entry(c_m, "name"))
                     .map(|name_string| name_string.to_string());
+                let layout_name = command_metadata
+                    .and_then(|c_m| kdl_child_string_value_for_entry(c_m, "layout"))
+                    .filter(|layout_name| {
+                        let layout_path = Path::new(layout_name);
+                        layout_path.extension().is_none() && layout_path.components().count() == 1
+                    });
+                if let Some(layout_name) = layout_name {
+                    // layouts given by name are looked up when the action runs, so that they can
+                    // be added or edited without reloading the config
+                    return Ok(Action::NewTabFromLayout(layout_name.to_owned(), name, cwd));
+                }
 
                 let layout_dir = config_options
                     .layout_dir
@@ -1514,6 +1793,9 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
             "SearchInput" => {
                 parse_kdl_action_u8_arguments!(action_name, action_arguments, kdl_action)
             },
+            "CopyModeInput" => {
+                parse_kdl_action_u8_arguments!(action_name, action_arguments, kdl_action)
+            },
             "SearchToggleOption" => parse_kdl_action_char_or_string_arguments!(
                 action_name,
                 action_arguments,
@@ -1553,6 +1835,12 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 let in_place = command_metadata
                     .and_then(|c_m| kdl_child_bool_value_for_entry(c_m, "in_place"))
                     .unwrap_or(false);
+                let replace = command_metadata
+                    .and_then(|c_m| kdl_child_bool_value_for_entry(c_m, "replace"))
+                    .unwrap_or(false);
+                let keep_scrollback = command_metadata
+                    .and_then(|c_m| kdl_child_bool_value_for_entry(c_m, "keep_scrollback"))
+                    .unwrap_or(false);
                 let run_command_action = RunCommandAction {
                     command: PathBuf::from(command),
                     args,
@@ -1576,7 +1864,12 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                     .map(|s| s.to_owned());
                 let pinned =
                     command_metadata.and_then(|c_m| kdl_child_bool_value_for_entry(c_m, "pinned"));
-                if floating {
+                if replace {
+                    Ok(Action::ReplacePaneCommand(
+                        run_command_action,
+                        keep_scrollback,
+                    ))
+                } else if floating {
                     Ok(Action::NewFloatingPane(
                         Some(run_command_action),
                         name,
@@ -1699,6 +1992,11 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 action_arguments,
                 kdl_action
             ),
+            "MovePaneToSession" | "SetTabCwd" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
             "MessagePlugin" => {
                 let arguments = action_arguments.iter().copied();
                 let mut args = kdl_arguments_that_are_strings(arguments)?;
@@ -1758,6 +2056,29 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                     plugin_id: None,
                 })
             },
+            "MultiExec" => {
+                let chars = action_arguments
+                    .iter()
+                    .find(|kdl_entry| kdl_entry.name().is_none())
+                    .and_then(|kdl_entry| kdl_entry.value().as_string())
+                    .ok_or_else(|| {
+                        ConfigError::new_kdl_error(
+                            "MultiExec requires the characters to write".into(),
+                            kdl_action.span().offset(),
+                            kdl_action.span().len(),
+                        )
+                    })?;
+                let group = kdl_get_string_property_or_child_value!(kdl_action, "group")
+                    .map(|group| group.to_owned());
+                let exclude_focused =
+                    kdl_get_bool_property_or_child_value!(kdl_action, "exclude_focused")
+                        .unwrap_or(false);
+                Ok(Action::MultiExec {
+                    chars: chars.to_owned(),
+                    group,
+                    exclude_focused,
+                })
+            },
             "MessagePluginId" => {
                 let arguments = action_arguments.iter().copied();
                 let mut args = kdl_arguments_that_are_digits(arguments)?;
@@ -1798,6 +2119,100 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 })
             },
             "TogglePanePinned" => Ok(Action::TogglePanePinned),
+            "ToggleFloatingPaneScope" => Ok(Action::ToggleFloatingPaneScope),
+            "ToggleMouseReportingOverride" => Ok(Action::ToggleMouseReportingOverride),
+            "TogglePaneInputLock" => Ok(Action::TogglePaneInputLock),
+            "ScrollToPreviousPrompt" => Ok(Action::ScrollToPreviousPrompt),
+            "ScrollToNextPrompt" => Ok(Action::ScrollToNextPrompt),
+            "SelectLastCommandOutput" => Ok(Action::SelectLastCommandOutput),
+            "ToggleRawPassthrough" => Ok(Action::ToggleRawPassthrough),
+            "ToggleDoNotDisturb" => Ok(Action::ToggleDoNotDisturb),
+            "TogglePaneLogging" => Ok(Action::TogglePaneLogging { pane_id: None }),
+            "RerunCommandPane" => Ok(Action::RerunCommandPane { pane_id: None }),
+            "OpenCommandHistory" => Ok(Action::OpenCommandHistory { pane_id: None }),
+            "ToggleActivityMonitor" => Ok(Action::ToggleActivityMonitor {
+                pane_id: None,
+                notify: kdl_get_bool_property_or_child_value!(kdl_action, "notify")
+                    .unwrap_or(false),
+            }),
+            "ToggleSilenceMonitor" => {
+                let arguments = action_arguments
+                    .iter()
+                    .copied()
+                    .filter(|entry| entry.name().is_none());
+                let seconds = match kdl_arguments_that_are_digits(arguments)?.first() {
+                    Some(seconds) if *seconds > 0 => *seconds as u64,
+                    Some(_) => {
+                        return Err(ConfigError::new_kdl_error(
+                            "The silence interval must be at least one second".into(),
+                            kdl_action.span().offset(),
+                            kdl_action.span().len(),
+                        ))
+                    },
+                    None => 30,
+                };
+                Ok(Action::ToggleSilenceMonitor {
+                    pane_id: None,
+                    seconds,
+                    notify: kdl_get_bool_property_or_child_value!(kdl_action, "notify")
+                        .unwrap_or(false),
+                })
+            },
+            "TogglePaneInGroup" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
+            "SelectSwapLayout" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
+            "QuerySwapLayoutNames" => Ok(Action::QuerySwapLayoutNames),
+            "SetUi" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
+            "ToggleExpandStack" => Ok(Action::ToggleExpandStack),
+            "MovePaneInStack" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
+            "FocusStackedPaneByTitle" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
+            "MovePaneToTab" | "LinkPaneToTab" => {
+                let tab_target = action_arguments
+                    .iter()
+                    .next()
+                    .and_then(|kdl_entry| {
+                        let value = kdl_entry.value();
+                        value
+                            .as_i64()
+                            .map(|index| TabTarget::Index(index as usize))
+                            .or_else(|| value.as_string().map(|name| TabTarget::Name(name.into())))
+                    })
+                    .ok_or_else(|| {
+                        ConfigError::new_kdl_error(
+                            format!("{} requires a tab index or a tab name", action_name),
+                            kdl_action.span().offset(),
+                            kdl_action.span().len(),
+                        )
+                    })?;
+                if action_name == "LinkPaneToTab" {
+                    Ok(Action::LinkPaneToTab(tab_target))
+                } else {
+                    Ok(Action::MovePaneToTab(None, tab_target))
+                }
+            },
+            "MoveTabToIndex" => {
+                parse_kdl_action_u8_arguments!(action_name, action_arguments, kdl_action)
+            },
+            "JoinTab" => parse_kdl_action_u8_arguments!(action_name, action_arguments, kdl_action),
             _ => Err(ConfigError::new_kdl_error(
                 format!("Unsupported action: {}", action_name).into(),
                 kdl_action.span().offset(),
@@ -2246,6 +2661,30 @@ impl Options {
                 .map(|(scroll_buffer_size, _entry)| scroll_buffer_size as usize);
         let copy_command = kdl_property_first_arg_as_string_or_error!(kdl_options, "copy_command")
             .map(|(copy_command, _entry)| copy_command.to_string());
+        let copy_providers = match kdl_options.get("copy_providers") {
+            Some(copy_providers) => Some(
+                copy_providers
+                    .entries()
+                    .iter()
+                    .map(|entry| {
+                        entry
+                            .value()
+                            .as_string()
+                            .and_then(|string| CopyProvider::from_str(string).ok())
+                            .ok_or_else(|| {
+                                kdl_parsing_error!(
+                                    format!(
+                                        "Invalid value for copy_providers: '{}'",
+                                        entry.value()
+                                    ),
+                                    entry
+                                )
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
         let copy_clipboard =
             match kdl_property_first_arg_as_string_or_error!(kdl_options, "copy_clipboard") {
                 Some((string, entry)) => Some(Clipboard::from_str(string).map_err(|_| {
@@ -2299,67 +2738,252 @@ impl Options {
         let show_release_notes =
             kdl_property_first_arg_as_bool_or_error!(kdl_options, "show_release_notes")
                 .map(|(v, _)| v);
-        Ok(Options {
-            simplified_ui,
-            theme,
-            default_mode,
-            default_shell,
-            default_cwd,
-            default_layout,
-            layout_dir,
-            theme_dir,
-            mouse_mode,
-            pane_frames,
-            mirror_session,
-            on_force_close,
-            scroll_buffer_size,
-            copy_command,
-            copy_clipboard,
-            copy_on_select,
-            scrollback_editor,
-            session_name,
-            attach_to_session,
-            auto_layout,
-            session_serialization,
-            serialize_pane_viewport,
-            scrollback_lines_to_serialize,
-            styled_underlines,
-            serialization_interval,
-            disable_session_metadata,
-            support_kitty_keyboard_protocol,
-            stacked_resize,
-            show_startup_tips,
-            show_release_notes,
-        })
-    }
-    pub fn from_string(stringified_keybindings: &String) -> Result<Self, ConfigError> {
-        let document: KdlDocument = stringified_keybindings.parse()?;
-        Options::from_kdl(&document)
-    }
-    fn simplified_ui_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
-        let comment_text = format!(
-            "{}\n{}\n{}\n{}\n{}\n{}",
-            " ",
-            "// Use a simplified UI without special fonts (arrow glyphs)",
-            "// Options:",
-            "//   - true",
-            "//   - false (Default)",
-            "// ",
-        );
-
-        let create_node = |node_value: bool| -> KdlNode {
-            let mut node = KdlNode::new("simplified_ui");
-            node.push(KdlValue::Bool(node_value));
-            node
-        };
-        if let Some(simplified_ui) = self.simplified_ui {
-            let mut node = create_node(simplified_ui);
-            if add_comments {
-                node.set_leading(format!("{}\n", comment_text));
-            }
-            Some(node)
-        } else if add_comments {
-            let mut node = create_node(true);
+        let quiet_hours =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "quiet_hours") {
+                Some((string, entry)) => {
+                    Some(QuietHours::from_str(string).map_err(|e| kdl_parsing_error!(e, entry))?)
+                },
+                None => None,
+            };
+        let session_name_template =
+            kdl_property_first_arg_as_string_or_error!(kdl_options, "session_name_template")
+                .map(|(template, _entry)| template.to_string());
+        let session_name_command =
+            kdl_property_first_arg_as_string_or_error!(kdl_options, "session_name_command")
+                .map(|(command, _entry)| command.to_string());
+        let update_check =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "update_check") {
+                Some((string, entry)) => Some(UpdateCheck::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for update_check: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let command_pane_on_exit =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "command_pane_on_exit") {
+                Some((string, entry)) => Some(CommandPaneExit::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for command_pane_on_exit: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let start_command_panes_suspended =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "start_command_panes_suspended")
+                .map(|(v, _)| v);
+        let title_precedence =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "title_precedence") {
+                Some((string, entry)) => Some(TitlePrecedence::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for title_precedence: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let tab_name_from_title =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "tab_name_from_title")
+                .map(|(v, _)| v);
+        let bell_forwarding =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "bell_forwarding") {
+                Some((string, entry)) => Some(BellForwarding::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for bell_forwarding: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let command_exit_banner =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "command_exit_banner") {
+                Some((string, entry)) => {
+                    Some(CommandExitBanner::from_str(string).map_err(|_| {
+                        kdl_parsing_error!(
+                            format!("Invalid value for command_exit_banner: '{}'", string),
+                            entry
+                        )
+                    })?)
+                },
+                None => None,
+            };
+        let idle_client_detach_timeout =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "idle_client_detach_timeout")
+                .map(|(minutes, _entry)| minutes as u64);
+        let osc52_forwarding =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "osc52_forwarding")
+                .map(|(v, _)| v);
+        let word_separators =
+            kdl_property_first_arg_as_string_or_error!(kdl_options, "word_separators")
+                .map(|(string, _entry)| string.to_string());
+        let global_floating_panes =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "global_floating_panes")
+                .map(|(v, _)| v);
+        let new_pane_cwd =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "new_pane_cwd") {
+                Some((string, entry)) => Some(NewPaneCwd::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for new_pane_cwd: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let automatic_tab_rename =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "automatic_tab_rename")
+                .map(|(v, _)| v);
+        let pane_frame_process_stats =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "pane_frame_process_stats")
+                .map(|(v, _)| v);
+        let confirm_close_with_running_processes = kdl_property_first_arg_as_bool_or_error!(
+            kdl_options,
+            "confirm_close_with_running_processes"
+        )
+        .map(|(v, _)| v);
+        let command_exit_notification_command = kdl_property_first_arg_as_string_or_error!(
+            kdl_options,
+            "command_exit_notification_command"
+        )
+        .map(|(string, _entry)| string.to_string());
+        let resource_limit = |option_name: &str| -> Result<Option<usize>, ConfigError> {
+            match kdl_property_first_arg_as_i64_or_error!(kdl_options, option_name) {
+                Some((limit, _entry)) if limit >= 0 => Ok(Some(limit as usize)),
+                Some((_limit, entry)) => Err(kdl_parsing_error!(
+                    format!("{} should not be negative", option_name),
+                    entry
+                )),
+                None => Ok(None),
+            }
+        };
+        let max_panes_per_tab = resource_limit("max_panes_per_tab")?;
+        let max_tabs = resource_limit("max_tabs")?;
+        let max_scrollback_memory = resource_limit("max_scrollback_memory")?;
+        let idle_session_timeout =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "idle_session_timeout")
+                .map(|(v, _entry)| v as u64);
+        let alternate_screen_scrollback =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "alternate_screen_scrollback")
+                .map(|(v, _)| v);
+        let clear_on_alternate_screen_exit =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "clear_on_alternate_screen_exit")
+                .map(|(v, _)| v);
+        let ambiguous_width =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "ambiguous_width") {
+                Some((string, entry)) => Some(AmbiguousWidth::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for ambiguous_width: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let grapheme_clusters =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "grapheme_clusters")
+                .map(|(v, _)| v);
+        let paste_sanitization =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "paste_sanitization") {
+                Some((string, entry)) => {
+                    Some(PasteSanitization::from_str(string).map_err(|_| {
+                        kdl_parsing_error!(
+                            format!("Invalid value for paste_sanitization: '{}'", string),
+                            entry
+                        )
+                    })?)
+                },
+                None => None,
+            };
+        Ok(Options {
+            simplified_ui,
+            theme,
+            default_mode,
+            default_shell,
+            default_cwd,
+            default_layout,
+            layout_dir,
+            theme_dir,
+            mouse_mode,
+            pane_frames,
+            mirror_session,
+            on_force_close,
+            scroll_buffer_size,
+            copy_command,
+            copy_providers,
+            copy_clipboard,
+            copy_on_select,
+            scrollback_editor,
+            session_name,
+            attach_to_session,
+            auto_layout,
+            session_serialization,
+            serialize_pane_viewport,
+            scrollback_lines_to_serialize,
+            styled_underlines,
+            serialization_interval,
+            disable_session_metadata,
+            support_kitty_keyboard_protocol,
+            stacked_resize,
+            show_startup_tips,
+            show_release_notes,
+            quiet_hours,
+            session_name_template,
+            session_name_command,
+            update_check,
+            command_pane_on_exit,
+            start_command_panes_suspended,
+            title_precedence,
+            tab_name_from_title,
+            bell_forwarding,
+            command_exit_banner,
+            idle_client_detach_timeout,
+            osc52_forwarding,
+            word_separators,
+            global_floating_panes,
+            new_pane_cwd,
+            automatic_tab_rename,
+            pane_frame_process_stats,
+            confirm_close_with_running_processes,
+            command_exit_notification_command,
+            max_panes_per_tab,
+            max_tabs,
+            max_scrollback_memory,
+            idle_session_timeout,
+            alternate_screen_scrollback,
+            clear_on_alternate_screen_exit,
+            ambiguous_width,
+            grapheme_clusters,
+            paste_sanitization,
+        })
+    }
+    pub fn from_string(stringified_keybindings: &String) -> Result<Self, ConfigError> {
+        let document: KdlDocument = stringified_keybindings.parse()?;
+        Options::from_kdl(&document)
+    }
+    fn simplified_ui_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Use a simplified UI without special fonts (arrow glyphs)",
+            "// Options:",
+            "//   - true",
+            "//   - false (Default)",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("simplified_ui");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(simplified_ui) = self.simplified_ui {
+            let mut node = create_node(simplified_ui);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
             node.set_leading(format!("{}\n// ", comment_text));
             Some(node)
         } else {
@@ -2722,10 +3346,10 @@ impl Options {
         let comment_text = format!(
             "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
             " ",
-            "// Provide a command to execute when copying text. The text will be piped to",
-            "// the stdin of the program to perform the copy. This can be used with",
-            "// terminal emulators which do not support the OSC 52 ANSI control sequence",
-            "// that will be used by default if this option is not set.",
+            "// Deprecated in favour of copy_providers. A command to execute when copying text,",
+            "// which is piped to the stdin of the program to perform the copy. It is tried",
+            "// before the copy_providers, which are used instead if it fails (exits with an",
+            "// error or cannot be run).",
             "// Examples:",
             "//",
             "// copy_command \"xclip -selection clipboard\" // x11",
@@ -2753,12 +3377,49 @@ impl Options {
             None
         }
     }
+    fn copy_providers_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// The clipboard providers to try when copying text, in order. Those that are not",
+            "// available (eg. xclip outside of an X11 session) are skipped and OSC 52 is used when",
+            "// none is. Run `zellij setup --check` to see which are available.",
+            "// Options: wl-copy, xclip, xsel, pbcopy, osc52",
+            "// Default: osc52",
+            "// ",
+        );
+
+        let create_node = |node_values: &[CopyProvider]| -> KdlNode {
+            let mut node = KdlNode::new("copy_providers");
+            for node_value in node_values {
+                node.push(node_value.as_str().to_owned());
+            }
+            node
+        };
+        if let Some(copy_providers) = &self.copy_providers {
+            let mut node = create_node(copy_providers);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(&[
+                CopyProvider::WlCopy,
+                CopyProvider::Xclip,
+                CopyProvider::Osc52,
+            ]);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
     fn copy_clipboard_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
         let comment_text = format!("{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
             " ",
             "// Choose the destination for copied text",
             "// Allows using the primary selection buffer (on x11/wayland) instead of the system clipboard.",
-            "// Does not apply when using copy_command.",
+            "// Applies to the copy_providers, but not to copy_command.",
             "// Options:",
             "//   - system (default)",
             "//   - primary",
@@ -2963,213 +3624,1090 @@ impl Options {
         let comment_text = format!(
             "{}\n{}\n{}\n{}\n{}\n{}",
             " ",
-            "// Whether pane viewports are serialized along with the session, default is false",
-            "// Options:",
-            "//   - true",
-            "//   - false (default)",
+            "// Whether pane viewports are serialized along with the session, default is false",
+            "// Options:",
+            "//   - true",
+            "//   - false (default)",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("serialize_pane_viewport");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(serialize_pane_viewport) = self.serialize_pane_viewport {
+            let mut node = create_node(serialize_pane_viewport);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(false);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn scrollback_lines_to_serialize_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!("{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Scrollback lines to serialize along with the pane viewport when serializing sessions, 0",
+            "// defaults to the scrollback size. If this number is higher than the scrollback size, it will",
+            "// also default to the scrollback size. This does nothing if `serialize_pane_viewport` is not true.",
+            "// ",
+        );
+
+        let create_node = |node_value: usize| -> KdlNode {
+            let mut node = KdlNode::new("scrollback_lines_to_serialize");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(scrollback_lines_to_serialize) = self.scrollback_lines_to_serialize {
+            let mut node = create_node(scrollback_lines_to_serialize);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(10000);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn styled_underlines_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Enable or disable the rendering of styled and colored underlines (undercurl).",
+            "// May need to be disabled for certain unsupported terminals",
+            "// (Requires restart)",
+            "// Default: true",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("styled_underlines");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(styled_underlines) = self.styled_underlines {
+            let mut node = create_node(styled_underlines);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(false);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn serialization_interval_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}",
+            " ", "// How often in seconds sessions are serialized", "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("serialization_interval");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(serialization_interval) = self.serialization_interval {
+            let mut node = create_node(serialization_interval);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(10000);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn disable_session_metadata_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!("{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Enable or disable writing of session metadata to disk (if disabled, other sessions might not know",
+            "// metadata info on this session)",
+            "// (Requires restart)",
+            "// Default: false",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("disable_session_metadata");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(disable_session_metadata) = self.disable_session_metadata {
+            let mut node = create_node(disable_session_metadata);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(false);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn support_kitty_keyboard_protocol_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!("{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Enable or disable support for the enhanced Kitty Keyboard Protocol (the host terminal must also support it)",
+            "// (Requires restart)",
+            "// Default: true (if the host terminal supports it)",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("support_kitty_keyboard_protocol");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(support_kitty_keyboard_protocol) = self.support_kitty_keyboard_protocol {
+            let mut node = create_node(support_kitty_keyboard_protocol);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(false);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn stacked_resize_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// Whether to stack panes when resizing beyond a certain size",
+            "// Default: true",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("stacked_resize");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(stacked_resize) = self.stacked_resize {
+            let mut node = create_node(stacked_resize);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(false);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn show_startup_tips_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ", "// Whether to show tips on startup", "// Default: true", "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("show_startup_tips");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(show_startup_tips) = self.show_startup_tips {
+            let mut node = create_node(show_startup_tips);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(false);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn show_release_notes_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ", "// Whether to show release notes on first version run", "// Default: true", "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("show_release_notes");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(show_release_notes) = self.show_release_notes {
+            let mut node = create_node(show_release_notes);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(false);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn quiet_hours_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}
+{}
+{}
+{}",
+            " ",
+            "// A daily range of local time during which bells are muted",
+            "// (eg. for sessions shown on a shared display)",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("quiet_hours");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(quiet_hours) = &self.quiet_hours {
+            let mut node = create_node(&quiet_hours.to_string());
+            if add_comments {
+                node.set_leading(format!(
+                    "{}
+",
+                    comment_text
+                ));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("22:00-07:00");
+            node.set_leading(format!(
+                "{}
+// ",
+                comment_text
+            ));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn session_name_template_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// A template for the names of new sessions, the {adjective}, {noun}, {date}",
+            "// and {directory} placeholders are replaced when the session is created",
+            "// Default: {adjective}-{noun}",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("session_name_template");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(session_name_template) = &self.session_name_template {
+            let mut node = create_node(&session_name_template);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("{directory}-{date}");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn session_name_command_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// A command whose first line of output is the name of new sessions",
+            "// (takes precedence over session_name_template)",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("session_name_command");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(session_name_command) = &self.session_name_command {
+            let mut node = create_node(&session_name_command);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("git rev-parse --abbrev-ref HEAD");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn update_check_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Warn in the status bar when the zellij binary was upgraded underneath a running session",
+            "// Options:",
+            "//   - off (default)",
+            "//   - installed (compare with the zellij binary installed on disk)",
+            "//   - release (also compare with the latest published release)",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("update_check");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(update_check) = &self.update_check {
+            let mut node = match update_check {
+                UpdateCheck::Off => create_node("off"),
+                UpdateCheck::Installed => create_node("installed"),
+                UpdateCheck::Release => create_node("release"),
+            };
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("installed");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn command_pane_on_exit_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// What happens to the panes of `zellij run` when their command exits",
+            "// (overridden by its --close-on-exit, --close-on-success and --keep-open flags)",
+            "// Options:",
+            "//   - hold (default, keep the pane open showing the exit status)",
+            "//   - close",
+            "//   - close_on_success (keep the pane open only if the command failed)",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("command_pane_on_exit");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(command_pane_on_exit) = &self.command_pane_on_exit {
+            let mut node = match command_pane_on_exit {
+                CommandPaneExit::Hold => create_node("hold"),
+                CommandPaneExit::Close => create_node("close"),
+                CommandPaneExit::CloseOnSuccess => create_node("close_on_success"),
+            };
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("close_on_success");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn start_command_panes_suspended_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Start the commands of `zellij run` suspended, only running them after ENTER is pressed",
+            "// (overridden by its --start-suspended and --start-immediately flags)",
+            "// Default: false",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("start_command_panes_suspended");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(start_command_panes_suspended) = self.start_command_panes_suspended {
+            let mut node = create_node(start_command_panes_suspended);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn title_precedence_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Whether a name set by the user or a title set by the program running in the pane",
+            "// (OSC 0/2) is shown when a pane or tab has both",
+            "// Options:",
+            "//   - user (default)",
+            "//   - application",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("title_precedence");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(title_precedence) = &self.title_precedence {
+            let mut node = match title_precedence {
+                TitlePrecedence::User => create_node("user"),
+                TitlePrecedence::Application => create_node("application"),
+            };
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("application");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn tab_name_from_title_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// Name tabs after the title that the program in their focused pane sets",
+            "// Default: false",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("tab_name_from_title");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(tab_name_from_title) = self.tab_name_from_title {
+            let mut node = create_node(tab_name_from_title);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn bell_forwarding_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Which bells rung in panes are passed on to the terminals of the clients (tabs",
+            "// with a bell in a pane that is not focused are marked either way)",
+            "// Options:",
+            "//   - visible (default): those of the panes the client is looking at",
+            "//   - all: those of all panes, in all tabs",
+            "//   - off",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("bell_forwarding");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(bell_forwarding) = &self.bell_forwarding {
+            let mut node = match bell_forwarding {
+                BellForwarding::Visible => create_node("visible"),
+                BellForwarding::All => create_node("all"),
+                BellForwarding::Off => create_node("off"),
+            };
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("all");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn command_exit_banner_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// What the frame of a command pane shows after its command exited",
+            "// Options:",
+            "//   - full (default): the exit code, how long the command ran and the keys to re-run it",
+            "//   - compact: the exit code and how long the command ran",
+            "//   - hidden",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("command_exit_banner");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(command_exit_banner) = &self.command_exit_banner {
+            let mut node = match command_exit_banner {
+                CommandExitBanner::Full => create_node("full"),
+                CommandExitBanner::Compact => create_node("compact"),
+                CommandExitBanner::Hidden => create_node("hidden"),
+            };
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("compact");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn idle_client_detach_timeout_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// Detach clients that have sent no input for this many minutes, after counting down",
+            "// the last minute on their screen (default: never)",
+            "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("idle_client_detach_timeout");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(idle_client_detach_timeout) = self.idle_client_detach_timeout {
+            let mut node = create_node(idle_client_detach_timeout);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(30);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn word_separators_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// The characters that, besides whitespace, end the word selected by double-clicking",
+            "// in a pane (triple-clicking selects the whole line)",
+            "// Default: []{}<>()",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("word_separators");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(word_separators) = &self.word_separators {
+            let mut node = create_node(word_separators);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("[]{}<>(),;:|");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn global_floating_panes_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Make floating panes global to the session, so that they follow the focused tab, instead",
+            "// of belonging to the tab they were opened in (the ToggleFloatingPaneScope action changes",
+            "// this for a single pane)",
+            "// Default: false",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("global_floating_panes");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(global_floating_panes) = self.global_floating_panes {
+            let mut node = create_node(global_floating_panes);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn new_pane_cwd_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Which working directory new panes open in when none is given explicitly",
+            "// Options:",
+            "//   - focused_pane (default): that of the focused pane, as reported by its shell",
+            "//   - tab: the one the tab was opened in",
+            "//   - session: the one the session was started in (or default_cwd, if set)",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("new_pane_cwd");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(new_pane_cwd) = &self.new_pane_cwd {
+            let mut node = match new_pane_cwd {
+                NewPaneCwd::FocusedPane => create_node("focused_pane"),
+                NewPaneCwd::Tab => create_node("tab"),
+                NewPaneCwd::Session => create_node("session"),
+            };
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("tab");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn automatic_tab_rename_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Name tabs after their focused pane: the title set by the program running in it or",
+            "// else the command running in it, following focus changes (a tab renamed by the user",
+            "// keeps its name)",
+            "// Default: false",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("automatic_tab_rename");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(automatic_tab_rename) = self.automatic_tab_rename {
+            let mut node = create_node(automatic_tab_rename);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn pane_frame_process_stats_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Show the name of the foreground process, and the CPU and memory usage of all the",
+            "// processes running in each terminal pane, at the bottom of its frame (sampled every",
+            "// few seconds)",
+            "// Default: false",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("pane_frame_process_stats");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(pane_frame_process_stats) = self.pane_frame_process_stats {
+            let mut node = create_node(pane_frame_process_stats);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn command_exit_notification_command_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// A command to run (with `sh -c`) when a command pane exits in a tab that no client",
+            "// is looking at, eg. to send a desktop notification. The notification is in the",
+            "// ZELLIJ_NOTIFICATION_TEXT environment variable, its parts in",
+            "// ZELLIJ_NOTIFICATION_COMMAND, ZELLIJ_NOTIFICATION_EXIT_CODE and ZELLIJ_NOTIFICATION_TAB",
+            "// (the tab is marked in the tab bar either way)",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("command_exit_notification_command");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(command_exit_notification_command) = &self.command_exit_notification_command {
+            let mut node = create_node(command_exit_notification_command);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("notify-send Zellij \"$ZELLIJ_NOTIFICATION_TEXT\"");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn max_panes_per_tab_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// The most panes a tab can have (the bars are not counted), new ones are refused",
+            "// beyond that",
+            "// Default: no limit",
+            "// ",
+        );
+
+        let create_node = |node_value: usize| -> KdlNode {
+            let mut node = KdlNode::new("max_panes_per_tab");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(max_panes_per_tab) = self.max_panes_per_tab {
+            let mut node = create_node(max_panes_per_tab);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(100);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn max_tabs_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// The most tabs a session can have, new ones are refused beyond that",
+            "// Default: no limit",
+            "// ",
+        );
+
+        let create_node = |node_value: usize| -> KdlNode {
+            let mut node = KdlNode::new("max_tabs");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(max_tabs) = self.max_tabs {
+            let mut node = create_node(max_tabs);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(50);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn max_scrollback_memory_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Once the scrollback of all the panes of a session takes this much memory (in MB), new",
+            "// panes and tabs are refused",
+            "// Default: no limit",
             "// ",
         );
 
-        let create_node = |node_value: bool| -> KdlNode {
-            let mut node = KdlNode::new("serialize_pane_viewport");
-            node.push(KdlValue::Bool(node_value));
+        let create_node = |node_value: usize| -> KdlNode {
+            let mut node = KdlNode::new("max_scrollback_memory");
+            node.push(KdlValue::Base10(node_value as i64));
             node
         };
-        if let Some(serialize_pane_viewport) = self.serialize_pane_viewport {
-            let mut node = create_node(serialize_pane_viewport);
+        if let Some(max_scrollback_memory) = self.max_scrollback_memory {
+            let mut node = create_node(max_scrollback_memory);
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
             Some(node)
         } else if add_comments {
-            let mut node = create_node(false);
+            let mut node = create_node(1024);
             node.set_leading(format!("{}\n// ", comment_text));
             Some(node)
         } else {
             None
         }
     }
-    fn scrollback_lines_to_serialize_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
-        let comment_text = format!("{}\n{}\n{}\n{}\n{}",
+    fn idle_session_timeout_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
             " ",
-            "// Scrollback lines to serialize along with the pane viewport when serializing sessions, 0",
-            "// defaults to the scrollback size. If this number is higher than the scrollback size, it will",
-            "// also default to the scrollback size. This does nothing if `serialize_pane_viewport` is not true.",
+            "// Serialize and kill the session once it has had no attached client and no pane output for",
+            "// this many minutes, so that forgotten sessions do not pile up. It can be resurrected later",
+            "// (default: never)",
             "// ",
         );
 
-        let create_node = |node_value: usize| -> KdlNode {
-            let mut node = KdlNode::new("scrollback_lines_to_serialize");
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("idle_session_timeout");
             node.push(KdlValue::Base10(node_value as i64));
             node
         };
-        if let Some(scrollback_lines_to_serialize) = self.scrollback_lines_to_serialize {
-            let mut node = create_node(scrollback_lines_to_serialize);
+        if let Some(idle_session_timeout) = self.idle_session_timeout {
+            let mut node = create_node(idle_session_timeout);
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
             Some(node)
         } else if add_comments {
-            let mut node = create_node(10000);
+            let mut node = create_node(720);
             node.set_leading(format!("{}\n// ", comment_text));
             Some(node)
         } else {
             None
         }
     }
-    fn styled_underlines_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+    fn alternate_screen_scrollback_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
         let comment_text = format!(
             "{}\n{}\n{}\n{}\n{}\n{}",
             " ",
-            "// Enable or disable the rendering of styled and colored underlines (undercurl).",
-            "// May need to be disabled for certain unsupported terminals",
-            "// (Requires restart)",
-            "// Default: true",
+            "// Let full screen applications (eg. vim, less, htop) draw on the main screen of panes instead of",
+            "// an alternate screen, so that what they printed stays in the scrollback after they exit.",
+            "// Can be set for a single pane in a layout.",
+            "// Default: false",
             "// ",
         );
 
         let create_node = |node_value: bool| -> KdlNode {
-            let mut node = KdlNode::new("styled_underlines");
+            let mut node = KdlNode::new("alternate_screen_scrollback");
             node.push(KdlValue::Bool(node_value));
             node
         };
-        if let Some(styled_underlines) = self.styled_underlines {
-            let mut node = create_node(styled_underlines);
+        if let Some(alternate_screen_scrollback) = self.alternate_screen_scrollback {
+            let mut node = create_node(alternate_screen_scrollback);
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
             Some(node)
         } else if add_comments {
-            let mut node = create_node(false);
+            let mut node = create_node(true);
             node.set_leading(format!("{}\n// ", comment_text));
             Some(node)
         } else {
             None
         }
     }
-    fn serialization_interval_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+    fn clear_on_alternate_screen_exit_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
         let comment_text = format!(
-            "{}\n{}\n{}",
-            " ", "// How often in seconds sessions are serialized", "// ",
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Clear the viewport of panes (moving what they showed to the scrollback) when a full screen",
+            "// application exits.",
+            "// Can be set for a single pane in a layout.",
+            "// Default: false",
+            "// ",
         );
 
-        let create_node = |node_value: u64| -> KdlNode {
-            let mut node = KdlNode::new("serialization_interval");
-            node.push(KdlValue::Base10(node_value as i64));
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("clear_on_alternate_screen_exit");
+            node.push(KdlValue::Bool(node_value));
             node
         };
-        if let Some(serialization_interval) = self.serialization_interval {
-            let mut node = create_node(serialization_interval);
+        if let Some(clear_on_alternate_screen_exit) = self.clear_on_alternate_screen_exit {
+            let mut node = create_node(clear_on_alternate_screen_exit);
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
             Some(node)
         } else if add_comments {
-            let mut node = create_node(10000);
+            let mut node = create_node(true);
             node.set_leading(format!("{}\n// ", comment_text));
             Some(node)
         } else {
             None
         }
     }
-    fn disable_session_metadata_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
-        let comment_text = format!("{}\n{}\n{}\n{}\n{}\n{}",
+    fn ambiguous_width_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
             " ",
-            "// Enable or disable writing of session metadata to disk (if disabled, other sessions might not know",
-            "// metadata info on this session)",
-            "// (Requires restart)",
-            "// Default: false",
+            "// Whether East Asian ambiguous width characters (eg. some Greek, Cyrillic and symbol",
+            "// characters) printed to panes take one or two columns. This should match the setting of the",
+            "// terminal emulator, otherwise CJK output can corrupt the layout.",
+            "// Options:",
+            "//   - narrow (default)",
+            "//   - wide",
             "// ",
         );
 
-        let create_node = |node_value: bool| -> KdlNode {
-            let mut node = KdlNode::new("disable_session_metadata");
-            node.push(KdlValue::Bool(node_value));
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("ambiguous_width");
+            node.push(node_value.to_owned());
             node
         };
-        if let Some(disable_session_metadata) = self.disable_session_metadata {
-            let mut node = create_node(disable_session_metadata);
+        if let Some(ambiguous_width) = &self.ambiguous_width {
+            let mut node = match ambiguous_width {
+                AmbiguousWidth::Narrow => create_node("narrow"),
+                AmbiguousWidth::Wide => create_node("wide"),
+            };
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
             Some(node)
         } else if add_comments {
-            let mut node = create_node(false);
+            let mut node = create_node("wide");
             node.set_leading(format!("{}\n// ", comment_text));
             Some(node)
         } else {
             None
         }
     }
-    fn support_kitty_keyboard_protocol_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
-        let comment_text = format!("{}\n{}\n{}\n{}\n{}",
+    fn grapheme_clusters_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
             " ",
-            "// Enable or disable support for the enhanced Kitty Keyboard Protocol (the host terminal must also support it)",
-            "// (Requires restart)",
-            "// Default: true (if the host terminal supports it)",
+            "// Count emoji sequences printed to panes the way modern terminal emulators display them (an",
+            "// emoji joined to the previous one or a skin tone modifier takes no columns of its own, and a",
+            "// character followed by the emoji presentation selector is wide). Enable this if emoji-heavy",
+            "// output misaligns the panes with your terminal emulator.",
+            "// Default: false",
             "// ",
         );
 
         let create_node = |node_value: bool| -> KdlNode {
-            let mut node = KdlNode::new("support_kitty_keyboard_protocol");
+            let mut node = KdlNode::new("grapheme_clusters");
             node.push(KdlValue::Bool(node_value));
             node
         };
-        if let Some(support_kitty_keyboard_protocol) = self.support_kitty_keyboard_protocol {
-            let mut node = create_node(support_kitty_keyboard_protocol);
+        if let Some(grapheme_clusters) = self.grapheme_clusters {
+            let mut node = create_node(grapheme_clusters);
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
             Some(node)
         } else if add_comments {
-            let mut node = create_node(false);
+            let mut node = create_node(true);
             node.set_leading(format!("{}\n// ", comment_text));
             Some(node)
         } else {
             None
         }
     }
-    fn stacked_resize_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+    fn paste_sanitization_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
         let comment_text = format!(
-            "{}\n{}\n{}\n{}",
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
             " ",
-            "// Whether to stack panes when resizing beyond a certain size",
-            "// Default: true",
+            "// What to do with the control characters and escape sequences in text pasted into panes. Text",
+            "// copied from a web page can hide escape sequences (eg. a bracketed paste terminator followed by",
+            "// a command) that would otherwise run in the shell as soon as it is pasted.",
+            "// Tabs and newlines are always kept.",
+            "// Options:",
+            "//   - off (default, paste the text as is)",
+            "//   - strip (remove them from the pasted text)",
+            "//   - escape (paste them as visible caret notation, eg. ^[)",
             "// ",
         );
 
-        let create_node = |node_value: bool| -> KdlNode {
-            let mut node = KdlNode::new("stacked_resize");
-            node.push(KdlValue::Bool(node_value));
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("paste_sanitization");
+            node.push(node_value.to_owned());
             node
         };
-        if let Some(stacked_resize) = self.stacked_resize {
-            let mut node = create_node(stacked_resize);
+        if let Some(paste_sanitization) = &self.paste_sanitization {
+            let mut node = match paste_sanitization {
+                PasteSanitization::Off => create_node("off"),
+                PasteSanitization::Strip => create_node("strip"),
+                PasteSanitization::Escape => create_node("escape"),
+            };
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
             Some(node)
         } else if add_comments {
-            let mut node = create_node(false);
+            let mut node = create_node("strip");
             node.set_leading(format!("{}\n// ", comment_text));
             Some(node)
         } else {
             None
         }
     }
-    fn show_startup_tips_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+    fn confirm_close_with_running_processes_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
         let comment_text = format!(
-            "{}\n{}\n{}\n{}",
-            " ", "// Whether to show tips on startup", "// Default: true", "// ",
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Ask for confirmation (in the focused pane) before closing a pane or a tab in which",
+            "// programs other than the shell are still running, rather than hanging them up",
+            "// Default: true",
+            "// ",
         );
 
         let create_node = |node_value: bool| -> KdlNode {
-            let mut node = KdlNode::new("show_startup_tips");
+            let mut node = KdlNode::new("confirm_close_with_running_processes");
             node.push(KdlValue::Bool(node_value));
             node
         };
-        if let Some(show_startup_tips) = self.show_startup_tips {
-            let mut node = create_node(show_startup_tips);
+        if let Some(confirm_close_with_running_processes) =
+            self.confirm_close_with_running_processes
+        {
+            let mut node = create_node(confirm_close_with_running_processes);
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
@@ -3182,19 +4720,24 @@ impl Options {
             None
         }
     }
-    fn show_release_notes_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+    fn osc52_forwarding_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
         let comment_text = format!(
-            "{}\n{}\n{}\n{}",
-            " ", "// Whether to show release notes on first version run", "// Default: true", "// ",
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Forward the clipboard writes (OSC 52) of programs running inside panes to all attached",
+            "// clients, so that eg. copying in a remote editor reaches the local clipboard (they also go",
+            "// through copy_command when it is set, and only to copy_clipboard when this is off)",
+            "// Default: true",
+            "// ",
         );
 
         let create_node = |node_value: bool| -> KdlNode {
-            let mut node = KdlNode::new("show_release_notes");
+            let mut node = KdlNode::new("osc52_forwarding");
             node.push(KdlValue::Bool(node_value));
             node
         };
-        if let Some(show_release_notes) = self.show_release_notes {
-            let mut node = create_node(show_release_notes);
+        if let Some(osc52_forwarding) = self.osc52_forwarding {
+            let mut node = create_node(osc52_forwarding);
             if add_comments {
                 node.set_leading(format!("{}\n", comment_text));
             }
@@ -3251,6 +4794,9 @@ impl Options {
         if let Some(copy_command) = self.copy_command_to_kdl(add_comments) {
             nodes.push(copy_command);
         }
+        if let Some(copy_providers) = self.copy_providers_to_kdl(add_comments) {
+            nodes.push(copy_providers);
+        }
         if let Some(copy_clipboard) = self.copy_clipboard_to_kdl(add_comments) {
             nodes.push(copy_clipboard);
         }
@@ -3303,6 +4849,102 @@ impl Options {
         if let Some(show_release_notes) = self.show_release_notes_to_kdl(add_comments) {
             nodes.push(show_release_notes);
         }
+        if let Some(quiet_hours) = self.quiet_hours_to_kdl(add_comments) {
+            nodes.push(quiet_hours);
+        }
+        if let Some(session_name_template) = self.session_name_template_to_kdl(add_comments) {
+            nodes.push(session_name_template);
+        }
+        if let Some(session_name_command) = self.session_name_command_to_kdl(add_comments) {
+            nodes.push(session_name_command);
+        }
+        if let Some(update_check) = self.update_check_to_kdl(add_comments) {
+            nodes.push(update_check);
+        }
+        if let Some(command_pane_on_exit) = self.command_pane_on_exit_to_kdl(add_comments) {
+            nodes.push(command_pane_on_exit);
+        }
+        if let Some(start_command_panes_suspended) =
+            self.start_command_panes_suspended_to_kdl(add_comments)
+        {
+            nodes.push(start_command_panes_suspended);
+        }
+        if let Some(title_precedence) = self.title_precedence_to_kdl(add_comments) {
+            nodes.push(title_precedence);
+        }
+        if let Some(tab_name_from_title) = self.tab_name_from_title_to_kdl(add_comments) {
+            nodes.push(tab_name_from_title);
+        }
+        if let Some(bell_forwarding) = self.bell_forwarding_to_kdl(add_comments) {
+            nodes.push(bell_forwarding);
+        }
+        if let Some(command_exit_banner) = self.command_exit_banner_to_kdl(add_comments) {
+            nodes.push(command_exit_banner);
+        }
+        if let Some(idle_client_detach_timeout) =
+            self.idle_client_detach_timeout_to_kdl(add_comments)
+        {
+            nodes.push(idle_client_detach_timeout);
+        }
+        if let Some(osc52_forwarding) = self.osc52_forwarding_to_kdl(add_comments) {
+            nodes.push(osc52_forwarding);
+        }
+        if let Some(word_separators) = self.word_separators_to_kdl(add_comments) {
+            nodes.push(word_separators);
+        }
+        if let Some(global_floating_panes) = self.global_floating_panes_to_kdl(add_comments) {
+            nodes.push(global_floating_panes);
+        }
+        if let Some(new_pane_cwd) = self.new_pane_cwd_to_kdl(add_comments) {
+            nodes.push(new_pane_cwd);
+        }
+        if let Some(automatic_tab_rename) = self.automatic_tab_rename_to_kdl(add_comments) {
+            nodes.push(automatic_tab_rename);
+        }
+        if let Some(pane_frame_process_stats) = self.pane_frame_process_stats_to_kdl(add_comments) {
+            nodes.push(pane_frame_process_stats);
+        }
+        if let Some(confirm_close_with_running_processes) =
+            self.confirm_close_with_running_processes_to_kdl(add_comments)
+        {
+            nodes.push(confirm_close_with_running_processes);
+        }
+        if let Some(command_exit_notification_command) =
+            self.command_exit_notification_command_to_kdl(add_comments)
+        {
+            nodes.push(command_exit_notification_command);
+        }
+        if let Some(max_panes_per_tab) = self.max_panes_per_tab_to_kdl(add_comments) {
+            nodes.push(max_panes_per_tab);
+        }
+        if let Some(max_tabs) = self.max_tabs_to_kdl(add_comments) {
+            nodes.push(max_tabs);
+        }
+        if let Some(max_scrollback_memory) = self.max_scrollback_memory_to_kdl(add_comments) {
+            nodes.push(max_scrollback_memory);
+        }
+        if let Some(idle_session_timeout) = self.idle_session_timeout_to_kdl(add_comments) {
+            nodes.push(idle_session_timeout);
+        }
+        if let Some(alternate_screen_scrollback) =
+            self.alternate_screen_scrollback_to_kdl(add_comments)
+        {
+            nodes.push(alternate_screen_scrollback);
+        }
+        if let Some(clear_on_alternate_screen_exit) =
+            self.clear_on_alternate_screen_exit_to_kdl(add_comments)
+        {
+            nodes.push(clear_on_alternate_screen_exit);
+        }
+        if let Some(ambiguous_width) = self.ambiguous_width_to_kdl(add_comments) {
+            nodes.push(ambiguous_width);
+        }
+        if let Some(grapheme_clusters) = self.grapheme_clusters_to_kdl(add_comments) {
+            nodes.push(grapheme_clusters);
+        }
+        if let Some(paste_sanitization) = self.paste_sanitization_to_kdl(add_comments) {
+            nodes.push(paste_sanitization);
+        }
         nodes
     }
 }
@@ -3314,7 +4956,23 @@ impl Layout {
         raw_swap_layouts: Option<(&str, &str)>, // raw_swap_layouts swap_layouts_file_name
         cwd: Option<PathBuf>,
     ) -> Result<Self, ConfigError> {
-        let mut kdl_layout_parser = KdlLayoutParser::new(raw_layout, cwd, file_name.clone());
+        Layout::from_kdl_with_templates(
+            raw_layout,
+            file_name,
+            raw_swap_layouts,
+            cwd,
+            &LayoutTemplates::default(),
+        )
+    }
+    pub fn from_kdl_with_templates(
+        raw_layout: &str,
+        file_name: Option<String>,
+        raw_swap_layouts: Option<(&str, &str)>, // raw_swap_layouts swap_layouts_file_name
+        cwd: Option<PathBuf>,
+        global_templates: &LayoutTemplates,
+    ) -> Result<Self, ConfigError> {
+        let mut kdl_layout_parser =
+            KdlLayoutParser::new(raw_layout, cwd, file_name.clone(), global_templates);
         let layout = kdl_layout_parser.parse().map_err(|e| match e {
             ConfigError::KdlError(kdl_error) => ConfigError::KdlError(kdl_error.add_src(
                 file_name.unwrap_or_else(|| "N/A".to_owned()),
@@ -3420,6 +5078,197 @@ impl EnvironmentVariables {
     }
 }
 
+impl LayoutTemplates {
+    pub fn from_kdl(kdl_templates: &KdlNode) -> Result<Self, ConfigError> {
+        let mut templates = BTreeMap::new();
+        for template in kdl_children_nodes_or_error!(kdl_templates, "empty templates block") {
+            let node_name = kdl_name!(template);
+            if node_name != "pane_template" && node_name != "tab_template" {
+                return Err(ConfigError::new_kdl_error(
+                    format!(
+                        "Unknown template type \"{}\", expected pane_template or tab_template",
+                        node_name
+                    ),
+                    template.span().offset(),
+                    template.span().len(),
+                ));
+            }
+            let template_name = kdl_get_string_property_or_child_value!(template, "name").ok_or(
+                ConfigError::new_kdl_error(
+                    "Templates must have a name".into(),
+                    template.span().offset(),
+                    template.span().len(),
+                ),
+            )?;
+            if templates.contains_key(template_name) {
+                return Err(ConfigError::new_kdl_error(
+                    format!("Duplicate definition of the \"{}\" template", template_name),
+                    template.span().offset(),
+                    template.span().len(),
+                ));
+            }
+            // stored formatted so that the same template always has the same raw representation
+            let mut raw_template = KdlDocument::new();
+            raw_template.nodes_mut().push(template.clone());
+            raw_template.fmt();
+            templates.insert(
+                template_name.to_owned(),
+                raw_template.to_string().trim().to_owned(),
+            );
+        }
+        Ok(LayoutTemplates { templates })
+    }
+    pub fn to_kdl(&self) -> Option<KdlNode> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut templates = KdlNode::new("templates");
+        let mut template_nodes = KdlDocument::new();
+        for raw_template in self.templates.values() {
+            match raw_template.parse::<KdlDocument>() {
+                Ok(mut template) => {
+                    template.fmt();
+                    template_nodes
+                        .nodes_mut()
+                        .extend(template.nodes().iter().cloned());
+                },
+                Err(e) => log::error!("Failed to serialize template: {}", e),
+            }
+        }
+        templates.set_children(template_nodes);
+        Some(templates)
+    }
+}
+
+impl Hooks {
+    pub fn from_kdl(kdl_hooks: &KdlNode, config_options: &Options) -> Result<Self, ConfigError> {
+        let mut hooks: BTreeMap<String, Vec<Hook>> = BTreeMap::new();
+        for kdl_hook in kdl_children_nodes_or_error!(kdl_hooks, "empty hooks block") {
+            let event_name = kdl_name!(kdl_hook);
+            if !SESSION_EVENTS.contains(&event_name) && !SERVER_EVENTS.contains(&event_name) {
+                return Err(ConfigError::new_kdl_error(
+                    format!(
+                        "Unknown hook event \"{}\", expected one of: {}",
+                        event_name,
+                        SERVER_EVENTS
+                            .iter()
+                            .chain(SESSION_EVENTS.iter())
+                            .copied()
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    kdl_hook.span().offset(),
+                    kdl_hook.span().len(),
+                ));
+            }
+            let hook = match (
+                kdl_first_entry_as_string!(kdl_hook),
+                kdl_children_nodes!(kdl_hook),
+            ) {
+                (Some(command), None) => Hook::Command(command.to_owned()),
+                (None, Some(_)) => Hook::Actions(actions_from_kdl!(kdl_hook, config_options)),
+                _ => {
+                    return Err(ConfigError::new_kdl_error(
+                        "A hook must be either a shell command or a block of actions".into(),
+                        kdl_hook.span().offset(),
+                        kdl_hook.span().len(),
+                    ))
+                },
+            };
+            // an event can have several hooks, these run in the order they are defined
+            hooks.entry(event_name.to_owned()).or_default().push(hook);
+        }
+        Ok(Hooks { hooks })
+    }
+    pub fn to_kdl(&self) -> Option<KdlNode> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut hooks = KdlNode::new("hooks");
+        let mut hook_nodes = KdlDocument::new();
+        for (event_name, event_hooks) in &self.hooks {
+            for hook in event_hooks {
+                let mut node = KdlNode::new(event_name.as_str());
+                match hook {
+                    Hook::Command(command) => {
+                        node.push(command.as_str());
+                    },
+                    Hook::Actions(actions) => {
+                        let mut action_nodes = KdlDocument::new();
+                        for action in actions {
+                            if let Some(action_node) = action.to_kdl() {
+                                action_nodes.nodes_mut().push(action_node);
+                            }
+                        }
+                        node.set_children(action_nodes);
+                    },
+                }
+                hook_nodes.nodes_mut().push(node);
+            }
+        }
+        hooks.set_children(hook_nodes);
+        Some(hooks)
+    }
+}
+
+impl DaemonConfig {
+    pub fn from_kdl(kdl_daemon: &KdlNode) -> Result<Self, ConfigError> {
+        let mut sessions: Vec<DaemonSession> = vec![];
+        for kdl_session in kdl_children_nodes_or_error!(kdl_daemon, "empty daemon block") {
+            if kdl_name!(kdl_session) != "session" {
+                return Err(ConfigError::new_kdl_error(
+                    format!(
+                        "Unknown daemon node \"{}\", expected session",
+                        kdl_name!(kdl_session)
+                    ),
+                    kdl_session.span().offset(),
+                    kdl_session.span().len(),
+                ));
+            }
+            let name =
+                kdl_first_entry_as_string!(kdl_session).ok_or(ConfigError::new_kdl_error(
+                    "Daemon sessions must have a name".into(),
+                    kdl_session.span().offset(),
+                    kdl_session.span().len(),
+                ))?;
+            if sessions.iter().any(|session| session.name == name) {
+                return Err(ConfigError::new_kdl_error(
+                    format!("Duplicate definition of the \"{}\" session", name),
+                    kdl_session.span().offset(),
+                    kdl_session.span().len(),
+                ));
+            }
+            sessions.push(DaemonSession {
+                name: name.to_owned(),
+                layout: kdl_get_string_property_or_child_value!(kdl_session, "layout")
+                    .map(PathBuf::from),
+                cwd: kdl_get_string_property_or_child_value!(kdl_session, "cwd").map(PathBuf::from),
+            });
+        }
+        Ok(DaemonConfig { sessions })
+    }
+    pub fn to_kdl(&self) -> Option<KdlNode> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut daemon = KdlNode::new("daemon");
+        let mut session_nodes = KdlDocument::new();
+        for session in &self.sessions {
+            let mut node = KdlNode::new("session");
+            node.push(session.name.as_str());
+            if let Some(layout) = &session.layout {
+                node.push(KdlEntry::new_prop("layout", layout.display().to_string()));
+            }
+            if let Some(cwd) = &session.cwd {
+                node.push(KdlEntry::new_prop("cwd", cwd.display().to_string()));
+            }
+            session_nodes.nodes_mut().push(node);
+        }
+        daemon.set_children(session_nodes);
+        Some(daemon)
+    }
+}
+
 impl Keybinds {
     fn bind_keys_in_block(
         block: &KdlNode,
@@ -3792,6 +5641,18 @@ impl Config {
             let config_env = EnvironmentVariables::from_kdl(&env_config)?;
             config.env = config.env.merge(config_env);
         }
+        if let Some(kdl_templates) = kdl_config.get("templates") {
+            let config_templates = LayoutTemplates::from_kdl(kdl_templates)?;
+            config.templates.merge(config_templates);
+        }
+        if let Some(kdl_hooks) = kdl_config.get("hooks") {
+            let config_hooks = Hooks::from_kdl(kdl_hooks, &config.options)?;
+            config.hooks.merge(config_hooks);
+        }
+        if let Some(kdl_daemon) = kdl_config.get("daemon") {
+            let config_daemon = DaemonConfig::from_kdl(kdl_daemon)?;
+            config.daemon.merge(config_daemon);
+        }
         Ok(config)
     }
     pub fn to_string(&self, add_comments: bool) -> String {
@@ -3819,6 +5680,18 @@ impl Config {
             document.nodes_mut().push(env);
         }
 
+        if let Some(templates) = self.templates.to_kdl() {
+            document.nodes_mut().push(templates);
+        }
+
+        if let Some(hooks) = self.hooks.to_kdl() {
+            document.nodes_mut().push(hooks);
+        }
+
+        if let Some(daemon) = self.daemon.to_kdl() {
+            document.nodes_mut().push(daemon);
+        }
+
         document
             .nodes_mut()
             .append(&mut self.options.to_kdl(add_comments));
@@ -4485,6 +6358,20 @@ impl SessionInfo {
                 }
             }
         }
+        let tags = kdl_document
+            .get("tags")
+            .map(|t| {
+                t.entries()
+                    .iter()
+                    .filter_map(|e| e.value().as_string().map(|s| s.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let note = kdl_document
+            .get("note")
+            .and_then(|n| n.entries().iter().next())
+            .and_then(|e| e.value().as_string())
+            .map(|s| s.to_owned());
         Ok(SessionInfo {
             name,
             tabs,
@@ -4494,6 +6381,8 @@ impl SessionInfo {
             available_layouts,
             plugins: Default::default(), // we do not serialize plugin information
             tab_history,
+            tags,
+            note,
         })
     }
     pub fn to_string(&self) -> String {
@@ -4558,6 +6447,18 @@ impl SessionInfo {
         kdl_document.nodes_mut().push(connected_clients);
         kdl_document.nodes_mut().push(available_layouts);
         kdl_document.nodes_mut().push(tab_history);
+        if !self.tags.is_empty() {
+            let mut tags = KdlNode::new("tags");
+            for tag in &self.tags {
+                tags.push(tag.clone());
+            }
+            kdl_document.nodes_mut().push(tags);
+        }
+        if let Some(note) = &self.note {
+            let mut note_node = KdlNode::new("note");
+            note_node.push(note.clone());
+            kdl_document.nodes_mut().push(note_node);
+        }
         kdl_document.fmt();
         kdl_document.to_string()
     }
@@ -4658,6 +6559,12 @@ impl TabInfo {
             display_area_columns,
             selectable_tiled_panes_count,
             selectable_floating_panes_count,
+            // alerts and input locks are only meaningful to the clients of the running session
+            has_activity_alert: false,
+            has_silence_alert: false,
+            has_bell_alert: false,
+            has_command_exit_alert: false,
+            has_input_locked_pane: false,
         })
     }
     pub fn encode_to_kdl(&self) -> KdlDocument {
@@ -4862,6 +6769,7 @@ impl PaneInfo {
         let terminal_command = optional_string_node!("terminal_command");
         let plugin_url = optional_string_node!("plugin_url");
         let is_selectable = bool_node!("is_selectable");
+        let cwd = optional_string_node!("cwd").map(PathBuf::from);
 
         let pane_info = PaneInfo {
             id,
@@ -4886,6 +6794,7 @@ impl PaneInfo {
             terminal_command,
             plugin_url,
             is_selectable,
+            cwd,
         };
         Ok((tab_position, pane_info))
     }
@@ -4946,6 +6855,9 @@ impl PaneInfo {
             string_node!("plugin_url", plugin_url.to_string());
         }
         bool_node!("is_selectable", self.is_selectable);
+        if let Some(cwd) = &self.cwd {
+            string_node!("cwd", cwd.display().to_string());
+        }
         kdl_doucment
     }
 }
@@ -5033,6 +6945,7 @@ fn serialize_and_deserialize_session_info_with_data() {
             terminal_command: Some("foo".to_owned()),
             plugin_url: None,
             is_selectable: true,
+            cwd: None,
         },
         PaneInfo {
             id: 1,
@@ -5057,6 +6970,7 @@ fn serialize_and_deserialize_session_info_with_data() {
             terminal_command: None,
             plugin_url: Some("i_am_a_fake_plugin".to_owned()),
             is_selectable: true,
+            cwd: None,
         },
     ];
     let mut panes = HashMap::new();
@@ -5081,6 +6995,11 @@ fn serialize_and_deserialize_session_info_with_data() {
                 display_area_columns: 10,
                 selectable_tiled_panes_count: 10,
                 selectable_floating_panes_count: 10,
+                has_activity_alert: false,
+                has_silence_alert: false,
+                has_bell_alert: false,
+                has_command_exit_alert: false,
+                has_input_locked_pane: false,
             },
             TabInfo {
                 position: 1,
@@ -5099,6 +7018,11 @@ fn serialize_and_deserialize_session_info_with_data() {
                 display_area_columns: 10,
                 selectable_tiled_panes_count: 10,
                 selectable_floating_panes_count: 10,
+                has_activity_alert: false,
+                has_silence_alert: false,
+                has_bell_alert: false,
+                has_command_exit_alert: false,
+                has_input_locked_pane: false,
             },
         ],
         panes: PaneManifest { panes },
@@ -5111,6 +7035,8 @@ fn serialize_and_deserialize_session_info_with_data() {
         ],
         plugins: Default::default(),
         tab_history: Default::default(),
+        tags: vec!["work".to_owned(), "db".to_owned()],
+        note: Some("deploying payments v2".to_owned()),
     };
     let serialized = session_info.to_string();
     let deserealized = SessionInfo::from_string(&serialized, "not this session").unwrap();
@@ -5888,6 +7814,56 @@ fn config_options_to_string() {
     insta::assert_snapshot!(fake_document.to_string());
 }
 
+#[test]
+fn config_options_with_quiet_hours() {
+    let fake_config = r#"quiet_hours "22:30-07:00""#;
+    let document: KdlDocument = fake_config.parse().unwrap();
+    let quiet_hours = Options::from_kdl(&document).unwrap().quiet_hours.unwrap();
+    assert!(
+        quiet_hours.contains(23 * 60),
+        "within the range, before midnight"
+    );
+    assert!(
+        quiet_hours.contains(6 * 60 + 59),
+        "within the range, after midnight"
+    );
+    assert!(!quiet_hours.contains(22 * 60), "before the range");
+    assert!(
+        !quiet_hours.contains(7 * 60),
+        "the end of the range is exclusive"
+    );
+    assert_eq!(quiet_hours.to_string(), "22:30-07:00");
+    let invalid_config: KdlDocument = r#"quiet_hours "25:00-07:00""#.parse().unwrap();
+    assert!(
+        Options::from_kdl(&invalid_config).is_err(),
+        "invalid quiet hours are a config error"
+    );
+}
+
+#[test]
+fn config_options_with_resource_limits() {
+    let fake_config = r#"
+        max_panes_per_tab 8
+        max_tabs 0
+    "#;
+    let document: KdlDocument = fake_config.parse().unwrap();
+    let options = Options::from_kdl(&document).unwrap();
+    assert_eq!(options.max_panes_per_tab, Some(8));
+    assert_eq!(options.max_tabs, Some(0));
+    assert_eq!(options.max_scrollback_memory, None);
+    for invalid_config in [
+        "max_panes_per_tab -1",
+        "max_tabs -2",
+        "max_scrollback_memory -512",
+    ] {
+        let invalid_config: KdlDocument = invalid_config.parse().unwrap();
+        assert!(
+            Options::from_kdl(&invalid_config).is_err(),
+            "negative limits do not wrap around to huge ones"
+        );
+    }
+}
+
 #[test]
 fn config_options_to_string_with_comments() {
     let fake_config = r##"
@@ -1,4 +1,5 @@
 pub mod cli;
+pub mod cli_style;
 pub mod consts;
 pub mod data;
 pub mod envs;
@@ -9,6 +10,7 @@ pub mod kdl;
 pub mod pane_size;
 pub mod plugin_api;
 pub mod position;
+pub mod session_events;
 pub mod session_serialization;
 pub mod setup;
 pub mod shared;
@@ -28,8 +30,8 @@ pub mod logging; // Requires log4rs
 #[cfg(not(target_family = "wasm"))]
 pub use ::{
     anyhow, async_channel, async_std, clap, humantime, interprocess, isahc, lazy_static, libc,
-    miette, nix, notify_debouncer_full, regex, serde, signal_hook, tempfile, termwiz, url, uuid,
-    vte,
+    miette, nix, notify_debouncer_full, regex, serde, serde_json, signal_hook, strip_ansi_escapes,
+    tempfile, termwiz, url, uuid, vte,
 };
 
 pub use ::prost;
@@ -137,7 +137,12 @@ impl TryFrom<ProtobufAction> for Action {
                 Some(OptionalPayload::DumpScreenPayload(payload)) => {
                     let file_path = payload.file_path;
                     let include_scrollback = payload.include_scrollback;
-                    Ok(Action::DumpScreen(file_path, include_scrollback))
+                    let include_ansi = payload.include_ansi;
+                    Ok(Action::DumpScreen(
+                        file_path,
+                        include_scrollback,
+                        include_ansi,
+                    ))
                 },
                 _ => Err("Wrong payload for Action::DumpScreen"),
             },
@@ -816,11 +821,12 @@ impl TryFrom<Action> for ProtobufAction {
                 name: ProtobufActionName::ClearScreen as i32,
                 optional_payload: None,
             }),
-            Action::DumpScreen(file_path, include_scrollback) => Ok(ProtobufAction {
+            Action::DumpScreen(file_path, include_scrollback, include_ansi) => Ok(ProtobufAction {
                 name: ProtobufActionName::DumpScreen as i32,
                 optional_payload: Some(OptionalPayload::DumpScreenPayload(DumpScreenPayload {
                     file_path,
                     include_scrollback,
+                    include_ansi,
                 })),
             }),
             Action::EditScrollback => Ok(ProtobufAction {
@@ -1244,9 +1250,59 @@ impl TryFrom<Action> for ProtobufAction {
             | Action::Copy
             | Action::DumpLayout
             | Action::CliPipe { .. }
-            | Action::ListClients
+            | Action::ListClients { .. }
             | Action::StackPanes(..)
             | Action::ChangeFloatingPaneCoordinates(..)
+            | Action::ToggleMouseReportingOverride
+            | Action::WriteRaw(..)
+            | Action::ToggleRawPassthrough
+            | Action::ToggleDoNotDisturb
+            | Action::ToggleFloatingPaneScope
+            | Action::SetSessionEnv(..)
+            | Action::SetTabCwd(..)
+            | Action::NewTabFromLayout(..)
+            | Action::SelectSwapLayout(..)
+            | Action::QuerySwapLayoutNames
+            | Action::SetUi(..)
+            | Action::ClosePaneWithId(..)
+            | Action::RenamePaneWithId(..)
+            | Action::TogglePaneIdFullscreen(..)
+            | Action::FocusPaneWithId(..)
+            | Action::CloseTabWithName(..)
+            | Action::RenameTabWithName(..)
+            | Action::ListPanes { .. }
+            | Action::SetSessionNote(..)
+            | Action::MovePaneToTab(..)
+            | Action::MoveTabToIndex(..)
+            | Action::JoinTab(..)
+            | Action::MovePaneToSession(..)
+            | Action::LinkPaneToTab(..)
+            | Action::DeclarePort(..)
+            | Action::UndeclarePort(..)
+            | Action::ListPorts { .. }
+            | Action::DumpSession
+            | Action::SearchScrollback { .. }
+            | Action::SearchPanes { .. }
+            | Action::ScrollToLine { .. }
+            | Action::TogglePaneLogging { .. }
+            | Action::RerunCommandPane { .. }
+            | Action::OpenCommandHistory { .. }
+            | Action::UndoClosePane
+            | Action::DuplicatePane(..)
+            | Action::ToggleActivityMonitor { .. }
+            | Action::ToggleSilenceMonitor { .. }
+            | Action::TogglePaneInGroup { .. }
+            | Action::ApplyToPaneGroup { .. }
+            | Action::MultiExec { .. }
+            | Action::ToggleExpandStack
+            | Action::MovePaneInStack(..)
+            | Action::FocusStackedPaneByTitle(..)
+            | Action::ReplacePaneCommand(..)
+            | Action::TogglePaneInputLock
+            | Action::ScrollToPreviousPrompt
+            | Action::ScrollToNextPrompt
+            | Action::SelectLastCommandOutput
+            | Action::CopyModeInput(..)
             | Action::SkipConfirm(..) => Err("Unsupported action"),
         }
     }
@@ -1259,6 +1315,7 @@ impl TryFrom<ProtobufSearchOption> for SearchOption {
             ProtobufSearchOption::CaseSensitivity => Ok(SearchOption::CaseSensitivity),
             ProtobufSearchOption::WholeWord => Ok(SearchOption::WholeWord),
             ProtobufSearchOption::Wrap => Ok(SearchOption::Wrap),
+            ProtobufSearchOption::Regex => Ok(SearchOption::Regex),
         }
     }
 }
@@ -1270,6 +1327,7 @@ impl TryFrom<SearchOption> for ProtobufSearchOption {
             SearchOption::CaseSensitivity => Ok(ProtobufSearchOption::CaseSensitivity),
             SearchOption::WholeWord => Ok(ProtobufSearchOption::WholeWord),
             SearchOption::Wrap => Ok(ProtobufSearchOption::Wrap),
+            SearchOption::Regex => Ok(ProtobufSearchOption::Regex),
         }
     }
 }
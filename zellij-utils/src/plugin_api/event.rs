@@ -359,6 +359,22 @@ impl TryFrom<ProtobufEvent> for Event {
                 None => Ok(Event::ConfigWasWrittenToDisk),
                 _ => Err("Malformed payload for the ConfigWasWrittenToDisk Event"),
             },
+            Some(ProtobufEventType::CommandPaneFinished) => match protobuf_event.payload {
+                Some(ProtobufEventPayload::CommandPaneFinishedPayload(
+                    command_pane_finished_payload,
+                )) => Ok(Event::CommandPaneFinished(
+                    command_pane_finished_payload.terminal_pane_id,
+                    command_pane_finished_payload.exit_code,
+                    Duration::from_millis(command_pane_finished_payload.runtime_ms),
+                )),
+                _ => Err("Malformed payload for the CommandPaneFinished Event"),
+            },
+            Some(ProtobufEventType::NamedTimer) => match protobuf_event.payload {
+                Some(ProtobufEventPayload::NamedTimerPayload(named_timer_payload)) => Ok(
+                    Event::NamedTimer(named_timer_payload.name, named_timer_payload.elapsed),
+                ),
+                _ => Err("Malformed payload for the NamedTimer Event"),
+            },
             None => Err("Unknown Protobuf Event"),
         }
     }
@@ -733,6 +749,23 @@ impl TryFrom<Event> for ProtobufEvent {
                 name: ProtobufEventType::ConfigWasWrittenToDisk as i32,
                 payload: None,
             }),
+            Event::CommandPaneFinished(terminal_pane_id, exit_code, runtime) => Ok(ProtobufEvent {
+                name: ProtobufEventType::CommandPaneFinished as i32,
+                payload: Some(event::Payload::CommandPaneFinishedPayload(
+                    CommandPaneFinishedPayload {
+                        terminal_pane_id,
+                        exit_code,
+                        runtime_ms: runtime.as_millis() as u64,
+                    },
+                )),
+            }),
+            Event::NamedTimer(name, elapsed) => Ok(ProtobufEvent {
+                name: ProtobufEventType::NamedTimer as i32,
+                payload: Some(event::Payload::NamedTimerPayload(NamedTimerPayload {
+                    name,
+                    elapsed,
+                })),
+            }),
         }
     }
 }
@@ -776,6 +809,8 @@ impl TryFrom<SessionInfo> for ProtobufSessionManifest {
                 .into_iter()
                 .map(|t| ProtobufClientTabHistory::from(t))
                 .collect(),
+            tags: session_info.tags,
+            note: session_info.note,
         })
     }
 }
@@ -861,6 +896,8 @@ impl TryFrom<ProtobufSessionManifest> for SessionInfo {
                 .collect(),
             plugins,
             tab_history,
+            tags: protobuf_session_manifest.tags,
+            note: protobuf_session_manifest.note,
         })
     }
 }
@@ -1072,6 +1109,7 @@ impl TryFrom<ProtobufPaneInfo> for PaneInfo {
             terminal_command: protobuf_pane_info.terminal_command,
             plugin_url: protobuf_pane_info.plugin_url,
             is_selectable: protobuf_pane_info.is_selectable,
+            cwd: protobuf_pane_info.cwd.map(PathBuf::from),
         })
     }
 }
@@ -1107,6 +1145,7 @@ impl TryFrom<PaneInfo> for ProtobufPaneInfo {
             terminal_command: pane_info.terminal_command,
             plugin_url: pane_info.plugin_url,
             is_selectable: pane_info.is_selectable,
+            cwd: pane_info.cwd.map(|cwd| cwd.to_string_lossy().into_owned()),
         })
     }
 }
@@ -1136,6 +1175,11 @@ impl TryFrom<ProtobufTabInfo> for TabInfo {
             selectable_tiled_panes_count: protobuf_tab_info.selectable_tiled_panes_count as usize,
             selectable_floating_panes_count: protobuf_tab_info.selectable_floating_panes_count
                 as usize,
+            has_activity_alert: protobuf_tab_info.has_activity_alert,
+            has_silence_alert: protobuf_tab_info.has_silence_alert,
+            has_bell_alert: protobuf_tab_info.has_bell_alert,
+            has_command_exit_alert: protobuf_tab_info.has_command_exit_alert,
+            has_input_locked_pane: protobuf_tab_info.has_input_locked_pane,
         })
     }
 }
@@ -1164,6 +1208,11 @@ impl TryFrom<TabInfo> for ProtobufTabInfo {
             display_area_columns: tab_info.display_area_columns as u32,
             selectable_tiled_panes_count: tab_info.selectable_tiled_panes_count as u32,
             selectable_floating_panes_count: tab_info.selectable_floating_panes_count as u32,
+            has_activity_alert: tab_info.has_activity_alert,
+            has_silence_alert: tab_info.has_silence_alert,
+            has_bell_alert: tab_info.has_bell_alert,
+            has_command_exit_alert: tab_info.has_command_exit_alert,
+            has_input_locked_pane: tab_info.has_input_locked_pane,
         })
     }
 }
@@ -1213,6 +1262,8 @@ impl TryFrom<ProtobufModeUpdatePayload> for ModeInfo {
             .editor
             .map(|e| PathBuf::from(e));
         let shell = protobuf_mode_update_payload.shell.map(|s| PathBuf::from(s));
+        let installed_version = protobuf_mode_update_payload.installed_version;
+        let latest_release = protobuf_mode_update_payload.latest_release;
         let capabilities = PluginCapabilities {
             arrow_fonts: protobuf_mode_update_payload.arrow_fonts_support,
         };
@@ -1225,6 +1276,8 @@ impl TryFrom<ProtobufModeUpdatePayload> for ModeInfo {
             base_mode,
             editor,
             shell,
+            installed_version,
+            latest_release,
         };
         Ok(mode_info)
     }
@@ -1242,6 +1295,8 @@ impl TryFrom<ModeInfo> for ProtobufModeUpdatePayload {
         let session_name = mode_info.session_name;
         let editor = mode_info.editor.map(|e| e.display().to_string());
         let shell = mode_info.shell.map(|s| s.display().to_string());
+        let installed_version = mode_info.installed_version;
+        let latest_release = mode_info.latest_release;
         let mut protobuf_input_mode_keybinds: Vec<ProtobufInputModeKeybinds> = vec![];
         for (input_mode, input_mode_keybinds) in mode_info.keybinds {
             let mode: ProtobufInputMode = input_mode.try_into()?;
@@ -1275,6 +1330,8 @@ impl TryFrom<ModeInfo> for ProtobufModeUpdatePayload {
             base_mode: base_mode.map(|b_m| b_m as i32),
             editor,
             shell,
+            installed_version,
+            latest_release,
         })
     }
 }
@@ -1344,6 +1401,8 @@ impl TryFrom<ProtobufEventType> for EventType {
             ProtobufEventType::FailedToChangeHostFolder => EventType::FailedToChangeHostFolder,
             ProtobufEventType::PastedText => EventType::PastedText,
             ProtobufEventType::ConfigWasWrittenToDisk => EventType::ConfigWasWrittenToDisk,
+            ProtobufEventType::CommandPaneFinished => EventType::CommandPaneFinished,
+            ProtobufEventType::NamedTimer => EventType::NamedTimer,
         })
     }
 }
@@ -1383,6 +1442,8 @@ impl TryFrom<EventType> for ProtobufEventType {
             EventType::FailedToChangeHostFolder => ProtobufEventType::FailedToChangeHostFolder,
             EventType::PastedText => ProtobufEventType::PastedText,
             EventType::ConfigWasWrittenToDisk => ProtobufEventType::ConfigWasWrittenToDisk,
+            EventType::CommandPaneFinished => ProtobufEventType::CommandPaneFinished,
+            EventType::NamedTimer => ProtobufEventType::NamedTimer,
         })
     }
 }
@@ -1523,6 +1584,8 @@ fn serialize_mode_update_event_with_non_default_values() {
         base_mode: Some(InputMode::Locked),
         editor: Some(PathBuf::from("my_awesome_editor")),
         shell: Some(PathBuf::from("my_awesome_shell")),
+        installed_version: Some("0.99.0".to_owned()),
+        latest_release: Some("1.0.0".to_owned()),
     });
     let protobuf_event: ProtobufEvent = mode_update_event.clone().try_into().unwrap();
     let serialized_protobuf_event = protobuf_event.encode_to_vec();
@@ -1571,6 +1634,11 @@ fn serialize_tab_update_event_with_non_default_values() {
             display_area_columns: 10,
             selectable_tiled_panes_count: 10,
             selectable_floating_panes_count: 10,
+            has_activity_alert: false,
+            has_silence_alert: false,
+            has_bell_alert: false,
+            has_command_exit_alert: false,
+            has_input_locked_pane: false,
         },
         TabInfo {
             position: 1,
@@ -1589,6 +1657,11 @@ fn serialize_tab_update_event_with_non_default_values() {
             display_area_columns: 10,
             selectable_tiled_panes_count: 10,
             selectable_floating_panes_count: 10,
+            has_activity_alert: false,
+            has_silence_alert: false,
+            has_bell_alert: false,
+            has_command_exit_alert: false,
+            has_input_locked_pane: false,
         },
         TabInfo::default(),
     ]);
@@ -1679,6 +1752,37 @@ fn serialize_timer_event() {
     );
 }
 
+#[test]
+fn serialize_named_timer_event() {
+    use prost::Message;
+    let named_timer_event = Event::NamedTimer("refresh-ci-status".to_owned(), 0.25);
+    let protobuf_event: ProtobufEvent = named_timer_event.clone().try_into().unwrap();
+    let serialized_protobuf_event = protobuf_event.encode_to_vec();
+    let deserialized_protobuf_event: ProtobufEvent =
+        Message::decode(serialized_protobuf_event.as_slice()).unwrap();
+    let deserialized_event: Event = deserialized_protobuf_event.try_into().unwrap();
+    assert_eq!(
+        named_timer_event, deserialized_event,
+        "Event properly serialized/deserialized without change"
+    );
+}
+
+#[test]
+fn serialize_command_pane_finished_event() {
+    use prost::Message;
+    let command_pane_finished_event =
+        Event::CommandPaneFinished(3, Some(1), Duration::from_millis(2500));
+    let protobuf_event: ProtobufEvent = command_pane_finished_event.clone().try_into().unwrap();
+    let serialized_protobuf_event = protobuf_event.encode_to_vec();
+    let deserialized_protobuf_event: ProtobufEvent =
+        Message::decode(serialized_protobuf_event.as_slice()).unwrap();
+    let deserialized_event: Event = deserialized_protobuf_event.try_into().unwrap();
+    assert_eq!(
+        command_pane_finished_event, deserialized_event,
+        "Event properly serialized/deserialized without change"
+    );
+}
+
 #[test]
 fn serialize_copy_to_clipboard_event() {
     use prost::Message;
@@ -1862,6 +1966,11 @@ fn serialize_session_update_event_with_non_default_values() {
             display_area_columns: 10,
             selectable_tiled_panes_count: 10,
             selectable_floating_panes_count: 10,
+            has_activity_alert: false,
+            has_silence_alert: false,
+            has_bell_alert: false,
+            has_command_exit_alert: false,
+            has_input_locked_pane: false,
         },
         TabInfo {
             position: 1,
@@ -1880,6 +1989,11 @@ fn serialize_session_update_event_with_non_default_values() {
             display_area_columns: 10,
             selectable_tiled_panes_count: 10,
             selectable_floating_panes_count: 10,
+            has_activity_alert: false,
+            has_silence_alert: false,
+            has_bell_alert: false,
+            has_command_exit_alert: false,
+            has_input_locked_pane: false,
         },
         TabInfo::default(),
     ];
@@ -1908,6 +2022,7 @@ fn serialize_session_update_event_with_non_default_values() {
             terminal_command: Some("foo".to_owned()),
             plugin_url: None,
             is_selectable: true,
+            cwd: Some(PathBuf::from("/path/to/foo")),
         },
         PaneInfo {
             id: 1,
@@ -1932,6 +2047,7 @@ fn serialize_session_update_event_with_non_default_values() {
             terminal_command: None,
             plugin_url: Some("i_am_a_fake_plugin".to_owned()),
             is_selectable: true,
+            cwd: None,
         },
     ];
     panes.insert(0, panes_list);
@@ -1961,6 +2077,8 @@ fn serialize_session_update_event_with_non_default_values() {
         ],
         plugins,
         tab_history,
+        tags: vec!["work".to_owned()],
+        note: Some("deploying payments v2".to_owned()),
     };
     let session_info_2 = SessionInfo {
         name: "session 2".to_owned(),
@@ -1977,6 +2095,8 @@ fn serialize_session_update_event_with_non_default_values() {
         ],
         plugins: Default::default(),
         tab_history: Default::default(),
+        tags: vec![],
+        note: None,
     };
     let session_infos = vec![session_info_1, session_info_2];
     let resurrectable_sessions = vec![];
@@ -36,6 +36,8 @@ impl TryFrom<ProtobufPipeMessage> for PipeMessage {
             payload,
             args,
             is_private,
+            request_id: protobuf_pipe_message.request_id,
+            in_reply_to: protobuf_pipe_message.in_reply_to,
         })
     }
 }
@@ -68,6 +70,37 @@ impl TryFrom<PipeMessage> for ProtobufPipeMessage {
             payload,
             args,
             is_private,
+            request_id: pipe_message.request_id,
+            in_reply_to: pipe_message.in_reply_to,
         })
     }
 }
+
+#[test]
+fn serialize_pipe_message_with_request_and_reply_ids() {
+    use prost::Message;
+    let mut pipe_message = PipeMessage::new(
+        PipeSource::Plugin(1),
+        "request",
+        &Some("payload".to_owned()),
+        &None,
+        true,
+    )
+    .with_correlation(
+        Some("my_request_id".to_owned()),
+        Some("previous_request_id".to_owned()),
+    );
+    pipe_message
+        .args
+        .insert("key".to_owned(), "value".to_owned());
+    let protobuf_pipe_message: ProtobufPipeMessage = pipe_message.clone().try_into().unwrap();
+    let serialized_protobuf_pipe_message = protobuf_pipe_message.encode_to_vec();
+    let deserialized_protobuf_pipe_message: ProtobufPipeMessage =
+        Message::decode(serialized_protobuf_pipe_message.as_slice()).unwrap();
+    let deserialized_pipe_message: PipeMessage =
+        deserialized_protobuf_pipe_message.try_into().unwrap();
+    assert_eq!(
+        pipe_message, deserialized_pipe_message,
+        "PipeMessage properly serialized/deserialized without change"
+    );
+}
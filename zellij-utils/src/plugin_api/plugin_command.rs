@@ -25,9 +25,10 @@ pub use super::generated_api::api::{
         RerunCommandPanePayload, ResizePaneIdWithDirectionPayload, ResizePayload,
         RunCommandPayload, ScrollDownInPaneIdPayload, ScrollToBottomInPaneIdPayload,
         ScrollToTopInPaneIdPayload, ScrollUpInPaneIdPayload, SetFloatingPanePinnedPayload,
-        SetTimeoutPayload, ShowPaneWithIdPayload, StackPanesPayload, SubscribePayload,
-        SwitchSessionPayload, SwitchTabToPayload, TogglePaneEmbedOrEjectForPaneIdPayload,
-        TogglePaneIdFullscreenPayload, UnsubscribePayload, WebRequestPayload,
+        SetTimeoutPayload, SetTimerPayload, ShowPaneWithIdPayload, StackPanesPayload,
+        SubscribePayload, SwitchSessionPayload, SwitchTabToPayload,
+        TogglePaneEmbedOrEjectForPaneIdPayload, TogglePaneIdFullscreenPayload, UnsubscribePayload,
+        WatchPathsPayload, WatchPathsResult as ProtobufWatchPathsResult, WebRequestPayload,
         WriteCharsToPaneIdPayload, WriteToPaneIdPayload,
     },
     plugin_permission::PermissionType as ProtobufPermissionType,
@@ -933,6 +934,8 @@ impl TryFrom<ProtobufPluginCommand> for PluginCommand {
                     message_args,
                     new_plugin_args,
                     destination_plugin_id,
+                    request_id,
+                    in_reply_to,
                 })) => {
                     let plugin_config: BTreeMap<String, String> = plugin_config
                         .into_iter()
@@ -960,6 +963,8 @@ impl TryFrom<ProtobufPluginCommand> for PluginCommand {
                             })
                         }),
                         destination_plugin_id,
+                        request_id,
+                        in_reply_to,
                     }))
                 },
                 _ => Err("Mismatched payload for MessageToPlugin"),
@@ -1540,6 +1545,24 @@ impl TryFrom<ProtobufPluginCommand> for PluginCommand {
                 },
                 _ => Err("Mismatched payload for OpenFileInPlaceOfPlugin"),
             },
+            Some(CommandName::WatchPaths) => match protobuf_plugin_command.payload {
+                Some(Payload::WatchPathsPayload(WatchPathsPayload { paths })) => Ok(
+                    PluginCommand::WatchPaths(paths.into_iter().map(PathBuf::from).collect()),
+                ),
+                _ => Err("Mismatched payload for WatchPaths"),
+            },
+            Some(CommandName::SetTimer) => match protobuf_plugin_command.payload {
+                Some(Payload::SetTimerPayload(SetTimerPayload {
+                    name,
+                    seconds,
+                    repeat,
+                })) => Ok(PluginCommand::SetTimer(name, seconds, repeat)),
+                _ => Err("Mismatched payload for SetTimer"),
+            },
+            Some(CommandName::CancelTimer) => match protobuf_plugin_command.payload {
+                Some(Payload::CancelTimerPayload(name)) => Ok(PluginCommand::CancelTimer(name)),
+                _ => Err("Mismatched payload for CancelTimer"),
+            },
             None => Err("Unrecognized plugin command"),
         }
     }
@@ -2069,6 +2092,8 @@ impl TryFrom<PluginCommand> for ProtobufPluginCommand {
                             }
                         }),
                         destination_plugin_id: message_to_plugin.destination_plugin_id,
+                        request_id: message_to_plugin.request_id,
+                        in_reply_to: message_to_plugin.in_reply_to,
                     })),
                 })
             },
@@ -2551,6 +2576,99 @@ impl TryFrom<PluginCommand> for ProtobufPluginCommand {
                     },
                 )),
             }),
+            PluginCommand::WatchPaths(paths) => Ok(ProtobufPluginCommand {
+                name: CommandName::WatchPaths as i32,
+                payload: Some(Payload::WatchPathsPayload(WatchPathsPayload {
+                    paths: paths.into_iter().map(|p| p.display().to_string()).collect(),
+                })),
+            }),
+            PluginCommand::SetTimer(name, seconds, repeat) => Ok(ProtobufPluginCommand {
+                name: CommandName::SetTimer as i32,
+                payload: Some(Payload::SetTimerPayload(SetTimerPayload {
+                    name,
+                    seconds,
+                    repeat,
+                })),
+            }),
+            PluginCommand::CancelTimer(name) => Ok(ProtobufPluginCommand {
+                name: CommandName::CancelTimer as i32,
+                payload: Some(Payload::CancelTimerPayload(name)),
+            }),
         }
     }
 }
+
+#[test]
+fn serialize_message_to_plugin_with_request_and_reply_ids() {
+    use prost::Message;
+    let plugin_command = PluginCommand::MessageToPlugin(
+        MessageToPlugin::new("request")
+            .with_destination_plugin_id(2)
+            .with_payload("payload")
+            .with_request_id("my_request_id")
+            .in_reply_to("previous_request_id"),
+    );
+    let protobuf_plugin_command: ProtobufPluginCommand = plugin_command.try_into().unwrap();
+    let serialized_protobuf_plugin_command = protobuf_plugin_command.encode_to_vec();
+    let deserialized_protobuf_plugin_command: ProtobufPluginCommand =
+        Message::decode(serialized_protobuf_plugin_command.as_slice()).unwrap();
+    let deserialized_plugin_command: PluginCommand =
+        deserialized_protobuf_plugin_command.try_into().unwrap();
+    match deserialized_plugin_command {
+        PluginCommand::MessageToPlugin(message_to_plugin) => {
+            assert_eq!(message_to_plugin.message_name, "request");
+            assert_eq!(message_to_plugin.destination_plugin_id, Some(2));
+            assert_eq!(
+                message_to_plugin.message_payload.as_deref(),
+                Some("payload")
+            );
+            assert_eq!(
+                message_to_plugin.request_id.as_deref(),
+                Some("my_request_id"),
+                "request id properly serialized/deserialized"
+            );
+            assert_eq!(
+                message_to_plugin.in_reply_to.as_deref(),
+                Some("previous_request_id"),
+                "in reply to properly serialized/deserialized"
+            );
+        },
+        _ => panic!("MessageToPlugin deserialized as another command"),
+    }
+}
+
+#[test]
+fn serialize_set_timer() {
+    use prost::Message;
+    let plugin_command = PluginCommand::SetTimer("refresh-ci-status".to_owned(), 2.5, true);
+    let protobuf_plugin_command: ProtobufPluginCommand = plugin_command.try_into().unwrap();
+    let serialized_protobuf_plugin_command = protobuf_plugin_command.encode_to_vec();
+    let deserialized_protobuf_plugin_command: ProtobufPluginCommand =
+        Message::decode(serialized_protobuf_plugin_command.as_slice()).unwrap();
+    let deserialized_plugin_command: PluginCommand =
+        deserialized_protobuf_plugin_command.try_into().unwrap();
+    match deserialized_plugin_command {
+        PluginCommand::SetTimer(name, seconds, repeat) => {
+            assert_eq!(name, "refresh-ci-status");
+            assert_eq!(seconds, 2.5);
+            assert!(repeat, "repeat properly serialized/deserialized");
+        },
+        _ => panic!("SetTimer deserialized as another command"),
+    }
+}
+
+#[test]
+fn serialize_cancel_timer() {
+    use prost::Message;
+    let plugin_command = PluginCommand::CancelTimer("refresh-ci-status".to_owned());
+    let protobuf_plugin_command: ProtobufPluginCommand = plugin_command.try_into().unwrap();
+    let serialized_protobuf_plugin_command = protobuf_plugin_command.encode_to_vec();
+    let deserialized_protobuf_plugin_command: ProtobufPluginCommand =
+        Message::decode(serialized_protobuf_plugin_command.as_slice()).unwrap();
+    let deserialized_plugin_command: PluginCommand =
+        deserialized_protobuf_plugin_command.try_into().unwrap();
+    match deserialized_plugin_command {
+        PluginCommand::CancelTimer(name) => assert_eq!(name, "refresh-ci-status"),
+        _ => panic!("CancelTimer deserialized as another command"),
+    }
+}
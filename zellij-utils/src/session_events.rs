@@ -0,0 +1,295 @@
+//! Turns the state updates that a session sends to clients following its events (eg.
+//! `zellij events --follow`) into discrete events, such as a tab being created or a command pane
+//! exiting. The first update of every kind only establishes what the session looks like.
+//!
+//! The server tracks these as well, to run the hooks of the configuration.
+use std::collections::BTreeMap;
+
+use crate::data::{Event, InputMode, PaneInfo, PaneManifest, TabInfo};
+use serde_json::{json, Value};
+
+pub const SESSION_EVENTS: &[&str] = &[
+    "tab-created",
+    "tab-closed",
+    "tab-renamed",
+    "tab-focused",
+    "pane-opened",
+    "pane-closed",
+    "pane-exited",
+    "mode-changed",
+    "session-renamed",
+];
+
+/// Events that the server reports to hooks directly, rather than by diffing state updates
+pub const SERVER_EVENTS: &[&str] = &["session-started", "client-attached", "client-detached"];
+
+#[derive(Debug, Default)]
+pub struct SessionEvents {
+    tabs: Option<Vec<TabInfo>>,
+    panes: Option<BTreeMap<String, (usize, PaneInfo)>>, // pane id => (tab position, pane)
+    mode: Option<InputMode>,
+    session_name: Option<String>,
+}
+
+pub fn record(event: &str, mut fields: Value) -> Value {
+    fields["event"] = json!(event);
+    fields
+}
+
+fn pane_id(pane: &PaneInfo) -> String {
+    if pane.is_plugin {
+        format!("plugin_{}", pane.id)
+    } else {
+        format!("terminal_{}", pane.id)
+    }
+}
+
+fn mode_name(mode: InputMode) -> String {
+    format!("{:?}", mode).to_lowercase()
+}
+
+fn pane_fields(pane_id: &str, tab_position: usize, pane: &PaneInfo) -> Value {
+    json!({
+        "pane_id": pane_id,
+        "tab_position": tab_position,
+        "title": pane.title,
+        "command": pane.terminal_command,
+        "plugin": pane.plugin_url,
+        "is_floating": pane.is_floating,
+    })
+}
+
+// the tabs in `tabs` that do not have a counterpart of the same name in `others`, or the tabs at
+// the end when names are ambiguous (eg. a tab was renamed at the same time)
+fn unmatched_tabs<'a>(tabs: &'a [TabInfo], others: &[TabInfo]) -> Vec<&'a TabInfo> {
+    let mut remaining: Vec<&str> = others.iter().map(|t| t.name.as_str()).collect();
+    let mut unmatched = vec![];
+    for tab in tabs {
+        match remaining.iter().position(|name| *name == tab.name) {
+            Some(index) => {
+                remaining.remove(index);
+            },
+            None => unmatched.push(tab),
+        }
+    }
+    if unmatched.len() == tabs.len().saturating_sub(others.len()) {
+        unmatched
+    } else {
+        tabs.iter().skip(others.len()).collect()
+    }
+}
+
+fn diff_tabs(previous: &[TabInfo], current: &[TabInfo]) -> Vec<Value> {
+    let mut events = vec![];
+    if current.len() > previous.len() {
+        for tab in unmatched_tabs(current, previous) {
+            events.push(record(
+                "tab-created",
+                json!({ "position": tab.position, "name": tab.name }),
+            ));
+        }
+    } else if current.len() < previous.len() {
+        for tab in unmatched_tabs(previous, current) {
+            events.push(record(
+                "tab-closed",
+                json!({ "position": tab.position, "name": tab.name }),
+            ));
+        }
+    } else {
+        for (previous_tab, tab) in previous.iter().zip(current.iter()) {
+            if previous_tab.name != tab.name {
+                events.push(record(
+                    "tab-renamed",
+                    json!({
+                        "position": tab.position,
+                        "old_name": previous_tab.name,
+                        "name": tab.name,
+                    }),
+                ));
+            }
+        }
+    }
+    let previous_active = previous.iter().find(|t| t.active);
+    let active = current.iter().find(|t| t.active);
+    if let Some(active) = active {
+        // positions shift when tabs are created or closed and names change when tabs are
+        // renamed, so the tabs are told apart by whichever did not change
+        let focus_changed = match previous_active {
+            Some(previous_active) if previous.len() == current.len() => {
+                previous_active.position != active.position
+            },
+            Some(previous_active) => previous_active.name != active.name,
+            None => true,
+        };
+        if focus_changed {
+            events.push(record(
+                "tab-focused",
+                json!({ "position": active.position, "name": active.name }),
+            ));
+        }
+    }
+    events
+}
+
+fn panes_by_id(pane_manifest: &PaneManifest) -> BTreeMap<String, (usize, PaneInfo)> {
+    pane_manifest
+        .panes
+        .iter()
+        .flat_map(|(tab_position, panes)| {
+            panes
+                .iter()
+                // the UI bars are not interesting
+                .filter(|pane| pane.is_selectable)
+                .map(move |pane| (pane_id(pane), (*tab_position, pane.clone())))
+        })
+        .collect()
+}
+
+fn diff_panes(
+    previous: &BTreeMap<String, (usize, PaneInfo)>,
+    current: &BTreeMap<String, (usize, PaneInfo)>,
+) -> Vec<Value> {
+    let mut events = vec![];
+    for (id, (tab_position, pane)) in previous {
+        if !current.contains_key(id) {
+            events.push(record("pane-closed", pane_fields(id, *tab_position, pane)));
+        }
+    }
+    for (id, (tab_position, pane)) in current {
+        match previous.get(id) {
+            None => {
+                events.push(record("pane-opened", pane_fields(id, *tab_position, pane)));
+            },
+            Some((_, previous_pane)) if pane.exited && !previous_pane.exited => {
+                let mut fields = pane_fields(id, *tab_position, pane);
+                fields["exit_status"] = json!(pane.exit_status);
+                events.push(record("pane-exited", fields));
+            },
+            Some(_) => {},
+        }
+    }
+    events
+}
+
+impl SessionEvents {
+    /// Returns the events (as json records) that happened since the previous update
+    pub fn update(&mut self, event: Event) -> Vec<Value> {
+        match event {
+            Event::TabUpdate(tabs) => {
+                let events = self
+                    .tabs
+                    .as_ref()
+                    .map(|previous| diff_tabs(previous, &tabs))
+                    .unwrap_or_default();
+                self.tabs = Some(tabs);
+                events
+            },
+            Event::PaneUpdate(pane_manifest) => {
+                let panes = panes_by_id(&pane_manifest);
+                let events = self
+                    .panes
+                    .as_ref()
+                    .map(|previous| diff_panes(previous, &panes))
+                    .unwrap_or_default();
+                self.panes = Some(panes);
+                events
+            },
+            Event::ModeUpdate(mode_info) => {
+                let mut events = vec![];
+                if let Some(previous_mode) = self.mode.replace(mode_info.mode) {
+                    if previous_mode != mode_info.mode {
+                        events.push(record(
+                            "mode-changed",
+                            json!({
+                                "old_mode": mode_name(previous_mode),
+                                "mode": mode_name(mode_info.mode),
+                            }),
+                        ));
+                    }
+                }
+                if let Some(session_name) = mode_info.session_name {
+                    if let Some(previous_name) = self.session_name.replace(session_name.clone()) {
+                        if previous_name != session_name {
+                            events.push(record(
+                                "session-renamed",
+                                json!({ "old_name": previous_name, "name": session_name }),
+                            ));
+                        }
+                    }
+                }
+                events
+            },
+            _ => vec![],
+        }
+    }
+    /// The current state of the session, once both its tabs and panes are known
+    pub fn state(&self) -> Option<Value> {
+        let tabs = self.tabs.as_ref()?;
+        let panes = self.panes.as_ref()?;
+        Some(record(
+            "state",
+            json!({
+                "session_name": self.session_name,
+                "mode": self.mode.map(mode_name),
+                "tabs": tabs
+                    .iter()
+                    .map(|tab| json!({
+                        "position": tab.position,
+                        "name": tab.name,
+                        "active": tab.active,
+                    }))
+                    .collect::<Vec<_>>(),
+                "panes": panes
+                    .iter()
+                    .map(|(id, (tab_position, pane))| pane_fields(id, *tab_position, pane))
+                    .collect::<Vec<_>>(),
+            }),
+        ))
+    }
+}
+
+pub fn event_name(record: &Value) -> &str {
+    record.get("event").and_then(Value::as_str).unwrap_or("")
+}
+
+/// One line of `<event> key=value...`, with strings quoted and null fields left out
+pub fn format_as_text(record: &Value) -> String {
+    let mut line = event_name(record).to_owned();
+    if let Some(fields) = record.as_object() {
+        for (key, value) in fields {
+            if key == "event" || value.is_null() {
+                continue;
+            }
+            line.push_str(&format!(" {}={}", key, value));
+        }
+    }
+    line
+}
+
+/// The fields of an event as environment variables (eg. `ZELLIJ_HOOK_PANE_ID`), with strings
+/// unquoted and null fields left out
+pub fn format_as_env_vars(record: &Value) -> BTreeMap<String, String> {
+    let mut env_vars = BTreeMap::new();
+    env_vars.insert(
+        "ZELLIJ_HOOK_EVENT".to_owned(),
+        event_name(record).to_owned(),
+    );
+    if let Some(fields) = record.as_object() {
+        for (key, value) in fields {
+            if key == "event" || value.is_null() {
+                continue;
+            }
+            let value = match value {
+                Value::String(string) => string.clone(),
+                value => value.to_string(),
+            };
+            env_vars.insert(format!("ZELLIJ_HOOK_{}", key.to_uppercase()), value);
+        }
+    }
+    env_vars
+}
+
+// The unit test location.
+#[path = "./unit/session_events_tests.rs"]
+#[cfg(test)]
+mod session_events_tests;
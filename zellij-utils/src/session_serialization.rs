@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use crate::{
+    input::command::{RunCommand, DEFAULT_WATCH_DEBOUNCE_MS},
     input::layout::PluginUserConfiguration,
     input::layout::{
         FloatingPaneLayout, Layout, LayoutConstraint, PercentOrFixed, Run, RunPluginOrAlias,
@@ -35,6 +36,7 @@ pub struct PaneLayoutManifest {
     pub is_borderless: bool,
     pub title: Option<String>,
     pub is_focused: bool,
+    pub is_fullscreen: bool,
     pub pane_contents: Option<String>,
 }
 
@@ -191,6 +193,7 @@ fn serialize_tiled_pane(
         let mut tiled_pane_node_children = KdlDocument::new();
         serialize_args(args, &mut tiled_pane_node_children);
         serialize_start_suspended(&command, &mut tiled_pane_node_children);
+        serialize_command_watch(&layout.run, &mut tiled_pane_node_children);
         serialize_plugin(plugin, plugin_config, &mut tiled_pane_node_children);
         if layout.children.is_empty() && layout.external_children_index.is_some() {
             tiled_pane_node_children
@@ -378,6 +381,11 @@ fn serialize_tiled_layout_attributes(
             .entries_mut()
             .push(KdlEntry::new_prop("expanded", KdlValue::Bool(true)));
     }
+    if layout.fullscreen {
+        kdl_node
+            .entries_mut()
+            .push(KdlEntry::new_prop("fullscreen", KdlValue::Bool(true)));
+    }
     if layout.children_split_direction != SplitDirection::default() {
         let direction = match layout.children_split_direction {
             SplitDirection::Horizontal => "horizontal",
@@ -463,6 +471,28 @@ fn serialize_floating_layout_attributes(
     }
 }
 
+fn serialize_command_watch(layout_run: &Option<Run>, pane_node_children: &mut KdlDocument) {
+    if let Some(Run::Command(RunCommand {
+        watch: Some(watch), ..
+    })) = layout_run
+    {
+        let mut watch_node = KdlNode::new("watch");
+        for pattern in &watch.patterns {
+            watch_node
+                .entries_mut()
+                .push(KdlEntry::new(pattern.to_owned()));
+        }
+        pane_node_children.nodes_mut().push(watch_node);
+        if watch.debounce_ms != DEFAULT_WATCH_DEBOUNCE_MS {
+            let mut watch_debounce_node = KdlNode::new("watch_debounce");
+            watch_debounce_node
+                .entries_mut()
+                .push(KdlEntry::new(KdlValue::Base10(watch.debounce_ms as i64)));
+            pane_node_children.nodes_mut().push(watch_debounce_node);
+        }
+    }
+}
+
 fn serialize_start_suspended(command: &Option<String>, pane_node_children: &mut KdlDocument) {
     if command.is_some() {
         let mut start_suspended_node = KdlNode::new("start_suspended");
@@ -658,6 +688,7 @@ fn serialize_floating_pane(
         &mut floating_pane_node,
     );
     serialize_start_suspended(&command, &mut floating_pane_node_children);
+    serialize_command_watch(&layout.run, &mut floating_pane_node_children);
     serialize_floating_layout_attributes(&layout, &mut floating_pane_node_children);
     serialize_args(args, &mut floating_pane_node_children);
     serialize_plugin(plugin, plugin_config, &mut floating_pane_node_children);
@@ -669,26 +700,28 @@ fn tiled_pane_layout_from_manifest(
     manifest: Option<&PaneLayoutManifest>,
     split_size: Option<SplitSize>,
 ) -> TiledPaneLayout {
-    let (run, borderless, is_expanded_in_stack, name, focus, pane_initial_contents) = manifest
-        .map(|g| {
-            let mut run = g.run.clone();
-            if let Some(cwd) = &g.cwd {
-                if let Some(run) = run.as_mut() {
-                    run.add_cwd(cwd);
-                } else {
-                    run = Some(Run::Cwd(cwd.clone()));
+    let (run, borderless, is_expanded_in_stack, name, focus, fullscreen, pane_initial_contents) =
+        manifest
+            .map(|g| {
+                let mut run = g.run.clone();
+                if let Some(cwd) = &g.cwd {
+                    if let Some(run) = run.as_mut() {
+                        run.add_cwd(cwd);
+                    } else {
+                        run = Some(Run::Cwd(cwd.clone()));
+                    }
                 }
-            }
-            (
-                run,
-                g.is_borderless,
-                g.geom.is_stacked() && g.geom.rows.inner > 1,
-                g.title.clone(),
-                Some(g.is_focused),
-                g.pane_contents.clone(),
-            )
-        })
-        .unwrap_or((None, false, false, None, None, None));
+                (
+                    run,
+                    g.is_borderless,
+                    g.geom.is_stacked() && g.geom.rows.inner > 1,
+                    g.title.clone(),
+                    Some(g.is_focused),
+                    g.is_fullscreen,
+                    g.pane_contents.clone(),
+                )
+            })
+            .unwrap_or((None, false, false, None, None, false, None));
     TiledPaneLayout {
         split_size,
         run,
@@ -696,6 +729,7 @@ fn tiled_pane_layout_from_manifest(
         is_expanded_in_stack,
         name,
         focus,
+        fullscreen,
         pane_initial_contents,
         ..Default::default()
     }
@@ -786,6 +820,7 @@ fn get_floating_panes_layout_from_panegeoms(
                 run,
                 focus: Some(m.is_focused),
                 already_running: false,
+                log: false,
                 pane_initial_contents: m.pane_contents.clone(),
                 logical_position: None,
             }
@@ -1257,6 +1292,51 @@ mod tests {
         assert_snapshot!(kdl.0);
     }
     #[test]
+    fn can_serialize_fullscreen_pane() {
+        let pane_geom = |x: usize| PaneGeom {
+            x,
+            y: 0,
+            rows: Dimension::fixed(10),
+            cols: Dimension::fixed(10),
+            stacked: None,
+            is_pinned: false,
+            logical_position: None,
+        };
+        let tab_layout_manifest = TabLayoutManifest {
+            tiled_panes: vec![
+                PaneLayoutManifest {
+                    geom: pane_geom(0),
+                    ..Default::default()
+                },
+                PaneLayoutManifest {
+                    geom: pane_geom(10),
+                    is_fullscreen: true,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let global_layout_manifest = GlobalLayoutManifest {
+            tabs: vec![("Tab #1".to_owned(), tab_layout_manifest)],
+            ..Default::default()
+        };
+        let kdl = serialize_session_layout(global_layout_manifest).unwrap();
+        assert_eq!(kdl.0.matches("fullscreen=true").count(), 1);
+        let layout = Layout::from_kdl(&kdl.0, Some("layout_file_name".into()), None, None).unwrap();
+        fn fullscreen_of_panes(layout: &TiledPaneLayout, fullscreen: &mut Vec<bool>) {
+            if layout.children.is_empty() {
+                fullscreen.push(layout.fullscreen);
+            }
+            for child in &layout.children {
+                fullscreen_of_panes(child, fullscreen);
+            }
+        }
+        let (_tab_name, tiled_panes, _floating_panes) = layout.tabs.iter().next().unwrap();
+        let mut fullscreen = vec![];
+        fullscreen_of_panes(tiled_panes, &mut fullscreen);
+        assert_eq!(fullscreen, vec![false, true]);
+    }
+    #[test]
     fn can_serialize_tab_hide_floating_panes() {
         let tab_layout_manifest = TabLayoutManifest {
             hide_floating_panes: true,
@@ -1428,6 +1508,49 @@ mod tests {
         assert_snapshot!(kdl.0);
     }
     #[test]
+    fn can_serialize_watched_command_panes() {
+        use crate::input::command::CommandWatch;
+        let watched_command = |debounce_ms| {
+            Some(Run::Command(RunCommand {
+                command: PathBuf::from("cargo"),
+                args: vec!["test".to_owned()],
+                watch: CommandWatch::new(
+                    vec!["**/*.rs".to_owned(), "Cargo.toml".to_owned()],
+                    Some(debounce_ms),
+                ),
+                ..Default::default()
+            }))
+        };
+        let tab_layout_manifest = TabLayoutManifest {
+            tiled_panes: vec![PaneLayoutManifest {
+                run: watched_command(1000),
+                ..Default::default()
+            }],
+            floating_panes: vec![PaneLayoutManifest {
+                run: watched_command(DEFAULT_WATCH_DEBOUNCE_MS),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let global_layout_manifest = GlobalLayoutManifest {
+            tabs: vec![("Tab #1".to_owned(), tab_layout_manifest)],
+            ..Default::default()
+        };
+        let kdl = serialize_session_layout(global_layout_manifest).unwrap().0;
+        assert_eq!(
+            kdl.matches("watch \"**/*.rs\" \"Cargo.toml\"").count(),
+            2,
+            "both panes are serialized with what they watch, got:\n{}",
+            kdl
+        );
+        assert_eq!(
+            kdl.matches("watch_debounce 1000").count(),
+            1,
+            "only a debounce other than the default is serialized, got:\n{}",
+            kdl
+        );
+    }
+    #[test]
     fn can_serialize_tab_with_floating_panes() {
         use crate::input::command::RunCommand;
         use crate::input::layout::RunPlugin;
@@ -3,6 +3,7 @@ use crate::consts::ASSET_MAP;
 use crate::input::theme::Themes;
 use crate::{
     cli::{CliArgs, Command, SessionCommand, Sessions},
+    cli_style::Painter,
     consts::{
         FEATURES, SYSTEM_DEFAULT_CONFIG_DIR, SYSTEM_DEFAULT_DATA_DIR_PREFIX, VERSION,
         ZELLIJ_CACHE_DIR, ZELLIJ_DEFAULT_THEMES, ZELLIJ_PROJ_DIR,
@@ -12,7 +13,7 @@ use crate::{
     input::{
         config::{Config, ConfigError},
         layout::Layout,
-        options::Options,
+        options::{CopyProvider, Options},
     },
 };
 use clap::{Args, IntoApp};
@@ -221,6 +222,24 @@ pub const ZSH_AUTO_START_SCRIPT: &[u8] = include_bytes!(concat!(
     "assets/shell/auto-start.zsh"
 ));
 
+pub const BASH_SHELL_INTEGRATION_SCRIPT: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "assets/shell/shell-integration.bash"
+));
+
+pub const FISH_SHELL_INTEGRATION_SCRIPT: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "assets/shell/shell-integration.fish"
+));
+
+pub const ZSH_SHELL_INTEGRATION_SCRIPT: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "assets/shell/shell-integration.zsh"
+));
+
 pub fn add_layout_ext(s: &str) -> String {
     match s {
         c if s.ends_with(".kdl") => c.to_owned(),
@@ -354,6 +373,11 @@ pub struct Setup {
     /// Generates auto-start script for the specified shell
     #[clap(long, value_name = "SHELL", value_parser)]
     pub generate_auto_start: Option<String>,
+
+    /// Generates shell hooks for the specified shell that report the current working directory
+    /// and running command to zellij
+    #[clap(long, value_name = "SHELL", value_parser)]
+    pub shell_integration: Option<String>,
 }
 
 impl Setup {
@@ -453,6 +477,11 @@ impl Setup {
             std::process::exit(0);
         }
 
+        if let Some(shell) = &self.shell_integration {
+            Self::generate_shell_integration(shell);
+            std::process::exit(0);
+        }
+
         if let Some(layout) = &self.dump_layout {
             dump_specified_layout(&layout)?;
             std::process::exit(0);
@@ -502,19 +531,16 @@ impl Setup {
             .clone()
             .or_else(|| config_dir.clone().map(|p| p.join(CONFIG_NAME)));
 
-        // according to
-        // https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
-        let hyperlink_start = "\u{1b}]8;;";
-        let hyperlink_mid = "\u{1b}\\";
-        let hyperlink_end = "\u{1b}]8;;\u{1b}\\";
+        let painter = Painter::stdout();
+        let label = |label: &str| painter.bold(label);
 
         let mut message = String::new();
 
-        writeln!(&mut message, "[Version]: {:?}", VERSION).unwrap();
+        writeln!(&mut message, "{} {:?}", label("[Version]:"), VERSION).unwrap();
         if let Some(config_dir) = config_dir {
-            writeln!(&mut message, "[CONFIG DIR]: {:?}", config_dir).unwrap();
+            writeln!(&mut message, "{} {:?}", label("[CONFIG DIR]:"), config_dir).unwrap();
         } else {
-            message.push_str("[CONFIG DIR]: Not Found\n");
+            writeln!(&mut message, "{} Not Found", label("[CONFIG DIR]:")).unwrap();
             let mut default_config_dirs = default_config_dirs()
                 .iter()
                 .filter_map(|p| p.clone())
@@ -530,21 +556,25 @@ impl Setup {
         if let Some(config_file) = config_file {
             writeln!(
                 &mut message,
-                "[LOOKING FOR CONFIG FILE FROM]: {:?}",
+                "{} {:?}",
+                label("[LOOKING FOR CONFIG FILE FROM]:"),
                 config_file
             )
             .unwrap();
             match Config::from_path(&config_file, None) {
-                Ok(_) => message.push_str("[CONFIG FILE]: Well defined.\n"),
+                Ok(_) => {
+                    writeln!(&mut message, "{} Well defined.", label("[CONFIG FILE]:")).unwrap()
+                },
                 Err(e) => writeln!(
                     &mut message,
-                    "[CONFIG ERROR]: {}. \n By default, zellij loads default configuration",
+                    "{} {}. \n By default, zellij loads default configuration",
+                    label("[CONFIG ERROR]:"),
                     e
                 )
                 .unwrap(),
             }
         } else {
-            message.push_str("[CONFIG FILE]: Not Found\n");
+            writeln!(&mut message, "{} Not Found", label("[CONFIG FILE]:")).unwrap();
             writeln!(
                 &mut message,
                 " By default zellij looks for a file called [{}] in the configuration directory",
@@ -552,9 +582,15 @@ impl Setup {
             )
             .unwrap();
         }
-        writeln!(&mut message, "[CACHE DIR]: {}", ZELLIJ_CACHE_DIR.display()).unwrap();
-        writeln!(&mut message, "[DATA DIR]: {:?}", data_dir).unwrap();
-        message.push_str(&format!("[PLUGIN DIR]: {:?}\n", plugin_dir));
+        writeln!(
+            &mut message,
+            "{} {}",
+            label("[CACHE DIR]:"),
+            ZELLIJ_CACHE_DIR.display()
+        )
+        .unwrap();
+        writeln!(&mut message, "{} {:?}", label("[DATA DIR]:"), data_dir).unwrap();
+        writeln!(&mut message, "{} {:?}", label("[PLUGIN DIR]:"), plugin_dir).unwrap();
         if !cfg!(feature = "disable_automatic_asset_installation") {
             writeln!(
                 &mut message,
@@ -568,43 +604,80 @@ impl Setup {
             .unwrap();
         }
         if let Some(layout_dir) = layout_dir {
-            writeln!(&mut message, "[LAYOUT DIR]: {:?}", layout_dir).unwrap();
+            writeln!(&mut message, "{} {:?}", label("[LAYOUT DIR]:"), layout_dir).unwrap();
         } else {
-            message.push_str("[LAYOUT DIR]: Not Found\n");
+            writeln!(&mut message, "{} Not Found", label("[LAYOUT DIR]:")).unwrap();
         }
-        writeln!(&mut message, "[SYSTEM DATA DIR]: {:?}", system_data_dir).unwrap();
+        writeln!(
+            &mut message,
+            "{} {:?}",
+            label("[SYSTEM DATA DIR]:"),
+            system_data_dir
+        )
+        .unwrap();
 
-        writeln!(&mut message, "[ARROW SEPARATOR]: {}", ARROW_SEPARATOR).unwrap();
+        writeln!(
+            &mut message,
+            "{} {}",
+            label("[ARROW SEPARATOR]:"),
+            ARROW_SEPARATOR
+        )
+        .unwrap();
         message.push_str(" Is the [ARROW_SEPARATOR] displayed correctly?\n");
         message.push_str(" If not you may want to either start zellij with a compatible mode: 'zellij options --simplified-ui true'\n");
-        let mut hyperlink_compat = String::new();
-        hyperlink_compat.push_str(hyperlink_start);
-        hyperlink_compat.push_str("https://zellij.dev/documentation/compatibility.html#the-status-bar-fonts-dont-render-correctly");
-        hyperlink_compat.push_str(hyperlink_mid);
-        hyperlink_compat.push_str("https://zellij.dev/documentation/compatibility.html#the-status-bar-fonts-dont-render-correctly");
-        hyperlink_compat.push_str(hyperlink_end);
+        let compatibility_url = "https://zellij.dev/documentation/compatibility.html#the-status-bar-fonts-dont-render-correctly";
+        let hyperlink_compat = painter.hyperlink(compatibility_url, compatibility_url);
         write!(
             &mut message,
             " Or check the font that is in use:\n {}\n",
             hyperlink_compat
         )
         .unwrap();
-        message.push_str("[MOUSE INTERACTION]: \n");
+        writeln!(&mut message, "{}", label("[MOUSE INTERACTION]:")).unwrap();
         message.push_str(" Can be temporarily disabled through pressing the [SHIFT] key.\n");
         message.push_str(" If that doesn't fix any issues consider to disable the mouse handling of zellij: 'zellij options --disable-mouse-mode'\n");
 
         let default_editor = std::env::var("EDITOR")
             .or_else(|_| std::env::var("VISUAL"))
             .unwrap_or_else(|_| String::from("Not set, checked $EDITOR and $VISUAL"));
-        writeln!(&mut message, "[DEFAULT EDITOR]: {}", default_editor).unwrap();
-        writeln!(&mut message, "[FEATURES]: {:?}", FEATURES).unwrap();
-        let mut hyperlink = String::new();
-        hyperlink.push_str(hyperlink_start);
-        hyperlink.push_str("https://www.zellij.dev/documentation/");
-        hyperlink.push_str(hyperlink_mid);
-        hyperlink.push_str("zellij.dev/documentation");
-        hyperlink.push_str(hyperlink_end);
-        writeln!(&mut message, "[DOCUMENTATION]: {}", hyperlink).unwrap();
+        writeln!(
+            &mut message,
+            "{} {}",
+            label("[DEFAULT EDITOR]:"),
+            default_editor
+        )
+        .unwrap();
+        writeln!(&mut message, "{}", label("[CLIPBOARD PROVIDERS]:")).unwrap();
+        if let Some(copy_command) = &config_options.copy_command {
+            writeln!(
+                &mut message,
+                " copy_command {:?}: tried first (deprecated, prefer copy_providers)",
+                copy_command
+            )
+            .unwrap();
+        }
+        let copy_providers = config_options
+            .copy_providers
+            .clone()
+            .unwrap_or_else(CopyProvider::default_providers);
+        for copy_provider in copy_providers {
+            match copy_provider.availability() {
+                Ok(()) => writeln!(&mut message, " {}: available", copy_provider.as_str()),
+                Err(reason) => writeln!(
+                    &mut message,
+                    " {}: not available ({})",
+                    copy_provider.as_str(),
+                    reason
+                ),
+            }
+            .unwrap();
+        }
+        writeln!(&mut message, "{} {:?}", label("[FEATURES]:"), FEATURES).unwrap();
+        let hyperlink = painter.hyperlink(
+            "https://www.zellij.dev/documentation/",
+            "zellij.dev/documentation",
+        );
+        writeln!(&mut message, "{} {}", label("[DOCUMENTATION]:"), hyperlink).unwrap();
         //printf '\e]8;;http://example.com\e\\This is a link\e]8;;\e\\\n'
 
         std::io::stdout().write_all(message.as_bytes())?;
@@ -661,11 +734,85 @@ impl Setup {
             _ => {},
         }
     }
+    fn generate_shell_integration(shell: &str) {
+        let shell: Shell = match shell.to_lowercase().parse() {
+            Ok(shell) => shell,
+            _ => {
+                eprintln!("Unsupported shell: {}", shell);
+                std::process::exit(1);
+            },
+        };
+
+        let mut out = std::io::stdout();
+        match shell {
+            Shell::Bash => {
+                let _ = out.write_all(BASH_SHELL_INTEGRATION_SCRIPT);
+            },
+            Shell::Fish => {
+                let _ = out.write_all(FISH_SHELL_INTEGRATION_SCRIPT);
+            },
+            Shell::Zsh => {
+                let _ = out.write_all(ZSH_SHELL_INTEGRATION_SCRIPT);
+            },
+            _ => {
+                eprintln!("Shell integration is not available for: {}", shell);
+                std::process::exit(1);
+            },
+        }
+    }
     fn parse_layout_and_override_config(
         cli_config_options: Option<&Options>,
         config: Config,
         cli_args: &CliArgs,
     ) -> Result<(Layout, Config), ConfigError> {
+        let (layout_dir, chosen_layout) =
+            Setup::layout_dir_and_chosen_layout(cli_config_options, &config, cli_args);
+        if let Some(layout_url) = chosen_layout
+            .as_ref()
+            .and_then(|l| l.to_str())
+            .and_then(|l| {
+                if l.starts_with("http://") || l.starts_with("https://") {
+                    Some(l)
+                } else {
+                    None
+                }
+            })
+        {
+            Layout::from_url(layout_url, config)
+        } else {
+            // we merge-override the config here because the layout might contain configuration
+            // that needs to take precedence
+            Layout::from_path_or_default(chosen_layout.as_ref(), layout_dir.clone(), config)
+        }
+    }
+    /// The file the layout of a session started with these arguments is read from, if it is
+    /// read from a file rather than from a url or from our assets
+    pub fn layout_file_path(cli_args: &CliArgs, config: &Config) -> Option<PathBuf> {
+        let (layout_dir, chosen_layout) =
+            Setup::layout_dir_and_chosen_layout(None, config, cli_args);
+        if chosen_layout
+            .as_ref()
+            .and_then(|l| l.to_str())
+            .map(|l| l.starts_with("http://") || l.starts_with("https://"))
+            .unwrap_or(false)
+        {
+            return None;
+        }
+        let (path_to_raw_layout, _raw_layout, _raw_swap_layouts) =
+            Layout::stringified_from_path_or_default(chosen_layout.as_ref(), layout_dir).ok()?;
+        let path_to_raw_layout = PathBuf::from(path_to_raw_layout);
+        if path_to_raw_layout.is_file() {
+            Some(path_to_raw_layout)
+        } else {
+            // layouts can be chosen without their extension
+            Some(path_to_raw_layout.with_extension("kdl")).filter(|path| path.is_file())
+        }
+    }
+    fn layout_dir_and_chosen_layout(
+        cli_config_options: Option<&Options>,
+        config: &Config,
+        cli_args: &CliArgs,
+    ) -> (Option<PathBuf>, Option<PathBuf>) {
         // find the layout folder relative to which we'll look for our layout
         let layout_dir = cli_config_options
             .as_ref()
@@ -686,23 +833,7 @@ impl Setup {
                     .and_then(|cli_options| cli_options.default_layout.clone())
             })
             .or_else(|| config.options.default_layout.clone());
-        if let Some(layout_url) = chosen_layout
-            .as_ref()
-            .and_then(|l| l.to_str())
-            .and_then(|l| {
-                if l.starts_with("http://") || l.starts_with("https://") {
-                    Some(l)
-                } else {
-                    None
-                }
-            })
-        {
-            Layout::from_url(layout_url, config)
-        } else {
-            // we merge-override the config here because the layout might contain configuration
-            // that needs to take precedence
-            Layout::from_path_or_default(chosen_layout.as_ref(), layout_dir.clone(), config)
-        }
+        (layout_dir, chosen_layout)
     }
     fn handle_setup_commands(cli_args: &CliArgs) {
         if let Some(Command::Setup(ref setup)) = &cli_args.command {
@@ -0,0 +1,146 @@
+use super::*;
+use crate::data::ModeInfo;
+use std::collections::HashMap;
+
+fn tab(position: usize, name: &str, active: bool) -> TabInfo {
+    TabInfo {
+        position,
+        name: name.to_owned(),
+        active,
+        ..Default::default()
+    }
+}
+
+fn terminal_pane(id: u32, exited: bool) -> PaneInfo {
+    PaneInfo {
+        id,
+        title: format!("Pane #{}", id),
+        exited,
+        exit_status: if exited { Some(1) } else { None },
+        is_selectable: true,
+        ..Default::default()
+    }
+}
+
+fn pane_update(panes: Vec<(usize, Vec<PaneInfo>)>) -> Event {
+    Event::PaneUpdate(PaneManifest {
+        panes: panes.into_iter().collect::<HashMap<_, _>>(),
+    })
+}
+
+fn event_names(events: &[Value]) -> Vec<&str> {
+    events.iter().map(event_name).collect()
+}
+
+#[test]
+fn first_updates_are_only_a_baseline() {
+    let mut session_events = SessionEvents::default();
+    assert!(session_events
+        .update(Event::TabUpdate(vec![tab(0, "Tab #1", true)]))
+        .is_empty());
+    assert!(session_events
+        .update(pane_update(vec![(0, vec![terminal_pane(1, false)])]))
+        .is_empty());
+    assert!(session_events
+        .update(Event::ModeUpdate(ModeInfo::default()))
+        .is_empty());
+}
+
+#[test]
+fn tabs_are_created_closed_renamed_and_focused() {
+    let mut session_events = SessionEvents::default();
+    session_events.update(Event::TabUpdate(vec![tab(0, "Tab #1", true)]));
+
+    let events = session_events.update(Event::TabUpdate(vec![
+        tab(0, "Tab #1", false),
+        tab(1, "Tab #2", true),
+    ]));
+    assert_eq!(event_names(&events), vec!["tab-created", "tab-focused"]);
+    assert_eq!(events[0]["name"], "Tab #2");
+    assert_eq!(events[0]["position"], 1);
+
+    let events = session_events.update(Event::TabUpdate(vec![
+        tab(0, "Tab #1", false),
+        tab(1, "logs", true),
+    ]));
+    assert_eq!(event_names(&events), vec!["tab-renamed"]);
+    assert_eq!(events[0]["old_name"], "Tab #2");
+    assert_eq!(events[0]["name"], "logs");
+
+    let events = session_events.update(Event::TabUpdate(vec![tab(0, "logs", true)]));
+    assert_eq!(event_names(&events), vec!["tab-closed"]);
+    assert_eq!(events[0]["name"], "Tab #1");
+}
+
+#[test]
+fn panes_are_opened_exited_and_closed() {
+    let mut session_events = SessionEvents::default();
+    session_events.update(pane_update(vec![(0, vec![terminal_pane(1, false)])]));
+
+    let events = session_events.update(pane_update(vec![(
+        0,
+        vec![terminal_pane(1, false), terminal_pane(2, false)],
+    )]));
+    assert_eq!(event_names(&events), vec!["pane-opened"]);
+    assert_eq!(events[0]["pane_id"], "terminal_2");
+
+    let events = session_events.update(pane_update(vec![(
+        0,
+        vec![terminal_pane(1, false), terminal_pane(2, true)],
+    )]));
+    assert_eq!(event_names(&events), vec!["pane-exited"]);
+    assert_eq!(events[0]["exit_status"], 1);
+
+    let events = session_events.update(pane_update(vec![(0, vec![terminal_pane(1, false)])]));
+    assert_eq!(event_names(&events), vec!["pane-closed"]);
+    assert_eq!(events[0]["pane_id"], "terminal_2");
+}
+
+#[test]
+fn mode_changes_and_session_renames() {
+    let mut session_events = SessionEvents::default();
+    session_events.update(Event::ModeUpdate(ModeInfo {
+        session_name: Some("main".to_owned()),
+        ..Default::default()
+    }));
+    let events = session_events.update(Event::ModeUpdate(ModeInfo {
+        mode: InputMode::Locked,
+        session_name: Some("work".to_owned()),
+        ..Default::default()
+    }));
+    assert_eq!(
+        event_names(&events),
+        vec!["mode-changed", "session-renamed"]
+    );
+    assert_eq!(events[0]["mode"], "locked");
+    assert_eq!(events[1]["old_name"], "main");
+}
+
+#[test]
+fn events_as_text() {
+    let record = json!({ "event": "tab-created", "name": "logs", "position": 1, "plugin": null });
+    assert_eq!(
+        format_as_text(&record),
+        "tab-created name=\"logs\" position=1"
+    );
+}
+
+#[test]
+fn events_as_env_vars() {
+    let record = json!({ "event": "tab-created", "name": "logs", "position": 1, "plugin": null });
+    let env_vars = format_as_env_vars(&record);
+    assert_eq!(
+        env_vars.get("ZELLIJ_HOOK_EVENT").map(|s| s.as_str()),
+        Some("tab-created")
+    );
+    assert_eq!(
+        env_vars.get("ZELLIJ_HOOK_NAME").map(|s| s.as_str()),
+        Some("logs"),
+        "strings are not quoted"
+    );
+    assert_eq!(
+        env_vars.get("ZELLIJ_HOOK_POSITION").map(|s| s.as_str()),
+        Some("1")
+    );
+    assert!(!env_vars.contains_key("ZELLIJ_HOOK_PLUGIN"));
+}